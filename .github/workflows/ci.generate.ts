@@ -558,6 +558,19 @@ const ci = {
           ].join("\n"),
           run: "cargo build --release --locked --all-targets",
         },
+        {
+          // The runtime of `deno compile --lite`, without FFI and KV. Its
+          // targets are listed in `LITE_TARGETS` of cli/standalone/binary.rs.
+          name: "Build lite release",
+          if: [
+            "matrix.job == 'test' &&",
+            "matrix.profile == 'release' &&",
+            "github.repository == 'denoland/deno' &&",
+            "(github.ref == 'refs/heads/main' || startsWith(github.ref, 'refs/tags/'))",
+          ].join("\n"),
+          run:
+            "cargo build --release --locked --bin deno --no-default-features --target-dir target/lite",
+        },
         {
           name: "Upload PR artifact (linux)",
           if: [
@@ -615,6 +628,31 @@ const ci = {
           run:
             "Compress-Archive -CompressionLevel Optimal -Force -Path target/release/deno.exe -DestinationPath target/release/deno-x86_64-pc-windows-msvc.zip",
         },
+        {
+          name: "Pre-release lite (unix)",
+          if: [
+            "runner.os != 'Windows' &&",
+            "matrix.job == 'test' &&",
+            "matrix.profile == 'release' &&",
+            "github.repository == 'denoland/deno' &&",
+            "(github.ref == 'refs/heads/main' || startsWith(github.ref, 'refs/tags/'))",
+          ].join("\n"),
+          run:
+            "zip -j target/release/deno-lite-$(rustc -vV | sed -n 's|host: ||p').zip target/lite/release/deno",
+        },
+        {
+          name: "Pre-release lite (windows)",
+          if: [
+            "runner.os == 'Windows' &&",
+            "matrix.job == 'test' &&",
+            "matrix.profile == 'release' &&",
+            "github.repository == 'denoland/deno' &&",
+            "(github.ref == 'refs/heads/main' || startsWith(github.ref, 'refs/tags/'))",
+          ].join("\n"),
+          shell: "pwsh",
+          run:
+            "Compress-Archive -CompressionLevel Optimal -Force -Path target/lite/release/deno.exe -DestinationPath target/release/deno-lite-x86_64-pc-windows-msvc.zip",
+        },
         {
           name: "Upload canary to dl.deno.land (unix)",
           if: [
@@ -885,6 +923,9 @@ const ci = {
               "target/release/deno-x86_64-pc-windows-msvc.zip",
               "target/release/deno-x86_64-unknown-linux-gnu.zip",
               "target/release/deno-x86_64-apple-darwin.zip",
+              "target/release/deno-lite-x86_64-pc-windows-msvc.zip",
+              "target/release/deno-lite-x86_64-unknown-linux-gnu.zip",
+              "target/release/deno-lite-x86_64-apple-darwin.zip",
               "target/release/deno_src.tar.gz",
               "target/release/lib.deno.d.ts",
             ].join("\n"),
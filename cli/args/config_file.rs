@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use crate::args::ConfigFlag;
+use crate::args::CoverageThresholds;
 use crate::args::Flags;
 use crate::util::fs::canonicalize_path;
 use crate::util::path::specifier_parent;
@@ -17,6 +18,7 @@ use deno_core::serde_json;
 use deno_core::serde_json::json;
 use deno_core::serde_json::Value;
 use deno_core::ModuleSpecifier;
+use deno_runtime::permissions::PermissionsOptions;
 use indexmap::IndexMap;
 use std::borrow::Cow;
 use std::collections::BTreeMap;
@@ -335,10 +337,15 @@ impl FilesConfig {
   /// Gets if the provided specifier is allowed based on the includes
   /// and excludes in the configuration file.
   pub fn matches_specifier(&self, specifier: &ModuleSpecifier) -> bool {
-    let file_path = match specifier_to_file_path(specifier) {
-      Ok(file_path) => file_path,
-      Err(_) => return false,
-    };
+    match specifier_to_file_path(specifier) {
+      Ok(file_path) => self.matches_path(&file_path),
+      Err(_) => false,
+    }
+  }
+
+  /// Gets if the provided path is allowed based on the includes and excludes
+  /// in the configuration file.
+  pub fn matches_path(&self, file_path: &Path) -> bool {
     // Skip files which is in the exclude list.
     if self.exclude.iter().any(|i| file_path.starts_with(i)) {
       return false;
@@ -402,6 +409,17 @@ struct SerializedLintConfig {
   #[serde(rename = "files")]
   pub deprecated_files: SerializedFilesConfig,
   pub report: Option<String>,
+  pub plugins: Vec<String>,
+  pub severity: HashMap<String, LintSeverity>,
+  pub overrides: Vec<SerializedLintOverride>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct SerializedLintOverride {
+  pub include: Vec<String>,
+  pub exclude: Vec<String>,
+  pub severity: HashMap<String, LintSeverity>,
 }
 
 impl SerializedLintConfig {
@@ -411,21 +429,90 @@ impl SerializedLintConfig {
   ) -> Result<LintConfig, AnyError> {
     let (include, exclude) = (self.include, self.exclude);
     let files = SerializedFilesConfig { include, exclude };
+    let config_dir = specifier_parent(config_file_specifier);
+    let plugins = self
+      .plugins
+      .into_iter()
+      .map(|p| {
+        config_dir.join(&p).with_context(|| {
+          format!("Invalid \"lint.plugins\" module specifier: {p}")
+        })
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    let overrides = self
+      .overrides
+      .into_iter()
+      .map(|o| {
+        let (include, exclude) = (o.include, o.exclude);
+        Ok(LintSeverityOverride {
+          files: SerializedFilesConfig { include, exclude }
+            .into_resolved(config_file_specifier)?,
+          severity: o.severity,
+        })
+      })
+      .collect::<Result<Vec<_>, AnyError>>()?;
 
     Ok(LintConfig {
       rules: self.rules,
       files: choose_files(files, self.deprecated_files)
         .into_resolved(config_file_specifier)?,
       report: self.report,
+      plugins,
+      severity: LintSeverityConfig {
+        rules: self.severity,
+        overrides,
+      },
     })
   }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum LintSeverity {
+  Warn,
+  Error,
+}
+
+/// The severities of the lint rules. The diagnostics of the rules that
+/// aren't configured are errors on the command line, and warnings in the
+/// editor.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LintSeverityConfig {
+  pub rules: HashMap<String, LintSeverity>,
+  pub overrides: Vec<LintSeverityOverride>,
+}
+
+impl LintSeverityConfig {
+  /// Gets the configured severity of a rule for a file. The overrides that
+  /// match the file take precedence over the rules, and the later ones over
+  /// the earlier ones.
+  pub fn get(&self, file_path: &Path, code: &str) -> Option<LintSeverity> {
+    self
+      .overrides
+      .iter()
+      .rev()
+      .filter(|o| o.files.matches_path(file_path))
+      .find_map(|o| o.severity.get(code))
+      .or_else(|| self.rules.get(code))
+      .copied()
+  }
+}
+
+/// The severities of the lint rules for some of the files.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LintSeverityOverride {
+  pub files: FilesConfig,
+  pub severity: HashMap<String, LintSeverity>,
+}
+
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct LintConfig {
   pub rules: LintRulesConfig,
   pub files: FilesConfig,
   pub report: Option<String>,
+  /// The modules of the plugins that provide more lint rules.
+  pub plugins: Vec<ModuleSpecifier>,
+  pub severity: LintSeverityConfig,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
@@ -436,6 +523,19 @@ pub enum ProseWrap {
   Preserve,
 }
 
+/// A language of the code that `deno fmt` formats inside other code: in the
+/// code blocks of markdown and JSDoc comments, and in the template literals
+/// that are tagged with the name of the language.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub enum EmbeddedLanguage {
+  Typescript,
+  Json,
+  Css,
+  Html,
+  Sql,
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
 #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
 pub struct FmtOptionsConfig {
@@ -445,6 +545,9 @@ pub struct FmtOptionsConfig {
   pub single_quote: Option<bool>,
   pub prose_wrap: Option<ProseWrap>,
   pub semi_colons: Option<bool>,
+  /// None of the languages when not set, except for the TypeScript,
+  /// JavaScript and JSON code blocks of markdown files.
+  pub embedded_languages: Option<Vec<EmbeddedLanguage>>,
 }
 
 impl FmtOptionsConfig {
@@ -455,6 +558,17 @@ impl FmtOptionsConfig {
       && self.single_quote.is_none()
       && self.prose_wrap.is_none()
       && self.semi_colons.is_none()
+      && self.embedded_languages.is_none()
+  }
+
+  pub fn is_embedded_language_enabled(
+    &self,
+    language: EmbeddedLanguage,
+  ) -> bool {
+    match &self.embedded_languages {
+      Some(languages) => languages.contains(&language),
+      None => false,
+    }
   }
 }
 
@@ -499,7 +613,7 @@ fn choose_fmt_options(
 
 /// `fmt` config representation for serde
 ///
-/// fields from `use_tabs`..`semi_colons` are expanded from [FmtOptionsConfig].
+/// fields from `use_tabs`..`embedded_languages` are expanded from [FmtOptionsConfig].
 /// fields `include` and `exclude` are expanded from [SerializedFilesConfig].
 #[derive(Clone, Debug, Default, Deserialize, PartialEq)]
 #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
@@ -510,12 +624,33 @@ struct SerializedFmtConfig {
   pub single_quote: Option<bool>,
   pub prose_wrap: Option<ProseWrap>,
   pub semi_colons: Option<bool>,
+  pub embedded_languages: Option<Vec<EmbeddedLanguage>>,
   #[serde(rename = "options")]
   pub deprecated_options: FmtOptionsConfig,
   pub include: Vec<String>,
   pub exclude: Vec<String>,
   #[serde(rename = "files")]
   pub deprecated_files: SerializedFilesConfig,
+  pub plugins: Vec<SerializedFmtPlugin>,
+}
+
+/// A plugin of `fmt.plugins`, which is either the specifier of its Wasm
+/// module, or an object with the specifier, the integrity and the
+/// configuration of the plugin.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum SerializedFmtPlugin {
+  Specifier(String),
+  WithConfig(SerializedFmtPluginWithConfig),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct SerializedFmtPluginWithConfig {
+  pub specifier: String,
+  pub integrity: Option<String>,
+  #[serde(default)]
+  pub config: serde_json::Map<String, Value>,
 }
 
 impl SerializedFmtConfig {
@@ -532,20 +667,59 @@ impl SerializedFmtConfig {
       single_quote: self.single_quote,
       prose_wrap: self.prose_wrap,
       semi_colons: self.semi_colons,
+      embedded_languages: self.embedded_languages,
     };
 
+    let config_dir = specifier_parent(config_file_specifier);
+    let plugins = self
+      .plugins
+      .into_iter()
+      .map(|plugin| {
+        let (specifier, integrity, config) = match plugin {
+          SerializedFmtPlugin::Specifier(specifier) => {
+            (specifier, None, Default::default())
+          }
+          SerializedFmtPlugin::WithConfig(plugin) => {
+            (plugin.specifier, plugin.integrity, plugin.config)
+          }
+        };
+        Ok(FmtPluginConfig {
+          specifier: config_dir.join(&specifier).with_context(|| {
+            format!("Invalid \"fmt.plugins\" module specifier: {specifier}")
+          })?,
+          integrity,
+          config,
+        })
+      })
+      .collect::<Result<Vec<_>, AnyError>>()?;
+
     Ok(FmtConfig {
       options: choose_fmt_options(options, self.deprecated_options),
       files: choose_files(files, self.deprecated_files)
         .into_resolved(config_file_specifier)?,
+      plugins,
     })
   }
 }
 
+/// A dprint Wasm plugin, which formats the files of the languages that
+/// `deno fmt` doesn't support.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct FmtPluginConfig {
+  pub specifier: ModuleSpecifier,
+  /// The SHA-256 checksum of the Wasm module, in hex, which is required for
+  /// remote plugins.
+  pub integrity: Option<String>,
+  /// The configuration of the plugin, which is passed to it along with the
+  /// global options like `lineWidth`.
+  pub config: serde_json::Map<String, Value>,
+}
+
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct FmtConfig {
   pub options: FmtOptionsConfig,
   pub files: FilesConfig,
+  pub plugins: Vec<FmtPluginConfig>,
 }
 
 /// `test` config representation for serde
@@ -558,6 +732,8 @@ struct SerializedTestConfig {
   pub exclude: Vec<String>,
   #[serde(rename = "files")]
   pub deprecated_files: SerializedFilesConfig,
+  pub setup: Option<String>,
+  pub teardown: Option<String>,
 }
 
 impl SerializedTestConfig {
@@ -567,10 +743,21 @@ impl SerializedTestConfig {
   ) -> Result<TestConfig, AnyError> {
     let (include, exclude) = (self.include, self.exclude);
     let files = SerializedFilesConfig { include, exclude };
+    let config_dir = specifier_parent(config_file_specifier);
+    let resolve_module = |p: Option<String>, name: &str| {
+      p.map(|p| {
+        config_dir.join(&p).with_context(|| {
+          format!("Invalid \"test.{name}\" module specifier: {p}")
+        })
+      })
+      .transpose()
+    };
 
     Ok(TestConfig {
       files: choose_files(files, self.deprecated_files)
         .into_resolved(config_file_specifier)?,
+      setup: resolve_module(self.setup, "setup")?,
+      teardown: resolve_module(self.teardown, "teardown")?,
     })
   }
 }
@@ -578,6 +765,10 @@ impl SerializedTestConfig {
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct TestConfig {
   pub files: FilesConfig,
+  /// The module that is run once before all the test modules.
+  pub setup: Option<ModuleSpecifier>,
+  /// The module that is run once after all the test modules.
+  pub teardown: Option<ModuleSpecifier>,
 }
 
 /// `bench` config representation for serde
@@ -612,6 +803,227 @@ pub struct BenchConfig {
   pub files: FilesConfig,
 }
 
+/// `coverage` config representation for serde
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct SerializedCoverageConfig {
+  pub thresholds: SerializedCoverageThresholds,
+}
+
+/// The minimum percentages of covered lines, functions and branches.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct SerializedCoverageThresholds {
+  pub lines: Option<u8>,
+  pub functions: Option<u8>,
+  pub branches: Option<u8>,
+}
+
+impl SerializedCoverageConfig {
+  pub fn into_resolved(self) -> Result<CoverageConfig, AnyError> {
+    let thresholds = self.thresholds;
+    for (name, threshold) in [
+      ("lines", thresholds.lines),
+      ("functions", thresholds.functions),
+      ("branches", thresholds.branches),
+    ] {
+      if threshold.map(|t| t > 100).unwrap_or(false) {
+        bail!("The \"{name}\" threshold must be a percentage from 0 to 100");
+      }
+    }
+    Ok(CoverageConfig {
+      thresholds: CoverageThresholds {
+        lines: thresholds.lines,
+        functions: thresholds.functions,
+        branches: thresholds.branches,
+      },
+    })
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CoverageConfig {
+  pub thresholds: CoverageThresholds,
+}
+
+/// `doc` config representation for serde
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct SerializedDocConfig {
+  pub lint: SerializedFilesConfig,
+}
+
+impl SerializedDocConfig {
+  pub fn into_resolved(
+    self,
+    config_file_specifier: &ModuleSpecifier,
+  ) -> Result<DocConfig, AnyError> {
+    Ok(DocConfig {
+      lint_files: self.lint.into_resolved(config_file_specifier)?,
+    })
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DocConfig {
+  /// The files whose symbols are the public API that `deno doc --lint`
+  /// checks.
+  pub lint_files: FilesConfig,
+}
+
+/// A task of the `"tasks"` configuration, which is either the command to
+/// execute, or an object with the command and the tasks it depends on.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum SerializedTaskDefinition {
+  Command(String),
+  WithDependencies(SerializedTaskDefinitionWithDependencies),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+struct SerializedTaskDefinitionWithDependencies {
+  #[serde(default)]
+  pub command: String,
+  #[serde(default)]
+  pub depends_on: Vec<String>,
+}
+
+impl SerializedTaskDefinition {
+  fn into_resolved(self) -> TaskDefinition {
+    match self {
+      SerializedTaskDefinition::Command(command) => TaskDefinition {
+        command,
+        depends_on: Vec::new(),
+      },
+      SerializedTaskDefinition::WithDependencies(task) => TaskDefinition {
+        command: task.command,
+        depends_on: task.depends_on,
+      },
+    }
+  }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TaskDefinition {
+  /// The command to execute, which is empty for tasks that only run the tasks
+  /// they depend on.
+  pub command: String,
+  /// The names of the tasks that have to run before this task.
+  pub depends_on: Vec<String>,
+}
+
+/// The access of a permission in a permission set, either to everything or
+/// nothing, or to a list of values like the ones of the `--allow-*` flags.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum SerializedPermissionValue {
+  Bool(bool),
+  List(Vec<String>),
+}
+
+impl SerializedPermissionValue {
+  fn into_list(self) -> Option<Vec<String>> {
+    match self {
+      SerializedPermissionValue::Bool(true) => Some(vec![]),
+      SerializedPermissionValue::Bool(false) => None,
+      SerializedPermissionValue::List(list) if list.is_empty() => None,
+      SerializedPermissionValue::List(list) => Some(list),
+    }
+  }
+
+  fn into_path_list(
+    self,
+    config_dir: &ModuleSpecifier,
+  ) -> Result<Option<Vec<PathBuf>>, AnyError> {
+    self
+      .into_list()
+      .map(|list| {
+        list
+          .into_iter()
+          .map(|p| {
+            let url = config_dir.join(&p)?;
+            specifier_to_file_path(&url)
+          })
+          .collect::<Result<Vec<_>, _>>()
+      })
+      .transpose()
+  }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct SerializedPermissionsList {
+  pub read: Option<SerializedPermissionValue>,
+  pub write: Option<SerializedPermissionValue>,
+  pub net: Option<SerializedPermissionValue>,
+  pub env: Option<SerializedPermissionValue>,
+  pub sys: Option<SerializedPermissionValue>,
+  pub run: Option<SerializedPermissionValue>,
+  pub ffi: Option<SerializedPermissionValue>,
+  pub vsock: Option<SerializedPermissionValue>,
+  pub hrtime: bool,
+}
+
+/// A named permission set of the `permissions` config representation for
+/// serde, with the access granted and denied like by the `--allow-*` and
+/// `--deny-*` flags.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct SerializedPermissionSet {
+  pub allow: SerializedPermissionsList,
+  pub deny: SerializedPermissionsList,
+}
+
+impl SerializedPermissionSet {
+  pub fn into_resolved(
+    self,
+    config_file_specifier: &ModuleSpecifier,
+  ) -> Result<PermissionsOptions, AnyError> {
+    let config_dir = specifier_parent(config_file_specifier);
+    let resolve_paths = |value: Option<SerializedPermissionValue>| {
+      value
+        .map(|v| v.into_path_list(&config_dir))
+        .transpose()
+        .map(Option::flatten)
+    };
+    let resolve_hosts = |value: Option<SerializedPermissionValue>| {
+      value
+        .and_then(SerializedPermissionValue::into_list)
+        .map(|list| {
+          if list.is_empty() {
+            Ok(list)
+          } else {
+            super::flags_allow_net::parse(list)
+          }
+        })
+        .transpose()
+    };
+    let (allow, deny) = (self.allow, self.deny);
+    Ok(PermissionsOptions {
+      allow_env: allow.env.and_then(SerializedPermissionValue::into_list),
+      allow_hrtime: allow.hrtime,
+      allow_net: resolve_hosts(allow.net)?,
+      allow_ffi: resolve_paths(allow.ffi)?,
+      allow_read: resolve_paths(allow.read)?,
+      allow_run: allow.run.and_then(SerializedPermissionValue::into_list),
+      allow_sys: allow.sys.and_then(SerializedPermissionValue::into_list),
+      allow_vsock: allow.vsock.and_then(SerializedPermissionValue::into_list),
+      allow_write: resolve_paths(allow.write)?,
+      deny_env: deny.env.and_then(SerializedPermissionValue::into_list),
+      deny_hrtime: deny.hrtime,
+      deny_net: resolve_hosts(deny.net)?,
+      deny_ffi: resolve_paths(deny.ffi)?,
+      deny_read: resolve_paths(deny.read)?,
+      deny_run: deny.run.and_then(SerializedPermissionValue::into_list),
+      deny_sys: deny.sys.and_then(SerializedPermissionValue::into_list),
+      deny_vsock: deny.vsock.and_then(SerializedPermissionValue::into_list),
+      deny_write: resolve_paths(deny.write)?,
+      prompt: false,
+    })
+  }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(untagged)]
 pub enum LockConfig {
@@ -631,7 +1043,17 @@ pub struct ConfigFileJson {
   pub tasks: Option<Value>,
   pub test: Option<Value>,
   pub bench: Option<Value>,
+  pub coverage: Option<Value>,
+  pub doc: Option<Value>,
   pub lock: Option<Value>,
+  pub permissions: Option<Value>,
+  pub env_file: Option<String>,
+  /// The name that the other members of a workspace import the directory of
+  /// the configuration file by.
+  pub name: Option<String>,
+  /// The module that is imported by the name of a workspace member.
+  pub exports: Option<String>,
+  pub workspace: Option<Vec<String>>,
 }
 
 #[derive(Clone, Debug)]
@@ -884,19 +1306,41 @@ impl ConfigFile {
     }
   }
 
+  pub fn to_coverage_config(&self) -> Result<Option<CoverageConfig>, AnyError> {
+    if let Some(config) = self.json.coverage.clone() {
+      let coverage_config: SerializedCoverageConfig =
+        serde_json::from_value(config)
+          .context("Failed to parse \"coverage\" configuration")?;
+      Ok(Some(coverage_config.into_resolved()?))
+    } else {
+      Ok(None)
+    }
+  }
+
+  pub fn to_doc_config(&self) -> Result<Option<DocConfig>, AnyError> {
+    if let Some(config) = self.json.doc.clone() {
+      let doc_config: SerializedDocConfig = serde_json::from_value(config)
+        .context("Failed to parse \"doc\" configuration")?;
+      Ok(Some(doc_config.into_resolved(&self.specifier)?))
+    } else {
+      Ok(None)
+    }
+  }
+
   /// Return any tasks that are defined in the configuration file as a sequence
   /// of JSON objects providing the name of the task and the arguments of the
   /// task in a detail field.
   pub fn to_lsp_tasks(&self) -> Option<Value> {
     let value = self.json.tasks.clone()?;
-    let tasks: BTreeMap<String, String> = serde_json::from_value(value).ok()?;
+    let tasks: BTreeMap<String, SerializedTaskDefinition> =
+      serde_json::from_value(value).ok()?;
     Some(
       tasks
         .into_iter()
         .map(|(key, value)| {
           json!({
             "name": key,
-            "detail": value,
+            "detail": value.into_resolved().command,
           })
         })
         .collect(),
@@ -905,12 +1349,17 @@ impl ConfigFile {
 
   pub fn to_tasks_config(
     &self,
-  ) -> Result<Option<IndexMap<String, String>>, AnyError> {
+  ) -> Result<Option<IndexMap<String, TaskDefinition>>, AnyError> {
     if let Some(config) = self.json.tasks.clone() {
-      let tasks_config: IndexMap<String, String> =
+      let tasks_config: IndexMap<String, SerializedTaskDefinition> =
         serde_json::from_value(config)
           .context("Failed to parse \"tasks\" configuration")?;
-      Ok(Some(tasks_config))
+      Ok(Some(
+        tasks_config
+          .into_iter()
+          .map(|(key, value)| (key, value.into_resolved()))
+          .collect(),
+      ))
     } else {
       Ok(None)
     }
@@ -960,7 +1409,7 @@ impl ConfigFile {
 
   pub fn resolve_tasks_config(
     &self,
-  ) -> Result<IndexMap<String, String>, AnyError> {
+  ) -> Result<IndexMap<String, TaskDefinition>, AnyError> {
     let maybe_tasks_config = self.to_tasks_config()?;
     let tasks_config = maybe_tasks_config.unwrap_or_default();
     for key in tasks_config.keys() {
@@ -975,9 +1424,76 @@ impl ConfigFile {
         bail!("Configuration file task names must start with an alphabetic character. Task: {}", key);
       }
     }
+    for (key, task) in &tasks_config {
+      for dependency in &task.depends_on {
+        if !tasks_config.contains_key(dependency) {
+          bail!(
+            "Task \"{}\" depends on \"{}\", which is not a task of the configuration file",
+            key,
+            dependency
+          );
+        }
+      }
+    }
     Ok(tasks_config)
   }
 
+  /// Return the named permission sets that are defined in the configuration
+  /// file, compiled into the options of the `--allow-*` and `--deny-*` flags.
+  pub fn to_permissions_config(
+    &self,
+  ) -> Result<Option<IndexMap<String, PermissionsOptions>>, AnyError> {
+    if let Some(config) = self.json.permissions.clone() {
+      let permission_sets: IndexMap<String, SerializedPermissionSet> =
+        serde_json::from_value(config)
+          .context("Failed to parse \"permissions\" configuration")?;
+      let mut permissions_config = IndexMap::new();
+      for (name, set) in permission_sets {
+        let options =
+          set.into_resolved(&self.specifier).with_context(|| {
+            format!("Failed to resolve permission set \"{name}\"")
+          })?;
+        permissions_config.insert(name, options);
+      }
+      Ok(Some(permissions_config))
+    } else {
+      Ok(None)
+    }
+  }
+
+  /// The directories of the members of the workspace, when the configuration
+  /// file is the root of a workspace.
+  pub fn to_workspace_member_dirs(
+    &self,
+  ) -> Result<Option<Vec<PathBuf>>, AnyError> {
+    let Some(members) = &self.json.workspace else {
+      return Ok(None);
+    };
+    let config_dir = specifier_parent(&self.specifier);
+    members
+      .iter()
+      .map(|member| {
+        let specifier = config_dir
+          .join(member)
+          .with_context(|| format!("Invalid \"workspace\" member: {member}"))?;
+        specifier_to_file_path(&specifier)
+      })
+      .collect::<Result<Vec<_>, _>>()
+      .map(Some)
+  }
+
+  /// The path of the dotenv file of the `"envFile"` option, which is relative
+  /// to the configuration file.
+  pub fn to_env_file_path(&self) -> Result<Option<PathBuf>, AnyError> {
+    let Some(env_file) = &self.json.env_file else {
+      return Ok(None);
+    };
+    let specifier = specifier_parent(&self.specifier)
+      .join(env_file)
+      .with_context(|| format!("Invalid \"envFile\" path: {env_file}"))?;
+    Ok(Some(specifier_to_file_path(&specifier)?))
+  }
+
   pub fn to_lock_config(&self) -> Result<Option<LockConfig>, AnyError> {
     if let Some(config) = self.json.lock.clone() {
       let lock_config: LockConfig = serde_json::from_value(config)
@@ -1217,7 +1733,11 @@ mod tests {
       },
       "tasks": {
         "build": "deno run --allow-read --allow-write build.ts",
-        "server": "deno run --allow-net --allow-read server.ts"
+        "server": "deno run --allow-net --allow-read server.ts",
+        "ci": {
+          "command": "deno test",
+          "dependsOn": ["build", "server"]
+        }
       }
     }"#;
     let config_dir = ModuleSpecifier::parse("file:///deno/").unwrap();
@@ -1267,18 +1787,26 @@ mod tests {
           prose_wrap: Some(ProseWrap::Preserve),
           ..Default::default()
         },
+        ..Default::default()
       }
     );
 
     let tasks_config = config_file.to_tasks_config().unwrap().unwrap();
     assert_eq!(
-      tasks_config["build"],
+      tasks_config["build"].command,
       "deno run --allow-read --allow-write build.ts",
     );
     assert_eq!(
-      tasks_config["server"],
+      tasks_config["server"].command,
       "deno run --allow-net --allow-read server.ts"
     );
+    assert_eq!(
+      tasks_config["ci"],
+      TaskDefinition {
+        command: "deno test".to_string(),
+        depends_on: vec!["build".to_string(), "server".to_string()],
+      }
+    );
   }
 
   /// if either "include" or "exclude" is specified, "files" is ignored
@@ -1403,6 +1931,264 @@ mod tests {
     assert_eq!(fmt_options_deprecated.semi_colons, Some(true));
   }
 
+  #[test]
+  fn test_parse_config_with_fmt_embedded_languages() {
+    let config_text = r#"{
+      "fmt": {
+        "embeddedLanguages": ["json"]
+      }
+    }"#;
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    let options = unpack_object(config_file.to_fmt_config(), "fmt").options;
+    assert_eq!(
+      options.embedded_languages,
+      Some(vec![EmbeddedLanguage::Json])
+    );
+    assert!(options.is_embedded_language_enabled(EmbeddedLanguage::Json));
+    assert!(!options.is_embedded_language_enabled(EmbeddedLanguage::Typescript));
+    assert!(!FmtOptionsConfig::default()
+      .is_embedded_language_enabled(EmbeddedLanguage::Typescript));
+  }
+
+  #[test]
+  fn test_parse_config_with_fmt_plugins() {
+    let config_text = r#"{
+      "fmt": {
+        "plugins": [
+          {
+            "specifier": "https://plugins.dprint.dev/toml-0.5.4.wasm",
+            "integrity": "6a2fd2d9e1ec3c2c7bce2cbbc5b4c3d2c1d8f0f3a8e4b5c6d7e8f9a0b1c2d3e4"
+          },
+          "./plugins/dockerfile.wasm",
+          {
+            "specifier": "./plugins/yaml.wasm",
+            "config": { "quotes": "preferSingle" }
+          }
+        ]
+      }
+    }"#;
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    let plugins = unpack_object(config_file.to_fmt_config(), "fmt").plugins;
+    assert_eq!(
+      plugins,
+      vec![
+        FmtPluginConfig {
+          specifier: ModuleSpecifier::parse(
+            "https://plugins.dprint.dev/toml-0.5.4.wasm"
+          )
+          .unwrap(),
+          integrity: Some(
+            "6a2fd2d9e1ec3c2c7bce2cbbc5b4c3d2c1d8f0f3a8e4b5c6d7e8f9a0b1c2d3e4"
+              .to_string()
+          ),
+          config: Default::default(),
+        },
+        FmtPluginConfig {
+          specifier: ModuleSpecifier::parse(
+            "file:///deno/plugins/dockerfile.wasm"
+          )
+          .unwrap(),
+          integrity: None,
+          config: Default::default(),
+        },
+        FmtPluginConfig {
+          specifier: ModuleSpecifier::parse("file:///deno/plugins/yaml.wasm")
+            .unwrap(),
+          integrity: None,
+          config: json!({ "quotes": "preferSingle" })
+            .as_object()
+            .unwrap()
+            .clone(),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_parse_config_with_permissions() {
+    let config_text = r#"{
+      "permissions": {
+        "default": {
+          "allow": { "read": ["./data"], "net": true, "hrtime": true }
+        },
+        "ci": {
+          "allow": { "env": ["HOME", "CI"], "net": [":8080"], "run": [] },
+          "deny": { "read": ["secrets/"], "net": ["169.254.0.0/16"] }
+        }
+      }
+    }"#;
+    let config_dir = ModuleSpecifier::parse("file:///deno/").unwrap();
+    let config_specifier = config_dir.join("deno.json").unwrap();
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    let permissions_config =
+      unpack_object(config_file.to_permissions_config(), "permissions");
+    assert_eq!(
+      permissions_config["default"],
+      PermissionsOptions {
+        allow_read: Some(vec![PathBuf::from("/deno/data")]),
+        allow_net: Some(vec![]),
+        allow_hrtime: true,
+        ..Default::default()
+      }
+    );
+    assert_eq!(
+      permissions_config["ci"],
+      PermissionsOptions {
+        allow_env: Some(vec!["HOME".to_string(), "CI".to_string()]),
+        allow_net: Some(vec![
+          "0.0.0.0:8080".to_string(),
+          "127.0.0.1:8080".to_string(),
+          "localhost:8080".to_string(),
+        ]),
+        deny_read: Some(vec![PathBuf::from("/deno/secrets/")]),
+        deny_net: Some(vec!["169.254.0.0/16".to_string()]),
+        ..Default::default()
+      }
+    );
+
+    let config_text = r#"{
+      "permissions": { "default": { "allow": { "network": true } } }
+    }"#;
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    assert!(config_file.to_permissions_config().is_err());
+  }
+
+  #[test]
+  fn test_parse_config_with_coverage() {
+    let config_text = r#"{
+      "coverage": { "thresholds": { "lines": 90, "branches": 75 } }
+    }"#;
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    let coverage_config =
+      unpack_object(config_file.to_coverage_config(), "coverage");
+    assert_eq!(
+      coverage_config.thresholds,
+      CoverageThresholds {
+        lines: Some(90),
+        functions: None,
+        branches: Some(75),
+      }
+    );
+
+    let config_text = r#"{ "coverage": { "thresholds": { "lines": 101 } } }"#;
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    assert!(config_file.to_coverage_config().is_err());
+  }
+
+  #[test]
+  fn test_parse_config_with_doc() {
+    let config_text = r#"{
+      "doc": { "lint": { "include": ["src/"], "exclude": ["src/internal/"] } }
+    }"#;
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    let doc_config = unpack_object(config_file.to_doc_config(), "doc");
+    assert_eq!(
+      doc_config.lint_files,
+      FilesConfig {
+        include: vec![PathBuf::from("/deno/src/")],
+        exclude: vec![PathBuf::from("/deno/src/internal/")],
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_config_with_env_file() {
+    let config_text = r#"{ "envFile": "./config/.env" }"#;
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    assert_eq!(
+      config_file.to_env_file_path().unwrap(),
+      Some(PathBuf::from("/deno/config/.env"))
+    );
+  }
+
+  #[test]
+  fn test_parse_config_with_workspace() {
+    let config_text = r#"{ "workspace": ["./packages/a", "packages/b/"] }"#;
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    assert_eq!(
+      config_file.to_workspace_member_dirs().unwrap(),
+      Some(vec![
+        PathBuf::from("/deno/packages/a"),
+        PathBuf::from("/deno/packages/b/"),
+      ])
+    );
+  }
+
+  #[test]
+  fn test_parse_config_with_test_setup() {
+    let config_text = r#"{
+      "test": { "setup": "./tests/setup.ts", "teardown": "tests/teardown.ts" }
+    }"#;
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    let test_config = unpack_object(config_file.to_test_config(), "test");
+    assert_eq!(
+      test_config.setup,
+      Some(ModuleSpecifier::parse("file:///deno/tests/setup.ts").unwrap())
+    );
+    assert_eq!(
+      test_config.teardown,
+      Some(ModuleSpecifier::parse("file:///deno/tests/teardown.ts").unwrap())
+    );
+  }
+
+  #[test]
+  fn test_parse_config_with_lint_severity() {
+    let config_text = r#"{
+      "lint": {
+        "severity": { "no-unused-vars": "warn", "no-empty": "error" },
+        "overrides": [
+          { "include": ["tests/"], "severity": { "no-empty": "warn" } },
+          {
+            "include": ["tests/"],
+            "exclude": ["tests/fixtures/"],
+            "severity": { "no-unused-vars": "error" }
+          }
+        ]
+      }
+    }"#;
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    let severity = unpack_object(config_file.to_lint_config(), "lint").severity;
+    let get = |path: &str, code: &str| severity.get(Path::new(path), code);
+    assert_eq!(
+      get("/deno/main.ts", "no-unused-vars"),
+      Some(LintSeverity::Warn)
+    );
+    assert_eq!(get("/deno/main.ts", "no-empty"), Some(LintSeverity::Error));
+    assert_eq!(get("/deno/main.ts", "no-debugger"), None);
+    assert_eq!(
+      get("/deno/tests/a_test.ts", "no-unused-vars"),
+      Some(LintSeverity::Error)
+    );
+    assert_eq!(
+      get("/deno/tests/a_test.ts", "no-empty"),
+      Some(LintSeverity::Warn)
+    );
+    assert_eq!(
+      get("/deno/tests/fixtures/a.ts", "no-unused-vars"),
+      Some(LintSeverity::Warn)
+    );
+
+    let config_text = r#"{ "lint": { "severity": { "no-empty": "off" } } }"#;
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    assert!(config_file.to_lint_config().is_err());
+  }
+
   #[test]
   fn test_parse_config_with_empty_file() {
     let config_text = "";
@@ -1549,6 +2335,21 @@ mod tests {
     );
   }
 
+  #[test]
+  fn task_depends_on_unknown_task() {
+    run_task_error_test(
+      r#"{
+        "tasks": {
+          "build": {
+            "command": "deno run build.ts",
+            "dependsOn": ["codegen"]
+          }
+        }
+      }"#,
+      "Task \"build\" depends on \"codegen\", which is not a task of the configuration file",
+    );
+  }
+
   fn run_task_error_test(config_text: &str, expected_error: &str) {
     let config_dir = ModuleSpecifier::parse("file:///deno/").unwrap();
     let config_specifier = config_dir.join("tsconfig.json").unwrap();
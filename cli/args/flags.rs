@@ -10,6 +10,7 @@ use clap::ValueHint;
 use deno_core::resolve_url_or_path;
 use deno_core::url::Url;
 use deno_runtime::permissions::parse_sys_kind;
+use deno_runtime::permissions::VsockDescriptor;
 use log::debug;
 use log::Level;
 use std::env;
@@ -20,6 +21,7 @@ use std::num::NonZeroUsize;
 use std::path::Path;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 
 use crate::util::fs::canonicalize_path;
 
@@ -37,12 +39,24 @@ pub struct BenchFlags {
   pub filter: Option<String>,
   pub json: bool,
   pub no_run: bool,
+  pub compare: Option<PathBuf>,
+}
+
+/// Where `deno bundle` emits the source map, with `--source-map`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BundleSourceMapFlag {
+  Inline,
+  External,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct BundleFlags {
   pub source_file: String,
   pub out_file: Option<PathBuf>,
+  pub external: Vec<String>,
+  /// Overrides the source map options of the configuration file.
+  pub source_map: Option<BundleSourceMapFlag>,
+  pub minify: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -62,6 +76,21 @@ pub struct CompileFlags {
   pub args: Vec<String>,
   pub target: Option<String>,
   pub include: Vec<String>,
+  pub sign: Option<CompileSignFlags>,
+  /// Whether to use the lite runtime, which leaves out the FFI and KV APIs.
+  pub lite: bool,
+}
+
+/// How to sign a compiled executable with the signing tool of its platform.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompileSignFlags {
+  /// The macOS signing identity, or the Windows certificate, which is a path
+  /// to a PKCS#12 file or the subject name of a certificate in the store.
+  pub identity: String,
+  pub timestamp_url: Option<String>,
+  /// A command that notarizes a signed macOS executable, whose path is passed
+  /// as the last argument.
+  pub notarize_command: Option<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -69,6 +98,14 @@ pub struct CompletionsFlags {
   pub buf: Box<[u8]>,
 }
 
+/// The minimum percentages of covered lines, functions and branches.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CoverageThresholds {
+  pub lines: Option<u8>,
+  pub functions: Option<u8>,
+  pub branches: Option<u8>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CoverageFlags {
   pub files: FileFlags,
@@ -76,6 +113,9 @@ pub struct CoverageFlags {
   pub include: Vec<String>,
   pub exclude: Vec<String>,
   pub lcov: bool,
+  pub html: Option<PathBuf>,
+  pub check: bool,
+  pub thresholds: CoverageThresholds,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -90,10 +130,18 @@ impl Default for DocSourceFileFlag {
   }
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DocHtmlFlag {
+  pub name: Option<String>,
+  pub output: PathBuf,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct DocFlags {
   pub private: bool,
   pub json: bool,
+  pub html: Option<DocHtmlFlag>,
+  pub lint: bool,
   pub source_file: DocSourceFileFlag,
   pub filter: Option<String>,
 }
@@ -125,6 +173,8 @@ pub struct InitFlags {
 pub struct InfoFlags {
   pub json: bool,
   pub file: Option<String>,
+  /// The module or npm package to show the chains of imports to.
+  pub why: Option<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -146,6 +196,7 @@ pub struct UninstallFlags {
 pub struct LintFlags {
   pub files: FileFlags,
   pub rules: bool,
+  pub fix: bool,
   pub maybe_rules_tags: Option<Vec<String>>,
   pub maybe_rules_include: Option<Vec<String>>,
   pub maybe_rules_exclude: Option<Vec<String>>,
@@ -153,6 +204,12 @@ pub struct LintFlags {
   pub compact: bool,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PermissionsFlags {
+  pub script: Option<String>,
+  pub clear: bool,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ReplFlags {
   pub eval_files: Option<Vec<String>>,
@@ -175,6 +232,70 @@ impl RunFlags {
 pub struct TaskFlags {
   pub cwd: Option<String>,
   pub task: Option<String>,
+  /// Run the tasks that don't depend on each other at the same time.
+  pub parallel: bool,
+}
+
+/// The part of the tests that are run, with `--shard=<index>/<count>`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TestShard {
+  /// The 1-based index of the shard.
+  pub index: NonZeroUsize,
+  pub count: NonZeroUsize,
+}
+
+impl FromStr for TestShard {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let invalid = || format!("invalid shard \"{s}\", expected <index>/<count>");
+    let (index, count) = s.split_once('/').ok_or_else(invalid)?;
+    let index = index.parse::<NonZeroUsize>().map_err(|_| invalid())?;
+    let count = count.parse::<NonZeroUsize>().map_err(|_| invalid())?;
+    if index > count {
+      return Err(format!(
+        "invalid shard \"{s}\", the index must not be greater than the count"
+      ));
+    }
+    Ok(Self { index, count })
+  }
+}
+
+/// Parses a duration like `500ms`, `2s` or `1m`. A number without a unit is in
+/// milliseconds.
+fn duration_parser(s: &str) -> Result<Duration, String> {
+  let invalid = || {
+    format!(
+      "invalid duration \"{s}\", expected a number with a unit of ms, s or m"
+    )
+  };
+  let (number, millis_per_unit) = if let Some(n) = s.strip_suffix("ms") {
+    (n, 1)
+  } else if let Some(n) = s.strip_suffix('s') {
+    (n, 1000)
+  } else if let Some(n) = s.strip_suffix('m') {
+    (n, 60 * 1000)
+  } else {
+    (s, 1)
+  };
+  let number = number.parse::<u64>().map_err(|_| invalid())?;
+  Ok(Duration::from_millis(number * millis_per_unit))
+}
+
+/// The format of the test results, with `--reporter`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TestReporterKind {
+  #[default]
+  Pretty,
+  Junit,
+  Tap,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TestReporterConfig {
+  pub kind: TestReporterKind,
+  /// The file that the reporter writes to, or stdout if `None`.
+  pub output: Option<PathBuf>,
 }
 
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
@@ -182,12 +303,20 @@ pub struct TestFlags {
   pub doc: bool,
   pub no_run: bool,
   pub fail_fast: Option<NonZeroUsize>,
+  pub retries: usize,
+  pub update_snapshots: bool,
+  pub rerun_all: bool,
+  pub slow_threshold: Option<Duration>,
   pub files: FileFlags,
   pub allow_none: bool,
   pub filter: Option<String>,
+  pub tags: Vec<String>,
+  pub skip_tags: Vec<String>,
   pub shuffle: Option<u64>,
+  pub shard: Option<TestShard>,
   pub concurrent_jobs: Option<NonZeroUsize>,
   pub trace_ops: bool,
+  pub reporters: Vec<TestReporterConfig>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -224,6 +353,7 @@ pub enum DenoSubcommand {
   Uninstall(UninstallFlags),
   Lsp,
   Lint(LintFlags),
+  Permissions(PermissionsFlags),
   Repl(ReplFlags),
   Run(RunFlags),
   Task(TaskFlags),
@@ -298,7 +428,19 @@ pub struct Flags {
   pub allow_read: Option<Vec<PathBuf>>,
   pub allow_run: Option<Vec<String>>,
   pub allow_sys: Option<Vec<String>>,
+  pub allow_vsock: Option<Vec<String>>,
   pub allow_write: Option<Vec<PathBuf>>,
+  pub deny_env: Option<Vec<String>>,
+  pub deny_hrtime: bool,
+  pub deny_net: Option<Vec<String>>,
+  pub deny_ffi: Option<Vec<PathBuf>>,
+  pub deny_read: Option<Vec<PathBuf>>,
+  pub deny_run: Option<Vec<String>>,
+  pub deny_sys: Option<Vec<String>>,
+  pub deny_vsock: Option<Vec<String>>,
+  pub deny_write: Option<Vec<PathBuf>>,
+  pub permission_set: Option<String>,
+  pub permission_audit: Option<PathBuf>,
   pub ca_stores: Option<Vec<String>>,
   pub ca_data: Option<CaData>,
   pub cache_blocklist: Vec<String>,
@@ -311,6 +453,9 @@ pub struct Flags {
   pub node_modules_dir: Option<bool>,
   pub coverage_dir: Option<String>,
   pub enable_testing_features: bool,
+  /// The dotenv file to load the environment variables of, with
+  /// `--env-file`.
+  pub env_file: Option<String>,
   pub ext: Option<String>,
   pub ignore: Vec<PathBuf>,
   pub import_map_path: Option<String>,
@@ -349,8 +494,17 @@ impl Flags {
   pub fn to_permission_args(&self) -> Vec<String> {
     let mut args = vec![];
 
+    if let Some(permission_set) = &self.permission_set {
+      args.push(format!("--permission-set={permission_set}"));
+    }
+
+    if let Some(permission_audit) = &self.permission_audit {
+      args.push(format!("--permission-audit={}", permission_audit.display()));
+    }
+
     if self.allow_all {
       args.push("--allow-all".to_string());
+      args.extend(self.to_deny_permission_args());
       return args;
     }
 
@@ -434,6 +588,17 @@ impl Flags {
       _ => {}
     }
 
+    match &self.allow_vsock {
+      Some(vsock_allowlist) if vsock_allowlist.is_empty() => {
+        args.push("--allow-vsock".to_string());
+      }
+      Some(vsock_allowlist) => {
+        let s = format!("--allow-vsock={}", vsock_allowlist.join(","));
+        args.push(s)
+      }
+      _ => {}
+    }
+
     match &self.allow_ffi {
       Some(ffi_allowlist) if ffi_allowlist.is_empty() => {
         args.push("--allow-ffi".to_string());
@@ -449,6 +614,37 @@ impl Flags {
       args.push("--allow-hrtime".to_string());
     }
 
+    args.extend(self.to_deny_permission_args());
+    args
+  }
+
+  fn to_deny_permission_args(&self) -> Vec<String> {
+    let mut args = vec![];
+    let denylists = [
+      ("read", self.deny_read.as_ref().map(|l| join_paths(l, ","))),
+      (
+        "write",
+        self.deny_write.as_ref().map(|l| join_paths(l, ",")),
+      ),
+      ("net", self.deny_net.as_ref().map(|l| l.join(","))),
+      ("env", self.deny_env.as_ref().map(|l| l.join(","))),
+      ("run", self.deny_run.as_ref().map(|l| l.join(","))),
+      ("sys", self.deny_sys.as_ref().map(|l| l.join(","))),
+      ("vsock", self.deny_vsock.as_ref().map(|l| l.join(","))),
+      ("ffi", self.deny_ffi.as_ref().map(|l| join_paths(l, ","))),
+    ];
+    for (name, denylist) in denylists {
+      match denylist {
+        Some(denylist) if denylist.is_empty() => {
+          args.push(format!("--deny-{name}"));
+        }
+        Some(denylist) => args.push(format!("--deny-{name}={denylist}")),
+        None => {}
+      }
+    }
+    if self.deny_hrtime {
+      args.push("--deny-hrtime".to_string());
+    }
     args
   }
 
@@ -463,7 +659,11 @@ impl Flags {
     match &self.subcommand {
       Fmt(FmtFlags { files, .. }) => Some(files.include.clone()),
       Lint(LintFlags { files, .. }) => Some(files.include.clone()),
-      Run(RunFlags { script }) => {
+      Run(RunFlags { script })
+      | Permissions(PermissionsFlags {
+        script: Some(script),
+        ..
+      }) => {
         if let Ok(module_specifier) = resolve_url_or_path(script, current_dir) {
           if module_specifier.scheme() == "file"
             || module_specifier.scheme() == "npm"
@@ -541,7 +741,18 @@ impl Flags {
       || self.allow_read.is_some()
       || self.allow_run.is_some()
       || self.allow_sys.is_some()
+      || self.allow_vsock.is_some()
       || self.allow_write.is_some()
+      || self.deny_hrtime
+      || self.deny_env.is_some()
+      || self.deny_ffi.is_some()
+      || self.deny_net.is_some()
+      || self.deny_read.is_some()
+      || self.deny_run.is_some()
+      || self.deny_sys.is_some()
+      || self.deny_vsock.is_some()
+      || self.deny_write.is_some()
+      || self.permission_set.is_some()
   }
 
   pub fn has_permission_in_argv(&self) -> bool {
@@ -554,7 +765,10 @@ impl Flags {
         || arg.starts_with("--allow-read")
         || arg.starts_with("--allow-run")
         || arg.starts_with("--allow-sys")
+        || arg.starts_with("--allow-vsock")
         || arg.starts_with("--allow-write")
+        || arg.starts_with("--deny-")
+        || arg.starts_with("--permission-set")
     })
   }
 }
@@ -579,18 +793,21 @@ static ENV_VARIABLES_HELP: &str = r#"ENVIRONMENT VARIABLES:
                          (alternative to passing --no-prompt on invocation)
     DENO_NO_UPDATE_CHECK Set to disable checking if a newer Deno version is
                          available
+    DENO_PERMISSION_SET  Permission set of the configuration file to use
+                         (alternative to passing --permission-set on
+                         invocation)
     DENO_V8_FLAGS        Set V8 command line options
     DENO_JOBS            Number of parallel workers used for the --parallel
                          flag with the test subcommand. Defaults to number
                          of available CPUs.
     HTTP_PROXY           Proxy address for HTTP requests
-                         (module downloads, fetch)
+                         (module downloads, fetch, WebSocket)
     HTTPS_PROXY          Proxy address for HTTPS requests
-                         (module downloads, fetch)
+                         (module downloads, fetch, WebSocket)
     NPM_CONFIG_REGISTRY  URL to use for the npm registry.
     NO_COLOR             Set to disable color
     NO_PROXY             Comma-separated list of hosts which do not use a proxy
-                         (module downloads, fetch)"#;
+                         (module downloads, fetch, WebSocket)"#;
 
 static DENO_HELP: &str = concat!(
   "A modern JavaScript and TypeScript runtime
@@ -653,6 +870,7 @@ pub fn flags_from_vec(args: Vec<String>) -> clap::error::Result<Flags> {
       "install" => install_parse(&mut flags, &mut m),
       "lint" => lint_parse(&mut flags, &mut m),
       "lsp" => lsp_parse(&mut flags, &mut m),
+      "permissions" => permissions_parse(&mut flags, &mut m),
       "repl" => repl_parse(&mut flags, &mut m),
       "run" => run_parse(&mut flags, &mut m),
       "task" => task_parse(&mut flags, &mut m),
@@ -685,6 +903,7 @@ fn handle_repl_flags(flags: &mut Flags, repl_flags: ReplFlags) {
     flags.allow_run = Some(vec![]);
     flags.allow_read = Some(vec![]);
     flags.allow_sys = Some(vec![]);
+    flags.allow_vsock = Some(vec![]);
     flags.allow_write = Some(vec![]);
     flags.allow_ffi = Some(vec![]);
     flags.allow_hrtime = true;
@@ -752,6 +971,7 @@ fn clap_root() -> Command {
     .subcommand(uninstall_subcommand())
     .subcommand(lsp_subcommand())
     .subcommand(lint_subcommand())
+    .subcommand(permissions_subcommand())
     .subcommand(repl_subcommand())
     .subcommand(run_subcommand())
     .subcommand(task_subcommand())
@@ -772,6 +992,20 @@ fn bench_subcommand() -> Command {
         .action(ArgAction::SetTrue)
         .help("UNSTABLE: Output benchmark result in JSON format"),
     )
+    .arg(
+      Arg::new("compare")
+        .long("compare")
+        .require_equals(true)
+        .value_name("FILE")
+        .value_parser(value_parser!(PathBuf))
+        .help("UNSTABLE: Compare the results with a baseline written by --json")
+        .long_help(
+          "UNSTABLE: Compare the results with a baseline written by an earlier
+run with --json. The benchmarks whose mean time per iteration is
+significantly higher than in the baseline, according to Welch's t-test
+at the 95% confidence level, are reported as regressions and fail the run.",
+        ),
+    )
     .arg(
       Arg::new("ignore")
         .long("ignore")
@@ -821,7 +1055,6 @@ glob {*_,*.,}bench.{js,mjs,ts,mts,jsx,tsx}:
 
 fn bundle_subcommand() -> Command {
   compile_args(Command::new("bundle"))
-    .hide(true)
     .arg(check_arg(true))
     .arg(
       Arg::new("source_file")
@@ -833,18 +1066,67 @@ fn bundle_subcommand() -> Command {
         .value_parser(value_parser!(PathBuf))
         .value_hint(ValueHint::FilePath),
     )
+    .arg(
+      Arg::new("external")
+        .long("external")
+        .value_name("SPECIFIER")
+        .help("Leave imports of SPECIFIER out of the bundle")
+        .long_help(
+          "Leave the imports of SPECIFIER out of the bundle, so they stay \
+imports of the bundled module and are resolved by whoever loads it. The \
+specifier is matched as it is written in the imports, and a trailing * \
+matches any specifier that starts with the rest of it. The flag can be given \
+more than once.
+
+  deno bundle --external=react --external=\"npm:*\" mod.ts mod.bundle.js",
+        )
+        .action(ArgAction::Append),
+    )
+    .arg(
+      Arg::new("source-map")
+        .long("source-map")
+        .value_name("MODE")
+        .help("Emit a source map of the bundle")
+        .long_help(
+          "Emit a source map of the bundle, overriding the \"sourceMap\" and \
+\"inlineSourceMap\" compiler options of the configuration file.
+
+  inline    Append the source map to the bundle as a data URL
+  external  Write the source map next to the output file, with a .map \
+extension. This is the default when no MODE is given",
+        )
+        .num_args(0..=1)
+        .require_equals(true)
+        .default_missing_value("external")
+        .value_parser(["inline", "external"]),
+    )
+    .arg(
+      Arg::new("minify")
+        .long("minify")
+        .help("Minify the bundle")
+        .long_help(
+          "Minify the bundle by leaving out the whitespace, comments and \
+other text that is not needed to run it. Names are kept as they are.",
+        )
+        .action(ArgAction::SetTrue),
+    )
     .arg(watch_arg(false))
     .arg(no_clear_screen_arg())
     .arg(executable_ext_arg())
     .about("Bundle module and dependencies into single file")
     .long_about(
-      "Output a single JavaScript file with all dependencies.
+      "Output a single JavaScript module with all dependencies.
 
   deno bundle https://deno.land/std/examples/colors.ts colors.bundle.js
 
 If no output file is given, the output is written to standard output:
 
-  deno bundle https://deno.land/std/examples/colors.ts",
+  deno bundle https://deno.land/std/examples/colors.ts
+
+Imports that are provided by the environment of the bundle, like a package \
+that is loaded from a CDN in the browser, can be left out with --external:
+
+  deno bundle --external=react --minify --source-map mod.tsx mod.js",
     )
 }
 
@@ -912,12 +1194,22 @@ fn compile_subcommand() -> Command {
     .arg(
       Arg::new("include")
         .long("include")
-        .help("UNSTABLE: Additional module to include in the module graph")
+        .help(
+          "UNSTABLE: Additional module or asset to include in the executable",
+        )
         .long_help(
           "Includes an additional module in the compiled executable's module
     graph. Use this flag if a dynamically imported module or a web worker main
     module fails to load in the executable. This flag can be passed multiple
-    times, to include multiple additional modules.",
+    times, to include multiple additional modules. A glob pattern with the
+    extension of a module, like \"plugins/*.ts\", includes all the modules it
+    matches, for the targets of dynamic imports that are computed at runtime.
+
+    Directories, the other glob patterns (for example \"assets/**\") and the
+    files that aren't modules are embedded as assets instead. The executable
+    can read them at their original path with `Deno.readFile()`,
+    `Deno.open()`, `Deno.readDir()` and the other read-only file system APIs.
+    Assets can't be imported.",
         )
         .action(ArgAction::Append)
         .value_hint(ValueHint::FilePath),
@@ -941,6 +1233,64 @@ fn compile_subcommand() -> Command {
           "aarch64-apple-darwin",
         ]),
     )
+    .arg(
+      Arg::new("sign")
+        .long("sign")
+        .value_name("IDENTITY")
+        .help("UNSTABLE: Sign the executable with a certificate")
+        .long_help(
+          "Signs the executable with the signing tool of the target platform,
+    so that it doesn't trigger the warnings of SmartScreen or Gatekeeper.
+
+    Windows executables are signed with signtool on Windows and with
+    osslsigncode elsewhere, and IDENTITY is a path to a PKCS#12 (.pfx) file,
+    or the subject name of a certificate in the store of signtool. The
+    password of the PKCS#12 file is read from DENO_SIGN_PASSWORD, which
+    osslsigncode reads from a private file. signtool only accepts PKCS#12
+    files without a password, so import the certificate into the store to
+    sign with it on Windows.
+
+    macOS executables are signed with codesign on macOS, and IDENTITY is the
+    name of a signing identity in the keychain.",
+        ),
+    )
+    .arg(
+      Arg::new("sign-timestamp-url")
+        .long("sign-timestamp-url")
+        .value_name("URL")
+        .requires("sign")
+        .help("The time-stamping server of the signature"),
+    )
+    .arg(
+      Arg::new("sign-notarize")
+        .long("sign-notarize")
+        .value_name("COMMAND")
+        .requires("sign")
+        .help("A command that notarizes the signed macOS executable")
+        .long_help(
+          "A command that is run after a macOS executable is signed, with the
+    path of the executable as its last argument, to notarize it, for example
+    with \"xcrun notarytool submit --wait --keychain-profile deno\". Its
+    arguments are split like a shell does, so they can be quoted.",
+        ),
+    )
+    .arg(
+      Arg::new("lite")
+        .long("lite")
+        .action(ArgAction::SetTrue)
+        .help("UNSTABLE: Use a smaller runtime without the FFI and KV APIs")
+        .long_help(
+          "Uses the lite runtime of the target, which leaves out the extensions
+    of the FFI and KV APIs to make the executable smaller. The APIs throw when
+    they are used. The lite runtime is always downloaded, even for the current
+    target, and is only available for x86_64-unknown-linux-gnu,
+    x86_64-pc-windows-msvc and x86_64-apple-darwin.
+
+    Only the code of the two extensions is left out: SQLite, which KV is built
+    on, stays in the executable for the Web Storage and Cache APIs, so the
+    executable is smaller by the size of the two extensions only.",
+        ),
+    )
     .arg(executable_ext_arg())
     .about("UNSTABLE: Compile the script into a self contained executable")
     .long_about(
@@ -1016,9 +1366,19 @@ Write a report using the lcov format:
 
   deno coverage --lcov --output=cov.lcov cov_profile/
 
-Generate html reports from lcov:
+Write a browsable HTML report to a directory:
+
+  deno coverage --html=cov_html cov_profile/
+
+Fail if less than 90% of the lines or 75% of the branches are covered, in total
+or in any file:
 
-  genhtml -o html_cov cov.lcov
+  deno coverage --check --lines=90 --branches=75 cov_profile/
+
+The thresholds can also be set in the \"coverage\" field of the configuration
+file, which the flags take precedence over:
+
+  { \"coverage\": { \"thresholds\": { \"lines\": 90, \"branches\": 75 } } }
 ",
     )
     .arg(
@@ -1056,6 +1416,27 @@ Generate html reports from lcov:
         .help("Output coverage report in lcov format")
         .action(ArgAction::SetTrue),
     )
+    .arg(
+      Arg::new("html")
+        .long("html")
+        .value_name("DIR")
+        .value_parser(value_parser!(PathBuf))
+        .require_equals(true)
+        .conflicts_with("lcov")
+        .help("Write an HTML coverage report to the given directory")
+        .value_hint(ValueHint::DirPath),
+    )
+    .arg(
+      Arg::new("check")
+        .long("check")
+        .help("Fail if the coverage is below the thresholds")
+        .action(ArgAction::SetTrue),
+    )
+    .arg(coverage_threshold_arg("lines"))
+    .arg(coverage_threshold_arg("functions"))
+    .arg(coverage_threshold_arg("branches"))
+    .arg(config_arg())
+    .arg(no_config_arg())
     .arg(
       Arg::new("output")
         .requires("lcov")
@@ -1080,6 +1461,16 @@ Generate html reports from lcov:
     )
 }
 
+fn coverage_threshold_arg(name: &'static str) -> Arg {
+  Arg::new(name)
+    .long(name)
+    .value_name("PERCENT")
+    .value_parser(value_parser!(u8).range(0..=100))
+    .require_equals(true)
+    .requires("check")
+    .help(format!("The minimum percentage of covered {name}"))
+}
+
 fn doc_subcommand() -> Command {
   Command::new("doc")
     .about("Show documentation for a module")
@@ -1098,6 +1489,14 @@ Output documentation in JSON format:
 
     deno doc --json ./path/to/module.ts
 
+Generate a static HTML documentation site in the ./docs/ directory:
+
+    deno doc --html --name=\"My library\" ./path/to/module.ts
+
+Check that the exported symbols are documented:
+
+    deno doc --lint ./path/to/module.ts
+
 Target a specific symbol:
 
     deno doc ./path/to/module.ts MyClass.someField
@@ -1119,6 +1518,57 @@ Show documentation for runtime built-ins:
         .help("Output documentation in JSON format")
         .action(ArgAction::SetTrue),
     )
+    .arg(
+      Arg::new("html")
+        .long("html")
+        .help("Output documentation as a static HTML site")
+        .long_help(
+          "Output documentation as a static HTML site, with a page for every \
+symbol, a search over the symbols and links to the source of every symbol. \
+The site is written to the directory of --output.",
+        )
+        .conflicts_with("json")
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("lint")
+        .long("lint")
+        .help("Check that the exported symbols are documented")
+        .long_help(
+          "Check that the exported symbols are documented, and fail when \
+one or a public member of its class has no JSDoc comment, when a function or \
+method has no explicit return type, when its JSDoc has no @returns tag \
+although it returns a value, or when a @param tag of its JSDoc names a \
+parameter that does not exist. Only the symbols of the local files are checked, and the \"doc.lint\" \
+field of the configuration file can narrow them down to the files of the \
+public API:
+
+  {
+    \"doc\": {
+      \"lint\": { \"include\": [\"src/\"], \"exclude\": [\"src/internal/\"] }
+    }
+  }",
+        )
+        .conflicts_with_all(["json", "html"])
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("name")
+        .long("name")
+        .value_name("NAME")
+        .help("The name of the documented library, used in the HTML site")
+        .requires("html"),
+    )
+    .arg(
+      Arg::new("output")
+        .long("output")
+        .value_name("DIR")
+        .help("The directory to write the HTML site to")
+        .requires("html")
+        .default_value("./docs/")
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::DirPath),
+    )
     .arg(
       Arg::new("private")
         .long("private")
@@ -1135,7 +1585,9 @@ Show documentation for runtime built-ins:
       Arg::new("filter")
         .help("Dot separated path to symbol")
         .required(false)
-        .conflicts_with("json"),
+        .conflicts_with("json")
+        .conflicts_with("html")
+        .conflicts_with("lint"),
     )
 }
 
@@ -1318,6 +1770,14 @@ type: JavaScript, TypeScript, or JSON.
 emit: Local path of compiled source code. (TypeScript only.)
 dependencies: Dependency tree of the source file.
 
+With --json, the sizes of the emitted code of the modules and the modules and \
+npm packages that are in the graph at several versions are shown as well.
+
+Show why a module or an npm package is in the graph, with the chains of \
+imports that lead to it:
+
+  deno info --why=npm:chalk main.ts
+
 Without any additional arguments, 'deno info' shows:
 
 DENO_DIR: Directory containing Deno-managed files.
@@ -1347,6 +1807,13 @@ TypeScript compiler cache: Subdirectory containing TS compiler output.",
         .help("UNSTABLE: Outputs the information in JSON format")
         .action(ArgAction::SetTrue),
     )
+    .arg(
+      Arg::new("why")
+        .long("why")
+        .value_name("SPECIFIER")
+        .help("Show the chains of imports that lead to a module or an npm package")
+        .requires("file"),
+    )
 }
 
 fn install_subcommand() -> Command {
@@ -1467,6 +1934,10 @@ List available rules:
 
   deno lint --rules
 
+Fix the problems of the rules that support it, like unused imports:
+
+  deno lint --fix
+
 Ignore diagnostics on the next line by preceding it with an ignore comment and
 rule name:
 
@@ -1486,6 +1957,20 @@ Ignore linting a file by adding an ignore comment at the top of the file:
         .help("List available rules")
         .action(ArgAction::SetTrue),
     )
+    .arg(
+      Arg::new("fix")
+        .long("fix")
+        .help("Fix the problems that have automatic fixes")
+        .long_help(
+          "Fix the problems of the rules that have automatic fixes, and report \
+the ones that are left. The fixed files are linted again until there is \
+nothing left to fix. The rules with fixes are no-extra-semi, no-unused-vars \
+(for imports) and prefer-const. Without --fix, the edits that would fix a \
+problem are reported with it by --json.",
+        )
+        .conflicts_with("rules")
+        .action(ArgAction::SetTrue),
+    )
     .arg(
       Arg::new("rules-tags")
         .long("rules-tags")
@@ -1551,6 +2036,39 @@ Ignore linting a file by adding an ignore comment at the top of the file:
     .arg(no_clear_screen_arg())
 }
 
+fn permissions_subcommand() -> Command {
+  Command::new("permissions")
+    .about("List or clear the remembered permission prompt decisions")
+    .long_about(
+      "List the permission prompt decisions of a project that were answered \
+with 'p' (allow and remember for this project). They are granted without \
+prompting when the project is run again.
+
+  deno permissions
+  deno permissions main.ts
+
+The decisions are remembered for the configuration file of the project, or \
+for the script when there is none.
+
+Forget the decisions of the project with --clear:
+
+  deno permissions --clear",
+    )
+    .arg(
+      Arg::new("script_arg")
+        .required(false)
+        .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+      Arg::new("clear")
+        .long("clear")
+        .action(ArgAction::SetTrue)
+        .help("Forget the remembered permission prompt decisions"),
+    )
+    .arg(config_arg())
+    .arg(no_config_arg())
+}
+
 fn repl_subcommand() -> Command {
   runtime_args(Command::new("repl"), true, true)
     .about("Read Eval Print Loop")
@@ -1583,6 +2101,7 @@ fn run_subcommand() -> Command {
     )
     .arg(no_clear_screen_arg())
     .arg(executable_ext_arg())
+    .arg(env_file_arg())
     .arg(
       script_arg()
         .required_unless_present("v8-flags")
@@ -1627,11 +2146,40 @@ fn task_subcommand() -> Command {
         .help("Specify the directory to run the task in")
         .value_hint(ValueHint::DirPath),
     )
+    .arg(
+      Arg::new("parallel")
+        .long("parallel")
+        .help("Run the tasks that don't depend on each other in parallel")
+        .long_help(
+          "Run the tasks that don't depend on each other in parallel. \
+The output of each task is prefixed with its name.",
+        )
+        .action(ArgAction::SetTrue),
+    )
+    .arg(env_file_arg())
+    .arg(permission_set_arg())
     .about("Run a task defined in the configuration file")
     .long_about(
       "Run a task defined in the configuration file
 
-  deno task build",
+  deno task build
+
+A task can depend on other tasks, which run before it:
+
+  {
+    \"tasks\": {
+      \"codegen\": \"deno run -A codegen.ts\",
+      \"build\": {
+        \"command\": \"deno run -A build.ts\",
+        \"dependsOn\": [\"codegen\", \"fmt:check\"]
+      }
+    }
+  }
+
+Use --parallel to run the tasks that don't depend on each other at the same
+time:
+
+  deno task --parallel build",
     )
 }
 
@@ -1676,6 +2224,31 @@ fn test_subcommand() -> Command {
         .value_name("N")
         .value_parser(value_parser!(NonZeroUsize)),
     )
+    .arg(
+      Arg::new("retries")
+        .long("retries")
+        .value_name("N")
+        .require_equals(true)
+        .value_parser(value_parser!(usize))
+        .help("Run failing tests again up to N times")
+        .long_help(
+          "Run failing tests again, in a new isolate, up to N times. The tests \
+that pass after failing are reported as flaky. The `retries` option of \
+`Deno.test()` overrides this for a single test.
+
+  deno test --retries=2",
+        ),
+    )
+    .arg(
+      Arg::new("update-snapshots")
+        .long("update-snapshots")
+        .help("Update the snapshots of TestContext.assertSnapshot()")
+        .long_help(
+          "Write the asserted values to the snapshot files instead of comparing \
+them. The snapshots of the tests that don't run are kept.",
+        )
+        .action(ArgAction::SetTrue),
+    )
     .arg(
       Arg::new("allow-none")
         .long("allow-none")
@@ -1688,6 +2261,37 @@ fn test_subcommand() -> Command {
         .long("filter")
         .help("Run tests with this string or pattern in the test name"),
     )
+    .arg(
+      Arg::new("tags")
+        .long("tags")
+        .value_name("TAGS")
+        .num_args(1..)
+        .use_value_delimiter(true)
+        .require_equals(true)
+        .help("Run only the tests that have at least one of these tags")
+        .long_help(
+          "Run only the tests that have at least one of these comma separated \
+tags, given with the `tags` option of `Deno.test()`. The tests that are left \
+out are reported as filtered out.
+
+  deno test --tags=integration,slow",
+        ),
+    )
+    .arg(
+      Arg::new("skip-tags")
+        .long("skip-tags")
+        .value_name("TAGS")
+        .num_args(1..)
+        .use_value_delimiter(true)
+        .require_equals(true)
+        .help("Skip the tests that have any of these tags")
+        .long_help(
+          "Skip the tests that have any of these comma separated tags, even if \
+they are selected by --tags.
+
+  deno test --skip-tags=slow",
+        ),
+    )
     .arg(
       Arg::new("shuffle")
         .long("shuffle")
@@ -1697,6 +2301,76 @@ fn test_subcommand() -> Command {
         .require_equals(true)
         .value_parser(value_parser!(u64)),
     )
+    .arg(
+      Arg::new("shard")
+        .long("shard")
+        .value_name("INDEX/COUNT")
+        .help("Run only the part INDEX of COUNT of the tests, which are assigned to the parts by the hash of their file and name")
+        .long_help(
+          "Split the tests into COUNT parts, and only run the part INDEX. Each \
+test is assigned to a part by the hash of its file path, relative to the \
+current directory, and its name, so that the parts are the same on every \
+machine, and running all of them runs all the tests. Useful to split a test \
+suite across CI machines.
+
+  deno test --shard=2/5",
+        )
+        .require_equals(true)
+        .value_parser(TestShard::from_str),
+    )
+    .arg(
+      Arg::new("slow-threshold")
+        .long("slow-threshold")
+        .value_name("DURATION")
+        .help("Highlight the tests that take longer than DURATION, like 500ms or 2s")
+        .long_help(
+          "Highlight the tests that take at least DURATION to run in the report, \
+and list them in the summary, slowest first. The DURATION is a number with a \
+unit of ms, s or m.
+
+  deno test --slow-threshold=500ms",
+        )
+        .require_equals(true)
+        .value_parser(duration_parser),
+    )
+    .arg(
+      Arg::new("reporter")
+        .long("reporter")
+        .value_name("REPORTER")
+        .help("Select the reporters of the test results, which can be given more than once")
+        .long_help(
+          "Select the reporters of the test results. The flag can be given \
+more than once to use several reporters at the same time, for example to keep \
+the pretty output on the console while writing a report file for CI.
+
+  pretty  Human readable output, the default
+  junit   JUnit XML report
+  tap     Test Anything Protocol, version 14
+
+  deno test --reporter=junit --reporter-output=report.xml",
+        )
+        .require_equals(true)
+        .action(ArgAction::Append)
+        .value_parser(["pretty", "junit", "tap"]),
+    )
+    .arg(
+      Arg::new("reporter-output")
+        .long("reporter-output")
+        .value_name("FILE")
+        .help("Write the output of a reporter to FILE instead of stdout")
+        .long_help(
+          "Write the output of the reporter selected by the preceding \
+--reporter flag to FILE instead of stdout. The pretty reporter can only write \
+to stdout, and keeps doing so unless another reporter does.
+
+  deno test --reporter=junit --reporter-output=report.xml \\
+    --reporter=tap --reporter-output=report.tap",
+        )
+        .require_equals(true)
+        .requires("reporter")
+        .action(ArgAction::Append)
+        .value_parser(value_parser!(PathBuf)),
+    )
     .arg(
       Arg::new("coverage")
         .long("coverage")
@@ -1736,6 +2410,17 @@ fn test_subcommand() -> Command {
         .conflicts_with("no-run")
         .conflicts_with("coverage"),
     )
+    .arg(
+      Arg::new("rerun-all")
+        .long("rerun-all")
+        .requires("watch")
+        .help("Rerun all test modules on any change in watch mode")
+        .long_help(
+          "Rerun all the test modules on any change in watch mode. By default only \
+the test modules that depend on the changed files are run again.",
+        )
+        .action(ArgAction::SetTrue),
+    )
     .arg(no_clear_screen_arg())
     .arg(script_arg().last(true))
     .about("Run tests")
@@ -1903,13 +2588,15 @@ static ALLOW_WRITE_HELP: &str = concat!(
 );
 
 static ALLOW_NET_HELP: &str = concat!(
-  "Allow network access. Optionally specify allowed IP addresses and host names, with ports as necessary.\n",
+  "Allow network access. Optionally specify allowed IP addresses, ranges of IP addresses in CIDR notation and host names, with ports as necessary. Host names starting with \"*.\" allow all of their subdomains.\n",
   "Docs: https://deno.land/manual@v",
   env!("CARGO_PKG_VERSION"),
   "/basics/permissions\n",
   "Examples:\n",
   "  --allow-net\n",
-  "  --allow-net=\"localhost:8080,deno.land\""
+  "  --allow-net=\"localhost:8080,deno.land\"\n",
+  "  --allow-net=\"10.0.0.0/8,192.168.1.0/24\"\n",
+  "  --allow-net=\"*.amazonaws.com\""
 );
 
 static ALLOW_ENV_HELP: &str = concat!(
@@ -1919,7 +2606,8 @@ static ALLOW_ENV_HELP: &str = concat!(
   "/basics/permissions\n",
   "Examples:\n",
   "  --allow-env\n",
-  "  --allow-env=\"PORT,HOME,PATH\""
+  "  --allow-env=\"PORT,HOME,PATH\"\n",
+  "  --allow-env=\"AWS_*,MYAPP_*\""
 );
 
 static ALLOW_SYS_HELP: &str = concat!(
@@ -1932,14 +2620,25 @@ static ALLOW_SYS_HELP: &str = concat!(
   "  --allow-sys=\"systemMemoryInfo,osRelease\""
 );
 
+static ALLOW_VSOCK_HELP: &str = concat!(
+  "Allow vsock access to virtual machines or their host (Linux only). Optionally specify allowed context IDs and ports.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --allow-vsock\n",
+  "  --allow-vsock=\"2,3:1024\""
+);
+
 static ALLOW_RUN_HELP: &str = concat!(
-  "Allow running subprocesses. Optionally specify allowed runnable program names.\n",
+  "Allow running subprocesses. Optionally specify allowed runnable program names, with a prefix of their arguments after a colon as necessary. Programs allowed with a prefix of their arguments can't be run with a changed environment or working directory.\n",
   "Docs: https://deno.land/manual@v",
   env!("CARGO_PKG_VERSION"),
   "/basics/permissions\n",
   "Examples:\n",
   "  --allow-run\n",
-  "  --allow-run=\"whoami,ps\""
+  "  --allow-run=\"whoami,ps\"\n",
+  "  --allow-run=\"git:status,git:diff\""
 );
 
 static ALLOW_FFI_HELP: &str = concat!(
@@ -1966,7 +2665,114 @@ static ALLOW_ALL_HELP: &str = concat!(
   "/basics/permissions\n"
 );
 
-fn permission_args(app: Command) -> Command {
+static DENY_READ_HELP: &str = concat!(
+  "Deny file system read access, over the allowed access and the prompts. Optionally specify denied paths.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --deny-read\n",
+  "  --deny-read=\"/etc,/var/log.txt\""
+);
+
+static DENY_WRITE_HELP: &str = concat!(
+  "Deny file system write access, over the allowed access and the prompts. Optionally specify denied paths.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --deny-write\n",
+  "  --deny-write=\"/etc,/var/log.txt\""
+);
+
+static DENY_NET_HELP: &str = concat!(
+  "Deny network access, over the allowed access and the prompts. Optionally specify denied IP addresses, ranges of IP addresses in CIDR notation and host names, with ports as necessary.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --deny-net\n",
+  "  --deny-net=\"metadata.internal,169.254.0.0/16\""
+);
+
+static DENY_ENV_HELP: &str = concat!(
+  "Deny access to system environment information, over the allowed access and the prompts. Optionally specify denied environment variables.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --deny-env\n",
+  "  --deny-env=\"AWS_SECRET_ACCESS_KEY\""
+);
+
+static DENY_SYS_HELP: &str = concat!(
+  "Deny access to OS information, over the allowed access and the prompts. Optionally specify denied APIs.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --deny-sys\n",
+  "  --deny-sys=\"networkInterfaces\""
+);
+
+static DENY_VSOCK_HELP: &str = concat!(
+  "Deny access to vsock addresses, over the allowed access and the prompts. Optionally specify denied context IDs, with ports as necessary.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --deny-vsock\n",
+  "  --deny-vsock=\"2:1024\""
+);
+
+static DENY_RUN_HELP: &str = concat!(
+  "Deny running subprocesses, over the allowed access and the prompts. Optionally specify denied programs, with a prefix of their arguments after a colon as necessary.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --deny-run\n",
+  "  --deny-run=\"curl,ssh\"\n",
+  "  --deny-run=\"git:push\""
+);
+
+static DENY_FFI_HELP: &str = concat!(
+  "(Unstable) Deny loading dynamic libraries, over the allowed access and the prompts. Optionally specify denied paths.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --deny-ffi\n",
+  "  --deny-ffi=\"./libfoo.so\""
+);
+
+static PERMISSION_SET_HELP: &str = concat!(
+  "Use a permission set defined in the \"permissions\" section of the configuration file.\n",
+  "Permission sets are only used when they are chosen with this flag.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/getting_started/configuration_file\n",
+  "Examples:\n",
+  "  --permission-set=ci"
+);
+
+static PERMISSION_AUDIT_HELP: &str = concat!(
+  "Record every permission check, with its result, the requesting API and the JavaScript stack, as JSON lines appended to the file.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n",
+  "Examples:\n",
+  "  --permission-audit=permissions.jsonl"
+);
+
+static DENY_HRTIME_HELP: &str = concat!(
+  "Deny high-resolution time measurement, over the allowed access and the prompts.\n",
+  "Docs: https://deno.land/manual@v",
+  env!("CARGO_PKG_VERSION"),
+  "/basics/permissions\n"
+);
+
+fn permission_args(app: Command) -> Command {
   app
     .arg(
       Arg::new("allow-read")
@@ -2009,17 +2815,7 @@ fn permission_args(app: Command) -> Command {
         .require_equals(true)
         .value_name("VARIABLE_NAME")
         .help(ALLOW_ENV_HELP)
-        .value_parser(|key: &str| {
-          if key.is_empty() || key.contains(&['=', '\0'] as &[char]) {
-            return Err(format!("invalid key \"{key}\""));
-          }
-
-          Ok(if cfg!(windows) {
-            key.to_uppercase()
-          } else {
-            key.to_string()
-          })
-        }),
+        .value_parser(parse_env_var_name),
     )
     .arg(
       Arg::new("allow-sys")
@@ -2031,6 +2827,16 @@ fn permission_args(app: Command) -> Command {
         .help(ALLOW_SYS_HELP)
         .value_parser(|key: &str| parse_sys_kind(key).map(ToString::to_string)),
     )
+    .arg(
+      Arg::new("allow-vsock")
+        .long("allow-vsock")
+        .num_args(0..)
+        .use_value_delimiter(true)
+        .require_equals(true)
+        .value_name("CID[:PORT]")
+        .help(ALLOW_VSOCK_HELP)
+        .value_parser(parse_vsock_addr),
+    )
     .arg(
       Arg::new("allow-run")
         .long("allow-run")
@@ -2064,6 +2870,104 @@ fn permission_args(app: Command) -> Command {
         .action(ArgAction::SetTrue)
         .help(ALLOW_ALL_HELP),
     )
+    .arg(
+      Arg::new("deny-read")
+        .long("deny-read")
+        .num_args(0..)
+        .use_value_delimiter(true)
+        .require_equals(true)
+        .value_name("PATH")
+        .help(DENY_READ_HELP)
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::AnyPath),
+    )
+    .arg(
+      Arg::new("deny-write")
+        .long("deny-write")
+        .num_args(0..)
+        .use_value_delimiter(true)
+        .require_equals(true)
+        .value_name("PATH")
+        .help(DENY_WRITE_HELP)
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::AnyPath),
+    )
+    .arg(
+      Arg::new("deny-net")
+        .long("deny-net")
+        .num_args(0..)
+        .use_value_delimiter(true)
+        .require_equals(true)
+        .value_name("IP_OR_HOSTNAME")
+        .help(DENY_NET_HELP)
+        .value_parser(flags_allow_net::validator),
+    )
+    .arg(
+      Arg::new("deny-env")
+        .long("deny-env")
+        .num_args(0..)
+        .use_value_delimiter(true)
+        .require_equals(true)
+        .value_name("VARIABLE_NAME")
+        .help(DENY_ENV_HELP)
+        .value_parser(parse_env_var_name),
+    )
+    .arg(
+      Arg::new("deny-sys")
+        .long("deny-sys")
+        .num_args(0..)
+        .use_value_delimiter(true)
+        .require_equals(true)
+        .value_name("API_NAME")
+        .help(DENY_SYS_HELP)
+        .value_parser(|key: &str| parse_sys_kind(key).map(ToString::to_string)),
+    )
+    .arg(
+      Arg::new("deny-vsock")
+        .long("deny-vsock")
+        .num_args(0..)
+        .use_value_delimiter(true)
+        .require_equals(true)
+        .value_name("CID[:PORT]")
+        .help(DENY_VSOCK_HELP)
+        .value_parser(parse_vsock_addr),
+    )
+    .arg(
+      Arg::new("deny-run")
+        .long("deny-run")
+        .num_args(0..)
+        .use_value_delimiter(true)
+        .require_equals(true)
+        .value_name("PROGRAM_NAME")
+        .help(DENY_RUN_HELP),
+    )
+    .arg(
+      Arg::new("deny-ffi")
+        .long("deny-ffi")
+        .num_args(0..)
+        .use_value_delimiter(true)
+        .require_equals(true)
+        .value_name("PATH")
+        .help(DENY_FFI_HELP)
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::AnyPath),
+    )
+    .arg(
+      Arg::new("deny-hrtime")
+        .long("deny-hrtime")
+        .action(ArgAction::SetTrue)
+        .help(DENY_HRTIME_HELP),
+    )
+    .arg(permission_set_arg())
+    .arg(
+      Arg::new("permission-audit")
+        .long("permission-audit")
+        .require_equals(true)
+        .value_name("FILE")
+        .help(PERMISSION_AUDIT_HELP)
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::FilePath),
+    )
     .arg(
       Arg::new("prompt")
         .long("prompt")
@@ -2081,6 +2985,24 @@ fn permission_args(app: Command) -> Command {
     )
 }
 
+fn parse_env_var_name(key: &str) -> Result<String, String> {
+  if key.is_empty() || key.contains(&['=', '\0'] as &[char]) {
+    return Err(format!("invalid key \"{key}\""));
+  }
+
+  Ok(if cfg!(windows) {
+    key.to_uppercase()
+  } else {
+    key.to_string()
+  })
+}
+
+fn parse_vsock_addr(addr: &str) -> Result<String, String> {
+  VsockDescriptor::from_str(addr)
+    .map(|_| addr.to_string())
+    .map_err(|e| e.to_string())
+}
+
 fn runtime_args(
   app: Command,
   include_perms: bool,
@@ -2200,6 +3122,24 @@ fn cached_only_arg() -> Arg {
     .help("Require that remote dependencies are already cached")
 }
 
+fn env_file_arg() -> Arg {
+  Arg::new("env-file")
+    .long("env-file")
+    .value_name("FILE")
+    .num_args(0..=1)
+    .require_equals(true)
+    .default_missing_value(".env")
+    .help("Load environment variables from a dotenv file")
+    .long_help(
+      "Load environment variables from a dotenv file, \
+which defaults to \".env\" when no file is given. \
+Variables that are already set are not overridden, \
+and references like ${VAR} in the values are expanded. \
+Reading the variables still requires --allow-env.",
+    )
+    .value_hint(ValueHint::FilePath)
+}
+
 /// Used for subcommands that operate on executable scripts only.
 /// `deno fmt` has its own `--ext` arg because its possible values differ.
 /// If --ext is not provided and the script doesn't have a file extension,
@@ -2399,6 +3339,14 @@ fn config_arg() -> Arg {
     .value_hint(ValueHint::FilePath)
 }
 
+fn permission_set_arg() -> Arg {
+  Arg::new("permission-set")
+    .long("permission-set")
+    .require_equals(true)
+    .value_name("NAME")
+    .help(PERMISSION_SET_HELP)
+}
+
 fn no_config_arg() -> Arg {
   Arg::new("no-config")
     .long("no-config")
@@ -2473,6 +3421,7 @@ fn bench_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   };
 
   let no_run = matches.get_flag("no-run");
+  let compare = matches.remove_one::<PathBuf>("compare");
 
   watch_arg_parse(flags, matches, false);
   flags.subcommand = DenoSubcommand::Bench(BenchFlags {
@@ -2480,6 +3429,7 @@ fn bench_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     filter,
     json,
     no_run,
+    compare,
   });
 }
 
@@ -2498,12 +3448,28 @@ fn bundle_parse(flags: &mut Flags, matches: &mut ArgMatches) {
       None
     };
 
+  let external = match matches.remove_many::<String>("external") {
+    Some(external) => external.collect(),
+    None => vec![],
+  };
+  let source_map = matches.remove_one::<String>("source-map").map(|mode| {
+    match mode.as_str() {
+      "inline" => BundleSourceMapFlag::Inline,
+      "external" => BundleSourceMapFlag::External,
+      _ => unreachable!(),
+    }
+  });
+  let minify = matches.get_flag("minify");
+
   watch_arg_parse(flags, matches, false);
   ext_arg_parse(flags, matches);
 
   flags.subcommand = DenoSubcommand::Bundle(BundleFlags {
     source_file,
     out_file,
+    external,
+    source_map,
+    minify,
   });
 }
 
@@ -2536,6 +3502,15 @@ fn compile_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     Some(f) => f.collect(),
     None => vec![],
   };
+  let sign =
+    matches
+      .remove_one::<String>("sign")
+      .map(|identity| CompileSignFlags {
+        identity,
+        timestamp_url: matches.remove_one::<String>("sign-timestamp-url"),
+        notarize_command: matches.remove_one::<String>("sign-notarize"),
+      });
+  let lite = matches.get_flag("lite");
   ext_arg_parse(flags, matches);
 
   flags.subcommand = DenoSubcommand::Compile(CompileFlags {
@@ -2544,6 +3519,8 @@ fn compile_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     args,
     target,
     include,
+    sign,
+    lite,
   });
 }
 
@@ -2577,6 +3554,7 @@ fn completions_parse(
 }
 
 fn coverage_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  config_args_parse(flags, matches);
   let files = match matches.remove_many::<PathBuf>("files") {
     Some(f) => f.collect(),
     None => vec![],
@@ -2595,6 +3573,13 @@ fn coverage_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   };
   let lcov = matches.get_flag("lcov");
   let output = matches.remove_one::<PathBuf>("output");
+  let html = matches.remove_one::<PathBuf>("html");
+  let check = matches.get_flag("check");
+  let thresholds = CoverageThresholds {
+    lines: matches.remove_one::<u8>("lines"),
+    functions: matches.remove_one::<u8>("functions"),
+    branches: matches.remove_one::<u8>("branches"),
+  };
   flags.subcommand = DenoSubcommand::Coverage(CoverageFlags {
     files: FileFlags {
       include: files,
@@ -2604,6 +3589,9 @@ fn coverage_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     include,
     exclude,
     lcov,
+    html,
+    check,
+    thresholds,
   });
 }
 
@@ -2627,10 +3615,21 @@ fn doc_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     .unwrap_or_default();
   let private = matches.get_flag("private");
   let json = matches.get_flag("json");
+  let lint = matches.get_flag("lint");
+  let html = if matches.get_flag("html") {
+    Some(DocHtmlFlag {
+      name: matches.remove_one::<String>("name"),
+      output: matches.remove_one::<PathBuf>("output").unwrap(),
+    })
+  } else {
+    None
+  };
   let filter = matches.remove_one::<String>("filter");
   flags.subcommand = DenoSubcommand::Doc(DocFlags {
     source_file,
     json,
+    html,
+    lint,
     filter,
     private,
   });
@@ -2643,6 +3642,7 @@ fn eval_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.allow_run = Some(vec![]);
   flags.allow_read = Some(vec![]);
   flags.allow_sys = Some(vec![]);
+  flags.allow_vsock = Some(vec![]);
   flags.allow_write = Some(vec![]);
   flags.allow_ffi = Some(vec![]);
   flags.allow_hrtime = true;
@@ -2725,6 +3725,7 @@ fn info_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.subcommand = DenoSubcommand::Info(InfoFlags {
     file: matches.remove_one::<String>("file"),
     json,
+    why: matches.remove_one::<String>("why"),
   });
 }
 
@@ -2772,6 +3773,7 @@ fn lint_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     None => vec![],
   };
   let rules = matches.get_flag("rules");
+  let fix = matches.get_flag("fix");
   let maybe_rules_tags = matches
     .remove_many::<String>("rules-tags")
     .map(|f| f.collect());
@@ -2792,6 +3794,7 @@ fn lint_parse(flags: &mut Flags, matches: &mut ArgMatches) {
       ignore,
     },
     rules,
+    fix,
     maybe_rules_tags,
     maybe_rules_include,
     maybe_rules_exclude,
@@ -2801,6 +3804,14 @@ fn lint_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   });
 }
 
+fn permissions_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  config_args_parse(flags, matches);
+  flags.subcommand = DenoSubcommand::Permissions(PermissionsFlags {
+    script: matches.remove_one::<String>("script_arg"),
+    clear: matches.get_flag("clear"),
+  });
+}
+
 fn repl_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   runtime_args_parse(flags, matches, true, true);
   unsafely_ignore_certificate_errors_parse(flags, matches);
@@ -2828,6 +3839,7 @@ fn run_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.argv.extend(script_arg);
 
   ext_arg_parse(flags, matches);
+  env_file_arg_parse(flags, matches);
 
   watch_arg_parse(flags, matches, true);
   flags.subcommand = DenoSubcommand::Run(RunFlags { script });
@@ -2838,10 +3850,13 @@ fn task_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     .remove_one::<String>("config")
     .map(ConfigFlag::Path)
     .unwrap_or(ConfigFlag::Discover);
+  env_file_arg_parse(flags, matches);
+  permission_set_arg_parse(flags, matches);
 
   let mut task_flags = TaskFlags {
     cwd: matches.remove_one::<String>("cwd"),
     task: None,
+    parallel: matches.get_flag("parallel"),
   };
 
   if let Some((task, mut matches)) = matches.remove_subcommand() {
@@ -2876,6 +3891,18 @@ fn test_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   let doc = matches.get_flag("doc");
   let allow_none = matches.get_flag("allow-none");
   let filter = matches.remove_one::<String>("filter");
+  let retries = matches.remove_one::<usize>("retries").unwrap_or(0);
+  let update_snapshots = matches.get_flag("update-snapshots");
+  let rerun_all = matches.get_flag("rerun-all");
+  let slow_threshold = matches.remove_one::<Duration>("slow-threshold");
+  let tags = match matches.remove_many::<String>("tags") {
+    Some(tags) => tags.collect(),
+    None => vec![],
+  };
+  let skip_tags = match matches.remove_many::<String>("skip-tags") {
+    Some(tags) => tags.collect(),
+    None => vec![],
+  };
 
   let fail_fast = if matches.contains_id("fail-fast") {
     Some(
@@ -2897,6 +3924,10 @@ fn test_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     None
   };
 
+  let shard = matches.remove_one::<TestShard>("shard");
+
+  let reporters = test_reporters_parse(matches);
+
   if let Some(script_arg) = matches.remove_many::<String>("script_arg") {
     flags.argv.extend(script_arg);
   }
@@ -2937,15 +3968,60 @@ fn test_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     no_run,
     doc,
     fail_fast,
+    retries,
+    update_snapshots,
+    rerun_all,
+    slow_threshold,
     files: FileFlags { include, ignore },
     filter,
+    tags,
+    skip_tags,
     shuffle,
+    shard,
     allow_none,
     concurrent_jobs,
     trace_ops,
+    reporters,
   });
 }
 
+/// Pairs each `--reporter-output` flag with the `--reporter` flag before it.
+fn test_reporters_parse(matches: &mut ArgMatches) -> Vec<TestReporterConfig> {
+  let reporter_indices = matches
+    .indices_of("reporter")
+    .map(|indices| indices.collect::<Vec<_>>())
+    .unwrap_or_default();
+  let output_indices = matches
+    .indices_of("reporter-output")
+    .map(|indices| indices.collect::<Vec<_>>())
+    .unwrap_or_default();
+  let mut reporters = match matches.remove_many::<String>("reporter") {
+    Some(reporters) => reporters
+      .map(|reporter| TestReporterConfig {
+        kind: match reporter.as_str() {
+          "pretty" => TestReporterKind::Pretty,
+          "junit" => TestReporterKind::Junit,
+          "tap" => TestReporterKind::Tap,
+          _ => unreachable!(),
+        },
+        output: None,
+      })
+      .collect::<Vec<_>>(),
+    None => return vec![],
+  };
+  if let Some(outputs) = matches.remove_many::<PathBuf>("reporter-output") {
+    for (output, output_index) in outputs.zip(output_indices) {
+      // An output that is given before any reporter is the first one's.
+      let position = reporter_indices
+        .iter()
+        .rposition(|index| *index < output_index)
+        .unwrap_or(0);
+      reporters[position].output = Some(output);
+    }
+  }
+  reporters
+}
+
 fn types_parse(flags: &mut Flags, _matches: &mut ArgMatches) {
   flags.subcommand = DenoSubcommand::Types;
 }
@@ -3034,6 +4110,11 @@ fn permission_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     debug!("sys info allowlist: {:#?}", &flags.allow_sys);
   }
 
+  if let Some(vsock_wl) = matches.remove_many::<String>("allow-vsock") {
+    flags.allow_vsock = Some(vsock_wl.collect());
+    debug!("vsock allowlist: {:#?}", &flags.allow_vsock);
+  }
+
   if let Some(ffi_wl) = matches.remove_many::<PathBuf>("allow-ffi") {
     flags.allow_ffi = Some(ffi_wl.collect());
     debug!("ffi allowlist: {:#?}", &flags.allow_ffi);
@@ -3042,6 +4123,52 @@ fn permission_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   if matches.get_flag("allow-hrtime") {
     flags.allow_hrtime = true;
   }
+
+  if let Some(read_wl) = matches.remove_many::<PathBuf>("deny-read") {
+    flags.deny_read = Some(read_wl.collect());
+  }
+
+  if let Some(write_wl) = matches.remove_many::<PathBuf>("deny-write") {
+    flags.deny_write = Some(write_wl.collect());
+  }
+
+  if let Some(net_wl) = matches.remove_many::<String>("deny-net") {
+    let net_denylist = flags_allow_net::parse(net_wl.collect()).unwrap();
+    flags.deny_net = Some(net_denylist);
+  }
+
+  if let Some(env_wl) = matches.remove_many::<String>("deny-env") {
+    flags.deny_env = Some(env_wl.collect());
+    debug!("env denylist: {:#?}", &flags.deny_env);
+  }
+
+  if let Some(run_wl) = matches.remove_many::<String>("deny-run") {
+    flags.deny_run = Some(run_wl.collect());
+    debug!("run denylist: {:#?}", &flags.deny_run);
+  }
+
+  if let Some(sys_wl) = matches.remove_many::<String>("deny-sys") {
+    flags.deny_sys = Some(sys_wl.collect());
+    debug!("sys info denylist: {:#?}", &flags.deny_sys);
+  }
+
+  if let Some(vsock_wl) = matches.remove_many::<String>("deny-vsock") {
+    flags.deny_vsock = Some(vsock_wl.collect());
+    debug!("vsock denylist: {:#?}", &flags.deny_vsock);
+  }
+
+  if let Some(ffi_wl) = matches.remove_many::<PathBuf>("deny-ffi") {
+    flags.deny_ffi = Some(ffi_wl.collect());
+    debug!("ffi denylist: {:#?}", &flags.deny_ffi);
+  }
+
+  if matches.get_flag("deny-hrtime") {
+    flags.deny_hrtime = true;
+  }
+
+  permission_set_arg_parse(flags, matches);
+  flags.permission_audit = matches.remove_one::<PathBuf>("permission-audit");
+
   if matches.get_flag("allow-all") {
     flags.allow_all = true;
     flags.allow_read = Some(vec![]);
@@ -3050,6 +4177,7 @@ fn permission_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     flags.allow_run = Some(vec![]);
     flags.allow_write = Some(vec![]);
     flags.allow_sys = Some(vec![]);
+    flags.allow_vsock = Some(vec![]);
     flags.allow_ffi = Some(vec![]);
     flags.allow_hrtime = true;
   }
@@ -3120,6 +4248,10 @@ fn inspect_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   };
 }
 
+fn permission_set_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.permission_set = matches.remove_one::<String>("permission-set");
+}
+
 fn import_map_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.import_map_path = matches.remove_one::<String>("import-map");
 }
@@ -3156,6 +4288,10 @@ fn cached_only_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   }
 }
 
+fn env_file_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.env_file = matches.remove_one::<String>("env-file");
+}
+
 fn ext_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.ext = matches.remove_one::<String>("ext");
 }
@@ -3546,6 +4682,7 @@ mod tests {
         allow_run: Some(vec![]),
         allow_read: Some(vec![]),
         allow_sys: Some(vec![]),
+        allow_vsock: Some(vec![]),
         allow_write: Some(vec![]),
         allow_ffi: Some(vec![]),
         allow_hrtime: true,
@@ -3570,49 +4707,174 @@ mod tests {
   }
 
   #[test]
-  fn allow_hrtime() {
-    let r = flags_from_vec(svec!["deno", "run", "--allow-hrtime", "gist.ts"]);
+  fn permission_set() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--permission-set=ci",
+      "--allow-env",
+      "gist.ts"
+    ]);
+    let flags = r.unwrap();
     assert_eq!(
-      r.unwrap(),
+      flags,
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "gist.ts".to_string(),
         }),
-        allow_hrtime: true,
+        permission_set: Some("ci".to_string()),
+        allow_env: Some(vec![]),
+        ..Flags::default()
+      }
+    );
+    assert_eq!(
+      flags.to_permission_args(),
+      svec!["--permission-set=ci", "--allow-env"]
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "task", "--permission-set=ci", "build"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
+          parallel: false,
+        }),
+        permission_set: Some("ci".to_string()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn double_hyphen() {
-    // notice that flags passed after double dash will not
-    // be parsed to Flags but instead forwarded to
-    // script args as Deno.args
+  fn permission_audit() {
     let r = flags_from_vec(svec![
       "deno",
       "run",
-      "--allow-write",
-      "script.ts",
-      "--",
-      "-D",
-      "--allow-net"
+      "--permission-audit=audit.jsonl",
+      "--allow-read",
+      "gist.ts"
     ]);
+    let flags = r.unwrap();
     assert_eq!(
-      r.unwrap(),
+      flags,
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags {
-          script: "script.ts".to_string(),
+          script: "gist.ts".to_string(),
         }),
-        argv: svec!["--", "-D", "--allow-net"],
-        allow_write: Some(vec![]),
+        permission_audit: Some(PathBuf::from("audit.jsonl")),
+        allow_read: Some(vec![]),
         ..Flags::default()
       }
     );
+    assert_eq!(
+      flags.to_permission_args(),
+      svec!["--permission-audit=audit.jsonl", "--allow-read"]
+    );
   }
 
   #[test]
-  fn fmt() {
+  fn deny_flags() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--allow-all",
+      "--deny-read=/etc",
+      "--deny-net=metadata.internal,:8080",
+      "--deny-env=SECRET",
+      "--deny-run",
+      "--deny-hrtime",
+      "gist.ts"
+    ]);
+    let flags = r.unwrap();
+    assert_eq!(
+      flags,
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "gist.ts".to_string(),
+        }),
+        allow_all: true,
+        allow_read: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_net: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_vsock: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        deny_read: Some(vec![PathBuf::from("/etc")]),
+        deny_net: Some(svec![
+          "metadata.internal",
+          "0.0.0.0:8080",
+          "127.0.0.1:8080",
+          "localhost:8080"
+        ]),
+        deny_env: Some(svec!["SECRET"]),
+        deny_run: Some(vec![]),
+        deny_hrtime: true,
+        ..Flags::default()
+      }
+    );
+    assert_eq!(
+      flags.to_permission_args(),
+      svec![
+        "--allow-all",
+        "--deny-read=/etc",
+        "--deny-net=metadata.internal,0.0.0.0:8080,127.0.0.1:8080,localhost:8080",
+        "--deny-env=SECRET",
+        "--deny-run",
+        "--deny-hrtime"
+      ]
+    );
+  }
+
+  #[test]
+  fn allow_hrtime() {
+    let r = flags_from_vec(svec!["deno", "run", "--allow-hrtime", "gist.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "gist.ts".to_string(),
+        }),
+        allow_hrtime: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn double_hyphen() {
+    // notice that flags passed after double dash will not
+    // be parsed to Flags but instead forwarded to
+    // script args as Deno.args
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--allow-write",
+      "script.ts",
+      "--",
+      "-D",
+      "--allow-net"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        argv: svec!["--", "-D", "--allow-net"],
+        allow_write: Some(vec![]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn fmt() {
     let r = flags_from_vec(svec!["deno", "fmt", "script_1.ts", "script_2.ts"]);
     assert_eq!(
       r.unwrap(),
@@ -3878,6 +5140,33 @@ mod tests {
     );
   }
 
+  #[test]
+  fn lint_fix() {
+    let r = flags_from_vec(svec!["deno", "lint", "--fix", "script_1.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![PathBuf::from("script_1.ts")],
+            ignore: vec![],
+          },
+          rules: false,
+          fix: true,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "lint", "--fix", "--rules"]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn lint() {
     let r = flags_from_vec(svec!["deno", "lint", "script_1.ts", "script_2.ts"]);
@@ -3893,6 +5182,7 @@ mod tests {
             ignore: vec![],
           },
           rules: false,
+          fix: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
@@ -3922,6 +5212,7 @@ mod tests {
             ignore: vec![],
           },
           rules: false,
+          fix: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
@@ -3953,6 +5244,7 @@ mod tests {
             ignore: vec![],
           },
           rules: false,
+          fix: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
@@ -3979,6 +5271,7 @@ mod tests {
             ],
           },
           rules: false,
+          fix: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
@@ -3999,6 +5292,7 @@ mod tests {
             ignore: vec![],
           },
           rules: true,
+          fix: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
@@ -4025,6 +5319,7 @@ mod tests {
             ignore: vec![],
           },
           rules: false,
+          fix: false,
           maybe_rules_tags: Some(svec![""]),
           maybe_rules_include: Some(svec!["ban-untagged-todo", "no-undef"]),
           maybe_rules_exclude: Some(svec!["no-const-assign"]),
@@ -4045,6 +5340,7 @@ mod tests {
             ignore: vec![],
           },
           rules: false,
+          fix: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
@@ -4072,6 +5368,7 @@ mod tests {
             ignore: vec![],
           },
           rules: false,
+          fix: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
@@ -4100,6 +5397,7 @@ mod tests {
             ignore: vec![],
           },
           rules: false,
+          fix: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
           maybe_rules_exclude: None,
@@ -4187,6 +5485,7 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Info(InfoFlags {
           json: false,
+          why: None,
           file: Some("script.ts".to_string()),
         }),
         ..Flags::default()
@@ -4199,6 +5498,7 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Info(InfoFlags {
           json: false,
+          why: None,
           file: Some("script.ts".to_string()),
         }),
         reload: true,
@@ -4212,18 +5512,42 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Info(InfoFlags {
           json: true,
+          why: None,
+          file: Some("script.ts".to_string()),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "info",
+      "--json",
+      "--why=npm:chalk",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: true,
+          why: Some("npm:chalk".to_string()),
           file: Some("script.ts".to_string()),
         }),
         ..Flags::default()
       }
     );
 
+    let r = flags_from_vec(svec!["deno", "info", "--why=npm:chalk"]);
+    assert!(r.is_err());
+
     let r = flags_from_vec(svec!["deno", "info"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Info(InfoFlags {
           json: false,
+          why: None,
           file: None
         }),
         ..Flags::default()
@@ -4236,6 +5560,7 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Info(InfoFlags {
           json: true,
+          why: None,
           file: None
         }),
         ..Flags::default()
@@ -4255,6 +5580,7 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Info(InfoFlags {
           json: false,
+          why: None,
           file: None
         }),
         config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
@@ -4296,6 +5622,7 @@ mod tests {
         allow_run: Some(vec![]),
         allow_read: Some(vec![]),
         allow_sys: Some(vec![]),
+        allow_vsock: Some(vec![]),
         allow_write: Some(vec![]),
         allow_ffi: Some(vec![]),
         allow_hrtime: true,
@@ -4319,6 +5646,7 @@ mod tests {
         allow_run: Some(vec![]),
         allow_read: Some(vec![]),
         allow_sys: Some(vec![]),
+        allow_vsock: Some(vec![]),
         allow_write: Some(vec![]),
         allow_ffi: Some(vec![]),
         allow_hrtime: true,
@@ -4343,6 +5671,7 @@ mod tests {
         allow_run: Some(vec![]),
         allow_read: Some(vec![]),
         allow_sys: Some(vec![]),
+        allow_vsock: Some(vec![]),
         allow_write: Some(vec![]),
         allow_ffi: Some(vec![]),
         allow_hrtime: true,
@@ -4381,6 +5710,7 @@ mod tests {
         allow_run: Some(vec![]),
         allow_read: Some(vec![]),
         allow_sys: Some(vec![]),
+        allow_vsock: Some(vec![]),
         allow_write: Some(vec![]),
         allow_ffi: Some(vec![]),
         allow_hrtime: true,
@@ -4411,6 +5741,7 @@ mod tests {
         allow_run: Some(vec![]),
         allow_read: Some(vec![]),
         allow_sys: Some(vec![]),
+        allow_vsock: Some(vec![]),
         allow_write: Some(vec![]),
         allow_ffi: Some(vec![]),
         allow_hrtime: true,
@@ -4419,6 +5750,40 @@ mod tests {
     );
   }
 
+  #[test]
+  fn permissions() {
+    let r = flags_from_vec(svec!["deno", "permissions"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Permissions(PermissionsFlags {
+          script: None,
+          clear: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "permissions",
+      "--clear",
+      "--config=deno.json",
+      "main.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Permissions(PermissionsFlags {
+          script: Some("main.ts".to_string()),
+          clear: true,
+        }),
+        config_flag: ConfigFlag::Path("deno.json".to_string()),
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn repl() {
     let r = flags_from_vec(svec!["deno"]);
@@ -4436,6 +5801,7 @@ mod tests {
         allow_run: Some(vec![]),
         allow_read: Some(vec![]),
         allow_sys: Some(vec![]),
+        allow_vsock: Some(vec![]),
         allow_write: Some(vec![]),
         allow_ffi: Some(vec![]),
         allow_hrtime: true,
@@ -4475,6 +5841,7 @@ mod tests {
         allow_run: Some(vec![]),
         allow_read: Some(vec![]),
         allow_sys: Some(vec![]),
+        allow_vsock: Some(vec![]),
         allow_write: Some(vec![]),
         allow_ffi: Some(vec![]),
         allow_hrtime: true,
@@ -4716,6 +6083,31 @@ mod tests {
     ]);
     assert!(r.is_err());
   }
+  #[test]
+  fn allow_vsock_allowlist() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--allow-vsock=2,3:1024",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        allow_vsock: Some(svec!["2", "3:1024"]),
+        ..Flags::default()
+      }
+    );
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-vsock=foo", "script.ts"]);
+    assert!(r.is_err());
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-vsock=3:", "script.ts"]);
+    assert!(r.is_err());
+  }
 
   #[test]
   fn reload_validator() {
@@ -4798,6 +6190,9 @@ mod tests {
         subcommand: DenoSubcommand::Bundle(BundleFlags {
           source_file: "source.ts".to_string(),
           out_file: None,
+          external: vec![],
+          source_map: None,
+          minify: false,
         }),
         type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
@@ -4822,6 +6217,9 @@ mod tests {
         subcommand: DenoSubcommand::Bundle(BundleFlags {
           source_file: "source.ts".to_string(),
           out_file: Some(PathBuf::from("bundle.js")),
+          external: vec![],
+          source_map: None,
+          minify: false,
         }),
         allow_write: Some(vec![]),
         no_remote: true,
@@ -4841,6 +6239,9 @@ mod tests {
         subcommand: DenoSubcommand::Bundle(BundleFlags {
           source_file: "source.ts".to_string(),
           out_file: Some(PathBuf::from("bundle.js")),
+          external: vec![],
+          source_map: None,
+          minify: false,
         }),
         type_check_mode: TypeCheckMode::Local,
         allow_write: Some(vec![]),
@@ -4864,6 +6265,9 @@ mod tests {
         subcommand: DenoSubcommand::Bundle(BundleFlags {
           source_file: "source.ts".to_string(),
           out_file: None,
+          external: vec![],
+          source_map: None,
+          minify: false,
         }),
         type_check_mode: TypeCheckMode::Local,
         lock_write: true,
@@ -4883,6 +6287,9 @@ mod tests {
         subcommand: DenoSubcommand::Bundle(BundleFlags {
           source_file: "source.ts".to_string(),
           out_file: None,
+          external: vec![],
+          source_map: None,
+          minify: false,
         }),
         type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
@@ -4900,6 +6307,9 @@ mod tests {
         subcommand: DenoSubcommand::Bundle(BundleFlags {
           source_file: "script.ts".to_string(),
           out_file: None,
+          external: vec![],
+          source_map: None,
+          minify: false,
         }),
         type_check_mode: TypeCheckMode::None,
         ..Flags::default()
@@ -4916,6 +6326,9 @@ mod tests {
         subcommand: DenoSubcommand::Bundle(BundleFlags {
           source_file: "source.ts".to_string(),
           out_file: None,
+          external: vec![],
+          source_map: None,
+          minify: false,
         }),
         type_check_mode: TypeCheckMode::Local,
         watch: Some(vec![]),
@@ -4939,6 +6352,9 @@ mod tests {
         subcommand: DenoSubcommand::Bundle(BundleFlags {
           source_file: "source.ts".to_string(),
           out_file: None,
+          external: vec![],
+          source_map: None,
+          minify: false,
         }),
         type_check_mode: TypeCheckMode::Local,
         watch: Some(vec![]),
@@ -4948,6 +6364,57 @@ mod tests {
     )
   }
 
+  #[test]
+  fn bundle_with_emit_options() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "bundle",
+      "--external=react",
+      "--external",
+      "npm:*",
+      "--source-map",
+      "--minify",
+      "source.ts",
+      "bundle.js"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: Some(PathBuf::from("bundle.js")),
+          external: svec!["react", "npm:*"],
+          source_map: Some(BundleSourceMapFlag::External),
+          minify: true,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        allow_write: Some(vec![]),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "bundle",
+      "--source-map=inline",
+      "source.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: None,
+          external: vec![],
+          source_map: Some(BundleSourceMapFlag::Inline),
+          minify: false,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn run_import_map() {
     let r = flags_from_vec(svec![
@@ -4982,6 +6449,7 @@ mod tests {
         subcommand: DenoSubcommand::Info(InfoFlags {
           file: Some("script.ts".to_string()),
           json: false,
+          why: None,
         }),
         import_map_path: Some("import_map.json".to_owned()),
         ..Flags::default()
@@ -5021,9 +6489,11 @@ mod tests {
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Doc(DocFlags {
+          lint: false,
           source_file: DocSourceFileFlag::Path("script.ts".to_owned()),
           private: false,
           json: false,
+          html: None,
           filter: None,
         }),
         import_map_path: Some("import_map.json".to_owned()),
@@ -5063,6 +6533,34 @@ mod tests {
     );
   }
 
+  #[test]
+  fn run_env_file() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--env-file=.env.dev", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        env_file: Some(".env.dev".to_string()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "run", "--env-file", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        env_file: Some(".env".to_string()),
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn run_seed_with_v8_flags() {
     let r = flags_from_vec(svec![
@@ -5681,15 +7179,23 @@ mod tests {
           no_run: true,
           doc: false,
           fail_fast: None,
+          retries: 0,
+          update_snapshots: false,
+          rerun_all: false,
+          slow_threshold: None,
           filter: Some("- foo".to_string()),
+          tags: vec![],
+          skip_tags: vec![],
           allow_none: true,
           files: FileFlags {
             include: vec![PathBuf::from("dir1/"), PathBuf::from("dir2/")],
             ignore: vec![],
           },
           shuffle: None,
+          shard: None,
           concurrent_jobs: None,
           trace_ops: true,
+          reporters: vec![],
         }),
         unstable: true,
         no_prompt: true,
@@ -5756,15 +7262,23 @@ mod tests {
           no_run: false,
           doc: false,
           fail_fast: None,
+          retries: 0,
+          update_snapshots: false,
+          rerun_all: false,
+          slow_threshold: None,
           filter: None,
+          tags: vec![],
+          skip_tags: vec![],
           allow_none: false,
           shuffle: None,
+          shard: None,
           files: FileFlags {
             include: vec![],
             ignore: vec![],
           },
           concurrent_jobs: Some(NonZeroUsize::new(4).unwrap()),
           trace_ops: false,
+          reporters: vec![],
         }),
         type_check_mode: TypeCheckMode::Local,
         no_prompt: true,
@@ -5786,15 +7300,23 @@ mod tests {
           no_run: false,
           doc: false,
           fail_fast: Some(NonZeroUsize::new(3).unwrap()),
+          retries: 0,
+          update_snapshots: false,
+          rerun_all: false,
+          slow_threshold: None,
           filter: None,
+          tags: vec![],
+          skip_tags: vec![],
           allow_none: false,
           shuffle: None,
+          shard: None,
           files: FileFlags {
             include: vec![],
             ignore: vec![],
           },
           concurrent_jobs: None,
           trace_ops: false,
+          reporters: vec![],
         }),
         type_check_mode: TypeCheckMode::Local,
         no_prompt: true,
@@ -5820,15 +7342,23 @@ mod tests {
           no_run: false,
           doc: false,
           fail_fast: None,
+          retries: 0,
+          update_snapshots: false,
+          rerun_all: false,
+          slow_threshold: None,
           filter: None,
+          tags: vec![],
+          skip_tags: vec![],
           allow_none: false,
           shuffle: None,
+          shard: None,
           files: FileFlags {
             include: vec![],
             ignore: vec![],
           },
           concurrent_jobs: None,
           trace_ops: false,
+          reporters: vec![],
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -5848,15 +7378,23 @@ mod tests {
           no_run: false,
           doc: false,
           fail_fast: None,
+          retries: 0,
+          update_snapshots: false,
+          rerun_all: false,
+          slow_threshold: None,
           filter: None,
+          tags: vec![],
+          skip_tags: vec![],
           allow_none: false,
           shuffle: Some(1),
+          shard: None,
           files: FileFlags {
             include: vec![],
             ignore: vec![],
           },
           concurrent_jobs: None,
           trace_ops: false,
+          reporters: vec![],
         }),
         no_prompt: true,
         watch: None,
@@ -5867,8 +7405,8 @@ mod tests {
   }
 
   #[test]
-  fn test_watch() {
-    let r = flags_from_vec(svec!["deno", "test", "--watch"]);
+  fn test_shard() {
+    let r = flags_from_vec(svec!["deno", "test", "--shard=2/5"]);
     assert_eq!(
       r.unwrap(),
       Flags {
@@ -5876,26 +7414,43 @@ mod tests {
           no_run: false,
           doc: false,
           fail_fast: None,
+          retries: 0,
+          update_snapshots: false,
+          rerun_all: false,
+          slow_threshold: None,
           filter: None,
+          tags: vec![],
+          skip_tags: vec![],
           allow_none: false,
           shuffle: None,
+          shard: Some(TestShard {
+            index: NonZeroUsize::new(2).unwrap(),
+            count: NonZeroUsize::new(5).unwrap(),
+          }),
           files: FileFlags {
             include: vec![],
             ignore: vec![],
           },
           concurrent_jobs: None,
           trace_ops: false,
+          reporters: vec![],
         }),
         no_prompt: true,
+        watch: None,
         type_check_mode: TypeCheckMode::Local,
-        watch: Some(vec![]),
         ..Flags::default()
       }
     );
+
+    for shard in ["0/5", "6/5", "2", "2/0", "a/b"] {
+      let r = flags_from_vec(svec!["deno", "test", format!("--shard={shard}")]);
+      assert!(r.is_err(), "{shard}");
+    }
   }
+
   #[test]
-  fn test_watch_explicit_cwd() {
-    let r = flags_from_vec(svec!["deno", "test", "--watch", "./"]);
+  fn test_slow_threshold() {
+    let r = flags_from_vec(svec!["deno", "test", "--slow-threshold=500ms"]);
     assert_eq!(
       r.unwrap(),
       Flags {
@@ -5903,28 +7458,52 @@ mod tests {
           no_run: false,
           doc: false,
           fail_fast: None,
+          retries: 0,
+          update_snapshots: false,
+          rerun_all: false,
+          slow_threshold: Some(Duration::from_millis(500)),
           filter: None,
+          tags: vec![],
+          skip_tags: vec![],
           allow_none: false,
           shuffle: None,
+          shard: None,
           files: FileFlags {
-            include: vec![PathBuf::from("./")],
+            include: vec![],
             ignore: vec![],
           },
           concurrent_jobs: None,
           trace_ops: false,
+          reporters: vec![],
         }),
         no_prompt: true,
+        watch: None,
         type_check_mode: TypeCheckMode::Local,
-        watch: Some(vec![]),
         ..Flags::default()
       }
     );
+
+    assert_eq!(duration_parser("2s"), Ok(Duration::from_secs(2)));
+    assert_eq!(duration_parser("1m"), Ok(Duration::from_secs(60)));
+    assert_eq!(duration_parser("250"), Ok(Duration::from_millis(250)));
+    for threshold in ["", "ms", "1.5s", "-1s", "1h"] {
+      let r = flags_from_vec(svec![
+        "deno",
+        "test",
+        format!("--slow-threshold={threshold}")
+      ]);
+      assert!(r.is_err(), "{threshold}");
+    }
   }
 
   #[test]
-  fn test_watch_with_no_clear_screen() {
-    let r =
-      flags_from_vec(svec!["deno", "test", "--watch", "--no-clear-screen"]);
+  fn test_tags() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "test",
+      "--tags=integration,slow",
+      "--skip-tags=flaky"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
@@ -5932,40 +7511,335 @@ mod tests {
           no_run: false,
           doc: false,
           fail_fast: None,
+          retries: 0,
+          update_snapshots: false,
+          rerun_all: false,
+          slow_threshold: None,
           filter: None,
+          tags: svec!["integration", "slow"],
+          skip_tags: svec!["flaky"],
           allow_none: false,
           shuffle: None,
+          shard: None,
           files: FileFlags {
             include: vec![],
             ignore: vec![],
           },
           concurrent_jobs: None,
           trace_ops: false,
+          reporters: vec![],
         }),
-        watch: Some(vec![]),
-        type_check_mode: TypeCheckMode::Local,
-        no_clear_screen: true,
         no_prompt: true,
+        watch: None,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn bundle_with_cafile() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "bundle",
-      "--cert",
-      "example.crt",
-      "source.ts"
-    ]);
+  fn test_retries() {
+    let r = flags_from_vec(svec!["deno", "test", "--retries=2"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          doc: false,
+          fail_fast: None,
+          retries: 2,
+          update_snapshots: false,
+          rerun_all: false,
+          slow_threshold: None,
+          filter: None,
+          tags: vec![],
+          skip_tags: vec![],
+          allow_none: false,
+          shuffle: None,
+          shard: None,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          concurrent_jobs: None,
+          trace_ops: false,
+          reporters: vec![],
+        }),
+        no_prompt: true,
+        watch: None,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "test", "--retries=-1"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn test_update_snapshots() {
+    let r = flags_from_vec(svec!["deno", "test", "--update-snapshots"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          doc: false,
+          fail_fast: None,
+          retries: 0,
+          update_snapshots: true,
+          rerun_all: false,
+          slow_threshold: None,
+          filter: None,
+          tags: vec![],
+          skip_tags: vec![],
+          allow_none: false,
+          shuffle: None,
+          shard: None,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          concurrent_jobs: None,
+          trace_ops: false,
+          reporters: vec![],
+        }),
+        no_prompt: true,
+        watch: None,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn test_reporter() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "test",
+      "--reporter=pretty",
+      "--reporter=junit",
+      "--reporter-output=report.xml",
+      "--reporter=tap",
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          doc: false,
+          fail_fast: None,
+          retries: 0,
+          update_snapshots: false,
+          rerun_all: false,
+          slow_threshold: None,
+          filter: None,
+          tags: vec![],
+          skip_tags: vec![],
+          allow_none: false,
+          shuffle: None,
+          shard: None,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          concurrent_jobs: None,
+          trace_ops: false,
+          reporters: vec![
+            TestReporterConfig {
+              kind: TestReporterKind::Pretty,
+              output: None,
+            },
+            TestReporterConfig {
+              kind: TestReporterKind::Junit,
+              output: Some(PathBuf::from("report.xml")),
+            },
+            TestReporterConfig {
+              kind: TestReporterKind::Tap,
+              output: None,
+            },
+          ],
+        }),
+        no_prompt: true,
+        watch: None,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=xml"]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec!["deno", "test", "--reporter-output=a.xml"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn test_watch() {
+    let r = flags_from_vec(svec!["deno", "test", "--watch"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          doc: false,
+          fail_fast: None,
+          retries: 0,
+          update_snapshots: false,
+          rerun_all: false,
+          slow_threshold: None,
+          filter: None,
+          tags: vec![],
+          skip_tags: vec![],
+          allow_none: false,
+          shuffle: None,
+          shard: None,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          concurrent_jobs: None,
+          trace_ops: false,
+          reporters: vec![],
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        watch: Some(vec![]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn test_watch_rerun_all() {
+    let r = flags_from_vec(svec!["deno", "test", "--watch", "--rerun-all"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          doc: false,
+          fail_fast: None,
+          retries: 0,
+          update_snapshots: false,
+          rerun_all: true,
+          slow_threshold: None,
+          filter: None,
+          tags: vec![],
+          skip_tags: vec![],
+          allow_none: false,
+          shuffle: None,
+          shard: None,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          concurrent_jobs: None,
+          trace_ops: false,
+          reporters: vec![],
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        watch: Some(vec![]),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "test", "--rerun-all"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn test_watch_explicit_cwd() {
+    let r = flags_from_vec(svec!["deno", "test", "--watch", "./"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          doc: false,
+          fail_fast: None,
+          retries: 0,
+          update_snapshots: false,
+          rerun_all: false,
+          slow_threshold: None,
+          filter: None,
+          tags: vec![],
+          skip_tags: vec![],
+          allow_none: false,
+          shuffle: None,
+          shard: None,
+          files: FileFlags {
+            include: vec![PathBuf::from("./")],
+            ignore: vec![],
+          },
+          concurrent_jobs: None,
+          trace_ops: false,
+          reporters: vec![],
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        watch: Some(vec![]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn test_watch_with_no_clear_screen() {
+    let r =
+      flags_from_vec(svec!["deno", "test", "--watch", "--no-clear-screen"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          doc: false,
+          fail_fast: None,
+          retries: 0,
+          update_snapshots: false,
+          rerun_all: false,
+          slow_threshold: None,
+          filter: None,
+          tags: vec![],
+          skip_tags: vec![],
+          allow_none: false,
+          shuffle: None,
+          shard: None,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          concurrent_jobs: None,
+          trace_ops: false,
+          reporters: vec![],
+        }),
+        watch: Some(vec![]),
+        type_check_mode: TypeCheckMode::Local,
+        no_clear_screen: true,
+        no_prompt: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn bundle_with_cafile() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "bundle",
+      "--cert",
+      "example.crt",
+      "source.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
           source_file: "source.ts".to_string(),
           out_file: None,
+          external: vec![],
+          source_map: None,
+          minify: false,
         }),
         type_check_mode: TypeCheckMode::Local,
         ca_data: Some(CaData::File("example.crt".to_owned())),
@@ -6029,6 +7903,7 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Info(InfoFlags {
           json: false,
+          why: None,
           file: Some("https://example.com".to_string()),
         }),
         ca_data: Some(CaData::File("example.crt".to_owned())),
@@ -6046,6 +7921,8 @@ mod tests {
         subcommand: DenoSubcommand::Doc(DocFlags {
           private: false,
           json: true,
+          html: None,
+          lint: false,
           source_file: DocSourceFileFlag::Path("path/to/module.ts".to_string()),
           filter: None,
         }),
@@ -6065,6 +7942,8 @@ mod tests {
         subcommand: DenoSubcommand::Doc(DocFlags {
           private: false,
           json: false,
+          html: None,
+          lint: false,
           source_file: DocSourceFileFlag::Path("path/to/module.ts".to_string()),
           filter: Some("SomeClass.someField".to_string()),
         }),
@@ -6079,6 +7958,8 @@ mod tests {
         subcommand: DenoSubcommand::Doc(DocFlags {
           private: false,
           json: false,
+          html: None,
+          lint: false,
           source_file: Default::default(),
           filter: None,
         }),
@@ -6093,6 +7974,8 @@ mod tests {
         subcommand: DenoSubcommand::Doc(DocFlags {
           private: false,
           json: false,
+          html: None,
+          lint: false,
           source_file: DocSourceFileFlag::Builtin,
           filter: Some("Deno.Listener".to_string()),
         }),
@@ -6114,6 +7997,8 @@ mod tests {
         subcommand: DenoSubcommand::Doc(DocFlags {
           private: true,
           json: false,
+          html: None,
+          lint: false,
           source_file: DocSourceFileFlag::Path("path/to/module.js".to_string()),
           filter: None,
         }),
@@ -6122,6 +8007,76 @@ mod tests {
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "doc",
+      "--html",
+      "--name=My library",
+      "--output=site",
+      "path/to/module.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: false,
+          html: Some(DocHtmlFlag {
+            name: Some("My library".to_string()),
+            output: PathBuf::from("site"),
+          }),
+          lint: false,
+          source_file: DocSourceFileFlag::Path("path/to/module.ts".to_string()),
+          filter: None,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "doc", "--html", "path/to/module.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: false,
+          html: Some(DocHtmlFlag {
+            name: None,
+            output: PathBuf::from("./docs/"),
+          }),
+          lint: false,
+          source_file: DocSourceFileFlag::Path("path/to/module.ts".to_string()),
+          filter: None,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "doc", "--lint", "path/to/module.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: false,
+          html: None,
+          lint: true,
+          source_file: DocSourceFileFlag::Path("path/to/module.ts".to_string()),
+          filter: None,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "doc", "--lint", "--json", "mod.ts"]);
+    assert!(r.is_err());
+
+    let r = flags_from_vec(svec!["deno", "doc", "--output=site", "mod.ts"]);
+    assert!(r.is_err());
+
+    let r = flags_from_vec(svec!["deno", "doc", "--html", "--json", "mod.ts"]);
+    assert!(r.is_err());
   }
 
   #[test]
@@ -6186,7 +8141,74 @@ mod tests {
           output: None,
           args: vec![],
           target: None,
-          include: vec![]
+          include: vec![],
+          sign: None,
+          lite: false,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn compile_with_sign() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "compile",
+      "--target",
+      "x86_64-pc-windows-msvc",
+      "--sign",
+      "cert.pfx",
+      "--sign-timestamp-url",
+      "http://timestamp.digicert.com",
+      "main.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Compile(CompileFlags {
+          source_file: "main.ts".to_string(),
+          output: None,
+          args: vec![],
+          target: Some("x86_64-pc-windows-msvc".to_string()),
+          include: vec![],
+          sign: Some(CompileSignFlags {
+            identity: "cert.pfx".to_string(),
+            timestamp_url: Some("http://timestamp.digicert.com".to_string()),
+            notarize_command: None,
+          }),
+          lite: false,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "compile",
+      "--sign-notarize",
+      "notarize",
+      "main.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn compile_lite() {
+    let r = flags_from_vec(svec!["deno", "compile", "--lite", "main.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Compile(CompileFlags {
+          source_file: "main.ts".to_string(),
+          output: None,
+          args: vec![],
+          target: None,
+          include: vec![],
+          sign: None,
+          lite: true,
         }),
         type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
@@ -6206,7 +8228,9 @@ mod tests {
           output: Some(PathBuf::from("colors")),
           args: svec!["foo", "bar"],
           target: None,
-          include: vec![]
+          include: vec![],
+          sign: None,
+          lite: false,
         }),
         import_map_path: Some("import_map.json".to_string()),
         no_remote: true,
@@ -6243,6 +8267,9 @@ mod tests {
           include: vec![r"^file:".to_string()],
           exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
           lcov: false,
+          html: None,
+          check: false,
+          thresholds: Default::default(),
         }),
         ..Flags::default()
       }
@@ -6270,11 +8297,94 @@ mod tests {
           exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
           lcov: true,
           output: Some(PathBuf::from("foo.lcov")),
+          html: None,
+          check: false,
+          thresholds: Default::default(),
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn coverage_with_html() {
+    let r =
+      flags_from_vec(svec!["deno", "coverage", "--html=cov_html", "foo.json"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Coverage(CoverageFlags {
+          files: FileFlags {
+            include: vec![PathBuf::from("foo.json")],
+            ignore: vec![],
+          },
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          lcov: false,
+          output: None,
+          html: Some(PathBuf::from("cov_html")),
+          check: false,
+          thresholds: Default::default(),
         }),
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--lcov",
+      "--html=cov_html",
+      "foo.json"
+    ]);
+    assert!(r.is_err());
   }
+  #[test]
+  fn coverage_with_check() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--check",
+      "--lines=90",
+      "--branches=75",
+      "foo.json"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Coverage(CoverageFlags {
+          files: FileFlags {
+            include: vec![PathBuf::from("foo.json")],
+            ignore: vec![],
+          },
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          lcov: false,
+          output: None,
+          html: None,
+          check: true,
+          thresholds: CoverageThresholds {
+            lines: Some(90),
+            functions: None,
+            branches: Some(75),
+          },
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--lines=101",
+      "--check",
+      "foo"
+    ]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec!["deno", "coverage", "--lines=90", "foo"]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn location_with_bad_scheme() {
     #[rustfmt::skip]
@@ -6385,6 +8495,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: Some("build".to_string()),
+          parallel: false,
         }),
         argv: svec!["hello", "world"],
         ..Flags::default()
@@ -6398,6 +8509,34 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: Some("build".to_string()),
+          parallel: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "task", "--env-file", "build"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
+          parallel: false,
+        }),
+        env_file: Some(".env".to_string()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "task", "--parallel", "build"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
+          parallel: true,
         }),
         ..Flags::default()
       }
@@ -6410,6 +8549,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: Some("foo".to_string()),
           task: Some("build".to_string()),
+          parallel: false,
         }),
         ..Flags::default()
       }
@@ -6434,6 +8574,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: Some("build".to_string()),
+          parallel: false,
         }),
         argv: svec!["--", "hello", "world"],
         config_flag: ConfigFlag::Path("deno.json".to_owned()),
@@ -6450,6 +8591,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: Some("foo".to_string()),
           task: Some("build".to_string()),
+          parallel: false,
         }),
         argv: svec!["--", "hello", "world"],
         ..Flags::default()
@@ -6467,6 +8609,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: Some("build".to_string()),
+          parallel: false,
         }),
         argv: svec!["--"],
         ..Flags::default()
@@ -6483,6 +8626,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: Some("build".to_string()),
+          parallel: false,
         }),
         argv: svec!["-1", "--test"],
         ..Flags::default()
@@ -6499,6 +8643,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: Some("build".to_string()),
+          parallel: false,
         }),
         argv: svec!["--test"],
         ..Flags::default()
@@ -6517,6 +8662,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: Some("build".to_string()),
+          parallel: false,
         }),
         unstable: true,
         log_level: Some(log::Level::Error),
@@ -6534,6 +8680,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: None,
+          parallel: false,
         }),
         ..Flags::default()
       }
@@ -6549,6 +8696,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: None,
+          parallel: false,
         }),
         config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
         ..Flags::default()
@@ -6565,6 +8713,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: None,
+          parallel: false,
         }),
         config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
         ..Flags::default()
@@ -6609,6 +8758,7 @@ mod tests {
           filter: Some("- foo".to_string()),
           json: true,
           no_run: true,
+          compare: None,
           files: FileFlags {
             include: vec![PathBuf::from("dir1/"), PathBuf::from("dir2/")],
             ignore: vec![],
@@ -6637,6 +8787,7 @@ mod tests {
           filter: None,
           json: false,
           no_run: false,
+          compare: None,
           files: FileFlags {
             include: vec![],
             ignore: vec![],
@@ -6650,6 +8801,35 @@ mod tests {
     );
   }
 
+  #[test]
+  fn bench_compare() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "bench",
+      "--json",
+      "--compare=baseline.json",
+      "bench.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bench(BenchFlags {
+          filter: None,
+          json: true,
+          no_run: false,
+          compare: Some(PathBuf::from("baseline.json")),
+          files: FileFlags {
+            include: vec![PathBuf::from("bench.ts")],
+            ignore: vec![],
+          },
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn run_with_check() {
     let r = flags_from_vec(svec!["deno", "run", "--check", "script.ts",]);
@@ -26,8 +26,29 @@ impl FromStr for BarePort {
   }
 }
 
+/// Whether `s` is a range of IP addresses in CIDR notation, like
+/// "10.0.0.0/8" or "fd00::/8".
+fn is_ip_range(s: &str) -> bool {
+  let Some((addr, prefix_len)) = s.split_once('/') else {
+    return false;
+  };
+  let addr = addr
+    .strip_prefix('[')
+    .and_then(|addr| addr.strip_suffix(']'))
+    .unwrap_or(addr);
+  let max_prefix_len = match addr.parse::<IpAddr>() {
+    Ok(IpAddr::V4(_)) => 32,
+    Ok(IpAddr::V6(_)) => 128,
+    Err(_) => return false,
+  };
+  prefix_len
+    .parse::<u8>()
+    .map_or(false, |prefix_len| prefix_len <= max_prefix_len)
+}
+
 pub fn validator(host_and_port: &str) -> Result<String, String> {
-  if Url::parse(&format!("internal://{host_and_port}")).is_ok()
+  if is_ip_range(host_and_port)
+    || Url::parse(&format!("internal://{host_and_port}")).is_ok()
     || host_and_port.parse::<IpAddr>().is_ok()
     || host_and_port.parse::<BarePort>().is_ok()
   {
@@ -43,7 +64,8 @@ pub fn validator(host_and_port: &str) -> Result<String, String> {
 pub fn parse(paths: Vec<String>) -> clap::error::Result<Vec<String>> {
   let mut out: Vec<String> = vec![];
   for host_and_port in paths.iter() {
-    if Url::parse(&format!("internal://{host_and_port}")).is_ok()
+    if is_ip_range(host_and_port)
+      || Url::parse(&format!("internal://{host_and_port}")).is_ok()
       || host_and_port.parse::<IpAddr>().is_ok()
     {
       out.push(host_and_port.to_owned())
@@ -182,6 +204,22 @@ mod tests {
     assert_eq!(actual, expected);
   }
 
+  #[test]
+  fn parse_net_args_ip_ranges() {
+    let entries = svec!["10.0.0.0/8", "192.168.1.0/24", "fd00::/8", "[::]/0"];
+    let expected = svec!["10.0.0.0/8", "192.168.1.0/24", "fd00::/8", "[::]/0"];
+    let actual = parse(entries).unwrap();
+    assert_eq!(actual, expected);
+  }
+
+  #[test]
+  fn parse_net_args_wildcards() {
+    let entries = svec!["*.example.com", "*.deno.land:443"];
+    let expected = svec!["*.example.com", "*.deno.land:443"];
+    let actual = parse(entries).unwrap();
+    assert_eq!(actual, expected);
+  }
+
   #[test]
   fn parse_net_args_ipv6_error1() {
     let entries = svec![":::"];
@@ -6,7 +6,9 @@ mod flags_allow_net;
 mod import_map;
 mod lockfile;
 pub mod package_json;
+mod workspace;
 
+use self::import_map::import_map_from_value;
 pub use self::import_map::resolve_import_map_from_specifier;
 use self::lockfile::snapshot_from_lockfile;
 use self::package_json::PackageJsonDeps;
@@ -20,12 +22,18 @@ use indexmap::IndexMap;
 pub use config_file::BenchConfig;
 pub use config_file::CompilerOptions;
 pub use config_file::ConfigFile;
+pub use config_file::CoverageConfig;
+pub use config_file::EmbeddedLanguage;
 pub use config_file::EmitConfigOptions;
 pub use config_file::FilesConfig;
 pub use config_file::FmtOptionsConfig;
+pub use config_file::FmtPluginConfig;
 pub use config_file::JsxImportSourceConfig;
 pub use config_file::LintRulesConfig;
+pub use config_file::LintSeverity;
+pub use config_file::LintSeverityConfig;
 pub use config_file::ProseWrap;
+pub use config_file::TaskDefinition;
 pub use config_file::TsConfig;
 pub use config_file::TsConfigForEmit;
 pub use config_file::TsConfigType;
@@ -33,6 +41,8 @@ pub use config_file::TsTypeLib;
 pub use flags::*;
 pub use lockfile::Lockfile;
 pub use lockfile::LockfileError;
+pub use workspace::Workspace;
+pub use workspace::WorkspaceMember;
 
 use deno_ast::ModuleSpecifier;
 use deno_core::anyhow::anyhow;
@@ -51,6 +61,7 @@ use deno_runtime::deno_tls::rustls_native_certs::load_native_certs;
 use deno_runtime::deno_tls::rustls_pemfile;
 use deno_runtime::deno_tls::webpki_roots;
 use deno_runtime::inspector_server::InspectorServer;
+use deno_runtime::permissions::PermissionPresets;
 use deno_runtime::permissions::PermissionsOptions;
 use once_cell::sync::Lazy;
 use once_cell::sync::OnceCell;
@@ -63,6 +74,7 @@ use std::num::NonZeroUsize;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 
 use crate::cache::DenoDir;
@@ -124,6 +136,7 @@ pub struct BenchOptions {
   pub filter: Option<String>,
   pub json: bool,
   pub no_run: bool,
+  pub compare: Option<PathBuf>,
 }
 
 impl BenchOptions {
@@ -140,6 +153,7 @@ impl BenchOptions {
       filter: bench_flags.filter,
       json: bench_flags.json,
       no_run: bench_flags.no_run,
+      compare: bench_flags.compare,
     })
   }
 }
@@ -150,6 +164,7 @@ pub struct FmtOptions {
   pub check: bool,
   pub options: FmtOptionsConfig,
   pub files: FilesConfig,
+  pub plugins: Vec<FmtPluginConfig>,
 }
 
 impl FmtOptions {
@@ -168,8 +183,11 @@ impl FmtOptions {
     } else {
       false
     };
-    let (maybe_config_options, maybe_config_files) =
-      maybe_fmt_config.map(|c| (c.options, c.files)).unzip();
+    let (maybe_config_options, maybe_config_files, plugins) =
+      match maybe_fmt_config {
+        Some(c) => (Some(c.options), Some(c.files), c.plugins),
+        None => (None, None, vec![]),
+      };
 
     Ok(Self {
       is_stdin,
@@ -182,6 +200,7 @@ impl FmtOptions {
         maybe_config_files,
         maybe_fmt_flags.map(|f| f.files),
       ),
+      plugins,
     })
   }
 }
@@ -233,11 +252,21 @@ pub struct TestOptions {
   pub doc: bool,
   pub no_run: bool,
   pub fail_fast: Option<NonZeroUsize>,
+  pub retries: usize,
+  pub update_snapshots: bool,
+  pub rerun_all: bool,
+  pub slow_threshold: Option<Duration>,
+  pub setup: Option<ModuleSpecifier>,
+  pub teardown: Option<ModuleSpecifier>,
   pub allow_none: bool,
   pub filter: Option<String>,
+  pub tags: Vec<String>,
+  pub skip_tags: Vec<String>,
   pub shuffle: Option<u64>,
+  pub shard: Option<TestShard>,
   pub concurrent_jobs: NonZeroUsize,
   pub trace_ops: bool,
+  pub reporters: Vec<TestReporterConfig>,
 }
 
 impl TestOptions {
@@ -246,26 +275,80 @@ impl TestOptions {
     maybe_test_flags: Option<TestFlags>,
   ) -> Result<Self, AnyError> {
     let test_flags = maybe_test_flags.unwrap_or_default();
+    let (maybe_files, setup, teardown) = match maybe_test_config {
+      Some(c) => (Some(c.files), c.setup, c.teardown),
+      None => (None, None, None),
+    };
 
     Ok(Self {
-      files: resolve_files(
-        maybe_test_config.map(|c| c.files),
-        Some(test_flags.files),
-      ),
+      files: resolve_files(maybe_files, Some(test_flags.files)),
       allow_none: test_flags.allow_none,
       concurrent_jobs: test_flags
         .concurrent_jobs
         .unwrap_or_else(|| NonZeroUsize::new(1).unwrap()),
       doc: test_flags.doc,
       fail_fast: test_flags.fail_fast,
+      retries: test_flags.retries,
+      update_snapshots: test_flags.update_snapshots,
+      rerun_all: test_flags.rerun_all,
+      slow_threshold: test_flags.slow_threshold,
+      setup,
+      teardown,
       filter: test_flags.filter,
+      tags: test_flags.tags,
+      skip_tags: test_flags.skip_tags,
       no_run: test_flags.no_run,
       shuffle: test_flags.shuffle,
+      shard: test_flags.shard,
       trace_ops: test_flags.trace_ops,
+      reporters: resolve_test_reporters(test_flags.reporters)?,
     })
   }
 }
 
+/// Validates the reporters, and adds the pretty one on stdout if no other
+/// reporter writes there.
+fn resolve_test_reporters(
+  mut reporters: Vec<TestReporterConfig>,
+) -> Result<Vec<TestReporterConfig>, AnyError> {
+  if reporters
+    .iter()
+    .any(|r| r.kind == TestReporterKind::Pretty && r.output.is_some())
+  {
+    bail!("The pretty reporter can only write to stdout.");
+  }
+  match reporters.iter().filter(|r| r.output.is_none()).count() {
+    0 => reporters.insert(
+      0,
+      TestReporterConfig {
+        kind: TestReporterKind::Pretty,
+        output: None,
+      },
+    ),
+    1 => {}
+    _ => bail!(
+      "Only one reporter can write to stdout, use --reporter-output for the others."
+    ),
+  }
+  Ok(reporters)
+}
+
+/// Merges the thresholds of the flags with the ones of the configuration file,
+/// with the flags taking precedence.
+fn resolve_coverage_thresholds(
+  maybe_coverage_config: Option<CoverageConfig>,
+  flags: CoverageThresholds,
+) -> CoverageThresholds {
+  let config = maybe_coverage_config
+    .map(|c| c.thresholds)
+    .unwrap_or_default();
+  CoverageThresholds {
+    lines: flags.lines.or(config.lines),
+    functions: flags.functions.or(config.functions),
+    branches: flags.branches.or(config.branches),
+  }
+}
+
 #[derive(Clone, Default, Debug)]
 pub enum LintReporterKind {
   #[default]
@@ -279,7 +362,11 @@ pub struct LintOptions {
   pub rules: LintRulesConfig,
   pub files: FilesConfig,
   pub is_stdin: bool,
+  pub fix: bool,
   pub reporter_kind: LintReporterKind,
+  /// The modules of the plugins that provide more lint rules.
+  pub plugins: Vec<ModuleSpecifier>,
+  pub severity: LintSeverityConfig,
 }
 
 impl LintOptions {
@@ -325,6 +412,7 @@ impl LintOptions {
       }
     }
 
+    let fix = maybe_lint_flags.as_ref().map(|f| f.fix).unwrap_or_default();
     let (
       maybe_file_flags,
       maybe_rules_tags,
@@ -341,11 +429,17 @@ impl LintOptions {
       })
       .unwrap_or_default();
 
-    let (maybe_config_files, maybe_config_rules) =
-      maybe_lint_config.map(|c| (c.files, c.rules)).unzip();
+    let (maybe_config_files, maybe_config_rules, plugins, severity) =
+      match maybe_lint_config {
+        Some(c) => (Some(c.files), Some(c.rules), c.plugins, c.severity),
+        None => (None, None, vec![], Default::default()),
+      };
     Ok(Self {
       reporter_kind: maybe_reporter_kind.unwrap_or_default(),
       is_stdin,
+      fix,
+      plugins,
+      severity,
       files: resolve_files(maybe_config_files, Some(maybe_file_flags)),
       rules: resolve_lint_rules_options(
         maybe_config_rules,
@@ -404,6 +498,36 @@ fn discover_package_json(
   Ok(None)
 }
 
+/// Load the variables of the dotenv file of the `--env-file` flag, or of the
+/// `"envFile"` option of the configuration file for `deno run` and
+/// `deno task`, into the environment of the process. Variables that are
+/// already set are kept.
+fn load_env_file(
+  flags: &Flags,
+  maybe_config_file: Option<&ConfigFile>,
+  current_dir: &Path,
+) -> Result<(), AnyError> {
+  let maybe_env_file_path = match (&flags.env_file, &flags.subcommand) {
+    (Some(env_file), _) => Some(current_dir.join(env_file)),
+    (None, DenoSubcommand::Run(_) | DenoSubcommand::Task(_)) => {
+      match maybe_config_file {
+        Some(config_file) => config_file.to_env_file_path()?,
+        None => None,
+      }
+    }
+    (None, _) => None,
+  };
+  if let Some(env_file_path) = maybe_env_file_path {
+    dotenvy::from_path(&env_file_path).with_context(|| {
+      format!(
+        "Failed to load the environment variables of \"{}\"",
+        env_file_path.display()
+      )
+    })?;
+  }
+  Ok(())
+}
+
 struct CliRootCertStoreProvider {
   cell: OnceCell<RootCertStore>,
   maybe_root_path: Option<PathBuf>,
@@ -565,6 +689,7 @@ pub struct CliOptions {
   initial_cwd: PathBuf,
   maybe_node_modules_folder: Option<PathBuf>,
   maybe_config_file: Option<ConfigFile>,
+  maybe_workspace: Option<Workspace>,
   maybe_package_json: Option<PackageJson>,
   maybe_lockfile: Option<Arc<Mutex<Lockfile>>>,
   overrides: CliOptionOverrides,
@@ -605,6 +730,7 @@ impl CliOptions {
       flags,
       initial_cwd,
       maybe_config_file,
+      maybe_workspace: None,
       maybe_lockfile,
       maybe_package_json,
       maybe_node_modules_folder,
@@ -639,15 +765,23 @@ impl CliOptions {
       maybe_package_json = discover_package_json(&flags, None, &initial_cwd)?;
     }
 
+    load_env_file(&flags, maybe_config_file.as_ref(), &initial_cwd)?;
+
+    let maybe_workspace = match &maybe_config_file {
+      Some(config_file) => Workspace::discover(config_file)?,
+      None => None,
+    };
     let maybe_lock_file =
       lockfile::discover(&flags, maybe_config_file.as_ref())?;
-    Self::new(
+    let mut options = Self::new(
       flags,
       initial_cwd,
       maybe_config_file,
       maybe_lock_file,
       maybe_package_json,
-    )
+    )?;
+    options.maybe_workspace = maybe_workspace;
+    Ok(options)
   }
 
   #[inline(always)]
@@ -699,11 +833,17 @@ impl CliOptions {
   ) -> Result<Option<ModuleSpecifier>, AnyError> {
     match self.overrides.import_map_specifier.clone() {
       Some(maybe_path) => Ok(maybe_path),
-      None => resolve_import_map_specifier(
-        self.flags.import_map_path.as_deref(),
-        self.maybe_config_file.as_ref(),
-        &self.initial_cwd,
-      ),
+      // the import map of a workspace is based on its root configuration file
+      None => match &self.maybe_workspace {
+        Some(workspace) if self.flags.import_map_path.is_none() => {
+          Ok(Some(workspace.root_config.specifier.clone()))
+        }
+        _ => resolve_import_map_specifier(
+          self.flags.import_map_path.as_deref(),
+          self.maybe_config_file.as_ref(),
+          &self.initial_cwd,
+        ),
+      },
     }
   }
 
@@ -715,6 +855,17 @@ impl CliOptions {
       Some(specifier) => specifier,
       None => return Ok(None),
     };
+    if let Some(workspace) = &self.maybe_workspace {
+      if workspace.root_config.specifier == import_map_specifier {
+        return workspace
+          .to_import_map_value()
+          .and_then(|value| import_map_from_value(&import_map_specifier, value))
+          .with_context(|| {
+            format!("Unable to load '{import_map_specifier}' import map")
+          })
+          .map(Some);
+      }
+    }
     resolve_import_map_from_specifier(
       &import_map_specifier,
       self.maybe_config_file().as_ref(),
@@ -763,6 +914,12 @@ impl CliOptions {
             .map_err(AnyError::from)
         }
       }
+      DenoSubcommand::Permissions(PermissionsFlags {
+        script: Some(script),
+        ..
+      }) => {
+        resolve_url_or_path(script, self.initial_cwd()).map_err(AnyError::from)
+      }
       _ => {
         bail!("No main module.")
       }
@@ -886,7 +1043,7 @@ impl CliOptions {
 
   pub fn resolve_tasks_config(
     &self,
-  ) -> Result<IndexMap<String, String>, AnyError> {
+  ) -> Result<IndexMap<String, TaskDefinition>, AnyError> {
     if let Some(config_file) = &self.maybe_config_file {
       config_file.resolve_tasks_config()
     } else if self.maybe_package_json.is_some() {
@@ -939,6 +1096,33 @@ impl CliOptions {
     }
   }
 
+  /// The members of the workspace, when the configuration file is the root
+  /// of a workspace.
+  pub fn workspace_members(&self) -> &[WorkspaceMember] {
+    match (&self.maybe_workspace, &self.maybe_config_file) {
+      (Some(workspace), Some(config_file))
+        if workspace.root_config.specifier == config_file.specifier =>
+      {
+        &workspace.members
+      }
+      _ => &[],
+    }
+  }
+
+  /// The workspace members whose files are checked with their own
+  /// configuration file by `deno fmt` and `deno lint` at the root of the
+  /// workspace, which is when no files are passed and not watching.
+  fn workspace_members_with_own_options(
+    &self,
+    file_flags: &FileFlags,
+  ) -> &[WorkspaceMember] {
+    if file_flags.include.is_empty() && self.flags.watch.is_none() {
+      self.workspace_members()
+    } else {
+      &[]
+    }
+  }
+
   pub fn resolve_fmt_options(
     &self,
     fmt_flags: FmtFlags,
@@ -948,7 +1132,36 @@ impl CliOptions {
     } else {
       None
     };
-    FmtOptions::resolve(maybe_fmt_config, Some(fmt_flags))
+    let members = self.workspace_members_with_own_options(&fmt_flags.files);
+    let mut fmt_options =
+      FmtOptions::resolve(maybe_fmt_config, Some(fmt_flags))?;
+    fmt_options
+      .files
+      .exclude
+      .extend(members.iter().map(|member| member.dir.clone()));
+    Ok(fmt_options)
+  }
+
+  /// Resolve the options of `deno fmt` for each workspace member that is
+  /// formatted with its own configuration file.
+  pub fn resolve_workspace_fmt_options(
+    &self,
+    fmt_flags: &FmtFlags,
+  ) -> Result<Vec<FmtOptions>, AnyError> {
+    self
+      .workspace_members_with_own_options(&fmt_flags.files)
+      .iter()
+      .map(|member| {
+        let mut fmt_options = FmtOptions::resolve(
+          member.config_file.to_fmt_config()?,
+          Some(fmt_flags.clone()),
+        )?;
+        if fmt_options.files.include.is_empty() {
+          fmt_options.files.include.push(member.dir.clone());
+        }
+        Ok(fmt_options)
+      })
+      .collect()
   }
 
   pub fn resolve_lint_options(
@@ -960,7 +1173,36 @@ impl CliOptions {
     } else {
       None
     };
-    LintOptions::resolve(maybe_lint_config, Some(lint_flags))
+    let members = self.workspace_members_with_own_options(&lint_flags.files);
+    let mut lint_options =
+      LintOptions::resolve(maybe_lint_config, Some(lint_flags))?;
+    lint_options
+      .files
+      .exclude
+      .extend(members.iter().map(|member| member.dir.clone()));
+    Ok(lint_options)
+  }
+
+  /// Resolve the options of `deno lint` for each workspace member that is
+  /// linted with its own configuration file.
+  pub fn resolve_workspace_lint_options(
+    &self,
+    lint_flags: &LintFlags,
+  ) -> Result<Vec<LintOptions>, AnyError> {
+    self
+      .workspace_members_with_own_options(&lint_flags.files)
+      .iter()
+      .map(|member| {
+        let mut lint_options = LintOptions::resolve(
+          member.config_file.to_lint_config()?,
+          Some(lint_flags.clone()),
+        )?;
+        if lint_options.files.include.is_empty() {
+          lint_options.files.include.push(member.dir.clone());
+        }
+        Ok(lint_options)
+      })
+      .collect()
   }
 
   pub fn resolve_test_options(
@@ -972,7 +1214,29 @@ impl CliOptions {
     } else {
       None
     };
-    TestOptions::resolve(maybe_test_config, Some(test_flags))
+    let has_file_flags = !test_flags.files.include.is_empty();
+    let mut test_options =
+      TestOptions::resolve(maybe_test_config, Some(test_flags))?;
+    if !has_file_flags {
+      // discover the tests of the workspace members with their own
+      // "test" configuration
+      for member in self.workspace_members() {
+        let member_files = member
+          .config_file
+          .to_test_config()?
+          .map(|test_config| test_config.files)
+          .unwrap_or_default();
+        if !test_options.files.include.is_empty() {
+          if member_files.include.is_empty() {
+            test_options.files.include.push(member.dir.clone());
+          } else {
+            test_options.files.include.extend(member_files.include);
+          }
+        }
+        test_options.files.exclude.extend(member_files.exclude);
+      }
+    }
+    Ok(test_options)
   }
 
   pub fn resolve_bench_options(
@@ -988,6 +1252,38 @@ impl CliOptions {
     BenchOptions::resolve(maybe_bench_config, Some(bench_flags))
   }
 
+  /// Resolves the coverage thresholds of the flags, falling back to the ones
+  /// of the configuration file.
+  pub fn resolve_coverage_thresholds(
+    &self,
+    thresholds: CoverageThresholds,
+  ) -> Result<CoverageThresholds, AnyError> {
+    let maybe_coverage_config =
+      if let Some(config_file) = &self.maybe_config_file {
+        config_file.to_coverage_config()?
+      } else {
+        None
+      };
+    Ok(resolve_coverage_thresholds(
+      maybe_coverage_config,
+      thresholds,
+    ))
+  }
+
+  /// Resolves the files whose symbols `deno doc --lint` checks.
+  pub fn resolve_doc_lint_files(&self) -> Result<FilesConfig, AnyError> {
+    let maybe_doc_config = if let Some(config_file) = &self.maybe_config_file {
+      config_file.to_doc_config()?
+    } else {
+      None
+    };
+    Ok(
+      maybe_doc_config
+        .map(|config| config.lint_files)
+        .unwrap_or_default(),
+    )
+  }
+
   /// Vector of user script CLI arguments.
   pub fn argv(&self) -> &Vec<String> {
     &self.flags.argv
@@ -1092,20 +1388,97 @@ impl CliOptions {
     self.flags.no_npm
   }
 
-  pub fn permissions_options(&self) -> PermissionsOptions {
-    PermissionsOptions {
-      allow_env: self.flags.allow_env.clone(),
-      allow_hrtime: self.flags.allow_hrtime,
-      allow_net: self.flags.allow_net.clone(),
-      allow_ffi: self.flags.allow_ffi.clone(),
-      allow_read: self.flags.allow_read.clone(),
-      allow_run: self.flags.allow_run.clone(),
-      allow_sys: self.flags.allow_sys.clone(),
-      allow_write: self.flags.allow_write.clone(),
-      prompt: !self.no_prompt(),
+  /// The name of the permission set of the configuration file to use, from
+  /// the `--permission-set` flag or the `DENO_PERMISSION_SET` env var.
+  pub fn permission_set(&self) -> Option<String> {
+    self
+      .flags
+      .permission_set
+      .clone()
+      .or_else(|| env::var("DENO_PERMISSION_SET").ok())
+      .filter(|name| !name.is_empty())
+  }
+
+  /// Resolve the permission set of the configuration file to use. A set is
+  /// only used when it's chosen explicitly, so that a configuration file
+  /// can't grant permissions by itself.
+  fn resolve_permission_set(
+    &self,
+  ) -> Result<Option<PermissionsOptions>, AnyError> {
+    let maybe_permissions_config = self.resolve_permissions_config()?;
+    match self.permission_set() {
+      Some(name) => {
+        let Some(permissions_config) = maybe_permissions_config else {
+          bail!(
+            "Permission set \"{}\" not found, there is no \"permissions\" configuration",
+            name
+          );
+        };
+        match permissions_config.get(&name) {
+          Some(options) => Ok(Some(options.clone())),
+          None => bail!("Permission set \"{}\" not found", name),
+        }
+      }
+      None => Ok(None),
+    }
+  }
+
+  fn resolve_permissions_config(
+    &self,
+  ) -> Result<Option<IndexMap<String, PermissionsOptions>>, AnyError> {
+    match &self.maybe_config_file {
+      Some(config_file) => config_file.to_permissions_config(),
+      None => Ok(None),
     }
   }
 
+  /// Resolve the permission sets of the configuration file, as the presets
+  /// that workers can be created with.
+  pub fn resolve_permission_presets(
+    &self,
+  ) -> Result<PermissionPresets, AnyError> {
+    let permissions_config =
+      self.resolve_permissions_config()?.unwrap_or_default();
+    Ok(PermissionPresets::new(
+      permissions_config.into_iter().collect(),
+    ))
+  }
+
+  /// The specifier that the remembered permission prompt decisions are keyed
+  /// by: the configuration file when there is one, otherwise the main module.
+  pub fn permission_prompt_store_key(&self) -> Option<ModuleSpecifier> {
+    self
+      .maybe_config_file_specifier()
+      .or_else(|| self.resolve_main_module().ok())
+  }
+
+  /// The permission options of the flags, over the ones of the permission
+  /// set of the configuration file for each kind of permission.
+  pub fn permissions_options(&self) -> Result<PermissionsOptions, AnyError> {
+    let set = self.resolve_permission_set()?.unwrap_or_default();
+    Ok(PermissionsOptions {
+      allow_env: self.flags.allow_env.clone().or(set.allow_env),
+      allow_hrtime: self.flags.allow_hrtime || set.allow_hrtime,
+      allow_net: self.flags.allow_net.clone().or(set.allow_net),
+      allow_ffi: self.flags.allow_ffi.clone().or(set.allow_ffi),
+      allow_read: self.flags.allow_read.clone().or(set.allow_read),
+      allow_run: self.flags.allow_run.clone().or(set.allow_run),
+      allow_sys: self.flags.allow_sys.clone().or(set.allow_sys),
+      allow_vsock: self.flags.allow_vsock.clone().or(set.allow_vsock),
+      allow_write: self.flags.allow_write.clone().or(set.allow_write),
+      deny_env: self.flags.deny_env.clone().or(set.deny_env),
+      deny_hrtime: self.flags.deny_hrtime || set.deny_hrtime,
+      deny_net: self.flags.deny_net.clone().or(set.deny_net),
+      deny_ffi: self.flags.deny_ffi.clone().or(set.deny_ffi),
+      deny_read: self.flags.deny_read.clone().or(set.deny_read),
+      deny_run: self.flags.deny_run.clone().or(set.deny_run),
+      deny_sys: self.flags.deny_sys.clone().or(set.deny_sys),
+      deny_vsock: self.flags.deny_vsock.clone().or(set.deny_vsock),
+      deny_write: self.flags.deny_write.clone().or(set.deny_write),
+      prompt: !self.no_prompt(),
+    })
+  }
+
   pub fn reload_flag(&self) -> bool {
     self.flags.reload
   }
@@ -1460,4 +1833,76 @@ mod test {
     let resolver = StorageKeyResolver::empty();
     assert_eq!(resolver.resolve_storage_key(&specifier), None);
   }
+
+  #[test]
+  fn resolve_test_reporters_adds_pretty() {
+    let junit = TestReporterConfig {
+      kind: TestReporterKind::Junit,
+      output: Some(PathBuf::from("report.xml")),
+    };
+    let pretty = TestReporterConfig {
+      kind: TestReporterKind::Pretty,
+      output: None,
+    };
+    assert_eq!(
+      resolve_test_reporters(vec![]).unwrap(),
+      vec![pretty.clone()]
+    );
+    assert_eq!(
+      resolve_test_reporters(vec![junit.clone()]).unwrap(),
+      vec![pretty, junit.clone()]
+    );
+
+    let tap = TestReporterConfig {
+      kind: TestReporterKind::Tap,
+      output: None,
+    };
+    assert_eq!(
+      resolve_test_reporters(vec![junit.clone(), tap.clone()]).unwrap(),
+      vec![junit, tap.clone()]
+    );
+    assert!(resolve_test_reporters(vec![
+      tap.clone(),
+      TestReporterConfig {
+        kind: TestReporterKind::Junit,
+        output: None,
+      }
+    ])
+    .is_err());
+    assert!(resolve_test_reporters(vec![TestReporterConfig {
+      kind: TestReporterKind::Pretty,
+      output: Some(PathBuf::from("report.txt")),
+    }])
+    .is_err());
+  }
+
+  #[test]
+  fn resolve_coverage_thresholds_prefers_flags() {
+    let config = CoverageConfig {
+      thresholds: CoverageThresholds {
+        lines: Some(90),
+        functions: Some(80),
+        branches: None,
+      },
+    };
+    assert_eq!(
+      resolve_coverage_thresholds(
+        Some(config),
+        CoverageThresholds {
+          lines: Some(50),
+          functions: None,
+          branches: Some(75),
+        }
+      ),
+      CoverageThresholds {
+        lines: Some(50),
+        functions: Some(80),
+        branches: Some(75),
+      }
+    );
+    assert_eq!(
+      resolve_coverage_thresholds(None, CoverageThresholds::default()),
+      CoverageThresholds::default()
+    );
+  }
 }
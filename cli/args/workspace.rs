@@ -0,0 +1,398 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::serde_json::Value;
+
+use super::ConfigFile;
+use crate::util::path::specifier_parent;
+use crate::util::path::specifier_to_file_path;
+
+/// A directory of a workspace with its own configuration file.
+#[derive(Clone, Debug)]
+pub struct WorkspaceMember {
+  /// The name that the other members import the member by, from the
+  /// `"name"` of its configuration file.
+  pub name: Option<String>,
+  pub dir: PathBuf,
+  pub config_file: ConfigFile,
+}
+
+/// A configuration file with a `"workspace"` option and the members that it
+/// lists.
+#[derive(Clone, Debug)]
+pub struct Workspace {
+  pub root_config: ConfigFile,
+  pub members: Vec<WorkspaceMember>,
+}
+
+impl Workspace {
+  /// Discover the workspace of a configuration file, which is either the root
+  /// of the workspace, or a member whose parent directories have the root.
+  pub fn discover(
+    config_file: &ConfigFile,
+  ) -> Result<Option<Workspace>, AnyError> {
+    if config_file.specifier.scheme() != "file" {
+      return Ok(None);
+    }
+    if config_file.json.workspace.is_some() {
+      return Self::from_root(config_file.clone()).map(Some);
+    }
+
+    let config_path = specifier_to_file_path(&config_file.specifier)?;
+    let Some(parent_dir) = config_path.parent().and_then(Path::parent) else {
+      return Ok(None);
+    };
+    let maybe_root_config =
+      match ConfigFile::discover_from(parent_dir, &mut HashSet::new()) {
+        Ok(maybe_root_config) => maybe_root_config,
+        Err(err) => {
+          log::debug!("Failed to discover a workspace: {:#}", err);
+          None
+        }
+      };
+    let Some(root_config) =
+      maybe_root_config.filter(|c| c.json.workspace.is_some())
+    else {
+      return Ok(None);
+    };
+    let workspace = Self::from_root(root_config)?;
+    let is_member = workspace
+      .members
+      .iter()
+      .any(|member| member.config_file.specifier == config_file.specifier);
+    Ok(is_member.then_some(workspace))
+  }
+
+  fn from_root(root_config: ConfigFile) -> Result<Workspace, AnyError> {
+    let member_dirs =
+      root_config.to_workspace_member_dirs()?.unwrap_or_default();
+    let mut members: Vec<WorkspaceMember> =
+      Vec::with_capacity(member_dirs.len());
+    for member_dir in member_dirs {
+      let config_file = read_member_config_file(&member_dir)?;
+      let name = config_file.json.name.clone();
+      if let Some(name) = &name {
+        if members.iter().any(|m| m.name.as_ref() == Some(name)) {
+          bail!("Several workspace members are named \"{}\"", name);
+        }
+      }
+      let config_path = specifier_to_file_path(&config_file.specifier)?;
+      members.push(WorkspaceMember {
+        name,
+        dir: config_path.parent().unwrap().to_path_buf(),
+        config_file,
+      });
+    }
+    Ok(Workspace {
+      root_config,
+      members,
+    })
+  }
+
+  /// The import map of the workspace: the imports of the root configuration
+  /// file, the names of the members, and the imports of every member scoped
+  /// to its directory. The imports of a configuration file are the ones of the
+  /// file of its `"importMap"` option when it has one.
+  pub fn to_import_map_value(&self) -> Result<Value, AnyError> {
+    let (root_value, root_base) = read_import_map_value(&self.root_config)?;
+    let mut imports =
+      resolve_specifier_map(&get_object(&root_value, "imports"), &root_base);
+    let mut scopes = resolve_scopes(&root_value, &root_base);
+
+    for member in &self.members {
+      let member_specifier = &member.config_file.specifier;
+      let member_dir = specifier_parent(member_specifier);
+      if let Some(name) = &member.name {
+        if let Some(exports) = &member.config_file.json.exports {
+          let exports = member_dir.join(exports).with_context(|| {
+            format!("Invalid \"exports\" of workspace member \"{name}\"")
+          })?;
+          imports
+            .entry(name.clone())
+            .or_insert_with(|| exports.to_string().into());
+        }
+        imports
+          .entry(format!("{name}/"))
+          .or_insert_with(|| member_dir.to_string().into());
+      }
+
+      let (member_value, member_base) =
+        read_import_map_value(&member.config_file)?;
+      let member_imports = resolve_specifier_map(
+        &get_object(&member_value, "imports"),
+        &member_base,
+      );
+      if !member_imports.is_empty() {
+        scopes.insert(member_dir.to_string(), member_imports.into());
+      }
+      scopes.extend(resolve_scopes(&member_value, &member_base));
+    }
+
+    Ok(serde_json::json!({
+      "imports": imports,
+      "scopes": scopes,
+    }))
+  }
+}
+
+fn read_member_config_file(member_dir: &Path) -> Result<ConfigFile, AnyError> {
+  for config_file_name in ["deno.json", "deno.jsonc"] {
+    let config_path = member_dir.join(config_file_name);
+    if config_path.exists() {
+      return ConfigFile::read(&config_path);
+    }
+  }
+  bail!(
+    "Workspace member \"{}\" has no deno.json or deno.jsonc file",
+    member_dir.display()
+  )
+}
+
+/// Reads the import map of a configuration file, which is the file of its
+/// `"importMap"` option, or its own `"imports"` and `"scopes"`. Returns it with
+/// the specifier that its relative specifiers are relative to.
+fn read_import_map_value(
+  config_file: &ConfigFile,
+) -> Result<(Value, ModuleSpecifier), AnyError> {
+  let Some(import_map_path) = config_file.to_import_map_path() else {
+    return Ok((
+      config_file.to_import_map_value(),
+      config_file.specifier.clone(),
+    ));
+  };
+  let import_map_specifier = config_file
+    .specifier
+    .join(&import_map_path)
+    .with_context(|| {
+      format!(
+        "Invalid \"importMap\" of configuration file \"{}\"",
+        config_file.specifier
+      )
+    })?;
+  // the import map of a workspace is created synchronously, so remote import
+  // maps can't be fetched
+  if import_map_specifier.scheme() != "file" {
+    bail!(
+      "The \"importMap\" of workspace configuration file \"{}\" must be a local file",
+      config_file.specifier
+    );
+  }
+  let import_map_path = specifier_to_file_path(&import_map_specifier)?;
+  let text = std::fs::read_to_string(&import_map_path).with_context(|| {
+    format!(
+      "Unable to read import map \"{}\"",
+      import_map_path.display()
+    )
+  })?;
+  let value = serde_json::from_str(&text).with_context(|| {
+    format!(
+      "Unable to parse import map \"{}\"",
+      import_map_path.display()
+    )
+  })?;
+  Ok((value, import_map_specifier))
+}
+
+fn get_object(value: &Value, key: &str) -> serde_json::Map<String, Value> {
+  match value.get(key) {
+    Some(Value::Object(map)) => map.clone(),
+    _ => Default::default(),
+  }
+}
+
+/// Resolves the relative keys and addresses of the imports of a member
+/// against its configuration file, since the import map of the workspace is
+/// based on the root configuration file.
+fn resolve_specifier_map(
+  specifier_map: &serde_json::Map<String, Value>,
+  base: &ModuleSpecifier,
+) -> serde_json::Map<String, Value> {
+  specifier_map
+    .iter()
+    .map(|(key, value)| {
+      let value = match value.as_str().map(|address| base.join(address)) {
+        Some(Ok(address)) => address.to_string().into(),
+        _ => value.clone(),
+      };
+      (resolve_key(key, base), value)
+    })
+    .collect()
+}
+
+fn resolve_scopes(
+  value: &Value,
+  base: &ModuleSpecifier,
+) -> serde_json::Map<String, Value> {
+  get_object(value, "scopes")
+    .into_iter()
+    .filter_map(|(scope, specifier_map)| match specifier_map {
+      Value::Object(specifier_map) => Some((
+        resolve_key(&scope, base),
+        resolve_specifier_map(&specifier_map, base).into(),
+      )),
+      _ => None,
+    })
+    .collect()
+}
+
+fn resolve_key(key: &str, base: &ModuleSpecifier) -> String {
+  if key.starts_with("./") || key.starts_with("../") || key.starts_with('/') {
+    if let Ok(specifier) = base.join(key) {
+      return specifier.to_string();
+    }
+  }
+  key.to_string()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  fn member(
+    name: Option<&str>,
+    config_text: &str,
+    dir: &str,
+  ) -> WorkspaceMember {
+    let specifier =
+      ModuleSpecifier::parse(&format!("file:///deno/{dir}/deno.json")).unwrap();
+    WorkspaceMember {
+      name: name.map(|name| name.to_string()),
+      dir: PathBuf::from(format!("/deno/{dir}")),
+      config_file: ConfigFile::new(config_text, &specifier).unwrap(),
+    }
+  }
+
+  #[test]
+  fn test_to_import_map_value() {
+    let root_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let workspace = Workspace {
+      root_config: ConfigFile::new(
+        r#"{
+          "workspace": ["./packages/greet", "./packages/app"],
+          "imports": { "@std/": "https://deno.land/std@0.190.0/" }
+        }"#,
+        &root_specifier,
+      )
+      .unwrap(),
+      members: vec![
+        member(
+          Some("@example/greet"),
+          r#"{ "name": "@example/greet", "exports": "./mod.ts" }"#,
+          "packages/greet",
+        ),
+        member(
+          None,
+          r#"{
+            "imports": {
+              "chalk": "npm:chalk@5",
+              "$/": "./src/"
+            }
+          }"#,
+          "packages/app",
+        ),
+      ],
+    };
+    assert_eq!(
+      workspace.to_import_map_value().unwrap(),
+      serde_json::json!({
+        "imports": {
+          "@std/": "https://deno.land/std@0.190.0/",
+          "@example/greet": "file:///deno/packages/greet/mod.ts",
+          "@example/greet/": "file:///deno/packages/greet/",
+        },
+        "scopes": {
+          "file:///deno/packages/app/": {
+            "chalk": "npm:chalk@5",
+            "$/": "file:///deno/packages/app/src/",
+          },
+        },
+      })
+    );
+  }
+
+  #[test]
+  fn test_to_import_map_value_with_import_map_files() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root_dir = temp_dir.path().canonicalize().unwrap();
+    let member_dir = root_dir.join("packages/app");
+    std::fs::create_dir_all(&member_dir).unwrap();
+    std::fs::write(
+      root_dir.join("import_map.json"),
+      r#"{ "imports": { "@std/": "./vendor/std/" } }"#,
+    )
+    .unwrap();
+    std::fs::write(
+      member_dir.join("import_map.json"),
+      r#"{
+        "imports": { "$/": "./src/" },
+        "scopes": { "./legacy/": { "chalk": "npm:chalk@4" } }
+      }"#,
+    )
+    .unwrap();
+    let root_specifier =
+      ModuleSpecifier::from_file_path(root_dir.join("deno.json")).unwrap();
+    let member_specifier =
+      ModuleSpecifier::from_file_path(member_dir.join("deno.json")).unwrap();
+    let workspace = Workspace {
+      root_config: ConfigFile::new(
+        r#"{
+          "workspace": ["./packages/app"],
+          "importMap": "./import_map.json"
+        }"#,
+        &root_specifier,
+      )
+      .unwrap(),
+      members: vec![WorkspaceMember {
+        name: None,
+        dir: member_dir.clone(),
+        config_file: ConfigFile::new(
+          r#"{ "importMap": "./import_map.json" }"#,
+          &member_specifier,
+        )
+        .unwrap(),
+      }],
+    };
+    let root_url = ModuleSpecifier::from_directory_path(&root_dir).unwrap();
+    let member_url = ModuleSpecifier::from_directory_path(&member_dir).unwrap();
+    assert_eq!(
+      workspace.to_import_map_value().unwrap(),
+      serde_json::json!({
+        "imports": {
+          "@std/": root_url.join("vendor/std/").unwrap().to_string(),
+        },
+        "scopes": {
+          member_url.to_string(): {
+            "$/": member_url.join("src/").unwrap().to_string(),
+          },
+          member_url.join("legacy/").unwrap().to_string(): {
+            "chalk": "npm:chalk@4",
+          },
+        },
+      })
+    );
+
+    let workspace = Workspace {
+      members: vec![WorkspaceMember {
+        name: None,
+        dir: member_dir,
+        config_file: ConfigFile::new(
+          r#"{ "importMap": "https://example.com/import_map.json" }"#,
+          &member_specifier,
+        )
+        .unwrap(),
+      }],
+      ..workspace
+    };
+    assert!(workspace.to_import_map_value().is_err());
+  }
+}
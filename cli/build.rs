@@ -9,7 +9,6 @@ use deno_core::Extension;
 use deno_core::ExtensionFileSource;
 use deno_core::ExtensionFileSourceCode;
 use deno_runtime::deno_cache::SqliteBackedCache;
-use deno_runtime::deno_kv::sqlite::SqliteDbHandler;
 use deno_runtime::permissions::PermissionsContainer;
 use deno_runtime::*;
 
@@ -244,6 +243,14 @@ mod ts {
       "cargo:rerun-if-changed={}",
       cwd.join("js").join("40_testing.js").display()
     );
+    println!(
+      "cargo:rerun-if-changed={}",
+      cwd.join("js").join("40_fmt.js").display()
+    );
+    println!(
+      "cargo:rerun-if-changed={}",
+      cwd.join("js").join("40_lint.js").display()
+    );
 
     // create a copy of the vector that includes any op crate libs to be passed
     // to the JavaScript compiler to build into the snapshot
@@ -312,6 +319,8 @@ deno_core::extension!(
   esm_entry_point = "ext:cli/99_main.js",
   esm = [
     dir "js",
+    "40_fmt.js",
+    "40_lint.js",
     "40_testing.js",
     "99_main.js"
   ],
@@ -350,16 +359,13 @@ fn create_cli_snapshot(snapshot_path: PathBuf) {
       deno_broadcast_channel::InMemoryBroadcastChannel::default(),
       false, // No --unstable.
     ),
-    deno_ffi::deno_ffi::init_ops::<PermissionsContainer>(false),
+    optional_extensions::deno_ffi_init_ops(false),
     deno_net::deno_net::init_ops::<PermissionsContainer>(
       None, false, // No --unstable.
       None,
     ),
     deno_tls::deno_tls::init_ops(),
-    deno_kv::deno_kv::init_ops(
-      SqliteDbHandler::<PermissionsContainer>::new(None),
-      false, // No --unstable.
-    ),
+    optional_extensions::deno_kv_init_ops(None, false), // No --unstable.
     deno_napi::deno_napi::init_ops::<PermissionsContainer>(),
     deno_http::deno_http::init_ops(),
     deno_io::deno_io::init_ops(Default::default()),
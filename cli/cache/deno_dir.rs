@@ -65,6 +65,11 @@ impl DenoDir {
     self.root.join("lint_incremental_cache_v1")
   }
 
+  /// Folder path used for the downloaded Wasm modules of fmt plugins.
+  pub fn fmt_plugins_folder_path(&self) -> PathBuf {
+    self.root.join("fmt_plugins")
+  }
+
   /// Path for caching swc dependency analysis.
   pub fn dep_analysis_db_file_path(&self) -> PathBuf {
     // bump this version name to invalidate the entire cache
@@ -99,11 +104,21 @@ impl DenoDir {
     self.root.join("location_data")
   }
 
+  /// Path to the folder of the HTTP cache of `fetch()`.
+  pub fn fetch_cache_folder_path(&self) -> PathBuf {
+    self.root.join("fetch_cache")
+  }
+
   /// File used for the upgrade checker.
   pub fn upgrade_check_file_path(&self) -> PathBuf {
     self.root.join("latest.txt")
   }
 
+  /// Folder used for the permission prompt decisions remembered per project.
+  pub fn permission_prompts_folder_path(&self) -> PathBuf {
+    self.root.join("permission_prompts")
+  }
+
   /// Folder used for the npm cache.
   pub fn npm_folder_path(&self) -> PathBuf {
     self.root.join("npm")
@@ -12,6 +12,18 @@ use deno_graph::Module;
 use deno_graph::ModuleGraph;
 use std::sync::Arc;
 
+/// Whether modules of the media type are transpiled before they are run.
+pub fn is_emittable(media_type: MediaType) -> bool {
+  matches!(
+    media_type,
+    MediaType::TypeScript
+      | MediaType::Mts
+      | MediaType::Cts
+      | MediaType::Jsx
+      | MediaType::Tsx
+  )
+}
+
 pub struct Emitter {
   emit_cache: EmitCache,
   parsed_source_cache: Arc<ParsedSourceCache>,
@@ -43,15 +55,7 @@ impl Emitter {
   ) -> Result<(), AnyError> {
     for module in graph.modules() {
       if let Module::Esm(module) = module {
-        let is_emittable = matches!(
-          module.media_type,
-          MediaType::TypeScript
-            | MediaType::Mts
-            | MediaType::Cts
-            | MediaType::Jsx
-            | MediaType::Tsx
-        );
-        if is_emittable {
+        if is_emittable(module.media_type) {
           self.emit_parsed_source(
             &module.specifier,
             module.media_type,
@@ -31,6 +31,7 @@ use crate::npm::NpmResolution;
 use crate::npm::PackageJsonDepsInstaller;
 use crate::resolver::CliGraphResolver;
 use crate::tools::check::TypeChecker;
+use crate::util::checksum;
 use crate::util::progress_bar::ProgressBar;
 use crate::util::progress_bar::ProgressBarStyle;
 use crate::watcher::FileWatcher;
@@ -555,6 +556,7 @@ impl CliFactory {
     let node_resolver = self.node_resolver().await?.clone();
     let npm_resolver = self.npm_resolver().await?.clone();
     let maybe_inspector_server = self.maybe_inspector_server().clone();
+    self.init_permission_prompt_store()?;
     Ok(Arc::new(move || {
       CliMainWorkerFactory::new(
         StorageKeyResolver::from_options(&options),
@@ -587,6 +589,7 @@ impl CliFactory {
     &self,
   ) -> Result<CliMainWorkerFactory, AnyError> {
     let node_resolver = self.node_resolver().await?;
+    self.init_permission_prompt_store()?;
     Ok(CliMainWorkerFactory::new(
       StorageKeyResolver::from_options(&self.options),
       self.npm_resolver().await?.clone(),
@@ -613,6 +616,34 @@ impl CliFactory {
     ))
   }
 
+  /// The file in the DENO_DIR where the permission prompts remember their
+  /// decisions for the project.
+  pub fn permission_prompt_store_path(
+    &self,
+  ) -> Result<Option<PathBuf>, AnyError> {
+    let Some(key) = self.options.permission_prompt_store_key() else {
+      return Ok(None);
+    };
+    let file_name = format!("{}.json", checksum::gen(&[key.as_str()]));
+    Ok(Some(
+      self
+        .deno_dir()?
+        .permission_prompts_folder_path()
+        .join(file_name),
+    ))
+  }
+
+  /// Lets the permission prompts remember their decisions for the project.
+  fn init_permission_prompt_store(&self) -> Result<(), AnyError> {
+    if self.options.no_prompt() {
+      return Ok(());
+    }
+    if let Some(path) = self.permission_prompt_store_path()? {
+      deno_runtime::permissions::set_prompt_store(&path);
+    }
+    Ok(())
+  }
+
   fn create_cli_main_worker_options(
     &self,
   ) -> Result<CliMainWorkerOptions, AnyError> {
@@ -647,6 +678,8 @@ impl CliFactory {
         maybe_binary_command_name
       },
       origin_data_folder_path: Some(self.deno_dir()?.origin_data_folder_path()),
+      fetch_cache_folder_path: Some(self.deno_dir()?.fetch_cache_folder_path()),
+      permission_presets: self.options.resolve_permission_presets()?,
       seed: self.options.seed(),
       unsafely_ignore_certificate_errors: self
         .options
@@ -163,6 +163,83 @@ pub fn graph_lock_or_exit(graph: &ModuleGraph, lockfile: &mut Lockfile) {
   }
 }
 
+/// Returns the modules that a module imports. The type dependencies are only
+/// included when they are type checked.
+fn get_module_dependencies<'a>(
+  graph: &'a ModuleGraph,
+  specifier: &ModuleSpecifier,
+  include_types: bool,
+) -> Vec<&'a ModuleSpecifier> {
+  let Some(module) = graph.get(specifier).and_then(|m| m.esm()) else {
+    return Vec::new();
+  };
+  module
+    .dependencies
+    .values()
+    .flat_map(|dep| {
+      let maybe_type = if include_types { dep.get_type() } else { None };
+      [dep.get_code(), maybe_type]
+    })
+    .flatten()
+    .collect()
+}
+
+/// Returns the modules that the roots depend on, directly or through other
+/// modules, including the roots themselves.
+pub fn get_all_dependencies<'a>(
+  graph: &'a ModuleGraph,
+  roots: &'a [ModuleSpecifier],
+  include_types: bool,
+) -> HashSet<&'a ModuleSpecifier> {
+  let mut seen = HashSet::new();
+  let mut pending = roots.iter().collect::<Vec<_>>();
+  while let Some(specifier) = pending.pop() {
+    if seen.insert(specifier) {
+      pending.extend(get_module_dependencies(graph, specifier, include_types));
+    }
+  }
+  seen
+}
+
+/// Returns the roots that depend on any of the changed modules, directly or
+/// through other modules, in the order of the roots. The graph is walked once
+/// from the changed files up to their dependents, so that it doesn't matter
+/// how many roots there are.
+pub fn get_roots_affected_by_changes(
+  graph: &ModuleGraph,
+  roots: &[ModuleSpecifier],
+  changed: &[ModuleSpecifier],
+  include_types: bool,
+) -> Vec<ModuleSpecifier> {
+  let mut dependents =
+    HashMap::<&ModuleSpecifier, Vec<&ModuleSpecifier>>::new();
+  for module in graph.modules() {
+    for dependency in
+      get_module_dependencies(graph, module.specifier(), include_types)
+    {
+      dependents
+        .entry(dependency)
+        .or_default()
+        .push(module.specifier());
+    }
+  }
+
+  let mut affected = HashSet::new();
+  let mut pending = changed.iter().collect::<Vec<_>>();
+  while let Some(specifier) = pending.pop() {
+    if affected.insert(specifier) {
+      if let Some(dependents) = dependents.get(specifier) {
+        pending.extend(dependents.iter().copied());
+      }
+    }
+  }
+  roots
+    .iter()
+    .filter(|root| affected.contains(root))
+    .cloned()
+    .collect()
+}
+
 pub struct ModuleGraphBuilder {
   options: Arc<CliOptions>,
   resolver: Arc<CliGraphResolver>,
@@ -244,9 +321,26 @@ impl ModuleGraphBuilder {
     roots: Vec<ModuleSpecifier>,
   ) -> Result<Arc<deno_graph::ModuleGraph>, AnyError> {
     let mut cache = self.create_graph_loader();
+    let cli_resolver = self.resolver.clone();
+    self
+      .create_graph_with_loader_and_maybe_check(
+        roots,
+        &mut cache,
+        cli_resolver.as_graph_resolver(),
+      )
+      .await
+  }
+
+  /// Like `create_graph_and_maybe_check`, but builds the graph with the given
+  /// loader and resolver.
+  pub async fn create_graph_with_loader_and_maybe_check(
+    &self,
+    roots: Vec<ModuleSpecifier>,
+    loader: &mut dyn deno_graph::source::Loader,
+    graph_resolver: &dyn deno_graph::source::Resolver,
+  ) -> Result<Arc<deno_graph::ModuleGraph>, AnyError> {
     let maybe_imports = self.options.to_maybe_imports()?;
     let cli_resolver = self.resolver.clone();
-    let graph_resolver = cli_resolver.as_graph_resolver();
     let graph_npm_resolver = cli_resolver.as_graph_npm_resolver();
     let analyzer = self.parsed_source_cache.as_analyzer();
     let mut graph = ModuleGraph::default();
@@ -254,7 +348,7 @@ impl ModuleGraphBuilder {
       .build_graph_with_npm_resolution(
         &mut graph,
         roots,
-        &mut cache,
+        loader,
         deno_graph::BuildOptions {
           is_dynamic: false,
           imports: maybe_imports,
@@ -512,7 +606,9 @@ mod test {
   use deno_graph::ResolutionError;
   use deno_graph::SpecifierError;
 
+  use crate::graph_util::get_all_dependencies;
   use crate::graph_util::get_resolution_error_bare_node_specifier;
+  use crate::graph_util::get_roots_affected_by_changes;
 
   #[test]
   fn import_map_node_resolution_error() {
@@ -552,4 +648,87 @@ mod test {
       assert_eq!(get_resolution_error_bare_node_specifier(&err), output,);
     }
   }
+
+  async fn build_graph(modules: &[(&str, &str)]) -> deno_graph::ModuleGraph {
+    let mut loader = deno_graph::source::MemoryLoader::new(
+      modules
+        .iter()
+        .map(|(specifier, content)| {
+          (
+            *specifier,
+            deno_graph::source::Source::Module {
+              specifier: *specifier,
+              content: *content,
+              maybe_headers: None,
+            },
+          )
+        })
+        .collect(),
+      Vec::new(),
+    );
+    let roots = modules
+      .iter()
+      .filter(|(specifier, _)| specifier.ends_with("_test.ts"))
+      .map(|(specifier, _)| ModuleSpecifier::parse(specifier).unwrap())
+      .collect();
+    let mut graph = deno_graph::ModuleGraph::default();
+    graph.build(roots, &mut loader, Default::default()).await;
+    graph
+  }
+
+  #[tokio::test]
+  async fn roots_affected_by_changes() {
+    let graph = build_graph(&[
+      ("file:///a_test.ts", "import './a.ts';"),
+      (
+        "file:///b_test.ts",
+        "import './b.ts'; import type {} from './t.ts';",
+      ),
+      ("file:///c_test.ts", "import './c.ts';"),
+      ("file:///a.ts", "import './shared.ts';"),
+      (
+        "file:///b.ts",
+        "import './shared.ts'; import './b_test.ts';",
+      ),
+      ("file:///c.ts", ""),
+      ("file:///shared.ts", ""),
+      ("file:///t.ts", ""),
+    ])
+    .await;
+    let roots = ["a_test", "b_test", "c_test"]
+      .iter()
+      .map(|name| {
+        ModuleSpecifier::parse(&format!("file:///{name}.ts")).unwrap()
+      })
+      .collect::<Vec<_>>();
+    let affected = |changed: &[&str], include_types: bool| {
+      let changed = changed
+        .iter()
+        .map(|name| ModuleSpecifier::parse(&format!("file:///{name}")).unwrap())
+        .collect::<Vec<_>>();
+      get_roots_affected_by_changes(&graph, &roots, &changed, include_types)
+        .into_iter()
+        .map(|specifier| specifier.path()[1..].to_string())
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+      affected(&["shared.ts"], true),
+      vec!["a_test.ts", "b_test.ts"]
+    );
+    assert_eq!(affected(&["c.ts"], true), vec!["c_test.ts"]);
+    assert_eq!(affected(&["t.ts"], true), vec!["b_test.ts"]);
+    assert_eq!(affected(&["t.ts"], false), Vec::<String>::new());
+    assert_eq!(affected(&["other.ts"], true), Vec::<String>::new());
+
+    let mut dependencies = get_all_dependencies(&graph, &roots[..1], true)
+      .into_iter()
+      .map(|specifier| specifier.as_str())
+      .collect::<Vec<_>>();
+    dependencies.sort();
+    assert_eq!(
+      dependencies,
+      vec!["file:///a.ts", "file:///a_test.ts", "file:///shared.ts"]
+    );
+  }
 }
@@ -0,0 +1,211 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+const core = globalThis.Deno.core;
+const internals = globalThis.__bootstrap.internals;
+const primordials = globalThis.__bootstrap.primordials;
+const {
+  ArrayPrototypeJoin,
+  ArrayPrototypePush,
+  Error,
+  JSONParse,
+  JSONStringify,
+  SafeArrayIterator,
+  TypedArrayPrototypeSet,
+  TypedArrayPrototypeSlice,
+  TypeError,
+  Uint8Array,
+} = primordials;
+const { Instance: WebAssemblyInstance, Module: WebAssemblyModule } =
+  globalThis.WebAssembly;
+
+/** The id of the only configuration that is registered with a plugin. */
+const CONFIG_ID = 1;
+
+/**
+ * @typedef FmtPluginInfo
+ * @property {string} name
+ * @property {string} version
+ * @property {string} configKey
+ * @property {string[]} fileExtensions
+ * @property {string[]} fileNames
+ */
+
+/**
+ * A dprint Wasm plugin of the schema version 4. The host and the plugin pass
+ * text to each other through the "shared bytes" of the plugin, which are
+ * cleared and written before each call.
+ */
+class FmtPlugin {
+  #exports;
+  /** @type {FmtPluginInfo} */
+  info;
+
+  /**
+   * @param {Uint8Array} wasmBytes
+   * @param {object} globalConfig
+   * @param {object} pluginConfig
+   */
+  constructor(wasmBytes, globalConfig, pluginConfig) {
+    const module = new WebAssemblyModule(wasmBytes);
+    const instance = new WebAssemblyInstance(
+      module,
+      this.#createImports(module),
+    );
+    this.#exports = instance.exports;
+    if (typeof this.#exports.dprint_plugin_version_4 !== "function") {
+      throw new TypeError(
+        "The module is not a dprint Wasm plugin of the schema version 4.",
+      );
+    }
+    if (this.#exports.dprint_plugin_version_4() !== 4) {
+      throw new TypeError("The dprint plugin has an unsupported schema.");
+    }
+
+    this.#writeJson({ global: globalConfig, plugin: pluginConfig });
+    this.#exports.register_config(CONFIG_ID);
+    const diagnostics = this.#readJson(
+      this.#exports.get_config_diagnostics(CONFIG_ID),
+    );
+    if (diagnostics.length > 0) {
+      const messages = [];
+      for (const diagnostic of new SafeArrayIterator(diagnostics)) {
+        ArrayPrototypePush(
+          messages,
+          `${diagnostic.propertyName}: ${diagnostic.message}`,
+        );
+      }
+      throw new TypeError(
+        `Invalid configuration of the dprint plugin:\n  ${
+          ArrayPrototypeJoin(messages, "\n  ")
+        }`,
+      );
+    }
+
+    const info = this.#readJson(this.#exports.get_plugin_info());
+    const fileMatching = this.#readJson(
+      this.#exports.get_config_file_matching(CONFIG_ID),
+    );
+    this.info = {
+      name: info.name,
+      version: info.version,
+      configKey: info.configKey,
+      fileExtensions: fileMatching.fileExtensions ?? [],
+      fileNames: fileMatching.fileNames ?? [],
+    };
+  }
+
+  /**
+   * @param {string} filePath
+   * @param {string} fileText
+   * @returns {string | null} The formatted text, or `null` when the text is
+   * formatted already.
+   */
+  format(filePath, fileText) {
+    this.#writeText(filePath);
+    this.#exports.set_file_path();
+    this.#writeJson({});
+    this.#exports.set_override_config();
+    this.#writeText(fileText);
+    switch (this.#exports.format(CONFIG_ID)) {
+      case 0:
+        return null;
+      case 1:
+        return this.#readText(this.#exports.get_formatted_text());
+      case 2:
+        throw new Error(this.#readText(this.#exports.get_error_text()));
+      default:
+        throw new TypeError("The dprint plugin returned an unknown result.");
+    }
+  }
+
+  /**
+   * The plugin may ask the host to format code that is embedded in its files,
+   * which is left as is. The other imports throw when they are called.
+   * @param {WebAssembly.Module} module
+   */
+  #createImports(module) {
+    const imports = {};
+    for (
+      const { module: moduleName, name, kind } of new SafeArrayIterator(
+        WebAssemblyModule.imports(module),
+      )
+    ) {
+      if (kind !== "function") {
+        continue;
+      }
+      imports[moduleName] ??= {};
+      if (moduleName === "dprint" && name === "host_format") {
+        imports[moduleName][name] = () => 0;
+      } else if (moduleName === "dprint" && name === "host_has_cancelled") {
+        imports[moduleName][name] = () => 0;
+      } else if (moduleName === "dprint" && name === "host_write_buffer") {
+        // only called after `host_format` returns formatted text or an error
+        imports[moduleName][name] = () => {};
+      } else {
+        imports[moduleName][name] = () => {
+          throw new TypeError(
+            `The host function "${moduleName}.${name}" is not supported.`,
+          );
+        };
+      }
+    }
+    return imports;
+  }
+
+  /** @param {Uint8Array} bytes */
+  #writeBytes(bytes) {
+    const ptr = this.#exports.clear_shared_bytes(bytes.length);
+    TypedArrayPrototypeSet(
+      new Uint8Array(this.#exports.memory.buffer, ptr, bytes.length),
+      bytes,
+    );
+  }
+
+  /** @param {number} length */
+  #readBytes(length) {
+    const ptr = this.#exports.get_shared_bytes_ptr();
+    return TypedArrayPrototypeSlice(
+      new Uint8Array(this.#exports.memory.buffer, ptr, length),
+    );
+  }
+
+  #writeText(text) {
+    this.#writeBytes(core.encode(text));
+  }
+
+  #readText(length) {
+    return core.decode(this.#readBytes(length));
+  }
+
+  #writeJson(value) {
+    this.#writeText(JSONStringify(value));
+  }
+
+  #readJson(length) {
+    return JSONParse(this.#readText(length));
+  }
+}
+
+/**
+ * Instantiates a dprint Wasm plugin with its configuration.
+ * @param {Uint8Array} wasmBytes
+ * @param {object} globalConfig
+ * @param {object} pluginConfig
+ * @returns {FmtPlugin}
+ */
+function startFmtPlugin(wasmBytes, globalConfig, pluginConfig) {
+  return new FmtPlugin(wasmBytes, globalConfig, pluginConfig);
+}
+
+/**
+ * @param {FmtPlugin} plugin
+ * @param {string} filePath
+ * @param {string} fileText
+ * @returns {string | null}
+ */
+function runFmtPlugin(plugin, filePath, fileText) {
+  return plugin.format(filePath, fileText);
+}
+
+internals.startFmtPlugin = startFmtPlugin;
+internals.runFmtPlugin = runFmtPlugin;
@@ -0,0 +1,116 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+const internals = globalThis.__bootstrap.internals;
+const primordials = globalThis.__bootstrap.primordials;
+const {
+  ArrayIsArray,
+  ArrayPrototypePush,
+  ObjectKeys,
+  SafeArrayIterator,
+  TypeError,
+} = primordials;
+
+/**
+ * @typedef LintPluginDiagnostic
+ * @property {string} code
+ * @property {string} message
+ * @property {string | null} hint
+ * @property {number} start
+ * @property {number} end
+ */
+
+/**
+ * Checks the shape of the default export of a plugin module.
+ * @param {unknown} plugin
+ */
+function assertLintPlugin(plugin) {
+  if (plugin === null || typeof plugin !== "object") {
+    throw new TypeError("The lint plugin must export an object by default.");
+  }
+  if (typeof plugin.name !== "string" || plugin.name === "") {
+    throw new TypeError("The lint plugin must have a name.");
+  }
+  if (plugin.rules === null || typeof plugin.rules !== "object") {
+    throw new TypeError(`The lint plugin "${plugin.name}" must have rules.`);
+  }
+  for (const code of new SafeArrayIterator(ObjectKeys(plugin.rules))) {
+    if (typeof plugin.rules[code]?.create !== "function") {
+      throw new TypeError(
+        `The rule "${plugin.name}/${code}" must have a create function.`,
+      );
+    }
+  }
+}
+
+/**
+ * Runs the rules of a plugin on the AST of a module. A rule returns from
+ * `create` an object of visitors by node type, which are called for each node
+ * of that type while the AST is walked.
+ * @param {any} plugin
+ * @param {string} fileName
+ * @param {string} sourceText
+ * @param {any} program The SWC AST of the module.
+ * @returns {LintPluginDiagnostic[]}
+ */
+function runLintPlugin(plugin, fileName, sourceText, program) {
+  assertLintPlugin(plugin);
+  const diagnostics = [];
+  const visitors = [];
+  for (const ruleCode of new SafeArrayIterator(ObjectKeys(plugin.rules))) {
+    const code = `${plugin.name}/${ruleCode}`;
+    const context = {
+      fileName,
+      sourceText,
+      report({ node, span = node?.span, message, hint }) {
+        if (typeof message !== "string") {
+          throw new TypeError(`The rule "${code}" reported no message.`);
+        }
+        if (
+          typeof span?.start !== "number" || typeof span?.end !== "number"
+        ) {
+          throw new TypeError(`The rule "${code}" reported no node or span.`);
+        }
+        ArrayPrototypePush(diagnostics, {
+          code,
+          message,
+          hint: typeof hint === "string" ? hint : null,
+          start: span.start,
+          end: span.end,
+        });
+      },
+    };
+    const visitor = plugin.rules[ruleCode].create(context);
+    if (visitor !== null && typeof visitor === "object") {
+      ArrayPrototypePush(visitors, visitor);
+    }
+  }
+  walk(program, visitors);
+  return diagnostics;
+}
+
+function walk(node, visitors) {
+  if (ArrayIsArray(node)) {
+    for (const item of new SafeArrayIterator(node)) {
+      walk(item, visitors);
+    }
+    return;
+  }
+  if (node === null || typeof node !== "object") {
+    return;
+  }
+  if (typeof node.type === "string") {
+    for (const visitor of new SafeArrayIterator(visitors)) {
+      const visit = visitor[node.type];
+      if (typeof visit === "function") {
+        visit(node);
+      }
+    }
+  }
+  for (const key of new SafeArrayIterator(ObjectKeys(node))) {
+    if (key !== "span") {
+      walk(node[key], visitors);
+    }
+  }
+}
+
+internals.runLintPlugin = runLintPlugin;
@@ -3,15 +3,17 @@
 const core = globalThis.Deno.core;
 const ops = core.ops;
 import { setExitHandler } from "ext:runtime/30_os.js";
-import { Console } from "ext:deno_console/01_console.js";
+import { Console, inspect } from "ext:deno_console/01_console.js";
 import { serializePermissions } from "ext:runtime/10_permissions.js";
-import { assert } from "ext:deno_web/00_infra.js";
+import { assert, AssertionError } from "ext:deno_web/00_infra.js";
 const primordials = globalThis.__bootstrap.primordials;
 const {
+  ArrayIsArray,
   ArrayPrototypeFilter,
   ArrayPrototypeJoin,
   ArrayPrototypePush,
   ArrayPrototypeShift,
+  ArrayPrototypeSome,
   DateNow,
   Error,
   FunctionPrototype,
@@ -20,12 +22,15 @@ const {
   MapPrototypeHas,
   MapPrototypeSet,
   MathCeil,
+  MathSqrt,
+  NumberIsSafeInteger,
   ObjectKeys,
   ObjectHasOwn,
   ObjectPrototypeIsPrototypeOf,
   Promise,
   SafeArrayIterator,
   Set,
+  StringPrototypeReplaceAll,
   SymbolToStringTag,
   TypeError,
 } = primordials;
@@ -254,6 +259,8 @@ function prettyResourceNames(name) {
       return ["A TCP listener", "opened", "closed"];
     case "udpSocket":
       return ["A UDP socket", "opened", "closed"];
+    case "dnsResolver":
+      return ["A DNS resolver", "created", "closed"];
     case "timer":
       return ["A timer", "started", "fired/cleared"];
     case "textDecoder":
@@ -321,6 +328,8 @@ function resourceCloseHint(name) {
       return "Close the TCP listener by calling `tcpListener.close()`.";
     case "udpSocket":
       return "Close the UDP socket by calling `udpSocket.close()`.";
+    case "dnsResolver":
+      return "Close the DNS resolver by calling `resolver.close()`.";
     case "timer":
       return "Clear the timer by calling `clearInterval` or `clearTimeout`.";
     case "textDecoder":
@@ -525,6 +534,10 @@ function withPermissions(fn, permissions) {
  *   location: TestLocation,
  *   ignore: boolean,
  *   only: boolean.
+ *   tags: string[],
+ *   retries?: number,
+ *   timeout?: number,
+ *   isolationGroup?: string,
  *   sanitizeOps: boolean,
  *   sanitizeResources: boolean,
  *   sanitizeExit: boolean,
@@ -589,6 +602,7 @@ function test(
   const defaults = {
     ignore: false,
     only: false,
+    tags: [],
     sanitizeOps: true,
     sanitizeResources: true,
     sanitizeExit: true,
@@ -665,6 +679,35 @@ function test(
     testDesc = { ...defaults, ...nameOrFnOrOptions, fn, name };
   }
 
+  if (
+    !ArrayIsArray(testDesc.tags) ||
+    ArrayPrototypeSome(testDesc.tags, (tag) => typeof tag !== "string")
+  ) {
+    throw new TypeError("The test tags must be an array of strings");
+  }
+
+  if (
+    testDesc.retries !== undefined &&
+    (!NumberIsSafeInteger(testDesc.retries) || testDesc.retries < 0)
+  ) {
+    throw new TypeError("The test retries must be a non-negative integer");
+  }
+
+  if (
+    testDesc.timeout !== undefined &&
+    (!NumberIsSafeInteger(testDesc.timeout) || testDesc.timeout <= 0)
+  ) {
+    throw new TypeError("The test timeout must be a positive integer");
+  }
+
+  if (
+    testDesc.isolationGroup !== undefined &&
+    (typeof testDesc.isolationGroup !== "string" ||
+      testDesc.isolationGroup === "")
+  ) {
+    throw new TypeError("The test isolation group must be a non-empty string");
+  }
+
   // Delete this prop in case the user passed it. It's used to detect steps.
   delete testDesc.parent;
   const jsError = core.destructureError(new Error());
@@ -791,10 +834,16 @@ function compareMeasurements(a, b) {
 }
 
 function benchStats(n, highPrecision, avg, min, max, all) {
+  const mean = avg / n;
+  let squaredDeviations = 0;
+  for (let i = 0; i < all.length; i++) {
+    squaredDeviations += (all[i] - mean) ** 2;
+  }
   return {
     n,
     min,
     max,
+    stddev: n > 1 ? MathSqrt(squaredDeviations / (n - 1)) : 0,
     p75: all[MathCeil(n * (75 / 100)) - 1],
     p99: all[MathCeil(n * (99 / 100)) - 1],
     p995: all[MathCeil(n * (99.5 / 100)) - 1],
@@ -957,6 +1006,18 @@ function benchNow() {
   return ops.op_bench_now();
 }
 
+let setupContext;
+let setupContextLoaded = false;
+
+/** The value returned by the global setup module of the run, if any. */
+function getSetupContext() {
+  if (!setupContextLoaded) {
+    setupContext = ops.op_test_setup_context();
+    setupContextLoaded = true;
+  }
+  return setupContext;
+}
+
 function getFullName(desc) {
   if ("parent" in desc) {
     return `${getFullName(desc.parent)} ... ${desc.name}`;
@@ -995,6 +1056,8 @@ function createTestContext(desc) {
     rootId = desc.id;
     rootName = desc.name;
   }
+  const snapshotName = getSnapshotName(desc);
+  let snapshotCount = 0;
   return {
     [SymbolToStringTag]: "TestContext",
     /**
@@ -1009,6 +1072,10 @@ function createTestContext(desc) {
      * File Uri of the test code.
      */
     origin: desc.origin,
+    /**
+     * The value returned by the global setup module of the run.
+     */
+    setupContext: getSetupContext(),
     /**
      * @param nameOrFnOrOptions {string | TestStepDefinition | ((t: TestContext) => void | Promise<void>)}
      * @param maybeFn {((t: TestContext) => void | Promise<void>) | undefined}
@@ -1088,9 +1155,55 @@ function createTestContext(desc) {
       stepReportResult(stepDesc, result, elapsed);
       return result == "ok";
     },
+
+    /**
+     * @param value {unknown}
+     */
+    assertSnapshot(value) {
+      snapshotCount++;
+      const message = ops.op_test_assert_snapshot(
+        `${snapshotName} ${snapshotCount}`,
+        serializeSnapshot(value),
+      );
+      if (message !== null) {
+        throw new AssertionError(message);
+      }
+    },
   };
 }
 
+/**
+ * The name of the snapshots of a test or step, after the steps that it is
+ * nested in.
+ * @param desc {TestDescription | TestStepDescription}
+ * @returns {string}
+ */
+function getSnapshotName(desc) {
+  let name = desc.name;
+  for (let parent = desc.parent; parent; parent = parent.parent) {
+    name = `${parent.name} > ${name}`;
+  }
+  return name;
+}
+
+/**
+ * @param value {unknown}
+ * @returns {string}
+ */
+function serializeSnapshot(value) {
+  const serialized = inspect(value, {
+    depth: Infinity,
+    sorted: true,
+    trailingComma: true,
+    compact: false,
+    iterableLimit: Infinity,
+    strAbbreviateSize: Infinity,
+    breakLength: Infinity,
+  });
+  // Keep the line breaks of strings readable in the snapshot files.
+  return StringPrototypeReplaceAll(serialized, "\\n", "\n");
+}
+
 /**
  * Wrap a user test function in one which returns a structured result.
  * @template T {Function}
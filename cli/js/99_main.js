@@ -1,3 +1,5 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+import "ext:cli/40_fmt.js";
+import "ext:cli/40_lint.js";
 import "ext:cli/40_testing.js";
 import "ext:cli/runtime/js/99_main.js";
@@ -5,7 +5,11 @@ use super::documents::Documents;
 use super::language_server;
 use super::tsc;
 
+use crate::args::LintSeverity;
+use crate::args::LintSeverityConfig;
 use crate::tools::lint::create_linter;
+use crate::tools::lint::LintPluginHost;
+use crate::util::path::specifier_to_file_path;
 
 use deno_ast::SourceRange;
 use deno_ast::SourceRangedForSpanned;
@@ -66,6 +70,8 @@ pub enum Category {
     message: String,
     code: String,
     hint: Option<String>,
+    /// The configured severity of the rule, which is a warning if unset.
+    severity: Option<LintSeverity>,
   },
 }
 
@@ -83,9 +89,13 @@ impl Reference {
         message,
         code,
         hint,
+        severity,
       } => lsp::Diagnostic {
         range: self.range,
-        severity: Some(lsp::DiagnosticSeverity::WARNING),
+        severity: Some(match severity {
+          Some(LintSeverity::Error) => lsp::DiagnosticSeverity::ERROR,
+          Some(LintSeverity::Warn) | None => lsp::DiagnosticSeverity::WARNING,
+        }),
         code: Some(lsp::NumberOrString::String(code.to_string())),
         code_description: None,
         source: Some("deno-lint".to_string()),
@@ -118,18 +128,35 @@ fn as_lsp_range(range: &deno_lint::diagnostic::Range) -> Range {
   }
 }
 
-pub fn get_lint_references(
+pub async fn get_lint_references(
   parsed_source: &deno_ast::ParsedSource,
   lint_rules: Vec<&'static dyn LintRule>,
+  lint_severity: &LintSeverityConfig,
+  maybe_plugin_host: Option<&LintPluginHost>,
 ) -> Result<Vec<Reference>, AnyError> {
-  let linter = create_linter(parsed_source.media_type(), lint_rules);
-  let lint_diagnostics = linter.lint_with_ast(parsed_source);
+  let mut lint_diagnostics =
+    create_linter(parsed_source.media_type(), lint_rules)
+      .lint_with_ast(parsed_source);
+  if let Some(plugin_host) = maybe_plugin_host {
+    match plugin_host.lint(parsed_source).await {
+      Ok(diagnostics) => lint_diagnostics.extend(diagnostics),
+      Err(err) => lsp_warn!(
+        "Unable to run the lint plugins on {}: {:#}",
+        parsed_source.specifier(),
+        err
+      ),
+    }
+  }
 
+  let maybe_file_path = specifier_to_file_path(parsed_source.specifier()).ok();
   Ok(
     lint_diagnostics
       .into_iter()
       .map(|d| Reference {
         category: Category::Lint {
+          severity: maybe_file_path
+            .as_ref()
+            .and_then(|path| lint_severity.get(path, &d.code)),
           message: d.message,
           code: d.code,
           hint: d.hint,
@@ -757,6 +784,7 @@ mod tests {
             message: "message1".to_string(),
             code: "code1".to_string(),
             hint: None,
+            severity: None,
           },
           range,
         },
@@ -775,12 +803,13 @@ mod tests {
             message: "message2".to_string(),
             code: "code2".to_string(),
             hint: Some("hint2".to_string()),
+            severity: Some(LintSeverity::Error),
           },
           range,
         },
         lsp::Diagnostic {
           range,
-          severity: Some(lsp::DiagnosticSeverity::WARNING),
+          severity: Some(lsp::DiagnosticSeverity::ERROR),
           code: Some(lsp::NumberOrString::String("code2".to_string())),
           source: Some("deno-lint".to_string()),
           message: "message2\nhint2".to_string(),
@@ -13,10 +13,13 @@ use super::performance::Performance;
 use super::tsc;
 use super::tsc::TsServer;
 
+use crate::args::flags_from_vec;
 use crate::args::LintOptions;
+use crate::factory::CliFactory;
 use crate::graph_util;
 use crate::graph_util::enhanced_resolution_error_message;
 use crate::tools::lint::get_configured_rules;
+use crate::tools::lint::LintPluginHost;
 
 use deno_ast::MediaType;
 use deno_core::anyhow::anyhow;
@@ -202,6 +205,7 @@ impl DiagnosticsServer {
         let mut lint_handle: Option<tokio::task::JoinHandle<()>> = None;
         let mut deps_handle: Option<tokio::task::JoinHandle<()>> = None;
         let diagnostics_publisher = DiagnosticsPublisher::new(client.clone());
+        let mut lint_plugins = LintPlugins::default();
 
         loop {
           match rx.recv().await {
@@ -293,6 +297,8 @@ impl DiagnosticsServer {
                 }
               }));
 
+              let maybe_plugin_host =
+                lint_plugins.get(&lint_options.plugins).await;
               let previous_lint_handle = lint_handle.take();
               lint_handle = Some(tokio::spawn({
                 let performance = performance.clone();
@@ -310,6 +316,7 @@ impl DiagnosticsServer {
                     &snapshot,
                     &config,
                     &lint_options,
+                    maybe_plugin_host.as_deref(),
                     token.clone(),
                   )
                   .await;
@@ -449,10 +456,48 @@ fn ts_json_to_diagnostics(
     .collect()
 }
 
+/// The plugins of the lint configuration, which are loaded again when the
+/// configuration changes them.
+#[derive(Default)]
+struct LintPlugins {
+  specifiers: Vec<ModuleSpecifier>,
+  maybe_host: Option<Arc<LintPluginHost>>,
+}
+
+impl LintPlugins {
+  async fn get(
+    &mut self,
+    specifiers: &[ModuleSpecifier],
+  ) -> Option<Arc<LintPluginHost>> {
+    if self.specifiers != specifiers {
+      self.specifiers = specifiers.to_vec();
+      self.maybe_host = None;
+      if !specifiers.is_empty() {
+        match start_lint_plugin_host(self.specifiers.clone()).await {
+          Ok(host) => self.maybe_host = Some(Arc::new(host)),
+          Err(err) => error!("Unable to load the lint plugins: {:#}", err),
+        }
+      }
+    }
+    self.maybe_host.clone()
+  }
+}
+
+async fn start_lint_plugin_host(
+  specifiers: Vec<ModuleSpecifier>,
+) -> Result<LintPluginHost, AnyError> {
+  let flags = flags_from_vec(vec!["deno".to_string(), "lint".to_string()])?;
+  let factory = CliFactory::from_flags(flags).await?;
+  let worker_factory =
+    Arc::new(factory.create_cli_main_worker_factory().await?);
+  LintPluginHost::start(worker_factory, specifiers).await
+}
+
 async fn generate_lint_diagnostics(
   snapshot: &language_server::StateSnapshot,
   config: &ConfigSnapshot,
   lint_options: &LintOptions,
+  maybe_plugin_host: Option<&LintPluginHost>,
   token: CancellationToken,
 ) -> DiagnosticVec {
   let documents = snapshot
@@ -483,18 +528,21 @@ async fn generate_lint_diagnostics(
           config,
           lint_options,
           lint_rules.clone(),
+          maybe_plugin_host,
           &document,
-        ),
+        )
+        .await,
       ));
     }
   }
   diagnostics_vec
 }
 
-fn generate_document_lint_diagnostics(
+async fn generate_document_lint_diagnostics(
   config: &ConfigSnapshot,
   lint_options: &LintOptions,
   lint_rules: Vec<&'static dyn LintRule>,
+  maybe_plugin_host: Option<&LintPluginHost>,
   document: &Document,
 ) -> Vec<lsp::Diagnostic> {
   if !config.specifier_enabled(document.specifier()) {
@@ -505,8 +553,13 @@ fn generate_document_lint_diagnostics(
   }
   match document.maybe_parsed_source() {
     Some(Ok(parsed_source)) => {
-      if let Ok(references) =
-        analysis::get_lint_references(&parsed_source, lint_rules)
+      if let Ok(references) = analysis::get_lint_references(
+        &parsed_source,
+        lint_rules,
+        &lint_options.severity,
+        maybe_plugin_host,
+      )
+      .await
       {
         references
           .into_iter()
@@ -65,6 +65,7 @@ use super::tsc::AssetsSnapshot;
 use super::tsc::TsServer;
 use super::urls;
 use super::urls::LspClientUrl;
+use crate::args::flags_from_vec;
 use crate::args::get_root_cert_store;
 use crate::args::package_json;
 use crate::args::resolve_import_map_from_specifier;
@@ -74,6 +75,8 @@ use crate::args::CliOptions;
 use crate::args::ConfigFile;
 use crate::args::Flags;
 use crate::args::FmtOptions;
+use crate::args::FmtOptionsConfig;
+use crate::args::FmtPluginConfig;
 use crate::args::LintOptions;
 use crate::args::TsConfig;
 use crate::cache::DenoDir;
@@ -90,6 +93,7 @@ use crate::npm::NpmCache;
 use crate::npm::NpmResolution;
 use crate::tools::fmt::format_file;
 use crate::tools::fmt::format_parsed_source;
+use crate::tools::fmt::FmtPluginHost;
 use crate::util::fs::remove_dir_all_if_exists;
 use crate::util::path::specifier_to_file_path;
 use crate::util::progress_bar::ProgressBar;
@@ -153,6 +157,9 @@ pub struct Inner {
   maybe_package_json: Option<PackageJson>,
   /// Configuration for formatter which has been taken from specified config file.
   fmt_options: FmtOptions,
+  /// The plugins of the formatter configuration, which are loaded when a file
+  /// is formatted.
+  fmt_plugins: tokio::sync::Mutex<FmtPlugins>,
   /// An optional configuration for linter which has been taken from specified config file.
   lint_options: LintOptions,
   /// A lazily create "server" for handling test run requests.
@@ -513,6 +520,7 @@ impl Inner {
       maybe_import_map_uri: None,
       maybe_package_json: None,
       fmt_options: Default::default(),
+      fmt_plugins: Default::default(),
       lint_options: Default::default(),
       maybe_testing_server: None,
       module_registries,
@@ -1466,24 +1474,34 @@ impl Inner {
       return Ok(None);
     }
 
-    let format_result = match document.maybe_parsed_source() {
-      Some(Ok(parsed_source)) => {
-        format_parsed_source(&parsed_source, &self.fmt_options.options)
-      }
-      Some(Err(err)) => Err(anyhow!("{}", err)),
-      None => {
-        // the file path is only used to determine what formatter should
-        // be used to format the file, so give the filepath an extension
-        // that matches what the user selected as the language
-        let file_path = document
-          .maybe_language_id()
-          .and_then(|id| id.as_extension())
-          .map(|ext| file_path.with_extension(ext))
-          .unwrap_or(file_path);
-        // it's not a js/ts file, so attempt to format its contents
-        format_file(&file_path, &document.content(), &self.fmt_options.options)
-      }
-    };
+    let maybe_plugin_host =
+      self.fmt_plugins.lock().await.get(&self.fmt_options).await;
+    let format_result =
+      match maybe_plugin_host.filter(|host| host.supports(&file_path)) {
+        Some(host) => host.format(&file_path, &document.content()).await,
+        None => match document.maybe_parsed_source() {
+          Some(Ok(parsed_source)) => {
+            format_parsed_source(&parsed_source, &self.fmt_options.options)
+          }
+          Some(Err(err)) => Err(anyhow!("{}", err)),
+          None => {
+            // the file path is only used to determine what formatter should
+            // be used to format the file, so give the filepath an extension
+            // that matches what the user selected as the language
+            let file_path = document
+              .maybe_language_id()
+              .and_then(|id| id.as_extension())
+              .map(|ext| file_path.with_extension(ext))
+              .unwrap_or(file_path);
+            // it's not a js/ts file, so attempt to format its contents
+            format_file(
+              &file_path,
+              &document.content(),
+              &self.fmt_options.options,
+            )
+          }
+        },
+      };
 
     let text_edits = match format_result {
       Ok(Some(new_text)) => Some(text::get_edits(
@@ -3203,6 +3221,46 @@ impl tower_lsp::LanguageServer for LanguageServer {
   }
 }
 
+/// The plugins of the formatter configuration, which are loaded again when the
+/// configuration changes them or their global options.
+#[derive(Debug, Default)]
+struct FmtPlugins {
+  plugins: Vec<FmtPluginConfig>,
+  options: FmtOptionsConfig,
+  maybe_host: Option<Arc<FmtPluginHost>>,
+}
+
+impl FmtPlugins {
+  async fn get(
+    &mut self,
+    fmt_options: &FmtOptions,
+  ) -> Option<Arc<FmtPluginHost>> {
+    if self.plugins != fmt_options.plugins
+      || self.options != fmt_options.options
+    {
+      self.plugins = fmt_options.plugins.clone();
+      self.options = fmt_options.options.clone();
+      self.maybe_host = None;
+      if !self.plugins.is_empty() {
+        match start_fmt_plugin_host(self.plugins.clone(), &self.options).await {
+          Ok(host) => self.maybe_host = Some(Arc::new(host)),
+          Err(err) => error!("Unable to load the fmt plugins: {:#}", err),
+        }
+      }
+    }
+    self.maybe_host.clone()
+  }
+}
+
+async fn start_fmt_plugin_host(
+  plugins: Vec<FmtPluginConfig>,
+  fmt_options: &FmtOptionsConfig,
+) -> Result<FmtPluginHost, AnyError> {
+  let flags = flags_from_vec(vec!["deno".to_string(), "fmt".to_string()])?;
+  let factory = CliFactory::from_flags(flags).await?;
+  FmtPluginHost::start(&factory, plugins, fmt_options).await
+}
+
 struct PrepareCacheResult {
   cli_options: CliOptions,
   roots: Vec<ModuleSpecifier>,
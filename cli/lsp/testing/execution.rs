@@ -223,7 +223,7 @@ impl TestRun {
     // `PermissionsContainer` - otherwise granting/revoking permissions in one
     // file would have impact on other files, which is undesirable.
     let permissions =
-      Permissions::from_options(&factory.cli_options().permissions_options())?;
+      Permissions::from_options(&factory.cli_options().permissions_options()?)?;
     test::check_specifiers(
       factory.cli_options(),
       factory.file_fetcher()?,
@@ -281,6 +281,8 @@ impl TestRun {
         exclude: lsp_filter
           .map(|f| f.exclude.values().map(|t| t.name.clone()).collect())
           .unwrap_or_default(),
+        tags: vec![],
+        skip_tags: vec![],
       };
       let token = self.token.clone();
 
@@ -299,8 +301,13 @@ impl TestRun {
             sender.clone(),
             fail_fast_tracker,
             &test::TestSpecifierOptions {
+              concurrent_groups: NonZeroUsize::new(concurrent_jobs).unwrap(),
               filter,
               shuffle: None,
+              shard: None,
+              retries: 0,
+              snapshots: test::TestSnapshots::new(false),
+              setup_context: Default::default(),
               trace_ops: false,
             },
           ))
@@ -374,6 +381,9 @@ impl TestRun {
 
               reporter.report_result(&description, &result, elapsed);
             }
+            test::TestEvent::Retry(..) => {
+              // The test is run again, and only its final result is reported.
+            }
             test::TestEvent::UncaughtError(origin, error) => {
               reporter.report_uncaught_error(&origin, &error);
               summary.failed += 1;
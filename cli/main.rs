@@ -43,6 +43,7 @@ use crate::util::v8::init_v8_flags;
 
 use args::CliOptions;
 use deno_core::anyhow::Context;
+use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::error::JsError;
 use deno_runtime::colors;
@@ -52,6 +53,7 @@ use factory::CliFactory;
 use std::env;
 use std::env::current_exe;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 async fn run_subcommand(flags: Flags) -> Result<i32, AnyError> {
   match flags.subcommand.clone() {
@@ -105,9 +107,22 @@ async fn run_subcommand(flags: Flags) -> Result<i32, AnyError> {
       Ok(0)
     }
     DenoSubcommand::Fmt(fmt_flags) => {
-      let cli_options = CliOptions::from_flags(flags.clone())?;
+      let cli_options = Arc::new(CliOptions::from_flags(flags.clone())?);
+      // the workspace members are formatted with their own configuration
+      let member_fmt_options =
+        cli_options.resolve_workspace_fmt_options(&fmt_flags)?;
       let fmt_options = cli_options.resolve_fmt_options(fmt_flags)?;
-      tools::fmt::format(cli_options, fmt_options).await?;
+      // every member is formatted or checked, even when another one failed
+      let mut errors = vec![];
+      for fmt_options in std::iter::once(fmt_options).chain(member_fmt_options)
+      {
+        if let Err(err) =
+          tools::fmt::format(cli_options.clone(), fmt_options).await
+        {
+          errors.push(err);
+        }
+      }
+      combine_errors(errors)?;
       Ok(0)
     }
     DenoSubcommand::Init(init_flags) => {
@@ -134,12 +149,33 @@ async fn run_subcommand(flags: Flags) -> Result<i32, AnyError> {
       if lint_flags.rules {
         tools::lint::print_rules_list(lint_flags.json);
       } else {
-        let cli_options = CliOptions::from_flags(flags)?;
+        let cli_options = Arc::new(CliOptions::from_flags(flags)?);
+        // the workspace members are linted with their own configuration
+        let member_lint_options =
+          cli_options.resolve_workspace_lint_options(&lint_flags)?;
         let lint_options = cli_options.resolve_lint_options(lint_flags)?;
-        tools::lint::lint(cli_options, lint_options).await?;
+        // every member is linted, even when another one has problems
+        let mut has_problems = false;
+        let mut errors = vec![];
+        for lint_options in
+          std::iter::once(lint_options).chain(member_lint_options)
+        {
+          match tools::lint::lint(cli_options.clone(), lint_options).await {
+            Ok(member_has_problems) => has_problems |= member_has_problems,
+            Err(err) => errors.push(err),
+          }
+        }
+        combine_errors(errors)?;
+        if has_problems {
+          return Ok(1);
+        }
       }
       Ok(0)
     }
+    DenoSubcommand::Permissions(permissions_flags) => {
+      tools::permissions::permissions(flags, permissions_flags).await?;
+      Ok(0)
+    }
     DenoSubcommand::Repl(repl_flags) => {
       tools::repl::run(flags, repl_flags).await
     }
@@ -219,6 +255,22 @@ fn setup_panic_hook() {
   }));
 }
 
+/// Fails with the errors of the root and of the members of a workspace,
+/// which are all reported.
+fn combine_errors(mut errors: Vec<AnyError>) -> Result<(), AnyError> {
+  match errors.len() {
+    0 => Ok(()),
+    1 => Err(errors.remove(0)),
+    _ => Err(generic_error(
+      errors
+        .iter()
+        .map(|err| format!("{err:#}"))
+        .collect::<Vec<_>>()
+        .join("\n"),
+    )),
+  }
+}
+
 fn unwrap_or_exit<T>(result: Result<T, AnyError>) -> T {
   match result {
     Ok(value) => value,
@@ -263,7 +315,9 @@ pub fn main() {
       match standalone::extract_standalone(&current_exe_path, args.clone())
         .await
       {
-        Ok(Some((metadata, eszip))) => standalone::run(eszip, metadata).await,
+        Ok(Some((metadata, eszip, assets_data))) => {
+          standalone::run(eszip, metadata, assets_data).await
+        }
         Ok(None) => Ok(()),
         Err(err) => Err(err),
       };
@@ -282,10 +336,20 @@ pub fn main() {
       Err(err) => unwrap_or_exit(Err(AnyError::from(err))),
     };
 
-    init_v8_flags(&flags.v8_flags, get_v8_flags_from_env());
+    let mut v8_flags = flags.v8_flags.clone();
+    // The JavaScript stack of the audited permission checks can't be captured
+    // in fast API calls.
+    if flags.permission_audit.is_some() {
+      v8_flags.push("--no-turbo-fast-api-calls".to_string());
+    }
+    init_v8_flags(&v8_flags, get_v8_flags_from_env());
 
     util::logger::init(flags.log_level);
 
+    if let Some(permission_audit) = &flags.permission_audit {
+      deno_runtime::permissions::set_audit_log(permission_audit)?;
+    }
+
     run_subcommand(flags).await
   };
 
@@ -4,11 +4,14 @@ use crate::tools::test::TestDescription;
 use crate::tools::test::TestEvent;
 use crate::tools::test::TestEventSender;
 use crate::tools::test::TestLocation;
+use crate::tools::test::TestSetupContext;
+use crate::tools::test::TestSnapshots;
 use crate::tools::test::TestStepDescription;
 
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::op;
+use deno_core::serde_json;
 use deno_core::serde_v8;
 use deno_core::v8;
 use deno_core::ModuleSpecifier;
@@ -19,6 +22,7 @@ use deno_runtime::permissions::PermissionsContainer;
 use serde::Deserialize;
 use serde::Deserializer;
 use serde::Serialize;
+use std::collections::HashMap;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use uuid::Uuid;
@@ -28,6 +32,24 @@ pub(crate) struct TestContainer(
   pub Vec<(TestDescription, v8::Global<v8::Function>)>,
 );
 
+/// The ids of the tests that are run in another isolate than the one that
+/// registered them first, by their name and location, so that they are reported
+/// as the same test. These are the tests that are run again after failing, and
+/// the tests of the isolation groups.
+#[derive(Clone, Default)]
+pub(crate) struct TestRetryIds(pub HashMap<(String, TestLocation), usize>);
+
+impl TestRetryIds {
+  pub fn new(tests: Vec<TestDescription>) -> Self {
+    Self(
+      tests
+        .into_iter()
+        .map(|desc| ((desc.name, desc.location), desc.id))
+        .collect(),
+    )
+  }
+}
+
 deno_core::extension!(deno_test,
   ops = [
     op_pledge_test_permissions,
@@ -35,12 +57,20 @@ deno_core::extension!(deno_test,
     op_register_test,
     op_register_test_step,
     op_dispatch_test_event,
+    op_test_assert_snapshot,
+    op_test_setup_context,
   ],
   options = {
     sender: TestEventSender,
+    retry_ids: TestRetryIds,
+    snapshots: TestSnapshots,
+    setup_context: TestSetupContext,
   },
   state = |state, options| {
     state.put(options.sender);
+    state.put(options.retry_ids);
+    state.put(options.snapshots);
+    state.put(options.setup_context);
     state.put(TestContainer::default());
   },
   customizer = |ext: &mut deno_core::ExtensionBuilder| {
@@ -104,6 +134,11 @@ struct TestInfo<'s> {
   ignore: bool,
   #[serde(default)]
   only: bool,
+  #[serde(default)]
+  tags: Vec<String>,
+  retries: Option<usize>,
+  timeout: Option<u64>,
+  isolation_group: Option<String>,
   location: TestLocation,
 }
 
@@ -122,13 +157,22 @@ fn op_register_test<'a>(
   state: &mut OpState,
   info: TestInfo<'a>,
 ) -> Result<TestRegisterResult, AnyError> {
-  let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+  let id = state
+    .borrow::<TestRetryIds>()
+    .0
+    .get(&(info.name.clone(), info.location.clone()))
+    .copied()
+    .unwrap_or_else(|| NEXT_ID.fetch_add(1, Ordering::SeqCst));
   let origin = state.borrow::<ModuleSpecifier>().to_string();
   let description = TestDescription {
     id,
     name: info.name,
     ignore: info.ignore,
     only: info.only,
+    tags: info.tags,
+    retries: info.retries,
+    timeout: info.timeout,
+    isolation_group: info.isolation_group,
     origin: origin.clone(),
     location: info.location,
   };
@@ -202,3 +246,23 @@ fn op_dispatch_test_event(
   sender.send(event).ok();
   Ok(())
 }
+
+/// Returns the message of the assertion error if the value doesn't match its
+/// snapshot.
+#[op]
+fn op_test_assert_snapshot(
+  state: &mut OpState,
+  name: String,
+  actual: String,
+) -> Result<Option<String>, AnyError> {
+  let specifier = state.borrow::<ModuleSpecifier>();
+  state
+    .borrow::<TestSnapshots>()
+    .assert(specifier, &name, &actual)
+}
+
+/// Returns the value returned by the global setup module of the test run.
+#[op]
+fn op_test_setup_context(state: &mut OpState) -> serde_json::Value {
+  state.borrow::<TestSetupContext>().value().clone()
+}
@@ -5,8 +5,10 @@ use std::io::Seek;
 use std::io::SeekFrom;
 use std::io::Write;
 use std::path::Path;
+use std::path::PathBuf;
 
 use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_core::futures::io::AllowStdIo;
@@ -28,8 +30,18 @@ use crate::http_util::HttpClient;
 use crate::util::progress_bar::ProgressBar;
 use crate::util::progress_bar::ProgressBarStyle;
 
+use super::sign;
+
 const MAGIC_TRAILER: &[u8; 8] = b"d3n0l4nd";
 
+/// The targets whose lite runtime is published, see the "Pre-release lite"
+/// steps of `.github/workflows/ci.generate.ts`.
+const LITE_TARGETS: &[&str] = &[
+  "x86_64-unknown-linux-gnu",
+  "x86_64-pc-windows-msvc",
+  "x86_64-apple-darwin",
+];
+
 #[derive(Deserialize, Serialize)]
 pub struct Metadata {
   pub argv: Vec<String>,
@@ -44,25 +56,55 @@ pub struct Metadata {
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
   pub maybe_import_map: Option<(Url, String)>,
   pub entrypoint: ModuleSpecifier,
+  /// The files included with `--include` that aren't modules, whose contents
+  /// follow each other, in this order, before the metadata.
+  pub assets: Vec<EmbeddedAsset>,
 }
 
+/// A file that is embedded in the binary, which is read at the path that it
+/// had when it was compiled.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EmbeddedAsset {
+  pub path: PathBuf,
+  pub len: u64,
+}
+
+/// Writes the binary with the payload appended, which ends at a multiple of
+/// [`sign::PAYLOAD_ALIGNMENT`]. When the binary will be signed, the signature
+/// of the original binary is removed and the layout is prepared for the new
+/// one, see [`sign`].
 pub fn write_binary_bytes(
   writer: &mut impl Write,
-  original_bin: Vec<u8>,
+  mut original_bin: Vec<u8>,
   metadata: &Metadata,
   eszip: eszip::EszipV2,
+  assets_data: &[u8],
+  prepare_for_signing: bool,
 ) -> Result<(), AnyError> {
   let metadata = serde_json::to_string(metadata)?.as_bytes().to_vec();
   let eszip_archive = eszip.into_bytes();
 
-  let eszip_pos = original_bin.len();
-  let metadata_pos = eszip_pos + eszip_archive.len();
+  if prepare_for_signing {
+    sign::remove_signature(&mut original_bin)?;
+  }
+  let payload_len =
+    eszip_archive.len() + assets_data.len() + metadata.len() + 24;
+  let padding = (sign::PAYLOAD_ALIGNMENT
+    - (original_bin.len() + payload_len) % sign::PAYLOAD_ALIGNMENT)
+    % sign::PAYLOAD_ALIGNMENT;
+  let eszip_pos = original_bin.len() + padding;
+  if prepare_for_signing {
+    sign::extend_linkedit(&mut original_bin, (eszip_pos + payload_len) as u64)?;
+  }
+  let metadata_pos = eszip_pos + eszip_archive.len() + assets_data.len();
   let mut trailer = MAGIC_TRAILER.to_vec();
   trailer.write_all(&eszip_pos.to_be_bytes())?;
   trailer.write_all(&metadata_pos.to_be_bytes())?;
 
   writer.write_all(&original_bin)?;
+  writer.write_all(&vec![0; padding])?;
   writer.write_all(&eszip_archive)?;
+  writer.write_all(assets_data)?;
   writer.write_all(&metadata)?;
   writer.write_all(&trailer)?;
 
@@ -73,7 +115,12 @@ pub fn is_standalone_binary(exe_path: &Path) -> bool {
   let Ok(mut output_file) = std::fs::File::open(exe_path) else {
     return false;
   };
-  if output_file.seek(SeekFrom::End(-24)).is_err() {
+  let Ok(payload_end) = sign::payload_end(&mut output_file) else {
+    return false;
+  };
+  if payload_end < 24
+    || output_file.seek(SeekFrom::Start(payload_end - 24)).is_err()
+  {
     // This seek may fail because the file is too small to possibly be
     // `deno compile` output.
     return false;
@@ -88,23 +135,29 @@ pub fn is_standalone_binary(exe_path: &Path) -> bool {
 
 /// This function will try to run this binary as a standalone binary
 /// produced by `deno compile`. It determines if this is a standalone
-/// binary by checking for the magic trailer string `d3n0l4nd` at EOF-24 (8 bytes * 3).
+/// binary by checking for the magic trailer string `d3n0l4nd` at EOF-24 (8 bytes * 3),
+/// or before the signature of a signed binary.
 /// The magic trailer is followed by:
 /// - a u64 pointer to the JS bundle embedded in the binary
 /// - a u64 pointer to JSON metadata (serialized flags) embedded in the binary
 /// These are dereferenced, and the bundle is executed under the configuration
-/// specified by the metadata. If no magic trailer is present, this function
-/// exits with `Ok(None)`.
+/// specified by the metadata. The embedded assets are right before the
+/// metadata. If no magic trailer is present, this function exits with
+/// `Ok(None)`.
 pub async fn extract_standalone(
   exe_path: &Path,
   cli_args: Vec<String>,
-) -> Result<Option<(Metadata, eszip::EszipV2)>, AnyError> {
-  let file = std::fs::File::open(exe_path)?;
+) -> Result<Option<(Metadata, eszip::EszipV2, Vec<u8>)>, AnyError> {
+  let mut file = std::fs::File::open(exe_path)?;
+  let payload_end = sign::payload_end(&mut file)?;
+  if payload_end < 24 {
+    return Ok(None);
+  }
 
   let mut bufreader =
     deno_core::futures::io::BufReader::new(AllowStdIo::new(file));
 
-  let trailer_pos = bufreader.seek(SeekFrom::End(-24)).await?;
+  let trailer_pos = bufreader.seek(SeekFrom::Start(payload_end - 24)).await?;
   let mut trailer = [0; 24];
   bufreader.read_exact(&mut trailer).await?;
   let (magic_trailer, rest) = trailer.split_at(8);
@@ -130,7 +183,7 @@ pub async fn extract_standalone(
 
   let mut metadata = String::new();
 
-  bufreader
+  (&mut bufreader)
     .take(metadata_len)
     .read_to_string(&mut metadata)
     .await
@@ -139,7 +192,18 @@ pub async fn extract_standalone(
   let mut metadata: Metadata = serde_json::from_str(&metadata).unwrap();
   metadata.argv.append(&mut cli_args[1..].to_vec());
 
-  Ok(Some((metadata, eszip)))
+  let assets_len = metadata.assets.iter().map(|asset| asset.len).sum::<u64>();
+  let mut assets_data = Vec::with_capacity(assets_len as usize);
+  bufreader
+    .seek(SeekFrom::Start(metadata_pos - assets_len))
+    .await?;
+  bufreader
+    .take(assets_len)
+    .read_to_end(&mut assets_data)
+    .await
+    .context("Failed to read the assets from the current executable")?;
+
+  Ok(Some((metadata, eszip, assets_data)))
 }
 
 fn u64_from_bytes(arr: &[u8]) -> Result<u64, AnyError> {
@@ -173,12 +237,14 @@ impl<'a> DenoCompileBinaryWriter<'a> {
     writer: &mut impl Write,
     eszip: eszip::EszipV2,
     module_specifier: &ModuleSpecifier,
+    asset_paths: &[PathBuf],
     compile_flags: &CompileFlags,
     cli_options: &CliOptions,
   ) -> Result<(), AnyError> {
     // Select base binary based on target
-    let original_binary =
-      self.get_base_binary(compile_flags.target.clone()).await?;
+    let original_binary = self
+      .get_base_binary(compile_flags.target.clone(), compile_flags.lite)
+      .await?;
 
     self
       .write_standalone_binary(
@@ -186,23 +252,39 @@ impl<'a> DenoCompileBinaryWriter<'a> {
         original_binary,
         eszip,
         module_specifier,
+        asset_paths,
         cli_options,
         compile_flags,
       )
       .await
   }
 
+  /// The binary of the target, or the current one when there's no target.
+  /// The lite runtime, which is built without the default features of
+  /// `deno_runtime`, is always downloaded.
   async fn get_base_binary(
     &self,
     target: Option<String>,
+    lite: bool,
   ) -> Result<Vec<u8>, AnyError> {
-    if target.is_none() {
+    if target.is_none() && !lite {
       let path = std::env::current_exe()?;
       return Ok(std::fs::read(path)?);
     }
 
     let target = target.unwrap_or_else(|| env!("TARGET").to_string());
-    let binary_name = format!("deno-{target}.zip");
+    let binary_name = if lite {
+      if !LITE_TARGETS.contains(&target.as_str()) {
+        bail!(
+          "The lite runtime isn't available for {}, only for {}.",
+          target,
+          LITE_TARGETS.join(", ")
+        );
+      }
+      format!("deno-lite-{target}.zip")
+    } else {
+      format!("deno-{target}.zip")
+    };
 
     let binary_path_suffix = if crate::version::is_canary() {
       format!("canary/{}/{}", crate::version::GIT_COMMIT_HASH, binary_name)
@@ -269,6 +351,7 @@ impl<'a> DenoCompileBinaryWriter<'a> {
     original_bin: Vec<u8>,
     eszip: eszip::EszipV2,
     entrypoint: &ModuleSpecifier,
+    asset_paths: &[PathBuf],
     cli_options: &CliOptions,
     compile_flags: &CompileFlags,
   ) -> Result<(), AnyError> {
@@ -284,12 +367,23 @@ impl<'a> DenoCompileBinaryWriter<'a> {
       .resolve_import_map(self.file_fetcher)
       .await?
       .map(|import_map| (import_map.base_url().clone(), import_map.to_json()));
+    let mut assets = Vec::with_capacity(asset_paths.len());
+    let mut assets_data = Vec::new();
+    for path in asset_paths {
+      let data = std::fs::read(path)
+        .with_context(|| format!("Reading: {}", path.display()))?;
+      assets.push(EmbeddedAsset {
+        path: path.clone(),
+        len: data.len() as u64,
+      });
+      assets_data.extend(data);
+    }
     let metadata = Metadata {
       argv: compile_flags.args.clone(),
       unstable: cli_options.unstable(),
       seed: cli_options.seed(),
       location: cli_options.location_flag().clone(),
-      permissions: cli_options.permissions_options(),
+      permissions: cli_options.permissions_options()?,
       v8_flags: cli_options.v8_flags().clone(),
       unsafely_ignore_certificate_errors: cli_options
         .unsafely_ignore_certificate_errors()
@@ -299,8 +393,16 @@ impl<'a> DenoCompileBinaryWriter<'a> {
       ca_data,
       entrypoint: entrypoint.clone(),
       maybe_import_map,
+      assets,
     };
 
-    write_binary_bytes(writer, original_bin, &metadata, eszip)
+    write_binary_bytes(
+      writer,
+      original_bin,
+      &metadata,
+      eszip,
+      &assets_data,
+      compile_flags.sign.is_some(),
+    )
   }
 }
@@ -0,0 +1,1084 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The file system of a compiled executable, which serves the assets that
+//! were embedded with `deno compile --include` at the paths they had when the
+//! executable was compiled, and passes everything else to the real file
+//! system. The assets are read-only.
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use deno_core::normalize_path;
+use deno_core::BufMutView;
+use deno_core::BufView;
+use deno_core::WriteOutcome;
+use deno_runtime::deno_fs::FileSystem;
+use deno_runtime::deno_fs::FsDirEntry;
+use deno_runtime::deno_fs::FsDirEntryWithStat;
+use deno_runtime::deno_fs::FsFileType;
+use deno_runtime::deno_fs::FsStatFs;
+use deno_runtime::deno_fs::OpenOptions;
+use deno_runtime::deno_fs::RealFs;
+use deno_runtime::deno_fs::ReflinkMode;
+use deno_runtime::deno_io::fs::File;
+use deno_runtime::deno_io::fs::FsError;
+use deno_runtime::deno_io::fs::FsResult;
+use deno_runtime::deno_io::fs::FsStat;
+
+use super::binary::EmbeddedAsset;
+
+/// The assets of a compiled executable, and the directories that contain
+/// them below the deepest directory that contains all of them.
+#[derive(Debug, Default)]
+pub struct EmbeddedAssets {
+  data: Arc<[u8]>,
+  files: HashMap<PathBuf, Range<usize>>,
+  /// The names of the entries of each directory, and whether they are files.
+  dirs: HashMap<PathBuf, BTreeMap<String, bool>>,
+}
+
+impl EmbeddedAssets {
+  /// Indexes the assets, whose contents follow each other in `data`.
+  pub fn new(assets: &[EmbeddedAsset], data: Vec<u8>) -> Self {
+    let mut files = HashMap::with_capacity(assets.len());
+    let mut offset = 0;
+    for asset in assets {
+      let len = asset.len as usize;
+      files.insert(asset.path.clone(), offset..offset + len);
+      offset += len;
+    }
+
+    let mut dirs: HashMap<PathBuf, BTreeMap<String, bool>> = HashMap::new();
+    if let Some(root) = common_parent(assets.iter().map(|a| a.path.as_path())) {
+      for asset in assets {
+        let mut is_file = true;
+        let mut path = asset.path.as_path();
+        while let Some(parent) = path.parent() {
+          if !parent.starts_with(&root) {
+            break;
+          }
+          if let Some(name) = path.file_name() {
+            dirs
+              .entry(parent.to_path_buf())
+              .or_default()
+              .insert(name.to_string_lossy().to_string(), is_file);
+          }
+          is_file = false;
+          path = parent;
+        }
+      }
+    }
+
+    Self {
+      data: data.into(),
+      files,
+      dirs,
+    }
+  }
+
+  /// The paths of the assets and of the directories that contain them.
+  pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+    self.files.keys().chain(self.dirs.keys())
+  }
+
+  fn read_file(&self, path: &Path) -> Option<&[u8]> {
+    let range = self.files.get(path)?;
+    Some(&self.data[range.clone()])
+  }
+
+  fn open(&self, path: &Path) -> Option<AssetFile> {
+    let range = self.files.get(path)?;
+    Some(AssetFile {
+      data: self.data.clone(),
+      range: range.clone(),
+      pos: Default::default(),
+    })
+  }
+
+  fn stat(&self, path: &Path) -> Option<FsStat> {
+    if let Some(range) = self.files.get(path) {
+      Some(asset_stat(true, range.len() as u64))
+    } else if self.dirs.contains_key(path) {
+      Some(asset_stat(false, 0))
+    } else {
+      None
+    }
+  }
+
+  fn read_dir(&self, path: &Path) -> Option<Vec<FsDirEntry>> {
+    let entries = self.dirs.get(path)?;
+    Some(
+      entries
+        .iter()
+        .map(|(name, is_file)| FsDirEntry {
+          name: name.clone(),
+          is_file: *is_file,
+          is_directory: !is_file,
+          is_symlink: false,
+        })
+        .collect(),
+    )
+  }
+
+  fn read_dir_with_stats(
+    &self,
+    path: &Path,
+  ) -> Option<Vec<FsDirEntryWithStat>> {
+    let entries = self.read_dir(path)?;
+    Some(
+      entries
+        .into_iter()
+        .map(|entry| {
+          let stat = self.stat(&path.join(&entry.name)).unwrap();
+          FsDirEntryWithStat { entry, stat }
+        })
+        .collect(),
+    )
+  }
+
+  fn contains(&self, path: &Path) -> bool {
+    self.files.contains_key(path) || self.dirs.contains_key(path)
+  }
+}
+
+/// The deepest directory that contains all of the paths.
+fn common_parent<'a>(
+  mut paths: impl Iterator<Item = &'a Path>,
+) -> Option<PathBuf> {
+  let mut parent = paths.next()?.parent()?.to_path_buf();
+  for path in paths {
+    while !path.starts_with(&parent) {
+      parent = parent.parent()?.to_path_buf();
+    }
+  }
+  Some(parent)
+}
+
+fn asset_stat(is_file: bool, size: u64) -> FsStat {
+  FsStat {
+    is_file,
+    is_directory: !is_file,
+    is_symlink: false,
+    size,
+    mtime: None,
+    atime: None,
+    birthtime: None,
+    dev: 0,
+    ino: 0,
+    mode: if is_file { 0o444 } else { 0o555 },
+    nlink: 1,
+    uid: 0,
+    gid: 0,
+    rdev: 0,
+    blksize: 0,
+    blocks: 0,
+  }
+}
+
+fn read_only_error() -> FsError {
+  io::Error::new(
+    io::ErrorKind::PermissionDenied,
+    "The assets of a compiled executable are read-only.",
+  )
+  .into()
+}
+
+/// An opened asset, which is read from the copy of the executable in memory.
+struct AssetFile {
+  data: Arc<[u8]>,
+  range: Range<usize>,
+  /// The position in the asset, which is shared with the clones of the file
+  /// like the position of a duplicated file descriptor.
+  pos: Rc<Cell<u64>>,
+}
+
+impl AssetFile {
+  fn bytes(&self) -> &[u8] {
+    &self.data[self.range.clone()]
+  }
+
+  fn read_from_pos(&self, buf: &mut [u8]) -> usize {
+    let bytes = self.bytes();
+    let pos = self.pos.get().min(bytes.len() as u64) as usize;
+    let nread = buf.len().min(bytes.len() - pos);
+    buf[..nread].copy_from_slice(&bytes[pos..pos + nread]);
+    self.pos.set(self.pos.get() + nread as u64);
+    nread
+  }
+
+  fn read_to_end(&self) -> Vec<u8> {
+    let len = self.bytes().len() as u64;
+    let mut buf = vec![0; len.saturating_sub(self.pos.get()) as usize];
+    self.read_from_pos(&mut buf);
+    buf
+  }
+
+  fn seek(&self, pos: io::SeekFrom) -> FsResult<u64> {
+    let pos = match pos {
+      io::SeekFrom::Start(offset) => Some(offset),
+      io::SeekFrom::End(offset) => {
+        (self.bytes().len() as u64).checked_add_signed(offset)
+      }
+      io::SeekFrom::Current(offset) => {
+        self.pos.get().checked_add_signed(offset)
+      }
+    };
+    let pos = pos.ok_or_else(|| {
+      io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "invalid seek to a negative or overflowing position",
+      )
+    })?;
+    self.pos.set(pos);
+    Ok(pos)
+  }
+
+  fn stat(&self) -> FsStat {
+    asset_stat(true, self.bytes().len() as u64)
+  }
+
+  fn mmap(
+    &self,
+    offset: u64,
+    len: Option<usize>,
+    shared: bool,
+  ) -> FsResult<memmap2::MmapMut> {
+    if shared {
+      return Err(read_only_error());
+    }
+    let bytes = self.bytes();
+    let offset = offset.min(bytes.len() as u64) as usize;
+    let bytes = &bytes[offset..];
+    let mut mmap = memmap2::MmapMut::map_anon(len.unwrap_or(bytes.len()))?;
+    let len = mmap.len().min(bytes.len());
+    mmap[..len].copy_from_slice(&bytes[..len]);
+    Ok(mmap)
+  }
+}
+
+#[async_trait::async_trait(?Send)]
+impl File for AssetFile {
+  fn read_sync(self: Rc<Self>, buf: &mut [u8]) -> FsResult<usize> {
+    Ok(self.read_from_pos(buf))
+  }
+  async fn read_byob(
+    self: Rc<Self>,
+    mut buf: BufMutView,
+  ) -> FsResult<(usize, BufMutView)> {
+    let nread = self.read_from_pos(&mut buf);
+    Ok((nread, buf))
+  }
+
+  fn write_sync(self: Rc<Self>, _buf: &[u8]) -> FsResult<usize> {
+    Err(read_only_error())
+  }
+  async fn write(self: Rc<Self>, _buf: BufView) -> FsResult<WriteOutcome> {
+    Err(read_only_error())
+  }
+
+  fn write_all_sync(self: Rc<Self>, _buf: &[u8]) -> FsResult<()> {
+    Err(read_only_error())
+  }
+  async fn write_all(self: Rc<Self>, _buf: BufView) -> FsResult<()> {
+    Err(read_only_error())
+  }
+
+  fn read_all_sync(self: Rc<Self>) -> FsResult<Vec<u8>> {
+    Ok(self.read_to_end())
+  }
+  async fn read_all_async(self: Rc<Self>) -> FsResult<Vec<u8>> {
+    Ok(self.read_to_end())
+  }
+
+  fn chmod_sync(self: Rc<Self>, _mode: u32) -> FsResult<()> {
+    Err(read_only_error())
+  }
+  async fn chmod_async(self: Rc<Self>, _mode: u32) -> FsResult<()> {
+    Err(read_only_error())
+  }
+
+  fn seek_sync(self: Rc<Self>, pos: io::SeekFrom) -> FsResult<u64> {
+    self.seek(pos)
+  }
+  async fn seek_async(self: Rc<Self>, pos: io::SeekFrom) -> FsResult<u64> {
+    self.seek(pos)
+  }
+
+  fn datasync_sync(self: Rc<Self>) -> FsResult<()> {
+    Ok(())
+  }
+  async fn datasync_async(self: Rc<Self>) -> FsResult<()> {
+    Ok(())
+  }
+
+  fn sync_sync(self: Rc<Self>) -> FsResult<()> {
+    Ok(())
+  }
+  async fn sync_async(self: Rc<Self>) -> FsResult<()> {
+    Ok(())
+  }
+
+  fn stat_sync(self: Rc<Self>) -> FsResult<FsStat> {
+    Ok(self.stat())
+  }
+  async fn stat_async(self: Rc<Self>) -> FsResult<FsStat> {
+    Ok(self.stat())
+  }
+
+  // nothing can write to the assets, so they don't need to be locked
+  fn lock_sync(self: Rc<Self>, _exclusive: bool) -> FsResult<()> {
+    Ok(())
+  }
+  async fn lock_async(self: Rc<Self>, _exclusive: bool) -> FsResult<()> {
+    Ok(())
+  }
+
+  fn unlock_sync(self: Rc<Self>) -> FsResult<()> {
+    Ok(())
+  }
+  async fn unlock_async(self: Rc<Self>) -> FsResult<()> {
+    Ok(())
+  }
+
+  fn truncate_sync(self: Rc<Self>, _len: u64) -> FsResult<()> {
+    Err(read_only_error())
+  }
+  async fn truncate_async(self: Rc<Self>, _len: u64) -> FsResult<()> {
+    Err(read_only_error())
+  }
+
+  fn allocate_sync(self: Rc<Self>, _len: u64) -> FsResult<()> {
+    Err(read_only_error())
+  }
+  async fn allocate_async(self: Rc<Self>, _len: u64) -> FsResult<()> {
+    Err(read_only_error())
+  }
+
+  fn get_xattr_sync(self: Rc<Self>, _name: &str) -> FsResult<Option<Vec<u8>>> {
+    Ok(None)
+  }
+  async fn get_xattr_async(
+    self: Rc<Self>,
+    _name: String,
+  ) -> FsResult<Option<Vec<u8>>> {
+    Ok(None)
+  }
+
+  fn set_xattr_sync(
+    self: Rc<Self>,
+    _name: &str,
+    _value: &[u8],
+  ) -> FsResult<()> {
+    Err(read_only_error())
+  }
+  async fn set_xattr_async(
+    self: Rc<Self>,
+    _name: String,
+    _value: Vec<u8>,
+  ) -> FsResult<()> {
+    Err(read_only_error())
+  }
+
+  fn list_xattr_sync(self: Rc<Self>) -> FsResult<Vec<String>> {
+    Ok(vec![])
+  }
+  async fn list_xattr_async(self: Rc<Self>) -> FsResult<Vec<String>> {
+    Ok(vec![])
+  }
+
+  fn remove_xattr_sync(self: Rc<Self>, _name: &str) -> FsResult<()> {
+    Err(read_only_error())
+  }
+  async fn remove_xattr_async(self: Rc<Self>, _name: String) -> FsResult<()> {
+    Err(read_only_error())
+  }
+
+  fn punch_hole_sync(self: Rc<Self>, _offset: u64, _len: u64) -> FsResult<()> {
+    Err(read_only_error())
+  }
+  async fn punch_hole_async(
+    self: Rc<Self>,
+    _offset: u64,
+    _len: u64,
+  ) -> FsResult<()> {
+    Err(read_only_error())
+  }
+
+  fn utime_sync(
+    self: Rc<Self>,
+    _atime_secs: i64,
+    _atime_nanos: u32,
+    _mtime_secs: i64,
+    _mtime_nanos: u32,
+  ) -> FsResult<()> {
+    Err(read_only_error())
+  }
+  async fn utime_async(
+    self: Rc<Self>,
+    _atime_secs: i64,
+    _atime_nanos: u32,
+    _mtime_secs: i64,
+    _mtime_nanos: u32,
+  ) -> FsResult<()> {
+    Err(read_only_error())
+  }
+
+  fn link_sync(self: Rc<Self>, _path: &Path) -> FsResult<()> {
+    Err(read_only_error())
+  }
+  async fn link_async(self: Rc<Self>, _path: PathBuf) -> FsResult<()> {
+    Err(read_only_error())
+  }
+
+  fn set_birthtime_sync(
+    self: Rc<Self>,
+    _secs: i64,
+    _nanos: u32,
+  ) -> FsResult<()> {
+    Err(read_only_error())
+  }
+  async fn set_birthtime_async(
+    self: Rc<Self>,
+    _secs: i64,
+    _nanos: u32,
+  ) -> FsResult<()> {
+    Err(read_only_error())
+  }
+
+  fn mmap_sync(
+    self: Rc<Self>,
+    offset: u64,
+    len: Option<usize>,
+    shared: bool,
+  ) -> FsResult<memmap2::MmapMut> {
+    self.mmap(offset, len, shared)
+  }
+
+  // lower level functionality
+  fn as_stdio(self: Rc<Self>) -> FsResult<std::process::Stdio> {
+    Err(FsError::NotSupported)
+  }
+  #[cfg(unix)]
+  fn backing_fd(self: Rc<Self>) -> Option<std::os::unix::prelude::RawFd> {
+    None
+  }
+  #[cfg(windows)]
+  fn backing_fd(self: Rc<Self>) -> Option<std::os::windows::io::RawHandle> {
+    None
+  }
+  fn try_clone_inner(self: Rc<Self>) -> FsResult<Rc<dyn File>> {
+    Ok(Rc::new(AssetFile {
+      data: self.data.clone(),
+      range: self.range.clone(),
+      pos: self.pos.clone(),
+    }))
+  }
+}
+
+#[derive(Debug)]
+pub struct DenoCompileFileSystem {
+  assets: EmbeddedAssets,
+}
+
+impl DenoCompileFileSystem {
+  pub fn new(assets: EmbeddedAssets) -> Self {
+    Self { assets }
+  }
+
+  /// The absolute and normalized path, which is how the assets are keyed.
+  fn resolve(&self, path: &Path) -> PathBuf {
+    if path.is_absolute() {
+      normalize_path(path)
+    } else {
+      match std::env::current_dir() {
+        Ok(cwd) => normalize_path(cwd.join(path)),
+        Err(_) => path.to_path_buf(),
+      }
+    }
+  }
+
+  fn is_asset(&self, path: &Path) -> bool {
+    self.assets.contains(&self.resolve(path))
+  }
+
+  /// Opens the asset at the path, if there's one, for reading only.
+  fn open_asset(
+    &self,
+    path: &Path,
+    options: &OpenOptions,
+  ) -> FsResult<Option<Rc<dyn File>>> {
+    let resolved = self.resolve(path);
+    if !self.assets.contains(&resolved) {
+      return Ok(None);
+    }
+    if options.write || options.append || options.truncate || options.create_new
+    {
+      return Err(read_only_error());
+    }
+    match self.assets.open(&resolved) {
+      Some(file) => Ok(Some(Rc::new(file))),
+      None => {
+        Err(io::Error::new(io::ErrorKind::Other, "Is a directory").into())
+      }
+    }
+  }
+
+  fn error_if_asset(&self, path: &Path) -> FsResult<()> {
+    if self.is_asset(path) {
+      Err(read_only_error())
+    } else {
+      Ok(())
+    }
+  }
+}
+
+#[async_trait::async_trait(?Send)]
+impl FileSystem for DenoCompileFileSystem {
+  fn cwd(&self) -> FsResult<PathBuf> {
+    RealFs.cwd()
+  }
+
+  fn tmp_dir(&self) -> FsResult<PathBuf> {
+    RealFs.tmp_dir()
+  }
+
+  fn chdir(&self, path: &Path) -> FsResult<()> {
+    RealFs.chdir(path)
+  }
+
+  fn umask(&self, mask: Option<u32>) -> FsResult<u32> {
+    RealFs.umask(mask)
+  }
+
+  fn open_sync(
+    &self,
+    path: &Path,
+    options: OpenOptions,
+  ) -> FsResult<Rc<dyn File>> {
+    match self.open_asset(path, &options)? {
+      Some(file) => Ok(file),
+      None => RealFs.open_sync(path, options),
+    }
+  }
+  async fn open_async(
+    &self,
+    path: PathBuf,
+    options: OpenOptions,
+  ) -> FsResult<Rc<dyn File>> {
+    match self.open_asset(&path, &options)? {
+      Some(file) => Ok(file),
+      None => RealFs.open_async(path, options).await,
+    }
+  }
+
+  fn open_tmp_file_sync(&self, dir: &Path) -> FsResult<Rc<dyn File>> {
+    self.error_if_asset(dir)?;
+    RealFs.open_tmp_file_sync(dir)
+  }
+  async fn open_tmp_file_async(&self, dir: PathBuf) -> FsResult<Rc<dyn File>> {
+    self.error_if_asset(&dir)?;
+    RealFs.open_tmp_file_async(dir).await
+  }
+
+  fn mkdir_sync(
+    &self,
+    path: &Path,
+    recursive: bool,
+    mode: u32,
+  ) -> FsResult<()> {
+    self.error_if_asset(path)?;
+    RealFs.mkdir_sync(path, recursive, mode)
+  }
+  async fn mkdir_async(
+    &self,
+    path: PathBuf,
+    recursive: bool,
+    mode: u32,
+  ) -> FsResult<()> {
+    self.error_if_asset(&path)?;
+    RealFs.mkdir_async(path, recursive, mode).await
+  }
+
+  fn chmod_sync(&self, path: &Path, mode: u32) -> FsResult<()> {
+    self.error_if_asset(path)?;
+    RealFs.chmod_sync(path, mode)
+  }
+  async fn chmod_async(&self, path: PathBuf, mode: u32) -> FsResult<()> {
+    self.error_if_asset(&path)?;
+    RealFs.chmod_async(path, mode).await
+  }
+
+  fn chown_sync(
+    &self,
+    path: &Path,
+    uid: Option<u32>,
+    gid: Option<u32>,
+  ) -> FsResult<()> {
+    self.error_if_asset(path)?;
+    RealFs.chown_sync(path, uid, gid)
+  }
+  async fn chown_async(
+    &self,
+    path: PathBuf,
+    uid: Option<u32>,
+    gid: Option<u32>,
+  ) -> FsResult<()> {
+    self.error_if_asset(&path)?;
+    RealFs.chown_async(path, uid, gid).await
+  }
+
+  fn remove_sync(&self, path: &Path, recursive: bool) -> FsResult<()> {
+    self.error_if_asset(path)?;
+    RealFs.remove_sync(path, recursive)
+  }
+  async fn remove_async(&self, path: PathBuf, recursive: bool) -> FsResult<()> {
+    self.error_if_asset(&path)?;
+    RealFs.remove_async(path, recursive).await
+  }
+
+  fn trash_sync(&self, path: &Path) -> FsResult<()> {
+    self.error_if_asset(path)?;
+    RealFs.trash_sync(path)
+  }
+  async fn trash_async(&self, path: PathBuf) -> FsResult<()> {
+    self.error_if_asset(&path)?;
+    RealFs.trash_async(path).await
+  }
+
+  fn copy_file_sync(
+    &self,
+    oldpath: &Path,
+    newpath: &Path,
+    reflink: ReflinkMode,
+  ) -> FsResult<()> {
+    self.error_if_asset(newpath)?;
+    match self.assets.read_file(&self.resolve(oldpath)) {
+      Some(data) => RealFs.write_file_sync(
+        newpath,
+        OpenOptions::write(true, false, false, None),
+        data,
+      ),
+      None => RealFs.copy_file_sync(oldpath, newpath, reflink),
+    }
+  }
+  async fn copy_file_async(
+    &self,
+    oldpath: PathBuf,
+    newpath: PathBuf,
+    reflink: ReflinkMode,
+  ) -> FsResult<()> {
+    self.error_if_asset(&newpath)?;
+    match self.assets.read_file(&self.resolve(&oldpath)) {
+      Some(data) => {
+        RealFs
+          .write_file_async(
+            newpath,
+            OpenOptions::write(true, false, false, None),
+            data.to_vec(),
+          )
+          .await
+      }
+      None => RealFs.copy_file_async(oldpath, newpath, reflink).await,
+    }
+  }
+
+  fn stat_sync(&self, path: &Path) -> FsResult<FsStat> {
+    match self.assets.stat(&self.resolve(path)) {
+      Some(stat) => Ok(stat),
+      None => RealFs.stat_sync(path),
+    }
+  }
+  async fn stat_async(&self, path: PathBuf) -> FsResult<FsStat> {
+    match self.assets.stat(&self.resolve(&path)) {
+      Some(stat) => Ok(stat),
+      None => RealFs.stat_async(path).await,
+    }
+  }
+
+  fn stat_fs_sync(&self, path: &Path) -> FsResult<FsStatFs> {
+    RealFs.stat_fs_sync(path)
+  }
+  async fn stat_fs_async(&self, path: PathBuf) -> FsResult<FsStatFs> {
+    RealFs.stat_fs_async(path).await
+  }
+
+  fn lstat_sync(&self, path: &Path) -> FsResult<FsStat> {
+    self.stat_sync(path)
+  }
+  async fn lstat_async(&self, path: PathBuf) -> FsResult<FsStat> {
+    match self.assets.stat(&self.resolve(&path)) {
+      Some(stat) => Ok(stat),
+      None => RealFs.lstat_async(path).await,
+    }
+  }
+
+  fn realpath_sync(&self, path: &Path) -> FsResult<PathBuf> {
+    let resolved = self.resolve(path);
+    if self.assets.contains(&resolved) {
+      Ok(resolved)
+    } else {
+      RealFs.realpath_sync(path)
+    }
+  }
+  async fn realpath_async(&self, path: PathBuf) -> FsResult<PathBuf> {
+    let resolved = self.resolve(&path);
+    if self.assets.contains(&resolved) {
+      Ok(resolved)
+    } else {
+      RealFs.realpath_async(path).await
+    }
+  }
+  fn realpath_sync_cached(&self, path: &Path) -> FsResult<PathBuf> {
+    let resolved = self.resolve(path);
+    if self.assets.contains(&resolved) {
+      Ok(resolved)
+    } else {
+      RealFs.realpath_sync_cached(path)
+    }
+  }
+
+  fn read_dir_sync(&self, path: &Path) -> FsResult<Vec<FsDirEntry>> {
+    match self.assets.read_dir(&self.resolve(path)) {
+      Some(entries) => Ok(entries),
+      None => RealFs.read_dir_sync(path),
+    }
+  }
+  async fn read_dir_async(&self, path: PathBuf) -> FsResult<Vec<FsDirEntry>> {
+    match self.assets.read_dir(&self.resolve(&path)) {
+      Some(entries) => Ok(entries),
+      None => RealFs.read_dir_async(path).await,
+    }
+  }
+  fn read_dir_with_stats_sync(
+    &self,
+    path: &Path,
+  ) -> FsResult<Vec<FsDirEntryWithStat>> {
+    match self.assets.read_dir_with_stats(&self.resolve(path)) {
+      Some(entries) => Ok(entries),
+      None => RealFs.read_dir_with_stats_sync(path),
+    }
+  }
+  async fn read_dir_with_stats_async(
+    &self,
+    path: PathBuf,
+  ) -> FsResult<Vec<FsDirEntryWithStat>> {
+    match self.assets.read_dir_with_stats(&self.resolve(&path)) {
+      Some(entries) => Ok(entries),
+      None => RealFs.read_dir_with_stats_async(path).await,
+    }
+  }
+
+  fn rename_sync(&self, oldpath: &Path, newpath: &Path) -> FsResult<()> {
+    self.error_if_asset(oldpath)?;
+    self.error_if_asset(newpath)?;
+    RealFs.rename_sync(oldpath, newpath)
+  }
+  async fn rename_async(
+    &self,
+    oldpath: PathBuf,
+    newpath: PathBuf,
+  ) -> FsResult<()> {
+    self.error_if_asset(&oldpath)?;
+    self.error_if_asset(&newpath)?;
+    RealFs.rename_async(oldpath, newpath).await
+  }
+
+  fn link_sync(&self, oldpath: &Path, newpath: &Path) -> FsResult<()> {
+    self.error_if_asset(oldpath)?;
+    self.error_if_asset(newpath)?;
+    RealFs.link_sync(oldpath, newpath)
+  }
+  async fn link_async(
+    &self,
+    oldpath: PathBuf,
+    newpath: PathBuf,
+  ) -> FsResult<()> {
+    self.error_if_asset(&oldpath)?;
+    self.error_if_asset(&newpath)?;
+    RealFs.link_async(oldpath, newpath).await
+  }
+
+  fn symlink_sync(
+    &self,
+    oldpath: &Path,
+    newpath: &Path,
+    file_type: Option<FsFileType>,
+  ) -> FsResult<()> {
+    self.error_if_asset(newpath)?;
+    RealFs.symlink_sync(oldpath, newpath, file_type)
+  }
+  async fn symlink_async(
+    &self,
+    oldpath: PathBuf,
+    newpath: PathBuf,
+    file_type: Option<FsFileType>,
+  ) -> FsResult<()> {
+    self.error_if_asset(&newpath)?;
+    RealFs.symlink_async(oldpath, newpath, file_type).await
+  }
+
+  fn read_link_sync(&self, path: &Path) -> FsResult<PathBuf> {
+    RealFs.read_link_sync(path)
+  }
+  async fn read_link_async(&self, path: PathBuf) -> FsResult<PathBuf> {
+    RealFs.read_link_async(path).await
+  }
+
+  fn truncate_sync(&self, path: &Path, len: u64) -> FsResult<()> {
+    self.error_if_asset(path)?;
+    RealFs.truncate_sync(path, len)
+  }
+  async fn truncate_async(&self, path: PathBuf, len: u64) -> FsResult<()> {
+    self.error_if_asset(&path)?;
+    RealFs.truncate_async(path, len).await
+  }
+
+  fn utime_sync(
+    &self,
+    path: &Path,
+    atime_secs: i64,
+    atime_nanos: u32,
+    mtime_secs: i64,
+    mtime_nanos: u32,
+  ) -> FsResult<()> {
+    self.error_if_asset(path)?;
+    RealFs.utime_sync(path, atime_secs, atime_nanos, mtime_secs, mtime_nanos)
+  }
+  async fn utime_async(
+    &self,
+    path: PathBuf,
+    atime_secs: i64,
+    atime_nanos: u32,
+    mtime_secs: i64,
+    mtime_nanos: u32,
+  ) -> FsResult<()> {
+    self.error_if_asset(&path)?;
+    RealFs
+      .utime_async(path, atime_secs, atime_nanos, mtime_secs, mtime_nanos)
+      .await
+  }
+  fn set_birthtime_sync(
+    &self,
+    path: &Path,
+    secs: i64,
+    nanos: u32,
+  ) -> FsResult<()> {
+    self.error_if_asset(path)?;
+    RealFs.set_birthtime_sync(path, secs, nanos)
+  }
+  async fn set_birthtime_async(
+    &self,
+    path: PathBuf,
+    secs: i64,
+    nanos: u32,
+  ) -> FsResult<()> {
+    self.error_if_asset(&path)?;
+    RealFs.set_birthtime_async(path, secs, nanos).await
+  }
+
+  fn get_xattr_sync(
+    &self,
+    path: &Path,
+    name: &str,
+  ) -> FsResult<Option<Vec<u8>>> {
+    if self.is_asset(path) {
+      return Ok(None);
+    }
+    RealFs.get_xattr_sync(path, name)
+  }
+  async fn get_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+  ) -> FsResult<Option<Vec<u8>>> {
+    if self.is_asset(&path) {
+      return Ok(None);
+    }
+    RealFs.get_xattr_async(path, name).await
+  }
+
+  fn set_xattr_sync(
+    &self,
+    path: &Path,
+    name: &str,
+    value: &[u8],
+  ) -> FsResult<()> {
+    self.error_if_asset(path)?;
+    RealFs.set_xattr_sync(path, name, value)
+  }
+  async fn set_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+    value: Vec<u8>,
+  ) -> FsResult<()> {
+    self.error_if_asset(&path)?;
+    RealFs.set_xattr_async(path, name, value).await
+  }
+
+  fn list_xattr_sync(&self, path: &Path) -> FsResult<Vec<String>> {
+    if self.is_asset(path) {
+      return Ok(vec![]);
+    }
+    RealFs.list_xattr_sync(path)
+  }
+  async fn list_xattr_async(&self, path: PathBuf) -> FsResult<Vec<String>> {
+    if self.is_asset(&path) {
+      return Ok(vec![]);
+    }
+    RealFs.list_xattr_async(path).await
+  }
+
+  fn remove_xattr_sync(&self, path: &Path, name: &str) -> FsResult<()> {
+    self.error_if_asset(path)?;
+    RealFs.remove_xattr_sync(path, name)
+  }
+  async fn remove_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+  ) -> FsResult<()> {
+    self.error_if_asset(&path)?;
+    RealFs.remove_xattr_async(path, name).await
+  }
+
+  fn write_file_atomic_sync(
+    &self,
+    path: &Path,
+    mode: Option<u32>,
+    data: &[u8],
+  ) -> FsResult<()> {
+    self.error_if_asset(path)?;
+    RealFs.write_file_atomic_sync(path, mode, data)
+  }
+  async fn write_file_atomic_async(
+    &self,
+    path: PathBuf,
+    mode: Option<u32>,
+    data: Vec<u8>,
+  ) -> FsResult<()> {
+    self.error_if_asset(&path)?;
+    RealFs.write_file_atomic_async(path, mode, data).await
+  }
+
+  fn read_file_sync(&self, path: &Path) -> FsResult<Vec<u8>> {
+    match self.assets.read_file(&self.resolve(path)) {
+      Some(data) => Ok(data.to_vec()),
+      None => RealFs.read_file_sync(path),
+    }
+  }
+  async fn read_file_async(&self, path: PathBuf) -> FsResult<Vec<u8>> {
+    match self.assets.read_file(&self.resolve(&path)) {
+      Some(data) => Ok(data.to_vec()),
+      None => RealFs.read_file_async(path).await,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn assets() -> EmbeddedAssets {
+    let root = if cfg!(windows) { "C:\\app" } else { "/app" };
+    let root = PathBuf::from(root);
+    EmbeddedAssets::new(
+      &[
+        EmbeddedAsset {
+          path: root.join("assets").join("a.txt"),
+          len: 1,
+        },
+        EmbeddedAsset {
+          path: root.join("assets").join("img").join("b.svg"),
+          len: 2,
+        },
+        EmbeddedAsset {
+          path: root.join("data.csv"),
+          len: 3,
+        },
+      ],
+      b"abbccc".to_vec(),
+    )
+  }
+
+  #[test]
+  fn test_embedded_assets() {
+    let root = if cfg!(windows) { "C:\\app" } else { "/app" };
+    let root = PathBuf::from(root);
+    let assets = assets();
+    assert_eq!(
+      assets.read_file(&root.join("assets").join("a.txt")),
+      Some(&b"a"[..])
+    );
+    assert_eq!(
+      assets.read_file(&root.join("assets").join("img").join("b.svg")),
+      Some(&b"bb"[..])
+    );
+    assert_eq!(assets.read_file(&root.join("data.csv")), Some(&b"ccc"[..]));
+    assert_eq!(assets.read_file(&root.join("assets")), None);
+
+    let names = |path: &Path| {
+      assets
+        .read_dir(path)
+        .unwrap()
+        .into_iter()
+        .map(|e| (e.name, e.is_file))
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(
+      names(&root),
+      vec![
+        ("assets".to_string(), false),
+        ("data.csv".to_string(), true)
+      ]
+    );
+    assert_eq!(
+      names(&root.join("assets")),
+      vec![("a.txt".to_string(), true), ("img".to_string(), false)]
+    );
+    // the directories above the assets are left to the real file system
+    assert!(assets.read_dir(root.parent().unwrap()).is_none());
+
+    let stat = assets.stat(&root.join("data.csv")).unwrap();
+    assert!(stat.is_file);
+    assert_eq!(stat.size, 3);
+    assert!(assets.stat(&root.join("assets")).unwrap().is_directory);
+    assert!(assets.stat(&root.join("missing.txt")).is_none());
+  }
+
+  #[test]
+  fn test_asset_file() {
+    let root = if cfg!(windows) { "C:\\app" } else { "/app" };
+    let root = PathBuf::from(root);
+    let assets = assets();
+    assert!(assets.open(&root.join("assets")).is_none());
+
+    let file = Rc::new(assets.open(&root.join("data.csv")).unwrap());
+    let mut buf = [0; 2];
+    assert_eq!(file.clone().read_sync(&mut buf).unwrap(), 2);
+    assert_eq!(&buf, b"cc");
+    // the clones share the position
+    let clone = file.clone().try_clone_inner().unwrap();
+    assert_eq!(clone.read_all_sync().unwrap(), b"c");
+    assert_eq!(file.clone().read_sync(&mut buf).unwrap(), 0);
+
+    assert_eq!(file.clone().seek_sync(io::SeekFrom::End(-2)).unwrap(), 1);
+    assert_eq!(file.clone().read_all_sync().unwrap(), b"cc");
+    assert!(file.clone().seek_sync(io::SeekFrom::Current(-4)).is_err());
+    assert_eq!(file.clone().seek_sync(io::SeekFrom::Start(8)).unwrap(), 8);
+    assert_eq!(file.clone().read_sync(&mut buf).unwrap(), 0);
+
+    assert_eq!(file.clone().stat_sync().unwrap().size, 3);
+    assert!(file.clone().write_sync(b"d").is_err());
+    assert!(file.clone().truncate_sync(0).is_err());
+    assert!(file.clone().mmap_sync(0, None, true).is_err());
+    assert_eq!(&file.mmap_sync(1, None, false).unwrap()[..], b"cc");
+  }
+}
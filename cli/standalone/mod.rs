@@ -30,7 +30,6 @@ use deno_core::ModuleSpecifier;
 use deno_core::ModuleType;
 use deno_core::ResolutionKind;
 use deno_graph::source::Resolver;
-use deno_runtime::deno_fs;
 use deno_runtime::deno_node::NodeResolver;
 use deno_runtime::deno_tls::rustls::RootCertStore;
 use deno_runtime::deno_tls::RootCertStoreProvider;
@@ -43,12 +42,17 @@ use std::rc::Rc;
 use std::sync::Arc;
 
 mod binary;
+mod file_system;
+mod sign;
 
 pub use binary::extract_standalone;
 pub use binary::is_standalone_binary;
 pub use binary::DenoCompileBinaryWriter;
+pub use sign::sign_executable;
 
 use self::binary::Metadata;
+use self::file_system::DenoCompileFileSystem;
+use self::file_system::EmbeddedAssets;
 
 #[derive(Clone)]
 struct EmbeddedModuleLoader {
@@ -94,7 +98,7 @@ impl ModuleLoader for EmbeddedModuleLoader {
     let module = self
       .eszip
       .get_module(module_specifier.as_str())
-      .ok_or_else(|| type_error("Module not found"));
+      .ok_or_else(|| module_not_found_error(&self.eszip, module_specifier));
     // TODO(mmastrac): This clone can probably be removed in the future if ModuleSpecifier is no longer a full-fledged URL
     let module_specifier = module_specifier.clone();
 
@@ -127,6 +131,42 @@ impl ModuleLoader for EmbeddedModuleLoader {
   }
 }
 
+/// The error of a module that isn't embedded in the executable, which is
+/// usually the target of a dynamic import that can't be analyzed when
+/// compiling. It lists the embedded modules of the same directory, to tell
+/// which of them were included.
+fn module_not_found_error(
+  eszip: &eszip::EszipV2,
+  specifier: &ModuleSpecifier,
+) -> AnyError {
+  let mut message = format!(
+    concat!(
+      "Module not found \"{}\" in the compiled executable. Modules that are ",
+      "imported dynamically with a computed specifier must be included when ",
+      "compiling, for example with --include=\"./plugins/*.ts\"."
+    ),
+    specifier
+  );
+  let dir = specifier.join("./").ok();
+  let mut siblings = eszip
+    .specifiers()
+    .into_iter()
+    .filter(|s| match &dir {
+      Some(dir) => s.starts_with(dir.as_str()),
+      None => false,
+    })
+    .collect::<Vec<_>>();
+  siblings.sort();
+  if !siblings.is_empty() {
+    message.push_str("\n\nThe embedded modules of its directory are:");
+    for sibling in siblings {
+      message.push_str("\n  ");
+      message.push_str(&sibling);
+    }
+  }
+  type_error(message)
+}
+
 struct StandaloneModuleLoaderFactory {
   loader: EmbeddedModuleLoader,
 }
@@ -180,8 +220,18 @@ impl RootCertStoreProvider for StandaloneRootCertStoreProvider {
 
 pub async fn run(
   eszip: eszip::EszipV2,
-  metadata: Metadata,
+  mut metadata: Metadata,
+  assets_data: Vec<u8>,
 ) -> Result<(), AnyError> {
+  let assets = EmbeddedAssets::new(&metadata.assets, assets_data);
+  // the assets can always be read, like the modules of the executable
+  if let Some(allow_read) = &mut metadata.permissions.allow_read {
+    if !allow_read.is_empty() {
+      allow_read.extend(assets.paths().cloned());
+    }
+  } else if !metadata.assets.is_empty() {
+    metadata.permissions.allow_read = Some(assets.paths().cloned().collect());
+  }
   let main_module = &metadata.entrypoint;
   let dir = DenoDir::new(None)?;
   let root_cert_store_provider = Arc::new(StandaloneRootCertStoreProvider {
@@ -207,7 +257,7 @@ pub async fn run(
     http_client.clone(),
     progress_bar.clone(),
   ));
-  let fs = Arc::new(deno_fs::RealFs);
+  let fs = Arc::new(DenoCompileFileSystem::new(assets));
   let npm_resolution =
     Arc::new(NpmResolution::from_serialized(npm_api.clone(), None, None));
   let npm_fs_resolver = create_npm_fs_resolver(
@@ -269,6 +319,8 @@ pub async fn run(
       // todo(dsherret): support a binary command being compiled
       maybe_binary_npm_command_name: None,
       origin_data_folder_path: None,
+      fetch_cache_folder_path: None,
+      permission_presets: Default::default(),
       seed: metadata.seed,
       unsafely_ignore_certificate_errors: metadata
         .unsafely_ignore_certificate_errors,
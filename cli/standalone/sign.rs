@@ -0,0 +1,511 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The signing of compiled executables. The signing tools append the
+//! signature after the payload of `deno compile`, so the layout of the binary
+//! is adjusted for them: the signature of the base binary is removed, the
+//! payload is aligned so that the signature starts right after it, and the
+//! `__LINKEDIT` segment of a Mach-O binary is extended over the payload, since
+//! `codesign` refuses data at the end of a binary that's outside of it.
+
+use std::io;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+
+use crate::args::CompileSignFlags;
+
+/// The alignment of the end of the payload, which is where the signing tools
+/// write the signature.
+pub const PAYLOAD_ALIGNMENT: usize = 16;
+
+const MACHO_MAGIC_64: u32 = 0xfeed_facf;
+const MACHO_HEADER_SIZE: usize = 32;
+const LC_SEGMENT_64: u32 = 0x19;
+const LC_CODE_SIGNATURE: u32 = 0x1d;
+const MACHO_PAGE_SIZE: u64 = 0x4000;
+
+const PE_SIGNATURE: &[u8; 4] = b"PE\0\0";
+const PE32_MAGIC: u16 = 0x10b;
+const PE32_PLUS_MAGIC: u16 = 0x20b;
+/// The index of the certificate table in the data directories.
+const PE_SECURITY_DIRECTORY: usize = 4;
+
+struct Signature {
+  header: SignatureHeader,
+  data_offset: u64,
+  data_size: u64,
+}
+
+/// Where the headers of a binary locate its signature.
+enum SignatureHeader {
+  /// The offset of the `LC_CODE_SIGNATURE` command.
+  MachO(usize),
+  /// The offset of the certificate table entry of the data directories.
+  Pe(usize),
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+  Some(u16::from_le_bytes(
+    bytes.get(offset..offset + 2)?.try_into().ok()?,
+  ))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+  Some(u32::from_le_bytes(
+    bytes.get(offset..offset + 4)?.try_into().ok()?,
+  ))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+  Some(u64::from_le_bytes(
+    bytes.get(offset..offset + 8)?.try_into().ok()?,
+  ))
+}
+
+fn write_u32(bytes: &mut [u8], offset: usize, value: u32) {
+  bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn write_u64(bytes: &mut [u8], offset: usize, value: u64) {
+  bytes[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
+
+/// The offsets, types and sizes of the load commands of a 64-bit Mach-O
+/// binary.
+fn macho_commands(bytes: &[u8]) -> Option<Vec<(usize, u32, usize)>> {
+  if read_u32(bytes, 0)? != MACHO_MAGIC_64 {
+    return None;
+  }
+  let ncmds = read_u32(bytes, 16)?;
+  let mut commands = Vec::with_capacity(ncmds as usize);
+  let mut offset = MACHO_HEADER_SIZE;
+  for _ in 0..ncmds {
+    let cmd = read_u32(bytes, offset)?;
+    let size = read_u32(bytes, offset + 4)? as usize;
+    commands.push((offset, cmd, size));
+    offset += size;
+  }
+  Some(commands)
+}
+
+/// The offset of the `__LINKEDIT` segment command of a Mach-O binary.
+fn macho_linkedit_offset(bytes: &[u8]) -> Option<usize> {
+  macho_commands(bytes)?
+    .into_iter()
+    .find(|(offset, cmd, _)| {
+      *cmd == LC_SEGMENT_64
+        && bytes.get(offset + 8..offset + 24)
+          == Some(b"__LINKEDIT\0\0\0\0\0\0".as_slice())
+    })
+    .map(|(offset, _, _)| offset)
+}
+
+/// The offset of the data directories of a PE binary.
+fn pe_data_directories_offset(bytes: &[u8]) -> Option<usize> {
+  if bytes.get(0..2) != Some(b"MZ".as_slice()) {
+    return None;
+  }
+  let pe_offset = read_u32(bytes, 0x3c)? as usize;
+  if bytes.get(pe_offset..pe_offset + 4) != Some(PE_SIGNATURE.as_slice()) {
+    return None;
+  }
+  // the optional header follows the signature and the COFF header
+  let optional_header_offset = pe_offset + 24;
+  match read_u16(bytes, optional_header_offset)? {
+    PE32_MAGIC => Some(optional_header_offset + 96),
+    PE32_PLUS_MAGIC => Some(optional_header_offset + 112),
+    _ => None,
+  }
+}
+
+/// Finds the signature of a binary from its headers.
+fn find_signature(bytes: &[u8]) -> Option<Signature> {
+  if let Some(commands) = macho_commands(bytes) {
+    let (command_offset, _, _) = commands
+      .into_iter()
+      .find(|(_, cmd, _)| *cmd == LC_CODE_SIGNATURE)?;
+    return Some(Signature {
+      header: SignatureHeader::MachO(command_offset),
+      data_offset: read_u32(bytes, command_offset + 8)? as u64,
+      data_size: read_u32(bytes, command_offset + 12)? as u64,
+    });
+  }
+  let directory_offset =
+    pe_data_directories_offset(bytes)? + PE_SECURITY_DIRECTORY * 8;
+  let data_offset = read_u32(bytes, directory_offset)? as u64;
+  let data_size = read_u32(bytes, directory_offset + 4)? as u64;
+  if data_offset == 0 || data_size == 0 {
+    return None;
+  }
+  Some(Signature {
+    header: SignatureHeader::Pe(directory_offset),
+    data_offset,
+    data_size,
+  })
+}
+
+/// Removes the signature of a base binary, which the payload would otherwise
+/// follow.
+pub fn remove_signature(bin: &mut Vec<u8>) -> Result<(), AnyError> {
+  let Some(signature) = find_signature(bin) else {
+    return Ok(());
+  };
+  let data_offset = signature.data_offset;
+  match signature.header {
+    SignatureHeader::MachO(command_offset) => {
+      let linkedit_offset = macho_linkedit_offset(bin)
+        .context("The Mach-O binary has no __LINKEDIT segment.")?;
+      let command_size = read_u32(bin, command_offset + 4).unwrap() as usize;
+      let ncmds = read_u32(bin, 16).unwrap();
+      let sizeofcmds = read_u32(bin, 20).unwrap() as usize;
+      let commands_end = MACHO_HEADER_SIZE + sizeofcmds;
+      // move the commands that follow the removed one, and zero the space
+      // that it leaves at the end
+      bin.copy_within(
+        command_offset + command_size..commands_end,
+        command_offset,
+      );
+      bin[commands_end - command_size..commands_end].fill(0);
+      write_u32(bin, 16, ncmds - 1);
+      write_u32(bin, 20, (sizeofcmds - command_size) as u32);
+
+      // the signature is at the end of the __LINKEDIT segment
+      let linkedit_offset = if linkedit_offset > command_offset {
+        linkedit_offset - command_size
+      } else {
+        linkedit_offset
+      };
+      let fileoff = read_u64(bin, linkedit_offset + 40).unwrap();
+      write_u64(bin, linkedit_offset + 48, data_offset - fileoff);
+      bin.truncate(data_offset as usize);
+    }
+    SignatureHeader::Pe(directory_offset) => {
+      write_u64(bin, directory_offset, 0);
+      bin.truncate(data_offset as usize);
+    }
+  }
+  Ok(())
+}
+
+/// Extends the `__LINKEDIT` segment of a Mach-O base binary to the end of the
+/// binary, which has the length `len` once the payload is appended.
+pub fn extend_linkedit(bin: &mut [u8], len: u64) -> Result<(), AnyError> {
+  if macho_commands(bin).is_none() {
+    return Ok(());
+  }
+  let linkedit_offset = macho_linkedit_offset(bin)
+    .context("The Mach-O binary has no __LINKEDIT segment.")?;
+  let fileoff = read_u64(bin, linkedit_offset + 40).unwrap();
+  let filesize = len - fileoff;
+  let vmsize =
+    (filesize + MACHO_PAGE_SIZE - 1) / MACHO_PAGE_SIZE * MACHO_PAGE_SIZE;
+  write_u64(bin, linkedit_offset + 32, vmsize);
+  write_u64(bin, linkedit_offset + 48, filesize);
+  Ok(())
+}
+
+/// The offset of the end of the payload of a binary, which is followed by its
+/// signature when it's signed. The signature of the base binary of an unsigned
+/// binary is before the payload, so only a signature that ends the binary
+/// counts.
+pub fn payload_end(file: &mut (impl Read + Seek)) -> io::Result<u64> {
+  let len = file.seek(SeekFrom::End(0))?;
+  let mut headers = vec![0; len.min(64 * 1024) as usize];
+  file.seek(SeekFrom::Start(0))?;
+  file.read_exact(&mut headers)?;
+  Ok(match find_signature(&headers) {
+    Some(signature) if signature.data_offset + signature.data_size == len => {
+      signature.data_offset
+    }
+    _ => len,
+  })
+}
+
+/// Signs a compiled executable with the signing tool of its platform.
+pub fn sign_executable(
+  path: &Path,
+  target: Option<&str>,
+  flags: &CompileSignFlags,
+) -> Result<(), AnyError> {
+  let (is_windows, is_macos) = match target {
+    Some(target) => (target.contains("windows"), target.contains("darwin")),
+    None => (cfg!(windows), cfg!(target_os = "macos")),
+  };
+  if is_windows {
+    sign_windows_executable(path, flags)
+  } else if is_macos {
+    sign_macos_executable(path, flags)
+  } else {
+    bail!("Only Windows and macOS executables can be signed.")
+  }
+}
+
+fn sign_windows_executable(
+  path: &Path,
+  flags: &CompileSignFlags,
+) -> Result<(), AnyError> {
+  let password = std::env::var("DENO_SIGN_PASSWORD").ok();
+  let is_certificate_file = Path::new(&flags.identity).is_file();
+  if cfg!(windows) {
+    let mut command = Command::new("signtool");
+    command.args(["sign", "/fd", "SHA256"]);
+    if is_certificate_file {
+      // signtool only takes the password on its command line, where other
+      // processes can read it.
+      if password.is_some() {
+        bail!(
+          "signtool can't read the password of a PKCS#12 file from \
+           DENO_SIGN_PASSWORD. Import the certificate into the certificate \
+           store and pass its subject name to --sign instead."
+        );
+      }
+      command.arg("/f").arg(&flags.identity);
+    } else {
+      command.arg("/n").arg(&flags.identity);
+    }
+    if let Some(url) = &flags.timestamp_url {
+      command.args(["/tr", url, "/td", "SHA256"]);
+    }
+    command.arg(path);
+    run_signing_tool("signtool", command)
+  } else {
+    if !is_certificate_file {
+      bail!(
+        "The certificate file \"{}\" does not exist. Windows executables can \
+         only be signed with a certificate of the store on Windows.",
+        flags.identity
+      );
+    }
+    let mut signed_path = path.as_os_str().to_owned();
+    signed_path.push(".signed");
+    let mut command = Command::new("osslsigncode");
+    command
+      .args(["sign", "-pkcs12"])
+      .arg(&flags.identity)
+      .args(["-h", "sha256"]);
+    // The password is passed in a file that only the current user can read,
+    // rather than on the command line.
+    let password_file = match &password {
+      Some(password) => {
+        let mut file = tempfile::NamedTempFile::new()?;
+        file.write_all(password.as_bytes())?;
+        file.flush()?;
+        command.arg("-readpass").arg(file.path());
+        Some(file)
+      }
+      None => None,
+    };
+    if let Some(url) = &flags.timestamp_url {
+      command.args(["-ts", url]);
+    }
+    command.arg("-in").arg(path).arg("-out").arg(&signed_path);
+    run_signing_tool("osslsigncode", command)?;
+    drop(password_file);
+    std::fs::rename(&signed_path, path)?;
+    Ok(())
+  }
+}
+
+fn sign_macos_executable(
+  path: &Path,
+  flags: &CompileSignFlags,
+) -> Result<(), AnyError> {
+  if !cfg!(target_os = "macos") {
+    bail!("macOS executables can only be signed on macOS.");
+  }
+  let mut command = Command::new("codesign");
+  command.args(["--sign", &flags.identity]).args([
+    "--force",
+    "--options",
+    "runtime",
+  ]);
+  if let Some(url) = &flags.timestamp_url {
+    command.arg(format!("--timestamp={url}"));
+  }
+  command.arg(path);
+  run_signing_tool("codesign", command)?;
+
+  if let Some(notarize_command) = &flags.notarize_command {
+    let args = split_command(notarize_command)?;
+    let Some((program, args)) = args.split_first() else {
+      return Ok(());
+    };
+    let mut command = Command::new(program);
+    command.args(args).arg(path);
+    run_signing_tool(program, command)?;
+  }
+  Ok(())
+}
+
+/// Splits a command into its arguments like a POSIX shell does, with
+/// whitespace outside of quotes separating them. Characters are taken
+/// literally inside single quotes, and a backslash escapes the next character
+/// outside of them.
+fn split_command(command: &str) -> Result<Vec<String>, AnyError> {
+  let mut args = vec![];
+  let mut arg = None::<String>;
+  let mut chars = command.chars();
+  while let Some(c) = chars.next() {
+    match c {
+      '\'' => {
+        let arg = arg.get_or_insert_with(String::new);
+        loop {
+          match chars.next() {
+            Some('\'') => break,
+            Some(c) => arg.push(c),
+            None => bail!("Unterminated quote in \"{command}\"."),
+          }
+        }
+      }
+      '"' => {
+        let arg = arg.get_or_insert_with(String::new);
+        loop {
+          match chars.next() {
+            Some('"') => break,
+            Some('\\') => match chars.next() {
+              Some(c @ ('"' | '\\')) => arg.push(c),
+              Some(c) => {
+                arg.push('\\');
+                arg.push(c);
+              }
+              None => bail!("Unterminated quote in \"{command}\"."),
+            },
+            Some(c) => arg.push(c),
+            None => bail!("Unterminated quote in \"{command}\"."),
+          }
+        }
+      }
+      '\\' => {
+        let Some(c) = chars.next() else {
+          bail!("Trailing backslash in \"{command}\".");
+        };
+        arg.get_or_insert_with(String::new).push(c);
+      }
+      c if c.is_whitespace() => args.extend(arg.take()),
+      c => arg.get_or_insert_with(String::new).push(c),
+    }
+  }
+  args.extend(arg);
+  Ok(args)
+}
+
+fn run_signing_tool(name: &str, mut command: Command) -> Result<(), AnyError> {
+  let status = command
+    .status()
+    .with_context(|| format!("Failed to run {name}. Is it installed?"))?;
+  if !status.success() {
+    bail!("{name} failed to sign the executable ({status}).");
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A Mach-O binary with a `__LINKEDIT` segment at 256, whose last 32 bytes
+  /// are the signature.
+  fn macho_binary() -> Vec<u8> {
+    let mut bin = vec![0; 320];
+    write_u32(&mut bin, 0, MACHO_MAGIC_64);
+    write_u32(&mut bin, 16, 2);
+    write_u32(&mut bin, 20, 72 + 16);
+    let segment = MACHO_HEADER_SIZE;
+    write_u32(&mut bin, segment, LC_SEGMENT_64);
+    write_u32(&mut bin, segment + 4, 72);
+    bin[segment + 8..segment + 18].copy_from_slice(b"__LINKEDIT");
+    write_u64(&mut bin, segment + 32, MACHO_PAGE_SIZE);
+    write_u64(&mut bin, segment + 40, 256);
+    write_u64(&mut bin, segment + 48, 64);
+    let signature = segment + 72;
+    write_u32(&mut bin, signature, LC_CODE_SIGNATURE);
+    write_u32(&mut bin, signature + 4, 16);
+    write_u32(&mut bin, signature + 8, 288);
+    write_u32(&mut bin, signature + 12, 32);
+    bin
+  }
+
+  #[test]
+  fn test_macho_signature() {
+    let mut bin = macho_binary();
+    assert_eq!(payload_end(&mut io::Cursor::new(&bin)).unwrap(), 288);
+    // the signature of the base binary of an unsigned binary
+    let mut unsigned = bin.clone();
+    unsigned.extend([1; 48]);
+    assert_eq!(payload_end(&mut io::Cursor::new(&unsigned)).unwrap(), 368);
+
+    remove_signature(&mut bin).unwrap();
+    assert_eq!(bin.len(), 288);
+    assert_eq!(read_u32(&bin, 16), Some(1));
+    assert_eq!(read_u32(&bin, 20), Some(72));
+    assert_eq!(read_u32(&bin, MACHO_HEADER_SIZE + 72), Some(0));
+    assert_eq!(read_u64(&bin, MACHO_HEADER_SIZE + 48), Some(32));
+    assert_eq!(payload_end(&mut io::Cursor::new(&bin)).unwrap(), 288);
+
+    extend_linkedit(&mut bin, 20_000).unwrap();
+    assert_eq!(read_u64(&bin, MACHO_HEADER_SIZE + 48), Some(20_000 - 256));
+    assert_eq!(read_u64(&bin, MACHO_HEADER_SIZE + 32), Some(0x8000));
+  }
+
+  #[test]
+  fn test_pe_signature() {
+    let mut bin = vec![0; 512];
+    bin[0..2].copy_from_slice(b"MZ");
+    write_u32(&mut bin, 0x3c, 64);
+    bin[64..68].copy_from_slice(PE_SIGNATURE);
+    bin[88..90].copy_from_slice(&PE32_PLUS_MAGIC.to_le_bytes());
+    let directory = 88 + 112 + PE_SECURITY_DIRECTORY * 8;
+    write_u32(&mut bin, directory, 480);
+    write_u32(&mut bin, directory + 4, 32);
+    assert_eq!(payload_end(&mut io::Cursor::new(&bin)).unwrap(), 480);
+
+    remove_signature(&mut bin).unwrap();
+    assert_eq!(bin.len(), 480);
+    assert_eq!(read_u64(&bin, directory), Some(0));
+    assert_eq!(payload_end(&mut io::Cursor::new(&bin)).unwrap(), 480);
+  }
+
+  #[test]
+  fn test_split_command() {
+    assert_eq!(
+      split_command("xcrun notarytool submit --wait").unwrap(),
+      vec!["xcrun", "notarytool", "submit", "--wait"]
+    );
+    assert_eq!(
+      split_command(
+        r#"notarize --profile "My Profile" --key '/a b/key.p8' a\ b "" x"y"z"#
+      )
+      .unwrap(),
+      vec![
+        "notarize",
+        "--profile",
+        "My Profile",
+        "--key",
+        "/a b/key.p8",
+        "a b",
+        "",
+        "xyz"
+      ]
+    );
+    assert_eq!(
+      split_command(r#"echo "a \"b\" \c""#).unwrap(),
+      vec!["echo", r#"a "b" \c"#]
+    );
+    assert!(split_command("notarize 'a").is_err());
+    assert!(split_command("notarize \"a").is_err());
+    assert!(split_command("").unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_payload_end_unknown_format() {
+    let bin = vec![1; 100];
+    assert_eq!(payload_end(&mut io::Cursor::new(&bin)).unwrap(), 100);
+  }
+}
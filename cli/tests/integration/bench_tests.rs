@@ -226,6 +226,63 @@ fn file_protocol() {
     .assert_matches_file("bench/file_protocol.out");
 }
 
+#[test]
+fn compare_with_baseline() {
+  let file_url =
+    Url::from_file_path(util::testdata_path().join("bench/pass.ts"))
+      .unwrap()
+      .to_string();
+  let context = TestContext::default();
+  let temp_dir = context.temp_dir();
+  let baseline = |avg: f64| {
+    format!(
+      r#"{{
+  "runtime": "Deno/1.0.0 x86_64-unknown-linux-gnu",
+  "cpu": "unknown",
+  "benches": [
+    {{
+      "origin": "{file_url}",
+      "group": null,
+      "name": "bench0",
+      "baseline": false,
+      "results": [{{ "ok": {{ "n": 1000, "min": {avg}, "max": {avg}, "avg": {avg}, "stddev": 0, "p75": {avg}, "p99": {avg}, "p995": {avg}, "p999": {avg} }} }}]
+    }}
+  ]
+}}"#
+    )
+  };
+
+  // An empty function can't take a day per iteration.
+  temp_dir.write("slow.json", baseline(8.64e13));
+  let output = context
+    .new_command()
+    .args(format!(
+      "bench --compare={} bench/pass.ts",
+      temp_dir.path().join("slow.json").display()
+    ))
+    .run();
+  output.assert_exit_code(0);
+  let out = util::strip_ansi_codes(output.combined_output()).to_string();
+  assert_contains!(out, "comparison with baseline");
+  assert_contains!(out, "improvement");
+
+  // Nor can it take a femtosecond.
+  temp_dir.write("fast.json", baseline(1e-6));
+  let output = context
+    .new_command()
+    .args(format!(
+      "bench --json --compare={} bench/pass.ts",
+      temp_dir.path().join("fast.json").display()
+    ))
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(output.stdout(), r#""verdict": "regression""#);
+  assert_contains!(
+    output.combined_output(),
+    "Bench failed because of 1 significant regression from the baseline"
+  );
+}
+
 itest!(package_json_basic {
   args: "bench",
   output: "package_json/basic/lib.bench.out",
@@ -480,3 +480,13 @@ itest!(bundle_shebang_file {
   args: "bundle subdir/shebang_file.js",
   output: "bundle/shebang_file.bundle.out",
 });
+
+itest!(bundle_external {
+  args: "bundle --quiet --external=npm:* bundle/external/mod.ts",
+  output: "bundle/external/mod.out",
+});
+
+itest!(bundle_external_minify_source_map {
+  args: "bundle --quiet --external=npm:* --minify --source-map=inline bundle/external/mod.ts",
+  output: "bundle/external/mod_minified.out",
+});
@@ -695,10 +695,42 @@ fn workers_not_in_module_map() {
   let output = Command::new(&exe).env("NO_COLOR", "").output().unwrap();
   assert!(!output.status.success());
   let stderr = String::from_utf8(output.stderr).unwrap();
-  assert!(stderr.starts_with(concat!(
-    "error: Uncaught (in worker \"\") Module not found\n",
+  assert!(stderr.starts_with(
+    "error: Uncaught (in worker \"\") Module not found \"file:///"
+  ));
+  assert!(stderr.contains("/workers/worker.ts\" in the compiled executable."));
+  assert!(stderr.contains("--include=\"./plugins/*.ts\""));
+  assert!(stderr.contains(
     "error: Uncaught (in promise) Error: Unhandled error in child worker.\n"
-  )));
+  ));
+}
+
+#[test]
+fn workers_new_url() {
+  let _guard = util::http_server();
+  let dir = TempDir::new();
+  let exe = if cfg!(windows) {
+    dir.path().join("new_url.exe")
+  } else {
+    dir.path().join("new_url")
+  };
+  let output = util::deno_cmd()
+    .current_dir(util::root_path())
+    .arg("compile")
+    .arg("--output")
+    .arg(&exe)
+    .arg(util::testdata_path().join("./compile/workers/new_url.ts"))
+    .output()
+    .unwrap();
+  assert!(output.status.success());
+
+  // the worker is embedded, because it's started with a `new URL()` relative
+  // to `import.meta.url`
+  let output = Command::new(&exe).env("NO_COLOR", "").output().unwrap();
+  assert!(output.status.success());
+  let expected_stdout =
+    concat!("Hello from worker!\n", "Received 42\n", "Closing\n");
+  assert_eq!(&String::from_utf8(output.stdout).unwrap(), expected_stdout);
 }
 
 #[test]
@@ -790,3 +822,87 @@ fn dynamic_import_unanalyzable() {
   .unwrap();
   assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
 }
+
+#[test]
+fn include_assets() {
+  let dir = TempDir::new();
+  let project = dir.path().join("project");
+  util::copy_dir_recursive(
+    &util::testdata_path().join("compile/assets"),
+    &project,
+  )
+  .unwrap();
+  let exe = if cfg!(windows) {
+    dir.path().join("include_assets.exe")
+  } else {
+    dir.path().join("include_assets")
+  };
+  let output = util::deno_cmd()
+    .current_dir(&project)
+    .arg("compile")
+    .arg("--include")
+    .arg("static/**")
+    .arg("--output")
+    .arg(&exe)
+    .arg("main.ts")
+    .output()
+    .unwrap();
+  assert!(output.status.success());
+
+  // the assets are read from the executable rather than the disk
+  std::fs::remove_dir_all(project.join("static")).unwrap();
+  let output = Command::new(&exe)
+    .current_dir(dir.path())
+    .env("NO_COLOR", "")
+    .output()
+    .unwrap();
+  assert!(output.status.success());
+  assert_eq!(
+    String::from_utf8(output.stdout).unwrap(),
+    "Hello from an asset!\n42\nasset\nhello.txt file, nested dir\n"
+  );
+}
+
+#[test]
+fn dynamic_import_glob_include() {
+  let dir = TempDir::new();
+  let exe = if cfg!(windows) {
+    dir.path().join("dynamic_import_glob_include.exe")
+  } else {
+    dir.path().join("dynamic_import_glob_include")
+  };
+  let output = util::deno_cmd()
+    .current_dir(util::testdata_path().join("compile/dynamic_imports_glob"))
+    .arg("compile")
+    .arg("--include")
+    .arg("./plugins/*.ts")
+    .arg("--output")
+    .arg(&exe)
+    .arg("main.ts")
+    .output()
+    .unwrap();
+  assert!(output.status.success());
+
+  let output = Command::new(&exe)
+    .env("NO_COLOR", "")
+    .arg("b")
+    .arg("a")
+    .output()
+    .unwrap();
+  assert!(output.status.success());
+  assert_eq!(
+    String::from_utf8(output.stdout).unwrap(),
+    "plugin b\nplugin a\n"
+  );
+
+  let output = Command::new(&exe)
+    .env("NO_COLOR", "")
+    .arg("c")
+    .output()
+    .unwrap();
+  assert!(!output.status.success());
+  let stderr = String::from_utf8(output.stderr).unwrap();
+  assert_contains!(stderr, "plugins/c.ts\" in the compiled executable");
+  assert_contains!(stderr, "plugins/a.ts");
+  assert_contains!(stderr, "plugins/b.ts");
+}
@@ -236,6 +236,134 @@ fn multifile_coverage() {
   output.assert_exit_code(0);
 }
 
+#[test]
+fn html_report() {
+  let context = TestContext::default();
+  let tempdir = context.deno_dir();
+  let cov_dir = tempdir.path().join("cov");
+  let html_dir = tempdir.path().join("html");
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "test".to_string(),
+      "--quiet".to_string(),
+      format!("--coverage={}", cov_dir.to_str().unwrap()),
+      "coverage/multifile/".to_string(),
+    ])
+    .run();
+
+  output.assert_exit_code(0);
+  output.skip_output_check();
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "coverage".to_string(),
+      format!("--html={}", html_dir.to_str().unwrap()),
+      format!("{}/", cov_dir.to_str().unwrap()),
+    ])
+    .run();
+
+  output.assert_exit_code(0);
+  assert!(output
+    .combined_output()
+    .contains("HTML coverage report has been generated at file://"));
+
+  let index = fs::read_to_string(html_dir.join("index.html")).unwrap();
+  assert!(index.contains(
+    r#"<a href="coverage/multifile/mod.js.html">coverage/multifile/mod.js</a>"#
+  ));
+  let page =
+    fs::read_to_string(html_dir.join("coverage/multifile/mod.js.html"))
+      .unwrap();
+  assert!(page.contains(r#"<a href="../../index.html">All files</a>"#));
+  assert!(page.contains(r#"<tr class="hit">"#));
+}
+
+#[test]
+fn check_thresholds() {
+  let context = TestContext::default();
+  let tempdir = context.deno_dir();
+  let cov_dir = tempdir.path().join("cov");
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "test".to_string(),
+      "-A".to_string(),
+      "--quiet".to_string(),
+      format!("--coverage={}", cov_dir.to_str().unwrap()),
+      "coverage/complex_test.ts".to_string(),
+    ])
+    .run();
+
+  output.assert_exit_code(0);
+  output.skip_output_check();
+
+  // complex.ts has 72.222% of its lines and 50% of its functions covered.
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "coverage".to_string(),
+      "--check".to_string(),
+      "--lines=70".to_string(),
+      "--functions=50".to_string(),
+      format!("{}/", cov_dir.to_str().unwrap()),
+    ])
+    .run();
+  output.assert_exit_code(0);
+  output.skip_output_check();
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "coverage".to_string(),
+      "--check".to_string(),
+      "--lines=80".to_string(),
+      "--functions=50".to_string(),
+      format!("{}/", cov_dir.to_str().unwrap()),
+    ])
+    .run();
+  output.assert_exit_code(1);
+  let out = util::strip_ansi_codes(output.combined_output()).to_string();
+  assert!(out.contains("error: Coverage is below the thresholds:"));
+  assert!(out.contains("lines 72.222% < 80% in file:///"));
+  assert!(out.contains("lines 72.222% < 80% in total"));
+  assert!(!out.contains("functions"));
+
+  // The thresholds of the configuration file apply unless overridden.
+  tempdir.write(
+    "deno.json",
+    r#"{ "coverage": { "thresholds": { "functions": 60 } } }"#,
+  );
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "coverage".to_string(),
+      "--check".to_string(),
+      format!("--config={}", tempdir.path().join("deno.json").display()),
+      format!("{}/", cov_dir.to_str().unwrap()),
+    ])
+    .run();
+  output.assert_exit_code(1);
+  let out = util::strip_ansi_codes(output.combined_output()).to_string();
+  assert!(out.contains("functions 50.000% < 60% in total"));
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "coverage".to_string(),
+      "--check".to_string(),
+      "--functions=50".to_string(),
+      format!("--config={}", tempdir.path().join("deno.json").display()),
+      format!("{}/", cov_dir.to_str().unwrap()),
+    ])
+    .run();
+  output.assert_exit_code(0);
+  output.skip_output_check();
+}
+
 fn no_snaps_included(test_name: &str, extension: &str) {
   let context = TestContext::default();
   let tempdir = context.deno_dir();
@@ -26,6 +26,53 @@ fn deno_doc() {
   }
 }
 
+#[test]
+fn deno_doc_html() {
+  let context = TestContext::default();
+  let docs_dir = context.deno_dir().path().join("docs");
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "doc".to_string(),
+      "--html".to_string(),
+      "--name=Test".to_string(),
+      format!("--output={}", docs_dir.to_str().unwrap()),
+      "doc/060_deno_doc_displays_all_overloads_in_details_view.ts".to_string(),
+    ])
+    .run();
+
+  output.assert_exit_code(0);
+  assert_contains!(
+    output.combined_output(),
+    "HTML documentation has been generated at file://"
+  );
+
+  let index = std::fs::read_to_string(docs_dir.join("index.html")).unwrap();
+  assert_contains!(index, "<title>Test</title>");
+  assert_contains!(index, r#"<a href="NS.html">NS</a>"#);
+  let page = std::fs::read_to_string(docs_dir.join("NS.html")).unwrap();
+  assert_contains!(page, r#"<a href="NS.test.html">NS.test</a>"#);
+  let page = std::fs::read_to_string(docs_dir.join("NS.test.html")).unwrap();
+  assert_contains!(
+    page,
+    "060_deno_doc_displays_all_overloads_in_details_view.ts#L"
+  );
+  let search_index =
+    std::fs::read_to_string(docs_dir.join("search_index.js")).unwrap();
+  assert_contains!(search_index, r#""name":"NS.test""#);
+}
+
+itest!(deno_doc_lint {
+  args: "doc --lint doc/lint/mod.ts",
+  output: "doc/lint/mod.out",
+  exit_code: 1,
+});
+
+itest!(deno_doc_lint_documented {
+  args: "doc --lint doc/lint/documented.ts",
+  output_str: Some(""),
+});
+
 itest!(deno_doc_import_map {
   args: "doc --unstable --import-map=doc/import_map.json doc/use_import_map.js",
   output: "doc/use_import_map.out",
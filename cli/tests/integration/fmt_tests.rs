@@ -1,7 +1,9 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use test_util as util;
+use test_util::assert_contains;
 use test_util::TempDir;
+use test_util::TestContextBuilder;
 use util::TestContext;
 
 #[test]
@@ -164,6 +166,64 @@ fn fmt_auto_ignore_git_and_node_modules() {
   assert_eq!(output.combined_output(), "error: No target files found.\n");
 }
 
+#[test]
+fn fmt_plugin_invalid_module() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{ "fmt": { "plugins": ["./plugins/yaml.wasm"] } }"#,
+  );
+  temp_dir.create_dir_all("plugins");
+  temp_dir.write("plugins/yaml.wasm", "not a Wasm module");
+  temp_dir.write("main.ts", "const a = 1;\n");
+
+  let output = context.new_command().args("fmt --check").run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "Failed to load the fmt plugin file:///"
+  );
+  assert_contains!(output.combined_output(), "plugins/yaml.wasm");
+}
+
+#[test]
+fn fmt_plugin_remote_integrity() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("main.ts", "const a = 1;\n");
+
+  temp_dir.write(
+    "deno.json",
+    r#"{ "fmt": { "plugins": ["http://localhost:4545/assets/unreachable.wasm"] } }"#,
+  );
+  let output = context.new_command().args("fmt --check").run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "Remote fmt plugins require an integrity. Add \"integrity\": \""
+  );
+
+  temp_dir.write(
+    "deno.json",
+    r#"{
+      "fmt": {
+        "plugins": [{
+          "specifier": "http://localhost:4545/assets/unreachable.wasm",
+          "integrity": "0000"
+        }]
+      }
+    }"#,
+  );
+  let output = context.new_command().args("fmt --check").run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "Integrity check failed.");
+  assert_contains!(output.combined_output(), "Expected: 0000");
+}
+
 itest!(fmt_quiet_check_fmt_dir {
   args: "fmt --check --quiet fmt/regular/",
   output_str: Some(""),
@@ -246,6 +306,12 @@ itest!(fmt_with_config_and_flags {
   output: "fmt/fmt_with_config_and_flags.out",
 });
 
+itest!(fmt_stdin_embedded_languages {
+  args: "fmt --config fmt/embedded_languages/deno.json -",
+  input: Some("/**\n * ```ts\n * const user=await findUser( 1 )\n * ```\n */\nexport function findUser(id: number) {\n  const options = json`{\"cache\":true}`;\n  return query(sql`\nSELECT * FROM users WHERE id = ${id}\n`, options);\n}\n"),
+  output: "fmt/embedded_languages.out",
+});
+
 itest!(fmt_with_malformed_config {
   args: "fmt --config fmt/deno.malformed.jsonc",
   output: "fmt/fmt_with_malformed_config.out",
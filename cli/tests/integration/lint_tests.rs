@@ -1,5 +1,10 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use test_util::assert_contains;
+use test_util::TestContextBuilder;
+
 itest!(ignore_unexplicit_files {
   args: "lint --unstable --ignore=./",
   output_str: Some("error: No target files found.\n"),
@@ -114,3 +119,159 @@ itest!(lint_with_malformed_config2 {
   output: "lint/with_malformed_config2.out",
   exit_code: 1,
 });
+
+#[test]
+fn lint_fix() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "main.ts",
+    concat!(
+      "import { a, b } from \"./mod.ts\";\n",
+      "import { c } from \"./mod.ts\";\n",
+      "let d = a;;\n",
+      "console.log(d);\n",
+    ),
+  );
+  temp_dir.write(
+    "mod.ts",
+    "export const a = 1;\nexport const b = 2;\nexport const c = 3;\n",
+  );
+
+  // The edits that fix the diagnostics are reported with them.
+  let output = context.new_command().args("lint --json main.ts").run();
+  output.assert_exit_code(1);
+  let report: serde_json::Value =
+    serde_json::from_str(output.combined_output()).unwrap();
+  let unused_c = report["diagnostics"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .find(|d| d["message"] == "`c` is never used")
+    .unwrap();
+  assert_eq!(
+    unused_c["fixes"],
+    json!([{ "range": { "start": 33, "end": 51 }, "newText": "import " }]),
+  );
+
+  let output = context.new_command().args("lint --fix main.ts").run();
+  output.assert_exit_code(0);
+  assert_eq!(
+    temp_dir.read_to_string("main.ts"),
+    concat!(
+      "import { a } from \"./mod.ts\";\n",
+      "import \"./mod.ts\";\n",
+      "const d = a;\n",
+      "console.log(d);\n",
+    ),
+  );
+
+  let output = context.new_command().args("lint --fix -").run();
+  output.assert_exit_code(1);
+  output.assert_matches_text(
+    "error: Lint fix on standard input is not supported.\n",
+  );
+}
+
+#[test]
+fn lint_plugins() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("deno.json", r#"{ "lint": { "plugins": ["./plugin.ts"] } }"#);
+  temp_dir.write(
+    "plugin.ts",
+    r#"export default {
+  name: "my-plugin",
+  rules: {
+    "no-foo": {
+      create(context) {
+        return {
+          Identifier(node) {
+            if (node.value === "foo") {
+              context.report({ node, message: "Don't use foo.", hint: "Use bar." });
+            }
+          },
+        };
+      },
+    },
+  },
+};
+"#,
+  );
+  temp_dir.write("main.ts", "const foo = 1;\nconsole.log(foo);\n");
+
+  let output = context.new_command().args("lint main.ts").run();
+  output.assert_exit_code(1);
+  output.assert_matches_text(
+    "(my-plugin/no-foo) Don't use foo.
+const foo = 1;
+      ^^^
+    at [WILDCARD]main.ts:1:7
+
+    hint: Use bar.
+[WILDCARD](my-plugin/no-foo) Don't use foo.
+console.log(foo);
+            ^^^
+    at [WILDCARD]main.ts:2:13
+
+    hint: Use bar.
+[WILDCARD]Found 2 problems
+Checked 1 file
+",
+  );
+
+  // The plugins run without permissions.
+  temp_dir.write(
+    "plugin.ts",
+    r#"Deno.readTextFileSync("main.ts");
+export default { name: "my-plugin", rules: {} };
+"#,
+  );
+  let output = context.new_command().args("lint main.ts").run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "Requires read access");
+}
+
+#[test]
+fn lint_severity() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{
+  "lint": {
+    "severity": { "no-unused-vars": "warn" },
+    "overrides": [
+      { "include": ["tests/"], "severity": { "no-unused-vars": "error" } }
+    ]
+  }
+}"#,
+  );
+  temp_dir.write("main.ts", "const a = 1;\n");
+  temp_dir.create_dir_all("tests");
+  temp_dir.write("tests/a.ts", "const b = 1;\n");
+
+  // warnings don't fail the run
+  let output = context.new_command().args("lint main.ts").run();
+  output.assert_exit_code(0);
+  output.assert_matches_text(
+    "warning (no-unused-vars) `a` is never used
+[WILDCARD]Found 1 problem (1 warning)
+Checked 1 file
+",
+  );
+
+  // the files are linted in parallel, so their order is unknown
+  let output = context.new_command().args("lint --compact").run();
+  output.assert_exit_code(1);
+  let output_text = output.combined_output();
+  assert_contains!(
+    output_text,
+    "main.ts: line 1, col 7 - warning: `a` is never used (no-unused-vars)"
+  );
+  assert_contains!(
+    output_text,
+    "a.ts: line 1, col 7 - `b` is never used (no-unused-vars)"
+  );
+  assert_contains!(output_text, "Found 2 problems (1 warning)");
+}
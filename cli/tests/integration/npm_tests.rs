@@ -1532,6 +1532,14 @@ itest!(info_peer_deps_json {
   http_server: true,
 });
 
+itest!(info_peer_deps_why {
+  args: "info --quiet --why=npm:@denotest/peer-dep-test-child npm/peer_deps_with_copied_folders/main.ts",
+  output: "npm/peer_deps_with_copied_folders/main_info_why.out",
+  exit_code: 0,
+  envs: env_vars_for_npm_tests(),
+  http_server: true,
+});
+
 itest!(create_require {
   args: "run --reload --allow-read npm/create_require/main.ts",
   output: "npm/create_require/main.out",
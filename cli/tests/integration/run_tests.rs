@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::serde_json;
 use deno_core::url;
 use deno_runtime::deno_fetch::reqwest;
 use std::io::Read;
@@ -13,6 +14,7 @@ use tokio::task::LocalSet;
 use trust_dns_client::serialize::txt::Lexer;
 use trust_dns_client::serialize::txt::Parser;
 use util::assert_contains;
+use util::assert_not_contains;
 use util::env_vars_for_npm_tests_no_sync_download;
 use util::TestContext;
 use util::TestContextBuilder;
@@ -1339,6 +1341,22 @@ itest!(v8_flags_env_run {
   output: "run/v8_flags.js.out",
 });
 
+itest!(env_file {
+  args: "run --allow-env=FOO,GREETING --env-file=run/env_file/.env run/env_file/main.ts",
+  output: "run/env_file/main.out",
+});
+
+itest!(env_file_no_permission {
+  args: "run --no-prompt --env-file=run/env_file/.env run/env_file/main.ts",
+  output: "run/env_file/no_permission.out",
+  exit_code: 1,
+});
+
+itest!(workspace_member_imports {
+  args: "run workspace/packages/app/main.ts",
+  output: "workspace/packages/app/main.out",
+});
+
 itest!(v8_flags_unrecognized {
   args: "repl --v8-flags=--foo,bar,--trace-gc,-baz",
   output: "run/v8_flags_unrecognized.out",
@@ -4440,3 +4458,139 @@ itest!(extension_dynamic_import {
   output: "run/extension_dynamic_import.ts.out",
   exit_code: 1,
 });
+
+#[test]
+fn permission_set_from_config() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{ "permissions": { "default": { "allow": { "env": true } } } }"#,
+  );
+  temp_dir.write(
+    "main.ts",
+    r#"console.log((await Deno.permissions.query({ name: "env" })).state);"#,
+  );
+
+  // A set is not applied unless it's chosen, not even the "default" one
+  let output = context.new_command().args("run --no-prompt main.ts").run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("prompt\n");
+
+  let output = context
+    .new_command()
+    .args("run --permission-set=default main.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("granted\n");
+}
+
+#[test]
+fn permission_audit_log() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("config.txt", "");
+  temp_dir.write(
+    "main.ts",
+    r#"function readConfig() {
+  return Deno.readTextFileSync("config.txt");
+}
+readConfig();
+try {
+  Deno.env.get("HOME");
+} catch {}
+"#,
+  );
+
+  let output = context
+    .new_command()
+    .args("run --allow-read --permission-audit=audit.jsonl main.ts")
+    .run();
+  output.skip_output_check();
+  output.assert_exit_code(0);
+
+  let records = temp_dir
+    .read_to_string("audit.jsonl")
+    .lines()
+    .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+    .collect::<Vec<_>>();
+  let read = records
+    .iter()
+    .find(|record| record["permission"] == "read")
+    .unwrap();
+  assert_eq!(read["api"], "Deno.readFileSync()");
+  assert_eq!(read["result"], "granted");
+  assert_eq!(read["prompted"], false);
+  assert!(read["descriptor"].as_str().unwrap().ends_with("config.txt"));
+  // The innermost frame is the call site in the module, not in the runtime
+  let stack = read["stack"].as_array().unwrap();
+  let call_site = stack[0].as_str().unwrap();
+  assert!(call_site.starts_with("readConfig (file:///"), "{call_site}");
+  assert_contains!(call_site, "main.ts:2:");
+
+  let env = records
+    .iter()
+    .find(|record| record["permission"] == "env")
+    .unwrap();
+  assert_eq!(env["descriptor"], "HOME");
+  assert_eq!(env["result"], "denied");
+  assert_contains!(env["stack"][0].as_str().unwrap(), "main.ts:6:");
+}
+
+#[test]
+fn permission_prompt_remembered() {
+  if !util::pty::Pty::is_supported() {
+    return;
+  }
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "main.ts",
+    r#"const status = await Deno.permissions.request({
+  name: "read",
+  path: "foo.txt",
+});
+console.log(status.state);
+"#,
+  );
+
+  context
+    .new_command()
+    .args("run --quiet main.ts")
+    .with_pty(|mut console| {
+      console.expect("p = allow and remember for this project");
+      console.write_line_raw("p");
+      console.expect("and remembered it for this project.");
+      console.expect("granted");
+    });
+
+  // The remembered decision is used without prompting on the next run
+  context
+    .new_command()
+    .args("run --quiet main.ts")
+    .with_pty(|mut console| {
+      console.expect("granted");
+      assert_not_contains!(console.all_output(), "Deno requests");
+    });
+
+  context
+    .new_command()
+    .args("permissions main.ts")
+    .run()
+    .assert_matches_text("read access to \"[WILDCARD]foo.txt\"\n");
+
+  context
+    .new_command()
+    .args("permissions --clear main.ts")
+    .run()
+    .assert_matches_text(
+      "Cleared the remembered permission prompt decisions.\n",
+    );
+
+  // Without a terminal to prompt on, the request is denied once it's cleared
+  context
+    .new_command()
+    .args("run --quiet main.ts")
+    .run()
+    .assert_matches_text("denied\n");
+}
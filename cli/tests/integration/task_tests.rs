@@ -62,6 +62,44 @@ fn task_emoji() {
     });
 }
 
+itest!(task_depends_on {
+  args: "task --config task/depends_on/deno.json build 1",
+  output: "task/depends_on/task_depends_on.out",
+  envs: vec![("NO_COLOR".to_string(), "1".to_string())],
+});
+
+itest!(task_depends_on_parallel {
+  args: "task --config task/depends_on/deno.json --parallel ci",
+  output: "task/depends_on/task_depends_on_parallel.out",
+  envs: vec![("NO_COLOR".to_string(), "1".to_string())],
+});
+
+itest!(task_depends_on_failure {
+  args: "task --config task/depends_on/deno.json fail",
+  output: "task/depends_on/task_depends_on_failure.out",
+  envs: vec![("NO_COLOR".to_string(), "1".to_string())],
+  exit_code: 1,
+});
+
+itest!(task_depends_on_cycle {
+  args: "task --config task/depends_on/deno.json cycle",
+  output: "task/depends_on/task_depends_on_cycle.out",
+  envs: vec![("NO_COLOR".to_string(), "1".to_string())],
+  exit_code: 1,
+});
+
+itest!(task_env_file {
+  args: "task -q --config task/env_file/deno.json greet",
+  output: "task/env_file/task_env_file.out",
+  envs: vec![("NO_COLOR".to_string(), "1".to_string())],
+});
+
+itest!(task_workspace_members {
+  args: "task --config workspace/deno.json hello",
+  output: "workspace/task_hello.out",
+  envs: vec![("NO_COLOR".to_string(), "1".to_string())],
+});
+
 itest!(task_boolean_logic {
   args: "task -q --config task/deno_json/deno.json boolean_logic",
   output: "task/deno_json/task_boolean_logic.out",
@@ -6,6 +6,7 @@ use util::assert_contains;
 use util::env_vars_for_npm_tests;
 use util::wildcard_match;
 use util::TestContext;
+use util::TestContextBuilder;
 
 #[test]
 fn no_color() {
@@ -295,6 +296,24 @@ itest!(filter {
   output: "test/filter.out",
 });
 
+itest!(tags {
+  args: "test --tags=integration --skip-tags=slow test/tags.ts",
+  exit_code: 0,
+  output: "test/tags.out",
+});
+
+itest!(skip_tags {
+  args: "test --skip-tags=slow test/tags.ts",
+  exit_code: 0,
+  output: "test/skip_tags.out",
+});
+
+itest!(retries {
+  args: "test --retries=1 --allow-env test/retries.ts",
+  exit_code: 1,
+  output: "test/retries.out",
+});
+
 itest!(shuffle {
   args: "test --shuffle test/shuffle",
   exit_code: 0,
@@ -424,6 +443,284 @@ fn file_protocol() {
     .assert_matches_file("test/file_protocol.out");
 }
 
+itest!(reporter_tap {
+  args: "test --quiet --reporter=tap test/reporter/reporter.ts",
+  output: "test/reporter/tap.out",
+  exit_code: 1,
+});
+
+itest!(reporter_junit {
+  args: "test --quiet --reporter=junit test/reporter/reporter.ts",
+  output: "test/reporter/junit.out",
+  exit_code: 1,
+});
+
+#[test]
+fn reporter_output() {
+  let context = TestContext::default();
+  let temp_dir = context.temp_dir();
+  let junit_path = temp_dir.path().join("report.xml");
+  let tap_path = temp_dir.path().join("report.tap");
+  let output = context
+    .new_command()
+    .args_vec([
+      "test".to_string(),
+      "--reporter=junit".to_string(),
+      format!("--reporter-output={}", junit_path.display()),
+      "--reporter=tap".to_string(),
+      format!("--reporter-output={}", tap_path.display()),
+      "test/reporter/reporter.ts".to_string(),
+    ])
+    .env("NO_COLOR", "1")
+    .run();
+  output.assert_exit_code(1);
+  // The pretty reporter keeps writing to the console.
+  assert_contains!(output.combined_output(), "fails ... FAILED");
+  let junit = temp_dir.read_to_string("report.xml");
+  assert_contains!(junit, r#"<testcase name="passes""#);
+  assert_contains!(junit, r#"<failure message="Error: Fail.">"#);
+  let tap = temp_dir.read_to_string("report.tap");
+  assert_contains!(tap, "TAP version 14\nok 1 - passes\nnot ok 2 - fails\n");
+  assert_contains!(tap, "\n1..4\n");
+}
+
+#[test]
+fn snapshots() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "snapshot_test.ts",
+    r#"Deno.test("object", (t) => {
+  t.assertSnapshot({ b: [1, "two"], a: "multi\nline" });
+  t.assertSnapshot("second");
+});
+
+Deno.test("steps", async (t) => {
+  await t.step("nested", (t) => {
+    t.assertSnapshot(42);
+  });
+});
+"#,
+  );
+
+  // The snapshots have to be created first.
+  let output = context.new_command().args("test snapshot_test.ts").run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "Missing snapshot: `object 1`");
+
+  let output = context
+    .new_command()
+    .args("test --update-snapshots snapshot_test.ts")
+    .run();
+  output.assert_exit_code(0);
+  let snapshots =
+    temp_dir.read_to_string("__snapshots__/snapshot_test.ts.snap");
+  assert_contains!(snapshots, "export const snapshot = {};\n");
+  assert_contains!(
+    snapshots,
+    "snapshot[`object 1`] = `\n{\n  a: \"multi\nline\",\n"
+  );
+  assert_contains!(snapshots, "snapshot[`object 2`] = `\"second\"`;\n");
+  assert_contains!(snapshots, "snapshot[`steps > nested 1`] = `42`;\n");
+
+  let output = context.new_command().args("test snapshot_test.ts").run();
+  output.assert_exit_code(0);
+
+  temp_dir.write(
+    "snapshot_test.ts",
+    r#"Deno.test("object", (t) => {
+  t.assertSnapshot({ b: [1, "two"], a: "multi\nline" });
+  t.assertSnapshot("changed");
+});
+"#,
+  );
+  let output = context.new_command().args("test snapshot_test.ts").run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "Snapshot does not match: `object 2`"
+  );
+
+  // The snapshots of the tests that no longer run are kept.
+  let output = context
+    .new_command()
+    .args("test --update-snapshots snapshot_test.ts")
+    .run();
+  output.assert_exit_code(0);
+  let snapshots =
+    temp_dir.read_to_string("__snapshots__/snapshot_test.ts.snap");
+  assert_contains!(snapshots, "snapshot[`object 2`] = `\"changed\"`;\n");
+  assert_contains!(snapshots, "snapshot[`steps > nested 1`] = `42`;\n");
+}
+
+#[test]
+fn global_setup_and_teardown() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{ "test": { "setup": "./setup.ts", "teardown": "./teardown.ts" } }"#,
+  );
+  temp_dir.write(
+    "setup.ts",
+    r#"export default async function () {
+  await Deno.writeTextFile("setup_runs.txt", "run\n", { append: true });
+  return { url: "postgres://localhost:5432" };
+}
+"#,
+  );
+  temp_dir.write(
+    "teardown.ts",
+    r#"export default function (context: { url: string }) {
+  Deno.writeTextFileSync("teardown.txt", context.url);
+}
+"#,
+  );
+  let test_file = r#"Deno.test("context", (t) => {
+  if ((t.setupContext as { url: string }).url !== "postgres://localhost:5432") {
+    throw new Error("unexpected setup context");
+  }
+});
+"#;
+  temp_dir.write("a_test.ts", test_file);
+  temp_dir.write("b_test.ts", test_file);
+
+  let output = context
+    .new_command()
+    .args("test --allow-write --parallel a_test.ts b_test.ts")
+    .run();
+  output.assert_exit_code(0);
+  // The setup runs once for all the test modules.
+  assert_eq!(temp_dir.read_to_string("setup_runs.txt"), "run\n");
+  assert_eq!(
+    temp_dir.read_to_string("teardown.txt"),
+    "postgres://localhost:5432"
+  );
+
+  temp_dir.write(
+    "setup.ts",
+    "export default function () { throw new Error(\"no database\"); }\n",
+  );
+  let output = context
+    .new_command()
+    .args("test --allow-write a_test.ts")
+    .run();
+  output.assert_exit_code(1);
+  let output = output.combined_output();
+  assert_contains!(output, "The global test setup failed");
+  assert_contains!(output, "no database");
+}
+
+#[test]
+fn timeout() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "timeout_test.ts",
+    r#"Deno.test({ name: "blocks", timeout: 200, fn() { while (true) {} } });
+
+Deno.test({
+  name: "hangs",
+  timeout: 200,
+  async fn() {
+    await new Promise((resolve) => setTimeout(resolve, 60_000));
+  },
+});
+
+Deno.test("runs after", () => {});
+"#,
+  );
+
+  let output = context.new_command().args("test timeout_test.ts").run();
+  output.assert_exit_code(1);
+  let output = util::strip_ansi_codes(output.combined_output()).to_string();
+  assert_contains!(output, "blocks ... FAILED (timed out)");
+  assert_contains!(output, "hangs ... FAILED (timed out)");
+  assert_contains!(output, "runs after ... ok");
+  assert_contains!(output, "Timed out after 200ms.");
+  assert_contains!(output, "FAILED | 1 passed | 2 failed");
+}
+
+#[test]
+fn slow_threshold() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  context.temp_dir().write(
+    "slow_test.ts",
+    r#"Deno.test("fast", () => {});
+
+Deno.test("slow", async () => {
+  await new Promise((resolve) => setTimeout(resolve, 300));
+});
+"#,
+  );
+
+  let output = context
+    .new_command()
+    .args("test --slow-threshold=200ms slow_test.ts")
+    .run();
+  output.assert_exit_code(0);
+  let output = util::strip_ansi_codes(output.combined_output()).to_string();
+  assert_contains!(output, ", slow)");
+  let slow_section = output.split(" SLOW ").nth(1).unwrap();
+  assert_contains!(slow_section, "slow => ./slow_test.ts:3:6");
+  assert!(!slow_section.contains("fast =>"));
+}
+
+#[test]
+fn isolation_groups() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  context.temp_dir().write(
+    "groups_test.ts",
+    r#"const state = globalThis as { group?: string };
+
+Deno.test({
+  name: "b 1",
+  isolationGroup: "b",
+  async fn() {
+    if (state.group !== undefined) throw new Error("shared isolate");
+    await new Promise((resolve) => setTimeout(resolve, 200));
+    state.group = "b";
+  },
+});
+
+Deno.test("main", () => {
+  if (state.group !== undefined) throw new Error("shared isolate");
+  state.group = "main";
+});
+
+Deno.test({
+  name: "a 1",
+  isolationGroup: "a",
+  fn() {
+    if (state.group !== undefined) throw new Error("shared isolate");
+    state.group = "a";
+  },
+});
+
+Deno.test({
+  name: "b 2",
+  isolationGroup: "b",
+  fn() {
+    if (state.group !== "b") throw new Error("not in the group isolate");
+  },
+});
+"#,
+  );
+
+  let output = context
+    .new_command()
+    .args("test --parallel groups_test.ts")
+    .run();
+  output.assert_exit_code(0);
+  let output = util::strip_ansi_codes(output.combined_output()).to_string();
+  // The groups are reported in the order in which they were declared, after
+  // the tests that aren't in a group, even though group "b" finishes last.
+  let positions = ["main", "b 1", "b 2", "a 1"]
+    .map(|name| output.find(&format!("{name} ... ok")).unwrap());
+  assert!(positions.windows(2).all(|w| w[0] < w[1]), "{output}");
+  assert_contains!(output, "ok | 4 passed | 0 failed");
+}
+
 itest!(uncaught_errors {
   args: "test --quiet test/uncaught_errors_1.ts test/uncaught_errors_2.ts test/uncaught_errors_3.ts",
   output: "test/uncaught_errors.out",
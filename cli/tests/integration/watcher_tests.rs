@@ -527,8 +527,6 @@ async fn bundle_js_watch() {
 
   let (_stdout_lines, mut stderr_lines) = child_lines(&mut deno);
 
-  assert_contains!(next_line(&mut stderr_lines).await.unwrap(), "Warning");
-  assert_contains!(next_line(&mut stderr_lines).await.unwrap(), "deno_emit");
   assert_contains!(next_line(&mut stderr_lines).await.unwrap(), "Check");
   assert_contains!(
     next_line(&mut stderr_lines).await.unwrap(),
@@ -600,8 +598,6 @@ async fn bundle_watch_not_exit() {
     .unwrap();
   let (_stdout_lines, mut stderr_lines) = child_lines(&mut deno);
 
-  assert_contains!(next_line(&mut stderr_lines).await.unwrap(), "Warning");
-  assert_contains!(next_line(&mut stderr_lines).await.unwrap(), "deno_emit");
   assert_contains!(
     next_line(&mut stderr_lines).await.unwrap(),
     "Bundle started"
@@ -1187,6 +1183,47 @@ async fn test_watch() {
   check_alive_then_kill(child);
 }
 
+#[tokio::test]
+async fn test_watch_rerun_all() {
+  let t = TempDir::new();
+  let foo_file = t.path().join("foo.js");
+  let foo_test = t.path().join("foo_test.js");
+  let bar_test = t.path().join("bar_test.js");
+  write(&foo_file, "export default function foo() { 1 + 1 }").unwrap();
+  write(
+    &foo_test,
+    "import foo from './foo.js'; Deno.test('foo', foo);",
+  )
+  .unwrap();
+  write(&bar_test, "Deno.test('bar', () => {});").unwrap();
+
+  let mut child = util::deno_cmd()
+    .current_dir(util::testdata_path())
+    .arg("test")
+    .arg("--watch")
+    .arg("--rerun-all")
+    .arg("--unstable")
+    .arg("--no-check")
+    .arg(t.path())
+    .env("NO_COLOR", "1")
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap();
+  let (mut stdout_lines, mut stderr_lines) = child_lines(&mut child);
+
+  wait_contains("2 passed | 0 failed", &mut stdout_lines).await;
+  wait_contains("Test finished", &mut stderr_lines).await;
+
+  // bar_test.js doesn't depend on foo.js, but it runs again anyway.
+  write(&foo_file, "export default function foo() { 2 + 2 }").unwrap();
+  assert_contains!(next_line(&mut stderr_lines).await.unwrap(), "Restarting");
+  wait_contains("2 passed | 0 failed", &mut stdout_lines).await;
+  wait_contains("Test finished", &mut stderr_lines).await;
+
+  check_alive_then_kill(child);
+}
+
 #[flaky_test]
 #[tokio::main]
 async fn test_watch_doc() {
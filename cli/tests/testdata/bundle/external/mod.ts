@@ -0,0 +1,6 @@
+import { h } from "npm:preact@10";
+import { greet } from "./greet.ts";
+
+export function app(name: string) {
+  return h("p", null, greet(name));
+}
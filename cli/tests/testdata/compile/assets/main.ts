@@ -0,0 +1,18 @@
+const dir = new URL("./static/", import.meta.url);
+const hello = await Deno.readTextFile(new URL("hello.txt", dir));
+console.log(hello.trim());
+const data = JSON.parse(
+  Deno.readTextFileSync(new URL("nested/data.json", dir)),
+);
+console.log(data.answer);
+const file = await Deno.open(new URL("hello.txt", dir));
+await file.seek(-7, Deno.SeekMode.End);
+const buf = new Uint8Array(5);
+await file.read(buf);
+file.close();
+console.log(new TextDecoder().decode(buf));
+const names = [];
+for await (const entry of Deno.readDir(dir)) {
+  names.push(`${entry.name} ${entry.isFile ? "file" : "dir"}`);
+}
+console.log(names.sort().join(", "));
@@ -0,0 +1,4 @@
+for (const name of Deno.args) {
+  const plugin = await import(`./plugins/${name}.ts`);
+  console.log(plugin.default());
+}
@@ -0,0 +1,11 @@
+// This time ./worker.ts is not imported, so it's only in the module map
+// because `deno compile` finds the workers started with `new URL()`.
+
+const worker = new Worker(
+  new URL("./worker.ts", import.meta.url),
+  { type: "module" },
+);
+
+setTimeout(() => {
+  worker.postMessage(42);
+}, 500);
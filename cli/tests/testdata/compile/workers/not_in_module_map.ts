@@ -1,8 +1,11 @@
 // This time ./worker.ts is not in the module map, so the worker
 // initialization will fail unless worker.js is passed as a side module.
+// The specifier is computed, so `deno compile` can't find the worker by
+// itself.
 
+const workerName = ["worker", "ts"].join(".");
 const worker = new Worker(
-  new URL("./worker.ts", import.meta.url),
+  new URL(`./${workerName}`, import.meta.url),
   { type: "module" },
 );
 
@@ -0,0 +1,9 @@
+/**
+ * Adds two numbers.
+ * @param a The first number.
+ * @param b The second number.
+ * @returns The sum.
+ */
+export function add(a: number, b: number): number {
+  return a + b;
+}
@@ -0,0 +1,48 @@
+/** Adds two numbers. */
+export function add(a: number, b: number): number {
+  return a + b;
+}
+
+/**
+ * Subtracts two numbers.
+ * @param x The first number.
+ * @param b The second number.
+ * @returns The difference.
+ */
+export function sub(a: number, b: number): number {
+  return a - b;
+}
+
+/** Multiplies two numbers. */
+export function mul(a: number, b: number) {
+  return a * b;
+}
+
+export const VERSION = "1.0.0";
+
+/** A counter. */
+export class Counter {
+  #count = 0;
+  step = 1;
+
+  /** Increments the counter. */
+  increment(): void {
+    this.#count += this.step;
+  }
+
+  reset(): void {
+    this.#count = 0;
+  }
+
+  /**
+   * The value of the counter.
+   * @returns The count.
+   */
+  get value(): number {
+    return this.#count;
+  }
+
+  set value(value: number) {
+    this.#count = value;
+  }
+}
@@ -0,0 +1,2 @@
+console.log(Deno.env.get("FOO"));
+console.log(Deno.env.get("GREETING"));
@@ -84,6 +84,18 @@ Deno.test("echo uint8array", async () => {
   await ws.closed;
 });
 
+Deno.test("opened & closeCode", async () => {
+  const ws = new WebSocketStream("ws://localhost:4242");
+  const { readable, writable } = await ws.opened;
+  assertEquals(await ws.connection, await ws.opened);
+  const buffer = new Uint8Array([102, 111, 111]).buffer;
+  await writable.getWriter().write(buffer);
+  const res = await readable.getReader().read();
+  assertEquals(res.value, new Uint8Array(buffer));
+  ws.close({ closeCode: 1000 });
+  await ws.closed;
+});
+
 Deno.test("aborting immediately throws an AbortError", async () => {
   const controller = new AbortController();
   const wss = new WebSocketStream("ws://localhost:4242", {
@@ -0,0 +1,14 @@
+Deno.test("passes", () => {});
+
+Deno.test("fails", () => {
+  throw new Error("Fail.");
+});
+
+Deno.test({ name: "ignored", ignore: true, fn() {} });
+
+Deno.test("steps", async (t) => {
+  await t.step("passing step", () => {});
+  await t.step("failing step", () => {
+    throw new Error("Step failed.");
+  });
+});
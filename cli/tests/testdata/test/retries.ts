@@ -0,0 +1,18 @@
+// The environment is shared by the isolates of the attempts.
+Deno.test({
+  name: "flaky",
+  retries: 2,
+  fn() {
+    const attempt = Number(Deno.env.get("RETRIES_TEST_ATTEMPT") ?? 0) + 1;
+    Deno.env.set("RETRIES_TEST_ATTEMPT", `${attempt}`);
+    if (attempt < 2) {
+      throw new Error(`attempt ${attempt} failed`);
+    }
+  },
+});
+
+Deno.test("always fails", () => {
+  throw new Error("always fails");
+});
+
+Deno.test("passes", () => {});
@@ -0,0 +1,7 @@
+Deno.test("untagged", () => {});
+
+Deno.test({ name: "integration", tags: ["integration"], fn() {} });
+
+Deno.test({ name: "slow integration", tags: ["integration", "slow"], fn() {} });
+
+Deno.test({ name: "slow", tags: ["slow"], fn() {} });
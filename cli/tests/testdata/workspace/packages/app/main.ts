@@ -0,0 +1,4 @@
+import { greet } from "@example/greet";
+import { name } from "$/name.ts";
+
+console.log(greet(name));
@@ -256,6 +256,242 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { run: true, read: true } },
+  async function commandKillProcessGroup() {
+    const command = new Deno.Command(Deno.execPath(), {
+      args: [
+        "eval",
+        `new Deno.Command(Deno.execPath(), {
+          args: ["eval", "setTimeout(() => {}, 10000)"],
+        }).spawn();
+        setTimeout(() => {}, 10000);`,
+      ],
+      stdout: "null",
+      stderr: "null",
+      processGroup: true,
+    });
+    const child = command.spawn();
+
+    Deno.kill(-child.pid, "SIGKILL");
+    const status = await child.status;
+
+    assertEquals(status.success, false);
+    if (Deno.build.os === "windows") {
+      assertEquals(status.code, 1);
+    } else {
+      assertEquals(status.signal, "SIGKILL");
+    }
+  },
+);
+
+Deno.test(
+  {
+    permissions: { run: true, read: true },
+    ignore: Deno.build.os !== "windows",
+  },
+  async function commandKillNotProcessGroup() {
+    const child = new Deno.Command(Deno.execPath(), {
+      args: ["eval", "setTimeout(() => {}, 10000)"],
+      stdout: "null",
+      stderr: "null",
+    }).spawn();
+
+    assertThrows(
+      () => Deno.kill(-child.pid, "SIGKILL"),
+      Deno.errors.NotFound,
+    );
+    child.kill("SIGKILL");
+    await child.status;
+  },
+);
+
+Deno.test(
+  { permissions: { run: true, read: true } },
+  async function commandPty() {
+    const command = new Deno.Command(Deno.execPath(), {
+      args: [
+        "eval",
+        "console.log(Deno.isatty(Deno.stdin.rid), Deno.consoleSize().columns)",
+      ],
+      pty: { columns: 100, rows: 30 },
+    });
+    const child = command.spawn();
+
+    assertThrows(() => child.stdout, TypeError, "stdout is not piped");
+    const output = await new Response(child.pty.readable).text();
+    const status = await child.status;
+
+    assertEquals(status.success, true);
+    assertStringIncludes(output, "true 100");
+  },
+);
+
+Deno.test(
+  { permissions: { run: true, read: true } },
+  function commandPtyOutputSync() {
+    const command = new Deno.Command(Deno.execPath(), {
+      args: ["eval", "console.log('hello')"],
+      pty: true,
+    });
+    assertThrows(
+      () => command.outputSync(),
+      TypeError,
+      "pty is not supported for this function",
+    );
+  },
+);
+
+Deno.test(
+  {
+    permissions: { run: true, read: true, write: true },
+    ignore: Deno.build.os === "windows",
+  },
+  async function commandIpc() {
+    const command = new Deno.Command(Deno.execPath(), {
+      args: [
+        "eval",
+        "--unstable",
+        `const ipc = Deno.parentIpc();
+        const { message, handles } = await ipc.receive();
+        const text = await new Response(handles[0].readable).text();
+        await ipc.send({ echo: message, text });
+        ipc.close();`,
+      ],
+      stdout: "null",
+      ipc: true,
+    });
+    const child = command.spawn();
+
+    const path = await Deno.makeTempFile();
+    await Deno.writeTextFile(path, "hello");
+    const file = await Deno.open(path);
+    await child.ipc.send({ id: 1, values: new Map([["a", 1n]]) }, [file]);
+    file.close();
+
+    const received = await child.ipc.receive();
+    assertEquals(received, {
+      message: {
+        echo: { id: 1, values: new Map([["a", 1n]]) },
+        text: "hello",
+      },
+      handles: [],
+    });
+    assertEquals(await child.ipc.receive(), null);
+    assertEquals((await child.status).success, true);
+    await Deno.remove(path);
+  },
+);
+
+Deno.test(
+  { permissions: { run: true, read: true } },
+  async function commandIpcWithoutHandles() {
+    const command = new Deno.Command(Deno.execPath(), {
+      args: [
+        "eval",
+        "--unstable",
+        `const ipc = Deno.parentIpc();
+        for await (const { message } of ipc) {
+          await ipc.send({ echo: message });
+        }`,
+      ],
+      stdout: "null",
+      ipc: true,
+    });
+    const child = command.spawn();
+
+    // Larger than a read, so that the frame arrives in pieces
+    const data = new Uint8Array(100 * 1024).fill(1);
+    await child.ipc.send({ id: 1, data });
+    assertEquals(await child.ipc.receive(), {
+      message: { echo: { id: 1, data } },
+      handles: [],
+    });
+    child.ipc.close();
+    assertEquals((await child.status).success, true);
+  },
+);
+
+Deno.test(
+  { permissions: { run: true, read: true } },
+  async function commandWithoutIpc() {
+    const command = new Deno.Command(Deno.execPath(), {
+      args: ["eval", "--unstable", "console.log(Deno.parentIpc())"],
+    });
+    const { stdout } = await command.output();
+    assertEquals(new TextDecoder().decode(stdout), "null\n");
+  },
+);
+
+Deno.test(
+  {
+    permissions: { run: true, read: true },
+    ignore: Deno.build.os === "windows",
+  },
+  function commandResourceLimits() {
+    const { stdout } = new Deno.Command("sh", {
+      args: ["-c", "ulimit -n; ulimit -t"],
+      limits: { cpuTime: 10, openFiles: 64 },
+    }).outputSync();
+    assertEquals(new TextDecoder().decode(stdout), "64\n10\n");
+  },
+);
+
+Deno.test(
+  {
+    permissions: { run: true, read: true },
+    ignore: Deno.build.os !== "windows",
+  },
+  function commandResourceLimitsWindows() {
+    const { success } = new Deno.Command(Deno.execPath(), {
+      args: ["eval", "new Uint8Array(1024 * 1024 * 1024).fill(1)"],
+      limits: { memory: 256 * 1024 * 1024 },
+    }).outputSync();
+    assertEquals(success, false);
+
+    assertThrows(
+      () =>
+        new Deno.Command(Deno.execPath(), {
+          args: ["eval", ""],
+          limits: { openFiles: 64 },
+        }).outputSync(),
+      Deno.errors.NotSupported,
+    );
+  },
+);
+
+Deno.test(
+  { permissions: { run: true, read: true } },
+  async function commandDetached() {
+    const command = new Deno.Command(Deno.execPath(), {
+      args: ["eval", "console.log('hello')"],
+      detached: true,
+    });
+    const child = command.spawn();
+
+    assertThrows(() => child.stdout, TypeError, "stdout is not piped");
+    const status = await child.status;
+    assertEquals(status.success, true);
+  },
+);
+
+Deno.test(
+  {
+    permissions: { run: true, read: true },
+    ignore: Deno.build.os === "windows",
+  },
+  async function commandDetachedNewSession() {
+    const command = new Deno.Command("sh", {
+      args: ["-c", "ps -o sid= -p $$; echo $$"],
+      stdout: "piped",
+      detached: true,
+    });
+    const { stdout } = await command.spawn().output();
+    const [sid, pid] = new TextDecoder().decode(stdout).trim().split(/\s+/);
+    assertEquals(sid, pid);
+  },
+);
+
 Deno.test(
   { permissions: { run: true, read: true } },
   async function commandKillFailed() {
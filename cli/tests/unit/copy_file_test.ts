@@ -236,3 +236,60 @@ Deno.test(
     copyFileSyncMode("Hello world!".repeat(128 * 1024));
   },
 );
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  function copyFileSyncReflink() {
+    const tempDir = Deno.makeTempDirSync();
+    const fromFilename = tempDir + "/from.txt";
+    const toFilename = tempDir + "/to.txt";
+    writeFileString(fromFilename, "Hello world!");
+    // The destination is longer than the source.
+    writeFileString(toFilename, "Goodbye, cruel world!");
+    Deno.copyFileSync(fromFilename, toFilename, { reflink: "never" });
+    assertSameContent(fromFilename, toFilename);
+
+    writeFileString(toFilename, "Goodbye, cruel world!");
+    try {
+      Deno.copyFileSync(fromFilename, toFilename, { reflink: "always" });
+      assertSameContent(fromFilename, toFilename);
+    } catch (err) {
+      // The file system of the temporary directory can't clone files.
+      assertEquals(err instanceof Deno.errors.NotSupported, true);
+      assertEquals(readFileString(toFilename), "Goodbye, cruel world!");
+    }
+
+    assertThrows(() => {
+      Deno.copyFileSync(fromFilename, toFilename, {
+        // @ts-expect-error testing invalid usage
+        reflink: "sometimes",
+      });
+    }, TypeError);
+
+    Deno.removeSync(tempDir, { recursive: true });
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function copyFileReflink() {
+    const tempDir = await Deno.makeTempDir();
+    const fromFilename = tempDir + "/from.txt";
+    const toFilename = tempDir + "/to.txt";
+    writeFileString(fromFilename, "Hello world!");
+    await Deno.copyFile(fromFilename, toFilename, { reflink: "never" });
+    assertSameContent(fromFilename, toFilename);
+
+    await Deno.remove(toFilename);
+    try {
+      await Deno.copyFile(fromFilename, toFilename, { reflink: "always" });
+      assertSameContent(fromFilename, toFilename);
+    } catch (err) {
+      assertEquals(err instanceof Deno.errors.NotSupported, true);
+      // A destination which didn't exist isn't left behind.
+      assertThrows(() => Deno.statSync(toFilename), Deno.errors.NotFound);
+    }
+
+    await Deno.remove(tempDir, { recursive: true });
+  },
+);
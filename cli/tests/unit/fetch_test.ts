@@ -3,6 +3,8 @@ import {
   assert,
   assertEquals,
   assertRejects,
+  assertStringIncludes,
+  assertThrows,
   deferred,
   delay,
   fail,
@@ -1367,6 +1369,79 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { read: true, net: true } },
+  async function fetchCustomClientPkcs12(): Promise<void> {
+    const caCert = await Deno.readTextFile("cli/tests/testdata/tls/RootCA.crt");
+    const pkcs12 = {
+      data: await Deno.readFile("cli/tests/testdata/tls/localhost.p12"),
+      password: "deno",
+    };
+    const client = Deno.createHttpClient({
+      caCerts: [caCert],
+      clientCertificates: [{ hosts: ["localhost"], pkcs12 }],
+    });
+    const response = await fetch("https://localhost:5552/echo_server", {
+      client,
+      method: "POST",
+      body: "Hello World",
+    });
+    assertEquals(await response.text(), "Hello World");
+    client.close();
+
+    assertThrows(
+      () =>
+        Deno.createHttpClient({
+          pkcs12: { ...pkcs12, password: "wrong" },
+        }),
+      Deno.errors.InvalidData,
+      "Invalid password for PKCS#12 bundle",
+    );
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function fetchCustomClientHosts(): Promise<void> {
+    const client = Deno.createHttpClient({
+      hosts: { "fetch.test": ["127.0.0.1"] },
+      dnsCacheTtl: 60,
+    });
+    const response = await fetch("http://fetch.test:4545/echo_server", {
+      client,
+      method: "POST",
+      body: "Hello World",
+    });
+    assertEquals(await response.text(), "Hello World");
+    client.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function fetchCustomClientHttpCache(): Promise<void> {
+    const client = Deno.createHttpClient({ httpCache: true });
+    const url = "http://localhost:4545/etag_script.ts";
+    const response = await fetch(url, { client, cache: "reload" });
+    assertEquals(await response.text(), "console.log('etag')");
+    // Revalidated with the ETag, and answered with the stored body.
+    const revalidated = await fetch(url, { client });
+    assertEquals(revalidated.status, 200);
+    assertEquals(await revalidated.text(), "console.log('etag')");
+    const cached = await fetch(url, { client, cache: "only-if-cached" });
+    assertEquals(await cached.text(), "console.log('etag')");
+    await assertRejects(
+      () =>
+        fetch("http://localhost:4545/etag_script.ts?uncached", {
+          client,
+          cache: "only-if-cached",
+        }),
+      TypeError,
+    );
+    client.close();
+  },
+);
+
 Deno.test(
   { permissions: { net: true } },
   async function fetchAbortWhileUploadStreaming(): Promise<void> {
@@ -1531,6 +1606,66 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { net: true, read: true } },
+  async function fetchClientHttpVersions() {
+    const caCert = await Deno.readTextFile("cli/tests/testdata/tls/RootCA.pem");
+    const http1Client = Deno.createHttpClient({
+      caCerts: [caCert],
+      http2: false,
+    });
+    let res = await fetch("https://localhost:5545/http_version", {
+      client: http1Client,
+    });
+    assertEquals(await res.text(), "HTTP/1.1");
+    http1Client.close();
+
+    const http2Client = Deno.createHttpClient({
+      caCerts: [caCert],
+      http1: false,
+    });
+    res = await fetch("https://localhost:5545/http_version", {
+      client: http2Client,
+    });
+    assertEquals(await res.text(), "HTTP/2.0");
+    http2Client.close();
+
+    assertThrows(
+      () => Deno.createHttpClient({ http1: false, http2: false }),
+      TypeError,
+      "Either `http1` or `http2` needs to be true",
+    );
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function fetchClientPoolOptions() {
+    const client = Deno.createHttpClient({
+      poolMaxIdlePerHost: 1,
+      poolIdleTimeout: false,
+      poolMaxLifetime: 60_000,
+    });
+    let res = await fetch("http://localhost:4545/assets/fixture.json", {
+      client,
+    });
+    await res.arrayBuffer();
+    client.closeIdleConnections();
+    res = await fetch("http://localhost:4545/assets/fixture.json", {
+      client,
+    });
+    assertEquals((await res.json()).name, "deno");
+    client.close();
+
+    assertThrows(
+      // @ts-ignore: testing an invalid value
+      () => Deno.createHttpClient({ poolIdleTimeout: true }),
+      TypeError,
+      "`poolIdleTimeout` must be a number or false",
+    );
+  },
+);
+
 Deno.test({ permissions: { read: false } }, async function fetchFilePerm() {
   await assertRejects(async () => {
     await fetch(import.meta.resolve("../testdata/subdir/json_1.json"));
@@ -1909,3 +2044,203 @@ Deno.test("Response with subarray TypedArray body", async () => {
   const expected = new Uint8Array([2, 3, 4, 5]);
   assertEquals(actual, expected);
 });
+
+Deno.test(
+  { permissions: { net: true } },
+  async function fetchWithRequestProxy() {
+    const listener = Deno.listen({ port: 4516 });
+    const server = (async () => {
+      const conn = await listener.accept();
+      const buf = new Uint8Array(1024);
+      const n = await conn.read(buf);
+      const request = new TextDecoder().decode(buf.subarray(0, n!));
+      await conn.write(
+        new TextEncoder().encode(
+          "HTTP/1.1 200 OK\r\ncontent-length: 7\r\nconnection: close\r\n\r\nproxied",
+        ),
+      );
+      conn.close();
+      return request;
+    })();
+
+    const response = await fetch("http://example.com/path", {
+      proxy: { url: "http://localhost:4516" },
+    });
+    assertEquals(await response.text(), "proxied");
+    assert(
+      (await server).startsWith("GET http://example.com/path HTTP/1.1\r\n"),
+    );
+    listener.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: ["example.com"] } },
+  async function fetchWithRequestProxyPermission() {
+    await assertRejects(
+      () => fetch("http://example.com", { proxy: { url: "http://localhost:1" } }),
+      Deno.errors.PermissionDenied,
+    );
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function fetchCookieJar() {
+    const listener = Deno.listen({ port: 4521 });
+    const server = (async () => {
+      const requests = [];
+      for (
+        const response of [
+          "302 Found\r\nlocation: /next\r\nset-cookie: session=1; Path=/; HttpOnly",
+          "200 OK",
+        ]
+      ) {
+        const conn = await listener.accept();
+        const buf = new Uint8Array(1024);
+        const n = await conn.read(buf);
+        requests.push(new TextDecoder().decode(buf.subarray(0, n!)));
+        await conn.write(
+          new TextEncoder().encode(
+            `HTTP/1.1 ${response}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n`,
+          ),
+        );
+        conn.close();
+      }
+      return requests;
+    })();
+
+    const changes: Deno.Cookie[][] = [];
+    const client = Deno.createHttpClient({
+      cookieJar: { onChange: (cookies) => changes.push(cookies) },
+    });
+    const response = await fetch("http://localhost:4521/login", { client });
+    assertEquals(response.status, 200);
+    await response.body?.cancel();
+    const requests = await server;
+    assert(!requests[0].includes("cookie:"));
+    assertStringIncludes(requests[1], "cookie: session=1\r\n");
+    assertEquals(changes, [[{
+      name: "session",
+      value: "1",
+      domain: "localhost",
+      path: "/",
+      expires: null,
+      secure: false,
+      httpOnly: true,
+      hostOnly: true,
+    }]]);
+    client.close();
+    listener.close();
+  },
+);
+
+Deno.test(
+  {
+    ignore: Deno.build.os === "windows",
+    permissions: { read: true, write: true },
+  },
+  async function fetchUnixSocketTransport() {
+    const path = await Deno.makeTempFile({ suffix: ".sock" });
+    await Deno.remove(path);
+    const listener = Deno.listen({ transport: "unix", path });
+    const server = (async () => {
+      const conn = await listener.accept();
+      const buf = new Uint8Array(1024);
+      const n = await conn.read(buf);
+      const request = new TextDecoder().decode(buf.subarray(0, n!));
+      await conn.write(
+        new TextEncoder().encode(
+          "HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\n[]",
+        ),
+      );
+      conn.close();
+      return request;
+    })();
+
+    const client = Deno.createHttpClient({ transport: { path } });
+    const response = await fetch("http://localhost/v1.43/containers/json", {
+      client,
+    });
+    assertEquals(await response.json(), []);
+    assert(
+      (await server).startsWith("GET /v1.43/containers/json HTTP/1.1\r\n"),
+    );
+    client.close();
+    listener.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function fetchDuplexHalfProgress() {
+    const chunks = ["hello ", "world"];
+    const body = new ReadableStream<Uint8Array>({
+      pull(controller) {
+        const chunk = chunks.shift();
+        if (chunk === undefined) {
+          controller.close();
+        } else {
+          controller.enqueue(new TextEncoder().encode(chunk));
+        }
+      },
+    });
+    const progress: FetchProgress[] = [];
+    const response = await fetch("http://localhost:4545/echo_server", {
+      method: "POST",
+      body,
+      duplex: "half",
+      onProgress(p) {
+        progress.push(p);
+      },
+    });
+    assertEquals(await response.text(), "hello world");
+    assertEquals(progress[0].bytesSent, 6);
+    assertEquals(progress[0].totalBytesToSend, null);
+    const last = progress[progress.length - 1];
+    assertEquals(last.bytesSent, 11);
+    assertEquals(last.bytesReceived, 11);
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function fetchTimeout() {
+    // The server accepts the connections but never responds.
+    const listener = Deno.listen({ hostname: "127.0.0.1", port: 0 });
+    const { port } = listener.addr as Deno.NetAddr;
+    const conns: Deno.Conn[] = [];
+    const accepting = (async () => {
+      for await (const conn of listener) {
+        conns.push(conn);
+      }
+    })();
+
+    const client = Deno.createHttpClient({
+      timeout: 100,
+      retry: { maxRetries: 2 },
+    });
+    await assertRejects(
+      () => fetch(`http://127.0.0.1:${port}/`, { client }),
+      TypeError,
+      "timed out",
+    );
+    await assertRejects(
+      () => fetch(`http://127.0.0.1:${port}/`, { timeout: 100, retry: false }),
+      TypeError,
+      "timed out",
+    );
+    assertThrows(
+      () => new Request(`http://127.0.0.1:${port}/`, { timeout: -1 }),
+      TypeError,
+      "`timeout` must be a non-negative number",
+    );
+
+    client.close();
+    listener.close();
+    await accepting;
+    for (const conn of conns) {
+      conn.close();
+    }
+  },
+);
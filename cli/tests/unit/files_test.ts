@@ -824,3 +824,89 @@ Deno.test(
     assertEquals(res, "hello \uFFFD");
   },
 );
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function fsFileAllocate() {
+    const path = await Deno.makeTempFile();
+    const file = await Deno.open(path, { read: true, write: true });
+    await file.write(new TextEncoder().encode("hello"));
+    await file.allocate(8192);
+    assertEquals((await file.stat()).size, 8192);
+    // Allocating less than the size of the file doesn't truncate it.
+    file.allocateSync(16);
+    assertEquals(file.statSync().size, 8192);
+    file.close();
+
+    const data = await Deno.readFile(path);
+    assertEquals(new TextDecoder().decode(data.subarray(0, 5)), "hello");
+    assertEquals(data.subarray(5), new Uint8Array(8187));
+    await Deno.remove(path);
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function fsFilePunchHole() {
+    const path = await Deno.makeTempFile();
+    const file = await Deno.open(path, { read: true, write: true });
+    await file.write(new Uint8Array(3 * 4096).fill(1));
+    try {
+      await file.punchHole(4096, 4096);
+    } catch (err) {
+      // The file system of the temporary directory doesn't support holes.
+      assert(err instanceof Deno.errors.NotSupported);
+      file.close();
+      await Deno.remove(path);
+      return;
+    }
+    assertEquals(file.statSync().size, 3 * 4096);
+    file.close();
+
+    const data = await Deno.readFile(path);
+    assertEquals(data.subarray(0, 4096), new Uint8Array(4096).fill(1));
+    assertEquals(data.subarray(4096, 8192), new Uint8Array(4096));
+    assertEquals(data.subarray(8192), new Uint8Array(4096).fill(1));
+    await Deno.remove(path);
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function fsFileMmap() {
+    const path = await Deno.makeTempFile();
+    await Deno.writeTextFile(path, "hello world");
+    const file = await Deno.open(path, { read: true, write: true });
+
+    // Changes to private mappings aren't written to the file.
+    const buffer = file.mmap({ offset: 6 });
+    assertEquals(new TextDecoder().decode(buffer), "world");
+    new Uint8Array(buffer).fill(0);
+    Deno.munmap(buffer);
+    assertEquals(buffer.byteLength, 0);
+
+    const shared = file.mmap({ length: 5, shared: true });
+    file.close();
+    new Uint8Array(shared).set(new TextEncoder().encode("HELLO"));
+    await Deno.msync(shared);
+    Deno.munmap(shared);
+    assertEquals(await Deno.readTextFile(path), "HELLO world");
+    await Deno.remove(path);
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  function fsFileMmapErrors() {
+    const path = Deno.makeTempFileSync();
+    const file = Deno.openSync(path);
+    assertThrows(() => file.mmap(), RangeError);
+    Deno.writeTextFileSync(path, "hello");
+    // The file isn't open for writing.
+    assertThrows(() => file.mmap({ shared: true }), Deno.errors.PermissionDenied);
+    file.close();
+    assertThrows(() => Deno.msyncSync(new ArrayBuffer(8)), TypeError);
+    assertThrows(() => Deno.munmap(new ArrayBuffer(8)), TypeError);
+    Deno.removeSync(path);
+  },
+);
@@ -15,12 +15,27 @@ Deno.test(
   },
 );
 
-async function runFlockTests(opts: { sync: boolean }) {
+Deno.test(
+  { permissions: { read: true, run: true, hrtime: true } },
+  async function flockFsFileSync() {
+    await runFlockTests({ sync: true, fsFile: true });
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, run: true, hrtime: true } },
+  async function flockFsFileAsync() {
+    await runFlockTests({ sync: false, fsFile: true });
+  },
+);
+
+async function runFlockTests(opts: { sync: boolean; fsFile?: boolean }) {
   assertEquals(
     await checkFirstBlocksSecond({
       firstExclusive: true,
       secondExclusive: false,
       sync: opts.sync,
+      fsFile: opts.fsFile,
     }),
     true,
     "exclusive blocks shared",
@@ -30,6 +45,7 @@ async function runFlockTests(opts: { sync: boolean }) {
       firstExclusive: false,
       secondExclusive: true,
       sync: opts.sync,
+      fsFile: opts.fsFile,
     }),
     true,
     "shared blocks exclusive",
@@ -39,6 +55,7 @@ async function runFlockTests(opts: { sync: boolean }) {
       firstExclusive: true,
       secondExclusive: true,
       sync: opts.sync,
+      fsFile: opts.fsFile,
     }),
     true,
     "exclusive blocks exclusive",
@@ -48,6 +65,7 @@ async function runFlockTests(opts: { sync: boolean }) {
       firstExclusive: false,
       secondExclusive: false,
       sync: opts.sync,
+      fsFile: opts.fsFile,
       // need to wait for both to enter the lock to prevent the case where the
       // first process enters and exits the lock before the second even enters
       waitBothEnteredLock: true,
@@ -61,15 +79,18 @@ async function checkFirstBlocksSecond(opts: {
   firstExclusive: boolean;
   secondExclusive: boolean;
   sync: boolean;
+  fsFile?: boolean;
   waitBothEnteredLock?: boolean;
 }) {
   const firstProcess = runFlockTestProcess({
     exclusive: opts.firstExclusive,
     sync: opts.sync,
+    fsFile: opts.fsFile,
   });
   const secondProcess = runFlockTestProcess({
     exclusive: opts.secondExclusive,
     sync: opts.sync,
+    fsFile: opts.fsFile,
   });
   try {
     const sleep = (time: number) => new Promise((r) => setTimeout(r, time));
@@ -107,10 +128,26 @@ async function checkFirstBlocksSecond(opts: {
   }
 }
 
-function runFlockTestProcess(opts: { exclusive: boolean; sync: boolean }) {
+function runFlockTestProcess(
+  opts: { exclusive: boolean; sync: boolean; fsFile?: boolean },
+) {
   const path = "cli/tests/testdata/assets/fixture.json";
+  const exclusive = opts.exclusive ? "true" : "false";
+  let lock, unlock;
+  if (opts.fsFile) {
+    lock = opts.sync
+      ? `file.lockSync(${exclusive});`
+      : `await file.lock(${exclusive});`;
+    unlock = opts.sync ? "file.unlockSync();" : "await file.unlock();";
+  } else {
+    lock = opts.sync
+      ? `Deno.flockSync(rid, ${exclusive});`
+      : `await Deno.flock(rid, ${exclusive});`;
+    unlock = opts.sync ? "Deno.funlockSync(rid);" : "await Deno.funlock(rid);";
+  }
   const scriptText = `
-    const { rid } = Deno.openSync("${path}");
+    const file = Deno.openSync("${path}");
+    const { rid } = file;
 
     // ready signal
     Deno.stdout.writeSync(new Uint8Array(1));
@@ -120,11 +157,7 @@ function runFlockTestProcess(opts: { exclusive: boolean; sync: boolean }) {
     // entering signal
     Deno.stdout.writeSync(new Uint8Array(1));
     // lock and record the entry time
-    ${
-    opts.sync
-      ? `Deno.flockSync(rid, ${opts.exclusive ? "true" : "false"});`
-      : `await Deno.flock(rid, ${opts.exclusive ? "true" : "false"});`
-  }
+    ${lock}
     const enterTime = new Date().getTime();
     // entered signal
     Deno.stdout.writeSync(new Uint8Array(1));
@@ -139,7 +172,7 @@ function runFlockTestProcess(opts: { exclusive: boolean; sync: boolean }) {
     await new Promise(resolve => setTimeout(resolve, 100));
 
     // release the lock
-    ${opts.sync ? "Deno.funlockSync(rid);" : "await Deno.funlock(rid);"}
+    ${unlock}
 
     // exited signal
     Deno.stdout.writeSync(new Uint8Array(1));
@@ -107,3 +107,40 @@ Deno.test(
     assertEquals(events, []);
   },
 );
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function watchFsFilters() {
+    const testDir = await makeTempDir();
+    Deno.mkdirSync(testDir + "/subdir");
+    const iter = Deno.watchFs(testDir, {
+      recursive: false,
+      extensions: ["ts"],
+      exclude: ["ignored.ts"],
+      debounce: 50,
+    });
+
+    const eventsPromise = (async () => {
+      const events = [];
+      for await (const event of iter) {
+        events.push(event);
+        if (event.paths.some((path) => path.endsWith("done.ts"))) break;
+      }
+      return events;
+    })();
+
+    Deno.writeTextFileSync(testDir + "/file.js", "");
+    Deno.writeTextFileSync(testDir + "/ignored.ts", "");
+    Deno.writeTextFileSync(testDir + "/subdir/file.ts", "");
+    Deno.writeTextFileSync(testDir + "/file.ts", "");
+    Deno.writeTextFileSync(testDir + "/done.ts", "");
+
+    const events = await eventsPromise;
+    iter.close();
+    const paths = events.flatMap((event) => event.paths);
+    assert(paths.some((path) => path.endsWith("/file.ts")));
+    assert(paths.every((path) => path.endsWith(".ts")));
+    assert(!paths.some((path) => path.endsWith("ignored.ts")));
+    assert(!paths.some((path) => path.includes("subdir/")));
+  },
+);
@@ -0,0 +1,111 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+import { assertEquals, assertRejects } from "./test_util.ts";
+
+function makeTree(): string {
+  const root = Deno.makeTempDirSync();
+  for (const dir of ["src/a", "src/.hidden", "node_modules/b"]) {
+    Deno.mkdirSync(`${root}/${dir}`, { recursive: true });
+  }
+  for (
+    const file of [
+      "main.ts",
+      "src/mod.ts",
+      "src/mod.d.ts",
+      "src/a/b.ts",
+      "src/a/c.js",
+      "src/.hidden/d.ts",
+      "node_modules/b/e.ts",
+    ]
+  ) {
+    Deno.writeTextFileSync(`${root}/${file}`, "");
+  }
+  return root;
+}
+
+async function globPaths(
+  pattern: string,
+  options: Deno.GlobOptions,
+): Promise<string[]> {
+  const paths = [];
+  for await (const entry of Deno.glob(pattern, options)) {
+    paths.push(entry.path.slice(String(options.root).length + 1));
+  }
+  return paths.map((path) => path.replaceAll("\\", "/")).sort();
+}
+
+Deno.test({ permissions: { read: true, write: true } }, async function glob() {
+  const root = makeTree();
+  assertEquals(await globPaths("**/*.ts", { root }), [
+    "main.ts",
+    "node_modules/b/e.ts",
+    "src/a/b.ts",
+    "src/mod.d.ts",
+    "src/mod.ts",
+  ]);
+  assertEquals(await globPaths("src/*", { root }), [
+    "src/a",
+    "src/mod.d.ts",
+    "src/mod.ts",
+  ]);
+  assertEquals(await globPaths("src/*", { root, includeDirs: false }), [
+    "src/mod.d.ts",
+    "src/mod.ts",
+  ]);
+  assertEquals(await globPaths("*/*/*.JS", { root, caseInsensitive: true }), [
+    "src/a/c.js",
+  ]);
+  assertEquals(await globPaths("missing/**/*.ts", { root }), []);
+});
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function globExclude() {
+    const root = makeTree();
+    assertEquals(
+      await globPaths("**/*.ts", {
+        root,
+        exclude: ["node_modules/", "*.d.ts", "/main.ts"],
+      }),
+      ["src/a/b.ts", "src/mod.ts"],
+    );
+    assertEquals(
+      await globPaths("**/*.ts", {
+        root,
+        exclude: ["*.ts", "!mod.ts"],
+      }),
+      ["src/mod.ts"],
+    );
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function globEntry() {
+    const root = makeTree();
+    const entries = [];
+    for await (const entry of Deno.glob("src/a", { root })) {
+      entries.push(entry);
+    }
+    assertEquals(entries.length, 1);
+    assertEquals(entries[0].name, "a");
+    assertEquals(entries[0].isDirectory, true);
+    assertEquals(entries[0].isFile, false);
+    assertEquals(entries[0].isSymlink, false);
+  },
+);
+
+Deno.test({ permissions: { read: true } }, async function globInvalidPattern() {
+  await assertRejects(async () => {
+    for await (const _ of Deno.glob("a**b")) {
+      // Nothing.
+    }
+  }, TypeError);
+});
+
+Deno.test({ permissions: { read: false } }, async function globPerm() {
+  await assertRejects(async () => {
+    for await (const _ of Deno.glob("*", { root: "." })) {
+      // Nothing.
+    }
+  }, Deno.errors.PermissionDenied);
+});
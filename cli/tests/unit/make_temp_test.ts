@@ -155,3 +155,59 @@ Deno.test(
     }
   },
 );
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function openTmpFile() {
+    const dir = Deno.makeTempDirSync();
+    const file = await Deno.openTmpFile(dir);
+    await file.write(new TextEncoder().encode("hello"));
+    await file.seek(0, Deno.SeekMode.Start);
+    const buf = new Uint8Array(5);
+    assertEquals(await file.read(buf), 5);
+    assertEquals(new TextDecoder().decode(buf), "hello");
+    // The file has no name, or it's deleted on close on Windows.
+    if (Deno.build.os !== "windows") {
+      assertEquals([...Deno.readDirSync(dir)], []);
+    }
+    file.close();
+    assertEquals([...Deno.readDirSync(dir)], []);
+  },
+);
+
+Deno.test(
+  {
+    ignore: Deno.build.os !== "linux" && Deno.build.os !== "windows",
+    permissions: { read: true, write: true },
+  },
+  function openTmpFileSyncLink() {
+    const dir = Deno.makeTempDirSync();
+    const file = Deno.openTmpFileSync(dir);
+    file.writeSync(new TextEncoder().encode("hello"));
+    file.linkSync(`${dir}/hello.txt`);
+    file.close();
+    assertEquals(Deno.readTextFileSync(`${dir}/hello.txt`), "hello");
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function openFileLinkNotTmpFile() {
+    const dir = await Deno.makeTempDir();
+    await Deno.writeTextFile(`${dir}/secret.txt`, "secret");
+    const file = await Deno.open(`${dir}/secret.txt`);
+    await assertRejects(() => file.link(`${dir}/copy.txt`));
+    assertThrows(() => file.linkSync(`${dir}/copy.txt`));
+    file.close();
+    assertEquals(
+      [...Deno.readDirSync(dir)].map((entry) => entry.name),
+      ["secret.txt"],
+    );
+  },
+);
+
+Deno.test({ permissions: { write: false } }, function openTmpFileSyncPerm() {
+  assertThrows(() => {
+    Deno.openTmpFileSync();
+  }, Deno.errors.PermissionDenied);
+});
@@ -91,3 +91,26 @@ Deno.test(function opNamesMatch() {
     ops.sort().filter((name) => name !== "asyncOpsInfo"),
   );
 });
+
+Deno.test(async function eventLoopMetrics() {
+  Deno.eventLoopMetrics({ reset: true });
+  // Block the event loop for a turn, while a timer is pending.
+  const timer = new Promise((resolve) => setTimeout(resolve, 0));
+  const start = performance.now();
+  while (performance.now() - start < 20) {
+    // busy wait
+  }
+  await timer;
+
+  const metrics = Deno.eventLoopMetrics({ reset: true });
+  assert(metrics.delay.count > 0);
+  assert(metrics.delay.max >= 20);
+  assert(metrics.delay.min <= metrics.delay.p50);
+  assert(metrics.delay.p50 <= metrics.delay.p99);
+  assert(metrics.active >= 20);
+  assert(metrics.utilization > 0 && metrics.utilization <= 1);
+  assert(metrics.pendingOps >= metrics.pendingUnrefedOps);
+
+  const reset = Deno.eventLoopMetrics();
+  assert(reset.delay.count <= 1);
+});
@@ -122,6 +122,55 @@ Deno.test(
   },
 );
 
+Deno.test(
+  {
+    ignore: Deno.build.os !== "linux",
+    permissions: { net: true },
+  },
+  async function netVsockPermission() {
+    assertThrows(() => {
+      Deno.listen({ transport: "vsock", cid: 0xFFFFFFFF, port: 1024 });
+    }, Deno.errors.PermissionDenied);
+    await assertRejects(async () => {
+      await Deno.connect({ transport: "vsock", cid: 2, port: 1024 });
+    }, Deno.errors.PermissionDenied);
+  },
+);
+
+Deno.test(
+  {
+    ignore: Deno.build.os === "windows",
+    permissions: { net: ["127.0.0.1", "0.0.0.0"] },
+  },
+  async function netIcmpEcho() {
+    const socket = Deno.icmpSocket();
+    const data = new TextEncoder().encode("ping");
+    await socket.sendEchoRequest("127.0.0.1", { sequence: 7, data });
+    let reply;
+    do {
+      reply = await socket.receive();
+    } while (reply.type !== 0);
+    assertEquals(reply.hostname, "127.0.0.1");
+    assertEquals(reply.sequence, 7);
+    assertEquals(reply.data, data);
+    await assertRejects(
+      () => socket.sendEchoRequest("1.1.1.1"),
+      Deno.errors.PermissionDenied,
+    );
+    socket.close();
+  },
+);
+
+Deno.test(
+  {
+    ignore: Deno.build.os === "windows",
+    permissions: { net: ["127.0.0.1"] },
+  },
+  function netIcmpSocketPermission() {
+    assertThrows(() => Deno.icmpSocket(), Deno.errors.PermissionDenied);
+  },
+);
+
 Deno.test(
   {
     permissions: { net: true },
@@ -318,6 +367,79 @@ Deno.test({ permissions: { net: true } }, async function netTcpSetKeepAlive() {
   conn.close();
 });
 
+Deno.test(
+  { permissions: { net: true } },
+  async function netTcpSetKeepAliveOptions() {
+    const listener = Deno.listen({ port: 3500 });
+    const accepted = listener.accept();
+    const conn = await Deno.connect({ hostname: "127.0.0.1", port: 3500 });
+    conn.setKeepAlive({ idle: 30_000, interval: 5_000, count: 3 });
+    conn.setKeepAlive({});
+    conn.setKeepAlive(false);
+    assertThrows(() => conn.setKeepAlive({ idle: -1 }), TypeError);
+    (await accepted).close();
+    listener.close();
+    conn.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function netConnectWithDnsResolver() {
+    const resolver = Deno.createDnsResolver({
+      nameServers: [{ ipAddr: "127.0.0.1", port: 4553 }],
+      hosts: { "deno.test": ["127.0.0.1", "::1"] },
+      maxTtl: 60,
+    });
+    assertEquals(
+      await Deno.resolveDns("deno.test", "A", { resolver }),
+      ["127.0.0.1"],
+    );
+    assertEquals(
+      await Deno.resolveDns("deno.test", "AAAA", { resolver }),
+      ["::1"],
+    );
+
+    const listener = Deno.listen({ hostname: "127.0.0.1", port: 3500 });
+    const accepted = listener.accept();
+    const conn = await Deno.connect({
+      hostname: "deno.test",
+      port: 3500,
+      resolver,
+    });
+    assertEquals(conn.remoteAddr.hostname, "127.0.0.1");
+    (await accepted).close();
+    conn.close();
+    listener.close();
+    resolver.close();
+
+    assertThrows(
+      () => Deno.createDnsResolver({ minTtl: 60, maxTtl: 10 }),
+      TypeError,
+    );
+  },
+);
+
+Deno.test(
+  {
+    ignore: Deno.build.os !== "linux",
+    permissions: { net: true },
+  },
+  async function netUnixAbstractDialListen() {
+    const path = "\0deno-test-abstract";
+    const listener = Deno.listen({ path, transport: "unix" });
+    assert(listener.addr.transport === "unix");
+    assertEquals(listener.addr.path, path);
+    const accepted = listener.accept();
+    const conn = await Deno.connect({ path, transport: "unix" });
+    assert(conn.remoteAddr.transport === "unix");
+    assertEquals(conn.remoteAddr.path, path);
+    (await accepted).close();
+    conn.close();
+    listener.close();
+  },
+);
+
 Deno.test(
   {
     ignore: Deno.build.os === "windows",
@@ -357,6 +479,59 @@ Deno.test(
   },
 );
 
+Deno.test(
+  {
+    ignore: Deno.build.os === "windows",
+    permissions: { read: true, write: true, net: true },
+  },
+  async function netUnixSendReceiveFds() {
+    const filePath = tmpUnixSocketPath();
+    const listener = Deno.listen({ path: filePath, transport: "unix" });
+    const accepted = listener.accept();
+    const conn = await Deno.connect({ path: filePath, transport: "unix" });
+    const server = await accepted as Deno.UnixConn;
+
+    const credentials = server.peerCredentials();
+    assertEquals(credentials.uid, Deno.uid());
+    assertEquals(credentials.gid, Deno.gid());
+
+    const file = await Deno.open("cli/tests/testdata/assets/hello.txt");
+    const tcpListener = Deno.listen({ port: 3501 });
+    const nwritten = await conn.sendFds(new Uint8Array([1, 2, 3]), [
+      file,
+      tcpListener,
+    ]);
+    assertEquals(nwritten, 3);
+    file.close();
+    tcpListener.close();
+
+    const buf = new Uint8Array(1024);
+    const { nread, resources } = await server.receiveFds(buf);
+    assertEquals(nread, 3);
+    assertEquals(resources.length, 2);
+    const receivedFile = resources[0] as Deno.FsFile;
+    assert(receivedFile instanceof Deno.FsFile);
+    const fileRead = await receivedFile.read(buf);
+    assertEquals(
+      new TextDecoder().decode(buf.subarray(0, fileRead!)),
+      "Hello world!",
+    );
+    const receivedListener = resources[1] as Deno.Listener;
+    assertEquals(receivedListener.addr.transport, "tcp");
+    assertEquals((receivedListener.addr as Deno.NetAddr).port, 3501);
+    const tcpConn = await Deno.connect({ port: 3501 });
+    (await receivedListener.accept()).close();
+    tcpConn.close();
+
+    for (const resource of resources) {
+      resource.close();
+    }
+    server.close();
+    conn.close();
+    listener.close();
+  },
+);
+
 Deno.test(
   { permissions: { net: true } },
   async function netUdpSendReceive() {
@@ -465,11 +640,29 @@ Deno.test(
 
     membership.setLoopback(true);
     membership.setLoopback(false);
+    membership.setTTL(50);
     membership.leave();
     listener.close();
   },
 );
 
+Deno.test(
+  { permissions: { net: true } },
+  async function netUdpMulticastSenderOptions() {
+    const listener = Deno.listenDatagram({
+      hostname: "127.0.0.1",
+      port: 3500,
+      transport: "udp",
+    });
+
+    await listener.setMulticastLoopback(true);
+    await listener.setMulticastTTL(2);
+    await listener.setMulticastInterface("127.0.0.1");
+    await assertRejects(() => listener.setMulticastInterface("not an ip"));
+    listener.close();
+  },
+);
+
 Deno.test(
   { permissions: { net: true }, ignore: true },
   async function netUdpSendReceiveMulticastv4() {
@@ -637,6 +830,36 @@ Deno.test(
   },
 );
 
+Deno.test(
+  {
+    ignore: Deno.build.os === "windows",
+    permissions: { read: true, write: true },
+  },
+  async function netUnixPacketUnboundSend() {
+    const filePath = tmpUnixSocketPath();
+    const receiver = Deno.listenDatagram({
+      path: filePath,
+      transport: "unixpacket",
+    });
+    const sender = Deno.listenDatagram({ transport: "unixpacket" });
+    assert(sender.addr.transport === "unixpacket");
+    assertEquals(sender.addr.path, null);
+
+    const byteLength = await sender.send(new Uint8Array([1, 2, 3]), {
+      transport: "unixpacket",
+      path: filePath,
+    });
+    assertEquals(byteLength, 3);
+
+    const [recvd, remote] = await receiver.receive();
+    assert(remote.transport === "unixpacket");
+    assertEquals(remote.path, null);
+    assertEquals(recvd, new Uint8Array([1, 2, 3]));
+    sender.close();
+    receiver.close();
+  },
+);
+
 // TODO(lucacasonato): support concurrent reads and writes on unixpacket sockets
 Deno.test(
   { ignore: true, permissions: { read: true, write: true } },
@@ -1224,7 +1447,7 @@ Deno.test({
 });
 
 Deno.test({
-  ignore: Deno.build.os === "linux",
+  ignore: Deno.build.os === "linux" || Deno.build.os === "darwin",
   permissions: { net: true },
 }, function netTcpListenReusePortDoesNothing() {
   const listener1 = Deno.listen({ port: 4003, reusePort: true });
@@ -1233,3 +1456,98 @@ Deno.test({
   }, Deno.errors.AddrInUse);
   listener1.close();
 });
+
+Deno.test({
+  ignore: Deno.build.os !== "linux" && Deno.build.os !== "darwin",
+  permissions: { net: true },
+}, function netUdpListenReusePort() {
+  const options = {
+    hostname: "127.0.0.1",
+    port: 4005,
+    transport: "udp" as const,
+    reusePort: true,
+  };
+  const socket1 = Deno.listenDatagram(options);
+  const socket2 = Deno.listenDatagram(options);
+  assertEquals(socket2.addr, socket1.addr);
+  socket1.close();
+  socket2.close();
+});
+
+Deno.test({
+  ignore: Deno.build.os === "linux" || Deno.build.os === "darwin",
+  permissions: { net: true },
+}, function netUdpListenReusePortDoesNothing() {
+  const options = {
+    hostname: "127.0.0.1",
+    port: 4005,
+    transport: "udp" as const,
+    reusePort: true,
+  };
+  const socket = Deno.listenDatagram(options);
+  assertThrows(() => {
+    Deno.listenDatagram(options);
+  }, Deno.errors.AddrInUse);
+  socket.close();
+});
+
+Deno.test(
+  { permissions: { net: true } },
+  async function netTcpListenProxyProtocol() {
+    const listener = Deno.listen({ port: 4004, proxyProtocol: true });
+    const encoder = new TextEncoder();
+
+    // A connection without a valid header is dropped
+    const bad = await Deno.connect({ port: 4004 });
+    await bad.write(encoder.encode("GET / HTTP/1.1\r\n\r\n"));
+
+    const good = await Deno.connect({ port: 4004 });
+    await good.write(
+      encoder.encode("PROXY TCP4 192.0.2.1 192.0.2.2 56324 443\r\nhello"),
+    );
+
+    const conn = await listener.accept();
+    assertEquals(conn.remoteAddr, {
+      transport: "tcp",
+      hostname: "192.0.2.1",
+      port: 56324,
+    });
+    const buf = new Uint8Array(5);
+    assertEquals(await conn.read(buf), 5);
+    assertEquals(new TextDecoder().decode(buf), "hello");
+
+    // The rejected connection was closed by the server
+    assertEquals(await bad.read(new Uint8Array(1)), null);
+
+    bad.close();
+    good.close();
+    conn.close();
+    listener.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function netTcpListenProxyProtocolSilentClient() {
+    const listener = Deno.listen({ port: 4005, proxyProtocol: true });
+
+    // A client that doesn't send its header doesn't hold up the next one
+    const silent = await Deno.connect({ port: 4005 });
+    const good = await Deno.connect({ port: 4005 });
+    await good.write(
+      new TextEncoder().encode("PROXY TCP4 192.0.2.1 192.0.2.2 56324 443\r\n"),
+    );
+
+    const conn = await listener.accept();
+    assertEquals(conn.remoteAddr, {
+      transport: "tcp",
+      hostname: "192.0.2.1",
+      port: 56324,
+    });
+
+    silent.close();
+    good.close();
+    conn.close();
+    listener.close();
+  },
+);
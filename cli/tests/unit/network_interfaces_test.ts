@@ -1,6 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
-import { assert } from "./test_util.ts";
+import { assert, assertThrows } from "./test_util.ts";
 
 Deno.test(
   {
@@ -12,7 +12,7 @@ Deno.test(
     assert(Array.isArray(networkInterfaces));
     assert(networkInterfaces.length > 0);
     for (
-      const { name, family, address, netmask, scopeid, cidr, mac }
+      const { name, family, address, netmask, scopeid, cidr, mac, stats }
         of networkInterfaces
     ) {
       assert(typeof name === "string");
@@ -25,6 +25,42 @@ Deno.test(
       );
       assert(typeof cidr === "string");
       assert(typeof mac === "string");
+      assert(
+        stats === null ||
+          (stats.rxBytes >= 0 && stats.txBytes >= 0 &&
+            stats.rxPackets >= 0 && stats.txPackets >= 0),
+      );
     }
+    if (Deno.build.os !== "windows") {
+      const loopback = networkInterfaces.find(({ address }) =>
+        address === "127.0.0.1"
+      );
+      assert(loopback?.stats);
+    }
+  },
+);
+
+Deno.test(
+  {
+    name: "Deno.watchNetworkChanges",
+    permissions: { sys: ["networkInterfaces"] },
+  },
+  async () => {
+    const watcher = Deno.watchNetworkChanges();
+    const next = watcher.next();
+    watcher.close();
+    assert((await next).done);
+  },
+);
+
+Deno.test(
+  {
+    name: "Deno.watchNetworkChanges permission",
+    permissions: { sys: false },
+  },
+  () => {
+    assertThrows(() => {
+      Deno.watchNetworkChanges();
+    }, Deno.errors.PermissionDenied);
   },
 );
@@ -300,3 +300,38 @@ Deno.test(function memoryUsage() {
   assert(typeof mem.external === "number");
   assert(mem.rss >= mem.heapTotal);
 });
+
+Deno.test(function resourceUsage() {
+  const usage = Deno.resourceUsage();
+  assert(usage.userCpuTime > 0);
+  assert(usage.systemCpuTime >= 0);
+  assert(usage.maxRss >= Deno.memoryUsage().heapUsed);
+  assert(usage.minorPageFaults >= 0);
+  assert(usage.majorPageFaults >= 0);
+  assert(usage.fsRead >= 0);
+  assert(usage.fsWrite >= 0);
+});
+
+Deno.test(
+  { permissions: { sys: ["getPriority", "setPriority"] } },
+  function getAndSetPriority() {
+    const priority = Deno.getPriority();
+    assert(priority >= -20 && priority <= 19);
+    assertEquals(Deno.getPriority(0), priority);
+    assertEquals(Deno.getPriority(Deno.pid), priority);
+    // Setting the current priority doesn't require elevated privileges.
+    Deno.setPriority(0, priority);
+    assertEquals(Deno.getPriority(), priority);
+    assertThrows(() => Deno.setPriority(0, 20), TypeError);
+    assertThrows(() => Deno.setPriority(0, -21), TypeError);
+  },
+);
+
+Deno.test({ permissions: { sys: false } }, function priorityPerm() {
+  assertThrows(() => {
+    Deno.getPriority();
+  }, Deno.errors.PermissionDenied);
+  assertThrows(() => {
+    Deno.setPriority(0, 0);
+  }, Deno.errors.PermissionDenied);
+});
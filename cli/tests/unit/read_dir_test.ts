@@ -111,3 +111,38 @@ Deno.test({ permissions: { read: true } }, async function readDirNotFound() {
     `readdir 'bad_dir_name'`,
   );
 });
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function readDirWithStats() {
+    const dir = Deno.makeTempDirSync();
+    Deno.writeTextFileSync(`${dir}/file.txt`, "hello");
+    Deno.mkdirSync(`${dir}/subdir`);
+    Deno.symlinkSync(`${dir}/file.txt`, `${dir}/link`);
+
+    const entries: Deno.DirEntryWithStats[] = [];
+    for await (const entry of Deno.readDirWithStats(dir)) {
+      entries.push(entry);
+    }
+    assertEquals(
+      [...Deno.readDirWithStatsSync(dir)].map((entry) => entry.name).sort(),
+      ["file.txt", "link", "subdir"],
+    );
+    entries.sort((a, b) => a.name.localeCompare(b.name));
+    const [file, link, subdir] = entries;
+    assert(file.isFile);
+    assertEquals(file.stats.size, 5);
+    assertEquals(file.stats.mtime, Deno.statSync(`${dir}/file.txt`).mtime);
+    // The stats of symlinks are their own.
+    assert(link.isSymlink);
+    assert(link.stats.isSymlink);
+    assert(subdir.isDirectory);
+    assert(subdir.stats.isDirectory);
+  },
+);
+
+Deno.test({ permissions: { read: false } }, function readDirWithStatsPerm() {
+  assertThrows(() => {
+    Deno.readDirWithStatsSync("tests/");
+  }, Deno.errors.PermissionDenied);
+});
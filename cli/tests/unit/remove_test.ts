@@ -1,5 +1,10 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
-import { assert, assertRejects, assertThrows } from "./test_util.ts";
+import {
+  assert,
+  assertEquals,
+  assertRejects,
+  assertThrows,
+} from "./test_util.ts";
 
 const REMOVE_METHODS = ["remove", "removeSync"] as const;
 
@@ -289,3 +294,43 @@ if (Deno.build.os === "windows") {
     },
   );
 }
+
+Deno.test(
+  {
+    ignore: Deno.build.os !== "linux",
+    permissions: { write: true, read: true, env: true },
+  },
+  async function removeTrash() {
+    // The trash has to be on the same file system as the removed files.
+    const dataHome = Deno.makeTempDirSync();
+    const previousDataHome = Deno.env.get("XDG_DATA_HOME");
+    Deno.env.set("XDG_DATA_HOME", dataHome);
+    try {
+      for (const method of REMOVE_METHODS) {
+        const dir = Deno.makeTempDirSync();
+        const path = dir + "/file.txt";
+        Deno.writeTextFileSync(path, method);
+        await Deno[method](path, { trash: true });
+        assertThrows(() => {
+          Deno.lstatSync(path);
+        }, Deno.errors.NotFound);
+
+        const trashName = method === "remove" ? "file.txt" : "file.txt.2";
+        assertEquals(
+          Deno.readTextFileSync(`${dataHome}/Trash/files/${trashName}`),
+          method,
+        );
+        const info = Deno.readTextFileSync(
+          `${dataHome}/Trash/info/${trashName}.trashinfo`,
+        );
+        assert(info.startsWith(`[Trash Info]\nPath=${path}\n`));
+      }
+    } finally {
+      if (previousDataHome === undefined) {
+        Deno.env.delete("XDG_DATA_HOME");
+      } else {
+        Deno.env.set("XDG_DATA_HOME", previousDataHome);
+      }
+    }
+  },
+);
@@ -1,5 +1,9 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
-import { assertEquals, assertStringIncludes } from "./test_util.ts";
+import {
+  assertEquals,
+  assertStringIncludes,
+  assertThrows,
+} from "./test_util.ts";
 
 Deno.test(async function fromInit() {
   const req = new Request("http://foo/", {
@@ -37,6 +41,31 @@ Deno.test(function requestRelativeUrl() {
   );
 });
 
+Deno.test(function requestCacheMode() {
+  assertEquals(new Request("http://foo/").cache, "default");
+  const request = new Request("http://foo/", { cache: "only-if-cached" });
+  assertEquals(request.cache, "only-if-cached");
+  assertEquals(new Request(request).cache, "only-if-cached");
+  assertEquals(request.clone().cache, "only-if-cached");
+});
+
+Deno.test(function requestDuplex() {
+  const request = new Request("http://foo/", {
+    method: "POST",
+    body: new ReadableStream(),
+    duplex: "half",
+  });
+  assertEquals(request.duplex, "half");
+  assertThrows(
+    () =>
+      new Request("http://foo/", {
+        // @ts-ignore: only "half" is a valid duplex mode
+        duplex: "full",
+      }),
+    TypeError,
+  );
+});
+
 Deno.test(async function cloneRequestBodyStream() {
   // hack to get a stream
   const stream =
@@ -13,6 +13,7 @@ import {
   assertThrows,
   Deferred,
   deferred,
+  delay,
   fail,
 } from "./test_util.ts";
 import { consoleSize } from "../../../runtime/js/40_tty.js";
@@ -809,6 +810,60 @@ Deno.test({ permissions: { net: true } }, async function httpServerWebSocket() {
   await server;
 });
 
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerUpgradeHttp() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+    const server = Deno.serve({
+      handler: (request) => {
+        Deno.upgradeHttp(request).then(async ([conn, firstPacket]) => {
+          assertEquals(new TextDecoder().decode(firstPacket), "Upgrade data");
+          // Echo a single packet
+          const buf = new Uint8Array(1024);
+          const read = await conn.read(buf);
+          await conn.write(buf.subarray(0, read!));
+          conn.close();
+        });
+        return new Response(null, {
+          status: 101,
+          headers: { "connection": "Upgrade", "upgrade": "echo" },
+        });
+      },
+      port: 4501,
+      signal: ac.signal,
+      onListen: onListen(listeningPromise),
+      onError: createOnErrorCb(ac),
+    });
+
+    await listeningPromise;
+
+    const conn = await Deno.connect({ port: 4501 });
+    await conn.write(
+      new TextEncoder().encode(
+        "GET / HTTP/1.1\r\nHost: 127.0.0.1:4501\r\nConnection: Upgrade\r\nUpgrade: echo\r\n\r\nUpgrade data",
+      ),
+    );
+    const buf = new Uint8Array(1024);
+    const len = await conn.read(buf);
+    assertMatch(
+      new TextDecoder().decode(buf.subarray(0, len!)),
+      /^HTTP\/1\.1 101 Switching Protocols\r\n/,
+    );
+
+    await conn.write(new TextEncoder().encode("buffer data"));
+    const echoed = await conn.read(buf);
+    assertEquals(
+      new TextDecoder().decode(buf.subarray(0, echoed!)),
+      "buffer data",
+    );
+
+    conn.close();
+    ac.abort();
+    await server;
+  },
+);
+
 Deno.test(
   { permissions: { net: true } },
   async function httpServerWebSocketRaw() {
@@ -2838,6 +2893,401 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerSendEarlyHints() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+    const hintsReceived = deferred();
+
+    const server = Deno.serve({
+      handler: async (_req, info) => {
+        info.sendEarlyHints({ link: "</style.css>; rel=preload; as=style" });
+        await hintsReceived;
+        return new Response("hello world");
+      },
+      port: 4501,
+      signal: ac.signal,
+      onListen: onListen(listeningPromise),
+      onError: createOnErrorCb(ac),
+    });
+
+    await listeningPromise;
+    const conn = await Deno.connect({ port: 4501 });
+    const encoder = new TextEncoder();
+    const decoder = new TextDecoder();
+    await conn.write(
+      encoder.encode("GET / HTTP/1.1\r\nHost: 127.0.0.1:4501\r\n\r\n"),
+    );
+
+    const buf = new Uint8Array(1024);
+    let text = "";
+    while (!text.includes("\r\n\r\n")) {
+      const n = await conn.read(buf);
+      assert(n);
+      text += decoder.decode(buf.subarray(0, n));
+    }
+    assertEquals(
+      text,
+      "HTTP/1.1 103 Early Hints\r\nlink: </style.css>; rel=preload; as=style\r\n\r\n",
+    );
+    hintsReceived.resolve();
+
+    text = "";
+    while (!text.endsWith("hello world")) {
+      const n = await conn.read(buf);
+      assert(n);
+      text += decoder.decode(buf.subarray(0, n));
+    }
+    assert(text.startsWith("HTTP/1.1 200 OK\r\n"));
+
+    conn.close();
+    ac.abort();
+    await server;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerTrailersIgnoredOnHttp1() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+
+    const server = Deno.serve({
+      handler: async (req, info) => {
+        assertEquals(await req.text(), "request body");
+        // HTTP/1.1 trailers are neither sent nor received
+        assertEquals(info.requestTrailers(), null);
+        info.setTrailers({ "x-checksum": "1234" });
+        return new Response("hello world");
+      },
+      port: 4501,
+      signal: ac.signal,
+      onListen: onListen(listeningPromise),
+      onError: createOnErrorCb(ac),
+    });
+
+    await listeningPromise;
+    const resp = await fetch("http://127.0.0.1:4501/", {
+      method: "POST",
+      body: "request body",
+    });
+    assertEquals(await resp.text(), "hello world");
+
+    ac.abort();
+    await server;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerKeepAliveTimeout() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+
+    const server = Deno.serve({
+      handler: () => new Response("hello world"),
+      port: 4501,
+      signal: ac.signal,
+      keepAliveTimeout: 100,
+      onListen: onListen(listeningPromise),
+      onError: createOnErrorCb(ac),
+    });
+
+    await listeningPromise;
+    const conn = await Deno.connect({ port: 4501 });
+    // The server closes the connection as we never send a request
+    assertEquals(await conn.read(new Uint8Array(1)), null);
+
+    conn.close();
+    ac.abort();
+    await server;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerRequestBodyIdleTimeout() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+    const bodyError = deferred();
+
+    const server = Deno.serve({
+      handler: async (req) => {
+        await assertRejects(() => req.text(), Deno.errors.TimedOut);
+        bodyError.resolve();
+        return new Response("timed out");
+      },
+      port: 4501,
+      signal: ac.signal,
+      requestBodyIdleTimeout: 100,
+      onListen: onListen(listeningPromise),
+      onError: createOnErrorCb(ac),
+    });
+
+    await listeningPromise;
+    const conn = await Deno.connect({ port: 4501 });
+    await conn.write(
+      new TextEncoder().encode(
+        "POST / HTTP/1.1\r\nHost: 127.0.0.1:4501\r\nContent-Length: 10\r\n\r\nab",
+      ),
+    );
+    await bodyError;
+
+    conn.close();
+    ac.abort();
+    await server;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerRequestBodyHighWaterMark() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+    const size = 1024 * 1024;
+
+    const server = Deno.serve({
+      handler: async (req, info) => {
+        const reader = req.body!.getReader();
+        // The body is received ahead of the reads up to the high water mark,
+        // and no further while it isn't read
+        while (info.requestBodyBufferedBytes() < 16 * 1024) {
+          await delay(10);
+        }
+        await delay(100);
+        const buffered = info.requestBodyBufferedBytes();
+        assert(buffered >= 16 * 1024);
+        assert(buffered <= 32 * 1024);
+        let read = 0;
+        while (true) {
+          const { done, value } = await reader.read();
+          if (done) break;
+          read += value.byteLength;
+          assert(info.requestBodyBufferedBytes() <= 32 * 1024);
+        }
+        assertEquals(info.requestBodyBufferedBytes(), 0);
+        return new Response(String(read));
+      },
+      port: 4501,
+      signal: ac.signal,
+      requestBodyHighWaterMark: 16 * 1024,
+      onListen: onListen(listeningPromise),
+      onError: createOnErrorCb(ac),
+    });
+
+    await listeningPromise;
+    const resp = await fetch("http://127.0.0.1:4501/", {
+      method: "POST",
+      body: new Uint8Array(size),
+    });
+    assertEquals(await resp.text(), String(size));
+
+    ac.abort();
+    await server;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerMaxConnectionsReject() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+
+    const server = Deno.serve({
+      handler: () => new Response("ok"),
+      port: 4501,
+      signal: ac.signal,
+      maxConnections: 1,
+      overloadBehavior: "reject",
+      onOverload: () => new Response("busy", { status: 503 }),
+      onListen: onListen(listeningPromise),
+      onError: createOnErrorCb(ac),
+    });
+
+    await listeningPromise;
+    // Holds the only connection slot
+    const conn = await Deno.connect({ port: 4501 });
+    const resp = await fetch("http://127.0.0.1:4501/");
+    assertEquals(resp.status, 503);
+    assertEquals(await resp.text(), "busy");
+    conn.close();
+
+    ac.abort();
+    await server;
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, net: true } },
+  async function httpServerHttp3AltSvc() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+    const port = 4501;
+
+    const server = Deno.serve({
+      handler: () => new Response("Hello World"),
+      hostname: "127.0.0.1",
+      port,
+      signal: ac.signal,
+      onListen: onListen(listeningPromise),
+      onError: createOnErrorCb(ac),
+      cert: Deno.readTextFileSync("cli/tests/testdata/tls/localhost.crt"),
+      key: Deno.readTextFileSync("cli/tests/testdata/tls/localhost.key"),
+      http3: true,
+    });
+
+    await listeningPromise;
+    const caCert = Deno.readTextFileSync("cli/tests/testdata/tls/RootCA.pem");
+    const client = Deno.createHttpClient({ caCerts: [caCert] });
+    const resp = await fetch(`https://localhost:${port}/`, {
+      client,
+      headers: { "connection": "close" },
+    });
+
+    assertEquals(resp.headers.get("alt-svc"), `h3=":${port}"; ma=86400`);
+    assertEquals(await resp.text(), "Hello World");
+
+    client.close();
+    ac.abort();
+    await server;
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, net: true } },
+  async function httpServerHttp3Client() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+    const port = 4501;
+
+    const server = Deno.serve({
+      handler: (req) => new Response(req.method),
+      hostname: "127.0.0.1",
+      port,
+      signal: ac.signal,
+      onListen: onListen(listeningPromise),
+      onError: createOnErrorCb(ac),
+      cert: Deno.readTextFileSync("cli/tests/testdata/tls/localhost.crt"),
+      key: Deno.readTextFileSync("cli/tests/testdata/tls/localhost.key"),
+      http3: true,
+    });
+
+    await listeningPromise;
+    const caCert = Deno.readTextFileSync("cli/tests/testdata/tls/RootCA.pem");
+    // The first response advertises HTTP/3 for the requests after it.
+    const client = Deno.createHttpClient({
+      caCerts: [caCert],
+      hosts: { localhost: ["127.0.0.1"] },
+      http3: true,
+    });
+    for (let i = 0; i < 2; i++) {
+      const resp = await fetch(`https://localhost:${port}/`, { client });
+      assertEquals(await resp.text(), "GET");
+      // Only the responses over TCP advertise HTTP/3, so the second one was
+      // served over HTTP/3
+      assertEquals(resp.headers.has("alt-svc"), i === 0);
+    }
+    client.close();
+
+    const priorKnowledgeClient = Deno.createHttpClient({
+      caCerts: [caCert],
+      hosts: { localhost: ["127.0.0.1"] },
+      http3: true,
+      http3PriorKnowledge: true,
+    });
+    const resp = await fetch(`https://localhost:${port}/`, {
+      client: priorKnowledgeClient,
+      method: "POST",
+      body: "body",
+    });
+    assertEquals(await resp.text(), "POST");
+    assertEquals(resp.headers.has("alt-svc"), false);
+    priorKnowledgeClient.close();
+
+    assertThrows(
+      () => Deno.createHttpClient({ http3PriorKnowledge: true }),
+      TypeError,
+      "`http3PriorKnowledge` requires `http3: true`",
+    );
+
+    ac.abort();
+    await server;
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, net: true } },
+  async function httpServerHttp3Request() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+    const port = 4501;
+
+    const server = Deno.serve({
+      handler: async (req) => {
+        const body = await req.text();
+        return new Response(`${req.method} ${new URL(req.url).pathname}`, {
+          headers: {
+            "x-body-length": `${body.length}`,
+            "x-header": req.headers.get("x-header") ?? "",
+          },
+        });
+      },
+      hostname: "127.0.0.1",
+      port,
+      signal: ac.signal,
+      onListen: onListen(listeningPromise),
+      onError: createOnErrorCb(ac),
+      cert: Deno.readTextFileSync("cli/tests/testdata/tls/localhost.crt"),
+      key: Deno.readTextFileSync("cli/tests/testdata/tls/localhost.key"),
+      http3: true,
+    });
+
+    await listeningPromise;
+    const client = Deno.createHttpClient({
+      caCerts: [Deno.readTextFileSync("cli/tests/testdata/tls/RootCA.pem")],
+      hosts: { localhost: ["127.0.0.1"] },
+      http3: true,
+      http3PriorKnowledge: true,
+    });
+    // Larger than the receive window of a stream, so that the body is read
+    // in pieces
+    const body = "a".repeat(4 * 1024 * 1024);
+    const resp = await fetch(`https://localhost:${port}/path`, {
+      client,
+      method: "POST",
+      headers: { "x-header": "value" },
+      body,
+    });
+    assertEquals(resp.status, 200);
+    assertEquals(resp.headers.get("x-body-length"), `${body.length}`);
+    assertEquals(resp.headers.get("x-header"), "value");
+    // Only the responses over TCP advertise HTTP/3
+    assertEquals(resp.headers.has("alt-svc"), false);
+    assertEquals(await resp.text(), "POST /path");
+
+    client.close();
+    ac.abort();
+    await server;
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerHttp3RequiresTls() {
+    await assertRejects(
+      () =>
+        Deno.serve({
+          handler: () => new Response("Hello World"),
+          port: 4501,
+          http3: true,
+        }),
+      TypeError,
+      "HTTP/3 requires cert and key to be provided.",
+    );
+  },
+);
+
 async function curlRequest(args: string[]) {
   const { success, stdout } = await new Deno.Command("curl", {
     args,
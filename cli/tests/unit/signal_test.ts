@@ -5,7 +5,7 @@ Deno.test(
   { ignore: Deno.build.os !== "windows" },
   function signalsNotImplemented() {
     const msg =
-      "Windows only supports ctrl-c (SIGINT) and ctrl-break (SIGBREAK).";
+      "Windows only supports ctrl-c (SIGINT), ctrl-break (SIGBREAK), console close (SIGHUP) and shutdown or logoff (SIGTERM).";
     assertThrows(
       () => {
         Deno.addSignalListener("SIGALRM", () => {});
@@ -20,13 +20,6 @@ Deno.test(
       Error,
       msg,
     );
-    assertThrows(
-      () => {
-        Deno.addSignalListener("SIGHUP", () => {});
-      },
-      Error,
-      msg,
-    );
     assertThrows(
       () => {
         Deno.addSignalListener("SIGIO", () => {});
@@ -48,13 +41,6 @@ Deno.test(
       Error,
       msg,
     );
-    assertThrows(
-      () => {
-        Deno.addSignalListener("SIGTERM", () => {});
-      },
-      Error,
-      msg,
-    );
     assertThrows(
       () => {
         Deno.addSignalListener("SIGUSR1", () => {});
@@ -104,6 +90,17 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { ignore: Deno.build.os !== "windows" },
+  function windowsConsoleSignals() {
+    const listener = () => {};
+    for (const signal of ["SIGBREAK", "SIGHUP", "SIGTERM"] as const) {
+      Deno.addSignalListener(signal, listener);
+      Deno.removeSignalListener(signal, listener);
+    }
+  },
+);
+
 Deno.test(
   {
     ignore: Deno.build.os === "windows",
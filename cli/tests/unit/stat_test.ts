@@ -336,3 +336,36 @@ Deno.test(
     assert(s.blocks !== null);
   },
 );
+
+Deno.test({ permissions: { read: true } }, function statFsSyncSuccess() {
+  const info = Deno.statFsSync(".");
+  assert(info.type.length > 0);
+  assert(info.blockSize > 0);
+  assert(info.blocks >= info.blocksFree);
+  assert(info.blocksFree >= info.blocksAvailable);
+  assert(info.totalBytes >= info.freeBytes);
+  assert(info.freeBytes >= info.availableBytes);
+  assertEquals(typeof info.flags.readOnly, "boolean");
+  if (Deno.build.os === "windows") {
+    assertEquals(info.files, null);
+  } else {
+    assert(info.files !== null);
+  }
+});
+
+Deno.test({ permissions: { read: true } }, async function statFsSuccess() {
+  const info = await Deno.statFs(pathToAbsoluteFileUrl("cli/tests"));
+  assertEquals(info.totalBytes, info.blocks * info.blockSize);
+});
+
+Deno.test({ permissions: { read: true } }, async function statFsNotFound() {
+  await assertRejects(async () => {
+    await Deno.statFs("bad_dir_name");
+  }, Deno.errors.NotFound);
+});
+
+Deno.test({ permissions: { read: false } }, function statFsPerm() {
+  assertThrows(() => {
+    Deno.statFsSync(".");
+  }, Deno.errors.PermissionDenied);
+});
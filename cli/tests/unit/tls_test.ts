@@ -8,6 +8,7 @@ import {
   assertThrows,
   Deferred,
   deferred,
+  delay,
 } from "./test_util.ts";
 import { BufReader, BufWriter } from "../../../test_util/std/io/mod.ts";
 import { readAll } from "../../../test_util/std/streams/read_all.ts";
@@ -1459,7 +1460,7 @@ Deno.test({
 });
 
 Deno.test({
-  ignore: Deno.build.os === "linux",
+  ignore: Deno.build.os === "linux" || Deno.build.os === "darwin",
   permissions: { net: true },
 }, function listenTlsReusePortDoesNothing() {
   const hostname = "localhost";
@@ -1476,3 +1477,90 @@ Deno.test({
   }, Deno.errors.AddrInUse);
   listener1.close();
 });
+
+Deno.test(
+  { permissions: { read: true, net: true } },
+  async function listenTlsSni() {
+    const hostname = "localhost";
+    const port = 4004;
+    // The default certificate isn't valid for "localhost", so the handshake
+    // only succeeds if the certificate is picked by the requested server name
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      cert: await Deno.readTextFile("cli/tests/testdata/tls/RootCA.crt"),
+      key: await Deno.readTextFile("cli/tests/testdata/tls/RootCA.key"),
+      sni: { "LOCALHOST": { cert, key } },
+    });
+    const accepted = listener.accept().then(async (conn) => {
+      await conn.write(encoder.encode("hello"));
+      conn.close();
+    });
+
+    const conn = await Deno.connectTls({ hostname, port, caCerts });
+    const buf = new Uint8Array(5);
+    await conn.read(buf);
+    assertEquals(decoder.decode(buf), "hello");
+    conn.close();
+    await accepted;
+    listener.close();
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, net: true } },
+  async function listenTlsSniCallback() {
+    const hostname = "localhost";
+    const port = 4006;
+    const serverNames: string[] = [];
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      cert: await Deno.readTextFile("cli/tests/testdata/tls/RootCA.crt"),
+      key: await Deno.readTextFile("cli/tests/testdata/tls/RootCA.key"),
+      sni: async (serverName) => {
+        serverNames.push(serverName);
+        await delay(10);
+        return serverName === "localhost" ? { cert, key } : undefined;
+      },
+    });
+    const accepted = listener.accept().then(async (conn) => {
+      await conn.write(encoder.encode("hello"));
+      conn.close();
+    });
+
+    const conn = await Deno.connectTls({ hostname, port, caCerts });
+    const buf = new Uint8Array(5);
+    await conn.read(buf);
+    assertEquals(decoder.decode(buf), "hello");
+    assertEquals(serverNames, ["localhost"]);
+    conn.close();
+    await accepted;
+    listener.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true, read: true, write: true } },
+  function listenTlsAcmeInvalidOptions() {
+    const acme = { domains: [], cacheDir: "./acme" };
+    assertThrows(
+      () => Deno.listenTls({ hostname: "localhost", port: 4005, acme }),
+      TypeError,
+      "`acme.domains` must not be empty.",
+    );
+    assertThrows(
+      () =>
+        Deno.listenTls({
+          hostname: "localhost",
+          port: 4005,
+          cert,
+          key,
+          sni: {},
+          acme: { ...acme, domains: ["localhost"] },
+        }),
+      Error,
+      "Both sni and acme are specified.",
+    );
+  },
+);
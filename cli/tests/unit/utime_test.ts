@@ -295,3 +295,40 @@ Deno.test(
     }, Deno.errors.PermissionDenied);
   },
 );
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  function utimeSyncFractionalAndBigIntSuccess() {
+    const testDir = Deno.makeTempDirSync();
+    const filename = testDir + "/file.txt";
+    Deno.writeFileSync(filename, new TextEncoder().encode("hello"));
+
+    Deno.utimeSync(filename, 1000.25, 50_000_125_000_000n);
+
+    const fileInfo = Deno.statSync(filename);
+    assertEquals(fileInfo.atime, new Date(1000250));
+    assertEquals(fileInfo.mtime, new Date(50000125));
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function utimeBirthtime() {
+    const testDir = Deno.makeTempDirSync();
+    const filename = testDir + "/file.txt";
+    Deno.writeFileSync(filename, new TextEncoder().encode("hello"));
+
+    const birthtime = new Date(1_000_000);
+    const utime = () =>
+      Deno.utime(filename, 2000, 3000, { birthtime });
+    if (Deno.build.os === "linux") {
+      await assertRejects(utime, Deno.errors.NotSupported);
+      return;
+    }
+    await utime();
+
+    const fileInfo = Deno.statSync(filename);
+    assertEquals(fileInfo.birthtime, birthtime);
+    assertEquals(fileInfo.mtime, new Date(3000 * 1000));
+  },
+);
@@ -169,3 +169,155 @@ Deno.test(
     }
   },
 );
+
+Deno.test(
+  { sanitizeOps: false },
+  async function websocketCustomHeaders() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+
+    const server = Deno.serve({
+      handler: (req) => {
+        const { response, socket } = Deno.upgradeWebSocket(req);
+        socket.onopen = () => {
+          socket.send(
+            `${req.headers.get("authorization")} ${req.headers.get("cookie")}`,
+          );
+          socket.close();
+        };
+        socket.onclose = () => ac.abort();
+        return response;
+      },
+      signal: ac.signal,
+      onListen: () => listeningPromise.resolve(),
+      hostname: "localhost",
+      port: 4248,
+    });
+
+    await listeningPromise;
+    const promise = deferred();
+    const ws = new WebSocket("ws://localhost:4248/", {
+      headers: { "Authorization": "Bearer token", "Cookie": "a=b" },
+    });
+    ws.onerror = () => fail();
+    ws.onmessage = (e) => {
+      assertEquals(e.data, "Bearer token a=b");
+    };
+    ws.onclose = () => promise.resolve();
+    await Promise.all([promise, server]);
+  },
+);
+
+Deno.test(
+  { sanitizeOps: false },
+  async function websocketPingRoundTrip() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+
+    const server = Deno.serve({
+      handler: (req) => {
+        const { response, socket } = Deno.upgradeWebSocket(req);
+        socket.onclose = () => ac.abort();
+        return response;
+      },
+      signal: ac.signal,
+      onListen: () => listeningPromise.resolve(),
+      hostname: "localhost",
+      port: 4249,
+    });
+
+    await listeningPromise;
+    const promise = deferred();
+    const ws = new WebSocket("ws://localhost:4249/");
+    ws.onerror = () => fail();
+    ws.onopen = async () => {
+      const [first, second] = await Promise.all([
+        ws.ping(new Uint8Array([1, 2, 3])),
+        ws.ping(),
+      ]);
+      assert(first >= 0);
+      assert(second >= 0);
+      assertThrows(() => ws.ping(new Uint8Array(126)), DOMException);
+      ws.close();
+    };
+    ws.onclose = () => promise.resolve();
+    await Promise.all([promise, server]);
+  },
+);
+
+Deno.test(
+  { sanitizeOps: false },
+  async function websocketMaxMessageSize() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+    const serverClosed = deferred<number>();
+
+    const server = Deno.serve({
+      handler: (req) => {
+        const { response, socket } = Deno.upgradeWebSocket(req, {
+          maxMessageSize: 4,
+        });
+        socket.onmessage = () => fail();
+        socket.onclose = (e) => {
+          serverClosed.resolve(e.code);
+          ac.abort();
+        };
+        return response;
+      },
+      signal: ac.signal,
+      onListen: () => listeningPromise.resolve(),
+      hostname: "localhost",
+      port: 4250,
+    });
+
+    await listeningPromise;
+    const clientClosed = deferred<number>();
+    const ws = new WebSocket("ws://localhost:4250/");
+    ws.onopen = () => ws.send("too big");
+    ws.onclose = (e) => clientClosed.resolve(e.code);
+    assertEquals(await serverClosed, 1009);
+    assertEquals(await clientClosed, 1009);
+    await server;
+  },
+);
+
+Deno.test(
+  { sanitizeOps: false },
+  async function websocketBufferedAmountLow() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+
+    const server = Deno.serve({
+      handler: (req) => {
+        const { response, socket } = Deno.upgradeWebSocket(req);
+        socket.onclose = () => ac.abort();
+        return response;
+      },
+      signal: ac.signal,
+      onListen: () => listeningPromise.resolve(),
+      hostname: "localhost",
+      port: 4251,
+    });
+
+    await listeningPromise;
+    const promise = deferred();
+    const ws = new WebSocket("ws://localhost:4251/");
+    ws.onerror = () => fail();
+    ws.onopen = () => {
+      ws.bufferedAmountLowThreshold = 1024;
+      for (let i = 0; i < 64; i++) {
+        ws.send(new Uint8Array(64 * 1024));
+      }
+      // Everything may already have been written to the socket
+      if (ws.bufferedAmount <= 1024) {
+        ws.close();
+      }
+    };
+    ws.onbufferedamountlow = () => {
+      assert(ws.bufferedAmount <= 1024);
+      ws.close();
+    };
+    ws.onclose = () => promise.resolve();
+    await Promise.all([promise, server]);
+  },
+);
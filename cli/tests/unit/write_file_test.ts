@@ -425,3 +425,56 @@ Deno.test(
     assertEquals(Deno.readFileSync(filename), new Uint8Array([1, 2]));
   },
 );
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  function writeFileAtomicSyncSuccess() {
+    const tempDir = Deno.makeTempDirSync();
+    const filename = tempDir + "/test.txt";
+    Deno.writeFileAtomicSync(filename, "Hello");
+    assertEquals(Deno.readTextFileSync(filename), "Hello");
+    Deno.writeFileAtomicSync(filename, new TextEncoder().encode("World"));
+    assertEquals(Deno.readTextFileSync(filename), "World");
+    // No temporary file is left behind.
+    assertEquals([...Deno.readDirSync(tempDir)].length, 1);
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function writeFileAtomicPreservesMode() {
+    const tempDir = await Deno.makeTempDir();
+    const filename = tempDir + "/test.txt";
+    await Deno.writeFile(filename, new Uint8Array(), { mode: 0o600 });
+    await Deno.writeFileAtomic(filename, "Hello");
+    assertEquals(await Deno.readTextFile(filename), "Hello");
+    if (Deno.build.os !== "windows") {
+      assertEquals((await Deno.stat(filename)).mode! & 0o777, 0o600);
+      await Deno.writeFileAtomic(filename, "World", { mode: 0o640 });
+      assertEquals((await Deno.stat(filename)).mode! & 0o777, 0o640);
+    }
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function writeFileAtomicFollowsSymlink() {
+    const tempDir = await Deno.makeTempDir();
+    const filename = tempDir + "/test.txt";
+    const linkname = tempDir + "/link.txt";
+    await Deno.writeTextFile(filename, "Hello");
+    await Deno.symlink(filename, linkname);
+    await Deno.writeFileAtomic(linkname, "World");
+    assert((await Deno.lstat(linkname)).isSymlink);
+    assertEquals(await Deno.readTextFile(filename), "World");
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: false } },
+  function writeFileAtomicSyncPerm() {
+    assertThrows(() => {
+      Deno.writeFileAtomicSync("/file.txt", "Hello");
+    }, Deno.errors.PermissionDenied);
+  },
+);
@@ -0,0 +1,125 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+import {
+  assert,
+  assertEquals,
+  assertRejects,
+  assertThrows,
+} from "./test_util.ts";
+
+// Linux only allows extended attributes in a namespace.
+const name = Deno.build.os === "windows" ? "deno" : "user.deno";
+
+function supportsXattrs(path: string): boolean {
+  try {
+    Deno.setXattrSync(path, name, "");
+    Deno.removeXattrSync(path, name);
+    return true;
+  } catch (err) {
+    if (err instanceof Deno.errors.NotSupported) {
+      return false;
+    }
+    throw err;
+  }
+}
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  function xattrSyncSuccess() {
+    const path = Deno.makeTempFileSync();
+    if (!supportsXattrs(path)) {
+      Deno.removeSync(path);
+      return;
+    }
+    assertEquals(Deno.getXattrSync(path, name), null);
+
+    Deno.setXattrSync(path, name, "hello");
+    assertEquals(
+      Deno.getXattrSync(path, name),
+      new TextEncoder().encode("hello"),
+    );
+    Deno.setXattrSync(path, name, new Uint8Array([1, 2, 3]));
+    assertEquals(Deno.getXattrSync(path, name), new Uint8Array([1, 2, 3]));
+    assert(Deno.listXattrSync(path).includes(name));
+
+    Deno.removeXattrSync(path, name);
+    assertEquals(Deno.getXattrSync(path, name), null);
+    assert(!Deno.listXattrSync(path).includes(name));
+    assertThrows(() => Deno.removeXattrSync(path, name));
+
+    Deno.removeSync(path);
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function xattrSuccess() {
+    const path = await Deno.makeTempFile();
+    if (!supportsXattrs(path)) {
+      await Deno.remove(path);
+      return;
+    }
+    await Deno.setXattr(path, name, "hello");
+    assertEquals(
+      await Deno.getXattr(path, name),
+      new TextEncoder().encode("hello"),
+    );
+    assert((await Deno.listXattr(path)).includes(name));
+    await Deno.removeXattr(path, name);
+    assertEquals(await Deno.getXattr(path, name), null);
+
+    await Deno.remove(path);
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function xattrFsFile() {
+    const path = await Deno.makeTempFile();
+    if (!supportsXattrs(path)) {
+      await Deno.remove(path);
+      return;
+    }
+    const file = await Deno.open(path, { read: true, write: true });
+    await file.setXattr(name, "hello");
+    assertEquals(Deno.getXattrSync(path, name), new TextEncoder().encode("hello"));
+    assertEquals(await file.getXattr(name), new TextEncoder().encode("hello"));
+    assert(file.listXattrSync().includes(name));
+    file.removeXattrSync(name);
+    assertEquals(file.getXattrSync(name), null);
+    assert(!(await file.listXattr()).includes(name));
+    file.close();
+
+    await Deno.remove(path);
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function xattrNotFound() {
+    const path = Deno.makeTempDirSync() + "/missing.txt";
+    assertThrows(() => {
+      Deno.getXattrSync(path, name);
+    }, Deno.errors.NotFound);
+    await assertRejects(async () => {
+      await Deno.setXattr(path, name, "hello");
+    }, Deno.errors.NotFound);
+  },
+);
+
+Deno.test({ permissions: { read: false } }, function xattrPerm() {
+  assertThrows(() => {
+    Deno.getXattrSync("/", name);
+  }, Deno.errors.PermissionDenied);
+  assertThrows(() => {
+    Deno.listXattrSync("/");
+  }, Deno.errors.PermissionDenied);
+});
+
+Deno.test({ permissions: { write: false } }, async function xattrWritePerm() {
+  await assertRejects(async () => {
+    await Deno.setXattr("/", name, "hello");
+  }, Deno.errors.PermissionDenied);
+  await assertRejects(async () => {
+    await Deno.removeXattr("/", name);
+  }, Deno.errors.PermissionDenied);
+});
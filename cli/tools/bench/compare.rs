@@ -0,0 +1,298 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The comparison of the results of a run with a baseline, which is the JSON
+//! output of an earlier `deno bench --json` run. The means of the benchmarks
+//! are compared with Welch's t-test, so that only the changes that are
+//! statistically significant are reported as regressions.
+
+use super::BenchDescription;
+use super::BenchResult;
+use super::BenchStats;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The two-sided critical values of Student's t-distribution at the 95%
+/// confidence level, for 1 to 30 degrees of freedom.
+const T_CRITICAL_VALUES_95: [f64; 30] = [
+  12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201,
+  2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074,
+  2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+
+#[derive(Deserialize)]
+struct BaselineFile {
+  benches: Vec<BaselineBench>,
+}
+
+#[derive(Deserialize)]
+struct BaselineBench {
+  origin: String,
+  group: Option<String>,
+  name: String,
+  results: Vec<BenchResult>,
+}
+
+type BenchKey = (String, Option<String>, String);
+
+/// The results of the benchmarks of a baseline run, by origin, group and name.
+#[derive(Debug, Default)]
+pub struct BenchBaseline {
+  stats: HashMap<BenchKey, BenchStats>,
+}
+
+impl BenchBaseline {
+  pub fn load(path: &Path) -> Result<Self, AnyError> {
+    let text = std::fs::read_to_string(path).with_context(|| {
+      format!("Failed to read the bench baseline {}", path.display())
+    })?;
+    Self::parse(&text).with_context(|| {
+      format!("Failed to parse the bench baseline {}", path.display())
+    })
+  }
+
+  fn parse(text: &str) -> Result<Self, AnyError> {
+    let file: BaselineFile = serde_json::from_str(text)?;
+    let mut stats = HashMap::new();
+    for bench in file.benches {
+      // The benchmarks that failed in the baseline have nothing to compare.
+      let Some(bench_stats) =
+        bench.results.into_iter().find_map(|result| match result {
+          BenchResult::Ok(stats) => Some(stats),
+          BenchResult::Failed(_) => None,
+        })
+      else {
+        continue;
+      };
+      stats.insert((bench.origin, bench.group, bench.name), bench_stats);
+    }
+    Ok(Self { stats })
+  }
+
+  /// Compares the measurements of a run with the baseline. The benchmarks
+  /// that aren't in the baseline are left out.
+  pub fn compare(
+    &self,
+    measurements: &[(BenchDescription, BenchStats)],
+  ) -> Vec<BenchComparison> {
+    measurements
+      .iter()
+      .filter_map(|(desc, current)| {
+        let key = (desc.origin.clone(), desc.group.clone(), desc.name.clone());
+        let baseline = self.stats.get(&key)?;
+        Some(BenchComparison::new(desc, baseline, current))
+      })
+      .collect()
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BenchVerdict {
+  Regression,
+  Improvement,
+  Unchanged,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchComparison {
+  pub origin: String,
+  pub group: Option<String>,
+  pub name: String,
+  /// The mean time per iteration in the baseline, in nanoseconds.
+  pub baseline_avg: f64,
+  /// The mean time per iteration in this run, in nanoseconds.
+  pub current_avg: f64,
+  /// The change of the mean relative to the baseline.
+  pub change: f64,
+  /// The 95% confidence interval of the change, relative to the baseline.
+  pub confidence_interval: (f64, f64),
+  pub verdict: BenchVerdict,
+}
+
+impl BenchComparison {
+  fn new(
+    desc: &BenchDescription,
+    baseline: &BenchStats,
+    current: &BenchStats,
+  ) -> Self {
+    let diff = current.avg - baseline.avg;
+    let margin = welch_margin_of_error(baseline, current);
+    let verdict = if diff.abs() <= margin {
+      BenchVerdict::Unchanged
+    } else if diff > 0.0 {
+      BenchVerdict::Regression
+    } else {
+      BenchVerdict::Improvement
+    };
+    Self {
+      origin: desc.origin.clone(),
+      group: desc.group.clone(),
+      name: desc.name.clone(),
+      baseline_avg: baseline.avg,
+      current_avg: current.avg,
+      change: diff / baseline.avg,
+      confidence_interval: (
+        (diff - margin) / baseline.avg,
+        (diff + margin) / baseline.avg,
+      ),
+      verdict,
+    }
+  }
+}
+
+/// Returns the margin of error of the difference of the means at the 95%
+/// confidence level, with Welch's t-test, which doesn't assume that the
+/// variances are equal.
+fn welch_margin_of_error(a: &BenchStats, b: &BenchStats) -> f64 {
+  if a.n < 2 || b.n < 2 {
+    return f64::INFINITY;
+  }
+  let var_a = a.stddev * a.stddev / a.n as f64;
+  let var_b = b.stddev * b.stddev / b.n as f64;
+  let standard_error = (var_a + var_b).sqrt();
+  if standard_error == 0.0 {
+    return 0.0;
+  }
+  // The Welch–Satterthwaite approximation of the degrees of freedom.
+  let df = (var_a + var_b).powi(2)
+    / (var_a.powi(2) / (a.n - 1) as f64 + var_b.powi(2) / (b.n - 1) as f64);
+  t_critical_value_95(df) * standard_error
+}
+
+/// Returns the two-sided critical value of Student's t-distribution at the 95%
+/// confidence level. The fractional degrees of freedom are rounded down, which
+/// errs on the side of wider intervals, and beyond the table the value is
+/// approximated with the Cornish-Fisher expansion.
+fn t_critical_value_95(df: f64) -> f64 {
+  if df < 1.0 {
+    return T_CRITICAL_VALUES_95[0];
+  }
+  if df <= T_CRITICAL_VALUES_95.len() as f64 {
+    return T_CRITICAL_VALUES_95[df as usize - 1];
+  }
+  const Z: f64 = 1.959964;
+  let g1 = (Z.powi(3) + Z) / 4.0;
+  let g2 = (5.0 * Z.powi(5) + 16.0 * Z.powi(3) + 3.0 * Z) / 96.0;
+  let g3 =
+    (3.0 * Z.powi(7) + 19.0 * Z.powi(5) + 17.0 * Z.powi(3) - 15.0 * Z) / 384.0;
+  Z + g1 / df + g2 / df.powi(2) + g3 / df.powi(3)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn stats(n: u64, avg: f64, stddev: f64) -> BenchStats {
+    BenchStats {
+      n,
+      min: avg,
+      max: avg,
+      avg,
+      stddev,
+      p75: avg,
+      p99: avg,
+      p995: avg,
+      p999: avg,
+    }
+  }
+
+  fn desc(name: &str) -> BenchDescription {
+    BenchDescription {
+      id: 0,
+      name: name.to_string(),
+      origin: "file:///bench.ts".to_string(),
+      baseline: false,
+      group: None,
+      ignore: false,
+      only: false,
+    }
+  }
+
+  #[test]
+  fn test_t_critical_value_95() {
+    assert_eq!(t_critical_value_95(0.5), 12.706);
+    assert_eq!(t_critical_value_95(10.7), 2.228);
+    assert!((t_critical_value_95(40.0) - 2.021).abs() < 0.001);
+    assert!((t_critical_value_95(120.0) - 1.980).abs() < 0.001);
+    assert!((t_critical_value_95(1e9) - 1.960).abs() < 0.001);
+  }
+
+  #[test]
+  fn test_welch_margin_of_error() {
+    // Equal sizes and variances: the standard error is sqrt(2 * 4 / 8) = 1,
+    // with 14 degrees of freedom.
+    let margin =
+      welch_margin_of_error(&stats(8, 10.0, 2.0), &stats(8, 0.0, 2.0));
+    assert!((margin - 2.145).abs() < 1e-9);
+    assert_eq!(
+      welch_margin_of_error(&stats(8, 10.0, 0.0), &stats(8, 12.0, 0.0)),
+      0.0
+    );
+    assert_eq!(
+      welch_margin_of_error(&stats(1, 10.0, 1.0), &stats(8, 12.0, 1.0)),
+      f64::INFINITY
+    );
+  }
+
+  #[test]
+  fn test_compare() {
+    let baseline = BenchBaseline::parse(
+      r#"{
+        "runtime": "Deno/1.0.0 x86_64-unknown-linux-gnu",
+        "cpu": "unknown",
+        "benches": [
+          {
+            "origin": "file:///bench.ts",
+            "group": null,
+            "name": "slower",
+            "baseline": false,
+            "results": [{ "ok": { "n": 100, "min": 90, "max": 110, "avg": 100, "stddev": 5, "p75": 100, "p99": 110, "p995": 110, "p999": 110 } }]
+          },
+          {
+            "origin": "file:///bench.ts",
+            "group": null,
+            "name": "faster",
+            "baseline": false,
+            "results": [{ "ok": { "n": 100, "min": 90, "max": 110, "avg": 100, "stddev": 5, "p75": 100, "p99": 110, "p995": 110, "p999": 110 } }]
+          },
+          {
+            "origin": "file:///bench.ts",
+            "group": null,
+            "name": "noisy",
+            "baseline": false,
+            "results": [{ "ok": { "n": 100, "min": 0, "max": 200, "avg": 100, "stddev": 50, "p75": 100, "p99": 200, "p995": 200, "p999": 200 } }]
+          }
+        ]
+      }"#,
+    )
+    .unwrap();
+    let comparisons = baseline.compare(&[
+      (desc("slower"), stats(100, 110.0, 5.0)),
+      (desc("faster"), stats(100, 90.0, 5.0)),
+      (desc("noisy"), stats(100, 105.0, 50.0)),
+      (desc("new"), stats(100, 100.0, 5.0)),
+    ]);
+    let verdicts = comparisons
+      .iter()
+      .map(|c| (c.name.as_str(), c.verdict))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      verdicts,
+      vec![
+        ("slower", BenchVerdict::Regression),
+        ("faster", BenchVerdict::Improvement),
+        ("noisy", BenchVerdict::Unchanged),
+      ]
+    );
+    assert!((comparisons[0].change - 0.1).abs() < 1e-9);
+    let (low, high) = comparisons[0].confidence_interval;
+    assert!(low > 0.0 && low < 0.1 && high > 0.1);
+  }
+}
@@ -6,6 +6,8 @@ use crate::args::TypeCheckMode;
 use crate::colors;
 use crate::display::write_json_to_stdout;
 use crate::factory::CliFactory;
+use crate::graph_util::get_all_dependencies;
+use crate::graph_util::get_roots_affected_by_changes;
 use crate::graph_util::graph_valid_with_cli_options;
 use crate::module_loader::ModuleLoadPreparer;
 use crate::ops;
@@ -37,18 +39,24 @@ use indexmap::IndexSet;
 use log::Level;
 use serde::Deserialize;
 use serde::Serialize;
-use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::mpsc::UnboundedSender;
 
+mod compare;
+
+use compare::BenchBaseline;
+use compare::BenchComparison;
+use compare::BenchVerdict;
+
 #[derive(Debug, Clone)]
 struct BenchSpecifierOptions {
   filter: TestFilter,
   json: bool,
   log_level: Option<log::Level>,
+  baseline: Option<Arc<BenchBaseline>>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
@@ -83,6 +91,8 @@ pub struct BenchReport {
   pub failed: usize,
   pub failures: Vec<(BenchDescription, Box<JsError>)>,
   pub measurements: Vec<(BenchDescription, BenchStats)>,
+  /// The comparisons of the measurements with the baseline, if any.
+  pub comparisons: Vec<BenchComparison>,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Eq, Hash)]
@@ -102,6 +112,10 @@ pub struct BenchStats {
   pub min: f64,
   pub max: f64,
   pub avg: f64,
+  /// The sample standard deviation of the time per iteration. It is missing
+  /// from the results of older versions.
+  #[serde(default)]
+  pub stddev: f64,
   pub p75: f64,
   pub p99: f64,
   pub p995: f64,
@@ -115,6 +129,7 @@ impl BenchReport {
       failed: 0,
       failures: Vec::new(),
       measurements: Vec::new(),
+      comparisons: Vec::new(),
     }
   }
 }
@@ -144,6 +159,8 @@ struct JsonReporterOutput {
   runtime: String,
   cpu: String,
   benches: Vec<JsonReporterBench>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  comparisons: Vec<BenchComparison>,
 }
 
 impl Default for JsonReporterOutput {
@@ -152,6 +169,7 @@ impl Default for JsonReporterOutput {
       runtime: format!("{} {}", get_user_agent(), env!("TARGET")),
       cpu: mitata::cpu::name(),
       benches: vec![],
+      comparisons: vec![],
     }
   }
 }
@@ -179,7 +197,8 @@ impl BenchReporter for JsonReporter {
   #[cold]
   fn report_plan(&mut self, _plan: &BenchPlan) {}
 
-  fn report_end(&mut self, _report: &BenchReport) {
+  fn report_end(&mut self, report: &BenchReport) {
+    self.0.comparisons = report.comparisons.clone();
     match write_json_to_stdout(self) {
       Ok(_) => (),
       Err(e) => println!("{e}"),
@@ -411,11 +430,58 @@ impl BenchReporter for ConsoleReporter {
     self.group_measurements.clear();
   }
 
-  fn report_end(&mut self, _: &BenchReport) {
+  fn report_end(&mut self, report: &BenchReport) {
     self.report_group_summary();
+    if !report.comparisons.is_empty() {
+      println!("\n{}", format_comparisons(&report.comparisons));
+    }
   }
 }
 
+/// Formats the comparisons with the baseline as a table for each module.
+fn format_comparisons(comparisons: &[BenchComparison]) -> String {
+  let size = mitata::reporter::size(
+    &comparisons
+      .iter()
+      .map(|c| c.name.as_str())
+      .collect::<Vec<_>>(),
+  );
+  let mut s = colors::bold("comparison with baseline").to_string();
+  let mut origin = None;
+  for comparison in comparisons {
+    if origin != Some(&comparison.origin) {
+      origin = Some(&comparison.origin);
+      s.push_str(&format!(
+        "\n{}\n{:<size$}{:>16}{:>16}{:>22}",
+        colors::gray(&comparison.origin),
+        "benchmark",
+        "baseline",
+        "current",
+        "change (95% CI)",
+      ));
+    }
+    let (low, high) = comparison.confidence_interval;
+    let change = format!(
+      "{:+.2}% ({:+.2}% … {:+.2}%)",
+      comparison.change * 100.0,
+      low * 100.0,
+      high * 100.0
+    );
+    let verdict = match comparison.verdict {
+      BenchVerdict::Regression => colors::red("regression").to_string(),
+      BenchVerdict::Improvement => colors::green("improvement").to_string(),
+      BenchVerdict::Unchanged => colors::gray("no change").to_string(),
+    };
+    s.push_str(&format!(
+      "\n{:<size$}{:>16}{:>16}  {change} {verdict}",
+      comparison.name,
+      format!("{}/iter", mitata::fmt_duration(comparison.baseline_avg)),
+      format!("{}/iter", mitata::fmt_duration(comparison.current_avg)),
+    ));
+  }
+  s
+}
+
 /// Type check a collection of module and document specifiers.
 async fn check_specifiers(
   cli_options: &CliOptions,
@@ -587,6 +653,10 @@ async fn bench_specifiers(
         }
       }
 
+      if let Some(baseline) = &options.baseline {
+        report.comparisons = baseline.compare(&report.measurements);
+      }
+
       reporter.report_end(&report);
 
       if used_only {
@@ -599,6 +669,23 @@ async fn bench_specifiers(
         return Err(generic_error("Bench failed"));
       }
 
+      let regressions = report
+        .comparisons
+        .iter()
+        .filter(|c| c.verdict == BenchVerdict::Regression)
+        .count();
+      if regressions > 0 {
+        return Err(generic_error(format!(
+          "Bench failed because of {} significant {} from the baseline",
+          regressions,
+          if regressions == 1 {
+            "regression"
+          } else {
+            "regressions"
+          }
+        )));
+      }
+
       Ok(())
     })
   };
@@ -638,7 +725,7 @@ pub async fn run_benchmarks(
   // `PermissionsContainer` - otherwise granting/revoking permissions in one
   // file would have impact on other files, which is undesirable.
   let permissions =
-    Permissions::from_options(&cli_options.permissions_options())?;
+    Permissions::from_options(&cli_options.permissions_options()?)?;
 
   let specifiers =
     collect_specifiers(&bench_options.files, is_supported_bench_path)?;
@@ -647,6 +734,13 @@ pub async fn run_benchmarks(
     return Err(generic_error("No bench modules found"));
   }
 
+  let baseline = bench_options
+    .compare
+    .as_deref()
+    .map(BenchBaseline::load)
+    .transpose()?
+    .map(Arc::new);
+
   check_specifiers(
     cli_options,
     factory.module_load_preparer().await?,
@@ -669,6 +763,7 @@ pub async fn run_benchmarks(
       filter: TestFilter::from_flag(&bench_options.filter),
       json: bench_options.json,
       log_level,
+      baseline,
     },
   )
   .await?;
@@ -676,7 +771,7 @@ pub async fn run_benchmarks(
   Ok(())
 }
 
-// TODO(bartlomieju): heavy duplication of code with `cli/tools/test.rs`
+// TODO(bartlomieju): heavy duplication of code with `cli/tools/test/mod.rs`
 pub async fn run_benchmarks_with_watch(
   cli_options: CliOptions,
   bench_options: BenchOptions,
@@ -690,8 +785,14 @@ pub async fn run_benchmarks_with_watch(
   // `PermissionsContainer` - otherwise granting/revoking permissions in one
   // file would have impact on other files, which is undesirable.
   let permissions =
-    Permissions::from_options(&cli_options.permissions_options())?;
+    Permissions::from_options(&cli_options.permissions_options()?)?;
   let no_check = cli_options.type_check_mode() == TypeCheckMode::None;
+  let baseline = bench_options
+    .compare
+    .as_deref()
+    .map(BenchBaseline::load)
+    .transpose()?
+    .map(Arc::new);
 
   let resolver = |changed: Option<Vec<PathBuf>>| {
     let paths_to_watch = bench_options.files.include.clone();
@@ -706,79 +807,31 @@ pub async fn run_benchmarks_with_watch(
         collect_specifiers(&bench_options.files, is_supported_bench_path)?;
 
       let mut paths_to_watch = paths_to_watch_clone;
-      let mut modules_to_reload = if files_changed {
-        Vec::new()
-      } else {
-        bench_modules.clone()
-      };
       let graph = module_graph_builder
         .create_graph(bench_modules.clone())
         .await?;
       graph_valid_with_cli_options(&graph, &bench_modules, &cli_options)?;
 
-      // TODO(@kitsonk) - This should be totally derivable from the graph.
-      for specifier in bench_modules {
-        fn get_dependencies<'a>(
-          graph: &'a deno_graph::ModuleGraph,
-          maybe_module: Option<&'a deno_graph::Module>,
-          // This needs to be accessible to skip getting dependencies if they're already there,
-          // otherwise this will cause a stack overflow with circular dependencies
-          output: &mut HashSet<&'a ModuleSpecifier>,
-          no_check: bool,
-        ) {
-          if let Some(module) = maybe_module.and_then(|m| m.esm()) {
-            for dep in module.dependencies.values() {
-              if let Some(specifier) = &dep.get_code() {
-                if !output.contains(specifier) {
-                  output.insert(specifier);
-                  get_dependencies(
-                    graph,
-                    graph.get(specifier),
-                    output,
-                    no_check,
-                  );
-                }
-              }
-              if !no_check {
-                if let Some(specifier) = &dep.get_type() {
-                  if !output.contains(specifier) {
-                    output.insert(specifier);
-                    get_dependencies(
-                      graph,
-                      graph.get(specifier),
-                      output,
-                      no_check,
-                    );
-                  }
-                }
-              }
-            }
-          }
-        }
-
-        // This bench module and all it's dependencies
-        let mut modules = HashSet::new();
-        modules.insert(&specifier);
-        get_dependencies(&graph, graph.get(&specifier), &mut modules, no_check);
-
-        paths_to_watch.extend(
-          modules
-            .iter()
-            .filter_map(|specifier| specifier.to_file_path().ok()),
-        );
-
-        if let Some(changed) = &changed {
-          for path in changed
+      paths_to_watch.extend(
+        get_all_dependencies(&graph, &bench_modules, !no_check)
+          .into_iter()
+          .filter_map(|specifier| specifier.to_file_path().ok()),
+      );
+      let modules_to_reload = match &changed {
+        Some(changed) => {
+          let changed = changed
             .iter()
             .filter_map(|path| ModuleSpecifier::from_file_path(path).ok())
-          {
-            if modules.contains(&path) {
-              modules_to_reload.push(specifier);
-              break;
-            }
-          }
+            .collect::<Vec<_>>();
+          get_roots_affected_by_changes(
+            &graph,
+            &bench_modules,
+            &changed,
+            !no_check,
+          )
         }
-      }
+        _ => bench_modules,
+      };
 
       Ok((paths_to_watch, modules_to_reload))
     }
@@ -813,6 +866,7 @@ pub async fn run_benchmarks_with_watch(
     let module_load_preparer = module_load_preparer.clone();
     let cli_options = cli_options.clone();
     let create_cli_main_worker_factory = create_cli_main_worker_factory.clone();
+    let baseline = baseline.clone();
 
     async move {
       let worker_factory = Arc::new(create_cli_main_worker_factory());
@@ -838,6 +892,7 @@ pub async fn run_benchmarks_with_watch(
           filter: TestFilter::from_flag(&bench_options.filter),
           json: bench_options.json,
           log_level,
+          baseline,
         },
       )
       .await?;
@@ -872,7 +927,7 @@ mod mitata {
   use crate::colors;
   use std::str::FromStr;
 
-  fn fmt_duration(time: f64) -> String {
+  pub fn fmt_duration(time: f64) -> String {
     // SAFETY: this is safe since its just reformatting numbers
     unsafe {
       if time < 1e0 {
@@ -0,0 +1,336 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use deno_ast::swc::ast;
+use deno_ast::swc::bundler;
+use deno_ast::swc::codegen;
+use deno_ast::swc::codegen::text_writer::JsWriter;
+use deno_ast::swc::common::comments::SingleThreadedComments;
+use deno_ast::swc::common::FileName;
+use deno_ast::swc::common::Globals;
+use deno_ast::swc::common::Mark;
+use deno_ast::swc::common::SourceFile;
+use deno_ast::swc::common::SourceMap;
+use deno_ast::swc::common::Span;
+use deno_ast::swc::common::GLOBALS;
+use deno_ast::swc::parser::lexer::Lexer;
+use deno_ast::swc::parser::Parser;
+use deno_ast::swc::parser::StringInput;
+use deno_ast::EmitOptions;
+use deno_ast::MediaType;
+use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::anyhow;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_graph::Module;
+use deno_graph::ModuleGraph;
+
+const IGNORE_DIRECTIVES: &str = "// deno-fmt-ignore-file
+// deno-lint-ignore-file
+// This code was bundled using `deno bundle` and it's not recommended to edit it manually
+
+";
+
+/// Where the source map of a bundle is emitted to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SourceMapMode {
+  /// Appended to the code as a data URL.
+  Inline,
+  /// Returned next to the code, so it can be written to a separate file.
+  Separate,
+}
+
+pub struct BundleOptions<'a> {
+  pub emit_options: EmitOptions,
+  /// The specifiers, as written in the imports, that are left as imports of
+  /// the bundle instead of being included in it.
+  pub external_specifiers: &'a [String],
+  pub maybe_source_map: Option<SourceMapMode>,
+  pub minify: bool,
+}
+
+pub struct BundleEmit {
+  pub code: String,
+  pub maybe_map: Option<String>,
+}
+
+/// Bundles the modules of the graph, starting at its first root, into a
+/// single ES module.
+pub fn bundle_graph(
+  graph: &ModuleGraph,
+  options: BundleOptions,
+) -> Result<BundleEmit, AnyError> {
+  let globals = Globals::new();
+  GLOBALS.set(&globals, || {
+    let cm = Rc::new(SourceMap::default());
+    let loader = BundleLoader {
+      graph,
+      emit_options: &options.emit_options,
+      cm: cm.clone(),
+    };
+    let resolver = BundleResolver(graph);
+    let config = bundler::Config {
+      module: bundler::ModuleType::Es,
+      external_modules: options
+        .external_specifiers
+        .iter()
+        .map(|specifier| specifier.as_str().into())
+        .collect(),
+      ..Default::default()
+    };
+    let mut bundler = bundler::Bundler::new(
+      &globals,
+      cm.clone(),
+      loader,
+      resolver,
+      config,
+      Box::new(BundleHook),
+    );
+    let mut entries = HashMap::new();
+    entries.insert("bundle".to_string(), FileName::Url(graph.roots[0].clone()));
+    let output = bundler
+      .bundle(entries)
+      .context("Unable to output during bundling.")?;
+
+    let mut buf = Vec::new();
+    let mut srcmap = Vec::new();
+    {
+      let cfg = codegen::Config {
+        minify: options.minify,
+        ascii_only: false,
+        target: deno_ast::ES_VERSION,
+        omit_last_semi: options.minify,
+      };
+      let wr =
+        Box::new(JsWriter::new(cm.clone(), "\n", &mut buf, Some(&mut srcmap)));
+      let mut emitter = codegen::Emitter {
+        cfg,
+        cm: cm.clone(),
+        comments: None,
+        wr,
+      };
+      emitter
+        .emit_module(&output[0].module)
+        .context("Unable to emit during bundling.")?;
+    }
+    let mut code =
+      String::from_utf8(buf).context("Emitted code is an invalid string.")?;
+    if !options.minify {
+      // the directives have to come after a shebang
+      let index = if code.starts_with("#!") {
+        code.find('\n').map(|i| i + 1).unwrap_or(code.len())
+      } else {
+        0
+      };
+      code.insert_str(index, IGNORE_DIRECTIVES);
+    }
+
+    let maybe_source_map = options.maybe_source_map.or_else(|| {
+      if options.emit_options.inline_source_map {
+        Some(SourceMapMode::Inline)
+      } else if options.emit_options.source_map {
+        Some(SourceMapMode::Separate)
+      } else {
+        None
+      }
+    });
+    let mut maybe_map = None;
+    if let Some(mode) = maybe_source_map {
+      let mut buf = Vec::new();
+      cm.build_source_map_from(&srcmap, None)
+        .to_writer(&mut buf)
+        .context("Unable to write the source map.")?;
+      match mode {
+        SourceMapMode::Inline => {
+          if !code.ends_with('\n') {
+            code.push('\n');
+          }
+          code.push_str("//# sourceMappingURL=data:application/json;base64,");
+          code.push_str(&base64::encode(buf));
+        }
+        SourceMapMode::Separate => {
+          maybe_map = Some(
+            String::from_utf8(buf)
+              .context("The source map is an invalid string.")?,
+          );
+        }
+      }
+    }
+
+    Ok(BundleEmit { code, maybe_map })
+  })
+}
+
+/// Provides the transpiled modules of the graph to the bundler.
+struct BundleLoader<'a> {
+  graph: &'a ModuleGraph,
+  emit_options: &'a EmitOptions,
+  cm: Rc<SourceMap>,
+}
+
+impl bundler::Load for BundleLoader<'_> {
+  fn load(
+    &self,
+    file_name: &FileName,
+  ) -> Result<bundler::ModuleData, AnyError> {
+    let FileName::Url(specifier) = file_name else {
+      unreachable!("Received a request for unsupported filename {file_name:?}");
+    };
+    let (source, media_type) = match self.graph.get(specifier) {
+      Some(Module::Esm(module)) => (&module.source, module.media_type),
+      Some(Module::Json(module)) => (&module.source, module.media_type),
+      _ => {
+        return Err(anyhow!(
+          "Module \"{}\" unexpectedly missing when bundling.",
+          specifier
+        ))
+      }
+    };
+    let (fm, module) = transpile_module(
+      specifier,
+      source,
+      media_type,
+      self.emit_options,
+      self.cm.clone(),
+    )?;
+    Ok(bundler::ModuleData {
+      fm,
+      module,
+      helpers: Default::default(),
+    })
+  }
+}
+
+/// Resolves the imports of the bundled modules like they were resolved when
+/// building the graph.
+struct BundleResolver<'a>(&'a ModuleGraph);
+
+impl bundler::Resolve for BundleResolver<'_> {
+  fn resolve(
+    &self,
+    referrer: &FileName,
+    specifier: &str,
+  ) -> Result<FileName, AnyError> {
+    let FileName::Url(referrer) = referrer else {
+      unreachable!("Unexpected non-URL referrer {referrer:?}");
+    };
+    let resolved = self
+      .0
+      .resolve_dependency(specifier, referrer, false)
+      .ok_or_else(|| {
+        anyhow!(
+          "Unable to resolve \"{}\" from \"{}\" when bundling.",
+          specifier,
+          referrer
+        )
+      })?;
+    Ok(FileName::Url(resolved.clone()))
+  }
+}
+
+/// Rewrites `import.meta` of the bundled modules, so the bundled code behaves
+/// like the unbundled code.
+struct BundleHook;
+
+impl bundler::Hook for BundleHook {
+  fn get_import_meta_props(
+    &self,
+    span: Span,
+    module_record: &bundler::ModuleRecord,
+  ) -> Result<Vec<ast::KeyValueProp>, AnyError> {
+    let url = match &module_record.file_name {
+      FileName::Url(url) => url.to_string(),
+      file_name => file_name.to_string(),
+    };
+    let main = if module_record.is_entry {
+      ast::Expr::Member(ast::MemberExpr {
+        span,
+        obj: Box::new(ast::Expr::MetaProp(ast::MetaPropExpr {
+          span,
+          kind: ast::MetaPropKind::ImportMeta,
+        })),
+        prop: ast::MemberProp::Ident(ast::Ident::new("main".into(), span)),
+      })
+    } else {
+      ast::Expr::Lit(ast::Lit::Bool(ast::Bool { span, value: false }))
+    };
+    Ok(vec![
+      ast::KeyValueProp {
+        key: ast::PropName::Ident(ast::Ident::new("url".into(), span)),
+        value: Box::new(ast::Expr::Lit(ast::Lit::Str(ast::Str {
+          span,
+          value: url.into(),
+          raw: None,
+        }))),
+      },
+      ast::KeyValueProp {
+        key: ast::PropName::Ident(ast::Ident::new("main".into(), span)),
+        value: Box::new(main),
+      },
+    ])
+  }
+}
+
+/// Parses a module of the graph into the source map of the bundle and strips
+/// its types.
+fn transpile_module(
+  specifier: &ModuleSpecifier,
+  source: &str,
+  media_type: MediaType,
+  options: &EmitOptions,
+  cm: Rc<SourceMap>,
+) -> Result<(Rc<SourceFile>, ast::Module), AnyError> {
+  let source = source.strip_prefix('\u{FEFF}').unwrap_or(source);
+  let (source, media_type) = if media_type == MediaType::Json {
+    (
+      format!(
+        "export default JSON.parse(`{}`);",
+        source.replace("${", "\\${").replace('`', "\\`")
+      ),
+      MediaType::JavaScript,
+    )
+  } else {
+    (source.to_string(), media_type)
+  };
+  let source_file =
+    cm.new_source_file(FileName::Url(specifier.clone()), source);
+  let input = StringInput::from(&*source_file);
+  let comments = SingleThreadedComments::default();
+  let lexer = Lexer::new(
+    deno_ast::get_syntax(media_type),
+    deno_ast::ES_VERSION,
+    input,
+    Some(&comments),
+  );
+  let mut parser = Parser::new_from(lexer);
+  let parse_error = |err: deno_ast::swc::parser::error::Error| {
+    let loc = cm.lookup_char_pos(err.span().lo);
+    anyhow!(
+      "{} at {}:{}:{}",
+      err.kind().msg(),
+      specifier,
+      loc.line,
+      loc.col_display + 1
+    )
+  };
+  let module = parser.parse_module().map_err(parse_error)?;
+  if let Some(err) = parser.take_errors().into_iter().next() {
+    return Err(parse_error(err));
+  }
+
+  let top_level_mark = Mark::fresh(Mark::root());
+  let program = deno_ast::fold_program(
+    ast::Program::Module(module),
+    options,
+    cm,
+    &comments,
+    top_level_mark,
+    &[],
+  )?;
+  let ast::Program::Module(module) = program else {
+    unreachable!();
+  };
+  Ok((source_file, module))
+}
@@ -0,0 +1,311 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use deno_core::error::AnyError;
+use deno_core::futures;
+use deno_core::futures::FutureExt;
+use deno_core::ModuleSpecifier;
+use deno_graph::source::LoadFuture;
+use deno_graph::source::LoadResponse;
+use deno_graph::source::Loader;
+use deno_graph::source::Resolver;
+use deno_graph::Module;
+use deno_runtime::colors;
+
+use crate::args::BundleFlags;
+use crate::args::BundleSourceMapFlag;
+use crate::args::CliOptions;
+use crate::args::Flags;
+use crate::args::TsConfigType;
+use crate::args::TypeCheckMode;
+use crate::factory::CliFactory;
+use crate::graph_util::error_for_any_npm_specifier;
+use crate::util;
+use crate::util::display;
+use crate::util::file_watcher::ResolutionResult;
+
+mod emit;
+
+/// The scheme of the specifiers that the imports left out of the bundle
+/// resolve to in the module graph.
+const EXTERNAL_SCHEME: &str = "external";
+
+pub async fn bundle(
+  flags: Flags,
+  bundle_flags: BundleFlags,
+) -> Result<(), AnyError> {
+  let cli_options = Arc::new(CliOptions::from_flags(flags)?);
+  let module_specifier = cli_options.resolve_main_module()?;
+
+  let resolver = |_| {
+    let cli_options = cli_options.clone();
+    let module_specifier = &module_specifier;
+    let external = &bundle_flags.external;
+    async move {
+      log::debug!(">>>>> bundle START");
+      let factory = CliFactory::from_cli_options(cli_options);
+      let module_graph_builder = factory.module_graph_builder().await?;
+      let cli_options = factory.cli_options();
+
+      let mut loader = ExternalLoader {
+        inner: module_graph_builder.create_graph_loader(),
+      };
+      let resolver = ExternalResolver {
+        inner: factory.resolver().await?.as_graph_resolver(),
+        external,
+      };
+      let graph = module_graph_builder
+        .create_graph_with_loader_and_maybe_check(
+          vec![module_specifier.clone()],
+          &mut loader,
+          &resolver,
+        )
+        .await?;
+
+      let mut paths_to_watch: Vec<PathBuf> = graph
+        .specifiers()
+        .filter_map(|(_, r)| {
+          r.ok().and_then(|module| match module {
+            Module::Esm(m) => m.specifier.to_file_path().ok(),
+            Module::Json(m) => m.specifier.to_file_path().ok(),
+            // nothing to watch
+            Module::Node(_) | Module::Npm(_) | Module::External(_) => None,
+          })
+        })
+        .collect();
+
+      if let Ok(Some(import_map_path)) = cli_options
+        .resolve_import_map_specifier()
+        .map(|ms| ms.and_then(|ref s| s.to_file_path().ok()))
+      {
+        paths_to_watch.push(import_map_path);
+      }
+
+      Ok((paths_to_watch, graph, cli_options.clone()))
+    }
+    .map(move |result| match result {
+      Ok((paths_to_watch, graph, ps)) => ResolutionResult::Restart {
+        paths_to_watch,
+        result: Ok((ps, graph)),
+      },
+      Err(e) => ResolutionResult::Restart {
+        paths_to_watch: vec![module_specifier.to_file_path().unwrap()],
+        result: Err(e),
+      },
+    })
+  };
+
+  let operation =
+    |(cli_options, graph): (Arc<CliOptions>, Arc<deno_graph::ModuleGraph>)| {
+      let bundle_flags = &bundle_flags;
+      async move {
+        // at the moment, we don't support npm specifiers in deno bundle, so show an error
+        error_for_any_npm_specifier(&graph)?;
+
+        let mut bundle_output =
+          bundle_module_graph(graph.as_ref(), &cli_options, bundle_flags)?;
+        log::debug!(">>>>> bundle END");
+
+        if let Some(out_file) = &bundle_flags.out_file {
+          let maybe_map_out_file = bundle_output.maybe_map.as_ref().map(|_| {
+            let ext = if let Some(curr_ext) = out_file.extension() {
+              format!("{}.map", curr_ext.to_string_lossy())
+            } else {
+              "map".to_string()
+            };
+            out_file.with_extension(ext)
+          });
+          if let Some(file_name) = maybe_map_out_file
+            .as_ref()
+            .and_then(|map_out_file| map_out_file.file_name())
+          {
+            if !bundle_output.code.ends_with('\n') {
+              bundle_output.code.push('\n');
+            }
+            bundle_output.code.push_str(&format!(
+              "//# sourceMappingURL={}",
+              file_name.to_string_lossy()
+            ));
+          }
+          let output_bytes = bundle_output.code.as_bytes();
+          let output_len = output_bytes.len();
+          util::fs::write_file(out_file, output_bytes, 0o644)?;
+          log::info!(
+            "{} {:?} ({})",
+            colors::green("Emit"),
+            out_file,
+            colors::gray(display::human_size(output_len as f64))
+          );
+          if let (Some(bundle_map), Some(map_out_file)) =
+            (bundle_output.maybe_map, maybe_map_out_file)
+          {
+            let map_bytes = bundle_map.as_bytes();
+            let map_len = map_bytes.len();
+            util::fs::write_file(&map_out_file, map_bytes, 0o644)?;
+            log::info!(
+              "{} {:?} ({})",
+              colors::green("Emit"),
+              map_out_file,
+              colors::gray(display::human_size(map_len as f64))
+            );
+          }
+        } else {
+          println!("{}", bundle_output.code);
+        }
+
+        Ok(())
+      }
+    };
+
+  if cli_options.watch_paths().is_some() {
+    util::file_watcher::watch_func(
+      resolver,
+      operation,
+      util::file_watcher::PrintConfig {
+        job_name: "Bundle".to_string(),
+        clear_screen: !cli_options.no_clear_screen(),
+      },
+    )
+    .await?;
+  } else {
+    let module_graph =
+      if let ResolutionResult::Restart { result, .. } = resolver(None).await {
+        result?
+      } else {
+        unreachable!();
+      };
+    operation(module_graph).await?;
+  }
+
+  Ok(())
+}
+
+fn bundle_module_graph(
+  graph: &deno_graph::ModuleGraph,
+  cli_options: &CliOptions,
+  bundle_flags: &BundleFlags,
+) -> Result<emit::BundleEmit, AnyError> {
+  log::info!("{} {}", colors::green("Bundle"), graph.roots[0]);
+
+  let ts_config_result =
+    cli_options.resolve_ts_config_for_emit(TsConfigType::Bundle)?;
+  if cli_options.type_check_mode() == TypeCheckMode::None {
+    if let Some(ignored_options) = ts_config_result.maybe_ignored_options {
+      log::warn!("{}", ignored_options);
+    }
+  }
+
+  let maybe_source_map = bundle_flags.source_map.map(|mode| match mode {
+    BundleSourceMapFlag::Inline => emit::SourceMapMode::Inline,
+    // without an output file, the bundle is written to stdout and there is
+    // no file to put the source map next to
+    BundleSourceMapFlag::External if bundle_flags.out_file.is_none() => {
+      emit::SourceMapMode::Inline
+    }
+    BundleSourceMapFlag::External => emit::SourceMapMode::Separate,
+  });
+  // the imports that are left out of the bundle, as they are written
+  let external_specifiers = graph
+    .modules()
+    .filter_map(|module| module.esm())
+    .flat_map(|module| module.dependencies.keys())
+    .filter(|specifier| is_external(&bundle_flags.external, specifier))
+    .cloned()
+    .collect::<BTreeSet<_>>()
+    .into_iter()
+    .collect::<Vec<_>>();
+
+  emit::bundle_graph(
+    graph,
+    emit::BundleOptions {
+      emit_options: ts_config_result.ts_config.into(),
+      external_specifiers: &external_specifiers,
+      maybe_source_map,
+      minify: bundle_flags.minify,
+    },
+  )
+}
+
+/// Whether the specifier, as it is written in an import, matches one of the
+/// `--external` patterns. A pattern that ends with `*` matches the specifiers
+/// that start with the rest of it.
+fn is_external(external: &[String], specifier: &str) -> bool {
+  external
+    .iter()
+    .any(|pattern| match pattern.strip_suffix('*') {
+      Some(prefix) => specifier.starts_with(prefix),
+      None => specifier == pattern,
+    })
+}
+
+/// Resolves the imports that are left out of the bundle to specifiers with the
+/// "external" scheme, which are never loaded, so that they don't have to be
+/// resolvable or exist.
+struct ExternalResolver<'a> {
+  inner: &'a dyn Resolver,
+  external: &'a [String],
+}
+
+impl Resolver for ExternalResolver<'_> {
+  fn default_jsx_import_source(&self) -> Option<String> {
+    self.inner.default_jsx_import_source()
+  }
+
+  fn jsx_import_source_module(&self) -> &str {
+    self.inner.jsx_import_source_module()
+  }
+
+  fn resolve(
+    &self,
+    specifier: &str,
+    referrer: &ModuleSpecifier,
+  ) -> Result<ModuleSpecifier, AnyError> {
+    if is_external(self.external, specifier) {
+      Ok(ModuleSpecifier::parse(&format!(
+        "{EXTERNAL_SCHEME}:{specifier}"
+      ))?)
+    } else {
+      self.inner.resolve(specifier, referrer)
+    }
+  }
+}
+
+struct ExternalLoader<L: Loader> {
+  inner: L,
+}
+
+impl<L: Loader> Loader for ExternalLoader<L> {
+  fn load(
+    &mut self,
+    specifier: &ModuleSpecifier,
+    is_dynamic: bool,
+  ) -> LoadFuture {
+    if specifier.scheme() == EXTERNAL_SCHEME {
+      return Box::pin(futures::future::ready(Ok(Some(
+        LoadResponse::External {
+          specifier: specifier.clone(),
+        },
+      ))));
+    }
+    self.inner.load(specifier, is_dynamic)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_is_external() {
+    let external = vec!["react".to_string(), "npm:*".to_string()];
+    assert!(is_external(&external, "react"));
+    assert!(!is_external(&external, "react-dom"));
+    assert!(is_external(&external, "npm:preact@10"));
+    assert!(is_external(&external, "npm:"));
+    assert!(!is_external(&external, "./npm:foo.ts"));
+    assert!(!is_external(&[], "react"));
+  }
+}
@@ -0,0 +1,327 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::CoverageReport;
+use super::CoverageReporter;
+use super::CoverageStats;
+use super::CoverageSummary;
+
+use deno_core::error::AnyError;
+use deno_core::url::Url;
+use percent_encoding::percent_decode_str;
+use percent_encoding::utf8_percent_encode;
+use percent_encoding::AsciiSet;
+use percent_encoding::CONTROLS;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.4em; }
+a { color: #0b5cad; }
+table { border-collapse: collapse; }
+th, td { padding: 0.2em 0.8em; text-align: left; }
+.summary th, .summary td { border-bottom: 1px solid #ddd; }
+.summary td.ratio { text-align: right; font-family: monospace; }
+.high { background: #dff0d8; }
+.medium { background: #fcf8e3; }
+.low { background: #f2dede; }
+.source td { padding: 0 0.8em; font-family: monospace; white-space: pre; }
+.source td.line, .source td.count { text-align: right; color: #888; }
+.source td.line a { color: inherit; text-decoration: none; }
+.source tr.hit td.count { background: #dff0d8; }
+.source tr.miss td.count, .source tr.miss td.code { background: #f2dede; }
+";
+
+/// The characters that are encoded in the segments of the paths of the pages
+/// when they are linked to.
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+  .add(b' ')
+  .add(b'"')
+  .add(b'#')
+  .add(b'%')
+  .add(b'<')
+  .add(b'>')
+  .add(b'?')
+  .add(b'`')
+  .add(b'{')
+  .add(b'}');
+
+/// Encodes the path of a page, relative to the report directory, as a URL.
+fn encode_page_path(page: &str) -> String {
+  page
+    .split('/')
+    .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT).to_string())
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+/// Formats a table cell with the covered percentage, colored by how high it is.
+fn format_cell(stats: CoverageStats) -> String {
+  let ratio = stats.ratio();
+  let class = if ratio >= 0.9 {
+    "high"
+  } else if ratio >= 0.75 {
+    "medium"
+  } else {
+    "low"
+  };
+  format!(
+    r#"<td class="ratio {class}">{:.3}% ({}/{})</td>"#,
+    ratio * 100.0,
+    stats.hit,
+    stats.found
+  )
+}
+
+struct FileSummary {
+  name: String,
+  /// The path of the page of the file, relative to the report directory.
+  page: String,
+  summary: CoverageSummary,
+}
+
+/// Writes a browsable report to a directory, with a page for each file that
+/// shows its source annotated with the hit counts of the lines, and an
+/// `index.html` page that summarizes the coverage of all the files.
+pub struct HtmlCoverageReporter {
+  dir: PathBuf,
+  cwd: Url,
+  files: Vec<FileSummary>,
+}
+
+impl HtmlCoverageReporter {
+  pub fn new(dir: PathBuf) -> HtmlCoverageReporter {
+    HtmlCoverageReporter {
+      dir,
+      cwd: Url::from_directory_path(std::env::current_dir().unwrap()).unwrap(),
+      files: Vec::new(),
+    }
+  }
+
+  /// Returns the name of the file shown in the report, and the path of its
+  /// page. The pages of the files in the current directory mirror its layout,
+  /// with the names of the files decoded from their URLs.
+  fn get_name_and_page(&self, url: &Url) -> (String, String) {
+    if url.scheme() == "file" {
+      let relative = self
+        .cwd
+        .make_relative(url)
+        .filter(|relative| !relative.starts_with("../"))
+        .and_then(|relative| {
+          percent_decode_str(&relative)
+            .decode_utf8()
+            .ok()
+            .map(Cow::into_owned)
+        });
+      if let Some(relative) = relative {
+        let page = format!("{relative}.html");
+        return (relative, page);
+      }
+    }
+    let sanitized = url
+      .as_str()
+      .chars()
+      .map(|c| {
+        if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+          c
+        } else {
+          '_'
+        }
+      })
+      .collect::<String>();
+    (url.to_string(), format!("external/{sanitized}.html"))
+  }
+
+  fn format_index_page(&self) -> String {
+    let mut total = CoverageSummary::default();
+    let mut rows = String::new();
+    for file in &self.files {
+      total.add(&file.summary);
+      writeln!(
+        rows,
+        r#"<tr><td><a href="{}">{}</a></td>{}{}{}</tr>"#,
+        escape_html(&encode_page_path(&file.page)),
+        escape_html(&file.name),
+        format_cell(file.summary.lines),
+        format_cell(file.summary.functions),
+        format_cell(file.summary.branches),
+      )
+      .unwrap();
+    }
+
+    let mut html = format_page_start("Coverage report");
+    html.push_str("<h1>Coverage report</h1>\n");
+    html.push_str(&format_summary_table(&total));
+    html.push_str("<table class=\"summary\">\n");
+    html.push_str(
+      "<tr><th>File</th><th>Lines</th><th>Functions</th><th>Branches</th></tr>\n",
+    );
+    html.push_str(&rows);
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+  }
+}
+
+impl CoverageReporter for HtmlCoverageReporter {
+  fn report(
+    &mut self,
+    coverage_report: &CoverageReport,
+    file_text: &str,
+  ) -> Result<(), AnyError> {
+    let (name, page) = self.get_name_and_page(&coverage_report.url);
+    let file = FileSummary {
+      name,
+      page,
+      summary: CoverageSummary::from_report(coverage_report),
+    };
+
+    let path = self.dir.join(&file.page);
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::write(path, format_file_page(&file, coverage_report, file_text))?;
+    self.files.push(file);
+    Ok(())
+  }
+
+  fn done(&mut self) -> Result<(), AnyError> {
+    fs::create_dir_all(&self.dir)?;
+    let index_path = self.dir.join("index.html");
+    fs::write(&index_path, self.format_index_page())?;
+    let index_path = fs::canonicalize(&index_path).unwrap_or(index_path);
+    println!(
+      "HTML coverage report has been generated at {}",
+      Url::from_file_path(&index_path)
+        .map(|url| url.to_string())
+        .unwrap_or_else(|_| index_path.display().to_string())
+    );
+    Ok(())
+  }
+}
+
+fn format_page_start(title: &str) -> String {
+  format!(
+    "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n",
+    escape_html(title)
+  )
+}
+
+fn format_summary_table(summary: &CoverageSummary) -> String {
+  format!(
+    "<table class=\"summary\">\n<tr><th>Lines</th>{}</tr>\n<tr><th>Functions</th>{}</tr>\n<tr><th>Branches</th>{}</tr>\n</table>\n<br>\n",
+    format_cell(summary.lines),
+    format_cell(summary.functions),
+    format_cell(summary.branches),
+  )
+}
+
+fn format_file_page(
+  file: &FileSummary,
+  coverage_report: &CoverageReport,
+  file_text: &str,
+) -> String {
+  let line_counts = coverage_report
+    .found_lines
+    .iter()
+    .copied()
+    .collect::<HashMap<usize, i64>>();
+  // The pages of the files are nested as deep as their paths.
+  let index_href =
+    format!("{}index.html", "../".repeat(file.page.matches('/').count()));
+
+  let mut html = format_page_start(&file.name);
+  writeln!(
+    html,
+    "<h1><a href=\"{}\">All files</a> / {}</h1>",
+    index_href,
+    escape_html(&file.name)
+  )
+  .unwrap();
+  html.push_str(&format_summary_table(&file.summary));
+  html.push_str("<table class=\"source\">\n");
+  for (index, line) in file_text.split('\n').enumerate() {
+    let number = index + 1;
+    let (class, count) = match line_counts.get(&index) {
+      Some(count) if *count > 0 => ("hit", format!("{count}x")),
+      Some(_) => ("miss", "0x".to_string()),
+      None => ("", String::new()),
+    };
+    writeln!(
+      html,
+      r#"<tr class="{class}"><td class="line"><a id="L{number}" href="#L{number}">{number}</a></td><td class="count">{count}</td><td class="code">{}</td></tr>"#,
+      escape_html(line.trim_end_matches('\r')),
+    )
+    .unwrap();
+  }
+  html.push_str("</table>\n</body>\n</html>\n");
+  html
+}
+
+fn escape_html(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&#39;"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_get_name_and_page() {
+    let mut reporter = HtmlCoverageReporter::new(PathBuf::from("html"));
+    reporter.cwd = Url::parse("file:///project/").unwrap();
+    assert_eq!(
+      reporter
+        .get_name_and_page(&Url::parse("file:///project/src/mod.ts").unwrap()),
+      ("src/mod.ts".to_string(), "src/mod.ts.html".to_string())
+    );
+    assert_eq!(
+      reporter.get_name_and_page(
+        &Url::parse("file:///project/src/my%20m%C3%B6d.ts").unwrap()
+      ),
+      (
+        "src/my möd.ts".to_string(),
+        "src/my möd.ts.html".to_string()
+      )
+    );
+    assert_eq!(
+      reporter.get_name_and_page(
+        &Url::parse("https://deno.land/std/path/mod.ts").unwrap()
+      ),
+      (
+        "https://deno.land/std/path/mod.ts".to_string(),
+        "external/https___deno.land_std_path_mod.ts.html".to_string()
+      )
+    );
+  }
+
+  #[test]
+  fn test_encode_page_path() {
+    assert_eq!(encode_page_path("src/mod.ts.html"), "src/mod.ts.html");
+    assert_eq!(
+      encode_page_path("src/my möd#1.ts.html"),
+      "src/my%20m%C3%B6d%231.ts.html"
+    );
+  }
+
+  #[test]
+  fn test_escape_html() {
+    assert_eq!(
+      escape_html(r#"<a href="x">'&'</a>"#),
+      "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+    );
+  }
+}
@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use crate::args::CoverageFlags;
+use crate::args::CoverageThresholds;
 use crate::args::FileFlags;
 use crate::args::Flags;
 use crate::colors;
@@ -33,10 +34,12 @@ use std::path::PathBuf;
 use text_lines::TextLines;
 use uuid::Uuid;
 
+mod html;
 mod json_types;
 mod merge;
 mod range_tree;
 
+use html::HtmlCoverageReporter;
 use json_types::*;
 
 pub struct CoverageCollector {
@@ -170,6 +173,90 @@ struct CoverageReport {
   output: Option<PathBuf>,
 }
 
+/// The number of covered and found items of a kind.
+#[derive(Clone, Copy, Default)]
+struct CoverageStats {
+  hit: usize,
+  found: usize,
+}
+
+impl CoverageStats {
+  fn add(&mut self, other: CoverageStats) {
+    self.hit += other.hit;
+    self.found += other.found;
+  }
+
+  /// The covered ratio, which is full when there is nothing to cover.
+  fn ratio(&self) -> f64 {
+    if self.found == 0 {
+      1.0
+    } else {
+      self.hit as f64 / self.found as f64
+    }
+  }
+}
+
+#[derive(Clone, Copy, Default)]
+struct CoverageSummary {
+  lines: CoverageStats,
+  functions: CoverageStats,
+  branches: CoverageStats,
+}
+
+impl CoverageSummary {
+  fn from_report(coverage_report: &CoverageReport) -> Self {
+    Self {
+      lines: CoverageStats {
+        hit: coverage_report
+          .found_lines
+          .iter()
+          .filter(|(_, count)| *count > 0)
+          .count(),
+        found: coverage_report.found_lines.len(),
+      },
+      functions: CoverageStats {
+        hit: coverage_report
+          .named_functions
+          .iter()
+          .filter(|f| f.execution_count > 0)
+          .count(),
+        found: coverage_report.named_functions.len(),
+      },
+      branches: CoverageStats {
+        hit: coverage_report.branches.iter().filter(|b| b.is_hit).count(),
+        found: coverage_report.branches.len(),
+      },
+    }
+  }
+
+  fn add(&mut self, other: &CoverageSummary) {
+    self.lines.add(other.lines);
+    self.functions.add(other.functions);
+    self.branches.add(other.branches);
+  }
+
+  /// Returns a line for each kind of coverage that is below its threshold.
+  fn check_thresholds(
+    &self,
+    thresholds: &CoverageThresholds,
+    name: &str,
+  ) -> Vec<String> {
+    [
+      ("lines", self.lines, thresholds.lines),
+      ("functions", self.functions, thresholds.functions),
+      ("branches", self.branches, thresholds.branches),
+    ]
+    .into_iter()
+    .filter_map(|(kind, stats, threshold)| {
+      let threshold = threshold?;
+      let percentage = stats.ratio() * 100.0;
+      (percentage < threshold as f64)
+        .then(|| format!("{kind} {percentage:.3}% < {threshold}% in {name}"))
+    })
+    .collect()
+  }
+}
+
 fn generate_coverage_report(
   script_coverage: &ScriptCoverage,
   script_source: String,
@@ -363,6 +450,7 @@ fn generate_coverage_report(
 enum CoverageReporterKind {
   Pretty,
   Lcov,
+  Html(PathBuf),
 }
 
 fn create_reporter(
@@ -371,6 +459,7 @@ fn create_reporter(
   match kind {
     CoverageReporterKind::Lcov => Box::new(LcovCoverageReporter::new()),
     CoverageReporterKind::Pretty => Box::new(PrettyCoverageReporter::new()),
+    CoverageReporterKind::Html(dir) => Box::new(HtmlCoverageReporter::new(dir)),
   }
 }
 
@@ -381,7 +470,7 @@ trait CoverageReporter {
     file_text: &str,
   ) -> Result<(), AnyError>;
 
-  fn done(&mut self);
+  fn done(&mut self) -> Result<(), AnyError>;
 }
 
 struct LcovCoverageReporter {}
@@ -483,7 +572,9 @@ impl CoverageReporter for LcovCoverageReporter {
     Ok(())
   }
 
-  fn done(&mut self) {}
+  fn done(&mut self) -> Result<(), AnyError> {
+    Ok(())
+  }
 }
 
 struct PrettyCoverageReporter {}
@@ -556,7 +647,9 @@ impl CoverageReporter for PrettyCoverageReporter {
     Ok(())
   }
 
-  fn done(&mut self) {}
+  fn done(&mut self) -> Result<(), AnyError> {
+    Ok(())
+  }
 }
 
 fn collect_coverages(
@@ -651,12 +744,29 @@ pub async fn cover_files(
 
   let reporter_kind = if coverage_flags.lcov {
     CoverageReporterKind::Lcov
+  } else if let Some(dir) = coverage_flags.html {
+    CoverageReporterKind::Html(dir)
   } else {
     CoverageReporterKind::Pretty
   };
 
   let mut reporter = create_reporter(reporter_kind);
 
+  let maybe_thresholds = if coverage_flags.check {
+    let thresholds =
+      cli_options.resolve_coverage_thresholds(coverage_flags.thresholds)?;
+    if thresholds == CoverageThresholds::default() {
+      return Err(generic_error(
+        "No coverage thresholds to check, use --lines, --functions or --branches, or the \"coverage\" field of the configuration file.",
+      ));
+    }
+    Some(thresholds)
+  } else {
+    None
+  };
+  let mut total_summary = CoverageSummary::default();
+  let mut threshold_failures = Vec::new();
+
   let out_mode = match coverage_flags.output {
     Some(ref path) => match File::create(path) {
       Ok(_) => Some(PathBuf::from(path)),
@@ -729,10 +839,29 @@ pub async fn cover_files(
 
     if !coverage_report.found_lines.is_empty() {
       reporter.report(&coverage_report, &original_source)?;
+
+      if let Some(thresholds) = &maybe_thresholds {
+        let summary = CoverageSummary::from_report(&coverage_report);
+        threshold_failures.extend(
+          summary.check_thresholds(thresholds, coverage_report.url.as_str()),
+        );
+        total_summary.add(&summary);
+      }
     }
   }
 
-  reporter.done();
+  reporter.done()?;
+
+  if let Some(thresholds) = &maybe_thresholds {
+    threshold_failures
+      .extend(total_summary.check_thresholds(thresholds, "total"));
+    if !threshold_failures.is_empty() {
+      return Err(generic_error(format!(
+        "Coverage is below the thresholds:\n  {}",
+        threshold_failures.join("\n  ")
+      )));
+    }
+  }
 
   Ok(())
 }
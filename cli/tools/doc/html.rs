@@ -0,0 +1,510 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::url::Url;
+use deno_doc as doc;
+
+use crate::util::path::relative_specifier;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 0; color: #222; }
+header { padding: 0.8em 2em; border-bottom: 1px solid #ddd; display: flex; gap: 2em; align-items: baseline; }
+header a.site { font-weight: bold; font-size: 1.2em; color: inherit; text-decoration: none; }
+main { margin: 2em; max-width: 60em; }
+h1 { font-size: 1.4em; }
+h2 { font-size: 1.2em; margin-top: 1.6em; }
+a { color: #0b5cad; }
+table { border-collapse: collapse; }
+th, td { padding: 0.2em 0.8em; text-align: left; vertical-align: top; }
+.symbols td { border-bottom: 1px solid #ddd; }
+.symbols td.name { font-family: monospace; white-space: nowrap; }
+.kind { color: #888; font-size: 0.9em; }
+.source { font-size: 0.9em; color: #888; }
+pre { background: #f6f8fa; padding: 0.8em; overflow-x: auto; }
+#search-results { list-style: none; padding: 0; margin: 0; position: absolute; background: #fff; border: 1px solid #ddd; }
+#search-results:empty { display: none; }
+#search-results li { padding: 0.2em 0.8em; font-family: monospace; }
+";
+
+const PAGE_END: &str = "</main>\n<script src=\"search_index.js\"></script>\n<script src=\"search.js\"></script>\n</body>\n</html>\n";
+
+const SEARCH_SCRIPT: &str = r#"(() => {
+  const input = document.getElementById("search");
+  const results = document.getElementById("search-results");
+  input.addEventListener("input", () => {
+    const query = input.value.trim().toLowerCase();
+    results.replaceChildren();
+    if (query === "") {
+      return;
+    }
+    for (const symbol of DOC_SEARCH_INDEX) {
+      if (!symbol.name.toLowerCase().includes(query)) {
+        continue;
+      }
+      const item = document.createElement("li");
+      const link = document.createElement("a");
+      link.href = symbol.page;
+      link.textContent = symbol.name;
+      const kind = document.createElement("span");
+      kind.className = "kind";
+      kind.textContent = ` ${symbol.kind}`;
+      item.append(link, kind);
+      results.append(item);
+    }
+  });
+})();
+"#;
+
+/// The order of the sections of the index page.
+const KIND_ORDER: [doc::DocNodeKind; 7] = [
+  doc::DocNodeKind::Namespace,
+  doc::DocNodeKind::Class,
+  doc::DocNodeKind::Enum,
+  doc::DocNodeKind::Variable,
+  doc::DocNodeKind::Function,
+  doc::DocNodeKind::Interface,
+  doc::DocNodeKind::TypeAlias,
+];
+
+struct Symbol<'a> {
+  /// The dot separated path of the symbol, like `Deno.readFile`.
+  name: String,
+  /// The path of the page of the symbol, relative to the output directory.
+  page: String,
+  /// The declarations of the symbol, which are several for overloaded
+  /// functions and merged declarations.
+  nodes: Vec<&'a doc::DocNode>,
+}
+
+/// Writes a static documentation site to a directory, with an `index.html`
+/// page that lists the exported symbols, a page for each symbol, including the
+/// ones in namespaces, and a search over the names of the symbols.
+pub struct HtmlDocGenerator<'a> {
+  name: String,
+  dir: PathBuf,
+  /// The URL of the index page, which the links to the sources are relative
+  /// to.
+  index_url: Url,
+  private: bool,
+  module_docs: Vec<&'a doc::DocNode>,
+  symbols: BTreeMap<String, Symbol<'a>>,
+}
+
+impl<'a> HtmlDocGenerator<'a> {
+  /// `dir` has to be absolute.
+  pub fn new(
+    name: String,
+    dir: PathBuf,
+    doc_nodes: &'a [doc::DocNode],
+    private: bool,
+  ) -> HtmlDocGenerator<'a> {
+    let index_url = Url::from_directory_path(&dir)
+      .unwrap()
+      .join("index.html")
+      .unwrap();
+    let mut generator = HtmlDocGenerator {
+      name,
+      dir,
+      index_url,
+      private,
+      module_docs: Vec::new(),
+      symbols: BTreeMap::new(),
+    };
+    generator.collect_symbols(doc_nodes, "");
+    generator
+  }
+
+  fn collect_symbols(&mut self, doc_nodes: &'a [doc::DocNode], prefix: &str) {
+    for node in doc_nodes {
+      match node.kind {
+        doc::DocNodeKind::Import => continue,
+        doc::DocNodeKind::ModuleDoc => {
+          if prefix.is_empty() {
+            self.module_docs.push(node);
+          }
+          continue;
+        }
+        _ => {}
+      }
+      let name = format!("{prefix}{}", node.name);
+      self
+        .symbols
+        .entry(name.clone())
+        .or_insert_with(|| Symbol {
+          page: get_page(&name),
+          name: name.clone(),
+          nodes: Vec::new(),
+        })
+        .nodes
+        .push(node);
+      if let Some(namespace_def) = &node.namespace_def {
+        self.collect_symbols(&namespace_def.elements, &format!("{name}."));
+      }
+    }
+  }
+
+  /// Writes the site and returns the path of its index page.
+  pub fn generate(&self) -> Result<PathBuf, AnyError> {
+    fs::create_dir_all(&self.dir)?;
+    for symbol in self.symbols.values() {
+      fs::write(self.dir.join(&symbol.page), self.format_symbol_page(symbol))?;
+    }
+    fs::write(
+      self.dir.join("search_index.js"),
+      format!("const DOC_SEARCH_INDEX = {};\n", self.format_search_index()),
+    )?;
+    fs::write(self.dir.join("search.js"), SEARCH_SCRIPT)?;
+    let index_path = self.dir.join("index.html");
+    fs::write(&index_path, self.format_index_page())?;
+    Ok(index_path)
+  }
+
+  fn format_search_index(&self) -> String {
+    let symbols = self
+      .symbols
+      .values()
+      .map(|symbol| {
+        json!({
+          "name": symbol.name,
+          "kind": get_kind_label(&symbol.nodes[0].kind),
+          "page": symbol.page,
+        })
+      })
+      .collect::<Vec<_>>();
+    serde_json::to_string(&symbols).unwrap()
+  }
+
+  fn format_index_page(&self) -> String {
+    let mut html = self.format_page_start(&self.name);
+    writeln!(html, "<h1>{}</h1>", escape_html(&self.name)).unwrap();
+    for node in &self.module_docs {
+      if let Some(text) = &node.js_doc.doc {
+        html.push_str(&format_doc_text(text));
+      }
+    }
+    for kind in &KIND_ORDER {
+      // only the top level symbols are listed, the others are listed on the
+      // pages of their namespaces
+      let symbols = self
+        .symbols
+        .values()
+        .filter(|symbol| {
+          !symbol.name.contains('.') && &symbol.nodes[0].kind == kind
+        })
+        .collect::<Vec<_>>();
+      if symbols.is_empty() {
+        continue;
+      }
+      writeln!(html, "<h2>{}</h2>", get_kind_heading(kind)).unwrap();
+      html.push_str(&format_symbols_table(&symbols));
+    }
+    html.push_str(PAGE_END);
+    html
+  }
+
+  fn format_symbol_page(&self, symbol: &Symbol) -> String {
+    let kind = get_kind_label(&symbol.nodes[0].kind);
+    let mut html = self.format_page_start(&symbol.name);
+    writeln!(
+      html,
+      "<h1><span class=\"kind\">{kind}</span> {}</h1>",
+      escape_html(&symbol.name)
+    )
+    .unwrap();
+    let namespace = match symbol.name.rfind('.') {
+      Some(index) => &symbol.name[..index],
+      None => "",
+    };
+    for node in &symbol.nodes {
+      let location =
+        format!("{}:{}", node.location.filename, node.location.line);
+      match self.get_source_href(&node.location) {
+        Some(href) => writeln!(
+          html,
+          "<p class=\"source\">Defined in <a href=\"{}\">{}</a></p>",
+          escape_html(&href),
+          escape_html(&location)
+        ),
+        None => writeln!(
+          html,
+          "<p class=\"source\">Defined in {}</p>",
+          escape_html(&location)
+        ),
+      }
+      .unwrap();
+      // the printer shows the signature with the documentation of the symbol
+      // and of its members
+      let text = format!(
+        "{}",
+        doc::DocPrinter::new(&[(*node).clone()], false, self.private)
+      );
+      writeln!(
+        html,
+        "<pre class=\"signature\">{}</pre>",
+        self.link_symbols(text.trim(), &symbol.name, namespace)
+      )
+      .unwrap();
+    }
+    if symbol.nodes[0].kind == doc::DocNodeKind::Namespace {
+      let prefix = format!("{}.", symbol.name);
+      let members = self
+        .symbols
+        .values()
+        .filter(|member| {
+          member
+            .name
+            .strip_prefix(&prefix)
+            .map(|name| !name.contains('.'))
+            .unwrap_or(false)
+        })
+        .collect::<Vec<_>>();
+      if !members.is_empty() {
+        html.push_str("<h2>Members</h2>\n");
+        html.push_str(&format_symbols_table(&members));
+      }
+    }
+    html.push_str(PAGE_END);
+    html
+  }
+
+  fn format_page_start(&self, title: &str) -> String {
+    let title = if title == self.name {
+      escape_html(title)
+    } else {
+      format!("{} - {}", escape_html(title), escape_html(&self.name))
+    };
+    format!(
+      "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<header><a class=\"site\" href=\"index.html\">{}</a><div><input id=\"search\" type=\"search\" placeholder=\"Search\" autocomplete=\"off\"><ul id=\"search-results\"></ul></div></header>\n<main>\n",
+      escape_html(&self.name)
+    )
+  }
+
+  /// Returns the link to the line of the source of a symbol. Remote modules
+  /// are linked to directly and local modules relative to the site.
+  fn get_source_href(&self, location: &doc::Location) -> Option<String> {
+    let url = Url::parse(&location.filename).ok()?;
+    let href = match url.scheme() {
+      "http" | "https" => url.to_string(),
+      "file" => relative_specifier(&self.index_url, &url)?,
+      _ => return None,
+    };
+    Some(format!("{href}#L{}", location.line))
+  }
+
+  /// Escapes the text and links the names of the other symbols in it to their
+  /// pages. Names are looked up in the namespace of the symbol first.
+  fn link_symbols(&self, text: &str, current: &str, namespace: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(is_identifier_char) {
+      html.push_str(&escape_html(&rest[..start]));
+      rest = &rest[start..];
+      let end = rest.find(|c| !is_identifier_char(c)).unwrap_or(rest.len());
+      let word = &rest[..end];
+      let maybe_symbol = if namespace.is_empty() {
+        None
+      } else {
+        self.symbols.get(&format!("{namespace}.{word}"))
+      }
+      .or_else(|| self.symbols.get(word))
+      .filter(|symbol| symbol.name != current);
+      match maybe_symbol {
+        Some(symbol) => write!(
+          html,
+          "<a href=\"{}\">{}</a>",
+          escape_html(&symbol.page),
+          escape_html(word)
+        )
+        .unwrap(),
+        None => html.push_str(&escape_html(word)),
+      }
+      rest = &rest[end..];
+    }
+    html.push_str(&escape_html(rest));
+    html
+  }
+}
+
+fn format_symbols_table(symbols: &[&Symbol]) -> String {
+  let mut html = String::from("<table class=\"symbols\">\n");
+  for symbol in symbols {
+    let summary = symbol
+      .nodes
+      .iter()
+      .find_map(|node| node.js_doc.doc.as_deref())
+      .map(get_summary)
+      .unwrap_or_default();
+    writeln!(
+      html,
+      "<tr><td class=\"name\"><a href=\"{}\">{}</a></td><td>{}</td></tr>",
+      escape_html(&symbol.page),
+      escape_html(&symbol.name),
+      escape_html(&summary)
+    )
+    .unwrap();
+  }
+  html.push_str("</table>\n");
+  html
+}
+
+/// Returns the name of the page file of a symbol, which is kept flat so that
+/// all pages can link to each other and to the scripts the same way.
+fn get_page(name: &str) -> String {
+  let sanitized = name
+    .chars()
+    .map(|c| {
+      if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '$') {
+        c
+      } else {
+        '_'
+      }
+    })
+    .collect::<String>();
+  // keep the pages of the symbols from overwriting the files of the site
+  if matches!(sanitized.as_str(), "index" | "search" | "search_index") {
+    format!("~{sanitized}.html")
+  } else {
+    format!("{sanitized}.html")
+  }
+}
+
+fn get_kind_label(kind: &doc::DocNodeKind) -> &'static str {
+  match kind {
+    doc::DocNodeKind::Class => "class",
+    doc::DocNodeKind::Enum => "enum",
+    doc::DocNodeKind::Function => "function",
+    doc::DocNodeKind::Interface => "interface",
+    doc::DocNodeKind::Namespace => "namespace",
+    doc::DocNodeKind::TypeAlias => "type",
+    doc::DocNodeKind::Variable => "variable",
+    doc::DocNodeKind::Import | doc::DocNodeKind::ModuleDoc => "",
+  }
+}
+
+fn get_kind_heading(kind: &doc::DocNodeKind) -> &'static str {
+  match kind {
+    doc::DocNodeKind::Class => "Classes",
+    doc::DocNodeKind::Enum => "Enums",
+    doc::DocNodeKind::Function => "Functions",
+    doc::DocNodeKind::Interface => "Interfaces",
+    doc::DocNodeKind::Namespace => "Namespaces",
+    doc::DocNodeKind::TypeAlias => "Type Aliases",
+    doc::DocNodeKind::Variable => "Variables",
+    doc::DocNodeKind::Import | doc::DocNodeKind::ModuleDoc => "",
+  }
+}
+
+/// Returns the first paragraph of a JSDoc text.
+fn get_summary(text: &str) -> String {
+  text
+    .trim()
+    .split("\n\n")
+    .next()
+    .unwrap_or_default()
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Formats the paragraphs and the fenced code blocks of a JSDoc text, and
+/// keeps the rest of its markdown as it is.
+fn format_doc_text(text: &str) -> String {
+  let mut html = String::new();
+  let mut paragraph = Vec::new();
+  let mut code_block: Option<Vec<&str>> = None;
+  for line in text.lines() {
+    if let Some(code_lines) = &mut code_block {
+      if line.trim_start().starts_with("```") {
+        writeln!(
+          html,
+          "<pre><code>{}</code></pre>",
+          escape_html(&code_lines.join("\n"))
+        )
+        .unwrap();
+        code_block = None;
+      } else {
+        code_lines.push(line);
+      }
+    } else if line.trim_start().starts_with("```") {
+      flush_paragraph(&mut html, &mut paragraph);
+      code_block = Some(Vec::new());
+    } else if line.trim().is_empty() {
+      flush_paragraph(&mut html, &mut paragraph);
+    } else {
+      paragraph.push(line.trim());
+    }
+  }
+  if let Some(code_lines) = code_block {
+    writeln!(
+      html,
+      "<pre><code>{}</code></pre>",
+      escape_html(&code_lines.join("\n"))
+    )
+    .unwrap();
+  }
+  flush_paragraph(&mut html, &mut paragraph);
+  html
+}
+
+fn flush_paragraph(html: &mut String, paragraph: &mut Vec<&str>) {
+  if !paragraph.is_empty() {
+    writeln!(html, "<p>{}</p>", escape_html(&paragraph.join(" "))).unwrap();
+    paragraph.clear();
+  }
+}
+
+fn is_identifier_char(c: char) -> bool {
+  c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn escape_html(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&#39;"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_get_page() {
+    assert_eq!(get_page("Deno.readFile"), "Deno.readFile.html");
+    assert_eq!(get_page("$"), "$.html");
+    assert_eq!(get_page("index"), "~index.html");
+    assert_eq!(get_page("a/b"), "a_b.html");
+  }
+
+  #[test]
+  fn test_get_summary() {
+    assert_eq!(
+      get_summary("Reads a file.\nWith more.\n\nDetails."),
+      "Reads a file. With more."
+    );
+    assert_eq!(get_summary(""), "");
+  }
+
+  #[test]
+  fn test_format_doc_text() {
+    assert_eq!(
+      format_doc_text("Adds <b>.\n\n```ts\nadd(1, 2);\n```\nDone."),
+      "<p>Adds &lt;b&gt;.</p>\n<pre><code>add(1, 2);</code></pre>\n<p>Done.</p>\n"
+    );
+  }
+}
@@ -0,0 +1,253 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashSet;
+
+use deno_ast::swc::ast::Accessibility;
+use deno_ast::swc::ast::MethodKind;
+use deno_core::url::Url;
+use deno_doc as doc;
+use doc::function::FunctionDef;
+use doc::js_doc::JsDoc;
+use doc::js_doc::JsDocTag;
+use doc::params::ParamDef;
+use doc::ts_type::TsTypeDef;
+
+use crate::args::FilesConfig;
+
+/// A problem with the documentation of an exported symbol.
+#[derive(Debug)]
+pub struct DocDiagnostic {
+  pub location: doc::Location,
+  pub message: String,
+}
+
+/// Checks that the exported symbols of the files of the public API and the
+/// public members of their classes are documented, and that the documentation
+/// of their functions matches their signatures.
+pub struct DocLinter<'a> {
+  files: &'a FilesConfig,
+  diagnostics: Vec<DocDiagnostic>,
+}
+
+impl<'a> DocLinter<'a> {
+  pub fn new(files: &'a FilesConfig) -> Self {
+    Self {
+      files,
+      diagnostics: Vec::new(),
+    }
+  }
+
+  pub fn lint(mut self, doc_nodes: &[doc::DocNode]) -> Vec<DocDiagnostic> {
+    self.lint_nodes(doc_nodes, "");
+    self.diagnostics.sort_by(|a, b| {
+      (&a.location.filename, a.location.line, a.location.col).cmp(&(
+        &b.location.filename,
+        b.location.line,
+        b.location.col,
+      ))
+    });
+    self.diagnostics
+  }
+
+  fn lint_nodes(&mut self, doc_nodes: &[doc::DocNode], prefix: &str) {
+    // several declarations of a symbol, like the overloads of a function, only
+    // need to be documented once
+    let documented = doc_nodes
+      .iter()
+      .filter(|node| !is_empty_js_doc(&node.js_doc))
+      .map(|node| node.name.as_str())
+      .collect::<HashSet<_>>();
+    let mut reported = HashSet::new();
+
+    for node in doc_nodes {
+      if matches!(
+        node.kind,
+        doc::DocNodeKind::Import | doc::DocNodeKind::ModuleDoc
+      ) || !self.is_public_api(&node.location)
+      {
+        continue;
+      }
+      let name = format!("{prefix}{}", node.name);
+      if !documented.contains(node.name.as_str())
+        && reported.insert(node.name.as_str())
+      {
+        self.report(
+          &node.location,
+          format!("Missing JSDoc comment for \"{name}\"."),
+        );
+      }
+      if let Some(function_def) = &node.function_def {
+        self.lint_function(&node.location, &name, &node.js_doc, function_def);
+      }
+      if let Some(class_def) = &node.class_def {
+        // the overloads of a method, and the getter and setter of an
+        // accessor, only need to be documented once
+        let documented_members = class_def
+          .methods
+          .iter()
+          .filter(|method| !is_empty_js_doc(&method.js_doc))
+          .map(|method| method.name.as_str())
+          .collect::<HashSet<_>>();
+        let mut reported_members = HashSet::new();
+        for property in &class_def.properties {
+          if is_public_member(property.accessibility, &property.name)
+            && is_empty_js_doc(&property.js_doc)
+          {
+            self.report(
+              &property.location,
+              format!(
+                "Missing JSDoc comment for \"{name}.{}\".",
+                property.name
+              ),
+            );
+          }
+        }
+        for method in &class_def.methods {
+          if !is_public_member(method.accessibility, &method.name) {
+            continue;
+          }
+          let method_name = format!("{name}.{}", method.name);
+          if !documented_members.contains(method.name.as_str())
+            && reported_members.insert(method.name.as_str())
+          {
+            self.report(
+              &method.location,
+              format!("Missing JSDoc comment for \"{method_name}\"."),
+            );
+          }
+          if method.kind == MethodKind::Setter {
+            self.lint_params(
+              &method.location,
+              &method_name,
+              &method.js_doc,
+              &method.function_def.params,
+            );
+          } else {
+            self.lint_function(
+              &method.location,
+              &method_name,
+              &method.js_doc,
+              &method.function_def,
+            );
+          }
+        }
+      }
+      if let Some(namespace_def) = &node.namespace_def {
+        self.lint_nodes(&namespace_def.elements, &format!("{name}."));
+      }
+    }
+  }
+
+  fn lint_function(
+    &mut self,
+    location: &doc::Location,
+    name: &str,
+    js_doc: &JsDoc,
+    function_def: &FunctionDef,
+  ) {
+    self.lint_params(location, name, js_doc, &function_def.params);
+    match &function_def.return_type {
+      None => self.report(
+        location,
+        format!("Missing an explicit return type for \"{name}\"."),
+      ),
+      // a missing comment is reported on its own
+      Some(return_type)
+        if !is_void(return_type)
+          && !is_empty_js_doc(js_doc)
+          && !js_doc
+            .tags
+            .iter()
+            .any(|tag| matches!(tag, JsDocTag::Return { .. })) =>
+      {
+        self
+          .report(location, format!("Missing JSDoc @returns for \"{name}\"."));
+      }
+      Some(_) => {}
+    }
+  }
+
+  fn lint_params(
+    &mut self,
+    location: &doc::Location,
+    name: &str,
+    js_doc: &JsDoc,
+    params: &[ParamDef],
+  ) {
+    // the names of destructured parameters are up to the documentation
+    let Some(param_names) = params
+      .iter()
+      .map(get_param_name)
+      .collect::<Option<HashSet<_>>>()
+    else {
+      return;
+    };
+    for tag in &js_doc.tags {
+      if let JsDocTag::Param { name: tag_name, .. } = tag {
+        // `@param options.foo` documents a property of `options`
+        let param_name = tag_name.split('.').next().unwrap_or(tag_name);
+        if !param_names.contains(param_name) {
+          self.report(
+            location,
+            format!(
+              "JSDoc @param \"{tag_name}\" of \"{name}\" does not match a parameter."
+            ),
+          );
+        }
+      }
+    }
+  }
+
+  fn is_public_api(&self, location: &doc::Location) -> bool {
+    Url::parse(&location.filename)
+      .map(|url| self.files.matches_specifier(&url))
+      .unwrap_or(false)
+  }
+
+  fn report(&mut self, location: &doc::Location, message: String) {
+    self.diagnostics.push(DocDiagnostic {
+      location: location.clone(),
+      message,
+    });
+  }
+}
+
+fn is_empty_js_doc(js_doc: &JsDoc) -> bool {
+  js_doc
+    .doc
+    .as_deref()
+    .map(str::trim)
+    .unwrap_or_default()
+    .is_empty()
+    && js_doc.tags.is_empty()
+}
+
+fn is_public_member(accessibility: Option<Accessibility>, name: &str) -> bool {
+  accessibility != Some(Accessibility::Private) && !name.starts_with('#')
+}
+
+/// Returns whether a return type has no value to document, like `void`,
+/// `never` and `Promise<void>`.
+fn is_void(ts_type: &TsTypeDef) -> bool {
+  if let Some(keyword) = &ts_type.keyword {
+    return matches!(keyword.as_str(), "void" | "never" | "undefined");
+  }
+  match &ts_type.type_ref {
+    Some(type_ref) if type_ref.type_name == "Promise" => type_ref
+      .type_params
+      .as_deref()
+      .map(|params| params.len() == 1 && is_void(&params[0]))
+      .unwrap_or(false),
+    _ => false,
+  }
+}
+
+/// Returns the name of a parameter, or `None` for destructured parameters.
+fn get_param_name(param: &ParamDef) -> Option<&str> {
+  match param {
+    ParamDef::Identifier { name, .. } => Some(name),
+    ParamDef::Assign { left, .. } => get_param_name(left),
+    ParamDef::Rest { arg, .. } => get_param_name(arg),
+    ParamDef::Array { .. } | ParamDef::Object { .. } => None,
+  }
+}
@@ -15,10 +15,14 @@ use deno_core::anyhow::bail;
 use deno_core::error::AnyError;
 use deno_core::resolve_path;
 use deno_core::resolve_url_or_path;
+use deno_core::url::Url;
 use deno_doc as doc;
 use deno_graph::ModuleSpecifier;
 use std::path::PathBuf;
 
+mod html;
+mod lint;
+
 pub async fn print_docs(
   flags: Flags,
   doc_flags: DocFlags,
@@ -103,8 +107,44 @@ pub async fn print_docs(
     }
   };
 
-  if doc_flags.json {
+  if doc_flags.lint {
+    let lint_files = cli_options.resolve_doc_lint_files()?;
+    let diagnostics = lint::DocLinter::new(&lint_files).lint(&doc_nodes);
+    for diagnostic in &diagnostics {
+      eprintln!(
+        "{}: {}\n    at {}:{}:{}\n",
+        colors::red_bold("error"),
+        diagnostic.message,
+        colors::cyan(&diagnostic.location.filename),
+        colors::yellow(diagnostic.location.line.to_string()),
+        colors::yellow((diagnostic.location.col + 1).to_string())
+      );
+    }
+    match diagnostics.len() {
+      0 => Ok(()),
+      1 => bail!("Found 1 documentation problem"),
+      len => bail!("Found {} documentation problems", len),
+    }
+  } else if doc_flags.json {
     write_json_to_stdout(&doc_nodes)
+  } else if let Some(html_flag) = doc_flags.html {
+    let name = html_flag
+      .name
+      .unwrap_or_else(|| match &doc_flags.source_file {
+        DocSourceFileFlag::Builtin => "Deno".to_string(),
+        DocSourceFileFlag::Path(_) => "Documentation".to_string(),
+      });
+    let dir = cli_options.initial_cwd().join(html_flag.output);
+    let generator =
+      html::HtmlDocGenerator::new(name, dir, &doc_nodes, doc_flags.private);
+    let index_path = generator.generate()?;
+    println!(
+      "HTML documentation has been generated at {}",
+      Url::from_file_path(&index_path)
+        .map(|url| url.to_string())
+        .unwrap_or_else(|_| index_path.display().to_string())
+    );
+    Ok(())
   } else {
     doc_nodes.retain(|doc_node| doc_node.kind != doc::DocNodeKind::Import);
     let details = if let Some(filter) = doc_flags.filter {
@@ -0,0 +1,587 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The formatting of code that is embedded in other code: the code blocks of
+//! markdown files and of JSDoc comments, and the template literals that are
+//! tagged with the name of a language, like json`...` or sql`...`. The
+//! languages are enabled with `fmt.embeddedLanguages` in the configuration
+//! file.
+
+use super::get_resolved_json_config;
+use super::get_resolved_typescript_config;
+use crate::args::EmbeddedLanguage;
+use crate::args::FmtOptionsConfig;
+
+use deno_ast::swc::ast::Expr;
+use deno_ast::swc::ast::TaggedTpl;
+use deno_ast::swc::common::comments::CommentKind;
+use deno_ast::swc::visit::noop_visit_type;
+use deno_ast::swc::visit::Visit;
+use deno_ast::swc::visit::VisitWith;
+use deno_ast::MediaType;
+use deno_ast::SourcePos;
+use deno_ast::SourceRangedForSpanned;
+use deno_ast::SourceTextInfo;
+use deno_ast::StartSourcePos;
+use deno_core::anyhow::anyhow;
+use deno_core::error::AnyError;
+use std::borrow::Cow;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Returns the language of a code block or a template literal from its tag,
+/// with the file extension to format it as.
+fn resolve_tag(tag: &str) -> Option<(EmbeddedLanguage, &'static str)> {
+  let resolved = match tag.to_lowercase().as_str() {
+    "ts" | "typescript" => (EmbeddedLanguage::Typescript, "ts"),
+    "tsx" => (EmbeddedLanguage::Typescript, "tsx"),
+    "cts" => (EmbeddedLanguage::Typescript, "cts"),
+    "mts" => (EmbeddedLanguage::Typescript, "mts"),
+    "js" | "javascript" => (EmbeddedLanguage::Typescript, "js"),
+    "jsx" => (EmbeddedLanguage::Typescript, "jsx"),
+    "cjs" => (EmbeddedLanguage::Typescript, "cjs"),
+    "mjs" => (EmbeddedLanguage::Typescript, "mjs"),
+    "json" => (EmbeddedLanguage::Json, "json"),
+    "jsonc" => (EmbeddedLanguage::Json, "jsonc"),
+    "css" => (EmbeddedLanguage::Css, "css"),
+    "html" => (EmbeddedLanguage::Html, "html"),
+    "sql" => (EmbeddedLanguage::Sql, "sql"),
+    _ => return None,
+  };
+  Some(resolved)
+}
+
+fn is_tag_enabled(tag: &str, fmt_options: &FmtOptionsConfig) -> bool {
+  match resolve_tag(tag) {
+    Some((language, _)) => fmt_options.is_embedded_language_enabled(language),
+    None => false,
+  }
+}
+
+/// Formats the code of a code block or a template literal with the tag `tag`.
+/// Returns `None` when the code is already formatted, or when its language is
+/// unknown or not enabled.
+pub fn format_embedded_code(
+  tag: &str,
+  text: &str,
+  line_width: u32,
+  fmt_options: &FmtOptionsConfig,
+) -> Result<Option<String>, AnyError> {
+  let Some((language, extension)) = resolve_tag(tag) else {
+    return Ok(None);
+  };
+  if !fmt_options.is_embedded_language_enabled(language) {
+    return Ok(None);
+  }
+  format_code(language, extension, text, line_width, fmt_options)
+}
+
+fn format_code(
+  language: EmbeddedLanguage,
+  extension: &str,
+  text: &str,
+  line_width: u32,
+  fmt_options: &FmtOptionsConfig,
+) -> Result<Option<String>, AnyError> {
+  let formatted = match language {
+    EmbeddedLanguage::Json => {
+      let mut json_config = get_resolved_json_config(fmt_options);
+      json_config.line_width = line_width;
+      return dprint_plugin_json::format_text(text, &json_config);
+    }
+    EmbeddedLanguage::Typescript => {
+      // It's important to tell dprint proper file extension, otherwise
+      // it might parse the file twice.
+      let fake_filename = PathBuf::from(format!("deno_fmt_stdin.{extension}"));
+      let mut codeblock_config = get_resolved_typescript_config(fmt_options);
+      codeblock_config.line_width = line_width;
+      return dprint_plugin_typescript::format_text(
+        &fake_filename,
+        text,
+        &codeblock_config,
+      );
+    }
+    EmbeddedLanguage::Css => {
+      let mut options = malva::config::FormatOptions::default();
+      options.layout.print_width = line_width as usize;
+      options.layout.use_tabs = fmt_options.use_tabs.unwrap_or(false);
+      options.layout.indent_width =
+        fmt_options.indent_width.unwrap_or(2) as usize;
+      malva::format_text(text, malva::Syntax::Css, &options)
+        .map_err(|err| anyhow!("{:?}", err))?
+    }
+    EmbeddedLanguage::Html => {
+      let mut options = markup_fmt::config::FormatOptions::default();
+      options.layout.print_width = line_width as usize;
+      options.layout.use_tabs = fmt_options.use_tabs.unwrap_or(false);
+      options.layout.indent_width =
+        fmt_options.indent_width.unwrap_or(2) as usize;
+      markup_fmt::format_text(
+        text,
+        markup_fmt::Language::Html,
+        &options,
+        |path: &Path, code: &str, print_width: usize| {
+          // The scripts and the styles of the document.
+          let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
+          let Some((language, extension)) = resolve_tag(extension) else {
+            return Ok::<_, AnyError>(Cow::Borrowed(code));
+          };
+          let formatted = format_code(
+            language,
+            extension,
+            code,
+            print_width as u32,
+            fmt_options,
+          )?;
+          Ok(formatted.map(Cow::Owned).unwrap_or(Cow::Borrowed(code)))
+        },
+      )
+      .map_err(|err| anyhow!("{:?}", err))?
+    }
+    EmbeddedLanguage::Sql => {
+      let options = sqlformat::FormatOptions {
+        indent: if fmt_options.use_tabs.unwrap_or(false) {
+          sqlformat::Indent::Tabs
+        } else {
+          sqlformat::Indent::Spaces(fmt_options.indent_width.unwrap_or(2))
+        },
+        ..Default::default()
+      };
+      let mut formatted =
+        sqlformat::format(text, &sqlformat::QueryParams::None, options);
+      formatted.push('\n');
+      formatted
+    }
+  };
+  if formatted == text {
+    Ok(None)
+  } else {
+    Ok(Some(formatted))
+  }
+}
+
+/// Formats the code that is embedded in a JS or TS module, which must be
+/// formatted already: the code blocks of its JSDoc comments and its tagged
+/// template literals. Code that fails to format, like an incomplete example,
+/// is left as is.
+pub fn format_embedded_code_in_module(
+  specifier: &str,
+  media_type: MediaType,
+  file_text: &str,
+  fmt_options: &FmtOptionsConfig,
+) -> Option<String> {
+  if matches!(&fmt_options.embedded_languages, Some(l) if l.is_empty()) {
+    return None;
+  }
+  let parsed_source = deno_ast::parse_module(deno_ast::ParseParams {
+    specifier: specifier.to_string(),
+    text_info: SourceTextInfo::from_string(file_text.to_string()),
+    media_type,
+    capture_tokens: false,
+    scope_analysis: false,
+    maybe_syntax: None,
+  })
+  .ok()?;
+  let text_info = parsed_source.text_info();
+  let text = text_info.text_str();
+  let start_pos = text_info.range().start;
+  let mut edits = vec![];
+
+  for comment in parsed_source.comments().get_vec() {
+    if comment.kind != CommentKind::Block || !comment.text.starts_with('*') {
+      continue;
+    }
+    let range = byte_index(start_pos, comment.start())
+      ..byte_index(start_pos, comment.end());
+    if let Some(new_text) = format_jsdoc(&text[range.clone()], fmt_options) {
+      edits.push((range, new_text));
+    }
+  }
+
+  let mut collector = TaggedTemplateCollector {
+    start_pos,
+    templates: vec![],
+  };
+  parsed_source.program_ref().visit_with(&mut collector);
+  for template in collector.templates {
+    if !is_tag_enabled(&template.tag, fmt_options) {
+      continue;
+    }
+    if let Some(new_text) = format_template(text, &template, fmt_options) {
+      edits.push((template.range, new_text));
+    }
+  }
+
+  if edits.is_empty() {
+    return None;
+  }
+  edits.sort_by_key(|(range, _)| range.start);
+  let mut new_text = String::with_capacity(text.len());
+  let mut last_end = 0;
+  for (range, edit_text) in edits {
+    new_text.push_str(&text[last_end..range.start]);
+    new_text.push_str(&edit_text);
+    last_end = range.end;
+  }
+  new_text.push_str(&text[last_end..]);
+  Some(new_text)
+}
+
+fn byte_index(start_pos: StartSourcePos, pos: SourcePos) -> usize {
+  pos.as_byte_index(start_pos)
+}
+
+fn get_line_width(fmt_options: &FmtOptionsConfig) -> u32 {
+  fmt_options.line_width.unwrap_or(80)
+}
+
+fn get_indent(fmt_options: &FmtOptionsConfig) -> String {
+  if fmt_options.use_tabs.unwrap_or(false) {
+    "\t".to_string()
+  } else {
+    " ".repeat(fmt_options.indent_width.unwrap_or(2) as usize)
+  }
+}
+
+/// Splits a line of a JSDoc comment into its ` * ` prefix and its text.
+fn split_jsdoc_line(line: &str) -> Option<(&str, &str)> {
+  let trimmed = line.trim_start();
+  if !trimmed.starts_with('*') || trimmed.starts_with("*/") {
+    return None;
+  }
+  let mut prefix_len = line.len() - trimmed.len() + 1;
+  if line[prefix_len..].starts_with(' ') {
+    prefix_len += 1;
+  }
+  Some(line.split_at(prefix_len))
+}
+
+/// Formats the fenced code blocks of a JSDoc comment. Returns `None` when none
+/// of them changed.
+fn format_jsdoc(
+  comment_text: &str,
+  fmt_options: &FmtOptionsConfig,
+) -> Option<String> {
+  if !comment_text.contains("```") || comment_text.contains('\r') {
+    return None;
+  }
+  let lines = comment_text.split('\n').collect::<Vec<_>>();
+  let mut new_lines = Vec::with_capacity(lines.len());
+  let mut changed = false;
+  let mut i = 0;
+  while i < lines.len() {
+    let line = lines[i];
+    new_lines.push(line.to_string());
+    i += 1;
+    // The first line is the one of `/**`.
+    if i == 1 {
+      continue;
+    }
+    let Some((prefix, tag)) = split_jsdoc_line(line).and_then(|(prefix, rest)| {
+      Some((prefix, rest.trim().strip_prefix("```")?))
+    }) else {
+      continue;
+    };
+    let Some(end) = (i..lines.len()).find(|j| {
+      matches!(split_jsdoc_line(lines[*j]), Some((_, r)) if r.trim() == "```")
+    }) else {
+      break;
+    };
+    let body = lines[i..end]
+      .iter()
+      .map(|line| split_jsdoc_line(line).map(|(_, rest)| rest))
+      .collect::<Option<Vec<_>>>();
+    let prefix = format!("{} ", prefix.trim_end());
+    let formatted = body.and_then(|body| {
+      let line_width =
+        get_line_width(fmt_options).saturating_sub(prefix.len() as u32);
+      format_embedded_code(
+        tag.trim(),
+        &body.join("\n"),
+        line_width,
+        fmt_options,
+      )
+      .ok()
+      .flatten()
+    });
+    match formatted {
+      Some(formatted) => {
+        for formatted_line in formatted.trim_end_matches('\n').split('\n') {
+          if formatted_line.is_empty() {
+            new_lines.push(prefix.trim_end().to_string());
+          } else {
+            new_lines.push(format!("{prefix}{formatted_line}"));
+          }
+        }
+        changed = true;
+      }
+      None => {
+        new_lines.extend(lines[i..end].iter().map(|line| line.to_string()));
+      }
+    }
+    new_lines.push(lines[end].to_string());
+    i = end + 1;
+  }
+  if changed {
+    Some(new_lines.join("\n"))
+  } else {
+    None
+  }
+}
+
+/// Returns the text that stands for the substitution at `index` of a
+/// template while its code is formatted.
+fn substitution_placeholder(index: usize) -> String {
+  format!("__deno_fmt_substitution_{index}__")
+}
+
+/// Formats the text of a tagged template literal. Its substitutions are
+/// replaced with placeholders while the code is formatted. A template that
+/// starts with a line break gets its code on lines of their own, which are
+/// indented one level more than the line of the template.
+fn format_template(
+  file_text: &str,
+  template: &TaggedTemplate,
+  fmt_options: &FmtOptionsConfig,
+) -> Option<String> {
+  let range = &template.range;
+  let raw = &file_text[range.clone()];
+  let mut code = String::with_capacity(raw.len());
+  let mut last_end = range.start;
+  for (index, substitution) in template.substitutions.iter().enumerate() {
+    code.push_str(&file_text[last_end..substitution.start]);
+    code.push_str(&substitution_placeholder(index));
+    last_end = substitution.end;
+  }
+  code.push_str(&file_text[last_end..range.end]);
+  // The escapes of the template would change the meaning of the code.
+  if code.contains('\\') || code.contains('\r') {
+    return None;
+  }
+  let line_start = file_text[..range.start]
+    .rfind('\n')
+    .map(|index| index + 1)
+    .unwrap_or(0);
+  let line = &file_text[line_start..range.start];
+  let base_indent = &line[..line.len() - line.trim_start().len()];
+  let inner_indent = format!("{base_indent}{}", get_indent(fmt_options));
+  let line_width =
+    get_line_width(fmt_options).saturating_sub(inner_indent.len() as u32);
+  let formatted =
+    match format_embedded_code(&template.tag, &code, line_width, fmt_options) {
+      Ok(Some(formatted)) => formatted,
+      Ok(None) => code.clone(),
+      Err(_) => return None,
+    };
+  let formatted_code = formatted.trim_matches('\n');
+  let mut new_text = if !raw.starts_with('\n') && !formatted_code.contains('\n')
+  {
+    formatted_code.to_string()
+  } else {
+    let mut new_text = String::from("\n");
+    for code_line in formatted_code.split('\n') {
+      if !code_line.is_empty() {
+        new_text.push_str(&inner_indent);
+        new_text.push_str(code_line);
+      }
+      new_text.push('\n');
+    }
+    new_text.push_str(base_indent);
+    new_text
+  };
+  if new_text.contains('`')
+    || new_text.contains("${")
+    || new_text.contains('\\')
+  {
+    return None;
+  }
+  for (index, substitution) in template.substitutions.iter().enumerate() {
+    // The formatter must keep every placeholder as is, and only once.
+    let placeholder = substitution_placeholder(index);
+    if new_text.matches(&placeholder).count() != 1 {
+      return None;
+    }
+    new_text =
+      new_text.replacen(&placeholder, &file_text[substitution.clone()], 1);
+  }
+  if new_text == raw {
+    return None;
+  }
+  Some(new_text)
+}
+
+/// A tagged template literal whose tag is an identifier.
+struct TaggedTemplate {
+  tag: String,
+  /// The range of the text of the template, without the backticks.
+  range: Range<usize>,
+  /// The ranges of the `${...}` substitutions of the template.
+  substitutions: Vec<Range<usize>>,
+}
+
+/// Collects the tagged template literals whose tag is an identifier.
+struct TaggedTemplateCollector {
+  start_pos: StartSourcePos,
+  templates: Vec<TaggedTemplate>,
+}
+
+impl Visit for TaggedTemplateCollector {
+  noop_visit_type!();
+
+  fn visit_tagged_tpl(&mut self, node: &TaggedTpl) {
+    if let Expr::Ident(ident) = &*node.tag {
+      // Without the backticks.
+      let start = byte_index(self.start_pos, node.tpl.start()) + 1;
+      let end = byte_index(self.start_pos, node.tpl.end()) - 1;
+      let substitutions = node
+        .tpl
+        .quasis
+        .windows(2)
+        .map(|quasis| {
+          byte_index(self.start_pos, quasis[0].end())
+            ..byte_index(self.start_pos, quasis[1].start())
+        })
+        .collect();
+      self.templates.push(TaggedTemplate {
+        tag: ident.sym.to_string(),
+        range: start..end,
+        substitutions,
+      });
+    }
+    node.visit_children_with(self);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn format_module(text: &str, fmt_options: &FmtOptionsConfig) -> String {
+    format_embedded_code_in_module(
+      "file:///mod.ts",
+      MediaType::TypeScript,
+      text,
+      fmt_options,
+    )
+    .unwrap_or_else(|| text.to_string())
+  }
+
+  fn all_languages() -> FmtOptionsConfig {
+    FmtOptionsConfig {
+      embedded_languages: Some(vec![
+        EmbeddedLanguage::Typescript,
+        EmbeddedLanguage::Json,
+        EmbeddedLanguage::Css,
+        EmbeddedLanguage::Html,
+        EmbeddedLanguage::Sql,
+      ]),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_format_jsdoc_code_blocks() {
+    let text = r#"/**
+ * Adds one.
+ *
+ * ```ts
+ * const b   =  addOne( 1 )
+ * ```
+ *
+ * ```sh
+ * deno   run mod.ts
+ * ```
+ */
+export function addOne(a: number) {
+  return a + 1;
+}
+"#;
+    assert_eq!(
+      format_module(text, &all_languages()),
+      text.replace("const b   =  addOne( 1 )", "const b = addOne(1);")
+    );
+  }
+
+  #[test]
+  fn test_format_tagged_templates() {
+    let text = r#"function data() {
+  const a = json`
+{"a":1,
+"b":2}
+`;
+  const b = json`[1,2]`;
+  const d = other`{"a":1}`;
+}
+"#;
+    let expected = r#"function data() {
+  const a = json`
+    {
+      "a": 1,
+      "b": 2
+    }
+  `;
+  const b = json`[1, 2]`;
+  const d = other`{"a":1}`;
+}
+"#;
+    assert_eq!(format_module(text, &all_languages()), expected);
+  }
+
+  #[test]
+  fn test_format_tagged_templates_with_substitutions() {
+    let text = r#"function code(id: number) {
+  const a = ts`const b=${id}`;
+  const c = sql`SELECT * FROM users WHERE id = ${id}`;
+  const d = sql`
+SELECT * FROM users WHERE id = ${id} AND ${"name"} = 'a'
+`;
+}
+"#;
+    let expected = r#"function code(id: number) {
+  const a = ts`const b = ${id};`;
+  const c = sql`
+    SELECT
+      *
+    FROM
+      users
+    WHERE
+      id = ${id}
+  `;
+  const d = sql`
+    SELECT
+      *
+    FROM
+      users
+    WHERE
+      id = ${id}
+      AND ${"name"} = 'a'
+  `;
+}
+"#;
+    assert_eq!(format_module(text, &all_languages()), expected);
+  }
+
+  #[test]
+  fn test_format_embedded_code_disabled() {
+    let text =
+      "/**\n * ```ts\n * const a=1\n * ```\n */\nconst b = json`[1,2]`;\n";
+    let fmt_options = FmtOptionsConfig {
+      embedded_languages: Some(vec![EmbeddedLanguage::Json]),
+      ..Default::default()
+    };
+    assert_eq!(
+      format_module(text, &fmt_options),
+      text.replace("[1,2]", "[1, 2]")
+    );
+    let fmt_options = FmtOptionsConfig {
+      embedded_languages: Some(vec![]),
+      ..Default::default()
+    };
+    assert_eq!(format_module(text, &fmt_options), text);
+    // The embedded languages are opt-in.
+    assert_eq!(format_module(text, &Default::default()), text);
+  }
+}
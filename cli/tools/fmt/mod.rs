@@ -8,6 +8,7 @@
 //! the same functions as ops available in JS runtime.
 
 use crate::args::CliOptions;
+use crate::args::EmbeddedLanguage;
 use crate::args::FilesConfig;
 use crate::args::FmtOptions;
 use crate::args::FmtOptionsConfig;
@@ -20,6 +21,7 @@ use crate::util::file_watcher::ResolutionResult;
 use crate::util::fs::FileCollector;
 use crate::util::path::get_extension;
 use crate::util::text_encoding;
+use deno_ast::MediaType;
 use deno_ast::ParsedSource;
 use deno_core::anyhow::anyhow;
 use deno_core::anyhow::bail;
@@ -31,6 +33,7 @@ use deno_core::parking_lot::Mutex;
 use log::debug;
 use log::info;
 use log::warn;
+use std::borrow::Cow;
 use std::fs;
 use std::io::stdin;
 use std::io::stdout;
@@ -44,9 +47,14 @@ use std::sync::Arc;
 
 use crate::cache::IncrementalCache;
 
+mod embedded;
+mod plugins;
+
+pub use plugins::FmtPluginHost;
+
 /// Format JavaScript/TypeScript files.
 pub async fn format(
-  cli_options: CliOptions,
+  cli_options: Arc<CliOptions>,
   fmt_options: FmtOptions,
 ) -> Result<(), AnyError> {
   if fmt_options.is_stdin {
@@ -63,29 +71,43 @@ pub async fn format(
   let files = fmt_options.files;
   let check = fmt_options.check;
   let fmt_config_options = fmt_options.options;
+  let fmt_plugins = fmt_options.plugins;
+
+  let factory = CliFactory::from_cli_options(cli_options);
+  let cli_options = factory.cli_options();
+  let caches = factory.caches()?;
+  let maybe_plugin_host = if fmt_plugins.is_empty() {
+    None
+  } else {
+    Some(Arc::new(
+      FmtPluginHost::start(&factory, fmt_plugins.clone(), &fmt_config_options)
+        .await?,
+    ))
+  };
 
   let resolver = |changed: Option<Vec<PathBuf>>| {
     let files_changed = changed.is_some();
 
-    let result = collect_fmt_files(&files).map(|files| {
-      let refmt_files = if let Some(paths) = changed {
-        if check {
-          files
-            .iter()
-            .any(|path| paths.contains(path))
-            .then_some(files)
-            .unwrap_or_else(|| [].to_vec())
+    let result =
+      collect_fmt_files(&files, maybe_plugin_host.as_deref()).map(|files| {
+        let refmt_files = if let Some(paths) = changed {
+          if check {
+            files
+              .iter()
+              .any(|path| paths.contains(path))
+              .then_some(files)
+              .unwrap_or_else(|| [].to_vec())
+          } else {
+            files
+              .into_iter()
+              .filter(|path| paths.contains(path))
+              .collect::<Vec<_>>()
+          }
         } else {
           files
-            .into_iter()
-            .filter(|path| paths.contains(path))
-            .collect::<Vec<_>>()
-        }
-      } else {
-        files
-      };
-      (refmt_files, fmt_config_options.clone())
-    });
+        };
+        (refmt_files, fmt_config_options.clone())
+      });
 
     let paths_to_watch = files.include.clone();
     async move {
@@ -101,20 +123,28 @@ pub async fn format(
       }
     }
   };
-  let factory = CliFactory::from_cli_options(Arc::new(cli_options));
-  let cli_options = factory.cli_options();
-  let caches = factory.caches()?;
   let operation = |(paths, fmt_options): (Vec<PathBuf>, FmtOptionsConfig)| async {
     let incremental_cache = Arc::new(IncrementalCache::new(
       caches.fmt_incremental_cache_db(),
-      &fmt_options,
+      &(&fmt_options, &fmt_plugins),
       &paths,
     ));
     if check {
-      check_source_files(paths, fmt_options, incremental_cache.clone()).await?;
+      check_source_files(
+        paths,
+        fmt_options,
+        maybe_plugin_host.clone(),
+        incremental_cache.clone(),
+      )
+      .await?;
     } else {
-      format_source_files(paths, fmt_options, incremental_cache.clone())
-        .await?;
+      format_source_files(
+        paths,
+        fmt_options,
+        maybe_plugin_host.clone(),
+        incremental_cache.clone(),
+      )
+      .await?;
     }
     incremental_cache.wait_completion().await;
     Ok(())
@@ -131,81 +161,58 @@ pub async fn format(
     )
     .await?;
   } else {
-    let files = collect_fmt_files(&files).and_then(|files| {
-      if files.is_empty() {
-        Err(generic_error("No target files found."))
-      } else {
-        Ok(files)
-      }
-    })?;
+    let files = collect_fmt_files(&files, maybe_plugin_host.as_deref())
+      .and_then(|files| {
+        if files.is_empty() {
+          Err(generic_error("No target files found."))
+        } else {
+          Ok(files)
+        }
+      })?;
     operation((files, fmt_config_options)).await?;
   }
 
   Ok(())
 }
 
-fn collect_fmt_files(files: &FilesConfig) -> Result<Vec<PathBuf>, AnyError> {
-  FileCollector::new(is_supported_ext_fmt)
-    .ignore_git_folder()
-    .ignore_node_modules()
-    .add_ignore_paths(&files.exclude)
-    .collect_files(&files.include)
+fn collect_fmt_files(
+  files: &FilesConfig,
+  maybe_plugin_host: Option<&FmtPluginHost>,
+) -> Result<Vec<PathBuf>, AnyError> {
+  FileCollector::new(|path| {
+    is_supported_ext_fmt(path)
+      || matches!(maybe_plugin_host, Some(host) if host.supports(path))
+  })
+  .ignore_git_folder()
+  .ignore_node_modules()
+  .add_ignore_paths(&files.exclude)
+  .collect_files(&files.include)
 }
 
 /// Formats markdown (using <https://github.com/dprint/dprint-plugin-markdown>) and its code blocks
-/// (ts/tsx, js/jsx).
+/// of the enabled embedded languages, or its ts/tsx, js/jsx and json/jsonc
+/// code blocks when none are configured.
 fn format_markdown(
   file_text: &str,
   fmt_options: &FmtOptionsConfig,
 ) -> Result<Option<String>, AnyError> {
   let markdown_config = get_resolved_markdown_config(fmt_options);
+  let fmt_options = if fmt_options.embedded_languages.is_none() {
+    Cow::Owned(FmtOptionsConfig {
+      embedded_languages: Some(vec![
+        EmbeddedLanguage::Typescript,
+        EmbeddedLanguage::Json,
+      ]),
+      ..fmt_options.clone()
+    })
+  } else {
+    Cow::Borrowed(fmt_options)
+  };
   dprint_plugin_markdown::format_text(
     file_text,
     &markdown_config,
     move |tag, text, line_width| {
-      let tag = tag.to_lowercase();
-      if matches!(
-        tag.as_str(),
-        "ts"
-          | "tsx"
-          | "js"
-          | "jsx"
-          | "cjs"
-          | "cts"
-          | "mjs"
-          | "mts"
-          | "javascript"
-          | "typescript"
-          | "json"
-          | "jsonc"
-      ) {
-        // It's important to tell dprint proper file extension, otherwise
-        // it might parse the file twice.
-        let extension = match tag.as_str() {
-          "javascript" => "js",
-          "typescript" => "ts",
-          rest => rest,
-        };
-
-        if matches!(extension, "json" | "jsonc") {
-          let mut json_config = get_resolved_json_config(fmt_options);
-          json_config.line_width = line_width;
-          dprint_plugin_json::format_text(text, &json_config)
-        } else {
-          let fake_filename =
-            PathBuf::from(format!("deno_fmt_stdin.{extension}"));
-          let mut codeblock_config =
-            get_resolved_typescript_config(fmt_options);
-          codeblock_config.line_width = line_width;
-          dprint_plugin_typescript::format_text(
-            &fake_filename,
-            text,
-            &codeblock_config,
-          )
-        }
-      } else {
-        Ok(None)
-      }
+      embedded::format_embedded_code(tag, text, line_width, &fmt_options)
     },
   )
 }
@@ -221,7 +228,8 @@ pub fn format_json(
   dprint_plugin_json::format_text(file_text, &config)
 }
 
-/// Formats a single TS, TSX, JS, JSX, JSONC, JSON, or MD file.
+/// Formats a single TS, TSX, JS, JSX, JSONC, JSON, or MD file, and the code
+/// that is embedded in it.
 pub fn format_file(
   file_path: &Path,
   file_text: &str,
@@ -237,7 +245,17 @@ pub fn format_file(
     format_json(file_text, fmt_options)
   } else {
     let config = get_resolved_typescript_config(fmt_options);
-    dprint_plugin_typescript::format_text(file_path, file_text, &config)
+    let formatted =
+      dprint_plugin_typescript::format_text(file_path, file_text, &config)?;
+    Ok(
+      embedded::format_embedded_code_in_module(
+        &file_path.to_string_lossy(),
+        MediaType::from_path(file_path),
+        formatted.as_deref().unwrap_or(file_text),
+        fmt_options,
+      )
+      .or(formatted),
+    )
   }
 }
 
@@ -245,15 +263,43 @@ pub fn format_parsed_source(
   parsed_source: &ParsedSource,
   fmt_options: &FmtOptionsConfig,
 ) -> Result<Option<String>, AnyError> {
-  dprint_plugin_typescript::format_parsed_source(
+  let formatted = dprint_plugin_typescript::format_parsed_source(
     parsed_source,
     &get_resolved_typescript_config(fmt_options),
+  )?;
+  Ok(
+    embedded::format_embedded_code_in_module(
+      parsed_source.specifier(),
+      parsed_source.media_type(),
+      formatted
+        .as_deref()
+        .unwrap_or_else(|| parsed_source.text_info().text_str()),
+      fmt_options,
+    )
+    .or(formatted),
   )
 }
 
+/// Formats a file with the fmt plugin that supports it, or with
+/// [`format_file`]. Blocks the thread when a plugin formats the file.
+fn format_file_blocking(
+  file_path: &Path,
+  file_text: &str,
+  fmt_options: &FmtOptionsConfig,
+  maybe_plugin_host: Option<&FmtPluginHost>,
+) -> Result<Option<String>, AnyError> {
+  match maybe_plugin_host {
+    Some(host) if host.supports(file_path) => {
+      host.format_blocking(file_path, file_text)
+    }
+    _ => format_file(file_path, file_text, fmt_options),
+  }
+}
+
 async fn check_source_files(
   paths: Vec<PathBuf>,
   fmt_options: FmtOptionsConfig,
+  maybe_plugin_host: Option<Arc<FmtPluginHost>>,
   incremental_cache: Arc<IncrementalCache>,
 ) -> Result<(), AnyError> {
   let not_formatted_files_count = Arc::new(AtomicUsize::new(0));
@@ -274,7 +320,12 @@ async fn check_source_files(
         return Ok(());
       }
 
-      match format_file(&file_path, &file_text, &fmt_options) {
+      match format_file_blocking(
+        &file_path,
+        &file_text,
+        &fmt_options,
+        maybe_plugin_host.as_deref(),
+      ) {
         Ok(Some(formatted_text)) => {
           not_formatted_files_count.fetch_add(1, Ordering::Relaxed);
           let _g = output_lock.lock();
@@ -335,6 +386,7 @@ async fn check_source_files(
 async fn format_source_files(
   paths: Vec<PathBuf>,
   fmt_options: FmtOptionsConfig,
+  maybe_plugin_host: Option<Arc<FmtPluginHost>>,
   incremental_cache: Arc<IncrementalCache>,
 ) -> Result<(), AnyError> {
   let formatted_files_count = Arc::new(AtomicUsize::new(0));
@@ -357,7 +409,14 @@ async fn format_source_files(
         &file_path,
         &file_contents.text,
         &fmt_options,
-        format_file,
+        |file_path, file_text, fmt_options| {
+          format_file_blocking(
+            file_path,
+            file_text,
+            fmt_options,
+            maybe_plugin_host.as_deref(),
+          )
+        },
       ) {
         Ok(Some(formatted_text)) => {
           incremental_cache.update_file(&file_path, &formatted_text);
@@ -0,0 +1,387 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The dprint Wasm plugins of `fmt.plugins` in the configuration file, which
+//! format the files of the languages that `deno fmt` doesn't support, like
+//! YAML or Dockerfiles. Each plugin runs in an isolate of its own, which has
+//! no permissions, and formats the files whose extensions or names it
+//! reports. Remote plugins are downloaded once to the DENO_DIR, and are
+//! verified against their integrity every time they are loaded.
+
+use crate::args::FmtOptionsConfig;
+use crate::args::FmtPluginConfig;
+use crate::cache::CACHE_PERM;
+use crate::factory::CliFactory;
+use crate::http_util::HttpClient;
+use crate::util::checksum;
+use crate::util::fs::atomic_write_file;
+use crate::util::path::get_extension;
+use crate::util::path::specifier_to_file_path;
+use crate::worker::CliMainWorkerFactory;
+
+use deno_core::anyhow::anyhow;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::serde_v8;
+use deno_core::v8;
+use deno_core::ModuleSpecifier;
+use deno_core::ZeroCopyBuf;
+use deno_runtime::permissions::Permissions;
+use deno_runtime::permissions::PermissionsContainer;
+use deno_runtime::permissions::PermissionsOptions;
+use deno_runtime::tokio_util::run_local;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+
+/// What a plugin reports about itself once it is configured.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FmtPluginInfo {
+  name: String,
+  version: String,
+  file_extensions: Vec<String>,
+  file_names: Vec<String>,
+}
+
+impl FmtPluginInfo {
+  fn matches_path(&self, path: &Path) -> bool {
+    let file_name = path.file_name().and_then(|name| name.to_str());
+    if let Some(file_name) = file_name {
+      if self.file_names.iter().any(|name| name == file_name) {
+        return true;
+      }
+    }
+    match get_extension(path) {
+      Some(ext) => self.file_extensions.iter().any(|plugin_ext| {
+        plugin_ext
+          .trim_start_matches('.')
+          .eq_ignore_ascii_case(&ext)
+      }),
+      None => false,
+    }
+  }
+}
+
+#[derive(Debug)]
+struct FmtPluginRequest {
+  file_path: String,
+  file_text: String,
+  response: oneshot::Sender<Result<Option<String>, AnyError>>,
+}
+
+/// The isolate of a plugin. It formats the files that are sent to it one at
+/// a time, and stops when it is dropped.
+#[derive(Debug)]
+struct FmtPlugin {
+  specifier: ModuleSpecifier,
+  info: FmtPluginInfo,
+  sender: mpsc::UnboundedSender<FmtPluginRequest>,
+}
+
+/// The plugins of a formatting run, which are shared by the threads that
+/// format the files.
+#[derive(Debug)]
+pub struct FmtPluginHost {
+  plugins: Vec<FmtPlugin>,
+}
+
+impl FmtPluginHost {
+  /// Loads the plugins, each in an isolate of its own, and configures them
+  /// with the global options of `fmt_options` and their own configuration.
+  pub async fn start(
+    factory: &CliFactory,
+    plugins: Vec<FmtPluginConfig>,
+    fmt_options: &FmtOptionsConfig,
+  ) -> Result<Self, AnyError> {
+    let worker_factory =
+      Arc::new(factory.create_cli_main_worker_factory().await?);
+    let cache_dir = factory.deno_dir()?.fmt_plugins_folder_path();
+    let global_config = json!({
+      "lineWidth": fmt_options.line_width.unwrap_or(80),
+      "indentWidth": fmt_options.indent_width.unwrap_or(2),
+      "useTabs": fmt_options.use_tabs.unwrap_or(false),
+      "newLineKind": "lf",
+    });
+    let mut started = Vec::with_capacity(plugins.len());
+    for plugin in plugins {
+      let specifier = plugin.specifier;
+      let (info, sender) = async {
+        let wasm_bytes = load_wasm_module(
+          factory.http_client(),
+          &cache_dir,
+          &specifier,
+          plugin.integrity.as_deref(),
+        )
+        .await?;
+        start_plugin(
+          worker_factory.clone(),
+          specifier.clone(),
+          wasm_bytes,
+          global_config.clone(),
+          serde_json::Value::Object(plugin.config),
+        )
+        .await
+      }
+      .await
+      .with_context(|| format!("Failed to load the fmt plugin {specifier}"))?;
+      log::debug!(
+        "Loaded the fmt plugin {} {} from {}",
+        info.name,
+        info.version,
+        specifier
+      );
+      started.push(FmtPlugin {
+        specifier,
+        info,
+        sender,
+      });
+    }
+    Ok(Self { plugins: started })
+  }
+
+  /// Whether one of the plugins formats the file.
+  pub fn supports(&self, file_path: &Path) -> bool {
+    self.get_plugin(file_path).is_some()
+  }
+
+  /// Formats a file with the first plugin that supports it.
+  pub async fn format(
+    &self,
+    file_path: &Path,
+    file_text: &str,
+  ) -> Result<Option<String>, AnyError> {
+    let (plugin, receiver) = self.send_request(file_path, file_text)?;
+    receiver.await.map_err(|_| plugin_stopped(plugin))?
+  }
+
+  /// Like [`FmtPluginHost::format`], but blocks the thread, which must not
+  /// be a thread of an async runtime.
+  pub fn format_blocking(
+    &self,
+    file_path: &Path,
+    file_text: &str,
+  ) -> Result<Option<String>, AnyError> {
+    let (plugin, receiver) = self.send_request(file_path, file_text)?;
+    receiver
+      .blocking_recv()
+      .map_err(|_| plugin_stopped(plugin))?
+  }
+
+  fn get_plugin(&self, file_path: &Path) -> Option<&FmtPlugin> {
+    self
+      .plugins
+      .iter()
+      .find(|plugin| plugin.info.matches_path(file_path))
+  }
+
+  #[allow(clippy::type_complexity)]
+  fn send_request(
+    &self,
+    file_path: &Path,
+    file_text: &str,
+  ) -> Result<
+    (
+      &FmtPlugin,
+      oneshot::Receiver<Result<Option<String>, AnyError>>,
+    ),
+    AnyError,
+  > {
+    let plugin = self.get_plugin(file_path).ok_or_else(|| {
+      anyhow!("No fmt plugin formats {}", file_path.display())
+    })?;
+    let (response, receiver) = oneshot::channel();
+    plugin
+      .sender
+      .send(FmtPluginRequest {
+        file_path: file_path.to_string_lossy().to_string(),
+        file_text: file_text.to_string(),
+        response,
+      })
+      .map_err(|_| plugin_stopped(plugin))?;
+    Ok((plugin, receiver))
+  }
+}
+
+fn plugin_stopped(plugin: &FmtPlugin) -> AnyError {
+  anyhow!("The fmt plugin {} stopped.", plugin.specifier)
+}
+
+/// Reads the Wasm module of a plugin. Remote modules are downloaded once, to
+/// a file that is named after a hash of their URL.
+async fn load_wasm_module(
+  http_client: &HttpClient,
+  cache_dir: &Path,
+  specifier: &ModuleSpecifier,
+  integrity: Option<&str>,
+) -> Result<Vec<u8>, AnyError> {
+  match specifier.scheme() {
+    "file" => {
+      let path = specifier_to_file_path(specifier)?;
+      let bytes = fs::read(&path)
+        .with_context(|| format!("Error reading {}", path.display()))?;
+      check_integrity(&bytes, integrity, false)?;
+      Ok(bytes)
+    }
+    "http" | "https" => {
+      let cache_path = cache_dir
+        .join(format!("{}.wasm", checksum::gen(&[specifier.as_str()])));
+      if let Ok(bytes) = fs::read(&cache_path) {
+        check_integrity(&bytes, integrity, true)?;
+        return Ok(bytes);
+      }
+      let bytes = http_client.download(specifier.clone()).await?;
+      check_integrity(&bytes, integrity, true)?;
+      fs::create_dir_all(cache_dir)?;
+      atomic_write_file(&cache_path, &bytes, CACHE_PERM)?;
+      Ok(bytes)
+    }
+    scheme => bail!("Unsupported scheme \"{scheme}\" of a fmt plugin."),
+  }
+}
+
+/// Checks the Wasm module of a plugin against the SHA-256 checksum of its
+/// `integrity`, which is required for remote plugins, since they run code
+/// that the configuration file doesn't pin otherwise.
+fn check_integrity(
+  bytes: &[u8],
+  integrity: Option<&str>,
+  is_remote: bool,
+) -> Result<(), AnyError> {
+  let actual = checksum::gen(&[bytes]);
+  match integrity {
+    Some(expected) if !expected.eq_ignore_ascii_case(&actual) => bail!(
+      "Integrity check failed.\n\nActual: {actual}\nExpected: {expected}"
+    ),
+    None if is_remote => bail!(
+      "Remote fmt plugins require an integrity. Add \"integrity\": \"{actual}\" to the plugin in \"fmt.plugins\" if you trust this module."
+    ),
+    _ => Ok(()),
+  }
+}
+
+/// Starts the isolate of a plugin, and returns once the plugin is configured.
+async fn start_plugin(
+  worker_factory: Arc<CliMainWorkerFactory>,
+  specifier: ModuleSpecifier,
+  wasm_bytes: Vec<u8>,
+  global_config: serde_json::Value,
+  plugin_config: serde_json::Value,
+) -> Result<(FmtPluginInfo, mpsc::UnboundedSender<FmtPluginRequest>), AnyError>
+{
+  let (sender, mut receiver) = mpsc::unbounded_channel::<FmtPluginRequest>();
+  let (ready_sender, ready_receiver) = oneshot::channel();
+  let handle = tokio::task::spawn_blocking(move || {
+    run_local(async move {
+      let permissions = PermissionsContainer::new(Permissions::from_options(
+        &PermissionsOptions::default(),
+      )?);
+      // the Wasm module is never executed as the main module of the worker
+      let worker = worker_factory
+        .create_main_worker(specifier, permissions)
+        .await?;
+      let mut worker = worker.into_main_worker();
+      let start_fmt_plugin = worker.js_runtime.execute_script_static(
+        "ext:cli/fmt_plugins.js",
+        "Deno[Deno.internal].startFmtPlugin",
+      )?;
+      let run_fmt_plugin = worker.js_runtime.execute_script_static(
+        "ext:cli/fmt_plugins.js",
+        "Deno[Deno.internal].runFmtPlugin",
+      )?;
+      let (start_fmt_plugin, run_fmt_plugin, args) = {
+        let scope = &mut worker.js_runtime.handle_scope();
+        let start_fmt_plugin = v8::Local::new(scope, start_fmt_plugin);
+        let start_fmt_plugin =
+          v8::Local::<v8::Function>::try_from(start_fmt_plugin)?;
+        let run_fmt_plugin = v8::Local::new(scope, run_fmt_plugin);
+        let run_fmt_plugin =
+          v8::Local::<v8::Function>::try_from(run_fmt_plugin)?;
+        let wasm_bytes = serde_v8::to_v8(scope, ZeroCopyBuf::from(wasm_bytes))?;
+        let global_config = serde_v8::to_v8(scope, &global_config)?;
+        let plugin_config = serde_v8::to_v8(scope, &plugin_config)?;
+        (
+          v8::Global::new(scope, start_fmt_plugin),
+          v8::Global::new(scope, run_fmt_plugin),
+          [
+            v8::Global::new(scope, wasm_bytes),
+            v8::Global::new(scope, global_config),
+            v8::Global::new(scope, plugin_config),
+          ],
+        )
+      };
+      let plugin = worker
+        .js_runtime
+        .call_with_args_and_await(&start_fmt_plugin, &args)
+        .await?;
+      let info = {
+        let scope = &mut worker.js_runtime.handle_scope();
+        let plugin = v8::Local::new(scope, &plugin);
+        let plugin = v8::Local::<v8::Object>::try_from(plugin)?;
+        let key = v8::String::new(scope, "info").unwrap();
+        let info = plugin.get(scope, key.into()).unwrap();
+        serde_v8::from_v8::<FmtPluginInfo>(scope, info)?
+      };
+      let _ = ready_sender.send(info);
+
+      while let Some(request) = receiver.recv().await {
+        let result = async {
+          let args = {
+            let scope = &mut worker.js_runtime.handle_scope();
+            let file_path = serde_v8::to_v8(scope, &request.file_path)?;
+            let file_text = serde_v8::to_v8(scope, &request.file_text)?;
+            [
+              plugin.clone(),
+              v8::Global::new(scope, file_path),
+              v8::Global::new(scope, file_text),
+            ]
+          };
+          let value = worker
+            .js_runtime
+            .call_with_args_and_await(&run_fmt_plugin, &args)
+            .await?;
+          let scope = &mut worker.js_runtime.handle_scope();
+          let value = v8::Local::new(scope, value);
+          let formatted_text = serde_v8::from_v8(scope, value)?;
+          Ok::<Option<String>, AnyError>(formatted_text)
+        }
+        .await;
+        let _ = request.response.send(result);
+      }
+      Ok::<_, AnyError>(())
+    })
+  });
+
+  match ready_receiver.await {
+    Ok(info) => Ok((info, sender)),
+    Err(_) => {
+      handle.await??;
+      bail!("The fmt plugin stopped before it was configured.")
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_fmt_plugin_info_matches_path() {
+    let info = FmtPluginInfo {
+      name: "dprint-plugin-dockerfile".to_string(),
+      version: "0.3.0".to_string(),
+      file_extensions: vec!["dockerfile".to_string(), ".YML".to_string()],
+      file_names: vec!["Dockerfile".to_string()],
+    };
+    assert!(info.matches_path(Path::new("/deno/Dockerfile")));
+    assert!(info.matches_path(Path::new("/deno/app.dockerfile")));
+    assert!(info.matches_path(Path::new("/deno/ci.yml")));
+    assert!(!info.matches_path(Path::new("/deno/dockerfile.ts")));
+    assert!(!info.matches_path(Path::new("/deno/Makefile")));
+  }
+}
@@ -1,11 +1,15 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::fmt;
 use std::fmt::Write;
 
 use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::bail;
 use deno_core::error::AnyError;
 use deno_core::resolve_url_or_path;
 use deno_core::serde_json;
@@ -24,9 +28,12 @@ use deno_semver::npm::NpmPackageNv;
 use deno_semver::npm::NpmPackageNvReference;
 use deno_semver::npm::NpmPackageReqReference;
 
+use crate::args::CliOptions;
 use crate::args::Flags;
 use crate::args::InfoFlags;
 use crate::display;
+use crate::emit::is_emittable;
+use crate::emit::Emitter;
 use crate::factory::CliFactory;
 use crate::graph_util::graph_lock_or_exit;
 use crate::npm::CliNpmResolver;
@@ -50,13 +57,33 @@ pub async fn info(flags: Flags, info_flags: InfoFlags) -> Result<(), AnyError> {
       graph_lock_or_exit(&graph, &mut lockfile.lock());
     }
 
+    let maybe_chains = match &info_flags.why {
+      Some(why) => {
+        let targets = resolve_why_targets(&graph, why, cli_options)?;
+        Some(find_dependency_chains(&graph, &targets))
+      }
+      None => None,
+    };
+
     if info_flags.json {
       let mut json_graph = json!(graph);
       add_npm_packages_to_json(&mut json_graph, npm_resolver);
+      add_emit_sizes_to_json(&mut json_graph, &graph, factory.emitter()?)?;
+      let json_graph = json_graph.as_object_mut().unwrap();
+      json_graph.insert(
+        "duplicates".to_string(),
+        find_duplicates(&graph, &npm_resolver.snapshot()),
+      );
+      if let Some(chains) = maybe_chains {
+        json_graph.insert("why".to_string(), json!(chains));
+      }
       display::write_json_to_stdout(&json_graph)?;
     } else {
       let mut output = String::new();
       GraphDisplayContext::write(&graph, npm_resolver, &mut output)?;
+      if let Some(chains) = maybe_chains {
+        write_dependency_chains(&chains, &mut output)?;
+      }
       display::write_to_stdout_ignore_sigpipe(output.as_bytes())?;
     }
   } else {
@@ -241,6 +268,257 @@ fn add_npm_packages_to_json(
   json.insert("npmPackages".to_string(), json_packages.into());
 }
 
+/// Adds the size of the emitted code of the modules that are transpiled, next
+/// to the size of their source. Modules that haven't been emitted before are
+/// emitted, and their code is cached for later runs.
+fn add_emit_sizes_to_json(
+  json: &mut serde_json::Value,
+  graph: &ModuleGraph,
+  emitter: &Emitter,
+) -> Result<(), AnyError> {
+  let mut emit_sizes = HashMap::new();
+  for module in graph.modules() {
+    let Module::Esm(module) = module else {
+      continue;
+    };
+    if is_emittable(module.media_type) {
+      let code = emitter.emit_parsed_source(
+        &module.specifier,
+        module.media_type,
+        &module.source,
+      )?;
+      emit_sizes.insert(module.specifier.as_str(), code.as_str().len());
+    }
+  }
+
+  let modules = json.get_mut("modules").and_then(|m| m.as_array_mut());
+  for module in modules.into_iter().flatten() {
+    let Some(module) = module.as_object_mut() else {
+      continue;
+    };
+    let maybe_emit_size = module
+      .get("specifier")
+      .and_then(|specifier| specifier.as_str())
+      .and_then(|specifier| emit_sizes.get(specifier));
+    if let Some(emit_size) = maybe_emit_size {
+      module.insert("emitSize".to_string(), (*emit_size).into());
+    }
+  }
+  Ok(())
+}
+
+/// Finds the modules that are in the graph at several versions, like
+/// `https://deno.land/std@0.190.0/path/mod.ts` next to
+/// `https://deno.land/std@0.191.0/path/mod.ts`, and the npm packages that are
+/// resolved to several versions.
+fn find_duplicates(
+  graph: &ModuleGraph,
+  snapshot: &NpmResolutionSnapshot,
+) -> serde_json::Value {
+  let mut duplicates = find_duplicate_modules(graph);
+  duplicates.extend(find_duplicate_npm_packages(
+    snapshot
+      .all_packages()
+      .into_iter()
+      .map(|package| package.pkg_id.clone()),
+  ));
+  duplicates.into()
+}
+
+fn find_duplicate_modules(graph: &ModuleGraph) -> Vec<serde_json::Value> {
+  let mut remote_modules: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+  for module in graph.modules() {
+    if let Some(name) = get_unversioned_specifier(module.specifier()) {
+      remote_modules
+        .entry(name)
+        .or_default()
+        .insert(module.specifier().to_string());
+    }
+  }
+  let mut duplicates = Vec::new();
+  for (name, specifiers) in remote_modules {
+    if specifiers.len() > 1 {
+      duplicates.push(json!({
+        "kind": "esm",
+        "name": name,
+        "specifiers": specifiers,
+      }));
+    }
+  }
+  duplicates
+}
+
+fn find_duplicate_npm_packages(
+  ids: impl Iterator<Item = NpmPackageId>,
+) -> Vec<serde_json::Value> {
+  let mut npm_packages: BTreeMap<String, Vec<NpmPackageId>> = BTreeMap::new();
+  for id in ids {
+    npm_packages.entry(id.nv.name.clone()).or_default().push(id);
+  }
+
+  let mut duplicates = Vec::new();
+  for (name, mut ids) in npm_packages {
+    ids.sort_by(|a, b| a.nv.version.cmp(&b.nv.version));
+    ids.dedup_by(|a, b| a.nv.version == b.nv.version);
+    if ids.len() > 1 {
+      duplicates.push(json!({
+        "kind": "npm",
+        "name": name,
+        "versions": ids
+          .iter()
+          .map(|id| id.nv.version.to_string())
+          .collect::<Vec<_>>(),
+      }));
+    }
+  }
+  duplicates
+}
+
+/// Returns the remote specifier without the version of its package, which is
+/// the first path segment of the form `name@version`, or `None` when the
+/// specifier has no version.
+fn get_unversioned_specifier(specifier: &ModuleSpecifier) -> Option<String> {
+  if !matches!(specifier.scheme(), "http" | "https") {
+    return None;
+  }
+  let mut found = false;
+  let segments = specifier
+    .path()
+    .split('/')
+    .map(|segment| {
+      if found {
+        return segment;
+      }
+      // skip the `@` of scoped package names, like `@scope/name@1.0.0`
+      match segment.get(1..).and_then(|rest| rest.find('@')) {
+        Some(index) => {
+          found = true;
+          &segment[..index + 1]
+        }
+        None => segment,
+      }
+    })
+    .collect::<Vec<_>>();
+  if found {
+    Some(format!("{}{}", specifier.host_str()?, segments.join("/")))
+  } else {
+    None
+  }
+}
+
+/// Resolves the modules that `deno info --why` asks about, either a specifier
+/// or an npm package name, like `npm:chalk`.
+fn resolve_why_targets(
+  graph: &ModuleGraph,
+  why: &str,
+  cli_options: &CliOptions,
+) -> Result<HashSet<ModuleSpecifier>, AnyError> {
+  let targets = if let Ok(npm_ref) = NpmPackageReqReference::from_str(why) {
+    graph
+      .modules()
+      .filter_map(|module| module.npm())
+      .filter(|module| module.nv_reference.nv.name == npm_ref.req.name)
+      .map(|module| module.specifier.clone())
+      .collect::<HashSet<_>>()
+  } else {
+    let specifier = resolve_url_or_path(why, cli_options.initial_cwd())?;
+    let specifier = graph.resolve(&specifier);
+    if graph.get(&specifier).is_some() {
+      HashSet::from([specifier])
+    } else {
+      HashSet::new()
+    }
+  };
+  if targets.is_empty() {
+    bail!("\"{}\" is not in the module graph.", why);
+  }
+  Ok(targets)
+}
+
+/// Returns a chain of imports from a root of the graph to one of the target
+/// modules for every module that imports a target. The chains are the
+/// shortest ones to the importing modules.
+fn find_dependency_chains(
+  graph: &ModuleGraph,
+  targets: &HashSet<ModuleSpecifier>,
+) -> Vec<Vec<ModuleSpecifier>> {
+  let mut parents: HashMap<ModuleSpecifier, Option<ModuleSpecifier>> =
+    HashMap::new();
+  let mut queue = VecDeque::new();
+  for root in &graph.roots {
+    let root = graph.resolve(root);
+    if !parents.contains_key(&root) {
+      parents.insert(root.clone(), None);
+      queue.push_back(root);
+    }
+  }
+
+  let mut chains = Vec::new();
+  while let Some(specifier) = queue.pop_front() {
+    let Some(module) = graph.get(&specifier).and_then(|m| m.esm()) else {
+      continue;
+    };
+    let dependencies = module
+      .dependencies
+      .values()
+      .flat_map(|dep| {
+        [
+          dep.maybe_code.maybe_specifier(),
+          dep.maybe_type.maybe_specifier(),
+        ]
+      })
+      .chain([module
+        .maybe_types_dependency
+        .as_ref()
+        .and_then(|dep| dep.dependency.maybe_specifier())])
+      .flatten()
+      .map(|dep| graph.resolve(dep))
+      .collect::<Vec<_>>();
+
+    if dependencies.iter().any(|dep| targets.contains(dep)) {
+      let mut chain = vec![specifier.clone()];
+      let mut current = &specifier;
+      while let Some(Some(parent)) = parents.get(current) {
+        chain.push(parent.clone());
+        current = parent;
+      }
+      chain.reverse();
+      for dep in dependencies.iter().filter(|dep| targets.contains(dep)) {
+        let mut chain = chain.clone();
+        chain.push(dep.clone());
+        if !chains.contains(&chain) {
+          chains.push(chain);
+        }
+      }
+    }
+
+    for dep in dependencies {
+      if !parents.contains_key(&dep) {
+        parents.insert(dep.clone(), Some(specifier.clone()));
+        queue.push_back(dep);
+      }
+    }
+  }
+  chains
+}
+
+fn write_dependency_chains<TWrite: Write>(
+  chains: &[Vec<ModuleSpecifier>],
+  writer: &mut TWrite,
+) -> fmt::Result {
+  writeln!(writer)?;
+  writeln!(writer, "{}", colors::bold("imported by:"))?;
+  for chain in chains {
+    let text = chain
+      .iter()
+      .map(|specifier| specifier.as_str())
+      .collect::<Vec<_>>()
+      .join(&format!(" {} ", colors::gray("->")));
+    writeln!(writer, "  {text}")?;
+  }
+  Ok(())
+}
+
 struct TreeNode {
   text: String,
   children: Vec<TreeNode>,
@@ -705,3 +983,133 @@ fn maybe_size_to_text(maybe_size: Option<u64>) -> String {
   ))
   .to_string()
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_get_unversioned_specifier() {
+    let unversioned = |specifier: &str| {
+      get_unversioned_specifier(&ModuleSpecifier::parse(specifier).unwrap())
+    };
+    assert_eq!(
+      unversioned("https://deno.land/std@0.190.0/path/mod.ts").as_deref(),
+      Some("deno.land/std/path/mod.ts")
+    );
+    assert_eq!(
+      unversioned("https://esm.sh/@preact/signals@1.1.3?target=deno")
+        .as_deref(),
+      Some("esm.sh/@preact/signals")
+    );
+    assert_eq!(
+      unversioned("https://deno.land/x/oak@v12.5.0/mod.ts").as_deref(),
+      Some("deno.land/x/oak/mod.ts")
+    );
+    assert_eq!(unversioned("https://example.com/mod.ts"), None);
+    assert_eq!(unversioned("file:///project/a@1/mod.ts"), None);
+  }
+
+  async fn build_graph(modules: &[(&str, &str)]) -> ModuleGraph {
+    let mut loader = deno_graph::source::MemoryLoader::new(
+      modules
+        .iter()
+        .map(|(specifier, content)| {
+          (
+            *specifier,
+            deno_graph::source::Source::Module {
+              specifier: *specifier,
+              content: *content,
+              maybe_headers: None,
+            },
+          )
+        })
+        .collect(),
+      Vec::new(),
+    );
+    let roots = vec![ModuleSpecifier::parse(modules[0].0).unwrap()];
+    let mut graph = ModuleGraph::default();
+    graph.build(roots, &mut loader, Default::default()).await;
+    graph
+  }
+
+  #[tokio::test]
+  async fn test_find_dependency_chains() {
+    let graph = build_graph(&[
+      ("file:///main.ts", "import './a.ts'; import './b.ts';"),
+      ("file:///a.ts", "import './shared.ts';"),
+      ("file:///b.ts", "import './c.ts';"),
+      ("file:///c.ts", "import './shared.ts';"),
+      ("file:///shared.ts", ""),
+    ])
+    .await;
+    let specifier = |s: &str| ModuleSpecifier::parse(s).unwrap();
+    let targets = HashSet::from([specifier("file:///shared.ts")]);
+    assert_eq!(
+      find_dependency_chains(&graph, &targets),
+      vec![
+        vec![
+          specifier("file:///main.ts"),
+          specifier("file:///a.ts"),
+          specifier("file:///shared.ts"),
+        ],
+        vec![
+          specifier("file:///main.ts"),
+          specifier("file:///b.ts"),
+          specifier("file:///c.ts"),
+          specifier("file:///shared.ts"),
+        ],
+      ]
+    );
+    let targets = HashSet::from([specifier("file:///main.ts")]);
+    assert!(find_dependency_chains(&graph, &targets).is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_find_duplicates() {
+    let graph = build_graph(&[
+      (
+        "file:///main.ts",
+        "import 'https://deno.land/std@0.190.0/path/mod.ts';
+        import 'https://deno.land/std@0.191.0/path/mod.ts';
+        import 'https://deno.land/std@0.191.0/fs/mod.ts';",
+      ),
+      ("https://deno.land/std@0.190.0/path/mod.ts", ""),
+      ("https://deno.land/std@0.191.0/path/mod.ts", ""),
+      ("https://deno.land/std@0.191.0/fs/mod.ts", ""),
+    ])
+    .await;
+    assert_eq!(
+      find_duplicate_modules(&graph),
+      vec![json!({
+        "kind": "esm",
+        "name": "deno.land/std/path/mod.ts",
+        "specifiers": [
+          "https://deno.land/std@0.190.0/path/mod.ts",
+          "https://deno.land/std@0.191.0/path/mod.ts",
+        ],
+      })]
+    );
+
+    let ids = [
+      "chalk@4.1.2",
+      "chalk@5.0.1",
+      "has-flag@4.0.0",
+      "@denotest/peer-dep-test-peer@1.0.0",
+      "@denotest/peer-dep-test-child@1.0.0_@denotest+peer-dep-test-peer@1.0.0",
+      "@denotest/peer-dep-test-child@1.0.0_@denotest+peer-dep-test-peer@2.0.0",
+    ];
+    assert_eq!(
+      find_duplicate_npm_packages(
+        ids
+          .iter()
+          .map(|id| NpmPackageId::from_serialized(id).unwrap())
+      ),
+      vec![json!({
+        "kind": "npm",
+        "name": "chalk",
+        "versions": ["4.1.2", "5.0.1"],
+      })]
+    );
+  }
+}
@@ -0,0 +1,480 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The fixes of `deno lint --fix`. The diagnostics of `deno_lint` don't carry
+//! fixes, so the edits that fix them are derived here from their ranges and
+//! the AST of the module, and reported along with them. A fix is only made
+//! when the edit is known to keep the meaning of the code.
+
+use deno_ast::swc::ast::ImportDecl;
+use deno_ast::swc::ast::ImportSpecifier;
+use deno_ast::swc::ast::ObjectPatProp;
+use deno_ast::swc::ast::Pat;
+use deno_ast::swc::ast::Program;
+use deno_ast::swc::ast::VarDecl;
+use deno_ast::swc::ast::VarDeclKind;
+use deno_ast::swc::visit::noop_visit_type;
+use deno_ast::swc::visit::Visit;
+use deno_ast::swc::visit::VisitWith;
+use deno_ast::LineAndColumnIndex;
+use deno_ast::MediaType;
+use deno_ast::ParsedSource;
+use deno_ast::SourcePos;
+use deno_ast::SourceRangedForSpanned;
+use deno_ast::SourceTextInfo;
+use deno_ast::StartSourcePos;
+use deno_core::error::AnyError;
+use deno_lint::diagnostic::LintDiagnostic;
+use deno_lint::linter::Linter;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// The number of times that a file is fixed and linted again at most, which
+/// stops fixes that undo each other from going on forever.
+const MAX_FIX_PASSES: usize = 10;
+
+/// A replacement of the text of a module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintFixEdit {
+  /// The byte range of the replaced text.
+  pub range: Range<usize>,
+  pub new_text: String,
+}
+
+/// Lints a module, and applies the fixes of its diagnostics until there is
+/// nothing left to fix. Every pass is verified by parsing and linting the
+/// fixed text again, and a pass whose text doesn't parse is discarded. Returns
+/// the fixed module and the diagnostics that are left.
+pub fn lint_and_fix(
+  linter: &Linter,
+  file_name: &str,
+  media_type: MediaType,
+  source_code: String,
+) -> Result<(ParsedSource, Vec<LintDiagnostic>), AnyError> {
+  let mut parsed_source = parse(file_name, media_type, source_code)?;
+  let mut passes = 0;
+  loop {
+    let diagnostics = linter.lint_with_ast(&parsed_source);
+    let edits = get_fixes(&parsed_source, &diagnostics)
+      .into_iter()
+      .flatten()
+      .collect::<Vec<_>>();
+    if edits.is_empty() || passes == MAX_FIX_PASSES {
+      return Ok((parsed_source, diagnostics));
+    }
+    let fixed_text =
+      apply_fix_edits(parsed_source.text_info().text_str(), edits);
+    match parse(file_name, media_type, fixed_text) {
+      Ok(fixed) => parsed_source = fixed,
+      // A fix that breaks the syntax is a bug, so the file is left as it was
+      // before the pass.
+      Err(_) => return Ok((parsed_source, diagnostics)),
+    }
+    passes += 1;
+  }
+}
+
+fn parse(
+  file_name: &str,
+  media_type: MediaType,
+  text: String,
+) -> Result<ParsedSource, AnyError> {
+  let parsed_source = deno_ast::parse_program(deno_ast::ParseParams {
+    specifier: file_name.to_string(),
+    text_info: SourceTextInfo::from_string(text),
+    media_type,
+    capture_tokens: true,
+    scope_analysis: true,
+    maybe_syntax: None,
+  })?;
+  Ok(parsed_source)
+}
+
+/// Returns the edits that fix each of the diagnostics of a module, in the
+/// order of the diagnostics. The diagnostics that can't be fixed have no
+/// edits, and the edits of different diagnostics may overlap or be the same.
+pub fn get_fixes(
+  parsed_source: &ParsedSource,
+  diagnostics: &[LintDiagnostic],
+) -> Vec<Vec<LintFixEdit>> {
+  let text_info = parsed_source.text_info();
+  let start_pos = text_info.range().start;
+  // The indexes of the diagnostics of a code by the byte index they start at.
+  let diagnostic_starts = |code: &str| {
+    diagnostics
+      .iter()
+      .enumerate()
+      .filter(|(_, d)| d.code == code)
+      .map(|(i, d)| {
+        let start = text_info
+          .loc_to_source_pos(LineAndColumnIndex {
+            line_index: d.range.start.line_index,
+            column_index: d.range.start.column_index,
+          })
+          .as_byte_index(start_pos);
+        (start, i)
+      })
+      .collect::<HashMap<_, _>>()
+  };
+  let unused = diagnostic_starts("no-unused-vars");
+  let never_reassigned = diagnostic_starts("prefer-const");
+  let extra_semis = diagnostic_starts("no-extra-semi");
+
+  let mut collector = FixTargetCollector {
+    start_pos,
+    let_decls: vec![],
+    imports: vec![],
+  };
+  let program = parsed_source.program();
+  let program: &Program = &program;
+  program.visit_with(&mut collector);
+
+  let text = text_info.text_str();
+  let mut fixes = vec![vec![]; diagnostics.len()];
+  for (start, i) in extra_semis {
+    if text[start..].starts_with(';') {
+      fixes[i].push(LintFixEdit {
+        range: start..start + 1,
+        new_text: String::new(),
+      });
+    }
+  }
+  for let_decl in collector.let_decls {
+    let Some(bindings) = let_decl.bindings else {
+      continue;
+    };
+    // The declaration is only fixed if none of its bindings are reassigned,
+    // which fixes the diagnostics of all of them.
+    let Some(indexes) = bindings
+      .iter()
+      .map(|start| never_reassigned.get(start).copied())
+      .collect::<Option<Vec<_>>>()
+    else {
+      continue;
+    };
+    if text[let_decl.start..].starts_with("let") {
+      let edit = LintFixEdit {
+        range: let_decl.start..let_decl.start + 3,
+        new_text: "const".to_string(),
+      };
+      for i in indexes {
+        fixes[i].push(edit.clone());
+      }
+    }
+  }
+  for import in collector.imports {
+    add_unused_import_fixes(text, &import, &unused, &mut fixes);
+  }
+  fixes
+}
+
+fn add_unused_import_fixes(
+  text: &str,
+  import: &ImportTarget,
+  unused: &HashMap<usize, usize>,
+  fixes: &mut [Vec<LintFixEdit>],
+) {
+  let diagnostic =
+    |specifier: &ImportSpecifierTarget| unused.get(&specifier.local_start);
+  if import.specifiers.is_empty() {
+    return;
+  }
+  if let Some(indexes) = import
+    .specifiers
+    .iter()
+    .map(diagnostic)
+    .collect::<Option<Vec<_>>>()
+  {
+    let edit = if import.is_type_only {
+      // The import is removed with the line break after it.
+      let mut end = import.range.end;
+      if text[end..].starts_with("\r\n") {
+        end += 2;
+      } else if text[end..].starts_with('\n') {
+        end += 1;
+      }
+      LintFixEdit {
+        range: import.range.start..end,
+        new_text: String::new(),
+      }
+    } else {
+      // The module may have side effects, so it's still imported.
+      LintFixEdit {
+        range: import.range.start..import.src_start,
+        new_text: "import ".to_string(),
+      }
+    };
+    for i in indexes {
+      fixes[*i].push(edit.clone());
+    }
+    return;
+  }
+
+  // A named specifier is removed with the comma that separates it from the
+  // next one, or from the previous one if it is the last. The ones that would
+  // leave empty braces are left alone.
+  let named = import
+    .specifiers
+    .iter()
+    .filter(|s| s.is_named)
+    .collect::<Vec<_>>();
+  if named.len() < 2 {
+    return;
+  }
+  for (i, specifier) in named.iter().enumerate() {
+    let Some(diagnostic) = diagnostic(specifier) else {
+      continue;
+    };
+    let range = match named.get(i + 1) {
+      Some(next) => specifier.range.start..next.range.start,
+      None => named[i - 1].range.end..specifier.range.end,
+    };
+    fixes[*diagnostic].push(LintFixEdit {
+      range,
+      new_text: String::new(),
+    });
+  }
+}
+
+/// Applies the edits to the text. The edits that overlap with an earlier one
+/// are left out, to be made by the next pass over the fixed text.
+pub fn apply_fix_edits(text: &str, mut edits: Vec<LintFixEdit>) -> String {
+  edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+  edits.dedup();
+  let mut fixed = String::with_capacity(text.len());
+  let mut last_end = 0;
+  for edit in edits {
+    if edit.range.start < last_end {
+      continue;
+    }
+    fixed.push_str(&text[last_end..edit.range.start]);
+    fixed.push_str(&edit.new_text);
+    last_end = edit.range.end;
+  }
+  fixed.push_str(&text[last_end..]);
+  fixed
+}
+
+struct LetDeclTarget {
+  /// The byte index of the `let` keyword.
+  start: usize,
+  /// The byte indexes of the bound identifiers, or `None` if the patterns
+  /// aren't supported.
+  bindings: Option<Vec<usize>>,
+}
+
+struct ImportSpecifierTarget {
+  range: Range<usize>,
+  /// The byte index of the local name.
+  local_start: usize,
+  is_named: bool,
+}
+
+struct ImportTarget {
+  range: Range<usize>,
+  /// The byte index of the module specifier.
+  src_start: usize,
+  is_type_only: bool,
+  specifiers: Vec<ImportSpecifierTarget>,
+}
+
+/// Collects the declarations that the fixes edit.
+struct FixTargetCollector {
+  start_pos: StartSourcePos,
+  let_decls: Vec<LetDeclTarget>,
+  imports: Vec<ImportTarget>,
+}
+
+impl FixTargetCollector {
+  fn byte_index(&self, pos: SourcePos) -> usize {
+    pos.as_byte_index(self.start_pos)
+  }
+
+  fn collect_bindings(&self, pat: &Pat, bindings: &mut Vec<usize>) -> bool {
+    match pat {
+      Pat::Ident(ident) => {
+        bindings.push(self.byte_index(ident.start()));
+        true
+      }
+      Pat::Array(array) => array
+        .elems
+        .iter()
+        .flatten()
+        .all(|elem| self.collect_bindings(elem, bindings)),
+      Pat::Object(object) => object.props.iter().all(|prop| match prop {
+        ObjectPatProp::KeyValue(prop) => {
+          self.collect_bindings(&prop.value, bindings)
+        }
+        ObjectPatProp::Assign(prop) => {
+          bindings.push(self.byte_index(prop.key.start()));
+          true
+        }
+        ObjectPatProp::Rest(rest) => self.collect_bindings(&rest.arg, bindings),
+      }),
+      Pat::Rest(rest) => self.collect_bindings(&rest.arg, bindings),
+      Pat::Assign(assign) => self.collect_bindings(&assign.left, bindings),
+      Pat::Invalid(_) | Pat::Expr(_) => false,
+    }
+  }
+}
+
+impl Visit for FixTargetCollector {
+  noop_visit_type!();
+
+  fn visit_var_decl(&mut self, node: &VarDecl) {
+    if node.kind == VarDeclKind::Let {
+      let mut bindings = vec![];
+      let supported = node
+        .decls
+        .iter()
+        .all(|decl| self.collect_bindings(&decl.name, &mut bindings));
+      self.let_decls.push(LetDeclTarget {
+        start: self.byte_index(node.start()),
+        bindings: supported.then_some(bindings),
+      });
+    }
+    node.visit_children_with(self);
+  }
+
+  fn visit_import_decl(&mut self, node: &ImportDecl) {
+    let specifiers = node
+      .specifiers
+      .iter()
+      .map(|specifier| {
+        let (local, is_named) = match specifier {
+          ImportSpecifier::Named(named) => (&named.local, true),
+          ImportSpecifier::Default(default) => (&default.local, false),
+          ImportSpecifier::Namespace(namespace) => (&namespace.local, false),
+        };
+        ImportSpecifierTarget {
+          range: self.byte_index(specifier.start())
+            ..self.byte_index(specifier.end()),
+          local_start: self.byte_index(local.start()),
+          is_named,
+        }
+      })
+      .collect();
+    self.imports.push(ImportTarget {
+      range: self.byte_index(node.start())..self.byte_index(node.end()),
+      src_start: self.byte_index(node.src.start()),
+      is_type_only: node.type_only,
+      specifiers,
+    });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::tools::lint::create_linter;
+  use deno_lint::rules::get_recommended_rules;
+
+  fn fix(source: &str) -> (Vec<String>, String) {
+    let linter = create_linter(MediaType::TypeScript, get_recommended_rules());
+    let (parsed_source, diagnostics) = lint_and_fix(
+      &linter,
+      "file:///mod.ts",
+      MediaType::TypeScript,
+      source.to_string(),
+    )
+    .unwrap();
+    (
+      diagnostics.into_iter().map(|d| d.code).collect(),
+      parsed_source.text_info().text_str().to_string(),
+    )
+  }
+
+  #[test]
+  fn test_fix_prefer_const() {
+    let (codes, text) =
+      fix("let a = 1;\nlet b = 2;\nb = 3;\nconsole.log(a, b);\n");
+    assert!(codes.is_empty(), "{codes:?}");
+    assert_eq!(
+      text,
+      "const a = 1;\nlet b = 2;\nb = 3;\nconsole.log(a, b);\n"
+    );
+
+    // A declaration is only fixed if none of its bindings are reassigned.
+    let (codes, text) = fix("let e = 1, f = 2;\nf++;\nconsole.log(e, f);\n");
+    assert_eq!(codes, vec!["prefer-const"]);
+    assert_eq!(text, "let e = 1, f = 2;\nf++;\nconsole.log(e, f);\n");
+  }
+
+  #[test]
+  fn test_fix_unused_imports() {
+    let (codes, text) = fix(
+      r#"import { a, b, c, d } from "./a.ts";
+import unused from "./b.ts";
+import * as ns from "./c.ts";
+import type { T } from "./d.ts";
+console.log(b, ns);
+"#,
+    );
+    assert!(codes.is_empty(), "{codes:?}");
+    // The modules of values are still imported for their side effects.
+    assert_eq!(
+      text,
+      r#"import { b } from "./a.ts";
+import "./b.ts";
+import * as ns from "./c.ts";
+console.log(b, ns);
+"#
+    );
+  }
+
+  #[test]
+  fn test_get_fixes() {
+    let linter = create_linter(MediaType::TypeScript, get_recommended_rules());
+    let (parsed_source, diagnostics) = linter
+      .lint(
+        "file:///mod.ts".to_string(),
+        "let a = 1;;\nfunction f() {}\nconsole.log(a);\n".to_string(),
+      )
+      .unwrap();
+    let fixes = get_fixes(&parsed_source, &diagnostics);
+    let mut codes_and_fixes = diagnostics
+      .iter()
+      .map(|d| d.code.as_str())
+      .zip(fixes)
+      .collect::<Vec<_>>();
+    codes_and_fixes.sort_by_key(|(code, _)| *code);
+    let edit = |range: Range<usize>, new_text: &str| LintFixEdit {
+      range,
+      new_text: new_text.to_string(),
+    };
+    assert_eq!(
+      codes_and_fixes,
+      vec![
+        ("no-extra-semi", vec![edit(10..11, "")]),
+        ("no-unused-vars", vec![]),
+        ("prefer-const", vec![edit(0..3, "const")]),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_fix_extra_semi() {
+    let (codes, text) = fix("function f() {};\nf();;\n");
+    assert!(codes.is_empty(), "{codes:?}");
+    assert_eq!(text, "function f() {}\nf();\n");
+  }
+
+  #[test]
+  fn test_apply_fix_edits_overlapping() {
+    let edit = |range: Range<usize>, new_text: &str| LintFixEdit {
+      range,
+      new_text: new_text.to_string(),
+    };
+    let text = apply_fix_edits(
+      "abcdef",
+      vec![
+        edit(4..5, "E"),
+        edit(0..2, ""),
+        edit(1..3, "X"),
+        edit(0..2, ""),
+      ],
+    );
+    assert_eq!(text, "cdEf");
+  }
+}
@@ -11,11 +11,14 @@ use crate::args::FilesConfig;
 use crate::args::LintOptions;
 use crate::args::LintReporterKind;
 use crate::args::LintRulesConfig;
+use crate::args::LintSeverity;
+use crate::args::LintSeverityConfig;
 use crate::colors;
 use crate::factory::CliFactory;
 use crate::tools::fmt::run_parallelized;
 use crate::util::file_watcher;
 use crate::util::file_watcher::ResolutionResult;
+use crate::util::fs::atomic_write_file;
 use crate::util::fs::FileCollector;
 use crate::util::path::is_supported_ext;
 use deno_ast::MediaType;
@@ -45,8 +48,17 @@ use std::sync::Mutex;
 
 use crate::cache::IncrementalCache;
 
+mod fix;
+mod plugins;
+
+use fix::LintFixEdit;
+pub use plugins::LintPluginHost;
+
 static STDIN_FILE_NAME: &str = "_stdin.ts";
 
+/// A diagnostic, with the edits that fix it.
+type FixableDiagnostic = (LintDiagnostic, Vec<LintFixEdit>);
+
 fn create_reporter(kind: LintReporterKind) -> Box<dyn LintReporter + Send> {
   match kind {
     LintReporterKind::Pretty => Box::new(PrettyLintReporter::new()),
@@ -55,10 +67,11 @@ fn create_reporter(kind: LintReporterKind) -> Box<dyn LintReporter + Send> {
   }
 }
 
+/// Lints the files of `lint_options`, returning whether problems were found.
 pub async fn lint(
-  cli_options: CliOptions,
+  cli_options: Arc<CliOptions>,
   lint_options: LintOptions,
-) -> Result<(), AnyError> {
+) -> Result<bool, AnyError> {
   // Try to get lint rules. If none were set use recommended rules.
   let lint_rules = get_configured_rules(lint_options.rules);
 
@@ -66,8 +79,15 @@ pub async fn lint(
     bail!("No rules have been configured")
   }
 
+  if lint_options.fix && lint_options.is_stdin {
+    return Err(generic_error(
+      "Lint fix on standard input is not supported.",
+    ));
+  }
+
   let files = lint_options.files;
   let reporter_kind = lint_options.reporter_kind;
+  let fix = lint_options.fix;
 
   let resolver = |changed: Option<Vec<PathBuf>>| {
     let files_changed = changed.is_some();
@@ -98,9 +118,22 @@ pub async fn lint(
   };
 
   let has_error = Arc::new(AtomicBool::new(false));
-  let factory = CliFactory::from_cli_options(Arc::new(cli_options));
+  let factory = CliFactory::from_cli_options(cli_options);
   let cli_options = factory.cli_options();
   let caches = factory.caches()?;
+  let maybe_plugin_host = if lint_options.plugins.is_empty() {
+    None
+  } else {
+    let worker_factory =
+      Arc::new(factory.create_cli_main_worker_factory().await?);
+    Some(Arc::new(
+      LintPluginHost::start(worker_factory, lint_options.plugins).await?,
+    ))
+  };
+  // the rules of plugins can change without their modules being linted, so
+  // the files are always linted again when there are plugins
+  let use_incremental_cache = maybe_plugin_host.is_none();
+  let lint_severity = Arc::new(lint_options.severity);
   let operation = |paths: Vec<PathBuf>| async {
     let incremental_cache = Arc::new(IncrementalCache::new(
       caches.lint_incremental_cache_db(),
@@ -122,17 +155,27 @@ pub async fn lint(
       let lint_rules = lint_rules.clone();
       let reporter_lock = reporter_lock.clone();
       let incremental_cache = incremental_cache.clone();
+      let maybe_plugin_host = maybe_plugin_host.clone();
+      let lint_severity = lint_severity.clone();
       move |file_path| {
         let file_text = fs::read_to_string(&file_path)?;
 
         // don't bother rechecking this file if it didn't have any diagnostics before
-        if incremental_cache.is_file_same(&file_path, &file_text) {
+        if use_incremental_cache
+          && incremental_cache.is_file_same(&file_path, &file_text)
+        {
           return Ok(());
         }
 
-        let r = lint_file(&file_path, file_text, lint_rules);
+        let r = lint_file(
+          &file_path,
+          file_text,
+          lint_rules,
+          maybe_plugin_host.as_deref(),
+          fix,
+        );
         if let Ok((file_diagnostics, file_text)) = &r {
-          if file_diagnostics.is_empty() {
+          if use_incremental_cache && file_diagnostics.is_empty() {
             // update the incremental cache if there were no diagnostics
             incremental_cache.update_file(&file_path, file_text)
           }
@@ -141,6 +184,7 @@ pub async fn lint(
         handle_lint_result(
           &file_path.to_string_lossy(),
           r,
+          &lint_severity,
           reporter_lock.clone(),
           has_error,
         );
@@ -172,10 +216,11 @@ pub async fn lint(
   } else {
     if lint_options.is_stdin {
       let reporter_lock = Arc::new(Mutex::new(create_reporter(reporter_kind)));
-      let r = lint_stdin(lint_rules);
+      let r = lint_stdin(lint_rules, maybe_plugin_host.as_deref()).await;
       handle_lint_result(
         STDIN_FILE_NAME,
         r,
+        &lint_severity,
         reporter_lock.clone(),
         has_error.clone(),
       );
@@ -191,13 +236,9 @@ pub async fn lint(
       debug!("Found {} files", target_files.len());
       operation(target_files).await?;
     };
-    let has_error = has_error.load(Ordering::Relaxed);
-    if has_error {
-      std::process::exit(1);
-    }
   }
 
-  Ok(())
+  Ok(has_error.load(Ordering::Relaxed))
 }
 
 fn collect_lint_files(files: &FilesConfig) -> Result<Vec<PathBuf>, AnyError> {
@@ -252,23 +293,64 @@ fn lint_file(
   file_path: &Path,
   source_code: String,
   lint_rules: Vec<&'static dyn LintRule>,
-) -> Result<(Vec<LintDiagnostic>, String), AnyError> {
+  maybe_plugin_host: Option<&LintPluginHost>,
+  fix: bool,
+) -> Result<(Vec<FixableDiagnostic>, String), AnyError> {
   let file_name = file_path.to_string_lossy().to_string();
   let media_type = MediaType::from_path(file_path);
 
   let linter = create_linter(media_type, lint_rules);
 
-  let (_, file_diagnostics) = linter.lint(file_name, source_code.clone())?;
+  let (parsed_source, mut file_diagnostics) = if fix {
+    let (parsed_source, file_diagnostics) =
+      fix::lint_and_fix(&linter, &file_name, media_type, source_code.clone())?;
+    let fixed_text = parsed_source.text_info().text_str();
+    if fixed_text != source_code {
+      // The file is replaced by a fixed copy, so that it's never left half
+      // written.
+      atomic_write_file(file_path, fixed_text, file_mode(file_path)?)?;
+    }
+    (parsed_source, file_diagnostics)
+  } else {
+    linter.lint(file_name, source_code)?
+  };
+  let fixes = fix::get_fixes(&parsed_source, &file_diagnostics);
+  let mut file_diagnostics =
+    file_diagnostics.into_iter().zip(fixes).collect::<Vec<_>>();
+  if let Some(plugin_host) = maybe_plugin_host {
+    file_diagnostics.extend(
+      plugin_host
+        .lint_blocking(&parsed_source)?
+        .into_iter()
+        .map(|d| (d, vec![])),
+    );
+  }
+
+  Ok((
+    file_diagnostics,
+    parsed_source.text_info().text_str().to_string(),
+  ))
+}
 
-  Ok((file_diagnostics, source_code))
+#[cfg(unix)]
+fn file_mode(file_path: &Path) -> Result<u32, AnyError> {
+  use std::os::unix::fs::PermissionsExt;
+  Ok(fs::metadata(file_path)?.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn file_mode(_file_path: &Path) -> Result<u32, AnyError> {
+  // The mode is only used on Unix.
+  Ok(0o644)
 }
 
 /// Lint stdin and write result to stdout.
 /// Treats input as TypeScript.
 /// Compatible with `--json` flag.
-fn lint_stdin(
+async fn lint_stdin(
   lint_rules: Vec<&'static dyn LintRule>,
-) -> Result<(Vec<LintDiagnostic>, String), AnyError> {
+  maybe_plugin_host: Option<&LintPluginHost>,
+) -> Result<(Vec<FixableDiagnostic>, String), AnyError> {
   let mut source_code = String::new();
   if stdin().read_to_string(&mut source_code).is_err() {
     return Err(generic_error("Failed to read from stdin"));
@@ -276,15 +358,28 @@ fn lint_stdin(
 
   let linter = create_linter(MediaType::TypeScript, lint_rules);
 
-  let (_, file_diagnostics) =
+  let (parsed_source, file_diagnostics) =
     linter.lint(STDIN_FILE_NAME.to_string(), source_code.clone())?;
+  let fixes = fix::get_fixes(&parsed_source, &file_diagnostics);
+  let mut file_diagnostics =
+    file_diagnostics.into_iter().zip(fixes).collect::<Vec<_>>();
+  if let Some(plugin_host) = maybe_plugin_host {
+    file_diagnostics.extend(
+      plugin_host
+        .lint(&parsed_source)
+        .await?
+        .into_iter()
+        .map(|d| (d, vec![])),
+    );
+  }
 
   Ok((file_diagnostics, source_code))
 }
 
 fn handle_lint_result(
   file_path: &str,
-  result: Result<(Vec<LintDiagnostic>, String), AnyError>,
+  result: Result<(Vec<FixableDiagnostic>, String), AnyError>,
+  lint_severity: &LintSeverityConfig,
   reporter_lock: Arc<Mutex<Box<dyn LintReporter + Send>>>,
   has_error: Arc<AtomicBool>,
 ) {
@@ -293,9 +388,20 @@ fn handle_lint_result(
   match result {
     Ok((mut file_diagnostics, source)) => {
       sort_diagnostics(&mut file_diagnostics);
-      for d in file_diagnostics.iter() {
-        has_error.store(true, Ordering::Relaxed);
-        reporter.visit_diagnostic(d, source.split('\n').collect());
+      for (d, fixes) in file_diagnostics.iter() {
+        let severity = lint_severity
+          .get(Path::new(file_path), &d.code)
+          .unwrap_or(LintSeverity::Error);
+        // warnings are shown, but don't fail the run
+        if severity == LintSeverity::Error {
+          has_error.store(true, Ordering::Relaxed);
+        }
+        reporter.visit_diagnostic(
+          d,
+          fixes,
+          severity,
+          source.split('\n').collect(),
+        );
       }
     }
     Err(err) => {
@@ -306,7 +412,13 @@ fn handle_lint_result(
 }
 
 trait LintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic, source_lines: Vec<&str>);
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    fixes: &[LintFixEdit],
+    severity: LintSeverity,
+    source_lines: Vec<&str>,
+  );
   fn visit_error(&mut self, file_path: &str, err: &AnyError);
   fn close(&mut self, check_count: usize);
 }
@@ -317,21 +429,59 @@ struct LintError {
   message: String,
 }
 
+/// Logs the number of problems that were found, and how many of them are
+/// warnings.
+fn log_problem_count(lint_count: u32, warning_count: u32) {
+  let problems = match lint_count {
+    0 => return,
+    1 => "Found 1 problem".to_string(),
+    n => format!("Found {n} problems"),
+  };
+  match warning_count {
+    0 => info!("{}", problems),
+    1 => info!("{} (1 warning)", problems),
+    n => info!("{} ({} warnings)", problems, n),
+  }
+}
+
 struct PrettyLintReporter {
   lint_count: u32,
+  warning_count: u32,
 }
 
 impl PrettyLintReporter {
   fn new() -> PrettyLintReporter {
-    PrettyLintReporter { lint_count: 0 }
+    PrettyLintReporter {
+      lint_count: 0,
+      warning_count: 0,
+    }
   }
 }
 
 impl LintReporter for PrettyLintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic, source_lines: Vec<&str>) {
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    _fixes: &[LintFixEdit],
+    severity: LintSeverity,
+    source_lines: Vec<&str>,
+  ) {
     self.lint_count += 1;
 
-    let pretty_message = format!("({}) {}", colors::red(&d.code), &d.message);
+    let pretty_message = match severity {
+      LintSeverity::Error => {
+        format!("({}) {}", colors::red(&d.code), &d.message)
+      }
+      LintSeverity::Warn => {
+        self.warning_count += 1;
+        format!(
+          "{} ({}) {}",
+          colors::yellow("warning"),
+          colors::yellow(&d.code),
+          &d.message
+        )
+      }
+    };
 
     let message = format_diagnostic(
       &d.code,
@@ -358,11 +508,7 @@ impl LintReporter for PrettyLintReporter {
   }
 
   fn close(&mut self, check_count: usize) {
-    match self.lint_count {
-      1 => info!("Found 1 problem"),
-      n if n > 1 => info!("Found {} problems", self.lint_count),
-      _ => (),
-    }
+    log_problem_count(self.lint_count, self.warning_count);
 
     match check_count {
       n if n <= 1 => info!("Checked {} file", n),
@@ -374,23 +520,41 @@ impl LintReporter for PrettyLintReporter {
 
 struct CompactLintReporter {
   lint_count: u32,
+  warning_count: u32,
 }
 
 impl CompactLintReporter {
   fn new() -> CompactLintReporter {
-    CompactLintReporter { lint_count: 0 }
+    CompactLintReporter {
+      lint_count: 0,
+      warning_count: 0,
+    }
   }
 }
 
 impl LintReporter for CompactLintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic, _source_lines: Vec<&str>) {
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    _fixes: &[LintFixEdit],
+    severity: LintSeverity,
+    _source_lines: Vec<&str>,
+  ) {
     self.lint_count += 1;
+    let warning = match severity {
+      LintSeverity::Error => "",
+      LintSeverity::Warn => {
+        self.warning_count += 1;
+        "warning: "
+      }
+    };
 
     eprintln!(
-      "{}: line {}, col {} - {} ({})",
+      "{}: line {}, col {} - {}{} ({})",
       d.filename,
       d.range.start.line_index + 1,
       d.range.start.column_index + 1,
+      warning,
       d.message,
       d.code
     )
@@ -402,11 +566,7 @@ impl LintReporter for CompactLintReporter {
   }
 
   fn close(&mut self, check_count: usize) {
-    match self.lint_count {
-      1 => info!("Found 1 problem"),
-      n if n > 1 => info!("Found {} problems", self.lint_count),
-      _ => (),
-    }
+    log_problem_count(self.lint_count, self.warning_count);
 
     match check_count {
       n if n <= 1 => info!("Checked {} file", n),
@@ -463,11 +623,17 @@ pub fn format_diagnostic(
   } else {
     "".to_string()
   };
-  let help = format!(
-    "    {} for further information visit https://lint.deno.land/#{}",
-    colors::cyan("help:"),
-    diagnostic_code
-  );
+  // the rules of plugins, which are prefixed with the name of the plugin,
+  // aren't documented on the website
+  let help = if diagnostic_code.contains('/') {
+    "".to_string()
+  } else {
+    format!(
+      "    {} for further information visit https://lint.deno.land/#{}",
+      colors::cyan("help:"),
+      diagnostic_code
+    )
+  };
 
   format!(
     "{message_line}\n{snippets}\n    at {formatted_location}\n\n{hint}{help}",
@@ -479,9 +645,19 @@ pub fn format_diagnostic(
   )
 }
 
+#[derive(Serialize)]
+struct JsonLintDiagnostic {
+  #[serde(flatten)]
+  diagnostic: LintDiagnostic,
+  severity: LintSeverity,
+  /// The edits that fix the diagnostic, with byte ranges.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  fixes: Vec<LintFixEdit>,
+}
+
 #[derive(Serialize)]
 struct JsonLintReporter {
-  diagnostics: Vec<LintDiagnostic>,
+  diagnostics: Vec<JsonLintDiagnostic>,
   errors: Vec<LintError>,
 }
 
@@ -495,8 +671,18 @@ impl JsonLintReporter {
 }
 
 impl LintReporter for JsonLintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic, _source_lines: Vec<&str>) {
-    self.diagnostics.push(d.clone());
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    fixes: &[LintFixEdit],
+    severity: LintSeverity,
+    _source_lines: Vec<&str>,
+  ) {
+    self.diagnostics.push(JsonLintDiagnostic {
+      diagnostic: d.clone(),
+      severity,
+      fixes: fixes.to_vec(),
+    });
   }
 
   fn visit_error(&mut self, file_path: &str, err: &AnyError) {
@@ -507,31 +693,37 @@ impl LintReporter for JsonLintReporter {
   }
 
   fn close(&mut self, _check_count: usize) {
-    sort_diagnostics(&mut self.diagnostics);
+    self
+      .diagnostics
+      .sort_by(|a, b| compare_diagnostics(&a.diagnostic, &b.diagnostic));
     let json = serde_json::to_string_pretty(&self);
     println!("{}", json.unwrap());
   }
 }
 
-fn sort_diagnostics(diagnostics: &mut [LintDiagnostic]) {
+fn sort_diagnostics(diagnostics: &mut [FixableDiagnostic]) {
   // Sort so that we guarantee a deterministic output which is useful for tests
-  diagnostics.sort_by(|a, b| {
-    use std::cmp::Ordering;
-    let file_order = a.filename.cmp(&b.filename);
-    match file_order {
-      Ordering::Equal => {
-        let line_order =
-          a.range.start.line_index.cmp(&b.range.start.line_index);
-        match line_order {
-          Ordering::Equal => {
-            a.range.start.column_index.cmp(&b.range.start.column_index)
-          }
-          _ => line_order,
+  diagnostics.sort_by(|(a, _), (b, _)| compare_diagnostics(a, b));
+}
+
+fn compare_diagnostics(
+  a: &LintDiagnostic,
+  b: &LintDiagnostic,
+) -> std::cmp::Ordering {
+  use std::cmp::Ordering;
+  let file_order = a.filename.cmp(&b.filename);
+  match file_order {
+    Ordering::Equal => {
+      let line_order = a.range.start.line_index.cmp(&b.range.start.line_index);
+      match line_order {
+        Ordering::Equal => {
+          a.range.start.column_index.cmp(&b.range.start.column_index)
         }
+        _ => line_order,
       }
-      _ => file_order,
     }
-  });
+    _ => file_order,
+  }
 }
 
 pub fn get_configured_rules(
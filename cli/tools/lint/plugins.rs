@@ -0,0 +1,308 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The lint rules of plugins, which are the modules listed in `lint.plugins`
+//! of the configuration file. A plugin module exports by default an object
+//! with the `name` of the plugin and its `rules`, and each rule is run on the
+//! SWC AST of every linted module. The plugins run in isolates of their own,
+//! which have no permissions.
+
+use crate::worker::CliMainWorkerFactory;
+
+use deno_ast::ParsedSource;
+use deno_ast::SourceTextInfo;
+use deno_core::anyhow::anyhow;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::serde_v8;
+use deno_core::v8;
+use deno_core::ModuleSpecifier;
+use deno_lint::diagnostic::LintDiagnostic;
+use deno_lint::diagnostic::Position;
+use deno_lint::diagnostic::Range;
+use deno_runtime::permissions::Permissions;
+use deno_runtime::permissions::PermissionsContainer;
+use deno_runtime::permissions::PermissionsOptions;
+use deno_runtime::tokio_util::run_local;
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+
+/// A diagnostic reported by a rule of a plugin, with the span of the SWC AST.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginDiagnostic {
+  code: String,
+  message: String,
+  hint: Option<String>,
+  start: u32,
+  end: u32,
+}
+
+struct LintPluginRequest {
+  file_name: String,
+  source_text: Arc<str>,
+  program: serde_json::Value,
+  response: oneshot::Sender<Result<Vec<PluginDiagnostic>, AnyError>>,
+}
+
+/// The isolate of a plugin. It lints the modules that are sent to it one at
+/// a time, and stops when it is dropped.
+struct LintPlugin {
+  specifier: ModuleSpecifier,
+  sender: mpsc::UnboundedSender<LintPluginRequest>,
+}
+
+/// The plugins of a lint run, which are shared by the threads that lint the
+/// modules.
+pub struct LintPluginHost {
+  plugins: Vec<LintPlugin>,
+}
+
+impl LintPluginHost {
+  /// Loads the plugin modules, each in an isolate of its own.
+  pub async fn start(
+    worker_factory: Arc<CliMainWorkerFactory>,
+    specifiers: Vec<ModuleSpecifier>,
+  ) -> Result<Self, AnyError> {
+    let mut plugins = Vec::with_capacity(specifiers.len());
+    for specifier in specifiers {
+      let sender = start_plugin(worker_factory.clone(), specifier.clone())
+        .await
+        .with_context(|| {
+          format!("Failed to load the lint plugin {specifier}")
+        })?;
+      plugins.push(LintPlugin { specifier, sender });
+    }
+    Ok(Self { plugins })
+  }
+
+  /// Runs the rules of the plugins on a module.
+  pub async fn lint(
+    &self,
+    parsed_source: &ParsedSource,
+  ) -> Result<Vec<LintDiagnostic>, AnyError> {
+    let mut diagnostics = vec![];
+    for (plugin, receiver) in self.send_requests(parsed_source)? {
+      let result = receiver.await.map_err(|_| plugin_stopped(plugin))?;
+      diagnostics.extend(to_lint_diagnostics(parsed_source, plugin, result?)?);
+    }
+    Ok(diagnostics)
+  }
+
+  /// Like [`LintPluginHost::lint`], but blocks the thread, which must not be
+  /// a thread of an async runtime.
+  pub fn lint_blocking(
+    &self,
+    parsed_source: &ParsedSource,
+  ) -> Result<Vec<LintDiagnostic>, AnyError> {
+    let mut diagnostics = vec![];
+    for (plugin, receiver) in self.send_requests(parsed_source)? {
+      let result = receiver
+        .blocking_recv()
+        .map_err(|_| plugin_stopped(plugin))?;
+      diagnostics.extend(to_lint_diagnostics(parsed_source, plugin, result?)?);
+    }
+    Ok(diagnostics)
+  }
+
+  #[allow(clippy::type_complexity)]
+  fn send_requests(
+    &self,
+    parsed_source: &ParsedSource,
+  ) -> Result<
+    Vec<(
+      &LintPlugin,
+      oneshot::Receiver<Result<Vec<PluginDiagnostic>, AnyError>>,
+    )>,
+    AnyError,
+  > {
+    let program = serde_json::to_value(parsed_source.program_ref())?;
+    let source_text = parsed_source.text_info().text();
+    let mut receivers = Vec::with_capacity(self.plugins.len());
+    for plugin in &self.plugins {
+      let (response, receiver) = oneshot::channel();
+      plugin
+        .sender
+        .send(LintPluginRequest {
+          file_name: parsed_source.specifier().to_string(),
+          source_text: source_text.clone(),
+          program: program.clone(),
+          response,
+        })
+        .map_err(|_| plugin_stopped(plugin))?;
+      receivers.push((plugin, receiver));
+    }
+    Ok(receivers)
+  }
+}
+
+fn plugin_stopped(plugin: &LintPlugin) -> AnyError {
+  anyhow!("The lint plugin {} stopped.", plugin.specifier)
+}
+
+/// Starts the isolate of a plugin, and returns once its module is loaded.
+async fn start_plugin(
+  worker_factory: Arc<CliMainWorkerFactory>,
+  specifier: ModuleSpecifier,
+) -> Result<mpsc::UnboundedSender<LintPluginRequest>, AnyError> {
+  let (sender, mut receiver) = mpsc::unbounded_channel::<LintPluginRequest>();
+  let (ready_sender, ready_receiver) = oneshot::channel();
+  let handle = tokio::task::spawn_blocking(move || {
+    run_local(async move {
+      let permissions = PermissionsContainer::new(Permissions::from_options(
+        &PermissionsOptions::default(),
+      )?);
+      let mut worker = worker_factory
+        .create_main_worker(specifier.clone(), permissions)
+        .await?;
+      let namespace = worker
+        .execute_side_module_namespace_possibly_with_npm()
+        .await?;
+      let mut worker = worker.into_main_worker();
+      let run_lint_plugin = worker.js_runtime.execute_script_static(
+        "ext:cli/lint_plugins.js",
+        "Deno[Deno.internal].runLintPlugin",
+      )?;
+      let (plugin, run_lint_plugin) = {
+        let scope = &mut worker.js_runtime.handle_scope();
+        let namespace = v8::Local::new(scope, namespace);
+        let key = v8::String::new(scope, "default").unwrap();
+        let plugin = namespace.get(scope, key.into()).ok_or_else(|| {
+          anyhow!(
+            "The module {specifier} must export a lint plugin by default."
+          )
+        })?;
+        let run_lint_plugin = v8::Local::new(scope, run_lint_plugin);
+        let run_lint_plugin =
+          v8::Local::<v8::Function>::try_from(run_lint_plugin)?;
+        (
+          v8::Global::new(scope, plugin),
+          v8::Global::new(scope, run_lint_plugin),
+        )
+      };
+      let _ = ready_sender.send(());
+
+      while let Some(request) = receiver.recv().await {
+        let result = async {
+          let args = {
+            let scope = &mut worker.js_runtime.handle_scope();
+            let file_name = serde_v8::to_v8(scope, &request.file_name)?;
+            let source_text = serde_v8::to_v8(scope, &*request.source_text)?;
+            let program = serde_v8::to_v8(scope, &request.program)?;
+            [
+              plugin.clone(),
+              v8::Global::new(scope, file_name),
+              v8::Global::new(scope, source_text),
+              v8::Global::new(scope, program),
+            ]
+          };
+          let value = worker
+            .js_runtime
+            .call_with_args_and_await(&run_lint_plugin, &args)
+            .await?;
+          let scope = &mut worker.js_runtime.handle_scope();
+          let value = v8::Local::new(scope, value);
+          let diagnostics = serde_v8::from_v8(scope, value)?;
+          Ok::<Vec<PluginDiagnostic>, AnyError>(diagnostics)
+        }
+        .await;
+        let _ = request.response.send(result);
+      }
+      Ok::<_, AnyError>(())
+    })
+  });
+
+  match ready_receiver.await {
+    Ok(()) => Ok(sender),
+    Err(_) => {
+      handle.await??;
+      bail!("The lint plugin stopped before it was loaded.")
+    }
+  }
+}
+
+fn to_lint_diagnostics(
+  parsed_source: &ParsedSource,
+  plugin: &LintPlugin,
+  diagnostics: Vec<PluginDiagnostic>,
+) -> Result<Vec<LintDiagnostic>, AnyError> {
+  let text_info = parsed_source.text_info();
+  diagnostics
+    .into_iter()
+    .map(|d| {
+      let range = to_range(text_info, d.start, d.end).ok_or_else(|| {
+        anyhow!(
+          "The rule \"{}\" of the lint plugin {} reported a span outside of {}.",
+          d.code,
+          plugin.specifier,
+          parsed_source.specifier()
+        )
+      })?;
+      Ok(LintDiagnostic {
+        range,
+        filename: parsed_source.specifier().to_string(),
+        message: d.message,
+        code: d.code,
+        hint: d.hint,
+      })
+    })
+    .collect()
+}
+
+/// Converts a span of the SWC AST of a module to a range of its text.
+fn to_range(text_info: &SourceTextInfo, start: u32, end: u32) -> Option<Range> {
+  let start_pos = text_info.range().start;
+  let offset = start_pos.as_byte_pos().0;
+  let len = text_info.text_str().len();
+  let to_position = |pos: u32| {
+    let byte_index = pos.checked_sub(offset)? as usize;
+    if byte_index > len || !text_info.text_str().is_char_boundary(byte_index) {
+      return None;
+    }
+    let loc = text_info.line_and_column_index(start_pos + byte_index);
+    Some(Position {
+      line_index: loc.line_index,
+      column_index: loc.column_index,
+      byte_index,
+    })
+  };
+  if start > end {
+    return None;
+  }
+  Some(Range {
+    start: to_position(start)?,
+    end: to_position(end)?,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use deno_ast::MediaType;
+
+  #[test]
+  fn test_to_range() {
+    let parsed_source = deno_ast::parse_module(deno_ast::ParseParams {
+      specifier: "file:///mod.ts".to_string(),
+      text_info: SourceTextInfo::from_string(
+        "const a = 1;\nconst b = 2;\n".to_string(),
+      ),
+      media_type: MediaType::TypeScript,
+      capture_tokens: false,
+      scope_analysis: false,
+      maybe_syntax: None,
+    })
+    .unwrap();
+    let text_info = parsed_source.text_info();
+    let offset = text_info.range().start.as_byte_pos().0;
+    let range = to_range(text_info, offset + 19, offset + 24).unwrap();
+    assert_eq!((range.start.line_index, range.start.column_index), (1, 6));
+    assert_eq!((range.end.line_index, range.end.column_index), (1, 11));
+    assert_eq!(range.start.byte_index, 19);
+    assert!(to_range(text_info, offset + 24, offset + 19).is_none());
+    assert!(to_range(text_info, offset, offset + 100).is_none());
+  }
+}
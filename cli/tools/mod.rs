@@ -10,6 +10,7 @@ pub mod info;
 pub mod init;
 pub mod installer;
 pub mod lint;
+pub mod permissions;
 pub mod repl;
 pub mod run;
 pub mod standalone;
@@ -0,0 +1,39 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use crate::args::Flags;
+use crate::args::PermissionsFlags;
+use crate::factory::CliFactory;
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_runtime::permissions::remembered_grants;
+
+/// Lists or clears the permission prompt decisions that are remembered for
+/// the project.
+pub async fn permissions(
+  flags: Flags,
+  permissions_flags: PermissionsFlags,
+) -> Result<(), AnyError> {
+  let factory = CliFactory::from_flags(flags).await?;
+  let Some(path) = factory.permission_prompt_store_path()? else {
+    bail!("No configuration file or script was found to remember the permission prompt decisions of.");
+  };
+
+  if permissions_flags.clear {
+    match std::fs::remove_file(&path) {
+      Ok(()) => {}
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+      Err(err) => return Err(err.into()),
+    }
+    log::info!("Cleared the remembered permission prompt decisions.");
+    return Ok(());
+  }
+
+  let grants = remembered_grants(&path);
+  if grants.is_empty() {
+    log::info!("No permission prompt decisions are remembered.");
+  }
+  for grant in grants {
+    println!("{}", grant.message);
+  }
+  Ok(())
+}
@@ -48,7 +48,7 @@ To grant permissions, set them before the script argument. For example:
   let main_module = cli_options.resolve_main_module()?;
 
   let permissions = PermissionsContainer::new(Permissions::from_options(
-    &cli_options.permissions_options(),
+    &cli_options.permissions_options()?,
   )?);
   let worker_factory = factory.create_cli_main_worker_factory().await?;
   let mut worker = worker_factory
@@ -67,7 +67,7 @@ pub async fn run_from_stdin(flags: Flags) -> Result<i32, AnyError> {
   let worker_factory = factory.create_cli_main_worker_factory().await?;
 
   let permissions = PermissionsContainer::new(Permissions::from_options(
-    &cli_options.permissions_options(),
+    &cli_options.permissions_options()?,
   )?);
   let mut source = Vec::new();
   std::io::stdin().read_to_end(&mut source)?;
@@ -109,7 +109,7 @@ async fn run_with_watch(flags: Flags) -> Result<i32, AnyError> {
   let operation = |main_module: ModuleSpecifier| {
     file_watcher.reset();
     let permissions = PermissionsContainer::new(Permissions::from_options(
-      &cli_options.permissions_options(),
+      &cli_options.permissions_options()?,
     )?);
     let create_cli_main_worker_factory = create_cli_main_worker_factory.clone();
 
@@ -148,7 +148,7 @@ pub async fn eval_command(
 
   let main_module = cli_options.resolve_main_module()?;
   let permissions = PermissionsContainer::new(Permissions::from_options(
-    &cli_options.permissions_options(),
+    &cli_options.permissions_options()?,
   )?);
   // Create a dummy source file.
   let source_code = if eval_flags.print {
@@ -2,20 +2,31 @@
 
 use crate::args::CompileFlags;
 use crate::args::Flags;
+use crate::cache::ParsedSourceCache;
 use crate::factory::CliFactory;
 use crate::graph_util::error_for_any_npm_specifier;
 use crate::standalone::is_standalone_binary;
+use crate::standalone::sign_executable;
 use crate::standalone::DenoCompileBinaryWriter;
 use crate::util::path::path_has_trailing_slash;
+use crate::util::path::specifier_to_file_path;
+use deno_ast::swc::ast;
+use deno_ast::swc::visit::Visit;
+use deno_ast::swc::visit::VisitWith;
+use deno_ast::MediaType;
+use deno_ast::ModuleSpecifier;
 use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
+use deno_core::normalize_path;
 use deno_core::resolve_url_or_path;
+use deno_graph::ModuleGraph;
 use deno_runtime::colors;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use walkdir::WalkDir;
 
 use super::installer::infer_name_from_url;
 
@@ -34,12 +45,12 @@ pub async fn compile(
   let binary_writer =
     DenoCompileBinaryWriter::new(file_fetcher, http_client, deno_dir);
   let module_specifier = cli_options.resolve_main_module()?;
+  let (side_modules, asset_paths) =
+    resolve_includes(&compile_flags.include, cli_options.initial_cwd())?;
   let module_roots = {
-    let mut vec = Vec::with_capacity(compile_flags.include.len() + 1);
+    let mut vec = Vec::with_capacity(side_modules.len() + 1);
     vec.push(module_specifier.clone());
-    for side_module in &compile_flags.include {
-      vec.push(resolve_url_or_path(side_module, cli_options.initial_cwd())?);
-    }
+    vec.extend(side_modules);
     vec
   };
 
@@ -49,12 +60,22 @@ pub async fn compile(
   )
   .await?;
 
-  let graph = Arc::try_unwrap(
-    module_graph_builder
-      .create_graph_and_maybe_check(module_roots)
-      .await?,
-  )
-  .unwrap();
+  // the main modules of workers are added to the roots until the graph has
+  // all of them, because they are only known once the modules are parsed
+  let mut module_roots = module_roots;
+  let graph = loop {
+    let graph = module_graph_builder
+      .create_graph_and_maybe_check(module_roots.clone())
+      .await?;
+    let worker_roots = collect_worker_specifiers(&graph, parsed_source_cache)
+      .into_iter()
+      .filter(|s| graph.get(s).is_none() && !module_roots.contains(s))
+      .collect::<Vec<_>>();
+    if worker_roots.is_empty() {
+      break Arc::try_unwrap(graph).unwrap();
+    }
+    module_roots.extend(worker_roots);
+  };
 
   // at the moment, we don't support npm specifiers in deno_compile, so show an error
   error_for_any_npm_specifier(&graph)?;
@@ -76,6 +97,7 @@ pub async fn compile(
       &mut file,
       eszip,
       &module_specifier,
+      &asset_paths,
       &compile_flags,
       cli_options,
     )
@@ -88,12 +110,182 @@ pub async fn compile(
   {
     use std::os::unix::fs::PermissionsExt;
     let perms = std::fs::Permissions::from_mode(0o777);
-    std::fs::set_permissions(output_path, perms)?;
+    std::fs::set_permissions(&output_path, perms)?;
+  }
+
+  if let Some(sign_flags) = &compile_flags.sign {
+    log::info!("{} {}", colors::green("Sign"), output_path.display());
+    sign_executable(&output_path, compile_flags.target.as_deref(), sign_flags)
+      .with_context(|| format!("Signing {}", output_path.display()))?;
   }
 
   Ok(())
 }
 
+/// Splits the values of `--include` into the additional modules of the module
+/// graph and the paths of the files that are embedded as assets. The files
+/// that match a glob pattern with the extension of a module, like
+/// "plugins/*.ts", are modules. Directories, the files that match the other
+/// glob patterns and the files that aren't modules are assets.
+fn resolve_includes(
+  include: &[String],
+  cwd: &Path,
+) -> Result<(Vec<ModuleSpecifier>, Vec<PathBuf>), AnyError> {
+  let mut side_modules = Vec::new();
+  let mut asset_paths = Vec::new();
+  for value in include {
+    if is_glob_pattern(value) {
+      let pattern = cwd.join(value);
+      let paths = glob::glob(&pattern.to_string_lossy())
+        .with_context(|| format!("Invalid glob pattern \"{value}\""))?;
+      let is_module_pattern = is_module_path(&pattern);
+      let mut matched = false;
+      for path in paths {
+        let path = normalize_path(path?);
+        if !path.is_file() {
+          continue;
+        }
+        if is_module_pattern {
+          side_modules.push(ModuleSpecifier::from_file_path(&path).map_err(
+            |_| generic_error(format!("Invalid path \"{}\"", path.display())),
+          )?);
+        } else {
+          asset_paths.push(path);
+        }
+        matched = true;
+      }
+      if !matched {
+        bail!("No files match the pattern \"{value}\" of --include.");
+      }
+      continue;
+    }
+
+    let specifier = resolve_url_or_path(value, cwd)?;
+    if specifier.scheme() != "file" {
+      side_modules.push(specifier);
+      continue;
+    }
+    let path = specifier_to_file_path(&specifier)?;
+    if path.is_dir() {
+      for entry in WalkDir::new(&path).sort_by_file_name() {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+          asset_paths.push(normalize_path(entry.path()));
+        }
+      }
+    } else if !is_module_path(&path) {
+      if !path.is_file() {
+        bail!("The asset \"{}\" does not exist.", path.display());
+      }
+      asset_paths.push(normalize_path(path));
+    } else {
+      side_modules.push(specifier);
+    }
+  }
+  asset_paths.sort();
+  asset_paths.dedup();
+  Ok((side_modules, asset_paths))
+}
+
+/// The main modules of the workers that the modules of the graph start with
+/// `new Worker(new URL("./worker.ts", import.meta.url))`.
+fn collect_worker_specifiers(
+  graph: &ModuleGraph,
+  parsed_source_cache: &ParsedSourceCache,
+) -> Vec<ModuleSpecifier> {
+  let mut specifiers = Vec::new();
+  for module in graph.modules() {
+    let Some(module) = module.esm() else {
+      continue;
+    };
+    let Ok(parsed_source) =
+      parsed_source_cache.get_parsed_source_from_esm_module(module)
+    else {
+      continue;
+    };
+    let mut collector = WorkerCollector {
+      referrer: &module.specifier,
+      specifiers: &mut specifiers,
+    };
+    parsed_source.program_ref().visit_with(&mut collector);
+  }
+  specifiers
+}
+
+struct WorkerCollector<'a> {
+  referrer: &'a ModuleSpecifier,
+  specifiers: &'a mut Vec<ModuleSpecifier>,
+}
+
+impl Visit for WorkerCollector<'_> {
+  fn visit_new_expr(&mut self, node: &ast::NewExpr) {
+    node.visit_children_with(self);
+    let ast::Expr::Ident(callee) = node.callee.as_ref() else {
+      return;
+    };
+    if &*callee.sym != "Worker" {
+      return;
+    }
+    let Some(url) = node.args.as_ref().and_then(|args| args.first()) else {
+      return;
+    };
+    if let Some(specifier) = import_meta_url_arg(&url.expr) {
+      if let Ok(specifier) = self.referrer.join(&specifier) {
+        self.specifiers.push(specifier);
+      }
+    }
+  }
+}
+
+/// The specifier of `new URL("<specifier>", import.meta.url)`, or of its
+/// `href`.
+fn import_meta_url_arg(expr: &ast::Expr) -> Option<String> {
+  let new_expr = match expr {
+    ast::Expr::New(new_expr) => new_expr,
+    ast::Expr::Member(ast::MemberExpr {
+      obj,
+      prop: ast::MemberProp::Ident(prop),
+      ..
+    }) if &*prop.sym == "href" => match obj.as_ref() {
+      ast::Expr::New(new_expr) => new_expr,
+      _ => return None,
+    },
+    _ => return None,
+  };
+  match new_expr.callee.as_ref() {
+    ast::Expr::Ident(ident) if &*ident.sym == "URL" => {}
+    _ => return None,
+  }
+  let args = new_expr.args.as_ref()?;
+  let [specifier, base] = args.as_slice() else {
+    return None;
+  };
+  let ast::Expr::Lit(ast::Lit::Str(specifier)) = specifier.expr.as_ref() else {
+    return None;
+  };
+  let ast::Expr::Member(base) = base.expr.as_ref() else {
+    return None;
+  };
+  match (base.obj.as_ref(), &base.prop) {
+    (
+      ast::Expr::MetaProp(ast::MetaPropExpr {
+        kind: ast::MetaPropKind::ImportMeta,
+        ..
+      }),
+      ast::MemberProp::Ident(prop),
+    ) if &*prop.sym == "url" => Some(specifier.value.to_string()),
+    _ => None,
+  }
+}
+
+fn is_module_path(path: &Path) -> bool {
+  MediaType::from_path(path) != MediaType::Unknown
+}
+
+fn is_glob_pattern(value: &str) -> bool {
+  value.contains(['*', '?', '['])
+}
+
 /// This function writes out a final binary to specified path. If output path
 /// is not already standalone binary it will return error instead.
 fn validate_output_path(output_path: &Path) -> Result<(), AnyError> {
@@ -212,6 +404,8 @@ mod test {
         args: Vec::new(),
         target: Some("x86_64-unknown-linux-gnu".to_string()),
         include: vec![],
+        sign: None,
+        lite: false,
       },
       &std::env::current_dir().unwrap(),
     )
@@ -233,6 +427,8 @@ mod test {
         args: Vec::new(),
         target: Some("x86_64-pc-windows-msvc".to_string()),
         include: vec![],
+        sign: None,
+        lite: false,
       },
       &std::env::current_dir().unwrap(),
     )
@@ -241,6 +437,45 @@ mod test {
     assert_eq!(path.file_name().unwrap(), "file.exe");
   }
 
+  #[test]
+  fn test_worker_collector() {
+    let referrer = ModuleSpecifier::parse("file:///app/main.ts").unwrap();
+    let parsed_source = deno_ast::parse_module(deno_ast::ParseParams {
+      specifier: referrer.to_string(),
+      text_info: deno_ast::SourceTextInfo::from_string(
+        r#"
+new Worker(new URL("./worker.ts", import.meta.url), { type: "module" });
+function start() {
+  return new Worker(new URL("../lib/other.js", import.meta.url).href);
+}
+new Worker(new URL("./ignored.ts", location.href));
+new Worker(new URL(`./${name}.ts`, import.meta.url));
+new SharedWorker(new URL("./shared.ts", import.meta.url));
+"#
+        .to_string(),
+      ),
+      media_type: MediaType::TypeScript,
+      capture_tokens: false,
+      scope_analysis: false,
+      maybe_syntax: None,
+    })
+    .unwrap();
+    let mut specifiers = Vec::new();
+    parsed_source
+      .program_ref()
+      .visit_with(&mut WorkerCollector {
+        referrer: &referrer,
+        specifiers: &mut specifiers,
+      });
+    assert_eq!(
+      specifiers,
+      vec![
+        ModuleSpecifier::parse("file:///app/worker.ts").unwrap(),
+        ModuleSpecifier::parse("file:///lib/other.js").unwrap(),
+      ]
+    );
+  }
+
   #[test]
   fn test_os_specific_file_path() {
     fn run_test(path: &str, target: Option<&str>, expected: &str) {
@@ -2,7 +2,9 @@
 
 use crate::args::CliOptions;
 use crate::args::Flags;
+use crate::args::TaskDefinition;
 use crate::args::TaskFlags;
+use crate::args::WorkspaceMember;
 use crate::colors;
 use crate::factory::CliFactory;
 use crate::npm::CliNpmResolver;
@@ -12,13 +14,20 @@ use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_core::futures;
 use deno_core::futures::future::LocalBoxFuture;
+use deno_core::futures::stream::FuturesUnordered;
+use deno_core::futures::StreamExt;
 use deno_runtime::deno_node::NodeResolver;
 use deno_semver::npm::NpmPackageNv;
 use deno_task_shell::ExecuteResult;
 use deno_task_shell::ShellCommand;
 use deno_task_shell::ShellCommandContext;
+use deno_task_shell::ShellPipeReader;
+use deno_task_shell::ShellState;
 use indexmap::IndexMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
 
@@ -35,15 +44,33 @@ pub async fn execute_script(
     .and_then(|p| p.scripts.clone())
     .unwrap_or_default();
 
+  // the tasks of the workspace members can be run from the root of the
+  // workspace, in every member that has the task
+  let workspace_tasks = cli_options
+    .workspace_members()
+    .iter()
+    .map(|member| Ok((member, member.config_file.resolve_tasks_config()?)))
+    .collect::<Result<Vec<_>, AnyError>>()?;
+
   let task_name = match &task_flags.task {
     Some(task) => task,
     None => {
-      print_available_tasks(&tasks_config, &package_json_scripts);
+      print_available_tasks(
+        &tasks_config,
+        &workspace_tasks,
+        &package_json_scripts,
+      );
       return Ok(1);
     }
   };
 
-  if let Some(script) = tasks_config.get(task_name) {
+  let members_with_task = workspace_tasks
+    .iter()
+    .filter(|(_, member_tasks_config)| {
+      member_tasks_config.contains_key(task_name)
+    })
+    .collect::<Vec<_>>();
+  if tasks_config.contains_key(task_name) {
     let config_file_url = cli_options.maybe_config_file_specifier().unwrap();
     let config_file_path = if config_file_url.scheme() == "file" {
       config_file_url.to_file_path().unwrap()
@@ -54,15 +81,34 @@ pub async fn execute_script(
       Some(path) => canonicalize_path(&PathBuf::from(path))?,
       None => config_file_path.parent().unwrap().to_owned(),
     };
-    let script = get_script_with_args(script, cli_options);
-    output_task(task_name, &script);
-    let seq_list = deno_task_shell::parser::parse(&script)
-      .with_context(|| format!("Error parsing script '{task_name}'."))?;
-    let env_vars = collect_env_vars();
-    let exit_code =
-      deno_task_shell::execute(seq_list, env_vars, &cwd, Default::default())
-        .await;
-    Ok(exit_code)
+    run_config_task(
+      &tasks_config,
+      task_name,
+      &cwd,
+      task_flags.parallel,
+      cli_options,
+    )
+    .await
+  } else if !members_with_task.is_empty() {
+    for (member, member_tasks_config) in members_with_task {
+      log::info!(
+        "{} {}",
+        colors::green("Workspace member"),
+        colors::cyan(get_member_label(member)),
+      );
+      let exit_code = run_config_task(
+        member_tasks_config,
+        task_name,
+        &member.dir,
+        task_flags.parallel,
+        cli_options,
+      )
+      .await?;
+      if exit_code != 0 {
+        return Ok(exit_code);
+      }
+    }
+    Ok(0)
   } else if let Some(script) = package_json_scripts.get(task_name) {
     let package_json_deps_installer =
       factory.package_json_deps_installer().await?;
@@ -107,17 +153,248 @@ pub async fn execute_script(
     let seq_list = deno_task_shell::parser::parse(&script)
       .with_context(|| format!("Error parsing script '{task_name}'."))?;
     let npx_commands = resolve_npm_commands(npm_resolver, node_resolver)?;
-    let env_vars = collect_env_vars();
+    let env_vars = collect_env_vars(cli_options);
     let exit_code =
       deno_task_shell::execute(seq_list, env_vars, &cwd, npx_commands).await;
     Ok(exit_code)
   } else {
     eprintln!("Task not found: {task_name}");
-    print_available_tasks(&tasks_config, &package_json_scripts);
+    print_available_tasks(
+      &tasks_config,
+      &workspace_tasks,
+      &package_json_scripts,
+    );
     Ok(1)
   }
 }
 
+/// Runs a task of a configuration file after the tasks it depends on.
+async fn run_config_task(
+  tasks_config: &IndexMap<String, TaskDefinition>,
+  task_name: &str,
+  cwd: &Path,
+  parallel: bool,
+  cli_options: &CliOptions,
+) -> Result<i32, AnyError> {
+  let tasks = resolve_task_order(tasks_config, task_name)?
+    .into_iter()
+    .map(|name| {
+      let task = &tasks_config[name];
+      // only the task that was asked for receives the additional arguments
+      let script = if task.command.is_empty() {
+        String::new()
+      } else if name == task_name {
+        get_script_with_args(&task.command, cli_options)
+      } else {
+        task.command.clone()
+      };
+      TaskToRun {
+        name,
+        script,
+        depends_on: &task.depends_on,
+      }
+    })
+    .collect::<Vec<_>>();
+  let env_vars = collect_env_vars(cli_options);
+  if parallel {
+    run_tasks_in_parallel(&tasks, &env_vars, cwd).await
+  } else {
+    for task in &tasks {
+      let exit_code = run_task(task, env_vars.clone(), cwd, false).await?;
+      if exit_code != 0 {
+        return Ok(exit_code);
+      }
+    }
+    Ok(0)
+  }
+}
+
+/// A task of the configuration file, with the command to execute.
+struct TaskToRun<'a> {
+  name: &'a str,
+  script: String,
+  depends_on: &'a [String],
+}
+
+/// Returns the names of the tasks to run for a task, in an order where every
+/// task comes after the tasks it depends on.
+fn resolve_task_order<'a>(
+  tasks_config: &'a IndexMap<String, TaskDefinition>,
+  task_name: &'a str,
+) -> Result<Vec<&'a str>, AnyError> {
+  fn visit<'a>(
+    tasks_config: &'a IndexMap<String, TaskDefinition>,
+    task_name: &'a str,
+    path: &mut Vec<&'a str>,
+    order: &mut Vec<&'a str>,
+  ) -> Result<(), AnyError> {
+    if order.contains(&task_name) {
+      return Ok(());
+    }
+    if let Some(index) = path.iter().position(|name| *name == task_name) {
+      let mut cycle = path[index..].to_vec();
+      cycle.push(task_name);
+      bail!("Task dependency cycle detected: {}", cycle.join(" -> "));
+    }
+    let Some(task) = tasks_config.get(task_name) else {
+      bail!("Task not found: {task_name}");
+    };
+    path.push(task_name);
+    for dependency in &task.depends_on {
+      visit(tasks_config, dependency, path, order)?;
+    }
+    path.pop();
+    order.push(task_name);
+    Ok(())
+  }
+
+  let mut order = Vec::new();
+  visit(tasks_config, task_name, &mut Vec::new(), &mut order)?;
+  Ok(order)
+}
+
+async fn run_task(
+  task: &TaskToRun<'_>,
+  env_vars: HashMap<String, String>,
+  cwd: &Path,
+  prefix_output: bool,
+) -> Result<i32, AnyError> {
+  // tasks without a command only run the tasks they depend on
+  if task.script.is_empty() {
+    return Ok(0);
+  }
+  output_task(task.name, &task.script);
+  let seq_list = deno_task_shell::parser::parse(&task.script)
+    .with_context(|| format!("Error parsing script '{}'.", task.name))?;
+  if !prefix_output {
+    return Ok(
+      deno_task_shell::execute(seq_list, env_vars, cwd, Default::default())
+        .await,
+    );
+  }
+
+  let prefix = format!("{} ", colors::cyan(format!("[{}]", task.name)));
+  let (stdout_reader, stdout_writer) = deno_task_shell::pipe();
+  let (stderr_reader, stderr_writer) = deno_task_shell::pipe();
+  let stdout_handle =
+    pipe_with_prefix(stdout_reader, prefix.clone(), std::io::stdout());
+  let stderr_handle =
+    pipe_with_prefix(stderr_reader, prefix, std::io::stderr());
+  let state = ShellState::new(env_vars, cwd, Default::default());
+  let exit_code = deno_task_shell::execute_with_pipes(
+    seq_list,
+    state,
+    ShellPipeReader::stdin(),
+    stdout_writer,
+    stderr_writer,
+  )
+  .await;
+  // the pipes are closed once the task has executed, which ends the threads
+  stdout_handle.await??;
+  stderr_handle.await??;
+  Ok(exit_code)
+}
+
+/// Runs the tasks, starting every task as soon as the tasks it depends on
+/// have succeeded. Once a task fails, no more tasks are started and the exit
+/// code of the failed task is returned after the running tasks have finished.
+async fn run_tasks_in_parallel(
+  tasks: &[TaskToRun<'_>],
+  env_vars: &HashMap<String, String>,
+  cwd: &Path,
+) -> Result<i32, AnyError> {
+  let mut started = HashSet::new();
+  let mut succeeded = HashSet::new();
+  let mut running = FuturesUnordered::new();
+  let mut exit_code = 0;
+  loop {
+    if exit_code == 0 {
+      for task in tasks {
+        if !started.contains(task.name)
+          && task
+            .depends_on
+            .iter()
+            .all(|dependency| succeeded.contains(dependency.as_str()))
+        {
+          started.insert(task.name);
+          running.push(async move {
+            let result = run_task(task, env_vars.clone(), cwd, true).await;
+            (task.name, result)
+          });
+        }
+      }
+    }
+    let Some((task_name, result)) = running.next().await else {
+      return Ok(exit_code);
+    };
+    let task_exit_code = result?;
+    if task_exit_code == 0 {
+      succeeded.insert(task_name);
+    } else if exit_code == 0 {
+      exit_code = task_exit_code;
+    }
+  }
+}
+
+fn pipe_with_prefix<W: Write + Send + 'static>(
+  reader: ShellPipeReader,
+  prefix: String,
+  inner: W,
+) -> tokio::task::JoinHandle<Result<(), AnyError>> {
+  tokio::task::spawn_blocking(move || {
+    let mut writer = PrefixedWriter {
+      prefix,
+      inner,
+      pending: Vec::new(),
+    };
+    reader.pipe_to(&mut writer)?;
+    writer.finish()?;
+    Ok(())
+  })
+}
+
+/// Writes the output of a task that runs in parallel with other tasks, with
+/// every line prefixed with the name of the task. Only whole lines are
+/// written, so the lines of the tasks don't get mixed up.
+struct PrefixedWriter<W: Write> {
+  prefix: String,
+  inner: W,
+  pending: Vec<u8>,
+}
+
+impl<W: Write> PrefixedWriter<W> {
+  fn write_line(&mut self, line: &[u8]) -> std::io::Result<()> {
+    let mut output = Vec::with_capacity(self.prefix.len() + line.len());
+    output.extend_from_slice(self.prefix.as_bytes());
+    output.extend_from_slice(line);
+    self.inner.write_all(&output)
+  }
+
+  fn finish(mut self) -> std::io::Result<()> {
+    if !self.pending.is_empty() {
+      let mut line = std::mem::take(&mut self.pending);
+      line.push(b'\n');
+      self.write_line(&line)?;
+    }
+    self.inner.flush()
+  }
+}
+
+impl<W: Write> Write for PrefixedWriter<W> {
+  fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    self.pending.extend_from_slice(buf);
+    while let Some(index) = self.pending.iter().position(|b| *b == b'\n') {
+      let line = self.pending.drain(..=index).collect::<Vec<_>>();
+      self.write_line(&line)?;
+    }
+    Ok(buf.len())
+  }
+
+  fn flush(&mut self) -> std::io::Result<()> {
+    self.inner.flush()
+  }
+}
+
 fn get_script_with_args(script: &str, options: &CliOptions) -> String {
   let additional_args = options
     .argv()
@@ -140,9 +417,13 @@ fn output_task(task_name: &str, script: &str) {
   );
 }
 
-fn collect_env_vars() -> HashMap<String, String> {
+fn collect_env_vars(options: &CliOptions) -> HashMap<String, String> {
   // get the starting env vars (the PWD env var will be set by deno_task_shell)
   let mut env_vars = std::env::vars().collect::<HashMap<String, String>>();
+  // forward the permission set to the deno commands of the task
+  if let Some(permission_set) = options.permission_set() {
+    env_vars.insert("DENO_PERMISSION_SET".to_string(), permission_set);
+  }
   const INIT_CWD_NAME: &str = "INIT_CWD";
   if !env_vars.contains_key(INIT_CWD_NAME) {
     if let Ok(cwd) = std::env::current_dir() {
@@ -154,30 +435,71 @@ fn collect_env_vars() -> HashMap<String, String> {
   env_vars
 }
 
+fn get_member_label(member: &WorkspaceMember) -> String {
+  match &member.name {
+    Some(name) => name.clone(),
+    None => member
+      .dir
+      .file_name()
+      .map(|name| name.to_string_lossy().to_string())
+      .unwrap_or_else(|| member.dir.display().to_string()),
+  }
+}
+
 fn print_available_tasks(
   // order can be important, so these use an index map
-  tasks_config: &IndexMap<String, String>,
+  tasks_config: &IndexMap<String, TaskDefinition>,
+  workspace_tasks: &[(&WorkspaceMember, IndexMap<String, TaskDefinition>)],
   package_json_scripts: &IndexMap<String, String>,
 ) {
   eprintln!("{}", colors::green("Available tasks:"));
 
   let mut had_task = false;
-  for (is_deno, (key, value)) in tasks_config.iter().map(|e| (true, e)).chain(
-    package_json_scripts
-      .iter()
-      .filter(|(key, _)| !tasks_config.contains_key(*key))
-      .map(|e| (false, e)),
-  ) {
+  for (maybe_source, (key, value, depends_on)) in tasks_config
+    .iter()
+    .map(|(key, task)| (None, (key, &task.command, task.depends_on.as_slice())))
+    .chain(
+      workspace_tasks
+        .iter()
+        .flat_map(|(member, member_tasks_config)| {
+          member_tasks_config.iter().map(move |(key, task)| {
+            (
+              Some(get_member_label(member)),
+              (key, &task.command, task.depends_on.as_slice()),
+            )
+          })
+        }),
+    )
+    .chain(
+      package_json_scripts
+        .iter()
+        .filter(|(key, _)| !tasks_config.contains_key(*key))
+        .map(|(key, value)| {
+          (
+            Some("package.json".to_string()),
+            (key, value, &[] as &[String]),
+          )
+        }),
+    )
+  {
     eprintln!(
       "- {}{}",
       colors::cyan(key),
-      if is_deno {
-        "".to_string()
-      } else {
-        format!(" {}", colors::italic_gray("(package.json)"))
+      match maybe_source {
+        Some(source) =>
+          format!(" {}", colors::italic_gray(format!("({source})"))),
+        None => "".to_string(),
       }
     );
-    eprintln!("    {value}");
+    if !value.is_empty() {
+      eprintln!("    {value}");
+    }
+    if !depends_on.is_empty() {
+      eprintln!(
+        "    {}",
+        colors::gray(format!("depends on: {}", depends_on.join(", ")))
+      );
+    }
     had_task = true;
   }
   if !had_task {
@@ -262,3 +584,75 @@ fn resolve_npm_commands(
   }
   Ok(result)
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn tasks_config(
+    tasks: &[(&str, &[&str])],
+  ) -> IndexMap<String, TaskDefinition> {
+    tasks
+      .iter()
+      .map(|(name, depends_on)| {
+        (
+          name.to_string(),
+          TaskDefinition {
+            command: format!("echo {name}"),
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+          },
+        )
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_resolve_task_order() {
+    let tasks_config = tasks_config(&[
+      ("build", &["codegen", "fmt"]),
+      ("codegen", &["fmt"]),
+      ("fmt", &[]),
+      ("test", &[]),
+    ]);
+    assert_eq!(
+      resolve_task_order(&tasks_config, "build").unwrap(),
+      vec!["fmt", "codegen", "build"]
+    );
+    assert_eq!(
+      resolve_task_order(&tasks_config, "test").unwrap(),
+      vec!["test"]
+    );
+  }
+
+  #[test]
+  fn test_resolve_task_order_cycle() {
+    let tasks_config = tasks_config(&[
+      ("build", &["codegen"]),
+      ("codegen", &["generate"]),
+      ("generate", &["build"]),
+    ]);
+    assert_eq!(
+      resolve_task_order(&tasks_config, "build")
+        .unwrap_err()
+        .to_string(),
+      "Task dependency cycle detected: build -> codegen -> generate -> build"
+    );
+  }
+
+  #[test]
+  fn test_prefixed_writer() {
+    let mut output = Vec::new();
+    let mut writer = PrefixedWriter {
+      prefix: "[build] ".to_string(),
+      inner: &mut output,
+      pending: Vec::new(),
+    };
+    writer.write_all(b"one\ntw").unwrap();
+    writer.write_all(b"o\nthree").unwrap();
+    writer.finish().unwrap();
+    assert_eq!(
+      String::from_utf8(output).unwrap(),
+      "[build] one\n[build] two\n[build] three\n"
+    );
+  }
+}
@@ -3,15 +3,20 @@
 use crate::args::CliOptions;
 use crate::args::FilesConfig;
 use crate::args::TestOptions;
+use crate::args::TestReporterConfig;
+use crate::args::TestReporterKind;
+use crate::args::TestShard;
 use crate::args::TypeCheckMode;
 use crate::colors;
-use crate::display;
 use crate::factory::CliFactory;
 use crate::file_fetcher::File;
 use crate::file_fetcher::FileFetcher;
+use crate::graph_util::get_all_dependencies;
+use crate::graph_util::get_roots_affected_by_changes;
 use crate::graph_util::graph_valid_with_cli_options;
 use crate::module_loader::ModuleLoadPreparer;
 use crate::ops;
+use crate::ops::testing::TestRetryIds;
 use crate::util::checksum;
 use crate::util::file_watcher;
 use crate::util::file_watcher::ResolutionResult;
@@ -34,7 +39,6 @@ use deno_core::futures::StreamExt;
 use deno_core::located_script_name;
 use deno_core::parking_lot::Mutex;
 use deno_core::serde_v8;
-use deno_core::url::Url;
 use deno_core::v8;
 use deno_core::ModuleSpecifier;
 use deno_runtime::deno_io::Stdio;
@@ -43,6 +47,7 @@ use deno_runtime::fmt_errors::format_js_error;
 use deno_runtime::permissions::Permissions;
 use deno_runtime::permissions::PermissionsContainer;
 use deno_runtime::tokio_util::run_local;
+use deno_runtime::worker::MainWorker;
 use indexmap::IndexMap;
 use indexmap::IndexSet;
 use log::Level;
@@ -51,8 +56,6 @@ use rand::seq::SliceRandom;
 use rand::SeedableRng;
 use regex::Regex;
 use serde::Deserialize;
-use std::collections::BTreeMap;
-use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt::Write as _;
@@ -64,6 +67,7 @@ use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
+use std::sync::mpsc::RecvTimeoutError;
 use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
@@ -73,6 +77,22 @@ use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::mpsc::WeakUnboundedSender;
 
+mod reporters;
+mod setup;
+mod snapshot;
+
+use reporters::format_test_step_ancestry;
+use reporters::open_reporter_output;
+use reporters::CompoundTestReporter;
+use reporters::JunitTestReporter;
+use reporters::PrettyTestReporter;
+use reporters::TapTestReporter;
+use reporters::TestReporter;
+use setup::run_global_teardown;
+use setup::GlobalSetup;
+pub use setup::TestSetupContext;
+pub use snapshot::TestSnapshots;
+
 /// The test mode is used to determine how a specifier is to be tested.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum TestMode {
@@ -91,6 +111,10 @@ pub struct TestFilter {
   pub regex: Option<Regex>,
   pub include: Option<Vec<String>>,
   pub exclude: Vec<String>,
+  /// Only include the tests that have at least one of these tags, if any.
+  pub tags: Vec<String>,
+  /// Exclude the tests that have any of these tags.
+  pub skip_tags: Vec<String>,
 }
 
 impl TestFilter {
@@ -116,6 +140,16 @@ impl TestFilter {
     true
   }
 
+  pub fn includes_tags(&self, tags: &[String]) -> bool {
+    if !self.tags.is_empty() && !tags.iter().any(|t| self.tags.contains(t)) {
+      return false;
+    }
+    if tags.iter().any(|t| self.skip_tags.contains(t)) {
+      return false;
+    }
+    true
+  }
+
   pub fn from_flag(flag: &Option<String>) -> Self {
     let mut substring = None;
     let mut regex = None;
@@ -151,6 +185,15 @@ pub struct TestDescription {
   pub name: String,
   pub ignore: bool,
   pub only: bool,
+  pub tags: Vec<String>,
+  /// The number of times to run the test again if it fails, overriding
+  /// `--retries`.
+  pub retries: Option<usize>,
+  /// The time in milliseconds after which the test is interrupted and fails.
+  pub timeout: Option<u64>,
+  /// The tests of an isolation group run in an isolate of their own, at the
+  /// same time as the other groups of the module.
+  pub isolation_group: Option<String>,
   pub origin: String,
   pub location: TestLocation,
 }
@@ -181,6 +224,7 @@ pub enum TestFailure {
   Incomplete,
   OverlapsWithSanitizers(IndexSet<String>), // Long names of overlapped tests
   HasSanitizersAndOverlaps(IndexSet<String>), // Long names of overlapped tests
+  TimedOut(u64),                            // Timeout in milliseconds
 }
 
 impl ToString for TestFailure {
@@ -222,6 +266,7 @@ impl ToString for TestFailure {
         }
         string
       }
+      TestFailure::TimedOut(timeout) => format!("Timed out after {}ms. The test was interrupted, and the tests after it in the module were run in a new isolate.", timeout),
     }
   }
 }
@@ -241,6 +286,7 @@ impl TestFailure {
       TestFailure::IncompleteSteps => {
         Some("due to incomplete steps".to_string())
       }
+      TestFailure::TimedOut(_) => Some("timed out".to_string()),
       _ => None,
     }
   }
@@ -314,6 +360,8 @@ pub enum TestEvent {
   Wait(usize),
   Output(Vec<u8>),
   Result(usize, TestResult, u64),
+  /// A failed attempt of a test, which is run again.
+  Retry(usize, TestFailure, u64),
   UncaughtError(String, Box<JsError>),
   StepRegister(TestStepDescription),
   StepWait(usize),
@@ -333,6 +381,11 @@ pub struct TestSummary {
   pub filtered_out: usize,
   pub measured: usize,
   pub failures: Vec<(TestDescription, TestFailure)>,
+  /// The tests that passed after failing, with the failures of the attempts.
+  pub flaky: Vec<(TestDescription, Vec<TestFailure>)>,
+  /// The tests that took at least `--slow-threshold`, with their elapsed time
+  /// in milliseconds.
+  pub slow: Vec<(TestDescription, u64)>,
   pub uncaught_errors: Vec<(String, Box<JsError>)>,
 }
 
@@ -341,16 +394,56 @@ struct TestSpecifiersOptions {
   concurrent_jobs: NonZeroUsize,
   fail_fast: Option<NonZeroUsize>,
   log_level: Option<log::Level>,
+  reporters: Vec<TestReporterConfig>,
+  slow_threshold: Option<Duration>,
   specifier: TestSpecifierOptions,
 }
 
 #[derive(Debug, Clone)]
 pub struct TestSpecifierOptions {
+  /// The number of isolation groups of a module that run at the same time.
+  pub concurrent_groups: NonZeroUsize,
   pub shuffle: Option<u64>,
   pub filter: TestFilter,
+  pub shard: Option<TestShardFilter>,
+  pub retries: usize,
+  pub snapshots: TestSnapshots,
+  pub setup_context: TestSetupContext,
   pub trace_ops: bool,
 }
 
+/// Selects the tests of a shard, by the hash of their file path and name.
+#[derive(Debug, Clone)]
+pub struct TestShardFilter {
+  shard: TestShard,
+  /// The directory that the file paths are relative to, so that the tests are
+  /// assigned to the same shards on every machine.
+  root: ModuleSpecifier,
+}
+
+impl TestShardFilter {
+  pub fn new(shard: TestShard, root: &Path) -> Self {
+    Self {
+      shard,
+      root: ModuleSpecifier::from_directory_path(root).unwrap(),
+    }
+  }
+
+  pub fn includes(&self, specifier: &ModuleSpecifier, name: &str) -> bool {
+    let path = self
+      .root
+      .make_relative(specifier)
+      .unwrap_or_else(|| specifier.to_string());
+    // FNV-1a, which unlike `DefaultHasher` is stable across releases.
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in path.bytes().chain([0]).chain(name.bytes()) {
+      hash ^= byte as u64;
+      hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash % self.shard.count.get() as u64 == self.shard.index.get() as u64 - 1
+  }
+}
+
 impl TestSummary {
   pub fn new() -> TestSummary {
     TestSummary {
@@ -364,6 +457,8 @@ impl TestSummary {
       filtered_out: 0,
       measured: 0,
       failures: Vec::new(),
+      flaky: Vec::new(),
+      slow: Vec::new(),
       uncaught_errors: Vec::new(),
     }
   }
@@ -373,488 +468,6 @@ impl TestSummary {
   }
 }
 
-struct PrettyTestReporter {
-  parallel: bool,
-  echo_output: bool,
-  in_new_line: bool,
-  scope_test_id: Option<usize>,
-  cwd: Url,
-  did_have_user_output: bool,
-  started_tests: bool,
-  child_results_buffer:
-    HashMap<usize, IndexMap<usize, (TestStepDescription, TestStepResult, u64)>>,
-}
-
-impl PrettyTestReporter {
-  fn new(parallel: bool, echo_output: bool) -> PrettyTestReporter {
-    PrettyTestReporter {
-      parallel,
-      echo_output,
-      in_new_line: true,
-      scope_test_id: None,
-      cwd: Url::from_directory_path(std::env::current_dir().unwrap()).unwrap(),
-      did_have_user_output: false,
-      started_tests: false,
-      child_results_buffer: Default::default(),
-    }
-  }
-
-  fn force_report_wait(&mut self, description: &TestDescription) {
-    if !self.in_new_line {
-      println!();
-    }
-    if self.parallel {
-      print!(
-        "{}",
-        colors::gray(format!(
-          "{} => ",
-          self.to_relative_path_or_remote_url(&description.origin)
-        ))
-      );
-    }
-    print!("{} ...", description.name);
-    self.in_new_line = false;
-    // flush for faster feedback when line buffered
-    std::io::stdout().flush().unwrap();
-    self.scope_test_id = Some(description.id);
-  }
-
-  fn to_relative_path_or_remote_url(&self, path_or_url: &str) -> String {
-    let url = Url::parse(path_or_url).unwrap();
-    if url.scheme() == "file" {
-      if let Some(mut r) = self.cwd.make_relative(&url) {
-        if !r.starts_with("../") {
-          r = format!("./{r}");
-        }
-        return r;
-      }
-    }
-    path_or_url.to_string()
-  }
-
-  fn force_report_step_wait(&mut self, description: &TestStepDescription) {
-    self.write_output_end();
-    if !self.in_new_line {
-      println!();
-    }
-    print!("{}{} ...", "  ".repeat(description.level), description.name);
-    self.in_new_line = false;
-    // flush for faster feedback when line buffered
-    std::io::stdout().flush().unwrap();
-    self.scope_test_id = Some(description.id);
-  }
-
-  fn force_report_step_result(
-    &mut self,
-    description: &TestStepDescription,
-    result: &TestStepResult,
-    elapsed: u64,
-  ) {
-    self.write_output_end();
-    if self.in_new_line || self.scope_test_id != Some(description.id) {
-      self.force_report_step_wait(description);
-    }
-
-    if !self.parallel {
-      let child_results = self
-        .child_results_buffer
-        .remove(&description.id)
-        .unwrap_or_default();
-      for (desc, result, elapsed) in child_results.values() {
-        self.force_report_step_result(desc, result, *elapsed);
-      }
-      if !child_results.is_empty() {
-        self.force_report_step_wait(description);
-      }
-    }
-
-    let status = match &result {
-      TestStepResult::Ok => colors::green("ok").to_string(),
-      TestStepResult::Ignored => colors::yellow("ignored").to_string(),
-      TestStepResult::Failed(failure) => failure.format_label(),
-    };
-    print!(" {}", status);
-    if let TestStepResult::Failed(failure) = result {
-      if let Some(inline_summary) = failure.format_inline_summary() {
-        print!(" ({})", inline_summary)
-      }
-    }
-    if !matches!(result, TestStepResult::Failed(TestFailure::Incomplete)) {
-      print!(
-        " {}",
-        colors::gray(format!("({})", display::human_elapsed(elapsed.into())))
-      );
-    }
-    println!();
-    self.in_new_line = true;
-    if self.parallel {
-      self.scope_test_id = None;
-    } else {
-      self.scope_test_id = Some(description.parent_id);
-    }
-    self
-      .child_results_buffer
-      .entry(description.parent_id)
-      .or_default()
-      .remove(&description.id);
-  }
-
-  fn write_output_end(&mut self) {
-    if self.did_have_user_output {
-      println!("{}", colors::gray("----- output end -----"));
-      self.in_new_line = true;
-      self.did_have_user_output = false;
-    }
-  }
-
-  fn report_register(&mut self, _description: &TestDescription) {}
-
-  fn report_plan(&mut self, plan: &TestPlan) {
-    if self.parallel {
-      return;
-    }
-    let inflection = if plan.total == 1 { "test" } else { "tests" };
-    println!(
-      "{}",
-      colors::gray(format!(
-        "running {} {} from {}",
-        plan.total,
-        inflection,
-        self.to_relative_path_or_remote_url(&plan.origin)
-      ))
-    );
-    self.in_new_line = true;
-  }
-
-  fn report_wait(&mut self, description: &TestDescription) {
-    if !self.parallel {
-      self.force_report_wait(description);
-    }
-    self.started_tests = true;
-  }
-
-  fn report_output(&mut self, output: &[u8]) {
-    if !self.echo_output {
-      return;
-    }
-
-    if !self.did_have_user_output && self.started_tests {
-      self.did_have_user_output = true;
-      if !self.in_new_line {
-        println!();
-      }
-      println!("{}", colors::gray("------- output -------"));
-      self.in_new_line = true;
-    }
-
-    // output everything to stdout in order to prevent
-    // stdout and stderr racing
-    std::io::stdout().write_all(output).unwrap();
-  }
-
-  fn report_result(
-    &mut self,
-    description: &TestDescription,
-    result: &TestResult,
-    elapsed: u64,
-  ) {
-    if self.parallel {
-      self.force_report_wait(description);
-    }
-
-    self.write_output_end();
-    if self.in_new_line || self.scope_test_id != Some(description.id) {
-      self.force_report_wait(description);
-    }
-
-    let status = match result {
-      TestResult::Ok => colors::green("ok").to_string(),
-      TestResult::Ignored => colors::yellow("ignored").to_string(),
-      TestResult::Failed(failure) => failure.format_label(),
-      TestResult::Cancelled => colors::gray("cancelled").to_string(),
-    };
-    print!(" {}", status);
-    if let TestResult::Failed(failure) = result {
-      if let Some(inline_summary) = failure.format_inline_summary() {
-        print!(" ({})", inline_summary)
-      }
-    }
-    println!(
-      " {}",
-      colors::gray(format!("({})", display::human_elapsed(elapsed.into())))
-    );
-    self.in_new_line = true;
-    self.scope_test_id = None;
-  }
-
-  fn report_uncaught_error(&mut self, origin: &str, _error: &JsError) {
-    if !self.in_new_line {
-      println!();
-    }
-    println!(
-      "Uncaught error from {} {}",
-      self.to_relative_path_or_remote_url(origin),
-      colors::red("FAILED")
-    );
-    self.in_new_line = true;
-    self.did_have_user_output = false;
-  }
-
-  fn report_step_register(&mut self, _description: &TestStepDescription) {}
-
-  fn report_step_wait(&mut self, description: &TestStepDescription) {
-    if !self.parallel && self.scope_test_id == Some(description.parent_id) {
-      self.force_report_step_wait(description);
-    }
-  }
-
-  fn report_step_result(
-    &mut self,
-    desc: &TestStepDescription,
-    result: &TestStepResult,
-    elapsed: u64,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) {
-    if self.parallel {
-      self.write_output_end();
-      print!(
-        "{} {} ...",
-        colors::gray(format!(
-          "{} =>",
-          self.to_relative_path_or_remote_url(&desc.origin)
-        )),
-        self.format_test_step_ancestry(desc, tests, test_steps)
-      );
-      self.in_new_line = false;
-      self.scope_test_id = Some(desc.id);
-      self.force_report_step_result(desc, result, elapsed);
-    } else {
-      let sibling_results =
-        self.child_results_buffer.entry(desc.parent_id).or_default();
-      if self.scope_test_id == Some(desc.id)
-        || self.scope_test_id == Some(desc.parent_id)
-      {
-        let sibling_results = std::mem::take(sibling_results);
-        self.force_report_step_result(desc, result, elapsed);
-        // Flush buffered sibling results.
-        for (desc, result, elapsed) in sibling_results.values() {
-          self.force_report_step_result(desc, result, *elapsed);
-        }
-      } else {
-        sibling_results
-          .insert(desc.id, (desc.clone(), result.clone(), elapsed));
-      }
-    }
-  }
-
-  fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration) {
-    if !summary.failures.is_empty() || !summary.uncaught_errors.is_empty() {
-      #[allow(clippy::type_complexity)] // Type alias doesn't look better here
-      let mut failures_by_origin: BTreeMap<
-        String,
-        (Vec<(&TestDescription, &TestFailure)>, Option<&JsError>),
-      > = BTreeMap::default();
-      let mut failure_titles = vec![];
-      for (description, failure) in &summary.failures {
-        let (failures, _) = failures_by_origin
-          .entry(description.origin.clone())
-          .or_default();
-        failures.push((description, failure));
-      }
-      for (origin, js_error) in &summary.uncaught_errors {
-        let (_, uncaught_error) =
-          failures_by_origin.entry(origin.clone()).or_default();
-        let _ = uncaught_error.insert(js_error.as_ref());
-      }
-      // note: the trailing whitespace is intentional to get a red background
-      println!("\n{}\n", colors::white_bold_on_red(" ERRORS "));
-      for (origin, (failures, uncaught_error)) in failures_by_origin {
-        for (description, failure) in failures {
-          if !failure.hide_in_summary() {
-            let failure_title = self.format_test_for_summary(description);
-            println!("{}", &failure_title);
-            println!("{}: {}", colors::red_bold("error"), failure.to_string());
-            println!();
-            failure_titles.push(failure_title);
-          }
-        }
-        if let Some(js_error) = uncaught_error {
-          let failure_title = format!(
-            "{} (uncaught error)",
-            self.to_relative_path_or_remote_url(&origin)
-          );
-          println!("{}", &failure_title);
-          println!(
-            "{}: {}",
-            colors::red_bold("error"),
-            format_test_error(js_error)
-          );
-          println!("This error was not caught from a test and caused the test runner to fail on the referenced module.");
-          println!("It most likely originated from a dangling promise, event/timeout handler or top-level code.");
-          println!();
-          failure_titles.push(failure_title);
-        }
-      }
-      // note: the trailing whitespace is intentional to get a red background
-      println!("{}\n", colors::white_bold_on_red(" FAILURES "));
-      for failure_title in failure_titles {
-        println!("{failure_title}");
-      }
-    }
-
-    let status = if summary.has_failed() {
-      colors::red("FAILED").to_string()
-    } else {
-      colors::green("ok").to_string()
-    };
-
-    let get_steps_text = |count: usize| -> String {
-      if count == 0 {
-        String::new()
-      } else if count == 1 {
-        " (1 step)".to_string()
-      } else {
-        format!(" ({count} steps)")
-      }
-    };
-
-    let mut summary_result = String::new();
-
-    write!(
-      summary_result,
-      "{} passed{} | {} failed{}",
-      summary.passed,
-      get_steps_text(summary.passed_steps),
-      summary.failed,
-      get_steps_text(summary.failed_steps),
-    )
-    .unwrap();
-
-    let ignored_steps = get_steps_text(summary.ignored_steps);
-    if summary.ignored > 0 || !ignored_steps.is_empty() {
-      write!(
-        summary_result,
-        " | {} ignored{}",
-        summary.ignored, ignored_steps
-      )
-      .unwrap()
-    }
-
-    if summary.measured > 0 {
-      write!(summary_result, " | {} measured", summary.measured,).unwrap();
-    }
-
-    if summary.filtered_out > 0 {
-      write!(summary_result, " | {} filtered out", summary.filtered_out)
-        .unwrap()
-    };
-
-    println!(
-      "\n{} | {} {}\n",
-      status,
-      summary_result,
-      colors::gray(format!(
-        "({})",
-        display::human_elapsed(elapsed.as_millis())
-      )),
-    );
-    self.in_new_line = true;
-  }
-
-  fn report_sigint(
-    &mut self,
-    tests_pending: &HashSet<usize>,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) {
-    if tests_pending.is_empty() {
-      return;
-    }
-    let mut formatted_pending = BTreeSet::new();
-    for id in tests_pending {
-      if let Some(desc) = tests.get(id) {
-        formatted_pending.insert(self.format_test_for_summary(desc));
-      }
-      if let Some(desc) = test_steps.get(id) {
-        formatted_pending
-          .insert(self.format_test_step_for_summary(desc, tests, test_steps));
-      }
-    }
-    println!(
-      "\n{} The following tests were pending:\n",
-      colors::intense_blue("SIGINT")
-    );
-    for entry in formatted_pending {
-      println!("{}", entry);
-    }
-    println!();
-    self.in_new_line = true;
-  }
-
-  fn format_test_step_ancestry(
-    &self,
-    desc: &TestStepDescription,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) -> String {
-    let root;
-    let mut ancestor_names = vec![];
-    let mut current_desc = desc;
-    loop {
-      if let Some(step_desc) = test_steps.get(&current_desc.parent_id) {
-        ancestor_names.push(&step_desc.name);
-        current_desc = step_desc;
-      } else {
-        root = tests.get(&current_desc.parent_id).unwrap();
-        break;
-      }
-    }
-    ancestor_names.reverse();
-    let mut result = String::new();
-    result.push_str(&root.name);
-    result.push_str(" ... ");
-    for name in ancestor_names {
-      result.push_str(name);
-      result.push_str(" ... ");
-    }
-    result.push_str(&desc.name);
-    result
-  }
-
-  fn format_test_for_summary(&self, desc: &TestDescription) -> String {
-    format!(
-      "{} {}",
-      &desc.name,
-      colors::gray(format!(
-        "=> {}:{}:{}",
-        self.to_relative_path_or_remote_url(&desc.location.file_name),
-        desc.location.line_number,
-        desc.location.column_number
-      ))
-    )
-  }
-
-  fn format_test_step_for_summary(
-    &self,
-    desc: &TestStepDescription,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) -> String {
-    let long_name = self.format_test_step_ancestry(desc, tests, test_steps);
-    format!(
-      "{} {}",
-      long_name,
-      colors::gray(format!(
-        "=> {}:{}:{}",
-        self.to_relative_path_or_remote_url(&desc.location.file_name),
-        desc.location.line_number,
-        desc.location.column_number
-      ))
-    )
-  }
-}
-
 fn abbreviate_test_error(js_error: &JsError) -> JsError {
   let mut js_error = js_error.clone();
   let frames = std::mem::take(&mut js_error.frames);
@@ -916,15 +529,126 @@ pub fn format_test_error(js_error: &JsError) -> String {
 /// Test a single specifier as documentation containing test programs, an executable test module or
 /// both.
 pub async fn test_specifier(
-  worker_factory: &CliMainWorkerFactory,
+  worker_factory: &Arc<CliMainWorkerFactory>,
   permissions: Permissions,
   specifier: ModuleSpecifier,
   mut sender: TestEventSender,
   fail_fast_tracker: FailFastTracker,
   options: &TestSpecifierOptions,
 ) -> Result<(), AnyError> {
+  let mut groups = IndexMap::new();
+  test_specifier_attempts(
+    worker_factory,
+    permissions.clone(),
+    &specifier,
+    &mut sender,
+    &fail_fast_tracker,
+    options,
+    TestRetryIds::default(),
+    &mut groups,
+  )
+  .await?;
+
+  // The isolation groups run at the same time, each in isolates of its own.
+  // Their events are reported a group at a time, in the order in which the
+  // groups were declared, so that the output doesn't depend on which group
+  // finishes first.
+  let group_runs = groups.into_values().map(|tests| {
+    let worker_factory = worker_factory.clone();
+    let permissions = permissions.clone();
+    let specifier = specifier.clone();
+    let fail_fast_tracker = fail_fast_tracker.clone();
+    let options = options.clone();
+    async move {
+      let (group_sender, mut receiver) = unbounded_channel();
+      let handle = tokio::task::spawn_blocking(move || {
+        let mut sender = TestEventSender::new(group_sender);
+        run_local(test_specifier_attempts(
+          &worker_factory,
+          permissions,
+          &specifier,
+          &mut sender,
+          &fail_fast_tracker,
+          &options,
+          TestRetryIds::new(tests),
+          &mut IndexMap::new(),
+        ))
+      });
+      let result = handle.await;
+      let mut events = vec![];
+      while let Some(event) = receiver.recv().await {
+        events.push(event);
+      }
+      (result, events)
+    }
+  });
+  let mut group_runs =
+    stream::iter(group_runs).buffered(options.concurrent_groups.get());
+  while let Some((result, events)) = group_runs.next().await {
+    for event in events {
+      sender.send(event)?;
+    }
+    result??;
+  }
+
+  options.snapshots.write_updated(&specifier)
+}
+
+/// Runs the tests of a module, and then runs the tests to retry in new
+/// isolates until there are none left. The first attempt adds the tests of the
+/// isolation groups to `groups` instead of running them.
+#[allow(clippy::too_many_arguments)]
+async fn test_specifier_attempts(
+  worker_factory: &CliMainWorkerFactory,
+  permissions: Permissions,
+  specifier: &ModuleSpecifier,
+  sender: &mut TestEventSender,
+  fail_fast_tracker: &FailFastTracker,
+  options: &TestSpecifierOptions,
+  mut retry_ids: TestRetryIds,
+  groups: &mut IndexMap<String, Vec<TestDescription>>,
+) -> Result<(), AnyError> {
+  let mut attempts = HashMap::new();
+  loop {
+    let retried = test_specifier_attempt(
+      worker_factory,
+      permissions.clone(),
+      specifier,
+      sender,
+      fail_fast_tracker,
+      options,
+      &retry_ids,
+      &mut attempts,
+      groups,
+    )
+    .await?;
+    if retried.is_empty() {
+      return Ok(());
+    }
+    retry_ids = TestRetryIds::new(retried);
+  }
+}
+
+/// Runs the tests of a module in a new isolate. The first attempt adds the
+/// tests of the isolation groups to `groups`, and the attempts after it only
+/// run the tests in `retry_ids`. `attempts` counts the failed
+/// attempts of each test. Returns the tests to run in a new isolate: the
+/// failed tests that have retries left, and the tests after one that timed
+/// out, since its isolate was interrupted.
+#[allow(clippy::too_many_arguments)]
+async fn test_specifier_attempt(
+  worker_factory: &CliMainWorkerFactory,
+  permissions: Permissions,
+  specifier: &ModuleSpecifier,
+  sender: &mut TestEventSender,
+  fail_fast_tracker: &FailFastTracker,
+  options: &TestSpecifierOptions,
+  retry_ids: &TestRetryIds,
+  attempts: &mut HashMap<usize, usize>,
+  groups: &mut IndexMap<String, Vec<TestDescription>>,
+) -> Result<Vec<TestDescription>, AnyError> {
   if fail_fast_tracker.should_stop() {
-    return Ok(());
+    return Ok(vec![]);
   }
   let stdout = StdioPipe::File(sender.stdout());
   let stderr = StdioPipe::File(sender.stderr());
@@ -932,7 +656,12 @@ pub async fn test_specifier(
     .create_custom_worker(
       specifier.clone(),
       PermissionsContainer::new(permissions),
-      vec![ops::testing::deno_test::init_ops(sender.clone())],
+      vec![ops::testing::deno_test::init_ops(
+        sender.clone(),
+        retry_ids.clone(),
+        options.snapshots.clone(),
+        options.setup_context.clone(),
+      )],
       Stdio {
         stdin: StdioPipe::Inherit,
         stdout,
@@ -952,7 +681,11 @@ pub async fn test_specifier(
           specifier.to_string(),
           Box::new(error.downcast::<JsError>().unwrap()),
         ))?;
-        return Ok(());
+        // The tests that were waiting to be retried can't be run anymore.
+        for id in retry_ids.0.values() {
+          sender.send(TestEvent::Result(*id, TestResult::Cancelled, 0))?;
+        }
+        return Ok(vec![]);
       } else {
         return Err(error);
       }
@@ -973,25 +706,50 @@ pub async fn test_specifier(
     let mut state = state_rc.borrow_mut();
     std::mem::take(&mut state.borrow_mut::<ops::testing::TestContainer>().0)
   };
-  let unfiltered = tests.len();
-  let (only, no_only): (Vec<_>, Vec<_>) =
-    tests.into_iter().partition(|(d, _)| d.only);
-  let used_only = !only.is_empty();
-  let tests = if used_only { only } else { no_only };
-  let mut tests = tests
-    .into_iter()
-    .filter(|(d, _)| options.filter.includes(&d.name))
-    .collect::<Vec<_>>();
-  if let Some(seed) = options.shuffle {
-    tests.shuffle(&mut SmallRng::seed_from_u64(seed));
-  }
-  sender.send(TestEvent::Plan(TestPlan {
-    origin: specifier.to_string(),
-    total: tests.len(),
-    filtered_out: unfiltered - tests.len(),
-    used_only,
-  }))?;
+  let tests = if retry_ids.0.is_empty() {
+    let unfiltered = tests.len();
+    let (only, no_only): (Vec<_>, Vec<_>) =
+      tests.into_iter().partition(|(d, _)| d.only);
+    let used_only = !only.is_empty();
+    let tests = if used_only { only } else { no_only };
+    let mut tests = tests
+      .into_iter()
+      .filter(|(d, _)| {
+        options.filter.includes(&d.name)
+          && options.filter.includes_tags(&d.tags)
+      })
+      .filter(|(d, _)| match &options.shard {
+        Some(shard) => shard.includes(specifier, &d.name),
+        None => true,
+      })
+      .collect::<Vec<_>>();
+    if let Some(seed) = options.shuffle {
+      tests.shuffle(&mut SmallRng::seed_from_u64(seed));
+    }
+    sender.send(TestEvent::Plan(TestPlan {
+      origin: specifier.to_string(),
+      total: tests.len(),
+      filtered_out: unfiltered - tests.len(),
+      used_only,
+    }))?;
+    let (grouped, tests): (Vec<_>, Vec<_>) = tests
+      .into_iter()
+      .partition(|(d, _)| d.isolation_group.is_some() && !d.ignore);
+    for (desc, _) in grouped {
+      let group = desc.isolation_group.clone().unwrap();
+      groups.entry(group).or_default().push(desc);
+    }
+    tests
+  } else {
+    // The retried tests were planned by the first attempt already.
+    tests
+      .into_iter()
+      .filter(|(d, _)| retry_ids.0.values().any(|id| *id == d.id))
+      .collect()
+  };
+  let mut retried = vec![];
   let mut had_uncaught_error = false;
+  let mut timed_out = false;
   for (desc, function) in tests {
     if fail_fast_tracker.should_stop() {
       break;
@@ -1004,11 +762,25 @@ pub async fn test_specifier(
       sender.send(TestEvent::Result(desc.id, TestResult::Cancelled, 0))?;
       continue;
     }
+    if timed_out {
+      retried.push(desc);
+      continue;
+    }
     sender.send(TestEvent::Wait(desc.id))?;
     let earlier = SystemTime::now();
-    let result = match worker.js_runtime.call_and_await(&function).await {
-      Ok(r) => r,
-      Err(error) => {
+    let result = match call_test_function(&mut worker, &function, desc.timeout)
+      .await
+    {
+      Some(Ok(r)) => {
+        let scope = &mut worker.js_runtime.handle_scope();
+        let r = v8::Local::new(scope, r);
+        serde_v8::from_v8::<TestResult>(scope, r)?
+      }
+      None => {
+        timed_out = true;
+        TestResult::Failed(TestFailure::TimedOut(desc.timeout.unwrap()))
+      }
+      Some(Err(error)) => {
         if error.is::<JsError>() {
           sender.send(TestEvent::UncaughtError(
             specifier.to_string(),
@@ -1023,13 +795,21 @@ pub async fn test_specifier(
         }
       }
     };
-    let scope = &mut worker.js_runtime.handle_scope();
-    let result = v8::Local::new(scope, result);
-    let result = serde_v8::from_v8::<TestResult>(scope, result)?;
-    if matches!(result, TestResult::Failed(_)) {
+    let elapsed = SystemTime::now().duration_since(earlier)?.as_millis();
+    if let TestResult::Failed(failure) = &result {
+      let failed_attempts = attempts.entry(desc.id).or_insert(0);
+      *failed_attempts += 1;
+      if *failed_attempts <= desc.retries.unwrap_or(options.retries) {
+        sender.send(TestEvent::Retry(
+          desc.id,
+          failure.clone(),
+          elapsed as u64,
+        ))?;
+        retried.push(desc);
+        continue;
+      }
       fail_fast_tracker.add_failure();
     }
-    let elapsed = SystemTime::now().duration_since(earlier)?.as_millis();
     sender.send(TestEvent::Result(desc.id, result, elapsed as u64))?;
   }
 
@@ -1043,7 +823,7 @@ pub async fn test_specifier(
       .with_event_loop(coverage_collector.stop_collecting().boxed_local())
       .await?;
   }
-  Ok(())
+  Ok(retried)
 }
 
 fn extract_files_from_regex_blocks(
@@ -1294,6 +1074,33 @@ pub async fn check_specifiers(
 
 static HAS_TEST_RUN_SIGINT_HANDLER: AtomicBool = AtomicBool::new(false);
 
+fn get_test_reporter(
+  options: &TestSpecifiersOptions,
+) -> Result<Box<dyn TestReporter + Send>, AnyError> {
+  let mut reporters = Vec::<Box<dyn TestReporter + Send>>::new();
+  for config in &options.reporters {
+    let reporter: Box<dyn TestReporter + Send> = match config.kind {
+      TestReporterKind::Pretty => Box::new(PrettyTestReporter::new(
+        options.concurrent_jobs.get() > 1,
+        options.log_level != Some(Level::Error),
+        options.slow_threshold,
+      )),
+      TestReporterKind::Junit => Box::new(JunitTestReporter::new(
+        open_reporter_output(config.output.as_deref())?,
+      )),
+      TestReporterKind::Tap => Box::new(TapTestReporter::new(
+        open_reporter_output(config.output.as_deref())?,
+      )),
+    };
+    reporters.push(reporter);
+  }
+  Ok(if reporters.len() == 1 {
+    reporters.pop().unwrap()
+  } else {
+    Box::new(CompoundTestReporter::new(reporters))
+  })
+}
+
 /// Test a collection of specifiers with test modes concurrently.
 async fn test_specifiers(
   worker_factory: Arc<CliMainWorkerFactory>,
@@ -1311,6 +1118,11 @@ async fn test_specifiers(
     specifiers
   };
 
+  let mut reporter = get_test_reporter(&options)?;
+  let slow_threshold = options
+    .slow_threshold
+    .map(|threshold| threshold.as_millis() as u64);
+
   let (sender, mut receiver) = unbounded_channel::<TestEvent>();
   let sender = TestEventSender::new(sender);
   let concurrent_jobs = options.concurrent_jobs;
@@ -1344,11 +1156,6 @@ async fn test_specifiers(
     .buffer_unordered(concurrent_jobs.get())
     .collect::<Vec<Result<Result<(), AnyError>, tokio::task::JoinError>>>();
 
-  let mut reporter = Box::new(PrettyTestReporter::new(
-    concurrent_jobs.get() > 1,
-    options.log_level != Some(Level::Error),
-  ));
-
   let handler = {
     tokio::task::spawn(async move {
       let earlier = Instant::now();
@@ -1356,6 +1163,8 @@ async fn test_specifiers(
       let mut test_steps = IndexMap::new();
       let mut tests_started = HashSet::new();
       let mut tests_with_result = HashSet::new();
+      let mut retried_failures: HashMap<usize, Vec<TestFailure>> =
+        HashMap::new();
       let mut summary = TestSummary::new();
       let mut used_only = false;
 
@@ -1393,6 +1202,9 @@ async fn test_specifiers(
               match &result {
                 TestResult::Ok => {
                   summary.passed += 1;
+                  if let Some(failures) = retried_failures.remove(&id) {
+                    summary.flaky.push((description.clone(), failures));
+                  }
                 }
                 TestResult::Ignored => {
                   summary.ignored += 1;
@@ -1407,10 +1219,33 @@ async fn test_specifiers(
                   summary.failed += 1;
                 }
               }
+              let is_slow = match slow_threshold {
+                Some(threshold) => elapsed >= threshold,
+                None => false,
+              };
+              if is_slow
+                && matches!(result, TestResult::Ok | TestResult::Failed(_))
+              {
+                summary.slow.push((description.clone(), elapsed));
+              }
               reporter.report_result(description, &result, elapsed);
             }
           }
 
+          TestEvent::Retry(id, failure, elapsed) => {
+            let description = tests.get(&id).unwrap();
+            let failures = retried_failures.entry(id).or_default();
+            failures.push(failure);
+            reporter.report_retry(
+              description,
+              failures.last().unwrap(),
+              elapsed,
+              failures.len(),
+            );
+            // The next attempt waits for the test again.
+            tests_started.remove(&id);
+          }
+
           TestEvent::UncaughtError(origin, error) => {
             reporter.report_uncaught_error(&origin, &error);
             summary.failed += 1;
@@ -1443,13 +1278,17 @@ async fn test_specifiers(
                   summary.failures.push((
                     TestDescription {
                       id: description.id,
-                      name: reporter.format_test_step_ancestry(
+                      name: format_test_step_ancestry(
                         description,
                         &tests,
                         &test_steps,
                       ),
                       ignore: false,
                       only: false,
+                      tags: vec![],
+                      retries: None,
+                      timeout: None,
+                      isolation_group: None,
                       origin: description.origin.clone(),
                       location: description.location.clone(),
                     },
@@ -1477,6 +1316,9 @@ async fn test_specifiers(
               &tests,
               &test_steps,
             );
+            if let Err(err) = reporter.flush_report() {
+              log::error!("{}: {err:#}", colors::red_bold("error"));
+            }
             std::process::exit(130);
           }
         }
@@ -1487,6 +1329,7 @@ async fn test_specifiers(
 
       let elapsed = Instant::now().duration_since(earlier);
       reporter.report_summary(&summary, &elapsed);
+      reporter.flush_report()?;
 
       if used_only {
         return Err(generic_error(
@@ -1514,6 +1357,64 @@ async fn test_specifiers(
   Ok(())
 }
 
+/// Runs the test modules between the global setup and teardown modules, if
+/// any. The teardown runs even if the tests fail.
+async fn test_specifiers_with_global_setup(
+  worker_factory: Arc<CliMainWorkerFactory>,
+  permissions: &Permissions,
+  specifiers: Vec<ModuleSpecifier>,
+  mut options: TestSpecifiersOptions,
+  test_options: &TestOptions,
+) -> Result<(), AnyError> {
+  let setup = match &test_options.setup {
+    Some(specifier) => Some(
+      GlobalSetup::start(
+        worker_factory.clone(),
+        permissions.clone(),
+        specifier.clone(),
+      )
+      .await?,
+    ),
+    None => None,
+  };
+  let context = setup
+    .as_ref()
+    .map(|setup| setup.context.clone())
+    .unwrap_or_default();
+  options.specifier.setup_context = context.clone();
+
+  let result =
+    test_specifiers(worker_factory.clone(), permissions, specifiers, options)
+      .await;
+
+  let setup_result = match setup {
+    Some(setup) => setup.stop().await,
+    None => Ok(()),
+  };
+  let teardown_result = match &test_options.teardown {
+    Some(specifier) => {
+      run_global_teardown(
+        worker_factory,
+        permissions.clone(),
+        specifier.clone(),
+        context,
+      )
+      .await
+    }
+    None => Ok(()),
+  };
+  for global_result in [setup_result, teardown_result] {
+    if let Err(err) = global_result {
+      // The failure of the tests is reported over the ones of the setup.
+      if result.is_ok() {
+        return Err(err);
+      }
+      log::error!("{}: {:#}", colors::red_bold("error"), err);
+    }
+  }
+  result
+}
+
 /// Checks if the path has a basename and extension Deno supports for tests.
 pub(crate) fn is_supported_test_path(path: &Path) -> bool {
   if let Some(name) = path.file_stem() {
@@ -1622,6 +1523,20 @@ async fn fetch_specifiers_with_test_mode(
   Ok(specifiers_with_mode)
 }
 
+/// Adds the global setup and teardown modules to the modules to type check.
+fn with_global_setup_modules(
+  mut specifiers_with_mode: Vec<(ModuleSpecifier, TestMode)>,
+  test_options: &TestOptions,
+) -> Vec<(ModuleSpecifier, TestMode)> {
+  for specifier in [&test_options.setup, &test_options.teardown]
+    .into_iter()
+    .flatten()
+  {
+    specifiers_with_mode.push((specifier.clone(), TestMode::Executable));
+  }
+  specifiers_with_mode
+}
+
 pub async fn run_tests(
   cli_options: CliOptions,
   test_options: TestOptions,
@@ -1634,7 +1549,7 @@ pub async fn run_tests(
   // `PermissionsContainer` - otherwise granting/revoking permissions in one
   // file would have impact on other files, which is undesirable.
   let permissions =
-    Permissions::from_options(&cli_options.permissions_options())?;
+    Permissions::from_options(&cli_options.permissions_options()?)?;
   let log_level = cli_options.log_level();
 
   let specifiers_with_mode = fetch_specifiers_with_test_mode(
@@ -1652,7 +1567,7 @@ pub async fn run_tests(
     cli_options,
     file_fetcher,
     module_load_preparer,
-    specifiers_with_mode.clone(),
+    with_global_setup_modules(specifiers_with_mode.clone(), &test_options),
   )
   .await?;
 
@@ -1663,7 +1578,7 @@ pub async fn run_tests(
   let worker_factory =
     Arc::new(factory.create_cli_main_worker_factory().await?);
 
-  test_specifiers(
+  test_specifiers_with_global_setup(
     worker_factory,
     &permissions,
     specifiers_with_mode
@@ -1677,12 +1592,26 @@ pub async fn run_tests(
       concurrent_jobs: test_options.concurrent_jobs,
       fail_fast: test_options.fail_fast,
       log_level,
+      reporters: test_options.reporters.clone(),
+      slow_threshold: test_options.slow_threshold,
       specifier: TestSpecifierOptions {
-        filter: TestFilter::from_flag(&test_options.filter),
+        concurrent_groups: test_options.concurrent_jobs,
+        filter: TestFilter {
+          tags: test_options.tags.clone(),
+          skip_tags: test_options.skip_tags.clone(),
+          ..TestFilter::from_flag(&test_options.filter)
+        },
         shuffle: test_options.shuffle,
+        shard: test_options
+          .shard
+          .map(|shard| TestShardFilter::new(shard, cli_options.initial_cwd())),
+        retries: test_options.retries,
+        snapshots: TestSnapshots::new(test_options.update_snapshots),
+        setup_context: Default::default(),
         trace_ops: test_options.trace_ops,
       },
     },
+    &test_options,
   )
   .await?;
 
@@ -1703,7 +1632,7 @@ pub async fn run_tests_with_watch(
   // `PermissionsContainer` - otherwise granting/revoking permissions in one
   // file would have impact on other files, which is undesirable.
   let permissions =
-    Permissions::from_options(&cli_options.permissions_options())?;
+    Permissions::from_options(&cli_options.permissions_options()?)?;
   let no_check = cli_options.type_check_mode() == TypeCheckMode::None;
   let log_level = cli_options.log_level();
 
@@ -1723,79 +1652,31 @@ pub async fn run_tests_with_watch(
       }?;
 
       let mut paths_to_watch = paths_to_watch_clone;
-      let mut modules_to_reload = if files_changed {
-        Vec::new()
-      } else {
-        test_modules.clone()
-      };
       let graph = module_graph_builder
         .create_graph(test_modules.clone())
         .await?;
       graph_valid_with_cli_options(&graph, &test_modules, &cli_options)?;
 
-      // TODO(@kitsonk) - This should be totally derivable from the graph.
-      for specifier in test_modules {
-        fn get_dependencies<'a>(
-          graph: &'a deno_graph::ModuleGraph,
-          maybe_module: Option<&'a deno_graph::Module>,
-          // This needs to be accessible to skip getting dependencies if they're already there,
-          // otherwise this will cause a stack overflow with circular dependencies
-          output: &mut HashSet<&'a ModuleSpecifier>,
-          no_check: bool,
-        ) {
-          if let Some(module) = maybe_module.and_then(|m| m.esm()) {
-            for dep in module.dependencies.values() {
-              if let Some(specifier) = &dep.get_code() {
-                if !output.contains(specifier) {
-                  output.insert(specifier);
-                  get_dependencies(
-                    graph,
-                    graph.get(specifier),
-                    output,
-                    no_check,
-                  );
-                }
-              }
-              if !no_check {
-                if let Some(specifier) = &dep.get_type() {
-                  if !output.contains(specifier) {
-                    output.insert(specifier);
-                    get_dependencies(
-                      graph,
-                      graph.get(specifier),
-                      output,
-                      no_check,
-                    );
-                  }
-                }
-              }
-            }
-          }
-        }
-
-        // This test module and all it's dependencies
-        let mut modules = HashSet::new();
-        modules.insert(&specifier);
-        get_dependencies(&graph, graph.get(&specifier), &mut modules, no_check);
-
-        paths_to_watch.extend(
-          modules
-            .iter()
-            .filter_map(|specifier| specifier.to_file_path().ok()),
-        );
-
-        if let Some(changed) = &changed {
-          for path in changed
+      paths_to_watch.extend(
+        get_all_dependencies(&graph, &test_modules, !no_check)
+          .into_iter()
+          .filter_map(|specifier| specifier.to_file_path().ok()),
+      );
+      let modules_to_reload = match &changed {
+        Some(changed) if !test_options.rerun_all => {
+          let changed = changed
             .iter()
             .filter_map(|path| ModuleSpecifier::from_file_path(path).ok())
-          {
-            if modules.contains(&path) {
-              modules_to_reload.push(specifier);
-              break;
-            }
-          }
+            .collect::<Vec<_>>();
+          get_roots_affected_by_changes(
+            &graph,
+            &test_modules,
+            &changed,
+            !no_check,
+          )
         }
-      }
+        _ => test_modules,
+      };
 
       Ok((paths_to_watch, modules_to_reload))
     }
@@ -1848,7 +1729,7 @@ pub async fn run_tests_with_watch(
         &cli_options,
         &file_fetcher,
         &module_load_preparer,
-        specifiers_with_mode.clone(),
+        with_global_setup_modules(specifiers_with_mode.clone(), test_options),
       )
       .await?;
 
@@ -1856,7 +1737,7 @@ pub async fn run_tests_with_watch(
         return Ok(());
       }
 
-      test_specifiers(
+      test_specifiers_with_global_setup(
         worker_factory,
         permissions,
         specifiers_with_mode
@@ -1870,12 +1751,26 @@ pub async fn run_tests_with_watch(
           concurrent_jobs: test_options.concurrent_jobs,
           fail_fast: test_options.fail_fast,
           log_level,
+          reporters: test_options.reporters.clone(),
+          slow_threshold: test_options.slow_threshold,
           specifier: TestSpecifierOptions {
-            filter: TestFilter::from_flag(&test_options.filter),
+            concurrent_groups: test_options.concurrent_jobs,
+            filter: TestFilter {
+              tags: test_options.tags.clone(),
+              skip_tags: test_options.skip_tags.clone(),
+              ..TestFilter::from_flag(&test_options.filter)
+            },
             shuffle: test_options.shuffle,
+            shard: test_options.shard.map(|shard| {
+              TestShardFilter::new(shard, cli_options.initial_cwd())
+            }),
+            retries: test_options.retries,
+            snapshots: TestSnapshots::new(test_options.update_snapshots),
+            setup_context: Default::default(),
             trace_ops: test_options.trace_ops,
           },
         },
+        test_options,
       )
       .await?;
 
@@ -1910,6 +1805,62 @@ pub async fn run_tests_with_watch(
   Ok(())
 }
 
+/// Calls a test function, and interrupts it if it runs past its timeout in
+/// milliseconds. Returns `None` if it timed out.
+async fn call_test_function(
+  worker: &mut MainWorker,
+  function: &v8::Global<v8::Function>,
+  timeout: Option<u64>,
+) -> Option<Result<v8::Global<v8::Value>, AnyError>> {
+  let Some(timeout) = timeout.map(Duration::from_millis) else {
+    return Some(worker.js_runtime.call_and_await(function).await);
+  };
+  let watchdog = TimeoutWatchdog::start(
+    worker.js_runtime.v8_isolate().thread_safe_handle(),
+    timeout,
+  );
+  // The watchdog interrupts the code that blocks the thread, and the timer
+  // the tests that wait for something that never happens.
+  let result = tokio::select! {
+    result = worker.js_runtime.call_and_await(function) => Some(result),
+    _ = tokio::time::sleep(timeout) => None,
+  };
+  if watchdog.stop() || result.is_none() {
+    // The termination is still pending if no JavaScript ran after it.
+    worker.js_runtime.v8_isolate().cancel_terminate_execution();
+    return None;
+  }
+  result
+}
+
+/// Terminates the execution of an isolate from another thread after a
+/// timeout, unless it is stopped before.
+struct TimeoutWatchdog {
+  stop_sender: std::sync::mpsc::Sender<()>,
+  thread: std::thread::JoinHandle<bool>,
+}
+
+impl TimeoutWatchdog {
+  fn start(isolate_handle: v8::IsolateHandle, timeout: Duration) -> Self {
+    let (stop_sender, stop_receiver) = std::sync::mpsc::channel();
+    let thread =
+      std::thread::spawn(move || match stop_receiver.recv_timeout(timeout) {
+        Err(RecvTimeoutError::Timeout) => isolate_handle.terminate_execution(),
+        Ok(()) | Err(RecvTimeoutError::Disconnected) => false,
+      });
+    Self {
+      stop_sender,
+      thread,
+    }
+  }
+
+  /// Returns whether the execution was terminated.
+  fn stop(self) -> bool {
+    let _ = self.stop_sender.send(());
+    self.thread.join().unwrap_or(false)
+  }
+}
+
 /// Tracks failures for the `--fail-fast` argument in
 /// order to tell when to stop running tests.
 #[derive(Clone)]
@@ -2160,4 +2111,60 @@ mod inner_test {
     assert!(!is_supported_test_path(Path::new("notatest.js")));
     assert!(!is_supported_test_path(Path::new("NotAtest.ts")));
   }
+
+  #[test]
+  fn test_filter_includes_tags() {
+    let tags =
+      |tags: &[&str]| tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+    let filter = TestFilter::default();
+    assert!(filter.includes_tags(&[]));
+    assert!(filter.includes_tags(&tags(&["slow"])));
+
+    let filter = TestFilter {
+      tags: tags(&["integration", "e2e"]),
+      skip_tags: tags(&["slow"]),
+      ..Default::default()
+    };
+    assert!(!filter.includes_tags(&[]));
+    assert!(filter.includes_tags(&tags(&["e2e"])));
+    assert!(filter.includes_tags(&tags(&["unit", "integration"])));
+    assert!(!filter.includes_tags(&tags(&["integration", "slow"])));
+    assert!(!filter.includes_tags(&tags(&["unit"])));
+  }
+
+  #[test]
+  fn test_shard_filter() {
+    let root = if cfg!(windows) {
+      "C:\\project"
+    } else {
+      "/project"
+    };
+    let count = 3;
+    let filters = (1..=count)
+      .map(|index| {
+        TestShardFilter::new(
+          format!("{index}/{count}").parse().unwrap(),
+          Path::new(root),
+        )
+      })
+      .collect::<Vec<_>>();
+    let root = ModuleSpecifier::from_directory_path(root).unwrap();
+    let mut shard_sizes = vec![0; count];
+    for file in ["a_test.ts", "sub/b_test.ts"] {
+      let specifier = root.join(file).unwrap();
+      for test in 0..50 {
+        let name = format!("test {test}");
+        let shards = filters
+          .iter()
+          .enumerate()
+          .filter(|(_, filter)| filter.includes(&specifier, &name))
+          .map(|(index, _)| index)
+          .collect::<Vec<_>>();
+        // Every test runs in exactly one shard.
+        assert_eq!(shards.len(), 1);
+        shard_sizes[shards[0]] += 1;
+      }
+    }
+    assert!(shard_sizes.iter().all(|size| *size > 0));
+  }
 }
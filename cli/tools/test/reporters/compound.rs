@@ -0,0 +1,138 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::TestReporter;
+use crate::tools::test::TestDescription;
+use crate::tools::test::TestFailure;
+use crate::tools::test::TestPlan;
+use crate::tools::test::TestResult;
+use crate::tools::test::TestStepDescription;
+use crate::tools::test::TestStepResult;
+use crate::tools::test::TestSummary;
+
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use indexmap::IndexMap;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Forwards the events to several reporters, so that a run can be reported on
+/// the console and to report files at the same time.
+pub struct CompoundTestReporter {
+  reporters: Vec<Box<dyn TestReporter + Send>>,
+}
+
+impl CompoundTestReporter {
+  pub fn new(reporters: Vec<Box<dyn TestReporter + Send>>) -> Self {
+    Self { reporters }
+  }
+}
+
+impl TestReporter for CompoundTestReporter {
+  fn report_register(&mut self, description: &TestDescription) {
+    for reporter in &mut self.reporters {
+      reporter.report_register(description);
+    }
+  }
+
+  fn report_plan(&mut self, plan: &TestPlan) {
+    for reporter in &mut self.reporters {
+      reporter.report_plan(plan);
+    }
+  }
+
+  fn report_wait(&mut self, description: &TestDescription) {
+    for reporter in &mut self.reporters {
+      reporter.report_wait(description);
+    }
+  }
+
+  fn report_output(&mut self, output: &[u8]) {
+    for reporter in &mut self.reporters {
+      reporter.report_output(output);
+    }
+  }
+
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  ) {
+    for reporter in &mut self.reporters {
+      reporter.report_result(description, result, elapsed);
+    }
+  }
+
+  fn report_retry(
+    &mut self,
+    description: &TestDescription,
+    failure: &TestFailure,
+    elapsed: u64,
+    attempt: usize,
+  ) {
+    for reporter in &mut self.reporters {
+      reporter.report_retry(description, failure, elapsed, attempt);
+    }
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError) {
+    for reporter in &mut self.reporters {
+      reporter.report_uncaught_error(origin, error);
+    }
+  }
+
+  fn report_step_register(&mut self, description: &TestStepDescription) {
+    for reporter in &mut self.reporters {
+      reporter.report_step_register(description);
+    }
+  }
+
+  fn report_step_wait(&mut self, description: &TestStepDescription) {
+    for reporter in &mut self.reporters {
+      reporter.report_step_wait(description);
+    }
+  }
+
+  fn report_step_result(
+    &mut self,
+    desc: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    for reporter in &mut self.reporters {
+      reporter.report_step_result(desc, result, elapsed, tests, test_steps);
+    }
+  }
+
+  fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration) {
+    for reporter in &mut self.reporters {
+      reporter.report_summary(summary, elapsed);
+    }
+  }
+
+  fn report_sigint(
+    &mut self,
+    tests_pending: &HashSet<usize>,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    for reporter in &mut self.reporters {
+      reporter.report_sigint(tests_pending, tests, test_steps);
+    }
+  }
+
+  fn flush_report(&mut self) -> Result<(), AnyError> {
+    // Flush all the reporters even if one of them fails, and report the first
+    // error.
+    let mut result = Ok(());
+    for reporter in &mut self.reporters {
+      let flushed = reporter.flush_report();
+      if result.is_ok() {
+        result = flushed;
+      }
+    }
+    result
+  }
+}
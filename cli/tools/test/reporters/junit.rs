@@ -0,0 +1,339 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::format_failure_plain;
+use super::get_test_step_ancestry;
+use super::to_relative_path_or_remote_url;
+use super::TestReporter;
+use crate::tools::test::format_test_error;
+use crate::tools::test::TestDescription;
+use crate::tools::test::TestFailure;
+use crate::tools::test::TestLocation;
+use crate::tools::test::TestPlan;
+use crate::tools::test::TestResult;
+use crate::tools::test::TestStepDescription;
+use crate::tools::test::TestStepResult;
+use crate::tools::test::TestSummary;
+
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::url::Url;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Write;
+use std::time::Duration;
+use std::time::Instant;
+
+enum JunitTestStatus {
+  Passed,
+  Skipped,
+  Failed(String),
+  /// An error that isn't the failure of a test, like an uncaught error.
+  Error(String),
+}
+
+struct JunitTestCase {
+  name: String,
+  location: Option<TestLocation>,
+  /// The elapsed time in milliseconds.
+  elapsed: u64,
+  /// Whether this is a step, whose time is included in the time of its test.
+  is_step: bool,
+  status: JunitTestStatus,
+  /// The failures of the earlier attempts of the test.
+  reruns: Vec<String>,
+}
+
+/// Writes a JUnit XML report, with a test suite for each module, at the end of
+/// the run. Test steps are reported as test cases of their own, named after
+/// the steps they are nested in.
+pub struct JunitTestReporter {
+  cwd: Url,
+  output: Box<dyn Write + Send>,
+  started: Instant,
+  suites: IndexMap<String, Vec<JunitTestCase>>,
+  /// The failures of the attempts of the tests that are run again, by id.
+  reruns: HashMap<usize, Vec<String>>,
+}
+
+impl JunitTestReporter {
+  pub fn new(output: Box<dyn Write + Send>) -> Self {
+    Self {
+      cwd: Url::from_directory_path(std::env::current_dir().unwrap()).unwrap(),
+      output,
+      started: Instant::now(),
+      suites: IndexMap::new(),
+      reruns: HashMap::new(),
+    }
+  }
+
+  fn add_test_case(&mut self, origin: &str, test_case: JunitTestCase) {
+    self
+      .suites
+      .entry(origin.to_string())
+      .or_default()
+      .push(test_case);
+  }
+
+  fn write_report(&mut self) -> std::io::Result<()> {
+    let out = &mut self.output;
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+      out,
+      r#"<testsuites name="deno test" {} time="{}">"#,
+      format_counts(self.suites.values().flatten()),
+      format_seconds(self.started.elapsed().as_millis() as u64),
+    )?;
+    for (origin, cases) in &self.suites {
+      let suite_name = to_relative_path_or_remote_url(&self.cwd, origin);
+      let elapsed: u64 = cases
+        .iter()
+        .filter(|case| !case.is_step)
+        .map(|case| case.elapsed)
+        .sum();
+      writeln!(
+        out,
+        r#"  <testsuite name="{}" {} time="{}">"#,
+        escape_xml(&suite_name),
+        format_counts(cases),
+        format_seconds(elapsed),
+      )?;
+      for case in cases {
+        write!(
+          out,
+          r#"    <testcase name="{}" classname="{}" time="{}""#,
+          escape_xml(&case.name),
+          escape_xml(&suite_name),
+          format_seconds(case.elapsed),
+        )?;
+        if let Some(location) = &case.location {
+          let file_name =
+            to_relative_path_or_remote_url(&self.cwd, &location.file_name);
+          write!(
+            out,
+            r#" file="{}" line="{}""#,
+            escape_xml(&file_name),
+            location.line_number,
+          )?;
+        }
+        let (tag, message, rerun_tag) = match &case.status {
+          JunitTestStatus::Passed if case.reruns.is_empty() => {
+            writeln!(out, "/>")?;
+            continue;
+          }
+          JunitTestStatus::Passed => (None, "", "flakyFailure"),
+          JunitTestStatus::Skipped => {
+            writeln!(out, ">\n      <skipped/>\n    </testcase>")?;
+            continue;
+          }
+          JunitTestStatus::Failed(message) => {
+            (Some("failure"), message.as_str(), "rerunFailure")
+          }
+          JunitTestStatus::Error(message) => {
+            (Some("error"), message.as_str(), "rerunError")
+          }
+        };
+        writeln!(out, ">")?;
+        if let Some(tag) = tag {
+          write_message_element(out, tag, message)?;
+        }
+        for message in &case.reruns {
+          write_message_element(out, rerun_tag, message)?;
+        }
+        writeln!(out, "    </testcase>")?;
+      }
+      writeln!(out, "  </testsuite>")?;
+    }
+    writeln!(out, "</testsuites>")?;
+    out.flush()
+  }
+}
+
+impl TestReporter for JunitTestReporter {
+  fn report_register(&mut self, _description: &TestDescription) {}
+
+  fn report_plan(&mut self, plan: &TestPlan) {
+    // Modules without tests are reported as empty test suites.
+    self.suites.entry(plan.origin.clone()).or_default();
+  }
+
+  fn report_wait(&mut self, _description: &TestDescription) {}
+
+  fn report_output(&mut self, _output: &[u8]) {}
+
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  ) {
+    let status = match result {
+      TestResult::Ok => JunitTestStatus::Passed,
+      TestResult::Ignored => JunitTestStatus::Skipped,
+      TestResult::Failed(failure) => {
+        JunitTestStatus::Failed(format_failure_plain(failure))
+      }
+      TestResult::Cancelled => {
+        JunitTestStatus::Failed("The test was cancelled.".to_string())
+      }
+    };
+    self.add_test_case(
+      &description.origin,
+      JunitTestCase {
+        name: description.name.clone(),
+        location: Some(description.location.clone()),
+        elapsed,
+        is_step: false,
+        status,
+        reruns: self.reruns.remove(&description.id).unwrap_or_default(),
+      },
+    );
+  }
+
+  fn report_retry(
+    &mut self,
+    description: &TestDescription,
+    failure: &TestFailure,
+    _elapsed: u64,
+    _attempt: usize,
+  ) {
+    self
+      .reruns
+      .entry(description.id)
+      .or_default()
+      .push(format_failure_plain(failure));
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError) {
+    let message =
+      console_static_text::ansi::strip_ansi_codes(&format_test_error(error))
+        .into_owned();
+    self.add_test_case(
+      origin,
+      JunitTestCase {
+        name: "(uncaught error)".to_string(),
+        location: None,
+        elapsed: 0,
+        is_step: false,
+        status: JunitTestStatus::Error(message),
+        reruns: vec![],
+      },
+    );
+  }
+
+  fn report_step_register(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_wait(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_result(
+    &mut self,
+    desc: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    let status = match result {
+      TestStepResult::Ok => JunitTestStatus::Passed,
+      TestStepResult::Ignored => JunitTestStatus::Skipped,
+      TestStepResult::Failed(failure) => {
+        JunitTestStatus::Failed(format_failure_plain(failure))
+      }
+    };
+    self.add_test_case(
+      &desc.origin,
+      JunitTestCase {
+        name: get_test_step_ancestry(desc, tests, test_steps).join(" > "),
+        location: Some(desc.location.clone()),
+        elapsed,
+        is_step: true,
+        status,
+        reruns: vec![],
+      },
+    );
+  }
+
+  fn report_summary(&mut self, _summary: &TestSummary, _elapsed: &Duration) {}
+
+  fn report_sigint(
+    &mut self,
+    _tests_pending: &HashSet<usize>,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+  }
+
+  fn flush_report(&mut self) -> Result<(), AnyError> {
+    self.write_report()?;
+    Ok(())
+  }
+}
+
+/// Formats the `tests`, `failures`, `errors` and `skipped` attributes.
+fn format_counts<'a>(
+  cases: impl IntoIterator<Item = &'a JunitTestCase>,
+) -> String {
+  let (mut tests, mut failures, mut errors, mut skipped) = (0, 0, 0, 0);
+  for case in cases {
+    tests += 1;
+    match case.status {
+      JunitTestStatus::Passed => {}
+      JunitTestStatus::Skipped => skipped += 1,
+      JunitTestStatus::Failed(_) => failures += 1,
+      JunitTestStatus::Error(_) => errors += 1,
+    }
+  }
+  format!(
+    r#"tests="{tests}" failures="{failures}" errors="{errors}" skipped="{skipped}""#
+  )
+}
+
+/// Writes an element for a failure, with its first line as the message.
+fn write_message_element(
+  out: &mut impl Write,
+  tag: &str,
+  message: &str,
+) -> std::io::Result<()> {
+  writeln!(
+    out,
+    r#"      <{tag} message="{}">{}</{tag}>"#,
+    escape_xml(message.lines().next().unwrap_or_default()),
+    escape_xml(message),
+  )
+}
+
+fn format_seconds(millis: u64) -> String {
+  format!("{:.3}", millis as f64 / 1000.0)
+}
+
+fn escape_xml(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&apos;"),
+      // The other control characters aren't allowed in XML 1.0 at all.
+      '\t' | '\n' | '\r' => escaped.push(c),
+      c if c.is_control() => escaped.push(char::REPLACEMENT_CHARACTER),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_escape_xml() {
+    assert_eq!(
+      escape_xml("a < b && \"c\" > 'd'"),
+      "a &lt; b &amp;&amp; &quot;c&quot; &gt; &apos;d&apos;"
+    );
+    assert_eq!(escape_xml("line\nline\u{1b}[31m"), "line\nline\u{fffd}[31m");
+  }
+}
@@ -0,0 +1,139 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::TestDescription;
+use super::TestFailure;
+use super::TestPlan;
+use super::TestResult;
+use super::TestStepDescription;
+use super::TestStepResult;
+use super::TestSummary;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::url::Url;
+use indexmap::IndexMap;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+mod compound;
+mod junit;
+mod pretty;
+mod tap;
+
+pub use compound::CompoundTestReporter;
+pub use junit::JunitTestReporter;
+pub use pretty::PrettyTestReporter;
+pub use tap::TapTestReporter;
+
+/// Receives the events of a test run, in the order they are received by the
+/// test runner.
+pub trait TestReporter {
+  fn report_register(&mut self, description: &TestDescription);
+  fn report_plan(&mut self, plan: &TestPlan);
+  fn report_wait(&mut self, description: &TestDescription);
+  fn report_output(&mut self, output: &[u8]);
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  );
+  /// Reports a failed attempt of a test, which is run again.
+  fn report_retry(
+    &mut self,
+    description: &TestDescription,
+    failure: &TestFailure,
+    elapsed: u64,
+    attempt: usize,
+  );
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError);
+  fn report_step_register(&mut self, description: &TestStepDescription);
+  fn report_step_wait(&mut self, description: &TestStepDescription);
+  fn report_step_result(
+    &mut self,
+    desc: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  );
+  fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration);
+  fn report_sigint(
+    &mut self,
+    tests_pending: &HashSet<usize>,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  );
+
+  /// Called once the run is over, including when it is interrupted, for the
+  /// reporters that write their report at the end.
+  fn flush_report(&mut self) -> Result<(), AnyError> {
+    Ok(())
+  }
+}
+
+/// Opens the file that a reporter writes to, or stdout.
+pub fn open_reporter_output(
+  output: Option<&Path>,
+) -> Result<Box<dyn Write + Send>, AnyError> {
+  match output {
+    Some(path) => {
+      let file = std::fs::File::create(path).with_context(|| {
+        format!("Failed to create the report file {}", path.display())
+      })?;
+      Ok(Box::new(std::io::BufWriter::new(file)))
+    }
+    None => Ok(Box::new(std::io::stdout())),
+  }
+}
+
+pub fn to_relative_path_or_remote_url(cwd: &Url, path_or_url: &str) -> String {
+  let url = Url::parse(path_or_url).unwrap();
+  if url.scheme() == "file" {
+    if let Some(mut r) = cwd.make_relative(&url) {
+      if !r.starts_with("../") {
+        r = format!("./{r}");
+      }
+      return r;
+    }
+  }
+  path_or_url.to_string()
+}
+
+/// Returns the names of the test and the steps that the step is nested in,
+/// outermost first, followed by the name of the step itself.
+pub fn get_test_step_ancestry<'a>(
+  desc: &'a TestStepDescription,
+  tests: &'a IndexMap<usize, TestDescription>,
+  test_steps: &'a IndexMap<usize, TestStepDescription>,
+) -> Vec<&'a str> {
+  let mut names = vec![desc.name.as_str()];
+  let mut current_desc = desc;
+  loop {
+    if let Some(step_desc) = test_steps.get(&current_desc.parent_id) {
+      names.push(&step_desc.name);
+      current_desc = step_desc;
+    } else {
+      names.push(&tests.get(&current_desc.parent_id).unwrap().name);
+      break;
+    }
+  }
+  names.reverse();
+  names
+}
+
+pub fn format_test_step_ancestry(
+  desc: &TestStepDescription,
+  tests: &IndexMap<usize, TestDescription>,
+  test_steps: &IndexMap<usize, TestStepDescription>,
+) -> String {
+  get_test_step_ancestry(desc, tests, test_steps).join(" ... ")
+}
+
+/// Formats a failure for the reports that are read by other programs.
+fn format_failure_plain(failure: &TestFailure) -> String {
+  console_static_text::ansi::strip_ansi_codes(&failure.to_string()).into_owned()
+}
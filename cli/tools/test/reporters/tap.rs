@@ -0,0 +1,321 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::format_failure_plain;
+use super::to_relative_path_or_remote_url;
+use super::TestReporter;
+use crate::tools::test::format_test_error;
+use crate::tools::test::TestDescription;
+use crate::tools::test::TestFailure;
+use crate::tools::test::TestLocation;
+use crate::tools::test::TestPlan;
+use crate::tools::test::TestResult;
+use crate::tools::test::TestStepDescription;
+use crate::tools::test::TestStepResult;
+use crate::tools::test::TestSummary;
+
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::time::Duration;
+
+const INDENT: &str = "    ";
+
+/// Writes the results in the Test Anything Protocol, version 14, as they come
+/// in. The steps of a test are written as its subtests once the test
+/// completes, so that they aren't interleaved with other tests when running
+/// in parallel.
+pub struct TapTestReporter {
+  cwd: Url,
+  output: Box<dyn Write + Send>,
+  /// The first error writing the output, which is returned on flush.
+  error: Option<std::io::Error>,
+  /// The number of the top-level test points written so far.
+  count: usize,
+  /// The results of the steps whose parent hasn't completed yet, by the id of
+  /// the parent.
+  step_results: HashMap<usize, Vec<(TestStepDescription, TestStepResult)>>,
+  interrupted: bool,
+}
+
+impl TapTestReporter {
+  pub fn new(output: Box<dyn Write + Send>) -> Self {
+    let mut reporter = Self {
+      cwd: Url::from_directory_path(std::env::current_dir().unwrap()).unwrap(),
+      output,
+      error: None,
+      count: 0,
+      step_results: HashMap::new(),
+      interrupted: false,
+    };
+    reporter.write("TAP version 14\n");
+    reporter
+  }
+
+  fn write(&mut self, text: &str) {
+    if self.error.is_none() {
+      if let Err(err) = self.output.write_all(text.as_bytes()) {
+        self.error = Some(err);
+      }
+    }
+  }
+
+  /// Formats the buffered results of the steps of `parent_id`, and of their
+  /// own steps, as a subtest at the given level.
+  fn format_subtests(
+    &mut self,
+    text: &mut String,
+    parent_id: usize,
+    parent_name: &str,
+    level: usize,
+  ) {
+    let Some(steps) = self.step_results.remove(&parent_id) else {
+      return;
+    };
+    let indent = INDENT.repeat(level);
+    writeln!(
+      text,
+      "{indent}# Subtest: {}",
+      escape_description(parent_name)
+    )
+    .unwrap();
+    for (i, (desc, result)) in steps.iter().enumerate() {
+      self.format_subtests(text, desc.id, &desc.name, level + 1);
+      let (ok, directive, failure) = match result {
+        TestStepResult::Ok => (true, "", None),
+        TestStepResult::Ignored => (true, " # SKIP", None),
+        TestStepResult::Failed(failure) => {
+          (false, "", Some(format_failure_plain(failure)))
+        }
+      };
+      format_test_point(text, level + 1, ok, i + 1, &desc.name, directive);
+      if let Some(message) = failure {
+        self.format_diagnostics(
+          text,
+          level + 1,
+          &message,
+          Some(&desc.location),
+        );
+      }
+    }
+    writeln!(text, "{indent}{INDENT}1..{}", steps.len()).unwrap();
+  }
+
+  /// Formats a YAML diagnostics block for a failed test point.
+  fn format_diagnostics(
+    &self,
+    text: &mut String,
+    level: usize,
+    message: &str,
+    location: Option<&TestLocation>,
+  ) {
+    let indent = format!("{}  ", INDENT.repeat(level));
+    writeln!(text, "{indent}---").unwrap();
+    writeln!(text, "{indent}message: |-").unwrap();
+    for line in message.lines() {
+      writeln!(text, "{indent}  {line}").unwrap();
+    }
+    writeln!(text, "{indent}severity: fail").unwrap();
+    if let Some(location) = location {
+      let file_name =
+        to_relative_path_or_remote_url(&self.cwd, &location.file_name);
+      writeln!(text, "{indent}at:").unwrap();
+      // JSON strings are valid YAML strings.
+      writeln!(
+        text,
+        "{indent}  file: {}",
+        serde_json::to_string(&file_name).unwrap()
+      )
+      .unwrap();
+      writeln!(text, "{indent}  line: {}", location.line_number).unwrap();
+      writeln!(text, "{indent}  column: {}", location.column_number).unwrap();
+    }
+    writeln!(text, "{indent}...").unwrap();
+  }
+}
+
+impl TestReporter for TapTestReporter {
+  fn report_register(&mut self, _description: &TestDescription) {}
+
+  fn report_plan(&mut self, _plan: &TestPlan) {}
+
+  fn report_wait(&mut self, _description: &TestDescription) {}
+
+  fn report_output(&mut self, output: &[u8]) {
+    // The output of the tests is kept as comments, which TAP consumers ignore.
+    let mut text = String::new();
+    for line in String::from_utf8_lossy(output).lines() {
+      writeln!(text, "# {line}").unwrap();
+    }
+    self.write(&text);
+  }
+
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    _elapsed: u64,
+  ) {
+    let mut text = String::new();
+    self.format_subtests(&mut text, description.id, &description.name, 0);
+    let (ok, directive, failure) = match result {
+      TestResult::Ok => (true, "", None),
+      TestResult::Ignored => (true, " # SKIP", None),
+      TestResult::Failed(failure) => {
+        (false, "", Some(format_failure_plain(failure)))
+      }
+      TestResult::Cancelled => {
+        (false, "", Some("The test was cancelled.".to_string()))
+      }
+    };
+    self.count += 1;
+    format_test_point(
+      &mut text,
+      0,
+      ok,
+      self.count,
+      &description.name,
+      directive,
+    );
+    if let Some(message) = failure {
+      self.format_diagnostics(
+        &mut text,
+        0,
+        &message,
+        Some(&description.location),
+      );
+    }
+    self.write(&text);
+  }
+
+  fn report_retry(
+    &mut self,
+    description: &TestDescription,
+    failure: &TestFailure,
+    _elapsed: u64,
+    attempt: usize,
+  ) {
+    // Only the final attempt is a test point, the earlier ones are comments.
+    self.step_results.remove(&description.id);
+    let mut text = String::new();
+    writeln!(
+      text,
+      "# {} failed on attempt {attempt}, retrying",
+      escape_description(&description.name)
+    )
+    .unwrap();
+    for line in format_failure_plain(failure).lines() {
+      writeln!(text, "#   {line}").unwrap();
+    }
+    self.write(&text);
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError) {
+    let mut text = String::new();
+    let message =
+      console_static_text::ansi::strip_ansi_codes(&format_test_error(error))
+        .into_owned();
+    let name = format!(
+      "{} (uncaught error)",
+      to_relative_path_or_remote_url(&self.cwd, origin)
+    );
+    self.count += 1;
+    format_test_point(&mut text, 0, false, self.count, &name, "");
+    self.format_diagnostics(&mut text, 0, &message, None);
+    self.write(&text);
+  }
+
+  fn report_step_register(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_wait(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_result(
+    &mut self,
+    desc: &TestStepDescription,
+    result: &TestStepResult,
+    _elapsed: u64,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    self
+      .step_results
+      .entry(desc.parent_id)
+      .or_default()
+      .push((desc.clone(), result.clone()));
+  }
+
+  fn report_summary(&mut self, _summary: &TestSummary, _elapsed: &Duration) {}
+
+  fn report_sigint(
+    &mut self,
+    _tests_pending: &HashSet<usize>,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    self.interrupted = true;
+    self.write("Bail out! Interrupted by SIGINT\n");
+  }
+
+  fn flush_report(&mut self) -> Result<(), AnyError> {
+    if !self.interrupted {
+      let plan = format!("1..{}\n", self.count);
+      self.write(&plan);
+    }
+    if let Some(err) = self.error.take() {
+      return Err(err.into());
+    }
+    self.output.flush()?;
+    Ok(())
+  }
+}
+
+fn format_test_point(
+  text: &mut String,
+  level: usize,
+  ok: bool,
+  number: usize,
+  name: &str,
+  directive: &str,
+) {
+  writeln!(
+    text,
+    "{}{} {} - {}{}",
+    INDENT.repeat(level),
+    if ok { "ok" } else { "not ok" },
+    number,
+    escape_description(name),
+    directive,
+  )
+  .unwrap();
+}
+
+/// Escapes the characters that have a meaning in the description of a test
+/// point, and keeps it on a single line.
+fn escape_description(name: &str) -> String {
+  let mut escaped = String::with_capacity(name.len());
+  for c in name.chars() {
+    match c {
+      '\\' => escaped.push_str("\\\\"),
+      '#' => escaped.push_str("\\#"),
+      '\n' | '\r' => escaped.push(' '),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_escape_description() {
+    assert_eq!(escape_description("a # b \\ c"), "a \\# b \\\\ c");
+    assert_eq!(escape_description("multi\nline"), "multi line");
+  }
+}
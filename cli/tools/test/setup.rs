@@ -0,0 +1,183 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The global setup and teardown modules of a test run, which are configured
+//! with `test.setup` and `test.teardown` in the configuration file. Each one
+//! runs once per run, in an isolate of its own, before and after all the test
+//! modules. The value returned by the setup is passed to the test modules as
+//! `TestContext.setupContext`, and to the teardown as its argument.
+
+use crate::worker::CliMainWorkerFactory;
+
+use deno_core::anyhow::anyhow;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::serde_v8;
+use deno_core::v8;
+use deno_core::ModuleSpecifier;
+use deno_runtime::permissions::Permissions;
+use deno_runtime::permissions::PermissionsContainer;
+use deno_runtime::tokio_util::run_local;
+use deno_runtime::worker::MainWorker;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// The value returned by the global setup module, which is `null` without
+/// one.
+#[derive(Debug, Clone, Default)]
+pub struct TestSetupContext(Arc<serde_json::Value>);
+
+impl TestSetupContext {
+  pub fn value(&self) -> &serde_json::Value {
+    &self.0
+  }
+}
+
+/// The isolate of the global setup module. It keeps running its event loop
+/// while the tests run, so that the servers that it starts stay up until the
+/// setup is stopped.
+pub struct GlobalSetup {
+  pub context: TestSetupContext,
+  stop_sender: oneshot::Sender<()>,
+  handle: JoinHandle<Result<(), AnyError>>,
+}
+
+impl GlobalSetup {
+  pub async fn start(
+    worker_factory: Arc<CliMainWorkerFactory>,
+    permissions: Permissions,
+    specifier: ModuleSpecifier,
+  ) -> Result<Self, AnyError> {
+    let (context_sender, context_receiver) = oneshot::channel();
+    let (stop_sender, stop_receiver) = oneshot::channel::<()>();
+    let handle = tokio::task::spawn_blocking(move || {
+      run_local(async move {
+        let mut worker = match call_default_export(
+          &worker_factory,
+          permissions,
+          &specifier,
+          None,
+        )
+        .await
+        {
+          Ok((worker, value)) => {
+            let _ = context_sender.send(Ok(value));
+            worker
+          }
+          Err(err) => {
+            let _ = context_sender.send(Err(err));
+            return Ok(());
+          }
+        };
+        tokio::select! {
+          result = worker.run_event_loop(false) => result,
+          _ = stop_receiver => Ok(()),
+        }
+      })
+    });
+
+    match context_receiver.await {
+      Ok(result) => Ok(Self {
+        context: TestSetupContext(Arc::new(
+          result.context("The global test setup failed")?,
+        )),
+        stop_sender,
+        handle,
+      }),
+      Err(_) => {
+        handle.await??;
+        bail!("The global test setup stopped without a result.")
+      }
+    }
+  }
+
+  /// Stops the event loop of the setup, and returns its uncaught error if it
+  /// had one while the tests ran.
+  pub async fn stop(self) -> Result<(), AnyError> {
+    // The event loop may have run to completion already.
+    let _ = self.stop_sender.send(());
+    self
+      .handle
+      .await?
+      .context("The global test setup had an uncaught error")
+  }
+}
+
+/// Runs the global teardown module with the value returned by the setup.
+pub async fn run_global_teardown(
+  worker_factory: Arc<CliMainWorkerFactory>,
+  permissions: Permissions,
+  specifier: ModuleSpecifier,
+  context: TestSetupContext,
+) -> Result<(), AnyError> {
+  tokio::task::spawn_blocking(move || {
+    run_local(async move {
+      call_default_export(
+        &worker_factory,
+        permissions,
+        &specifier,
+        Some(context.value()),
+      )
+      .await
+      .context("The global test teardown failed")?;
+      Ok(())
+    })
+  })
+  .await?
+}
+
+/// Executes a module and awaits the call of the function that it exports by
+/// default. Returns the worker, with its event loop still running, and the
+/// value returned by the function, which must be serializable as JSON.
+async fn call_default_export(
+  worker_factory: &CliMainWorkerFactory,
+  permissions: Permissions,
+  specifier: &ModuleSpecifier,
+  arg: Option<&serde_json::Value>,
+) -> Result<(MainWorker, serde_json::Value), AnyError> {
+  let mut worker = worker_factory
+    .create_main_worker(
+      specifier.clone(),
+      PermissionsContainer::new(permissions),
+    )
+    .await?;
+  let namespace = worker
+    .execute_side_module_namespace_possibly_with_npm()
+    .await?;
+  let mut worker = worker.into_main_worker();
+
+  let (function, args) = {
+    let scope = &mut worker.js_runtime.handle_scope();
+    let namespace = v8::Local::new(scope, namespace);
+    let key = v8::String::new(scope, "default").unwrap();
+    let function = namespace
+      .get(scope, key.into())
+      .and_then(|value| v8::Local::<v8::Function>::try_from(value).ok())
+      .ok_or_else(|| {
+        anyhow!("The module {specifier} must export a function by default.")
+      })?;
+    let args = match arg {
+      Some(arg) => {
+        let arg = serde_v8::to_v8(scope, arg)?;
+        vec![v8::Global::new(scope, arg)]
+      }
+      None => vec![],
+    };
+    (v8::Global::new(scope, function), args)
+  };
+
+  let value = worker
+    .js_runtime
+    .call_with_args_and_await(&function, &args)
+    .await?;
+  let value = {
+    let scope = &mut worker.js_runtime.handle_scope();
+    let value = v8::Local::new(scope, value);
+    serde_v8::from_v8::<serde_json::Value>(scope, value).with_context(|| {
+      format!("The value returned by {specifier} must be serializable as JSON")
+    })?
+  };
+  Ok((worker, value))
+}
@@ -0,0 +1,253 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The snapshots of `TestContext.assertSnapshot()`. The snapshots of a test
+//! module are kept in `__snapshots__/<module>.snap` next to it, in the same
+//! format as the snapshot module of the standard library, so that both can
+//! read the files of the other.
+
+use crate::util::diff::diff;
+use crate::util::fs::atomic_write_file;
+
+use deno_core::anyhow::anyhow;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
+use deno_core::ModuleSpecifier;
+use indexmap::IndexMap;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const SNAPSHOT_FILE_HEADER: &str = "export const snapshot = {};\n";
+
+#[derive(Debug, Default)]
+struct SnapshotFile {
+  snapshots: IndexMap<String, String>,
+  /// Whether the snapshots were changed since the file was last written.
+  updated: bool,
+}
+
+/// The snapshot files of a test run. It is shared by the workers that run the
+/// test modules in parallel, and the access to a file is serialized so that
+/// its updates aren't lost.
+#[derive(Debug, Clone, Default)]
+pub struct TestSnapshots {
+  update: bool,
+  files: Arc<Mutex<HashMap<PathBuf, SnapshotFile>>>,
+}
+
+impl TestSnapshots {
+  pub fn new(update: bool) -> Self {
+    Self {
+      update,
+      files: Default::default(),
+    }
+  }
+
+  /// Compares the serialized `actual` value with the snapshot `name` of the
+  /// test module, or records it when updating the snapshots. Returns the
+  /// message of the assertion error if they differ.
+  pub fn assert(
+    &self,
+    specifier: &ModuleSpecifier,
+    name: &str,
+    actual: &str,
+  ) -> Result<Option<String>, AnyError> {
+    let path = snapshot_file_path(specifier)?;
+    let mut files = self.files.lock();
+    let file = match files.entry(path) {
+      Entry::Occupied(entry) => entry.into_mut(),
+      Entry::Vacant(entry) => {
+        let file = read_snapshot_file(entry.key())?;
+        entry.insert(file)
+      }
+    };
+
+    if self.update {
+      if file.snapshots.get(name).map(String::as_str) != Some(actual) {
+        file.snapshots.insert(name.to_string(), actual.to_string());
+        file.updated = true;
+      }
+      return Ok(None);
+    }
+
+    match file.snapshots.get(name) {
+      Some(expected) if expected == actual => Ok(None),
+      Some(expected) => Ok(Some(format!(
+        "Snapshot does not match: `{name}`\n\n{}\nRun `deno test --update-snapshots` to update it.",
+        diff(expected, actual),
+      ))),
+      None => Ok(Some(format!(
+        "Missing snapshot: `{name}`\n\nRun `deno test --update-snapshots` to create it."
+      ))),
+    }
+  }
+
+  /// Writes the snapshot file of the test module if its snapshots were
+  /// updated. The snapshots of the tests that didn't run are kept.
+  pub fn write_updated(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<(), AnyError> {
+    if !self.update {
+      return Ok(());
+    }
+    let Ok(path) = snapshot_file_path(specifier) else {
+      return Ok(());
+    };
+    let mut files = self.files.lock();
+    let Some(file) = files.get_mut(&path).filter(|file| file.updated) else {
+      return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+      std::fs::create_dir_all(dir)?;
+    }
+    atomic_write_file(&path, format_snapshot_file(&file.snapshots), 0o644)
+      .with_context(|| {
+        format!("Failed to write the snapshot file {}", path.display())
+      })?;
+    file.updated = false;
+    Ok(())
+  }
+}
+
+fn snapshot_file_path(
+  specifier: &ModuleSpecifier,
+) -> Result<PathBuf, AnyError> {
+  let path = specifier.to_file_path().map_err(|_| {
+    anyhow!("Snapshots are only supported for local test modules.")
+  })?;
+  let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+    return Err(anyhow!("Invalid test module path: {}", path.display()));
+  };
+  let mut snapshot_file_name = file_name.to_os_string();
+  snapshot_file_name.push(".snap");
+  Ok(dir.join("__snapshots__").join(snapshot_file_name))
+}
+
+fn read_snapshot_file(path: &Path) -> Result<SnapshotFile, AnyError> {
+  let text = match std::fs::read_to_string(path) {
+    Ok(text) => text,
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+      return Ok(SnapshotFile::default());
+    }
+    Err(err) => return Err(err.into()),
+  };
+  let snapshots = parse_snapshot_file(&text).ok_or_else(|| {
+    anyhow!("Failed to parse the snapshot file {}", path.display())
+  })?;
+  Ok(SnapshotFile {
+    snapshots,
+    updated: false,
+  })
+}
+
+/// Parses the ``snapshot[`name`] = `value`;`` statements of a snapshot file.
+fn parse_snapshot_file(text: &str) -> Option<IndexMap<String, String>> {
+  const PREFIX: &str = "snapshot[`";
+  let mut snapshots = IndexMap::new();
+  let mut rest = text;
+  while let Some(start) = rest.find(PREFIX) {
+    let (name, after) = parse_template_literal(&rest[start + PREFIX.len()..])?;
+    let after = after.strip_prefix("] = `")?;
+    let (value, after) = parse_template_literal(after)?;
+    rest = after.strip_prefix(';')?;
+    // Multiline values start and end on lines of their own.
+    let value =
+      match value.strip_prefix('\n').and_then(|v| v.strip_suffix('\n')) {
+        Some(value) => value.to_string(),
+        None => value,
+      };
+    snapshots.insert(name, value);
+  }
+  Some(snapshots)
+}
+
+/// Returns the unescaped content of a template literal up to its closing
+/// backtick, and the text after it.
+fn parse_template_literal(text: &str) -> Option<(String, &str)> {
+  let mut value = String::new();
+  let mut chars = text.char_indices();
+  while let Some((i, c)) = chars.next() {
+    match c {
+      '\\' => value.push(chars.next()?.1),
+      '`' => return Some((value, &text[i + 1..])),
+      c => value.push(c),
+    }
+  }
+  None
+}
+
+fn format_snapshot_file(snapshots: &IndexMap<String, String>) -> String {
+  let mut text = SNAPSHOT_FILE_HEADER.to_string();
+  for (name, value) in snapshots {
+    let value = if value.contains('\n') {
+      format!("\n{}\n", escape_template_literal(value))
+    } else {
+      escape_template_literal(value)
+    };
+    write!(
+      text,
+      "\nsnapshot[`{}`] = `{}`;\n",
+      escape_template_literal(name),
+      value
+    )
+    .unwrap();
+  }
+  text
+}
+
+fn escape_template_literal(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    if matches!(c, '\\' | '`' | '$') {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_snapshot_file_round_trip() {
+    let mut snapshots = IndexMap::new();
+    snapshots.insert("plain 1".to_string(), "{ a: 1 }".to_string());
+    snapshots.insert(
+      "step > `quoted` 1".to_string(),
+      "[\n  \"${x}\",\n  \"\\\\\",\n]".to_string(),
+    );
+    let text = format_snapshot_file(&snapshots);
+    assert_eq!(
+      text,
+      r#"export const snapshot = {};
+
+snapshot[`plain 1`] = `{ a: 1 }`;
+
+snapshot[`step > \`quoted\` 1`] = `
+[
+  "\${x}",
+  "\\\\",
+]
+`;
+"#
+    );
+    assert_eq!(parse_snapshot_file(&text), Some(snapshots));
+  }
+
+  #[test]
+  fn test_parse_snapshot_file_invalid() {
+    assert_eq!(parse_snapshot_file("snapshot[`a`] = `b"), None);
+    assert_eq!(parse_snapshot_file("snapshot[`a`] + 1;"), None);
+    assert_eq!(
+      parse_snapshot_file(SNAPSHOT_FILE_HEADER),
+      Some(IndexMap::new())
+    );
+  }
+}
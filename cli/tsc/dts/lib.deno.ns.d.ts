@@ -361,6 +361,26 @@ declare namespace Deno {
     cidr: string;
     /** The MAC address. */
     mac: string;
+    /** The traffic counters of the interface, since it was brought up, or
+     * `null` if they aren't available. The counters are the same for all the
+     * addresses of the interface. */
+    stats: NetworkInterfaceStats | null;
+  }
+
+  /** The traffic counters of a network interface, returned by
+   * {@linkcode Deno.networkInterfaces}.
+   *
+   * @category Network
+   */
+  export interface NetworkInterfaceStats {
+    /** The number of bytes received. */
+    rxBytes: number;
+    /** The number of bytes sent. */
+    txBytes: number;
+    /** The number of packets received. */
+    rxPackets: number;
+    /** The number of packets sent. */
+    txPackets: number;
   }
 
   /**
@@ -582,6 +602,17 @@ declare namespace Deno {
      */
     net?: "inherit" | boolean | string[];
 
+    /** Specifies if the `vsock` permission should be requested or revoked.
+     * if set to `"inherit"`, the current `vsock` permission will be inherited.
+     * if set to `true`, the global `vsock` permission will be requested.
+     * if set to `false`, the global `vsock` permission will be revoked.
+     * if set to `string[]`, the `vsock` permission will be requested with the
+     * specified addresses with the format `"<cid>[:<port>]"`.
+     *
+     * @default {false}
+     */
+    vsock?: "inherit" | boolean | string[];
+
     /** Specifies if the `ffi` permission should be requested or revoked.
      * If set to `"inherit"`, the current `ffi` permission will be inherited.
      * If set to `true`, the global `ffi` permission will be requested.
@@ -637,6 +668,26 @@ declare namespace Deno {
     /** If the current test is a step of another test, the parent test context
      * will be set here. */
     parent?: TestContext;
+    /** The value returned by the function that the global setup module of
+     * the run exports by default, which is configured with `"test": {
+     * "setup": "./setup.ts" }` in the configuration file. It is `null` if
+     * there is no setup module.
+     *
+     * ```ts
+     * // setup.ts
+     * export default async function () {
+     *   const server = await startDatabase();
+     *   return { url: server.url };
+     * }
+     *
+     * // db_test.ts
+     * Deno.test("query", async (t) => {
+     *   const { url } = t.setupContext as { url: string };
+     *   // ...
+     * });
+     * ```
+     */
+    readonly setupContext: unknown;
 
     /** Run a sub step of the parent test or step. Returns a promise
      * that resolves to a boolean signifying if the step completed successfully.
@@ -709,6 +760,21 @@ declare namespace Deno {
      * ```
      */
     step(fn: (t: TestContext) => void | Promise<void>): Promise<boolean>;
+
+    /** Assert that the value matches its snapshot. The snapshots of a test
+     * module are kept in `__snapshots__/<module>.snap` next to it, and are
+     * named after the test and the number of the assertion in it.
+     *
+     * Run `deno test --update-snapshots` to create or update the snapshots
+     * from the current values.
+     *
+     * ```ts
+     * Deno.test("user", (t) => {
+     *   t.assertSnapshot({ name: "deno", roles: ["admin"] });
+     * });
+     * ```
+     */
+    assertSnapshot(value: unknown): void;
   }
 
   /** @category Testing */
@@ -760,6 +826,60 @@ declare namespace Deno {
     /** If at least one test has `only` set to `true`, only run tests that have
      * `only` set to `true` and fail the test suite. */
     only?: boolean;
+    /** Tags of the test, which let a subset of the tests be run with the
+     * `--tags` and `--skip-tags` flags of `deno test`.
+     *
+     * ```ts
+     * Deno.test({
+     *   name: "fetches the dashboard",
+     *   tags: ["integration", "slow"],
+     *   async fn() {
+     *     // ...
+     *   },
+     * });
+     * ```
+     *
+     * @default {[]} */
+    tags?: string[];
+    /** The number of times to run the test again, in a new isolate, if it
+     * fails. A test that passes after failing is reported as flaky. Overrides
+     * the `--retries` flag of `deno test` for this test.
+     *
+     * @default {0} */
+    retries?: number;
+    /** The time in milliseconds after which the test fails. The test is
+     * interrupted even if it blocks the thread, and the tests after it in
+     * the module run in a new isolate.
+     *
+     * ```ts
+     * Deno.test({
+     *   name: "responds in time",
+     *   timeout: 5000,
+     *   async fn() {
+     *     await fetch("http://localhost:8000/");
+     *   },
+     * });
+     * ```
+     */
+    timeout?: number;
+    /** The name of the isolation group of the test. The tests of a group
+     * run in an isolate of their own, so with `deno test --parallel` the
+     * groups of a module run at the same time as each other. Only put tests
+     * in groups that don't depend on the state that the other tests of the
+     * module leave behind. The results of the groups are reported a group at
+     * a time, after the tests of the module that aren't in a group.
+     *
+     * ```ts
+     * Deno.test({
+     *   name: "parses a large file",
+     *   isolationGroup: "parser",
+     *   fn() {
+     *     // ...
+     *   },
+     * });
+     * ```
+     */
+    isolationGroup?: string;
     /** Check that the number of async completed operations after the test step
      * is the same as number of dispatched operations. This ensures that the
      * code tested does not start async operations which it then does
@@ -1983,6 +2103,27 @@ declare namespace Deno {
    */
   export function close(rid: number): void;
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Options which can be set when using {@linkcode Deno.FsFile.mmap}.
+   *
+   * @category File System */
+  export interface MmapOptions {
+    /** The offset in the file of the first byte to map.
+     *
+     * @default {0} */
+    offset?: number;
+    /** The number of bytes to map. Defaults to all of them up to the end of
+     * the file. */
+    length?: number;
+    /** Whether changes to the buffer are written to the file, which must then
+     * be open for reading and writing. Otherwise they are only seen through
+     * the buffer, and the file may be open for reading only.
+     *
+     * @default {false} */
+    shared?: boolean;
+  }
+
   /** The Deno abstraction for reading and writing files.
    *
    * This is the most straight forward way of handling files within Deno and is
@@ -2273,6 +2414,204 @@ declare namespace Deno {
      * ```
      */
     statSync(): FileInfo;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Acquire an advisory file-system lock for the file. A shared lock can be
+     * held by several processes at once, while an exclusive lock can only be
+     * held by one, and the returned promise resolves once the lock is
+     * acquired. This uses `flock()` on Unix and `LockFileEx()` on Windows.
+     *
+     * ```ts
+     * const file = await Deno.open("my_file.txt", { read: true, write: true });
+     * await file.lock(true);
+     * // only this process can hold a lock on the file here
+     * await file.unlock();
+     * file.close();
+     * ```
+     *
+     * @param [exclusive=false]
+     */
+    lock(exclusive?: boolean): Promise<void>;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Synchronously acquire an advisory file-system lock for the file,
+     * blocking until the lock is acquired.
+     *
+     * ```ts
+     * const file = Deno.openSync("my_file.txt", { read: true, write: true });
+     * file.lockSync(true);
+     * // only this process can hold a lock on the file here
+     * file.unlockSync();
+     * file.close();
+     * ```
+     *
+     * @param [exclusive=false]
+     */
+    lockSync(exclusive?: boolean): void;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Release an advisory file-system lock for the file.
+     */
+    unlock(): Promise<void>;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Synchronously release an advisory file-system lock for the file.
+     */
+    unlockSync(): void;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Make sure that the disk space for the first `len` bytes of the file is
+     * allocated, so that writing them can't fail for lack of space. The file
+     * is extended with zeros if it is shorter than `len`, but never
+     * truncated.
+     *
+     * ```ts
+     * const file = await Deno.open("my_file.db", { write: true, create: true });
+     * await file.allocate(64 * 1024 * 1024);
+     * file.close();
+     * ```
+     */
+    allocate(len: number): Promise<void>;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Synchronously make sure that the disk space for the first `len` bytes
+     * of the file is allocated. The file is extended with zeros if it is
+     * shorter than `len`, but never truncated.
+     *
+     * ```ts
+     * const file = Deno.openSync("my_file.db", { write: true, create: true });
+     * file.allocateSync(64 * 1024 * 1024);
+     * file.close();
+     * ```
+     */
+    allocateSync(len: number): void;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Deallocate the disk space of `len` bytes of the file starting at
+     * `offset`, which read as zeros afterwards. The size of the file doesn't
+     * change. On Windows the file is made sparse first, and on macOS `offset`
+     * and `len` must be multiples of the block size of the file system.
+     *
+     * Throws a `Deno.errors.NotSupported` error if the file system doesn't
+     * support holes.
+     *
+     * ```ts
+     * const file = await Deno.open("my_file.db", { write: true });
+     * await file.punchHole(4096, 8192);
+     * file.close();
+     * ```
+     */
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Maps the file into memory, returning an `ArrayBuffer` backed by it, so
+     * that it can be read without copying it. The pages are only read from the
+     * disk when they are accessed.
+     *
+     * The mapping stays valid after the file is closed, until
+     * {@linkcode Deno.munmap} is called or the buffer is garbage collected.
+     * Accessing the buffer after the file was truncated to a shorter length
+     * crashes the process.
+     *
+     * ```ts
+     * const file = await Deno.open("my_file.db", { read: true, write: true });
+     * const bytes = new Uint8Array(file.mmap({ shared: true }));
+     * bytes[0] = 42;
+     * await Deno.msync(bytes.buffer);
+     * Deno.munmap(bytes.buffer);
+     * file.close();
+     * ```
+     */
+    mmap(options?: MmapOptions): ArrayBuffer;
+    punchHole(offset: number, len: number): Promise<void>;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Synchronously deallocate the disk space of `len` bytes of the file
+     * starting at `offset`, which read as zeros afterwards. The size of the
+     * file doesn't change.
+     *
+     * ```ts
+     * const file = Deno.openSync("my_file.db", { write: true });
+     * file.punchHoleSync(4096, 8192);
+     * file.close();
+     * ```
+     */
+    punchHoleSync(offset: number, len: number): void;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Returns the value of an extended attribute of the file, or `null` if
+     * the file doesn't have it. See {@linkcode Deno.getXattr}.
+     */
+    getXattr(name: string): Promise<Uint8Array | null>;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Synchronously returns the value of an extended attribute of the file,
+     * or `null` if the file doesn't have it.
+     */
+    getXattrSync(name: string): Uint8Array | null;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Sets the value of an extended attribute of the file. A string value is
+     * encoded as UTF-8. See {@linkcode Deno.setXattr}.
+     */
+    setXattr(name: string, value: string | Uint8Array): Promise<void>;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Synchronously sets the value of an extended attribute of the file.
+     */
+    setXattrSync(name: string, value: string | Uint8Array): void;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Returns the names of the extended attributes of the file.
+     */
+    listXattr(): Promise<string[]>;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Synchronously returns the names of the extended attributes of the file.
+     */
+    listXattrSync(): string[];
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Removes an extended attribute of the file.
+     */
+    removeXattr(name: string): Promise<void>;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Synchronously removes an extended attribute of the file.
+     */
+    removeXattrSync(name: string): void;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Gives a name to a file opened with {@linkcode Deno.openTmpFile}, which
+     * then isn't deleted when it's closed anymore. `path` must be on the same
+     * file system as the file and must not exist. Other files can't be
+     * linked.
+     *
+     * Only supported on Linux and Windows, and on Linux only for files that
+     * were created with `O_TMPFILE`.
+     *
+     * ```ts
+     * const file = await Deno.openTmpFile("./data");
+     * await file.write(new TextEncoder().encode("Hello world"));
+     * await file.link("./data/hello.txt");
+     * file.close();
+     * ```
+     */
+    link(path: string | URL): Promise<void>;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Synchronously gives a name to a file opened with
+     * {@linkcode Deno.openTmpFileSync}, which then isn't deleted when it's
+     * closed anymore. `path` must be on the same file system as the file and
+     * must not exist. Other files can't be linked.
+     *
+     * ```ts
+     * const file = Deno.openTmpFileSync("./data");
+     * file.writeSync(new TextEncoder().encode("Hello world"));
+     * file.linkSync("./data/hello.txt");
+     * file.close();
+     * ```
+     */
+    linkSync(path: string | URL): void;
     /** Close the file. Closing a file when you are finished with it is
      * important to avoid leaking resources.
      *
@@ -2898,6 +3237,16 @@ declare namespace Deno {
      *
      * @default {false} */
     recursive?: boolean;
+    /** If set to `true`, path is moved to the trash of the system instead of
+     * being removed, so that it can be restored: the recycle bin on Windows,
+     * the trash of Finder on macOS and the XDG trash elsewhere. Directories
+     * are moved with all of their contents, whatever `recursive` is.
+     *
+     * Throws a `Deno.errors.NotSupported` error if there's no trash on the
+     * file system of path.
+     *
+     * @default {false} */
+    trash?: boolean;
   }
 
   /** Removes the named file or directory.
@@ -3223,6 +3572,25 @@ declare namespace Deno {
    */
   export function readDirSync(path: string | URL): Iterable<DirEntry>;
 
+  /** Options which can be set when using {@linkcode Deno.copyFile} and
+   * {@linkcode Deno.copyFileSync}.
+   *
+   * @category File System */
+  export interface CopyFileOptions {
+    /** Whether the copy is a clone (or "reflink") of the file, which shares
+     * the data of the original until either of them is written to. This is
+     * supported by btrfs and XFS on Linux, APFS on macOS and ReFS on Windows.
+     *
+     * - `"auto"` clones the file when possible, and copies its contents
+     *   otherwise.
+     * - `"always"` clones the file, and throws a `Deno.errors.NotSupported`
+     *   error when it can't be cloned.
+     * - `"never"` always copies the contents of the file.
+     *
+     * @default {"auto"} */
+    reflink?: "auto" | "always" | "never";
+  }
+
   /** Copies the contents and permissions of one file to another specified path,
    * by default creating a new file if needed, else overwriting. Fails if target
    * path is a directory or is unwritable.
@@ -3231,6 +3599,13 @@ declare namespace Deno {
    * await Deno.copyFile("from.txt", "to.txt");
    * ```
    *
+   * To fail instead of copying the contents of the file when the file system
+   * can't clone it:
+   *
+   * ```ts
+   * await Deno.copyFile("from.txt", "to.txt", { reflink: "always" });
+   * ```
+   *
    * Requires `allow-read` permission on `fromPath`.
    *
    * Requires `allow-write` permission on `toPath`.
@@ -3241,6 +3616,7 @@ declare namespace Deno {
   export function copyFile(
     fromPath: string | URL,
     toPath: string | URL,
+    options?: CopyFileOptions,
   ): Promise<void>;
 
   /** Synchronously copies the contents and permissions of one file to another
@@ -3261,6 +3637,7 @@ declare namespace Deno {
   export function copyFileSync(
     fromPath: string | URL,
     toPath: string | URL,
+    options?: CopyFileOptions,
   ): void;
 
   /** Resolves to the full path destination of the named symbolic link.
@@ -3662,6 +4039,18 @@ declare namespace Deno {
    * The recursive option is `true` by default and, for directories, will watch
    * the specified directory and all sub directories.
    *
+   * The events can be filtered by path and debounced with the other
+   * {@linkcode WatchFsOptions}, which happens before they are passed to
+   * JavaScript.
+   *
+   * ```ts
+   * const watcher = Deno.watchFs(".", {
+   *   extensions: ["ts", "tsx"],
+   *   exclude: ["node_modules/**", "**\/.git/**"],
+   *   debounce: 100,
+   * });
+   * ```
+   *
    * Note that the exact ordering of the events can vary between operating
    * systems.
    *
@@ -3694,9 +4083,32 @@ declare namespace Deno {
    */
   export function watchFs(
     paths: string | string[],
-    options?: { recursive: boolean },
+    options?: WatchFsOptions,
   ): FsWatcher;
 
+  /** Options which can be set when using {@linkcode Deno.watchFs}.
+   *
+   * @category File System */
+  export interface WatchFsOptions {
+    /** Whether the sub directories of directories are watched. Defaults to
+     * `true`. */
+    recursive?: boolean;
+    /** Glob patterns of the paths to report events for, relative to the
+     * watched path they are in, e.g. `"src/**\/*.ts"`. A `*` doesn't match
+     * across path separators. */
+    include?: string[];
+    /** Glob patterns of the paths to not report events for, which take
+     * precedence over `include`. */
+    exclude?: string[];
+    /** The extensions of the paths to report events for, with or without the
+     * leading dot. */
+    extensions?: string[];
+    /** The time, in milliseconds, that events are held back for after the
+     * last event, so that a burst of events is reported at once without the
+     * duplicate events. */
+    debounce?: number;
+  }
+
   /**
    * @deprecated Use {@linkcode Deno.Command} instead.
    *
@@ -3911,8 +4323,12 @@ declare namespace Deno {
    * );
    * ```
    *
-   * _Note_: On Windows only `"SIGINT"` (CTRL+C) and `"SIGBREAK"` (CTRL+Break)
-   * are supported.
+   * _Note_: On Windows only `"SIGINT"` (CTRL+C), `"SIGBREAK"` (CTRL+Break),
+   * `"SIGHUP"` (the console window is closed) and `"SIGTERM"` (the system
+   * shuts down or the user logs off, which is only received by services) are
+   * supported. Windows terminates the process shortly after the console is
+   * closed or the system shuts down, so the handlers of these signals should
+   * clean up quickly.
    *
    * @category Runtime Environment
    */
@@ -3929,8 +4345,12 @@ declare namespace Deno {
    * Deno.removeSignalListener("SIGTERM", listener);
    * ```
    *
-   * _Note_: On Windows only `"SIGINT"` (CTRL+C) and `"SIGBREAK"` (CTRL+Break)
-   * are supported.
+   * _Note_: On Windows only `"SIGINT"` (CTRL+C), `"SIGBREAK"` (CTRL+Break),
+   * `"SIGHUP"` (the console window is closed) and `"SIGTERM"` (the system
+   * shuts down or the user logs off, which is only received by services) are
+   * supported. Windows terminates the process shortly after the console is
+   * closed or the system shuts down, so the handlers of these signals should
+   * clean up quickly.
    *
    * @category Runtime Environment
    */
@@ -4096,6 +4516,16 @@ declare namespace Deno {
     get stdin(): WritableStream<Uint8Array>;
     get stdout(): ReadableStream<Uint8Array>;
     get stderr(): ReadableStream<Uint8Array>;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * The pseudo-terminal of a child spawned with the `pty` option. Throws
+     * otherwise. */
+    get pty(): ChildPty;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * The message channel to a child spawned with the `ipc` option. Throws
+     * otherwise. */
+    get ipc(): IpcChannel;
     readonly pid: number;
     /** Get the status of the child. */
     readonly status: Promise<CommandStatus>;
@@ -4113,7 +4543,8 @@ declare namespace Deno {
      * from exiting. */
     ref(): void;
     /** Ensure that the status of the child process does not block the Deno
-     * process from exiting. */
+     * process from exiting. The child process is still killed when the Deno
+     * process exits, unless it was spawned with `detached: true`. */
     unref(): void;
   }
 
@@ -4179,6 +4610,209 @@ declare namespace Deno {
      *
      * @default {false} */
     windowsRawArguments?: boolean;
+
+    /** Spawns the subprocess as the leader of a new process group, whose id
+     * is the pid of the subprocess. The processes that it spawns join the
+     * group, so that they can all be terminated together with
+     * `Deno.kill(-child.pid)`, instead of leaking the grandchildren when only
+     * the subprocess is killed.
+     *
+     * On Windows, the subprocess is created with `CREATE_NEW_PROCESS_GROUP`,
+     * which also stops it from receiving the CTRL+C of the console, and the
+     * group is made up of the processes of a job object that it's assigned
+     * to. Detached descendants leave the group.
+     *
+     * @default {false} */
+    processGroup?: boolean;
+
+    /** Spawns the subprocess so that it outlives the Deno process, for
+     * example to start a daemon. It leads a new session, which is also a new
+     * process group, so that it doesn't receive the signals of the terminal
+     * of the Deno process, and `stdin`, `stdout` and `stderr` default to
+     * `"null"`. The Deno process still waits for it to exit, unless
+     * {@linkcode Deno.ChildProcess.unref} is called. Subprocesses that aren't
+     * detached are killed when the Deno process exits, even if they are
+     * unrefed.
+     *
+     * ```ts
+     * const child = new Deno.Command("./server", { detached: true }).spawn();
+     * child.unref();
+     * ```
+     *
+     * On Windows, the subprocess is created without a console and outside of
+     * the job object of the Deno process, if any.
+     *
+     * @default {false} */
+    detached?: boolean;
+
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Spawns the subprocess with a pseudo-terminal as its controlling
+     * terminal and its stdin, stdout and stderr, so that it behaves like it
+     * was run interactively, for example by printing colors and prompts. Its
+     * IO is then available through {@linkcode Deno.ChildProcess.pty} instead
+     * of the `stdin`, `stdout` and `stderr` options. `true` is a terminal of
+     * 80 columns and 24 rows.
+     *
+     * ```ts
+     * const child = new Deno.Command("bash", { pty: true }).spawn();
+     * const writer = child.pty.writable.getWriter();
+     * await writer.write(new TextEncoder().encode("ls --color\nexit\n"));
+     * await child.pty.readable.pipeTo(Deno.stdout.writable);
+     * ```
+     *
+     * On Windows, the pseudo-terminal is a pseudo console, which requires
+     * Windows 10 1809 or later, and can't be combined with the `ipc`,
+     * `limits` and `cgroup` options.
+     *
+     * Requires the `--unstable` flag. Only supported in
+     * {@linkcode Deno.Command.spawn}.
+     *
+     * @default {false} */
+    pty?: boolean | { columns: number; rows: number };
+
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Establishes a message channel with the subprocess, which is available
+     * as {@linkcode Deno.ChildProcess.ipc} in this process, and as
+     * {@linkcode Deno.parentIpc} in the subprocess if it is a Deno process.
+     * Other programs find the descriptor of the Unix socket of the channel in
+     * the `DENO_IPC_FD` environment variable.
+     *
+     * ```ts
+     * const child = new Deno.Command(Deno.execPath(), {
+     *   args: ["run", "--unstable", "worker.ts"],
+     *   ipc: true,
+     * }).spawn();
+     * await child.ipc.send({ task: "resize", width: 100 });
+     * for await (const { message } of child.ipc) {
+     *   console.log(message);
+     * }
+     * ```
+     *
+     * On Windows, the channel is a named pipe, whose client end other
+     * programs find the handle of in the `DENO_IPC_HANDLE` environment
+     * variable. Handles can't be sent over it, and it can't be combined with
+     * the `pty` option.
+     *
+     * Requires the `--unstable` flag. Only supported in
+     * {@linkcode Deno.Command.spawn}.
+     *
+     * @default {false} */
+    ipc?: boolean;
+
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Limits the resources that the subprocess can use. They are set as both
+     * the soft and hard limits, so that the subprocess can't raise them, and
+     * are inherited by its descendants.
+     *
+     * ```ts
+     * const command = new Deno.Command("./untrusted-task", {
+     *   limits: { cpuTime: 10, memory: 512 * 1024 * 1024, openFiles: 64 },
+     * });
+     * ```
+     *
+     * On Windows, the limits are enforced with a job object that the
+     * subprocess is assigned to before it runs, and `openFiles` isn't
+     * supported.
+     *
+     * Requires the `--unstable` flag. */
+    limits?: CommandResourceLimits;
+
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * The cgroup v2 to move the subprocess into before it runs, either as an
+     * absolute path or relative to `/sys/fs/cgroup`, so that the limits of
+     * the cgroup apply to the subprocess and its descendants. The cgroup must
+     * already exist and be writable by the current user.
+     *
+     * Requires the `--unstable` flag, and `allow-write` permission for the
+     * `cgroup.procs` file of the cgroup. Only supported on Linux. */
+    cgroup?: string;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The resource limits of a subprocess, set with the `limits` option of
+   * {@linkcode Deno.Command}. A process that exceeds its CPU time is killed
+   * with `SIGXCPU`, while allocating memory or opening files beyond the limits
+   * fails.
+   *
+   * @category Sub Process
+   */
+  export interface CommandResourceLimits {
+    /** The CPU time, in seconds. On Windows, it's the time spent in user
+     * mode, and a process that exceeds it is terminated. */
+    cpuTime?: number;
+    /** The size of the virtual memory, in bytes. It isn't enforced on
+     * macOS, and is the size of the committed memory on Windows. */
+    memory?: number;
+    /** The number of open file descriptors. Not supported on Windows. */
+    openFiles?: number;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A resource that can be sent alongside an IPC message.
+   *
+   * @category Sub Process
+   */
+  export type IpcHandle = FsFile | Conn | Listener;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A message received from an {@linkcode Deno.IpcChannel}.
+   *
+   * @category Sub Process
+   */
+  export interface IpcMessage {
+    /** The structured clone of the sent message. */
+    message: unknown;
+    /** The handles sent with the message, in the same order. */
+    handles: IpcHandle[];
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The message channel between a parent and a child Deno process spawned
+   * with the `ipc` option.
+   *
+   * @category Sub Process
+   */
+  export interface IpcChannel extends AsyncIterable<IpcMessage> {
+    /** Sends a message, which is serialized with the structured clone
+     * algorithm, along with the given files, connections and listeners. They
+     * are duplicated into the other process, and stay open in this one.
+     * Sending handles isn't supported on Windows. */
+    send(message: unknown, handles?: IpcHandle[]): Promise<void>;
+    /** Receives the next message, or `null` once the channel is closed. */
+    receive(): Promise<IpcMessage | null>;
+    /** Closes the channel. */
+    close(): void;
+    /** Make a pending `receive()` prevent the process from exiting. */
+    ref(): void;
+    /** Make a pending `receive()` not prevent the process from exiting. */
+    unref(): void;
+    [Symbol.asyncIterator](): AsyncIterableIterator<IpcMessage>;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The pseudo-terminal of a {@linkcode Deno.ChildProcess} spawned with the
+   * `pty` option.
+   *
+   * @category Sub Process
+   */
+  export interface ChildPty {
+    /** The output that the subprocess writes to the terminal, until it has
+     * exited. */
+    readonly readable: ReadableStream<Uint8Array>;
+    /** The input of the terminal, as typed by a user. */
+    readonly writable: WritableStream<Uint8Array>;
+    /** Resizes the terminal, which sends `SIGWINCH` to the subprocess on
+     * Unix. */
+    resize(columns: number, rows: number): void;
   }
 
   /**
@@ -4301,6 +4935,7 @@ declare namespace Deno {
     | "read"
     | "write"
     | "net"
+    | "vsock"
     | "env"
     | "sys"
     | "ffi"
@@ -4372,14 +5007,38 @@ declare namespace Deno {
    * @category Permissions */
   export interface NetPermissionDescriptor {
     name: "net";
-    /** Optional host string of the form `"<hostname>[:<port>]"`. Examples:
+    /** Optional host string of the form `"<hostname>[:<port>]"`, or a range
+     * of IP addresses in CIDR notation. A hostname starting with `"*."` is
+     * for all of its subdomains. Examples:
      *
      *      "github.com"
      *      "deno.land:8080"
+     *      "*.deno.land"
+     *      "10.0.0.0/8"
+     *
+     * A host name is allowed by a granted range when all the addresses it
+     * resolves to are in it, by the APIs that connect to the addresses
+     * directly, like `Deno.connect()` and `Deno.connectTls()`. The host
+     * names of `fetch()` and `WebSocket` must be granted themselves, as they
+     * may be connected to through a proxy.
      */
     host?: string;
   }
 
+  /** The permission descriptor for the `allow-vsock` permissions, which
+   * controls access to connecting to and listening on vsock addresses, the
+   * sockets between virtual machines and their host. The options `cid` and
+   * `port` allow scoping the permission to a specific context ID and port.
+   *
+   * @category Permissions */
+  export interface VsockPermissionDescriptor {
+    name: "vsock";
+    /** Optional context ID of the virtual machine or host. */
+    cid?: number;
+    /** Optional port, which is only used together with `cid`. */
+    port?: number;
+  }
+
   /** The permission descriptor for the `allow-env` permissions, which controls
    * access to being able to read and write to the process environment variables
    * as well as access other information about the environment. The option
@@ -4410,7 +5069,9 @@ declare namespace Deno {
       | "osRelease"
       | "osUptime"
       | "uid"
-      | "gid";
+      | "gid"
+      | "getPriority"
+      | "setPriority";
   }
 
   /** The permission descriptor for the `allow-ffi` permissions, which controls
@@ -4450,6 +5111,7 @@ declare namespace Deno {
     | ReadPermissionDescriptor
     | WritePermissionDescriptor
     | NetPermissionDescriptor
+    | VsockPermissionDescriptor
     | EnvPermissionDescriptor
     | SysPermissionDescriptor
     | FfiPermissionDescriptor
@@ -4918,10 +5580,26 @@ declare namespace Deno {
    */
   export function ftruncateSync(rid: number, len?: number): void;
 
+  /**
+   * Options which can be set when using {@linkcode Deno.utime},
+   * {@linkcode Deno.utimeSync}, {@linkcode Deno.futime} and
+   * {@linkcode Deno.futimeSync}.
+   *
+   * @category File System */
+  export interface UtimeOptions {
+    /** The creation time of the file, in the same forms as the access and
+     * modification times. It can only be set on Windows and on macOS, when the
+     * file system records it (like APFS), otherwise a
+     * `Deno.errors.NotSupported` error is thrown. */
+    birthtime?: number | bigint | Date;
+  }
+
   /**
    * Synchronously changes the access (`atime`) and modification (`mtime`) times
    * of a file stream resource referenced by `rid`. Given times are either in
-   * seconds (UNIX epoch time) or as `Date` objects.
+   * seconds (UNIX epoch time), possibly fractional, in nanoseconds as a
+   * `bigint`, or as `Date` objects. The creation time can also be set with
+   * the `birthtime` option, see {@linkcode Deno.UtimeOptions}.
    *
    * ```ts
    * const file = Deno.openSync("file.txt", { create: true, write: true });
@@ -4932,14 +5610,17 @@ declare namespace Deno {
    */
   export function futimeSync(
     rid: number,
-    atime: number | Date,
-    mtime: number | Date,
+    atime: number | bigint | Date,
+    mtime: number | bigint | Date,
+    options?: UtimeOptions,
   ): void;
 
   /**
    * Changes the access (`atime`) and modification (`mtime`) times of a file
    * stream resource referenced by `rid`. Given times are either in seconds
-   * (UNIX epoch time) or as `Date` objects.
+   * (UNIX epoch time), possibly fractional, in nanoseconds as a `bigint`, or
+   * as `Date` objects. The creation time can also be set with the
+   * `birthtime` option, see {@linkcode Deno.UtimeOptions}.
    *
    * ```ts
    * const file = await Deno.open("file.txt", { create: true, write: true });
@@ -4950,8 +5631,9 @@ declare namespace Deno {
    */
   export function futime(
     rid: number,
-    atime: number | Date,
-    mtime: number | Date,
+    atime: number | bigint | Date,
+    mtime: number | bigint | Date,
+    options?: UtimeOptions,
   ): Promise<void>;
 
   /**
@@ -4988,7 +5670,9 @@ declare namespace Deno {
   /**
    * Synchronously changes the access (`atime`) and modification (`mtime`) times
    * of a file system object referenced by `path`. Given times are either in
-   * seconds (UNIX epoch time) or as `Date` objects.
+   * seconds (UNIX epoch time), possibly fractional, in nanoseconds as a
+   * `bigint`, or as `Date` objects. The creation time can also be set with
+   * the `birthtime` option, see {@linkcode Deno.UtimeOptions}.
    *
    * ```ts
    * Deno.utimeSync("myfile.txt", 1556495550, new Date());
@@ -5001,14 +5685,17 @@ declare namespace Deno {
    */
   export function utimeSync(
     path: string | URL,
-    atime: number | Date,
-    mtime: number | Date,
+    atime: number | bigint | Date,
+    mtime: number | bigint | Date,
+    options?: UtimeOptions,
   ): void;
 
   /**
    * Changes the access (`atime`) and modification (`mtime`) times of a file
    * system object referenced by `path`. Given times are either in seconds
-   * (UNIX epoch time) or as `Date` objects.
+   * (UNIX epoch time), possibly fractional, in nanoseconds as a `bigint`, or
+   * as `Date` objects. The creation time can also be set with the
+   * `birthtime` option, see {@linkcode Deno.UtimeOptions}.
    *
    * ```ts
    * await Deno.utime("myfile.txt", 1556495550, new Date());
@@ -5021,8 +5708,9 @@ declare namespace Deno {
    */
   export function utime(
     path: string | URL,
-    atime: number | Date,
-    mtime: number | Date,
+    atime: number | bigint | Date,
+    mtime: number | bigint | Date,
+    options?: UtimeOptions,
   ): Promise<void>;
 
   /** The event yielded from an {@linkcode HttpConn} which represents an HTTP
@@ -5146,6 +5834,35 @@ declare namespace Deno {
      *
      * The default is 120 seconds. Set to `0` to disable timeouts. */
     idleTimeout?: number;
+    /** The number of milliseconds between keepalive pings sent to the
+     * client. If the client doesn't answer a ping with a pong within
+     * `pingTimeout`, the connection is closed with code 1001 and the
+     * `close` and `error` events are emitted.
+     *
+     * By default no keepalive pings are sent. */
+    pingInterval?: number;
+    /** The number of milliseconds to wait for the pong to a keepalive ping.
+     *
+     * Defaults to `pingInterval`. */
+    pingTimeout?: number;
+    /** The largest frame the client may send, in bytes. Larger frames are
+     * rejected based on their header, and the connection is closed with code
+     * 1009.
+     *
+     * The default is 64 MiB. */
+    maxFrameSize?: number;
+    /** The largest message the client may send, in bytes, including all of
+     * the fragments of a fragmented message. Larger messages close the
+     * connection with code 1009 as soon as the limit is exceeded.
+     *
+     * By default there is no limit besides `maxFrameSize`. */
+    maxMessageSize?: number;
+    /** Whether fragmented messages are reassembled before they are delivered.
+     * When `false`, every fragment is delivered as a message of its own as
+     * soon as it arrives, so the boundaries of fragmented messages are lost.
+     *
+     * @default {true} */
+    reassembleFragments?: boolean;
   }
 
   /**
@@ -5196,8 +5913,9 @@ declare namespace Deno {
    * which also signals processes.
    *
    * If `pid` is negative, the signal will be sent to the process group
-   * identified by `pid`. An error will be thrown if a negative `pid` is used on
-   * Windows.
+   * identified by `-pid`, like the ones created with the `processGroup` option
+   * of {@linkcode Deno.Command}. On Windows, only the process groups of the
+   * subprocesses of the current process can be terminated.
    *
    * ```ts
    * const p = Deno.run({
@@ -5207,6 +5925,16 @@ declare namespace Deno {
    * Deno.kill(p.pid, "SIGINT");
    * ```
    *
+   * ```ts
+   * const child = new Deno.Command("sh", {
+   *   args: ["-c", "sleep 10000 & sleep 10000"],
+   *   processGroup: true,
+   * }).spawn();
+   *
+   * // Terminates `sh` and both of the `sleep` processes.
+   * Deno.kill(-child.pid, "SIGTERM");
+   * ```
+   *
    * Requires `allow-run` permission.
    *
    * @tags allow-run
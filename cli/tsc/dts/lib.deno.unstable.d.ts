@@ -26,6 +26,220 @@ declare namespace Deno {
    */
   export function umask(mask?: number): number;
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The resource usage of the process, returned by
+   * {@linkcode Deno.resourceUsage}.
+   *
+   * @category Runtime Environment
+   */
+  export interface ResourceUsage {
+    /** The CPU time spent in user mode, in microseconds. */
+    userCpuTime: number;
+    /** The CPU time spent in kernel mode, in microseconds. */
+    systemCpuTime: number;
+    /** The peak resident set size, in bytes. */
+    maxRss: number;
+    /** The number of page faults that were served without any IO. Always `0`
+     * on Windows. */
+    minorPageFaults: number;
+    /** The number of page faults that required IO. On Windows, the number of
+     * all the page faults. */
+    majorPageFaults: number;
+    /** The number of file system reads. On Windows, the number of read
+     * operations of any kind of IO. */
+    fsRead: number;
+    /** The number of file system writes. On Windows, the number of write
+     * operations of any kind of IO. */
+    fsWrite: number;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Returns the CPU time, memory and IO that the process has used so far.
+   *
+   * ```ts
+   * const { userCpuTime, systemCpuTime, maxRss } = Deno.resourceUsage();
+   * console.log(`${(userCpuTime + systemCpuTime) / 1000}ms of CPU time`);
+   * console.log(`${maxRss / 1024 / 1024}MB of peak memory`);
+   * ```
+   *
+   * @category Runtime Environment
+   */
+  export function resourceUsage(): ResourceUsage;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Returns the scheduling priority of the process `pid`, or of the current
+   * process if `pid` is `0` or omitted. The priority is a nice value, from the
+   * highest priority `-20` to the lowest priority `19`. On Windows, it is
+   * mapped from the priority class of the process.
+   *
+   * ```ts
+   * console.log(Deno.getPriority()); // e.g. 0
+   * ```
+   *
+   * Requires `allow-sys` permission.
+   *
+   * @tags allow-sys
+   * @category Runtime Environment
+   */
+  export function getPriority(pid?: number): number;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Sets the scheduling priority of the process `pid`, or of the current
+   * process if `pid` is `0`. The priority is a nice value, from the highest
+   * priority `-20` to the lowest priority `19`. On Windows, it is mapped onto
+   * the closest priority class.
+   *
+   * Raising the priority of a process usually requires elevated privileges.
+   *
+   * ```ts
+   * const child = new Deno.Command("make", { args: ["all"] }).spawn();
+   * Deno.setPriority(child.pid, 10);
+   * ```
+   *
+   * Requires `allow-sys` permission.
+   *
+   * @tags allow-sys
+   * @category Runtime Environment
+   */
+  export function setPriority(pid: number, priority: number): void;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Returns the message channel to the parent process, if this process was
+   * spawned with the `ipc` option of {@linkcode Deno.Command}, and `null`
+   * otherwise.
+   *
+   * ```ts
+   * const ipc = Deno.parentIpc();
+   * for await (const { message } of ipc!) {
+   *   await ipc!.send({ echo: message });
+   * }
+   * ```
+   *
+   * @category Sub Process
+   */
+  export function parentIpc(): Deno.IpcChannel | null;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The durations of the turns of the event loop, in milliseconds. A turn
+   * runs the callbacks of the events that are ready, during which the other
+   * events have to wait, so long turns mean that the process is slow to
+   * respond. The percentiles are approximated to within a factor of two.
+   *
+   * @category Observability
+   */
+  export interface EventLoopDelay {
+    count: number;
+    min: number;
+    max: number;
+    mean: number;
+    p50: number;
+    p90: number;
+    p99: number;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The health of the event loop, as returned by
+   * {@linkcode Deno.eventLoopMetrics}.
+   *
+   * @category Observability
+   */
+  export interface EventLoopMetrics {
+    delay: EventLoopDelay;
+    /** The time spent running the turns, in milliseconds. */
+    active: number;
+    /** The time spent waiting for events in between, in milliseconds. */
+    idle: number;
+    /** The fraction of the time spent running, from `0` to `1`. */
+    utilization: number;
+    /** The number of async ops that haven't completed yet, such as pending
+     * reads, timers and requests. */
+    pendingOps: number;
+    /** The number of those that don't keep the process alive. */
+    pendingUnrefedOps: number;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Returns the metrics of the event loop since the start of the process or
+   * worker, or since they were last reset with the `reset` option. They are
+   * always collected, so they can be exported to a monitoring system without
+   * attaching the inspector.
+   *
+   * ```ts
+   * setInterval(() => {
+   *   const { delay, utilization } = Deno.eventLoopMetrics({ reset: true });
+   *   console.log(`p99 delay: ${delay.p99}ms, utilization: ${utilization}`);
+   * }, 10_000);
+   * ```
+   *
+   * @category Observability
+   */
+  export function eventLoopMetrics(
+    options?: { reset?: boolean },
+  ): EventLoopMetrics;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A change of the network configuration of the system, yielded by
+   * {@linkcode Deno.NetworkChangesWatcher}.
+   *
+   * - `"interface"`: an interface was added, removed, or brought up or down.
+   * - `"address"`: an address was added to or removed from an interface.
+   * - `"route"`: a default route was added, removed or changed. On macOS and
+   *   the BSDs, the changes of any route are reported.
+   *
+   * @category Network
+   */
+  export interface NetworkChangeEvent {
+    kind: "interface" | "address" | "route";
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Returned by {@linkcode Deno.watchNetworkChanges}. It is an async iterator
+   * of the changes of the network configuration.
+   *
+   * @category Network
+   */
+  export interface NetworkChangesWatcher
+    extends AsyncIterable<NetworkChangeEvent> {
+    /** Stops watching the network changes. */
+    close(): void;
+    [Symbol.asyncIterator](): AsyncIterableIterator<NetworkChangeEvent>;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Watches the network configuration of the system, and yields an event when
+   * interfaces, addresses or default routes change, so that the program can
+   * react to the changes of connectivity without polling
+   * {@linkcode Deno.networkInterfaces}. The changes that happen together may
+   * be reported as a single event of each kind.
+   *
+   * ```ts
+   * const watcher = Deno.watchNetworkChanges();
+   * for await (const { kind } of watcher) {
+   *   console.log(`The network configuration changed: ${kind}`);
+   *   console.log(Deno.networkInterfaces());
+   * }
+   * ```
+   *
+   * Call `watcher.close()` to stop watching.
+   *
+   * Requires `allow-sys` permission.
+   *
+   * @tags allow-sys
+   * @category Network
+   */
+  export function watchNetworkChanges(): NetworkChangesWatcher;
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * All plain number types for interfacing with foreign functions.
@@ -800,6 +1014,10 @@ declare namespace Deno {
     rid: number;
     /** Close the HTTP client. */
     close(): void;
+    /** Close the idle connections in the connection pool of the client. The
+     * connections of the requests in progress are closed once they are done,
+     * and new requests open new connections. */
+    closeIdleConnections(): void;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -820,20 +1038,264 @@ declare namespace Deno {
     certChain?: string;
     /** PEM formatted (RSA or PKCS8) private key of client certificate. */
     privateKey?: string;
+    /** A client certificate and its private key in a PKCS#12 (`.p12` or
+     * `.pfx`) bundle, instead of `certChain` and `privateKey`.
+     *
+     * ```ts
+     * const client = Deno.createHttpClient({
+     *   pkcs12: {
+     *     data: await Deno.readFile("./client.p12"),
+     *     password: Deno.env.get("CLIENT_P12_PASSWORD"),
+     *   },
+     * });
+     * ```
+     */
+    pkcs12?: Pkcs12Bundle;
+    /** Client certificates that are used instead of the one of the client
+     * for the requests to some hosts. The first certificate with a matching
+     * host is used. */
+    clientCertificates?: HostClientCertificate[];
+    /** The resolver used to look up the hosts the client connects to,
+     * instead of the system resolver. */
+    resolver?: DnsResolver;
+    /** Addresses that are connected to for these hosts, without resolving
+     * them, e.g. to pin a hostname to a local server in tests. The `Host`
+     * header and the name the certificate is verified against are still the
+     * ones of the URL.
+     *
+     * ```ts
+     * const client = Deno.createHttpClient({
+     *   hosts: { "api.example.com": ["127.0.0.1"] },
+     * });
+     * ```
+     */
+    hosts?: Record<string, string[]>;
+    /** The time, in seconds, that the addresses of hosts are cached for and
+     * shared by the connections of the client, whatever the TTL of their
+     * records is. By default hosts are looked up for each new connection. */
+    dnsCacheTtl?: number;
+    /** Caches the responses of the client in the Deno cache directory,
+     * following the `Cache-Control`, `Expires`, `ETag` and `Last-Modified`
+     * headers (RFC 9111). Stale responses are revalidated with a
+     * conditional request. How a request uses the cache can be set with its
+     * `cache` option, e.g. `"only-if-cached"` to only respond with stored
+     * responses while offline.
+     *
+     * ```ts
+     * const client = Deno.createHttpClient({ httpCache: true });
+     * const response = await fetch("https://api.example.com/items", {
+     *   client,
+     *   cache: "force-cache",
+     * });
+     * ```
+     *
+     * Only the responses to `GET` requests are stored, separately for each
+     * value of the request headers named by their `Vary` header. Responses
+     * that set cookies are never stored. Like `localStorage`, the cache is
+     * separate for each `--location` or configuration file, or else main
+     * module. */
+    httpCache?: boolean;
+    /** Stores the cookies set by responses and sends them with the later
+     * requests of the client, including the ones of followed redirects, like
+     * a browser does. `Cookie` headers set on a request are sent before the
+     * cookies of the jar.
+     *
+     * ```ts
+     * const client = Deno.createHttpClient({
+     *   cookieJar: {
+     *     initialCookies: JSON.parse(
+     *       await Deno.readTextFile("./cookies.json"),
+     *     ),
+     *     onChange(cookies) {
+     *       Deno.writeTextFileSync("./cookies.json", JSON.stringify(cookies));
+     *     },
+     *   },
+     * });
+     * ```
+     */
+    cookieJar?: boolean | CookieJarOptions;
+    /** Sends all the requests of the client over the Unix domain socket at
+     * `path`, whatever their host is, e.g. to talk to the Docker daemon.
+     * Requires `allow-read` and `allow-write` permission for the path
+     * instead of `allow-net`. Can't be combined with a `proxy`.
+     *
+     * ```ts
+     * const client = Deno.createHttpClient({
+     *   transport: { path: "/var/run/docker.sock" },
+     * });
+     * const response = await fetch(
+     *   "http://localhost/v1.43/containers/json",
+     *   { client },
+     * );
+     * ```
+     *
+     * Not supported on Windows. */
+    transport?: { path: string };
+    /** The maximum number of idle connections that are kept open per host.
+     * Defaults to no limit. */
+    poolMaxIdlePerHost?: number;
+    /** The time, in milliseconds, after which idle connections are closed,
+     * or `false` to keep them open. Defaults to 90 seconds. */
+    poolIdleTimeout?: number | false;
+    /** The time, in milliseconds, after which the connections of the pool
+     * aren't reused for new requests anymore, e.g. to pick up DNS changes.
+     * The pool is replaced by a new one at that point, so a connection may
+     * be retired before it is that old. Defaults to no limit. */
+    poolMaxLifetime?: number;
+    /** Whether HTTP/1.1 is used. Defaults to `true`. */
+    http1?: boolean;
+    /** Whether HTTP/2 is used. Defaults to `true`, in which case it is
+     * negotiated with TLS servers. With `http1: false`, HTTP/2 is also used
+     * for `http:` URLs without negotiating it. */
+    http2?: boolean;
+    /** The time, in milliseconds, that requests may take overall, from
+     * connecting to reading the whole response body and including their
+     * retries. Requests fail with a `TypeError` once it has elapsed. A
+     * request can override it with the `timeout` option of `fetch()`. */
+    timeout?: number;
+    /** The time, in milliseconds, that connecting to a server may take. Does
+     * not apply to a Unix socket `transport`. */
+    connectTimeout?: number;
+    /** Retries idempotent requests that fail to connect, which the server
+     * therefore can't have received. Requests with a `ReadableStream` body
+     * are never retried. A request can override it with the `retry` option
+     * of `fetch()`.
+     *
+     * ```ts
+     * const client = Deno.createHttpClient({
+     *   connectTimeout: 5_000,
+     *   retry: { maxRetries: 3, initialDelay: 200 },
+     * });
+     * ```
+     */
+    retry?: RetryOptions;
+    /** Sends the requests to an `https:` origin over HTTP/3 once one of its
+     * responses advertised HTTP/3 on the same port with an `Alt-Svc` header.
+     * If a QUIC connection to the origin can't be established, the request
+     * is sent with HTTP/2 or HTTP/1.1 instead. Can't be combined with a
+     * `proxy` or a `transport`. Defaults to `false`. */
+    http3?: boolean;
+    /** Sends requests to `https:` origins over HTTP/3 right away, without
+     * waiting for them to advertise it. Requires `http3: true`. */
+    http3PriorKnowledge?: boolean;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
    *
-   * The definition of a proxy when specifying
+   * The retries of the requests of {@linkcode Deno.createHttpClient} clients,
+   * which are delayed by an exponential backoff.
+   *
+   * @category Fetch API
+   */
+  export interface RetryOptions {
+    /** The number of times a request is retried. */
+    maxRetries: number;
+    /** The delay before the first retry, in milliseconds, which is doubled
+     * for every further retry. Defaults to 100. */
+    initialDelay?: number;
+    /** The longest delay between two retries, in milliseconds. Defaults to
+     * 10000. */
+    maxDelay?: number;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The options of the cookie jar of a client, when specifying
    * {@linkcode Deno.CreateHttpClientOptions}.
    *
    * @category Fetch API
    */
+  export interface CookieJarOptions {
+    /** The cookies that the jar starts with, e.g. the ones that were saved
+     * by `onChange` before. */
+    initialCookies?: Cookie[];
+    /** Called with all the cookies of the jar after a response changed them,
+     * so that they can be persisted. */
+    onChange?: (cookies: Cookie[]) => void;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A cookie in the cookie jar of a {@linkcode Deno.HttpClient}.
+   *
+   * @category Fetch API
+   */
+  export interface Cookie {
+    name: string;
+    value: string;
+    /** The host, or the domain for `hostOnly: false`, that the cookie is sent
+     * to. */
+    domain: string;
+    path: string;
+    /** When the cookie expires, in milliseconds since the Unix epoch. Session
+     * cookies have no expiry. */
+    expires?: number | null;
+    secure?: boolean;
+    httpOnly?: boolean;
+    /** Whether the cookie is only sent to `domain`, and not also to its
+     * subdomains. */
+    hostOnly?: boolean;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A PKCS#12 bundle with a client certificate, its chain and its private
+   * key, when specifying {@linkcode Deno.CreateHttpClientOptions}. Bundles
+   * encrypted with PBES2 and AES, the default of current tools like OpenSSL 3,
+   * and with the legacy RC2 and 3DES encryptions of older tools and Windows
+   * are supported.
+   *
+   * @category Fetch API
+   */
+  export interface Pkcs12Bundle {
+    /** The contents of the bundle. */
+    data: Uint8Array;
+    /** The password the bundle is encrypted with. Defaults to an empty
+     * password. */
+    password?: string;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A client certificate that is only used for the requests to some hosts,
+   * when specifying {@linkcode Deno.CreateHttpClientOptions}.
+   *
+   * @category Fetch API
+   */
+  export interface HostClientCertificate {
+    /** The hosts to use the certificate for. `"*.example.com"` matches the
+     * subdomains of `example.com`, but not `example.com` itself. */
+    hosts: string[];
+    /** PEM formatted client certificate chain. */
+    certChain?: string;
+    /** PEM formatted (RSA or PKCS8) private key of client certificate. */
+    privateKey?: string;
+    /** The certificate and its private key in a PKCS#12 bundle, instead of
+     * `certChain` and `privateKey`. */
+    pkcs12?: Pkcs12Bundle;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The definition of a proxy when specifying
+   * {@linkcode Deno.CreateHttpClientOptions}, or the `proxy` option of a
+   * single {@linkcode fetch} request.
+   *
+   * @category Fetch API
+   */
   export interface Proxy {
-    /** The string URL of the proxy server to use. */
+    /** The string URL of the proxy server to use. `http:`, `https:` and
+     * `socks5:` URLs are supported. */
     url: string;
-    /** The basic auth credentials to be used against the proxy server. */
+    /** The basic auth credentials to be used against the proxy server. For
+     * SOCKS5 proxies they are sent with username/password authentication. */
     basicAuth?: BasicAuth;
+    /** Hosts that are connected to directly instead of through the proxy,
+     * like the entries of the `NO_PROXY` environment variable. An entry
+     * matches the host and its subdomains, an IP address or a network like
+     * `"10.0.0.0/8"` matches the addresses it contains, and `"*"` matches
+     * every host. */
+    noProxy?: string[];
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -901,6 +1363,8 @@ declare namespace Deno {
     leave: () => Promise<void>;
     /** Sets the multicast loopback option. If enabled, multicast packets will be looped back to the local socket. */
     setLoopback: (loopback: boolean) => Promise<void>;
+    /** Sets the hop limit of outgoing multicast packets for this socket. */
+    setTTL: (ttl: number) => Promise<void>;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -922,6 +1386,23 @@ declare namespace Deno {
       networkInterface: number,
     ): Promise<MulticastV6Membership>;
 
+    /** Sets the multicast loopback option of the socket, without joining a
+     * group. If enabled, multicast packets sent from the socket will be looped
+     * back to it. */
+    setMulticastLoopback(loopback: boolean): Promise<void>;
+
+    /** Sets the time-to-live (or, for IPv6 sockets, the hop limit) of
+     * outgoing multicast packets, which limits how many routers they may
+     * cross. The default of `1` keeps them on the local network. */
+    setMulticastTTL(ttl: number): Promise<void>;
+
+    /** Selects the network interface that outgoing multicast packets are
+     * sent from. For IPv4 this is the address of the interface, and for IPv6
+     * its index, matching the `networkInterface` argument of
+     * {@linkcode DatagramConn.joinMulticastV4} and
+     * {@linkcode DatagramConn.joinMulticastV6}. */
+    setMulticastInterface(networkInterface: string | number): Promise<void>;
+
     /** Waits for and resolves to the next message to the instance.
      *
      * Messages are received in the format of a tuple containing the data array
@@ -948,13 +1429,27 @@ declare namespace Deno {
      *
      * On Linux this will cause the kernel to distribute incoming connections
      * across the different processes that are listening on the same address and
-     * port.
+     * port. On macOS the port can be shared, but connections are only
+     * delivered to the most recently opened listener.
      *
-     * This flag is only supported on Linux. It is silently ignored on other
-     * platforms.
+     * This flag is only supported on Linux and macOS. It is silently ignored
+     * on other platforms.
      *
      * @default {false} */
     reusePort?: boolean;
+
+    /** When `true`, every accepted connection is expected to start with a
+     * [PROXY protocol](https://www.haproxy.org/download/2.8/doc/proxy-protocol.txt)
+     * (v1 or v2) header, as sent by load balancers such as HAProxy or AWS
+     * NLB. The header is consumed, and the client address it carries is
+     * reported as the `remoteAddr` of the connection.
+     *
+     * Connections that don't send a valid header are dropped. Only enable this
+     * if the listener can't be reached without going through the proxy, since
+     * anyone who can connect directly can claim an arbitrary address.
+     *
+     * @default {false} */
+    proxyProtocol?: boolean;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -965,7 +1460,12 @@ declare namespace Deno {
    * @category Network
    */
   export interface UnixListenOptions {
-    /** A path to the Unix Socket. */
+    /** A path to the Unix Socket.
+     *
+     * On Linux, a path starting with a NUL byte, like `"\0name"`, is the
+     * name of a socket in the abstract namespace instead of a file. Abstract
+     * sockets require `allow-net` permission for the host `@name` rather than
+     * `allow-read` and `allow-write` permission. */
     path: string;
   }
 
@@ -984,6 +1484,17 @@ declare namespace Deno {
      * @default {false} */
     reuseAddress?: boolean;
 
+    /** When `true` the SO_REUSEPORT flag will be set on the socket. This
+     * allows multiple processes or workers to bind the same address and
+     * port, with the kernel distributing incoming datagrams between them on
+     * Linux.
+     *
+     * This flag is only supported on Linux and macOS. It is silently ignored
+     * on other platforms.
+     *
+     * @default {false} */
+    reusePort?: boolean;
+
     /** When `true`, sent multicast packets will be looped back to the local socket.
      *
      * @default {false} */
@@ -1007,6 +1518,39 @@ declare namespace Deno {
     options: UnixListenOptions & { transport: "unix" },
   ): Listener;
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Options for vsock listeners and connections, which use the `AF_VSOCK`
+   * sockets between virtual machines (e.g. Firecracker or Cloud Hypervisor
+   * guests) and their host. They are only supported on Linux.
+   *
+   * @category Network
+   */
+  export interface VsockOptions {
+    transport: "vsock";
+    /** The context ID (CID) of the machine. The host is `2`, and a listener
+     * can use `0xFFFFFFFF` (`VMADDR_CID_ANY`) to accept connections
+     * for any CID. */
+    cid: number;
+    port: number;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Listen announces on a vsock address, for example in a guest agent that
+   * the host connects to.
+   *
+   * ```ts
+   * const listener = Deno.listen({ transport: "vsock", cid: 0xFFFFFFFF, port: 1024 });
+   * ```
+   *
+   * Requires `allow-vsock` permission.
+   *
+   * @tags allow-vsock
+   * @category Network
+   */
+  export function listen(options: VsockOptions): Listener;
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * Listen announces on the local transport address.
@@ -1052,12 +1596,37 @@ declare namespace Deno {
     options: UnixListenOptions & { transport: "unixpacket" },
   ): DatagramConn;
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Opens a Unix datagram socket that isn't bound to a path. It can send
+   * datagrams, for example to a system daemon such as systemd-journald, but
+   * not receive any, since other sockets have no address to reply to.
+   *
+   * ```ts
+   * const socket = Deno.listenDatagram({ transport: "unixpacket" });
+   * await socket.send(new TextEncoder().encode("MESSAGE=hello\n"), {
+   *   transport: "unixpacket",
+   *   path: "/run/systemd/journal/socket",
+   * });
+   * ```
+   *
+   * Requires `allow-write` permission for the paths datagrams are sent to.
+   *
+   * @tags allow-write
+   * @category Network
+   */
+  export function listenDatagram(
+    options: { transport: "unixpacket"; path?: undefined },
+  ): DatagramConn;
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * @category Network
    */
   export interface UnixConnectOptions {
     transport: "unix";
+    /** A path to the Unix Socket, or the name of an abstract socket with a
+     * leading NUL byte on Linux. See {@linkcode UnixListenOptions.path}. */
     path: string;
   }
 
@@ -1100,6 +1669,110 @@ declare namespace Deno {
    */
   export function connect(options: UnixConnectOptions): Promise<UnixConn>;
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A connection over a vsock socket.
+   *
+   * @category Network
+   */
+  // deno-lint-ignore no-empty-interface
+  export interface VsockConn extends Conn {}
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Connects to a vsock address, for example to the host (CID `2`) from
+   * a guest.
+   *
+   * ```ts
+   * const conn = await Deno.connect({ transport: "vsock", cid: 2, port: 1024 });
+   * ```
+   *
+   * Requires `allow-vsock` permission.
+   *
+   * @tags allow-vsock
+   * @category Network
+   */
+  export function connect(options: VsockOptions): Promise<VsockConn>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A resource that can be sent over a Unix connection with
+   * {@linkcode UnixConn.sendFds}.
+   *
+   * @category Network
+   */
+  export type UnixSendableResource = FsFile | Conn | Listener;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The result of {@linkcode UnixConn.receiveFds}.
+   *
+   * @category Network
+   */
+  export interface UnixReceivedFds {
+    /** The number of bytes read into the buffer, or `null` if the connection
+     * has been closed by the peer. */
+    nread: number | null;
+    /** The resources for the file descriptors that were passed along with
+     * the data. Stream sockets are surfaced as connections or listeners, and
+     * any other descriptor as a file. */
+    resources: UnixSendableResource[];
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The credentials of the process at the other end of a Unix connection, as
+   * they were when the connection was established.
+   *
+   * @category Network
+   */
+  export interface UnixPeerCredentials {
+    /** The process ID of the peer, which is not available on all
+     * platforms. */
+    pid: number | null;
+    uid: number;
+    gid: number;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * @category Network
+   */
+  export interface UnixConn {
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Writes `data` to the connection, passing the file descriptors of the
+     * given resources along with it (`SCM_RIGHTS`). The peer receives copies
+     * of the descriptors, the resources remain open in this process.
+     *
+     * ```ts
+     * const conn = await Deno.connect({ transport: "unix", path: "/tmp/worker.sock" });
+     * const file = await Deno.open("./data.txt");
+     * await conn.sendFds(new TextEncoder().encode("data.txt"), [file]);
+     * ```
+     */
+    sendFds(
+      data: Uint8Array,
+      resources: UnixSendableResource[],
+    ): Promise<number>;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Reads data into the buffer like {@linkcode Conn.read}, along with any
+     * file descriptors that were sent with it. At most 64 descriptors are
+     * received with one message.
+     *
+     * ```ts
+     * const { nread, resources } = await conn.receiveFds(new Uint8Array(1024));
+     * ```
+     */
+    receiveFds(buffer: Uint8Array): Promise<UnixReceivedFds>;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Returns the credentials of the peer process (`SO_PEERCRED` on Linux,
+     * `getpeereid()` on other Unix platforms). */
+    peerCredentials(): UnixPeerCredentials;
+  }
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * @category Network
@@ -1122,7 +1795,246 @@ declare namespace Deno {
      * TLS handshake.
      */
     alpnProtocols?: string[];
-  }
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * The resolver used to look up `hostname`, instead of the system
+     * resolver.
+     */
+    resolver?: DnsResolver;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * @category Network
+   */
+  export interface ConnectOptions {
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * The resolver used to look up `hostname`, instead of the system
+     * resolver.
+     */
+    resolver?: DnsResolver;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * @category Network
+   */
+  export interface ResolveDnsOptions {
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * The resolver to query, instead of the system resolver. Its `hosts`
+     * overrides are returned for `A` and `AAAA` queries. When set,
+     * `nameServer` is ignored.
+     */
+    resolver?: DnsResolver;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A name server queried by a {@linkcode DnsResolver}.
+   *
+   * @category Network
+   */
+  export interface DnsResolverNameServer {
+    /** The IP address of the name server. */
+    ipAddr: string;
+    /** The port number the queries are sent to.
+     *
+     * @default {53 for "udp" and "tcp", 443 for "https"} */
+    port?: number;
+    /** How the name server is queried. `"https"` sends
+     * [DNS-over-HTTPS](https://www.rfc-editor.org/rfc/rfc8484) queries to the
+     * `/dns-query` path of the server.
+     *
+     * @default {"udp"} */
+    protocol?: "udp" | "tcp" | "https";
+    /** The name that the TLS certificate of a DNS-over-HTTPS name server is
+     * verified against. Defaults to `ipAddr`. */
+    serverName?: string;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Options for {@linkcode Deno.createDnsResolver}.
+   *
+   * @category Network
+   */
+  export interface CreateDnsResolverOptions {
+    /** The name servers to query. If not specified, defaults to the system
+     * configuration. For example `/etc/resolv.conf` on Unix-like systems. */
+    nameServers?: DnsResolverNameServer[];
+    /** Addresses that are used for these host names without querying a name
+     * server, like entries of a hosts file. */
+    hosts?: Record<string, string[]>;
+    /** The maximum number of responses that are cached.
+     *
+     * @default {32} */
+    cacheSize?: number;
+    /** The minimum number of seconds that responses are cached for, even if
+     * their records have a shorter TTL. */
+    minTtl?: number;
+    /** The maximum number of seconds that responses are cached for, even if
+     * their records have a longer TTL. */
+    maxTtl?: number;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A DNS resolver created with {@linkcode Deno.createDnsResolver}. Its cache
+   * is shared by all the APIs it is passed to.
+   *
+   * @category Network
+   */
+  export class DnsResolver {
+    /** The resource ID of the resolver. */
+    readonly rid: number;
+    /** Releases the resolver. Connections that are in progress keep using
+     * it. */
+    close(): void;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Creates a DNS resolver that can be passed to {@linkcode Deno.resolveDns},
+   * {@linkcode Deno.connect}, {@linkcode Deno.connectTls} and
+   * {@linkcode Deno.createHttpClient} with their `resolver` option, to look
+   * up hosts with custom name servers, DNS-over-HTTPS or per-host overrides.
+   *
+   * ```ts
+   * const resolver = Deno.createDnsResolver({
+   *   nameServers: [
+   *     { ipAddr: "1.1.1.1", protocol: "https", serverName: "cloudflare-dns.com" },
+   *   ],
+   *   hosts: { "api.internal": ["10.0.0.5"] },
+   *   maxTtl: 300,
+   * });
+   * const conn = await Deno.connect({ hostname: "example.com", port: 80, resolver });
+   * const client = Deno.createHttpClient({ resolver });
+   * const response = await fetch("https://api.internal", { client });
+   * ```
+   *
+   * Requires `allow-net` permission for the name servers.
+   *
+   * @tags allow-net
+   * @category Network
+   */
+  export function createDnsResolver(
+    options?: CreateDnsResolverOptions,
+  ): DnsResolver;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Options for {@linkcode Deno.icmpSocket}.
+   *
+   * @category Network
+   */
+  export interface IcmpSocketOptions {
+    /** The IP version of the socket.
+     *
+     * @default {"ipv4"} */
+    family?: "ipv4" | "ipv6";
+    /** The TTL, or hop limit for IPv6, of the packets that are sent. */
+    ttl?: number;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Options for {@linkcode Deno.IcmpSocket.sendEchoRequest}.
+   *
+   * @category Network
+   */
+  export interface IcmpEchoRequestOptions {
+    /** Identifies the requests of a process. Sockets which don't need
+     * elevated privileges ignore it and the system picks the identifier.
+     *
+     * @default {0} */
+    identifier?: number;
+    /** @default {0} */
+    sequence?: number;
+    /** The payload of the request, which is sent back in the reply. */
+    data?: Uint8Array;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A message received from an {@linkcode Deno.IcmpSocket}.
+   *
+   * @category Network
+   */
+  export interface IcmpMessage {
+    /** The ICMP message type, e.g. `0` for an IPv4 echo reply or `11` when
+     * the TTL of a request was exceeded. */
+    type: number;
+    code: number;
+    /** The identifier of echo requests and replies, `null` otherwise. */
+    identifier: number | null;
+    /** The sequence number of echo requests and replies, `null` otherwise. */
+    sequence: number | null;
+    /** The TTL of the received packet, if the system reports it. */
+    ttl: number | null;
+    /** The address that sent the message. */
+    hostname: string;
+    /** The payload following the ICMP header. */
+    data: Uint8Array;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A socket created with {@linkcode Deno.icmpSocket}.
+   *
+   * @category Network
+   */
+  export class IcmpSocket {
+    /** The resource ID of the socket. */
+    readonly rid: number;
+    readonly family: "ipv4" | "ipv6";
+    /** Sends an echo request to a host.
+     *
+     * Requires `allow-net` permission for the host. */
+    sendEchoRequest(
+      hostname: string,
+      options?: IcmpEchoRequestOptions,
+    ): Promise<void>;
+    /** Waits for the next message received by the socket. A socket which
+     * needed elevated privileges receives all the ICMP messages of the
+     * system, not only the replies to its requests. */
+    receive(): Promise<IcmpMessage>;
+    /** Changes the TTL of the packets that are sent. */
+    setTtl(ttl: number): void;
+    close(): void;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Opens an ICMP socket to send echo requests, as the `ping` and
+   * `traceroute` utilities do.
+   *
+   * ```ts
+   * const socket = Deno.icmpSocket({ ttl: 64 });
+   * await socket.sendEchoRequest("1.1.1.1", { sequence: 1 });
+   * const reply = await socket.receive();
+   * console.log(reply.hostname, reply.sequence, reply.ttl);
+   * socket.close();
+   * ```
+   *
+   * Unprivileged ICMP sockets are used where the system provides them, which
+   * on Linux depends on the `net.ipv4.ping_group_range` sysctl. Otherwise a
+   * raw socket is opened, which needs the `CAP_NET_RAW` capability, and
+   * opening the socket throws a {@linkcode Deno.errors.PermissionDenied}
+   * error without it.
+   *
+   * Since the socket receives the messages of any host, opening it requires
+   * net permission to `0.0.0.0`, or `::` with `family: "ipv6"`, like
+   * listening on all interfaces does. Sending an echo request also requires
+   * net permission to its host.
+   *
+   * Not supported on Windows.
+   *
+   * @tags allow-net
+   * @category Network
+   */
+  export function icmpSocket(options?: IcmpSocketOptions): IcmpSocket;
 
   /** **UNSTABLE**: New API, yet to be vetted.
    *
@@ -1183,6 +2095,122 @@ declare namespace Deno {
      * TLS handshake.
      */
     alpnProtocols?: string[];
+
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * When `true`, every accepted connection is expected to start with a
+     * PROXY protocol header preceding the TLS handshake. See
+     * {@linkcode TcpListenOptions.proxyProtocol}.
+     *
+     * @default {false} */
+    proxyProtocol?: boolean;
+
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Certificates to present to clients based on the server name they
+     * request (SNI), keyed by hostname. A hostname may start with a `*.`
+     * wildcard label, which matches exactly one label. Clients that don't
+     * send a server name, or send one that isn't listed, are presented the
+     * `cert` and `key` of the listener.
+     *
+     * ```ts
+     * const listener = Deno.listenTls({
+     *   port: 443,
+     *   cert: defaultCert,
+     *   key: defaultKey,
+     *   sni: {
+     *     "example.com": { cert: exampleCert, key: exampleKey },
+     *     "*.example.com": { cert: wildcardCert, key: wildcardKey },
+     *   },
+     * });
+     * ```
+     *
+     * It may instead be a function, called with the server name of every
+     * client that sends one, which returns (or resolves to) the certificate
+     * to present. When it returns `undefined` the `cert` and `key` of the
+     * listener are presented, and when it throws the handshake fails.
+     *
+     * ```ts
+     * const listener = Deno.listenTls({
+     *   port: 443,
+     *   cert: defaultCert,
+     *   key: defaultKey,
+     *   sni: async (serverName) => await loadCertificate(serverName),
+     * });
+     * ```
+     */
+    sni?:
+      | Record<string, { cert: string; key: string }>
+      | ((
+        serverName: string,
+      ) =>
+        | { cert: string; key: string }
+        | undefined
+        | Promise<{ cert: string; key: string } | undefined>);
+
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Provisions the certificate of the listener from an ACME certificate
+     * authority (Let's Encrypt by default), and renews it in the background
+     * before it expires. See {@linkcode AcmeOptions}.
+     *
+     * ```ts
+     * const listener = Deno.listenTls({
+     *   port: 443,
+     *   acme: {
+     *     domains: ["example.com"],
+     *     contact: ["admin@example.com"],
+     *     cacheDir: "./acme",
+     *   },
+     * });
+     * ```
+     *
+     * @tags allow-net, allow-read, allow-write
+     */
+    acme?: AcmeOptions;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Options for provisioning certificates from an ACME certificate authority.
+   *
+   * Ownership of the domains is proven with the `tls-alpn-01` challenge by
+   * default, which is answered by the listener itself, so it must be
+   * reachable on port 443 for each of the domains. `acme-tls/1` is added to
+   * the ALPN protocols of the listener for that purpose. With the `http-01`
+   * challenge, a plain HTTP server answers the challenges on `httpPort`
+   * while they are validated, which must be reachable on port 80.
+   *
+   * The account credentials and the private key of the certificate are
+   * stored in `cacheDir` with files that only the current user can read.
+   *
+   * Until a certificate has been provisioned, the `cert` and `key` of the
+   * listener are presented if specified, otherwise handshakes fail.
+   *
+   * @category Network
+   */
+  export interface AcmeOptions {
+    /** The domains the certificate is issued for. */
+    domains: string[];
+    /** Email addresses the certificate authority may use to contact the
+     * owner of the account, for example about expiring certificates. */
+    contact?: string[];
+    /** The directory URL of the certificate authority.
+     *
+     * @default {"https://acme-v02.api.letsencrypt.org/directory"} */
+    directoryUrl?: string;
+    /** The directory the account credentials and certificate are stored in,
+     * so that they are reused across restarts. */
+    cacheDir: string;
+    /** The challenge that proves the ownership of the domains.
+     *
+     * @default {"tls-alpn-01"} */
+    challenge?: "tls-alpn-01" | "http-01";
+    /** The port the `http-01` challenges are answered on, when it is
+     * forwarded from port 80.
+     *
+     * @default {80} */
+    httpPort?: number;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -1233,6 +2261,512 @@ declare namespace Deno {
    */
   export function funlockSync(rid: number): void;
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Returns the value of an extended attribute of a file, or `null` if the
+   * file doesn't have it. These are the xattrs of Linux and macOS, where names
+   * on Linux need a namespace like `user.`, and the alternate data streams of
+   * NTFS on Windows.
+   *
+   * ```ts
+   * const value = await Deno.getXattr("file.txt", "user.checksum");
+   * ```
+   *
+   * Requires `allow-read` permission.
+   *
+   * @tags allow-read
+   * @category File System
+   */
+  export function getXattr(
+    path: string | URL,
+    name: string,
+  ): Promise<Uint8Array | null>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Synchronously returns the value of an extended attribute of a file, or
+   * `null` if the file doesn't have it.
+   *
+   * ```ts
+   * const value = Deno.getXattrSync("file.txt", "user.checksum");
+   * ```
+   *
+   * Requires `allow-read` permission.
+   *
+   * @tags allow-read
+   * @category File System
+   */
+  export function getXattrSync(
+    path: string | URL,
+    name: string,
+  ): Uint8Array | null;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Sets the value of an extended attribute of a file, replacing the previous
+   * one. A string value is encoded as UTF-8.
+   *
+   * ```ts
+   * await Deno.setXattr("file.txt", "user.checksum", "d41d8cd9");
+   * ```
+   *
+   * Requires `allow-write` permission.
+   *
+   * @tags allow-write
+   * @category File System
+   */
+  export function setXattr(
+    path: string | URL,
+    name: string,
+    value: string | Uint8Array,
+  ): Promise<void>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Synchronously sets the value of an extended attribute of a file,
+   * replacing the previous one. A string value is encoded as UTF-8.
+   *
+   * ```ts
+   * Deno.setXattrSync("file.txt", "user.checksum", "d41d8cd9");
+   * ```
+   *
+   * Requires `allow-write` permission.
+   *
+   * @tags allow-write
+   * @category File System
+   */
+  export function setXattrSync(
+    path: string | URL,
+    name: string,
+    value: string | Uint8Array,
+  ): void;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Returns the names of the extended attributes of a file.
+   *
+   * ```ts
+   * for (const name of await Deno.listXattr("file.txt")) {
+   *   console.log(name);
+   * }
+   * ```
+   *
+   * Requires `allow-read` permission.
+   *
+   * @tags allow-read
+   * @category File System
+   */
+  export function listXattr(path: string | URL): Promise<string[]>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Synchronously returns the names of the extended attributes of a file.
+   *
+   * ```ts
+   * for (const name of Deno.listXattrSync("file.txt")) {
+   *   console.log(name);
+   * }
+   * ```
+   *
+   * Requires `allow-read` permission.
+   *
+   * @tags allow-read
+   * @category File System
+   */
+  export function listXattrSync(path: string | URL): string[];
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Removes an extended attribute of a file. Throws if the file doesn't have
+   * it.
+   *
+   * ```ts
+   * await Deno.removeXattr("file.txt", "user.checksum");
+   * ```
+   *
+   * Requires `allow-write` permission.
+   *
+   * @tags allow-write
+   * @category File System
+   */
+  export function removeXattr(path: string | URL, name: string): Promise<void>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Synchronously removes an extended attribute of a file. Throws if the file
+   * doesn't have it.
+   *
+   * ```ts
+   * Deno.removeXattrSync("file.txt", "user.checksum");
+   * ```
+   *
+   * Requires `allow-write` permission.
+   *
+   * @tags allow-write
+   * @category File System
+   */
+  export function removeXattrSync(path: string | URL, name: string): void;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The statistics of a file system, returned by {@linkcode Deno.statFs} and
+   * {@linkcode Deno.statFsSync}.
+   *
+   * @category File System
+   */
+  export interface StatFsInfo {
+    /** The type of the file system, like `"ext4"`, `"apfs"` or `"NTFS"`. On
+     * Linux, the types which aren't known are formatted as the hexadecimal
+     * magic number of the file system. */
+    type: string;
+    /** The size of a block, in bytes. */
+    blockSize: number;
+    /** The number of blocks. */
+    blocks: number;
+    /** The number of free blocks. */
+    blocksFree: number;
+    /** The number of free blocks that unprivileged users can use. */
+    blocksAvailable: number;
+    /** The size of the file system, in bytes. */
+    totalBytes: number;
+    /** The free space, in bytes. */
+    freeBytes: number;
+    /** The free space that unprivileged users can use, in bytes. */
+    availableBytes: number;
+    /** The number of inodes. Always `null` on Windows. */
+    files: number | null;
+    /** The number of free inodes. Always `null` on Windows. */
+    filesFree: number | null;
+    /** The flags that the file system was mounted with. On Windows, only
+     * `readOnly` is supported. */
+    flags: {
+      readOnly: boolean;
+      noSuid: boolean;
+      noDev: boolean;
+      noExec: boolean;
+      synchronous: boolean;
+      noAtime: boolean;
+    };
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Returns the statistics of the file system that contains `path`.
+   *
+   * ```ts
+   * const { availableBytes } = await Deno.statFs(".");
+   * if (availableBytes < 100 * 1024 * 1024) {
+   *   console.log("Less than 100MB of disk space left");
+   * }
+   * ```
+   *
+   * Requires `allow-read` permission.
+   *
+   * @tags allow-read
+   * @category File System
+   */
+  export function statFs(path: string | URL): Promise<StatFsInfo>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Synchronously returns the statistics of the file system that contains
+   * `path`.
+   *
+   * ```ts
+   * const { availableBytes } = Deno.statFsSync(".");
+   * ```
+   *
+   * Requires `allow-read` permission.
+   *
+   * @tags allow-read
+   * @category File System
+   */
+  export function statFsSync(path: string | URL): StatFsInfo;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Options which can be set when using {@linkcode Deno.writeFileAtomic} and
+   * {@linkcode Deno.writeFileAtomicSync}.
+   *
+   * @category File System */
+  export interface WriteFileAtomicOptions {
+    /** Permissions always applied to the file. Defaults to the permissions of
+     * the file being replaced, if any. Ignored on Windows. */
+    mode?: number;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Atomically replaces the contents of a file, creating it if needed. The
+   * data is written to a temporary file in the same directory, flushed to the
+   * disk and then renamed over `path`, so that even after a crash `path` has
+   * either its old or its new contents, never a mix of both.
+   *
+   * If `path` is a symlink, the file it points to is replaced.
+   *
+   * ```ts
+   * await Deno.writeFileAtomic("config.json", JSON.stringify(config));
+   * ```
+   *
+   * Requires `allow-write` permission.
+   *
+   * @tags allow-write
+   * @category File System
+   */
+  export function writeFileAtomic(
+    path: string | URL,
+    data: Uint8Array | string,
+    options?: WriteFileAtomicOptions,
+  ): Promise<void>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Synchronously and atomically replaces the contents of a file, creating it
+   * if needed. The data is written to a temporary file in the same directory,
+   * flushed to the disk and then renamed over `path`, so that even after a
+   * crash `path` has either its old or its new contents, never a mix of both.
+   *
+   * If `path` is a symlink, the file it points to is replaced.
+   *
+   * ```ts
+   * Deno.writeFileAtomicSync("config.json", JSON.stringify(config));
+   * ```
+   *
+   * Requires `allow-write` permission.
+   *
+   * @tags allow-write
+   * @category File System
+   */
+  export function writeFileAtomicSync(
+    path: string | URL,
+    data: Uint8Array | string,
+    options?: WriteFileAtomicOptions,
+  ): void;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Options which can be set when using {@linkcode Deno.glob}.
+   *
+   * @category File System */
+  export interface GlobOptions {
+    /** The directory that relative patterns, and the exclusions, are relative
+     * to. Defaults to the current working directory. */
+    root?: string | URL;
+    /** Gitignore-style patterns of the paths to skip, matched relative to
+     * `root`. A pattern without a `/`, like `"node_modules"` or `"*.log"`,
+     * skips the files with that name at any depth; a pattern with one, like
+     * `"/out"` or `"src/gen"`, only skips that path. A trailing `/` only
+     * skips directories, and a leading `!` includes back what a previous
+     * pattern skipped. The contents of skipped directories are never read.
+     *
+     * @default {[]} */
+    exclude?: string[];
+    /** Whether directories matching the pattern are yielded.
+     *
+     * @default {true} */
+    includeDirs?: boolean;
+    /** @default {false} */
+    caseInsensitive?: boolean;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A file or directory matching the pattern passed to {@linkcode Deno.glob}.
+   *
+   * @category File System */
+  export interface GlobEntry extends DirEntry {
+    /** The path of the entry, which is `root` joined with the path relative
+     * to it, or absolute for absolute patterns. */
+    path: string;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Returns an async iterator of the files and directories matching a glob
+   * pattern, in no particular order. The directories are read concurrently,
+   * and only those that may contain matches are read at all.
+   *
+   * Patterns are made of the components of paths separated by `/`, where `*`
+   * matches any characters but `/`, `?` matches one, `[abc]` matches one of
+   * the characters in the brackets and a `**` component matches any number of
+   * directories. Wildcards don't match the leading `.` of hidden files.
+   * Symlinks are yielded, but not followed.
+   *
+   * ```ts
+   * for await (const entry of Deno.glob("src/**\/*.ts", {
+   *   exclude: ["node_modules/", "*.d.ts"],
+   * })) {
+   *   console.log(entry.path);
+   * }
+   * ```
+   *
+   * Requires `allow-read` permission.
+   *
+   * @tags allow-read
+   * @category File System
+   */
+  export function glob(
+    pattern: string,
+    options?: GlobOptions,
+  ): AsyncIterable<GlobEntry>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Opens a temporary file for reading and writing in `dir`, or in the
+   * default directory for temporary files, which has no name and is deleted
+   * as soon as it's closed, so that it can't be left behind even if the
+   * process crashes. Use {@linkcode Deno.FsFile.link} to give it a name once
+   * it has been written.
+   *
+   * On Linux the file is created with `O_TMPFILE` where the file system
+   * supports it, and on Windows it is marked to be deleted on close. Elsewhere
+   * it's created and unlinked right away.
+   *
+   * ```ts
+   * const file = await Deno.openTmpFile("./data");
+   * await file.write(new TextEncoder().encode("Hello world"));
+   * await file.link("./data/hello.txt");
+   * file.close();
+   * ```
+   *
+   * Requires `allow-write` permission.
+   *
+   * @tags allow-write
+   * @category File System
+   */
+  export function openTmpFile(dir?: string | URL): Promise<FsFile>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Synchronously opens a temporary file for reading and writing in `dir`,
+   * or in the default directory for temporary files, which has no name and
+   * is deleted as soon as it's closed. Use {@linkcode Deno.FsFile.linkSync}
+   * to give it a name once it has been written.
+   *
+   * ```ts
+   * const file = Deno.openTmpFileSync("./data");
+   * file.writeSync(new TextEncoder().encode("Hello world"));
+   * file.linkSync("./data/hello.txt");
+   * file.close();
+   * ```
+   *
+   * Requires `allow-write` permission.
+   *
+   * @tags allow-write
+   * @category File System
+   */
+  export function openTmpFileSync(dir?: string | URL): FsFile;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Writes the changes to a buffer returned by {@linkcode Deno.FsFile.mmap}
+   * with the `shared` option to the file, and waits until they are on the
+   * disk. Changes to private mappings are never written.
+   *
+   * ```ts
+   * const file = await Deno.open("my_file.db", { read: true, write: true });
+   * const buffer = file.mmap({ shared: true });
+   * new Uint8Array(buffer).fill(0);
+   * await Deno.msync(buffer);
+   * ```
+   *
+   * @category File System
+   */
+  export function msync(buffer: ArrayBuffer): Promise<void>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Synchronously writes the changes to a buffer returned by
+   * {@linkcode Deno.FsFile.mmap} with the `shared` option to the file, and
+   * waits until they are on the disk.
+   *
+   * ```ts
+   * const file = Deno.openSync("my_file.db", { read: true, write: true });
+   * const buffer = file.mmap({ shared: true });
+   * new Uint8Array(buffer).fill(0);
+   * Deno.msyncSync(buffer);
+   * ```
+   *
+   * @category File System
+   */
+  export function msyncSync(buffer: ArrayBuffer): void;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Unmaps a buffer returned by {@linkcode Deno.FsFile.mmap} right away,
+   * rather than once it's garbage collected. The buffer and its views are
+   * detached, so they are empty afterwards.
+   *
+   * ```ts
+   * const file = Deno.openSync("my_file.db");
+   * const buffer = file.mmap();
+   * file.close();
+   * // ...
+   * Deno.munmap(buffer);
+   * ```
+   *
+   * @category File System
+   */
+  export function munmap(buffer: ArrayBuffer): void;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * An entry returned by {@linkcode Deno.readDirWithStats}.
+   *
+   * @category File System */
+  export interface DirEntryWithStats extends DirEntry {
+    /** The metadata of the entry itself, like {@linkcode Deno.lstat} returns
+     * it, so symlinks aren't followed. */
+    stats: FileInfo;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Reads the directory given by `path` like {@linkcode Deno.readDir}, along
+   * with the metadata of all its entries in the same call, which is much
+   * faster than calling {@linkcode Deno.lstat} for each of them.
+   *
+   * ```ts
+   * let size = 0;
+   * for await (const entry of Deno.readDirWithStats("./logs")) {
+   *   size += entry.stats.size;
+   * }
+   * ```
+   *
+   * Throws error if `path` is not a directory.
+   *
+   * Requires `allow-read` permission.
+   *
+   * @tags allow-read
+   * @category File System
+   */
+  export function readDirWithStats(
+    path: string | URL,
+  ): AsyncIterable<DirEntryWithStats>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Synchronously reads the directory given by `path` like
+   * {@linkcode Deno.readDirSync}, along with the metadata of all its entries.
+   *
+   * ```ts
+   * let size = 0;
+   * for (const entry of Deno.readDirWithStatsSync("./logs")) {
+   *   size += entry.stats.size;
+   * }
+   * ```
+   *
+   * Throws error if `path` is not a directory.
+   *
+   * Requires `allow-read` permission.
+   *
+   * @tags allow-read
+   * @category File System
+   */
+  export function readDirWithStatsSync(
+    path: string | URL,
+  ): Iterable<DirEntryWithStats>;
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * Information for a HTTP request.
@@ -1242,6 +2776,59 @@ declare namespace Deno {
   export interface ServeHandlerInfo {
     /** The remote address of the connection. */
     remoteAddr: Deno.NetAddr;
+
+    /** Sends a `103 Early Hints` informational response with the given
+     * headers (usually `Link` preload hints) before the final response.
+     *
+     * ```ts
+     * Deno.serve((_req, info) => {
+     *   info.sendEarlyHints({ link: "</style.css>; rel=preload; as=style" });
+     *   return new Response("Hello, world");
+     * });
+     * ```
+     *
+     * Early hints are only delivered to HTTP/1.1 clients and are silently
+     * dropped for other protocols. Throws if the response has already been
+     * sent.
+     */
+    sendEarlyHints(headers: HeadersInit): void;
+
+    /** Sets the trailers that are sent after the response body. Trailers may
+     * be set at any time until the response body has been fully written, so
+     * trailer values can be computed from a streaming body (for example a
+     * checksum or a gRPC status). Names of the trailers should be declared
+     * with a `Trailer` header on the response.
+     *
+     * ```ts
+     * Deno.serve((_req, info) => {
+     *   const body = new ReadableStream({
+     *     start(controller) {
+     *       controller.enqueue(new TextEncoder().encode("hello"));
+     *       info.setTrailers({ "grpc-status": "0" });
+     *       controller.close();
+     *     },
+     *   });
+     *   return new Response(body, { headers: { trailer: "grpc-status" } });
+     * });
+     * ```
+     *
+     * Trailers are currently only transmitted over HTTP/2 connections.
+     */
+    setTrailers(headers: HeadersInit): void;
+
+    /** Returns the trailers that were sent after the request body, or `null`
+     * if the body has not been fully read yet or had no trailers.
+     *
+     * Trailers are currently only received over HTTP/2 connections.
+     */
+    requestTrailers(): Headers | null;
+
+    /** Returns the number of bytes of the request body that have been
+     * received from the client but not yet read by the handler. See
+     * {@linkcode ServeOptions.requestBodyHighWaterMark}, without which the
+     * body is only received as it is read.
+     */
+    requestBodyBufferedBytes(): number;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -1272,6 +2859,81 @@ declare namespace Deno {
     /** Sets `SO_REUSEPORT` on POSIX systems. */
     reusePort?: boolean;
 
+    /** Expects every connection to start with a PROXY protocol header, and
+     * reports the client address it carries in the `remoteAddr` of
+     * {@linkcode ServeHandlerInfo}. See
+     * {@linkcode TcpListenOptions.proxyProtocol}.
+     *
+     * @default {false} */
+    proxyProtocol?: boolean;
+
+    /** The number of milliseconds a client has to send the complete headers
+     * of a request once it has started sending them, after which the
+     * connection is closed. Only applies to HTTP/1.1 connections.
+     *
+     * By default there is no timeout. */
+    headersTimeout?: number;
+
+    /** The number of milliseconds a request body may go without receiving
+     * any data while it is being read, after which reading the body fails
+     * with a {@linkcode Deno.errors.TimedOut} error.
+     *
+     * By default there is no timeout. */
+    requestBodyIdleTimeout?: number;
+
+    /** The number of milliseconds a connection may stay open without any
+     * request in flight, after which it is closed. This also applies to
+     * connections that never send a request.
+     *
+     * By default there is no timeout. */
+    keepAliveTimeout?: number;
+
+    /** The number of bytes of a request body the server accepts from the
+     * client before it waits for the handler to read them. The body is
+     * received ahead of the reads once the handler accesses it, up to this
+     * many bytes plus the chunk that crosses the mark. Once this many bytes
+     * are buffered, the client is held back (through flow control on
+     * HTTP/2 and HTTP/3, or by no longer reading from the socket on
+     * HTTP/1.1), so large uploads aren't buffered in memory when the handler
+     * is slow to consume them. HTTP/1.1 connections always buffer at least
+     * 8 KiB.
+     *
+     * By default the server's built-in buffer sizes are used. */
+    requestBodyHighWaterMark?: number;
+
+    /** The maximum number of connections the server serves at the same
+     * time. What happens to connections beyond the limit is controlled by
+     * {@linkcode ServeOptions.overloadBehavior}.
+     *
+     * By default there is no limit. */
+    maxConnections?: number;
+
+    /** The maximum number of requests a single HTTP/2 or HTTP/3 connection
+     * may have in flight at the same time.
+     *
+     * By default the protocol's built-in limit is used. */
+    maxConcurrentStreams?: number;
+
+    /** What the server does with new connections once
+     * {@linkcode ServeOptions.maxConnections} is reached. With `"backoff"`
+     * the server stops accepting connections until one of the open
+     * connections is closed. With `"reject"` the connection is accepted and
+     * its requests are answered by {@linkcode ServeOptions.onOverload}
+     * instead of the handler. HTTP/1.1 connections are closed after the
+     * first response. At most `maxConnections` overloaded connections are
+     * served this way at a time; any further connections are closed
+     * immediately.
+     *
+     * @default {"backoff"} */
+    overloadBehavior?: "backoff" | "reject";
+
+    /** The handler to invoke for requests on connections beyond
+     * {@linkcode ServeOptions.maxConnections} when
+     * {@linkcode ServeOptions.overloadBehavior} is `"reject"`.
+     *
+     * By default a `503 Service Unavailable` response is sent. */
+    onOverload?: (request: Request) => Response | Promise<Response>;
+
     /** The handler to invoke when route handlers throw an error. */
     onError?: (error: unknown) => Response | Promise<Response>;
 
@@ -1291,6 +2953,41 @@ declare namespace Deno {
 
     /** Cert chain in PEM format */
     key: string;
+
+    /** Certificates to present based on the server name requested by the
+     * client. See {@linkcode ListenTlsOptions.sni}. */
+    sni?: ListenTlsOptions["sni"];
+
+    /** **EXPERIMENTAL**: Also serves HTTP/3 over QUIC on the same port (over
+     * UDP), using the same certificate and key. HTTP/1.1 and HTTP/2
+     * responses advertise it to clients with an `alt-svc` header, unless the
+     * handler sets one itself.
+     *
+     * @default {false} */
+    http3?: boolean;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Additional options which are used when opening a TLS (HTTPS) server
+   * whose certificate is provisioned from an ACME certificate authority.
+   *
+   * @category HTTP Server
+   */
+  export interface ServeAcmeOptions extends ServeOptions {
+    /** Provisions the certificate from an ACME certificate authority. See
+     * {@linkcode ListenTlsOptions.acme}. */
+    acme: AcmeOptions;
+
+    /** Cert chain in PEM format, presented until a certificate has been
+     * provisioned */
+    cert?: string;
+
+    /** Server private key in PEM format */
+    key?: string;
+
+    /** See {@linkcode ServeTlsOptions.http3}. */
+    http3?: boolean;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -1422,7 +3119,7 @@ declare namespace Deno {
    * @category HTTP Server
    */
   export function serve(
-    options: ServeOptions | ServeTlsOptions,
+    options: ServeOptions | ServeTlsOptions | ServeAcmeOptions,
     handler: ServeHandler,
   ): Promise<void>;
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -1485,7 +3182,7 @@ declare namespace Deno {
    * @category HTTP Server
    */
   export function serve(
-    options: ServeInit & (ServeOptions | ServeTlsOptions),
+    options: ServeInit & (ServeOptions | ServeTlsOptions | ServeAcmeOptions),
   ): Promise<void>;
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -1507,8 +3204,14 @@ declare namespace Deno {
    * }
    * ```
    *
+   * When used with {@linkcode Deno.serve}, the response returned from the
+   * handler is sent first, and the promise resolves once it has been written.
+   * Any `101 Switching Protocols` response (or successful response to a
+   * `CONNECT` request) can be used, so this also works for custom protocols
+   * and tunnels. The promise rejects if any other response is sent.
+   *
    * This method can only be called on requests originating the
-   * {@linkcode Deno.serveHttp} server.
+   * {@linkcode Deno.serveHttp} or {@linkcode Deno.serve} servers.
    *
    * @category HTTP Server
    */
@@ -2100,6 +3803,8 @@ declare interface WorkerOptions {
    * - `"inherit"` will take the permissions of the thread the worker is created
    *   in.
    * - `"none"` will use the default behavior and have no permission
+   * - Any other string will use the permission preset of that name, as
+   *   defined in the `"permissions"` section of the configuration file
    * - A list of routes can be provided that are relative to the file the worker
    *   is created in to limit the access of the worker (read/write permissions
    *   only)
@@ -2122,10 +3827,77 @@ declare interface WorkerOptions {
    */
   deno?: {
     /** Set to `"none"` to disable all the permissions in the worker. */
-    permissions?: Deno.PermissionOptions;
+    permissions?: Deno.PermissionOptions | string;
   };
 }
 
+/** **UNSTABLE**: New API, yet to be vetted.
+ *
+ * @category Fetch API
+ */
+declare interface RequestInit {
+  /** A proxy for this request only, which takes precedence over the proxy of
+   * the `client` of the request and the proxy environment variables. The
+   * other options of the client still apply.
+   *
+   * ```ts
+   * const response = await fetch("https://example.com", {
+   *   proxy: {
+   *     url: "socks5://localhost:1080",
+   *     basicAuth: { username: "deno", password: "secret" },
+   *   },
+   * });
+   * ```
+   *
+   * Requires `allow-net` permission for the proxy.
+   */
+  proxy?: Deno.Proxy;
+  /** Called as the request body is sent and the response body is received,
+   * for example to show the progress of a large upload.
+   *
+   * ```ts
+   * const file = await Deno.open("./video.mp4");
+   * const { size } = await file.stat();
+   * await fetch("https://example.com/upload", {
+   *   method: "PUT",
+   *   body: file.readable,
+   *   duplex: "half",
+   *   headers: { "content-length": String(size) },
+   *   onProgress({ bytesSent }) {
+   *     console.log(`${Math.round((bytesSent / size) * 100)}%`);
+   *   },
+   * });
+   * ```
+   */
+  onProgress?: (progress: FetchProgress) => void;
+  /** The time, in milliseconds, that this request may take overall, which
+   * takes precedence over the `timeout` of its `client`. */
+  timeout?: number;
+  /** The retries of this request, or `false` to disable the retries of its
+   * `client`. */
+  retry?: Deno.RetryOptions | false;
+}
+
+/** **UNSTABLE**: New API, yet to be vetted.
+ *
+ * The progress of a `fetch()` request reported to its `onProgress` callback.
+ * The counts are reset when a redirect is followed.
+ *
+ * @category Fetch API
+ */
+declare interface FetchProgress {
+  /** The number of bytes of the request body that have been sent. */
+  bytesSent: number;
+  /** The length of the request body, or `null` if it isn't known in advance,
+   * as is the case for `ReadableStream` bodies. */
+  totalBytesToSend: number | null;
+  /** The number of bytes of the response body that have been received. */
+  bytesReceived: number;
+  /** The `Content-Length` of the response once its headers are received, or
+   * `null` if it isn't known. */
+  totalBytesToReceive: number | null;
+}
+
 /** **UNSTABLE**: New API, yet to be vetted.
  *
  * @category Web Sockets
@@ -2134,7 +3906,35 @@ declare interface WebSocketStreamOptions {
   protocols?: string[];
   signal?: AbortSignal;
   headers?: HeadersInit;
-}
+  /** The HTTP or SOCKS5 proxy to connect through. Defaults to the proxy set
+   * by the `HTTPS_PROXY` or `HTTP_PROXY` environment variable. */
+  proxy?: Deno.Proxy;
+  /** The largest frame the server may send, in bytes. Larger frames are
+   * rejected based on their header, and the connection is closed with code
+   * 1009.
+   *
+   * The default is 64 MiB. */
+  maxFrameSize?: number;
+  /** The largest message the server may send, in bytes, including all of
+   * the fragments of a fragmented message. Larger messages close the
+   * connection with code 1009 as soon as the limit is exceeded.
+   *
+   * By default there is no limit besides `maxFrameSize`, and compressed
+   * messages can't inflate to more than 64 MiB. */
+  maxMessageSize?: number;
+  /** Whether fragmented messages are reassembled before they are delivered.
+   * When `false`, every fragment is delivered as a message of its own as
+   * soon as it arrives, so the boundaries of fragmented messages are lost.
+   *
+   * @default {true} */
+  reassembleFragments?: boolean;
+  /** Whether to offer the permessage-deflate extension, which lets the
+   * server compress the messages it sends. Messages sent by the client are
+   * never compressed.
+   *
+   * @default {true} */
+  permessageDeflate?: boolean;
+
 
 /** **UNSTABLE**: New API, yet to be vetted.
  *
@@ -2142,7 +3942,7 @@ declare interface WebSocketStreamOptions {
  */
 declare interface WebSocketConnection {
   readable: ReadableStream<string | Uint8Array>;
-  writable: WritableStream<string | Uint8Array>;
+  writable: WritableStream<string | BufferSource>;
   extensions: string;
   protocol: string;
 }
@@ -2152,6 +3952,8 @@ declare interface WebSocketConnection {
  * @category Web Sockets
  */
 declare interface WebSocketCloseInfo {
+  closeCode?: number;
+  /** @deprecated Use {@linkcode WebSocketCloseInfo.closeCode} instead. */
   code?: number;
   reason?: string;
 }
@@ -2164,6 +3966,13 @@ declare interface WebSocketCloseInfo {
 declare class WebSocketStream {
   constructor(url: string, options?: WebSocketStreamOptions);
   url: string;
+  /** Resolves with the streams of the connection once it is open. Reading
+   * from `readable` only pulls the next message off the connection when
+   * the previous one has been consumed, and writes to `writable` resolve
+   * once the message has been sent, so both directions apply
+   * backpressure. */
+  opened: Promise<WebSocketConnection>;
+  /** @deprecated Use {@linkcode WebSocketStream.opened} instead. */
   connection: Promise<WebSocketConnection>;
   closed: Promise<WebSocketCloseInfo>;
   close(closeInfo?: WebSocketCloseInfo): void;
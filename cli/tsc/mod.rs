@@ -649,7 +649,12 @@ fn resolve_graph_specifier_types(
       }
     }
     Some(Module::External(module)) => {
-      // we currently only use "External" for when the module is in an npm package
+      // "External" is used for when the module is in an npm package, or for
+      // the imports that `deno bundle` leaves out of the bundle, which have
+      // no types
+      if module.specifier.scheme() != "file" {
+        return Ok(None);
+      }
       Ok(state.maybe_node_resolver.as_ref().map(|node_resolver| {
         let specifier =
           node::resolve_specifier_into_node_modules(&module.specifier);
@@ -6,6 +6,7 @@ pub use deno_core::normalize_path;
 use deno_core::ModuleSpecifier;
 use deno_runtime::deno_crypto::rand;
 use deno_runtime::deno_node::PathClean;
+use deno_runtime::fs_util::canonicalize_path_cached;
 use std::borrow::Cow;
 use std::env::current_dir;
 use std::fs::OpenOptions;
@@ -86,7 +87,8 @@ pub fn canonicalize_path(path: &Path) -> Result<PathBuf, Error> {
 
 /// Canonicalizes a path which might be non-existent by going up the
 /// ancestors until it finds a directory that exists, canonicalizes
-/// that path, then adds back the remaining path components.
+/// that path, then adds back the remaining path components. The results are
+/// cached, see `canonicalize_path_cached()`.
 ///
 /// Note: When using this, you should be aware that a symlink may
 /// subsequently be created along this path by some other code.
@@ -97,7 +99,7 @@ pub fn canonicalize_path_maybe_not_exists(
   let mut path = path.as_path();
   let mut names_stack = Vec::new();
   loop {
-    match canonicalize_path(path) {
+    match canonicalize_path_cached(path) {
       Ok(mut canonicalized_path) => {
         for name in names_stack.into_iter().rev() {
           canonicalized_path = canonicalized_path.join(name);
@@ -10,6 +10,7 @@ use deno_core::futures::task::LocalFutureObj;
 use deno_core::futures::FutureExt;
 use deno_core::located_script_name;
 use deno_core::url::Url;
+use deno_core::v8;
 use deno_core::CompiledWasmModuleStore;
 use deno_core::Extension;
 use deno_core::ModuleId;
@@ -28,6 +29,7 @@ use deno_runtime::fmt_errors::format_js_error;
 use deno_runtime::inspector_server::InspectorServer;
 use deno_runtime::ops::worker_host::CreateWebWorkerCb;
 use deno_runtime::ops::worker_host::WorkerEventCb;
+use deno_runtime::permissions::PermissionPresets;
 use deno_runtime::permissions::PermissionsContainer;
 use deno_runtime::web_worker::WebWorker;
 use deno_runtime::web_worker::WebWorkerOptions;
@@ -81,6 +83,8 @@ pub struct CliMainWorkerOptions {
   pub location: Option<Url>,
   pub maybe_binary_npm_command_name: Option<String>,
   pub origin_data_folder_path: Option<PathBuf>,
+  pub fetch_cache_folder_path: Option<PathBuf>,
+  pub permission_presets: PermissionPresets,
   pub seed: Option<u64>,
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
   pub unstable: bool,
@@ -255,6 +259,16 @@ impl CliMainWorker {
     self.evaluate_module_possibly_with_npm(id).await
   }
 
+  /// Executes the main module as a side module, and returns its namespace
+  /// object, which holds its exports.
+  pub async fn execute_side_module_namespace_possibly_with_npm(
+    &mut self,
+  ) -> Result<v8::Global<v8::Object>, AnyError> {
+    let id = self.worker.preload_side_module(&self.main_module).await?;
+    self.evaluate_module_possibly_with_npm(id).await?;
+    self.worker.js_runtime.get_module_namespace(id)
+  }
+
   async fn evaluate_module_possibly_with_npm(
     &mut self,
     id: ModuleId,
@@ -400,6 +414,10 @@ impl CliMainWorkerFactory {
         .unwrap() // must be set if storage key resolver returns a value
         .join(checksum::gen(&[key.as_bytes()]))
     });
+    let http_cache_dir = maybe_storage_key.as_ref().and_then(|key| {
+      let dir = shared.options.fetch_cache_folder_path.as_ref()?;
+      Some(dir.join(checksum::gen(&[key.as_bytes()])))
+    });
     let cache_storage_dir = maybe_storage_key.map(|key| {
       // TODO(@satyarohith): storage quota management
       // Note: we currently use temp_dir() to avoid managing storage size.
@@ -439,6 +457,7 @@ impl CliMainWorkerFactory {
       seed: shared.options.seed,
       source_map_getter: maybe_source_map_getter,
       format_js_error_fn: Some(Arc::new(format_js_error)),
+      permission_presets: shared.options.permission_presets.clone(),
       create_web_worker_cb,
       web_worker_preload_module_cb,
       web_worker_pre_execute_module_cb,
@@ -451,6 +470,7 @@ impl CliMainWorkerFactory {
       get_error_class_fn: Some(&errors::get_error_class_name),
       cache_storage_dir,
       origin_storage_dir,
+      http_cache_dir,
       blob_store: shared.blob_store.clone(),
       broadcast_channel: shared.broadcast_channel.clone(),
       shared_array_buffer_store: Some(shared.shared_array_buffer_store.clone()),
@@ -531,6 +551,10 @@ fn create_web_worker_callback(
     let maybe_storage_key = shared
       .storage_key_resolver
       .resolve_storage_key(&args.main_module);
+    let http_cache_dir = maybe_storage_key.as_ref().and_then(|key| {
+      let dir = shared.options.fetch_cache_folder_path.as_ref()?;
+      Some(dir.join(checksum::gen(&[key.as_bytes()])))
+    });
     let cache_storage_dir = maybe_storage_key.map(|key| {
       // TODO(@satyarohith): storage quota management
       // Note: we currently use temp_dir() to avoid managing storage size.
@@ -569,6 +593,7 @@ fn create_web_worker_callback(
       preload_module_cb,
       pre_execute_module_cb,
       format_js_error_fn: Some(Arc::new(format_js_error)),
+      permission_presets: shared.options.permission_presets.clone(),
       source_map_getter: maybe_source_map_getter,
       module_loader,
       fs: shared.fs.clone(),
@@ -584,6 +609,7 @@ fn create_web_worker_callback(
       ),
       stdio: stdio.clone(),
       cache_storage_dir,
+      http_cache_dir,
     };
 
     WebWorker::bootstrap_from_options(
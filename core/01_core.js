@@ -629,6 +629,10 @@ for (let i = 0; i < 10; i++) {
     return aggregate;
   }
 
+  function eventLoopMetrics({ reset = false } = {}) {
+    return ops.op_event_loop_metrics(reset);
+  }
+
   let reportExceptionCallback = undefined;
 
   // Used to report errors thrown from functions passed to `queueMicrotask()`.
@@ -800,6 +804,7 @@ for (let i = 0; i < 10; i++) {
     opAsync,
     resources,
     metrics,
+    eventLoopMetrics,
     registerErrorBuilder,
     registerErrorClass,
     buildCustomError,
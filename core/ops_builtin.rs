@@ -64,6 +64,7 @@ crate::extension!(
     ops_builtin_v8::op_get_non_index_property_names,
     ops_builtin_v8::op_get_constructor_name,
     ops_builtin_v8::op_memory_usage,
+    ops_builtin_v8::op_event_loop_metrics,
     ops_builtin_v8::op_set_wasm_streaming_callback,
     ops_builtin_v8::op_abort_wasm_streaming,
     ops_builtin_v8::op_destructure_error,
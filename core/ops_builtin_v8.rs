@@ -6,6 +6,7 @@ use crate::error::range_error;
 use crate::error::type_error;
 use crate::error::JsError;
 use crate::ops_builtin::WasmStreamingResource;
+use crate::ops_metrics::EventLoopSummary;
 use crate::resolve_url;
 use crate::serde_v8::from_v8;
 use crate::source_map::apply_source_map;
@@ -699,6 +700,26 @@ fn op_memory_usage(scope: &mut v8::HandleScope) -> MemoryUsage {
   }
 }
 
+/// Returns the metrics of the event loop since they were last reset, and
+/// resets them if `reset` is set.
+#[op(v8)]
+fn op_event_loop_metrics(
+  scope: &mut v8::HandleScope,
+  reset: bool,
+) -> EventLoopSummary {
+  let pending_unrefed_ops =
+    JsRealm::state_from_scope(scope).borrow().unrefed_ops.len();
+  let state_rc = JsRuntime::state(scope);
+  let mut state = state_rc.borrow_mut();
+  let summary = state
+    .event_loop_metrics
+    .summary(state.pending_ops.len(), pending_unrefed_ops);
+  if reset {
+    state.event_loop_metrics.reset();
+  }
+  summary
+}
+
 #[op(v8)]
 fn op_set_wasm_streaming_callback(
   scope: &mut v8::HandleScope,
@@ -4,6 +4,8 @@ use crate::serde::Serialize;
 use crate::OpId;
 use std::cell::RefCell;
 use std::cell::RefMut;
+use std::time::Duration;
+use std::time::Instant;
 
 // TODO(@AaronO): split into AggregateMetrics & PerOpMetrics
 #[derive(Clone, Default, Debug, Serialize)]
@@ -89,3 +91,180 @@ impl OpsTracker {
     metrics.ops_completed_async += 1;
   }
 }
+
+/// The number of buckets of `EventLoopMetrics::delay`, the last of which
+/// holds the turns of more than 2^38 microseconds, or about three days.
+const DELAY_BUCKETS: usize = 40;
+
+/// Measures the turns of the event loop. A turn blocks the thread, so its
+/// duration is how long an event that became ready during it waited, at most,
+/// to be handled. In between, the event loop is idle.
+#[derive(Debug)]
+pub struct EventLoopMetrics {
+  /// The durations of the turns, in microseconds, where bucket `i` counts
+  /// those shorter than 2^i microseconds.
+  delay: [u64; DELAY_BUCKETS],
+  count: u64,
+  min: Duration,
+  max: Duration,
+  active: Duration,
+  idle: Duration,
+  last_turn_end: Option<Instant>,
+}
+
+impl Default for EventLoopMetrics {
+  fn default() -> Self {
+    Self {
+      delay: [0; DELAY_BUCKETS],
+      count: 0,
+      min: Duration::MAX,
+      max: Duration::ZERO,
+      active: Duration::ZERO,
+      idle: Duration::ZERO,
+      last_turn_end: None,
+    }
+  }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventLoopDelay {
+  pub count: u64,
+  pub min: f64,
+  pub max: f64,
+  pub mean: f64,
+  pub p50: f64,
+  pub p90: f64,
+  pub p99: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventLoopSummary {
+  /// The durations of the turns, in milliseconds.
+  pub delay: EventLoopDelay,
+  /// The time spent running and waiting, in milliseconds.
+  pub active: f64,
+  pub idle: f64,
+  /// The fraction of the time spent running.
+  pub utilization: f64,
+  /// The async ops that haven't completed yet, including the unrefed ones.
+  pub pending_ops: usize,
+  pub pending_unrefed_ops: usize,
+}
+
+impl EventLoopMetrics {
+  pub fn record_turn(&mut self, start: Instant, end: Instant) {
+    let duration = end.saturating_duration_since(start);
+    let micros = duration.as_micros().min(u64::MAX as u128) as u64;
+    let bucket = (u64::BITS - micros.leading_zeros()) as usize;
+    self.delay[bucket.min(DELAY_BUCKETS - 1)] += 1;
+    self.count += 1;
+    self.min = self.min.min(duration);
+    self.max = self.max.max(duration);
+    self.active += duration;
+    if let Some(last_turn_end) = self.last_turn_end {
+      self.idle += start.saturating_duration_since(last_turn_end);
+    }
+    self.last_turn_end = Some(end);
+  }
+
+  /// Clears the recorded turns, but keeps measuring the idle time from the
+  /// last one.
+  pub fn reset(&mut self) {
+    *self = Self {
+      last_turn_end: self.last_turn_end,
+      ..Default::default()
+    };
+  }
+
+  /// The duration below which `percentile` percent of the turns are. It's the
+  /// upper bound of the bucket of the turn, so at most twice as long.
+  fn percentile(&self, percentile: f64) -> Duration {
+    let rank = (self.count as f64 * percentile / 100.0).ceil() as u64;
+    let mut seen = 0;
+    for (i, count) in self.delay.iter().enumerate() {
+      seen += count;
+      if seen >= rank.max(1) {
+        let upper_bound = Duration::from_micros(1 << i);
+        return upper_bound.clamp(self.min, self.max);
+      }
+    }
+    self.max
+  }
+
+  pub fn summary(
+    &self,
+    pending_ops: usize,
+    pending_unrefed_ops: usize,
+  ) -> EventLoopSummary {
+    let millis = |duration: Duration| duration.as_nanos() as f64 / 1e6;
+    let delay = if self.count == 0 {
+      EventLoopDelay {
+        count: 0,
+        min: 0.0,
+        max: 0.0,
+        mean: 0.0,
+        p50: 0.0,
+        p90: 0.0,
+        p99: 0.0,
+      }
+    } else {
+      EventLoopDelay {
+        count: self.count,
+        min: millis(self.min),
+        max: millis(self.max),
+        mean: millis(self.active) / self.count as f64,
+        p50: millis(self.percentile(50.0)),
+        p90: millis(self.percentile(90.0)),
+        p99: millis(self.percentile(99.0)),
+      }
+    };
+    let total = self.active + self.idle;
+    EventLoopSummary {
+      delay,
+      active: millis(self.active),
+      idle: millis(self.idle),
+      utilization: if total.is_zero() {
+        0.0
+      } else {
+        self.active.as_secs_f64() / total.as_secs_f64()
+      },
+      pending_ops,
+      pending_unrefed_ops,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn event_loop_metrics() {
+    let mut metrics = EventLoopMetrics::default();
+    let start = Instant::now();
+    let ms = Duration::from_millis;
+    for i in 0..10 {
+      let turn_start = start + ms(i * 10);
+      let duration = if i == 9 { ms(8) } else { ms(1) };
+      metrics.record_turn(turn_start, turn_start + duration);
+    }
+
+    let summary = metrics.summary(2, 1);
+    assert_eq!(summary.delay.count, 10);
+    assert_eq!(summary.delay.min, 1.0);
+    assert_eq!(summary.delay.max, 8.0);
+    assert_eq!(summary.delay.mean, 1.7);
+    // 1ms is in the bucket of up to 1024µs.
+    assert_eq!(summary.delay.p50, 1.024);
+    assert_eq!(summary.delay.p99, 8.0);
+    assert_eq!(summary.active, 17.0);
+    assert_eq!(summary.idle, 81.0);
+    assert_eq!(summary.pending_ops, 2);
+
+    metrics.reset();
+    assert_eq!(metrics.summary(0, 0).delay.count, 0);
+    assert_eq!(metrics.summary(0, 0).idle, 0.0);
+  }
+}
@@ -17,6 +17,7 @@ use crate::modules::ModuleLoadId;
 use crate::modules::ModuleLoader;
 use crate::modules::ModuleMap;
 use crate::ops::*;
+use crate::ops_metrics::EventLoopMetrics;
 use crate::realm::ContextState;
 use crate::realm::JsRealm;
 use crate::realm::JsRealmLocal;
@@ -53,6 +54,7 @@ use std::sync::Mutex;
 use std::sync::Once;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Instant;
 use v8::OwnedIsolate;
 
 pub enum Snapshot {
@@ -177,6 +179,7 @@ pub struct JsRuntimeState {
   // flimsy. Try to poll it similarly to `pending_promise_rejections`.
   pub(crate) dispatched_exception: Option<v8::Global<v8::Value>>,
   pub(crate) inspector: Option<Rc<RefCell<JsRuntimeInspector>>>,
+  pub(crate) event_loop_metrics: EventLoopMetrics,
   waker: AtomicWaker,
 }
 
@@ -348,6 +351,7 @@ impl JsRuntime {
       waker: AtomicWaker::new(),
       have_unpolled_ops: false,
       dispatched_exception: None,
+      event_loop_metrics: Default::default(),
       // Some fields are initialized later after isolate is created
       inspector: None,
       global_realm: None,
@@ -988,12 +992,26 @@ impl JsRuntime {
   pub async fn call_and_await(
     &mut self,
     function: &v8::Global<v8::Function>,
+  ) -> Result<v8::Global<v8::Value>, Error> {
+    self.call_with_args_and_await(function, &[]).await
+  }
+
+  /// Like [`JsRuntime::call_and_await`], but passes the given arguments to the
+  /// function.
+  pub async fn call_with_args_and_await(
+    &mut self,
+    function: &v8::Global<v8::Function>,
+    args: &[v8::Global<v8::Value>],
   ) -> Result<v8::Global<v8::Value>, Error> {
     let promise = {
       let scope = &mut self.handle_scope();
       let cb = function.open(scope);
       let this = v8::undefined(scope).into();
-      let promise = cb.call(scope, this, &[]);
+      let args = args
+        .iter()
+        .map(|arg| v8::Local::new(scope, arg))
+        .collect::<Vec<_>>();
+      let promise = cb.call(scope, this, &args);
       if promise.is_none() || scope.is_execution_terminating() {
         let undefined = v8::undefined(scope).into();
         return exception_to_err_result(scope, undefined, false);
@@ -1241,6 +1259,21 @@ impl JsRuntime {
     &mut self,
     cx: &mut Context,
     wait_for_inspector: bool,
+  ) -> Poll<Result<(), Error>> {
+    let turn_start = Instant::now();
+    let result = self.poll_event_loop_turn(cx, wait_for_inspector);
+    self
+      .state
+      .borrow_mut()
+      .event_loop_metrics
+      .record_turn(turn_start, Instant::now());
+    result
+  }
+
+  fn poll_event_loop_turn(
+    &mut self,
+    cx: &mut Context,
+    wait_for_inspector: bool,
   ) -> Poll<Result<(), Error>> {
     let has_inspector: bool;
 
@@ -2942,6 +2975,32 @@ pub mod tests {
     );
   }
 
+  #[tokio::test]
+  async fn test_call_with_args_and_await() {
+    let mut runtime = JsRuntime::new(Default::default());
+    let function = runtime
+      .execute_script_static("a.js", "async (a, b) => a + b")
+      .unwrap();
+    let (function, args) = {
+      let scope = &mut runtime.handle_scope();
+      let function = v8::Local::new(scope, function);
+      let function = v8::Local::<v8::Function>::try_from(function).unwrap();
+      let a: v8::Local<v8::Value> = v8::Integer::new(scope, 1).into();
+      let b: v8::Local<v8::Value> = v8::Integer::new(scope, 2).into();
+      (
+        v8::Global::new(scope, function),
+        vec![v8::Global::new(scope, a), v8::Global::new(scope, b)],
+      )
+    };
+    let result_global = runtime
+      .call_with_args_and_await(&function, &args)
+      .await
+      .unwrap();
+    let scope = &mut runtime.handle_scope();
+    let value = result_global.open(scope);
+    assert_eq!(value.integer_value(scope).unwrap(), 3);
+  }
+
   #[test]
   fn terminate_execution_webassembly() {
     let (mut runtime, _dispatch_count) = setup(Mode::Async);
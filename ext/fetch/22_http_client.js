@@ -12,6 +12,16 @@
 
 const core = globalThis.Deno.core;
 const ops = core.ops;
+const primordials = globalThis.__bootstrap.primordials;
+const {
+  MapPrototypeDelete,
+  MapPrototypeGet,
+  MapPrototypeSet,
+  SafeMap,
+} = primordials;
+
+/** @type {Map<number, (cookies: Deno.Cookie[]) => void>} */
+const cookieChangeListeners = new SafeMap();
 
 /**
  * @param {Deno.CreateHttpClientOptions} options
@@ -19,11 +29,32 @@ const ops = core.ops;
  */
 function createHttpClient(options) {
   options.caCerts ??= [];
-  return new HttpClient(
-    ops.op_fetch_custom_client(
-      options,
-    ),
-  );
+  const cookieJar = options.cookieJar === true ? {} : options.cookieJar;
+  const rid = ops.op_fetch_custom_client({
+    ...options,
+    resolver: options.resolver?.rid,
+    cookieJar: cookieJar ? cookieJar.initialCookies ?? [] : undefined,
+  });
+  if (typeof cookieJar?.onChange === "function") {
+    MapPrototypeSet(cookieChangeListeners, rid, cookieJar.onChange);
+  }
+  return new HttpClient(rid);
+}
+
+/**
+ * Calls the `onChange` hook of the cookie jar of a client, if a response has
+ * changed its cookies.
+ * @param {number} rid
+ */
+function notifyCookieChanges(rid) {
+  const onChange = MapPrototypeGet(cookieChangeListeners, rid);
+  if (onChange === undefined) {
+    return;
+  }
+  const cookies = ops.op_fetch_take_cookie_changes(rid);
+  if (cookies !== null) {
+    onChange(cookies);
+  }
 }
 
 class HttpClient {
@@ -34,9 +65,18 @@ class HttpClient {
     this.rid = rid;
   }
   close() {
+    MapPrototypeDelete(cookieChangeListeners, this.rid);
     core.close(this.rid);
   }
+  closeIdleConnections() {
+    ops.op_fetch_close_idle_connections(this.rid);
+  }
 }
 const HttpClientPrototype = HttpClient.prototype;
 
-export { createHttpClient, HttpClient, HttpClientPrototype };
+export {
+  createHttpClient,
+  HttpClient,
+  HttpClientPrototype,
+  notifyCookieChanges,
+};
@@ -73,11 +73,16 @@ function processUrlList(urlList, urlListProcessed) {
  * @property {() => string} currentUrl
  * @property {() => [string, string][]} headerList
  * @property {null | typeof __window.bootstrap.fetchBody.InnerBody} body
+ * @property {RequestCache} cacheMode
  * @property {"follow" | "error" | "manual"} redirectMode
  * @property {number} redirectCount
  * @property {(() => string)[]} urlList
  * @property {string[]} urlListProcessed
  * @property {number | null} clientRid NOTE: non standard extension for `Deno.HttpClient`.
+ * @property {Deno.Proxy | null} proxy NOTE: non standard extension for per request proxies.
+ * @property {((progress: FetchProgress) => void) | null} onProgress NOTE: non standard extension for the progress of requests.
+ * @property {number | null} timeout NOTE: non standard extension for per request timeouts.
+ * @property {Deno.RetryOptions | null} retry NOTE: non standard extension for per request retries.
  * @property {Blob | null} blobUrlEntry
  */
 
@@ -121,11 +126,16 @@ function newInnerRequest(method, url, headerList, body, maybeBlob) {
       this.headerListInner = value;
     },
     body,
+    cacheMode: "default",
     redirectMode: "follow",
     redirectCount: 0,
     urlList: [typeof url === "string" ? () => url : url],
     urlListProcessed: [],
     clientRid: null,
+    proxy: null,
+    onProgress: null,
+    timeout: null,
+    retry: null,
     blobUrlEntry,
     url() {
       if (this.urlListProcessed[0] === undefined) {
@@ -172,11 +182,16 @@ function cloneInnerRequest(request, skipBody = false) {
     method: request.method,
     headerList,
     body,
+    cacheMode: request.cacheMode,
     redirectMode: request.redirectMode,
     redirectCount: request.redirectCount,
     urlList: request.urlList,
     urlListProcessed: request.urlListProcessed,
     clientRid: request.clientRid,
+    proxy: request.proxy,
+    onProgress: request.onProgress,
+    timeout: request.timeout,
+    retry: request.retry,
     blobUrlEntry: request.blobUrlEntry,
     url() {
       if (this.urlListProcessed[0] === undefined) {
@@ -318,6 +333,11 @@ class Request {
 
     // 12. is folded into the else statement of step 6 above.
 
+    // 21.
+    if (init.cache !== undefined) {
+      request.cacheMode = init.cache;
+    }
+
     // 22.
     if (init.redirect !== undefined) {
       request.redirectMode = init.redirect;
@@ -351,6 +371,66 @@ class Request {
       request.clientRid = init.client?.rid ?? null;
     }
 
+    // NOTE: non standard extension. This handles the per request proxy
+    if (init.proxy !== undefined) {
+      if (init.proxy !== null && typeof init.proxy?.url !== "string") {
+        throw webidl.makeException(
+          TypeError,
+          "`proxy` must be an object with a `url`",
+          prefix,
+          "Argument 2",
+        );
+      }
+      request.proxy = init.proxy;
+    }
+
+    // NOTE: non standard extension. This handles the progress callback
+    if (init.onProgress !== undefined) {
+      if (init.onProgress !== null && typeof init.onProgress !== "function") {
+        throw webidl.makeException(
+          TypeError,
+          "`onProgress` must be a function",
+          prefix,
+          "Argument 2",
+        );
+      }
+      request.onProgress = init.onProgress;
+    }
+
+    // NOTE: non standard extension. This handles the per request timeout
+    if (init.timeout !== undefined) {
+      if (
+        init.timeout !== null &&
+        !(typeof init.timeout === "number" && init.timeout >= 0)
+      ) {
+        throw webidl.makeException(
+          TypeError,
+          "`timeout` must be a non-negative number",
+          prefix,
+          "Argument 2",
+        );
+      }
+      request.timeout = init.timeout;
+    }
+
+    // NOTE: non standard extension. This handles the per request retries
+    if (init.retry !== undefined) {
+      if (init.retry === false) {
+        request.retry = { maxRetries: 0 };
+      } else if (
+        init.retry !== null && typeof init.retry?.maxRetries !== "number"
+      ) {
+        throw webidl.makeException(
+          TypeError,
+          "`retry` must be false or an object with `maxRetries`",
+          prefix,
+          "Argument 2",
+        );
+      } else {
+        request.retry = init.retry;
+      }
+    }
+
     // 27.
     this[_request] = request;
 
@@ -452,11 +532,21 @@ class Request {
     return this[_headers];
   }
 
+  get cache() {
+    webidl.assertBranded(this, RequestPrototype);
+    return this[_request].cacheMode;
+  }
+
   get redirect() {
     webidl.assertBranded(this, RequestPrototype);
     return this[_request].redirectMode;
   }
 
+  get duplex() {
+    webidl.assertBranded(this, RequestPrototype);
+    return "half";
+  }
+
   get signal() {
     webidl.assertBranded(this, RequestPrototype);
     return this[_signal];
@@ -514,6 +604,17 @@ webidl.converters["RequestInfo_DOMString"] = (V, prefix, context, opts) => {
   // Passed to new URL(...) which implicitly converts DOMString -> USVString
   return webidl.converters["DOMString"](V, prefix, context, opts);
 };
+webidl.converters["RequestCache"] = webidl.createEnumConverter(
+  "RequestCache",
+  [
+    "default",
+    "no-store",
+    "reload",
+    "no-cache",
+    "force-cache",
+    "only-if-cached",
+  ],
+);
 webidl.converters["RequestRedirect"] = webidl.createEnumConverter(
   "RequestRedirect",
   [
@@ -522,6 +623,12 @@ webidl.converters["RequestRedirect"] = webidl.createEnumConverter(
     "manual",
   ],
 );
+webidl.converters["RequestDuplex"] = webidl.createEnumConverter(
+  "RequestDuplex",
+  [
+    "half",
+  ],
+);
 webidl.converters["RequestInit"] = webidl.createDictionaryConverter(
   "RequestInit",
   [
@@ -533,6 +640,7 @@ webidl.converters["RequestInit"] = webidl.createDictionaryConverter(
         webidl.converters["BodyInit_DOMString"],
       ),
     },
+    { key: "cache", converter: webidl.converters["RequestCache"] },
     { key: "redirect", converter: webidl.converters["RequestRedirect"] },
     {
       key: "signal",
@@ -541,6 +649,11 @@ webidl.converters["RequestInit"] = webidl.createDictionaryConverter(
       ),
     },
     { key: "client", converter: webidl.converters.any },
+    { key: "duplex", converter: webidl.converters["RequestDuplex"] },
+    { key: "proxy", converter: webidl.converters.any },
+    { key: "onProgress", converter: webidl.converters.any },
+    { key: "timeout", converter: webidl.converters.any },
+    { key: "retry", converter: webidl.converters.any },
   ],
 );
 
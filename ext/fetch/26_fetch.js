@@ -19,8 +19,10 @@ import {
   errorReadableStream,
   readableStreamForRid,
   ReadableStreamPrototype,
+  TransformStream,
 } from "ext:deno_web/06_streams.js";
 import { extractBody, InnerBody } from "ext:deno_fetch/22_body.js";
+import { notifyCookieChanges } from "ext:deno_fetch/22_http_client.js";
 import { processUrlList, toInnerRequest } from "ext:deno_fetch/23_request.js";
 import {
   abortedNetworkError,
@@ -31,6 +33,7 @@ import {
   toInnerResponse,
 } from "ext:deno_fetch/23_response.js";
 import * as abortSignal from "ext:deno_web/03_abort_signal.js";
+import { reportError } from "ext:deno_web/02_event.js";
 const primordials = globalThis.__bootstrap.primordials;
 const {
   ArrayPrototypePush,
@@ -65,16 +68,32 @@ const REQUEST_BODY_HEADER_NAMES = [
 const requestBodyReaders = new SafeWeakMap();
 
 /**
- * @param {{ method: string, url: string, headers: [string, string][], clientRid: number | null, hasBody: boolean }} args
+ * @param {{ method: string, url: string, headers: [string, string][], clientRid: number | null, proxy: Deno.Proxy | null, cacheMode: RequestCache, timeout: number | null, retry: Deno.RetryOptions | null, hasBody: boolean }} args
  * @param {Uint8Array | null} body
  * @returns {{ requestRid: number, requestBodyRid: number | null }}
  */
-function opFetch(method, url, headers, clientRid, hasBody, bodyLength, body) {
+function opFetch(
+  method,
+  url,
+  headers,
+  clientRid,
+  proxy,
+  cacheMode,
+  timeout,
+  retry,
+  hasBody,
+  bodyLength,
+  body,
+) {
   return ops.op_fetch(
     method,
     url,
     headers,
     clientRid,
+    proxy,
+    cacheMode,
+    timeout,
+    retry,
     hasBody,
     bodyLength,
     body,
@@ -108,6 +127,37 @@ function createResponseBodyStream(responseBodyRid, terminator) {
   return readable;
 }
 
+/**
+ * NOTE: non standard extension for the `onProgress` option of requests.
+ * @param {InnerRequest} req
+ * @param {FetchProgress} progress
+ */
+function reportProgress(req, progress) {
+  try {
+    req.onProgress({ ...progress });
+  } catch (err) {
+    reportError(err);
+  }
+}
+
+/**
+ * @param {ReadableStream<Uint8Array>} readable
+ * @param {InnerRequest} req
+ * @param {FetchProgress} progress
+ * @returns {ReadableStream<Uint8Array>}
+ */
+function createProgressStream(readable, req, progress) {
+  return readable.pipeThrough(
+    new TransformStream({
+      transform(chunk, controller) {
+        progress.bytesReceived += chunk.byteLength;
+        reportProgress(req, progress);
+        controller.enqueue(chunk);
+      },
+    }),
+  );
+}
+
 /**
  * @param {InnerRequest} req
  * @param {boolean} recursive
@@ -179,11 +229,25 @@ async function mainFetch(req, recursive, terminator) {
     }
   }
 
+  /** @type {FetchProgress | null} */
+  const progress = req.onProgress !== null
+    ? {
+      bytesSent: 0,
+      totalBytesToSend: reqBody !== null ? req.body.length : 0,
+      bytesReceived: 0,
+      totalBytesToReceive: null,
+    }
+    : null;
+
   const { requestRid, requestBodyRid, cancelHandleRid } = opFetch(
     req.method,
     req.currentUrl(),
     req.headerList,
     req.clientRid,
+    req.proxy,
+    req.cacheMode,
+    req.timeout,
+    req.retry,
     reqBody !== null,
     req.body?.length,
     ObjectPrototypeIsPrototypeOf(Uint8ArrayPrototype, reqBody) ? reqBody : null,
@@ -246,6 +310,10 @@ async function mainFetch(req, recursive, terminator) {
           requestSendErrorSet = true;
           break;
         }
+        if (progress !== null) {
+          progress.bytesSent += val.byteLength;
+          reportProgress(req, progress);
+        }
       }
       if (done && !terminator.aborted) {
         try {
@@ -280,6 +348,17 @@ async function mainFetch(req, recursive, terminator) {
     }
   }
   if (terminator.aborted) return abortedNetworkError();
+  // NOTE: non standard extension for the cookie jars of `Deno.HttpClient`.
+  if (req.clientRid !== null) {
+    notifyCookieChanges(req.clientRid);
+  }
+  if (progress !== null) {
+    if (requestBodyRid === null && reqBody !== null) {
+      progress.bytesSent = reqBody.byteLength;
+    }
+    progress.totalBytesToReceive = resp.contentLength;
+    reportProgress(req, progress);
+  }
 
   processUrlList(req.urlList, req.urlListProcessed);
 
@@ -318,9 +397,11 @@ async function mainFetch(req, recursive, terminator) {
       response.body = null;
       core.close(resp.responseRid);
     } else {
-      response.body = new InnerBody(
-        createResponseBodyStream(resp.responseRid, terminator),
-      );
+      let readable = createResponseBodyStream(resp.responseRid, terminator);
+      if (progress !== null) {
+        readable = createProgressStream(readable, req, progress);
+      }
+      response.body = new InnerBody(readable);
     }
   }
 
@@ -0,0 +1,547 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The cookie store of clients created with `cookieJar`, following RFC 6265.
+//! Cookies set by responses are sent with the later requests of the client,
+//! including the ones of redirects that `fetch()` follows.
+//!
+//! There is no public suffix list, so only `Domain` attributes without a dot,
+//! like `com`, are rejected.
+//!
+//! The jar holds at most [`MAX_COOKIES_PER_DOMAIN`] cookies per domain and
+//! [`MAX_COOKIES`] in total, the limits of RFC 6265, section 6.1, and evicts
+//! the oldest cookies beyond them.
+
+use http::header::COOKIE;
+use http::header::SET_COOKIE;
+use http::HeaderMap;
+use http::HeaderValue;
+use reqwest::Url;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// The most cookies the jar holds for a domain.
+const MAX_COOKIES_PER_DOMAIN: usize = 50;
+
+/// The most cookies the jar holds.
+const MAX_COOKIES: usize = 3000;
+
+/// The maximum size of the name and the value of a cookie together. Larger
+/// cookies are ignored.
+const MAX_COOKIE_SIZE: usize = 4096;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Cookie {
+  name: String,
+  value: String,
+  domain: String,
+  path: String,
+  /// When the cookie expires, in milliseconds since the Unix epoch. Session
+  /// cookies don't expire.
+  expires: Option<u64>,
+  #[serde(default)]
+  secure: bool,
+  #[serde(default)]
+  http_only: bool,
+  /// Whether the cookie is only sent to `domain`, and not to its subdomains.
+  #[serde(default)]
+  host_only: bool,
+}
+
+impl Cookie {
+  /// Parses a `Set-Cookie` header of a response to a request to `url`, see
+  /// RFC 6265, section 5.2.
+  fn parse(header: &str, url: &Url, now: SystemTime) -> Option<Self> {
+    let host = url.host_str()?.to_ascii_lowercase();
+    let mut parts = header.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() || name.len() + value.trim().len() > MAX_COOKIE_SIZE {
+      return None;
+    }
+
+    let mut cookie = Cookie {
+      name: name.to_string(),
+      value: value.trim().to_string(),
+      domain: host.clone(),
+      path: default_path(url),
+      expires: None,
+      secure: false,
+      http_only: false,
+      host_only: true,
+    };
+    let mut max_age = None;
+    for attribute in parts {
+      let (name, value) = match attribute.split_once('=') {
+        Some((name, value)) => (name.trim(), value.trim()),
+        None => (attribute.trim(), ""),
+      };
+      match name.to_ascii_lowercase().as_str() {
+        "expires" => {
+          if let Some(expires) = parse_cookie_date(value) {
+            cookie.expires = Some(unix_millis(expires));
+          }
+        }
+        "max-age" => {
+          if let Ok(seconds) = value.parse::<i64>() {
+            max_age = Some(seconds);
+          }
+        }
+        "domain" if !value.is_empty() => {
+          let domain = value.trim_start_matches('.').to_ascii_lowercase();
+          if !domain_matches(&host, &domain)
+            || (!domain.contains('.') && domain != host)
+          {
+            return None;
+          }
+          cookie.host_only = domain == host;
+          cookie.domain = domain;
+        }
+        "path" if value.starts_with('/') => cookie.path = value.to_string(),
+        "secure" => cookie.secure = true,
+        "httponly" => cookie.http_only = true,
+        _ => {}
+      }
+    }
+    // Max-Age takes precedence over Expires.
+    if let Some(seconds) = max_age {
+      cookie.expires = Some(if seconds <= 0 {
+        0
+      } else {
+        unix_millis(now).saturating_add((seconds as u64).saturating_mul(1000))
+      });
+    }
+    // Insecure origins can't set secure cookies, see
+    // draft-ietf-httpbis-rfc6265bis, section 5.7.
+    if cookie.secure && url.scheme() != "https" {
+      return None;
+    }
+    Some(cookie)
+  }
+
+  fn is_expired(&self, now: SystemTime) -> bool {
+    self
+      .expires
+      .map_or(false, |expires| expires <= unix_millis(now))
+  }
+
+  /// Whether the cookie is sent with a request to `url`, see RFC 6265,
+  /// section 5.4.
+  fn matches(&self, url: &Url) -> bool {
+    let Some(host) = url.host_str() else {
+      return false;
+    };
+    let host = host.to_ascii_lowercase();
+    let domain_matches = if self.host_only {
+      host == self.domain
+    } else {
+      domain_matches(&host, &self.domain)
+    };
+    domain_matches
+      && path_matches(url.path(), &self.path)
+      && (!self.secure || url.scheme() == "https")
+  }
+}
+
+#[derive(Default)]
+pub struct CookieJar {
+  cookies: Vec<Cookie>,
+  changed: bool,
+}
+
+impl CookieJar {
+  pub fn new(cookies: Vec<Cookie>) -> Self {
+    let mut jar = Self {
+      cookies,
+      changed: false,
+    };
+    jar.evict();
+    jar
+  }
+
+  /// Adds the cookies for `url` to the `Cookie` header of a request, after
+  /// the ones that were set on the request itself.
+  pub fn add_cookie_header(&mut self, url: &Url, headers: &mut HeaderMap) {
+    let now = SystemTime::now();
+    let mut cookies = self
+      .cookies
+      .iter()
+      .filter(|cookie| !cookie.is_expired(now) && cookie.matches(url))
+      .collect::<Vec<_>>();
+    if cookies.is_empty() {
+      return;
+    }
+    // Cookies with longer paths are listed first, the others in the order
+    // they were created in.
+    cookies.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+    let mut header = cookies
+      .iter()
+      .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+      .collect::<Vec<_>>()
+      .join("; ");
+    if let Some(existing) = headers.get(COOKIE).and_then(|v| v.to_str().ok()) {
+      header = format!("{existing}; {header}");
+    }
+    if let Ok(value) = HeaderValue::from_str(&header) {
+      headers.insert(COOKIE, value);
+    }
+  }
+
+  /// Stores the cookies set by the response to a request to `url`.
+  pub fn store_response_cookies(&mut self, url: &Url, headers: &HeaderMap) {
+    let now = SystemTime::now();
+    for header in headers.get_all(SET_COOKIE) {
+      let Ok(header) = header.to_str() else {
+        continue;
+      };
+      if let Some(cookie) = Cookie::parse(header, url, now) {
+        self.insert(cookie, now);
+      }
+    }
+  }
+
+  fn insert(&mut self, cookie: Cookie, now: SystemTime) {
+    self.cookies.retain(|c| !c.is_expired(now));
+    let existing = self.cookies.iter().position(|c| {
+      c.name == cookie.name
+        && c.domain == cookie.domain
+        && c.path == cookie.path
+    });
+    match (existing, cookie.is_expired(now)) {
+      (Some(index), true) => {
+        self.cookies.remove(index);
+      }
+      (Some(index), false) => self.cookies[index] = cookie,
+      (None, true) => return,
+      (None, false) => self.cookies.push(cookie),
+    }
+    self.evict();
+    self.changed = true;
+  }
+
+  /// Evicts the oldest cookies of the domains that have too many of them, and
+  /// then the oldest cookies of the jar if it still has too many. The cookies
+  /// are kept in the order they were created in.
+  fn evict(&mut self) {
+    let mut domain_counts = HashMap::<&str, usize>::new();
+    for cookie in &self.cookies {
+      *domain_counts.entry(&cookie.domain).or_default() += 1;
+    }
+    let mut excess = domain_counts
+      .into_iter()
+      .filter(|(_, count)| *count > MAX_COOKIES_PER_DOMAIN)
+      .map(|(domain, count)| {
+        (domain.to_string(), count - MAX_COOKIES_PER_DOMAIN)
+      })
+      .collect::<HashMap<_, _>>();
+    if !excess.is_empty() {
+      self
+        .cookies
+        .retain(|cookie| match excess.get_mut(&cookie.domain) {
+          Some(count) if *count > 0 => {
+            *count -= 1;
+            false
+          }
+          _ => true,
+        });
+    }
+    if self.cookies.len() > MAX_COOKIES {
+      self.cookies.drain(..self.cookies.len() - MAX_COOKIES);
+    }
+  }
+
+  /// Returns the cookies of the jar if they have changed since the last call.
+  pub fn take_changes(&mut self) -> Option<Vec<Cookie>> {
+    if !std::mem::take(&mut self.changed) {
+      return None;
+    }
+    let now = SystemTime::now();
+    Some(
+      self
+        .cookies
+        .iter()
+        .filter(|cookie| !cookie.is_expired(now))
+        .cloned()
+        .collect(),
+    )
+  }
+}
+
+/// RFC 6265, section 5.1.3.
+fn domain_matches(host: &str, domain: &str) -> bool {
+  host == domain
+    || (host.ends_with(domain)
+      && host[..host.len() - domain.len()].ends_with('.')
+      && host.parse::<IpAddr>().is_err())
+}
+
+/// RFC 6265, section 5.1.4.
+fn default_path(url: &Url) -> String {
+  let path = url.path();
+  match path.rfind('/') {
+    Some(0) | None => "/".to_string(),
+    Some(index) => path[..index].to_string(),
+  }
+}
+
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+  request_path == cookie_path
+    || (request_path.starts_with(cookie_path)
+      && (cookie_path.ends_with('/')
+        || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+/// Parses the date of an `Expires` attribute, which is more lenient than
+/// HTTP dates, see RFC 6265, section 5.1.1.
+fn parse_cookie_date(value: &str) -> Option<SystemTime> {
+  let mut time = None;
+  let mut day = None;
+  let mut month = None;
+  let mut year = None;
+  let tokens = value
+    .split(|c: char| {
+      !(c.is_ascii_alphanumeric() || c == ':')
+        || c == '\x7f'
+        || c.is_ascii_control()
+    })
+    .filter(|token| !token.is_empty());
+  for token in tokens {
+    let digits = token.bytes().take_while(u8::is_ascii_digit).count();
+    if time.is_none() {
+      let parts = token.split(':').collect::<Vec<_>>();
+      if parts.len() == 3
+        && parts.iter().all(|part| {
+          (1..=2).contains(&part.len())
+            && part.bytes().all(|b| b.is_ascii_digit())
+        })
+      {
+        let parts = parts
+          .iter()
+          .map(|p| p.parse::<u64>().unwrap())
+          .collect::<Vec<_>>();
+        time = Some((parts[0], parts[1], parts[2]));
+        continue;
+      }
+    }
+    if day.is_none() && (1..=2).contains(&digits) {
+      day = token[..digits].parse::<u64>().ok();
+      continue;
+    }
+    if month.is_none() && token.len() >= 3 {
+      let prefix = token[..3].to_ascii_lowercase();
+      if let Some(index) = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct",
+        "nov", "dec",
+      ]
+      .iter()
+      .position(|m| *m == prefix)
+      {
+        month = Some(index as u64 + 1);
+        continue;
+      }
+    }
+    if year.is_none() && (2..=4).contains(&digits) {
+      year = token[..digits].parse::<u64>().ok().map(|year| match year {
+        70..=99 => year + 1900,
+        0..=69 => year + 2000,
+        _ => year,
+      });
+    }
+  }
+
+  let ((hour, minute, second), day, month, year) = (time?, day?, month?, year?);
+  if !(1..=31).contains(&day)
+    || year < 1601
+    || hour > 23
+    || minute > 59
+    || second > 59
+  {
+    return None;
+  }
+  let days = days_from_civil(year, month, day)?;
+  let seconds = days * 86400 + hour * 3600 + minute * 60 + second;
+  Some(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+/// The number of days from 1970-01-01 to a date, or `None` for dates before
+/// it, which have expired anyway.
+fn days_from_civil(year: u64, month: u64, day: u64) -> Option<u64> {
+  let (year, month) = if month <= 2 {
+    (year - 1, month + 9)
+  } else {
+    (year, month - 3)
+  };
+  let era = year / 400;
+  let year_of_era = year % 400;
+  let day_of_year = (153 * month + 2) / 5 + day - 1;
+  let day_of_era =
+    year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+  (era * 146097 + day_of_era).checked_sub(719468)
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+  time
+    .duration_since(SystemTime::UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn cookie_header(jar: &mut CookieJar, url: &str) -> Option<String> {
+    let mut headers = HeaderMap::new();
+    jar.add_cookie_header(&url.parse().unwrap(), &mut headers);
+    headers
+      .get(COOKIE)
+      .map(|value| value.to_str().unwrap().to_string())
+  }
+
+  fn set_cookies(jar: &mut CookieJar, url: &str, cookies: &[&str]) {
+    let mut headers = HeaderMap::new();
+    for cookie in cookies {
+      headers.append(SET_COOKIE, HeaderValue::from_str(cookie).unwrap());
+    }
+    jar.store_response_cookies(&url.parse().unwrap(), &headers);
+  }
+
+  #[test]
+  fn test_domain_and_path() {
+    let mut jar = CookieJar::default();
+    set_cookies(
+      &mut jar,
+      "https://www.example.com/account/login",
+      &[
+        "session=1; Path=/account; HttpOnly",
+        "theme=dark; Domain=.example.com; Path=/",
+        "host=1",
+        "tld=1; Domain=com",
+        "other=1; Domain=example.org",
+      ],
+    );
+    assert_eq!(
+      cookie_header(&mut jar, "https://www.example.com/account/settings"),
+      Some("session=1; host=1; theme=dark".to_string())
+    );
+    assert_eq!(
+      cookie_header(&mut jar, "https://api.example.com/"),
+      Some("theme=dark".to_string())
+    );
+    assert_eq!(
+      cookie_header(&mut jar, "https://www.example.com/accounts"),
+      Some("theme=dark".to_string())
+    );
+    assert_eq!(cookie_header(&mut jar, "https://example.org/"), None);
+    assert_eq!(jar.take_changes().unwrap().len(), 3);
+    assert_eq!(jar.take_changes(), None);
+  }
+
+  #[test]
+  fn test_secure_and_expiry() {
+    let mut jar = CookieJar::default();
+    set_cookies(&mut jar, "http://example.com/", &["insecure=1; Secure"]);
+    assert_eq!(jar.take_changes(), None);
+
+    set_cookies(
+      &mut jar,
+      "https://example.com/",
+      &[
+        "a=1; Secure",
+        "b=1; Max-Age=60",
+        "c=1; Expires=Wed, 21-Oct-2015 07:28:00 GMT",
+      ],
+    );
+    assert_eq!(
+      cookie_header(&mut jar, "https://example.com/"),
+      Some("a=1; b=1".to_string())
+    );
+    assert_eq!(
+      cookie_header(&mut jar, "http://example.com/"),
+      Some("b=1".to_string())
+    );
+    set_cookies(&mut jar, "https://example.com/", &["b=; Max-Age=0"]);
+    assert_eq!(
+      cookie_header(&mut jar, "https://example.com/"),
+      Some("a=1".to_string())
+    );
+  }
+
+  #[test]
+  fn test_max_age_overflow() {
+    let mut jar = CookieJar::default();
+    set_cookies(
+      &mut jar,
+      "https://example.com/",
+      &["a=1; Max-Age=9223372036854775807"],
+    );
+    assert_eq!(
+      cookie_header(&mut jar, "https://example.com/"),
+      Some("a=1".to_string())
+    );
+    assert_eq!(jar.cookies[0].expires, Some(u64::MAX));
+  }
+
+  #[test]
+  fn test_limits() {
+    let mut jar = CookieJar::default();
+    let value = "v".repeat(MAX_COOKIE_SIZE);
+    set_cookies(&mut jar, "https://example.com/", &[&format!("a={value}")]);
+    assert_eq!(jar.take_changes(), None);
+
+    for i in 0..=MAX_COOKIES_PER_DOMAIN {
+      set_cookies(&mut jar, "https://example.com/", &[&format!("c{i}=1")]);
+    }
+    assert_eq!(jar.cookies.len(), MAX_COOKIES_PER_DOMAIN);
+    // The oldest cookie of the domain is evicted.
+    assert_eq!(jar.cookies[0].name, "c1");
+
+    for i in 0..MAX_COOKIES {
+      let url = format!("https://{}.example.org/", i % 100);
+      set_cookies(&mut jar, &url, &[&format!("d{i}=1")]);
+    }
+    assert_eq!(jar.cookies.len(), MAX_COOKIES);
+    assert!(jar
+      .cookies
+      .iter()
+      .all(|cookie| cookie.name.starts_with('d')));
+  }
+
+  #[test]
+  fn test_existing_cookie_header() {
+    let mut jar = CookieJar::new(vec![Cookie {
+      name: "a".to_string(),
+      value: "1".to_string(),
+      domain: "example.com".to_string(),
+      path: "/".to_string(),
+      expires: None,
+      secure: false,
+      http_only: false,
+      host_only: true,
+    }]);
+    let mut headers = HeaderMap::new();
+    headers.insert(COOKIE, HeaderValue::from_static("b=2"));
+    jar
+      .add_cookie_header(&"http://example.com/".parse().unwrap(), &mut headers);
+    assert_eq!(headers.get(COOKIE).unwrap(), "b=2; a=1");
+  }
+
+  #[test]
+  fn test_parse_cookie_date() {
+    let expected = SystemTime::UNIX_EPOCH + Duration::from_secs(1445412480);
+    for date in [
+      "Wed, 21 Oct 2015 07:28:00 GMT",
+      "Wed, 21-Oct-2015 07:28:00 GMT",
+      "Wednesday, 21-Oct-15 07:28:00 GMT",
+      "Wed Oct 21 07:28:00 2015",
+    ] {
+      assert_eq!(parse_cookie_date(date), Some(expected), "{date}");
+    }
+    assert_eq!(parse_cookie_date("not a date"), None);
+    assert_eq!(parse_cookie_date("Wed, 32 Oct 2015 07:28:00 GMT"), None);
+  }
+}
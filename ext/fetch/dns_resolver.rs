@@ -0,0 +1,198 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The resolution of the hosts of `Deno.createHttpClient()` clients, which is
+//! shared by all the connections of a client. Hosts are looked up in the
+//! `hosts` overrides first, then in the cache and finally with the resolver
+//! passed in the `resolver` option, or the system resolver.
+
+use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
+use deno_net::resolver::DnsResolver;
+use hyper::client::connect::dns::Name;
+use indexmap::IndexMap;
+use reqwest::dns::Addrs;
+use reqwest::dns::Resolve;
+use reqwest::dns::Resolving;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+/// The maximum number of hosts in a cache, after which expired entries are
+/// removed, and then the oldest ones.
+const CACHE_CAPACITY: usize = 1024;
+
+#[derive(Default)]
+pub struct HttpClientResolver {
+  hosts: HashMap<String, Vec<IpAddr>>,
+  cache: Option<DnsCache>,
+  /// The resolver, or the system resolver if `None`.
+  resolver: Option<Arc<DnsResolver>>,
+}
+
+impl HttpClientResolver {
+  pub fn new(
+    hosts: HashMap<String, Vec<IpAddr>>,
+    cache_ttl: Option<Duration>,
+    resolver: Option<Arc<DnsResolver>>,
+  ) -> Self {
+    Self {
+      hosts: hosts
+        .into_iter()
+        .map(|(host, addrs)| (normalize_host(&host), addrs))
+        .collect(),
+      cache: cache_ttl.map(DnsCache::new),
+      resolver,
+    }
+  }
+
+  pub async fn lookup_ip(&self, host: &str) -> Result<Vec<IpAddr>, AnyError> {
+    let host = normalize_host(host);
+    if let Some(addrs) = self.hosts.get(&host) {
+      return Ok(addrs.clone());
+    }
+    if let Some(addrs) = self.cache.as_ref().and_then(|cache| cache.get(&host))
+    {
+      return Ok(addrs);
+    }
+    let addrs = match &self.resolver {
+      Some(resolver) => resolver.lookup_ip(&host).await?,
+      None => tokio::net::lookup_host((host.as_str(), 0))
+        .await?
+        .map(|addr| addr.ip())
+        .collect(),
+    };
+    if let Some(cache) = &self.cache {
+      cache.insert(host, addrs.clone());
+    }
+    Ok(addrs)
+  }
+}
+
+fn normalize_host(host: &str) -> String {
+  host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// Checks the addresses that a host resolved to before they are connected to,
+/// e.g. against the denied IP ranges of the permissions.
+pub type ResolvedAddrsCheck =
+  Arc<dyn Fn(&str, &[SocketAddr]) -> Result<(), AnyError> + Send + Sync>;
+
+/// Resolves the hosts of a client with its shared resolver, checking the
+/// resolved addresses with `check` if there is one.
+pub struct ResolverAdapter {
+  pub resolver: Arc<HttpClientResolver>,
+  pub check: Option<ResolvedAddrsCheck>,
+}
+
+impl Resolve for ResolverAdapter {
+  fn resolve(&self, name: Name) -> Resolving {
+    let resolver = self.resolver.clone();
+    let check = self.check.clone();
+    Box::pin(async move {
+      // The port is filled in by the connector.
+      let addrs = resolver
+        .lookup_ip(name.as_str())
+        .await?
+        .into_iter()
+        .map(|ip| SocketAddr::new(ip, 0))
+        .collect::<Vec<_>>();
+      if let Some(check) = check {
+        check(name.as_str(), &addrs)?;
+      }
+      let addrs: Addrs = Box::new(addrs.into_iter());
+      Ok(addrs)
+    })
+  }
+}
+
+/// Caches the addresses of hosts for a fixed time, whatever the TTL of their
+/// records is.
+struct DnsCache {
+  ttl: Duration,
+  entries: Mutex<IndexMap<String, (Instant, Vec<IpAddr>)>>,
+}
+
+impl DnsCache {
+  fn new(ttl: Duration) -> Self {
+    Self {
+      ttl,
+      entries: Default::default(),
+    }
+  }
+
+  fn get(&self, host: &str) -> Option<Vec<IpAddr>> {
+    let entries = self.entries.lock();
+    let (expires, addrs) = entries.get(host)?;
+    (Instant::now() < *expires).then(|| addrs.clone())
+  }
+
+  fn insert(&self, host: String, addrs: Vec<IpAddr>) {
+    let now = Instant::now();
+    let mut entries = self.entries.lock();
+    // The entries are kept in the order they were inserted in, which is also
+    // the order they expire in.
+    entries.shift_remove(&host);
+    if entries.len() >= CACHE_CAPACITY {
+      entries.retain(|_, (expires, _)| now < *expires);
+      if entries.len() >= CACHE_CAPACITY {
+        entries.shift_remove_index(0);
+      }
+    }
+    entries.insert(host, (now + self.ttl, addrs));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn test_hosts() {
+    let addr = "127.0.0.2".parse::<IpAddr>().unwrap();
+    let resolver = HttpClientResolver::new(
+      HashMap::from([("Example.test.".to_string(), vec![addr])]),
+      None,
+      None,
+    );
+    assert_eq!(
+      resolver.lookup_ip("example.test").await.unwrap(),
+      vec![addr]
+    );
+    assert_eq!(
+      resolver.lookup_ip("EXAMPLE.TEST.").await.unwrap(),
+      vec![addr]
+    );
+  }
+
+  #[test]
+  fn test_cache() {
+    let addrs = vec!["127.0.0.2".parse::<IpAddr>().unwrap()];
+    let cache = DnsCache::new(Duration::from_secs(60));
+    assert_eq!(cache.get("example.test"), None);
+    cache.insert("example.test".to_string(), addrs.clone());
+    assert_eq!(cache.get("example.test"), Some(addrs.clone()));
+
+    let cache = DnsCache::new(Duration::ZERO);
+    cache.insert("example.test".to_string(), addrs);
+    assert_eq!(cache.get("example.test"), None);
+  }
+
+  #[test]
+  fn test_cache_capacity() {
+    let addrs = vec!["127.0.0.2".parse::<IpAddr>().unwrap()];
+    let cache = DnsCache::new(Duration::from_secs(60));
+    for i in 0..=CACHE_CAPACITY {
+      cache.insert(format!("{i}.example.test"), addrs.clone());
+    }
+    assert_eq!(cache.entries.lock().len(), CACHE_CAPACITY);
+    assert_eq!(cache.get("0.example.test"), None);
+    assert_eq!(cache.get("1.example.test"), Some(addrs.clone()));
+    assert_eq!(
+      cache.get(&format!("{CACHE_CAPACITY}.example.test")),
+      Some(addrs)
+    );
+  }
+}
@@ -0,0 +1,386 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The HTTP/3 transport of clients created with `http3: true`. Requests to an
+//! `https:` origin are sent over QUIC once one of its responses advertised
+//! HTTP/3 on the same port with `alt-svc`, or right away for clients created
+//! with `http3PriorKnowledge: true`. When a QUIC connection can't be
+//! established, the request is sent with HTTP/2 or HTTP/1.1 instead, and the
+//! origin isn't tried over HTTP/3 again for a while.
+
+use bytes::Buf;
+use bytes::Bytes;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::futures::future::poll_fn;
+use deno_core::futures::StreamExt;
+use deno_core::parking_lot::Mutex;
+use deno_core::url::Url;
+use deno_tls::rustls::ClientConfig;
+use http::header::HeaderName;
+use http::HeaderMap;
+use http::HeaderValue;
+use reqwest::header::ALT_SVC;
+use reqwest::header::CONNECTION;
+use reqwest::header::TRANSFER_ENCODING;
+use reqwest::header::UPGRADE;
+use reqwest::header::USER_AGENT;
+use reqwest::ResponseBuilderExt;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::dns_resolver::HttpClientResolver;
+
+/// ALPN negotation for "h3"
+const TLS_ALPN_HTTP_3: &[u8] = b"h3";
+
+/// How long an `alt-svc` advertisement is used for without a `ma` parameter,
+/// see https://www.rfc-editor.org/rfc/rfc7838#section-3.1.
+const DEFAULT_ALT_SVC_MAX_AGE: u64 = 86400;
+
+/// How long an origin isn't tried over HTTP/3 after a QUIC connection to it
+/// couldn't be established.
+const BROKEN_DURATION: Duration = Duration::from_secs(300);
+
+/// How long establishing a QUIC connection may take before falling back, if
+/// the client doesn't have a `connectTimeout`.
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+type SendRequest = h3::client::SendRequest<h3_quinn::OpenStreams, Bytes>;
+
+/// The host and port of an `https:` URL.
+type Origin = (String, u16);
+
+pub struct Http3Client {
+  tls_config: Arc<ClientConfig>,
+  resolver: Option<Arc<HttpClientResolver>>,
+  user_agent: HeaderValue,
+  prior_knowledge: bool,
+  connect_timeout: Duration,
+  /// The open connections, with an ID that tells them apart from later
+  /// connections to the same origin.
+  connections: Arc<Mutex<HashMap<Origin, (u64, SendRequest)>>>,
+  next_connection_id: AtomicU64,
+  /// The origins that advertised HTTP/3, with the time the advertisement
+  /// expires at.
+  alternatives: Mutex<HashMap<Origin, Instant>>,
+  /// The origins that are not tried over HTTP/3, until the given time.
+  broken: Mutex<HashMap<Origin, Instant>>,
+}
+
+impl Http3Client {
+  pub fn new(
+    mut tls_config: ClientConfig,
+    resolver: Option<Arc<HttpClientResolver>>,
+    user_agent: &str,
+    prior_knowledge: bool,
+    connect_timeout: Option<Duration>,
+  ) -> Result<Self, AnyError> {
+    tls_config.alpn_protocols = vec![TLS_ALPN_HTTP_3.to_vec()];
+    // QUIC requires early data to be either disabled or unlimited, so keep it
+    // disabled.
+    tls_config.enable_early_data = false;
+    Ok(Self {
+      tls_config: Arc::new(tls_config),
+      resolver,
+      user_agent: HeaderValue::from_str(user_agent)?,
+      prior_knowledge,
+      connect_timeout: connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+      connections: Default::default(),
+      next_connection_id: AtomicU64::new(0),
+      alternatives: Default::default(),
+      broken: Default::default(),
+    })
+  }
+
+  /// Whether a request to a URL is sent over HTTP/3.
+  pub fn is_used_for(&self, url: &Url) -> bool {
+    let Some(origin) = origin(url) else {
+      return false;
+    };
+    let now = Instant::now();
+    if self
+      .broken
+      .lock()
+      .get(&origin)
+      .map_or(false, |until| now < *until)
+    {
+      return false;
+    }
+    self.prior_knowledge
+      || self
+        .alternatives
+        .lock()
+        .get(&origin)
+        .map_or(false, |expires| now < *expires)
+  }
+
+  /// Stores the HTTP/3 alternative advertised in the `alt-svc` header of a
+  /// response, if there is one.
+  pub fn store_alt_svc(&self, url: &Url, headers: &HeaderMap) {
+    let Some(origin) = origin(url) else {
+      return;
+    };
+    let Some(value) = headers.get(ALT_SVC).and_then(|v| v.to_str().ok())
+    else {
+      return;
+    };
+    let mut alternatives = self.alternatives.lock();
+    match parse_alt_svc(value, &origin) {
+      Some(max_age) => {
+        alternatives.insert(origin, Instant::now() + max_age);
+      }
+      None => {
+        alternatives.remove(&origin);
+      }
+    }
+  }
+
+  /// Closes the connections, once the requests in progress are done.
+  pub fn close_connections(&self) {
+    self.connections.lock().clear();
+  }
+
+  /// Returns a connection to the origin of a URL, which is established if
+  /// there is none yet. If that fails, the origin is marked as broken.
+  pub async fn connect(&self, url: &Url) -> Result<SendRequest, AnyError> {
+    let origin =
+      origin(url).ok_or_else(|| type_error("HTTP/3 requires an https: URL"))?;
+    if let Some((_, sender)) = self.connections.lock().get(&origin) {
+      return Ok(sender.clone());
+    }
+    let result =
+      tokio::time::timeout(self.connect_timeout, self.establish(&origin))
+        .await
+        .unwrap_or_else(|_| {
+          Err(type_error("Timed out establishing a QUIC connection"))
+        });
+    match result {
+      Ok(sender) => Ok(sender),
+      Err(err) => {
+        self
+          .broken
+          .lock()
+          .insert(origin, Instant::now() + BROKEN_DURATION);
+        Err(err)
+      }
+    }
+  }
+
+  async fn establish(&self, origin: &Origin) -> Result<SendRequest, AnyError> {
+    let (host, port) = origin;
+    let ip = match &self.resolver {
+      Some(resolver) => resolver.lookup_ip(host).await?.into_iter().next(),
+      None => tokio::net::lookup_host((host.as_str(), *port))
+        .await?
+        .map(|addr| addr.ip())
+        .next(),
+    }
+    .ok_or_else(|| type_error(format!("No addresses found for {host}")))?;
+    let bind_addr = match ip {
+      IpAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+      IpAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+    };
+    let endpoint = quinn::Endpoint::client(bind_addr)?;
+    let config = quinn::ClientConfig::new(self.tls_config.clone());
+    let conn = endpoint
+      .connect_with(config, SocketAddr::new(ip, *port), host)?
+      .await?;
+    let (mut driver, sender) =
+      h3::client::new(h3_quinn::Connection::new(conn)).await?;
+
+    let id = self.next_connection_id.fetch_add(1, Ordering::Relaxed);
+    self
+      .connections
+      .lock()
+      .insert(origin.clone(), (id, sender.clone()));
+    let connections = self.connections.clone();
+    let origin = origin.clone();
+    tokio::spawn(async move {
+      let _ = poll_fn(|cx| driver.poll_close(cx)).await;
+      let mut connections = connections.lock();
+      if connections.get(&origin).map_or(false, |(i, _)| *i == id) {
+        connections.remove(&origin);
+      }
+    });
+    Ok(sender)
+  }
+
+  /// Sends a request that was built with `reqwest` over a connection. A
+  /// streamed body is passed separately, like for Unix socket transports.
+  /// The timeout of the request only applies until the response headers are
+  /// received.
+  pub async fn send(
+    &self,
+    mut sender: SendRequest,
+    request: reqwest::Request,
+    streamed_body: Option<hyper::Body>,
+  ) -> Result<reqwest::Response, AnyError> {
+    let url = request.url().clone();
+    let timeout = request.timeout().copied();
+    let (mut parts, body) = http::Request::try_from(request)?.into_parts();
+    // These headers are specific to HTTP/1.1 connections.
+    for name in [CONNECTION, TRANSFER_ENCODING, UPGRADE] {
+      parts.headers.remove(name);
+    }
+    parts.headers.remove(HeaderName::from_static("keep-alive"));
+    if !parts.headers.contains_key(USER_AGENT) {
+      parts.headers.insert(USER_AGENT, self.user_agent.clone());
+    }
+    let mut body = match streamed_body {
+      Some(body) => body,
+      None => body
+        .as_bytes()
+        .map(|bytes| hyper::Body::from(bytes.to_vec()))
+        .unwrap_or_else(hyper::Body::empty),
+    };
+
+    let response = async move {
+      let stream = sender
+        .send_request(http::Request::from_parts(parts, ()))
+        .await?;
+      let (mut send, mut recv) = stream.split();
+      // The body is sent while waiting for the response, which may arrive
+      // before the whole body has been sent.
+      tokio::spawn(async move {
+        while let Some(chunk) = body.next().await {
+          let Ok(chunk) = chunk else {
+            return;
+          };
+          if send.send_data(chunk).await.is_err() {
+            return;
+          }
+        }
+        let _ = send.finish().await;
+      });
+      let response = recv.recv_response().await?;
+      Ok::<_, AnyError>((response, recv))
+    };
+    let (response, recv) = match timeout {
+      Some(timeout) => tokio::time::timeout(timeout, response)
+        .await
+        .map_err(|_| type_error("The request timed out"))??,
+      None => response.await?,
+    };
+
+    let body = deno_core::futures::stream::unfold(recv, |mut recv| async {
+      match recv.recv_data().await {
+        Ok(Some(mut data)) => {
+          let data = data.copy_to_bytes(data.remaining());
+          Some((Ok::<_, h3::Error>(data), recv))
+        }
+        Ok(None) => None,
+        Err(err) => Some((Err(err), recv)),
+      }
+    });
+    let (parts, _) = response.into_parts();
+    let mut builder = http::Response::builder()
+      .status(parts.status)
+      .version(http::Version::HTTP_3)
+      .url(url);
+    if let Some(headers) = builder.headers_mut() {
+      *headers = parts.headers;
+    }
+    Ok(reqwest::Response::from(
+      builder.body(reqwest::Body::wrap_stream(body))?,
+    ))
+  }
+}
+
+fn origin(url: &Url) -> Option<Origin> {
+  if url.scheme() != "https" {
+    return None;
+  }
+  Some((url.host_str()?.to_string(), url.port_or_known_default()?))
+}
+
+/// Returns how long an `alt-svc` header value advertises HTTP/3 for an
+/// origin. Only alternatives on the same host and port are used, since the
+/// permissions to connect to other ones haven't been checked.
+fn parse_alt_svc(value: &str, origin: &Origin) -> Option<Duration> {
+  let (host, port) = origin;
+  for alternative in value.split(',') {
+    let mut params = alternative.split(';').map(str::trim);
+    let Some((protocol, authority)) =
+      params.next().and_then(|entry| entry.split_once('='))
+    else {
+      continue;
+    };
+    if protocol.trim() != "h3" {
+      continue;
+    }
+    let authority = authority.trim().trim_matches('"');
+    let Some((alt_host, alt_port)) = authority.rsplit_once(':') else {
+      continue;
+    };
+    if (!alt_host.is_empty() && !alt_host.eq_ignore_ascii_case(host))
+      || alt_port.parse::<u16>().ok() != Some(*port)
+    {
+      continue;
+    }
+    let max_age = params
+      .find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        (name.trim() == "ma")
+          .then(|| value.trim().trim_matches('"').parse().ok())?
+      })
+      .unwrap_or(DEFAULT_ALT_SVC_MAX_AGE);
+    return Some(Duration::from_secs(max_age));
+  }
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_alt_svc() {
+    let origin = ("example.com".to_string(), 443);
+    assert_eq!(
+      parse_alt_svc("h3=\":443\"; ma=3600", &origin),
+      Some(Duration::from_secs(3600))
+    );
+    assert_eq!(
+      parse_alt_svc("h2=\":443\", h3=\"example.com:443\"", &origin),
+      Some(Duration::from_secs(DEFAULT_ALT_SVC_MAX_AGE))
+    );
+    // Alternatives on another port or host aren't used.
+    assert_eq!(parse_alt_svc("h3=\":8443\"", &origin), None);
+    assert_eq!(parse_alt_svc("h3=\"cdn.example.com:443\"", &origin), None);
+    assert_eq!(parse_alt_svc("h3-29=\":443\"", &origin), None);
+    assert_eq!(parse_alt_svc("clear", &origin), None);
+  }
+
+  #[test]
+  fn test_store_alt_svc() {
+    let client = Http3Client::new(
+      deno_tls::create_client_config(None, vec![], None, None).unwrap(),
+      None,
+      "Deno",
+      false,
+      None,
+    )
+    .unwrap();
+    let url = Url::parse("https://example.com/").unwrap();
+    assert!(!client.is_used_for(&url));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(ALT_SVC, HeaderValue::from_static("h3=\":443\""));
+    client.store_alt_svc(&url, &headers);
+    assert!(client.is_used_for(&url));
+    assert!(!client.is_used_for(&Url::parse("https://example.org/").unwrap()));
+    assert!(!client.is_used_for(&Url::parse("http://example.com/").unwrap()));
+
+    headers.insert(ALT_SVC, HeaderValue::from_static("clear"));
+    client.store_alt_svc(&url, &headers);
+    assert!(!client.is_used_for(&url));
+  }
+}
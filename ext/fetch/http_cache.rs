@@ -0,0 +1,794 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! A private HTTP cache, following RFC 9111, for the requests of clients
+//! created with `Deno.createHttpClient({ httpCache: true })`. Responses to
+//! `GET` requests are stored in the cache directory of the `Options`, and how
+//! they are used depends on the `cache` mode of each request.
+//!
+//! The responses to a URL are stored in a directory named after it, with a
+//! `vary.json` file listing the request headers named by their `Vary` header,
+//! and a response for each of the values of these headers.
+
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::futures::Future;
+use deno_core::futures::Stream;
+use deno_core::futures::StreamExt;
+use deno_core::serde_json;
+use http::header::AGE;
+use http::header::AUTHORIZATION;
+use http::header::CACHE_CONTROL;
+use http::header::DATE;
+use http::header::ETAG;
+use http::header::EXPIRES;
+use http::header::IF_MATCH;
+use http::header::IF_MODIFIED_SINCE;
+use http::header::IF_NONE_MATCH;
+use http::header::IF_RANGE;
+use http::header::IF_UNMODIFIED_SINCE;
+use http::header::LAST_MODIFIED;
+use http::header::SET_COOKIE;
+use http::header::VARY;
+use http::HeaderMap;
+use http::HeaderValue;
+use http::StatusCode;
+use reqwest::ResponseBuilderExt;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::SystemTime;
+use tokio::task::JoinHandle;
+
+/// Bodies larger than this are not stored.
+const MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+
+/// The statuses of responses that can be stored without explicit freshness
+/// information, see RFC 9110, section 15.1.
+const HEURISTICALLY_CACHEABLE: &[u16] =
+  &[200, 203, 204, 300, 301, 308, 404, 405, 410, 414, 501];
+
+/// The `cache` mode of a request,
+/// https://fetch.spec.whatwg.org/#concept-request-cache-mode.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CacheMode {
+  #[default]
+  Default,
+  NoStore,
+  Reload,
+  NoCache,
+  ForceCache,
+  OnlyIfCached,
+}
+
+pub type SendFuture =
+  Pin<Box<dyn Future<Output = Result<reqwest::Response, AnyError>>>>;
+
+pub struct HttpCache {
+  dir: PathBuf,
+}
+
+impl HttpCache {
+  pub fn new(dir: PathBuf) -> Self {
+    Self { dir }
+  }
+
+  /// Responds to a `GET` request from the cache, or with `send`, storing or
+  /// revalidating the stored response as needed.
+  pub async fn fetch(
+    self: Arc<Self>,
+    mut request: reqwest::Request,
+    mut mode: CacheMode,
+    send: impl FnOnce(reqwest::Request) -> SendFuture,
+  ) -> Result<reqwest::Response, AnyError> {
+    // https://fetch.spec.whatwg.org/#http-network-or-cache-fetch step 8.21
+    let headers = request.headers();
+    if mode == CacheMode::Default
+      && [
+        IF_MODIFIED_SINCE,
+        IF_NONE_MATCH,
+        IF_UNMODIFIED_SINCE,
+        IF_MATCH,
+        IF_RANGE,
+      ]
+      .iter()
+      .any(|name| headers.contains_key(name))
+    {
+      mode = CacheMode::NoStore;
+    }
+    if mode == CacheMode::NoStore {
+      return send(request).await;
+    }
+
+    let url_dir = self.dir.join(url_key(request.url()));
+    let stored = match mode {
+      CacheMode::Reload => None,
+      _ => {
+        let url_dir = url_dir.clone();
+        let request_headers = request.headers().clone();
+        tokio::task::spawn_blocking(move || read(&url_dir, &request_headers))
+          .await?
+          .filter(|stored| stored.matches(request.headers()))
+      }
+    };
+    let now = SystemTime::now();
+    match &stored {
+      Some(stored)
+        if matches!(mode, CacheMode::ForceCache | CacheMode::OnlyIfCached)
+          || (mode == CacheMode::Default && stored.is_fresh(now)) =>
+      {
+        return stored.to_response(now);
+      }
+      Some(stored) => {
+        let headers = stored.headers();
+        if let Some(etag) = headers.get(ETAG) {
+          request.headers_mut().insert(IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(last_modified) = headers.get(LAST_MODIFIED) {
+          request
+            .headers_mut()
+            .insert(IF_MODIFIED_SINCE, last_modified.clone());
+        }
+      }
+      None if mode == CacheMode::OnlyIfCached => {
+        return Err(type_error(
+          "NetworkError when attempting to fetch resource.",
+        ));
+      }
+      None => {}
+    }
+
+    let request_headers = request.headers().clone();
+    let request_time = SystemTime::now();
+    let response = send(request).await?;
+    let response_time = SystemTime::now();
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+      if let Some(mut stored) = stored {
+        stored.update(response.headers(), request_time, response_time);
+        let metadata = stored.metadata.clone();
+        tokio::task::spawn_blocking(move || {
+          write_metadata(&url_dir, &metadata)
+        })
+        .await??;
+        return stored.to_response(response_time);
+      }
+    }
+    if !is_storable(&request_headers, &response) {
+      return Ok(response);
+    }
+
+    let metadata =
+      Metadata::new(&request_headers, &response, request_time, response_time);
+    Self::store(url_dir, metadata, response)
+  }
+
+  /// Removes the stored responses for a URL, after an unsafe request to it,
+  /// see RFC 9111, section 4.4.
+  pub async fn invalidate(&self, url: &reqwest::Url) {
+    let _ = tokio::fs::remove_dir_all(self.dir.join(url_key(url))).await;
+  }
+
+  /// Returns the response with a body that stores it once it has been read
+  /// completely.
+  fn store(
+    url_dir: PathBuf,
+    metadata: Metadata,
+    response: reqwest::Response,
+  ) -> Result<reqwest::Response, AnyError> {
+    let url = response.url().clone();
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let stream = StoringStream {
+      inner: Box::pin(response.bytes_stream()),
+      body: Some(Vec::new()),
+      entry: Some((url_dir, metadata)),
+      write: None,
+    };
+    let mut builder = http::Response::builder()
+      .status(status)
+      .version(version)
+      .url(url);
+    if let Some(response_headers) = builder.headers_mut() {
+      *response_headers = headers;
+    }
+    Ok(reqwest::Response::from(
+      builder.body(reqwest::Body::wrap_stream(stream))?,
+    ))
+  }
+}
+
+/// Reads the response stored for a URL that was selected by the same values
+/// of the request headers that it varies on.
+fn read(url_dir: &Path, request_headers: &HeaderMap) -> Option<StoredResponse> {
+  let vary = std::fs::read(url_dir.join("vary.json")).ok()?;
+  let vary: Vec<String> = serde_json::from_slice(&vary).ok()?;
+  let key = variant_key(&vary_values(request_headers, &vary));
+  let metadata = std::fs::read(url_dir.join(format!("{key}.json"))).ok()?;
+  let metadata = serde_json::from_slice(&metadata).ok()?;
+  let body = std::fs::read(url_dir.join(format!("{key}.body"))).ok()?;
+  Some(StoredResponse { metadata, body })
+}
+
+fn write_metadata(url_dir: &Path, metadata: &Metadata) -> io::Result<()> {
+  let key = variant_key(&metadata.vary);
+  write(
+    url_dir,
+    &format!("{key}.json"),
+    &serde_json::to_vec(metadata)?,
+  )
+}
+
+/// Writes a response, and then the names of the headers it varies on, which
+/// select the responses that are read for the URL.
+fn write_entry(
+  url_dir: &Path,
+  metadata: &Metadata,
+  body: &[u8],
+) -> io::Result<()> {
+  let key = variant_key(&metadata.vary);
+  write(url_dir, &format!("{key}.body"), body)?;
+  write_metadata(url_dir, metadata)?;
+  let vary = metadata
+    .vary
+    .iter()
+    .map(|(name, _)| name)
+    .collect::<Vec<_>>();
+  write(url_dir, "vary.json", &serde_json::to_vec(&vary)?)
+}
+
+/// Writes a file atomically, so that concurrent requests don't read it
+/// partially.
+fn write(url_dir: &Path, name: &str, data: &[u8]) -> io::Result<()> {
+  std::fs::create_dir_all(url_dir)?;
+  let path = url_dir.join(name);
+  let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+  std::fs::write(&temp_path, data)?;
+  std::fs::rename(temp_path, path)
+}
+
+/// The name of the directory of the responses stored for a URL.
+fn url_key(url: &reqwest::Url) -> String {
+  let mut url = url.clone();
+  url.set_fragment(None);
+  format!("{:x}", Sha256::digest(url.as_str().as_bytes()))
+}
+
+/// The name of the files of a response stored for a URL, from the values of
+/// the request headers that it varies on.
+fn variant_key(vary: &[(String, Option<String>)]) -> String {
+  let mut hasher = Sha256::new();
+  for (name, value) in vary {
+    hasher.update(name.as_bytes());
+    match value {
+      Some(value) => {
+        hasher.update(b":");
+        hasher.update(value.as_bytes());
+      }
+      None => hasher.update(b"!"),
+    }
+    hasher.update(b"\n");
+  }
+  format!("{:x}", hasher.finalize())
+}
+
+/// The values of the request headers with the names given by a `Vary` header.
+fn vary_values(
+  request_headers: &HeaderMap,
+  names: &[String],
+) -> Vec<(String, Option<String>)> {
+  names
+    .iter()
+    .map(|name| (name.clone(), joined_values(request_headers, name)))
+    .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Metadata {
+  url: String,
+  status: u16,
+  headers: Vec<(String, String)>,
+  /// The values of the request headers named by the `Vary` header.
+  vary: Vec<(String, Option<String>)>,
+  /// When the request was sent and its response was received, in seconds
+  /// since the Unix epoch.
+  request_time: u64,
+  response_time: u64,
+}
+
+impl Metadata {
+  fn new(
+    request_headers: &HeaderMap,
+    response: &reqwest::Response,
+    request_time: SystemTime,
+    response_time: SystemTime,
+  ) -> Self {
+    let headers = response.headers();
+    Self {
+      url: response.url().to_string(),
+      status: response.status().as_u16(),
+      headers: headers
+        .iter()
+        .map(|(name, value)| {
+          (
+            name.to_string(),
+            String::from_utf8_lossy(value.as_bytes()).into_owned(),
+          )
+        })
+        .collect(),
+      vary: vary_values(request_headers, &vary_names(headers)),
+      request_time: unix_time(request_time),
+      response_time: unix_time(response_time),
+    }
+  }
+}
+
+struct StoredResponse {
+  metadata: Metadata,
+  body: Vec<u8>,
+}
+
+impl StoredResponse {
+  fn headers(&self) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in &self.metadata.headers {
+      if let (Ok(name), Ok(value)) = (
+        http::header::HeaderName::from_bytes(name.as_bytes()),
+        HeaderValue::from_str(value),
+      ) {
+        headers.append(name, value);
+      }
+    }
+    headers
+  }
+
+  /// Whether the stored response was selected by the same values of the
+  /// headers named by its `Vary` header.
+  fn matches(&self, request_headers: &HeaderMap) -> bool {
+    self
+      .metadata
+      .vary
+      .iter()
+      .all(|(name, value)| joined_values(request_headers, name) == *value)
+  }
+
+  fn current_age(&self, now: SystemTime) -> Duration {
+    // RFC 9111, section 4.2.3.
+    let headers = self.headers();
+    let request_time = from_unix_time(self.metadata.request_time);
+    let response_time = from_unix_time(self.metadata.response_time);
+    let date = http_date(&headers, DATE).unwrap_or(response_time);
+    let age_value = headers
+      .get(AGE)
+      .and_then(|value| value.to_str().ok()?.parse().ok())
+      .map(Duration::from_secs)
+      .unwrap_or_default();
+    let apparent_age = response_time.duration_since(date).unwrap_or_default();
+    let response_delay = response_time
+      .duration_since(request_time)
+      .unwrap_or_default();
+    let corrected_initial_age = apparent_age.max(age_value + response_delay);
+    let resident_time = now.duration_since(response_time).unwrap_or_default();
+    corrected_initial_age + resident_time
+  }
+
+  fn freshness_lifetime(&self) -> Duration {
+    // RFC 9111, section 4.2.1.
+    let headers = self.headers();
+    let cache_control = CacheControl::new(&headers);
+    if let Some(max_age) = cache_control.max_age {
+      return Duration::from_secs(max_age);
+    }
+    let date = http_date(&headers, DATE)
+      .unwrap_or_else(|| from_unix_time(self.metadata.response_time));
+    if headers.contains_key(EXPIRES) {
+      // Invalid dates, like "0", mean that the response has expired.
+      return http_date(&headers, EXPIRES)
+        .and_then(|expires| expires.duration_since(date).ok())
+        .unwrap_or_default();
+    }
+    // A heuristic of RFC 9111, section 4.2.2.
+    match http_date(&headers, LAST_MODIFIED) {
+      Some(last_modified)
+        if HEURISTICALLY_CACHEABLE.contains(&self.metadata.status) =>
+      {
+        date.duration_since(last_modified).unwrap_or_default() / 10
+      }
+      _ => Duration::ZERO,
+    }
+  }
+
+  fn is_fresh(&self, now: SystemTime) -> bool {
+    !CacheControl::new(&self.headers()).no_cache
+      && self.freshness_lifetime() > self.current_age(now)
+  }
+
+  /// Updates the stored response with the headers of a `304 Not Modified`
+  /// response, see RFC 9111, section 3.2.
+  fn update(
+    &mut self,
+    headers: &HeaderMap,
+    request_time: SystemTime,
+    response_time: SystemTime,
+  ) {
+    let mut stored = self.headers();
+    for name in headers.keys() {
+      if name == http::header::CONTENT_LENGTH {
+        continue;
+      }
+      stored.remove(name);
+      for value in headers.get_all(name) {
+        stored.append(name, value.clone());
+      }
+    }
+    self.metadata.headers = stored
+      .iter()
+      .map(|(name, value)| {
+        (
+          name.to_string(),
+          String::from_utf8_lossy(value.as_bytes()).into_owned(),
+        )
+      })
+      .collect();
+    self.metadata.request_time = unix_time(request_time);
+    self.metadata.response_time = unix_time(response_time);
+  }
+
+  fn to_response(
+    &self,
+    now: SystemTime,
+  ) -> Result<reqwest::Response, AnyError> {
+    let mut headers = self.headers();
+    headers.insert(AGE, HeaderValue::from(self.current_age(now).as_secs()));
+    let mut builder = http::Response::builder()
+      .status(self.metadata.status)
+      .url(reqwest::Url::parse(&self.metadata.url)?);
+    if let Some(response_headers) = builder.headers_mut() {
+      *response_headers = headers;
+    }
+    Ok(reqwest::Response::from(
+      builder.body(reqwest::Body::from(self.body.clone()))?,
+    ))
+  }
+}
+
+/// The directives of a `Cache-Control` header that are used.
+#[derive(Default)]
+struct CacheControl {
+  max_age: Option<u64>,
+  no_cache: bool,
+  no_store: bool,
+  public: bool,
+  must_revalidate: bool,
+  s_maxage: bool,
+}
+
+impl CacheControl {
+  fn new(headers: &HeaderMap) -> Self {
+    let mut cache_control = Self::default();
+    for value in headers.get_all(CACHE_CONTROL) {
+      let Ok(value) = value.to_str() else {
+        continue;
+      };
+      for directive in value.split(',') {
+        let (name, argument) = match directive.split_once('=') {
+          Some((name, argument)) => {
+            (name, Some(argument.trim().trim_matches('"')))
+          }
+          None => (directive, None),
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+          "max-age" => {
+            cache_control.max_age = argument.and_then(|a| a.parse().ok())
+          }
+          "no-cache" => cache_control.no_cache = true,
+          "no-store" => cache_control.no_store = true,
+          "public" => cache_control.public = true,
+          "must-revalidate" => cache_control.must_revalidate = true,
+          "s-maxage" => cache_control.s_maxage = true,
+          _ => {}
+        }
+      }
+    }
+    cache_control
+  }
+}
+
+/// Whether a response can be stored, see RFC 9111, section 3.
+fn is_storable(
+  request_headers: &HeaderMap,
+  response: &reqwest::Response,
+) -> bool {
+  let headers = response.headers();
+  let cache_control = CacheControl::new(headers);
+  if CacheControl::new(request_headers).no_store || cache_control.no_store {
+    return false;
+  }
+  if vary_names(headers).iter().any(|name| name == "*") {
+    return false;
+  }
+  // Cookies set for one request must not be replayed to others
+  if headers.contains_key(SET_COOKIE) {
+    return false;
+  }
+  if request_headers.contains_key(AUTHORIZATION)
+    && !(cache_control.public
+      || cache_control.must_revalidate
+      || cache_control.s_maxage)
+  {
+    return false;
+  }
+  if response
+    .content_length()
+    .map_or(false, |length| length > MAX_BODY_SIZE as u64)
+  {
+    return false;
+  }
+  let status = response.status().as_u16();
+  cache_control.max_age.is_some()
+    || headers.contains_key(EXPIRES)
+    || cache_control.public
+    || (HEURISTICALLY_CACHEABLE.contains(&status)
+      && (headers.contains_key(ETAG) || headers.contains_key(LAST_MODIFIED)))
+}
+
+fn vary_names(headers: &HeaderMap) -> Vec<String> {
+  headers
+    .get_all(VARY)
+    .iter()
+    .filter_map(|value| value.to_str().ok())
+    .flat_map(|value| value.split(','))
+    .map(|name| name.trim().to_ascii_lowercase())
+    .filter(|name| !name.is_empty())
+    .collect()
+}
+
+fn joined_values(headers: &HeaderMap, name: &str) -> Option<String> {
+  let values = headers
+    .get_all(name)
+    .iter()
+    .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+    .collect::<Vec<_>>();
+  (!values.is_empty()).then(|| values.join(", "))
+}
+
+fn http_date(
+  headers: &HeaderMap,
+  name: http::header::HeaderName,
+) -> Option<SystemTime> {
+  httpdate::parse_http_date(headers.get(name)?.to_str().ok()?).ok()
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+  time
+    .duration_since(SystemTime::UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_secs()
+}
+
+fn from_unix_time(secs: u64) -> SystemTime {
+  SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+type EntryWrite = JoinHandle<io::Result<()>>;
+
+/// The body of a response that is being stored, which writes the cache entry
+/// once it has been read completely. Responses whose body fails or is too
+/// large are not stored.
+struct StoringStream<S> {
+  inner: Pin<Box<S>>,
+  body: Option<Vec<u8>>,
+  entry: Option<(PathBuf, Metadata)>,
+  write: Option<EntryWrite>,
+}
+
+impl<S> Stream for StoringStream<S>
+where
+  S: Stream<Item = reqwest::Result<bytes::Bytes>>,
+{
+  type Item = reqwest::Result<bytes::Bytes>;
+
+  fn poll_next(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Option<Self::Item>> {
+    if let Some(write) = &mut self.write {
+      // Errors only mean that the response isn't cached.
+      let _ = std::task::ready!(Pin::new(write).poll(cx));
+      self.write = None;
+      return Poll::Ready(None);
+    }
+    match std::task::ready!(self.inner.poll_next_unpin(cx)) {
+      Some(Ok(chunk)) => {
+        if let Some(body) = &mut self.body {
+          if body.len() + chunk.len() > MAX_BODY_SIZE {
+            self.body = None;
+          } else {
+            body.extend_from_slice(&chunk);
+          }
+        }
+        Poll::Ready(Some(Ok(chunk)))
+      }
+      Some(Err(err)) => {
+        self.body = None;
+        Poll::Ready(Some(Err(err)))
+      }
+      None => {
+        let (Some(body), Some((url_dir, metadata))) =
+          (self.body.take(), self.entry.take())
+        else {
+          return Poll::Ready(None);
+        };
+        self.write = Some(tokio::task::spawn_blocking(move || {
+          write_entry(&url_dir, &metadata, &body)
+        }));
+        self.poll_next(cx)
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn stored(headers: &[(&str, &str)], age: u64) -> StoredResponse {
+    let now = unix_time(SystemTime::now());
+    StoredResponse {
+      metadata: Metadata {
+        url: "https://deno.land/".to_string(),
+        status: 200,
+        headers: headers
+          .iter()
+          .map(|(name, value)| (name.to_string(), value.to_string()))
+          .collect(),
+        vary: vec![("accept".to_string(), Some("text/html".to_string()))],
+        request_time: now - age,
+        response_time: now - age,
+      },
+      body: vec![],
+    }
+  }
+
+  #[test]
+  fn test_freshness() {
+    let now = SystemTime::now();
+    assert!(stored(&[("cache-control", "max-age=60")], 10).is_fresh(now));
+    assert!(!stored(&[("cache-control", "max-age=60")], 100).is_fresh(now));
+    assert!(
+      !stored(&[("cache-control", "max-age=60, no-cache")], 10).is_fresh(now)
+    );
+    assert!(
+      !stored(&[("cache-control", "max-age=60"), ("age", "70")], 0)
+        .is_fresh(now)
+    );
+    assert!(!stored(&[("expires", "0")], 0).is_fresh(now));
+    assert!(!stored(&[("etag", "\"1\"")], 0).is_fresh(now));
+
+    let date = httpdate::fmt_http_date(now);
+    let last_modified =
+      httpdate::fmt_http_date(now - Duration::from_secs(1000));
+    let heuristic =
+      stored(&[("date", &date), ("last-modified", &last_modified)], 0);
+    assert_eq!(heuristic.freshness_lifetime(), Duration::from_secs(100));
+  }
+
+  #[test]
+  fn test_vary() {
+    let stored = stored(&[("vary", "Accept")], 0);
+    let mut headers = HeaderMap::new();
+    assert!(!stored.matches(&headers));
+    headers.insert("accept", HeaderValue::from_static("text/html"));
+    assert!(stored.matches(&headers));
+  }
+
+  #[tokio::test]
+  async fn test_fetch() {
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+      let mut requests = vec![];
+      for response in [
+        "200 OK\r\ncache-control: max-age=0\r\netag: \"1\"\r\ncontent-length: 5\r\n\r\nhello",
+        "304 Not Modified\r\netag: \"1\"\r\n\r\n",
+      ] {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = vec![0; 1024];
+        let n = stream.read(&mut buf).await.unwrap();
+        requests.push(String::from_utf8_lossy(&buf[..n]).to_string());
+        let response = format!("HTTP/1.1 {response}");
+        let response = response.replacen("\r\n", "\r\nconnection: close\r\n", 1);
+        stream.write_all(response.as_bytes()).await.unwrap();
+      }
+      requests
+    });
+
+    let dir = std::env::temp_dir()
+      .join(format!("deno_fetch_http_cache_{}", std::process::id()));
+    let cache = Arc::new(HttpCache::new(dir.clone()));
+    let client = reqwest::Client::new();
+    let fetch = |mode| {
+      let client = client.clone();
+      let request = client.get(&url).build().unwrap();
+      cache.clone().fetch(request, mode, move |request| {
+        Box::pin(async move { Ok(client.execute(request).await?) })
+      })
+    };
+
+    let response = fetch(CacheMode::Default).await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "hello");
+    // Stale, so it is revalidated.
+    let response = fetch(CacheMode::Default).await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), "hello");
+    let response = fetch(CacheMode::OnlyIfCached).await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "hello");
+    assert!(fetch(CacheMode::NoStore).await.is_err());
+
+    let requests = server.await.unwrap();
+    std::fs::remove_dir_all(dir).unwrap();
+    assert!(!requests[0].contains("if-none-match"));
+    assert!(requests[1].contains("if-none-match: \"1\"\r\n"));
+  }
+
+  #[test]
+  fn test_cache_key() {
+    assert_eq!(
+      url_key(&"https://deno.land/#a".parse().unwrap()),
+      url_key(&"https://deno.land/#b".parse().unwrap())
+    );
+    assert_ne!(
+      url_key(&"https://deno.land/?a".parse().unwrap()),
+      url_key(&"https://deno.land/?b".parse().unwrap())
+    );
+
+    let mut headers = HeaderMap::new();
+    headers.insert("accept", HeaderValue::from_static("text/html"));
+    let names = vec!["accept".to_string()];
+    let html = variant_key(&vary_values(&headers, &names));
+    headers.insert("accept", HeaderValue::from_static("application/json"));
+    let json = variant_key(&vary_values(&headers, &names));
+    let none = variant_key(&vary_values(&HeaderMap::new(), &names));
+    assert_ne!(html, json);
+    assert_ne!(html, none);
+    assert_ne!(variant_key(&[]), none);
+  }
+
+  #[test]
+  fn test_is_storable() {
+    let response = |headers: &[(&str, &str)]| {
+      let mut builder = http::Response::builder().status(200);
+      for (name, value) in headers {
+        builder = builder.header(*name, *value);
+      }
+      reqwest::Response::from(builder.body(reqwest::Body::from("")).unwrap())
+    };
+    let request_headers = HeaderMap::new();
+    assert!(is_storable(
+      &request_headers,
+      &response(&[("cache-control", "max-age=60")])
+    ));
+    assert!(!is_storable(
+      &request_headers,
+      &response(&[("cache-control", "max-age=60"), ("set-cookie", "a=b")])
+    ));
+    assert!(!is_storable(
+      &request_headers,
+      &response(&[("cache-control", "max-age=60"), ("vary", "*")])
+    ));
+  }
+}
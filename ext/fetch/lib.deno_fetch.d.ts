@@ -163,6 +163,8 @@ type RequestCache =
 /** @category Fetch API */
 type RequestCredentials = "include" | "omit" | "same-origin";
 /** @category Fetch API */
+type RequestDuplex = "half";
+/** @category Fetch API */
 type RequestMode = "cors" | "navigate" | "no-cors" | "same-origin";
 /** @category Fetch API */
 type RequestRedirect = "error" | "follow" | "manual";
@@ -223,6 +225,12 @@ interface RequestInit {
    * credentials.
    */
   credentials?: RequestCredentials;
+  /**
+   * A string indicating whether the response can be received while the
+   * request body is still being sent. Only `"half"` is supported, with which
+   * a `ReadableStream` body is sent as it is read instead of being buffered.
+   */
+  duplex?: RequestDuplex;
   /**
    * A Headers object, an object literal, or an array of two-item arrays to set
    * request's headers.
@@ -294,6 +302,10 @@ declare class Request implements Body {
    * Returns the kind of resource requested by request, e.g., "document" or "script".
    */
   readonly destination: RequestDestination;
+  /**
+   * Returns the duplex mode of request, which is always "half".
+   */
+  readonly duplex: RequestDuplex;
   /**
    * Returns a Headers object consisting of the headers associated with request.
    * Note that headers added in the network layer by the user agent will not be
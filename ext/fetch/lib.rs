@@ -1,17 +1,29 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 mod byte_stream;
+mod cookie_jar;
+mod dns_resolver;
 mod fs_fetch_handler;
+mod http3;
+mod http_cache;
+mod retry;
+#[cfg(unix)]
+mod unix_socket;
 
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::cell::RefMut;
 use std::cmp::min;
+use std::collections::HashMap;
 use std::convert::From;
+use std::net::IpAddr;
 use std::path::Path;
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
 use deno_core::error::type_error;
 use deno_core::error::AnyError;
@@ -36,6 +48,7 @@ use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::ResourceId;
 use deno_core::ZeroCopyBuf;
+use deno_net::resolver::DnsResolverResource;
 use deno_tls::rustls::RootCertStore;
 use deno_tls::Proxy;
 use deno_tls::RootCertStoreProvider;
@@ -64,9 +77,38 @@ use tokio::sync::mpsc;
 pub use data_url;
 pub use reqwest;
 
+pub use dns_resolver::ResolvedAddrsCheck;
 pub use fs_fetch_handler::FsFetchHandler;
 
 use crate::byte_stream::MpscByteStream;
+use crate::cookie_jar::Cookie;
+use crate::cookie_jar::CookieJar;
+use crate::dns_resolver::HttpClientResolver;
+use crate::dns_resolver::ResolverAdapter;
+use crate::http3::Http3Client;
+use crate::http_cache::CacheMode;
+use crate::http_cache::HttpCache;
+use crate::http_cache::SendFuture;
+use crate::retry::send_with_retries;
+use crate::retry::RetryPolicy;
+#[cfg(unix)]
+use crate::unix_socket::UnixSocketClient;
+
+/// There are no Unix socket transports on other platforms.
+#[cfg(not(unix))]
+#[derive(Clone)]
+enum UnixSocketClient {}
+
+#[cfg(not(unix))]
+impl UnixSocketClient {
+  async fn send(
+    &self,
+    _request: reqwest::Request,
+    _streamed_body: Option<hyper::Body>,
+  ) -> Result<Response, AnyError> {
+    match *self {}
+  }
+}
 
 #[derive(Clone)]
 pub struct Options {
@@ -78,6 +120,12 @@ pub struct Options {
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
   pub client_cert_chain_and_key: Option<(String, String)>,
   pub file_fetch_handler: Rc<dyn FetchHandler>,
+  /// The directory that the HTTP caches of clients created with
+  /// `httpCache: true` store responses in.
+  pub http_cache_dir: Option<PathBuf>,
+  /// Checks the addresses that the hosts of requests resolve to before they
+  /// are connected to.
+  pub resolved_addrs_check: Option<ResolvedAddrsCheck>,
 }
 
 impl Options {
@@ -99,6 +147,8 @@ impl Default for Options {
       unsafely_ignore_certificate_errors: None,
       client_cert_chain_and_key: None,
       file_fetch_handler: Rc::new(DefaultFileFetchHandler),
+      http_cache_dir: None,
+      resolved_addrs_check: None,
     }
   }
 }
@@ -110,6 +160,8 @@ deno_core::extension!(deno_fetch,
     op_fetch<FP>,
     op_fetch_send,
     op_fetch_custom_client<FP>,
+    op_fetch_take_cookie_changes,
+    op_fetch_close_idle_connections,
   ],
   esm = [
     "20_headers.js",
@@ -178,6 +230,7 @@ pub trait FetchPermissions {
     api_name: &str,
   ) -> Result<(), AnyError>;
   fn check_read(&mut self, _p: &Path, api_name: &str) -> Result<(), AnyError>;
+  fn check_write(&mut self, _p: &Path, api_name: &str) -> Result<(), AnyError>;
 }
 
 pub fn get_declaration() -> PathBuf {
@@ -198,19 +251,105 @@ pub fn get_or_create_client_from_state(
     Ok(client.clone())
   } else {
     let options = state.borrow::<Options>();
-    let client = create_http_client(
+    let mut builder = http_client_builder(
       &options.user_agent,
       options.root_cert_store()?,
       vec![],
       options.proxy.clone(),
       options.unsafely_ignore_certificate_errors.clone(),
       options.client_cert_chain_and_key.clone(),
+      vec!["h2".into(), "http/1.1".into()],
     )?;
+    if let Some(check) = &options.resolved_addrs_check {
+      builder = builder.dns_resolver(Arc::new(ResolverAdapter {
+        resolver: Default::default(),
+        check: Some(check.clone()),
+      }));
+    }
+    // unwrap here because it can only fail when native TLS is used.
+    let client = builder.build().unwrap();
     state.put::<reqwest::Client>(client.clone());
     Ok(client)
   }
 }
 
+/// The most clients that are kept for the proxies given to `fetch()`, for
+/// each client they are derived from.
+const MAX_PROXY_CLIENTS: usize = 16;
+
+/// The clients derived from the default client for the proxies given to
+/// `fetch()` with the `proxy` option.
+#[derive(Default)]
+struct DefaultProxyClients(HashMap<Proxy, Client>);
+
+/// Caches the client for a proxy, evicting others to keep at most
+/// `MAX_PROXY_CLIENTS` of them.
+fn insert_proxy_client<K: Clone + Eq + std::hash::Hash>(
+  clients: &mut HashMap<K, Client>,
+  key: K,
+  client: Client,
+) {
+  while clients.len() >= MAX_PROXY_CLIENTS {
+    let key = clients.keys().next().unwrap().clone();
+    clients.remove(&key);
+  }
+  clients.insert(key, client);
+}
+
+/// Returns the client for a request with its own proxy. It is derived from
+/// the client the request would be sent with otherwise, and cached for later
+/// requests through the same proxy.
+fn get_or_create_proxy_client(
+  state: &mut OpState,
+  client_rid: Option<ResourceId>,
+  url: &Url,
+  proxy: Proxy,
+) -> Result<Client, AnyError> {
+  if let Some(rid) = client_rid {
+    let r = state.resource_table.get::<HttpClientResource>(rid)?;
+    if r.unix_socket_path.is_some() {
+      return Err(type_error(
+        "Requests of a client with a Unix socket transport can't use a proxy",
+      ));
+    }
+    let options = state.borrow::<Options>();
+    let mut clients = r.clients(options)?;
+    let key = (proxy, r.host_client_index(url));
+    if let Some(client) = clients.proxy_clients.get(&key) {
+      return Ok(client.clone());
+    }
+    let config = match key.1 {
+      Some(index) => &r.host_configs[index].config,
+      None => &r.config,
+    };
+    let client = config.create_client(options, Some(key.0.clone()))?;
+    insert_proxy_client(&mut clients.proxy_clients, key, client.clone());
+    return Ok(client);
+  }
+
+  if let Some(client) = state
+    .try_borrow::<DefaultProxyClients>()
+    .and_then(|clients| clients.0.get(&proxy))
+  {
+    return Ok(client.clone());
+  }
+  let options = state.borrow::<Options>();
+  let config = HttpClientConfig {
+    client_cert_chain_and_key: options.client_cert_chain_and_key.clone(),
+    ..Default::default()
+  };
+  let client = config.create_client(options, Some(proxy.clone()))?;
+  if !state.has::<DefaultProxyClients>() {
+    state.put(DefaultProxyClients::default());
+  }
+  insert_proxy_client(
+    &mut state.borrow_mut::<DefaultProxyClients>().0,
+    proxy,
+    client.clone(),
+  );
+  Ok(client)
+}
+
 #[op]
 pub fn op_fetch<FP>(
   state: &mut OpState,
@@ -218,6 +357,10 @@ pub fn op_fetch<FP>(
   url: String,
   headers: Vec<(ByteString, ByteString)>,
   client_rid: Option<u32>,
+  proxy: Option<Proxy>,
+  cache_mode: CacheMode,
+  timeout: Option<u64>,
+  retry: Option<RetryPolicy>,
   has_body: bool,
   body_length: Option<u64>,
   data: Option<ZeroCopyBuf>,
@@ -225,16 +368,50 @@ pub fn op_fetch<FP>(
 where
   FP: FetchPermissions + 'static,
 {
-  let client = if let Some(rid) = client_rid {
+  let method = Method::from_bytes(&method)?;
+  let url = Url::parse(&url)?;
+
+  let mut unix_socket_client = None;
+  let mut http_cache = None;
+  let mut cookie_jar = None;
+  let mut alt_svc_client = None;
+  let mut http3_client = None;
+  let mut timeout = timeout.map(Duration::from_millis);
+  let mut retry = retry;
+  if let Some(rid) = client_rid {
     let r = state.resource_table.get::<HttpClientResource>(rid)?;
-    r.client.clone()
+    http_cache = r.http_cache.clone();
+    cookie_jar = r.cookie_jar.clone();
+    alt_svc_client = r.http3_client.clone();
+    timeout = timeout.or(r.timeout);
+    retry = retry.or(r.retry);
+  }
+  let client = if let Some(proxy) = proxy {
+    let proxy_url = Url::parse(&proxy.url)?;
+    state
+      .borrow_mut::<FP>()
+      .check_net_url(&proxy_url, "fetch()")?;
+    get_or_create_proxy_client(state, client_rid, &url, proxy)?
+  } else if let Some(rid) = client_rid {
+    let r = state.resource_table.get::<HttpClientResource>(rid)?;
+    let clients = r.clients(state.borrow::<Options>())?;
+    unix_socket_client = clients.unix_socket_client.clone();
+    match r.host_client_index(&url) {
+      Some(index) => clients.host_clients[index].clone(),
+      None => {
+        // The HTTP/3 client doesn't have the client certificates that are
+        // only used for some hosts.
+        http3_client = r
+          .http3_client
+          .clone()
+          .filter(|http3_client| http3_client.is_used_for(&url));
+        clients.client.clone()
+      }
+    }
   } else {
     get_or_create_client_from_state(state)?
   };
 
-  let method = Method::from_bytes(&method)?;
-  let url = Url::parse(&url)?;
-
   // Check scheme before asking for net permission
   let scheme = url.scheme();
   let (request_rid, request_body_rid, cancel_handle_rid) = match scheme {
@@ -266,8 +443,12 @@ where
       (request_rid, maybe_request_body_rid, maybe_cancel_handle_rid)
     }
     "http" | "https" => {
-      let permissions = state.borrow_mut::<FP>();
-      permissions.check_net_url(&url, "fetch()")?;
+      // Requests of clients with a Unix socket transport don't go to the
+      // network, the path of the socket has been checked instead.
+      if unix_socket_client.is_none() {
+        let permissions = state.borrow_mut::<FP>();
+        permissions.check_net_url(&url, "fetch()")?;
+      }
 
       // Make sure that we have a valid URI early, as reqwest's `RequestBuilder::send`
       // internally uses `expect_uri`, which panics instead of returning a usable `Result`.
@@ -276,6 +457,7 @@ where
       }
 
       let mut request = client.request(method.clone(), url);
+      let mut streamed_body = None;
 
       let request_body_rid = if has_body {
         match data {
@@ -290,7 +472,11 @@ where
                 request.header(CONTENT_LENGTH, HeaderValue::from(body_size))
             }
 
-            request = request.body(Body::wrap_stream(stream));
+            if unix_socket_client.is_some() || http3_client.is_some() {
+              streamed_body = Some(hyper::Body::wrap_stream(stream));
+            } else {
+              request = request.body(Body::wrap_stream(stream));
+            }
 
             let request_body_rid =
               state.resource_table.add(FetchRequestBodyResource {
@@ -344,17 +530,102 @@ where
       let cancel_handle = CancelHandle::new_rc();
       let cancel_handle_ = cancel_handle.clone();
 
-      let fut = async move {
-        request
-          .send()
+      let mut request =
+        request.build().map_err(|err| type_error(err.to_string()))?;
+      if let Some(cookie_jar) = &cookie_jar {
+        let url = request.url().clone();
+        cookie_jar
+          .borrow_mut()
+          .add_cookie_header(&url, request.headers_mut());
+      }
+      // A streamed body can't be sent again.
+      if request_body_rid.is_some() {
+        retry = None;
+      }
+      let send = move |request: reqwest::Request| -> SendFuture {
+        let url = request.url().clone();
+        let mut streamed_body = streamed_body;
+        let response: SendFuture = Box::pin(send_with_retries(
+          request,
+          retry,
+          timeout,
+          move |request| -> SendFuture {
+            match &unix_socket_client {
+              Some(unix_socket_client) => {
+                let unix_socket_client = unix_socket_client.clone();
+                let streamed_body = streamed_body.take();
+                Box::pin(async move {
+                  unix_socket_client.send(request, streamed_body).await
+                })
+              }
+              None => {
+                let client = client.clone();
+                let Some(http3_client) = http3_client.clone() else {
+                  return Box::pin(async move {
+                    Ok(client.execute(request).await?)
+                  });
+                };
+                let streamed_body = streamed_body.take();
+                Box::pin(async move {
+                  match http3_client.connect(request.url()).await {
+                    Ok(sender) => {
+                      http3_client.send(sender, request, streamed_body).await
+                    }
+                    // Fall back to HTTP/2 or HTTP/1.1.
+                    Err(_) => {
+                      let mut request = request;
+                      if let Some(body) = streamed_body {
+                        *request.body_mut() = Some(Body::wrap_stream(body));
+                      }
+                      Ok(client.execute(request).await?)
+                    }
+                  }
+                })
+              }
+            }
+          },
+        ));
+        if cookie_jar.is_none() && alt_svc_client.is_none() {
+          return response;
+        }
+        Box::pin(async move {
+          let response = response.await?;
+          if let Some(cookie_jar) = cookie_jar {
+            cookie_jar
+              .borrow_mut()
+              .store_response_cookies(&url, response.headers());
+          }
+          if let Some(alt_svc_client) = alt_svc_client {
+            alt_svc_client.store_alt_svc(&url, response.headers());
+          }
+          Ok(response)
+        })
+      };
+      let response: SendFuture = match http_cache {
+        Some(http_cache) if method == Method::GET => {
+          Box::pin(http_cache.fetch(request, cache_mode, send))
+        }
+        Some(http_cache) if !is_safe_method(&method) => {
+          let url = request.url().clone();
+          let response = send(request);
+          Box::pin(async move {
+            let response = response.await?;
+            if !response.status().is_server_error() {
+              http_cache.invalidate(&url).await;
+            }
+            Ok(response)
+          })
+        }
+        _ => send(request),
+      };
+      let fut: CancelableResponseFuture = Box::pin(async move {
+        response
           .or_cancel(cancel_handle_)
           .await
           .map(|res| res.map_err(|err| type_error(err.to_string())))
-      };
+      });
 
-      let request_rid = state
-        .resource_table
-        .add(FetchRequestResource(Box::pin(fut)));
+      let request_rid = state.resource_table.add(FetchRequestResource(fut));
 
       let cancel_handle_rid =
         state.resource_table.add(FetchCancelHandle(cancel_handle));
@@ -397,6 +668,14 @@ where
   })
 }
 
+/// https://httpwg.org/specs/rfc9110.html#safe.methods
+fn is_safe_method(method: &Method) -> bool {
+  matches!(
+    *method,
+    Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+  )
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FetchResponse {
@@ -591,7 +870,29 @@ impl Resource for FetchResponseBodyResource {
 }
 
 struct HttpClientResource {
-  client: Client,
+  config: HttpClientConfig,
+  proxy: Option<Proxy>,
+  /// The configs with another client certificate, for the requests to the
+  /// hosts it was given for.
+  host_configs: Vec<HostClientConfig>,
+  /// Set for clients with a Unix socket transport.
+  unix_socket_path: Option<PathBuf>,
+  /// The time after which the clients are created again, so that their
+  /// connections aren't reused anymore.
+  pool_max_lifetime: Option<Duration>,
+  /// The clients, which are created when they are first used. Dropping them
+  /// closes the idle connections of their pools.
+  clients: RefCell<Option<HttpClients>>,
+  /// Set for clients created with `httpCache: true`.
+  http_cache: Option<Arc<HttpCache>>,
+  /// Set for clients created with a `cookieJar`.
+  cookie_jar: Option<Rc<RefCell<CookieJar>>>,
+  /// The timeout of the requests that don't have their own.
+  timeout: Option<Duration>,
+  /// The retries of the requests that don't have their own.
+  retry: Option<RetryPolicy>,
+  /// Set for clients created with `http3: true`.
+  http3_client: Option<Arc<Http3Client>>,
 }
 
 impl Resource for HttpClientResource {
@@ -601,8 +902,170 @@ impl Resource for HttpClientResource {
 }
 
 impl HttpClientResource {
-  fn new(client: Client) -> Self {
-    Self { client }
+  /// Returns the clients, creating them if they haven't been used since they
+  /// were last closed or if they have outlived `pool_max_lifetime`.
+  fn clients(
+    &self,
+    options: &Options,
+  ) -> Result<RefMut<HttpClients>, AnyError> {
+    let mut clients = self.clients.borrow_mut();
+    if let (Some(existing), Some(max_lifetime)) =
+      (&*clients, self.pool_max_lifetime)
+    {
+      if existing.created.elapsed() >= max_lifetime {
+        *clients = None;
+      }
+    }
+    if clients.is_none() {
+      *clients = Some(HttpClients {
+        client: self.config.create_client(options, self.proxy.clone())?,
+        host_clients: self
+          .host_configs
+          .iter()
+          .map(|host_config| {
+            host_config
+              .config
+              .create_client(options, self.proxy.clone())
+          })
+          .collect::<Result<_, _>>()?,
+        proxy_clients: Default::default(),
+        unix_socket_client: match &self.unix_socket_path {
+          Some(path) => Some(create_unix_socket_client(path, &self.config)?),
+          None => None,
+        },
+        created: Instant::now(),
+      });
+    }
+    Ok(RefMut::map(clients, |clients| clients.as_mut().unwrap()))
+  }
+
+  /// Closes the idle connections of the pools of the clients. The connections
+  /// of the requests in progress are closed once they are done.
+  fn close_idle_connections(&self) {
+    self.clients.borrow_mut().take();
+    if let Some(http3_client) = &self.http3_client {
+      http3_client.close_connections();
+    }
+  }
+
+  /// Returns the index of the first host client that is used for the host of
+  /// a URL.
+  fn host_client_index(&self, url: &Url) -> Option<usize> {
+    let host = url.host_str()?;
+    self.host_configs.iter().position(|host_config| {
+      host_config
+        .hosts
+        .iter()
+        .any(|pattern| host_matches(pattern, host))
+    })
+  }
+}
+
+struct HostClientConfig {
+  hosts: Vec<String>,
+  config: HttpClientConfig,
+}
+
+/// The clients of a `Deno.HttpClient`, which each have their own pool.
+struct HttpClients {
+  client: Client,
+  /// The clients of the host configs, by index.
+  host_clients: Vec<Client>,
+  /// Clients for the requests through a proxy other than the one of
+  /// `client`, by the index of the host config they are derived from. At
+  /// most `MAX_PROXY_CLIENTS` of them are kept.
+  proxy_clients: HashMap<(Proxy, Option<usize>), Client>,
+  /// Set for clients with a Unix socket transport, which send their requests
+  /// with it instead of `client`.
+  unix_socket_client: Option<UnixSocketClient>,
+  created: Instant,
+}
+
+/// Whether a host matches a host of a client certificate, either exactly or
+/// for a pattern like `*.example.com` as a subdomain.
+fn host_matches(pattern: &str, host: &str) -> bool {
+  let host = host.trim_end_matches('.').to_ascii_lowercase();
+  let pattern = pattern.trim_end_matches('.').to_ascii_lowercase();
+  match pattern.strip_prefix("*.") {
+    Some(domain) => host
+      .strip_suffix(domain)
+      .and_then(|subdomain| subdomain.strip_suffix('.'))
+      .map_or(false, |subdomain| !subdomain.is_empty()),
+    None => host == pattern,
+  }
+}
+
+/// The options a client was created with, besides its proxy.
+#[derive(Clone, Default)]
+struct HttpClientConfig {
+  ca_certs: Vec<Vec<u8>>,
+  client_cert_chain_and_key: Option<(String, String)>,
+  resolver: Option<Arc<HttpClientResolver>>,
+  pool_max_idle_per_host: Option<usize>,
+  /// `Some(None)` disables the idle timeout of the connections of the pool.
+  pool_idle_timeout: Option<Option<Duration>>,
+  http1_only: bool,
+  http2_only: bool,
+  connect_timeout: Option<Duration>,
+}
+
+impl HttpClientConfig {
+  fn create_client(
+    &self,
+    options: &Options,
+    proxy: Option<Proxy>,
+  ) -> Result<Client, AnyError> {
+    let alpn_protocols = if self.http1_only {
+      vec!["http/1.1".into()]
+    } else if self.http2_only {
+      vec!["h2".into()]
+    } else {
+      vec!["h2".into(), "http/1.1".into()]
+    };
+    let mut builder = http_client_builder(
+      &options.user_agent,
+      options.root_cert_store()?,
+      self.ca_certs.clone(),
+      proxy,
+      options.unsafely_ignore_certificate_errors.clone(),
+      self.client_cert_chain_and_key.clone(),
+      alpn_protocols,
+    )?;
+    if self.resolver.is_some() || options.resolved_addrs_check.is_some() {
+      builder = builder.dns_resolver(Arc::new(ResolverAdapter {
+        resolver: self.resolver.clone().unwrap_or_default(),
+        check: options.resolved_addrs_check.clone(),
+      }));
+    }
+    if let Some(max) = self.pool_max_idle_per_host {
+      builder = builder.pool_max_idle_per_host(max);
+    }
+    if let Some(timeout) = self.pool_idle_timeout {
+      builder = builder.pool_idle_timeout(timeout);
+    }
+    if let Some(timeout) = self.connect_timeout {
+      builder = builder.connect_timeout(timeout);
+    }
+    if self.http1_only {
+      builder = builder.http1_only();
+    } else if self.http2_only {
+      builder = builder.http2_prior_knowledge();
+    }
+    // unwrap here because it can only fail when native TLS is used.
+    Ok(builder.build().unwrap())
+  }
+
+  /// Returns the builder of the `hyper` clients of Unix socket transports.
+  fn hyper_client_builder(&self) -> hyper::client::Builder {
+    let mut builder = hyper::Client::builder();
+    if let Some(max) = self.pool_max_idle_per_host {
+      builder.pool_max_idle_per_host(max);
+    }
+    if let Some(timeout) = self.pool_idle_timeout {
+      builder.pool_idle_timeout(timeout);
+    }
+    builder.http2_only(self.http2_only);
+    builder
   }
 }
 
@@ -613,6 +1076,73 @@ pub struct CreateHttpClientOptions {
   proxy: Option<Proxy>,
   cert_chain: Option<String>,
   private_key: Option<String>,
+  pkcs12: Option<Pkcs12Bundle>,
+  #[serde(default)]
+  client_certificates: Vec<HostClientCertificate>,
+  resolver: Option<ResourceId>,
+  /// Addresses that are used for these hosts without resolving them.
+  #[serde(default)]
+  hosts: HashMap<String, Vec<IpAddr>>,
+  /// The time, in seconds, that the addresses of hosts are cached for.
+  dns_cache_ttl: Option<u64>,
+  /// Whether the responses of the client are cached.
+  #[serde(default)]
+  http_cache: bool,
+  /// The initial cookies of the cookie jar of the client, if it has one.
+  cookie_jar: Option<Vec<Cookie>>,
+  transport: Option<HttpClientTransport>,
+  pool_max_idle_per_host: Option<usize>,
+  /// In milliseconds, or `false` to keep idle connections open.
+  pool_idle_timeout: Option<PoolIdleTimeout>,
+  /// In milliseconds.
+  pool_max_lifetime: Option<u64>,
+  http1: Option<bool>,
+  http2: Option<bool>,
+  /// The time, in milliseconds, that the requests of the client may take,
+  /// including the retries and reading the response body.
+  timeout: Option<u64>,
+  /// In milliseconds.
+  connect_timeout: Option<u64>,
+  retry: Option<RetryPolicy>,
+  /// Whether requests are sent over HTTP/3 to the origins that advertise it.
+  #[serde(default)]
+  http3: bool,
+  /// Whether requests are sent over HTTP/3 without waiting for an origin to
+  /// advertise it.
+  #[serde(default)]
+  http3_prior_knowledge: bool,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum PoolIdleTimeout {
+  Enabled(bool),
+  Millis(u64),
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Pkcs12Bundle {
+  /// The DER encoded bundle.
+  data: ZeroCopyBuf,
+  password: Option<String>,
+}
+
+/// A client certificate that is only used for some hosts.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct HostClientCertificate {
+  hosts: Vec<String>,
+  cert_chain: Option<String>,
+  private_key: Option<String>,
+  pkcs12: Option<Pkcs12Bundle>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct HttpClientTransport {
+  /// The path of a Unix domain socket to send all requests to.
+  path: String,
 }
 
 #[op]
@@ -629,41 +1159,236 @@ where
     permissions.check_net_url(&url, "Deno.createHttpClient()")?;
   }
 
-  let client_cert_chain_and_key = {
-    if args.cert_chain.is_some() || args.private_key.is_some() {
-      let cert_chain = args
-        .cert_chain
-        .ok_or_else(|| type_error("No certificate chain provided"))?;
-      let private_key = args
-        .private_key
-        .ok_or_else(|| type_error("No private key provided"))?;
+  let unix_socket_path = match args.transport {
+    Some(transport) => {
+      if args.proxy.is_some() {
+        return Err(type_error(
+          "A client with a Unix socket transport can't have a proxy",
+        ));
+      }
+      let permissions = state.borrow_mut::<FP>();
+      let path = PathBuf::from(transport.path);
+      permissions.check_read(&path, "Deno.createHttpClient()")?;
+      permissions.check_write(&path, "Deno.createHttpClient()")?;
+      Some(path)
+    }
+    None => None,
+  };
 
-      Some((cert_chain, private_key))
-    } else {
-      None
+  let pool_idle_timeout = match args.pool_idle_timeout {
+    Some(PoolIdleTimeout::Enabled(false)) => Some(None),
+    Some(PoolIdleTimeout::Enabled(true)) => {
+      return Err(type_error("`poolIdleTimeout` must be a number or false"));
     }
+    Some(PoolIdleTimeout::Millis(millis)) => {
+      Some(Some(Duration::from_millis(millis)))
+    }
+    None => None,
   };
+  let (http1_only, http2_only) =
+    match (args.http1.unwrap_or(true), args.http2.unwrap_or(true)) {
+      (true, true) => (false, false),
+      (true, false) => (true, false),
+      (false, true) => (false, true),
+      (false, false) => {
+        return Err(type_error("Either `http1` or `http2` needs to be true"));
+      }
+    };
 
-  let options = state.borrow::<Options>();
-  let ca_certs = args
-    .ca_certs
-    .into_iter()
-    .map(|cert| cert.into_bytes())
-    .collect::<Vec<_>>();
-
-  let client = create_http_client(
-    &options.user_agent,
-    options.root_cert_store()?,
-    ca_certs,
-    args.proxy,
-    options.unsafely_ignore_certificate_errors.clone(),
+  let client_cert_chain_and_key =
+    load_client_certificate(args.cert_chain, args.private_key, args.pkcs12)?;
+
+  let dns_resolver = match args.resolver {
+    Some(rid) => Some(
+      state
+        .resource_table
+        .get::<DnsResolverResource>(rid)?
+        .0
+        .clone(),
+    ),
+    None => None,
+  };
+  // Without any of the options, reqwest resolves hosts with the system
+  // resolver itself.
+  let resolver = if dns_resolver.is_some()
+    || !args.hosts.is_empty()
+    || args.dns_cache_ttl.is_some()
+  {
+    Some(Arc::new(HttpClientResolver::new(
+      args.hosts,
+      args.dns_cache_ttl.map(Duration::from_secs),
+      dns_resolver,
+    )))
+  } else {
+    None
+  };
+
+  let config = HttpClientConfig {
+    ca_certs: args
+      .ca_certs
+      .into_iter()
+      .map(|cert| cert.into_bytes())
+      .collect(),
     client_cert_chain_and_key,
-  )?;
+    resolver,
+    pool_max_idle_per_host: args.pool_max_idle_per_host,
+    pool_idle_timeout,
+    http1_only,
+    http2_only,
+    connect_timeout: args.connect_timeout.map(Duration::from_millis),
+  };
+  let options = state.borrow::<Options>();
+
+  let mut host_configs = Vec::with_capacity(args.client_certificates.len());
+  for certificate in args.client_certificates {
+    if certificate.hosts.is_empty() {
+      return Err(type_error("No hosts provided for client certificate"));
+    }
+    let Some(client_cert_chain_and_key) = load_client_certificate(
+      certificate.cert_chain,
+      certificate.private_key,
+      certificate.pkcs12,
+    )? else {
+      return Err(type_error("No client certificate provided"));
+    };
+    host_configs.push(HostClientConfig {
+      hosts: certificate.hosts,
+      config: HttpClientConfig {
+        client_cert_chain_and_key: Some(client_cert_chain_and_key),
+        ..config.clone()
+      },
+    });
+  }
+
+  let http3_client = if args.http3 {
+    if args.proxy.is_some() || unix_socket_path.is_some() {
+      return Err(type_error(
+        "A client with a proxy or a Unix socket transport can't use HTTP/3",
+      ));
+    }
+    let tls_config = deno_tls::create_client_config(
+      options.root_cert_store()?,
+      config.ca_certs.clone(),
+      options.unsafely_ignore_certificate_errors.clone(),
+      config.client_cert_chain_and_key.clone(),
+    )?;
+    Some(Arc::new(Http3Client::new(
+      tls_config,
+      config.resolver.clone(),
+      &options.user_agent,
+      args.http3_prior_knowledge,
+      config.connect_timeout,
+    )?))
+  } else if args.http3_prior_knowledge {
+    return Err(type_error("`http3PriorKnowledge` requires `http3: true`"));
+  } else {
+    None
+  };
 
-  let rid = state.resource_table.add(HttpClientResource::new(client));
+  let http_cache = if args.http_cache {
+    let Some(dir) = options.http_cache_dir.clone() else {
+      return Err(type_error("The HTTP cache is not available"));
+    };
+    Some(Arc::new(HttpCache::new(dir)))
+  } else {
+    None
+  };
+
+  let resource = HttpClientResource {
+    config,
+    proxy: args.proxy,
+    host_configs,
+    unix_socket_path,
+    pool_max_lifetime: args.pool_max_lifetime.map(Duration::from_millis),
+    clients: Default::default(),
+    http_cache,
+    cookie_jar: args
+      .cookie_jar
+      .map(|cookies| Rc::new(RefCell::new(CookieJar::new(cookies)))),
+    timeout: args.timeout.map(Duration::from_millis),
+    retry: args.retry,
+    http3_client,
+  };
+  // Create the clients now so that invalid options are reported here.
+  drop(resource.clients(options)?);
+  let rid = state.resource_table.add(resource);
   Ok(rid)
 }
 
+/// Closes the idle connections of a client, see
+/// `HttpClientResource::close_idle_connections()`.
+#[op]
+pub fn op_fetch_close_idle_connections(
+  state: &mut OpState,
+  rid: ResourceId,
+) -> Result<(), AnyError> {
+  let r = state.resource_table.get::<HttpClientResource>(rid)?;
+  r.close_idle_connections();
+  Ok(())
+}
+
+/// Returns the cookies of the cookie jar of a client if they have changed
+/// since the last call, for its `onChange` hook.
+#[op]
+pub fn op_fetch_take_cookie_changes(
+  state: &mut OpState,
+  rid: ResourceId,
+) -> Result<Option<Vec<Cookie>>, AnyError> {
+  let r = state.resource_table.get::<HttpClientResource>(rid)?;
+  let Some(cookie_jar) = &r.cookie_jar else {
+    return Ok(None);
+  };
+  let changes = cookie_jar.borrow_mut().take_changes();
+  Ok(changes)
+}
+
+/// Returns the PEM encoded certificate chain and private key of a client
+/// certificate, which is either given in PEM format or as a PKCS#12 bundle.
+fn load_client_certificate(
+  cert_chain: Option<String>,
+  private_key: Option<String>,
+  pkcs12: Option<Pkcs12Bundle>,
+) -> Result<Option<(String, String)>, AnyError> {
+  if let Some(pkcs12) = pkcs12 {
+    if cert_chain.is_some() || private_key.is_some() {
+      return Err(type_error(
+        "A PKCS#12 bundle can't be combined with a certificate chain or private key",
+      ));
+    }
+    let password = pkcs12.password.as_deref().unwrap_or_default();
+    return Ok(Some(deno_tls::pkcs12::load_pkcs12(&pkcs12.data, password)?));
+  }
+  if cert_chain.is_none() && private_key.is_none() {
+    return Ok(None);
+  }
+  let cert_chain =
+    cert_chain.ok_or_else(|| type_error("No certificate chain provided"))?;
+  let private_key =
+    private_key.ok_or_else(|| type_error("No private key provided"))?;
+  Ok(Some((cert_chain, private_key)))
+}
+
+#[cfg(unix)]
+fn create_unix_socket_client(
+  path: &Path,
+  config: &HttpClientConfig,
+) -> Result<UnixSocketClient, AnyError> {
+  Ok(UnixSocketClient::new(
+    path.to_path_buf(),
+    &config.hyper_client_builder(),
+  ))
+}
+
+#[cfg(not(unix))]
+fn create_unix_socket_client(
+  _path: &Path,
+  _config: &HttpClientConfig,
+) -> Result<UnixSocketClient, AnyError> {
+  Err(type_error(
+    "Unix socket transports are not supported on this platform",
+  ))
+}
+
 /// Create new instance of async reqwest::Client. This client supports
 /// proxies and doesn't follow redirects.
 pub fn create_http_client(
@@ -674,6 +1399,28 @@ pub fn create_http_client(
   unsafely_ignore_certificate_errors: Option<Vec<String>>,
   client_cert_chain_and_key: Option<(String, String)>,
 ) -> Result<Client, AnyError> {
+  let builder = http_client_builder(
+    user_agent,
+    root_cert_store,
+    ca_certs,
+    proxy,
+    unsafely_ignore_certificate_errors,
+    client_cert_chain_and_key,
+    vec!["h2".into(), "http/1.1".into()],
+  )?;
+  // unwrap here because it can only fail when native TLS is used.
+  Ok(builder.build().unwrap())
+}
+
+fn http_client_builder(
+  user_agent: &str,
+  root_cert_store: Option<RootCertStore>,
+  ca_certs: Vec<Vec<u8>>,
+  proxy: Option<Proxy>,
+  unsafely_ignore_certificate_errors: Option<Vec<String>>,
+  client_cert_chain_and_key: Option<(String, String)>,
+  alpn_protocols: Vec<Vec<u8>>,
+) -> Result<reqwest::ClientBuilder, AnyError> {
   let mut tls_config = deno_tls::create_client_config(
     root_cert_store,
     ca_certs,
@@ -681,7 +1428,7 @@ pub fn create_http_client(
     client_cert_chain_and_key,
   )?;
 
-  tls_config.alpn_protocols = vec!["h2".into(), "http/1.1".into()];
+  tls_config.alpn_protocols = alpn_protocols;
 
   let mut headers = HeaderMap::new();
   headers.insert(USER_AGENT, user_agent.parse().unwrap());
@@ -691,14 +1438,32 @@ pub fn create_http_client(
     .use_preconfigured_tls(tls_config);
 
   if let Some(proxy) = proxy {
-    let mut reqwest_proxy = reqwest::Proxy::all(&proxy.url)?;
-    if let Some(basic_auth) = &proxy.basic_auth {
+    let proxy_url = parse_proxy_url(&proxy.url)?;
+    let basic_auth = proxy.basic_auth.clone();
+    // Hosts are excluded with the same `noProxy` matching as WebSocket
+    // connections
+    let mut reqwest_proxy =
+      reqwest::Proxy::custom(move |url| match url.host_str() {
+        Some(host) if proxy.is_excluded(host) => None,
+        _ => Some(proxy_url.clone()),
+      });
+    if let Some(basic_auth) = &basic_auth {
       reqwest_proxy =
         reqwest_proxy.basic_auth(&basic_auth.username, &basic_auth.password);
     }
     builder = builder.proxy(reqwest_proxy);
   }
 
-  // unwrap here because it can only fail when native TLS is used.
-  Ok(builder.build().unwrap())
+  Ok(builder)
+}
+
+/// Parses the url of a proxy, which is assumed to be an HTTP proxy if it has
+/// no scheme.
+fn parse_proxy_url(url: &str) -> Result<Url, AnyError> {
+  match Url::parse(url) {
+    Err(deno_core::url::ParseError::RelativeUrlWithoutBase) => {
+      Ok(Url::parse(&format!("http://{url}"))?)
+    }
+    result => Ok(result?),
+  }
 }
@@ -0,0 +1,169 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The timeouts and retries of requests, for the `timeout` and `retry`
+//! options of `Deno.createHttpClient()` and `fetch()`. Only idempotent
+//! requests whose body doesn't have to be streamed are retried, and only when
+//! they fail to connect, so the server can't have seen them. The retries go
+//! through the same client, and so reuse the connections of its pool.
+
+use deno_core::error::AnyError;
+use reqwest::Method;
+use reqwest::Response;
+use serde::Deserialize;
+use std::cmp::min;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// The delay before the first retry, in milliseconds.
+const DEFAULT_INITIAL_DELAY: u64 = 100;
+/// The longest delay between two retries, in milliseconds.
+const DEFAULT_MAX_DELAY: u64 = 10_000;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryPolicy {
+  max_retries: u32,
+  /// In milliseconds, doubled after every retry.
+  #[serde(default = "default_initial_delay")]
+  initial_delay: u64,
+  /// In milliseconds.
+  #[serde(default = "default_max_delay")]
+  max_delay: u64,
+}
+
+fn default_initial_delay() -> u64 {
+  DEFAULT_INITIAL_DELAY
+}
+
+fn default_max_delay() -> u64 {
+  DEFAULT_MAX_DELAY
+}
+
+impl RetryPolicy {
+  /// Returns the delay before a retry, counted from 0.
+  fn delay(&self, retry: u32) -> Duration {
+    let delay = self
+      .initial_delay
+      .saturating_mul(1 << min(retry, u64::BITS - 1));
+    Duration::from_millis(min(delay, self.max_delay))
+  }
+}
+
+/// Whether requests with a method can be retried, see
+/// https://www.rfc-editor.org/rfc/rfc9110#section-9.2.2.
+fn is_idempotent_method(method: &Method) -> bool {
+  matches!(
+    *method,
+    Method::GET
+      | Method::HEAD
+      | Method::OPTIONS
+      | Method::TRACE
+      | Method::PUT
+      | Method::DELETE
+  )
+}
+
+fn is_connect_error(err: &AnyError) -> bool {
+  if let Some(err) = err.downcast_ref::<reqwest::Error>() {
+    return err.is_connect();
+  }
+  err
+    .downcast_ref::<hyper::Error>()
+    .map_or(false, |err| err.is_connect())
+}
+
+/// Sends a request with `send`, retrying it according to `retry`. The
+/// `timeout` is the time the request may take overall, including the
+/// retries and the delays between them.
+pub async fn send_with_retries<F, Fut>(
+  mut request: reqwest::Request,
+  retry: Option<RetryPolicy>,
+  timeout: Option<Duration>,
+  mut send: F,
+) -> Result<Response, AnyError>
+where
+  F: FnMut(reqwest::Request) -> Fut,
+  Fut: Future<Output = Result<Response, AnyError>>,
+{
+  let deadline = timeout.map(|timeout| Instant::now() + timeout);
+  let retry = retry.filter(|_| is_idempotent_method(request.method()));
+  let mut retries = 0;
+  loop {
+    if let Some(deadline) = deadline {
+      *request.timeout_mut() =
+        Some(deadline.saturating_duration_since(Instant::now()));
+    }
+    let Some(retry) = retry.filter(|retry| retries < retry.max_retries) else {
+      return send(request).await;
+    };
+    let Some(attempt) = request.try_clone() else {
+      return send(request).await;
+    };
+    match send(attempt).await {
+      Err(err) if is_connect_error(&err) => {
+        let delay = retry.delay(retries);
+        if let Some(deadline) = deadline {
+          if Instant::now() + delay >= deadline {
+            return Err(err);
+          }
+        }
+        tokio::time::sleep(delay).await;
+        retries += 1;
+      }
+      result => return result,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::cell::Cell;
+
+  #[test]
+  fn test_delay() {
+    let retry = RetryPolicy {
+      max_retries: 10,
+      initial_delay: 100,
+      max_delay: 1000,
+    };
+    assert_eq!(retry.delay(0), Duration::from_millis(100));
+    assert_eq!(retry.delay(1), Duration::from_millis(200));
+    assert_eq!(retry.delay(3), Duration::from_millis(800));
+    assert_eq!(retry.delay(4), Duration::from_millis(1000));
+    assert_eq!(retry.delay(u32::MAX), Duration::from_millis(1000));
+  }
+
+  #[tokio::test]
+  async fn test_send_with_retries() {
+    // Nothing listens on the port, so every attempt fails to connect.
+    let client = reqwest::Client::new();
+    let retry = RetryPolicy {
+      max_retries: 2,
+      initial_delay: 1,
+      max_delay: 1,
+    };
+    let attempts = Cell::new(0);
+    let send = |request| {
+      attempts.set(attempts.get() + 1);
+      let client = client.clone();
+      async move { Ok(client.execute(request).await?) }
+    };
+
+    let request = client.get("http://127.0.0.1:9").build().unwrap();
+    let err = send_with_retries(request, Some(retry), None, send)
+      .await
+      .unwrap_err();
+    assert!(is_connect_error(&err));
+    assert_eq!(attempts.get(), 3);
+
+    // POST requests aren't idempotent.
+    attempts.set(0);
+    let request = client.post("http://127.0.0.1:9").build().unwrap();
+    send_with_retries(request, Some(retry), None, send)
+      .await
+      .unwrap_err();
+    assert_eq!(attempts.get(), 1);
+  }
+}
@@ -0,0 +1,185 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Sends the requests of clients created with a `transport.path` over a Unix
+//! domain socket, like the ones of the Docker daemon, instead of connecting
+//! to the host of the URL.
+
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::futures::Future;
+use hyper::client::connect::Connected;
+use hyper::client::connect::Connection;
+use hyper::service::Service;
+use hyper::Uri;
+use reqwest::ResponseBuilderExt;
+use std::io;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
+use tokio::net::UnixStream;
+
+/// Connects to the socket at a path, whatever the URI of the request is.
+#[derive(Clone)]
+struct UnixConnector(Arc<PathBuf>);
+
+impl Service<Uri> for UnixConnector {
+  type Response = UnixConnection;
+  type Error = io::Error;
+  type Future =
+    Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+  fn poll_ready(
+    &mut self,
+    _cx: &mut Context<'_>,
+  ) -> Poll<Result<(), io::Error>> {
+    Poll::Ready(Ok(()))
+  }
+
+  fn call(&mut self, _uri: Uri) -> Self::Future {
+    let path = self.0.clone();
+    Box::pin(
+      async move { Ok(UnixConnection(UnixStream::connect(&*path).await?)) },
+    )
+  }
+}
+
+struct UnixConnection(UnixStream);
+
+impl Connection for UnixConnection {
+  fn connected(&self) -> Connected {
+    Connected::new()
+  }
+}
+
+impl AsyncRead for UnixConnection {
+  fn poll_read(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &mut ReadBuf<'_>,
+  ) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.0).poll_read(cx, buf)
+  }
+}
+
+impl AsyncWrite for UnixConnection {
+  fn poll_write(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &[u8],
+  ) -> Poll<io::Result<usize>> {
+    Pin::new(&mut self.0).poll_write(cx, buf)
+  }
+
+  fn poll_flush(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.0).poll_flush(cx)
+  }
+
+  fn poll_shutdown(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.0).poll_shutdown(cx)
+  }
+}
+
+#[derive(Clone)]
+pub struct UnixSocketClient(hyper::Client<UnixConnector>);
+
+impl UnixSocketClient {
+  /// Creates a client whose connection pool is configured with `builder`.
+  pub fn new(path: PathBuf, builder: &hyper::client::Builder) -> Self {
+    Self(builder.build(UnixConnector(Arc::new(path))))
+  }
+
+  /// Sends a request that was built with `reqwest`. A streamed body is passed
+  /// separately, since it can't be taken out of the `reqwest` request again.
+  /// The timeout of the request only applies until the response headers are
+  /// received.
+  pub async fn send(
+    &self,
+    request: reqwest::Request,
+    streamed_body: Option<hyper::Body>,
+  ) -> Result<reqwest::Response, AnyError> {
+    let url = request.url().clone();
+    let timeout = request.timeout().copied();
+    let (parts, body) = http::Request::try_from(request)?.into_parts();
+    let body = match streamed_body {
+      Some(body) => body,
+      None => body
+        .as_bytes()
+        .map(|bytes| hyper::Body::from(bytes.to_vec()))
+        .unwrap_or_else(hyper::Body::empty),
+    };
+    let response = self.0.request(http::Request::from_parts(parts, body));
+    let response = match timeout {
+      Some(timeout) => tokio::time::timeout(timeout, response)
+        .await
+        .map_err(|_| type_error("The request timed out"))??,
+      None => response.await?,
+    };
+
+    let (parts, body) = response.into_parts();
+    let mut builder = http::Response::builder()
+      .status(parts.status)
+      .version(parts.version)
+      .url(url);
+    if let Some(headers) = builder.headers_mut() {
+      *headers = parts.headers;
+    }
+    Ok(reqwest::Response::from(
+      builder.body(reqwest::Body::from(body))?,
+    ))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tokio::io::AsyncReadExt;
+  use tokio::io::AsyncWriteExt;
+  use tokio::net::UnixListener;
+
+  #[tokio::test]
+  async fn test_send() {
+    let path = std::env::temp_dir()
+      .join(format!("deno_fetch_test_{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+    let server = tokio::spawn(async move {
+      let (mut stream, _) = listener.accept().await.unwrap();
+      let mut buf = vec![0; 1024];
+      let n = stream.read(&mut buf).await.unwrap();
+      stream
+        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\n[]")
+        .await
+        .unwrap();
+      String::from_utf8_lossy(&buf[..n]).to_string()
+    });
+
+    let client = UnixSocketClient::new(path.clone(), &Default::default());
+    let request = reqwest::Client::new()
+      .get("http://localhost/v1.43/containers/json")
+      .build()
+      .unwrap();
+    let response = client.send(request, None).await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+      response.url().as_str(),
+      "http://localhost/v1.43/containers/json"
+    );
+    assert_eq!(response.text().await.unwrap(), "[]");
+
+    let request = server.await.unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert!(request.starts_with("GET /v1.43/containers/json HTTP/1.1\r\n"));
+    assert!(request.contains("host: localhost\r\n"));
+  }
+}
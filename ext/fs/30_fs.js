@@ -20,12 +20,15 @@ const {
 const primordials = globalThis.__bootstrap.primordials;
 const {
   ArrayPrototypeFilter,
+  ArrayPrototypeMap,
   Date,
   DatePrototype,
   DatePrototypeGetTime,
   Error,
   Function,
-  MathTrunc,
+  MathFloor,
+  MathRound,
+  Number,
   ObjectEntries,
   ObjectPrototypeIsPrototypeOf,
   ObjectValues,
@@ -76,21 +79,25 @@ async function chown(
 function copyFileSync(
   fromPath,
   toPath,
+  options = {},
 ) {
   ops.op_fs_copy_file_sync(
     pathFromURL(fromPath),
     pathFromURL(toPath),
+    options.reflink,
   );
 }
 
 async function copyFile(
   fromPath,
   toPath,
+  options = {},
 ) {
   await core.opAsync(
     "op_fs_copy_file_async",
     pathFromURL(fromPath),
     pathFromURL(toPath),
+    options.reflink,
   );
 }
 
@@ -174,6 +181,66 @@ function readDir(path) {
   };
 }
 
+function toDirEntryWithStats(entry) {
+  return {
+    name: entry.name,
+    isFile: entry.isFile,
+    isDirectory: entry.isDirectory,
+    isSymlink: entry.isSymlink,
+    stats: parseFileInfo(entry.stat),
+  };
+}
+
+function readDirWithStatsSync(path) {
+  return ArrayPrototypeMap(
+    ops.op_fs_read_dir_with_stats_sync(pathFromURL(path)),
+    toDirEntryWithStats,
+  )[SymbolIterator]();
+}
+
+function readDirWithStats(path) {
+  const array = core.opAsync(
+    "op_fs_read_dir_with_stats_async",
+    pathFromURL(path),
+  );
+  return {
+    async *[SymbolAsyncIterator]() {
+      const dir = await array;
+      for (let i = 0; i < dir.length; ++i) {
+        yield toDirEntryWithStats(dir[i]);
+      }
+    },
+  };
+}
+
+function glob(pattern, options = {}) {
+  const root = options.root === undefined
+    ? undefined
+    : pathFromURL(options.root);
+  return {
+    async *[SymbolAsyncIterator]() {
+      const rid = ops.op_fs_glob_open(root, pattern, {
+        exclude: options.exclude ?? [],
+        includeDirs: options.includeDirs ?? true,
+        caseInsensitive: options.caseInsensitive ?? false,
+      });
+      try {
+        while (true) {
+          const entries = await core.opAsync("op_fs_glob_next", rid);
+          if (entries === null) {
+            break;
+          }
+          for (let i = 0; i < entries.length; ++i) {
+            yield entries[i];
+          }
+        }
+      } finally {
+        core.tryClose(rid);
+      }
+    },
+  };
+}
+
 function readLinkSync(path) {
   return ops.op_fs_read_link_sync(pathFromURL(path));
 }
@@ -197,6 +264,7 @@ function removeSync(
   ops.op_fs_remove_sync(
     pathFromURL(path),
     !!options.recursive,
+    !!options.trash,
   );
 }
 
@@ -208,6 +276,7 @@ async function remove(
     "op_fs_remove_async",
     pathFromURL(path),
     !!options.recursive,
+    !!options.trash,
   );
 }
 
@@ -389,20 +458,36 @@ async function link(oldpath, newpath) {
   await op_fs_link_async(oldpath, newpath);
 }
 
+const NANOS_PER_SECOND = 1_000_000_000n;
+
+// Returns `[seconds, nanoseconds]` since the UNIX epoch, with the nanoseconds
+// always positive. Numbers are seconds, with a fractional part, and bigints
+// are nanoseconds.
 function toUnixTimeFromEpoch(value) {
-  if (ObjectPrototypeIsPrototypeOf(DatePrototype, value)) {
-    const time = DatePrototypeGetTime(value);
-    const seconds = MathTrunc(time / 1e3);
-    const nanoseconds = MathTrunc(time - (seconds * 1e3)) * 1e6;
+  if (typeof value === "bigint") {
+    let seconds = value / NANOS_PER_SECOND;
+    let nanoseconds = value % NANOS_PER_SECOND;
+    if (nanoseconds < 0n) {
+      seconds -= 1n;
+      nanoseconds += NANOS_PER_SECOND;
+    }
 
     return [
-      seconds,
-      nanoseconds,
+      Number(seconds),
+      Number(nanoseconds),
     ];
   }
 
-  const seconds = value;
-  const nanoseconds = 0;
+  if (ObjectPrototypeIsPrototypeOf(DatePrototype, value)) {
+    value = DatePrototypeGetTime(value) / 1e3;
+  }
+
+  let seconds = MathFloor(value);
+  let nanoseconds = MathRound((value - seconds) * 1e9);
+  if (nanoseconds >= 1e9) {
+    seconds += 1;
+    nanoseconds = 0;
+  }
 
   return [
     seconds,
@@ -410,23 +495,42 @@ function toUnixTimeFromEpoch(value) {
   ];
 }
 
+function toBirthtime(options) {
+  if (options?.birthtime === undefined) {
+    return [null, 0];
+  }
+  return toUnixTimeFromEpoch(options.birthtime);
+}
+
 function futimeSync(
   rid,
   atime,
   mtime,
+  options,
 ) {
   const { 0: atimeSec, 1: atimeNsec } = toUnixTimeFromEpoch(atime);
   const { 0: mtimeSec, 1: mtimeNsec } = toUnixTimeFromEpoch(mtime);
-  ops.op_fs_futime_sync(rid, atimeSec, atimeNsec, mtimeSec, mtimeNsec);
+  const { 0: btimeSec, 1: btimeNsec } = toBirthtime(options);
+  ops.op_fs_futime_sync(
+    rid,
+    atimeSec,
+    atimeNsec,
+    mtimeSec,
+    mtimeNsec,
+    btimeSec,
+    btimeNsec,
+  );
 }
 
 async function futime(
   rid,
   atime,
   mtime,
+  options,
 ) {
   const { 0: atimeSec, 1: atimeNsec } = toUnixTimeFromEpoch(atime);
   const { 0: mtimeSec, 1: mtimeNsec } = toUnixTimeFromEpoch(mtime);
+  const { 0: btimeSec, 1: btimeNsec } = toBirthtime(options);
   await core.opAsync(
     "op_fs_futime_async",
     rid,
@@ -434,6 +538,8 @@ async function futime(
     atimeNsec,
     mtimeSec,
     mtimeNsec,
+    btimeSec,
+    btimeNsec,
   );
 }
 
@@ -441,15 +547,19 @@ function utimeSync(
   path,
   atime,
   mtime,
+  options,
 ) {
   const { 0: atimeSec, 1: atimeNsec } = toUnixTimeFromEpoch(atime);
   const { 0: mtimeSec, 1: mtimeNsec } = toUnixTimeFromEpoch(mtime);
+  const { 0: btimeSec, 1: btimeNsec } = toBirthtime(options);
   ops.op_fs_utime_sync(
     pathFromURL(path),
     atimeSec,
     atimeNsec,
     mtimeSec,
     mtimeNsec,
+    btimeSec,
+    btimeNsec,
   );
 }
 
@@ -457,9 +567,11 @@ async function utime(
   path,
   atime,
   mtime,
+  options,
 ) {
   const { 0: atimeSec, 1: atimeNsec } = toUnixTimeFromEpoch(atime);
   const { 0: mtimeSec, 1: mtimeNsec } = toUnixTimeFromEpoch(mtime);
+  const { 0: btimeSec, 1: btimeNsec } = toBirthtime(options);
   await core.opAsync(
     "op_fs_utime_async",
     pathFromURL(path),
@@ -467,6 +579,8 @@ async function utime(
     atimeNsec,
     mtimeSec,
     mtimeNsec,
+    btimeSec,
+    btimeNsec,
   );
 }
 
@@ -527,6 +641,97 @@ async function funlock(rid) {
   await core.opAsync("op_fs_funlock_async", rid);
 }
 
+function allocateSync(rid, len) {
+  ops.op_fs_allocate_sync(rid, coerceLen(len));
+}
+
+async function allocate(rid, len) {
+  await core.opAsync("op_fs_allocate_async", rid, coerceLen(len));
+}
+
+function mmap(rid, options = {}) {
+  return ops.op_fs_fmmap(
+    rid,
+    coerceLen(options.offset),
+    options.length === undefined ? undefined : coerceLen(options.length),
+    options.shared ?? false,
+  );
+}
+
+function msyncSync(buffer) {
+  ops.op_fs_msync_sync(buffer);
+}
+
+async function msync(buffer) {
+  await core.opAsync("op_fs_msync_async", buffer);
+}
+
+function munmap(buffer) {
+  ops.op_fs_munmap(buffer);
+}
+
+function punchHoleSync(rid, offset, len) {
+  ops.op_fs_punch_hole_sync(rid, coerceLen(offset), coerceLen(len));
+}
+
+async function punchHole(rid, offset, len) {
+  await core.opAsync(
+    "op_fs_punch_hole_async",
+    rid,
+    coerceLen(offset),
+    coerceLen(len),
+  );
+}
+
+function statFsSync(path) {
+  return ops.op_fs_stat_fs_sync(pathFromURL(path));
+}
+
+function statFs(path) {
+  return core.opAsync("op_fs_stat_fs_async", pathFromURL(path));
+}
+
+function xattrValue(value) {
+  return typeof value === "string" ? core.encode(value) : value;
+}
+
+function getXattrSync(path, name) {
+  return ops.op_fs_get_xattr_sync(pathFromURL(path), name);
+}
+
+function getXattr(path, name) {
+  return core.opAsync("op_fs_get_xattr_async", pathFromURL(path), name);
+}
+
+function setXattrSync(path, name, value) {
+  ops.op_fs_set_xattr_sync(pathFromURL(path), name, xattrValue(value));
+}
+
+async function setXattr(path, name, value) {
+  await core.opAsync(
+    "op_fs_set_xattr_async",
+    pathFromURL(path),
+    name,
+    xattrValue(value),
+  );
+}
+
+function listXattrSync(path) {
+  return ops.op_fs_list_xattr_sync(pathFromURL(path));
+}
+
+function listXattr(path) {
+  return core.opAsync("op_fs_list_xattr_async", pathFromURL(path));
+}
+
+function removeXattrSync(path, name) {
+  ops.op_fs_remove_xattr_sync(pathFromURL(path), name);
+}
+
+async function removeXattr(path, name) {
+  await core.opAsync("op_fs_remove_xattr_async", pathFromURL(path), name);
+}
+
 function seekSync(
   rid,
   offset,
@@ -570,6 +775,21 @@ async function open(
   return new FsFile(rid);
 }
 
+function openTmpFileSync(dir) {
+  const rid = ops.op_fs_open_tmp_file_sync(
+    dir === undefined ? undefined : pathFromURL(dir),
+  );
+  return new FsFile(rid);
+}
+
+async function openTmpFile(dir) {
+  const rid = await core.opAsync(
+    "op_fs_open_tmp_file_async",
+    dir === undefined ? undefined : pathFromURL(dir),
+  );
+  return new FsFile(rid);
+}
+
 function createSync(path) {
   return openSync(path, {
     read: true,
@@ -642,6 +862,87 @@ class FsFile {
     return fstatSync(this.rid);
   }
 
+  lock(exclusive = false) {
+    return flock(this.rid, exclusive);
+  }
+
+  lockSync(exclusive = false) {
+    return flockSync(this.rid, exclusive);
+  }
+
+  unlock() {
+    return funlock(this.rid);
+  }
+
+  unlockSync() {
+    return funlockSync(this.rid);
+  }
+
+  allocate(len) {
+    return allocate(this.rid, len);
+  }
+
+  allocateSync(len) {
+    return allocateSync(this.rid, len);
+  }
+
+  mmap(options) {
+    return mmap(this.rid, options);
+  }
+
+  punchHole(offset, len) {
+    return punchHole(this.rid, offset, len);
+  }
+
+  punchHoleSync(offset, len) {
+    return punchHoleSync(this.rid, offset, len);
+  }
+
+  getXattr(name) {
+    return core.opAsync("op_fs_fget_xattr_async", this.rid, name);
+  }
+
+  getXattrSync(name) {
+    return ops.op_fs_fget_xattr_sync(this.rid, name);
+  }
+
+  async setXattr(name, value) {
+    await core.opAsync(
+      "op_fs_fset_xattr_async",
+      this.rid,
+      name,
+      xattrValue(value),
+    );
+  }
+
+  setXattrSync(name, value) {
+    ops.op_fs_fset_xattr_sync(this.rid, name, xattrValue(value));
+  }
+
+  listXattr() {
+    return core.opAsync("op_fs_flist_xattr_async", this.rid);
+  }
+
+  listXattrSync() {
+    return ops.op_fs_flist_xattr_sync(this.rid);
+  }
+
+  async removeXattr(name) {
+    await core.opAsync("op_fs_fremove_xattr_async", this.rid, name);
+  }
+
+  removeXattrSync(name) {
+    ops.op_fs_fremove_xattr_sync(this.rid, name);
+  }
+
+  async link(path) {
+    await core.opAsync("op_fs_flink_async", this.rid, pathFromURL(path));
+  }
+
+  linkSync(path) {
+    ops.op_fs_flink_sync(this.rid, pathFromURL(path));
+  }
+
   close() {
     core.close(this.rid);
   }
@@ -813,6 +1114,31 @@ async function writeFile(
   }
 }
 
+function writeFileAtomicSync(
+  path,
+  data,
+  options = {},
+) {
+  ops.op_fs_write_file_atomic_sync(
+    pathFromURL(path),
+    options.mode,
+    typeof data === "string" ? core.encode(data) : data,
+  );
+}
+
+async function writeFileAtomic(
+  path,
+  data,
+  options = {},
+) {
+  await core.opAsync(
+    "op_fs_write_file_atomic_async",
+    pathFromURL(path),
+    options.mode,
+    typeof data === "string" ? core.encode(data) : data,
+  );
+}
+
 function writeTextFileSync(
   path,
   data,
@@ -866,8 +1192,13 @@ export {
   funlockSync,
   futime,
   futimeSync,
+  getXattr,
+  getXattrSync,
+  glob,
   link,
   linkSync,
+  listXattr,
+  listXattrSync,
   lstat,
   lstatSync,
   makeTempDir,
@@ -876,10 +1207,17 @@ export {
   makeTempFileSync,
   mkdir,
   mkdirSync,
+  msync,
+  msyncSync,
+  munmap,
   open,
   openSync,
+  openTmpFile,
+  openTmpFileSync,
   readDir,
   readDirSync,
+  readDirWithStats,
+  readDirWithStatsSync,
   readFile,
   readFileSync,
   readLink,
@@ -890,11 +1228,17 @@ export {
   realPathSync,
   remove,
   removeSync,
+  removeXattr,
+  removeXattrSync,
   rename,
   renameSync,
   seek,
   seekSync,
+  setXattr,
+  setXattrSync,
   stat,
+  statFs,
+  statFsSync,
   statSync,
   symlink,
   symlinkSync,
@@ -904,6 +1248,8 @@ export {
   utime,
   utimeSync,
   writeFile,
+  writeFileAtomic,
+  writeFileAtomicSync,
   writeFileSync,
   writeTextFile,
   writeTextFileSync,
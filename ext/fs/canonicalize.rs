@@ -0,0 +1,182 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+#![allow(clippy::disallowed_methods)]
+
+use deno_core::parking_lot::Mutex;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::io::Error;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+fn canonicalize_path(path: &Path) -> Result<PathBuf, Error> {
+  Ok(deno_core::strip_unc_prefix(path.canonicalize()?))
+}
+
+/// The maximum number of paths in the cache of `canonicalize_path_cached()`,
+/// after which it starts over.
+const CANONICALIZE_CACHE_CAPACITY: usize = 8192;
+
+static CANONICALIZE_CACHE: Lazy<Mutex<HashMap<PathBuf, CanonicalizedPath>>> =
+  Lazy::new(Default::default);
+
+struct CanonicalizedPath {
+  path: PathBuf,
+  parent: DirStamp,
+  /// The stamp of the parent of `path`, if it's another directory.
+  target_parent: Option<DirStamp>,
+}
+
+/// The identity of a directory and when its entries last changed, which is
+/// enough to know that they still resolve to the same paths.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct DirStamp {
+  #[cfg(unix)]
+  dev: u64,
+  #[cfg(unix)]
+  ino: u64,
+  /// Renaming a directory changes its ctime, but not its mtime.
+  #[cfg(unix)]
+  ctime: (i64, i64),
+  modified: Option<SystemTime>,
+}
+
+impl DirStamp {
+  fn of(dir: &Path) -> Option<Self> {
+    let metadata = std::fs::metadata(dir).ok()?;
+    Some(Self {
+      #[cfg(unix)]
+      dev: metadata.dev(),
+      #[cfg(unix)]
+      ino: metadata.ino(),
+      #[cfg(unix)]
+      ctime: (metadata.ctime(), metadata.ctime_nsec()),
+      modified: metadata.modified().ok(),
+    })
+  }
+
+  /// Whether the directory changed so recently that it may change again
+  /// without its times changing, as they are only updated every few
+  /// milliseconds on some file systems.
+  fn is_racy(&self) -> bool {
+    match self.modified.map(|modified| modified.elapsed()) {
+      Some(Ok(elapsed)) => elapsed < Duration::from_secs(1),
+      _ => true,
+    }
+  }
+}
+
+/// Like `canonicalize_path()`, but caches the results for absolute paths
+/// for the whole process. A result is reused as long as the parent
+/// directories of the path and of what it resolves to haven't changed, which
+/// takes one or two `stat()` calls rather than one call per component. Paths
+/// in directories that were just changed aren't cached yet.
+///
+/// Changes further up that don't touch these directories, like an ancestor
+/// being moved and replaced with a symlink to its new place, aren't noticed,
+/// so this should only be used for paths that are unlikely to change while
+/// they are being loaded, like those of modules.
+pub fn canonicalize_path_cached(path: &Path) -> Result<PathBuf, Error> {
+  let Some(parent) = path.parent().filter(|_| path.is_absolute()) else {
+    return canonicalize_path(path);
+  };
+
+  // The directories are checked without holding the lock.
+  let cached = CANONICALIZE_CACHE
+    .lock()
+    .get(path)
+    .map(|cached| (cached.path.clone(), cached.parent, cached.target_parent));
+  if let Some((cached_path, parent_stamp, target_parent_stamp)) = cached {
+    let is_fresh = DirStamp::of(parent) == Some(parent_stamp)
+      && match (target_parent_stamp, cached_path.parent()) {
+        (Some(stamp), Some(target_parent)) => {
+          DirStamp::of(target_parent) == Some(stamp)
+        }
+        _ => true,
+      };
+    if is_fresh {
+      return Ok(cached_path);
+    }
+  }
+
+  let parent_stamp = DirStamp::of(parent);
+  let canonicalized = canonicalize_path(path)?;
+  let target_parent_stamp = canonicalized.parent().and_then(DirStamp::of);
+  let Some(parent_stamp) = parent_stamp else {
+    return Ok(canonicalized);
+  };
+  let target_parent = match target_parent_stamp {
+    Some(stamp) if stamp == parent_stamp => None,
+    Some(stamp) => Some(stamp),
+    // The result can't be checked later.
+    None => return Ok(canonicalized),
+  };
+  if !parent_stamp.is_racy() && !target_parent.map_or(false, |s| s.is_racy()) {
+    let mut cache = CANONICALIZE_CACHE.lock();
+    if cache.len() >= CANONICALIZE_CACHE_CAPACITY {
+      cache.clear();
+    }
+    cache.insert(
+      path.to_path_buf(),
+      CanonicalizedPath {
+        path: canonicalized.clone(),
+        parent: parent_stamp,
+        target_parent,
+      },
+    );
+  }
+  Ok(canonicalized)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_canonicalize_path_cached() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = canonicalize_path(temp_dir.path()).unwrap();
+    std::fs::create_dir(root.join("a")).unwrap();
+    std::fs::create_dir(root.join("b")).unwrap();
+    let path = root.join("link");
+
+    symlink_dir(&root.join("a"), &path);
+    assert_eq!(canonicalize_path_cached(&path).unwrap(), root.join("a"));
+    // Changes that recent aren't cached.
+    assert!(!CANONICALIZE_CACHE.lock().contains_key(&path));
+
+    let an_hour_ago = filetime::FileTime::from_system_time(
+      SystemTime::now() - Duration::from_secs(3600),
+    );
+    filetime::set_file_mtime(&root, an_hour_ago).unwrap();
+    assert_eq!(canonicalize_path_cached(&path).unwrap(), root.join("a"));
+    assert!(CANONICALIZE_CACHE.lock().contains_key(&path));
+    assert_eq!(canonicalize_path_cached(&path).unwrap(), root.join("a"));
+
+    // Replacing the symlink changes its parent directory.
+    std::fs::remove_file(&path)
+      .or_else(|_| std::fs::remove_dir(&path))
+      .unwrap();
+    symlink_dir(&root.join("b"), &path);
+    assert_eq!(canonicalize_path_cached(&path).unwrap(), root.join("b"));
+
+    let two_hours_ago = filetime::FileTime::from_system_time(
+      SystemTime::now() - Duration::from_secs(7200),
+    );
+    filetime::set_file_mtime(&root, two_hours_ago).unwrap();
+    assert_eq!(canonicalize_path_cached(&path).unwrap(), root.join("b"));
+    std::fs::remove_dir(root.join("b")).unwrap();
+    assert!(canonicalize_path_cached(&path).is_err());
+  }
+
+  fn symlink_dir(original: &Path, link: &Path) {
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(original, link).unwrap();
+    #[cfg(windows)]
+    std::os::windows::fs::symlink_dir(original, link).unwrap();
+  }
+}
@@ -0,0 +1,345 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The matching and the directory traversal of `Deno.glob()`. Directories
+//! are read concurrently on the blocking thread pool, and those that can't
+//! contain matches or are excluded are never read.
+
+use std::collections::VecDeque;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::futures::future::LocalBoxFuture;
+use deno_core::futures::stream::FuturesUnordered;
+use deno_core::futures::FutureExt;
+use deno_core::futures::StreamExt;
+use deno_io::fs::FsError;
+use deno_io::fs::FsResult;
+use glob::MatchOptions;
+use glob::Pattern;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::interface::FsDirEntry;
+use crate::FileSystemRc;
+
+/// The maximum number of directories that are read at the same time.
+const MAX_CONCURRENT_READS: usize = 16;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobOptions {
+  /// Gitignore-style patterns of the paths to skip, relative to the root.
+  exclude: Vec<String>,
+  include_dirs: bool,
+  case_insensitive: bool,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobEntry {
+  pub path: PathBuf,
+  pub name: String,
+  pub is_file: bool,
+  pub is_directory: bool,
+  pub is_symlink: bool,
+}
+
+fn is_glob(component: &str) -> bool {
+  component.contains(['*', '?', '['])
+}
+
+fn parse_pattern(pattern: &str) -> Result<Pattern, AnyError> {
+  Pattern::new(pattern).map_err(|err| {
+    type_error(format!("Invalid glob pattern \"{pattern}\": {err}"))
+  })
+}
+
+/// A gitignore-style pattern: it matches the names of the files at any depth,
+/// unless it contains a `/`, in which case it matches their paths relative to
+/// the root. A trailing `/` restricts it to directories and a leading `!`
+/// includes back what a previous pattern excluded.
+#[derive(Debug)]
+struct Exclusion {
+  pattern: Pattern,
+  negated: bool,
+  dir_only: bool,
+  anchored: bool,
+}
+
+impl Exclusion {
+  fn new(pattern: &str) -> Result<Self, AnyError> {
+    let (negated, pattern) = match pattern.strip_prefix('!') {
+      Some(pattern) => (true, pattern),
+      None => (false, pattern),
+    };
+    let (dir_only, pattern) = match pattern.strip_suffix('/') {
+      Some(pattern) => (true, pattern),
+      None => (false, pattern),
+    };
+    let anchored = pattern.contains('/');
+    let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+    Ok(Self {
+      pattern: parse_pattern(pattern)?,
+      negated,
+      dir_only,
+      anchored,
+    })
+  }
+}
+
+/// Decides which paths, relative to the root and separated by `/`, match a
+/// glob pattern.
+#[derive(Debug)]
+struct GlobMatcher {
+  pattern: Pattern,
+  /// The components of the pattern before the first `**`.
+  components: Vec<Pattern>,
+  /// The number of components of the pattern.
+  depth: usize,
+  recursive: bool,
+  exclude: Vec<Exclusion>,
+  include_dirs: bool,
+  case_sensitive: bool,
+}
+
+impl GlobMatcher {
+  fn new(pattern: &str, options: &GlobOptions) -> Result<Self, AnyError> {
+    let mut components = Vec::new();
+    let mut recursive = false;
+    for component in pattern.split('/') {
+      if component == "**" {
+        recursive = true;
+        break;
+      }
+      components.push(parse_pattern(component)?);
+    }
+    Ok(Self {
+      pattern: parse_pattern(pattern)?,
+      components,
+      depth: pattern.split('/').count(),
+      recursive,
+      exclude: options
+        .exclude
+        .iter()
+        .map(|pattern| Exclusion::new(pattern))
+        .collect::<Result<_, _>>()?,
+      include_dirs: options.include_dirs,
+      case_sensitive: !options.case_insensitive,
+    })
+  }
+
+  fn options(&self) -> MatchOptions {
+    MatchOptions {
+      case_sensitive: self.case_sensitive,
+      require_literal_separator: true,
+      require_literal_leading_dot: true,
+    }
+  }
+
+  fn is_match(&self, path: &str, is_directory: bool) -> bool {
+    (self.include_dirs || !is_directory)
+      && self.pattern.matches_with(path, self.options())
+  }
+
+  /// Whether the entries of a directory may match, so it has to be read.
+  fn may_match_in(&self, dir: &str) -> bool {
+    let components = dir.split('/').filter(|c| !c.is_empty());
+    let mut count = 0;
+    for (i, component) in components.enumerate() {
+      count += 1;
+      match self.components.get(i) {
+        Some(pattern) if !pattern.matches_with(component, self.options()) => {
+          return false
+        }
+        Some(_) => {}
+        None if self.recursive => return true,
+        None => return false,
+      }
+    }
+    self.recursive || count < self.depth
+  }
+
+  fn is_excluded(&self, path: &str, name: &str, is_directory: bool) -> bool {
+    // Unlike in globs, `*` matches leading dots in gitignore files.
+    let options = MatchOptions {
+      case_sensitive: self.case_sensitive,
+      require_literal_separator: true,
+      require_literal_leading_dot: false,
+    };
+    let mut excluded = false;
+    for exclusion in &self.exclude {
+      if exclusion.dir_only && !is_directory {
+        continue;
+      }
+      let target = if exclusion.anchored { path } else { name };
+      if exclusion.pattern.matches_with(target, options) {
+        excluded = !exclusion.negated;
+      }
+    }
+    excluded
+  }
+}
+
+type ReadDirFuture =
+  LocalBoxFuture<'static, (PathBuf, String, FsResult<Vec<FsDirEntry>>)>;
+
+/// Walks the directories that may contain matches of a pattern.
+pub struct GlobWalker {
+  fs: FileSystemRc,
+  matcher: GlobMatcher,
+  /// The directories left to read, with their paths relative to the root.
+  queue: VecDeque<(PathBuf, String)>,
+  reads: FuturesUnordered<ReadDirFuture>,
+}
+
+impl GlobWalker {
+  /// Returns the walker, and the directory where it starts, which is the
+  /// longest part of the pattern without any wildcards.
+  pub fn new(
+    fs: FileSystemRc,
+    root: &Path,
+    pattern: &str,
+    options: &GlobOptions,
+  ) -> Result<(Self, PathBuf), AnyError> {
+    let pattern = if cfg!(windows) {
+      pattern.replace('\\', "/")
+    } else {
+      pattern.to_string()
+    };
+    // Absolute patterns are relative to the root of the file system.
+    let (root, pattern) = if Path::new(&pattern).is_absolute() {
+      let (root, rest) = pattern.split_once('/').unwrap_or((&pattern, ""));
+      (PathBuf::from(format!("{root}/")), rest.to_string())
+    } else {
+      (root.to_path_buf(), pattern)
+    };
+    let matcher = GlobMatcher::new(&pattern, options)?;
+
+    let components = pattern.split('/').collect::<Vec<_>>();
+    let literal = components[..components.len() - 1]
+      .iter()
+      .take_while(|component| !is_glob(component))
+      .copied()
+      .collect::<Vec<_>>();
+    let start = root.join(literal.join("/"));
+    let walker = Self {
+      fs,
+      matcher,
+      queue: VecDeque::from([(start.clone(), literal.join("/"))]),
+      reads: FuturesUnordered::new(),
+    };
+    Ok((walker, start))
+  }
+
+  /// Returns the matches in the next directory that has any, in no
+  /// particular order, or `None` once all the directories have been read.
+  /// Directories that are removed during the walk are skipped.
+  pub async fn next(
+    &mut self,
+  ) -> Result<Option<Vec<GlobEntry>>, (PathBuf, FsError)> {
+    loop {
+      while self.reads.len() < MAX_CONCURRENT_READS {
+        let Some((dir, relative)) = self.queue.pop_front() else {
+          break;
+        };
+        let fs = self.fs.clone();
+        self.reads.push(
+          async move {
+            let entries = fs.read_dir_async(dir.clone()).await;
+            (dir, relative, entries)
+          }
+          .boxed_local(),
+        );
+      }
+
+      let Some((dir, relative, entries)) = self.reads.next().await else {
+        return Ok(None);
+      };
+      let entries = match entries {
+        Ok(entries) => entries,
+        Err(FsError::Io(err)) if err.kind() == io::ErrorKind::NotFound => {
+          continue
+        }
+        Err(err) => return Err((dir, err)),
+      };
+
+      let mut matches = Vec::new();
+      for entry in entries {
+        let path = if relative.is_empty() {
+          entry.name.clone()
+        } else {
+          format!("{relative}/{}", entry.name)
+        };
+        if self
+          .matcher
+          .is_excluded(&path, &entry.name, entry.is_directory)
+        {
+          continue;
+        }
+        if entry.is_directory && self.matcher.may_match_in(&path) {
+          self.queue.push_back((dir.join(&entry.name), path.clone()));
+        }
+        if self.matcher.is_match(&path, entry.is_directory) {
+          matches.push(GlobEntry {
+            path: dir.join(&entry.name),
+            name: entry.name,
+            is_file: entry.is_file,
+            is_directory: entry.is_directory,
+            is_symlink: entry.is_symlink,
+          });
+        }
+      }
+      if !matches.is_empty() {
+        return Ok(Some(matches));
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn matcher(pattern: &str, exclude: &[&str]) -> GlobMatcher {
+    let options = GlobOptions {
+      exclude: exclude.iter().map(|s| s.to_string()).collect(),
+      include_dirs: true,
+      case_insensitive: false,
+    };
+    GlobMatcher::new(pattern, &options).unwrap()
+  }
+
+  #[test]
+  fn test_glob_matcher() {
+    let m = matcher("src/**/*.ts", &[]);
+    assert!(m.is_match("src/a.ts", false));
+    assert!(m.is_match("src/a/b/c.ts", false));
+    assert!(!m.is_match("src/.a.ts", false));
+    assert!(!m.is_match("src/.a/b.ts", false));
+    assert!(!m.is_match("lib/a.ts", false));
+    assert!(m.may_match_in(""));
+    assert!(m.may_match_in("src"));
+    assert!(m.may_match_in("src/a/b"));
+    assert!(!m.may_match_in("lib"));
+
+    let m = matcher("*/*.json", &[]);
+    assert!(m.is_match("a/b.json", false));
+    assert!(!m.is_match("a/b/c.json", false));
+    assert!(m.may_match_in("a"));
+    assert!(!m.may_match_in("a/b"));
+  }
+
+  #[test]
+  fn test_glob_exclusions() {
+    let m = matcher("**/*", &["node_modules/", "*.log", "!keep.log", "/out"]);
+    assert!(m.is_excluded("a/node_modules", "node_modules", true));
+    assert!(!m.is_excluded("a/node_modules", "node_modules", false));
+    assert!(m.is_excluded("a/b.log", "b.log", false));
+    assert!(!m.is_excluded("a/keep.log", "keep.log", false));
+    assert!(m.is_excluded("out", "out", true));
+    assert!(!m.is_excluded("a/out", "out", true));
+  }
+}
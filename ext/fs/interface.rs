@@ -27,6 +27,20 @@ pub struct OpenOptions {
   pub mode: Option<u32>,
 }
 
+/// Whether `copy_file` clones files, so the copy shares the data blocks of the
+/// original until either of them is written to.
+#[derive(Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReflinkMode {
+  /// Clone files when the file system supports it, and copy them otherwise.
+  #[default]
+  Auto,
+  /// Fail when files can't be cloned.
+  Always,
+  /// Always copy the contents of files.
+  Never,
+}
+
 impl OpenOptions {
   pub fn read() -> Self {
     Self {
@@ -75,6 +89,46 @@ pub struct FsDirEntry {
   pub is_symlink: bool,
 }
 
+/// A directory entry with the metadata of the entry itself, rather than of
+/// what it links to, see `Deno.readDirWithStats()`.
+pub struct FsDirEntryWithStat {
+  pub entry: FsDirEntry,
+  pub stat: FsStat,
+}
+
+/// The statistics of a file system, see `Deno.statFs()`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FsStatFs {
+  /// The name of the type of the file system, like "ext4", "apfs" or "NTFS".
+  #[serde(rename = "type")]
+  pub fs_type: String,
+  pub block_size: u64,
+  pub blocks: u64,
+  pub blocks_free: u64,
+  /// The free blocks which unprivileged users can use.
+  pub blocks_available: u64,
+  pub total_bytes: u64,
+  pub free_bytes: u64,
+  pub available_bytes: u64,
+  /// The number of inodes, which Windows doesn't have.
+  pub files: Option<u64>,
+  pub files_free: Option<u64>,
+  pub flags: FsStatFsFlags,
+}
+
+/// The flags the file system was mounted with.
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FsStatFsFlags {
+  pub read_only: bool,
+  pub no_suid: bool,
+  pub no_dev: bool,
+  pub no_exec: bool,
+  pub synchronous: bool,
+  pub no_atime: bool,
+}
+
 #[allow(clippy::disallowed_types)]
 pub type FileSystemRc = crate::sync::MaybeArc<dyn FileSystem>;
 
@@ -96,6 +150,11 @@ pub trait FileSystem: std::fmt::Debug + MaybeSend + MaybeSync {
     options: OpenOptions,
   ) -> FsResult<Rc<dyn File>>;
 
+  /// Opens a file in `dir` for reading and writing that has no name, and is
+  /// deleted once it's closed unless it's linked with `File::link_sync()`.
+  fn open_tmp_file_sync(&self, dir: &Path) -> FsResult<Rc<dyn File>>;
+  async fn open_tmp_file_async(&self, dir: PathBuf) -> FsResult<Rc<dyn File>>;
+
   fn mkdir_sync(&self, path: &Path, recusive: bool, mode: u32) -> FsResult<()>;
   async fn mkdir_async(
     &self,
@@ -122,25 +181,51 @@ pub trait FileSystem: std::fmt::Debug + MaybeSend + MaybeSync {
 
   fn remove_sync(&self, path: &Path, recursive: bool) -> FsResult<()>;
   async fn remove_async(&self, path: PathBuf, recursive: bool) -> FsResult<()>;
+  /// Moves a file or a directory to the trash of the system, for reversible
+  /// removals.
+  fn trash_sync(&self, path: &Path) -> FsResult<()>;
+  async fn trash_async(&self, path: PathBuf) -> FsResult<()>;
 
-  fn copy_file_sync(&self, oldpath: &Path, newpath: &Path) -> FsResult<()>;
+  fn copy_file_sync(
+    &self,
+    oldpath: &Path,
+    newpath: &Path,
+    reflink: ReflinkMode,
+  ) -> FsResult<()>;
   async fn copy_file_async(
     &self,
     oldpath: PathBuf,
     newpath: PathBuf,
+    reflink: ReflinkMode,
   ) -> FsResult<()>;
 
   fn stat_sync(&self, path: &Path) -> FsResult<FsStat>;
   async fn stat_async(&self, path: PathBuf) -> FsResult<FsStat>;
 
+  fn stat_fs_sync(&self, path: &Path) -> FsResult<FsStatFs>;
+  async fn stat_fs_async(&self, path: PathBuf) -> FsResult<FsStatFs>;
+
   fn lstat_sync(&self, path: &Path) -> FsResult<FsStat>;
   async fn lstat_async(&self, path: PathBuf) -> FsResult<FsStat>;
 
   fn realpath_sync(&self, path: &Path) -> FsResult<PathBuf>;
   async fn realpath_async(&self, path: PathBuf) -> FsResult<PathBuf>;
+  /// Like `realpath_sync()`, but the result may be cached, so it should only
+  /// be used for paths that are unlikely to change, like those of modules.
+  fn realpath_sync_cached(&self, path: &Path) -> FsResult<PathBuf> {
+    self.realpath_sync(path)
+  }
 
   fn read_dir_sync(&self, path: &Path) -> FsResult<Vec<FsDirEntry>>;
   async fn read_dir_async(&self, path: PathBuf) -> FsResult<Vec<FsDirEntry>>;
+  fn read_dir_with_stats_sync(
+    &self,
+    path: &Path,
+  ) -> FsResult<Vec<FsDirEntryWithStat>>;
+  async fn read_dir_with_stats_async(
+    &self,
+    path: PathBuf,
+  ) -> FsResult<Vec<FsDirEntryWithStat>>;
 
   fn rename_sync(&self, oldpath: &Path, newpath: &Path) -> FsResult<()>;
   async fn rename_async(
@@ -191,6 +276,52 @@ pub trait FileSystem: std::fmt::Debug + MaybeSend + MaybeSync {
     mtime_secs: i64,
     mtime_nanos: u32,
   ) -> FsResult<()>;
+  fn set_birthtime_sync(
+    &self,
+    path: &Path,
+    secs: i64,
+    nanos: u32,
+  ) -> FsResult<()>;
+  async fn set_birthtime_async(
+    &self,
+    path: PathBuf,
+    secs: i64,
+    nanos: u32,
+  ) -> FsResult<()>;
+
+  fn get_xattr_sync(
+    &self,
+    path: &Path,
+    name: &str,
+  ) -> FsResult<Option<Vec<u8>>>;
+  async fn get_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+  ) -> FsResult<Option<Vec<u8>>>;
+
+  fn set_xattr_sync(
+    &self,
+    path: &Path,
+    name: &str,
+    value: &[u8],
+  ) -> FsResult<()>;
+  async fn set_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+    value: Vec<u8>,
+  ) -> FsResult<()>;
+
+  fn list_xattr_sync(&self, path: &Path) -> FsResult<Vec<String>>;
+  async fn list_xattr_async(&self, path: PathBuf) -> FsResult<Vec<String>>;
+
+  fn remove_xattr_sync(&self, path: &Path, name: &str) -> FsResult<()>;
+  async fn remove_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+  ) -> FsResult<()>;
 
   fn write_file_sync(
     &self,
@@ -219,6 +350,21 @@ pub trait FileSystem: std::fmt::Debug + MaybeSend + MaybeSync {
     Ok(())
   }
 
+  /// Replaces the contents of a file without ever leaving it partially
+  /// written, even if the process or the machine crashes.
+  fn write_file_atomic_sync(
+    &self,
+    path: &Path,
+    mode: Option<u32>,
+    data: &[u8],
+  ) -> FsResult<()>;
+  async fn write_file_atomic_async(
+    &self,
+    path: PathBuf,
+    mode: Option<u32>,
+    data: Vec<u8>,
+  ) -> FsResult<()>;
+
   fn read_file_sync(&self, path: &Path) -> FsResult<Vec<u8>> {
     let options = OpenOptions::read();
     let file = self.open_sync(path, options)?;
@@ -1,19 +1,29 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+mod canonicalize;
+mod glob;
 mod interface;
+mod mmap;
 mod ops;
 mod std_fs;
 pub mod sync;
+mod trash;
 
+pub use crate::canonicalize::canonicalize_path_cached;
 pub use crate::interface::FileSystem;
 pub use crate::interface::FileSystemRc;
 pub use crate::interface::FsDirEntry;
+pub use crate::interface::FsDirEntryWithStat;
 pub use crate::interface::FsFileType;
+pub use crate::interface::FsStatFs;
+pub use crate::interface::FsStatFsFlags;
 pub use crate::interface::OpenOptions;
+pub use crate::interface::ReflinkMode;
 pub use crate::std_fs::RealFs;
 pub use crate::sync::MaybeSend;
 pub use crate::sync::MaybeSync;
 
+use crate::mmap::MmapRegistry;
 use crate::ops::*;
 
 use deno_core::error::AnyError;
@@ -106,12 +116,18 @@ deno_core::extension!(deno_fs,
     op_fs_copy_file_async<P>,
     op_fs_stat_sync<P>,
     op_fs_stat_async<P>,
+    op_fs_stat_fs_sync<P>,
+    op_fs_stat_fs_async<P>,
     op_fs_lstat_sync<P>,
     op_fs_lstat_async<P>,
     op_fs_realpath_sync<P>,
     op_fs_realpath_async<P>,
     op_fs_read_dir_sync<P>,
     op_fs_read_dir_async<P>,
+    op_fs_read_dir_with_stats_sync<P>,
+    op_fs_read_dir_with_stats_async<P>,
+    op_fs_glob_open<P>,
+    op_fs_glob_next,
     op_fs_rename_sync<P>,
     op_fs_rename_async<P>,
     op_fs_link_sync<P>,
@@ -128,8 +144,12 @@ deno_core::extension!(deno_fs,
     op_fs_make_temp_dir_async<P>,
     op_fs_make_temp_file_sync<P>,
     op_fs_make_temp_file_async<P>,
+    op_fs_open_tmp_file_sync<P>,
+    op_fs_open_tmp_file_async<P>,
     op_fs_write_file_sync<P>,
     op_fs_write_file_async<P>,
+    op_fs_write_file_atomic_sync<P>,
+    op_fs_write_file_atomic_async<P>,
     op_fs_read_file_sync<P>,
     op_fs_read_file_async<P>,
     op_fs_read_file_text_sync<P>,
@@ -147,6 +167,32 @@ deno_core::extension!(deno_fs,
     op_fs_flock_async,
     op_fs_funlock_sync,
     op_fs_funlock_async,
+    op_fs_flink_sync<P>,
+    op_fs_flink_async<P>,
+    op_fs_fmmap,
+    op_fs_msync_sync,
+    op_fs_msync_async,
+    op_fs_munmap,
+    op_fs_allocate_sync,
+    op_fs_allocate_async,
+    op_fs_punch_hole_sync,
+    op_fs_punch_hole_async,
+    op_fs_get_xattr_sync<P>,
+    op_fs_get_xattr_async<P>,
+    op_fs_set_xattr_sync<P>,
+    op_fs_set_xattr_async<P>,
+    op_fs_list_xattr_sync<P>,
+    op_fs_list_xattr_async<P>,
+    op_fs_remove_xattr_sync<P>,
+    op_fs_remove_xattr_async<P>,
+    op_fs_fget_xattr_sync,
+    op_fs_fget_xattr_async,
+    op_fs_fset_xattr_sync,
+    op_fs_fset_xattr_async,
+    op_fs_flist_xattr_sync,
+    op_fs_flist_xattr_async,
+    op_fs_fremove_xattr_sync,
+    op_fs_fremove_xattr_async,
     op_fs_ftruncate_sync,
     op_fs_ftruncate_async,
     op_fs_futime_sync,
@@ -161,5 +207,6 @@ deno_core::extension!(deno_fs,
   state = |state, options| {
     state.put(UnstableChecker { unstable: options.unstable });
     state.put(options.fs);
+    state.put(MmapRegistry::default());
   },
 );
@@ -0,0 +1,85 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+// The mappings are shared with the backing stores of V8, which may be freed
+// on any thread, so they need a real `Arc` rather than a `MaybeArc`.
+#![allow(clippy::disallowed_types)]
+
+//! The memory mappings of `Deno.FsFile.mmap()`. Each mapping is owned by the
+//! backing store of the ArrayBuffer it is exposed as, so it's unmapped once
+//! the buffer is garbage collected or detached by `Deno.munmap()`. The ops
+//! using a mapping keep it alive until they are done with it.
+
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::sync::Arc;
+use std::sync::Weak;
+
+use deno_core::v8;
+use memmap2::MmapMut;
+
+pub type SharedMmap = Arc<MmapMut>;
+
+/// The live mappings, by the address of their memory.
+#[derive(Default)]
+pub struct MmapRegistry(HashMap<usize, Weak<MmapMut>>);
+
+impl MmapRegistry {
+  /// Returns the mapping that `buffer` is exactly the memory of, if any.
+  pub fn get(&self, buffer: &[u8]) -> Option<SharedMmap> {
+    self.get_by_address(buffer.as_ptr() as usize, buffer.len())
+  }
+
+  pub fn is_mapped(&self, buffer: &v8::ArrayBuffer) -> bool {
+    match buffer.data() {
+      Some(ptr) => self
+        .get_by_address(ptr.as_ptr() as usize, buffer.byte_length())
+        .is_some(),
+      None => false,
+    }
+  }
+
+  fn get_by_address(&self, address: usize, len: usize) -> Option<SharedMmap> {
+    self
+      .0
+      .get(&address)
+      .and_then(Weak::upgrade)
+      .filter(|mmap| mmap.len() == len)
+  }
+
+  /// Creates an ArrayBuffer backed by the memory of the mapping.
+  pub fn array_buffer<'s>(
+    &mut self,
+    scope: &mut v8::HandleScope<'s>,
+    mut mmap: MmapMut,
+  ) -> v8::Local<'s, v8::ArrayBuffer> {
+    // Forget the mappings that have been unmapped since the last time.
+    self.0.retain(|_, mmap| mmap.strong_count() > 0);
+
+    let ptr = mmap.as_mut_ptr();
+    let len = mmap.len();
+    let mmap = Arc::new(mmap);
+    self.0.insert(ptr as usize, Arc::downgrade(&mmap));
+    let deleter_data = Box::into_raw(Box::new(mmap));
+    // SAFETY: the memory stays mapped until the deleter drops the mapping,
+    // which V8 only does once the backing store isn't used anymore.
+    let backing_store = unsafe {
+      v8::ArrayBuffer::new_backing_store_from_ptr(
+        ptr as *mut c_void,
+        len,
+        mmap_deleter_callback,
+        deleter_data as *mut c_void,
+      )
+    }
+    .make_shared();
+    v8::ArrayBuffer::with_backing_store(scope, &backing_store)
+  }
+}
+
+unsafe extern "C" fn mmap_deleter_callback(
+  _data: *mut c_void,
+  _byte_length: usize,
+  deleter_data: *mut c_void,
+) {
+  // SAFETY: `deleter_data` is the box created in `array_buffer()`.
+  drop(Box::from_raw(deleter_data as *mut Arc<MmapMut>));
+}
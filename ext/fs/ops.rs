@@ -9,12 +9,18 @@ use std::path::PathBuf;
 use std::rc::Rc;
 
 use deno_core::error::custom_error;
+use deno_core::error::range_error;
 use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::op;
+use deno_core::serde_v8;
+use deno_core::v8;
+use deno_core::AsyncRefCell;
 use deno_core::CancelFuture;
 use deno_core::CancelHandle;
 use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
 use deno_core::ResourceId;
 use deno_core::ZeroCopyBuf;
 use deno_io::fs::FileResource;
@@ -27,11 +33,19 @@ use serde::Serialize;
 
 use crate::check_unstable;
 use crate::check_unstable2;
+use crate::glob::GlobEntry;
+use crate::glob::GlobOptions;
+use crate::glob::GlobWalker;
 use crate::interface::FileSystemRc;
 use crate::interface::FsDirEntry;
+use crate::interface::FsDirEntryWithStat;
 use crate::interface::FsFileType;
+use crate::interface::FsStatFs;
+use crate::mmap::MmapRegistry;
+use crate::mmap::SharedMmap;
 use crate::FsPermissions;
 use crate::OpenOptions;
+use crate::ReflinkMode;
 
 #[op]
 pub fn op_fs_cwd<P>(state: &mut OpState) -> Result<String, AnyError>
@@ -263,6 +277,7 @@ fn op_fs_remove_sync<P>(
   state: &mut OpState,
   path: &str,
   recursive: bool,
+  trash: bool,
 ) -> Result<(), AnyError>
 where
   P: FsPermissions + 'static,
@@ -274,8 +289,12 @@ where
     .check_write(&path, "Deno.removeSync()")?;
 
   let fs = state.borrow::<FileSystemRc>();
-  fs.remove_sync(&path, recursive)
-    .context_path("remove", &path)?;
+  if trash {
+    fs.trash_sync(&path).context_path("remove", &path)?;
+  } else {
+    fs.remove_sync(&path, recursive)
+      .context_path("remove", &path)?;
+  }
 
   Ok(())
 }
@@ -285,6 +304,7 @@ async fn op_fs_remove_async<P>(
   state: Rc<RefCell<OpState>>,
   path: String,
   recursive: bool,
+  trash: bool,
 ) -> Result<(), AnyError>
 where
   P: FsPermissions + 'static,
@@ -299,9 +319,15 @@ where
     state.borrow::<FileSystemRc>().clone()
   };
 
-  fs.remove_async(path.clone(), recursive)
-    .await
-    .context_path("remove", &path)?;
+  if trash {
+    fs.trash_async(path.clone())
+      .await
+      .context_path("remove", &path)?;
+  } else {
+    fs.remove_async(path.clone(), recursive)
+      .await
+      .context_path("remove", &path)?;
+  }
 
   Ok(())
 }
@@ -311,6 +337,7 @@ fn op_fs_copy_file_sync<P>(
   state: &mut OpState,
   from: &str,
   to: &str,
+  reflink: Option<ReflinkMode>,
 ) -> Result<(), AnyError>
 where
   P: FsPermissions + 'static,
@@ -323,7 +350,7 @@ where
   permissions.check_write(&to, "Deno.copyFileSync()")?;
 
   let fs = state.borrow::<FileSystemRc>();
-  fs.copy_file_sync(&from, &to)
+  fs.copy_file_sync(&from, &to, reflink.unwrap_or_default())
     .context_two_path("copy", &from, &to)?;
 
   Ok(())
@@ -334,6 +361,7 @@ async fn op_fs_copy_file_async<P>(
   state: Rc<RefCell<OpState>>,
   from: String,
   to: String,
+  reflink: Option<ReflinkMode>,
 ) -> Result<(), AnyError>
 where
   P: FsPermissions + 'static,
@@ -349,7 +377,7 @@ where
     state.borrow::<FileSystemRc>().clone()
   };
 
-  fs.copy_file_async(from.clone(), to.clone())
+  fs.copy_file_async(from.clone(), to.clone(), reflink.unwrap_or_default())
     .await
     .context_two_path("copy", &from, &to)?;
 
@@ -398,6 +426,46 @@ where
   Ok(SerializableStat::from(stat))
 }
 
+#[op]
+fn op_fs_stat_fs_sync<P>(
+  state: &mut OpState,
+  path: String,
+) -> Result<FsStatFs, AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable(state, "Deno.statFsSync");
+  let path = PathBuf::from(path);
+  state
+    .borrow_mut::<P>()
+    .check_read(&path, "Deno.statFsSync()")?;
+  let fs = state.borrow::<FileSystemRc>();
+  let stat_fs = fs.stat_fs_sync(&path).context_path("statfs", &path)?;
+  Ok(stat_fs)
+}
+
+#[op]
+async fn op_fs_stat_fs_async<P>(
+  state: Rc<RefCell<OpState>>,
+  path: String,
+) -> Result<FsStatFs, AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable2(&state, "Deno.statFs");
+  let path = PathBuf::from(path);
+  let fs = {
+    let mut state = state.borrow_mut();
+    state.borrow_mut::<P>().check_read(&path, "Deno.statFs()")?;
+    state.borrow::<FileSystemRc>().clone()
+  };
+  let stat_fs = fs
+    .stat_fs_async(path.clone())
+    .await
+    .context_path("statfs", &path)?;
+  Ok(stat_fs)
+}
+
 #[op]
 fn op_fs_lstat_sync<P>(
   state: &mut OpState,
@@ -539,6 +607,135 @@ where
   Ok(entries)
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DirEntryWithStat {
+  name: String,
+  is_file: bool,
+  is_directory: bool,
+  is_symlink: bool,
+  stat: SerializableStat,
+}
+
+impl From<FsDirEntryWithStat> for DirEntryWithStat {
+  fn from(entry: FsDirEntryWithStat) -> Self {
+    DirEntryWithStat {
+      name: entry.entry.name,
+      is_file: entry.entry.is_file,
+      is_directory: entry.entry.is_directory,
+      is_symlink: entry.entry.is_symlink,
+      stat: entry.stat.into(),
+    }
+  }
+}
+
+#[op]
+fn op_fs_read_dir_with_stats_sync<P>(
+  state: &mut OpState,
+  path: String,
+) -> Result<Vec<DirEntryWithStat>, AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable(state, "Deno.readDirWithStatsSync");
+  let path = PathBuf::from(path);
+
+  state
+    .borrow_mut::<P>()
+    .check_read(&path, "Deno.readDirWithStatsSync()")?;
+
+  let fs = state.borrow::<FileSystemRc>();
+  let entries = fs
+    .read_dir_with_stats_sync(&path)
+    .context_path("readdir", &path)?;
+
+  Ok(entries.into_iter().map(Into::into).collect())
+}
+
+#[op]
+async fn op_fs_read_dir_with_stats_async<P>(
+  state: Rc<RefCell<OpState>>,
+  path: String,
+) -> Result<Vec<DirEntryWithStat>, AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable2(&state, "Deno.readDirWithStats");
+  let path = PathBuf::from(path);
+
+  let fs = {
+    let mut state = state.borrow_mut();
+    state
+      .borrow_mut::<P>()
+      .check_read(&path, "Deno.readDirWithStats()")?;
+    state.borrow::<FileSystemRc>().clone()
+  };
+
+  let entries = fs
+    .read_dir_with_stats_async(path.clone())
+    .await
+    .context_path("readdir", &path)?;
+
+  Ok(entries.into_iter().map(Into::into).collect())
+}
+
+struct GlobResource {
+  walker: AsyncRefCell<GlobWalker>,
+  cancel: CancelHandle,
+}
+
+impl Resource for GlobResource {
+  fn name(&self) -> Cow<str> {
+    "glob".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel.cancel();
+  }
+}
+
+#[op]
+fn op_fs_glob_open<P>(
+  state: &mut OpState,
+  root: Option<String>,
+  pattern: String,
+  options: GlobOptions,
+) -> Result<ResourceId, AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable(state, "Deno.glob");
+  let fs = state.borrow::<FileSystemRc>().clone();
+  let root = match root {
+    Some(root) => PathBuf::from(root),
+    None => fs.cwd().context("cwd")?,
+  };
+
+  let (walker, start) = GlobWalker::new(fs, &root, &pattern, &options)?;
+  state.borrow_mut::<P>().check_read(&start, "Deno.glob()")?;
+
+  let rid = state.resource_table.add(GlobResource {
+    walker: AsyncRefCell::new(walker),
+    cancel: Default::default(),
+  });
+  Ok(rid)
+}
+
+#[op]
+async fn op_fs_glob_next(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+) -> Result<Option<Vec<GlobEntry>>, AnyError> {
+  let resource = state.borrow().resource_table.get::<GlobResource>(rid)?;
+  let mut walker = RcRef::map(&resource, |r| &r.walker).borrow_mut().await;
+  let cancel = RcRef::map(&resource, |r| &r.cancel);
+  walker
+    .next()
+    .or_cancel(cancel)
+    .await?
+    .map_err(|(path, err)| err.context(format!("readdir '{}'", path.display())))
+}
+
 #[op]
 fn op_fs_rename_sync<P>(
   state: &mut OpState,
@@ -800,6 +997,8 @@ fn op_fs_utime_sync<P>(
   atime_nanos: u32,
   mtime_secs: i64,
   mtime_nanos: u32,
+  btime_secs: Option<i64>,
+  btime_nanos: u32,
 ) -> Result<(), AnyError>
 where
   P: FsPermissions + 'static,
@@ -809,6 +1008,11 @@ where
   state.borrow_mut::<P>().check_write(&path, "Deno.utime()")?;
 
   let fs = state.borrow::<FileSystemRc>();
+  // Set first, as macOS moves the birthtime back to an earlier mtime.
+  if let Some(btime_secs) = btime_secs {
+    fs.set_birthtime_sync(&path, btime_secs, btime_nanos)
+      .context_path("utime", &path)?;
+  }
   fs.utime_sync(&path, atime_secs, atime_nanos, mtime_secs, mtime_nanos)
     .context_path("utime", &path)?;
 
@@ -823,6 +1027,8 @@ async fn op_fs_utime_async<P>(
   atime_nanos: u32,
   mtime_secs: i64,
   mtime_nanos: u32,
+  btime_secs: Option<i64>,
+  btime_nanos: u32,
 ) -> Result<(), AnyError>
 where
   P: FsPermissions + 'static,
@@ -835,6 +1041,11 @@ where
     state.borrow::<FileSystemRc>().clone()
   };
 
+  if let Some(btime_secs) = btime_secs {
+    fs.set_birthtime_async(path.clone(), btime_secs, btime_nanos)
+      .await
+      .context_path("utime", &path)?;
+  }
   fs.utime_async(
     path.clone(),
     atime_secs,
@@ -858,7 +1069,7 @@ fn op_fs_make_temp_dir_sync<P>(
 where
   P: FsPermissions + 'static,
 {
-  let (dir, fs) = make_temp_check_sync::<P>(state, dir)?;
+  let (dir, fs) = make_temp_check_sync::<P>(state, dir, "Deno.makeTempDir()")?;
 
   let mut rng = thread_rng();
 
@@ -891,7 +1102,7 @@ async fn op_fs_make_temp_dir_async<P>(
 where
   P: FsPermissions + 'static,
 {
-  let (dir, fs) = make_temp_check_async::<P>(state, dir)?;
+  let (dir, fs) = make_temp_check_async::<P>(state, dir, "Deno.makeTempDir()")?;
 
   let mut rng = thread_rng();
 
@@ -924,7 +1135,7 @@ fn op_fs_make_temp_file_sync<P>(
 where
   P: FsPermissions + 'static,
 {
-  let (dir, fs) = make_temp_check_sync::<P>(state, dir)?;
+  let (dir, fs) = make_temp_check_sync::<P>(state, dir, "Deno.makeTempFile()")?;
 
   let open_opts = OpenOptions {
     write: true,
@@ -964,7 +1175,8 @@ async fn op_fs_make_temp_file_async<P>(
 where
   P: FsPermissions + 'static,
 {
-  let (dir, fs) = make_temp_check_async::<P>(state, dir)?;
+  let (dir, fs) =
+    make_temp_check_async::<P>(state, dir, "Deno.makeTempFile()")?;
 
   let open_opts = OpenOptions {
     write: true,
@@ -993,9 +1205,52 @@ where
   .context("tmpfile")
 }
 
+#[op]
+fn op_fs_open_tmp_file_sync<P>(
+  state: &mut OpState,
+  dir: Option<String>,
+) -> Result<ResourceId, AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable(state, "Deno.openTmpFileSync");
+  let (dir, fs) =
+    make_temp_check_sync::<P>(state, dir, "Deno.openTmpFileSync()")?;
+  let file = fs.open_tmp_file_sync(&dir).context_path("open", &dir)?;
+
+  let rid = state
+    .resource_table
+    .add(FileResource::new(file, "fsFile".to_string()));
+  Ok(rid)
+}
+
+#[op]
+async fn op_fs_open_tmp_file_async<P>(
+  state: Rc<RefCell<OpState>>,
+  dir: Option<String>,
+) -> Result<ResourceId, AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable2(&state, "Deno.openTmpFile");
+  let (dir, fs) =
+    make_temp_check_async::<P>(state.clone(), dir, "Deno.openTmpFile()")?;
+  let file = fs
+    .open_tmp_file_async(dir.clone())
+    .await
+    .context_path("open", &dir)?;
+
+  let rid = state
+    .borrow_mut()
+    .resource_table
+    .add(FileResource::new(file, "fsFile".to_string()));
+  Ok(rid)
+}
+
 fn make_temp_check_sync<P>(
   state: &mut OpState,
   dir: Option<String>,
+  api_name: &str,
 ) -> Result<(PathBuf, FileSystemRc), AnyError>
 where
   P: FsPermissions + 'static,
@@ -1004,18 +1259,14 @@ where
   let dir = match dir {
     Some(dir) => {
       let dir = PathBuf::from(dir);
-      state
-        .borrow_mut::<P>()
-        .check_write(&dir, "Deno.makeTempFile()")?;
+      state.borrow_mut::<P>().check_write(&dir, api_name)?;
       dir
     }
     None => {
       let dir = fs.tmp_dir().context("tmpdir")?;
-      state.borrow_mut::<P>().check_write_blind(
-        &dir,
-        "TMP",
-        "Deno.makeTempFile()",
-      )?;
+      state
+        .borrow_mut::<P>()
+        .check_write_blind(&dir, "TMP", api_name)?;
       dir
     }
   };
@@ -1025,6 +1276,7 @@ where
 fn make_temp_check_async<P>(
   state: Rc<RefCell<OpState>>,
   dir: Option<String>,
+  api_name: &str,
 ) -> Result<(PathBuf, FileSystemRc), AnyError>
 where
   P: FsPermissions + 'static,
@@ -1034,18 +1286,14 @@ where
   let dir = match dir {
     Some(dir) => {
       let dir = PathBuf::from(dir);
-      state
-        .borrow_mut::<P>()
-        .check_write(&dir, "Deno.makeTempFile()")?;
+      state.borrow_mut::<P>().check_write(&dir, api_name)?;
       dir
     }
     None => {
       let dir = fs.tmp_dir().context("tmpdir")?;
-      state.borrow_mut::<P>().check_write_blind(
-        &dir,
-        "TMP",
-        "Deno.makeTempFile()",
-      )?;
+      state
+        .borrow_mut::<P>()
+        .check_write_blind(&dir, "TMP", api_name)?;
       dir
     }
   };
@@ -1140,6 +1388,58 @@ where
   Ok(())
 }
 
+#[op]
+fn op_fs_write_file_atomic_sync<P>(
+  state: &mut OpState,
+  path: String,
+  mode: Option<u32>,
+  data: ZeroCopyBuf,
+) -> Result<(), AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable(state, "Deno.writeFileAtomicSync");
+  let path = PathBuf::from(path);
+
+  state
+    .borrow_mut::<P>()
+    .check_write(&path, "Deno.writeFileAtomicSync()")?;
+
+  let fs = state.borrow::<FileSystemRc>();
+  fs.write_file_atomic_sync(&path, mode, &data)
+    .context_path("writefile", &path)?;
+
+  Ok(())
+}
+
+#[op]
+async fn op_fs_write_file_atomic_async<P>(
+  state: Rc<RefCell<OpState>>,
+  path: String,
+  mode: Option<u32>,
+  data: ZeroCopyBuf,
+) -> Result<(), AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable2(&state, "Deno.writeFileAtomic");
+  let path = PathBuf::from(path);
+
+  let fs = {
+    let mut state = state.borrow_mut();
+    state
+      .borrow_mut::<P>()
+      .check_write(&path, "Deno.writeFileAtomic()")?;
+    state.borrow::<FileSystemRc>().clone()
+  };
+
+  fs.write_file_atomic_async(path.clone(), mode, data.to_vec())
+    .await
+    .context_path("writefile", &path)?;
+
+  Ok(())
+}
+
 #[op]
 fn op_fs_read_file_sync<P>(
   state: &mut OpState,
@@ -1410,6 +1710,461 @@ async fn op_fs_funlock_async(
   Ok(())
 }
 
+#[op]
+fn op_fs_flink_sync<P>(
+  state: &mut OpState,
+  rid: ResourceId,
+  path: String,
+) -> Result<(), AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable(state, "Deno.FsFile.linkSync");
+  let path = PathBuf::from(path);
+  state
+    .borrow_mut::<P>()
+    .check_write(&path, "Deno.FsFile.linkSync()")?;
+  let file = FileResource::get_file(state, rid)?;
+  file.link_sync(&path).context_path("link", &path)?;
+  Ok(())
+}
+
+#[op]
+async fn op_fs_flink_async<P>(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  path: String,
+) -> Result<(), AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable2(&state, "Deno.FsFile.link");
+  let path = PathBuf::from(path);
+  let file = {
+    let mut state = state.borrow_mut();
+    state
+      .borrow_mut::<P>()
+      .check_write(&path, "Deno.FsFile.link()")?;
+    FileResource::get_file(&state, rid)?
+  };
+  file
+    .link_async(path.clone())
+    .await
+    .context_path("link", &path)?;
+  Ok(())
+}
+
+#[op(v8)]
+fn op_fs_fmmap<'scope>(
+  scope: &mut v8::HandleScope<'scope>,
+  state: &mut OpState,
+  rid: ResourceId,
+  offset: u64,
+  len: Option<usize>,
+  shared: bool,
+) -> Result<serde_v8::Value<'scope>, AnyError> {
+  check_unstable(state, "Deno.FsFile.mmap");
+  let file = FileResource::get_file(state, rid)?;
+  let mmap = file.mmap_sync(offset, len, shared).context("mmap")?;
+  if mmap.is_empty() {
+    return Err(range_error("Can't map an empty range of a file"));
+  }
+  let buffer = state.borrow_mut::<MmapRegistry>().array_buffer(scope, mmap);
+  Ok(v8::Local::<v8::Value>::from(buffer).into())
+}
+
+fn get_mmap(state: &OpState, buffer: &[u8]) -> Result<SharedMmap, AnyError> {
+  state
+    .borrow::<MmapRegistry>()
+    .get(buffer)
+    .ok_or_else(|| type_error("The buffer isn't a memory mapped file"))
+}
+
+#[op]
+fn op_fs_msync_sync(
+  state: &mut OpState,
+  buffer: ZeroCopyBuf,
+) -> Result<(), AnyError> {
+  check_unstable(state, "Deno.msyncSync");
+  let mmap = get_mmap(state, &buffer)?;
+  mmap.flush().map_err(FsError::Io).context("msync")?;
+  Ok(())
+}
+
+#[op]
+async fn op_fs_msync_async(
+  state: Rc<RefCell<OpState>>,
+  buffer: ZeroCopyBuf,
+) -> Result<(), AnyError> {
+  check_unstable2(&state, "Deno.msync");
+  let mmap = get_mmap(&state.borrow(), &buffer)?;
+  // The mapping stays alive until the flush is done, even if the buffer is
+  // unmapped in the meantime.
+  tokio::task::spawn_blocking(move || mmap.flush())
+    .await?
+    .map_err(FsError::Io)
+    .context("msync")?;
+  Ok(())
+}
+
+#[op(v8)]
+fn op_fs_munmap<'scope>(
+  _scope: &mut v8::HandleScope<'scope>,
+  state: &mut OpState,
+  buffer: serde_v8::Value<'scope>,
+) -> Result<(), AnyError> {
+  check_unstable(state, "Deno.munmap");
+  let buffer = v8::Local::<v8::ArrayBuffer>::try_from(buffer.v8_value)
+    .map_err(|_| type_error("Expected an ArrayBuffer"))?;
+  if !state.borrow::<MmapRegistry>().is_mapped(&buffer) {
+    return Err(type_error("The buffer isn't a memory mapped file"));
+  }
+  // The memory is unmapped once the backing store is dropped, which is right
+  // away unless it's also used by an op.
+  buffer.detach(None);
+  Ok(())
+}
+
+#[op]
+fn op_fs_allocate_sync(
+  state: &mut OpState,
+  rid: ResourceId,
+  len: u64,
+) -> Result<(), AnyError> {
+  check_unstable(state, "Deno.FsFile.allocateSync");
+  let file = FileResource::get_file(state, rid)?;
+  file.allocate_sync(len)?;
+  Ok(())
+}
+
+#[op]
+async fn op_fs_allocate_async(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  len: u64,
+) -> Result<(), AnyError> {
+  check_unstable2(&state, "Deno.FsFile.allocate");
+  let file = FileResource::get_file(&state.borrow(), rid)?;
+  file.allocate_async(len).await?;
+  Ok(())
+}
+
+#[op]
+fn op_fs_punch_hole_sync(
+  state: &mut OpState,
+  rid: ResourceId,
+  offset: u64,
+  len: u64,
+) -> Result<(), AnyError> {
+  check_unstable(state, "Deno.FsFile.punchHoleSync");
+  let file = FileResource::get_file(state, rid)?;
+  file.punch_hole_sync(offset, len)?;
+  Ok(())
+}
+
+#[op]
+async fn op_fs_punch_hole_async(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  offset: u64,
+  len: u64,
+) -> Result<(), AnyError> {
+  check_unstable2(&state, "Deno.FsFile.punchHole");
+  let file = FileResource::get_file(&state.borrow(), rid)?;
+  file.punch_hole_async(offset, len).await?;
+  Ok(())
+}
+
+#[op]
+fn op_fs_get_xattr_sync<P>(
+  state: &mut OpState,
+  path: String,
+  name: &str,
+) -> Result<Option<ZeroCopyBuf>, AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable(state, "Deno.getXattrSync");
+  let path = PathBuf::from(path);
+
+  let permissions = state.borrow_mut::<P>();
+  permissions.check_read(&path, "Deno.getXattrSync()")?;
+
+  let fs = state.borrow::<FileSystemRc>();
+  let result = fs
+    .get_xattr_sync(&path, name)
+    .context_path("getxattr", &path)?;
+
+  Ok(result.map(Into::into))
+}
+
+#[op]
+async fn op_fs_get_xattr_async<P>(
+  state: Rc<RefCell<OpState>>,
+  path: String,
+  name: String,
+) -> Result<Option<ZeroCopyBuf>, AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable2(&state, "Deno.getXattr");
+  let path = PathBuf::from(path);
+
+  let fs = {
+    let mut state = state.borrow_mut();
+    let permissions = state.borrow_mut::<P>();
+    permissions.check_read(&path, "Deno.getXattr()")?;
+    state.borrow::<FileSystemRc>().clone()
+  };
+
+  let result = fs
+    .get_xattr_async(path.clone(), name)
+    .await
+    .context_path("getxattr", &path)?;
+
+  Ok(result.map(Into::into))
+}
+
+#[op]
+fn op_fs_set_xattr_sync<P>(
+  state: &mut OpState,
+  path: String,
+  name: &str,
+  value: &[u8],
+) -> Result<(), AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable(state, "Deno.setXattrSync");
+  let path = PathBuf::from(path);
+
+  let permissions = state.borrow_mut::<P>();
+  permissions.check_write(&path, "Deno.setXattrSync()")?;
+
+  let fs = state.borrow::<FileSystemRc>();
+  fs.set_xattr_sync(&path, name, value)
+    .context_path("setxattr", &path)?;
+
+  Ok(())
+}
+
+#[op]
+async fn op_fs_set_xattr_async<P>(
+  state: Rc<RefCell<OpState>>,
+  path: String,
+  name: String,
+  value: ZeroCopyBuf,
+) -> Result<(), AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable2(&state, "Deno.setXattr");
+  let path = PathBuf::from(path);
+
+  let fs = {
+    let mut state = state.borrow_mut();
+    let permissions = state.borrow_mut::<P>();
+    permissions.check_write(&path, "Deno.setXattr()")?;
+    state.borrow::<FileSystemRc>().clone()
+  };
+
+  fs.set_xattr_async(path.clone(), name, value.to_vec())
+    .await
+    .context_path("setxattr", &path)?;
+
+  Ok(())
+}
+
+#[op]
+fn op_fs_list_xattr_sync<P>(
+  state: &mut OpState,
+  path: String,
+) -> Result<Vec<String>, AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable(state, "Deno.listXattrSync");
+  let path = PathBuf::from(path);
+
+  let permissions = state.borrow_mut::<P>();
+  permissions.check_read(&path, "Deno.listXattrSync()")?;
+
+  let fs = state.borrow::<FileSystemRc>();
+  let result = fs.list_xattr_sync(&path).context_path("listxattr", &path)?;
+
+  Ok(result)
+}
+
+#[op]
+async fn op_fs_list_xattr_async<P>(
+  state: Rc<RefCell<OpState>>,
+  path: String,
+) -> Result<Vec<String>, AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable2(&state, "Deno.listXattr");
+  let path = PathBuf::from(path);
+
+  let fs = {
+    let mut state = state.borrow_mut();
+    let permissions = state.borrow_mut::<P>();
+    permissions.check_read(&path, "Deno.listXattr()")?;
+    state.borrow::<FileSystemRc>().clone()
+  };
+
+  let result = fs
+    .list_xattr_async(path.clone())
+    .await
+    .context_path("listxattr", &path)?;
+
+  Ok(result)
+}
+
+#[op]
+fn op_fs_remove_xattr_sync<P>(
+  state: &mut OpState,
+  path: String,
+  name: &str,
+) -> Result<(), AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable(state, "Deno.removeXattrSync");
+  let path = PathBuf::from(path);
+
+  let permissions = state.borrow_mut::<P>();
+  permissions.check_write(&path, "Deno.removeXattrSync()")?;
+
+  let fs = state.borrow::<FileSystemRc>();
+  fs.remove_xattr_sync(&path, name)
+    .context_path("removexattr", &path)?;
+
+  Ok(())
+}
+
+#[op]
+async fn op_fs_remove_xattr_async<P>(
+  state: Rc<RefCell<OpState>>,
+  path: String,
+  name: String,
+) -> Result<(), AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable2(&state, "Deno.removeXattr");
+  let path = PathBuf::from(path);
+
+  let fs = {
+    let mut state = state.borrow_mut();
+    let permissions = state.borrow_mut::<P>();
+    permissions.check_write(&path, "Deno.removeXattr()")?;
+    state.borrow::<FileSystemRc>().clone()
+  };
+
+  fs.remove_xattr_async(path.clone(), name)
+    .await
+    .context_path("removexattr", &path)?;
+
+  Ok(())
+}
+
+#[op]
+fn op_fs_fget_xattr_sync(
+  state: &mut OpState,
+  rid: ResourceId,
+  name: &str,
+) -> Result<Option<ZeroCopyBuf>, AnyError> {
+  check_unstable(state, "Deno.FsFile.getXattrSync");
+  let file = FileResource::get_file(state, rid)?;
+  let result = file.get_xattr_sync(name)?;
+  Ok(result.map(Into::into))
+}
+
+#[op]
+async fn op_fs_fget_xattr_async(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  name: String,
+) -> Result<Option<ZeroCopyBuf>, AnyError> {
+  check_unstable2(&state, "Deno.FsFile.getXattr");
+  let file = FileResource::get_file(&state.borrow(), rid)?;
+  let result = file.get_xattr_async(name).await?;
+  Ok(result.map(Into::into))
+}
+
+#[op]
+fn op_fs_fset_xattr_sync(
+  state: &mut OpState,
+  rid: ResourceId,
+  name: &str,
+  value: &[u8],
+) -> Result<(), AnyError> {
+  check_unstable(state, "Deno.FsFile.setXattrSync");
+  let file = FileResource::get_file(state, rid)?;
+  file.set_xattr_sync(name, value)?;
+  Ok(())
+}
+
+#[op]
+async fn op_fs_fset_xattr_async(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  name: String,
+  value: ZeroCopyBuf,
+) -> Result<(), AnyError> {
+  check_unstable2(&state, "Deno.FsFile.setXattr");
+  let file = FileResource::get_file(&state.borrow(), rid)?;
+  file.set_xattr_async(name, value.to_vec()).await?;
+  Ok(())
+}
+
+#[op]
+fn op_fs_flist_xattr_sync(
+  state: &mut OpState,
+  rid: ResourceId,
+) -> Result<Vec<String>, AnyError> {
+  check_unstable(state, "Deno.FsFile.listXattrSync");
+  let file = FileResource::get_file(state, rid)?;
+  let result = file.list_xattr_sync()?;
+  Ok(result)
+}
+
+#[op]
+async fn op_fs_flist_xattr_async(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+) -> Result<Vec<String>, AnyError> {
+  check_unstable2(&state, "Deno.FsFile.listXattr");
+  let file = FileResource::get_file(&state.borrow(), rid)?;
+  let result = file.list_xattr_async().await?;
+  Ok(result)
+}
+
+#[op]
+fn op_fs_fremove_xattr_sync(
+  state: &mut OpState,
+  rid: ResourceId,
+  name: &str,
+) -> Result<(), AnyError> {
+  check_unstable(state, "Deno.FsFile.removeXattrSync");
+  let file = FileResource::get_file(state, rid)?;
+  file.remove_xattr_sync(name)?;
+  Ok(())
+}
+
+#[op]
+async fn op_fs_fremove_xattr_async(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  name: String,
+) -> Result<(), AnyError> {
+  check_unstable2(&state, "Deno.FsFile.removeXattr");
+  let file = FileResource::get_file(&state.borrow(), rid)?;
+  file.remove_xattr_async(name).await?;
+  Ok(())
+}
+
 #[op]
 fn op_fs_ftruncate_sync(
   state: &mut OpState,
@@ -1440,8 +2195,13 @@ fn op_fs_futime_sync(
   atime_nanos: u32,
   mtime_secs: i64,
   mtime_nanos: u32,
+  btime_secs: Option<i64>,
+  btime_nanos: u32,
 ) -> Result<(), AnyError> {
   let file = FileResource::get_file(state, rid)?;
+  if let Some(btime_secs) = btime_secs {
+    file.clone().set_birthtime_sync(btime_secs, btime_nanos)?;
+  }
   file.utime_sync(atime_secs, atime_nanos, mtime_secs, mtime_nanos)?;
   Ok(())
 }
@@ -1454,8 +2214,16 @@ async fn op_fs_futime_async(
   atime_nanos: u32,
   mtime_secs: i64,
   mtime_nanos: u32,
+  btime_secs: Option<i64>,
+  btime_nanos: u32,
 ) -> Result<(), AnyError> {
   let file = FileResource::get_file(&state.borrow(), rid)?;
+  if let Some(btime_secs) = btime_secs {
+    file
+      .clone()
+      .set_birthtime_async(btime_secs, btime_nanos)
+      .await?;
+  }
   file
     .utime_async(atime_secs, atime_nanos, mtime_secs, mtime_nanos)
     .await?;
@@ -12,14 +12,21 @@ use std::rc::Rc;
 use deno_io::fs::File;
 use deno_io::fs::FsResult;
 use deno_io::fs::FsStat;
+use deno_io::xattr;
+use deno_io::xattr::XattrTarget;
 use deno_io::StdFileResourceInner;
 
+use crate::canonicalize::canonicalize_path_cached;
 use crate::interface::FsDirEntry;
+use crate::interface::FsDirEntryWithStat;
 use crate::interface::FsFileType;
+use crate::interface::FsStatFs;
+use crate::interface::FsStatFsFlags;
+use crate::trash;
 use crate::FileSystem;
 use crate::OpenOptions;
+use crate::ReflinkMode;
 
-#[cfg(not(unix))]
 use deno_io::fs::FsError;
 
 #[derive(Debug, Clone)]
@@ -91,6 +98,16 @@ impl FileSystem for RealFs {
     Ok(Rc::new(StdFileResourceInner::file(std_file)))
   }
 
+  fn open_tmp_file_sync(&self, dir: &Path) -> FsResult<Rc<dyn File>> {
+    let std_file = open_tmp_file(dir)?;
+    Ok(Rc::new(StdFileResourceInner::tmp_file(std_file)))
+  }
+  async fn open_tmp_file_async(&self, dir: PathBuf) -> FsResult<Rc<dyn File>> {
+    let std_file =
+      tokio::task::spawn_blocking(move || open_tmp_file(&dir)).await??;
+    Ok(Rc::new(StdFileResourceInner::tmp_file(std_file)))
+  }
+
   fn mkdir_sync(
     &self,
     path: &Path,
@@ -139,11 +156,28 @@ impl FileSystem for RealFs {
     tokio::task::spawn_blocking(move || remove(&path, recursive)).await?
   }
 
-  fn copy_file_sync(&self, from: &Path, to: &Path) -> FsResult<()> {
-    copy_file(from, to)
+  fn trash_sync(&self, path: &Path) -> FsResult<()> {
+    trash::trash(path)
+  }
+  async fn trash_async(&self, path: PathBuf) -> FsResult<()> {
+    tokio::task::spawn_blocking(move || trash::trash(&path)).await?
+  }
+
+  fn copy_file_sync(
+    &self,
+    from: &Path,
+    to: &Path,
+    reflink: ReflinkMode,
+  ) -> FsResult<()> {
+    copy_file(from, to, reflink)
   }
-  async fn copy_file_async(&self, from: PathBuf, to: PathBuf) -> FsResult<()> {
-    tokio::task::spawn_blocking(move || copy_file(&from, &to)).await?
+  async fn copy_file_async(
+    &self,
+    from: PathBuf,
+    to: PathBuf,
+    reflink: ReflinkMode,
+  ) -> FsResult<()> {
+    tokio::task::spawn_blocking(move || copy_file(&from, &to, reflink)).await?
   }
 
   fn stat_sync(&self, path: &Path) -> FsResult<FsStat> {
@@ -155,6 +189,13 @@ impl FileSystem for RealFs {
       .map(Into::into)
   }
 
+  fn stat_fs_sync(&self, path: &Path) -> FsResult<FsStatFs> {
+    stat_fs(path)
+  }
+  async fn stat_fs_async(&self, path: PathBuf) -> FsResult<FsStatFs> {
+    tokio::task::spawn_blocking(move || stat_fs(&path)).await?
+  }
+
   fn lstat_sync(&self, path: &Path) -> FsResult<FsStat> {
     lstat(path).map(Into::into)
   }
@@ -170,6 +211,9 @@ impl FileSystem for RealFs {
   async fn realpath_async(&self, path: PathBuf) -> FsResult<PathBuf> {
     tokio::task::spawn_blocking(move || realpath(&path)).await?
   }
+  fn realpath_sync_cached(&self, path: &Path) -> FsResult<PathBuf> {
+    Ok(canonicalize_path_cached(path)?)
+  }
 
   fn read_dir_sync(&self, path: &Path) -> FsResult<Vec<FsDirEntry>> {
     read_dir(path)
@@ -178,6 +222,19 @@ impl FileSystem for RealFs {
     tokio::task::spawn_blocking(move || read_dir(&path)).await?
   }
 
+  fn read_dir_with_stats_sync(
+    &self,
+    path: &Path,
+  ) -> FsResult<Vec<FsDirEntryWithStat>> {
+    read_dir_with_stats(path)
+  }
+  async fn read_dir_with_stats_async(
+    &self,
+    path: PathBuf,
+  ) -> FsResult<Vec<FsDirEntryWithStat>> {
+    tokio::task::spawn_blocking(move || read_dir_with_stats(&path)).await?
+  }
+
   fn rename_sync(&self, oldpath: &Path, newpath: &Path) -> FsResult<()> {
     fs::rename(oldpath, newpath).map_err(Into::into)
   }
@@ -265,6 +322,83 @@ impl FileSystem for RealFs {
     })
     .await?
   }
+  fn set_birthtime_sync(
+    &self,
+    path: &Path,
+    secs: i64,
+    nanos: u32,
+  ) -> FsResult<()> {
+    set_birthtime(path, secs, nanos)
+  }
+  async fn set_birthtime_async(
+    &self,
+    path: PathBuf,
+    secs: i64,
+    nanos: u32,
+  ) -> FsResult<()> {
+    tokio::task::spawn_blocking(move || set_birthtime(&path, secs, nanos))
+      .await?
+  }
+
+  fn get_xattr_sync(
+    &self,
+    path: &Path,
+    name: &str,
+  ) -> FsResult<Option<Vec<u8>>> {
+    xattr::get(XattrTarget::Path(path), name)
+  }
+  async fn get_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+  ) -> FsResult<Option<Vec<u8>>> {
+    tokio::task::spawn_blocking(move || {
+      xattr::get(XattrTarget::Path(&path), &name)
+    })
+    .await?
+  }
+
+  fn set_xattr_sync(
+    &self,
+    path: &Path,
+    name: &str,
+    value: &[u8],
+  ) -> FsResult<()> {
+    xattr::set(XattrTarget::Path(path), name, value)
+  }
+  async fn set_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+    value: Vec<u8>,
+  ) -> FsResult<()> {
+    tokio::task::spawn_blocking(move || {
+      xattr::set(XattrTarget::Path(&path), &name, &value)
+    })
+    .await?
+  }
+
+  fn list_xattr_sync(&self, path: &Path) -> FsResult<Vec<String>> {
+    xattr::list(XattrTarget::Path(path))
+  }
+  async fn list_xattr_async(&self, path: PathBuf) -> FsResult<Vec<String>> {
+    tokio::task::spawn_blocking(move || xattr::list(XattrTarget::Path(&path)))
+      .await?
+  }
+
+  fn remove_xattr_sync(&self, path: &Path, name: &str) -> FsResult<()> {
+    xattr::remove(XattrTarget::Path(path), name)
+  }
+  async fn remove_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+  ) -> FsResult<()> {
+    tokio::task::spawn_blocking(move || {
+      xattr::remove(XattrTarget::Path(&path), &name)
+    })
+    .await?
+  }
 
   fn write_file_sync(
     &self,
@@ -303,6 +437,24 @@ impl FileSystem for RealFs {
     .await?
   }
 
+  fn write_file_atomic_sync(
+    &self,
+    path: &Path,
+    mode: Option<u32>,
+    data: &[u8],
+  ) -> FsResult<()> {
+    write_file_atomic(path, mode, data)
+  }
+  async fn write_file_atomic_async(
+    &self,
+    path: PathBuf,
+    mode: Option<u32>,
+    data: Vec<u8>,
+  ) -> FsResult<()> {
+    tokio::task::spawn_blocking(move || write_file_atomic(&path, mode, &data))
+      .await?
+  }
+
   fn read_file_sync(&self, path: &Path) -> FsResult<Vec<u8>> {
     fs::read(path).map_err(Into::into)
   }
@@ -397,79 +549,812 @@ fn remove(path: &Path, recursive: bool) -> FsResult<()> {
   res.map_err(Into::into)
 }
 
-fn copy_file(from: &Path, to: &Path) -> FsResult<()> {
+fn copy_file(from: &Path, to: &Path, reflink: ReflinkMode) -> FsResult<()> {
+  match reflink {
+    ReflinkMode::Auto => {}
+    ReflinkMode::Always => {
+      return clone_file(from, to).map_err(|err| {
+        if is_clone_unsupported(&err) {
+          FsError::NotSupported
+        } else {
+          err.into()
+        }
+      });
+    }
+    // std::fs::copy() lets the file system share the data of the files on
+    // Linux (with copy_file_range()) and macOS (with fclonefileat()).
+    ReflinkMode::Never => return copy_file_contents(from, to),
+  }
+
   #[cfg(target_os = "macos")]
   {
-    use libc::clonefile;
-    use libc::stat;
-    use libc::unlink;
-    use std::ffi::CString;
-    use std::io::Read;
+    // std::fs::copy does open() + fcopyfile() on macOS. We try to use
+    // clonefile() instead, which is more efficient. Both are an overkill for
+    // < 128KB files, so those get a regular copy.
+    if fs::metadata(from)?.len() <= 128 * 1024 {
+      return copy_file_contents(from, to);
+    }
+    if clone_file(from, to).is_ok() {
+      return Ok(());
+    }
+    // clonefile() failed, fall back to std::fs::copy().
+  }
+
+  #[cfg(any(target_os = "linux", windows))]
+  if clone_file(from, to).is_ok() {
+    return Ok(());
+  }
+
+  fs::copy(from, to)?;
+
+  Ok(())
+}
+
+/// Copies the contents and permissions of a file with reads and writes.
+fn copy_file_contents(from: &Path, to: &Path) -> FsResult<()> {
+  use std::io::Read;
+
+  let mut buf = [0u8; 128 * 1024];
+  let mut from_file = fs::File::open(from)?;
+  let perm = from_file.metadata()?.permissions();
+
+  let mut options = fs::OpenOptions::new();
+  options.write(true).create(true).truncate(true);
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::fs::PermissionsExt;
+    // create the file with the correct mode right away
+    options.mode(perm.mode());
+  }
+  let mut to_file = options.open(to)?;
+  let writer_metadata = to_file.metadata()?;
+  loop {
+    let nread = from_file.read(&mut buf)?;
+    if nread == 0 {
+      break;
+    }
+    to_file.write_all(&buf[..nread])?;
+  }
+  if writer_metadata.is_file() {
+    // Set the correct file permissions, in case the file already existed.
+    // Don't set the permissions on already existing non-files like
+    // pipes/FIFOs or device nodes.
+    to_file.set_permissions(perm)?;
+  }
+  Ok(())
+}
+
+/// Opens the destination of a clone for writing, without truncating it, so
+/// that it is left intact when the clone fails. Returns whether the file was
+/// created.
+#[cfg(any(target_os = "linux", windows))]
+fn open_clone_destination(
+  to: &Path,
+  perm: &fs::Permissions,
+) -> io::Result<(fs::File, bool)> {
+  let mut options = fs::OpenOptions::new();
+  options.write(true);
+  #[cfg(unix)]
+  {
     use std::os::unix::fs::OpenOptionsExt;
     use std::os::unix::fs::PermissionsExt;
-    use std::os::unix::prelude::OsStrExt;
+    options.mode(perm.mode());
+  }
+  #[cfg(not(unix))]
+  let _ = perm;
+  match options.clone().create_new(true).open(to) {
+    Ok(file) => Ok((file, true)),
+    Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+      Ok((options.open(to)?, false))
+    }
+    Err(err) => Err(err),
+  }
+}
+
+/// Clones a file with the FICLONE ioctl, which btrfs and XFS support.
+#[cfg(target_os = "linux")]
+fn clone_file(from: &Path, to: &Path) -> io::Result<()> {
+  use std::os::unix::io::AsRawFd;
+
+  let from_file = fs::File::open(from)?;
+  let metadata = from_file.metadata()?;
+  let perm = metadata.permissions();
+  let (to_file, created) = open_clone_destination(to, &perm)?;
+  // SAFETY: both file descriptors are open.
+  let ret = unsafe {
+    libc::ioctl(
+      to_file.as_raw_fd(),
+      libc::FICLONE as _,
+      from_file.as_raw_fd(),
+    )
+  };
+  if ret != 0 {
+    let err = io::Error::last_os_error();
+    drop(to_file);
+    if created {
+      let _ = fs::remove_file(to);
+    }
+    return Err(err);
+  }
+  // The clone doesn't shrink a destination that was longer than the source.
+  to_file.set_len(metadata.len())?;
+  if to_file.metadata()?.is_file() {
+    to_file.set_permissions(perm)?;
+  }
+  Ok(())
+}
 
-    let from_str = CString::new(from.as_os_str().as_bytes()).unwrap();
-    let to_str = CString::new(to.as_os_str().as_bytes()).unwrap();
+/// Clones a file with clonefile(), which APFS supports.
+#[cfg(target_os = "macos")]
+fn clone_file(from: &Path, to: &Path) -> io::Result<()> {
+  use libc::clonefile;
+  use libc::unlink;
+  use std::ffi::CString;
+  use std::os::unix::prelude::OsStrExt;
 
-    // SAFETY: `from` and `to` are valid C strings.
-    // std::fs::copy does open() + fcopyfile() on macOS. We try to use
-    // clonefile() instead, which is more efficient.
-    unsafe {
-      let mut st = std::mem::zeroed();
-      let ret = stat(from_str.as_ptr(), &mut st);
-      if ret != 0 {
-        return Err(io::Error::last_os_error().into());
-      }
+  let from_str = CString::new(from.as_os_str().as_bytes()).unwrap();
+  let to_str = CString::new(to.as_os_str().as_bytes()).unwrap();
 
-      if st.st_size > 128 * 1024 {
-        // Try unlink. If it fails, we are going to try clonefile() anyway.
-        let _ = unlink(to_str.as_ptr());
-        // Matches rust stdlib behavior for io::copy.
-        // https://github.com/rust-lang/rust/blob/3fdd578d72a24d4efc2fe2ad18eec3b6ba72271e/library/std/src/sys/unix/fs.rs#L1613-L1616
-        if clonefile(from_str.as_ptr(), to_str.as_ptr(), 0) == 0 {
-          return Ok(());
-        }
-      } else {
-        // Do a regular copy. fcopyfile() is an overkill for < 128KB
-        // files.
-        let mut buf = [0u8; 128 * 1024];
-        let mut from_file = fs::File::open(from)?;
-        let perm = from_file.metadata()?.permissions();
-
-        let mut to_file = fs::OpenOptions::new()
-          // create the file with the correct mode right away
-          .mode(perm.mode())
-          .write(true)
-          .create(true)
-          .truncate(true)
-          .open(to)?;
-        let writer_metadata = to_file.metadata()?;
-        if writer_metadata.is_file() {
-          // Set the correct file permissions, in case the file already existed.
-          // Don't set the permissions on already existing non-files like
-          // pipes/FIFOs or device nodes.
-          to_file.set_permissions(perm)?;
-        }
-        loop {
-          let nread = from_file.read(&mut buf)?;
-          if nread == 0 {
-            break;
-          }
-          to_file.write_all(&buf[..nread])?;
-        }
-        return Ok(());
-      }
+  // SAFETY: `from` and `to` are valid C strings.
+  unsafe {
+    // clonefile() fails when the destination exists. If unlinking it fails,
+    // we are going to try clonefile() anyway.
+    let _ = unlink(to_str.as_ptr());
+    // Matches rust stdlib behavior for io::copy.
+    // https://github.com/rust-lang/rust/blob/3fdd578d72a24d4efc2fe2ad18eec3b6ba72271e/library/std/src/sys/unix/fs.rs#L1613-L1616
+    if clonefile(from_str.as_ptr(), to_str.as_ptr(), 0) != 0 {
+      return Err(io::Error::last_os_error());
     }
+  }
+  Ok(())
+}
 
-    // clonefile() failed, fall back to std::fs::copy().
+/// Clones a file with block cloning, which ReFS supports. See
+/// https://learn.microsoft.com/en-us/windows/win32/fileio/block-cloning.
+#[cfg(windows)]
+fn clone_file(from: &Path, to: &Path) -> io::Result<()> {
+  use std::os::windows::fs::MetadataExt;
+  use std::os::windows::io::AsRawHandle;
+  use winapi::shared::minwindef::DWORD;
+  use winapi::shared::winerror::ERROR_NOT_SAME_DEVICE;
+  use winapi::shared::winerror::ERROR_NOT_SUPPORTED;
+  use winapi::um::fileapi::GetVolumeInformationByHandleW;
+  use winapi::um::ioapiset::DeviceIoControl;
+  use winapi::um::winioctl::FSCTL_GET_INTEGRITY_INFORMATION;
+  use winapi::um::winioctl::FSCTL_SET_SPARSE;
+  use winapi::um::winnt::FILE_ATTRIBUTE_SPARSE_FILE;
+  use winapi::um::winnt::FILE_SUPPORTS_BLOCK_REFCOUNTING;
+  use winapi::um::winnt::HANDLE;
+
+  // winapi doesn't define these.
+  const FSCTL_DUPLICATE_EXTENTS_TO_FILE: DWORD = 0x98344;
+  #[repr(C)]
+  #[derive(Default)]
+  struct IntegrityInformationBuffer {
+    checksum_algorithm: u16,
+    reserved: u16,
+    flags: u32,
+    checksum_chunk_size_in_bytes: u32,
+    cluster_size_in_bytes: u32,
+  }
+  #[repr(C)]
+  struct DuplicateExtentsData {
+    file_handle: HANDLE,
+    source_file_offset: i64,
+    target_file_offset: i64,
+    byte_count: i64,
+  }
+  // The byte count of a single FSCTL_DUPLICATE_EXTENTS_TO_FILE must be less
+  // than 4GB, and a multiple of the cluster size.
+  const MAX_CHUNK_SIZE: u64 = 1 << 31;
+
+  /// Returns the serial number and flags of the volume of a file.
+  fn volume_information(file: &fs::File) -> io::Result<(DWORD, DWORD)> {
+    let mut serial_number = 0;
+    let mut flags = 0;
+    // SAFETY: winapi call, the buffers for the names are optional.
+    let ret = unsafe {
+      GetVolumeInformationByHandleW(
+        file.as_raw_handle() as HANDLE,
+        std::ptr::null_mut(),
+        0,
+        &mut serial_number,
+        std::ptr::null_mut(),
+        &mut flags,
+        std::ptr::null_mut(),
+        0,
+      )
+    };
+    if ret == 0 {
+      return Err(io::Error::last_os_error());
+    }
+    Ok((serial_number, flags))
   }
 
-  fs::copy(from, to)?;
+  fn device_io_control<T>(
+    file: &fs::File,
+    code: DWORD,
+    input: Option<&mut T>,
+    output: Option<&mut T>,
+  ) -> io::Result<()> {
+    let size = std::mem::size_of::<T>() as DWORD;
+    let (input, input_size) = match input {
+      Some(input) => (input as *mut T as *mut _, size),
+      None => (std::ptr::null_mut(), 0),
+    };
+    let (output, output_size) = match output {
+      Some(output) => (output as *mut T as *mut _, size),
+      None => (std::ptr::null_mut(), 0),
+    };
+    let mut bytes_returned = 0;
+    // SAFETY: winapi call, the buffers are valid for their sizes.
+    let ret = unsafe {
+      DeviceIoControl(
+        file.as_raw_handle() as HANDLE,
+        code,
+        input,
+        input_size,
+        output,
+        output_size,
+        &mut bytes_returned,
+        std::ptr::null_mut(),
+      )
+    };
+    if ret == 0 {
+      return Err(io::Error::last_os_error());
+    }
+    Ok(())
+  }
+
+  let from_file = fs::File::open(from)?;
+  let metadata = from_file.metadata()?;
+  let (serial_number, flags) = volume_information(&from_file)?;
+  if flags & FILE_SUPPORTS_BLOCK_REFCOUNTING == 0 {
+    return Err(io::Error::from_raw_os_error(ERROR_NOT_SUPPORTED as i32));
+  }
+  let mut integrity_information = IntegrityInformationBuffer::default();
+  device_io_control(
+    &from_file,
+    FSCTL_GET_INTEGRITY_INFORMATION,
+    None,
+    Some(&mut integrity_information),
+  )?;
+  let cluster_size = integrity_information.cluster_size_in_bytes as u64;
+  if cluster_size == 0 {
+    return Err(io::Error::from_raw_os_error(ERROR_NOT_SUPPORTED as i32));
+  }
+
+  let perm = metadata.permissions();
+  let (to_file, created) = open_clone_destination(to, &perm)?;
+  let result = (|| {
+    if volume_information(&to_file)?.0 != serial_number {
+      return Err(io::Error::from_raw_os_error(ERROR_NOT_SAME_DEVICE as i32));
+    }
+    if metadata.file_attributes() & FILE_ATTRIBUTE_SPARSE_FILE != 0 {
+      // The destination of a sparse file must be sparse too.
+      device_io_control::<()>(&to_file, FSCTL_SET_SPARSE, None, None)?;
+    }
+    // The cloned regions have to be inside of the destination.
+    to_file.set_len(metadata.len())?;
+    // The last cluster is cloned whole, even past the end of the file.
+    let len = (metadata.len() + cluster_size - 1) / cluster_size * cluster_size;
+    let mut offset = 0;
+    while offset < len {
+      let byte_count = std::cmp::min(len - offset, MAX_CHUNK_SIZE);
+      let mut data = DuplicateExtentsData {
+        file_handle: from_file.as_raw_handle() as HANDLE,
+        source_file_offset: offset as i64,
+        target_file_offset: offset as i64,
+        byte_count: byte_count as i64,
+      };
+      device_io_control(
+        &to_file,
+        FSCTL_DUPLICATE_EXTENTS_TO_FILE,
+        Some(&mut data),
+        None,
+      )?;
+      offset += byte_count;
+    }
+    to_file.set_permissions(perm)
+  })();
+  if result.is_err() {
+    drop(to_file);
+    if created {
+      let _ = fs::remove_file(to);
+    }
+  }
+  result
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn clone_file(_from: &Path, _to: &Path) -> io::Result<()> {
+  Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Whether cloning a file failed because the file system can't clone it, as
+/// opposed to, say, the file not being found.
+fn is_clone_unsupported(err: &io::Error) -> bool {
+  if err.kind() == io::ErrorKind::Unsupported {
+    return true;
+  }
+  #[cfg(unix)]
+  {
+    // ENOTSUP and EOPNOTSUPP are the same on Linux, but not on macOS.
+    let codes = [
+      libc::ENOTSUP,
+      libc::EOPNOTSUPP,
+      libc::EXDEV,
+      libc::EINVAL,
+      libc::ENOTTY,
+      libc::ENOSYS,
+    ];
+    err
+      .raw_os_error()
+      .map_or(false, |code| codes.contains(&code))
+  }
+  #[cfg(windows)]
+  {
+    use winapi::shared::winerror::ERROR_INVALID_FUNCTION;
+    use winapi::shared::winerror::ERROR_NOT_SAME_DEVICE;
+    use winapi::shared::winerror::ERROR_NOT_SUPPORTED;
+    matches!(
+      err.raw_os_error().map(|code| code as u32),
+      Some(
+        ERROR_INVALID_FUNCTION | ERROR_NOT_SAME_DEVICE | ERROR_NOT_SUPPORTED
+      )
+    )
+  }
+}
+
+// The types of the fields of statvfs depend on the architecture.
+#[cfg(target_os = "linux")]
+#[allow(clippy::unnecessary_cast)]
+fn stat_fs(path: &Path) -> FsResult<FsStatFs> {
+  use std::ffi::CString;
+  use std::os::unix::ffi::OsStrExt;
+
+  let path =
+    CString::new(path.as_os_str().as_bytes()).map_err(io::Error::from)?;
+  // SAFETY: `path` is a valid C string, and the structs are plain data that
+  // the calls fill in.
+  let (vfs, fs) = unsafe {
+    let mut vfs: libc::statvfs = std::mem::zeroed();
+    if libc::statvfs(path.as_ptr(), &mut vfs) != 0 {
+      return Err(io::Error::last_os_error().into());
+    }
+    // statvfs() doesn't return the type of the file system.
+    let mut fs: libc::statfs = std::mem::zeroed();
+    if libc::statfs(path.as_ptr(), &mut fs) != 0 {
+      return Err(io::Error::last_os_error().into());
+    }
+    (vfs, fs)
+  };
+
+  let block_size = vfs.f_frsize as u64;
+  let flag = |flag| vfs.f_flag & flag != 0;
+  Ok(FsStatFs {
+    fs_type: linux_fs_type(fs.f_type as u32),
+    block_size,
+    blocks: vfs.f_blocks as u64,
+    blocks_free: vfs.f_bfree as u64,
+    blocks_available: vfs.f_bavail as u64,
+    total_bytes: vfs.f_blocks as u64 * block_size,
+    free_bytes: vfs.f_bfree as u64 * block_size,
+    available_bytes: vfs.f_bavail as u64 * block_size,
+    files: Some(vfs.f_files as u64),
+    files_free: Some(vfs.f_ffree as u64),
+    flags: FsStatFsFlags {
+      read_only: flag(libc::ST_RDONLY),
+      no_suid: flag(libc::ST_NOSUID),
+      no_dev: flag(libc::ST_NODEV),
+      no_exec: flag(libc::ST_NOEXEC),
+      synchronous: flag(libc::ST_SYNCHRONOUS),
+      no_atime: flag(libc::ST_NOATIME),
+    },
+  })
+}
+
+/// Returns the name of a file system from its magic number, see statfs(2).
+#[cfg(target_os = "linux")]
+fn linux_fs_type(magic: u32) -> String {
+  let name = match magic {
+    0x0000_9660 => "iso9660",
+    0x0000_4d44 => "vfat",
+    0x0000_6969 => "nfs",
+    0x0000_9fa0 => "proc",
+    0x0000_ef53 => "ext4",
+    0x0102_1994 => "tmpfs",
+    0x2011_bab0 => "exfat",
+    0x2fc1_2fc1 => "zfs",
+    0x5346_544e => "ntfs",
+    0x5846_5342 => "xfs",
+    0x6265_6572 => "sysfs",
+    0x6573_5546 => "fuse",
+    0x6367_7270 => "cgroup2",
+    0x7371_7368 => "squashfs",
+    0x794c_7630 => "overlay",
+    0x8584_58f6 => "ramfs",
+    0x9123_683e => "btrfs",
+    0xf2f5_2010 => "f2fs",
+    0xfe53_4d42 => "smb2",
+    0xff53_4d42 => "cifs",
+    _ => return format!("0x{magic:x}"),
+  };
+  name.to_string()
+}
+
+#[cfg(target_os = "macos")]
+fn stat_fs(path: &Path) -> FsResult<FsStatFs> {
+  use std::ffi::CStr;
+  use std::ffi::CString;
+  use std::os::unix::ffi::OsStrExt;
+
+  let path =
+    CString::new(path.as_os_str().as_bytes()).map_err(io::Error::from)?;
+  // SAFETY: `path` is a valid C string, and the struct is plain data that the
+  // call fills in.
+  let fs = unsafe {
+    let mut fs: libc::statfs = std::mem::zeroed();
+    if libc::statfs(path.as_ptr(), &mut fs) != 0 {
+      return Err(io::Error::last_os_error().into());
+    }
+    fs
+  };
+  // SAFETY: the name is NUL terminated.
+  let fs_type = unsafe { CStr::from_ptr(fs.f_fstypename.as_ptr()) };
+
+  let block_size = fs.f_bsize as u64;
+  let flag = |flag: libc::c_int| fs.f_flags & flag as u32 != 0;
+  Ok(FsStatFs {
+    fs_type: fs_type.to_string_lossy().into_owned(),
+    block_size,
+    blocks: fs.f_blocks,
+    blocks_free: fs.f_bfree,
+    blocks_available: fs.f_bavail,
+    total_bytes: fs.f_blocks * block_size,
+    free_bytes: fs.f_bfree * block_size,
+    available_bytes: fs.f_bavail * block_size,
+    files: Some(fs.f_files),
+    files_free: Some(fs.f_ffree),
+    flags: FsStatFsFlags {
+      read_only: flag(libc::MNT_RDONLY),
+      no_suid: flag(libc::MNT_NOSUID),
+      no_dev: flag(libc::MNT_NODEV),
+      no_exec: flag(libc::MNT_NOEXEC),
+      synchronous: flag(libc::MNT_SYNCHRONOUS),
+      no_atime: flag(libc::MNT_NOATIME),
+    },
+  })
+}
+
+#[cfg(windows)]
+fn stat_fs(path: &Path) -> FsResult<FsStatFs> {
+  use std::os::windows::ffi::OsStrExt;
+  use winapi::shared::minwindef::MAX_PATH;
+  use winapi::um::fileapi::GetDiskFreeSpaceExW;
+  use winapi::um::fileapi::GetDiskFreeSpaceW;
+  use winapi::um::fileapi::GetVolumeInformationW;
+  use winapi::um::fileapi::GetVolumePathNameW;
+  use winapi::um::winnt::FILE_READ_ONLY_VOLUME;
+
+  let path: Vec<u16> = path.as_os_str().encode_wide().chain([0]).collect();
+  // The root of the volume isn't longer than the path.
+  let mut root = vec![0u16; std::cmp::max(path.len(), MAX_PATH) + 1];
+  let mut available_bytes = 0u64;
+  let mut total_bytes = 0u64;
+  let mut free_bytes = 0u64;
+  let mut sectors_per_cluster = 0;
+  let mut bytes_per_sector = 0;
+  let mut free_clusters = 0;
+  let mut total_clusters = 0;
+  let mut fs_type = [0u16; MAX_PATH + 1];
+  let mut flags = 0;
+  // SAFETY: winapi calls, the strings are NUL terminated and the buffers are
+  // valid for their lengths.
+  unsafe {
+    if GetVolumePathNameW(path.as_ptr(), root.as_mut_ptr(), root.len() as _)
+      == 0
+      || GetDiskFreeSpaceExW(
+        path.as_ptr(),
+        &mut available_bytes as *mut u64 as *mut _,
+        &mut total_bytes as *mut u64 as *mut _,
+        &mut free_bytes as *mut u64 as *mut _,
+      ) == 0
+      || GetDiskFreeSpaceW(
+        root.as_ptr(),
+        &mut sectors_per_cluster,
+        &mut bytes_per_sector,
+        &mut free_clusters,
+        &mut total_clusters,
+      ) == 0
+      || GetVolumeInformationW(
+        root.as_ptr(),
+        std::ptr::null_mut(),
+        0,
+        std::ptr::null_mut(),
+        std::ptr::null_mut(),
+        &mut flags,
+        fs_type.as_mut_ptr(),
+        fs_type.len() as _,
+      ) == 0
+    {
+      return Err(io::Error::last_os_error().into());
+    }
+  }
+  let fs_type_len = fs_type.iter().position(|c| *c == 0).unwrap_or(0);
+
+  // The cluster counts of GetDiskFreeSpaceW() are capped for large volumes.
+  let block_size =
+    std::cmp::max(sectors_per_cluster * bytes_per_sector, 1) as u64;
+  Ok(FsStatFs {
+    fs_type: String::from_utf16_lossy(&fs_type[..fs_type_len]),
+    block_size,
+    blocks: total_bytes / block_size,
+    blocks_free: free_bytes / block_size,
+    blocks_available: available_bytes / block_size,
+    total_bytes,
+    free_bytes,
+    available_bytes,
+    files: None,
+    files_free: None,
+    flags: FsStatFsFlags {
+      read_only: flags & FILE_READ_ONLY_VOLUME != 0,
+      ..Default::default()
+    },
+  })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn stat_fs(_path: &Path) -> FsResult<FsStatFs> {
+  Err(FsError::NotSupported)
+}
+
+/// Writes the data to a temporary file next to `path`, flushes it to the disk
+/// and renames it over `path`, so that `path` has either its old or its new
+/// contents at any time. Without a `mode`, the permissions of the replaced
+/// file are kept on Unix.
+fn write_file_atomic(
+  path: &Path,
+  mode: Option<u32>,
+  data: &[u8],
+) -> FsResult<()> {
+  // Replace the file that a symlink points to, rather than the symlink.
+  let path = match fs::symlink_metadata(path) {
+    Ok(metadata) if metadata.is_symlink() => fs::canonicalize(path)?,
+    _ => path.to_path_buf(),
+  };
+  let Some(file_name) = path.file_name() else {
+    return Err(
+      io::Error::new(io::ErrorKind::InvalidInput, "Path has no file name")
+        .into(),
+    );
+  };
+  let dir = match path.parent() {
+    Some(dir) if !dir.as_os_str().is_empty() => dir,
+    _ => Path::new("."),
+  };
+
+  let (tmp_path, mut file) = create_tmp_file(dir, file_name)?;
+  let result = (|| -> FsResult<()> {
+    set_tmp_file_permissions(&file, &path, mode)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    drop(file);
+    replace_file(&tmp_path, &path)?;
+    Ok(())
+  })();
+  if result.is_err() {
+    let _ = fs::remove_file(&tmp_path);
+  }
+  result
+}
+
+fn create_tmp_file(
+  dir: &Path,
+  file_name: &std::ffi::OsStr,
+) -> io::Result<(PathBuf, fs::File)> {
+  use rand::Rng;
+
+  let mut rng = rand::thread_rng();
+  loop {
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(file_name);
+    tmp_name.push(format!(".{:08x}.tmp", rng.gen::<u32>()));
+    let tmp_path = dir.join(tmp_name);
+    let mut options = fs::OpenOptions::new();
+    options.read(true).write(true).create_new(true);
+    // The handle must be allowed to delete the file, for
+    // `set_delete_on_close()`.
+    #[cfg(windows)]
+    {
+      use std::os::windows::fs::OpenOptionsExt;
+      use winapi::um::winnt::DELETE;
+      use winapi::um::winnt::GENERIC_READ;
+      use winapi::um::winnt::GENERIC_WRITE;
+      options.access_mode(GENERIC_READ | GENERIC_WRITE | DELETE);
+    }
+    match options.open(&tmp_path) {
+      Ok(file) => return Ok((tmp_path, file)),
+      Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+      Err(err) => return Err(err),
+    }
+  }
+}
+
+/// Opens a file that is deleted once it's closed, see
+/// `FileSystem::open_tmp_file_sync()`. On Linux it's created without a name
+/// with O_TMPFILE, so that it can't be left behind even if the process is
+/// killed, and elsewhere it's named and then unlinked or marked for deletion
+/// right away.
+#[cfg(target_os = "linux")]
+fn open_tmp_file(dir: &Path) -> io::Result<fs::File> {
+  use std::os::unix::fs::OpenOptionsExt;
+
+  let result = fs::OpenOptions::new()
+    .read(true)
+    .write(true)
+    .mode(0o600)
+    .custom_flags(libc::O_TMPFILE)
+    .open(dir);
+  match result {
+    // Kernels and file systems without O_TMPFILE fail with either of these.
+    Err(err)
+      if err.raw_os_error() == Some(libc::EOPNOTSUPP)
+        || err.raw_os_error() == Some(libc::EISDIR) =>
+    {
+      open_unlinked_tmp_file(dir)
+    }
+    result => result,
+  }
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn open_tmp_file(dir: &Path) -> io::Result<fs::File> {
+  open_unlinked_tmp_file(dir)
+}
+
+#[cfg(unix)]
+fn open_unlinked_tmp_file(dir: &Path) -> io::Result<fs::File> {
+  use std::os::unix::fs::PermissionsExt;
+
+  let (tmp_path, file) = create_tmp_file(dir, "deno".as_ref())?;
+  let result = file
+    .set_permissions(fs::Permissions::from_mode(0o600))
+    .and_then(|_| fs::remove_file(&tmp_path));
+  if let Err(err) = result {
+    let _ = fs::remove_file(&tmp_path);
+    return Err(err);
+  }
+  Ok(file)
+}
+
+#[cfg(windows)]
+fn open_tmp_file(dir: &Path) -> io::Result<fs::File> {
+  let (tmp_path, file) = create_tmp_file(dir, "deno".as_ref())?;
+  if let Err(err) = deno_io::set_delete_on_close(&file, true) {
+    drop(file);
+    let _ = fs::remove_file(&tmp_path);
+    return Err(err);
+  }
+  Ok(file)
+}
+
+#[cfg(unix)]
+fn set_tmp_file_permissions(
+  file: &fs::File,
+  path: &Path,
+  mode: Option<u32>,
+) -> io::Result<()> {
+  use std::os::unix::fs::MetadataExt;
+  use std::os::unix::fs::PermissionsExt;
+  use std::os::unix::io::AsRawFd;
+
+  if let Some(mode) = mode {
+    return file.set_permissions(fs::Permissions::from_mode(mode));
+  }
+  let Ok(existing) = fs::metadata(path) else {
+    return Ok(());
+  };
+  file.set_permissions(existing.permissions())?;
+  // Only root can give the file to another user, so this may fail.
+  // SAFETY: libc call, the file descriptor is open.
+  unsafe {
+    libc::fchown(file.as_raw_fd(), existing.uid(), existing.gid());
+  }
+  Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_tmp_file_permissions(
+  _file: &fs::File,
+  _path: &Path,
+  _mode: Option<u32>,
+) -> io::Result<()> {
+  Ok(())
+}
 
+#[cfg(unix)]
+fn replace_file(from: &Path, to: &Path) -> io::Result<()> {
+  fs::rename(from, to)?;
+  // The rename itself is only durable once the directory is synced. It has
+  // already happened though, so this is best effort.
+  if let Some(dir) = to.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+    if let Ok(dir) = fs::File::open(dir) {
+      let _ = dir.sync_all();
+    }
+  }
   Ok(())
 }
 
+#[cfg(windows)]
+fn replace_file(from: &Path, to: &Path) -> io::Result<()> {
+  use std::os::windows::ffi::OsStrExt;
+  use winapi::shared::winerror::ERROR_ACCESS_DENIED;
+  use winapi::shared::winerror::ERROR_SHARING_VIOLATION;
+  use winapi::um::winbase::MoveFileExW;
+  use winapi::um::winbase::MOVEFILE_REPLACE_EXISTING;
+  use winapi::um::winbase::MOVEFILE_WRITE_THROUGH;
+
+  const MAX_RETRIES: u64 = 10;
+
+  let wide = |path: &Path| -> Vec<u16> {
+    path.as_os_str().encode_wide().chain(Some(0)).collect()
+  };
+  let from = wide(from);
+  let to = wide(to);
+  let mut retries = 0;
+  loop {
+    // SAFETY: winapi call, the paths are null terminated.
+    let ret = unsafe {
+      MoveFileExW(
+        from.as_ptr(),
+        to.as_ptr(),
+        MOVEFILE_REPLACE_EXISTING | MOVEFILE_WRITE_THROUGH,
+      )
+    };
+    if ret != 0 {
+      return Ok(());
+    }
+    let err = io::Error::last_os_error();
+    // A file can't be replaced while it's open without FILE_SHARE_DELETE,
+    // which virus scanners and indexers briefly do, so retry for a while.
+    let code = err.raw_os_error();
+    let is_busy = code == Some(ERROR_ACCESS_DENIED as i32)
+      || code == Some(ERROR_SHARING_VIOLATION as i32);
+    if !is_busy || retries == MAX_RETRIES {
+      return Err(err);
+    }
+    retries += 1;
+    std::thread::sleep(std::time::Duration::from_millis(10 * retries));
+  }
+}
+
+#[cfg(target_os = "macos")]
+fn set_birthtime(path: &Path, secs: i64, nanos: u32) -> FsResult<()> {
+  let file = fs::File::open(path)?;
+  deno_io::set_birthtime(&file, secs, nanos)
+}
+
+#[cfg(windows)]
+fn set_birthtime(path: &Path, secs: i64, nanos: u32) -> FsResult<()> {
+  use std::os::windows::fs::OpenOptionsExt;
+  use winapi::um::winbase::FILE_FLAG_BACKUP_SEMANTICS;
+  use winapi::um::winnt::FILE_WRITE_ATTRIBUTES;
+
+  // Directories can only be opened with backup semantics.
+  let file = fs::OpenOptions::new()
+    .access_mode(FILE_WRITE_ATTRIBUTES)
+    .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+    .open(path)?;
+  deno_io::set_birthtime(&file, secs, nanos)
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn set_birthtime(_path: &Path, _secs: i64, _nanos: u32) -> FsResult<()> {
+  Err(FsError::NotSupported)
+}
+
 #[cfg(not(windows))]
 fn stat(path: &Path) -> FsResult<FsStat> {
   let metadata = fs::metadata(path)?;
@@ -599,6 +1484,32 @@ fn read_dir(path: &Path) -> FsResult<Vec<FsDirEntry>> {
   Ok(entries)
 }
 
+/// Reads the directory and the metadata of its entries in one go. On Linux
+/// the metadata comes from `statx()` relative to the open directory, so the
+/// paths aren't resolved again, and on Windows it's already part of what
+/// `FindNextFileW()` returns. Entries removed in the meantime are skipped.
+fn read_dir_with_stats(path: &Path) -> FsResult<Vec<FsDirEntryWithStat>> {
+  let entries = fs::read_dir(path)?
+    .filter_map(|entry| {
+      let entry = entry.ok()?;
+      let name = entry.file_name().into_string().ok()?;
+      let metadata = entry.metadata().ok()?;
+      let file_type = metadata.file_type();
+      Some(FsDirEntryWithStat {
+        entry: FsDirEntry {
+          name,
+          is_file: file_type.is_file(),
+          is_directory: file_type.is_dir(),
+          is_symlink: file_type.is_symlink(),
+        },
+        stat: FsStat::from_std(metadata),
+      })
+    })
+    .collect();
+
+  Ok(entries)
+}
+
 #[cfg(not(windows))]
 fn symlink(
   oldpath: &Path,
@@ -0,0 +1,324 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+#![allow(clippy::disallowed_methods)]
+
+//! Moving files to the trash, for `Deno.remove(path, { trash: true })`. It's
+//! the XDG trash on Linux and the other Unixes, the `.Trash` directories of
+//! Finder on macOS and the recycle bin on Windows.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_io::fs::FsError;
+use deno_io::fs::FsResult;
+
+/// Moves a file or a directory with all of its contents to the trash.
+pub fn trash(path: &Path) -> FsResult<()> {
+  let path: PathBuf = if path.is_absolute() {
+    path.to_path_buf()
+  } else {
+    std::env::current_dir()?.join(path)
+  };
+  sys::trash(&path).map_err(|err| {
+    if err.kind() == io::ErrorKind::Unsupported {
+      FsError::NotSupported
+    } else {
+      FsError::Io(err)
+    }
+  })
+}
+
+#[cfg(unix)]
+fn unsupported(message: &str) -> io::Error {
+  io::Error::new(io::ErrorKind::Unsupported, message)
+}
+
+#[cfg(unix)]
+fn home_dir() -> Option<PathBuf> {
+  std::env::var_os("HOME")
+    .filter(|home| !home.is_empty())
+    .map(PathBuf::from)
+}
+
+/// Returns the root of the file system that `path` is on, by going up until
+/// the device changes.
+#[cfg(unix)]
+fn mount_point(path: &Path, dev: u64) -> PathBuf {
+  use std::os::unix::fs::MetadataExt;
+
+  let mut mount_point = path;
+  while let Some(parent) = mount_point.parent() {
+    match std::fs::symlink_metadata(parent) {
+      Ok(metadata) if metadata.dev() == dev => mount_point = parent,
+      _ => break,
+    }
+  }
+  mount_point.to_path_buf()
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod sys {
+  use std::fs;
+  use std::io;
+  use std::io::Write;
+  use std::os::unix::ffi::OsStrExt;
+  use std::os::unix::fs::DirBuilderExt;
+  use std::os::unix::fs::MetadataExt;
+  use std::os::unix::fs::PermissionsExt;
+  use std::path::Path;
+  use std::path::PathBuf;
+
+  /// The trash directories where the file may go, in order of preference,
+  /// see https://specifications.freedesktop.org/trash-spec/trashspec-1.0.html.
+  fn trash_dirs(path: &Path, dev: u64) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+      .filter(|dir| !dir.is_empty())
+      .map(PathBuf::from)
+      .or_else(|| super::home_dir().map(|home| home.join(".local/share")));
+    if let Some(data_home) = data_home {
+      dirs.push(data_home.join("Trash"));
+    }
+
+    // SAFETY: libc call without arguments.
+    let uid = unsafe { libc::geteuid() };
+    let top_dir = super::mount_point(path, dev);
+    let shared = top_dir.join(".Trash");
+    // The shared trash directory must have the sticky bit and not be a
+    // symlink, or it must not be used.
+    if let Ok(metadata) = fs::symlink_metadata(&shared) {
+      if metadata.is_dir() && metadata.permissions().mode() & 0o1000 != 0 {
+        dirs.push(shared.join(uid.to_string()));
+      }
+    }
+    dirs.push(top_dir.join(format!(".Trash-{uid}")));
+    dirs
+  }
+
+  pub fn trash(path: &Path) -> io::Result<()> {
+    let dev = fs::symlink_metadata(path)?.dev();
+    for trash_dir in trash_dirs(path, dev) {
+      let files = trash_dir.join("files");
+      let info = trash_dir.join("info");
+      let created = fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&files)
+        .and_then(|_| {
+          fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(&info)
+        });
+      match created.and_then(|_| fs::symlink_metadata(&files)) {
+        Ok(metadata) if metadata.dev() == dev => {
+          return move_to_trash(path, &files, &info);
+        }
+        _ => continue,
+      }
+    }
+    Err(super::unsupported("No trash directory on the file system"))
+  }
+
+  /// Reserves a name in the trash by creating its `.trashinfo` file, and
+  /// moves the file there.
+  fn move_to_trash(path: &Path, files: &Path, info: &Path) -> io::Result<()> {
+    let Some(file_name) = path.file_name() else {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "Path has no file name",
+      ));
+    };
+    let contents = format!(
+      "[Trash Info]\nPath={}\nDeletionDate={}\n",
+      encode_path(path),
+      deletion_date()
+    );
+    for n in 1.. {
+      let mut name = file_name.to_os_string();
+      if n > 1 {
+        name.push(format!(".{n}"));
+      }
+      let mut info_name = name.clone();
+      info_name.push(".trashinfo");
+      let info_path = info.join(info_name);
+      let mut info_file = match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&info_path)
+      {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+        Err(err) => return Err(err),
+      };
+      let trashed_path = files.join(&name);
+      if fs::symlink_metadata(&trashed_path).is_ok() {
+        let _ = fs::remove_file(&info_path);
+        continue;
+      }
+      let result = info_file
+        .write_all(contents.as_bytes())
+        .and_then(|_| fs::rename(path, &trashed_path));
+      if result.is_err() {
+        let _ = fs::remove_file(&info_path);
+      }
+      return result;
+    }
+    unreachable!()
+  }
+
+  /// Percent-encodes a path like in URLs, as the spec requires.
+  fn encode_path(path: &Path) -> String {
+    let mut encoded = String::new();
+    for &byte in path.as_os_str().as_bytes() {
+      if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+        encoded.push(byte as char);
+      } else {
+        encoded.push_str(&format!("%{byte:02X}"));
+      }
+    }
+    encoded
+  }
+
+  /// Returns the current local time, as `YYYY-MM-DDThh:mm:ss`.
+  fn deletion_date() -> String {
+    // SAFETY: libc calls, `tm` is initialized by `localtime_r`.
+    let tm = unsafe {
+      let now = libc::time(std::ptr::null_mut());
+      let mut tm = std::mem::zeroed::<libc::tm>();
+      libc::localtime_r(&now, &mut tm);
+      tm
+    };
+    format!(
+      "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+      tm.tm_year + 1900,
+      tm.tm_mon + 1,
+      tm.tm_mday,
+      tm.tm_hour,
+      tm.tm_min,
+      tm.tm_sec
+    )
+  }
+}
+
+#[cfg(target_os = "macos")]
+mod sys {
+  use std::ffi::CString;
+  use std::ffi::OsString;
+  use std::fs;
+  use std::io;
+  use std::os::unix::ffi::OsStrExt;
+  use std::os::unix::fs::DirBuilderExt;
+  use std::os::unix::fs::MetadataExt;
+  use std::path::Path;
+
+  /// Returns the `.Trash` directory of the user on the volume of the file,
+  /// where Finder moves the files of that volume.
+  fn trash_dir(path: &Path, dev: u64) -> io::Result<std::path::PathBuf> {
+    if let Some(home) = super::home_dir() {
+      let trash_dir = home.join(".Trash");
+      if let Ok(metadata) = fs::symlink_metadata(&trash_dir) {
+        if metadata.dev() == dev {
+          return Ok(trash_dir);
+        }
+      }
+    }
+    // SAFETY: libc call without arguments.
+    let uid = unsafe { libc::geteuid() };
+    let trashes = super::mount_point(path, dev).join(".Trashes");
+    if fs::symlink_metadata(&trashes).is_err() {
+      return Err(super::unsupported("No trash directory on the volume"));
+    }
+    let trash_dir = trashes.join(uid.to_string());
+    fs::DirBuilder::new()
+      .recursive(true)
+      .mode(0o700)
+      .create(&trash_dir)?;
+    Ok(trash_dir)
+  }
+
+  pub fn trash(path: &Path) -> io::Result<()> {
+    let dev = fs::symlink_metadata(path)?.dev();
+    let trash_dir = trash_dir(path, dev)?;
+    let from = CString::new(path.as_os_str().as_bytes())?;
+    let stem = path.file_stem().unwrap_or_default();
+    let extension = path.extension();
+    // Finder names the duplicates "name 2.ext", "name 3.ext" and so on.
+    for n in 1.. {
+      let mut name = OsString::from(stem);
+      if n > 1 {
+        name.push(format!(" {n}"));
+      }
+      if let Some(extension) = extension {
+        name.push(".");
+        name.push(extension);
+      }
+      let to = CString::new(trash_dir.join(name).as_os_str().as_bytes())?;
+      // SAFETY: libc call, the paths are null terminated.
+      let ret = unsafe {
+        libc::renamex_np(from.as_ptr(), to.as_ptr(), libc::RENAME_EXCL)
+      };
+      if ret == 0 {
+        return Ok(());
+      }
+      let err = io::Error::last_os_error();
+      if err.raw_os_error() != Some(libc::EEXIST) {
+        return Err(err);
+      }
+    }
+    unreachable!()
+  }
+}
+
+#[cfg(windows)]
+mod sys {
+  use std::io;
+  use std::os::windows::ffi::OsStrExt;
+  use std::path::Path;
+  use winapi::um::shellapi::SHFileOperationW;
+  use winapi::um::shellapi::FOF_ALLOWUNDO;
+  use winapi::um::shellapi::FOF_NOCONFIRMATION;
+  use winapi::um::shellapi::FOF_NOERRORUI;
+  use winapi::um::shellapi::FOF_SILENT;
+  use winapi::um::shellapi::FO_DELETE;
+  use winapi::um::shellapi::SHFILEOPSTRUCTW;
+
+  pub fn trash(path: &Path) -> io::Result<()> {
+    // Fail like `Deno.remove()` for missing files, rather than with the
+    // codes of the shell.
+    std::fs::symlink_metadata(path)?;
+
+    // The list of paths is terminated by an empty path.
+    let from = path
+      .as_os_str()
+      .encode_wide()
+      .chain([0, 0])
+      .collect::<Vec<u16>>();
+    let mut operation = SHFILEOPSTRUCTW {
+      hwnd: std::ptr::null_mut(),
+      wFunc: FO_DELETE as u32,
+      pFrom: from.as_ptr(),
+      pTo: std::ptr::null(),
+      fFlags: FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_NOERRORUI | FOF_SILENT,
+      fAnyOperationsAborted: 0,
+      hNameMappings: std::ptr::null_mut(),
+      lpszProgressTitle: std::ptr::null(),
+    };
+    // SAFETY: winapi call, `from` outlives it.
+    let ret = unsafe { SHFileOperationW(&mut operation) };
+    if ret != 0 {
+      return Err(io::Error::new(
+        io::ErrorKind::Other,
+        format!("Failed to move to the recycle bin (error {ret:#x})"),
+      ));
+    }
+    if operation.fAnyOperationsAborted != 0 {
+      return Err(io::Error::new(
+        io::ErrorKind::Interrupted,
+        "Moving to the recycle bin was aborted",
+      ));
+    }
+    Ok(())
+  }
+}
@@ -6,6 +6,11 @@ const internals = globalThis.__bootstrap.internals;
 
 const { BadResourcePrototype } = core;
 import { InnerBody } from "ext:deno_fetch/22_body.js";
+import {
+  headerListFromHeaders,
+  Headers,
+  headersFromHeaderList,
+} from "ext:deno_fetch/20_headers.js";
 import { Event } from "ext:deno_web/02_event.js";
 import {
   fromInnerResponse,
@@ -34,10 +39,12 @@ import {
   readableStreamForRid,
   ReadableStreamPrototype,
 } from "ext:deno_web/06_streams.js";
-import { TcpConn } from "ext:deno_net/01_net.js";
+import { TcpConn, UnixConn } from "ext:deno_net/01_net.js";
+import { TlsConn } from "ext:deno_net/02_tls.js";
 const {
   ObjectPrototypeIsPrototypeOf,
   PromisePrototypeCatch,
+  PromisePrototypeThen,
   SafeSet,
   SafeSetIterator,
   SetPrototypeAdd,
@@ -51,34 +58,48 @@ const {
 const {
   op_http_wait,
   op_http_upgrade_next,
+  op_http_get_request_body_buffered,
   op_http_get_request_headers,
   op_http_get_request_method_and_url,
+  op_http_get_request_trailers,
+  op_http_is_overloaded,
   op_http_read_request_body,
+  op_http_send_early_hints,
   op_http_serve,
   op_http_set_promise_complete,
   op_http_set_response_body_bytes,
   op_http_set_response_body_resource,
   op_http_set_response_body_stream,
+  op_http_set_response_body_stream_trailers,
   op_http_set_response_body_text,
   op_http_set_response_header,
   op_http_set_response_headers,
+  op_http_set_response_trailers,
   op_http_upgrade_raw,
+  op_http_upgrade_stream,
   op_ws_server_create,
 } = core.generateAsyncOpHandler(
   "op_http_wait",
   "op_http_upgrade_next",
+  "op_http_get_request_body_buffered",
   "op_http_get_request_headers",
   "op_http_get_request_method_and_url",
+  "op_http_get_request_trailers",
+  "op_http_is_overloaded",
   "op_http_read_request_body",
+  "op_http_send_early_hints",
   "op_http_serve",
   "op_http_set_promise_complete",
   "op_http_set_response_body_bytes",
   "op_http_set_response_body_resource",
   "op_http_set_response_body_stream",
+  "op_http_set_response_body_stream_trailers",
   "op_http_set_response_body_text",
   "op_http_set_response_header",
   "op_http_set_response_headers",
+  "op_http_set_response_trailers",
   "op_http_upgrade_raw",
+  "op_http_upgrade_stream",
   "op_ws_server_create",
 );
 const _upgraded = Symbol("_upgraded");
@@ -113,6 +134,34 @@ function internalServerError() {
   );
 }
 
+function serviceUnavailable() {
+  // "Service Unavailable"
+  return new Response(
+    new Uint8Array([
+      83,
+      101,
+      114,
+      118,
+      105,
+      99,
+      101,
+      32,
+      85,
+      110,
+      97,
+      118,
+      97,
+      105,
+      108,
+      97,
+      98,
+      108,
+      101,
+    ]),
+    { status: 503 },
+  );
+}
+
 // Used to ensure that user returns a valid response (but not a different response) from handlers that are upgraded.
 const UPGRADE_RESPONSE_SENTINEL = fromInnerResponse(
   newInnerResponse(101),
@@ -134,6 +183,7 @@ class InnerRequest {
   #streamRid;
   #body;
   #upgraded;
+  #responseTrailers;
 
   constructor(slabId, context) {
     this.#slabId = slabId;
@@ -161,10 +211,25 @@ class InnerRequest {
       throw new Deno.errors.Http("already closed");
     }
 
-    // upgradeHttp is async
-    // TODO(mmastrac)
+    // upgradeHttp is async, and resolves once the response returned from the callback has been
+    // sent. Unlike the other upgrades, the response is sent as usual.
     if (upgradeType == "upgradeHttp") {
-      throw "upgradeHttp is unavailable in Deno.serve at this time";
+      const remoteAddr = this.remoteAddr;
+      const upgrade = op_http_upgrade_stream(this.#slabId);
+      return PromisePrototypeThen(upgrade, (upgrade) => {
+        const { 0: rid, 1: connType, 2: firstPacket } = upgrade;
+        let conn;
+        if (connType === "tcp") {
+          conn = new TcpConn(rid, remoteAddr, undefined);
+        } else if (connType === "tls") {
+          conn = new TlsConn(rid, remoteAddr, undefined);
+        } else if (connType === "unix") {
+          conn = new UnixConn(rid, remoteAddr, undefined);
+        } else {
+          throw new Error("unreachable");
+        }
+        return [conn, firstPacket];
+      });
     }
 
     // upgradeHttpRaw is sync
@@ -327,6 +392,43 @@ class InnerRequest {
     return op_http_get_request_headers(this.#slabId);
   }
 
+  sendEarlyHints(headers) {
+    if (this.#slabId === undefined) {
+      throw new TypeError("request closed");
+    }
+    if (this.#upgraded) {
+      throw new Deno.errors.Http("already upgraded");
+    }
+    const headerList = headerListFromHeaders(new Headers(headers));
+    op_http_send_early_hints(this.#slabId, headerList);
+  }
+
+  setTrailers(headers) {
+    this.#responseTrailers = headerListFromHeaders(new Headers(headers));
+  }
+
+  get responseTrailers() {
+    return this.#responseTrailers;
+  }
+
+  requestBodyBufferedBytes() {
+    if (this.#slabId === undefined) {
+      throw new TypeError("request closed");
+    }
+    return op_http_get_request_body_buffered(this.#slabId);
+  }
+
+  requestTrailers() {
+    if (this.#slabId === undefined) {
+      throw new TypeError("request closed");
+    }
+    const trailers = op_http_get_request_trailers(this.#slabId);
+    if (trailers === null) {
+      return null;
+    }
+    return headersFromHeaderList(trailers, "immutable");
+  }
+
   get slabId() {
     return this.#slabId;
   }
@@ -391,10 +493,17 @@ function fastSyncResponseOrStream(req, respBody) {
   return stream;
 }
 
-async function asyncResponse(responseBodies, req, status, stream) {
+async function asyncResponse(
+  responseBodies,
+  req,
+  status,
+  stream,
+  innerRequest,
+) {
   const reader = stream.getReader();
   let responseRid;
   let closed = false;
+  let errored = false;
   let timeout;
 
   try {
@@ -465,6 +574,7 @@ async function asyncResponse(responseBodies, req, status, stream) {
     }
   } catch (error) {
     closed = true;
+    errored = true;
     try {
       await reader.cancel(error);
     } catch {
@@ -477,10 +587,18 @@ async function asyncResponse(responseBodies, req, status, stream) {
     if (timeout !== undefined) {
       clearTimeout(timeout);
     }
+    // Trailers are only sent for bodies that completed successfully
+    const trailers = errored ? undefined : innerRequest?.responseTrailers;
     if (responseRid) {
+      if (trailers !== undefined) {
+        op_http_set_response_body_stream_trailers(responseRid, trailers);
+      }
       core.tryClose(responseRid);
       SetPrototypeDelete(responseBodies, responseRid);
     } else {
+      if (trailers !== undefined) {
+        op_http_set_response_trailers(req, trailers);
+      }
       op_http_set_promise_complete(req, status);
     }
   }
@@ -493,14 +611,27 @@ async function asyncResponse(responseBodies, req, status, stream) {
  *
  * This function returns a promise that will only reject in the case of abnormal exit.
  */
-function mapToCallback(responseBodies, context, signal, callback, onError) {
+function mapToCallback(
+  responseBodies,
+  context,
+  signal,
+  callback,
+  onError,
+  onOverload,
+) {
   return async function (req) {
     // Get the response from the user-provided callback. If that fails, use onError. If that fails, return a fallback
     // 500 error.
     let innerRequest;
     let response;
     try {
-      if (callback.length > 0) {
+      if (onOverload !== undefined && op_http_is_overloaded(req)) {
+        // The connection was accepted beyond the connection limit
+        innerRequest = new InnerRequest(req, context);
+        response = await onOverload(
+          fromInnerRequest(innerRequest, signal, "immutable"),
+        );
+      } else if (callback.length > 0) {
         innerRequest = new InnerRequest(req, context);
         const request = fromInnerRequest(innerRequest, signal, "immutable");
         if (callback.length === 1) {
@@ -510,6 +641,18 @@ function mapToCallback(responseBodies, context, signal, callback, onError) {
             get remoteAddr() {
               return innerRequest.remoteAddr;
             },
+            sendEarlyHints(headers) {
+              innerRequest.sendEarlyHints(headers);
+            },
+            setTrailers(headers) {
+              innerRequest.setTrailers(headers);
+            },
+            requestTrailers() {
+              return innerRequest.requestTrailers();
+            },
+            requestBodyBufferedBytes() {
+              return innerRequest.requestBodyBufferedBytes();
+            },
           });
         }
       } else {
@@ -555,8 +698,12 @@ function mapToCallback(responseBodies, context, signal, callback, onError) {
     const stream = fastSyncResponseOrStream(req, inner.body);
     if (stream !== null) {
       // Handle the stream asynchronously
-      await asyncResponse(responseBodies, req, status, stream);
+      await asyncResponse(responseBodies, req, status, stream, innerRequest);
     } else {
+      const trailers = innerRequest?.responseTrailers;
+      if (trailers !== undefined) {
+        op_http_set_response_trailers(req, trailers);
+      }
       op_http_set_promise_complete(req, status);
     }
 
@@ -590,7 +737,7 @@ async function serve(arg1, arg2) {
     options = {};
   }
 
-  const wantsHttps = options.cert || options.key;
+  const wantsHttps = options.cert || options.key || options.acme;
   const signal = options.signal;
   const onError = options.onError ?? function (error) {
     console.error(error);
@@ -600,8 +747,23 @@ async function serve(arg1, arg2) {
     hostname: options.hostname ?? "0.0.0.0",
     port: options.port ?? (wantsHttps ? 9000 : 8000),
     reusePort: options.reusePort ?? false,
+    proxyProtocol: options.proxyProtocol ?? false,
   };
 
+  const serverOptions = {
+    headersTimeout: options.headersTimeout,
+    requestBodyIdleTimeout: options.requestBodyIdleTimeout,
+    keepAliveTimeout: options.keepAliveTimeout,
+    requestBodyHighWaterMark: options.requestBodyHighWaterMark,
+    maxConnections: options.maxConnections,
+    maxConcurrentStreams: options.maxConcurrentStreams,
+    overloadBehavior: options.overloadBehavior,
+  };
+  // Only servers that reject connections beyond the limit serve overloaded connections
+  const onOverload = options.overloadBehavior === "reject"
+    ? options.onOverload ?? serviceUnavailable
+    : undefined;
+
   const abortController = new AbortController();
 
   const responseBodies = new SafeSet();
@@ -612,27 +774,37 @@ async function serve(arg1, arg2) {
     abortController.signal,
     handler,
     onError,
+    onOverload,
   );
 
   if (wantsHttps) {
-    if (!options.cert || !options.key) {
+    if (!options.acme && (!options.cert || !options.key)) {
       throw new TypeError(
         "Both cert and key must be provided to enable HTTPS.",
       );
     }
     listenOpts.cert = options.cert;
     listenOpts.key = options.key;
+    listenOpts.sni = options.sni;
+    listenOpts.acme = options.acme;
     listenOpts.alpnProtocols = ["h2", "http/1.1"];
     const listener = Deno.listenTls(listenOpts);
     listenOpts.port = listener.addr.port;
     context.initialize(op_http_serve(
       listener.rid,
+      serverOptions,
+      options.http3 ?? false,
     ));
   } else {
+    if (options.http3) {
+      throw new TypeError("HTTP/3 requires cert and key to be provided.");
+    }
     const listener = Deno.listen(listenOpts);
     listenOpts.port = listener.addr.port;
     context.initialize(op_http_serve(
       listener.rid,
+      serverOptions,
+      false,
     ));
   }
 
@@ -27,12 +27,16 @@ import {
   _eventLoop,
   _idleTimeoutDuration,
   _idleTimeoutTimeout,
+  _limits,
+  _pingInterval,
+  _pingTimeout,
   _protocol,
   _readyState,
   _rid,
   _role,
   _server,
   _serverHandleIdleTimeout,
+  limitsFromOptions,
   SERVER,
   WebSocket,
 } from "ext:deno_websocket/01_websocket.js";
@@ -464,6 +468,9 @@ function upgradeWebSocket(request, options = {}) {
   socket[_server] = true;
   socket[_idleTimeoutDuration] = options.idleTimeout ?? 120;
   socket[_idleTimeoutTimeout] = null;
+  socket[_pingInterval] = options.pingInterval;
+  socket[_pingTimeout] = options.pingTimeout;
+  socket[_limits] = limitsFromOptions(options);
 
   if (inner._wantsUpgrade) {
     return inner._wantsUpgrade("upgradeWebSocket", r, socket);
@@ -0,0 +1,161 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+use crate::server_options::HttpServerOptions;
+use crate::server_options::OverloadBehavior;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+
+/// Enforces the `maxConnections` option across all of the connections of a server, including its
+/// HTTP/3 connections.
+#[derive(Clone)]
+pub struct ConnectionLimit(Option<Limits>);
+
+#[derive(Clone)]
+struct Limits {
+  connections: Arc<Semaphore>,
+  /// The connections that are answered by the overload handler, of which there are at most as
+  /// many as `maxConnections`, so that rejecting connections is bounded too.
+  overloaded: Arc<Semaphore>,
+  behavior: OverloadBehavior,
+}
+
+/// How an accepted connection is served.
+pub enum Admission {
+  /// The server has no connection limit.
+  Unlimited,
+  /// The connection holds one of the server's connection slots.
+  Admitted(OwnedSemaphorePermit),
+  /// The server was at its connection limit, so the connection is only answered by the overload
+  /// handler. It holds one of the slots for those connections.
+  Overloaded(OwnedSemaphorePermit),
+  /// The server was at its connection limit, and as many connections as the limit are already
+  /// answered by the overload handler, so the connection is closed without being served.
+  Refused,
+}
+
+impl ConnectionLimit {
+  pub fn new(options: &HttpServerOptions) -> Self {
+    Self(options.max_connections().map(|max| Limits {
+      connections: Arc::new(Semaphore::new(max as usize)),
+      overloaded: Arc::new(Semaphore::new(max as usize)),
+      behavior: options.overload_behavior(),
+    }))
+  }
+
+  /// Waits until the server may accept another connection, which is immediately for servers
+  /// that reject connections beyond the limit instead of backing off. Pass the returned slot
+  /// to [`ConnectionLimit::admit`] once the connection has been accepted.
+  pub async fn reserve(&self) -> Option<OwnedSemaphorePermit> {
+    match &self.0 {
+      Some(Limits {
+        connections,
+        behavior: OverloadBehavior::Backoff,
+        ..
+      }) => {
+        // The semaphore is never closed
+        connections.clone().acquire_owned().await.ok()
+      }
+      _ => None,
+    }
+  }
+
+  pub fn admit(&self, reserved: Option<OwnedSemaphorePermit>) -> Admission {
+    if let Some(permit) = reserved {
+      return Admission::Admitted(permit);
+    }
+    let Some(limits) = &self.0 else {
+      return Admission::Unlimited;
+    };
+    if let Ok(permit) = limits.connections.clone().try_acquire_owned() {
+      return Admission::Admitted(permit);
+    }
+    match limits.overloaded.clone().try_acquire_owned() {
+      Ok(permit) => Admission::Overloaded(permit),
+      Err(_) => Admission::Refused,
+    }
+  }
+}
+
+impl Admission {
+  /// The options to serve the connection with.
+  pub fn options(&self, options: HttpServerOptions) -> HttpServerOptions {
+    match self {
+      Self::Overloaded(_) => options.overloaded(),
+      _ => options,
+    }
+  }
+
+  pub fn is_refused(&self) -> bool {
+    matches!(self, Self::Refused)
+  }
+
+  /// Keeps the connection's slot claimed until `conn`, which serves it, completes or is dropped.
+  pub async fn hold<F: Future>(self, conn: F) -> F::Output {
+    let output = conn.await;
+    drop(self);
+    output
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use deno_core::futures::FutureExt;
+  use deno_core::serde_json::json;
+
+  fn limit(behavior: &str) -> ConnectionLimit {
+    let options: HttpServerOptions = deno_core::serde_json::from_value(
+      json!({ "maxConnections": 1, "overloadBehavior": behavior }),
+    )
+    .unwrap();
+    ConnectionLimit::new(&options)
+  }
+
+  #[test]
+  fn test_reject() {
+    let limit = limit("reject");
+    assert!(limit.reserve().now_or_never().unwrap().is_none());
+    let first = limit.admit(None);
+    assert!(matches!(first, Admission::Admitted(_)));
+    let overloaded = limit.admit(None);
+    assert!(matches!(overloaded, Admission::Overloaded(_)));
+    // As many connections as the limit are answered by the overload handler
+    assert!(limit.admit(None).is_refused());
+    drop(overloaded);
+    assert!(matches!(limit.admit(None), Admission::Overloaded(_)));
+    drop(first);
+    assert!(matches!(limit.admit(None), Admission::Admitted(_)));
+  }
+
+  #[test]
+  fn test_hold() {
+    let limit = limit("reject");
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    let mut conn = limit.admit(None).hold(rx).boxed();
+    assert!((&mut conn).now_or_never().is_none());
+    assert!(matches!(limit.admit(None), Admission::Overloaded(_)));
+    tx.send(()).unwrap();
+    assert!(conn.now_or_never().is_some());
+    assert!(matches!(limit.admit(None), Admission::Admitted(_)));
+  }
+
+  #[test]
+  fn test_backoff() {
+    let limit = limit("backoff");
+    let reserved = limit.reserve().now_or_never().unwrap();
+    let first = limit.admit(reserved);
+    assert!(matches!(first, Admission::Admitted(_)));
+    // No more connections are accepted until the first one is done
+    assert!(limit.reserve().now_or_never().is_none());
+    drop(first);
+    assert!(limit.reserve().now_or_never().unwrap().is_some());
+  }
+
+  #[test]
+  fn test_unlimited() {
+    let limit = ConnectionLimit::new(&HttpServerOptions::default());
+    assert!(limit.reserve().now_or_never().unwrap().is_none());
+    assert!(matches!(limit.admit(None), Admission::Unlimited));
+  }
+}
@@ -0,0 +1,237 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+use crate::connection_limit::ConnectionLimit;
+use crate::http_next::new_slab_future;
+use crate::http_next::with_keep_alive_timeout;
+use crate::interim_response::InterimResponseWriter;
+use crate::request_body::IncomingBody;
+use crate::request_properties::DefaultHttpRequestProperties;
+use crate::request_properties::HttpConnectionProperties;
+use crate::request_properties::HttpListenProperties;
+use crate::request_properties::HttpPropertyExtractor;
+use crate::server_options::HttpServerOptions;
+use crate::timeouts::ConnectionIdle;
+use bytes::Buf;
+use bytes::Bytes;
+use deno_core::error::AnyError;
+use deno_core::futures::future::poll_fn;
+use deno_core::futures::stream::LocalBoxStream;
+use deno_core::futures::Stream;
+use deno_core::futures::StreamExt;
+use deno_core::BufView;
+use deno_core::CancelHandle;
+use deno_core::CancelTryFuture;
+use deno_net::raw::NetworkStreamAddress;
+use deno_tls::rustls::ServerConfig;
+use h3::server::RequestStream;
+use hyper1::body::Body;
+use hyper1::body::Frame;
+use hyper1::http::HeaderValue;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::Poll;
+use tokio::task::spawn_local;
+
+/// ALPN negotation for "h3"
+const TLS_ALPN_HTTP_3: &[u8] = b"h3";
+
+/// How long clients may cache the `alt-svc` advertisement for, in seconds.
+const ALT_SVC_MAX_AGE: u32 = 86400;
+
+/// Binds a QUIC endpoint on the UDP port matching `addr` that accepts HTTP/3 connections, using
+/// the same certificates as the TLS listener the server was started with.
+pub(crate) fn listen_http3(
+  addr: SocketAddr,
+  tls_config: &ServerConfig,
+  options: HttpServerOptions,
+) -> Result<quinn::Endpoint, AnyError> {
+  let mut tls_config = tls_config.clone();
+  tls_config.alpn_protocols = vec![TLS_ALPN_HTTP_3.to_vec()];
+  // QUIC requires early data to be either disabled or unlimited, so keep it disabled.
+  tls_config.max_early_data_size = 0;
+  let mut config = quinn::ServerConfig::with_crypto(Arc::new(tls_config));
+  let mut transport = quinn::TransportConfig::default();
+  if let Some(high_water_mark) = options.request_body_high_water_mark() {
+    transport.stream_receive_window(high_water_mark.into());
+  }
+  if let Some(max_concurrent_streams) = options.max_concurrent_streams() {
+    transport.max_concurrent_bidi_streams(max_concurrent_streams.into());
+  }
+  config.transport_config(Arc::new(transport));
+  Ok(quinn::Endpoint::server(config, addr)?)
+}
+
+/// The `alt-svc` header advertised by the HTTP/1.1 and HTTP/2 listener, so that clients can
+/// switch over to HTTP/3 on the given port.
+pub(crate) fn alt_svc(port: u16) -> HeaderValue {
+  HeaderValue::try_from(format!("h3=\":{port}\"; ma={ALT_SVC_MAX_AGE}"))
+    .unwrap()
+}
+
+/// Accepts QUIC connections from `endpoint` until the server is closed, feeding their requests
+/// into the same slab as the HTTP/1.1 and HTTP/2 connections.
+pub(crate) async fn serve_http3(
+  endpoint: quinn::Endpoint,
+  listen_properties: HttpListenProperties,
+  options: HttpServerOptions,
+  limit: ConnectionLimit,
+  cancel: Rc<CancelHandle>,
+  tx: tokio::sync::mpsc::Sender<u32>,
+) -> Result<(), AnyError> {
+  loop {
+    let reserved = limit.reserve().await;
+    let Some(connecting) = endpoint.accept().await else {
+      return Ok(());
+    };
+    let request_info = DefaultHttpRequestProperties::connection_properties(
+      &listen_properties,
+      &NetworkStreamAddress::Ip(connecting.remote_address()),
+    );
+    let admission = limit.admit(reserved);
+    if admission.is_refused() {
+      // Dropping the connection closes it
+      continue;
+    }
+    let options = admission.options(options);
+    let idle = ConnectionIdle::default();
+    let conn = serve_http3_connection(
+      connecting,
+      request_info,
+      options,
+      idle.clone(),
+      tx.clone(),
+    );
+    spawn_local(
+      admission.hold(
+        with_keep_alive_timeout(conn, idle, options)
+          .try_or_cancel(cancel.clone()),
+      ),
+    );
+  }
+}
+
+async fn serve_http3_connection(
+  connecting: quinn::Connecting,
+  request_info: HttpConnectionProperties,
+  options: HttpServerOptions,
+  idle: ConnectionIdle,
+  tx: tokio::sync::mpsc::Sender<u32>,
+) -> Result<(), AnyError> {
+  let conn = h3_quinn::Connection::new(connecting.await?);
+  let mut conn = h3::server::Connection::<_, BufView>::new(conn).await?;
+  while let Some((request, stream)) = conn.accept().await? {
+    spawn_local(serve_http3_request(
+      request,
+      stream,
+      request_info.clone(),
+      options,
+      idle.clone(),
+      tx.clone(),
+    ));
+  }
+  Ok(())
+}
+
+async fn serve_http3_request(
+  request: hyper1::Request<()>,
+  stream: RequestStream<h3_quinn::BidiStream<BufView>, BufView>,
+  request_info: HttpConnectionProperties,
+  options: HttpServerOptions,
+  idle: ConnectionIdle,
+  tx: tokio::sync::mpsc::Sender<u32>,
+) {
+  let (mut send, recv) = stream.split();
+  let res =
+    respond_http3(request, &mut send, recv, request_info, options, idle, tx)
+      .await;
+  if let Err(err) = res {
+    log::debug!("Failed to serve HTTP/3 request: {err}");
+    // Reset the stream, so that the client sees the response as failed
+    // rather than as ending early.
+    send.stop_stream(h3::error::Code::H3_INTERNAL_ERROR);
+  }
+}
+
+async fn respond_http3(
+  request: hyper1::Request<()>,
+  send: &mut RequestStream<h3_quinn::SendStream<BufView>, BufView>,
+  recv: RequestStream<h3_quinn::RecvStream, BufView>,
+  request_info: HttpConnectionProperties,
+  options: HttpServerOptions,
+  idle: ConnectionIdle,
+  tx: tokio::sync::mpsc::Sender<u32>,
+) -> Result<(), AnyError> {
+  let request =
+    request.map(|_| IncomingBody::Http3(Http3RequestBody::new(recv)));
+  let response = new_slab_future(
+    request,
+    request_info,
+    InterimResponseWriter::default(),
+    options,
+    &idle,
+    None,
+    tx,
+  )
+  .await?;
+
+  let (parts, mut body) = response.into_parts();
+  send
+    .send_response(hyper1::Response::from_parts(parts, ()))
+    .await?;
+  while let Some(frame) = poll_fn(|cx| Pin::new(&mut body).poll_frame(cx)).await
+  {
+    let frame = match frame?.into_data() {
+      Ok(data) => {
+        send.send_data(data).await?;
+        continue;
+      }
+      Err(frame) => frame,
+    };
+    if let Ok(trailers) = frame.into_trailers() {
+      send.send_trailers(trailers).await?;
+      return Ok(());
+    }
+  }
+  send.finish().await?;
+  Ok(())
+}
+
+/// The body of an HTTP/3 request, read from the receiving half of its request stream.
+pub struct Http3RequestBody(
+  LocalBoxStream<'static, Result<Frame<Bytes>, AnyError>>,
+);
+
+impl Http3RequestBody {
+  fn new(recv: RequestStream<h3_quinn::RecvStream, BufView>) -> Self {
+    let stream = deno_core::futures::stream::unfold(Some(recv), |recv| async {
+      let mut recv = recv?;
+      let frame = match recv.recv_data().await {
+        Ok(Some(mut data)) => {
+          let data = data.copy_to_bytes(data.remaining());
+          return Some((Ok(Frame::data(data)), Some(recv)));
+        }
+        Ok(None) => match recv.recv_trailers().await {
+          Ok(Some(trailers)) => Ok(Frame::trailers(trailers)),
+          Ok(None) => return None,
+          Err(err) => Err(err.into()),
+        },
+        Err(err) => Err(err.into()),
+      };
+      Some((frame, None))
+    });
+    Self(stream.boxed_local())
+  }
+}
+
+impl Body for Http3RequestBody {
+  type Data = Bytes;
+  type Error = AnyError;
+
+  fn poll_frame(
+    mut self: Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+  ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+    Pin::new(&mut self.0).poll_next(cx)
+  }
+}
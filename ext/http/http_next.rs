@@ -1,7 +1,15 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+use crate::connection_limit::Admission;
+use crate::connection_limit::ConnectionLimit;
 use crate::extract_network_stream;
+use crate::http3::alt_svc;
+use crate::http3::listen_http3;
+use crate::http3::serve_http3;
+use crate::interim_response::early_hints;
+use crate::interim_response::InterimResponseWriter;
 use crate::network_buffered_stream::NetworkStreamPrefixCheck;
 use crate::request_body::HttpRequestBody;
+use crate::request_body::IncomingBody;
 use crate::request_properties::DefaultHttpRequestProperties;
 use crate::request_properties::HttpConnectionProperties;
 use crate::request_properties::HttpListenProperties;
@@ -10,8 +18,12 @@ use crate::response_body::CompletionHandle;
 use crate::response_body::ResponseBytes;
 use crate::response_body::ResponseBytesInner;
 use crate::response_body::V8StreamHttpResponseBody;
+use crate::server_options::HttpServerOptions;
+use crate::timeouts::ConnectionIdle;
+use crate::timeouts::TokioTimer;
 use crate::websocket_upgrade::WebSocketUpgrade;
 use crate::LocalExecutor;
+use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::futures::TryFutureExt;
 use deno_core::op;
@@ -31,8 +43,11 @@ use deno_net::ops_tls::TlsStream;
 use deno_net::raw::put_network_stream_resource;
 use deno_net::raw::NetworkStream;
 use deno_net::raw::NetworkStreamAddress;
+use deno_net::raw::NetworkStreamListener;
+use deno_net::raw::NetworkStreamType;
 use http::request::Parts;
 use hyper1::body::Incoming;
+use hyper1::header::ALT_SVC;
 use hyper1::header::COOKIE;
 use hyper1::http::HeaderName;
 use hyper1::http::HeaderValue;
@@ -41,8 +56,10 @@ use hyper1::server::conn::http2;
 use hyper1::service::service_fn;
 use hyper1::service::HttpService;
 use hyper1::upgrade::OnUpgrade;
+use hyper1::HeaderMap;
 
 use hyper1::StatusCode;
+use hyper1::Version;
 use pin_project::pin_project;
 use pin_project::pinned_drop;
 use slab::Slab;
@@ -55,6 +72,7 @@ use std::net::SocketAddr;
 use std::net::SocketAddrV4;
 use std::pin::Pin;
 use std::rc::Rc;
+use std::time::Duration;
 
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWriteExt;
@@ -79,6 +97,9 @@ type Response = hyper1::Response<ResponseBytes>;
 /// MUST be followed by a SETTINGS frame (Section 6.5), which MAY be empty.
 const HTTP2_PREFIX: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
 
+/// The smallest read buffer hyper allows for HTTP/1.1 connections.
+const HTTP1_MIN_BUF_SIZE: usize = 8192;
+
 /// ALPN negotation for "h2"
 const TLS_ALPN_HTTP_2: &[u8] = b"h2";
 
@@ -99,11 +120,15 @@ impl<
 pub struct HttpSlabRecord {
   request_info: HttpConnectionProperties,
   request_parts: Parts,
-  request_body: Option<Incoming>,
+  request_body: Option<IncomingBody>,
   // The response may get taken before we tear this down
   response: Option<Response>,
   body: Option<Rc<HttpRequestBody>>,
   promise: CompletionHandle,
+  interim: InterimResponseWriter,
+  request_body_idle_timeout: Option<Duration>,
+  request_body_high_water_mark: Option<u32>,
+  overloaded: bool,
   #[cfg(__zombie_http_tracking)]
   alive: bool,
 }
@@ -188,7 +213,7 @@ with!(with_req, with_req_mut, Parts, http, http.request_parts);
 with!(
   with_req_body,
   with_req_body_mut,
-  Option<Incoming>,
+  Option<IncomingBody>,
   http,
   http.request_body
 );
@@ -216,18 +241,27 @@ with!(
 with!(with_http, with_http_mut, HttpSlabRecord, http, http);
 
 fn slab_insert(
-  request: Request,
+  request: hyper1::Request<IncomingBody>,
   request_info: HttpConnectionProperties,
+  interim: InterimResponseWriter,
+  options: HttpServerOptions,
+  idle: &ConnectionIdle,
 ) -> u32 {
   SLAB.with(|slab| {
     let (request_parts, request_body) = request.into_parts();
+    let mut body = ResponseBytes::default();
+    body.set_idle_guard(idle.guard());
     slab.borrow_mut().insert(HttpSlabRecord {
       request_info,
       request_parts,
       request_body: Some(request_body),
-      response: Some(Response::new(ResponseBytes::default())),
+      response: Some(Response::new(body)),
       body: None,
       promise: CompletionHandle::default(),
+      interim,
+      request_body_idle_timeout: options.request_body_idle_timeout(),
+      request_body_high_water_mark: options.request_body_high_water_mark(),
+      overloaded: options.is_overloaded(),
       #[cfg(__zombie_http_tracking)]
       alive: true,
     })
@@ -309,6 +343,49 @@ pub fn op_http_upgrade_raw(
   )
 }
 
+/// Hands over the connection of an `Upgrade` (or `CONNECT`) request once the handler's response
+/// has been sent, returning the raw network stream along with any bytes the client sent after
+/// the request head.
+#[op]
+pub fn op_http_upgrade_stream(
+  state: Rc<RefCell<OpState>>,
+  index: u32,
+) -> Result<
+  impl Future<Output = Result<(ResourceId, &'static str, ZeroCopyBuf), AnyError>>
+    + 'static,
+  AnyError,
+> {
+  // The upgrade has to be taken now, as the request is torn down once the response is sent
+  let upgrade = with_http_mut(index, |http| {
+    http
+      .request_parts
+      .extensions
+      .remove::<OnUpgrade>()
+      .ok_or_else(|| AnyError::msg("upgrade unavailable"))
+  })?;
+
+  Ok(async move {
+    // This only resolves if the response was `101 Switching Protocols` (or a successful response
+    // to `CONNECT`)
+    let upgraded = upgrade.await?;
+    let (stream, bytes) = extract_network_stream(upgraded);
+    let conn_type = match stream.stream() {
+      NetworkStreamType::Tcp => "tcp",
+      NetworkStreamType::Tls => "tls",
+      #[cfg(unix)]
+      NetworkStreamType::Unix => "unix",
+    };
+    Ok((
+      put_network_stream_resource(
+        &mut state.borrow_mut().resource_table,
+        stream,
+      )?,
+      conn_type,
+      ZeroCopyBuf::from(bytes.to_vec()),
+    ))
+  })
+}
+
 #[op]
 pub async fn op_http_upgrade_next(
   state: Rc<RefCell<OpState>>,
@@ -352,6 +429,34 @@ pub async fn op_http_upgrade_next(
   ))
 }
 
+#[op]
+pub fn op_http_send_early_hints(
+  index: u32,
+  headers: Vec<(ByteString, ByteString)>,
+) -> Result<bool, AnyError> {
+  with_http(index, |http| {
+    if http.promise.is_completed() {
+      return Err(type_error("Response already sent"));
+    }
+
+    // Informational responses must not be sent to HTTP/1.0 clients (RFC 9110, section 15.2)
+    if http.request_parts.version != Version::HTTP_11
+      || !http.interim.is_supported()
+    {
+      return Ok(false);
+    }
+
+    let mut hints = Vec::with_capacity(headers.len());
+    for (name, value) in headers {
+      hints.push((
+        HeaderName::from_bytes(&name)?,
+        HeaderValue::from_bytes(&value)?,
+      ));
+    }
+    Ok(http.interim.send(early_hints(), &hints))
+  })
+}
+
 #[op(fast)]
 pub fn op_http_set_promise_complete(index: u32, status: u16) {
   with_resp_mut(index, |resp| {
@@ -445,8 +550,19 @@ pub fn op_http_read_request_body(
   state: &mut OpState,
   index: u32,
 ) -> ResourceId {
-  let incoming = with_req_body_mut(index, |body| body.take().unwrap());
-  let body_resource = Rc::new(HttpRequestBody::new(incoming));
+  let (incoming, idle_timeout, high_water_mark) =
+    with_http_mut(index, |http| {
+      (
+        http.request_body.take().unwrap(),
+        http.request_body_idle_timeout,
+        http.request_body_high_water_mark,
+      )
+    });
+  let body_resource = Rc::new(HttpRequestBody::new(
+    incoming,
+    idle_timeout,
+    high_water_mark,
+  ));
   let res = state.resource_table.add_rc(body_resource.clone());
   with_body_mut(index, |body| {
     *body = Some(body_resource);
@@ -516,18 +632,91 @@ pub fn op_http_set_response_body_stream(
 ) -> Result<ResourceId, AnyError> {
   // TODO(mmastrac): what should this channel size be?
   let (tx, rx) = tokio::sync::mpsc::channel(1);
-  let (tx, rx) = (
-    V8StreamHttpResponseBody::new(tx),
-    ResponseBytesInner::V8Stream(rx),
-  );
-
-  with_resp_mut(index, move |response| {
-    response.as_mut().unwrap().body_mut().initialize(rx);
+  let tx = with_resp_mut(index, move |response| {
+    let body = response.as_mut().unwrap().body_mut();
+    body.initialize(ResponseBytesInner::V8Stream(rx));
+    V8StreamHttpResponseBody::new(tx, body.trailers_handle())
   });
 
   Ok(state.resource_table.add(tx))
 }
 
+fn trailers_to_header_map(
+  trailers: Vec<(ByteString, ByteString)>,
+) -> Result<HeaderMap, AnyError> {
+  let mut map = HeaderMap::with_capacity(trailers.len());
+  for (name, value) in trailers {
+    map.append(
+      HeaderName::from_bytes(&name)?,
+      HeaderValue::from_bytes(&value)?,
+    );
+  }
+  Ok(map)
+}
+
+#[op]
+pub fn op_http_set_response_trailers(
+  index: u32,
+  trailers: Vec<(ByteString, ByteString)>,
+) -> Result<(), AnyError> {
+  let trailers = trailers_to_header_map(trailers)?;
+  with_resp(index, |response| {
+    response
+      .as_ref()
+      .unwrap()
+      .body()
+      .trailers_handle()
+      .set(trailers);
+  });
+  Ok(())
+}
+
+#[op]
+pub fn op_http_set_response_body_stream_trailers(
+  state: &mut OpState,
+  rid: ResourceId,
+  trailers: Vec<(ByteString, ByteString)>,
+) -> Result<(), AnyError> {
+  let trailers = trailers_to_header_map(trailers)?;
+  let body = state.resource_table.get::<V8StreamHttpResponseBody>(rid)?;
+  body.set_trailers(trailers);
+  Ok(())
+}
+
+#[op]
+pub fn op_http_get_request_trailers(
+  index: u32,
+) -> Option<Vec<(ByteString, ByteString)>> {
+  // The body resource may already be closed once it has been fully read, so we go through
+  // the request record rather than the resource table.
+  let trailers = with_body(index, |body| body.as_ref()?.trailers())?;
+  Some(
+    trailers
+      .iter()
+      .map(|(name, value)| (name.as_str().into(), value.as_bytes().into()))
+      .collect(),
+  )
+}
+
+/// The number of bytes of the request body that have been received but not yet read by the
+/// handler.
+#[op(fast)]
+pub fn op_http_get_request_body_buffered(index: u32) -> u32 {
+  with_body(index, |body| {
+    body
+      .as_ref()
+      .map(|body| body.buffered())
+      .unwrap_or_default()
+  }) as u32
+}
+
+/// Whether the request was received on a connection accepted beyond the server's connection
+/// limit, in which case it should be answered by the overload handler.
+#[op(fast)]
+pub fn op_http_is_overloaded(index: u32) -> bool {
+  with_http(index, |http| http.overloaded)
+}
+
 #[op(fast)]
 pub fn op_http_set_response_body_text(index: u32, text: String) {
   if !text.is_empty() {
@@ -579,16 +768,30 @@ pub async fn op_http_track(
 }
 
 #[pin_project(PinnedDrop)]
-pub struct SlabFuture<F: Future<Output = ()>>(u32, #[pin] F);
+pub struct SlabFuture<F: Future<Output = ()>>(
+  u32,
+  Option<HeaderValue>,
+  #[pin] F,
+);
 
 pub fn new_slab_future(
-  request: Request,
+  request: hyper1::Request<impl Into<IncomingBody>>,
   request_info: HttpConnectionProperties,
+  interim: InterimResponseWriter,
+  options: HttpServerOptions,
+  idle: &ConnectionIdle,
+  alt_svc: Option<HeaderValue>,
   tx: tokio::sync::mpsc::Sender<u32>,
 ) -> SlabFuture<impl Future<Output = ()>> {
-  let index = slab_insert(request, request_info);
+  let index = slab_insert(
+    request.map(Into::into),
+    request_info,
+    interim,
+    options,
+    idle,
+  );
   let rx = with_promise(index, |promise| promise.clone());
-  SlabFuture(index, async move {
+  SlabFuture(index, alt_svc, async move {
     if tx.send(index).await.is_ok() {
       // We only need to wait for completion if we aren't closed
       rx.await;
@@ -622,21 +825,37 @@ impl<F: Future<Output = ()>> Future for SlabFuture<F> {
     cx: &mut std::task::Context<'_>,
   ) -> std::task::Poll<Self::Output> {
     let index = self.0;
-    self
-      .project()
-      .1
-      .poll(cx)
-      .map(|_| Ok(with_resp_mut(index, |resp| resp.take().unwrap())))
+    let this = self.project();
+    this.2.poll(cx).map(|_| {
+      let mut response = with_resp_mut(index, |resp| resp.take().unwrap());
+      if let Some(alt_svc) = this.1.take() {
+        let headers = response.headers_mut();
+        if !headers.contains_key(ALT_SVC) {
+          headers.insert(ALT_SVC, alt_svc);
+        }
+      }
+      Ok(response)
+    })
   }
 }
 
 fn serve_http11_unconditional(
   io: impl HttpServeStream,
   svc: impl HttpService<Incoming, ResBody = ResponseBytes> + 'static,
+  interim: InterimResponseWriter,
+  options: HttpServerOptions,
 ) -> impl Future<Output = Result<(), AnyError>> + 'static {
-  let conn = http1::Builder::new()
-    .keep_alive(true)
-    .serve_connection(io, svc);
+  let mut builder = http1::Builder::new();
+  // Connections beyond the connection limit are closed once they have been answered
+  builder.keep_alive(!options.is_overloaded());
+  if let Some(timeout) = options.headers_timeout() {
+    builder.timer(TokioTimer).header_read_timeout(timeout);
+  }
+  if let Some(high_water_mark) = options.request_body_high_water_mark() {
+    // hyper won't read more than this from the socket until the buffered body has been consumed
+    builder.max_buf_size((high_water_mark as usize).max(HTTP1_MIN_BUF_SIZE));
+  }
+  let conn = builder.serve_connection(interim.wrap(io), svc);
 
   conn.with_upgrades().map_err(AnyError::from)
 }
@@ -644,78 +863,134 @@ fn serve_http11_unconditional(
 fn serve_http2_unconditional(
   io: impl HttpServeStream,
   svc: impl HttpService<Incoming, ResBody = ResponseBytes> + 'static,
+  options: HttpServerOptions,
 ) -> impl Future<Output = Result<(), AnyError>> + 'static {
-  let conn = http2::Builder::new(LocalExecutor).serve_connection(io, svc);
+  let mut builder = http2::Builder::new(LocalExecutor);
+  // The flow control window limits how much a client may send on a stream before we have
+  // consumed the body
+  builder.initial_stream_window_size(options.request_body_high_water_mark());
+  builder.max_concurrent_streams(options.max_concurrent_streams());
+  let conn = builder.serve_connection(io, svc);
   conn.map_err(AnyError::from)
 }
 
 async fn serve_http2_autodetect(
   io: impl HttpServeStream,
   svc: impl HttpService<Incoming, ResBody = ResponseBytes> + 'static,
+  interim: InterimResponseWriter,
+  options: HttpServerOptions,
 ) -> Result<(), AnyError> {
   let prefix = NetworkStreamPrefixCheck::new(io, HTTP2_PREFIX);
   let (matches, io) = prefix.match_prefix().await?;
   if matches {
-    serve_http2_unconditional(io, svc).await
+    serve_http2_unconditional(io, svc, options).await
   } else {
-    serve_http11_unconditional(io, svc).await
+    serve_http11_unconditional(io, svc, interim, options).await
+  }
+}
+
+/// Drops the connection once no request has been in flight on it for longer than the keep-alive
+/// timeout.
+pub(crate) async fn with_keep_alive_timeout(
+  conn: impl Future<Output = Result<(), AnyError>>,
+  idle: ConnectionIdle,
+  options: HttpServerOptions,
+) -> Result<(), AnyError> {
+  let Some(timeout) = options.keep_alive_timeout() else {
+    return conn.await;
+  };
+  tokio::select! {
+    res = conn => res,
+    _ = idle.timeout(timeout) => Ok(()),
   }
 }
 
 fn serve_https(
   mut io: TlsStream,
   request_info: HttpConnectionProperties,
+  options: HttpServerOptions,
+  admission: Admission,
+  alt_svc: Option<HeaderValue>,
   cancel: Rc<CancelHandle>,
   tx: tokio::sync::mpsc::Sender<u32>,
 ) -> JoinHandle<Result<(), AnyError>> {
+  let interim = InterimResponseWriter::default();
+  let interim_clone = interim.clone();
+  let idle = ConnectionIdle::default();
+  let idle_clone = idle.clone();
   // TODO(mmastrac): This is faster if we can use tokio::spawn but then the send bounds get us
   let svc = service_fn(move |req: Request| {
-    new_slab_future(req, request_info.clone(), tx.clone())
+    new_slab_future(
+      req,
+      request_info.clone(),
+      interim_clone.clone(),
+      options,
+      &idle_clone,
+      alt_svc.clone(),
+      tx.clone(),
+    )
   });
-  spawn_local(
-    async {
-      io.handshake().await?;
-      // If the client specifically negotiates a protocol, we will use it. If not, we'll auto-detect
-      // based on the prefix bytes
-      let handshake = io.get_ref().1.alpn_protocol();
-      if handshake == Some(TLS_ALPN_HTTP_2) {
-        serve_http2_unconditional(io, svc).await
-      } else if handshake == Some(TLS_ALPN_HTTP_11) {
-        serve_http11_unconditional(io, svc).await
-      } else {
-        serve_http2_autodetect(io, svc).await
-      }
+  let conn = async move {
+    io.handshake().await?;
+    // If the client specifically negotiates a protocol, we will use it. If not, we'll auto-detect
+    // based on the prefix bytes
+    let handshake = io.get_ref().1.alpn_protocol();
+    if handshake == Some(TLS_ALPN_HTTP_2) {
+      serve_http2_unconditional(io, svc, options).await
+    } else if handshake == Some(TLS_ALPN_HTTP_11) {
+      serve_http11_unconditional(io, svc, interim, options).await
+    } else {
+      serve_http2_autodetect(io, svc, interim, options).await
     }
-    .try_or_cancel(cancel),
+  };
+  spawn_local(
+    admission
+      .hold(with_keep_alive_timeout(conn, idle, options).try_or_cancel(cancel)),
   )
 }
 
 fn serve_http(
   io: impl HttpServeStream,
   request_info: HttpConnectionProperties,
+  options: HttpServerOptions,
+  admission: Admission,
   cancel: Rc<CancelHandle>,
   tx: tokio::sync::mpsc::Sender<u32>,
 ) -> JoinHandle<Result<(), AnyError>> {
+  let interim = InterimResponseWriter::default();
+  let interim_clone = interim.clone();
+  let idle = ConnectionIdle::default();
+  let idle_clone = idle.clone();
   // TODO(mmastrac): This is faster if we can use tokio::spawn but then the send bounds get us
   let svc = service_fn(move |req: Request| {
-    new_slab_future(req, request_info.clone(), tx.clone())
+    new_slab_future(
+      req,
+      request_info.clone(),
+      interim_clone.clone(),
+      options,
+      &idle_clone,
+      None,
+      tx.clone(),
+    )
   });
-  spawn_local(serve_http2_autodetect(io, svc).try_or_cancel(cancel))
+  let conn = serve_http2_autodetect(io, svc, interim, options);
+  spawn_local(
+    admission
+      .hold(with_keep_alive_timeout(conn, idle, options).try_or_cancel(cancel)),
+  )
 }
 
 fn serve_http_on(
   network_stream: NetworkStream,
+  peer_address: NetworkStreamAddress,
   listen_properties: &HttpListenProperties,
+  options: HttpServerOptions,
+  admission: Admission,
+  alt_svc: Option<HeaderValue>,
   cancel: Rc<CancelHandle>,
   tx: tokio::sync::mpsc::Sender<u32>,
 ) -> JoinHandle<Result<(), AnyError>> {
-  // We always want some sort of peer address. If we can't get one, just make up one.
-  let peer_address = network_stream.peer_address().unwrap_or_else(|_| {
-    NetworkStreamAddress::Ip(SocketAddr::V4(SocketAddrV4::new(
-      Ipv4Addr::new(0, 0, 0, 0),
-      0,
-    )))
-  });
+  let options = admission.options(options);
   let connection_properties: HttpConnectionProperties =
     DefaultHttpRequestProperties::connection_properties(
       listen_properties,
@@ -724,14 +999,20 @@ fn serve_http_on(
 
   match network_stream {
     NetworkStream::Tcp(conn) => {
-      serve_http(conn, connection_properties, cancel, tx)
-    }
-    NetworkStream::Tls(conn) => {
-      serve_https(conn, connection_properties, cancel, tx)
+      serve_http(conn, connection_properties, options, admission, cancel, tx)
     }
+    NetworkStream::Tls(conn) => serve_https(
+      conn,
+      connection_properties,
+      options,
+      admission,
+      alt_svc,
+      cancel,
+      tx,
+    ),
     #[cfg(unix)]
     NetworkStream::Unix(conn) => {
-      serve_http(conn, connection_properties, cancel, tx)
+      serve_http(conn, connection_properties, options, admission, cancel, tx)
     }
   }
 }
@@ -770,8 +1051,10 @@ impl Drop for HttpJoinHandle {
 pub fn op_http_serve(
   state: Rc<RefCell<OpState>>,
   listener_rid: ResourceId,
+  options: HttpServerOptions,
+  http3: bool,
 ) -> Result<(ResourceId, &'static str, String), AnyError> {
-  let listener =
+  let mut listener =
     DefaultHttpRequestProperties::get_network_stream_listener_for_rid(
       &mut state.borrow_mut(),
       listener_rid,
@@ -783,6 +1066,23 @@ pub fn op_http_serve(
     &local_address,
   );
 
+  // HTTP/3 is served over UDP on the same port as the TLS listener, which advertises it to
+  // clients through the `alt-svc` header.
+  let http3 = if http3 {
+    let (NetworkStreamListener::Tls(acceptor), NetworkStreamAddress::Ip(addr)) =
+      (&listener, &local_address)
+    else {
+      return Err(type_error("HTTP/3 requires a TLS listener"));
+    };
+    Some(listen_http3(*addr, acceptor.config(), options)?)
+  } else {
+    None
+  };
+  let alt_svc = match &http3 {
+    Some(endpoint) => Some(alt_svc(endpoint.local_addr()?.port())),
+    None => None,
+  };
+
   let (tx, rx) = tokio::sync::mpsc::channel(10);
   let resource: Rc<HttpJoinHandle> = Rc::new(HttpJoinHandle(
     AsyncRefCell::new(None),
@@ -791,16 +1091,41 @@ pub fn op_http_serve(
   ));
   let cancel_clone = resource.cancel_handle();
 
+  let limit = ConnectionLimit::new(&options);
+  if let Some(endpoint) = http3 {
+    spawn_local(
+      serve_http3(
+        endpoint,
+        listen_properties.clone(),
+        options,
+        limit.clone(),
+        cancel_clone.clone(),
+        tx.clone(),
+      )
+      .try_or_cancel(cancel_clone.clone()),
+    );
+  }
+
   let listen_properties_clone: HttpListenProperties = listen_properties.clone();
   let handle = spawn_local(async move {
     loop {
-      let conn = listener
+      let reserved = limit.reserve().or_cancel(cancel_clone.clone()).await?;
+      let (conn, peer_address) = listener
         .accept()
         .try_or_cancel(cancel_clone.clone())
         .await?;
+      let admission = limit.admit(reserved);
+      if admission.is_refused() {
+        // Dropping the connection closes it
+        continue;
+      }
       serve_http_on(
         conn,
+        peer_address,
         &listen_properties_clone,
+        options,
+        admission,
+        alt_svc.clone(),
         cancel_clone.clone(),
         tx.clone(),
       );
@@ -825,6 +1150,7 @@ pub fn op_http_serve(
 pub fn op_http_serve_on(
   state: Rc<RefCell<OpState>>,
   conn: ResourceId,
+  options: HttpServerOptions,
 ) -> Result<(ResourceId, &'static str, String), AnyError> {
   let network_stream: NetworkStream =
     DefaultHttpRequestProperties::get_network_stream_for_rid(
@@ -837,6 +1163,13 @@ pub fn op_http_serve_on(
     network_stream.stream(),
     &local_address,
   );
+  // We always want some sort of peer address. If we can't get one, just make up one.
+  let peer_address = network_stream.peer_address().unwrap_or_else(|_| {
+    NetworkStreamAddress::Ip(SocketAddr::V4(SocketAddrV4::new(
+      Ipv4Addr::new(0, 0, 0, 0),
+      0,
+    )))
+  });
 
   let (tx, rx) = tokio::sync::mpsc::channel(10);
   let resource: Rc<HttpJoinHandle> = Rc::new(HttpJoinHandle(
@@ -847,7 +1180,11 @@ pub fn op_http_serve_on(
 
   let handle: JoinHandle<Result<(), deno_core::anyhow::Error>> = serve_http_on(
     network_stream,
+    peer_address,
     &listen_properties,
+    options,
+    Admission::Unlimited,
+    None,
     resource.cancel_handle(),
     tx,
   );
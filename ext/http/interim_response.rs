@@ -0,0 +1,232 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+use deno_core::futures::ready;
+use hyper1::http::HeaderName;
+use hyper1::http::HeaderValue;
+use hyper1::StatusCode;
+use std::io;
+use std::io::IoSlice;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
+
+/// `103 Early Hints` (RFC 8297), which the `http` crate doesn't have a constant for.
+pub fn early_hints() -> StatusCode {
+  StatusCode::from_u16(103).unwrap()
+}
+
+#[derive(Default)]
+struct InterimResponseState {
+  enabled: bool,
+  pending: Vec<u8>,
+  waker: Option<Waker>,
+}
+
+/// A handle to an HTTP/1.1 connection that allows informational (1xx) responses, such as
+/// `103 Early Hints`, to be written out-of-band while the final response is still being
+/// produced.
+///
+/// hyper has no API for sending informational responses from a server, but because HTTP/1.1
+/// connections only write one response at a time, we can safely interleave the serialized
+/// informational head into the stream before hyper writes anything else. The connection task
+/// is woken up so the bytes are flushed even if hyper has nothing else to write.
+///
+/// HTTP/2 connections are never wrapped, so the writer reports that it is unsupported.
+#[derive(Clone, Default)]
+pub struct InterimResponseWriter(Arc<Mutex<InterimResponseState>>);
+
+impl InterimResponseWriter {
+  /// Returns true if this writer is attached to a connection that can carry informational
+  /// responses.
+  pub fn is_supported(&self) -> bool {
+    self.0.lock().unwrap().enabled
+  }
+
+  /// Queues an informational response with the given status and headers. Returns false if
+  /// the underlying connection doesn't support informational responses.
+  pub fn send(
+    &self,
+    status: StatusCode,
+    headers: &[(HeaderName, HeaderValue)],
+  ) -> bool {
+    debug_assert!(status.is_informational());
+    let mut state = self.0.lock().unwrap();
+    if !state.enabled {
+      return false;
+    }
+
+    let pending = &mut state.pending;
+    pending.extend_from_slice(b"HTTP/1.1 ");
+    pending.extend_from_slice(status.as_str().as_bytes());
+    pending.push(b' ');
+    let reason = match status.as_u16() {
+      103 => "Early Hints",
+      _ => status.canonical_reason().unwrap_or_default(),
+    };
+    pending.extend_from_slice(reason.as_bytes());
+    pending.extend_from_slice(b"\r\n");
+    for (name, value) in headers {
+      pending.extend_from_slice(name.as_str().as_bytes());
+      pending.extend_from_slice(b": ");
+      pending.extend_from_slice(value.as_bytes());
+      pending.extend_from_slice(b"\r\n");
+    }
+    pending.extend_from_slice(b"\r\n");
+
+    if let Some(waker) = state.waker.take() {
+      drop(state);
+      waker.wake();
+    }
+    true
+  }
+
+  /// Wraps an HTTP/1.1 stream, enabling informational responses on this writer.
+  pub fn wrap<S: AsyncRead + AsyncWrite + Unpin>(
+    &self,
+    io: S,
+  ) -> InterimResponseStream<S> {
+    self.0.lock().unwrap().enabled = true;
+    InterimResponseStream {
+      io,
+      writer: self.clone(),
+    }
+  }
+}
+
+/// A stream that writes any queued informational responses from an [`InterimResponseWriter`]
+/// before passing writes through to the underlying stream.
+pub struct InterimResponseStream<S: AsyncRead + AsyncWrite + Unpin> {
+  io: S,
+  writer: InterimResponseWriter,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> InterimResponseStream<S> {
+  pub fn into_inner(self) -> S {
+    self.writer.0.lock().unwrap().enabled = false;
+    self.io
+  }
+
+  fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    let mut state = self.writer.0.lock().unwrap();
+    if !state
+      .waker
+      .as_ref()
+      .map_or(false, |w| w.will_wake(cx.waker()))
+    {
+      state.waker = Some(cx.waker().clone());
+    }
+    while !state.pending.is_empty() {
+      let n = ready!(Pin::new(&mut self.io).poll_write(cx, &state.pending))?;
+      if n == 0 {
+        return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+      }
+      state.pending.drain(..n);
+    }
+    Poll::Ready(Ok(()))
+  }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncRead for InterimResponseStream<S> {
+  fn poll_read(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &mut ReadBuf<'_>,
+  ) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.io).poll_read(cx, buf)
+  }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncWrite
+  for InterimResponseStream<S>
+{
+  fn poll_write(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &[u8],
+  ) -> Poll<io::Result<usize>> {
+    ready!(self.poll_drain(cx))?;
+    Pin::new(&mut self.io).poll_write(cx, buf)
+  }
+
+  fn poll_write_vectored(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    bufs: &[IoSlice<'_>],
+  ) -> Poll<io::Result<usize>> {
+    ready!(self.poll_drain(cx))?;
+    Pin::new(&mut self.io).poll_write_vectored(cx, bufs)
+  }
+
+  fn is_write_vectored(&self) -> bool {
+    self.io.is_write_vectored()
+  }
+
+  fn poll_flush(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<io::Result<()>> {
+    ready!(self.poll_drain(cx))?;
+    Pin::new(&mut self.io).poll_flush(cx)
+  }
+
+  fn poll_shutdown(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.io).poll_shutdown(cx)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use hyper1::header::LINK;
+  use tokio::io::AsyncReadExt;
+  use tokio::io::AsyncWriteExt;
+
+  #[test]
+  fn test_unsupported_without_wrap() {
+    let writer = InterimResponseWriter::default();
+    assert!(!writer.is_supported());
+    assert!(!writer.send(early_hints(), &[]));
+  }
+
+  #[tokio::test]
+  async fn test_interim_response_written_first() {
+    let (mut client, server) = tokio::io::duplex(1024);
+    let writer = InterimResponseWriter::default();
+    let mut stream = writer.wrap(server);
+    assert!(writer.is_supported());
+    assert!(writer.send(
+      early_hints(),
+      &[(LINK, HeaderValue::from_static("</style.css>; rel=preload"))]
+    ));
+    stream.write_all(b"HTTP/1.1 200 OK\r\n\r\n").await.unwrap();
+    drop(stream);
+
+    let mut output = String::new();
+    client.read_to_string(&mut output).await.unwrap();
+    assert_eq!(
+      output,
+      "HTTP/1.1 103 Early Hints\r\nlink: </style.css>; rel=preload\r\n\r\nHTTP/1.1 200 OK\r\n\r\n"
+    );
+  }
+
+  #[tokio::test]
+  async fn test_interim_response_flushed() {
+    let (mut client, server) = tokio::io::duplex(1024);
+    let writer = InterimResponseWriter::default();
+    let mut stream = writer.wrap(server);
+    writer.send(early_hints(), &[]);
+    stream.flush().await.unwrap();
+
+    let mut buf = [0; 26];
+    client.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"HTTP/1.1 103 Early Hints\r\n");
+  }
+}
@@ -70,17 +70,23 @@ use tokio::io::AsyncWrite;
 use tokio::io::AsyncWriteExt;
 use tokio::task::spawn_local;
 
+use crate::interim_response::InterimResponseStream;
 use crate::network_buffered_stream::NetworkBufferedStream;
 use crate::reader_stream::ExternallyAbortableReaderStream;
 use crate::reader_stream::ShutdownHandle;
 
 pub mod compressible;
+mod connection_limit;
+mod http3;
 mod http_next;
+mod interim_response;
 mod network_buffered_stream;
 mod reader_stream;
 mod request_body;
 mod request_properties;
 mod response_body;
+mod server_options;
+mod timeouts;
 mod websocket_upgrade;
 
 deno_core::extension!(
@@ -95,21 +101,28 @@ deno_core::extension!(
     op_http_write_headers,
     op_http_write_resource,
     op_http_write,
+    http_next::op_http_get_request_body_buffered,
     http_next::op_http_get_request_header,
     http_next::op_http_get_request_headers,
     http_next::op_http_get_request_method_and_url,
+    http_next::op_http_get_request_trailers,
+    http_next::op_http_is_overloaded,
     http_next::op_http_read_request_body,
+    http_next::op_http_send_early_hints,
     http_next::op_http_serve_on,
     http_next::op_http_serve,
     http_next::op_http_set_promise_complete,
     http_next::op_http_set_response_body_bytes,
     http_next::op_http_set_response_body_resource,
     http_next::op_http_set_response_body_stream,
+    http_next::op_http_set_response_body_stream_trailers,
     http_next::op_http_set_response_body_text,
     http_next::op_http_set_response_header,
     http_next::op_http_set_response_headers,
+    http_next::op_http_set_response_trailers,
     http_next::op_http_track,
     http_next::op_http_upgrade_raw,
+    http_next::op_http_upgrade_stream,
     http_next::op_http_upgrade_next,
     http_next::op_http_wait,
   ],
@@ -1074,27 +1087,49 @@ fn maybe_extract_network_stream<
     Err(x) => x,
   };
 
-  match upgraded.downcast::<NetworkBufferedStream<T>>() {
+  let upgraded = match upgraded.downcast::<NetworkBufferedStream<T>>() {
     Ok((stream, upgraded_bytes)) => {
-      // Both the upgrade and the stream might have unread bytes
-      let (io, stream_bytes) = stream.into_inner();
-      let bytes = match (stream_bytes.is_empty(), upgraded_bytes.is_empty()) {
-        (false, false) => Bytes::default(),
-        (true, false) => upgraded_bytes,
-        (false, true) => stream_bytes,
-        (true, true) => {
-          // The upgraded bytes come first as they have already been read
-          let mut v = upgraded_bytes.to_vec();
-          v.append(&mut stream_bytes.to_vec());
-          Bytes::from(v)
-        }
-      };
-      Ok((io.into(), bytes))
+      return Ok(extract_buffered_stream(stream, upgraded_bytes))
+    }
+    Err(x) => x,
+  };
+
+  // HTTP/1.1 connections may be wrapped to support informational responses
+  let upgraded = match upgraded.downcast::<InterimResponseStream<T>>() {
+    Ok((stream, bytes)) => return Ok((stream.into_inner().into(), bytes)),
+    Err(x) => x,
+  };
+
+  match upgraded.downcast::<InterimResponseStream<NetworkBufferedStream<T>>>() {
+    Ok((stream, upgraded_bytes)) => {
+      Ok(extract_buffered_stream(stream.into_inner(), upgraded_bytes))
     }
     Err(x) => Err(x),
   }
 }
 
+fn extract_buffered_stream<
+  T: Into<NetworkStream> + AsyncRead + AsyncWrite + Unpin + 'static,
+>(
+  stream: NetworkBufferedStream<T>,
+  upgraded_bytes: Bytes,
+) -> (NetworkStream, Bytes) {
+  // Both the upgrade and the stream might have unread bytes
+  let (io, stream_bytes) = stream.into_inner();
+  let bytes = match (stream_bytes.is_empty(), upgraded_bytes.is_empty()) {
+    (false, false) => Bytes::default(),
+    (true, false) => upgraded_bytes,
+    (false, true) => stream_bytes,
+    (true, true) => {
+      // The upgraded bytes come first as they have already been read
+      let mut v = upgraded_bytes.to_vec();
+      v.append(&mut stream_bytes.to_vec());
+      Bytes::from(v)
+    }
+  };
+  (io.into(), bytes)
+}
+
 fn extract_network_stream<U: CanDowncastUpgrade>(
   upgraded: U,
 ) -> (NetworkStream, Bytes) {
@@ -1,6 +1,8 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+use crate::http3::Http3RequestBody;
 use bytes::Bytes;
 use deno_core::error::AnyError;
+use deno_core::futures::ready;
 use deno_core::futures::stream::Peekable;
 use deno_core::futures::Stream;
 use deno_core::futures::StreamExt;
@@ -10,14 +12,104 @@ use deno_core::BufView;
 use deno_core::RcRef;
 use deno_core::Resource;
 use hyper1::body::Body;
+use hyper1::body::Frame;
 use hyper1::body::Incoming;
 use hyper1::body::SizeHint;
+use hyper1::HeaderMap;
 use std::borrow::Cow;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::future::Future;
 use std::pin::Pin;
 use std::rc::Rc;
+use std::task::Poll;
+use std::time::Duration;
+use tokio::sync::mpsc::unbounded_channel;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::Notify;
+use tokio::task::spawn_local;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio::time::Sleep;
 
-/// Converts a hyper incoming body stream into a stream of [`Bytes`] that we can use to read in V8.
-struct ReadFuture(Incoming);
+/// The body of a request, either received by hyper (HTTP/1.1 and HTTP/2) or read from an
+/// HTTP/3 request stream.
+pub enum IncomingBody {
+  Hyper(Incoming),
+  Http3(Http3RequestBody),
+}
+
+impl From<Incoming> for IncomingBody {
+  fn from(body: Incoming) -> Self {
+    Self::Hyper(body)
+  }
+}
+
+impl Body for IncomingBody {
+  type Data = Bytes;
+  type Error = AnyError;
+
+  fn poll_frame(
+    self: Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+  ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+    match self.get_mut() {
+      Self::Hyper(body) => Pin::new(body).poll_frame(cx).map_err(Into::into),
+      Self::Http3(body) => Pin::new(body).poll_frame(cx),
+    }
+  }
+
+  fn is_end_stream(&self) -> bool {
+    match self {
+      Self::Hyper(body) => body.is_end_stream(),
+      Self::Http3(body) => body.is_end_stream(),
+    }
+  }
+
+  fn size_hint(&self) -> SizeHint {
+    match self {
+      Self::Hyper(body) => body.size_hint(),
+      Self::Http3(body) => body.size_hint(),
+    }
+  }
+}
+
+/// Converts an incoming body stream into a stream of [`Bytes`] that we can use to read in V8. Any
+/// trailers received at the end of the body are stashed away so they can be retrieved separately.
+/// The bytes it yields are added to the count of buffered bytes, until they are read.
+struct ReadFuture(
+  IncomingBody,
+  Rc<RefCell<Option<HeaderMap>>>,
+  Option<IdleTimeout>,
+  Rc<Cell<usize>>,
+);
+
+/// Fails the body if we are left waiting on the client for data for too long. The clock only runs
+/// while we are actually waiting, so a body that isn't being read because of backpressure in JS
+/// doesn't time out.
+struct IdleTimeout {
+  timeout: Duration,
+  sleep: Pin<Box<Sleep>>,
+  waiting: bool,
+}
+
+impl IdleTimeout {
+  fn new(timeout: Duration) -> Self {
+    Self {
+      timeout,
+      sleep: Box::pin(tokio::time::sleep(timeout)),
+      waiting: false,
+    }
+  }
+
+  fn poll_expired(&mut self, cx: &mut std::task::Context<'_>) -> Poll<()> {
+    if !self.waiting {
+      self.waiting = true;
+      self.sleep.as_mut().reset(Instant::now() + self.timeout);
+    }
+    self.sleep.as_mut().poll(cx)
+  }
+}
 
 impl Stream for ReadFuture {
   type Item = Result<Bytes, AnyError>;
@@ -25,34 +117,168 @@ impl Stream for ReadFuture {
   fn poll_next(
     self: Pin<&mut Self>,
     cx: &mut std::task::Context<'_>,
-  ) -> std::task::Poll<Option<Self::Item>> {
-    let res = Pin::new(&mut self.get_mut().0).poll_frame(cx);
-    match res {
-      std::task::Poll::Ready(Some(Ok(frame))) => {
-        if let Ok(data) = frame.into_data() {
-          // Ensure that we never yield an empty frame
-          if !data.is_empty() {
-            return std::task::Poll::Ready(Some(Ok(data)));
+  ) -> Poll<Option<Self::Item>> {
+    let this = self.get_mut();
+    loop {
+      let res = match Pin::new(&mut this.0).poll_frame(cx) {
+        Poll::Ready(res) => {
+          if let Some(timeout) = &mut this.2 {
+            timeout.waiting = false;
           }
+          res
         }
+        Poll::Pending => {
+          if let Some(timeout) = &mut this.2 {
+            ready!(timeout.poll_expired(cx));
+            return Poll::Ready(Some(Err(
+              std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "timed out reading request body",
+              )
+              .into(),
+            )));
+          }
+          return Poll::Pending;
+        }
+      };
+      match res {
+        Some(Ok(frame)) => {
+          let frame = match frame.into_data() {
+            // Ensure that we never yield an empty frame
+            Ok(data) if data.is_empty() => continue,
+            Ok(data) => {
+              this.3.set(this.3.get() + data.len());
+              return Poll::Ready(Some(Ok(data)));
+            }
+            Err(frame) => frame,
+          };
+          if let Ok(trailers) = frame.into_trailers() {
+            *this.1.borrow_mut() = Some(trailers);
+          }
+        }
+        Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+        None => return Poll::Ready(None),
       }
-      std::task::Poll::Ready(None) => return std::task::Poll::Ready(None),
-      _ => {}
     }
-    std::task::Poll::Pending
   }
 }
 
-pub struct HttpRequestBody(AsyncRefCell<Peekable<ReadFuture>>, SizeHint);
+/// Receives a body ahead of the reads, until the bytes that haven't been read reach the high water
+/// mark. The task is cancelled when the body is dropped.
+struct ReadAhead {
+  rx: UnboundedReceiver<Result<Bytes, AnyError>>,
+  task: JoinHandle<()>,
+}
+
+impl ReadAhead {
+  fn new(
+    mut stream: ReadFuture,
+    high_water_mark: usize,
+    read: Rc<Notify>,
+  ) -> Self {
+    let (tx, rx) = unbounded_channel();
+    let buffered = stream.3.clone();
+    let task = spawn_local(async move {
+      loop {
+        while buffered.get() >= high_water_mark {
+          read.notified().await;
+        }
+        let Some(item) = stream.next().await else {
+          return;
+        };
+        let is_err = item.is_err();
+        if tx.send(item).is_err() || is_err {
+          return;
+        }
+      }
+    });
+    Self { rx, task }
+  }
+}
+
+impl Drop for ReadAhead {
+  fn drop(&mut self) {
+    self.task.abort();
+  }
+}
+
+enum BodyStream {
+  Direct(ReadFuture),
+  ReadAhead(ReadAhead),
+}
+
+impl Stream for BodyStream {
+  type Item = Result<Bytes, AnyError>;
+
+  fn poll_next(
+    self: Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+  ) -> Poll<Option<Self::Item>> {
+    match self.get_mut() {
+      Self::Direct(stream) => Pin::new(stream).poll_next(cx),
+      Self::ReadAhead(read_ahead) => read_ahead.rx.poll_recv(cx),
+    }
+  }
+}
+
+pub struct HttpRequestBody(
+  AsyncRefCell<Peekable<BodyStream>>,
+  SizeHint,
+  Rc<RefCell<Option<HeaderMap>>>,
+  // The number of bytes we have received but not yet handed out
+  Rc<Cell<usize>>,
+  // Notified when bytes are handed out, for the task that reads ahead
+  Rc<Notify>,
+);
 
 impl HttpRequestBody {
-  pub fn new(body: Incoming) -> Self {
+  /// Without a high water mark, the body is only received from the client as it is read.
+  pub fn new(
+    body: IncomingBody,
+    idle_timeout: Option<Duration>,
+    high_water_mark: Option<u32>,
+  ) -> Self {
     let size_hint = body.size_hint();
-    Self(AsyncRefCell::new(ReadFuture(body).peekable()), size_hint)
+    let trailers = Rc::new(RefCell::new(None));
+    let idle_timeout = idle_timeout.map(IdleTimeout::new);
+    let buffered = Rc::new(Cell::new(0));
+    let read = Rc::new(Notify::new());
+    let stream =
+      ReadFuture(body, trailers.clone(), idle_timeout, buffered.clone());
+    let stream = match high_water_mark {
+      Some(high_water_mark) => BodyStream::ReadAhead(ReadAhead::new(
+        stream,
+        high_water_mark as usize,
+        read.clone(),
+      )),
+      None => BodyStream::Direct(stream),
+    };
+    Self(
+      AsyncRefCell::new(stream.peekable()),
+      size_hint,
+      trailers,
+      buffered,
+      read,
+    )
+  }
+
+  /// The trailers sent with this body, available once the body has been fully read.
+  pub fn trailers(&self) -> Option<HeaderMap> {
+    self.2.borrow().clone()
+  }
+
+  /// The number of bytes of this body that have been received but not yet read.
+  pub fn buffered(&self) -> usize {
+    self.3.get()
+  }
+
+  fn consumed(&self, len: usize) {
+    self.3.set(self.3.get() - len);
+    self.4.notify_one();
   }
 
   async fn read(self: Rc<Self>, limit: usize) -> Result<BufView, AnyError> {
-    let peekable = RcRef::map(self, |this| &this.0);
+    let peekable = RcRef::map(&self, |this| &this.0);
     let mut peekable = peekable.borrow_mut().await;
     match Pin::new(&mut *peekable).peek_mut().await {
       None => Ok(BufView::empty()),
@@ -60,9 +286,12 @@ impl HttpRequestBody {
       Some(Ok(bytes)) => {
         if bytes.len() <= limit {
           // We can safely take the next item since we peeked it
-          return Ok(BufView::from(peekable.next().await.unwrap()?));
+          let bytes = peekable.next().await.unwrap()?;
+          self.consumed(bytes.len());
+          return Ok(BufView::from(bytes));
         }
         let ret = bytes.split_to(limit);
+        self.consumed(ret.len());
         Ok(BufView::from(ret))
       }
     }
@@ -20,6 +20,9 @@ use deno_core::WriteOutcome;
 use hyper1::body::Body;
 use hyper1::body::Frame;
 use hyper1::body::SizeHint;
+use hyper1::HeaderMap;
+
+use crate::timeouts::ConnectionIdleGuard;
 
 #[derive(Clone, Debug, Default)]
 pub struct CompletionHandle {
@@ -34,6 +37,10 @@ struct CompletionHandleInner {
 }
 
 impl CompletionHandle {
+  pub fn is_completed(&self) -> bool {
+    self.inner.borrow().complete
+  }
+
   pub fn complete(&self, success: bool) {
     let mut mut_self = self.inner.borrow_mut();
     mut_self.complete = true;
@@ -62,6 +69,26 @@ impl Future for CompletionHandle {
   }
 }
 
+/// Trailers that should be sent once the response body has been fully written. This handle is shared
+/// between the response body and whoever is producing it, as the trailer values are usually only known
+/// once the body stream completes.
+#[derive(Clone, Debug, Default)]
+pub struct ResponseTrailers(Rc<RefCell<Option<HeaderMap>>>);
+
+impl ResponseTrailers {
+  pub fn set(&self, trailers: HeaderMap) {
+    *self.0.borrow_mut() = Some(trailers);
+  }
+
+  fn is_pending(&self) -> bool {
+    self.0.borrow().is_some()
+  }
+
+  fn take(&self) -> Option<HeaderMap> {
+    self.0.borrow_mut().take()
+  }
+}
+
 #[derive(Default)]
 pub enum ResponseBytesInner {
   /// An empty stream.
@@ -92,8 +119,16 @@ impl std::fmt::Debug for ResponseBytesInner {
 /// This represents the union of possible response types in Deno with the stream-style [`Body`] interface
 /// required by hyper. As the API requires information about request completion (including a success/fail
 /// flag), we include a very lightweight [`CompletionHandle`] for interested parties to listen on.
+///
+/// The body also holds a [`ConnectionIdleGuard`], which keeps the connection from being considered idle
+/// until hyper is done with the body.
 #[derive(Debug, Default)]
-pub struct ResponseBytes(ResponseBytesInner, CompletionHandle);
+pub struct ResponseBytes(
+  ResponseBytesInner,
+  CompletionHandle,
+  ResponseTrailers,
+  Option<ConnectionIdleGuard>,
+);
 
 impl ResponseBytes {
   pub fn initialize(&mut self, inner: ResponseBytesInner) {
@@ -105,6 +140,22 @@ impl ResponseBytes {
     self.1.clone()
   }
 
+  pub fn trailers_handle(&self) -> ResponseTrailers {
+    self.2.clone()
+  }
+
+  pub fn set_idle_guard(&mut self, guard: ConnectionIdleGuard) {
+    self.3 = Some(guard);
+  }
+
+  /// Completes the body, returning the trailers frame if trailers were provided.
+  fn complete_with_trailers(
+    &mut self,
+  ) -> Option<Result<Frame<BufView>, AnyError>> {
+    self.complete(true);
+    self.2.take().map(|trailers| Ok(Frame::trailers(trailers)))
+  }
+
   fn complete(&mut self, success: bool) -> ResponseBytesInner {
     if matches!(self.0, ResponseBytesInner::Done) {
       return ResponseBytesInner::Done;
@@ -146,7 +197,8 @@ impl Body for ResponseBytes {
   ) -> std::task::Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
     match &mut self.0 {
       ResponseBytesInner::Done | ResponseBytesInner::Empty => {
-        unreachable!()
+        // We are only polled past the end of the body if there are trailers to send
+        std::task::Poll::Ready(self.complete_with_trailers())
       }
       ResponseBytesInner::Bytes(..) => {
         if let ResponseBytesInner::Bytes(data) = self.complete(true) {
@@ -166,8 +218,7 @@ impl Body for ResponseBytes {
               if *auto_close {
                 stm.clone().close();
               }
-              self.complete(true);
-              return std::task::Poll::Ready(None);
+              return std::task::Poll::Ready(self.complete_with_trailers());
             }
             // Re-arm the future
             *future = stm.clone().read(64 * 1024);
@@ -181,8 +232,7 @@ impl Body for ResponseBytes {
           std::task::Poll::Ready(Some(Ok(Frame::data(buf))))
         }
         std::task::Poll::Ready(None) => {
-          self.complete(true);
-          std::task::Poll::Ready(None)
+          std::task::Poll::Ready(self.complete_with_trailers())
         }
       },
     }
@@ -190,6 +240,7 @@ impl Body for ResponseBytes {
 
   fn is_end_stream(&self) -> bool {
     matches!(self.0, ResponseBytesInner::Done | ResponseBytesInner::Empty)
+      && !self.2.is_pending()
   }
 
   fn size_hint(&self) -> SizeHint {
@@ -211,11 +262,23 @@ impl Drop for ResponseBytes {
 pub struct V8StreamHttpResponseBody(
   AsyncRefCell<Option<tokio::sync::mpsc::Sender<BufView>>>,
   CancelHandle,
+  ResponseTrailers,
 );
 
 impl V8StreamHttpResponseBody {
-  pub fn new(sender: tokio::sync::mpsc::Sender<BufView>) -> Self {
-    Self(AsyncRefCell::new(Some(sender)), CancelHandle::default())
+  pub fn new(
+    sender: tokio::sync::mpsc::Sender<BufView>,
+    trailers: ResponseTrailers,
+  ) -> Self {
+    Self(
+      AsyncRefCell::new(Some(sender)),
+      CancelHandle::default(),
+      trailers,
+    )
+  }
+
+  pub fn set_trailers(&self, trailers: HeaderMap) {
+    self.2.set(trailers)
   }
 }
 
@@ -251,3 +314,61 @@ impl Resource for V8StreamHttpResponseBody {
     self.1.cancel();
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use deno_core::futures::future::poll_fn;
+  use hyper1::http::HeaderValue;
+
+  async fn next_frame(body: &mut ResponseBytes) -> Option<Frame<BufView>> {
+    if body.is_end_stream() {
+      return None;
+    }
+    poll_fn(|cx| Pin::new(&mut *body).poll_frame(cx))
+      .await
+      .map(|frame| frame.unwrap())
+  }
+
+  #[tokio::test]
+  async fn test_trailers_after_bytes() {
+    let mut body = ResponseBytes::default();
+    body.initialize(ResponseBytesInner::Bytes(BufView::from(b"abc".to_vec())));
+    let mut trailers = HeaderMap::new();
+    trailers.insert("grpc-status", HeaderValue::from_static("0"));
+    body.trailers_handle().set(trailers.clone());
+
+    let completion = body.completion_handle();
+    let frame = next_frame(&mut body).await.unwrap();
+    assert_eq!(frame.into_data().ok().as_deref(), Some(&b"abc"[..]));
+    let frame = next_frame(&mut body).await.unwrap();
+    assert_eq!(frame.into_trailers().ok(), Some(trailers));
+    assert!(next_frame(&mut body).await.is_none());
+    assert!(completion.await);
+  }
+
+  #[tokio::test]
+  async fn test_trailers_after_stream() {
+    let (tx, rx) = tokio::sync::mpsc::channel(1);
+    let mut body = ResponseBytes::default();
+    body.initialize(ResponseBytesInner::V8Stream(rx));
+    let writer = V8StreamHttpResponseBody::new(tx, body.trailers_handle());
+
+    let mut trailers = HeaderMap::new();
+    trailers.insert("x-checksum", HeaderValue::from_static("1234"));
+    writer.set_trailers(trailers.clone());
+    drop(writer);
+
+    let frame = next_frame(&mut body).await.unwrap();
+    assert_eq!(frame.into_trailers().ok(), Some(trailers));
+    assert!(next_frame(&mut body).await.is_none());
+  }
+
+  #[tokio::test]
+  async fn test_no_trailers() {
+    let mut body = ResponseBytes::default();
+    body.initialize(ResponseBytesInner::Bytes(BufView::from(b"abc".to_vec())));
+    assert!(next_frame(&mut body).await.unwrap().is_data());
+    assert!(next_frame(&mut body).await.is_none());
+  }
+}
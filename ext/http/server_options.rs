@@ -0,0 +1,85 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Options for a `Deno.serve` server. Timeouts are given in milliseconds, and any timeout that
+/// is not set is disabled.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpServerOptions {
+  /// How long a client has to send the complete head of a request, once it has started sending it.
+  /// Only applies to HTTP/1.1 connections.
+  headers_timeout: Option<u64>,
+  /// How long a request body may go without receiving any data while it is being read.
+  request_body_idle_timeout: Option<u64>,
+  /// How long a connection may stay open without any request in flight.
+  keep_alive_timeout: Option<u64>,
+  /// How many bytes of a request body we accept from the client before waiting for the handler
+  /// to read them.
+  request_body_high_water_mark: Option<u32>,
+  /// How many connections the server serves at once.
+  max_connections: Option<u32>,
+  /// How many requests a client may have in flight at once on an HTTP/2 or HTTP/3 connection.
+  max_concurrent_streams: Option<u32>,
+  /// What happens to connections beyond `max_connections`.
+  #[serde(default)]
+  overload_behavior: OverloadBehavior,
+  /// Set for connections that were accepted while the server was at its connection limit, whose
+  /// requests are answered by the overload handler.
+  #[serde(skip)]
+  overloaded: bool,
+}
+
+/// What the server does with new connections once it serves `max_connections` of them.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum OverloadBehavior {
+  /// Stops accepting connections until one of the current ones closes, leaving new ones waiting
+  /// in the listen backlog.
+  #[default]
+  Backoff,
+  /// Accepts the connection, answers its requests with the overload handler, and closes it.
+  Reject,
+}
+
+impl HttpServerOptions {
+  pub fn headers_timeout(&self) -> Option<Duration> {
+    self.headers_timeout.map(Duration::from_millis)
+  }
+
+  pub fn request_body_idle_timeout(&self) -> Option<Duration> {
+    self.request_body_idle_timeout.map(Duration::from_millis)
+  }
+
+  pub fn keep_alive_timeout(&self) -> Option<Duration> {
+    self.keep_alive_timeout.map(Duration::from_millis)
+  }
+
+  pub fn request_body_high_water_mark(&self) -> Option<u32> {
+    self.request_body_high_water_mark
+  }
+
+  pub fn max_connections(&self) -> Option<u32> {
+    self.max_connections
+  }
+
+  pub fn max_concurrent_streams(&self) -> Option<u32> {
+    self.max_concurrent_streams
+  }
+
+  pub fn overload_behavior(&self) -> OverloadBehavior {
+    self.overload_behavior
+  }
+
+  pub fn is_overloaded(&self) -> bool {
+    self.overloaded
+  }
+
+  /// The options for a connection accepted beyond the connection limit.
+  pub fn overloaded(self) -> Self {
+    Self {
+      overloaded: true,
+      ..self
+    }
+  }
+}
@@ -0,0 +1,136 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+use deno_core::futures::future::poll_fn;
+use pin_project::pin_project;
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// A [`hyper1::rt::Timer`] backed by tokio, which hyper needs to enforce the header read timeout.
+#[derive(Clone, Copy)]
+pub struct TokioTimer;
+
+#[pin_project]
+struct TokioSleep(#[pin] tokio::time::Sleep);
+
+impl Future for TokioSleep {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    self.project().0.poll(cx)
+  }
+}
+
+impl hyper1::rt::Sleep for TokioSleep {}
+
+impl hyper1::rt::Timer for TokioTimer {
+  fn sleep(&self, duration: Duration) -> Pin<Box<dyn hyper1::rt::Sleep>> {
+    Box::pin(TokioSleep(tokio::time::sleep(duration)))
+  }
+
+  fn sleep_until(
+    &self,
+    deadline: std::time::Instant,
+  ) -> Pin<Box<dyn hyper1::rt::Sleep>> {
+    Box::pin(TokioSleep(tokio::time::sleep_until(deadline.into())))
+  }
+}
+
+#[derive(Debug)]
+struct ConnectionIdleState {
+  active: usize,
+  idle_since: Instant,
+  waker: Option<Waker>,
+}
+
+/// Tracks the requests in flight on a connection, so that connections that stay idle for too long
+/// can be shut down.
+///
+/// A request is in flight from the moment it is received until its response body has been
+/// completely written (or abandoned), which is when its [`ConnectionIdleGuard`] is dropped.
+#[derive(Clone, Debug)]
+pub struct ConnectionIdle(Rc<RefCell<ConnectionIdleState>>);
+
+impl Default for ConnectionIdle {
+  fn default() -> Self {
+    Self(Rc::new(RefCell::new(ConnectionIdleState {
+      active: 0,
+      idle_since: Instant::now(),
+      waker: None,
+    })))
+  }
+}
+
+impl ConnectionIdle {
+  /// Marks a request as in flight until the returned guard is dropped.
+  pub fn guard(&self) -> ConnectionIdleGuard {
+    self.0.borrow_mut().active += 1;
+    ConnectionIdleGuard(self.clone())
+  }
+
+  /// Resolves once no request has been in flight for `timeout`.
+  pub async fn timeout(self, timeout: Duration) {
+    let mut sleep = Box::pin(tokio::time::sleep(timeout));
+    poll_fn(|cx| {
+      let mut state = self.0.borrow_mut();
+      state.waker = Some(cx.waker().clone());
+      if state.active > 0 {
+        return Poll::Pending;
+      }
+      let deadline = state.idle_since + timeout;
+      drop(state);
+      if sleep.deadline() != deadline {
+        sleep.as_mut().reset(deadline);
+      }
+      sleep.as_mut().poll(cx)
+    })
+    .await
+  }
+}
+
+#[derive(Debug)]
+pub struct ConnectionIdleGuard(ConnectionIdle);
+
+impl Drop for ConnectionIdleGuard {
+  fn drop(&mut self) {
+    let mut state = self.0 .0.borrow_mut();
+    state.active -= 1;
+    if state.active == 0 {
+      state.idle_since = Instant::now();
+      if let Some(waker) = state.waker.take() {
+        drop(state);
+        waker.wake();
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use deno_core::futures::FutureExt;
+
+  #[tokio::test]
+  async fn test_idle_timeout() {
+    let idle = ConnectionIdle::default();
+    let mut timeout = idle
+      .clone()
+      .timeout(Duration::from_millis(50))
+      .boxed_local();
+
+    let guard = idle.guard();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    // A request is in flight, so the connection isn't idle
+    assert!((&mut timeout).now_or_never().is_none());
+
+    drop(guard);
+    assert!((&mut timeout).now_or_never().is_none());
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!((&mut timeout).now_or_never().is_some());
+  }
+}
@@ -2,6 +2,8 @@
 
 use std::borrow::Cow;
 use std::io;
+use std::path::Path;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
@@ -188,6 +190,41 @@ pub trait File {
   fn truncate_sync(self: Rc<Self>, len: u64) -> FsResult<()>;
   async fn truncate_async(self: Rc<Self>, len: u64) -> FsResult<()>;
 
+  /// Makes sure that the disk space for the first `len` bytes of the file is
+  /// allocated, extending the file if it is shorter.
+  fn allocate_sync(self: Rc<Self>, len: u64) -> FsResult<()>;
+  async fn allocate_async(self: Rc<Self>, len: u64) -> FsResult<()>;
+
+  /// Returns the value of an extended attribute of the file, see
+  /// [`crate::xattr`].
+  fn get_xattr_sync(self: Rc<Self>, name: &str) -> FsResult<Option<Vec<u8>>>;
+  async fn get_xattr_async(
+    self: Rc<Self>,
+    name: String,
+  ) -> FsResult<Option<Vec<u8>>>;
+
+  fn set_xattr_sync(self: Rc<Self>, name: &str, value: &[u8]) -> FsResult<()>;
+  async fn set_xattr_async(
+    self: Rc<Self>,
+    name: String,
+    value: Vec<u8>,
+  ) -> FsResult<()>;
+
+  fn list_xattr_sync(self: Rc<Self>) -> FsResult<Vec<String>>;
+  async fn list_xattr_async(self: Rc<Self>) -> FsResult<Vec<String>>;
+
+  fn remove_xattr_sync(self: Rc<Self>, name: &str) -> FsResult<()>;
+  async fn remove_xattr_async(self: Rc<Self>, name: String) -> FsResult<()>;
+
+  /// Deallocates the disk space of a range of the file, which reads as zeros
+  /// afterwards. The size of the file doesn't change.
+  fn punch_hole_sync(self: Rc<Self>, offset: u64, len: u64) -> FsResult<()>;
+  async fn punch_hole_async(
+    self: Rc<Self>,
+    offset: u64,
+    len: u64,
+  ) -> FsResult<()>;
+
   fn utime_sync(
     self: Rc<Self>,
     atime_secs: i64,
@@ -203,6 +240,28 @@ pub trait File {
     mtime_nanos: u32,
   ) -> FsResult<()>;
 
+  /// Gives a path to an anonymous temporary file, see `Deno.openTmpFile()`,
+  /// which then isn't deleted when it's closed anymore.
+  fn link_sync(self: Rc<Self>, path: &Path) -> FsResult<()>;
+  async fn link_async(self: Rc<Self>, path: PathBuf) -> FsResult<()>;
+
+  fn set_birthtime_sync(self: Rc<Self>, secs: i64, nanos: u32) -> FsResult<()>;
+  async fn set_birthtime_async(
+    self: Rc<Self>,
+    secs: i64,
+    nanos: u32,
+  ) -> FsResult<()>;
+
+  /// Maps `len` bytes of the file from `offset`, or all of them up to its
+  /// end, into memory. The changes to a `shared` mapping are written to the
+  /// file, and those to a private one are only seen through it.
+  fn mmap_sync(
+    self: Rc<Self>,
+    offset: u64,
+    len: Option<usize>,
+    shared: bool,
+  ) -> FsResult<memmap2::MmapMut>;
+
   // lower level functionality
   fn as_stdio(self: Rc<Self>) -> FsResult<std::process::Stdio>;
   #[cfg(unix)]
@@ -27,12 +27,15 @@ use std::io::ErrorKind;
 use std::io::Read;
 use std::io::Seek;
 use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
 use std::rc::Rc;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWrite;
 use tokio::io::AsyncWriteExt;
 use tokio::process;
+use xattr::XattrTarget;
 
 #[cfg(unix)]
 use std::os::unix::io::FromRawFd;
@@ -45,6 +48,7 @@ use winapi::um::processenv::GetStdHandle;
 use winapi::um::winbase;
 
 pub mod fs;
+pub mod xattr;
 
 // Store the stdio fd/handles in global statics in order to keep them
 // alive for the duration of the application since the last handle/fd
@@ -306,6 +310,9 @@ pub struct StdFileResourceInner {
   // Used to keep async actions in order and only allow one
   // to occur at a time
   cell_async_task_queue: TaskQueue,
+  // Whether the file was opened with `Deno.openTmpFile()`, which is the only
+  // kind of file that can be given a path with `link()`
+  tmp_file: bool,
 }
 
 impl StdFileResourceInner {
@@ -313,11 +320,33 @@ impl StdFileResourceInner {
     StdFileResourceInner::new(StdFileResourceKind::File, fs_file)
   }
 
+  /// An anonymous temporary file, see `Deno.openTmpFile()`.
+  pub fn tmp_file(fs_file: StdFile) -> Self {
+    StdFileResourceInner {
+      tmp_file: true,
+      ..StdFileResourceInner::file(fs_file)
+    }
+  }
+
   fn new(kind: StdFileResourceKind, fs_file: StdFile) -> Self {
     StdFileResourceInner {
       kind,
       cell: RefCell::new(Some(fs_file)),
       cell_async_task_queue: Default::default(),
+      tmp_file: false,
+    }
+  }
+
+  /// Linking another file would give a new path to it without the
+  /// permissions of its original path being checked.
+  fn check_tmp_file(&self) -> FsResult<()> {
+    if self.tmp_file {
+      Ok(())
+    } else {
+      Err(FsError::Io(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "Only files opened with Deno.openTmpFile() can be linked",
+      )))
     }
   }
 
@@ -651,6 +680,77 @@ impl crate::fs::File for StdFileResourceInner {
       .await
   }
 
+  fn allocate_sync(self: Rc<Self>, len: u64) -> FsResult<()> {
+    self.with_sync(|file| Ok(file.allocate(len)?))
+  }
+  async fn allocate_async(self: Rc<Self>, len: u64) -> FsResult<()> {
+    self
+      .with_inner_blocking_task(move |file| Ok(file.allocate(len)?))
+      .await
+  }
+
+  fn get_xattr_sync(self: Rc<Self>, name: &str) -> FsResult<Option<Vec<u8>>> {
+    self.with_sync(|file| xattr::get(XattrTarget::File(file), name))
+  }
+  async fn get_xattr_async(
+    self: Rc<Self>,
+    name: String,
+  ) -> FsResult<Option<Vec<u8>>> {
+    self
+      .with_inner_blocking_task(move |file| {
+        xattr::get(XattrTarget::File(file), &name)
+      })
+      .await
+  }
+
+  fn set_xattr_sync(self: Rc<Self>, name: &str, value: &[u8]) -> FsResult<()> {
+    self.with_sync(|file| xattr::set(XattrTarget::File(file), name, value))
+  }
+  async fn set_xattr_async(
+    self: Rc<Self>,
+    name: String,
+    value: Vec<u8>,
+  ) -> FsResult<()> {
+    self
+      .with_inner_blocking_task(move |file| {
+        xattr::set(XattrTarget::File(file), &name, &value)
+      })
+      .await
+  }
+
+  fn list_xattr_sync(self: Rc<Self>) -> FsResult<Vec<String>> {
+    self.with_sync(|file| xattr::list(XattrTarget::File(file)))
+  }
+  async fn list_xattr_async(self: Rc<Self>) -> FsResult<Vec<String>> {
+    self
+      .with_inner_blocking_task(|file| xattr::list(XattrTarget::File(file)))
+      .await
+  }
+
+  fn remove_xattr_sync(self: Rc<Self>, name: &str) -> FsResult<()> {
+    self.with_sync(|file| xattr::remove(XattrTarget::File(file), name))
+  }
+  async fn remove_xattr_async(self: Rc<Self>, name: String) -> FsResult<()> {
+    self
+      .with_inner_blocking_task(move |file| {
+        xattr::remove(XattrTarget::File(file), &name)
+      })
+      .await
+  }
+
+  fn punch_hole_sync(self: Rc<Self>, offset: u64, len: u64) -> FsResult<()> {
+    self.with_sync(|file| punch_hole(file, offset, len))
+  }
+  async fn punch_hole_async(
+    self: Rc<Self>,
+    offset: u64,
+    len: u64,
+  ) -> FsResult<()> {
+    self
+      .with_inner_blocking_task(move |file| punch_hole(file, offset, len))
+      .await
+  }
+
   fn utime_sync(
     self: Rc<Self>,
     atime_secs: i64,
@@ -684,6 +784,56 @@ impl crate::fs::File for StdFileResourceInner {
       .await
   }
 
+  fn mmap_sync(
+    self: Rc<Self>,
+    offset: u64,
+    len: Option<usize>,
+    shared: bool,
+  ) -> FsResult<memmap2::MmapMut> {
+    self.with_sync(|file| {
+      let mut options = memmap2::MmapOptions::new();
+      options.offset(offset);
+      if let Some(len) = len {
+        options.len(len);
+      }
+      // SAFETY: the memory is only valid while the file isn't truncated,
+      // which is up to the user, like with `mmap()` itself. Private mappings
+      // are copy-on-write, so they are writable even for read-only files.
+      let mmap = unsafe {
+        if shared {
+          options.map_mut(&*file)?
+        } else {
+          options.map_copy(&*file)?
+        }
+      };
+      Ok(mmap)
+    })
+  }
+
+  fn link_sync(self: Rc<Self>, path: &Path) -> FsResult<()> {
+    self.check_tmp_file()?;
+    self.with_sync(|file| link_tmp_file(file, path))
+  }
+  async fn link_async(self: Rc<Self>, path: PathBuf) -> FsResult<()> {
+    self.check_tmp_file()?;
+    self
+      .with_inner_blocking_task(move |file| link_tmp_file(file, &path))
+      .await
+  }
+
+  fn set_birthtime_sync(self: Rc<Self>, secs: i64, nanos: u32) -> FsResult<()> {
+    self.with_sync(|file| set_birthtime(file, secs, nanos))
+  }
+  async fn set_birthtime_async(
+    self: Rc<Self>,
+    secs: i64,
+    nanos: u32,
+  ) -> FsResult<()> {
+    self
+      .with_inner_blocking_task(move |file| set_birthtime(file, secs, nanos))
+      .await
+  }
+
   async fn read_byob(
     self: Rc<Self>,
     mut buf: BufMutView,
@@ -731,6 +881,320 @@ impl crate::fs::File for StdFileResourceInner {
   }
 }
 
+#[cfg(target_os = "linux")]
+fn punch_hole(file: &StdFile, offset: u64, len: u64) -> FsResult<()> {
+  use std::os::unix::io::AsRawFd;
+
+  // SAFETY: libc call, the file descriptor is open.
+  let ret = unsafe {
+    libc::fallocate(
+      file.as_raw_fd(),
+      libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE,
+      offset as libc::off_t,
+      len as libc::off_t,
+    )
+  };
+  if ret != 0 {
+    let err = io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::EOPNOTSUPP) {
+      return Err(FsError::NotSupported);
+    }
+    return Err(err.into());
+  }
+  Ok(())
+}
+
+/// On macOS, `offset` and `len` must be multiples of the block size of the
+/// file system.
+#[cfg(target_os = "macos")]
+fn punch_hole(file: &StdFile, offset: u64, len: u64) -> FsResult<()> {
+  use std::os::unix::io::AsRawFd;
+
+  // libc doesn't define these.
+  const F_PUNCHHOLE: libc::c_int = 99;
+  #[repr(C)]
+  struct PunchHoleArgs {
+    fp_flags: libc::c_uint,
+    reserved: libc::c_uint,
+    fp_offset: libc::off_t,
+    fp_length: libc::off_t,
+  }
+
+  let args = PunchHoleArgs {
+    fp_flags: 0,
+    reserved: 0,
+    fp_offset: offset as libc::off_t,
+    fp_length: len as libc::off_t,
+  };
+  // SAFETY: libc call, the file descriptor is open and `args` outlives it.
+  let ret = unsafe { libc::fcntl(file.as_raw_fd(), F_PUNCHHOLE, &args) };
+  if ret == -1 {
+    let err = io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::ENOTSUP) {
+      return Err(FsError::NotSupported);
+    }
+    return Err(err.into());
+  }
+  Ok(())
+}
+
+#[cfg(windows)]
+fn punch_hole(file: &StdFile, offset: u64, len: u64) -> FsResult<()> {
+  use std::os::windows::io::AsRawHandle;
+  use winapi::shared::minwindef::DWORD;
+  use winapi::shared::winerror::ERROR_INVALID_FUNCTION;
+  use winapi::um::ioapiset::DeviceIoControl;
+  use winapi::um::winioctl::FSCTL_SET_SPARSE;
+  use winapi::um::winioctl::FSCTL_SET_ZERO_DATA;
+
+  // winapi doesn't define this.
+  #[repr(C)]
+  struct FileZeroDataInformation {
+    file_offset: i64,
+    beyond_final_zero: i64,
+  }
+
+  let device_io_control = |code: DWORD, input: *mut _, input_size: usize| {
+    let mut bytes_returned = 0;
+    // SAFETY: winapi call, the input buffer is valid for its size.
+    let ret = unsafe {
+      DeviceIoControl(
+        file.as_raw_handle() as _,
+        code,
+        input,
+        input_size as DWORD,
+        std::ptr::null_mut(),
+        0,
+        &mut bytes_returned,
+        std::ptr::null_mut(),
+      )
+    };
+    if ret == 0 {
+      let err = io::Error::last_os_error();
+      if err.raw_os_error() == Some(ERROR_INVALID_FUNCTION as i32) {
+        return Err(FsError::NotSupported);
+      }
+      return Err(err.into());
+    }
+    Ok(())
+  };
+
+  // Zeroing a range only deallocates it in sparse files.
+  device_io_control(FSCTL_SET_SPARSE, std::ptr::null_mut(), 0)?;
+  let mut info = FileZeroDataInformation {
+    file_offset: offset as i64,
+    beyond_final_zero: offset.saturating_add(len) as i64,
+  };
+  device_io_control(
+    FSCTL_SET_ZERO_DATA,
+    &mut info as *mut FileZeroDataInformation as *mut _,
+    std::mem::size_of::<FileZeroDataInformation>(),
+  )
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn punch_hole(_file: &StdFile, _offset: u64, _len: u64) -> FsResult<()> {
+  Err(FsError::NotSupported)
+}
+
+#[cfg(target_os = "linux")]
+fn link_tmp_file(file: &StdFile, path: &Path) -> FsResult<()> {
+  use std::ffi::CString;
+  use std::os::unix::ffi::OsStrExt;
+  use std::os::unix::io::AsRawFd;
+
+  // Linking the file descriptor itself with AT_EMPTY_PATH requires
+  // CAP_DAC_READ_SEARCH, but linking its entry in /proc doesn't.
+  let fd_path = CString::new(format!("/proc/self/fd/{}", file.as_raw_fd()))
+    .map_err(io::Error::from)?;
+  let path =
+    CString::new(path.as_os_str().as_bytes()).map_err(io::Error::from)?;
+  // SAFETY: libc call, the paths are null terminated.
+  let ret = unsafe {
+    libc::linkat(
+      libc::AT_FDCWD,
+      fd_path.as_ptr(),
+      libc::AT_FDCWD,
+      path.as_ptr(),
+      libc::AT_SYMLINK_FOLLOW,
+    )
+  };
+  if ret != 0 {
+    return Err(io::Error::last_os_error().into());
+  }
+  Ok(())
+}
+
+/// Marks a file to be deleted once all of its handles are closed, or unmarks
+/// it. Unlike with FILE_FLAG_DELETE_ON_CLOSE, this can be undone.
+#[cfg(windows)]
+pub fn set_delete_on_close(file: &StdFile, delete: bool) -> io::Result<()> {
+  use std::os::windows::io::AsRawHandle;
+  use winapi::um::fileapi::SetFileInformationByHandle;
+  use winapi::um::fileapi::FILE_DISPOSITION_INFO;
+  use winapi::um::minwinbase::FileDispositionInfo;
+
+  let mut info = FILE_DISPOSITION_INFO {
+    DeleteFile: delete as u8,
+  };
+  // SAFETY: winapi call, `info` outlives it.
+  let ret = unsafe {
+    SetFileInformationByHandle(
+      file.as_raw_handle() as _,
+      FileDispositionInfo,
+      &mut info as *mut FILE_DISPOSITION_INFO as *mut _,
+      std::mem::size_of::<FILE_DISPOSITION_INFO>() as u32,
+    )
+  };
+  if ret == 0 {
+    return Err(io::Error::last_os_error());
+  }
+  Ok(())
+}
+
+/// Renames the file through its handle, as its current path isn't known.
+#[cfg(windows)]
+fn link_tmp_file(file: &StdFile, path: &Path) -> FsResult<()> {
+  use std::os::windows::ffi::OsStrExt;
+  use std::os::windows::io::AsRawHandle;
+  use winapi::um::fileapi::SetFileInformationByHandle;
+  use winapi::um::fileapi::FILE_RENAME_INFO;
+  use winapi::um::minwinbase::FileRenameInfo;
+
+  let path = if path.is_absolute() {
+    path.to_path_buf()
+  } else {
+    std::env::current_dir()?.join(path)
+  };
+  let name = path.as_os_str().encode_wide().collect::<Vec<u16>>();
+
+  // FILE_RENAME_INFO ends with the name, so it is allocated with it, and its
+  // own first character is left for the null terminator. The buffer is made
+  // of u64s for the alignment of the struct.
+  let size = std::mem::size_of::<FILE_RENAME_INFO>() + name.len() * 2;
+  let mut buffer = vec![0u64; (size + 7) / 8];
+  let info = buffer.as_mut_ptr() as *mut FILE_RENAME_INFO;
+  // SAFETY: the buffer is large enough for the struct and the name, and
+  // zeroed, which is a valid value for the struct.
+  unsafe {
+    (*info).ReplaceIfExists = 0;
+    (*info).RootDirectory = std::ptr::null_mut();
+    (*info).FileNameLength = (name.len() * 2) as u32;
+    std::ptr::copy_nonoverlapping(
+      name.as_ptr(),
+      (*info).FileName.as_mut_ptr(),
+      name.len(),
+    );
+  }
+
+  set_delete_on_close(file, false)?;
+  // SAFETY: winapi call, `buffer` outlives it.
+  let ret = unsafe {
+    SetFileInformationByHandle(
+      file.as_raw_handle() as _,
+      FileRenameInfo,
+      info as *mut _,
+      size as u32,
+    )
+  };
+  if ret == 0 {
+    let err = io::Error::last_os_error();
+    let _ = set_delete_on_close(file, true);
+    return Err(err.into());
+  }
+  Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn link_tmp_file(_file: &StdFile, _path: &Path) -> FsResult<()> {
+  Err(FsError::NotSupported)
+}
+
+/// Sets the creation time of a file, which is only possible on Windows and on
+/// the file systems of macOS that record it, like APFS and HFS+.
+#[cfg(target_os = "macos")]
+pub fn set_birthtime(file: &StdFile, secs: i64, nanos: u32) -> FsResult<()> {
+  use std::os::unix::io::AsRawFd;
+
+  let mut attrs = libc::attrlist {
+    bitmapcount: libc::ATTR_BIT_MAP_COUNT,
+    reserved: 0,
+    commonattr: libc::ATTR_CMN_CRTIME,
+    volattr: 0,
+    dirattr: 0,
+    fileattr: 0,
+    forkattr: 0,
+  };
+  let mut time = libc::timespec {
+    tv_sec: secs as libc::time_t,
+    tv_nsec: nanos as libc::c_long,
+  };
+  // SAFETY: libc call, the file descriptor is open and `attrs` and `time`
+  // outlive it.
+  let ret = unsafe {
+    libc::fsetattrlist(
+      file.as_raw_fd(),
+      &mut attrs as *mut libc::attrlist as *mut libc::c_void,
+      &mut time as *mut libc::timespec as *mut libc::c_void,
+      std::mem::size_of::<libc::timespec>(),
+      0,
+    )
+  };
+  if ret != 0 {
+    let err = io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::ENOTSUP) {
+      return Err(FsError::NotSupported);
+    }
+    return Err(err.into());
+  }
+  Ok(())
+}
+
+/// Sets the creation time of a file, which is only possible on Windows and on
+/// the file systems of macOS that record it, like APFS and HFS+.
+#[cfg(windows)]
+pub fn set_birthtime(file: &StdFile, secs: i64, nanos: u32) -> FsResult<()> {
+  use std::os::windows::io::AsRawHandle;
+  use winapi::shared::minwindef::FILETIME;
+  use winapi::um::fileapi::SetFileTime;
+
+  // The number of seconds between 1601-01-01, the epoch of Windows, and
+  // 1970-01-01.
+  const EPOCH_DIFFERENCE: i64 = 11_644_473_600;
+
+  // In intervals of 100 nanoseconds.
+  let intervals = secs
+    .checked_add(EPOCH_DIFFERENCE)
+    .and_then(|secs| secs.checked_mul(10_000_000))
+    .and_then(|intervals| intervals.checked_add((nanos / 100) as i64))
+    .filter(|intervals| *intervals >= 0)
+    .ok_or_else(|| {
+      io::Error::new(ErrorKind::InvalidInput, "birthtime is out of range")
+    })?;
+  let time = FILETIME {
+    dwLowDateTime: intervals as u32,
+    dwHighDateTime: (intervals >> 32) as u32,
+  };
+  // SAFETY: winapi call, `time` outlives it.
+  let ret = unsafe {
+    SetFileTime(
+      file.as_raw_handle() as _,
+      &time,
+      std::ptr::null(),
+      std::ptr::null(),
+    )
+  };
+  if ret == 0 {
+    return Err(io::Error::last_os_error().into());
+  }
+  Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn set_birthtime(_file: &StdFile, _secs: i64, _nanos: u32) -> FsResult<()> {
+  Err(FsError::NotSupported)
+}
+
 // override op_print to use the stdout and stderr in the resource table
 #[op]
 pub fn op_print(
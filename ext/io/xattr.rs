@@ -0,0 +1,491 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Extended attributes of files, for `Deno.getXattr()` and friends. They are
+//! the xattrs of Linux and macOS, and the alternate data streams of NTFS on
+//! Windows.
+
+use std::fs::File as StdFile;
+use std::io;
+use std::path::Path;
+
+use crate::fs::FsError;
+use crate::fs::FsResult;
+
+/// The file whose extended attributes are accessed, either by its path or
+/// through an open file.
+#[derive(Clone, Copy)]
+pub enum XattrTarget<'a> {
+  Path(&'a Path),
+  File(&'a StdFile),
+}
+
+/// Returns the value of an extended attribute, or `None` if the file doesn't
+/// have it.
+pub fn get(target: XattrTarget, name: &str) -> FsResult<Option<Vec<u8>>> {
+  sys::get(target, name).map_err(into_fs_error)
+}
+
+/// Sets the value of an extended attribute, replacing the previous one.
+pub fn set(target: XattrTarget, name: &str, value: &[u8]) -> FsResult<()> {
+  sys::set(target, name, value).map_err(into_fs_error)
+}
+
+/// Returns the names of the extended attributes of a file.
+pub fn list(target: XattrTarget) -> FsResult<Vec<String>> {
+  sys::list(target).map_err(into_fs_error)
+}
+
+/// Removes an extended attribute, failing if the file doesn't have it.
+pub fn remove(target: XattrTarget, name: &str) -> FsResult<()> {
+  sys::remove(target, name).map_err(into_fs_error)
+}
+
+fn into_fs_error(err: io::Error) -> FsError {
+  if err.kind() == io::ErrorKind::Unsupported {
+    return FsError::NotSupported;
+  }
+  #[cfg(unix)]
+  {
+    // ENOTSUP and EOPNOTSUPP are the same on Linux, but not on macOS.
+    let code = err.raw_os_error();
+    if code == Some(libc::ENOTSUP) || code == Some(libc::EOPNOTSUPP) {
+      return FsError::NotSupported;
+    }
+  }
+  FsError::Io(err)
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod sys {
+  use super::XattrTarget;
+  use libc::c_char;
+  use libc::c_void;
+  use std::ffi::CString;
+  use std::io;
+  use std::os::unix::ffi::OsStrExt;
+  use std::os::unix::io::AsRawFd;
+  use std::os::unix::io::RawFd;
+
+  #[cfg(target_os = "linux")]
+  const ENOATTR: libc::c_int = libc::ENODATA;
+  #[cfg(target_os = "macos")]
+  const ENOATTR: libc::c_int = libc::ENOATTR;
+
+  enum Target {
+    Path(CString),
+    Fd(RawFd),
+  }
+
+  impl Target {
+    fn new(target: XattrTarget) -> io::Result<Self> {
+      Ok(match target {
+        XattrTarget::Path(path) => {
+          Self::Path(CString::new(path.as_os_str().as_bytes())?)
+        }
+        XattrTarget::File(file) => Self::Fd(file.as_raw_fd()),
+      })
+    }
+  }
+
+  #[cfg(target_os = "linux")]
+  unsafe fn getxattr(
+    target: &Target,
+    name: *const c_char,
+    value: *mut c_void,
+    size: usize,
+  ) -> isize {
+    match target {
+      Target::Path(path) => libc::getxattr(path.as_ptr(), name, value, size),
+      Target::Fd(fd) => libc::fgetxattr(*fd, name, value, size),
+    }
+  }
+
+  #[cfg(target_os = "macos")]
+  unsafe fn getxattr(
+    target: &Target,
+    name: *const c_char,
+    value: *mut c_void,
+    size: usize,
+  ) -> isize {
+    match target {
+      Target::Path(path) => {
+        libc::getxattr(path.as_ptr(), name, value, size, 0, 0)
+      }
+      Target::Fd(fd) => libc::fgetxattr(*fd, name, value, size, 0, 0),
+    }
+  }
+
+  #[cfg(target_os = "linux")]
+  unsafe fn setxattr(
+    target: &Target,
+    name: *const c_char,
+    value: *const c_void,
+    size: usize,
+  ) -> libc::c_int {
+    match target {
+      Target::Path(path) => libc::setxattr(path.as_ptr(), name, value, size, 0),
+      Target::Fd(fd) => libc::fsetxattr(*fd, name, value, size, 0),
+    }
+  }
+
+  #[cfg(target_os = "macos")]
+  unsafe fn setxattr(
+    target: &Target,
+    name: *const c_char,
+    value: *const c_void,
+    size: usize,
+  ) -> libc::c_int {
+    match target {
+      Target::Path(path) => {
+        libc::setxattr(path.as_ptr(), name, value, size, 0, 0)
+      }
+      Target::Fd(fd) => libc::fsetxattr(*fd, name, value, size, 0, 0),
+    }
+  }
+
+  #[cfg(target_os = "linux")]
+  unsafe fn listxattr(
+    target: &Target,
+    list: *mut c_char,
+    size: usize,
+  ) -> isize {
+    match target {
+      Target::Path(path) => libc::listxattr(path.as_ptr(), list, size),
+      Target::Fd(fd) => libc::flistxattr(*fd, list, size),
+    }
+  }
+
+  #[cfg(target_os = "macos")]
+  unsafe fn listxattr(
+    target: &Target,
+    list: *mut c_char,
+    size: usize,
+  ) -> isize {
+    match target {
+      Target::Path(path) => libc::listxattr(path.as_ptr(), list, size, 0),
+      Target::Fd(fd) => libc::flistxattr(*fd, list, size, 0),
+    }
+  }
+
+  #[cfg(target_os = "linux")]
+  unsafe fn removexattr(target: &Target, name: *const c_char) -> libc::c_int {
+    match target {
+      Target::Path(path) => libc::removexattr(path.as_ptr(), name),
+      Target::Fd(fd) => libc::fremovexattr(*fd, name),
+    }
+  }
+
+  #[cfg(target_os = "macos")]
+  unsafe fn removexattr(target: &Target, name: *const c_char) -> libc::c_int {
+    match target {
+      Target::Path(path) => libc::removexattr(path.as_ptr(), name, 0),
+      Target::Fd(fd) => libc::fremovexattr(*fd, name, 0),
+    }
+  }
+
+  /// Calls `read` with a buffer that is large enough for what it reads, which
+  /// is queried first by calling it with an empty buffer. The size can change
+  /// between the two calls, in which case they are repeated.
+  fn read_with_buffer(
+    mut read: impl FnMut(*mut c_void, usize) -> isize,
+  ) -> io::Result<Vec<u8>> {
+    loop {
+      let size = read(std::ptr::null_mut(), 0);
+      if size < 0 {
+        return Err(io::Error::last_os_error());
+      }
+      let mut buf = vec![0u8; size as usize];
+      let len = read(buf.as_mut_ptr() as *mut c_void, buf.len());
+      if len >= 0 {
+        buf.truncate(len as usize);
+        return Ok(buf);
+      }
+      let err = io::Error::last_os_error();
+      if err.raw_os_error() != Some(libc::ERANGE) {
+        return Err(err);
+      }
+    }
+  }
+
+  pub fn get(target: XattrTarget, name: &str) -> io::Result<Option<Vec<u8>>> {
+    let target = Target::new(target)?;
+    let name = CString::new(name)?;
+    // SAFETY: libc call, the buffer is valid for its size.
+    let result = read_with_buffer(|value, size| unsafe {
+      getxattr(&target, name.as_ptr(), value, size)
+    });
+    match result {
+      Ok(value) => Ok(Some(value)),
+      Err(err) if err.raw_os_error() == Some(ENOATTR) => Ok(None),
+      Err(err) => Err(err),
+    }
+  }
+
+  pub fn set(target: XattrTarget, name: &str, value: &[u8]) -> io::Result<()> {
+    let target = Target::new(target)?;
+    let name = CString::new(name)?;
+    // SAFETY: libc call, the value is valid for its length.
+    let ret = unsafe {
+      setxattr(
+        &target,
+        name.as_ptr(),
+        value.as_ptr() as *const c_void,
+        value.len(),
+      )
+    };
+    if ret != 0 {
+      return Err(io::Error::last_os_error());
+    }
+    Ok(())
+  }
+
+  pub fn list(target: XattrTarget) -> io::Result<Vec<String>> {
+    let target = Target::new(target)?;
+    // SAFETY: libc call, the buffer is valid for its size.
+    let list = read_with_buffer(|list, size| unsafe {
+      listxattr(&target, list as *mut c_char, size)
+    })?;
+    // The names are NUL terminated.
+    Ok(
+      list
+        .split(|byte| *byte == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect(),
+    )
+  }
+
+  pub fn remove(target: XattrTarget, name: &str) -> io::Result<()> {
+    let target = Target::new(target)?;
+    let name = CString::new(name)?;
+    // SAFETY: libc call.
+    let ret = unsafe { removexattr(&target, name.as_ptr()) };
+    if ret != 0 {
+      return Err(io::Error::last_os_error());
+    }
+    Ok(())
+  }
+}
+
+#[cfg(windows)]
+mod sys {
+  use super::XattrTarget;
+  use std::ffi::OsString;
+  use std::fs;
+  use std::fs::File as StdFile;
+  use std::io;
+  use std::os::windows::ffi::OsStrExt;
+  use std::os::windows::ffi::OsStringExt;
+  use std::os::windows::io::AsRawHandle;
+  use std::path::Path;
+  use std::path::PathBuf;
+  use winapi::shared::minwindef::DWORD;
+  use winapi::shared::minwindef::MAX_PATH;
+  use winapi::shared::winerror::ERROR_HANDLE_EOF;
+  use winapi::um::fileapi::FindClose;
+  use winapi::um::fileapi::FindFirstStreamW;
+  use winapi::um::fileapi::FindNextStreamW;
+  use winapi::um::fileapi::FindStreamInfoStandard;
+  use winapi::um::fileapi::GetFinalPathNameByHandleW;
+  use winapi::um::handleapi::INVALID_HANDLE_VALUE;
+
+  // winapi doesn't define this.
+  #[repr(C)]
+  struct FindStreamData {
+    stream_size: i64,
+    stream_name: [u16; MAX_PATH + 36],
+  }
+
+  fn final_path(file: &StdFile) -> io::Result<PathBuf> {
+    let mut buf = vec![0u16; MAX_PATH];
+    loop {
+      // SAFETY: winapi call, the buffer is valid for its length.
+      let len = unsafe {
+        GetFinalPathNameByHandleW(
+          file.as_raw_handle() as _,
+          buf.as_mut_ptr(),
+          buf.len() as DWORD,
+          0,
+        )
+      } as usize;
+      if len == 0 {
+        return Err(io::Error::last_os_error());
+      }
+      if len < buf.len() {
+        buf.truncate(len);
+        return Ok(OsString::from_wide(&buf).into());
+      }
+      // The buffer was too small, and `len` is the size it needs to be.
+      buf.resize(len, 0);
+    }
+  }
+
+  /// Returns the path of the file, after checking that it exists, since
+  /// writing to a stream of a file that doesn't exist creates it.
+  fn path(target: XattrTarget) -> io::Result<PathBuf> {
+    let path = match target {
+      XattrTarget::Path(path) => path.to_path_buf(),
+      XattrTarget::File(file) => final_path(file)?,
+    };
+    fs::metadata(&path)?;
+    Ok(path)
+  }
+
+  fn stream_path(path: &Path, name: &str) -> io::Result<PathBuf> {
+    if name.is_empty() || name.contains([':', '\\', '/']) {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Invalid extended attribute name: {name:?}"),
+      ));
+    }
+    let mut stream_path = path.as_os_str().to_owned();
+    stream_path.push(":");
+    stream_path.push(name);
+    Ok(stream_path.into())
+  }
+
+  pub fn get(target: XattrTarget, name: &str) -> io::Result<Option<Vec<u8>>> {
+    let path = path(target)?;
+    match fs::read(stream_path(&path, name)?) {
+      Ok(value) => Ok(Some(value)),
+      Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+      Err(err) => Err(err),
+    }
+  }
+
+  pub fn set(target: XattrTarget, name: &str, value: &[u8]) -> io::Result<()> {
+    let path = path(target)?;
+    fs::write(stream_path(&path, name)?, value)
+  }
+
+  pub fn list(target: XattrTarget) -> io::Result<Vec<String>> {
+    let path = path(target)?;
+    let path: Vec<u16> = path.as_os_str().encode_wide().chain([0]).collect();
+    // SAFETY: the struct is plain data.
+    let mut data: FindStreamData = unsafe { std::mem::zeroed() };
+    // SAFETY: winapi call, `path` is NUL terminated and `data` is the struct
+    // for `FindStreamInfoStandard`.
+    let handle = unsafe {
+      FindFirstStreamW(
+        path.as_ptr(),
+        FindStreamInfoStandard,
+        &mut data as *mut FindStreamData as *mut _,
+        0,
+      )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+      let err = io::Error::last_os_error();
+      // Directories don't have any stream.
+      if err.raw_os_error() == Some(ERROR_HANDLE_EOF as i32) {
+        return Ok(Vec::new());
+      }
+      return Err(err);
+    }
+
+    let mut names = Vec::new();
+    let result = loop {
+      let len = data
+        .stream_name
+        .iter()
+        .position(|c| *c == 0)
+        .unwrap_or(data.stream_name.len());
+      let name = String::from_utf16_lossy(&data.stream_name[..len]);
+      // The names look like ":name:$DATA", and the default stream of a file
+      // is "::$DATA".
+      if let Some(name) = name
+        .strip_prefix(':')
+        .and_then(|name| name.strip_suffix(":$DATA"))
+        .filter(|name| !name.is_empty())
+      {
+        names.push(name.to_string());
+      }
+      // SAFETY: winapi call, `handle` is open.
+      let ret = unsafe {
+        FindNextStreamW(handle, &mut data as *mut FindStreamData as *mut _)
+      };
+      if ret == 0 {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() == Some(ERROR_HANDLE_EOF as i32) {
+          break Ok(names);
+        }
+        break Err(err);
+      }
+    };
+    // SAFETY: winapi call, `handle` is open.
+    unsafe { FindClose(handle) };
+    result
+  }
+
+  pub fn remove(target: XattrTarget, name: &str) -> io::Result<()> {
+    let path = path(target)?;
+    fs::remove_file(stream_path(&path, name)?)
+  }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod sys {
+  use super::XattrTarget;
+  use std::io;
+
+  pub fn get(_target: XattrTarget, _name: &str) -> io::Result<Option<Vec<u8>>> {
+    Err(io::ErrorKind::Unsupported.into())
+  }
+
+  pub fn set(
+    _target: XattrTarget,
+    _name: &str,
+    _value: &[u8],
+  ) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+  }
+
+  pub fn list(_target: XattrTarget) -> io::Result<Vec<String>> {
+    Err(io::ErrorKind::Unsupported.into())
+  }
+
+  pub fn remove(_target: XattrTarget, _name: &str) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn test_xattr() {
+    let dir = std::env::temp_dir()
+      .join(format!("deno_io_xattr_test_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("file.txt");
+    std::fs::write(&path, "hello").unwrap();
+    let target = XattrTarget::Path(&path);
+
+    match set(target, "user.deno", b"value") {
+      Ok(()) => {}
+      // The file system of the temporary directory doesn't support xattrs.
+      Err(FsError::NotSupported) => {
+        std::fs::remove_dir_all(&dir).unwrap();
+        return;
+      }
+      Err(_) => panic!("setting an xattr failed"),
+    }
+    assert_eq!(get(target, "user.deno").ok(), Some(Some(b"value".to_vec())));
+    assert_eq!(get(target, "user.missing").ok(), Some(None));
+    let names = list(target).ok().unwrap();
+    assert!(names.contains(&"user.deno".to_string()));
+
+    let file = std::fs::File::open(&path).unwrap();
+    let file_target = XattrTarget::File(&file);
+    assert_eq!(
+      get(file_target, "user.deno").ok(),
+      Some(Some(b"value".to_vec()))
+    );
+    assert!(remove(file_target, "user.deno").is_ok());
+    assert_eq!(get(target, "user.deno").ok(), Some(None));
+    assert!(remove(target, "user.deno").is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+}
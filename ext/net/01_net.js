@@ -9,14 +9,17 @@ import {
   writableStreamForRid,
 } from "ext:deno_web/06_streams.js";
 import * as abortSignal from "ext:deno_web/03_abort_signal.js";
+import { FsFile } from "ext:deno_fs/30_fs.js";
 const primordials = globalThis.__bootstrap.primordials;
 const {
   ArrayPrototypeFilter,
   ArrayPrototypeForEach,
+  ArrayPrototypeMap,
   ArrayPrototypePush,
   Error,
   ObjectPrototypeIsPrototypeOf,
   PromiseResolve,
+  StringPrototypeIncludes,
   SymbolAsyncIterator,
   SymbolFor,
   TypeError,
@@ -49,7 +52,10 @@ async function resolveDns(query, recordType, options) {
       cancelRid,
       query,
       recordType,
-      options,
+      options: options && {
+        nameServer: options.nameServer,
+        resolver: options.resolver?.rid,
+      },
     });
   } finally {
     if (options?.signal) {
@@ -61,6 +67,75 @@ async function resolveDns(query, recordType, options) {
   }
 }
 
+class DnsResolver {
+  #rid = 0;
+
+  constructor(rid) {
+    this.#rid = rid;
+  }
+
+  get rid() {
+    return this.#rid;
+  }
+
+  close() {
+    core.close(this.#rid);
+  }
+}
+
+function createDnsResolver(options = {}) {
+  return new DnsResolver(ops.op_net_create_dns_resolver(options));
+}
+
+class IcmpSocket {
+  #rid = 0;
+  #family;
+
+  constructor(rid, family) {
+    this.#rid = rid;
+    this.#family = family;
+  }
+
+  get rid() {
+    return this.#rid;
+  }
+
+  get family() {
+    return this.#family;
+  }
+
+  async sendEchoRequest(hostname, options = {}) {
+    await core.opAsync(
+      "op_net_icmp_send_echo",
+      this.#rid,
+      hostname,
+      options.identifier ?? 0,
+      options.sequence ?? 0,
+      options.data ?? new Uint8Array(),
+    );
+  }
+
+  receive() {
+    return core.opAsync("op_net_icmp_recv", this.#rid);
+  }
+
+  setTtl(ttl) {
+    ops.op_net_icmp_set_ttl(this.#rid, ttl);
+  }
+
+  close() {
+    core.close(this.#rid);
+  }
+}
+
+function icmpSocket(options = {}) {
+  const family = options.family ?? "ipv4";
+  return new IcmpSocket(
+    ops.op_net_icmp_socket(family, options.ttl),
+    family,
+  );
+}
+
 class Conn {
   #rid = 0;
   #remoteAddr = null;
@@ -166,11 +241,68 @@ class TcpConn extends Conn {
   }
 
   setKeepAlive(keepAlive = true) {
+    if (typeof keepAlive === "object" && keepAlive !== null) {
+      return ops.op_set_keepalive_options(this.rid, keepAlive);
+    }
     return ops.op_set_keepalive(this.rid, keepAlive);
   }
 }
 
-class UnixConn extends Conn {}
+class UnixConn extends Conn {
+  async sendFds(data, resources) {
+    return await core.opAsync(
+      "op_net_send_fds_unix",
+      this.rid,
+      data,
+      ArrayPrototypeMap(resources, (resource) => resource.rid),
+    );
+  }
+
+  async receiveFds(buffer) {
+    const { 0: nread, 1: received } = await core.opAsync(
+      "op_net_recv_fds_unix",
+      this.rid,
+      buffer,
+    );
+    return {
+      nread: nread === 0 && received.length === 0 ? null : nread,
+      resources: ArrayPrototypeMap(received, receivedResource),
+    };
+  }
+
+  peerCredentials() {
+    return ops.op_net_peer_cred_unix(this.rid);
+  }
+}
+
+class VsockConn extends Conn {}
+
+function receivedResource(received) {
+  switch (received.kind) {
+    case "file":
+      return new FsFile(received.rid);
+    case "tcp":
+      received.localAddr.transport = "tcp";
+      received.remoteAddr.transport = "tcp";
+      return new TcpConn(received.rid, received.remoteAddr, received.localAddr);
+    case "unix":
+      return new UnixConn(
+        received.rid,
+        { transport: "unix", path: received.remoteAddr },
+        { transport: "unix", path: received.localAddr },
+      );
+    case "tcpListener":
+      received.addr.transport = "tcp";
+      return new Listener(received.rid, received.addr);
+    case "unixListener":
+      return new Listener(received.rid, {
+        transport: "unix",
+        path: received.path,
+      });
+    default:
+      throw new Error("unreachable");
+  }
+}
 
 class Listener {
   #rid = 0;
@@ -200,6 +332,9 @@ class Listener {
       case "unix":
         promise = core.opAsync("op_net_accept_unix", this.rid);
         break;
+      case "vsock":
+        promise = core.opAsync("op_net_accept_vsock", this.rid);
+        break;
       default:
         throw new Error(`Unsupported transport: ${this.addr.transport}`);
     }
@@ -217,6 +352,10 @@ class Listener {
         { transport: "unix", path: remoteAddr },
         { transport: "unix", path: localAddr },
       );
+    } else if (this.addr.transport == "vsock") {
+      localAddr.transport = "vsock";
+      remoteAddr.transport = "vsock";
+      return new VsockConn(rid, remoteAddr, localAddr);
     } else {
       throw new Error("unreachable");
     }
@@ -311,6 +450,7 @@ class Datagram {
         core.opAsync(
           "op_net_set_multi_ttl_udp",
           this.rid,
+          true,
           ttl,
         ),
     };
@@ -339,9 +479,53 @@ class Datagram {
           false,
           loopback,
         ),
+      setTTL: (ttl) =>
+        core.opAsync(
+          "op_net_set_multi_ttl_udp",
+          this.rid,
+          false,
+          ttl,
+        ),
     };
   }
 
+  setMulticastLoopback(loopback) {
+    return core.opAsync(
+      "op_net_set_multi_loopback_udp",
+      this.rid,
+      !this.#isIPv6(),
+      loopback,
+    );
+  }
+
+  setMulticastTTL(ttl) {
+    return core.opAsync(
+      "op_net_set_multi_ttl_udp",
+      this.rid,
+      !this.#isIPv6(),
+      ttl,
+    );
+  }
+
+  setMulticastInterface(multiInterface) {
+    if (typeof multiInterface === "number") {
+      return core.opAsync(
+        "op_net_set_multi_interface_v6_udp",
+        this.rid,
+        multiInterface,
+      );
+    }
+    return core.opAsync(
+      "op_net_set_multi_interface_v4_udp",
+      this.rid,
+      multiInterface,
+    );
+  }
+
+  #isIPv6() {
+    return StringPrototypeIncludes(this.#addr.hostname, ":");
+  }
+
   async receive(p) {
     const buf = p || new Uint8Array(this.bufSize);
     let nread;
@@ -421,7 +605,7 @@ function listen(args) {
       const { 0: rid, 1: addr } = ops.op_net_listen_tcp({
         hostname: args.hostname ?? "0.0.0.0",
         port: args.port,
-      }, args.reusePort);
+      }, args.reusePort, args.proxyProtocol ?? false);
       addr.transport = "tcp";
       return new Listener(rid, addr);
     }
@@ -433,6 +617,11 @@ function listen(args) {
       };
       return new Listener(rid, addr);
     }
+    case "vsock": {
+      const { 0: rid, 1: addr } = ops.op_net_listen_vsock(args.cid, args.port);
+      addr.transport = "vsock";
+      return new Listener(rid, addr);
+    }
     default:
       throw new TypeError(`Unsupported transport: '${transport}'`);
   }
@@ -448,6 +637,7 @@ function createListenDatagram(udpOpFn, unixOpFn) {
             port: args.port,
           },
           args.reuseAddress ?? false,
+          args.reusePort ?? false,
           args.loopback ?? false,
         );
         addr.transport = "udp";
@@ -476,6 +666,7 @@ async function connect(args) {
           hostname: args.hostname ?? "127.0.0.1",
           port: args.port,
         },
+        args.resolver?.rid,
       );
       localAddr.transport = "tcp";
       remoteAddr.transport = "tcp";
@@ -492,6 +683,16 @@ async function connect(args) {
         { transport: "unix", path: localAddr },
       );
     }
+    case "vsock": {
+      const { 0: rid, 1: localAddr, 2: remoteAddr } = await core.opAsync(
+        "op_net_connect_vsock",
+        args.cid,
+        args.port,
+      );
+      localAddr.transport = "vsock";
+      remoteAddr.transport = "vsock";
+      return new VsockConn(rid, remoteAddr, localAddr);
+    }
     default:
       throw new TypeError(`Unsupported transport: '${transport}'`);
   }
@@ -500,12 +701,18 @@ async function connect(args) {
 export {
   Conn,
   connect,
+  createDnsResolver,
   createListenDatagram,
   Datagram,
+  DnsResolver,
+  IcmpSocket,
+  icmpSocket,
   listen,
   Listener,
+  receivedResource,
   resolveDns,
   shutdown,
   TcpConn,
   UnixConn,
+  VsockConn,
 };
@@ -4,7 +4,9 @@ const core = globalThis.Deno.core;
 const ops = core.ops;
 import { Conn, Listener } from "ext:deno_net/01_net.js";
 const primordials = globalThis.__bootstrap.primordials;
-const { TypeError } = primordials;
+const { SymbolFor, TypeError } = primordials;
+
+const promiseIdSymbol = SymbolFor("Deno.core.internalPromiseId");
 
 function opStartTls(args) {
   return core.opAsync("op_tls_start", args);
@@ -29,6 +31,7 @@ async function connectTls({
   certChain = undefined,
   privateKey = undefined,
   alpnProtocols = undefined,
+  resolver = undefined,
 }) {
   if (transport !== "tcp") {
     throw new TypeError(`Unsupported transport: '${transport}'`);
@@ -36,7 +39,14 @@ async function connectTls({
   const { 0: rid, 1: localAddr, 2: remoteAddr } = await core.opAsync(
     "op_net_connect_tls",
     { hostname, port },
-    { certFile, caCerts, certChain, privateKey, alpnProtocols },
+    {
+      certFile,
+      caCerts,
+      certChain,
+      privateKey,
+      alpnProtocols,
+      resolver: resolver?.rid,
+    },
   );
   localAddr.transport = "tcp";
   remoteAddr.transport = "tcp";
@@ -65,17 +75,59 @@ function listenTls({
   transport = "tcp",
   alpnProtocols = undefined,
   reusePort = false,
+  proxyProtocol = false,
+  sni = undefined,
+  acme = undefined,
 }) {
   if (transport !== "tcp") {
     throw new TypeError(`Unsupported transport: '${transport}'`);
   }
-  const { 0: rid, 1: localAddr } = ops.op_net_listen_tls(
+  const sniCallback = typeof sni === "function" ? sni : undefined;
+  const { 0: rid, 1: localAddr, 2: sniCallbackRid } = ops.op_net_listen_tls(
     { hostname, port },
-    { cert, certFile, key, keyFile, alpnProtocols, reusePort },
+    {
+      cert,
+      certFile,
+      key,
+      keyFile,
+      alpnProtocols,
+      reusePort,
+      proxyProtocol,
+      sni: sniCallback ? undefined : sni,
+      sniCallback: sniCallback !== undefined,
+      acme,
+    },
   );
+  if (sniCallback) {
+    serveSniCallback(sniCallbackRid, sniCallback);
+  }
   return new TlsListener(rid, localAddr);
 }
 
+/** Answers the requests of the connections of a listener for the certificate
+ * of their server name with the SNI callback, until the listener is closed. */
+async function serveSniCallback(rid, callback) {
+  while (true) {
+    const promise = core.opAsync("op_tls_sni_next", rid);
+    // The listener keeps the event loop alive while it's accepting
+    core.unrefOp(promise[promiseIdSymbol]);
+    const request = await promise;
+    if (request === null) {
+      core.tryClose(rid);
+      return;
+    }
+    const { 0: id, 1: serverName } = request;
+    (async () => {
+      try {
+        const cert = await callback(serverName);
+        ops.op_tls_sni_resolve(rid, id, cert ?? null, null);
+      } catch (error) {
+        ops.op_tls_sni_resolve(rid, id, null, String(error));
+      }
+    })();
+  }
+}
+
 async function startTls(
   conn,
   {
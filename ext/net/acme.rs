@@ -0,0 +1,833 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Provisions and renews the certificate of a TLS listener from an ACME certificate authority
+//! such as Let's Encrypt. Domain ownership is proven with the `tls-alpn-01` challenge, which is
+//! answered by the listener itself, so no other port has to be opened, or with the `http-01`
+//! challenge, which is answered by a plain HTTP server that only runs while an order is
+//! validated.
+
+use crate::sni::certified_key;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_tls::load_certs;
+use deno_tls::load_private_keys;
+use deno_tls::rustls::server::ClientHello;
+use deno_tls::rustls::server::ResolvesServerCert;
+use deno_tls::rustls::sign::CertifiedKey;
+use deno_tls::rustls::PrivateKey;
+use instant_acme::Account;
+use instant_acme::AccountCredentials;
+use instant_acme::AuthorizationStatus;
+use instant_acme::ChallengeType;
+use instant_acme::Identifier;
+use instant_acme::LetsEncrypt;
+use instant_acme::NewAccount;
+use instant_acme::NewOrder;
+use instant_acme::Order;
+use instant_acme::OrderStatus;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::RwLock;
+use std::sync::Weak;
+use std::time::Duration;
+use std::time::SystemTime;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use x509_parser::extensions::GeneralName;
+
+/// The ALPN protocol the certificate authority negotiates to validate a `tls-alpn-01`
+/// challenge (RFC 8737).
+pub const ACME_TLS_ALPN_NAME: &[u8] = b"acme-tls/1";
+
+/// How long before its expiry a certificate is renewed.
+const RENEW_BEFORE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// How long to wait before trying again after provisioning a certificate failed.
+const RETRY_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// How many times the state of an order is polled before giving up on it.
+const MAX_POLL_ATTEMPTS: usize = 10;
+
+/// The path under which the `http-01` challenges are requested (RFC 8555, section 8.3).
+const HTTP01_PATH_PREFIX: &str = "/.well-known/acme-challenge/";
+
+/// How large the head of a request for an `http-01` challenge may be.
+const MAX_HTTP01_REQUEST_SIZE: usize = 8 * 1024;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+pub enum AcmeChallenge {
+  #[default]
+  #[serde(rename = "tls-alpn-01")]
+  TlsAlpn01,
+  #[serde(rename = "http-01")]
+  Http01,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcmeOptions {
+  domains: Vec<String>,
+  #[serde(default)]
+  contact: Vec<String>,
+  directory_url: Option<String>,
+  cache_dir: String,
+  #[serde(default)]
+  challenge: AcmeChallenge,
+  http_port: Option<u16>,
+}
+
+impl AcmeOptions {
+  pub fn domains(&self) -> &[String] {
+    &self.domains
+  }
+
+  pub fn directory_url(&self) -> &str {
+    self
+      .directory_url
+      .as_deref()
+      .unwrap_or(LetsEncrypt::Production.url())
+  }
+
+  pub fn cache_dir(&self) -> &Path {
+    Path::new(&self.cache_dir)
+  }
+
+  pub fn challenge(&self) -> AcmeChallenge {
+    self.challenge
+  }
+
+  /// The port the `http-01` challenges are answered on.
+  pub fn http_port(&self) -> u16 {
+    self.http_port.unwrap_or(80)
+  }
+
+  fn cache_path(&self, file_name: &str) -> PathBuf {
+    self.cache_dir().join(file_name)
+  }
+
+  /// The path of the file of the certificate or of its key, which is named after the first
+  /// domain.
+  fn cert_cache_path(&self, extension: &str) -> PathBuf {
+    self.cache_path(&format!(
+      "{}.{extension}",
+      cache_file_name(&self.domains[0])
+    ))
+  }
+}
+
+/// Turns a domain into a file name, so that a domain like `../x` or `*.example.com` can't
+/// escape the cache directory or contain characters that aren't allowed in file names.
+fn cache_file_name(domain: &str) -> String {
+  let name = domain
+    .chars()
+    .map(|c| match c {
+      'a'..='z' | '0'..='9' | '-' | '.' => c,
+      'A'..='Z' => c.to_ascii_lowercase(),
+      _ => '_',
+    })
+    .collect::<String>();
+  // Without leading dots, the name can't be `.` or `..`.
+  match name.trim_start_matches('.') {
+    "" => "_".to_string(),
+    name => name.to_string(),
+  }
+}
+
+/// Writes a file that holds a secret, like a private key, which only the current user may
+/// read.
+async fn write_private_file(
+  path: &Path,
+  contents: impl AsRef<[u8]>,
+) -> Result<(), AnyError> {
+  let mut options = tokio::fs::OpenOptions::new();
+  options.write(true).create(true).truncate(true);
+  #[cfg(unix)]
+  options.mode(0o600);
+  let mut file = options.open(path).await?;
+  // The mode only applies to files that are created, not to existing ones.
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    file
+      .set_permissions(std::fs::Permissions::from_mode(0o600))
+      .await?;
+  }
+  file.write_all(contents.as_ref()).await?;
+  file.flush().await?;
+  Ok(())
+}
+
+/// Resolves the certificate provisioned over ACME, or the validation certificate when the
+/// certificate authority connects to answer a `tls-alpn-01` challenge.
+///
+/// Until a certificate has been provisioned, the certificate the listener was created with is
+/// used. Without one, handshakes fail.
+pub struct AcmeResolver {
+  cert: RwLock<Option<Arc<CertifiedKey>>>,
+  challenges: RwLock<HashMap<String, Arc<CertifiedKey>>>,
+}
+
+impl AcmeResolver {
+  pub fn new(cert: Option<Arc<CertifiedKey>>) -> Self {
+    Self {
+      cert: RwLock::new(cert),
+      challenges: Default::default(),
+    }
+  }
+}
+
+impl ResolvesServerCert for AcmeResolver {
+  fn resolve(&self, client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+    let is_challenge = client_hello
+      .alpn()
+      .map(|mut protocols| protocols.any(|p| p == ACME_TLS_ALPN_NAME))
+      .unwrap_or(false);
+    if is_challenge {
+      let server_name = client_hello.server_name()?.to_ascii_lowercase();
+      return self.challenges.read().unwrap().get(&server_name).cloned();
+    }
+    self.cert.read().unwrap().clone()
+  }
+}
+
+/// Keeps the certificate of `resolver` provisioned and renewed in the background, for as long as
+/// the resolver is alive.
+pub fn spawn_acme(options: AcmeOptions, resolver: &Arc<AcmeResolver>) {
+  tokio::spawn(run(options, Arc::downgrade(resolver)));
+}
+
+async fn run(options: AcmeOptions, resolver: Weak<AcmeResolver>) {
+  let mut next = match load_cached_cert(&options).await {
+    Ok(Some((cert, not_after))) => {
+      let Some(resolver) = resolver.upgrade() else {
+        return;
+      };
+      *resolver.cert.write().unwrap() = Some(cert);
+      renewal_time(not_after)
+    }
+    Ok(None) => Instant::now(),
+    Err(err) => {
+      log::warn!("Ignoring the cached ACME certificate: {err}");
+      Instant::now()
+    }
+  };
+  loop {
+    tokio::time::sleep_until(next).await;
+    let Some(resolver) = resolver.upgrade() else {
+      return;
+    };
+    next = match provision(&options, &resolver).await {
+      Ok(not_after) => renewal_time(not_after),
+      Err(err) => {
+        log::error!(
+          "Failed to provision a certificate for {} over ACME: {err}",
+          options.domains.join(", ")
+        );
+        Instant::now() + RETRY_AFTER
+      }
+    };
+    resolver.challenges.write().unwrap().clear();
+  }
+}
+
+fn renewal_time(not_after: SystemTime) -> Instant {
+  let valid_for = not_after
+    .duration_since(SystemTime::now())
+    .unwrap_or_default();
+  Instant::now() + valid_for.saturating_sub(RENEW_BEFORE)
+}
+
+/// Loads the certificate stored by a previous run, unless it doesn't cover all of the domains.
+async fn load_cached_cert(
+  options: &AcmeOptions,
+) -> Result<Option<(Arc<CertifiedKey>, SystemTime)>, AnyError> {
+  let cert_path = options.cert_cache_path("crt");
+  let key_path = options.cert_cache_path("key");
+  let (Ok(cert), Ok(key)) = (
+    tokio::fs::read_to_string(&cert_path).await,
+    tokio::fs::read_to_string(&key_path).await,
+  ) else {
+    return Ok(None);
+  };
+  let cert_chain = load_certs(&mut BufReader::new(cert.as_bytes()))?;
+  let (names, not_after) = inspect_cert(&cert_chain[0].0)?;
+  let covers_domains = options
+    .domains
+    .iter()
+    .all(|domain| names.iter().any(|name| name.eq_ignore_ascii_case(domain)));
+  if !covers_domains {
+    return Ok(None);
+  }
+  let key = load_private_keys(key.as_bytes())?.remove(0);
+  let cert = certified_key(cert_chain, &key)?;
+  Ok(Some((cert, not_after)))
+}
+
+/// Returns the DNS names a certificate is valid for and its expiry.
+fn inspect_cert(der: &[u8]) -> Result<(Vec<String>, SystemTime), AnyError> {
+  let (_, cert) = x509_parser::parse_x509_certificate(der)
+    .map_err(|e| generic_error(format!("Invalid certificate: {e}")))?;
+  let names = cert
+    .subject_alternative_name()?
+    .map(|san| {
+      san
+        .value
+        .general_names
+        .iter()
+        .filter_map(|name| match name {
+          GeneralName::DNSName(name) => Some(name.to_string()),
+          _ => None,
+        })
+        .collect()
+    })
+    .unwrap_or_default();
+  let not_after = cert.validity().not_after.timestamp();
+  let not_after =
+    SystemTime::UNIX_EPOCH + Duration::from_secs(not_after.max(0) as u64);
+  Ok((names, not_after))
+}
+
+async fn load_or_create_account(
+  options: &AcmeOptions,
+) -> Result<Account, AnyError> {
+  let path = options.cache_path("account.json");
+  if let Ok(credentials) = tokio::fs::read(&path).await {
+    let credentials: AccountCredentials =
+      deno_core::serde_json::from_slice(&credentials)?;
+    return Ok(Account::from_credentials(credentials)?);
+  }
+  let contact = options
+    .contact
+    .iter()
+    .map(|contact| format!("mailto:{contact}"))
+    .collect::<Vec<_>>();
+  let contact = contact.iter().map(String::as_str).collect::<Vec<_>>();
+  let account = Account::create(
+    &NewAccount {
+      contact: &contact,
+      terms_of_service_agreed: true,
+      only_return_existing: false,
+    },
+    options.directory_url(),
+  )
+  .await?;
+  let credentials = deno_core::serde_json::to_vec(&account.credentials())?;
+  tokio::fs::create_dir_all(options.cache_dir()).await?;
+  write_private_file(&path, credentials).await?;
+  Ok(account)
+}
+
+/// Orders a new certificate for the domains, stores it in the cache directory and starts
+/// serving it. Returns the expiry of the new certificate.
+async fn provision(
+  options: &AcmeOptions,
+  resolver: &AcmeResolver,
+) -> Result<SystemTime, AnyError> {
+  let account = load_or_create_account(options).await?;
+  let identifiers = options
+    .domains
+    .iter()
+    .map(|domain| Identifier::Dns(domain.clone()))
+    .collect::<Vec<_>>();
+  let mut order = account
+    .new_order(&NewOrder {
+      identifiers: &identifiers,
+    })
+    .await?;
+
+  let challenge_type = match options.challenge {
+    AcmeChallenge::TlsAlpn01 => ChallengeType::TlsAlpn01,
+    AcmeChallenge::Http01 => ChallengeType::Http01,
+  };
+  let mut challenge_urls = vec![];
+  let mut http01_key_authorizations = HashMap::new();
+  for authorization in order.authorizations().await? {
+    match authorization.status {
+      AuthorizationStatus::Pending => {}
+      AuthorizationStatus::Valid => continue,
+      status => {
+        return Err(generic_error(format!(
+          "Unexpected authorization status: {status:?}"
+        )))
+      }
+    }
+    let Identifier::Dns(domain) = authorization.identifier;
+    let challenge = authorization
+      .challenges
+      .iter()
+      .find(|challenge| challenge.r#type == challenge_type)
+      .ok_or_else(|| {
+        generic_error(format!(
+          "No {} challenge offered for {domain}",
+          challenge_name(options.challenge)
+        ))
+      })?;
+    let key_authorization = order.key_authorization(challenge);
+    match options.challenge {
+      AcmeChallenge::TlsAlpn01 => {
+        let cert =
+          challenge_cert(&domain, key_authorization.digest().as_ref())?;
+        resolver
+          .challenges
+          .write()
+          .unwrap()
+          .insert(domain.to_ascii_lowercase(), cert);
+      }
+      AcmeChallenge::Http01 => {
+        http01_key_authorizations.insert(
+          challenge.token.clone(),
+          key_authorization.as_str().to_string(),
+        );
+      }
+    }
+    challenge_urls.push(challenge.url.clone());
+  }
+  // Answers the challenges until the order has been validated.
+  let _http01_server = if http01_key_authorizations.is_empty() {
+    None
+  } else {
+    Some(
+      Http01Server::start(options.http_port(), http01_key_authorizations)
+        .await?,
+    )
+  };
+  for url in &challenge_urls {
+    order.set_challenge_ready(url).await?;
+  }
+
+  poll_order(&mut order).await?;
+
+  let mut params = rcgen::CertificateParams::new(options.domains.clone());
+  params.distinguished_name = rcgen::DistinguishedName::new();
+  let csr = rcgen::Certificate::from_params(params)?;
+  order.finalize(&csr.serialize_request_der()?).await?;
+  let mut cert_chain_pem = None;
+  for _ in 0..MAX_POLL_ATTEMPTS {
+    cert_chain_pem = order.certificate().await?;
+    if cert_chain_pem.is_some() {
+      break;
+    }
+    tokio::time::sleep(Duration::from_secs(1)).await;
+  }
+  let cert_chain_pem = cert_chain_pem
+    .ok_or_else(|| generic_error("Timed out waiting for the certificate"))?;
+
+  let cert_chain = load_certs(&mut BufReader::new(cert_chain_pem.as_bytes()))?;
+  let (_, not_after) = inspect_cert(&cert_chain[0].0)?;
+  tokio::fs::create_dir_all(options.cache_dir()).await?;
+  tokio::fs::write(options.cert_cache_path("crt"), &cert_chain_pem).await?;
+  write_private_file(
+    &options.cert_cache_path("key"),
+    csr.serialize_private_key_pem(),
+  )
+  .await?;
+
+  let key = PrivateKey(csr.serialize_private_key_der());
+  let cert = certified_key(cert_chain, &key)?;
+  *resolver.cert.write().unwrap() = Some(cert);
+  Ok(not_after)
+}
+
+/// Polls the state of `order` with exponential backoff until all of its challenges have been
+/// validated, failing if the order becomes invalid.
+async fn poll_order(order: &mut Order) -> Result<(), AnyError> {
+  let mut delay = Duration::from_millis(250);
+  for _ in 0..MAX_POLL_ATTEMPTS {
+    tokio::time::sleep(delay).await;
+    let state = order.refresh().await?;
+    if state.status == OrderStatus::Ready {
+      return Ok(());
+    }
+    if state.status == OrderStatus::Invalid {
+      return Err(match &state.error {
+        Some(problem) => generic_error(format!("Order is invalid: {problem}")),
+        None => generic_error("Order is invalid"),
+      });
+    }
+    delay *= 2;
+  }
+  Err(generic_error(
+    "Timed out waiting for the order to be validated",
+  ))
+}
+
+fn challenge_name(challenge: AcmeChallenge) -> &'static str {
+  match challenge {
+    AcmeChallenge::TlsAlpn01 => "tls-alpn-01",
+    AcmeChallenge::Http01 => "http-01",
+  }
+}
+
+/// A plain HTTP server that answers the `http-01` challenges with the key authorization of
+/// their token, until it is dropped.
+struct Http01Server(JoinHandle<()>);
+
+impl Http01Server {
+  async fn start(
+    port: u16,
+    key_authorizations: HashMap<String, String>,
+  ) -> Result<Self, AnyError> {
+    let listener = TcpListener::bind(("0.0.0.0", port)).await?;
+    let key_authorizations = Arc::new(key_authorizations);
+    Ok(Self(tokio::spawn(async move {
+      loop {
+        let Ok((stream, _)) = listener.accept().await else {
+          continue;
+        };
+        let key_authorizations = key_authorizations.clone();
+        tokio::spawn(async move {
+          let _ = tokio::time::timeout(
+            Duration::from_secs(10),
+            answer_http01(stream, &key_authorizations),
+          )
+          .await;
+        });
+      }
+    })))
+  }
+}
+
+impl Drop for Http01Server {
+  fn drop(&mut self) {
+    self.0.abort();
+  }
+}
+
+async fn answer_http01(
+  mut stream: TcpStream,
+  key_authorizations: &HashMap<String, String>,
+) -> Result<(), AnyError> {
+  let mut head = Vec::new();
+  let mut buf = [0; 1024];
+  while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+    let n = stream.read(&mut buf).await?;
+    if n == 0 || head.len() + n > MAX_HTTP01_REQUEST_SIZE {
+      return Ok(());
+    }
+    head.extend_from_slice(&buf[..n]);
+  }
+  let response =
+    http01_response(&String::from_utf8_lossy(&head), key_authorizations);
+  stream.write_all(response.as_bytes()).await?;
+  stream.shutdown().await?;
+  Ok(())
+}
+
+/// Returns the response to the head of a request for an `http-01` challenge.
+fn http01_response(
+  head: &str,
+  key_authorizations: &HashMap<String, String>,
+) -> String {
+  let mut request_line = head.lines().next().unwrap_or_default().split(' ');
+  let key_authorization = match (request_line.next(), request_line.next()) {
+    (Some("GET"), Some(path)) => path
+      .strip_prefix(HTTP01_PATH_PREFIX)
+      .and_then(|token| key_authorizations.get(token)),
+    _ => None,
+  };
+  match key_authorization {
+    Some(body) => format!(
+      "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+      body.len()
+    ),
+    None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+  }
+}
+
+/// Creates the self-signed certificate that answers the `tls-alpn-01` challenge for `domain`.
+fn challenge_cert(
+  domain: &str,
+  key_authorization_digest: &[u8],
+) -> Result<Arc<CertifiedKey>, AnyError> {
+  let mut params = rcgen::CertificateParams::new(vec![domain.to_string()]);
+  params.distinguished_name = rcgen::DistinguishedName::new();
+  params.custom_extensions = vec![rcgen::CustomExtension::new_acme_identifier(
+    key_authorization_digest,
+  )];
+  let cert = rcgen::Certificate::from_params(params)?;
+  let cert_chain = vec![deno_tls::rustls::Certificate(cert.serialize_der()?)];
+  certified_key(cert_chain, &PrivateKey(cert.serialize_private_key_der()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use deno_core::serde_json::json;
+  use deno_core::serde_json::Value;
+  use hyper::Body;
+  use hyper::Request;
+  use hyper::Response;
+  use std::convert::Infallible;
+  use std::sync::atomic::AtomicBool;
+  use std::sync::atomic::Ordering;
+
+  const CERT: &str = include_str!("../../cli/tests/testdata/tls/localhost.crt");
+
+  /// A certificate authority that issues `localhost.crt` for `localhost`, once the `http-01`
+  /// challenge has been answered on `http_port`. The signatures of the requests are not
+  /// checked.
+  struct MockAcme {
+    base: String,
+    http_port: u16,
+    validated: AtomicBool,
+    finalized: AtomicBool,
+  }
+
+  impl MockAcme {
+    fn order(&self) -> Value {
+      let base = &self.base;
+      let status = if self.finalized.load(Ordering::SeqCst) {
+        "valid"
+      } else if self.validated.load(Ordering::SeqCst) {
+        "ready"
+      } else {
+        "pending"
+      };
+      let certificate = self
+        .finalized
+        .load(Ordering::SeqCst)
+        .then(|| format!("{base}/cert/1"));
+      json!({
+        "status": status,
+        "identifiers": [{ "type": "dns", "value": "localhost" }],
+        "authorizations": [format!("{base}/authz/1")],
+        "finalize": format!("{base}/finalize/1"),
+        "certificate": certificate,
+      })
+    }
+
+    fn challenge(&self) -> Value {
+      let status = if self.validated.load(Ordering::SeqCst) {
+        "valid"
+      } else {
+        "pending"
+      };
+      json!({
+        "type": "http-01",
+        "url": format!("{}/challenge/1", self.base),
+        "token": "token1",
+        "status": status,
+      })
+    }
+
+    /// Requests the key authorization of the challenge like a certificate authority does.
+    async fn validate(&self) {
+      let mut stream = TcpStream::connect(("127.0.0.1", self.http_port))
+        .await
+        .unwrap();
+      stream
+        .write_all(
+          b"GET /.well-known/acme-challenge/token1 HTTP/1.1\r\nHost: localhost\r\n\r\n",
+        )
+        .await
+        .unwrap();
+      let mut response = String::new();
+      stream.read_to_string(&mut response).await.unwrap();
+      let (head, body) = response.split_once("\r\n\r\n").unwrap();
+      if head.starts_with("HTTP/1.1 200") && body.starts_with("token1.") {
+        self.validated.store(true, Ordering::SeqCst);
+      }
+    }
+
+    async fn handle(&self, req: Request<Body>) -> Response<Body> {
+      let base = &self.base;
+      let response = Response::builder().header("Replay-Nonce", "nonce");
+      let (response, body) = match req.uri().path() {
+        "/directory" => (
+          response,
+          json!({
+            "newNonce": format!("{base}/nonce"),
+            "newAccount": format!("{base}/account"),
+            "newOrder": format!("{base}/order"),
+            "revokeCert": format!("{base}/revoke"),
+            "keyChange": format!("{base}/key-change"),
+          }),
+        ),
+        "/nonce" => return response.body(Body::empty()).unwrap(),
+        "/account" => (
+          response
+            .status(201)
+            .header("Location", format!("{base}/account/1")),
+          json!({ "status": "valid" }),
+        ),
+        "/order" => (
+          response
+            .status(201)
+            .header("Location", format!("{base}/order/1")),
+          self.order(),
+        ),
+        "/order/1" => (response, self.order()),
+        "/authz/1" => (
+          response,
+          json!({
+            "status": "pending",
+            "identifier": { "type": "dns", "value": "localhost" },
+            "challenges": [self.challenge()],
+          }),
+        ),
+        "/challenge/1" => {
+          self.validate().await;
+          (response, self.challenge())
+        }
+        "/finalize/1" => {
+          self.finalized.store(true, Ordering::SeqCst);
+          (response, self.order())
+        }
+        "/cert/1" => {
+          return response
+            .header("Content-Type", "application/pem-certificate-chain")
+            .body(Body::from(CERT))
+            .unwrap()
+        }
+        _ => return response.status(404).body(Body::empty()).unwrap(),
+      };
+      response
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+    }
+  }
+
+  /// Starts a [`MockAcme`], returning its directory URL.
+  fn start_mock_acme(http_port: u16) -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let acme = Arc::new(MockAcme {
+      base: base.clone(),
+      http_port,
+      validated: AtomicBool::new(false),
+      finalized: AtomicBool::new(false),
+    });
+    let make_service = hyper::service::make_service_fn(move |_| {
+      let acme = acme.clone();
+      async move {
+        Ok::<_, Infallible>(hyper::service::service_fn(move |req| {
+          let acme = acme.clone();
+          async move { Ok::<_, Infallible>(acme.handle(req).await) }
+        }))
+      }
+    });
+    tokio::spawn(
+      hyper::Server::from_tcp(listener)
+        .unwrap()
+        .serve(make_service),
+    );
+    format!("{base}/directory")
+  }
+
+  #[tokio::test]
+  async fn test_provision_http01() {
+    let http_port = std::net::TcpListener::bind("127.0.0.1:0")
+      .unwrap()
+      .local_addr()
+      .unwrap()
+      .port();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let options = AcmeOptions {
+      domains: vec!["localhost".to_string()],
+      contact: vec![],
+      directory_url: Some(start_mock_acme(http_port)),
+      cache_dir: cache_dir.path().to_string_lossy().to_string(),
+      challenge: AcmeChallenge::Http01,
+      http_port: Some(http_port),
+    };
+    let resolver = AcmeResolver::new(None);
+    let not_after = provision(&options, &resolver).await.unwrap();
+    assert!(resolver.cert.read().unwrap().is_some());
+
+    let (cert, cached_not_after) =
+      load_cached_cert(&options).await.unwrap().unwrap();
+    assert_eq!(
+      cert.cert,
+      resolver.cert.read().unwrap().as_ref().unwrap().cert
+    );
+    assert_eq!(cached_not_after, not_after);
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      for file_name in ["account.json", "localhost.key"] {
+        let metadata =
+          std::fs::metadata(cache_dir.path().join(file_name)).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+      }
+    }
+  }
+
+  #[test]
+  fn test_cache_file_name() {
+    assert_eq!(cache_file_name("Example.COM"), "example.com");
+    assert_eq!(cache_file_name("*.example.com"), "_.example.com");
+    assert_eq!(cache_file_name("../../etc/passwd"), "_.._etc_passwd");
+    assert_eq!(cache_file_name(".."), "_");
+    assert_eq!(cache_file_name("a\\b:c"), "a_b_c");
+  }
+
+  #[test]
+  fn test_http01_response() {
+    let key_authorizations =
+      HashMap::from([("token1".to_string(), "token1.thumbprint".to_string())]);
+    let response = http01_response(
+      "GET /.well-known/acme-challenge/token1 HTTP/1.1\r\nHost: a\r\n\r\n",
+      &key_authorizations,
+    );
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.ends_with("\r\n\r\ntoken1.thumbprint"));
+    for head in [
+      "GET /.well-known/acme-challenge/token2 HTTP/1.1\r\n\r\n",
+      "POST /.well-known/acme-challenge/token1 HTTP/1.1\r\n\r\n",
+      "GET /token1 HTTP/1.1\r\n\r\n",
+    ] {
+      assert!(http01_response(head, &key_authorizations)
+        .starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+  }
+
+  #[test]
+  fn test_challenge_cert() {
+    let digest = [7; 32];
+    let cert = challenge_cert("example.com", &digest).unwrap();
+    let (_, cert) =
+      x509_parser::parse_x509_certificate(&cert.cert[0].0).unwrap();
+    let san = cert.subject_alternative_name().unwrap().unwrap();
+    assert_eq!(
+      san.value.general_names,
+      vec![GeneralName::DNSName("example.com")]
+    );
+    // id-pe-acmeIdentifier, which must be critical and hold the key authorization digest
+    let oid =
+      x509_parser::oid_registry::Oid::from(&[1, 3, 6, 1, 5, 5, 7, 1, 31])
+        .unwrap();
+    let ext = cert.get_extension_unique(&oid).unwrap().unwrap();
+    assert!(ext.critical);
+    assert!(ext.value.ends_with(&digest));
+  }
+
+  #[test]
+  fn test_inspect_cert() {
+    let cert = include_str!("../../cli/tests/testdata/tls/localhost.crt");
+    let cert_chain = load_certs(&mut BufReader::new(cert.as_bytes())).unwrap();
+    let (names, not_after) = inspect_cert(&cert_chain[0].0).unwrap();
+    assert!(names.iter().any(|name| name == "localhost"));
+    assert!(not_after > SystemTime::UNIX_EPOCH);
+  }
+
+  #[test]
+  fn test_renewal_time() {
+    let now = Instant::now();
+    // Certificates that are about to expire are renewed right away
+    assert!(renewal_time(SystemTime::now()) <= now + Duration::from_secs(1));
+    let not_after = SystemTime::now() + RENEW_BEFORE * 2;
+    let renewal = renewal_time(not_after);
+    assert!(renewal >= now + RENEW_BEFORE - Duration::from_secs(1));
+    assert!(renewal <= now + RENEW_BEFORE + Duration::from_secs(1));
+  }
+}
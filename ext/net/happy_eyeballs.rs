@@ -0,0 +1,119 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Dual-stack connection establishment as described by
+//! [RFC 8305](https://www.rfc-editor.org/rfc/rfc8305) ("Happy Eyeballs"), so
+//! that hosts are still reached quickly from networks where one of the
+//! address families is broken.
+
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::futures::stream::FuturesUnordered;
+use deno_core::futures::StreamExt;
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpStream;
+
+/// The time to wait for a connection attempt before starting the next one,
+/// as recommended by RFC 8305.
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Connects to the first of the addresses that accepts the connection. The
+/// attempts are started one after the other, as soon as the previous one has
+/// failed or after [`CONNECTION_ATTEMPT_DELAY`], alternating between IPv6 and
+/// IPv4 addresses.
+pub async fn connect(addrs: Vec<SocketAddr>) -> Result<TcpStream, AnyError> {
+  let mut pending = interleave(addrs);
+  let mut attempts = FuturesUnordered::new();
+  match pending.pop_front() {
+    Some(addr) => attempts.push(TcpStream::connect(addr)),
+    None => return Err(generic_error("No resolved address found")),
+  }
+  loop {
+    let delay = tokio::time::sleep(CONNECTION_ATTEMPT_DELAY);
+    tokio::select! {
+      Some(result) = attempts.next() => match result {
+        Ok(stream) => return Ok(stream),
+        Err(err) => match pending.pop_front() {
+          Some(addr) => attempts.push(TcpStream::connect(addr)),
+          None if attempts.is_empty() => return Err(err.into()),
+          None => {}
+        },
+      },
+      _ = delay, if !pending.is_empty() => {
+        attempts.push(TcpStream::connect(pending.pop_front().unwrap()));
+      }
+    }
+  }
+}
+
+/// Orders the addresses so that their families alternate, starting with the
+/// family of the first one. The order within each family is kept.
+fn interleave(addrs: Vec<SocketAddr>) -> VecDeque<SocketAddr> {
+  let Some(first) = addrs.first() else {
+    return VecDeque::new();
+  };
+  let first_is_ipv6 = first.is_ipv6();
+  let (mut preferred, mut other): (VecDeque<_>, VecDeque<_>) = addrs
+    .into_iter()
+    .partition(|addr| addr.is_ipv6() == first_is_ipv6);
+  let mut interleaved = VecDeque::with_capacity(preferred.len() + other.len());
+  loop {
+    match (preferred.pop_front(), other.pop_front()) {
+      (None, None) => return interleaved,
+      (a, b) => interleaved.extend(a.into_iter().chain(b)),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tokio::net::TcpListener;
+
+  fn addrs(addrs: &[&str]) -> Vec<SocketAddr> {
+    addrs.iter().map(|addr| addr.parse().unwrap()).collect()
+  }
+
+  #[test]
+  fn test_interleave() {
+    assert_eq!(
+      Vec::from(interleave(addrs(&[
+        "[::1]:80",
+        "[::2]:80",
+        "[::3]:80",
+        "127.0.0.1:80",
+        "127.0.0.2:80",
+      ]))),
+      addrs(&[
+        "[::1]:80",
+        "127.0.0.1:80",
+        "[::2]:80",
+        "127.0.0.2:80",
+        "[::3]:80",
+      ])
+    );
+    assert_eq!(
+      Vec::from(interleave(addrs(&["127.0.0.1:80", "[::1]:80"]))),
+      addrs(&["127.0.0.1:80", "[::1]:80"])
+    );
+    assert!(interleave(vec![]).is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_connect_falls_back() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    // Nothing listens on the first address, so it is refused.
+    let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let closed_addr = closed.local_addr().unwrap();
+    drop(closed);
+    let stream =
+      connect(vec![closed_addr, SocketAddr::from(([127, 0, 0, 1], port))])
+        .await
+        .unwrap();
+    assert_eq!(stream.peer_addr().unwrap().port(), port);
+    assert!(connect(vec![closed_addr]).await.is_err());
+    assert!(connect(vec![]).await.is_err());
+  }
+}
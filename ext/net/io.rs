@@ -10,6 +10,7 @@ use deno_core::CancelTryFuture;
 use deno_core::RcRef;
 use deno_core::Resource;
 use socket2::SockRef;
+use socket2::TcpKeepalive;
 use std::borrow::Cow;
 use std::rc::Rc;
 use tokio::io::AsyncRead;
@@ -106,6 +107,13 @@ impl Resource for TcpStreamResource {
   fn close(self: Rc<Self>) {
     self.cancel_read_ops();
   }
+
+  #[cfg(unix)]
+  fn backing_fd(self: Rc<Self>) -> Option<std::os::unix::prelude::RawFd> {
+    use std::os::unix::io::AsRawFd;
+    let wr = RcRef::map(self, |r| &r.wr).try_borrow()?;
+    Some(wr.as_ref().as_ref().as_raw_fd())
+  }
 }
 
 impl TcpStreamResource {
@@ -121,6 +129,15 @@ impl TcpStreamResource {
       .map_socket(Box::new(move |socket| Ok(socket.set_keepalive(keepalive)?)))
   }
 
+  pub fn set_tcp_keepalive(
+    self: Rc<Self>,
+    keepalive: TcpKeepalive,
+  ) -> Result<(), AnyError> {
+    self.map_socket(Box::new(move |socket| {
+      Ok(socket.set_tcp_keepalive(&keepalive)?)
+    }))
+  }
+
   #[allow(clippy::type_complexity)]
   fn map_socket(
     self: Rc<Self>,
@@ -175,4 +192,23 @@ impl Resource for UnixStreamResource {
   fn close(self: Rc<Self>) {
     self.cancel_read_ops();
   }
+
+  #[cfg(unix)]
+  fn backing_fd(self: Rc<Self>) -> Option<std::os::unix::prelude::RawFd> {
+    use std::os::unix::io::AsRawFd;
+    let wr = RcRef::map(self, |r| &r.wr).try_borrow()?;
+    Some(wr.as_ref().as_ref().as_raw_fd())
+  }
+}
+
+#[cfg(unix)]
+impl UnixStreamResource {
+  /// The credentials of the process on the other end of the connection.
+  pub fn peer_cred(self: Rc<Self>) -> Result<unix::UCred, AnyError> {
+    if let Some(wr) = RcRef::map(self, |r| &r.wr).try_borrow() {
+      return Ok(wr.as_ref().as_ref().peer_cred()?);
+    }
+
+    Err(generic_error("Unable to get resources"))
+  }
 }
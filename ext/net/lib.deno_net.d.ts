@@ -17,8 +17,19 @@ declare namespace Deno {
     path: string;
   }
 
+  /** The address of a vsock connection or listener, which is only available
+   * with the unstable `"vsock"` transport on Linux.
+   *
+   * @category Network */
+  export interface VsockAddr {
+    transport: "vsock";
+    /** The context ID of the virtual machine or host. */
+    cid: number;
+    port: number;
+  }
+
   /** @category Network */
-  export type Addr = NetAddr | UnixAddr;
+  export type Addr = NetAddr | UnixAddr | VsockAddr;
 
   /** A generic network listener for stream-oriented protocols.
    *
@@ -200,6 +211,10 @@ declare namespace Deno {
    * const conn4 = await Deno.connect({ hostname: "golang.org", port: 80, transport: "tcp" });
    * ```
    *
+   * If the hostname resolves to several addresses, connection attempts to
+   * them are started 250ms apart, alternating between IPv6 and IPv4, and the
+   * first connection that is established is used ("Happy Eyeballs", RFC 8305).
+   *
    * Requires `allow-net` permission for "tcp".
    *
    * @tags allow-net
@@ -215,8 +230,32 @@ declare namespace Deno {
      * @param [noDelay=true]
      */
     setNoDelay(noDelay?: boolean): void;
-    /** Enable/disable keep-alive functionality. */
-    setKeepAlive(keepAlive?: boolean): void;
+    /** Enable/disable keep-alive functionality. Passing an object enables
+     * keep-alive with the given probe configuration.
+     *
+     * ```ts
+     * const conn = await Deno.connect({ hostname: "example.com", port: 80 });
+     * conn.setKeepAlive({ idle: 60_000, interval: 10_000, count: 3 });
+     * ```
+     *
+     * @param [keepAlive=true]
+     */
+    setKeepAlive(keepAlive?: boolean | TcpKeepAliveOptions): void;
+  }
+
+  /** The keep-alive probe configuration of a {@linkcode TcpConn}. Values that
+   * are left out keep their system defaults.
+   *
+   * @category Network */
+  export interface TcpKeepAliveOptions {
+    /** The number of milliseconds the connection must be idle before the
+     * first keep-alive probe is sent. */
+    idle?: number;
+    /** The number of milliseconds between two keep-alive probes. */
+    interval?: number;
+    /** The number of unanswered probes after which the connection is
+     * dropped. This is ignored on Windows. */
+    count?: number;
   }
 
   /** @category Network */
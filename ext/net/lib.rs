@@ -1,18 +1,28 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+pub mod acme;
+pub mod happy_eyeballs;
 pub mod io;
 pub mod ops;
+#[cfg(unix)]
+pub mod ops_icmp;
 pub mod ops_tls;
 #[cfg(unix)]
 pub mod ops_unix;
+#[cfg(target_os = "linux")]
+pub mod ops_vsock;
+pub mod proxy_protocol;
 pub mod raw;
 pub mod resolve_addr;
+pub mod resolver;
+pub mod sni;
 
 use deno_core::error::AnyError;
 use deno_core::OpState;
 use deno_tls::rustls::RootCertStore;
 use deno_tls::RootCertStoreProvider;
 use std::cell::RefCell;
+use std::net::SocketAddr;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
@@ -24,6 +34,28 @@ pub trait NetPermissions {
     _host: &(T, Option<u16>),
     _api_name: &str,
   ) -> Result<(), AnyError>;
+  /// Checks a host that's about to be resolved and connected to. A host name
+  /// may be allowed here and checked by `check_net_resolved()` instead, as it
+  /// may resolve to addresses in granted ranges of IP addresses.
+  fn check_net_unresolved<T: AsRef<str>>(
+    &mut self,
+    _host: &(T, Option<u16>),
+    _api_name: &str,
+  ) -> Result<(), AnyError>;
+  /// Checks the addresses that a host resolved to, right before connecting to
+  /// them. Ranges of IP addresses are matched against host names this way.
+  fn check_net_resolved<T: AsRef<str>>(
+    &mut self,
+    _host: &(T, Option<u16>),
+    _addrs: &[SocketAddr],
+    _api_name: &str,
+  ) -> Result<(), AnyError>;
+  fn check_vsock(
+    &mut self,
+    _cid: u32,
+    _port: u32,
+    _api_name: &str,
+  ) -> Result<(), AnyError>;
   fn check_read(&mut self, _p: &Path, _api_name: &str) -> Result<(), AnyError>;
   fn check_write(&mut self, _p: &Path, _api_name: &str)
     -> Result<(), AnyError>;
@@ -104,15 +136,21 @@ deno_core::extension!(deno_net,
     ops::op_net_leave_multi_v6_udp<P>,
     ops::op_net_set_multi_loopback_udp<P>,
     ops::op_net_set_multi_ttl_udp<P>,
+    ops::op_net_set_multi_interface_v4_udp<P>,
+    ops::op_net_set_multi_interface_v6_udp<P>,
     ops::op_dns_resolve<P>,
+    resolver::op_net_create_dns_resolver<P>,
     ops::op_set_nodelay,
     ops::op_set_keepalive,
+    ops::op_set_keepalive_options,
 
     ops_tls::op_tls_start<P>,
     ops_tls::op_net_connect_tls<P>,
     ops_tls::op_net_listen_tls<P>,
     ops_tls::op_net_accept_tls,
     ops_tls::op_tls_handshake,
+    sni::op_tls_sni_next,
+    sni::op_tls_sni_resolve,
 
     #[cfg(unix)] ops_unix::op_net_accept_unix,
     #[cfg(unix)] ops_unix::op_net_connect_unix<P>,
@@ -121,6 +159,17 @@ deno_core::extension!(deno_net,
     #[cfg(unix)] ops_unix::op_node_unstable_net_listen_unixpacket<P>,
     #[cfg(unix)] ops_unix::op_net_recv_unixpacket,
     #[cfg(unix)] ops_unix::op_net_send_unixpacket<P>,
+    #[cfg(unix)] ops_unix::op_net_send_fds_unix,
+    #[cfg(unix)] ops_unix::op_net_recv_fds_unix,
+    #[cfg(unix)] ops_unix::op_net_peer_cred_unix,
+
+    #[cfg(target_os = "linux")] ops_vsock::op_net_connect_vsock<P>,
+    #[cfg(target_os = "linux")] ops_vsock::op_net_listen_vsock<P>,
+    #[cfg(target_os = "linux")] ops_vsock::op_net_accept_vsock,
+    #[cfg(unix)] ops_icmp::op_net_icmp_socket<P>,
+    #[cfg(unix)] ops_icmp::op_net_icmp_set_ttl,
+    #[cfg(unix)] ops_icmp::op_net_icmp_send_echo<P>,
+    #[cfg(unix)] ops_icmp::op_net_icmp_recv,
   ],
   esm = [ "01_net.js", "02_tls.js" ],
   options = {
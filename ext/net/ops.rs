@@ -1,8 +1,12 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::happy_eyeballs;
 use crate::io::TcpStreamResource;
+use crate::proxy_protocol::TcpAcceptor;
 use crate::resolve_addr::resolve_addr;
 use crate::resolve_addr::resolve_addr_sync;
+use crate::resolver::resolve_connect_addrs;
+use crate::resolver::DnsResolverResource;
 use crate::NetPermissions;
 use deno_core::error::bad_resource;
 use deno_core::error::custom_error;
@@ -24,7 +28,9 @@ use serde::Deserialize;
 use serde::Serialize;
 use socket2::Domain;
 use socket2::Protocol;
+use socket2::SockRef;
 use socket2::Socket;
+use socket2::TcpKeepalive;
 use socket2::Type;
 use std::borrow::Cow;
 use std::cell::RefCell;
@@ -33,8 +39,8 @@ use std::net::Ipv6Addr;
 use std::net::SocketAddr;
 use std::rc::Rc;
 use std::str::FromStr;
+use std::time::Duration;
 use tokio::net::TcpListener;
-use tokio::net::TcpStream;
 use tokio::net::UdpSocket;
 use trust_dns_proto::rr::rdata::caa::Value;
 use trust_dns_proto::rr::record_data::RData;
@@ -86,17 +92,16 @@ async fn op_net_accept_tcp(
     .resource_table
     .get::<TcpListenerResource>(rid)
     .map_err(|_| bad_resource("Listener has been closed"))?;
-  let listener = RcRef::map(&resource, |r| &r.listener)
+  let mut listener = RcRef::map(&resource, |r| &r.listener)
     .try_borrow_mut()
     .ok_or_else(|| custom_error("Busy", "Another accept task is ongoing"))?;
   let cancel = RcRef::map(resource, |r| &r.cancel);
-  let (tcp_stream, _socket_addr) = listener
+  let (tcp_stream, remote_addr) = listener
     .accept()
     .try_or_cancel(cancel)
     .await
     .map_err(accept_err)?;
   let local_addr = tcp_stream.local_addr()?;
-  let remote_addr = tcp_stream.peer_addr()?;
 
   let mut state = state.borrow_mut();
   let rid = state
@@ -137,15 +142,20 @@ where
 {
   {
     let mut s = state.borrow_mut();
-    s.borrow_mut::<NP>().check_net(
+    s.borrow_mut::<NP>().check_net_unresolved(
       &(&addr.hostname, Some(addr.port)),
       "Deno.DatagramConn.send()",
     )?;
   }
-  let addr = resolve_addr(&addr.hostname, addr.port)
+  let resolved = resolve_addr(&addr.hostname, addr.port)
     .await?
     .next()
     .ok_or_else(|| generic_error("No resolved address found"))?;
+  state.borrow_mut().borrow_mut::<NP>().check_net_resolved(
+    &(&addr.hostname, Some(addr.port)),
+    &[resolved],
+    "Deno.DatagramConn.send()",
+  )?;
 
   let resource = state
     .borrow_mut()
@@ -153,7 +163,7 @@ where
     .get::<UdpSocketResource>(rid)
     .map_err(|_| bad_resource("Socket has been closed"))?;
   let socket = RcRef::map(&resource, |r| &r.socket).borrow().await;
-  let nwritten = socket.send_to(&zero_copy, &addr).await?;
+  let nwritten = socket.send_to(&zero_copy, &resolved).await?;
 
   Ok(nwritten)
 }
@@ -286,6 +296,7 @@ where
 async fn op_net_set_multi_ttl_udp<NP>(
   state: Rc<RefCell<OpState>>,
   rid: ResourceId,
+  is_v4_membership: bool,
   ttl: u32,
 ) -> Result<(), AnyError>
 where
@@ -298,7 +309,55 @@ where
     .map_err(|_| bad_resource("Socket has been closed"))?;
   let socket = RcRef::map(&resource, |r| &r.socket).borrow().await;
 
-  socket.set_multicast_ttl_v4(ttl)?;
+  if is_v4_membership {
+    socket.set_multicast_ttl_v4(ttl)?;
+  } else {
+    SockRef::from(&*socket).set_multicast_hops_v6(ttl)?;
+  }
+
+  Ok(())
+}
+
+#[op]
+async fn op_net_set_multi_interface_v4_udp<NP>(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  multi_interface: String,
+) -> Result<(), AnyError>
+where
+  NP: NetPermissions + 'static,
+{
+  let resource = state
+    .borrow_mut()
+    .resource_table
+    .get::<UdpSocketResource>(rid)
+    .map_err(|_| bad_resource("Socket has been closed"))?;
+  let socket = RcRef::map(&resource, |r| &r.socket).borrow().await;
+
+  let interface_addr = Ipv4Addr::from_str(multi_interface.as_str())?;
+
+  SockRef::from(&*socket).set_multicast_if_v4(&interface_addr)?;
+
+  Ok(())
+}
+
+#[op]
+async fn op_net_set_multi_interface_v6_udp<NP>(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  multi_interface: u32,
+) -> Result<(), AnyError>
+where
+  NP: NetPermissions + 'static,
+{
+  let resource = state
+    .borrow_mut()
+    .resource_table
+    .get::<UdpSocketResource>(rid)
+    .map_err(|_| bad_resource("Socket has been closed"))?;
+  let socket = RcRef::map(&resource, |r| &r.socket).borrow().await;
+
+  SockRef::from(&*socket).set_multicast_if_v6(multi_interface)?;
 
   Ok(())
 }
@@ -307,22 +366,28 @@ where
 pub async fn op_net_connect_tcp<NP>(
   state: Rc<RefCell<OpState>>,
   addr: IpAddr,
+  resolver_rid: Option<ResourceId>,
 ) -> Result<(ResourceId, IpAddr, IpAddr), AnyError>
 where
   NP: NetPermissions + 'static,
 {
   {
     let mut state_ = state.borrow_mut();
-    state_
-      .borrow_mut::<NP>()
-      .check_net(&(&addr.hostname, Some(addr.port)), "Deno.connect()")?;
+    state_.borrow_mut::<NP>().check_net_unresolved(
+      &(&addr.hostname, Some(addr.port)),
+      "Deno.connect()",
+    )?;
   }
 
-  let addr = resolve_addr(&addr.hostname, addr.port)
-    .await?
-    .next()
-    .ok_or_else(|| generic_error("No resolved address found"))?;
-  let tcp_stream = TcpStream::connect(&addr).await?;
+  let addrs =
+    resolve_connect_addrs(&state, resolver_rid, &addr.hostname, addr.port)
+      .await?;
+  state.borrow_mut().borrow_mut::<NP>().check_net_resolved(
+    &(&addr.hostname, Some(addr.port)),
+    &addrs,
+    "Deno.connect()",
+  )?;
+  let tcp_stream = happy_eyeballs::connect(addrs).await?;
   let local_addr = tcp_stream.local_addr()?;
   let remote_addr = tcp_stream.peer_addr()?;
 
@@ -335,7 +400,7 @@ where
 }
 
 pub struct TcpListenerResource {
-  pub listener: AsyncRefCell<TcpListener>,
+  pub listener: AsyncRefCell<TcpAcceptor>,
   pub cancel: CancelHandle,
 }
 
@@ -347,6 +412,13 @@ impl Resource for TcpListenerResource {
   fn close(self: Rc<Self>) {
     self.cancel.cancel();
   }
+
+  #[cfg(unix)]
+  fn backing_fd(self: Rc<Self>) -> Option<std::os::unix::prelude::RawFd> {
+    use std::os::unix::io::AsRawFd;
+    let listener = RcRef::map(self, |r| &r.listener).try_borrow()?;
+    Some(listener.as_raw_fd())
+  }
 }
 
 struct UdpSocketResource {
@@ -369,6 +441,7 @@ fn op_net_listen_tcp<NP>(
   state: &mut OpState,
   addr: IpAddr,
   reuse_port: bool,
+  proxy_protocol: bool,
 ) -> Result<(ResourceId, IpAddr), AnyError>
 where
   NP: NetPermissions + 'static,
@@ -376,6 +449,9 @@ where
   if reuse_port {
     super::check_unstable(state, "Deno.listen({ reusePort: true })");
   }
+  if proxy_protocol {
+    super::check_unstable(state, "Deno.listen({ proxyProtocol: true })");
+  }
   state
     .borrow_mut::<NP>()
     .check_net(&(&addr.hostname, Some(addr.port)), "Deno.listen()")?;
@@ -391,7 +467,7 @@ where
   #[cfg(not(windows))]
   socket.set_reuse_address(true)?;
   if reuse_port {
-    #[cfg(target_os = "linux")]
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
     socket.set_reuse_port(true)?;
   }
   let socket_addr = socket2::SockAddr::from(addr);
@@ -402,7 +478,7 @@ where
   let listener = TcpListener::from_std(std_listener)?;
   let local_addr = listener.local_addr()?;
   let listener_resource = TcpListenerResource {
-    listener: AsyncRefCell::new(listener),
+    listener: AsyncRefCell::new(TcpAcceptor::new(listener, proxy_protocol)),
     cancel: Default::default(),
   };
   let rid = state.resource_table.add(listener_resource);
@@ -414,6 +490,7 @@ fn net_listen_udp<NP>(
   state: &mut OpState,
   addr: IpAddr,
   reuse_address: bool,
+  reuse_port: bool,
   loopback: bool,
 ) -> Result<(ResourceId, IpAddr), AnyError>
 where
@@ -447,6 +524,10 @@ where
     #[cfg(all(unix, not(target_os = "linux")))]
     socket_tmp.set_reuse_port(true)?;
   }
+  if reuse_port {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    socket_tmp.set_reuse_port(true)?;
+  }
   let socket_addr = socket2::SockAddr::from(addr);
   socket_tmp.bind(&socket_addr)?;
   socket_tmp.set_nonblocking(true)?;
@@ -478,13 +559,14 @@ fn op_net_listen_udp<NP>(
   state: &mut OpState,
   addr: IpAddr,
   reuse_address: bool,
+  reuse_port: bool,
   loopback: bool,
 ) -> Result<(ResourceId, IpAddr), AnyError>
 where
   NP: NetPermissions + 'static,
 {
   super::check_unstable(state, "Deno.listenDatagram");
-  net_listen_udp::<NP>(state, addr, reuse_address, loopback)
+  net_listen_udp::<NP>(state, addr, reuse_address, reuse_port, loopback)
 }
 
 #[op]
@@ -492,12 +574,13 @@ fn op_node_unstable_net_listen_udp<NP>(
   state: &mut OpState,
   addr: IpAddr,
   reuse_address: bool,
+  reuse_port: bool,
   loopback: bool,
 ) -> Result<(ResourceId, IpAddr), AnyError>
 where
   NP: NetPermissions + 'static,
 {
-  net_listen_udp::<NP>(state, addr, reuse_address, loopback)
+  net_listen_udp::<NP>(state, addr, reuse_address, reuse_port, loopback)
 }
 
 #[derive(Serialize, Eq, PartialEq, Debug)]
@@ -557,6 +640,7 @@ pub struct ResolveAddrArgs {
 #[serde(rename_all = "camelCase")]
 pub struct ResolveDnsOption {
   name_server: Option<NameServer>,
+  resolver: Option<ResourceId>,
 }
 
 fn default_port() -> u16 {
@@ -586,36 +670,57 @@ where
     cancel_rid,
   } = args;
 
-  let (config, opts) = if let Some(name_server) =
-    options.as_ref().and_then(|o| o.name_server.as_ref())
-  {
-    let group = NameServerConfigGroup::from_ips_clear(
-      &[name_server.ip_addr.parse()?],
-      name_server.port,
-      true,
-    );
-    (
-      ResolverConfig::from_parts(None, vec![], group),
-      ResolverOpts::default(),
-    )
-  } else {
-    system_conf::read_system_conf()?
+  let custom_resolver = match options.as_ref().and_then(|o| o.resolver) {
+    Some(rid) => Some(
+      state
+        .borrow()
+        .resource_table
+        .get::<DnsResolverResource>(rid)?,
+    ),
+    None => None,
   };
 
-  {
-    let mut s = state.borrow_mut();
-    let perm = s.borrow_mut::<NP>();
-
-    // Checks permission against the name servers which will be actually queried.
-    for ns in config.name_servers() {
-      let socker_addr = &ns.socket_addr;
-      let ip = socker_addr.ip().to_string();
-      let port = socker_addr.port();
-      perm.check_net(&(ip, Some(port)), "Deno.resolveDns()")?;
+  let resolver = if let Some(custom_resolver) = custom_resolver {
+    // The permissions for the name servers of a resolver are checked when it
+    // is created.
+    if let Some(addrs) = custom_resolver.0.host_override(&query) {
+      if matches!(record_type, RecordType::A | RecordType::AAAA) {
+        return Ok(host_override_records(record_type, addrs));
+      }
+    }
+    custom_resolver.0.resolver().clone()
+  } else {
+    let (config, opts) = if let Some(name_server) =
+      options.as_ref().and_then(|o| o.name_server.as_ref())
+    {
+      let group = NameServerConfigGroup::from_ips_clear(
+        &[name_server.ip_addr.parse()?],
+        name_server.port,
+        true,
+      );
+      (
+        ResolverConfig::from_parts(None, vec![], group),
+        ResolverOpts::default(),
+      )
+    } else {
+      system_conf::read_system_conf()?
+    };
+
+    {
+      let mut s = state.borrow_mut();
+      let perm = s.borrow_mut::<NP>();
+
+      // Checks permission against the name servers which will be actually queried.
+      for ns in config.name_servers() {
+        let socker_addr = &ns.socket_addr;
+        let ip = socker_addr.ip().to_string();
+        let port = socker_addr.port();
+        perm.check_net(&(ip, Some(port)), "Deno.resolveDns()")?;
+      }
     }
-  }
 
-  let resolver = AsyncResolver::tokio(config, opts)?;
+    AsyncResolver::tokio(config, opts)?
+  };
 
   let lookup_fut = resolver.lookup(query, record_type);
 
@@ -680,6 +785,69 @@ pub fn op_set_keepalive(
   resource.set_keepalive(keepalive)
 }
 
+/// The keep-alive probe configuration of a TCP connection. Durations are in
+/// milliseconds, and the system defaults are kept for omitted values.
+#[derive(Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct KeepAliveOptions {
+  /// How long the connection must be idle before the first probe is sent.
+  idle: Option<u64>,
+  /// The time between two probes.
+  interval: Option<u64>,
+  /// The number of unanswered probes after which the connection is dropped.
+  /// Not supported on Windows.
+  count: Option<u32>,
+}
+
+impl KeepAliveOptions {
+  fn to_tcp_keepalive(&self) -> TcpKeepalive {
+    let mut keepalive = TcpKeepalive::new();
+    if let Some(idle) = self.idle {
+      keepalive = keepalive.with_time(Duration::from_millis(idle));
+    }
+    #[cfg(any(target_os = "linux", target_os = "macos", windows))]
+    if let Some(interval) = self.interval {
+      keepalive = keepalive.with_interval(Duration::from_millis(interval));
+    }
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    if let Some(count) = self.count {
+      keepalive = keepalive.with_retries(count);
+    }
+    keepalive
+  }
+}
+
+#[op]
+pub fn op_set_keepalive_options(
+  state: &mut OpState,
+  rid: ResourceId,
+  options: KeepAliveOptions,
+) -> Result<(), AnyError> {
+  let resource: Rc<TcpStreamResource> =
+    state.resource_table.get::<TcpStreamResource>(rid)?;
+  resource.set_tcp_keepalive(options.to_tcp_keepalive())
+}
+
+/// The A or AAAA records for a host whose addresses are configured with the
+/// `hosts` option of a resolver.
+fn host_override_records(
+  record_type: RecordType,
+  addrs: &[std::net::IpAddr],
+) -> Vec<DnsReturnRecord> {
+  addrs
+    .iter()
+    .filter_map(|addr| match (record_type, addr) {
+      (RecordType::A, std::net::IpAddr::V4(ip)) => {
+        Some(DnsReturnRecord::A(ip.to_string()))
+      }
+      (RecordType::AAAA, std::net::IpAddr::V6(ip)) => {
+        Some(DnsReturnRecord::Aaaa(ip.to_string()))
+      }
+      _ => None,
+    })
+    .collect()
+}
+
 fn rdata_to_return_record(
   ty: RecordType,
 ) -> impl Fn(&RData) -> Result<Option<DnsReturnRecord>, AnyError> {
@@ -986,6 +1154,32 @@ mod tests {
       Ok(())
     }
 
+    fn check_net_unresolved<T: AsRef<str>>(
+      &mut self,
+      _host: &(T, Option<u16>),
+      _api_name: &str,
+    ) -> Result<(), AnyError> {
+      Ok(())
+    }
+
+    fn check_net_resolved<T: AsRef<str>>(
+      &mut self,
+      _host: &(T, Option<u16>),
+      _addrs: &[SocketAddr],
+      _api_name: &str,
+    ) -> Result<(), AnyError> {
+      Ok(())
+    }
+
+    fn check_vsock(
+      &mut self,
+      _cid: u32,
+      _port: u32,
+      _api_name: &str,
+    ) -> Result<(), AnyError> {
+      Ok(())
+    }
+
     fn check_read(
       &mut self,
       _p: &Path,
@@ -1027,6 +1221,26 @@ mod tests {
     check_sockopt(String::from("127.0.0.1:4146"), set_keepalive, test_fn).await;
   }
 
+  #[cfg(any(target_os = "linux", target_os = "macos"))]
+  #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+  async fn tcp_set_keepalive_options() {
+    let set_keepalive = Box::new(|state: &mut OpState, rid| {
+      let options = KeepAliveOptions {
+        idle: Some(30_000),
+        interval: Some(5_000),
+        count: Some(3),
+      };
+      op_set_keepalive_options::call(state, rid, options).unwrap();
+    });
+    let test_fn = Box::new(|socket: SockRef| {
+      assert!(socket.keepalive().unwrap());
+      assert_eq!(socket.keepalive_time().unwrap(), Duration::from_secs(30));
+      assert_eq!(socket.keepalive_interval().unwrap(), Duration::from_secs(5));
+      assert_eq!(socket.keepalive_retries().unwrap(), 3);
+    });
+    check_sockopt(String::from("127.0.0.1:4147"), set_keepalive, test_fn).await;
+  }
+
   #[allow(clippy::type_complexity)]
   async fn check_sockopt(
     addr: String,
@@ -1065,7 +1279,7 @@ mod tests {
     };
 
     let mut connect_fut =
-      op_net_connect_tcp::call::<TestPermission>(conn_state, ip_addr)
+      op_net_connect_tcp::call::<TestPermission>(conn_state, ip_addr, None)
         .boxed_local();
     let mut rid = None;
 
@@ -0,0 +1,405 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! ICMP sockets, which send echo requests and receive the replies to them
+//! like `ping` does. Unprivileged ICMP datagram sockets are used where the
+//! system allows them, and raw sockets, which need the `CAP_NET_RAW`
+//! capability or root, otherwise.
+
+use crate::resolve_addr::resolve_addr;
+use crate::NetPermissions;
+use deno_core::error::bad_resource;
+use deno_core::error::custom_error;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::op;
+use deno_core::CancelHandle;
+use deno_core::CancelTryFuture;
+use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
+use deno_core::ResourceId;
+use deno_core::ZeroCopyBuf;
+use serde::Deserialize;
+use serde::Serialize;
+use socket2::Domain;
+use socket2::Protocol;
+use socket2::SockAddr;
+use socket2::Socket;
+use socket2::Type;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::io;
+use std::mem::MaybeUninit;
+use std::net::SocketAddr;
+use std::rc::Rc;
+use tokio::io::unix::AsyncFd;
+
+const ICMPV4_ECHO_REPLY: u8 = 0;
+const ICMPV4_ECHO_REQUEST: u8 = 8;
+const ICMPV6_ECHO_REQUEST: u8 = 128;
+const ICMPV6_ECHO_REPLY: u8 = 129;
+
+/// The size of the ICMP header, including the identifier and sequence number
+/// of echo messages.
+const ICMP_HEADER_SIZE: usize = 8;
+
+const PERMISSION_DENIED_MSG: &str = "Opening an ICMP socket requires the CAP_NET_RAW capability, or a group listed in the net.ipv4.ping_group_range sysctl";
+
+/// Large enough for any IP packet.
+const RECV_BUFFER_SIZE: usize = 65536;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IcmpFamily {
+  Ipv4,
+  Ipv6,
+}
+
+impl IcmpFamily {
+  fn echo_request_type(self) -> u8 {
+    match self {
+      IcmpFamily::Ipv4 => ICMPV4_ECHO_REQUEST,
+      IcmpFamily::Ipv6 => ICMPV6_ECHO_REQUEST,
+    }
+  }
+
+  fn is_echo(self, icmp_type: u8) -> bool {
+    match self {
+      IcmpFamily::Ipv4 => {
+        matches!(icmp_type, ICMPV4_ECHO_REQUEST | ICMPV4_ECHO_REPLY)
+      }
+      IcmpFamily::Ipv6 => {
+        matches!(icmp_type, ICMPV6_ECHO_REQUEST | ICMPV6_ECHO_REPLY)
+      }
+    }
+  }
+}
+
+/// Opens an ICMP socket, returning whether the packets received from it start
+/// with their IP header.
+fn open_socket(family: IcmpFamily) -> Result<(Socket, bool), AnyError> {
+  let (domain, protocol) = match family {
+    IcmpFamily::Ipv4 => (Domain::IPV4, Protocol::ICMPV4),
+    IcmpFamily::Ipv6 => (Domain::IPV6, Protocol::ICMPV6),
+  };
+  let (socket, has_ip_header) =
+    match Socket::new(domain, Type::DGRAM, Some(protocol)) {
+      // Only Linux strips the IP header from what ICMP datagram sockets
+      // receive. Packets of raw ICMPv6 sockets never include it.
+      Ok(socket) => (
+        socket,
+        family == IcmpFamily::Ipv4 && !cfg!(target_os = "linux"),
+      ),
+      Err(_) => match Socket::new(domain, Type::RAW, Some(protocol)) {
+        Ok(socket) => (socket, family == IcmpFamily::Ipv4),
+        Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+          return Err(custom_error("PermissionDenied", PERMISSION_DENIED_MSG));
+        }
+        Err(err) => return Err(err.into()),
+      },
+    };
+  socket.set_nonblocking(true)?;
+  Ok((socket, has_ip_header))
+}
+
+/// Builds an echo request. The checksum of ICMPv6 messages covers the IPv6
+/// pseudo header, so it is left for the kernel to fill in.
+fn echo_request(
+  family: IcmpFamily,
+  identifier: u16,
+  sequence: u16,
+  data: &[u8],
+) -> Vec<u8> {
+  let mut packet = Vec::with_capacity(ICMP_HEADER_SIZE + data.len());
+  packet.extend_from_slice(&[family.echo_request_type(), 0, 0, 0]);
+  packet.extend_from_slice(&identifier.to_be_bytes());
+  packet.extend_from_slice(&sequence.to_be_bytes());
+  packet.extend_from_slice(data);
+  if family == IcmpFamily::Ipv4 {
+    let checksum = internet_checksum(&packet);
+    packet[2..4].copy_from_slice(&checksum.to_be_bytes());
+  }
+  packet
+}
+
+/// The checksum of RFC 1071.
+fn internet_checksum(data: &[u8]) -> u16 {
+  let mut sum = data
+    .chunks(2)
+    .map(|chunk| {
+      let high = chunk[0] as u32;
+      let low = chunk.get(1).copied().unwrap_or(0) as u32;
+      (high << 8) | low
+    })
+    .sum::<u32>();
+  while sum >> 16 != 0 {
+    sum = (sum & 0xffff) + (sum >> 16);
+  }
+  !(sum as u16)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IcmpMessage {
+  r#type: u8,
+  code: u8,
+  /// Only set for echo requests and replies.
+  identifier: Option<u16>,
+  sequence: Option<u16>,
+  /// The TTL of the packet, when the IP header was received with it.
+  ttl: Option<u8>,
+  hostname: String,
+  data: ZeroCopyBuf,
+}
+
+fn parse_message(
+  family: IcmpFamily,
+  has_ip_header: bool,
+  packet: &[u8],
+  remote_addr: SocketAddr,
+) -> Result<IcmpMessage, AnyError> {
+  let truncated = || generic_error("Received a truncated ICMP message");
+  let (ttl, packet) = if has_ip_header {
+    let header_len =
+      (*packet.first().ok_or_else(truncated)? & 0x0f) as usize * 4;
+    if packet.len() < header_len.max(20) {
+      return Err(truncated());
+    }
+    (Some(packet[8]), &packet[header_len..])
+  } else {
+    (None, packet)
+  };
+  if packet.len() < ICMP_HEADER_SIZE {
+    return Err(truncated());
+  }
+  let (identifier, sequence) = if family.is_echo(packet[0]) {
+    (
+      Some(u16::from_be_bytes([packet[4], packet[5]])),
+      Some(u16::from_be_bytes([packet[6], packet[7]])),
+    )
+  } else {
+    (None, None)
+  };
+  Ok(IcmpMessage {
+    r#type: packet[0],
+    code: packet[1],
+    identifier,
+    sequence,
+    ttl,
+    hostname: remote_addr.ip().to_string(),
+    data: packet[ICMP_HEADER_SIZE..].to_vec().into(),
+  })
+}
+
+struct IcmpSocketResource {
+  socket: AsyncFd<Socket>,
+  family: IcmpFamily,
+  has_ip_header: bool,
+  cancel: CancelHandle,
+}
+
+impl IcmpSocketResource {
+  async fn send_to(&self, packet: &[u8], addr: &SockAddr) -> io::Result<()> {
+    loop {
+      let mut guard = self.socket.writable().await?;
+      if let Ok(result) = guard.try_io(|s| s.get_ref().send_to(packet, addr)) {
+        result?;
+        return Ok(());
+      }
+    }
+  }
+
+  async fn recv_from(
+    &self,
+    buf: &mut [u8],
+  ) -> io::Result<(usize, Option<SocketAddr>)> {
+    loop {
+      let mut guard = self.socket.readable().await?;
+      let result = guard.try_io(|s| {
+        // SAFETY: `recv_from` only writes initialized bytes to the buffer,
+        // which is initialized already.
+        let buf = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
+        s.get_ref().recv_from(buf)
+      });
+      if let Ok(result) = result {
+        let (nread, addr) = result?;
+        return Ok((nread, addr.as_socket()));
+      }
+    }
+  }
+}
+
+impl Resource for IcmpSocketResource {
+  fn name(&self) -> Cow<str> {
+    "icmpSocket".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel.cancel();
+  }
+}
+
+fn get_socket(
+  state: &Rc<RefCell<OpState>>,
+  rid: ResourceId,
+) -> Result<Rc<IcmpSocketResource>, AnyError> {
+  state
+    .borrow()
+    .resource_table
+    .get::<IcmpSocketResource>(rid)
+    .map_err(|_| bad_resource("Socket has been closed"))
+}
+
+#[op]
+pub fn op_net_icmp_socket<NP>(
+  state: &mut OpState,
+  family: IcmpFamily,
+  ttl: Option<u32>,
+) -> Result<ResourceId, AnyError>
+where
+  NP: NetPermissions + 'static,
+{
+  super::check_unstable(state, "Deno.icmpSocket");
+  // The socket receives the ICMP messages of any host, like a socket bound to
+  // the unspecified address
+  let unspecified = match family {
+    IcmpFamily::Ipv4 => "0.0.0.0",
+    IcmpFamily::Ipv6 => "::",
+  };
+  state
+    .borrow_mut::<NP>()
+    .check_net(&(unspecified, None), "Deno.icmpSocket()")?;
+  let (socket, has_ip_header) = open_socket(family)?;
+  if let Some(ttl) = ttl {
+    set_ttl(&socket, family, ttl)?;
+  }
+  let rid = state.resource_table.add(IcmpSocketResource {
+    socket: AsyncFd::new(socket)?,
+    family,
+    has_ip_header,
+    cancel: Default::default(),
+  });
+  Ok(rid)
+}
+
+fn set_ttl(socket: &Socket, family: IcmpFamily, ttl: u32) -> io::Result<()> {
+  match family {
+    IcmpFamily::Ipv4 => socket.set_ttl(ttl),
+    IcmpFamily::Ipv6 => socket.set_unicast_hops_v6(ttl),
+  }
+}
+
+#[op]
+pub fn op_net_icmp_set_ttl(
+  state: &mut OpState,
+  rid: ResourceId,
+  ttl: u32,
+) -> Result<(), AnyError> {
+  let resource = state
+    .resource_table
+    .get::<IcmpSocketResource>(rid)
+    .map_err(|_| bad_resource("Socket has been closed"))?;
+  set_ttl(resource.socket.get_ref(), resource.family, ttl)?;
+  Ok(())
+}
+
+#[op]
+pub async fn op_net_icmp_send_echo<NP>(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  hostname: String,
+  identifier: u16,
+  sequence: u16,
+  data: ZeroCopyBuf,
+) -> Result<(), AnyError>
+where
+  NP: NetPermissions + 'static,
+{
+  state.borrow_mut().borrow_mut::<NP>().check_net_unresolved(
+    &(&hostname, None),
+    "Deno.IcmpSocket.sendEchoRequest()",
+  )?;
+  let resource = get_socket(&state, rid)?;
+  let addr = resolve_addr(&hostname, 0)
+    .await?
+    .find(|addr| match resource.family {
+      IcmpFamily::Ipv4 => addr.is_ipv4(),
+      IcmpFamily::Ipv6 => addr.is_ipv6(),
+    })
+    .ok_or_else(|| generic_error("No resolved address found"))?;
+  state.borrow_mut().borrow_mut::<NP>().check_net_resolved(
+    &(&hostname, None),
+    &[addr],
+    "Deno.IcmpSocket.sendEchoRequest()",
+  )?;
+  let packet = echo_request(resource.family, identifier, sequence, &data);
+  let cancel = RcRef::map(&resource, |r| &r.cancel);
+  resource
+    .send_to(&packet, &addr.into())
+    .try_or_cancel(cancel)
+    .await?;
+  Ok(())
+}
+
+#[op]
+pub async fn op_net_icmp_recv(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+) -> Result<IcmpMessage, AnyError> {
+  let resource = get_socket(&state, rid)?;
+  let mut buf = vec![0; RECV_BUFFER_SIZE];
+  let cancel = RcRef::map(&resource, |r| &r.cancel);
+  let (nread, remote_addr) =
+    resource.recv_from(&mut buf).try_or_cancel(cancel).await?;
+  let remote_addr = remote_addr
+    .ok_or_else(|| generic_error("Received an ICMP message without address"))?;
+  parse_message(
+    resource.family,
+    resource.has_ip_header,
+    &buf[..nread],
+    remote_addr,
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_echo_request() {
+    let packet = echo_request(IcmpFamily::Ipv4, 0x1234, 1, b"ping");
+    assert_eq!(
+      packet,
+      [8, 0, 0x06, 0xfa, 0x12, 0x34, 0, 1, b'p', b'i', b'n', b'g']
+    );
+    assert_eq!(internet_checksum(&packet), 0);
+
+    let packet = echo_request(IcmpFamily::Ipv6, 0x1234, 1, b"");
+    assert_eq!(packet, [128, 0, 0, 0, 0x12, 0x34, 0, 1]);
+  }
+
+  #[test]
+  fn test_parse_message() {
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let mut packet = vec![0x45, 0, 0, 32, 0, 0, 0, 0, 64, 1, 0, 0];
+    packet.extend_from_slice(&[127, 0, 0, 1, 127, 0, 0, 1]);
+    packet.extend_from_slice(&[0, 0, 0, 0, 0x12, 0x34, 0, 7, 1, 2, 3]);
+    let message = parse_message(IcmpFamily::Ipv4, true, &packet, addr).unwrap();
+    assert_eq!(message.r#type, ICMPV4_ECHO_REPLY);
+    assert_eq!(message.identifier, Some(0x1234));
+    assert_eq!(message.sequence, Some(7));
+    assert_eq!(message.ttl, Some(64));
+    assert_eq!(message.hostname, "127.0.0.1");
+
+    // Time exceeded
+    let message =
+      parse_message(IcmpFamily::Ipv4, false, &[11, 0, 0, 0, 0, 0, 0, 0], addr)
+        .unwrap();
+    assert_eq!(message.r#type, 11);
+    assert_eq!(message.identifier, None);
+    assert_eq!(message.ttl, None);
+
+    assert!(parse_message(IcmpFamily::Ipv6, false, &[129, 0], addr).is_err());
+    assert!(parse_message(IcmpFamily::Ipv4, true, &packet[..10], addr).is_err());
+  }
+}
@@ -1,10 +1,21 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::acme::spawn_acme;
+use crate::acme::AcmeChallenge;
+use crate::acme::AcmeOptions;
+use crate::acme::AcmeResolver;
+use crate::acme::ACME_TLS_ALPN_NAME;
+use crate::happy_eyeballs;
 use crate::io::TcpStreamResource;
 use crate::ops::IpAddr;
 use crate::ops::TlsHandshakeInfo;
-use crate::resolve_addr::resolve_addr;
+use crate::proxy_protocol::TcpAcceptor;
 use crate::resolve_addr::resolve_addr_sync;
+use crate::resolver::resolve_connect_addrs;
+use crate::sni::certified_key;
+use crate::sni::SniCert;
+use crate::sni::SniResolver;
+use crate::sni::TlsAcceptor;
 use crate::DefaultTlsOptions;
 use crate::NetPermissions;
 use crate::UnsafelyIgnoreCertificateErrors;
@@ -26,6 +37,7 @@ use deno_core::futures::task::Waker;
 use deno_core::op;
 
 use deno_core::parking_lot::Mutex;
+use deno_core::url::Url;
 use deno_core::AsyncRefCell;
 use deno_core::AsyncResult;
 use deno_core::ByteString;
@@ -55,6 +67,7 @@ use socket2::Socket;
 use socket2::Type;
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::convert::From;
 use std::convert::TryFrom;
 use std::fs::File;
@@ -653,7 +666,7 @@ impl Shared {
   }
 }
 
-struct ImplementReadTrait<'a, T>(&'a mut T);
+pub(crate) struct ImplementReadTrait<'a, T>(pub(crate) &'a mut T);
 
 impl Read for ImplementReadTrait<'_, TcpStream> {
   fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
@@ -768,6 +781,7 @@ pub struct ConnectTlsArgs {
   cert_chain: Option<String>,
   private_key: Option<String>,
   alpn_protocols: Option<Vec<String>>,
+  resolver: Option<ResourceId>,
 }
 
 #[derive(Deserialize)]
@@ -886,8 +900,10 @@ where
   {
     let mut s = state.borrow_mut();
     let permissions = s.borrow_mut::<NP>();
-    permissions
-      .check_net(&(&addr.hostname, Some(addr.port)), "Deno.connectTls()")?;
+    permissions.check_net_unresolved(
+      &(&addr.hostname, Some(addr.port)),
+      "Deno.connectTls()",
+    )?;
     if let Some(path) = cert_file {
       permissions.check_read(Path::new(path), "Deno.connectTls()")?;
     }
@@ -911,11 +927,15 @@ where
     .root_cert_store()?;
   let hostname_dns = ServerName::try_from(&*addr.hostname)
     .map_err(|_| invalid_hostname(&addr.hostname))?;
-  let connect_addr = resolve_addr(&addr.hostname, addr.port)
-    .await?
-    .next()
-    .ok_or_else(|| generic_error("No resolved address found"))?;
-  let tcp_stream = TcpStream::connect(connect_addr).await?;
+  let addrs =
+    resolve_connect_addrs(&state, args.resolver, &addr.hostname, addr.port)
+      .await?;
+  state.borrow_mut().borrow_mut::<NP>().check_net_resolved(
+    &(&addr.hostname, Some(addr.port)),
+    &addrs,
+    "Deno.connectTls()",
+  )?;
+  let tcp_stream = happy_eyeballs::connect(addrs).await?;
   let local_addr = tcp_stream.local_addr()?;
   let remote_addr = tcp_stream.peer_addr()?;
 
@@ -974,8 +994,7 @@ fn load_private_keys_from_file(
 }
 
 pub struct TlsListenerResource {
-  pub(crate) tcp_listener: AsyncRefCell<TcpListener>,
-  pub(crate) tls_config: Arc<ServerConfig>,
+  pub(crate) acceptor: AsyncRefCell<TlsAcceptor>,
   cancel_handle: CancelHandle,
 }
 
@@ -1000,6 +1019,10 @@ pub struct ListenTlsArgs {
   key_file: Option<String>,
   alpn_protocols: Option<Vec<String>>,
   reuse_port: bool,
+  proxy_protocol: bool,
+  sni: Option<HashMap<String, SniCert>>,
+  sni_callback: bool,
+  acme: Option<AcmeOptions>,
 }
 
 #[op]
@@ -1007,13 +1030,22 @@ pub fn op_net_listen_tls<NP>(
   state: &mut OpState,
   addr: IpAddr,
   args: ListenTlsArgs,
-) -> Result<(ResourceId, IpAddr), AnyError>
+) -> Result<(ResourceId, IpAddr, Option<ResourceId>), AnyError>
 where
   NP: NetPermissions + 'static,
 {
   if args.reuse_port {
     super::check_unstable(state, "Deno.listenTls({ reusePort: true })");
   }
+  if args.proxy_protocol {
+    super::check_unstable(state, "Deno.listenTls({ proxyProtocol: true })");
+  }
+  if args.sni.is_some() || args.sni_callback {
+    super::check_unstable(state, "Deno.listenTls({ sni })");
+  }
+  if args.acme.is_some() {
+    super::check_unstable(state, "Deno.listenTls({ acme })");
+  }
 
   let cert_file = args.cert_file.as_deref();
   let key_file = args.key_file.as_deref();
@@ -1030,39 +1062,106 @@ where
     if let Some(path) = key_file {
       permissions.check_read(Path::new(path), "Deno.listenTls()")?;
     }
+    if let Some(acme) = &args.acme {
+      let directory_url = Url::parse(acme.directory_url())?;
+      let hostname = directory_url
+        .host_str()
+        .ok_or_else(|| generic_error("`acme.directoryUrl` has no host"))?;
+      permissions.check_net(
+        &(hostname, directory_url.port_or_known_default()),
+        "Deno.listenTls()",
+      )?;
+      if acme.challenge() == AcmeChallenge::Http01 {
+        permissions.check_net(
+          &("0.0.0.0", Some(acme.http_port())),
+          "Deno.listenTls()",
+        )?;
+      }
+      permissions.check_read(acme.cache_dir(), "Deno.listenTls()")?;
+      permissions.check_write(acme.cache_dir(), "Deno.listenTls()")?;
+    }
   }
 
   let cert_chain = if cert_file.is_some() && cert.is_some() {
     return Err(generic_error("Both cert and certFile is specified. You can specify either one of them."));
   } else if let Some(path) = cert_file {
-    load_certs_from_file(path)?
+    Some(load_certs_from_file(path)?)
   } else if let Some(cert) = cert {
-    load_certs(&mut BufReader::new(cert.as_bytes()))?
+    Some(load_certs(&mut BufReader::new(cert.as_bytes()))?)
   } else {
-    return Err(generic_error("`cert` is not specified."));
+    None
   };
   let key_der = if key_file.is_some() && key.is_some() {
     return Err(generic_error(
       "Both key and keyFile is specified. You can specify either one of them.",
     ));
   } else if let Some(path) = key_file {
-    load_private_keys_from_file(path)?.remove(0)
+    Some(load_private_keys_from_file(path)?.remove(0))
   } else if let Some(key) = key {
-    load_private_keys(key.as_bytes())?.remove(0)
+    Some(load_private_keys(key.as_bytes())?.remove(0))
   } else {
-    return Err(generic_error("`key` is not specified."));
+    None
   };
+  let uses_acme = args.acme.is_some();
 
-  let mut tls_config = ServerConfig::builder()
+  let tls_config = ServerConfig::builder()
     .with_safe_defaults()
-    .with_no_client_auth()
-    .with_single_cert(cert_chain, key_der)
-    .expect("invalid key or certificate");
+    .with_no_client_auth();
+  let mut tls_config =
+    match (args.sni, args.acme) {
+      (Some(_), Some(_)) => return Err(generic_error(
+        "Both sni and acme are specified. You can specify either one of them.",
+      )),
+      (None, Some(_)) if args.sni_callback => return Err(generic_error(
+        "Both sni and acme are specified. You can specify either one of them.",
+      )),
+      // The certificate is optional with ACME, as it is only served until one has been provisioned
+      (None, Some(acme)) => {
+        if acme.domains().is_empty() {
+          return Err(type_error("`acme.domains` must not be empty."));
+        }
+        let cert = match (cert_chain, key_der) {
+          (Some(cert_chain), Some(key_der)) => {
+            Some(certified_key(cert_chain, &key_der)?)
+          }
+          (None, None) => None,
+          (None, Some(_)) => {
+            return Err(generic_error("`cert` is not specified."))
+          }
+          (Some(_), None) => {
+            return Err(generic_error("`key` is not specified."))
+          }
+        };
+        let resolver = Arc::new(AcmeResolver::new(cert));
+        spawn_acme(acme, &resolver);
+        tls_config.with_cert_resolver(resolver)
+      }
+      (sni, None) => {
+        let cert_chain = cert_chain
+          .ok_or_else(|| generic_error("`cert` is not specified."))?;
+        let key_der =
+          key_der.ok_or_else(|| generic_error("`key` is not specified."))?;
+        match sni {
+          // Connections whose server name isn't listed get the default certificate
+          Some(sni) => {
+            let default = certified_key(cert_chain, &key_der)?;
+            tls_config
+              .with_cert_resolver(Arc::new(SniResolver::new(default, sni)?))
+          }
+          None => tls_config
+            .with_single_cert(cert_chain, key_der)
+            .expect("invalid key or certificate"),
+        }
+      }
+    };
   if let Some(alpn_protocols) = args.alpn_protocols {
     super::check_unstable(state, "Deno.listenTls#alpn_protocols");
     tls_config.alpn_protocols =
       alpn_protocols.into_iter().map(|s| s.into_bytes()).collect();
   }
+  if uses_acme {
+    tls_config.alpn_protocols.push(ACME_TLS_ALPN_NAME.to_vec());
+  }
 
   let bind_addr = resolve_addr_sync(&addr.hostname, addr.port)?
     .next()
@@ -1076,7 +1175,7 @@ where
   #[cfg(not(windows))]
   socket.set_reuse_address(true)?;
   if args.reuse_port {
-    #[cfg(target_os = "linux")]
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
     socket.set_reuse_port(true)?;
   }
   let socket_addr = socket2::SockAddr::from(bind_addr);
@@ -1087,15 +1186,23 @@ where
   let tcp_listener = TcpListener::from_std(std_listener)?;
   let local_addr = tcp_listener.local_addr()?;
 
+  let tcp_acceptor = TcpAcceptor::new(tcp_listener, args.proxy_protocol);
+  let tls_config = Arc::new(tls_config);
+  let (acceptor, sni_callback_rid) = if args.sni_callback {
+    let (acceptor, sni_callback) =
+      TlsAcceptor::with_sni_callback(tcp_acceptor, tls_config);
+    (acceptor, Some(state.resource_table.add(sni_callback)))
+  } else {
+    (TlsAcceptor::new(tcp_acceptor, tls_config), None)
+  };
   let tls_listener_resource = TlsListenerResource {
-    tcp_listener: AsyncRefCell::new(tcp_listener),
-    tls_config: Arc::new(tls_config),
+    acceptor: AsyncRefCell::new(acceptor),
     cancel_handle: Default::default(),
   };
 
   let rid = state.resource_table.add(tls_listener_resource);
 
-  Ok((rid, IpAddr::from(local_addr)))
+  Ok((rid, IpAddr::from(local_addr), sni_callback_rid))
 }
 
 #[op]
@@ -1110,12 +1217,12 @@ pub async fn op_net_accept_tls(
     .map_err(|_| bad_resource("Listener has been closed"))?;
 
   let cancel_handle = RcRef::map(&resource, |r| &r.cancel_handle);
-  let tcp_listener = RcRef::map(&resource, |r| &r.tcp_listener)
+  let mut acceptor = RcRef::map(&resource, |r| &r.acceptor)
     .try_borrow_mut()
     .ok_or_else(|| custom_error("Busy", "Another accept task is ongoing"))?;
 
-  let (tcp_stream, remote_addr) =
-    match tcp_listener.accept().try_or_cancel(&cancel_handle).await {
+  let (tls_stream, remote_addr) =
+    match acceptor.accept().try_or_cancel(&cancel_handle).await {
       Ok(tuple) => tuple,
       Err(err) if err.kind() == ErrorKind::Interrupted => {
         // FIXME(bartlomieju): compatibility with current JS implementation.
@@ -1124,10 +1231,7 @@ pub async fn op_net_accept_tls(
       Err(err) => return Err(err.into()),
     };
 
-  let local_addr = tcp_stream.local_addr()?;
-
-  let tls_stream =
-    TlsStream::new_server_side(tcp_stream, resource.tls_config.clone());
+  let local_addr = tls_stream.local_addr()?;
 
   let rid = {
     let mut state_ = state.borrow_mut();
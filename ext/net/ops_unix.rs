@@ -1,9 +1,14 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::io::TcpStreamResource;
 use crate::io::UnixStreamResource;
+use crate::ops::IpAddr;
+use crate::ops::TcpListenerResource;
+use crate::proxy_protocol::TcpAcceptor;
 use crate::NetPermissions;
 use deno_core::error::bad_resource;
 use deno_core::error::custom_error;
+use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::op;
 use deno_core::AsyncRefCell;
@@ -14,12 +19,34 @@ use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::ResourceId;
 use deno_core::ZeroCopyBuf;
+use deno_io::fs::FileResource;
+use deno_io::StdFileResourceInner;
+use nix::sys::socket;
+use nix::sys::socket::sockopt;
+use nix::sys::socket::AddressFamily;
+use nix::sys::socket::ControlMessage;
+use nix::sys::socket::ControlMessageOwned;
+use nix::sys::socket::MsgFlags;
+use nix::sys::socket::SockType;
+use nix::sys::socket::SockaddrLike;
+use nix::sys::socket::SockaddrStorage;
+use nix::sys::stat::fstat;
+use nix::sys::stat::SFlag;
 use serde::Deserialize;
 use serde::Serialize;
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::io::IoSlice;
+use std::io::IoSliceMut;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::io::FromRawFd;
+use std::os::unix::io::OwnedFd;
+use std::os::unix::io::RawFd;
 use std::path::Path;
 use std::rc::Rc;
+use tokio::io::Interest;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
 use tokio::net::UnixDatagram;
 use tokio::net::UnixListener;
 pub use tokio::net::UnixStream;
@@ -45,6 +72,11 @@ impl Resource for UnixListenerResource {
   fn close(self: Rc<Self>) {
     self.cancel.cancel();
   }
+
+  fn backing_fd(self: Rc<Self>) -> Option<std::os::unix::prelude::RawFd> {
+    let listener = RcRef::map(self, |r| &r.listener).try_borrow()?;
+    Some(listener.as_raw_fd())
+  }
 }
 
 pub struct UnixDatagramResource {
@@ -62,6 +94,48 @@ impl Resource for UnixDatagramResource {
   }
 }
 
+/// The name of a socket in the Linux abstract namespace, which is written with
+/// a leading NUL byte, like `"\0name"`.
+fn abstract_name(path: &str) -> Result<Option<&str>, AnyError> {
+  match path.strip_prefix('\0') {
+    Some(_) if !cfg!(target_os = "linux") => Err(type_error(
+      "Abstract Unix sockets are only supported on Linux",
+    )),
+    name => Ok(name),
+  }
+}
+
+/// Checks the permissions to use the socket at the path. Sockets in the
+/// abstract namespace aren't files, so they are checked against the net
+/// permission instead, as the host `@name`.
+fn check_socket_path<NP: NetPermissions>(
+  permissions: &mut NP,
+  path: &str,
+  read: bool,
+  api_name: &str,
+) -> Result<(), AnyError> {
+  if let Some(name) = abstract_name(path)? {
+    return permissions.check_net(&(format!("@{name}"), None), api_name);
+  }
+  let address_path = Path::new(path);
+  if read {
+    permissions.check_read(address_path, api_name)?;
+  }
+  permissions.check_write(address_path, api_name)
+}
+
+/// The path of a socket address, which is the path that was used to create
+/// the socket for abstract sockets.
+fn socket_path(
+  addr: &tokio::net::unix::SocketAddr,
+  path: &str,
+) -> Result<Option<String>, AnyError> {
+  if abstract_name(path)?.is_some() {
+    return Ok(Some(path.to_string()));
+  }
+  addr.as_pathname().map(pathstring).transpose()
+}
+
 #[derive(Serialize)]
 pub struct UnixAddr {
   pub path: Option<String>,
@@ -111,23 +185,21 @@ pub async fn op_net_connect_unix<NP>(
 where
   NP: NetPermissions + 'static,
 {
-  let address_path = Path::new(&path);
   super::check_unstable2(&state, "Deno.connect");
   {
     let mut state_ = state.borrow_mut();
-    state_
-      .borrow_mut::<NP>()
-      .check_read(address_path, "Deno.connect()")?;
-    state_
-      .borrow_mut::<NP>()
-      .check_write(address_path, "Deno.connect()")?;
+    check_socket_path(
+      state_.borrow_mut::<NP>(),
+      &path,
+      true,
+      "Deno.connect()",
+    )?;
   }
   let unix_stream = UnixStream::connect(Path::new(&path)).await?;
   let local_addr = unix_stream.local_addr()?;
   let remote_addr = unix_stream.peer_addr()?;
   let local_addr_path = local_addr.as_pathname().map(pathstring).transpose()?;
-  let remote_addr_path =
-    remote_addr.as_pathname().map(pathstring).transpose()?;
+  let remote_addr_path = socket_path(&remote_addr, &path)?;
   let mut state_ = state.borrow_mut();
   let resource = UnixStreamResource::new(unix_stream.into_split());
   let rid = state_.resource_table.add(resource);
@@ -168,8 +240,12 @@ where
   let address_path = Path::new(&path);
   {
     let mut s = state.borrow_mut();
-    s.borrow_mut::<NP>()
-      .check_write(address_path, "Deno.DatagramConn.send()")?;
+    check_socket_path(
+      s.borrow_mut::<NP>(),
+      &path,
+      false,
+      "Deno.DatagramConn.send()",
+    )?;
   }
 
   let resource = state
@@ -193,14 +269,11 @@ pub fn op_net_listen_unix<NP>(
 where
   NP: NetPermissions + 'static,
 {
-  let address_path = Path::new(&path);
   super::check_unstable(state, "Deno.listen");
-  let permissions = state.borrow_mut::<NP>();
-  permissions.check_read(address_path, "Deno.listen()")?;
-  permissions.check_write(address_path, "Deno.listen()")?;
-  let listener = UnixListener::bind(address_path)?;
+  check_socket_path(state.borrow_mut::<NP>(), &path, true, "Deno.listen()")?;
+  let listener = UnixListener::bind(Path::new(&path))?;
   let local_addr = listener.local_addr()?;
-  let pathname = local_addr.as_pathname().map(pathstring).transpose()?;
+  let pathname = socket_path(&local_addr, &path)?;
   let listener_resource = UnixListenerResource {
     listener: AsyncRefCell::new(listener),
     cancel: Default::default(),
@@ -211,18 +284,30 @@ where
 
 pub fn net_listen_unixpacket<NP>(
   state: &mut OpState,
-  path: String,
+  path: Option<String>,
 ) -> Result<(ResourceId, Option<String>), AnyError>
 where
   NP: NetPermissions + 'static,
 {
-  let address_path = Path::new(&path);
-  let permissions = state.borrow_mut::<NP>();
-  permissions.check_read(address_path, "Deno.listenDatagram()")?;
-  permissions.check_write(address_path, "Deno.listenDatagram()")?;
-  let socket = UnixDatagram::bind(address_path)?;
+  // A socket without a path can only be used to send datagrams, so it doesn't
+  // touch the file system.
+  let socket = match &path {
+    Some(path) => {
+      check_socket_path(
+        state.borrow_mut::<NP>(),
+        path,
+        true,
+        "Deno.listenDatagram()",
+      )?;
+      UnixDatagram::bind(Path::new(path))?
+    }
+    None => UnixDatagram::unbound()?,
+  };
   let local_addr = socket.local_addr()?;
-  let pathname = local_addr.as_pathname().map(pathstring).transpose()?;
+  let pathname = match &path {
+    Some(path) => socket_path(&local_addr, path)?,
+    None => None,
+  };
   let datagram_resource = UnixDatagramResource {
     socket: AsyncRefCell::new(socket),
     cancel: Default::default(),
@@ -234,7 +319,7 @@ where
 #[op]
 pub fn op_net_listen_unixpacket<NP>(
   state: &mut OpState,
-  path: String,
+  path: Option<String>,
 ) -> Result<(ResourceId, Option<String>), AnyError>
 where
   NP: NetPermissions + 'static,
@@ -246,7 +331,7 @@ where
 #[op]
 pub fn op_node_unstable_net_listen_unixpacket<NP>(
   state: &mut OpState,
-  path: String,
+  path: Option<String>,
 ) -> Result<(ResourceId, Option<String>), AnyError>
 where
   NP: NetPermissions + 'static,
@@ -254,6 +339,302 @@ where
   net_listen_unixpacket::<NP>(state, path)
 }
 
+/// The largest number of file descriptors that are received with a single
+/// message. Descriptors beyond it are discarded by the kernel.
+const MAX_RECEIVED_FDS: usize = 64;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const RECV_FDS_FLAGS: MsgFlags = MsgFlags::MSG_CMSG_CLOEXEC;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const RECV_FDS_FLAGS: MsgFlags = MsgFlags::empty();
+
+/// A resource that was created for a file descriptor received over a Unix
+/// socket.
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ReceivedResource {
+  File {
+    rid: ResourceId,
+  },
+  #[serde(rename_all = "camelCase")]
+  Tcp {
+    rid: ResourceId,
+    local_addr: IpAddr,
+    remote_addr: IpAddr,
+  },
+  #[serde(rename_all = "camelCase")]
+  Unix {
+    rid: ResourceId,
+    local_addr: Option<String>,
+    remote_addr: Option<String>,
+  },
+  TcpListener {
+    rid: ResourceId,
+    addr: IpAddr,
+  },
+  UnixListener {
+    rid: ResourceId,
+    path: Option<String>,
+  },
+}
+
+#[derive(Serialize)]
+pub struct PeerCredentials {
+  pid: Option<i32>,
+  uid: u32,
+  gid: u32,
+}
+
+#[op]
+pub async fn op_net_send_fds_unix(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  data: ZeroCopyBuf,
+  rids: Vec<ResourceId>,
+) -> Result<usize, AnyError> {
+  let (resource, fd_resources) = {
+    let state = state.borrow();
+    let resource = state
+      .resource_table
+      .get::<UnixStreamResource>(rid)
+      .map_err(|_| bad_resource("Connection has been closed"))?;
+    let fd_resources = rids
+      .into_iter()
+      .map(|rid| state.resource_table.get_any(rid))
+      .collect::<Result<Vec<_>, _>>()?;
+    (resource, fd_resources)
+  };
+  // The resources are kept alive until the message is sent, so that their
+  // descriptors can't be closed and reused in the meantime.
+  let mut fds: Vec<RawFd> = Vec::with_capacity(fd_resources.len());
+  for fd_resource in &fd_resources {
+    let fd = fd_resource.clone().backing_fd().ok_or_else(|| {
+      type_error(format!(
+        "Resource '{}' can't be sent over a Unix socket",
+        fd_resource.name()
+      ))
+    })?;
+    fds.push(fd);
+  }
+  let cmsgs = if fds.is_empty() {
+    vec![]
+  } else {
+    vec![ControlMessage::ScmRights(&fds)]
+  };
+
+  let wr = resource.wr_borrow_mut().await;
+  let stream: &UnixStream = (*wr).as_ref();
+  let nwritten = stream
+    .async_io(Interest::WRITABLE, || {
+      let iov = [IoSlice::new(&data)];
+      Ok(socket::sendmsg::<()>(
+        stream.as_raw_fd(),
+        &iov,
+        &cmsgs,
+        MsgFlags::empty(),
+        None,
+      )?)
+    })
+    .await?;
+  Ok(nwritten)
+}
+
+#[op]
+pub async fn op_net_recv_fds_unix(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  mut buf: ZeroCopyBuf,
+) -> Result<(usize, Vec<ReceivedResource>), AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<UnixStreamResource>(rid)
+    .map_err(|_| bad_resource("Connection has been closed"))?;
+  let cancel = resource.cancel_handle();
+  let rd = resource.rd_borrow_mut().await;
+  let stream: &UnixStream = (*rd).as_ref();
+  let mut cmsg_buffer = nix::cmsg_space!([RawFd; MAX_RECEIVED_FDS]);
+  let (nread, fds) = stream
+    .async_io(Interest::READABLE, || {
+      let mut iov = [IoSliceMut::new(&mut buf)];
+      let msg = socket::recvmsg::<()>(
+        stream.as_raw_fd(),
+        &mut iov,
+        Some(&mut cmsg_buffer),
+        RECV_FDS_FLAGS,
+      )?;
+      let mut fds = vec![];
+      for cmsg in msg.cmsgs() {
+        if let ControlMessageOwned::ScmRights(received) = cmsg {
+          // SAFETY: the descriptors were just created by the kernel for this
+          // process and nothing else refers to them.
+          fds.extend(
+            received
+              .into_iter()
+              .map(|fd| unsafe { OwnedFd::from_raw_fd(fd) }),
+          );
+        }
+      }
+      Ok((msg.bytes, fds))
+    })
+    .try_or_cancel(cancel)
+    .await?;
+
+  let mut state = state.borrow_mut();
+  let resources = fds
+    .into_iter()
+    .map(|fd| add_received_fd(&mut state, fd))
+    .collect::<Result<Vec<_>, _>>()?;
+  Ok((nread, resources))
+}
+
+/// Adds a resource for a received file descriptor. Stream sockets become
+/// connections or listeners, anything else is treated as a file.
+fn add_received_fd(
+  state: &mut OpState,
+  fd: OwnedFd,
+) -> Result<ReceivedResource, AnyError> {
+  let raw_fd = fd.as_raw_fd();
+  #[cfg(not(any(target_os = "linux", target_os = "android")))]
+  {
+    use nix::fcntl::fcntl;
+    use nix::fcntl::FcntlArg;
+    use nix::fcntl::FdFlag;
+    fcntl(raw_fd, FcntlArg::F_SETFD(FdFlag::FD_CLOEXEC))?;
+  }
+
+  let mode = SFlag::from_bits_truncate(fstat(raw_fd)?.st_mode);
+  let is_stream_socket = mode & SFlag::S_IFMT == SFlag::S_IFSOCK
+    && socket::getsockopt(raw_fd, sockopt::SockType)? == SockType::Stream;
+  if !is_stream_socket {
+    return Ok(add_received_file(state, fd));
+  }
+
+  let listening = socket::getsockopt(raw_fd, sockopt::AcceptConn)?;
+  let family = socket::getsockname::<SockaddrStorage>(raw_fd)?.family();
+  let resource = match (family, listening) {
+    (Some(AddressFamily::Unix), false) => {
+      let stream = std::os::unix::net::UnixStream::from(fd);
+      stream.set_nonblocking(true)?;
+      let stream = UnixStream::from_std(stream)?;
+      let local_addr = stream.local_addr()?;
+      let remote_addr = stream.peer_addr()?;
+      let rid = state
+        .resource_table
+        .add(UnixStreamResource::new(stream.into_split()));
+      ReceivedResource::Unix {
+        rid,
+        local_addr: local_addr.as_pathname().map(pathstring).transpose()?,
+        remote_addr: remote_addr.as_pathname().map(pathstring).transpose()?,
+      }
+    }
+    (Some(AddressFamily::Unix), true) => {
+      let listener = std::os::unix::net::UnixListener::from(fd);
+      listener.set_nonblocking(true)?;
+      let listener = UnixListener::from_std(listener)?;
+      let local_addr = listener.local_addr()?;
+      let rid = state.resource_table.add(UnixListenerResource {
+        listener: AsyncRefCell::new(listener),
+        cancel: Default::default(),
+      });
+      ReceivedResource::UnixListener {
+        rid,
+        path: local_addr.as_pathname().map(pathstring).transpose()?,
+      }
+    }
+    (Some(AddressFamily::Inet | AddressFamily::Inet6), false) => {
+      let stream = std::net::TcpStream::from(fd);
+      stream.set_nonblocking(true)?;
+      let stream = TcpStream::from_std(stream)?;
+      let local_addr = stream.local_addr()?;
+      let remote_addr = stream.peer_addr()?;
+      let rid = state
+        .resource_table
+        .add(TcpStreamResource::new(stream.into_split()));
+      ReceivedResource::Tcp {
+        rid,
+        local_addr: IpAddr::from(local_addr),
+        remote_addr: IpAddr::from(remote_addr),
+      }
+    }
+    (Some(AddressFamily::Inet | AddressFamily::Inet6), true) => {
+      let listener = std::net::TcpListener::from(fd);
+      listener.set_nonblocking(true)?;
+      let listener = TcpListener::from_std(listener)?;
+      let local_addr = listener.local_addr()?;
+      let rid = state.resource_table.add(TcpListenerResource {
+        listener: AsyncRefCell::new(TcpAcceptor::new(listener, false)),
+        cancel: Default::default(),
+      });
+      ReceivedResource::TcpListener {
+        rid,
+        addr: IpAddr::from(local_addr),
+      }
+    }
+    _ => add_received_file(state, fd),
+  };
+  Ok(resource)
+}
+
+fn add_received_file(state: &mut OpState, fd: OwnedFd) -> ReceivedResource {
+  let file = StdFileResourceInner::file(std::fs::File::from(fd));
+  let rid = state
+    .resource_table
+    .add(FileResource::new(Rc::new(file), "fsFile".to_string()));
+  ReceivedResource::File { rid }
+}
+
+#[op]
+pub fn op_net_peer_cred_unix(
+  state: &mut OpState,
+  rid: ResourceId,
+) -> Result<PeerCredentials, AnyError> {
+  let resource = state
+    .resource_table
+    .get::<UnixStreamResource>(rid)
+    .map_err(|_| bad_resource("Connection has been closed"))?;
+  let cred = resource.peer_cred()?;
+  Ok(PeerCredentials {
+    pid: cred.pid(),
+    uid: cred.uid(),
+    gid: cred.gid(),
+  })
+}
+
 pub fn pathstring(pathname: &Path) -> Result<String, AnyError> {
   into_string(pathname.into())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn test_add_received_fd() {
+    let mut state = OpState::new(0);
+
+    let file = std::fs::File::open("lib.rs").unwrap();
+    let resource = add_received_fd(&mut state, file.into()).unwrap();
+    assert!(matches!(resource, ReceivedResource::File { .. }));
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let stream = std::net::TcpStream::connect(addr).unwrap();
+    let resource = add_received_fd(&mut state, listener.into()).unwrap();
+    assert!(
+      matches!(resource, ReceivedResource::TcpListener { addr: IpAddr { port, .. }, .. } if port == addr.port())
+    );
+    let resource = add_received_fd(&mut state, stream.into()).unwrap();
+    assert!(
+      matches!(resource, ReceivedResource::Tcp { remote_addr: IpAddr { port, .. }, .. } if port == addr.port())
+    );
+
+    let (a, _b) = std::os::unix::net::UnixStream::pair().unwrap();
+    let resource = add_received_fd(&mut state, a.into()).unwrap();
+    assert!(matches!(resource, ReceivedResource::Unix { .. }));
+
+    let (a, _b) = std::os::unix::net::UnixDatagram::pair().unwrap();
+    let resource = add_received_fd(&mut state, a.into()).unwrap();
+    assert!(matches!(resource, ReceivedResource::File { .. }));
+  }
+}
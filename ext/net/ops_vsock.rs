@@ -0,0 +1,309 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Stream sockets of the `AF_VSOCK` address family, which connect virtual
+//! machines to their host (e.g. Firecracker or Cloud Hypervisor guests)
+//! without any network being set up in the guest. Addresses are a context ID
+//! (CID), which identifies the machine, and a port.
+
+use crate::io::FullDuplexResource;
+use crate::NetPermissions;
+use deno_core::error::bad_resource;
+use deno_core::error::custom_error;
+use deno_core::error::AnyError;
+use deno_core::op;
+use deno_core::AsyncRefCell;
+use deno_core::AsyncResult;
+use deno_core::CancelHandle;
+use deno_core::CancelTryFuture;
+use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
+use deno_core::ResourceId;
+use nix::errno::Errno;
+use nix::sys::socket;
+use nix::sys::socket::sockopt;
+use nix::sys::socket::AddressFamily;
+use nix::sys::socket::Shutdown;
+use nix::sys::socket::SockFlag;
+use nix::sys::socket::SockType;
+use nix::sys::socket::VsockAddr;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::io::FromRawFd;
+use std::os::unix::io::OwnedFd;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+use tokio::io::unix::AsyncFd;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
+
+const LISTEN_BACKLOG: usize = 128;
+
+fn vsock_socket() -> io::Result<OwnedFd> {
+  let fd = socket::socket(
+    AddressFamily::Vsock,
+    SockType::Stream,
+    SockFlag::SOCK_NONBLOCK | SockFlag::SOCK_CLOEXEC,
+    None,
+  )?;
+  // SAFETY: the socket was just created and nothing else refers to it.
+  Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+pub struct VsockStream(Arc<AsyncFd<OwnedFd>>);
+
+impl VsockStream {
+  fn new(fd: OwnedFd) -> io::Result<Self> {
+    Ok(Self(Arc::new(AsyncFd::new(fd)?)))
+  }
+
+  pub async fn connect(cid: u32, port: u32) -> io::Result<Self> {
+    let fd = vsock_socket()?;
+    match socket::connect(fd.as_raw_fd(), &VsockAddr::new(cid, port)) {
+      Ok(()) | Err(Errno::EINPROGRESS) => {}
+      Err(err) => return Err(err.into()),
+    }
+    let stream = Self::new(fd)?;
+    // The connection is established, or has failed, once the socket becomes
+    // writable.
+    let _ = stream.0.writable().await?;
+    match socket::getsockopt(stream.0.as_raw_fd(), sockopt::SocketError)? {
+      0 => Ok(stream),
+      errno => Err(io::Error::from_raw_os_error(errno)),
+    }
+  }
+
+  pub fn local_addr(&self) -> io::Result<VsockAddr> {
+    Ok(socket::getsockname(self.0.as_raw_fd())?)
+  }
+
+  pub fn peer_addr(&self) -> io::Result<VsockAddr> {
+    Ok(socket::getpeername(self.0.as_raw_fd())?)
+  }
+
+  pub fn into_split(self) -> (VsockReadHalf, VsockWriteHalf) {
+    (VsockReadHalf(self.0.clone()), VsockWriteHalf(self.0))
+  }
+}
+
+pub struct VsockReadHalf(Arc<AsyncFd<OwnedFd>>);
+
+impl AsyncRead for VsockReadHalf {
+  fn poll_read(
+    self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &mut ReadBuf<'_>,
+  ) -> Poll<io::Result<()>> {
+    loop {
+      let mut guard = ready!(self.0.poll_read_ready(cx))?;
+      let unfilled = buf.initialize_unfilled();
+      match guard.try_io(|fd| Ok(nix::unistd::read(fd.as_raw_fd(), unfilled)?))
+      {
+        Ok(Ok(nread)) => {
+          buf.advance(nread);
+          return Poll::Ready(Ok(()));
+        }
+        Ok(Err(err)) => return Poll::Ready(Err(err)),
+        Err(_would_block) => continue,
+      }
+    }
+  }
+}
+
+pub struct VsockWriteHalf(Arc<AsyncFd<OwnedFd>>);
+
+impl AsyncWrite for VsockWriteHalf {
+  fn poll_write(
+    self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &[u8],
+  ) -> Poll<io::Result<usize>> {
+    loop {
+      let mut guard = ready!(self.0.poll_write_ready(cx))?;
+      match guard.try_io(|fd| Ok(nix::unistd::write(fd.as_raw_fd(), buf)?)) {
+        Ok(result) => return Poll::Ready(result),
+        Err(_would_block) => continue,
+      }
+    }
+  }
+
+  fn poll_flush(
+    self: Pin<&mut Self>,
+    _cx: &mut Context<'_>,
+  ) -> Poll<io::Result<()>> {
+    Poll::Ready(Ok(()))
+  }
+
+  fn poll_shutdown(
+    self: Pin<&mut Self>,
+    _cx: &mut Context<'_>,
+  ) -> Poll<io::Result<()>> {
+    Poll::Ready(Ok(socket::shutdown(self.0.as_raw_fd(), Shutdown::Write)?))
+  }
+}
+
+pub struct VsockListener(AsyncFd<OwnedFd>);
+
+impl VsockListener {
+  pub fn bind(cid: u32, port: u32) -> io::Result<Self> {
+    let fd = vsock_socket()?;
+    socket::bind(fd.as_raw_fd(), &VsockAddr::new(cid, port))?;
+    socket::listen(fd.as_raw_fd(), LISTEN_BACKLOG)?;
+    Ok(Self(AsyncFd::new(fd)?))
+  }
+
+  pub async fn accept(&self) -> io::Result<(VsockStream, VsockAddr)> {
+    loop {
+      let mut guard = self.0.readable().await?;
+      let result = guard.try_io(|fd| {
+        Ok(socket::accept4(
+          fd.as_raw_fd(),
+          SockFlag::SOCK_NONBLOCK | SockFlag::SOCK_CLOEXEC,
+        )?)
+      });
+      if let Ok(result) = result {
+        // SAFETY: the socket was just accepted and nothing else refers to it.
+        let fd = unsafe { OwnedFd::from_raw_fd(result?) };
+        let stream = VsockStream::new(fd)?;
+        let remote_addr = stream.peer_addr()?;
+        return Ok((stream, remote_addr));
+      }
+    }
+  }
+
+  pub fn local_addr(&self) -> io::Result<VsockAddr> {
+    Ok(socket::getsockname(self.0.as_raw_fd())?)
+  }
+}
+
+pub type VsockStreamResource =
+  FullDuplexResource<VsockReadHalf, VsockWriteHalf>;
+
+impl Resource for VsockStreamResource {
+  deno_core::impl_readable_byob!();
+  deno_core::impl_writable!();
+
+  fn name(&self) -> Cow<str> {
+    "vsockStream".into()
+  }
+
+  fn shutdown(self: Rc<Self>) -> AsyncResult<()> {
+    Box::pin(self.shutdown())
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel_read_ops();
+  }
+}
+
+struct VsockListenerResource {
+  listener: AsyncRefCell<VsockListener>,
+  cancel: CancelHandle,
+}
+
+impl Resource for VsockListenerResource {
+  fn name(&self) -> Cow<str> {
+    "vsockListener".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel.cancel();
+  }
+}
+
+#[derive(Serialize)]
+pub struct VsockAddrInfo {
+  cid: u32,
+  port: u32,
+}
+
+impl From<VsockAddr> for VsockAddrInfo {
+  fn from(addr: VsockAddr) -> Self {
+    Self {
+      cid: addr.cid(),
+      port: addr.port(),
+    }
+  }
+}
+
+#[op]
+pub async fn op_net_connect_vsock<NP>(
+  state: Rc<RefCell<OpState>>,
+  cid: u32,
+  port: u32,
+) -> Result<(ResourceId, VsockAddrInfo, VsockAddrInfo), AnyError>
+where
+  NP: NetPermissions + 'static,
+{
+  super::check_unstable2(&state, "Deno.connect");
+  state.borrow_mut().borrow_mut::<NP>().check_vsock(
+    cid,
+    port,
+    "Deno.connect()",
+  )?;
+  let stream = VsockStream::connect(cid, port).await?;
+  let local_addr = stream.local_addr()?;
+  let remote_addr = stream.peer_addr()?;
+  let rid = state
+    .borrow_mut()
+    .resource_table
+    .add(VsockStreamResource::new(stream.into_split()));
+  Ok((rid, local_addr.into(), remote_addr.into()))
+}
+
+#[op]
+pub fn op_net_listen_vsock<NP>(
+  state: &mut OpState,
+  cid: u32,
+  port: u32,
+) -> Result<(ResourceId, VsockAddrInfo), AnyError>
+where
+  NP: NetPermissions + 'static,
+{
+  super::check_unstable(state, "Deno.listen");
+  state
+    .borrow_mut::<NP>()
+    .check_vsock(cid, port, "Deno.listen()")?;
+  let listener = VsockListener::bind(cid, port)?;
+  let local_addr = listener.local_addr()?;
+  let rid = state.resource_table.add(VsockListenerResource {
+    listener: AsyncRefCell::new(listener),
+    cancel: Default::default(),
+  });
+  Ok((rid, local_addr.into()))
+}
+
+#[op]
+pub async fn op_net_accept_vsock(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+) -> Result<(ResourceId, VsockAddrInfo, VsockAddrInfo), AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<VsockListenerResource>(rid)
+    .map_err(|_| bad_resource("Listener has been closed"))?;
+  let listener = RcRef::map(&resource, |r| &r.listener)
+    .try_borrow_mut()
+    .ok_or_else(|| custom_error("Busy", "Listener already in use"))?;
+  let cancel = RcRef::map(resource, |r| &r.cancel);
+  let (stream, remote_addr) = listener
+    .accept()
+    .try_or_cancel(cancel)
+    .await
+    .map_err(crate::ops::accept_err)?;
+  let local_addr = stream.local_addr()?;
+  let rid = state
+    .borrow_mut()
+    .resource_table
+    .add(VsockStreamResource::new(stream.into_split()));
+  Ok((rid, local_addr.into(), remote_addr.into()))
+}
@@ -0,0 +1,405 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Support for the [PROXY protocol](https://www.haproxy.org/download/2.8/doc/proxy-protocol.txt),
+//! which load balancers such as HAProxy or AWS NLB use to pass the original
+//! client address to the server they forward a connection to.
+
+use deno_core::futures::stream::FuturesUnordered;
+use deno_core::futures::StreamExt;
+use std::io;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+
+const V1_PREFIX: &[u8] = b"PROXY";
+const V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+
+/// The maximum length of a v1 header, including the trailing CRLF.
+const V1_MAX_LENGTH: usize = 107;
+
+/// How long a freshly accepted connection has to send its PROXY header before
+/// it is dropped.
+const PROXY_HEADER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How many accepted connections may be waiting for their PROXY header at
+/// once. Beyond that, no connection is accepted until a header has been read
+/// or has timed out, so the others wait in the backlog of the listener.
+const MAX_PENDING_HEADERS: usize = 1024;
+
+fn invalid_header(message: &str) -> io::Error {
+  io::Error::new(
+    io::ErrorKind::InvalidData,
+    format!("Invalid PROXY protocol header: {message}"),
+  )
+}
+
+/// Reads a v1 or v2 PROXY protocol header from the start of `io`, consuming
+/// exactly the bytes of the header.
+///
+/// Returns the source address announced by the proxy, or `None` if the proxy
+/// didn't relay a TCP connection (`UNKNOWN` in v1, `LOCAL` or a non-TCP
+/// address family in v2), in which case the peer address should be used.
+pub async fn read_proxy_header<S: AsyncRead + Unpin>(
+  io: &mut S,
+) -> io::Result<Option<SocketAddr>> {
+  // This is long enough to tell both versions apart, and short enough to never
+  // read past the end of the shortest possible header.
+  let mut prefix = [0; 5];
+  io.read_exact(&mut prefix).await?;
+
+  if prefix == V1_PREFIX {
+    // The v1 header is terminated by a CRLF, so we must read it byte-by-byte
+    // to avoid consuming any of the payload.
+    let mut line = prefix.to_vec();
+    while !line.ends_with(b"\r\n") {
+      if line.len() == V1_MAX_LENGTH {
+        return Err(invalid_header("header too long"));
+      }
+      line.push(io.read_u8().await?);
+    }
+    parse_v1(&line[..line.len() - 2])
+  } else if prefix == V2_SIGNATURE[..5] {
+    let mut header = [0; 16];
+    header[..5].copy_from_slice(&prefix);
+    io.read_exact(&mut header[5..]).await?;
+    let length = u16::from_be_bytes([header[14], header[15]]) as usize;
+    let mut addresses = vec![0; length];
+    io.read_exact(&mut addresses).await?;
+    parse_v2(&header, &addresses)
+  } else {
+    Err(invalid_header("missing signature"))
+  }
+}
+
+fn parse_v1(line: &[u8]) -> io::Result<Option<SocketAddr>> {
+  let line = std::str::from_utf8(line)
+    .map_err(|_| invalid_header("header is not ASCII"))?;
+  let mut parts = line.split(' ');
+  if parts.next() != Some("PROXY") {
+    return Err(invalid_header("missing signature"));
+  }
+  let protocol = parts.next();
+  if protocol == Some("UNKNOWN") {
+    // The rest of the line must be ignored.
+    return Ok(None);
+  }
+
+  let mut next = || parts.next().ok_or_else(|| invalid_header("too short"));
+  let source: IpAddr = next()?
+    .parse()
+    .map_err(|_| invalid_header("invalid source address"))?;
+  let _destination: IpAddr = next()?
+    .parse()
+    .map_err(|_| invalid_header("invalid destination address"))?;
+  let source_port: u16 = next()?
+    .parse()
+    .map_err(|_| invalid_header("invalid source port"))?;
+  let _destination_port: u16 = next()?
+    .parse()
+    .map_err(|_| invalid_header("invalid destination port"))?;
+  if parts.next().is_some() {
+    return Err(invalid_header("too long"));
+  }
+
+  match (protocol, source) {
+    (Some("TCP4"), IpAddr::V4(_)) | (Some("TCP6"), IpAddr::V6(_)) => {
+      Ok(Some(SocketAddr::new(source, source_port)))
+    }
+    _ => Err(invalid_header("unsupported protocol")),
+  }
+}
+
+fn parse_v2(
+  header: &[u8; 16],
+  addresses: &[u8],
+) -> io::Result<Option<SocketAddr>> {
+  if header[..12] != *V2_SIGNATURE {
+    return Err(invalid_header("missing signature"));
+  }
+
+  let version = header[12] >> 4;
+  let command = header[12] & 0x0f;
+  if version != 2 {
+    return Err(invalid_header("unsupported version"));
+  }
+  match command {
+    // LOCAL: the connection was established by the proxy itself, eg. for a
+    // health check.
+    0x0 => return Ok(None),
+    // PROXY
+    0x1 => {}
+    _ => return Err(invalid_header("unsupported command")),
+  }
+
+  // Anything after the addresses are TLVs, which we don't need.
+  match header[13] {
+    // TCP over IPv4
+    0x11 => {
+      if addresses.len() < 12 {
+        return Err(invalid_header("address block too short"));
+      }
+      let ip: [u8; 4] = addresses[0..4].try_into().unwrap();
+      let port = u16::from_be_bytes([addresses[8], addresses[9]]);
+      Ok(Some(SocketAddr::new(Ipv4Addr::from(ip).into(), port)))
+    }
+    // TCP over IPv6
+    0x21 => {
+      if addresses.len() < 36 {
+        return Err(invalid_header("address block too short"));
+      }
+      let ip: [u8; 16] = addresses[0..16].try_into().unwrap();
+      let port = u16::from_be_bytes([addresses[32], addresses[33]]);
+      Ok(Some(SocketAddr::new(Ipv6Addr::from(ip).into(), port)))
+    }
+    // UNSPEC, UDP or UNIX sockets: the receiver must fall back to the peer
+    // address.
+    _ => Ok(None),
+  }
+}
+
+/// Accepts TCP connections on a listener.
+///
+/// If `proxy_protocol` is set, the PROXY protocol header of every accepted
+/// connection is read in a task of its own, and the connection is returned
+/// once its header is, with the client address that it announces. This way a
+/// client that is slow to send its header doesn't hold up the others, and
+/// connections that don't send a valid header in time are dropped. At most
+/// `max_pending` headers are read at once.
+pub struct TcpAcceptor {
+  listener: TcpListener,
+  proxy_protocol: bool,
+  pending: FuturesUnordered<JoinHandle<io::Result<(TcpStream, SocketAddr)>>>,
+  max_pending: usize,
+}
+
+impl TcpAcceptor {
+  pub fn new(listener: TcpListener, proxy_protocol: bool) -> Self {
+    Self {
+      listener,
+      proxy_protocol,
+      pending: FuturesUnordered::new(),
+      max_pending: MAX_PENDING_HEADERS,
+    }
+  }
+
+  pub fn local_addr(&self) -> io::Result<SocketAddr> {
+    self.listener.local_addr()
+  }
+
+  /// Accepts a connection, returning it along with the address of the client.
+  ///
+  /// This is cancel safe: connections whose header is still being read are
+  /// kept for the next call.
+  pub async fn accept(&mut self) -> io::Result<(TcpStream, SocketAddr)> {
+    if !self.proxy_protocol {
+      return self.listener.accept().await;
+    }
+    loop {
+      tokio::select! {
+        res = self.listener.accept(), if self.pending.len() < self.max_pending => {
+          let (stream, peer_addr) = res?;
+          self.pending.push(tokio::spawn(read_proxy_header_with_timeout(
+            stream, peer_addr,
+          )));
+        }
+        Some(res) = self.pending.next(), if !self.pending.is_empty() => {
+          if let Ok(Ok(conn)) = res {
+            return Ok(conn);
+          }
+        }
+      }
+    }
+  }
+}
+
+impl Drop for TcpAcceptor {
+  fn drop(&mut self) {
+    for handle in self.pending.iter() {
+      handle.abort();
+    }
+  }
+}
+
+#[cfg(unix)]
+impl std::os::unix::io::AsRawFd for TcpAcceptor {
+  fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+    self.listener.as_raw_fd()
+  }
+}
+
+async fn read_proxy_header_with_timeout(
+  mut stream: TcpStream,
+  peer_addr: SocketAddr,
+) -> io::Result<(TcpStream, SocketAddr)> {
+  let addr =
+    tokio::time::timeout(PROXY_HEADER_TIMEOUT, read_proxy_header(&mut stream))
+      .await
+      .map_err(|_| {
+        io::Error::new(
+          io::ErrorKind::TimedOut,
+          "Timed out reading PROXY header",
+        )
+      })??;
+  Ok((stream, addr.unwrap_or(peer_addr)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  async fn read(mut input: &[u8]) -> (io::Result<Option<SocketAddr>>, &[u8]) {
+    let res = read_proxy_header(&mut input).await;
+    (res, input)
+  }
+
+  #[tokio::test]
+  async fn test_v1_tcp4() {
+    let (res, rest) =
+      read(b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\nGET /").await;
+    assert_eq!(res.unwrap(), Some("192.168.0.1:56324".parse().unwrap()));
+    assert_eq!(rest, b"GET /");
+  }
+
+  #[tokio::test]
+  async fn test_v1_tcp6() {
+    let (res, rest) = read(b"PROXY TCP6 ::1 ::2 1234 80\r\n").await;
+    assert_eq!(res.unwrap(), Some("[::1]:1234".parse().unwrap()));
+    assert_eq!(rest, b"");
+  }
+
+  #[tokio::test]
+  async fn test_v1_unknown() {
+    let (res, rest) = read(b"PROXY UNKNOWN\r\nhello").await;
+    assert_eq!(res.unwrap(), None);
+    assert_eq!(rest, b"hello");
+  }
+
+  #[tokio::test]
+  async fn test_v1_invalid() {
+    for input in [
+      &b"PROXY TCP4 192.168.0.1 192.168.0.11 56324\r\n"[..],
+      b"PROXY TCP4 ::1 ::2 1234 80\r\n",
+      b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443 1\r\n",
+      b"PROXY TCP4 192.168.0.1 192.168.0.11 99999 443\r\n",
+      b"GET / HTTP/1.1\r\n",
+    ] {
+      assert!(read(input).await.0.is_err());
+    }
+    let long = [&b"PROXY "[..], &[b'a'; 200]].concat();
+    assert!(read(&long).await.0.is_err());
+  }
+
+  fn v2(command: u8, family: u8, addresses: &[u8]) -> Vec<u8> {
+    let mut header = V2_SIGNATURE.to_vec();
+    header.push(0x20 | command);
+    header.push(family);
+    header.extend_from_slice(&(addresses.len() as u16).to_be_bytes());
+    header.extend_from_slice(addresses);
+    header
+  }
+
+  #[tokio::test]
+  async fn test_v2_tcp4() {
+    let mut input =
+      v2(0x1, 0x11, &[10, 0, 0, 1, 10, 0, 0, 2, 0x1f, 0x90, 0, 80]);
+    input.extend_from_slice(b"payload");
+    let (res, rest) = read(&input).await;
+    assert_eq!(res.unwrap(), Some("10.0.0.1:8080".parse().unwrap()));
+    assert_eq!(rest, b"payload");
+  }
+
+  #[tokio::test]
+  async fn test_v2_tcp6_with_tlvs() {
+    let mut addresses = vec![0; 36];
+    addresses[15] = 1;
+    addresses[31] = 2;
+    addresses[32..34].copy_from_slice(&443u16.to_be_bytes());
+    // A NOOP TLV
+    addresses.extend_from_slice(&[0x04, 0x00, 0x01, 0x00]);
+    let input = v2(0x1, 0x21, &addresses);
+    let (res, rest) = read(&input).await;
+    assert_eq!(res.unwrap(), Some("[::1]:443".parse().unwrap()));
+    assert_eq!(rest, b"");
+  }
+
+  #[tokio::test]
+  async fn test_v2_local() {
+    let input = v2(0x0, 0x00, &[]);
+    let (res, rest) = read(&input).await;
+    assert_eq!(res.unwrap(), None);
+    assert_eq!(rest, b"");
+  }
+
+  #[tokio::test]
+  async fn test_v2_invalid() {
+    assert!(read(&v2(0x2, 0x11, &[0; 12])).await.0.is_err());
+    assert!(read(&v2(0x1, 0x11, &[0; 4])).await.0.is_err());
+    let mut bad_version = v2(0x1, 0x11, &[0; 12]);
+    bad_version[12] = 0x11;
+    assert!(read(&bad_version).await.0.is_err());
+  }
+
+  #[tokio::test]
+  async fn test_acceptor_silent_client() {
+    use tokio::io::AsyncWriteExt;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut acceptor = TcpAcceptor::new(listener, true);
+
+    let _silent = TcpStream::connect(addr).await.unwrap();
+    let mut good = TcpStream::connect(addr).await.unwrap();
+    good
+      .write_all(b"PROXY TCP4 192.0.2.1 192.0.2.2 56324 443\r\n")
+      .await
+      .unwrap();
+
+    let (_, remote_addr) =
+      tokio::time::timeout(Duration::from_secs(5), acceptor.accept())
+        .await
+        .expect("the silent client held up the accept loop")
+        .unwrap();
+    assert_eq!(remote_addr, "192.0.2.1:56324".parse().unwrap());
+  }
+
+  #[tokio::test]
+  async fn test_acceptor_max_pending() {
+    use tokio::io::AsyncWriteExt;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut acceptor = TcpAcceptor::new(listener, true);
+    acceptor.max_pending = 1;
+
+    let mut slow = TcpStream::connect(addr).await.unwrap();
+    let mut good = TcpStream::connect(addr).await.unwrap();
+    good
+      .write_all(b"PROXY TCP4 192.0.2.1 192.0.2.2 56324 443\r\n")
+      .await
+      .unwrap();
+
+    // The slow client takes the only slot, so the other one isn't accepted
+    // until its header has been read.
+    assert!(tokio::time::timeout(
+      Duration::from_millis(200),
+      acceptor.accept()
+    )
+    .await
+    .is_err());
+    slow
+      .write_all(b"PROXY TCP4 192.0.2.3 192.0.2.2 56325 443\r\n")
+      .await
+      .unwrap();
+    let (_, remote_addr) = acceptor.accept().await.unwrap();
+    assert_eq!(remote_addr, "192.0.2.3:56325".parse().unwrap());
+    let (_, remote_addr) = acceptor.accept().await.unwrap();
+    assert_eq!(remote_addr, "192.0.2.1:56324".parse().unwrap());
+  }
+}
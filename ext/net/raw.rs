@@ -8,15 +8,15 @@ use crate::ops_tls::TlsStream;
 use crate::ops_tls::TlsStreamResource;
 #[cfg(unix)]
 use crate::ops_unix::UnixListenerResource;
+use crate::proxy_protocol::TcpAcceptor;
+use crate::sni::TlsAcceptor;
 use deno_core::error::bad_resource;
 use deno_core::error::bad_resource_id;
 use deno_core::error::AnyError;
 use deno_core::ResourceId;
 use deno_core::ResourceTable;
-use deno_tls::rustls::ServerConfig;
 use pin_project::pin_project;
 use std::rc::Rc;
-use std::sync::Arc;
 use tokio::net::TcpStream;
 #[cfg(unix)]
 use tokio::net::UnixStream;
@@ -165,8 +165,8 @@ impl tokio::io::AsyncWrite for NetworkStream {
 
 /// A raw stream listener of one of the types handled by this extension.
 pub enum NetworkStreamListener {
-  Tcp(tokio::net::TcpListener),
-  Tls(tokio::net::TcpListener, Arc<ServerConfig>),
+  Tcp(TcpAcceptor),
+  Tls(TlsAcceptor),
   #[cfg(unix)]
   Unix(tokio::net::UnixListener),
 }
@@ -178,21 +178,26 @@ pub enum NetworkStreamAddress {
 }
 
 impl NetworkStreamListener {
-  /// Accepts a connection on this listener.
-  pub async fn accept(&self) -> Result<NetworkStream, AnyError> {
+  /// Accepts a connection on this listener, returning it along with the address of the client.
+  pub async fn accept(
+    &mut self,
+  ) -> Result<(NetworkStream, NetworkStreamAddress), AnyError> {
     Ok(match self {
       Self::Tcp(tcp) => {
-        let (stream, _addr) = tcp.accept().await?;
-        NetworkStream::Tcp(stream)
+        let (stream, addr) = tcp.accept().await?;
+        (NetworkStream::Tcp(stream), NetworkStreamAddress::Ip(addr))
       }
-      Self::Tls(tcp, config) => {
-        let (stream, _addr) = tcp.accept().await?;
-        NetworkStream::Tls(TlsStream::new_server_side(stream, config.clone()))
+      Self::Tls(tls) => {
+        let (stream, addr) = tls.accept().await?;
+        (NetworkStream::Tls(stream), NetworkStreamAddress::Ip(addr))
       }
       #[cfg(unix)]
       Self::Unix(unix) => {
-        let (stream, _addr) = unix.accept().await?;
-        NetworkStream::Unix(stream)
+        let (stream, addr) = unix.accept().await?;
+        (
+          NetworkStream::Unix(stream),
+          NetworkStreamAddress::Unix(addr),
+        )
       }
     })
   }
@@ -200,7 +205,7 @@ impl NetworkStreamListener {
   pub fn listen_address(&self) -> Result<NetworkStreamAddress, std::io::Error> {
     match self {
       Self::Tcp(tcp) => Ok(NetworkStreamAddress::Ip(tcp.local_addr()?)),
-      Self::Tls(tcp, _) => Ok(NetworkStreamAddress::Ip(tcp.local_addr()?)),
+      Self::Tls(tls) => Ok(NetworkStreamAddress::Ip(tls.local_addr()?)),
       #[cfg(unix)]
       Self::Unix(unix) => Ok(NetworkStreamAddress::Unix(unix.local_addr()?)),
     }
@@ -304,10 +309,7 @@ pub fn take_network_stream_listener_resource(
   {
     let resource = Rc::try_unwrap(resource_rc)
       .map_err(|_| bad_resource("TLS socket listener is currently in use"))?;
-    return Ok(NetworkStreamListener::Tls(
-      resource.tcp_listener.into_inner(),
-      resource.tls_config,
-    ));
+    return Ok(NetworkStreamListener::Tls(resource.acceptor.into_inner()));
   }
 
   #[cfg(unix)]
@@ -0,0 +1,339 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! User configured DNS resolution. A resolver is created with
+//! `Deno.createDnsResolver()` and can be passed to `Deno.resolveDns()`,
+//! `Deno.connect()`, `Deno.connectTls()` and `Deno.createHttpClient()`, which
+//! then share its name servers, host overrides and cache.
+
+use crate::resolve_addr::resolve_addr;
+use crate::DefaultTlsOptions;
+use crate::NetPermissions;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::op;
+use deno_core::OpState;
+use deno_core::Resource;
+use deno_core::ResourceId;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use trust_dns_resolver::config::NameServerConfig;
+use trust_dns_resolver::config::Protocol;
+use trust_dns_resolver::config::ResolverConfig;
+use trust_dns_resolver::config::ResolverOpts;
+use trust_dns_resolver::system_conf;
+use trust_dns_resolver::TokioAsyncResolver;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsResolverOptions {
+  /// The name servers to query, instead of the ones of the system
+  /// configuration.
+  #[serde(default)]
+  name_servers: Vec<NameServerOptions>,
+  /// Addresses that are used for these hosts without querying a name server.
+  #[serde(default)]
+  hosts: HashMap<String, Vec<IpAddr>>,
+  /// The maximum number of cached responses.
+  cache_size: Option<usize>,
+  /// Bounds for the time, in seconds, that responses are cached for,
+  /// overriding the TTL of the records.
+  min_ttl: Option<u64>,
+  max_ttl: Option<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct NameServerOptions {
+  ip_addr: IpAddr,
+  port: Option<u16>,
+  #[serde(default)]
+  protocol: NameServerProtocol,
+  /// The name that the certificate of a DNS-over-HTTPS server is verified
+  /// against.
+  server_name: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum NameServerProtocol {
+  #[default]
+  Udp,
+  Tcp,
+  Https,
+}
+
+impl DnsResolverOptions {
+  fn to_config(
+    &self,
+    root_cert_store: Option<deno_tls::rustls::RootCertStore>,
+  ) -> Result<(ResolverConfig, ResolverOpts), AnyError> {
+    let (config, mut opts) = if self.name_servers.is_empty() {
+      system_conf::read_system_conf()?
+    } else {
+      let mut config = ResolverConfig::new();
+      for name_server in &self.name_servers {
+        config.add_name_server(name_server.to_config()?);
+      }
+      if self
+        .name_servers
+        .iter()
+        .any(|ns| matches!(ns.protocol, NameServerProtocol::Https))
+      {
+        let mut tls_config =
+          deno_tls::create_client_config(root_cert_store, vec![], None, None)?;
+        tls_config.alpn_protocols = vec!["h2".into()];
+        config.set_tls_client_config(Arc::new(tls_config));
+      }
+      (config, ResolverOpts::default())
+    };
+    if let Some(cache_size) = self.cache_size {
+      opts.cache_size = cache_size;
+    }
+    if let Some(min_ttl) = self.min_ttl {
+      opts.positive_min_ttl = Some(Duration::from_secs(min_ttl));
+      opts.negative_min_ttl = Some(Duration::from_secs(min_ttl));
+    }
+    if let Some(max_ttl) = self.max_ttl {
+      opts.positive_max_ttl = Some(Duration::from_secs(max_ttl));
+      opts.negative_max_ttl = Some(Duration::from_secs(max_ttl));
+    }
+    if self.min_ttl.unwrap_or(0) > self.max_ttl.unwrap_or(u64::MAX) {
+      return Err(type_error("minTtl must not be larger than maxTtl"));
+    }
+    Ok((config, opts))
+  }
+}
+
+impl NameServerOptions {
+  fn to_config(&self) -> Result<NameServerConfig, AnyError> {
+    let (protocol, default_port) = match self.protocol {
+      NameServerProtocol::Udp => (Protocol::Udp, 53),
+      NameServerProtocol::Tcp => (Protocol::Tcp, 53),
+      NameServerProtocol::Https => (Protocol::Https, 443),
+    };
+    let tls_dns_name = match self.protocol {
+      NameServerProtocol::Https => Some(
+        self
+          .server_name
+          .clone()
+          .unwrap_or_else(|| self.ip_addr.to_string()),
+      ),
+      _ => None,
+    };
+    if tls_dns_name.is_none() && self.server_name.is_some() {
+      return Err(type_error(
+        "serverName is only supported for DNS-over-HTTPS name servers",
+      ));
+    }
+    Ok(NameServerConfig {
+      socket_addr: SocketAddr::new(
+        self.ip_addr,
+        self.port.unwrap_or(default_port),
+      ),
+      protocol,
+      tls_dns_name,
+      trust_nx_responses: true,
+      tls_config: None,
+      bind_addr: None,
+    })
+  }
+}
+
+pub struct DnsResolver {
+  resolver: TokioAsyncResolver,
+  hosts: HashMap<String, Vec<IpAddr>>,
+}
+
+impl DnsResolver {
+  fn new(
+    config: ResolverConfig,
+    opts: ResolverOpts,
+    hosts: HashMap<String, Vec<IpAddr>>,
+  ) -> Result<Self, AnyError> {
+    Ok(Self {
+      resolver: TokioAsyncResolver::tokio(config, opts)?,
+      hosts: hosts
+        .into_iter()
+        .map(|(host, addrs)| (host.trim_end_matches('.').to_string(), addrs))
+        .collect(),
+    })
+  }
+
+  pub fn resolver(&self) -> &TokioAsyncResolver {
+    &self.resolver
+  }
+
+  /// The addresses configured for the host with the `hosts` option, if any.
+  pub fn host_override(&self, hostname: &str) -> Option<&[IpAddr]> {
+    self
+      .hosts
+      .get(hostname.trim_end_matches('.'))
+      .map(|addrs| addrs.as_slice())
+  }
+
+  /// Looks up the IP addresses of a host.
+  pub async fn lookup_ip(
+    &self,
+    hostname: &str,
+  ) -> Result<Vec<IpAddr>, AnyError> {
+    // Default to localhost if given just the port. Example: ":80"
+    if hostname.is_empty() {
+      return Ok(vec![IpAddr::from([0, 0, 0, 0])]);
+    }
+    // If this looks like an ipv6 IP address. Example: "[2001:db8::1]"
+    // Then we remove the brackets.
+    let hostname = hostname.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(addr) = hostname.parse::<IpAddr>() {
+      return Ok(vec![addr]);
+    }
+    if let Some(addrs) = self.host_override(hostname) {
+      return Ok(addrs.to_vec());
+    }
+    let lookup = self.resolver.lookup_ip(hostname).await?;
+    Ok(lookup.iter().collect())
+  }
+}
+
+pub struct DnsResolverResource(pub Arc<DnsResolver>);
+
+impl Resource for DnsResolverResource {
+  fn name(&self) -> Cow<str> {
+    "dnsResolver".into()
+  }
+}
+
+/// Resolves the addresses to connect to, with the resolver of the given
+/// resource if there is one and with the system resolver otherwise.
+pub async fn resolve_connect_addrs(
+  state: &Rc<RefCell<OpState>>,
+  resolver_rid: Option<ResourceId>,
+  hostname: &str,
+  port: u16,
+) -> Result<Vec<SocketAddr>, AnyError> {
+  let addrs = match resolver_rid {
+    Some(rid) => {
+      let resolver = state
+        .borrow()
+        .resource_table
+        .get::<DnsResolverResource>(rid)?;
+      let addrs = resolver.0.lookup_ip(hostname).await?;
+      addrs
+        .into_iter()
+        .map(|ip| SocketAddr::new(ip, port))
+        .collect()
+    }
+    None => resolve_addr(hostname, port).await?.collect(),
+  };
+  Ok(addrs)
+}
+
+#[op]
+pub fn op_net_create_dns_resolver<NP>(
+  state: &mut OpState,
+  options: DnsResolverOptions,
+) -> Result<ResourceId, AnyError>
+where
+  NP: NetPermissions + 'static,
+{
+  super::check_unstable(state, "Deno.createDnsResolver");
+  let root_cert_store =
+    state.borrow::<DefaultTlsOptions>().root_cert_store()?;
+  let (config, opts) = options.to_config(root_cert_store)?;
+
+  // Checks permission against the name servers which will be actually queried.
+  let permissions = state.borrow_mut::<NP>();
+  for ns in config.name_servers() {
+    let ip = ns.socket_addr.ip().to_string();
+    let port = ns.socket_addr.port();
+    permissions.check_net(&(ip, Some(port)), "Deno.createDnsResolver()")?;
+  }
+
+  let resolver = DnsResolver::new(config, opts, options.hosts)?;
+  let rid = state
+    .resource_table
+    .add(DnsResolverResource(Arc::new(resolver)));
+  Ok(rid)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use deno_core::serde_json;
+  use deno_core::serde_json::json;
+
+  fn options(value: serde_json::Value) -> DnsResolverOptions {
+    serde_json::from_value(value).unwrap()
+  }
+
+  #[test]
+  fn test_name_servers() {
+    let options = options(json!({
+      "nameServers": [
+        { "ipAddr": "1.1.1.1" },
+        { "ipAddr": "8.8.8.8", "port": 5353, "protocol": "tcp" },
+        { "ipAddr": "1.0.0.1", "protocol": "https", "serverName": "cloudflare-dns.com" },
+      ],
+      "cacheSize": 16,
+      "minTtl": 10,
+      "maxTtl": 60,
+    }));
+    let (config, opts) = options.to_config(None).unwrap();
+    let name_servers = config.name_servers();
+    assert_eq!(name_servers.len(), 3);
+    assert_eq!(name_servers[0].socket_addr, "1.1.1.1:53".parse().unwrap());
+    assert_eq!(name_servers[0].protocol, Protocol::Udp);
+    assert_eq!(name_servers[1].socket_addr, "8.8.8.8:5353".parse().unwrap());
+    assert_eq!(name_servers[1].protocol, Protocol::Tcp);
+    assert_eq!(name_servers[2].socket_addr, "1.0.0.1:443".parse().unwrap());
+    assert_eq!(name_servers[2].protocol, Protocol::Https);
+    assert_eq!(
+      name_servers[2].tls_dns_name.as_deref(),
+      Some("cloudflare-dns.com")
+    );
+    assert_eq!(opts.cache_size, 16);
+    assert_eq!(opts.positive_min_ttl, Some(Duration::from_secs(10)));
+    assert_eq!(opts.positive_max_ttl, Some(Duration::from_secs(60)));
+  }
+
+  #[test]
+  fn test_invalid_options() {
+    let server_name = options(json!({
+      "nameServers": [{ "ipAddr": "1.1.1.1", "serverName": "one.one.one.one" }],
+    }));
+    assert!(server_name.to_config(None).is_err());
+    let ttl = options(json!({
+      "nameServers": [{ "ipAddr": "1.1.1.1" }],
+      "minTtl": 60,
+      "maxTtl": 10,
+    }));
+    assert!(ttl.to_config(None).is_err());
+  }
+
+  #[tokio::test]
+  async fn test_lookup_ip() {
+    let options = options(json!({
+      "nameServers": [{ "ipAddr": "127.0.0.1" }],
+      "hosts": { "example.test.": ["127.0.0.2", "::1"] },
+    }));
+    let (config, opts) = options.to_config(None).unwrap();
+    let resolver = DnsResolver::new(config, opts, options.hosts).unwrap();
+    assert_eq!(
+      resolver.lookup_ip("example.test").await.unwrap(),
+      vec![
+        "127.0.0.2".parse::<IpAddr>().unwrap(),
+        "::1".parse::<IpAddr>().unwrap()
+      ]
+    );
+    assert_eq!(
+      resolver.lookup_ip("[::2]").await.unwrap(),
+      vec!["::2".parse::<IpAddr>().unwrap()]
+    );
+  }
+}
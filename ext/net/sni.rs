@@ -0,0 +1,418 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Selects the certificate a TLS listener presents based on the server name (SNI) sent by the
+//! client, so that a single listener can terminate TLS for many domains. The certificates are
+//! either looked up in a map of hostnames, or picked for each connection by a callback in JS.
+
+use crate::ops_tls::ImplementReadTrait;
+use crate::ops_tls::TlsStream;
+use crate::proxy_protocol::TcpAcceptor;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::futures::stream::FuturesUnordered;
+use deno_core::futures::StreamExt;
+use deno_core::op;
+use deno_core::AsyncRefCell;
+use deno_core::CancelHandle;
+use deno_core::CancelTryFuture;
+use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
+use deno_core::ResourceId;
+use deno_tls::load_certs;
+use deno_tls::load_private_keys;
+use deno_tls::rustls::server::Accepted;
+use deno_tls::rustls::server::Acceptor;
+use deno_tls::rustls::server::ClientHello;
+use deno_tls::rustls::server::ResolvesServerCert;
+use deno_tls::rustls::sign::any_supported_type;
+use deno_tls::rustls::sign::CertifiedKey;
+use deno_tls::rustls::Certificate;
+use deno_tls::rustls::PrivateKey;
+use deno_tls::rustls::ServerConfig;
+use deno_tls::rustls::ServerConnection;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+use std::io::BufReader;
+use std::net::SocketAddr;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::task::spawn_local;
+use tokio::task::JoinHandle;
+
+/// How long a client of a listener with an SNI callback has to send its ClientHello before it is
+/// dropped.
+const CLIENT_HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How many connections of a listener with an SNI callback may be waiting for their ClientHello
+/// or their certificate at once. Beyond that, no connection is accepted until one of them is
+/// done, so the others wait in the backlog of the listener.
+const MAX_PENDING_HELLOS: usize = 1024;
+
+/// A certificate chain and private key in PEM format.
+#[derive(Deserialize)]
+pub struct SniCert {
+  cert: String,
+  key: String,
+}
+
+impl SniCert {
+  fn certified_key(self) -> Result<Arc<CertifiedKey>, AnyError> {
+    let cert_chain = load_certs(&mut BufReader::new(self.cert.as_bytes()))?;
+    let key = load_private_keys(self.key.as_bytes())?.remove(0);
+    certified_key(cert_chain, &key)
+  }
+}
+
+/// Resolves the certificate for a connection from a map of hostnames, falling back to the
+/// listener's default certificate when the client sends no server name or one that isn't in
+/// the map. Hostnames may start with a `*.` wildcard label, which matches exactly one label.
+pub struct SniResolver {
+  default: Arc<CertifiedKey>,
+  certs: HashMap<String, Arc<CertifiedKey>>,
+}
+
+impl SniResolver {
+  pub fn new(
+    default: Arc<CertifiedKey>,
+    certs: HashMap<String, SniCert>,
+  ) -> Result<Self, AnyError> {
+    let certs = certs
+      .into_iter()
+      .map(|(hostname, cert)| {
+        let certified_key = cert
+          .certified_key()
+          .map_err(|e| generic_error(format!("{hostname}: {e}")))?;
+        Ok((hostname.to_ascii_lowercase(), certified_key))
+      })
+      .collect::<Result<_, AnyError>>()?;
+    Ok(Self { default, certs })
+  }
+
+  fn lookup(&self, server_name: Option<&str>) -> Arc<CertifiedKey> {
+    let Some(server_name) = server_name else {
+      return self.default.clone();
+    };
+    let server_name = server_name.to_ascii_lowercase();
+    if let Some(certified_key) = self.certs.get(&server_name) {
+      return certified_key.clone();
+    }
+    if let Some((_, parent)) = server_name.split_once('.') {
+      if let Some(certified_key) = self.certs.get(&format!("*.{parent}")) {
+        return certified_key.clone();
+      }
+    }
+    self.default.clone()
+  }
+}
+
+impl ResolvesServerCert for SniResolver {
+  fn resolve(&self, client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+    Some(self.lookup(client_hello.server_name()))
+  }
+}
+
+pub fn certified_key(
+  cert_chain: Vec<Certificate>,
+  key: &PrivateKey,
+) -> Result<Arc<CertifiedKey>, AnyError> {
+  let key = any_supported_type(key)
+    .map_err(|_| generic_error("invalid key or certificate"))?;
+  Ok(Arc::new(CertifiedKey::new(cert_chain, key)))
+}
+
+/// Presents the certificate that the SNI callback picked for a connection.
+struct FixedCertResolver(Arc<CertifiedKey>);
+
+impl ResolvesServerCert for FixedCertResolver {
+  fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+    Some(self.0.clone())
+  }
+}
+
+type SniResponse = Result<Option<Arc<CertifiedKey>>, AnyError>;
+
+/// The request of a connection for the certificate of the server name it sent. The callback may
+/// answer with no certificate, for the certificates of the listener.
+struct SniRequest {
+  server_name: String,
+  tx: oneshot::Sender<SniResponse>,
+}
+
+/// The requests of the connections of a listener to its SNI callback, which takes them with
+/// `op_tls_sni_next` and answers them with `op_tls_sni_resolve`. It is a resource of its own, as
+/// the listener may be taken out of the resource table, like by `Deno.serve`.
+pub struct SniCallbackResource {
+  rx: AsyncRefCell<mpsc::UnboundedReceiver<SniRequest>>,
+  pending: RefCell<HashMap<u32, oneshot::Sender<SniResponse>>>,
+  next_id: Cell<u32>,
+  cancel_handle: CancelHandle,
+}
+
+impl Resource for SniCallbackResource {
+  fn name(&self) -> Cow<str> {
+    "tlsSniCallback".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel_handle.cancel();
+  }
+}
+
+/// Takes the next request of a connection for its certificate, as its id and the server name it
+/// sent, or `None` once the listener has been closed.
+#[op]
+pub async fn op_tls_sni_next(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+) -> Result<Option<(u32, String)>, AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<SniCallbackResource>(rid)?;
+  let cancel_handle = RcRef::map(&resource, |r| &r.cancel_handle);
+  let mut rx = RcRef::map(&resource, |r| &r.rx).borrow_mut().await;
+  let Ok(Some(request)) = rx.recv().try_or_cancel(cancel_handle).await else {
+    return Ok(None);
+  };
+  let id = resource.next_id.get();
+  resource.next_id.set(id.wrapping_add(1));
+  resource.pending.borrow_mut().insert(id, request.tx);
+  Ok(Some((id, request.server_name)))
+}
+
+/// Answers the request of a connection with the certificate that the callback returned, if any,
+/// or with the error that it threw, which fails the handshake.
+#[op]
+pub fn op_tls_sni_resolve(
+  state: &mut OpState,
+  rid: ResourceId,
+  id: u32,
+  cert: Option<SniCert>,
+  error: Option<String>,
+) -> Result<(), AnyError> {
+  // The listener may have been closed in the meantime
+  let Ok(resource) = state.resource_table.get::<SniCallbackResource>(rid)
+  else {
+    return Ok(());
+  };
+  let Some(tx) = resource.pending.borrow_mut().remove(&id) else {
+    return Ok(());
+  };
+  let response = match (error, cert) {
+    (Some(error), _) => Err(generic_error(error)),
+    (None, Some(cert)) => cert.certified_key().map(Some),
+    (None, None) => Ok(None),
+  };
+  let _ = tx.send(response);
+  Ok(())
+}
+
+type PendingConnection =
+  JoinHandle<Result<(TcpStream, ServerConnection, SocketAddr), AnyError>>;
+
+/// Accepts the TLS connections of a listener. With an SNI callback, the ClientHello of each
+/// connection is read in a task of its own, which then waits for the callback to pick the
+/// certificate for the server name, so that a slow client or callback doesn't hold up the other
+/// connections. Clients that don't send a server name get the certificates of the listener.
+pub struct TlsAcceptor {
+  tcp: TcpAcceptor,
+  config: Arc<ServerConfig>,
+  sni_callback: Option<mpsc::UnboundedSender<SniRequest>>,
+  pending: FuturesUnordered<PendingConnection>,
+}
+
+impl TlsAcceptor {
+  pub fn new(tcp: TcpAcceptor, config: Arc<ServerConfig>) -> Self {
+    Self {
+      tcp,
+      config,
+      sni_callback: None,
+      pending: FuturesUnordered::new(),
+    }
+  }
+
+  /// Returns the acceptor along with the resource through which the SNI callback picks the
+  /// certificates of the connections.
+  pub fn with_sni_callback(
+    tcp: TcpAcceptor,
+    config: Arc<ServerConfig>,
+  ) -> (Self, SniCallbackResource) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let acceptor = Self {
+      sni_callback: Some(tx),
+      ..Self::new(tcp, config)
+    };
+    let resource = SniCallbackResource {
+      rx: AsyncRefCell::new(rx),
+      pending: Default::default(),
+      next_id: Default::default(),
+      cancel_handle: Default::default(),
+    };
+    (acceptor, resource)
+  }
+
+  pub fn config(&self) -> &Arc<ServerConfig> {
+    &self.config
+  }
+
+  pub fn local_addr(&self) -> io::Result<SocketAddr> {
+    self.tcp.local_addr()
+  }
+
+  /// Accepts a connection, returning it along with the address of the client.
+  ///
+  /// This is cancel safe: connections that are still waiting for their certificate are kept for
+  /// the next call.
+  pub async fn accept(&mut self) -> io::Result<(TlsStream, SocketAddr)> {
+    let Some(sni_callback) = &self.sni_callback else {
+      let (tcp, addr) = self.tcp.accept().await?;
+      return Ok((TlsStream::new_server_side(tcp, self.config.clone()), addr));
+    };
+    loop {
+      tokio::select! {
+        res = self.tcp.accept(), if self.pending.len() < MAX_PENDING_HELLOS => {
+          let (tcp, addr) = res?;
+          self.pending.push(spawn_local(accept_with_sni_callback(
+            tcp,
+            addr,
+            self.config.clone(),
+            sni_callback.clone(),
+          )));
+        }
+        Some(res) = self.pending.next(), if !self.pending.is_empty() => {
+          if let Ok(Ok((tcp, connection, addr))) = res {
+            return Ok((TlsStream::new_server_side_from(tcp, connection), addr));
+          }
+        }
+      }
+    }
+  }
+}
+
+impl Drop for TlsAcceptor {
+  fn drop(&mut self) {
+    for handle in self.pending.iter() {
+      handle.abort();
+    }
+  }
+}
+
+async fn accept_with_sni_callback(
+  mut tcp: TcpStream,
+  addr: SocketAddr,
+  config: Arc<ServerConfig>,
+  sni_callback: mpsc::UnboundedSender<SniRequest>,
+) -> Result<(TcpStream, ServerConnection, SocketAddr), AnyError> {
+  let accepted =
+    tokio::time::timeout(CLIENT_HELLO_TIMEOUT, read_client_hello(&mut tcp))
+      .await
+      .map_err(|_| generic_error("timed out reading the ClientHello"))??;
+  let server_name = accepted.client_hello().server_name().map(str::to_string);
+  let config = match server_name {
+    Some(server_name) => {
+      let (tx, rx) = oneshot::channel();
+      sni_callback
+        .send(SniRequest { server_name, tx })
+        .map_err(|_| generic_error("The listener has been closed"))?;
+      let certified_key = rx
+        .await
+        .map_err(|_| generic_error("The listener has been closed"))??;
+      match certified_key {
+        Some(certified_key) => {
+          let mut config = (*config).clone();
+          config.cert_resolver = Arc::new(FixedCertResolver(certified_key));
+          Arc::new(config)
+        }
+        None => config,
+      }
+    }
+    None => config,
+  };
+  let connection = accepted.into_connection(config)?;
+  Ok((tcp, connection, addr))
+}
+
+async fn read_client_hello(tcp: &mut TcpStream) -> Result<Accepted, AnyError> {
+  let mut acceptor = Acceptor::new()?;
+  loop {
+    tcp.readable().await?;
+    match acceptor.read_tls(&mut ImplementReadTrait(tcp)) {
+      Ok(0) => {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into())
+      }
+      Ok(_) => {}
+      Err(err) if err.kind() == io::ErrorKind::WouldBlock => continue,
+      Err(err) => return Err(err.into()),
+    }
+    if let Some(accepted) = acceptor.accept()? {
+      return Ok(accepted);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const CERT: &str = include_str!("../../cli/tests/testdata/tls/localhost.crt");
+  const KEY: &str = include_str!("../../cli/tests/testdata/tls/localhost.key");
+
+  fn default_key() -> Arc<CertifiedKey> {
+    let cert_chain = load_certs(&mut BufReader::new(CERT.as_bytes())).unwrap();
+    let key = load_private_keys(KEY.as_bytes()).unwrap().remove(0);
+    certified_key(cert_chain, &key).unwrap()
+  }
+
+  fn sni_cert(key: &str) -> SniCert {
+    SniCert {
+      cert: CERT.to_string(),
+      key: key.to_string(),
+    }
+  }
+
+  #[test]
+  fn test_lookup() {
+    let default = default_key();
+    let resolver = SniResolver::new(
+      default.clone(),
+      HashMap::from([
+        ("Example.com".to_string(), sni_cert(KEY)),
+        ("*.example.com".to_string(), sni_cert(KEY)),
+      ]),
+    )
+    .unwrap();
+    let exact = &resolver.certs["example.com"];
+    let wildcard = &resolver.certs["*.example.com"];
+
+    assert!(Arc::ptr_eq(&resolver.lookup(None), &default));
+    assert!(Arc::ptr_eq(&resolver.lookup(Some("EXAMPLE.com")), exact));
+    assert!(Arc::ptr_eq(
+      &resolver.lookup(Some("www.example.com")),
+      wildcard
+    ));
+    // Wildcards only match a single label
+    assert!(Arc::ptr_eq(
+      &resolver.lookup(Some("a.b.example.com")),
+      &default
+    ));
+    assert!(Arc::ptr_eq(&resolver.lookup(Some("deno.land")), &default));
+  }
+
+  #[test]
+  fn test_invalid_key() {
+    let res = SniResolver::new(
+      default_key(),
+      HashMap::from([("example.com".to_string(), sni_cert("not a key"))]),
+    );
+    assert!(res.is_err());
+  }
+}
@@ -286,7 +286,7 @@ where
   ensure_read_permission::<P>(state, &path)?;
   let fs = state.borrow::<FileSystemRc>();
   let canonicalized_path =
-    deno_core::strip_unc_prefix(fs.realpath_sync(&path)?);
+    deno_core::strip_unc_prefix(fs.realpath_sync_cached(&path)?);
   Ok(canonicalized_path.to_string_lossy().to_string())
 }
 
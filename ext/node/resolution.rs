@@ -1082,7 +1082,7 @@ impl NodeResolver {
   ) -> Result<PathBuf, AnyError> {
     let file_path = url.to_file_path().unwrap();
     let current_dir = deno_core::strip_unc_prefix(
-      self.fs.realpath_sync(file_path.parent().unwrap())?,
+      self.fs.realpath_sync_cached(file_path.parent().unwrap())?,
     );
     let mut current_dir = current_dir.as_path();
     let package_json_path = current_dir.join("package.json");
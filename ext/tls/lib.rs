@@ -6,6 +6,8 @@ pub use rustls_pemfile;
 pub use webpki;
 pub use webpki_roots;
 
+pub mod pkcs12;
+
 use deno_core::anyhow::anyhow;
 use deno_core::error::custom_error;
 use deno_core::error::AnyError;
@@ -31,6 +33,7 @@ use std::collections::HashMap;
 use std::io::BufRead;
 use std::io::BufReader;
 use std::io::Cursor;
+use std::net::IpAddr;
 use std::sync::Arc;
 use std::time::SystemTime;
 
@@ -130,21 +133,89 @@ impl ServerCertVerifier for NoCertificateVerification {
   }
 }
 
-#[derive(Deserialize, Default, Debug, Clone)]
+#[derive(Deserialize, Default, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 #[serde(default)]
 pub struct Proxy {
   pub url: String,
   pub basic_auth: Option<BasicAuth>,
+  /// Hosts, IP addresses and networks that are connected to directly, with
+  /// the same syntax as the entries of the `NO_PROXY` environment variable.
+  pub no_proxy: Vec<String>,
 }
 
-#[derive(Deserialize, Default, Debug, Clone)]
+#[derive(Deserialize, Default, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(default)]
 pub struct BasicAuth {
   pub username: String,
   pub password: String,
 }
 
+impl Proxy {
+  /// Whether connections to `host` bypass the proxy because of `no_proxy`.
+  pub fn is_excluded(&self, host: &str) -> bool {
+    self
+      .no_proxy
+      .iter()
+      .any(|entry| no_proxy_entry_matches(entry, host))
+  }
+}
+
+/// Whether `host` matches an entry of a comma separated `NO_PROXY` list. `*`
+/// matches every host, an IP address or a network like `10.0.0.0/8` matches
+/// the IP addresses it contains, and any other entry matches the domain
+/// itself and all of its subdomains, with or without a leading dot.
+pub fn no_proxy_matches(no_proxy: &str, host: &str) -> bool {
+  no_proxy
+    .split(',')
+    .any(|entry| no_proxy_entry_matches(entry, host))
+}
+
+fn no_proxy_entry_matches(entry: &str, host: &str) -> bool {
+  let entry = entry.trim();
+  let host = host.trim_start_matches('[').trim_end_matches(']');
+  if entry.is_empty() {
+    return false;
+  }
+  if entry == "*" {
+    return true;
+  }
+  if let Ok(ip) = host.parse::<IpAddr>() {
+    let entry = entry.trim_start_matches('[').trim_end_matches(']');
+    return match entry.split_once('/') {
+      Some((network, prefix_len)) => {
+        match (network.parse::<IpAddr>(), prefix_len.parse::<u32>()) {
+          (Ok(network), Ok(prefix_len)) => {
+            network_contains(network, prefix_len, ip)
+          }
+          _ => false,
+        }
+      }
+      None => entry.parse::<IpAddr>().map_or(false, |entry| entry == ip),
+    };
+  }
+  let domain = entry.trim_start_matches('.');
+  domain.eq_ignore_ascii_case(host)
+    || (host.len() > domain.len()
+      && host.is_char_boundary(host.len() - domain.len())
+      && host[host.len() - domain.len()..].eq_ignore_ascii_case(domain)
+      && host.as_bytes()[host.len() - domain.len() - 1] == b'.')
+}
+
+fn network_contains(network: IpAddr, prefix_len: u32, ip: IpAddr) -> bool {
+  match (network, ip) {
+    (IpAddr::V4(network), IpAddr::V4(ip)) if prefix_len <= 32 => {
+      let mask = u32::MAX.checked_shl(32 - prefix_len).unwrap_or(0);
+      u32::from(network) & mask == u32::from(ip) & mask
+    }
+    (IpAddr::V6(network), IpAddr::V6(ip)) if prefix_len <= 128 => {
+      let mask = u128::MAX.checked_shl(128 - prefix_len).unwrap_or(0);
+      u128::from(network) & mask == u128::from(ip) & mask
+    }
+    _ => false,
+  }
+}
+
 #[derive(Default)]
 struct ClientSessionMemoryCache(Mutex<HashMap<Vec<u8>, Vec<u8>>>);
 
@@ -313,3 +384,30 @@ pub fn load_private_keys(bytes: &[u8]) -> Result<Vec<PrivateKey>, AnyError> {
 
   Ok(keys)
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_no_proxy_matches() {
+    assert!(no_proxy_matches("*", "deno.land"));
+    assert!(no_proxy_matches("Deno.Land", "deno.land"));
+    assert!(no_proxy_matches("localhost, .deno.land", "api.deno.land"));
+    assert!(no_proxy_matches("deno.land", "api.deno.land"));
+    assert!(no_proxy_matches(".deno.land", "deno.land"));
+    assert!(!no_proxy_matches("land", "deno.lands"));
+    assert!(!no_proxy_matches("", "deno.land"));
+    assert!(no_proxy_matches("::1", "[::1]"));
+    assert!(no_proxy_matches("[::1]", "::1"));
+    assert!(no_proxy_matches("127.0.0.1", "127.0.0.1"));
+    assert!(!no_proxy_matches("127.0.0.1", "127.0.0.2"));
+    assert!(no_proxy_matches("10.0.0.0/8", "10.1.2.3"));
+    assert!(!no_proxy_matches("10.0.0.0/8", "11.1.2.3"));
+    assert!(no_proxy_matches("0.0.0.0/0", "11.1.2.3"));
+    assert!(no_proxy_matches("fd00::/8", "[fd12::1]"));
+    assert!(!no_proxy_matches("fd00::/8", "fe80::1"));
+    assert!(!no_proxy_matches("10.0.0.0/8", "::1"));
+    assert!(!no_proxy_matches("10.0.0.0/33", "10.1.2.3"));
+  }
+}
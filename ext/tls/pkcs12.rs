@@ -0,0 +1,555 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Decoding of PKCS#12 (`.p12` or `.pfx`) bundles, which hold a certificate
+//! chain together with its private key. Bundles encrypted with PBES2, the
+//! default of OpenSSL 3 and of current versions of most other tools, are
+//! supported, as are bundles encrypted with the legacy RC2 and 3DES ciphers
+//! of PKCS#12 itself, which older tools and Windows still produce. RC4 isn't.
+
+use aes::cipher::block_padding::Pkcs7;
+use aes::cipher::BlockDecryptMut;
+use aes::cipher::KeyIvInit;
+use deno_core::error::custom_error;
+use deno_core::error::AnyError;
+use hmac::Mac;
+use hmac::SimpleHmac;
+use sha1::Sha1;
+use sha2::digest::core_api::BlockSizeUser;
+use sha2::Digest;
+use sha2::Sha256;
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_OID: u8 = 0x06;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_CONTEXT_0: u8 = 0xa0;
+const TAG_IMPLICIT_0: u8 = 0x80;
+
+// The DER encoded contents of the object identifiers that are used.
+const OID_DATA: &[u8] = &[42, 134, 72, 134, 247, 13, 1, 7, 1];
+const OID_ENCRYPTED_DATA: &[u8] = &[42, 134, 72, 134, 247, 13, 1, 7, 6];
+const OID_KEY_BAG: &[u8] = &[42, 134, 72, 134, 247, 13, 1, 12, 10, 1, 1];
+const OID_SHROUDED_KEY_BAG: &[u8] =
+  &[42, 134, 72, 134, 247, 13, 1, 12, 10, 1, 2];
+const OID_CERT_BAG: &[u8] = &[42, 134, 72, 134, 247, 13, 1, 12, 10, 1, 3];
+const OID_X509_CERTIFICATE: &[u8] = &[42, 134, 72, 134, 247, 13, 1, 9, 22, 1];
+const OID_LOCAL_KEY_ID: &[u8] = &[42, 134, 72, 134, 247, 13, 1, 9, 21];
+const OID_PBE_SHA1_3DES: &[u8] = &[42, 134, 72, 134, 247, 13, 1, 12, 1, 3];
+const OID_PBE_SHA1_2DES: &[u8] = &[42, 134, 72, 134, 247, 13, 1, 12, 1, 4];
+const OID_PBE_SHA1_RC2_128: &[u8] = &[42, 134, 72, 134, 247, 13, 1, 12, 1, 5];
+const OID_PBE_SHA1_RC2_40: &[u8] = &[42, 134, 72, 134, 247, 13, 1, 12, 1, 6];
+const OID_PBES2: &[u8] = &[42, 134, 72, 134, 247, 13, 1, 5, 13];
+const OID_PBKDF2: &[u8] = &[42, 134, 72, 134, 247, 13, 1, 5, 12];
+const OID_HMAC_SHA1: &[u8] = &[42, 134, 72, 134, 247, 13, 2, 7];
+const OID_HMAC_SHA256: &[u8] = &[42, 134, 72, 134, 247, 13, 2, 9];
+const OID_AES128_CBC: &[u8] = &[96, 134, 72, 1, 101, 3, 4, 1, 2];
+const OID_AES192_CBC: &[u8] = &[96, 134, 72, 1, 101, 3, 4, 1, 22];
+const OID_AES256_CBC: &[u8] = &[96, 134, 72, 1, 101, 3, 4, 1, 42];
+const OID_SHA1: &[u8] = &[43, 14, 3, 2, 26];
+const OID_SHA256: &[u8] = &[96, 134, 72, 1, 101, 3, 4, 2, 1];
+
+fn invalid_err() -> AnyError {
+  custom_error("InvalidData", "Unable to decode PKCS#12 bundle")
+}
+
+fn unsupported_err(what: &str) -> AnyError {
+  custom_error(
+    "InvalidData",
+    format!("Unsupported {what} in PKCS#12 bundle, only PBES2 encryption with AES and the RC2 and 3DES encryption of PKCS#12 are supported"),
+  )
+}
+
+fn password_err() -> AnyError {
+  custom_error(
+    "InvalidData",
+    "Invalid password for PKCS#12 bundle, or the bundle is corrupted",
+  )
+}
+
+/// Reads DER encoded values. Indefinite lengths, which some tools emit in
+/// BER encoded bundles, are rejected.
+struct DerReader<'a>(&'a [u8]);
+
+impl<'a> DerReader<'a> {
+  fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  fn peek_tag(&self) -> Option<u8> {
+    self.0.first().copied()
+  }
+
+  /// Reads a value, returning its tag, its contents and its whole encoding.
+  fn read_any(&mut self) -> Result<(u8, &'a [u8], &'a [u8]), AnyError> {
+    let input = self.0;
+    let (&tag, rest) = input.split_first().ok_or_else(invalid_err)?;
+    let (&first, mut rest) = rest.split_first().ok_or_else(invalid_err)?;
+    let len = if first < 0x80 {
+      first as usize
+    } else {
+      let n = (first & 0x7f) as usize;
+      if n == 0 || n > 4 || rest.len() < n {
+        return Err(invalid_err());
+      }
+      let len = rest[..n]
+        .iter()
+        .fold(0usize, |len, byte| (len << 8) | *byte as usize);
+      rest = &rest[n..];
+      len
+    };
+    if rest.len() < len {
+      return Err(invalid_err());
+    }
+    let header_len = input.len() - rest.len();
+    self.0 = &rest[len..];
+    Ok((tag, &rest[..len], &input[..header_len + len]))
+  }
+
+  fn read(&mut self, expected_tag: u8) -> Result<&'a [u8], AnyError> {
+    match self.read_any()? {
+      (tag, contents, _) if tag == expected_tag => Ok(contents),
+      _ => Err(invalid_err()),
+    }
+  }
+
+  fn sequence(&mut self) -> Result<DerReader<'a>, AnyError> {
+    Ok(DerReader(self.read(TAG_SEQUENCE)?))
+  }
+
+  fn explicit(&mut self) -> Result<DerReader<'a>, AnyError> {
+    Ok(DerReader(self.read(TAG_CONTEXT_0)?))
+  }
+
+  fn oid(&mut self) -> Result<&'a [u8], AnyError> {
+    self.read(TAG_OID)
+  }
+
+  fn octet_string(&mut self) -> Result<&'a [u8], AnyError> {
+    self.read(TAG_OCTET_STRING)
+  }
+
+  fn integer(&mut self) -> Result<u32, AnyError> {
+    let contents = self.read(TAG_INTEGER)?;
+    let first_significant = contents
+      .iter()
+      .position(|byte| *byte != 0)
+      .unwrap_or(contents.len());
+    let contents = &contents[first_significant..];
+    if contents.len() > 4 {
+      return Err(invalid_err());
+    }
+    Ok(
+      contents
+        .iter()
+        .fold(0u32, |value, byte| (value << 8) | *byte as u32),
+    )
+  }
+}
+
+#[derive(Default)]
+struct Bags {
+  /// PKCS#8 private keys, with their local key IDs.
+  keys: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+  /// DER encoded certificates, with their local key IDs.
+  certs: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+/// Decodes a PKCS#12 bundle into a PEM encoded certificate chain and private
+/// key. The chain starts with the certificate of the key.
+pub fn load_pkcs12(
+  data: &[u8],
+  password: &str,
+) -> Result<(String, String), AnyError> {
+  let mut pfx = DerReader(data).sequence()?;
+  if pfx.integer()? != 3 {
+    return Err(invalid_err());
+  }
+  let mut auth_safe = pfx.sequence()?;
+  if auth_safe.oid()? != OID_DATA {
+    return Err(unsupported_err("integrity mode"));
+  }
+  let auth_safe = auth_safe.explicit()?.octet_string()?;
+  if !pfx.is_empty() {
+    verify_mac(pfx.sequence()?, auth_safe, password)?;
+  }
+
+  let mut bags = Bags::default();
+  let mut content_infos = DerReader(auth_safe).sequence()?;
+  while !content_infos.is_empty() {
+    let mut content_info = content_infos.sequence()?;
+    let content_type = content_info.oid()?;
+    let mut content = content_info.explicit()?;
+    let safe_contents = match content_type {
+      OID_DATA => content.octet_string()?.to_vec(),
+      OID_ENCRYPTED_DATA => {
+        let mut encrypted_data = content.sequence()?;
+        encrypted_data.integer()?;
+        let mut encrypted_content_info = encrypted_data.sequence()?;
+        encrypted_content_info.oid()?;
+        let algorithm = encrypted_content_info.sequence()?;
+        let encrypted_content = encrypted_content_info.read(TAG_IMPLICIT_0)?;
+        decrypt(algorithm, encrypted_content, password)?
+      }
+      _ => return Err(unsupported_err("content type")),
+    };
+    read_safe_contents(&safe_contents, password, &mut bags)?;
+  }
+
+  let Some((key, key_id)) = bags.keys.into_iter().next() else {
+    return Err(custom_error("InvalidData", "No keys found in PKCS#12 bundle"));
+  };
+  let mut certs = bags.certs;
+  if certs.is_empty() {
+    return Err(custom_error(
+      "InvalidData",
+      "No certificates found in PKCS#12 bundle",
+    ));
+  }
+  // Stable, so that the order of the rest of the chain is kept.
+  certs.sort_by_key(|(_, id)| key_id.is_none() || *id != key_id);
+
+  let cert_chain = certs
+    .iter()
+    .map(|(cert, _)| pem("CERTIFICATE", cert))
+    .collect::<String>();
+  Ok((cert_chain, pem("PRIVATE KEY", &key)))
+}
+
+fn read_safe_contents(
+  data: &[u8],
+  password: &str,
+  bags: &mut Bags,
+) -> Result<(), AnyError> {
+  let mut safe_bags = DerReader(data).sequence()?;
+  while !safe_bags.is_empty() {
+    let mut bag = safe_bags.sequence()?;
+    let bag_id = bag.oid()?;
+    let mut value = bag.explicit()?;
+    let local_key_id = if bag.peek_tag() == Some(TAG_SET) {
+      local_key_id(DerReader(bag.read(TAG_SET)?))?
+    } else {
+      None
+    };
+    match bag_id {
+      OID_KEY_BAG => {
+        let (_, _, key) = value.read_any()?;
+        bags.keys.push((key.to_vec(), local_key_id));
+      }
+      OID_SHROUDED_KEY_BAG => {
+        let mut encrypted_key = value.sequence()?;
+        let algorithm = encrypted_key.sequence()?;
+        let key = decrypt(algorithm, encrypted_key.octet_string()?, password)?;
+        bags.keys.push((key, local_key_id));
+      }
+      OID_CERT_BAG => {
+        let mut cert_bag = value.sequence()?;
+        if cert_bag.oid()? == OID_X509_CERTIFICATE {
+          let cert = cert_bag.explicit()?.octet_string()?;
+          bags.certs.push((cert.to_vec(), local_key_id));
+        }
+      }
+      // CRLs, secrets and nested safe contents aren't needed.
+      _ => {}
+    }
+  }
+  Ok(())
+}
+
+fn local_key_id(
+  mut attributes: DerReader,
+) -> Result<Option<Vec<u8>>, AnyError> {
+  while !attributes.is_empty() {
+    let mut attribute = attributes.sequence()?;
+    if attribute.oid()? == OID_LOCAL_KEY_ID {
+      let mut values = DerReader(attribute.read(TAG_SET)?);
+      return Ok(Some(values.octet_string()?.to_vec()));
+    }
+  }
+  Ok(None)
+}
+
+fn decrypt(
+  mut algorithm: DerReader,
+  data: &[u8],
+  password: &str,
+) -> Result<Vec<u8>, AnyError> {
+  let scheme = algorithm.oid()?;
+  let params = algorithm.sequence()?;
+  match scheme {
+    OID_PBES2 => decrypt_pbes2(params, data, password),
+    OID_PBE_SHA1_3DES | OID_PBE_SHA1_2DES | OID_PBE_SHA1_RC2_128
+    | OID_PBE_SHA1_RC2_40 => decrypt_legacy(scheme, params, data, password),
+    _ => Err(unsupported_err("encryption")),
+  }
+}
+
+/// Decrypts data encrypted with PBES2, which is defined in RFC 8018.
+fn decrypt_pbes2(
+  mut params: DerReader,
+  data: &[u8],
+  password: &str,
+) -> Result<Vec<u8>, AnyError> {
+  let mut kdf = params.sequence()?;
+  if kdf.oid()? != OID_PBKDF2 {
+    return Err(unsupported_err("key derivation"));
+  }
+  let mut kdf_params = kdf.sequence()?;
+  let salt = kdf_params.octet_string()?;
+  let iterations = kdf_params.integer()?;
+  if kdf_params.peek_tag() == Some(TAG_INTEGER) {
+    // The key length, which is implied by the cipher.
+    kdf_params.integer()?;
+  }
+  let prf = if kdf_params.is_empty() {
+    OID_HMAC_SHA1
+  } else {
+    kdf_params.sequence()?.oid()?
+  };
+
+  let mut scheme = params.sequence()?;
+  let cipher = scheme.oid()?;
+  let iv = scheme.octet_string()?;
+  let mut key = vec![
+    0;
+    match cipher {
+      OID_AES128_CBC => 16,
+      OID_AES192_CBC => 24,
+      OID_AES256_CBC => 32,
+      _ => return Err(unsupported_err("cipher")),
+    }
+  ];
+  match prf {
+    OID_HMAC_SHA1 => pbkdf2::pbkdf2_hmac::<Sha1>(
+      password.as_bytes(),
+      salt,
+      iterations,
+      &mut key,
+    ),
+    OID_HMAC_SHA256 => pbkdf2::pbkdf2_hmac::<Sha256>(
+      password.as_bytes(),
+      salt,
+      iterations,
+      &mut key,
+    ),
+    _ => return Err(unsupported_err("key derivation")),
+  }
+
+  let plaintext = match key.len() {
+    16 => cbc::Decryptor::<aes::Aes128>::new_from_slices(&key, iv)
+      .map_err(|_| invalid_err())?
+      .decrypt_padded_vec_mut::<Pkcs7>(data),
+    24 => cbc::Decryptor::<aes::Aes192>::new_from_slices(&key, iv)
+      .map_err(|_| invalid_err())?
+      .decrypt_padded_vec_mut::<Pkcs7>(data),
+    _ => cbc::Decryptor::<aes::Aes256>::new_from_slices(&key, iv)
+      .map_err(|_| invalid_err())?
+      .decrypt_padded_vec_mut::<Pkcs7>(data),
+  };
+  plaintext.map_err(|_| password_err())
+}
+
+/// Decrypts data encrypted with the password based encryption of RFC 7292,
+/// appendix C, whose key and IV are derived as in appendix B.2.
+fn decrypt_legacy(
+  scheme: &[u8],
+  mut params: DerReader,
+  data: &[u8],
+  password: &str,
+) -> Result<Vec<u8>, AnyError> {
+  let salt = params.octet_string()?;
+  let iterations = params.integer()?;
+  let password = bmp_password(password);
+  let derive =
+    |id, len| pkcs12_kdf::<Sha1>(id, &password, salt, iterations, len);
+  let key_len = match scheme {
+    OID_PBE_SHA1_3DES => 24,
+    OID_PBE_SHA1_2DES | OID_PBE_SHA1_RC2_128 => 16,
+    _ => 5,
+  };
+  let key = derive(1, key_len);
+  let iv = derive(2, 8);
+
+  // RC2 takes keys of any length, and its effective key length defaults to
+  // the length of the key, as these schemes require.
+  let plaintext = match scheme {
+    OID_PBE_SHA1_3DES => {
+      cbc::Decryptor::<des::TdesEde3>::new_from_slices(&key, &iv)
+        .map_err(|_| invalid_err())?
+        .decrypt_padded_vec_mut::<Pkcs7>(data)
+    }
+    OID_PBE_SHA1_2DES => {
+      cbc::Decryptor::<des::TdesEde2>::new_from_slices(&key, &iv)
+        .map_err(|_| invalid_err())?
+        .decrypt_padded_vec_mut::<Pkcs7>(data)
+    }
+    _ => cbc::Decryptor::<rc2::Rc2>::new_from_slices(&key, &iv)
+      .map_err(|_| invalid_err())?
+      .decrypt_padded_vec_mut::<Pkcs7>(data),
+  };
+  plaintext.map_err(|_| password_err())
+}
+
+/// Encodes a password as a null terminated BMPString, which is what the key
+/// derivation of RFC 7292 takes.
+fn bmp_password(password: &str) -> Vec<u8> {
+  password
+    .encode_utf16()
+    .chain([0])
+    .flat_map(|c| c.to_be_bytes())
+    .collect()
+}
+
+fn verify_mac(
+  mut mac_data: DerReader,
+  data: &[u8],
+  password: &str,
+) -> Result<(), AnyError> {
+  let mut digest_info = mac_data.sequence()?;
+  let digest_algorithm = digest_info.sequence()?.oid()?;
+  let digest = digest_info.octet_string()?;
+  let salt = mac_data.octet_string()?;
+  let iterations = if mac_data.is_empty() {
+    1
+  } else {
+    mac_data.integer()?
+  };
+
+  let password = bmp_password(password);
+  let valid = match digest_algorithm {
+    OID_SHA1 => verify_hmac::<Sha1>(&password, salt, iterations, data, digest),
+    OID_SHA256 => {
+      verify_hmac::<Sha256>(&password, salt, iterations, data, digest)
+    }
+    _ => return Err(unsupported_err("MAC algorithm")),
+  };
+  if valid {
+    Ok(())
+  } else {
+    Err(password_err())
+  }
+}
+
+fn verify_hmac<D: Digest + BlockSizeUser>(
+  password: &[u8],
+  salt: &[u8],
+  iterations: u32,
+  data: &[u8],
+  expected: &[u8],
+) -> bool {
+  let key = pkcs12_kdf::<D>(
+    3,
+    password,
+    salt,
+    iterations,
+    <D as Digest>::output_size(),
+  );
+  let mut mac = SimpleHmac::<D>::new_from_slice(&key).unwrap();
+  mac.update(data);
+  mac.verify_slice(expected).is_ok()
+}
+
+/// The key derivation of RFC 7292, appendix B.2, which derives the MAC key
+/// and the keys and IVs of the legacy encryption schemes.
+fn pkcs12_kdf<D: Digest + BlockSizeUser>(
+  id: u8,
+  password: &[u8],
+  salt: &[u8],
+  iterations: u32,
+  len: usize,
+) -> Vec<u8> {
+  let v = D::block_size();
+  let fill = |data: &[u8]| -> Vec<u8> {
+    let len = v * ((data.len() + v - 1) / v);
+    data.iter().cycle().take(len).copied().collect()
+  };
+  let mut input = fill(salt);
+  input.extend(fill(password));
+
+  let mut output = Vec::with_capacity(len);
+  loop {
+    let mut hash = D::new()
+      .chain_update(vec![id; v])
+      .chain_update(&input)
+      .finalize();
+    for _ in 1..iterations {
+      hash = D::digest(&hash);
+    }
+    let remaining = len - output.len();
+    output.extend_from_slice(&hash[..remaining.min(hash.len())]);
+    if output.len() == len {
+      return output;
+    }
+    let b = hash.iter().cycle().take(v).copied().collect::<Vec<_>>();
+    for block in input.chunks_mut(v) {
+      let mut carry = 1u16;
+      for (x, y) in block.iter_mut().rev().zip(b.iter().rev()) {
+        let sum = *x as u16 + *y as u16 + carry;
+        *x = sum as u8;
+        carry = sum >> 8;
+      }
+    }
+  }
+}
+
+fn pem(label: &str, der: &[u8]) -> String {
+  let encoded = base64::encode(der);
+  let mut pem = format!("-----BEGIN {label}-----\n");
+  for line in encoded.as_bytes().chunks(64) {
+    pem.push_str(std::str::from_utf8(line).unwrap());
+    pem.push('\n');
+  }
+  pem.push_str(&format!("-----END {label}-----\n"));
+  pem
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::load_certs;
+  use crate::load_private_keys;
+
+  const BUNDLE: &[u8] =
+    include_bytes!("../../cli/tests/testdata/tls/localhost.p12");
+  const LEGACY_BUNDLE: &[u8] =
+    include_bytes!("../../cli/tests/testdata/tls/localhost_legacy.p12");
+  const CERT: &str = include_str!("../../cli/tests/testdata/tls/localhost.crt");
+  const KEY: &str = include_str!("../../cli/tests/testdata/tls/localhost.key");
+
+  #[test]
+  fn test_load_pkcs12() {
+    let (cert_chain, private_key) = load_pkcs12(BUNDLE, "deno").unwrap();
+    assert_eq!(
+      load_certs(&mut cert_chain.as_bytes()).unwrap(),
+      load_certs(&mut CERT.as_bytes()).unwrap()
+    );
+    assert_eq!(
+      load_private_keys(private_key.as_bytes()).unwrap(),
+      load_private_keys(KEY.as_bytes()).unwrap()
+    );
+  }
+
+  #[test]
+  fn test_load_pkcs12_legacy() {
+    // The certificate is encrypted with 40-bit RC2 and the key with 3DES.
+    let (cert_chain, private_key) = load_pkcs12(LEGACY_BUNDLE, "deno").unwrap();
+    assert_eq!(
+      load_certs(&mut cert_chain.as_bytes()).unwrap(),
+      load_certs(&mut CERT.as_bytes()).unwrap()
+    );
+    assert_eq!(
+      load_private_keys(private_key.as_bytes()).unwrap(),
+      load_private_keys(KEY.as_bytes()).unwrap()
+    );
+  }
+
+  #[test]
+  fn test_load_pkcs12_wrong_password() {
+    let err = load_pkcs12(BUNDLE, "wrong").unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Invalid password for PKCS#12 bundle, or the bundle is corrupted"
+    );
+    assert!(load_pkcs12(b"not a bundle", "deno").is_err());
+  }
+}
@@ -19,16 +19,28 @@ import {
   MessageEvent,
 } from "ext:deno_web/02_event.js";
 import { Blob, BlobPrototype } from "ext:deno_web/09_file.js";
+import {
+  fillHeaders,
+  headerListFromHeaders,
+  headersFromHeaderList,
+} from "ext:deno_fetch/20_headers.js";
 const primordials = globalThis.__bootstrap.primordials;
 const {
   ArrayBufferPrototype,
   ArrayBufferIsView,
   ArrayBufferPrototypeGetByteLength,
+  ArrayIsArray,
   ArrayPrototypeJoin,
   ArrayPrototypeMap,
+  ArrayPrototypeFindIndex,
+  ArrayPrototypePush,
   ArrayPrototypeSome,
+  ArrayPrototypeSplice,
   DataView,
+  DataViewPrototypeGetBuffer,
   DataViewPrototypeGetByteLength,
+  DataViewPrototypeGetByteOffset,
+  DateNow,
   ErrorPrototypeToString,
   ObjectDefineProperties,
   ObjectPrototypeIsPrototypeOf,
@@ -45,11 +57,18 @@ const {
   SymbolIterator,
   PromisePrototypeCatch,
   SymbolFor,
+  TypedArrayPrototypeEvery,
+  TypedArrayPrototypeGetBuffer,
   TypedArrayPrototypeGetByteLength,
+  TypedArrayPrototypeGetByteOffset,
   TypedArrayPrototypeGetSymbolToStringTag,
+  TypedArrayPrototypeSlice,
+  Uint8Array,
 } = primordials;
 const op_ws_check_permission_and_cancel_handle =
   core.ops.op_ws_check_permission_and_cancel_handle;
+const op_ws_set_limits = core.ops.op_ws_set_limits;
+const op_ws_get_buffered_amount = core.ops.op_ws_get_buffered_amount;
 const {
   op_ws_create,
   op_ws_close,
@@ -81,6 +100,128 @@ webidl.converters["sequence<DOMString> or DOMString"] = (
   return webidl.converters.DOMString(V, prefix, context, opts);
 };
 
+webidl.converters.WebSocketOptions = webidl.createDictionaryConverter(
+  "WebSocketOptions",
+  [
+    {
+      key: "protocols",
+      converter: webidl.converters["sequence<DOMString> or DOMString"],
+      get defaultValue() {
+        return [];
+      },
+    },
+    {
+      key: "headers",
+      converter: webidl.converters.HeadersInit,
+    },
+    {
+      key: "proxy",
+      converter: webidl.converters.any,
+    },
+    {
+      key: "pingInterval",
+      converter: webidl.converters["unsigned long"],
+    },
+    {
+      key: "pingTimeout",
+      converter: webidl.converters["unsigned long"],
+    },
+    {
+      key: "maxFrameSize",
+      converter: webidl.converters["unsigned long long"],
+    },
+    {
+      key: "maxMessageSize",
+      converter: webidl.converters["unsigned long long"],
+    },
+    {
+      key: "reassembleFragments",
+      converter: webidl.converters.boolean,
+    },
+    {
+      key: "permessageDeflate",
+      converter: webidl.converters.boolean,
+    },
+  ],
+);
+
+/**
+ * Picks the message size limits of a socket out of its options.
+ * @param {{ maxFrameSize?: number, maxMessageSize?: number, reassembleFragments?: boolean }} options
+ */
+function limitsFromOptions(options) {
+  if (
+    options.maxFrameSize === undefined &&
+    options.maxMessageSize === undefined &&
+    options.reassembleFragments === undefined
+  ) {
+    return undefined;
+  }
+  return {
+    maxFrameSize: options.maxFrameSize,
+    maxMessageSize: options.maxMessageSize,
+    reassembleFragments: options.reassembleFragments ?? true,
+  };
+}
+
+webidl.converters["sequence<DOMString> or DOMString or WebSocketOptions"] = (
+  V,
+  prefix,
+  context,
+  opts,
+) => {
+  // Union for (sequence<DOMString> or DOMString or WebSocketOptions)
+  if (webidl.type(V) === "Object" && V !== null) {
+    if (V[SymbolIterator] !== undefined) {
+      return webidl.converters["sequence<DOMString>"](V, prefix, context, opts);
+    }
+    return webidl.converters.WebSocketOptions(V, prefix, context, opts);
+  }
+  return webidl.converters.DOMString(V, prefix, context, opts);
+};
+
+/**
+ * @param {ArrayBufferView | ArrayBuffer} input
+ * @returns {Uint8Array}
+ */
+function copyBuffer(input) {
+  if (ArrayBufferIsView(input)) {
+    if (TypedArrayPrototypeGetSymbolToStringTag(input) !== undefined) {
+      // TypedArray
+      return TypedArrayPrototypeSlice(
+        new Uint8Array(
+          TypedArrayPrototypeGetBuffer(/** @type {Uint8Array} */ (input)),
+          TypedArrayPrototypeGetByteOffset(/** @type {Uint8Array} */ (input)),
+          TypedArrayPrototypeGetByteLength(/** @type {Uint8Array} */ (input)),
+        ),
+      );
+    } else {
+      // DataView
+      return TypedArrayPrototypeSlice(
+        new Uint8Array(
+          DataViewPrototypeGetBuffer(/** @type {DataView} */ (input)),
+          DataViewPrototypeGetByteOffset(/** @type {DataView} */ (input)),
+          DataViewPrototypeGetByteLength(/** @type {DataView} */ (input)),
+        ),
+      );
+    }
+  }
+  // ArrayBuffer
+  return TypedArrayPrototypeSlice(
+    new Uint8Array(input, 0, ArrayBufferPrototypeGetByteLength(input)),
+  );
+}
+
+/**
+ * @param {Uint8Array} a
+ * @param {Uint8Array} b
+ */
+function bytesEqual(a, b) {
+  return TypedArrayPrototypeGetByteLength(a) ===
+      TypedArrayPrototypeGetByteLength(b) &&
+    TypedArrayPrototypeEvery(a, (byte, i) => byte === b[i]);
+}
+
 webidl.converters["WebSocketSend"] = (V, prefix, context, opts) => {
   // Union for (Blob or ArrayBufferView or ArrayBuffer or USVString)
   if (ObjectPrototypeIsPrototypeOf(BlobPrototype, V)) {
@@ -119,12 +260,22 @@ const _extensions = Symbol("[[extensions]]");
 const _protocol = Symbol("[[protocol]]");
 const _binaryType = Symbol("[[binaryType]]");
 const _bufferedAmount = Symbol("[[bufferedAmount]]");
+const _bufferedAmountLowThreshold = Symbol("[[bufferedAmountLowThreshold]]");
+const _aboveLowThreshold = Symbol("[[aboveLowThreshold]]");
+const _checkBufferedAmountLow = Symbol("[[checkBufferedAmountLow]]");
 const _eventLoop = Symbol("[[eventLoop]]");
 
 const _server = Symbol("[[server]]");
 const _idleTimeoutDuration = Symbol("[[idleTimeout]]");
 const _idleTimeoutTimeout = Symbol("[[idleTimeoutTimeout]]");
 const _serverHandleIdleTimeout = Symbol("[[serverHandleIdleTimeout]]");
+const _closeUnresponsive = Symbol("[[closeUnresponsive]]");
+const _pendingPings = Symbol("[[pendingPings]]");
+const _pingInterval = Symbol("[[pingInterval]]");
+const _pingTimeout = Symbol("[[pingTimeout]]");
+const _keepAliveTimeout = Symbol("[[keepAliveTimeout]]");
+const _scheduleKeepAlive = Symbol("[[scheduleKeepAlive]]");
+const _limits = Symbol("[[limits]]");
 class WebSocket extends EventTarget {
   [_rid];
   [_role];
@@ -186,10 +337,27 @@ class WebSocket extends EventTarget {
     }
   }
 
+  // The bytes that were passed to send() but not yet queued in Rust
   [_bufferedAmount] = 0;
   get bufferedAmount() {
     webidl.assertBranded(this, WebSocketPrototype);
-    return this[_bufferedAmount];
+    if (this[_rid] === undefined) {
+      return this[_bufferedAmount];
+    }
+    return this[_bufferedAmount] + op_ws_get_buffered_amount(this[_rid]);
+  }
+
+  [_bufferedAmountLowThreshold] = 0;
+  get bufferedAmountLowThreshold() {
+    webidl.assertBranded(this, WebSocketPrototype);
+    return this[_bufferedAmountLowThreshold];
+  }
+  set bufferedAmountLowThreshold(value) {
+    webidl.assertBranded(this, WebSocketPrototype);
+    this[_bufferedAmountLowThreshold] = webidl.converters["unsigned long"](
+      value,
+      "Failed to set 'bufferedAmountLowThreshold' on 'WebSocket'",
+    );
   }
 
   constructor(url, protocols = []) {
@@ -198,12 +366,31 @@ class WebSocket extends EventTarget {
     const prefix = "Failed to construct 'WebSocket'";
     webidl.requiredArguments(arguments.length, 1, prefix);
     url = webidl.converters.USVString(url, prefix, "Argument 1");
-    protocols = webidl.converters["sequence<DOMString> or DOMString"](
+    protocols = webidl.converters[
+      "sequence<DOMString> or DOMString or WebSocketOptions"
+    ](
       protocols,
       prefix,
       "Argument 2",
     );
 
+    // Non-standard: options can also set the headers sent with the handshake
+    // and the proxy to connect through
+    const headers = headersFromHeaderList([], "request");
+    let proxy;
+    let permessageDeflate;
+    if (typeof protocols === "object" && !ArrayIsArray(protocols)) {
+      if (protocols.headers !== undefined) {
+        fillHeaders(headers, protocols.headers);
+      }
+      proxy = protocols.proxy;
+      permessageDeflate = protocols.permessageDeflate;
+      this[_pingInterval] = protocols.pingInterval;
+      this[_pingTimeout] = protocols.pingTimeout;
+      this[_limits] = limitsFromOptions(protocols);
+      protocols = protocols.protocols;
+    }
+
     let wsURL;
 
     try {
@@ -270,6 +457,10 @@ class WebSocket extends EventTarget {
         "new WebSocket()",
         wsURL.href,
         ArrayPrototypeJoin(protocols, ", "),
+        undefined,
+        headerListFromHeaders(headers),
+        proxy,
+        permessageDeflate,
       ),
       (create) => {
         this[_rid] = create.rid;
@@ -326,54 +517,57 @@ class WebSocket extends EventTarget {
 
     /**
      * @param {ArrayBufferView} view
-     * @param {number} byteLength
      */
-    const sendTypedArray = (view, byteLength) => {
-      this[_bufferedAmount] += byteLength;
+    const sendTypedArray = (view) => {
       PromisePrototypeThen(
         op_ws_send_binary(
           this[_rid],
           view,
         ),
-        () => {
-          this[_bufferedAmount] -= byteLength;
-        },
+        () => this[_checkBufferedAmountLow](),
       );
     };
 
     if (ObjectPrototypeIsPrototypeOf(BlobPrototype, data)) {
+      // The blob counts towards bufferedAmount while it is being read
+      const size = data.size;
+      this[_bufferedAmount] += size;
       PromisePrototypeThen(
         data.slice().arrayBuffer(),
-        (ab) =>
-          sendTypedArray(
-            new DataView(ab),
-            ArrayBufferPrototypeGetByteLength(ab),
-          ),
+        (ab) => {
+          this[_bufferedAmount] -= size;
+          sendTypedArray(new DataView(ab));
+        },
       );
-    } else if (ArrayBufferIsView(data)) {
-      if (TypedArrayPrototypeGetSymbolToStringTag(data) === undefined) {
-        // DataView
-        sendTypedArray(data, DataViewPrototypeGetByteLength(data));
-      } else {
-        // TypedArray
-        sendTypedArray(data, TypedArrayPrototypeGetByteLength(data));
-      }
-    } else if (ObjectPrototypeIsPrototypeOf(ArrayBufferPrototype, data)) {
-      sendTypedArray(data, ArrayBufferPrototypeGetByteLength(data));
+    } else if (
+      ArrayBufferIsView(data) ||
+      ObjectPrototypeIsPrototypeOf(ArrayBufferPrototype, data)
+    ) {
+      sendTypedArray(data);
     } else {
-      const string = String(data);
-      const d = core.encode(string);
-      this[_bufferedAmount] += TypedArrayPrototypeGetByteLength(d);
       PromisePrototypeThen(
         op_ws_send_text(
           this[_rid],
-          string,
+          String(data),
         ),
-        () => {
-          this[_bufferedAmount] -= TypedArrayPrototypeGetByteLength(d);
-        },
+        () => this[_checkBufferedAmountLow](),
       );
     }
+
+    if (this.bufferedAmount > this[_bufferedAmountLowThreshold]) {
+      this[_aboveLowThreshold] = true;
+    }
+  }
+
+  [_aboveLowThreshold] = false;
+  [_checkBufferedAmountLow]() {
+    if (
+      this[_aboveLowThreshold] &&
+      this.bufferedAmount <= this[_bufferedAmountLowThreshold]
+    ) {
+      this[_aboveLowThreshold] = false;
+      this.dispatchEvent(new Event("bufferedamountlow"));
+    }
   }
 
   close(code = undefined, reason = undefined) {
@@ -440,7 +634,78 @@ class WebSocket extends EventTarget {
     }
   }
 
+  /**
+   * Non-standard: sends a ping frame, resolving with the round trip time in
+   * milliseconds once the matching pong has arrived.
+   * @param {BufferSource} data
+   * @returns {Promise<number>}
+   */
+  ping(data = new Uint8Array()) {
+    webidl.assertBranded(this, WebSocketPrototype);
+    const prefix = "Failed to execute 'ping' on 'WebSocket'";
+    data = webidl.converters.BufferSource(data, prefix, "Argument 1");
+
+    if (this[_readyState] !== OPEN) {
+      throw new DOMException("readyState not OPEN", "InvalidStateError");
+    }
+
+    const payload = copyBuffer(data);
+    if (TypedArrayPrototypeGetByteLength(payload) > 125) {
+      throw new DOMException(
+        "The ping payload may not be longer than 125 bytes.",
+        "SyntaxError",
+      );
+    }
+
+    return new Promise((resolve, reject) => {
+      const ping = { payload, start: DateNow(), resolve, reject };
+      ArrayPrototypePush(this[_pendingPings], ping);
+      PromisePrototypeCatch(
+        op_ws_send_ping(this[_rid], payload),
+        (err) => {
+          const index = ArrayPrototypeFindIndex(
+            this[_pendingPings],
+            (pending) => pending === ping,
+          );
+          if (index !== -1) {
+            ArrayPrototypeSplice(this[_pendingPings], index, 1);
+          }
+          reject(err);
+        },
+      );
+    });
+  }
+
+  [_pendingPings] = [];
+
+  [_scheduleKeepAlive]() {
+    if (!this[_pingInterval] || this[_readyState] !== OPEN) {
+      return;
+    }
+    this[_keepAliveTimeout] = setTimeout(() => {
+      if (this[_readyState] !== OPEN) {
+        return;
+      }
+      const timeout = setTimeout(
+        () => this[_closeUnresponsive](),
+        this[_pingTimeout] ?? this[_pingInterval],
+      );
+      PromisePrototypeThen(
+        this.ping(),
+        () => {
+          clearTimeout(timeout);
+          this[_scheduleKeepAlive]();
+        },
+        () => clearTimeout(timeout),
+      );
+    }, this[_pingInterval]);
+  }
+
   async [_eventLoop]() {
+    if (this[_limits] !== undefined) {
+      op_ws_set_limits(this[_rid], this[_limits]);
+    }
+    this[_scheduleKeepAlive]();
     while (this[_readyState] !== CLOSED) {
       const { 0: kind, 1: value } = await op_ws_next_event(this[_rid]);
 
@@ -477,6 +742,23 @@ class WebSocket extends EventTarget {
         case 2: {
           /* pong */
           this[_serverHandleIdleTimeout]();
+          // A pong answers the oldest ping with the same payload, and any
+          // pings sent before it (RFC 6455 section 5.5.3)
+          const index = ArrayPrototypeFindIndex(
+            this[_pendingPings],
+            (ping) => bytesEqual(ping.payload, value),
+          );
+          if (index !== -1) {
+            const now = DateNow();
+            const answered = ArrayPrototypeSplice(
+              this[_pendingPings],
+              0,
+              index + 1,
+            );
+            for (let i = 0; i < answered.length; ++i) {
+              answered[i].resolve(now - answered[i].start);
+            }
+          }
           break;
         }
         case 3: {
@@ -523,6 +805,14 @@ class WebSocket extends EventTarget {
         }
       }
     }
+
+    clearTimeout(this[_keepAliveTimeout]);
+    const pings = ArrayPrototypeSplice(this[_pendingPings], 0);
+    for (let i = 0; i < pings.length; ++i) {
+      pings[i].reject(
+        new DOMException("The WebSocket was closed.", "InvalidStateError"),
+      );
+    }
   }
 
   [_serverHandleIdleTimeout]() {
@@ -533,23 +823,7 @@ class WebSocket extends EventTarget {
           await op_ws_send_ping(this[_rid]);
           this[_idleTimeoutTimeout] = setTimeout(async () => {
             if (this[_readyState] === OPEN) {
-              this[_readyState] = CLOSING;
-              const reason = "No response from ping frame.";
-              await op_ws_close(this[_rid], 1001, reason);
-              this[_readyState] = CLOSED;
-
-              const errEvent = new ErrorEvent("error", {
-                message: reason,
-              });
-              this.dispatchEvent(errEvent);
-
-              const event = new CloseEvent("close", {
-                wasClean: false,
-                code: 1001,
-                reason,
-              });
-              this.dispatchEvent(event);
-              core.tryClose(this[_rid]);
+              await this[_closeUnresponsive]();
             } else {
               clearTimeout(this[_idleTimeoutTimeout]);
             }
@@ -561,6 +835,29 @@ class WebSocket extends EventTarget {
     }
   }
 
+  async [_closeUnresponsive]() {
+    if (this[_readyState] !== OPEN) {
+      return;
+    }
+    this[_readyState] = CLOSING;
+    const reason = "No response from ping frame.";
+    await op_ws_close(this[_rid], 1001, reason);
+    this[_readyState] = CLOSED;
+
+    const errEvent = new ErrorEvent("error", {
+      message: reason,
+    });
+    this.dispatchEvent(errEvent);
+
+    const event = new CloseEvent("close", {
+      wasClean: false,
+      code: 1001,
+      reason,
+    });
+    this.dispatchEvent(event);
+    core.tryClose(this[_rid]);
+  }
+
   [SymbolFor("Deno.customInspect")](inspect) {
     return `${this.constructor.name} ${
       inspect({
@@ -594,6 +891,7 @@ defineEventHandler(WebSocket.prototype, "message");
 defineEventHandler(WebSocket.prototype, "error");
 defineEventHandler(WebSocket.prototype, "close");
 defineEventHandler(WebSocket.prototype, "open");
+defineEventHandler(WebSocket.prototype, "bufferedamountlow");
 
 webidl.configurePrototype(WebSocket);
 const WebSocketPrototype = WebSocket.prototype;
@@ -602,12 +900,16 @@ export {
   _eventLoop,
   _idleTimeoutDuration,
   _idleTimeoutTimeout,
+  _limits,
+  _pingInterval,
+  _pingTimeout,
   _protocol,
   _readyState,
   _rid,
   _role,
   _server,
   _serverHandleIdleTimeout,
+  limitsFromOptions,
   SERVER,
   WebSocket,
 };
@@ -9,6 +9,7 @@ import * as webidl from "ext:deno_webidl/00_webidl.js";
 import { Deferred, writableStreamClose } from "ext:deno_web/06_streams.js";
 import DOMException from "ext:deno_web/01_dom_exception.js";
 import { add, remove } from "ext:deno_web/03_abort_signal.js";
+import { limitsFromOptions } from "ext:deno_websocket/01_websocket.js";
 import {
   fillHeaders,
   headerListFromHeaders,
@@ -16,8 +17,11 @@ import {
 } from "ext:deno_fetch/20_headers.js";
 const primordials = globalThis.__bootstrap.primordials;
 const {
+  ArrayBufferIsView,
+  ArrayBufferPrototype,
   ArrayPrototypeJoin,
   ArrayPrototypeMap,
+  DataView,
   DateNow,
   Error,
   ObjectPrototypeIsPrototypeOf,
@@ -31,7 +35,6 @@ const {
   SymbolFor,
   TypeError,
   TypedArrayPrototypeGetByteLength,
-  Uint8ArrayPrototype,
 } = primordials;
 const {
   op_ws_send_text,
@@ -65,11 +68,35 @@ webidl.converters.WebSocketStreamOptions = webidl.createDictionaryConverter(
       key: "headers",
       converter: webidl.converters.HeadersInit,
     },
+    {
+      key: "proxy",
+      converter: webidl.converters.any,
+    },
+    {
+      key: "maxFrameSize",
+      converter: webidl.converters["unsigned long long"],
+    },
+    {
+      key: "maxMessageSize",
+      converter: webidl.converters["unsigned long long"],
+    },
+    {
+      key: "reassembleFragments",
+      converter: webidl.converters.boolean,
+    },
+    {
+      key: "permessageDeflate",
+      converter: webidl.converters.boolean,
+    },
   ],
 );
 webidl.converters.WebSocketCloseInfo = webidl.createDictionaryConverter(
   "WebSocketCloseInfo",
   [
+    {
+      key: "closeCode",
+      converter: webidl.converters["unsigned short"],
+    },
     {
       key: "code",
       converter: webidl.converters["unsigned short"],
@@ -84,6 +111,10 @@ webidl.converters.WebSocketCloseInfo = webidl.createDictionaryConverter(
 
 const CLOSE_RESPONSE_TIMEOUT = 5000;
 
+function isCloseInfo(reason) {
+  return reason?.closeCode !== undefined || reason?.code !== undefined;
+}
+
 const _rid = Symbol("[[rid]]");
 const _url = Symbol("[[url]]");
 const _connection = Symbol("[[connection]]");
@@ -173,6 +204,8 @@ class WebSocketStream {
           options.protocols ? ArrayPrototypeJoin(options.protocols, ", ") : "",
           cancelRid,
           headerListFromHeaders(headers),
+          options.proxy,
+          options.permessageDeflate,
         ),
         (create) => {
           options.signal?.[remove](abort);
@@ -212,24 +245,30 @@ class WebSocketStream {
             );
           } else {
             this[_rid] = create.rid;
+            const limits = limitsFromOptions(options);
+            if (limits !== undefined) {
+              ops.op_ws_set_limits(this[_rid], limits);
+            }
 
             const writable = new WritableStream({
               write: async (chunk) => {
                 if (typeof chunk === "string") {
                   await op_ws_send_text(this[_rid], chunk);
+                } else if (ArrayBufferIsView(chunk)) {
+                  await op_ws_send_binary(this[_rid], chunk);
                 } else if (
-                  ObjectPrototypeIsPrototypeOf(Uint8ArrayPrototype, chunk)
+                  ObjectPrototypeIsPrototypeOf(ArrayBufferPrototype, chunk)
                 ) {
-                  await op_ws_send_binary(this[_rid], chunk);
+                  await op_ws_send_binary(this[_rid], new DataView(chunk));
                 } else {
                   throw new TypeError(
-                    "A chunk may only be either a string or an Uint8Array",
+                    "A chunk may only be either a string or a BufferSource",
                   );
                 }
               },
               close: async (reason) => {
                 try {
-                  this.close(reason?.code !== undefined ? reason : {});
+                  this.close(isCloseInfo(reason) ? reason : {});
                 } catch (_) {
                   this.close();
                 }
@@ -237,7 +276,7 @@ class WebSocketStream {
               },
               abort: async (reason) => {
                 try {
-                  this.close(reason?.code !== undefined ? reason : {});
+                  this.close(isCloseInfo(reason) ? reason : {});
                 } catch (_) {
                   this.close();
                 }
@@ -276,6 +315,7 @@ class WebSocketStream {
                 default: {
                   /* close */
                   this[_closed].resolve({
+                    closeCode: kind,
                     code: kind,
                     reason: value,
                   });
@@ -326,7 +366,7 @@ class WebSocketStream {
               pull,
               cancel: async (reason) => {
                 try {
-                  this.close(reason?.code !== undefined ? reason : {});
+                  this.close(isCloseInfo(reason) ? reason : {});
                 } catch (_) {
                   this.close();
                 }
@@ -357,6 +397,12 @@ class WebSocketStream {
   }
 
   [_connection] = new Deferred();
+  get opened() {
+    webidl.assertBranded(this, WebSocketStreamPrototype);
+    return this[_connection].promise;
+  }
+
+  // Kept for code written against earlier drafts, which named `opened` this way
   get connection() {
     webidl.assertBranded(this, WebSocketStreamPrototype);
     return this[_connection].promise;
@@ -378,10 +424,11 @@ class WebSocketStream {
       "Argument 1",
     );
 
+    let code = closeInfo.closeCode ?? closeInfo.code;
     if (
-      closeInfo.code &&
-      !(closeInfo.code === 1000 ||
-        (3000 <= closeInfo.code && closeInfo.code < 5000))
+      code &&
+      !(code === 1000 ||
+        (3000 <= code && code < 5000))
     ) {
       throw new DOMException(
         "The close code must be either 1000 or in the range of 3000 to 4999.",
@@ -400,7 +447,6 @@ class WebSocketStream {
       );
     }
 
-    let code = closeInfo.code;
     if (closeInfo.reason && code === undefined) {
       code = 1000;
     }
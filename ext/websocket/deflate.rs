@@ -0,0 +1,423 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Client support for the permessage-deflate extension (RFC 7692).
+//!
+//! `fastwebsockets` rejects frames with the RSV1 bit set, so compressed frames
+//! are rewritten as they are read from the socket: [`Rsv1Tracker`] clears the
+//! bit in the frame headers and records which messages were compressed, and
+//! [`Inflater`] decompresses the payloads of those messages once
+//! `fastwebsockets` has parsed the frames. Messages sent by the client are not
+//! compressed, which the extension allows.
+
+use crate::message::MessageError;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use fastwebsockets::Frame;
+use fastwebsockets::OpCode;
+use flate2::Decompress;
+use flate2::FlushDecompress;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+pub const OFFER: &str = "permessage-deflate";
+
+const RSV1: u8 = 0b0100_0000;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+
+/// The empty stored block that terminates every compressed message, which
+/// the sender strips from the payload.
+const TAIL: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// The size that a compressed message may inflate to when the socket has no
+/// `maxMessageSize`, the same as the default limit of the size of a frame.
+const DEFAULT_MAX_INFLATED_SIZE: usize = 64 << 20;
+
+/// For every data message read from the socket, in order: `None` if it is
+/// uncompressed, or whether it is a text message if it is compressed.
+type MessageFlags = Rc<RefCell<VecDeque<Option<bool>>>>;
+
+/// The parameters of the extension accepted by the server.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DeflateConfig {
+  server_no_context_takeover: bool,
+}
+
+/// Parses the `Sec-WebSocket-Extensions` header of the handshake response,
+/// returning the configuration if the server accepted permessage-deflate.
+pub fn negotiated(extensions: &str) -> Result<Option<DeflateConfig>, AnyError> {
+  let Some(extension) = extensions
+    .split(',')
+    .find(|extension| extension.split(';').next().map(str::trim) == Some(OFFER))
+  else {
+    return Ok(None);
+  };
+
+  let mut config = DeflateConfig::default();
+  for param in extension.split(';').skip(1) {
+    let (name, value) = match param.split_once('=') {
+      Some((name, value)) => {
+        (name.trim(), Some(value.trim().trim_matches('"')))
+      }
+      None => (param.trim(), None),
+    };
+    match (name, value) {
+      ("server_no_context_takeover", None) => {
+        config.server_no_context_takeover = true;
+      }
+      // Only affects compression, which the client doesn't do
+      ("client_no_context_takeover", None) => {}
+      ("server_max_window_bits" | "client_max_window_bits", Some(bits))
+        if matches!(bits.parse::<u8>(), Ok(8..=15)) => {}
+      _ => {
+        return Err(type_error(format!(
+          "Invalid permessage-deflate parameter in the server response: {}",
+          param.trim()
+        )))
+      }
+    }
+  }
+  Ok(Some(config))
+}
+
+pub fn new(config: &DeflateConfig) -> (Rsv1Tracker, Inflater) {
+  let flags = MessageFlags::default();
+  let tracker = Rsv1Tracker {
+    flags: flags.clone(),
+    header: [0; 14],
+    header_len: 0,
+    payload_remaining: 0,
+  };
+  let inflater = Inflater {
+    flags,
+    // A 15 bit window also decodes data compressed with a smaller one
+    decompress: Decompress::new(false),
+    no_context_takeover: config.server_no_context_takeover,
+    current: None,
+    inflated: 0,
+  };
+  (tracker, inflater)
+}
+
+/// Follows the frames in the bytes read from the socket, clearing the RSV1
+/// bit of compressed messages. Compressed text messages are turned into
+/// binary ones, so that their payload isn't validated as UTF-8 before it is
+/// decompressed.
+pub struct Rsv1Tracker {
+  flags: MessageFlags,
+  header: [u8; 14],
+  header_len: usize,
+  payload_remaining: u64,
+}
+
+impl Rsv1Tracker {
+  pub fn process(&mut self, mut bytes: &mut [u8]) {
+    while !bytes.is_empty() {
+      if self.payload_remaining > 0 {
+        let skip = self.payload_remaining.min(bytes.len() as u64);
+        self.payload_remaining -= skip;
+        bytes = &mut bytes[skip as usize..];
+        continue;
+      }
+
+      let byte = &mut bytes[0];
+      if self.header_len == 0 {
+        let opcode = *byte & 0x0f;
+        if opcode == OPCODE_TEXT || opcode == OPCODE_BINARY {
+          let compressed = *byte & RSV1 != 0;
+          if compressed {
+            *byte = (*byte & !RSV1 & 0xf0) | OPCODE_BINARY;
+          }
+          self
+            .flags
+            .borrow_mut()
+            .push_back(compressed.then_some(opcode == OPCODE_TEXT));
+        }
+      }
+      self.header[self.header_len] = *byte;
+      self.header_len += 1;
+      bytes = &mut bytes[1..];
+
+      if let Some((header_len, payload_len)) =
+        parse_header(&self.header[..self.header_len])
+      {
+        if self.header_len == header_len {
+          self.header_len = 0;
+          self.payload_remaining = payload_len;
+        }
+      }
+    }
+  }
+}
+
+/// Returns the size of a frame header and the payload length of the frame,
+/// once enough of the header is known.
+fn parse_header(header: &[u8]) -> Option<(usize, u64)> {
+  if header.len() < 2 {
+    return None;
+  }
+  let mask_len = if header[1] & 0x80 != 0 { 4 } else { 0 };
+  let (extra, payload_len) = match header[1] & 0x7f {
+    126 => {
+      let bytes = header.get(2..4)?;
+      (2, u16::from_be_bytes([bytes[0], bytes[1]]) as u64)
+    }
+    127 => {
+      let bytes = header.get(2..10)?;
+      (8, u64::from_be_bytes(bytes.try_into().unwrap()))
+    }
+    len => (0, len as u64),
+  };
+  Some((2 + extra + mask_len, payload_len))
+}
+
+pub struct Inflater {
+  flags: MessageFlags,
+  decompress: Decompress,
+  no_context_takeover: bool,
+  /// The opcode of the compressed message being received, if any.
+  current: Option<OpCode>,
+  /// The size of the inflated fragments of the message being received.
+  inflated: usize,
+}
+
+impl Inflater {
+  /// Decompresses the payload of a frame of a compressed message. Other
+  /// frames are returned as is.
+  pub fn inflate(
+    &mut self,
+    frame: Frame,
+    max_size: Option<usize>,
+  ) -> Result<Frame, MessageError> {
+    let opcode = match frame.opcode {
+      OpCode::Text | OpCode::Binary => {
+        let flag = self.flags.borrow_mut().pop_front().flatten();
+        let Some(is_text) = flag else {
+          self.current = None;
+          return Ok(frame);
+        };
+        let opcode = if is_text {
+          OpCode::Text
+        } else {
+          OpCode::Binary
+        };
+        self.current = Some(opcode);
+        self.inflated = 0;
+        opcode
+      }
+      OpCode::Continuation if self.current.is_some() => OpCode::Continuation,
+      _ => return Ok(frame),
+    };
+
+    let max_size = max_size
+      .unwrap_or(DEFAULT_MAX_INFLATED_SIZE)
+      .saturating_sub(self.inflated);
+    let mut payload = Vec::with_capacity(frame.payload.len() * 2);
+    self.decompress(&frame.payload, &mut payload, max_size)?;
+    if frame.fin {
+      self.decompress(&TAIL, &mut payload, max_size)?;
+      if self.no_context_takeover {
+        self.decompress.reset(false);
+      }
+      // Unfragmented text messages are otherwise validated by fastwebsockets
+      if opcode == OpCode::Text && std::str::from_utf8(&payload).is_err() {
+        return Err(MessageError::Invalid("Invalid UTF-8"));
+      }
+      self.current = None;
+    } else {
+      self.inflated += payload.len();
+    }
+    Ok(Frame::new(frame.fin, opcode, None, payload))
+  }
+
+  fn decompress(
+    &mut self,
+    mut input: &[u8],
+    output: &mut Vec<u8>,
+    max_size: usize,
+  ) -> Result<(), MessageError> {
+    loop {
+      if output.len() == output.capacity() {
+        if output.len() > max_size {
+          return Err(MessageError::TooBig);
+        }
+        output.reserve(output.len().max(1024));
+      }
+      let total_in = self.decompress.total_in();
+      self
+        .decompress
+        .decompress_vec(input, output, FlushDecompress::Sync)
+        .map_err(|_| MessageError::Invalid("Invalid compressed data"))?;
+      input = &input[(self.decompress.total_in() - total_in) as usize..];
+      // Everything was decompressed if there was room left for more output
+      if input.is_empty() && output.len() < output.capacity() {
+        break;
+      }
+    }
+    if output.len() > max_size {
+      return Err(MessageError::TooBig);
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use flate2::Compress;
+  use flate2::Compression;
+  use flate2::FlushCompress;
+
+  fn compress(data: &[u8]) -> Vec<u8> {
+    let mut compress = Compress::new(Compression::default(), false);
+    let mut output = Vec::with_capacity(data.len() + 64);
+    compress
+      .compress_vec(data, &mut output, FlushCompress::Sync)
+      .unwrap();
+    assert!(output.ends_with(&TAIL));
+    output.truncate(output.len() - TAIL.len());
+    output
+  }
+
+  /// Encodes an unmasked frame, as sent by a server.
+  fn encode(fin: bool, rsv1: bool, opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![(fin as u8) << 7 | (rsv1 as u8) << 6 | opcode];
+    match payload.len() {
+      len @ 0..=125 => bytes.push(len as u8),
+      len @ 126..=65535 => {
+        bytes.push(126);
+        bytes.extend_from_slice(&(len as u16).to_be_bytes());
+      }
+      len => {
+        bytes.push(127);
+        bytes.extend_from_slice(&(len as u64).to_be_bytes());
+      }
+    }
+    bytes.extend_from_slice(payload);
+    bytes
+  }
+
+  #[test]
+  fn test_negotiated() {
+    assert_eq!(negotiated("").unwrap(), None);
+    assert_eq!(negotiated("x-other").unwrap(), None);
+    assert_eq!(
+      negotiated("permessage-deflate").unwrap(),
+      Some(DeflateConfig::default())
+    );
+    assert_eq!(
+      negotiated(
+        "permessage-deflate; server_no_context_takeover; server_max_window_bits=10"
+      )
+      .unwrap(),
+      Some(DeflateConfig {
+        server_no_context_takeover: true
+      })
+    );
+    assert!(
+      negotiated("permessage-deflate; server_max_window_bits=16").is_err()
+    );
+    assert!(negotiated("permessage-deflate; unknown").is_err());
+  }
+
+  #[test]
+  fn test_rsv1_tracker() {
+    let (mut tracker, _) = new(&DeflateConfig::default());
+    let large = vec![0; 300];
+    let mut bytes = encode(true, true, OPCODE_TEXT, b"abc");
+    bytes.extend(encode(true, false, OPCODE_BINARY, &large));
+    bytes.extend(encode(true, false, 0x9, b""));
+    bytes.extend(encode(false, true, OPCODE_BINARY, b"x"));
+    // Feed the bytes in small chunks, splitting headers
+    for chunk in bytes.chunks_mut(3) {
+      tracker.process(chunk);
+    }
+    assert_eq!(bytes[0], 0x80 | OPCODE_BINARY);
+    assert_eq!(
+      tracker.flags.borrow().iter().copied().collect::<Vec<_>>(),
+      vec![Some(true), None, Some(false)]
+    );
+    assert_eq!(bytes[bytes.len() - 3], OPCODE_BINARY);
+  }
+
+  #[test]
+  fn test_inflate() {
+    let (_, mut inflater) = new(&DeflateConfig::default());
+    let text = "hello hello hello hello";
+    inflater.flags.borrow_mut().extend([Some(true), None]);
+
+    let compressed = compress(text.as_bytes());
+    let frame = Frame::new(true, OpCode::Binary, None, compressed);
+    let frame = inflater.inflate(frame, None).unwrap();
+    assert_eq!(frame.opcode, OpCode::Text);
+    assert_eq!(frame.payload, text.as_bytes());
+
+    let frame = Frame::new(true, OpCode::Binary, None, b"raw".to_vec());
+    let frame = inflater.inflate(frame, None).unwrap();
+    assert_eq!(frame.payload, b"raw");
+  }
+
+  #[test]
+  fn test_inflate_fragmented() {
+    let (_, mut inflater) = new(&DeflateConfig::default());
+    inflater.flags.borrow_mut().push_back(Some(false));
+    let data = vec![7u8; 10_000];
+    let compressed = compress(&data);
+    let (first, second) = compressed.split_at(compressed.len() / 2);
+
+    let frame = Frame::new(false, OpCode::Binary, None, first.to_vec());
+    let mut payload = inflater.inflate(frame, None).unwrap().payload;
+    let frame = Frame::new(true, OpCode::Continuation, None, second.to_vec());
+    let frame = inflater.inflate(frame, None).unwrap();
+    assert_eq!(frame.opcode, OpCode::Continuation);
+    payload.extend(frame.payload);
+    assert_eq!(payload, data);
+  }
+
+  #[test]
+  fn test_inflate_too_big() {
+    let (_, mut inflater) = new(&DeflateConfig::default());
+    inflater.flags.borrow_mut().push_back(Some(false));
+    let compressed = compress(&vec![0u8; 1 << 20]);
+    assert!(compressed.len() < 4096);
+    let frame = Frame::new(true, OpCode::Binary, None, compressed);
+    assert!(matches!(
+      inflater.inflate(frame, Some(4096)),
+      Err(MessageError::TooBig)
+    ));
+
+    // The limit applies to all of the fragments of a message
+    let (_, mut inflater) = new(&DeflateConfig::default());
+    inflater.flags.borrow_mut().push_back(Some(false));
+    let mut compress = Compress::new(Compression::default(), false);
+    let mut first = Vec::with_capacity(1024);
+    compress
+      .compress_vec(&[0u8; 3000], &mut first, FlushCompress::Sync)
+      .unwrap();
+    let mut second = Vec::with_capacity(1024);
+    compress
+      .compress_vec(&[0u8; 3000], &mut second, FlushCompress::Sync)
+      .unwrap();
+    second.truncate(second.len() - TAIL.len());
+    let frame = Frame::new(false, OpCode::Binary, None, first);
+    assert_eq!(
+      inflater.inflate(frame, Some(4096)).unwrap().payload.len(),
+      3000
+    );
+    let frame = Frame::new(true, OpCode::Continuation, None, second);
+    assert!(matches!(
+      inflater.inflate(frame, Some(4096)),
+      Err(MessageError::TooBig)
+    ));
+
+    let (_, mut inflater) = new(&DeflateConfig::default());
+    inflater.flags.borrow_mut().push_back(Some(false));
+    let compressed = compress(&vec![0u8; DEFAULT_MAX_INFLATED_SIZE + 1]);
+    let frame = Frame::new(true, OpCode::Binary, None, compressed);
+    assert!(matches!(
+      inflater.inflate(frame, None),
+      Err(MessageError::TooBig)
+    ));
+  }
+}
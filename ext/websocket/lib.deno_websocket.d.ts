@@ -31,12 +31,66 @@ declare class CloseEvent extends Event {
 
 /** @category Web Sockets */
 interface WebSocketEventMap {
+  bufferedamountlow: Event;
   close: CloseEvent;
   error: Event;
   message: MessageEvent;
   open: Event;
 }
 
+/** @category Web Sockets */
+interface WebSocketOptions {
+  /** The subprotocols the client is willing to use. */
+  protocols?: string | string[];
+  /** Additional headers to send with the opening handshake. */
+  headers?: HeadersInit;
+  /** The proxy to connect through. `url` is an `http:`, `socks5:` or
+   * `socks5h:` URL, optionally with credentials. By default the proxy set by
+   * the `HTTPS_PROXY` (for `wss:`) or `HTTP_PROXY` (for `ws:`) environment
+   * variable is used, unless the host is listed in `NO_PROXY`. Connecting to
+   * the proxy requires net permission to it. With `socks5:` the host is
+   * resolved locally, and with `socks5h:` by the proxy. */
+  proxy?: {
+    url: string;
+    basicAuth?: { username: string; password: string };
+  };
+  /** The number of milliseconds between keepalive pings sent to the
+   * server. If the server doesn't answer a ping with a pong within
+   * `pingTimeout`, the connection is closed with code 1001.
+   *
+   * By default no keepalive pings are sent. */
+  pingInterval?: number;
+  /** The number of milliseconds to wait for the pong to a keepalive ping.
+   *
+   * Defaults to `pingInterval`. */
+  pingTimeout?: number;
+  /** The largest frame the server may send, in bytes. Larger frames are
+   * rejected based on their header, and the connection is closed with code
+   * 1009.
+   *
+   * The default is 64 MiB. */
+  maxFrameSize?: number;
+  /** The largest message the server may send, in bytes, including all of
+   * the fragments of a fragmented message. Larger messages close the
+   * connection with code 1009 as soon as the limit is exceeded.
+   *
+   * By default there is no limit besides `maxFrameSize`, and compressed
+   * messages can't inflate to more than 64 MiB. */
+  maxMessageSize?: number;
+  /** Whether fragmented messages are reassembled before they are delivered.
+   * When `false`, every fragment is delivered as a message of its own as
+   * soon as it arrives, so the boundaries of fragmented messages are lost.
+   *
+   * @default {true} */
+  reassembleFragments?: boolean;
+  /** Whether to offer the permessage-deflate extension, which lets the
+   * server compress the messages it sends. Messages sent by the client are
+   * never compressed.
+   *
+   * @default {true} */
+  permessageDeflate?: boolean;
+
+
 /**
  * Provides the API for creating and managing a WebSocket connection to a
  * server, as well as for sending and receiving data on the connection.
@@ -49,6 +103,11 @@ interface WebSocketEventMap {
  */
 declare class WebSocket extends EventTarget {
   constructor(url: string | URL, protocols?: string | string[]);
+  /** Non-standard: `options.headers` are sent with the opening handshake,
+   * which allows setting headers like `Authorization` or `Cookie`. Headers
+   * that are part of the handshake itself, like `Sec-WebSocket-Key`, are
+   * ignored. */
+  constructor(url: string | URL, options?: WebSocketOptions);
 
   static readonly CLOSED: number;
   static readonly CLOSING: number;
@@ -67,10 +126,18 @@ declare class WebSocket extends EventTarget {
    * If the WebSocket connection is closed, this attribute's value will only increase with each call to the send() method. (The number does not reset to zero once the connection closes.)
    */
   readonly bufferedAmount: number;
+  /**
+   * Non-standard: the `bufferedamountlow` event is fired once
+   * `bufferedAmount` drops to this number of bytes or below after being
+   * above it, so that senders can wait for queued data to drain before
+   * sending more. The default is 0.
+   */
+  bufferedAmountLowThreshold: number;
   /**
    * Returns the extensions selected by the server, if any.
    */
   readonly extensions: string;
+  onbufferedamountlow: ((this: WebSocket, ev: Event) => any) | null;
   onclose: ((this: WebSocket, ev: CloseEvent) => any) | null;
   onerror: ((this: WebSocket, ev: Event | ErrorEvent) => any) | null;
   onmessage: ((this: WebSocket, ev: MessageEvent) => any) | null;
@@ -95,6 +162,14 @@ declare class WebSocket extends EventTarget {
    * Transmits data using the WebSocket connection. data can be a string, a Blob, an ArrayBuffer, or an ArrayBufferView.
    */
   send(data: string | ArrayBufferLike | Blob | ArrayBufferView): void;
+
+  /**
+   * Non-standard: sends a ping frame with the given payload of at most 125
+   * bytes. Resolves with the round trip time in milliseconds once the
+   * matching pong has been received, and rejects if the connection closes
+   * first.
+   */
+  ping(data?: BufferSource): Promise<number>;
   readonly CLOSED: number;
   readonly CLOSING: number;
   readonly CONNECTING: number;
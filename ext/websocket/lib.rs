@@ -1,4 +1,8 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+use crate::deflate::Inflater;
+use crate::message::MessageCollector;
+use crate::message::MessageError;
+use crate::message::WsLimits;
 use crate::stream::WebSocketStream;
 use bytes::Bytes;
 use deno_core::error::invalid_hostname;
@@ -19,6 +23,7 @@ use deno_core::ZeroCopyBuf;
 use deno_net::raw::take_network_stream_resource;
 use deno_net::raw::NetworkStream;
 use deno_tls::create_client_config;
+use deno_tls::Proxy;
 use deno_tls::RootCertStoreProvider;
 use http::header::CONNECTION;
 use http::header::UPGRADE;
@@ -35,23 +40,26 @@ use std::cell::RefCell;
 use std::convert::TryFrom;
 use std::fmt;
 use std::future::Future;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncWrite;
-use tokio::net::TcpStream;
 use tokio_rustls::rustls::RootCertStore;
 use tokio_rustls::rustls::ServerName;
 use tokio_rustls::TlsConnector;
 
 use fastwebsockets::CloseCode;
-use fastwebsockets::FragmentCollector;
 use fastwebsockets::Frame;
 use fastwebsockets::OpCode;
 use fastwebsockets::Role;
 use fastwebsockets::WebSocket;
+use fastwebsockets::WebSocketError;
 
+mod deflate;
+mod message;
+mod proxy;
 mod stream;
 
 #[derive(Clone)]
@@ -75,6 +83,12 @@ pub trait WebSocketPermissions {
     _url: &url::Url,
     _api_name: &str,
   ) -> Result<(), AnyError>;
+  fn check_net_resolved<T: AsRef<str>>(
+    &mut self,
+    _host: &(T, Option<u16>),
+    _addrs: &[SocketAddr],
+    _api_name: &str,
+  ) -> Result<(), AnyError>;
 }
 
 /// `UnsafelyIgnoreCertificateErrors` is a wrapper struct so it can be placed inside `GothamState`;
@@ -130,11 +144,21 @@ pub struct CreateResponse {
   extensions: String,
 }
 
+type Handshake = (
+  WebSocket<WebSocketStream>,
+  http::Response<Body>,
+  Option<Inflater>,
+);
+
 async fn handshake<S: AsyncRead + AsyncWrite + Send + Unpin + 'static>(
   cancel_resource: Option<Rc<CancelHandle>>,
   request: Request<Body>,
   socket: S,
-) -> Result<(WebSocket<WebSocketStream>, http::Response<Body>), AnyError> {
+) -> Result<Handshake, AnyError> {
+  let offered_deflate = request
+    .headers()
+    .get(http::header::SEC_WEBSOCKET_EXTENSIONS)
+    .is_some();
   let client =
     fastwebsockets::handshake::client(&LocalExecutor, request, socket);
 
@@ -150,11 +174,26 @@ async fn handshake<S: AsyncRead + AsyncWrite + Send + Unpin + 'static>(
   })?;
 
   let upgraded = upgraded.into_inner();
-  let stream =
+  let mut stream =
     WebSocketStream::new(stream::WsStreamKind::Upgraded(upgraded), None);
+  let mut inflater = None;
+  if offered_deflate {
+    let extensions = response
+      .headers()
+      .get_all(http::header::SEC_WEBSOCKET_EXTENSIONS)
+      .iter()
+      .filter_map(|header| header.to_str().ok())
+      .collect::<Vec<_>>()
+      .join(", ");
+    if let Some(config) = deflate::negotiated(&extensions)? {
+      let (tracker, deflate_inflater) = deflate::new(&config);
+      stream = stream.with_rsv1_tracker(tracker);
+      inflater = Some(deflate_inflater);
+    }
+  }
   let stream = WebSocket::after_handshake(stream, Role::Client);
 
-  Ok((stream, response))
+  Ok((stream, response, inflater))
 }
 
 #[op]
@@ -165,6 +204,8 @@ pub async fn op_ws_create<WP>(
   protocols: String,
   cancel_handle: Option<ResourceId>,
   headers: Option<Vec<(ByteString, ByteString)>>,
+  proxy: Option<Proxy>,
+  permessage_deflate: Option<bool>,
 ) -> Result<CreateResponse, AnyError>
 where
   WP: WebSocketPermissions + 'static,
@@ -225,6 +266,10 @@ where
     request = request.header("Sec-WebSocket-Protocol", protocols);
   }
 
+  if permessage_deflate.unwrap_or(true) {
+    request = request.header("Sec-WebSocket-Extensions", deflate::OFFER);
+  }
+
   if let Some(headers) = headers {
     for (key, value) in headers {
       let name = HeaderName::from_bytes(&key)
@@ -243,7 +288,12 @@ where
           | http::header::UPGRADE
           | http::header::CONNECTION
       );
-      if !is_disallowed_header {
+      if name == http::header::USER_AGENT {
+        // Replaces the default user agent instead of adding a second one
+        if let Some(headers) = request.headers_mut() {
+          headers.insert(name, v);
+        }
+      } else if !is_disallowed_header {
         request = request.header(name, v);
       }
     }
@@ -256,10 +306,24 @@ where
     Some("ws") => 80,
     _ => unreachable!(),
   });
-  let addr = format!("{domain}:{port}");
-  let tcp_socket = TcpStream::connect(addr).await?;
+  let secure = uri.scheme_str() == Some("wss");
+  let proxy = proxy.or_else(|| proxy::proxy_from_env(secure, domain));
+  if let Some(proxy) = proxy.as_ref().filter(|p| !p.is_excluded(domain)) {
+    state
+      .borrow_mut()
+      .borrow_mut::<WP>()
+      .check_net_url(&proxy::proxy_url(proxy)?, &api_name)?;
+  }
+  let tcp_socket = proxy::connect(proxy.as_ref(), domain, *port, |addrs| {
+    state.borrow_mut().borrow_mut::<WP>().check_net_resolved(
+      &(domain, Some(*port)),
+      addrs,
+      &api_name,
+    )
+  })
+  .await?;
 
-  let (stream, response) = match uri.scheme_str() {
+  let (stream, response, inflater) = match uri.scheme_str() {
     Some("ws") => handshake(cancel_resource, request, tcp_socket).await?,
     Some("wss") => {
       let tls_config = create_client_config(
@@ -281,10 +345,7 @@ where
     state.borrow_mut().resource_table.close(cancel_rid).ok();
   }
 
-  let resource = ServerWebSocket {
-    ws: AsyncRefCell::new(FragmentCollector::new(stream)),
-    closed: Rc::new(Cell::new(false)),
-  };
+  let resource = ServerWebSocket::new(stream).with_inflater(inflater);
   let mut state = state.borrow_mut();
   let rid = state.resource_table.add(resource);
 
@@ -315,11 +376,32 @@ pub enum MessageKind {
 }
 
 pub struct ServerWebSocket {
-  ws: AsyncRefCell<FragmentCollector<WebSocketStream>>,
+  ws: AsyncRefCell<WebSocket<WebSocketStream>>,
+  messages: RefCell<MessageCollector>,
+  inflater: RefCell<Option<Inflater>>,
+  limits: Cell<WsLimits>,
+  /// The number of payload bytes of data frames that are queued for writing.
+  buffered: Cell<usize>,
   closed: Rc<Cell<bool>>,
 }
 
 impl ServerWebSocket {
+  fn new(ws: WebSocket<WebSocketStream>) -> Self {
+    Self {
+      ws: AsyncRefCell::new(ws),
+      messages: Default::default(),
+      inflater: Default::default(),
+      limits: Default::default(),
+      buffered: Cell::new(0),
+      closed: Rc::new(Cell::new(false)),
+    }
+  }
+
+  fn with_inflater(self, inflater: Option<Inflater>) -> Self {
+    *self.inflater.borrow_mut() = inflater;
+    self
+  }
+
   #[inline]
   pub async fn write_frame(
     self: Rc<Self>,
@@ -334,6 +416,19 @@ impl ServerWebSocket {
       .map_err(|err| type_error(err.to_string()))?;
     Ok(())
   }
+
+  /// Writes a data frame, counting its payload towards `bufferedAmount`
+  /// until it has been written.
+  async fn write_data_frame(
+    self: Rc<Self>,
+    frame: Frame,
+  ) -> Result<(), AnyError> {
+    let len = frame.payload.len();
+    self.buffered.set(self.buffered.get() + len);
+    let res = self.clone().write_frame(frame).await;
+    self.buffered.set(self.buffered.get() - len);
+    res
+  }
 }
 
 impl Resource for ServerWebSocket {
@@ -358,12 +453,7 @@ pub fn ws_create_server_stream(
   ws.set_auto_close(true);
   ws.set_auto_pong(true);
 
-  let ws_resource = ServerWebSocket {
-    ws: AsyncRefCell::new(FragmentCollector::new(ws)),
-    closed: Rc::new(Cell::new(false)),
-  };
-
-  let rid = state.resource_table.add(ws_resource);
+  let rid = state.resource_table.add(ServerWebSocket::new(ws));
   Ok(rid)
 }
 
@@ -394,7 +484,7 @@ pub async fn op_ws_send_binary(
     .resource_table
     .get::<ServerWebSocket>(rid)?;
   resource
-    .write_frame(Frame::new(true, OpCode::Binary, None, data.to_vec()))
+    .write_data_frame(Frame::new(true, OpCode::Binary, None, data.to_vec()))
     .await
 }
 
@@ -409,10 +499,19 @@ pub async fn op_ws_send_text(
     .resource_table
     .get::<ServerWebSocket>(rid)?;
   resource
-    .write_frame(Frame::new(true, OpCode::Text, None, data.into_bytes()))
+    .write_data_frame(Frame::new(true, OpCode::Text, None, data.into_bytes()))
     .await
 }
 
+#[op(fast)]
+pub fn op_ws_get_buffered_amount(state: &mut OpState, rid: ResourceId) -> u32 {
+  state
+    .resource_table
+    .get::<ServerWebSocket>(rid)
+    .map(|resource| resource.buffered.get().min(u32::MAX as usize) as u32)
+    .unwrap_or(0)
+}
+
 #[op]
 pub async fn op_ws_send_pong(
   state: Rc<RefCell<OpState>>,
@@ -429,13 +528,21 @@ pub async fn op_ws_send_pong(
 pub async fn op_ws_send_ping(
   state: Rc<RefCell<OpState>>,
   rid: ResourceId,
+  payload: Option<ZeroCopyBuf>,
 ) -> Result<(), AnyError> {
+  let payload = payload.map(|payload| payload.to_vec()).unwrap_or_default();
+  // Control frames can't be fragmented, which limits their payload
+  if payload.len() > 125 {
+    return Err(type_error(
+      "The ping payload may not be longer than 125 bytes",
+    ));
+  }
   let resource = state
     .borrow_mut()
     .resource_table
     .get::<ServerWebSocket>(rid)?;
   resource
-    .write_frame(Frame::new(true, OpCode::Ping, None, vec![]))
+    .write_frame(Frame::new(true, OpCode::Ping, None, payload))
     .await
 }
 
@@ -455,11 +562,27 @@ pub async fn op_ws_close(
     .unwrap_or_else(|| Frame::close_raw(vec![]));
 
   let cell = Rc::clone(&resource.closed);
-  cell.set(true);
+  if cell.replace(true) {
+    // The close frame was already sent
+    return Ok(());
+  }
   resource.write_frame(frame).await?;
   Ok(())
 }
 
+/// Sets the limits for the messages received by a socket. This must be
+/// called before the first message is read.
+#[op]
+pub fn op_ws_set_limits(
+  state: &mut OpState,
+  rid: ResourceId,
+  limits: WsLimits,
+) -> Result<(), AnyError> {
+  let resource = state.resource_table.get::<ServerWebSocket>(rid)?;
+  resource.limits.set(limits);
+  Ok(())
+}
+
 #[op(fast)]
 pub async fn op_ws_next_event(
   state: Rc<RefCell<OpState>>,
@@ -471,9 +594,20 @@ pub async fn op_ws_next_event(
     .get::<ServerWebSocket>(rid)?;
 
   let mut ws = RcRef::map(&resource, |r| &r.ws).borrow_mut().await;
+  let limits = resource.limits.get();
+  // Frames larger than the limits are rejected before they are read
+  ws.set_max_message_size(limits.frame_limit().saturating_add(1));
   loop {
-    let val = match ws.read_frame().await {
-      Ok(val) => val,
+    let frame = match ws.read_frame().await {
+      Ok(frame) => {
+        let frame = match &mut *resource.inflater.borrow_mut() {
+          Some(inflater) => inflater.inflate(frame, limits.max_message_size()),
+          None => Ok(frame),
+        };
+        frame
+          .and_then(|frame| resource.messages.borrow_mut().push(frame, &limits))
+      }
+      Err(WebSocketError::FrameTooLarge) => Err(MessageError::TooBig),
       Err(err) => {
         // No message was received, socket closed while we waited.
         // Try close the stream, ignoring any errors, and report closed status to JavaScript.
@@ -491,6 +625,22 @@ pub async fn op_ws_next_event(
         ));
       }
     };
+    let val = match frame {
+      Ok(Some(val)) => val,
+      Ok(None) => continue,
+      Err(MessageError::TooBig) => {
+        const REASON: &str = "Message too big";
+        resource.closed.set(true);
+        _ = ws.write_frame(Frame::close(1009, REASON.as_bytes())).await;
+        return Ok((1009, StringOrBuffer::String(REASON.to_string())));
+      }
+      Err(MessageError::Invalid(err)) => {
+        return Ok((
+          MessageKind::Error as u16,
+          StringOrBuffer::String(err.to_string()),
+        ));
+      }
+    };
 
     break Ok(match val.opcode {
       OpCode::Text => (
@@ -513,7 +663,7 @@ pub async fn op_ws_next_event(
       }
       OpCode::Pong => (
         MessageKind::Pong as u16,
-        StringOrBuffer::Buffer(vec![].into()),
+        StringOrBuffer::Buffer(val.payload.into()),
       ),
       OpCode::Continuation | OpCode::Ping => {
         continue;
@@ -529,12 +679,14 @@ deno_core::extension!(deno_websocket,
     op_ws_check_permission_and_cancel_handle<P>,
     op_ws_create<P>,
     op_ws_close,
+    op_ws_get_buffered_amount,
     op_ws_next_event,
     op_ws_send_binary,
     op_ws_send_text,
     op_ws_send_ping,
     op_ws_send_pong,
     op_ws_server_create,
+    op_ws_set_limits,
   ],
   esm = [ "01_websocket.js", "02_websocketstream.js" ],
   options = {
@@ -0,0 +1,255 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Turns the data frames read from a WebSocket into messages, enforcing the
+//! size limits of the socket while fragmented messages are being received.
+
+use fastwebsockets::Frame;
+use fastwebsockets::OpCode;
+use serde::Deserialize;
+
+/// The default limit for the size of a single frame, which matches the
+/// limit `fastwebsockets` applies by default.
+const DEFAULT_MAX_FRAME_SIZE: usize = 64 << 20;
+
+/// Per socket limits for incoming messages.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WsLimits {
+  max_frame_size: Option<usize>,
+  max_message_size: Option<usize>,
+  reassemble_fragments: bool,
+}
+
+impl Default for WsLimits {
+  fn default() -> Self {
+    Self {
+      max_frame_size: None,
+      max_message_size: None,
+      reassemble_fragments: true,
+    }
+  }
+}
+
+impl WsLimits {
+  pub fn max_message_size(&self) -> Option<usize> {
+    self.max_message_size
+  }
+
+  /// The largest frame payload that may be read. Frames are rejected based on
+  /// their header, before their payload is allocated.
+  pub fn frame_limit(&self) -> usize {
+    let max_frame_size = self.max_frame_size.unwrap_or(DEFAULT_MAX_FRAME_SIZE);
+    match self.max_message_size {
+      Some(max_message_size) => max_frame_size.min(max_message_size),
+      None => max_frame_size,
+    }
+  }
+}
+
+#[derive(Debug)]
+pub enum MessageError {
+  /// The message is larger than the socket's `maxMessageSize`.
+  TooBig,
+  Invalid(&'static str),
+}
+
+#[derive(Default)]
+pub struct MessageCollector {
+  /// The opcode of the fragmented message being received.
+  opcode: Option<OpCode>,
+  /// The number of payload bytes of the fragmented message received so far.
+  received: usize,
+  /// The fragments collected so far when reassembling, or else the trailing
+  /// bytes of an incomplete UTF-8 sequence of a text message.
+  buffer: Vec<u8>,
+}
+
+impl MessageCollector {
+  /// Feeds a frame that was read from the socket, returning the frame to
+  /// deliver to JavaScript, if any. Control frames are passed through as is.
+  pub fn push(
+    &mut self,
+    frame: Frame,
+    limits: &WsLimits,
+  ) -> Result<Option<Frame>, MessageError> {
+    let opcode = match frame.opcode {
+      OpCode::Text | OpCode::Binary => {
+        if self.opcode.is_some() {
+          return Err(MessageError::Invalid("Invalid fragment"));
+        }
+        if frame.fin {
+          self.check_size(frame.payload.len(), limits)?;
+          return Ok(Some(frame));
+        }
+        self.opcode = Some(frame.opcode);
+        frame.opcode
+      }
+      OpCode::Continuation => match self.opcode {
+        Some(opcode) => opcode,
+        None => {
+          return Err(MessageError::Invalid("Invalid continuation frame"))
+        }
+      },
+      _ => return Ok(Some(frame)),
+    };
+
+    self.check_size(self.received + frame.payload.len(), limits)?;
+    self.received += frame.payload.len();
+    let fin = frame.fin;
+    if fin {
+      self.opcode = None;
+      self.received = 0;
+    }
+
+    if limits.reassemble_fragments {
+      self.buffer.extend_from_slice(&frame.payload);
+      if !fin {
+        return Ok(None);
+      }
+      let payload = std::mem::take(&mut self.buffer);
+      if opcode == OpCode::Text && std::str::from_utf8(&payload).is_err() {
+        return Err(MessageError::Invalid("Invalid UTF-8"));
+      }
+      return Ok(Some(Frame::new(true, opcode, None, payload)));
+    }
+
+    // Fragments are delivered as they arrive. Text fragments are split at the
+    // last complete character, so that each of them is valid UTF-8.
+    let mut payload = frame.payload;
+    if opcode == OpCode::Text {
+      if !self.buffer.is_empty() {
+        payload.splice(0..0, std::mem::take(&mut self.buffer));
+      }
+      if let Err(err) = std::str::from_utf8(&payload) {
+        if err.error_len().is_some() || fin {
+          return Err(MessageError::Invalid("Invalid UTF-8"));
+        }
+        self.buffer = payload.split_off(err.valid_up_to());
+      }
+      if payload.is_empty() {
+        return Ok(None);
+      }
+    }
+    Ok(Some(Frame::new(true, opcode, None, payload)))
+  }
+
+  fn check_size(
+    &self,
+    size: usize,
+    limits: &WsLimits,
+  ) -> Result<(), MessageError> {
+    match limits.max_message_size {
+      Some(max_message_size) if size > max_message_size => {
+        Err(MessageError::TooBig)
+      }
+      _ => Ok(()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn frame(fin: bool, opcode: OpCode, payload: &[u8]) -> Frame {
+    Frame::new(fin, opcode, None, payload.to_vec())
+  }
+
+  fn limits(
+    max_message_size: Option<usize>,
+    reassemble_fragments: bool,
+  ) -> WsLimits {
+    WsLimits {
+      max_frame_size: None,
+      max_message_size,
+      reassemble_fragments,
+    }
+  }
+
+  #[test]
+  fn test_reassemble() {
+    let limits = limits(None, true);
+    let mut collector = MessageCollector::default();
+    let text = "héllo".as_bytes();
+    assert!(collector
+      .push(frame(false, OpCode::Text, &text[..2]), &limits)
+      .unwrap()
+      .is_none());
+    let message = collector
+      .push(frame(true, OpCode::Continuation, &text[2..]), &limits)
+      .unwrap()
+      .unwrap();
+    assert_eq!(message.opcode, OpCode::Text);
+    assert_eq!(message.payload, text);
+
+    let pong = collector
+      .push(frame(true, OpCode::Pong, b""), &limits)
+      .unwrap()
+      .unwrap();
+    assert_eq!(pong.opcode, OpCode::Pong);
+  }
+
+  #[test]
+  fn test_max_message_size() {
+    let limits = limits(Some(4), true);
+    let mut collector = MessageCollector::default();
+    assert!(matches!(
+      collector.push(frame(true, OpCode::Binary, b"12345"), &limits),
+      Err(MessageError::TooBig)
+    ));
+    let mut collector = MessageCollector::default();
+    collector
+      .push(frame(false, OpCode::Binary, b"123"), &limits)
+      .unwrap();
+    assert!(matches!(
+      collector.push(frame(true, OpCode::Continuation, b"45"), &limits),
+      Err(MessageError::TooBig)
+    ));
+  }
+
+  #[test]
+  fn test_no_reassembly() {
+    let limits = limits(None, false);
+    let mut collector = MessageCollector::default();
+    let text = "héllo".as_bytes();
+    // The first fragment ends in the middle of "é"
+    let first = collector
+      .push(frame(false, OpCode::Text, &text[..2]), &limits)
+      .unwrap()
+      .unwrap();
+    assert_eq!(first.payload, b"h");
+    let second = collector
+      .push(frame(true, OpCode::Continuation, &text[2..]), &limits)
+      .unwrap()
+      .unwrap();
+    assert_eq!(second.opcode, OpCode::Text);
+    assert_eq!(second.payload, "éllo".as_bytes());
+
+    let binary = collector
+      .push(frame(false, OpCode::Binary, b"12"), &limits)
+      .unwrap()
+      .unwrap();
+    assert_eq!(binary.payload, b"12");
+  }
+
+  #[test]
+  fn test_invalid() {
+    let limits = WsLimits::default();
+    let mut collector = MessageCollector::default();
+    assert!(collector
+      .push(frame(true, OpCode::Continuation, b""), &limits)
+      .is_err());
+    collector
+      .push(frame(false, OpCode::Binary, b""), &limits)
+      .unwrap();
+    assert!(collector
+      .push(frame(true, OpCode::Binary, b""), &limits)
+      .is_err());
+  }
+
+  #[test]
+  fn test_frame_limit() {
+    assert_eq!(WsLimits::default().frame_limit(), DEFAULT_MAX_FRAME_SIZE);
+    assert_eq!(limits(Some(1024), true).frame_limit(), 1024);
+  }
+}
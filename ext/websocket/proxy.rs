@@ -0,0 +1,463 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Tunnels WebSocket client connections through an HTTP (`CONNECT`) or SOCKS5
+//! proxy, either given explicitly or picked up from the `HTTP_PROXY`,
+//! `HTTPS_PROXY` and `NO_PROXY` environment variables.
+
+use deno_core::error::generic_error;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::url::Url;
+use deno_tls::no_proxy_matches;
+use deno_tls::BasicAuth;
+use deno_tls::Proxy;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::lookup_host;
+use tokio::net::TcpStream;
+
+/// The longest proxy response head that is accepted for a `CONNECT` request.
+const MAX_CONNECT_RESPONSE_SIZE: usize = 8 * 1024;
+
+/// Looks up the proxy for a connection to `host` from the environment. `ws:`
+/// URLs use `HTTP_PROXY` and `wss:` URLs use `HTTPS_PROXY`, unless the host is
+/// excluded by `NO_PROXY`.
+pub fn proxy_from_env(secure: bool, host: &str) -> Option<Proxy> {
+  proxy_from(secure, host, |name| {
+    std::env::var(name)
+      .or_else(|_| std::env::var(name.to_ascii_lowercase()))
+      .ok()
+      .filter(|value| !value.is_empty())
+  })
+}
+
+fn proxy_from(
+  secure: bool,
+  host: &str,
+  var: impl Fn(&str) -> Option<String>,
+) -> Option<Proxy> {
+  let url = var(if secure { "HTTPS_PROXY" } else { "HTTP_PROXY" })?;
+  if let Some(no_proxy) = var("NO_PROXY") {
+    if no_proxy_matches(&no_proxy, host) {
+      return None;
+    }
+  }
+  Some(Proxy {
+    url,
+    basic_auth: None,
+    no_proxy: vec![],
+  })
+}
+
+/// The url of a proxy, which needs net permission to `host:port` to be
+/// connected to.
+pub fn proxy_url(proxy: &Proxy) -> Result<Url, AnyError> {
+  Url::parse(&proxy.url)
+    .map_err(|err| type_error(format!("Invalid proxy url: {err}")))
+}
+
+/// Opens a TCP connection to `host:port`, through `proxy` if there is one and
+/// it isn't bypassed for the host. The addresses that the host resolves to
+/// are passed through `check_resolved` before they are connected to, directly
+/// or with a `socks5` proxy, which unlike `socks5h` is given the address of
+/// the host instead of its name.
+pub async fn connect(
+  proxy: Option<&Proxy>,
+  host: &str,
+  port: u16,
+  check_resolved: impl FnOnce(&[SocketAddr]) -> Result<(), AnyError>,
+) -> Result<TcpStream, AnyError> {
+  let proxy = proxy.filter(|proxy| !proxy.is_excluded(host));
+  let Some(proxy) = proxy else {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs = lookup_host((host, port)).await?.collect::<Vec<_>>();
+    check_resolved(&addrs)?;
+    return Ok(TcpStream::connect(&*addrs).await?);
+  };
+  let url = proxy_url(proxy)?;
+  let proxy_host = url
+    .host_str()
+    .ok_or_else(|| type_error("Invalid proxy url: missing host"))?;
+  let proxy_host = proxy_host.trim_start_matches('[').trim_end_matches(']');
+  // Credentials can be given explicitly or as part of the proxy url
+  let basic_auth = proxy.basic_auth.clone().or_else(|| {
+    (!url.username().is_empty()).then(|| BasicAuth {
+      username: percent_decode(url.username()),
+      password: percent_decode(url.password().unwrap_or_default()),
+    })
+  });
+  match url.scheme() {
+    "http" => {
+      let proxy_port = url.port().unwrap_or(80);
+      let mut stream = TcpStream::connect((proxy_host, proxy_port)).await?;
+      http_connect(&mut stream, host, port, basic_auth).await?;
+      Ok(stream)
+    }
+    "socks5" => {
+      let target = host.trim_start_matches('[').trim_end_matches(']');
+      let addrs = lookup_host((target, port)).await?.collect::<Vec<_>>();
+      check_resolved(&addrs)?;
+      let addr = addrs
+        .first()
+        .ok_or_else(|| generic_error(format!("Failed to resolve {host}")))?;
+      let proxy_port = url.port().unwrap_or(1080);
+      let mut stream = TcpStream::connect((proxy_host, proxy_port)).await?;
+      socks5_connect(&mut stream, &addr.ip().to_string(), port, basic_auth)
+        .await?;
+      Ok(stream)
+    }
+    "socks5h" => {
+      let proxy_port = url.port().unwrap_or(1080);
+      let mut stream = TcpStream::connect((proxy_host, proxy_port)).await?;
+      socks5_connect(&mut stream, host, port, basic_auth).await?;
+      Ok(stream)
+    }
+    scheme => Err(type_error(format!(
+      "Unsupported proxy scheme \"{scheme}\", expected http, socks5 or socks5h"
+    ))),
+  }
+}
+
+fn percent_decode(value: &str) -> String {
+  percent_encoding::percent_decode_str(value)
+    .decode_utf8_lossy()
+    .into_owned()
+}
+
+async fn http_connect(
+  stream: &mut TcpStream,
+  host: &str,
+  port: u16,
+  basic_auth: Option<BasicAuth>,
+) -> Result<(), AnyError> {
+  let authority = match host.parse::<IpAddr>() {
+    Ok(IpAddr::V6(_)) => format!("[{host}]:{port}"),
+    _ => format!("{host}:{port}"),
+  };
+  let mut request =
+    format!("CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n");
+  if let Some(BasicAuth { username, password }) = basic_auth {
+    let credentials = base64::encode(format!("{username}:{password}"));
+    request.push_str(&format!("Proxy-Authorization: Basic {credentials}\r\n"));
+  }
+  request.push_str("\r\n");
+  stream.write_all(request.as_bytes()).await?;
+
+  // Read the response head one byte at a time, so that none of the tunneled
+  // data is consumed
+  let mut head = Vec::new();
+  while !head.ends_with(b"\r\n\r\n") {
+    if head.len() == MAX_CONNECT_RESPONSE_SIZE {
+      return Err(generic_error("Proxy response is too large"));
+    }
+    let byte = stream.read_u8().await.map_err(|_| {
+      generic_error("Proxy closed the connection before responding")
+    })?;
+    head.push(byte);
+  }
+  let status_line = head
+    .split(|b| *b == b'\n')
+    .next()
+    .map(|line| String::from_utf8_lossy(line).trim().to_string())
+    .unwrap_or_default();
+  match status_line.split(' ').nth(1) {
+    Some(status) if status.starts_with('2') => Ok(()),
+    Some("407") => Err(generic_error("Proxy authentication required")),
+    _ => Err(generic_error(format!(
+      "Proxy refused to connect: {status_line}"
+    ))),
+  }
+}
+
+async fn socks5_connect(
+  stream: &mut TcpStream,
+  host: &str,
+  port: u16,
+  basic_auth: Option<BasicAuth>,
+) -> Result<(), AnyError> {
+  const VERSION: u8 = 0x05;
+  const NO_AUTH: u8 = 0x00;
+  const USERNAME_PASSWORD: u8 = 0x02;
+
+  let method = if basic_auth.is_some() {
+    USERNAME_PASSWORD
+  } else {
+    NO_AUTH
+  };
+  stream.write_all(&[VERSION, 1, method]).await?;
+  let mut reply = [0u8; 2];
+  stream.read_exact(&mut reply).await?;
+  if reply[0] != VERSION || reply[1] != method {
+    return Err(generic_error(
+      "SOCKS5 proxy rejected the authentication method",
+    ));
+  }
+
+  if let Some(BasicAuth { username, password }) = basic_auth {
+    if username.len() > 255 || password.len() > 255 {
+      return Err(type_error(
+        "SOCKS5 proxy credentials must be at most 255 bytes",
+      ));
+    }
+    let mut request = vec![0x01, username.len() as u8];
+    request.extend_from_slice(username.as_bytes());
+    request.push(password.len() as u8);
+    request.extend_from_slice(password.as_bytes());
+    stream.write_all(&request).await?;
+    stream.read_exact(&mut reply).await?;
+    if reply[1] != 0x00 {
+      return Err(generic_error("SOCKS5 proxy authentication failed"));
+    }
+  }
+
+  // CONNECT, with the destination resolved by the proxy unless it is an IP
+  let mut request = vec![VERSION, 0x01, 0x00];
+  match host.trim_start_matches('[').trim_end_matches(']').parse() {
+    Ok(IpAddr::V4(ip)) => {
+      request.push(0x01);
+      request.extend_from_slice(&ip.octets());
+    }
+    Ok(IpAddr::V6(ip)) => {
+      request.push(0x04);
+      request.extend_from_slice(&ip.octets());
+    }
+    Err(_) => {
+      if host.len() > 255 {
+        return Err(type_error("Hostname is too long for a SOCKS5 proxy"));
+      }
+      request.push(0x03);
+      request.push(host.len() as u8);
+      request.extend_from_slice(host.as_bytes());
+    }
+  }
+  request.extend_from_slice(&port.to_be_bytes());
+  stream.write_all(&request).await?;
+
+  let mut reply = [0u8; 4];
+  stream.read_exact(&mut reply).await?;
+  if reply[1] != 0x00 {
+    return Err(generic_error(format!(
+      "SOCKS5 proxy refused to connect (reply {})",
+      reply[1]
+    )));
+  }
+  // Skip the bound address
+  let address_len = match reply[3] {
+    0x01 => 4,
+    0x04 => 16,
+    0x03 => stream.read_u8().await? as usize,
+    _ => return Err(generic_error("Invalid SOCKS5 proxy reply")),
+  };
+  let mut bound = vec![0u8; address_len + 2];
+  stream.read_exact(&mut bound).await?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tokio::net::TcpListener;
+
+  fn env<'a>(
+    vars: &'a [(&'a str, &'a str)],
+  ) -> impl Fn(&str) -> Option<String> + 'a {
+    |name| {
+      vars
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, v)| v.to_string())
+    }
+  }
+
+  #[test]
+  fn test_proxy_from_env() {
+    let vars = [
+      ("HTTP_PROXY", "http://proxy:3128"),
+      ("HTTPS_PROXY", "socks5://proxy:1080"),
+      ("NO_PROXY", "localhost, .internal.com"),
+    ];
+    let proxy = proxy_from(false, "deno.land", env(&vars)).unwrap();
+    assert_eq!(proxy.url, "http://proxy:3128");
+    let proxy = proxy_from(true, "deno.land", env(&vars)).unwrap();
+    assert_eq!(proxy.url, "socks5://proxy:1080");
+    assert!(proxy_from(false, "localhost", env(&vars)).is_none());
+    assert!(proxy_from(true, "api.internal.com", env(&vars)).is_none());
+    assert!(proxy_from(true, "internal.com", env(&vars)).is_none());
+    assert!(proxy_from(true, "notinternal.com", env(&vars)).is_some());
+    assert!(proxy_from(true, "deno.land", env(&[])).is_none());
+  }
+
+  #[tokio::test]
+  async fn test_http_connect() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let proxy = tokio::spawn(async move {
+      let (mut stream, _) = listener.accept().await.unwrap();
+      let mut buf = vec![0u8; 1024];
+      let n = stream.read(&mut buf).await.unwrap();
+      let request = String::from_utf8_lossy(&buf[..n]).to_string();
+      stream
+        .write_all(b"HTTP/1.1 200 Connection established\r\n\r\nhello")
+        .await
+        .unwrap();
+      request
+    });
+
+    let proxy_config = Proxy {
+      url: format!("http://user:p%40ss@{addr}"),
+      basic_auth: None,
+      no_proxy: vec![],
+    };
+    let mut stream =
+      connect(Some(&proxy_config), "example.com", 443, |_| unreachable!())
+        .await
+        .unwrap();
+    let mut tunneled = [0u8; 5];
+    stream.read_exact(&mut tunneled).await.unwrap();
+    assert_eq!(&tunneled, b"hello");
+
+    let request = proxy.await.unwrap();
+    assert!(request.starts_with("CONNECT example.com:443 HTTP/1.1\r\n"));
+    let credentials = base64::encode("user:p@ss");
+    assert!(
+      request.contains(&format!("Proxy-Authorization: Basic {credentials}"))
+    );
+  }
+
+  #[tokio::test]
+  async fn test_no_proxy_connect() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let proxy_config = Proxy {
+      url: "http://127.0.0.1:1".to_string(),
+      basic_auth: None,
+      no_proxy: vec!["127.0.0.1".to_string()],
+    };
+    let mut checked = vec![];
+    let stream =
+      connect(Some(&proxy_config), "127.0.0.1", addr.port(), |addrs| {
+        checked.extend_from_slice(addrs);
+        Ok(())
+      })
+      .await
+      .unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+    assert_eq!(checked, vec![addr]);
+  }
+
+  #[tokio::test]
+  async fn test_http_connect_refused() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+      let (mut stream, _) = listener.accept().await.unwrap();
+      let mut buf = vec![0u8; 1024];
+      _ = stream.read(&mut buf).await.unwrap();
+      stream
+        .write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n")
+        .await
+        .unwrap();
+    });
+
+    let proxy_config = Proxy {
+      url: format!("http://{addr}"),
+      basic_auth: None,
+      no_proxy: vec![],
+    };
+    let err =
+      connect(Some(&proxy_config), "example.com", 80, |_| unreachable!())
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Proxy authentication required");
+  }
+
+  #[tokio::test]
+  async fn test_socks5h_connect() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let proxy = tokio::spawn(async move {
+      let (mut stream, _) = listener.accept().await.unwrap();
+      let mut greeting = [0u8; 3];
+      stream.read_exact(&mut greeting).await.unwrap();
+      assert_eq!(greeting, [0x05, 1, 0x02]);
+      stream.write_all(&[0x05, 0x02]).await.unwrap();
+
+      let mut auth = [0u8; 2 + 4 + 1 + 6];
+      stream.read_exact(&mut auth).await.unwrap();
+      assert_eq!(&auth, b"\x01\x04deno\x06secret");
+      stream.write_all(&[0x01, 0x00]).await.unwrap();
+
+      let mut request = [0u8; 5 + 11 + 2];
+      stream.read_exact(&mut request).await.unwrap();
+      assert_eq!(&request[..5], &[0x05, 0x01, 0x00, 0x03, 11]);
+      assert_eq!(&request[5..16], b"example.com");
+      assert_eq!(u16::from_be_bytes([request[16], request[17]]), 443);
+      stream
+        .write_all(&[0x05, 0x00, 0x00, 0x01, 127, 0, 0, 1, 0, 80])
+        .await
+        .unwrap();
+      stream.write_all(b"hello").await.unwrap();
+    });
+
+    let proxy_config = Proxy {
+      url: format!("socks5h://{addr}"),
+      basic_auth: Some(BasicAuth {
+        username: "deno".to_string(),
+        password: "secret".to_string(),
+      }),
+      no_proxy: vec![],
+    };
+    let mut stream =
+      connect(Some(&proxy_config), "example.com", 443, |_| unreachable!())
+        .await
+        .unwrap();
+    let mut tunneled = [0u8; 5];
+    stream.read_exact(&mut tunneled).await.unwrap();
+    assert_eq!(&tunneled, b"hello");
+    proxy.await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_socks5_connect_resolves_locally() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let proxy = tokio::spawn(async move {
+      let (mut stream, _) = listener.accept().await.unwrap();
+      let mut greeting = [0u8; 3];
+      stream.read_exact(&mut greeting).await.unwrap();
+      stream.write_all(&[0x05, 0x00]).await.unwrap();
+
+      let mut request = [0u8; 4 + 4 + 2];
+      stream.read_exact(&mut request).await.unwrap();
+      assert_eq!(&request[..8], &[0x05, 0x01, 0x00, 0x01, 127, 0, 0, 1]);
+      stream
+        .write_all(&[0x05, 0x00, 0x00, 0x01, 127, 0, 0, 1, 0, 80])
+        .await
+        .unwrap();
+    });
+
+    let proxy_config = Proxy {
+      url: format!("socks5://{addr}"),
+      basic_auth: None,
+      no_proxy: vec![],
+    };
+    let mut checked = vec![];
+    connect(Some(&proxy_config), "127.0.0.1", 443, |addrs| {
+      checked.extend_from_slice(addrs);
+      Ok(())
+    })
+    .await
+    .unwrap();
+    proxy.await.unwrap();
+    assert_eq!(checked, vec!["127.0.0.1:443".parse().unwrap()]);
+
+    let err = connect(Some(&proxy_config), "127.0.0.1", 443, |_| {
+      Err(generic_error("denied"))
+    })
+    .await
+    .unwrap_err();
+    assert_eq!(err.to_string(), "denied");
+  }
+}
@@ -1,4 +1,5 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+use crate::deflate::Rsv1Tracker;
 use bytes::Buf;
 use bytes::Bytes;
 use deno_net::raw::NetworkStream;
@@ -18,6 +19,8 @@ pub(crate) enum WsStreamKind {
 pub(crate) struct WebSocketStream {
   stream: WsStreamKind,
   pre: Option<Bytes>,
+  /// Set when permessage-deflate was negotiated.
+  rsv1_tracker: Option<Rsv1Tracker>,
 }
 
 impl WebSocketStream {
@@ -25,16 +28,38 @@ impl WebSocketStream {
     Self {
       stream,
       pre: buffer,
+      rsv1_tracker: None,
     }
   }
+
+  pub fn with_rsv1_tracker(mut self, tracker: Rsv1Tracker) -> Self {
+    self.rsv1_tracker = Some(tracker);
+    self
+  }
 }
 
 impl AsyncRead for WebSocketStream {
-  // From hyper's Rewind (https://github.com/hyperium/hyper), MIT License, Copyright (c) Sean McArthur
   fn poll_read(
     mut self: Pin<&mut Self>,
     cx: &mut std::task::Context<'_>,
     buf: &mut ReadBuf<'_>,
+  ) -> Poll<std::io::Result<()>> {
+    let filled = buf.filled().len();
+    let res = self.as_mut().poll_read_inner(cx, buf);
+    if let (Poll::Ready(Ok(())), Some(tracker)) = (&res, &mut self.rsv1_tracker)
+    {
+      tracker.process(&mut buf.filled_mut()[filled..]);
+    }
+    res
+  }
+}
+
+impl WebSocketStream {
+  // From hyper's Rewind (https://github.com/hyperium/hyper), MIT License, Copyright (c) Sean McArthur
+  fn poll_read_inner(
+    mut self: Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+    buf: &mut ReadBuf<'_>,
   ) -> Poll<std::io::Result<()>> {
     if let Some(mut prefix) = self.pre.take() {
       // If there are no remaining bytes, let the bytes get dropped.
@@ -76,6 +76,14 @@ mod startup_snapshot {
     ) -> Result<(), deno_core::error::AnyError> {
       unreachable!("snapshotting!")
     }
+
+    fn check_write(
+      &mut self,
+      _p: &Path,
+      _api_name: &str,
+    ) -> Result<(), deno_core::error::AnyError> {
+      unreachable!("snapshotting!")
+    }
   }
 
   impl deno_websocket::WebSocketPermissions for Permissions {
@@ -86,6 +94,15 @@ mod startup_snapshot {
     ) -> Result<(), deno_core::error::AnyError> {
       unreachable!("snapshotting!")
     }
+
+    fn check_net_resolved<T: AsRef<str>>(
+      &mut self,
+      _host: &(T, Option<u16>),
+      _addrs: &[std::net::SocketAddr],
+      _api_name: &str,
+    ) -> Result<(), deno_core::error::AnyError> {
+      unreachable!("snapshotting!")
+    }
   }
 
   impl deno_web::TimersPermission for Permissions {
@@ -102,6 +119,7 @@ mod startup_snapshot {
     }
   }
 
+  #[cfg(feature = "ffi")]
   impl deno_ffi::FfiPermissions for Permissions {
     fn check(
       &mut self,
@@ -135,6 +153,32 @@ mod startup_snapshot {
       unreachable!("snapshotting!")
     }
 
+    fn check_net_unresolved<T: AsRef<str>>(
+      &mut self,
+      _host: &(T, Option<u16>),
+      _api_name: &str,
+    ) -> Result<(), deno_core::error::AnyError> {
+      unreachable!("snapshotting!")
+    }
+
+    fn check_net_resolved<T: AsRef<str>>(
+      &mut self,
+      _host: &(T, Option<u16>),
+      _addrs: &[std::net::SocketAddr],
+      _api_name: &str,
+    ) -> Result<(), deno_core::error::AnyError> {
+      unreachable!("snapshotting!")
+    }
+
+    fn check_vsock(
+      &mut self,
+      _cid: u32,
+      _port: u32,
+      _api_name: &str,
+    ) -> Result<(), deno_core::error::AnyError> {
+      unreachable!("snapshotting!")
+    }
+
     fn check_read(
       &mut self,
       _p: &Path,
@@ -196,6 +240,7 @@ mod startup_snapshot {
     }
   }
 
+  #[cfg(feature = "kv")]
   impl deno_kv::sqlite::SqliteDbHandlerPermissions for Permissions {
     fn check_read(
       &mut self,
@@ -214,6 +259,43 @@ mod startup_snapshot {
     }
   }
 
+  // the extensions in place of the ones that are left out of the runtime, see
+  // `optional_extensions.rs`
+  #[cfg(not(feature = "ffi"))]
+  deno_core::extension!(deno_ffi,
+    esm = [ dir "js/lite", "00_ffi.js" ],
+  );
+
+  #[cfg(not(feature = "kv"))]
+  deno_core::extension!(deno_kv,
+    esm = [ dir "js/lite", "01_db.ts" ],
+  );
+
+  fn deno_ffi_init_ops_and_esm() -> Extension {
+    #[cfg(feature = "ffi")]
+    {
+      deno_ffi::deno_ffi::init_ops_and_esm::<Permissions>(false)
+    }
+    #[cfg(not(feature = "ffi"))]
+    {
+      deno_ffi::init_ops_and_esm()
+    }
+  }
+
+  fn deno_kv_init_ops_and_esm() -> Extension {
+    #[cfg(feature = "kv")]
+    {
+      deno_kv::deno_kv::init_ops_and_esm(
+        deno_kv::sqlite::SqliteDbHandler::<Permissions>::new(None),
+        false, // No --unstable
+      )
+    }
+    #[cfg(not(feature = "kv"))]
+    {
+      deno_kv::init_ops_and_esm()
+    }
+  }
+
   deno_core::extension!(runtime,
     deps = [
       deno_webidl,
@@ -308,16 +390,13 @@ mod startup_snapshot {
         deno_broadcast_channel::InMemoryBroadcastChannel::default(),
         false, // No --unstable.
       ),
-      deno_ffi::deno_ffi::init_ops_and_esm::<Permissions>(false),
+      deno_ffi_init_ops_and_esm(),
       deno_net::deno_net::init_ops_and_esm::<Permissions>(
         None, false, // No --unstable.
         None,
       ),
       deno_tls::deno_tls::init_ops_and_esm(),
-      deno_kv::deno_kv::init_ops_and_esm(
-        deno_kv::sqlite::SqliteDbHandler::<Permissions>::new(None),
-        false, // No --unstable
-      ),
+      deno_kv_init_ops_and_esm(),
       deno_napi::deno_napi::init_ops_and_esm::<Permissions>(),
       deno_http::deno_http::init_ops_and_esm(),
       deno_io::deno_io::init_ops_and_esm(Default::default()),
@@ -94,6 +94,12 @@ pub fn black_on_green<S: AsRef<str>>(s: S) -> impl fmt::Display {
   style(s, style_spec)
 }
 
+pub fn black_on_yellow<S: AsRef<str>>(s: S) -> impl fmt::Display {
+  let mut style_spec = ColorSpec::new();
+  style_spec.set_bg(Some(Yellow)).set_fg(Some(Black));
+  style(s, style_spec)
+}
+
 pub fn yellow<S: AsRef<str>>(s: S) -> impl fmt::Display {
   let mut style_spec = ColorSpec::new();
   style_spec.set_fg(Some(Yellow));
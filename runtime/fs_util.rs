@@ -3,6 +3,7 @@
 use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 pub use deno_core::normalize_path;
+pub use deno_fs::canonicalize_path_cached;
 use std::env::current_dir;
 use std::io::Error;
 use std::path::Path;
@@ -13,6 +14,36 @@ pub fn canonicalize_path(path: &Path) -> Result<PathBuf, Error> {
   Ok(deno_core::strip_unc_prefix(path.canonicalize()?))
 }
 
+/// Like `canonicalize_path()`, but for paths that may not exist: the longest
+/// of their ancestors that exists is canonicalized, and the rest of the path
+/// is appended to it.
+pub fn canonicalize_path_maybe_not_exists(
+  path: &Path,
+) -> Result<PathBuf, Error> {
+  let path = normalize_path(path);
+  let mut path = path.as_path();
+  let mut names_stack = Vec::new();
+  loop {
+    match canonicalize_path(path) {
+      Ok(mut canonicalized_path) => {
+        for name in names_stack.into_iter().rev() {
+          canonicalized_path = canonicalized_path.join(name);
+        }
+        return Ok(canonicalized_path);
+      }
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+        let (Some(parent), Some(name)) = (path.parent(), path.file_name())
+        else {
+          return Err(err);
+        };
+        names_stack.push(name);
+        path = parent;
+      }
+      Err(err) => return Err(err),
+    }
+  }
+}
+
 #[inline]
 pub fn resolve_from_cwd(path: &Path) -> Result<PathBuf, AnyError> {
   if path.is_absolute() {
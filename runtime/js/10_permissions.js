@@ -32,11 +32,12 @@ const illegalConstructorKey = Symbol("illegalConstructorKey");
  * @property {PermissionStatus} status
  */
 
-/** @type {ReadonlyArray<"read" | "write" | "net" | "env" | "sys" | "run" | "ffi" | "hrtime">} */
+/** @type {ReadonlyArray<"read" | "write" | "net" | "vsock" | "env" | "sys" | "run" | "ffi" | "hrtime">} */
 const permissionNames = [
   "read",
   "write",
   "net",
+  "vsock",
   "env",
   "sys",
   "run",
@@ -129,6 +130,8 @@ function cache(desc, state) {
     key += `-${desc.path}&`;
   } else if (desc.name === "net" && desc.host) {
     key += `-${desc.host}&`;
+  } else if (desc.name === "vsock" && desc.cid !== undefined) {
+    key += `-${desc.cid}:${desc.port}&`;
   } else if (desc.name === "run" && desc.command) {
     key += `-${desc.command}&`;
   } else if (desc.name === "env" && desc.variable) {
@@ -266,7 +269,7 @@ function serializePermissions(permissions) {
       }
     }
     for (
-      const key of new SafeArrayIterator(["env", "hrtime", "net", "sys"])
+      const key of new SafeArrayIterator(["env", "hrtime", "net", "sys", "vsock"])
     ) {
       if (ArrayIsArray(permissions[key])) {
         serializedPermissions[key] = ArrayPrototypeSlice(permissions[key]);
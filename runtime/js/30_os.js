@@ -1,12 +1,14 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 const core = globalThis.Deno.core;
-const ops = core.ops;
+const { BadResourcePrototype, InterruptedPrototype, ops } = core;
 import { Event, EventTarget } from "ext:deno_web/02_event.js";
 const primordials = globalThis.__bootstrap.primordials;
 const {
   Error,
   FunctionPrototypeBind,
+  ObjectPrototypeIsPrototypeOf,
+  SymbolAsyncIterator,
   SymbolFor,
 } = primordials;
 
@@ -35,10 +37,63 @@ function systemMemoryInfo() {
   return ops.op_system_memory_info();
 }
 
+function getPriority(pid = 0) {
+  return ops.op_get_priority(pid);
+}
+
+function setPriority(pid, priority) {
+  return ops.op_set_priority(pid, priority);
+}
+
+function resourceUsage() {
+  return ops.op_resource_usage();
+}
+
 function networkInterfaces() {
   return ops.op_network_interfaces();
 }
 
+class NetworkChangesWatcher {
+  #rid = 0;
+
+  constructor() {
+    this.#rid = ops.op_network_changes_open();
+  }
+
+  get rid() {
+    return this.#rid;
+  }
+
+  async next() {
+    try {
+      const kind = await core.opAsync("op_network_changes_poll", this.#rid);
+      return kind
+        ? { value: { kind }, done: false }
+        : { value: undefined, done: true };
+    } catch (error) {
+      if (
+        ObjectPrototypeIsPrototypeOf(BadResourcePrototype, error) ||
+        ObjectPrototypeIsPrototypeOf(InterruptedPrototype, error)
+      ) {
+        return { value: undefined, done: true };
+      }
+      throw error;
+    }
+  }
+
+  close() {
+    core.close(this.#rid);
+  }
+
+  [SymbolAsyncIterator]() {
+    return this;
+  }
+}
+
+function watchNetworkChanges() {
+  return new NetworkChangesWatcher();
+}
+
 function gid() {
   return ops.op_gid();
 }
@@ -110,13 +165,17 @@ export {
   env,
   execPath,
   exit,
+  getPriority,
   gid,
   hostname,
   loadavg,
   networkInterfaces,
   osRelease,
   osUptime,
+  resourceUsage,
   setExitHandler,
+  setPriority,
   systemMemoryInfo,
   uid,
+  watchNetworkChanges,
 };
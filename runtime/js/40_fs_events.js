@@ -13,8 +13,21 @@ class FsWatcher {
   #rid = 0;
 
   constructor(paths, options) {
-    const { recursive } = options;
-    this.#rid = ops.op_fs_events_open({ recursive, paths });
+    const {
+      recursive = true,
+      include,
+      exclude,
+      extensions,
+      debounce,
+    } = options;
+    this.#rid = ops.op_fs_events_open({
+      recursive,
+      paths,
+      include,
+      exclude,
+      extensions,
+      debounce,
+    });
   }
 
   get rid() {
@@ -2,22 +2,39 @@
 
 const core = globalThis.Deno.core;
 const ops = core.ops;
+const { BadResourcePrototype, InterruptedPrototype } = core;
 const primordials = globalThis.__bootstrap.primordials;
 const {
   ArrayPrototypeMap,
+  ArrayPrototypePush,
   ArrayPrototypeSlice,
+  ArrayPrototypeSplice,
+  DataView,
+  DataViewPrototypeGetUint32,
+  DataViewPrototypeSetUint32,
   TypeError,
   ObjectEntries,
   SafeArrayIterator,
   String,
   ObjectPrototypeIsPrototypeOf,
+  PromisePrototypeCatch,
   PromisePrototypeThen,
+  PromiseResolve,
   SafePromiseAll,
+  SymbolAsyncIterator,
   SymbolFor,
   Symbol,
+  TypedArrayPrototypeGetBuffer,
+  TypedArrayPrototypeGetByteLength,
+  TypedArrayPrototypeGetByteOffset,
+  TypedArrayPrototypeSet,
+  TypedArrayPrototypeSubarray,
+  Uint8Array,
 } = primordials;
 import { FsFile } from "ext:deno_fs/30_fs.js";
 import { readAll } from "ext:deno_io/12_io.js";
+import { receivedResource } from "ext:deno_net/01_net.js";
+import DOMException from "ext:deno_web/01_dom_exception.js";
 import { assert, pathFromURL } from "ext:deno_web/00_infra.js";
 import * as abortSignal from "ext:deno_web/03_abort_signal.js";
 import {
@@ -158,6 +175,12 @@ function spawnChildInner(opFn, command, apiName, {
   stderr = "piped",
   signal = undefined,
   windowsRawArguments = false,
+  processGroup = false,
+  detached = false,
+  pty = false,
+  ipc = false,
+  limits = undefined,
+  cgroup = undefined,
 } = {}) {
   const child = opFn({
     cmd: pathFromURL(command),
@@ -171,6 +194,12 @@ function spawnChildInner(opFn, command, apiName, {
     stdout,
     stderr,
     windowsRawArguments,
+    processGroup,
+    detached,
+    pty: pty === true ? { columns: 80, rows: 24 } : pty || null,
+    ipc,
+    limits,
+    cgroup,
   }, apiName);
   return new ChildProcess(illegalConstructorKey, {
     ...child,
@@ -197,6 +226,228 @@ function collectOutput(readableStream) {
   return readableStreamCollectIntoUint8Array(readableStream);
 }
 
+// A frame is the length of the serialized message and the number of the
+// handles sent with it, as little endian u32s, followed by the message.
+const IPC_HEADER_SIZE = 8;
+const IPC_READ_SIZE = 64 * 1024;
+
+class IpcChannel {
+  #rid;
+  #readBuffer = new Uint8Array(0);
+  #receivedHandles = [];
+  #sendQueue = PromiseResolve();
+  #receivePromiseId = null;
+  #unrefed = false;
+
+  constructor(key = null, rid) {
+    if (key !== illegalConstructorKey) {
+      throw new TypeError("Illegal constructor.");
+    }
+
+    this.#rid = rid;
+  }
+
+  send(message, handles = []) {
+    if (core.build.os === "windows" && handles.length > 0) {
+      throw new TypeError("Sending handles over IPC is not supported on Windows");
+    }
+    const data = core.serialize(message, undefined, (err) => {
+      throw new DOMException(err, "DataCloneError");
+    });
+    const length = TypedArrayPrototypeGetByteLength(data);
+    const frame = new Uint8Array(IPC_HEADER_SIZE + length);
+    const view = new DataView(TypedArrayPrototypeGetBuffer(frame));
+    DataViewPrototypeSetUint32(view, 0, length, true);
+    DataViewPrototypeSetUint32(view, 4, handles.length, true);
+    TypedArrayPrototypeSet(frame, data, IPC_HEADER_SIZE);
+    const rids = ArrayPrototypeMap(handles, (handle) => handle.rid);
+
+    // The frames are sent one at a time, so that they aren't interleaved.
+    const sent = PromisePrototypeThen(
+      this.#sendQueue,
+      () => this.#sendFrame(frame, rids),
+    );
+    this.#sendQueue = PromisePrototypeCatch(sent, () => {});
+    return sent;
+  }
+
+  async #sendFrame(frame, rids) {
+    let offset = 0;
+    while (offset < TypedArrayPrototypeGetByteLength(frame)) {
+      if (core.build.os === "windows") {
+        offset += await core.write(
+          this.#rid,
+          TypedArrayPrototypeSubarray(frame, offset),
+        );
+        continue;
+      }
+      offset += await core.opAsync(
+        "op_net_send_fds_unix",
+        this.#rid,
+        TypedArrayPrototypeSubarray(frame, offset),
+        // The handles are attached to the first byte of the frame.
+        offset === 0 ? rids : [],
+      );
+    }
+  }
+
+  async receive() {
+    while (true) {
+      const message = this.#takeMessage();
+      if (message !== null) {
+        return message;
+      }
+
+      const buffer = new Uint8Array(IPC_READ_SIZE);
+      // Handles can't be received over the named pipe of Windows.
+      const isWindows = core.build.os === "windows";
+      const promise = isWindows
+        ? core.read(this.#rid, buffer)
+        : core.opAsync("op_net_recv_fds_unix", this.#rid, buffer);
+      this.#receivePromiseId = promise[promiseIdSymbol];
+      if (this.#unrefed) core.unrefOp(this.#receivePromiseId);
+      let nread, received = [];
+      try {
+        if (isWindows) {
+          nread = await promise;
+        } else {
+          ({ 0: nread, 1: received } = await promise);
+        }
+      } catch (error) {
+        if (
+          ObjectPrototypeIsPrototypeOf(BadResourcePrototype, error) ||
+          ObjectPrototypeIsPrototypeOf(InterruptedPrototype, error)
+        ) {
+          return null;
+        }
+        throw error;
+      } finally {
+        this.#receivePromiseId = null;
+      }
+      if (nread === 0 && received.length === 0) {
+        return null;
+      }
+
+      const readBuffer = new Uint8Array(
+        TypedArrayPrototypeGetByteLength(this.#readBuffer) + nread,
+      );
+      TypedArrayPrototypeSet(readBuffer, this.#readBuffer, 0);
+      TypedArrayPrototypeSet(
+        readBuffer,
+        TypedArrayPrototypeSubarray(buffer, 0, nread),
+        TypedArrayPrototypeGetByteLength(this.#readBuffer),
+      );
+      this.#readBuffer = readBuffer;
+      for (let i = 0; i < received.length; ++i) {
+        ArrayPrototypePush(
+          this.#receivedHandles,
+          receivedResource(received[i]),
+        );
+      }
+    }
+  }
+
+  #takeMessage() {
+    const buffer = this.#readBuffer;
+    const byteLength = TypedArrayPrototypeGetByteLength(buffer);
+    if (byteLength < IPC_HEADER_SIZE) {
+      return null;
+    }
+    const view = new DataView(
+      TypedArrayPrototypeGetBuffer(buffer),
+      TypedArrayPrototypeGetByteOffset(buffer),
+      byteLength,
+    );
+    const length = DataViewPrototypeGetUint32(view, 0, true);
+    const handleCount = DataViewPrototypeGetUint32(view, 4, true);
+    if (byteLength < IPC_HEADER_SIZE + length) {
+      return null;
+    }
+
+    const data = TypedArrayPrototypeSubarray(
+      buffer,
+      IPC_HEADER_SIZE,
+      IPC_HEADER_SIZE + length,
+    );
+    this.#readBuffer = TypedArrayPrototypeSubarray(
+      buffer,
+      IPC_HEADER_SIZE + length,
+    );
+    return {
+      message: core.deserialize(data),
+      handles: ArrayPrototypeSplice(this.#receivedHandles, 0, handleCount),
+    };
+  }
+
+  close() {
+    core.close(this.#rid);
+  }
+
+  ref() {
+    this.#unrefed = false;
+    if (this.#receivePromiseId !== null) {
+      core.refOp(this.#receivePromiseId);
+    }
+  }
+
+  unref() {
+    this.#unrefed = true;
+    if (this.#receivePromiseId !== null) {
+      core.unrefOp(this.#receivePromiseId);
+    }
+  }
+
+  async *[SymbolAsyncIterator]() {
+    while (true) {
+      const message = await this.receive();
+      if (message === null) {
+        return;
+      }
+      yield message;
+    }
+  }
+}
+
+let parentIpc = undefined;
+
+function getParentIpc() {
+  if (parentIpc === undefined) {
+    const rid = ops.op_ipc_parent_open();
+    parentIpc = rid === null
+      ? null
+      : new IpcChannel(illegalConstructorKey, rid);
+  }
+  return parentIpc;
+}
+
+class ChildPty {
+  #rid;
+
+  #readable;
+  get readable() {
+    return this.#readable;
+  }
+
+  #writable;
+  get writable() {
+    return this.#writable;
+  }
+
+  constructor(key = null, rid) {
+    if (key !== illegalConstructorKey) {
+      throw new TypeError("Illegal constructor.");
+    }
+
+    this.#rid = rid;
+    this.#readable = readableStreamForRidUnrefable(rid);
+    this.#writable = writableStreamForRid(rid);
+  }
+
+  resize(columns, rows) {
+    ops.op_pty_resize(this.#rid, { columns, rows });
+  }
+}
+
 class ChildProcess {
   #rid;
   #waitPromiseId;
@@ -207,6 +458,22 @@ class ChildProcess {
     return this.#pid;
   }
 
+  #ipc = null;
+  get ipc() {
+    if (this.#ipc == null) {
+      throw new TypeError("ipc is not enabled");
+    }
+    return this.#ipc;
+  }
+
+  #pty = null;
+  get pty() {
+    if (this.#pty == null) {
+      throw new TypeError("pty is not enabled");
+    }
+    return this.#pty;
+  }
+
   #stdin = null;
   get stdin() {
     if (this.#stdin == null) {
@@ -240,6 +507,8 @@ class ChildProcess {
     stdinRid,
     stdoutRid,
     stderrRid,
+    ptyRid,
+    ipcRid,
   } = null) {
     if (key !== illegalConstructorKey) {
       throw new TypeError("Illegal constructor.");
@@ -262,6 +531,14 @@ class ChildProcess {
       this.#stderr = readableStreamForRidUnrefable(stderrRid);
     }
 
+    if (ptyRid !== null) {
+      this.#pty = new ChildPty(illegalConstructorKey, ptyRid);
+    }
+
+    if (ipcRid !== null) {
+      this.#ipc = new IpcChannel(illegalConstructorKey, ipcRid);
+    }
+
     const onAbort = () => this.kill("SIGTERM");
     signal?.[abortSignal.add](onAbort);
 
@@ -328,6 +605,8 @@ class ChildProcess {
     core.refOp(this.#waitPromiseId);
     if (this.#stdout) readableStreamForRidUnrefableRef(this.#stdout);
     if (this.#stderr) readableStreamForRidUnrefableRef(this.#stderr);
+    if (this.#pty) readableStreamForRidUnrefableRef(this.#pty.readable);
+    this.#ipc?.ref();
   }
 
   unref() {
@@ -335,6 +614,8 @@ class ChildProcess {
     core.unrefOp(this.#waitPromiseId);
     if (this.#stdout) readableStreamForRidUnrefableUnref(this.#stdout);
     if (this.#stderr) readableStreamForRidUnrefableUnref(this.#stderr);
+    if (this.#pty) readableStreamForRidUnrefableUnref(this.#pty.readable);
+    this.#ipc?.unref();
   }
 }
 
@@ -344,6 +625,16 @@ function spawn(command, options) {
       "Piped stdin is not supported for this function, use 'Deno.Command().spawn()' instead",
     );
   }
+  if (options?.pty) {
+    throw new TypeError(
+      "pty is not supported for this function, use 'Deno.Command().spawn()' instead",
+    );
+  }
+  if (options?.ipc) {
+    throw new TypeError(
+      "ipc is not supported for this function, use 'Deno.Command().spawn()' instead",
+    );
+  }
   return spawnChildInner(
     ops.op_spawn_child,
     command,
@@ -364,12 +655,27 @@ function spawnSync(command, {
   stdout = "piped",
   stderr = "piped",
   windowsRawArguments = false,
+  processGroup = false,
+  pty = false,
+  ipc = false,
+  limits = undefined,
+  cgroup = undefined,
 } = {}) {
   if (stdin === "piped") {
     throw new TypeError(
       "Piped stdin is not supported for this function, use 'Deno.Command().spawn()' instead",
     );
   }
+  if (pty) {
+    throw new TypeError(
+      "pty is not supported for this function, use 'Deno.Command().spawn()' instead",
+    );
+  }
+  if (ipc) {
+    throw new TypeError(
+      "ipc is not supported for this function, use 'Deno.Command().spawn()' instead",
+    );
+  }
   const result = ops.op_spawn_sync({
     cmd: pathFromURL(command),
     args: ArrayPrototypeMap(args, String),
@@ -382,6 +688,9 @@ function spawnSync(command, {
     stdout,
     stderr,
     windowsRawArguments,
+    processGroup,
+    limits,
+    cgroup,
   });
   return {
     success: result.status.success,
@@ -430,14 +739,17 @@ class Command {
   }
 
   spawn() {
+    // Detached children don't inherit the stdio by default, so that they don't
+    // hold on to the terminal of the parent.
+    const stdio = this.#options?.detached ? "null" : "inherit";
     const options = {
       ...(this.#options ?? {}),
-      stdout: this.#options?.stdout ?? "inherit",
-      stderr: this.#options?.stderr ?? "inherit",
-      stdin: this.#options?.stdin ?? "inherit",
+      stdout: this.#options?.stdout ?? stdio,
+      stderr: this.#options?.stderr ?? stdio,
+      stdin: this.#options?.stdin ?? stdio,
     };
     return spawnChild(this.#command, options);
   }
 }
 
-export { ChildProcess, Command, kill, Process, run };
+export { ChildProcess, Command, getParentIpc, kill, Process, run };
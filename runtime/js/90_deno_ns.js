@@ -157,8 +157,18 @@ const denoNsUnstable = {
     ops.op_net_listen_unixpacket,
   ),
   umask: fs.umask,
+  resourceUsage: os.resourceUsage,
+  getPriority: os.getPriority,
+  setPriority: os.setPriority,
+  watchNetworkChanges: os.watchNetworkChanges,
+  parentIpc: process.getParentIpc,
+  eventLoopMetrics: core.eventLoopMetrics,
   HttpClient: httpClient.HttpClient,
   createHttpClient: httpClient.createHttpClient,
+  DnsResolver: net.DnsResolver,
+  createDnsResolver: net.createDnsResolver,
+  IcmpSocket: net.IcmpSocket,
+  icmpSocket: net.icmpSocket,
   // TODO(bartlomieju): why is it needed?
   http,
   dlopen: ffi.dlopen,
@@ -170,6 +180,26 @@ const denoNsUnstable = {
   flockSync: fs.flockSync,
   funlock: fs.funlock,
   funlockSync: fs.funlockSync,
+  getXattr: fs.getXattr,
+  getXattrSync: fs.getXattrSync,
+  setXattr: fs.setXattr,
+  setXattrSync: fs.setXattrSync,
+  listXattr: fs.listXattr,
+  listXattrSync: fs.listXattrSync,
+  removeXattr: fs.removeXattr,
+  removeXattrSync: fs.removeXattrSync,
+  statFs: fs.statFs,
+  statFsSync: fs.statFsSync,
+  writeFileAtomic: fs.writeFileAtomic,
+  writeFileAtomicSync: fs.writeFileAtomicSync,
+  glob: fs.glob,
+  openTmpFile: fs.openTmpFile,
+  openTmpFileSync: fs.openTmpFileSync,
+  msync: fs.msync,
+  msyncSync: fs.msyncSync,
+  munmap: fs.munmap,
+  readDirWithStats: fs.readDirWithStats,
+  readDirWithStatsSync: fs.readDirWithStatsSync,
   upgradeHttp: http.upgradeHttp,
   serve: http.serve,
   openKv: kv.openKv,
@@ -0,0 +1,49 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+// The FFI API of a runtime that is built without the "ffi" feature, like the
+// lite runtime of `deno compile --lite`.
+
+const primordials = globalThis.__bootstrap.primordials;
+const { TypeError } = primordials;
+
+function unavailable() {
+  throw new TypeError(
+    "The FFI API is not available in this build of the runtime.",
+  );
+}
+
+function dlopen(_path, _symbols) {
+  unavailable();
+}
+
+class UnsafeCallback {
+  constructor() {
+    unavailable();
+  }
+}
+
+class UnsafeFnPointer {
+  constructor() {
+    unavailable();
+  }
+}
+
+class UnsafePointer {
+  constructor() {
+    unavailable();
+  }
+}
+
+class UnsafePointerView {
+  constructor() {
+    unavailable();
+  }
+}
+
+export {
+  dlopen,
+  UnsafeCallback,
+  UnsafeFnPointer,
+  UnsafePointer,
+  UnsafePointerView,
+};
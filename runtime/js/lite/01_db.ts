@@ -0,0 +1,39 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+// The KV API of a runtime that is built without the "kv" feature, like the
+// lite runtime of `deno compile --lite`.
+
+// @ts-ignore internal api
+const primordials = globalThis.__bootstrap.primordials;
+const { TypeError } = primordials;
+
+function unavailable(): never {
+  throw new TypeError(
+    "The KV API is not available in this build of the runtime.",
+  );
+}
+
+// deno-lint-ignore require-await
+async function openKv(_path?: string): Promise<never> {
+  unavailable();
+}
+
+class Kv {
+  constructor() {
+    unavailable();
+  }
+}
+
+class KvU64 {
+  constructor() {
+    unavailable();
+  }
+}
+
+class KvListIterator {
+  constructor() {
+    unavailable();
+  }
+}
+
+export { Kv, KvListIterator, KvU64, openKv };
@@ -6,10 +6,12 @@ pub use deno_console;
 pub use deno_core;
 pub use deno_crypto;
 pub use deno_fetch;
+#[cfg(feature = "ffi")]
 pub use deno_ffi;
 pub use deno_fs;
 pub use deno_http;
 pub use deno_io;
+#[cfg(feature = "kv")]
 pub use deno_kv;
 pub use deno_napi;
 pub use deno_net;
@@ -28,6 +30,7 @@ pub mod fs_util;
 pub mod inspector_server;
 pub mod js;
 pub mod ops;
+pub mod optional_extensions;
 pub mod permissions;
 pub mod tokio_util;
 pub mod web_worker;
@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use crate::permissions::PermissionsContainer;
+use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::parking_lot::Mutex;
 use deno_core::AsyncRefCell;
@@ -13,6 +14,8 @@ use deno_core::ResourceId;
 
 use deno_core::op;
 
+use glob::MatchOptions;
+use glob::Pattern;
 use notify::event::Event as NotifyEvent;
 use notify::Error as NotifyError;
 use notify::EventKind;
@@ -23,11 +26,18 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::convert::From;
+use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
+/// The maximum number of events that are coalesced by debouncing, after
+/// which they are reported even if more events keep coming.
+const MAX_DEBOUNCED_EVENTS: usize = 1024;
+
 deno_core::extension!(
   deno_fs_events,
   ops = [op_fs_events_open, op_fs_events_poll],
@@ -40,6 +50,11 @@ struct FsEventsResource {
   #[allow(unused)]
   watcher: RecommendedWatcher,
   receiver: AsyncRefCell<mpsc::Receiver<Result<FsEvent, AnyError>>>,
+  /// How long events are held back for after the last event, to coalesce
+  /// the events of a burst.
+  debounce: Option<Duration>,
+  /// The debounced events that haven't been polled yet.
+  pending: RefCell<VecDeque<Result<FsEvent, AnyError>>>,
   cancel: CancelHandle,
 }
 
@@ -61,7 +76,7 @@ impl Resource for FsEventsResource {
 ///
 /// Feel free to expand this struct as long as you can add tests to demonstrate
 /// the complexity.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, PartialEq)]
 struct FsEvent {
   kind: &'static str,
   paths: Vec<PathBuf>,
@@ -93,6 +108,102 @@ impl From<NotifyEvent> for FsEvent {
 pub struct OpenArgs {
   recursive: bool,
   paths: Vec<String>,
+  /// Glob patterns of the paths to report events for, relative to the
+  /// watched path they are in.
+  #[serde(default)]
+  include: Vec<String>,
+  /// Glob patterns of the paths to not report events for.
+  #[serde(default)]
+  exclude: Vec<String>,
+  /// The extensions of the paths to report events for.
+  #[serde(default)]
+  extensions: Vec<String>,
+  /// In milliseconds.
+  debounce: Option<u64>,
+}
+
+/// Decides which events are reported, before they are sent to JavaScript.
+#[derive(Debug)]
+struct EventFilter {
+  roots: Vec<PathBuf>,
+  include: Vec<Pattern>,
+  exclude: Vec<Pattern>,
+  extensions: Vec<String>,
+}
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+  case_sensitive: true,
+  require_literal_separator: true,
+  require_literal_leading_dot: false,
+};
+
+impl EventFilter {
+  fn new(
+    roots: &[PathBuf],
+    include: &[String],
+    exclude: &[String],
+    extensions: &[String],
+  ) -> Result<Self, AnyError> {
+    let parse = |patterns: &[String]| {
+      patterns
+        .iter()
+        .map(|pattern| {
+          Pattern::new(pattern).map_err(|err| {
+            type_error(format!("Invalid glob pattern \"{pattern}\": {err}"))
+          })
+        })
+        .collect::<Result<Vec<_>, _>>()
+    };
+    // Events may be reported with the canonical paths of the watched paths,
+    // e.g. on macOS.
+    let mut all_roots = roots.to_vec();
+    all_roots.extend(roots.iter().filter_map(|root| root.canonicalize().ok()));
+    Ok(Self {
+      roots: all_roots,
+      include: parse(include)?,
+      exclude: parse(exclude)?,
+      extensions: extensions
+        .iter()
+        .map(|extension| extension.trim_start_matches('.').to_string())
+        .collect(),
+    })
+  }
+
+  fn is_empty(&self) -> bool {
+    self.include.is_empty()
+      && self.exclude.is_empty()
+      && self.extensions.is_empty()
+  }
+
+  /// Whether an event is reported, which is the case if any of its paths
+  /// is, so that both paths of renames are reported.
+  fn matches_event(&self, event: &FsEvent) -> bool {
+    event.paths.is_empty() || event.paths.iter().any(|path| self.matches(path))
+  }
+
+  fn matches(&self, path: &Path) -> bool {
+    if !self.extensions.is_empty() {
+      let Some(extension) = path.extension().and_then(|e| e.to_str()) else {
+        return false;
+      };
+      if !self.extensions.iter().any(|e| e == extension) {
+        return false;
+      }
+    }
+    let relative = self
+      .roots
+      .iter()
+      .filter_map(|root| path.strip_prefix(root).ok())
+      .next()
+      .unwrap_or(path);
+    let matches_any = |patterns: &[Pattern]| {
+      patterns
+        .iter()
+        .any(|pattern| pattern.matches_path_with(relative, MATCH_OPTIONS))
+    };
+    (self.include.is_empty() || matches_any(&self.include))
+      && !matches_any(&self.exclude)
+  }
 }
 
 #[op]
@@ -100,11 +211,20 @@ fn op_fs_events_open(
   state: &mut OpState,
   args: OpenArgs,
 ) -> Result<ResourceId, AnyError> {
+  let paths = args.paths.iter().map(PathBuf::from).collect::<Vec<_>>();
+  let filter =
+    EventFilter::new(&paths, &args.include, &args.exclude, &args.extensions)?;
+  let filter = (!filter.is_empty()).then_some(filter);
   let (sender, receiver) = mpsc::channel::<Result<FsEvent, AnyError>>(16);
   let sender = Mutex::new(sender);
   let mut watcher: RecommendedWatcher = Watcher::new(
     move |res: Result<NotifyEvent, NotifyError>| {
       let res2 = res.map(FsEvent::from).map_err(AnyError::from);
+      if let (Some(filter), Ok(event)) = (&filter, &res2) {
+        if !filter.matches_event(event) {
+          return;
+        }
+      }
       let sender = sender.lock();
       // Ignore result, if send failed it means that watcher was already closed,
       // but not all messages have been flushed.
@@ -117,16 +237,17 @@ fn op_fs_events_open(
   } else {
     RecursiveMode::NonRecursive
   };
-  for path in &args.paths {
-    let path = PathBuf::from(path);
+  for path in &paths {
     state
       .borrow_mut::<PermissionsContainer>()
-      .check_read(&path, "Deno.watchFs()")?;
-    watcher.watch(&path, recursive_mode)?;
+      .check_read(path, "Deno.watchFs()")?;
+    watcher.watch(path, recursive_mode)?;
   }
   let resource = FsEventsResource {
     watcher,
     receiver: AsyncRefCell::new(receiver),
+    debounce: args.debounce.map(Duration::from_millis),
+    pending: Default::default(),
     cancel: Default::default(),
   };
   let rid = state.resource_table.add(resource);
@@ -139,12 +260,83 @@ async fn op_fs_events_poll(
   rid: ResourceId,
 ) -> Result<Option<FsEvent>, AnyError> {
   let resource = state.borrow().resource_table.get::<FsEventsResource>(rid)?;
+  if let Some(result) = resource.pending.borrow_mut().pop_front() {
+    return result.map(Some);
+  }
   let mut receiver = RcRef::map(&resource, |r| &r.receiver).borrow_mut().await;
-  let cancel = RcRef::map(resource, |r| &r.cancel);
+  let cancel = RcRef::map(&resource, |r| &r.cancel);
   let maybe_result = receiver.recv().or_cancel(cancel).await?;
-  match maybe_result {
-    Some(Ok(value)) => Ok(Some(value)),
-    Some(Err(err)) => Err(err),
-    None => Ok(None),
+  let (Some(debounce), Some(Ok(first))) = (resource.debounce, &maybe_result)
+  else {
+    return match maybe_result {
+      Some(Ok(value)) => Ok(Some(value)),
+      Some(Err(err)) => Err(err),
+      None => Ok(None),
+    };
+  };
+
+  // Wait for a pause in the events, dropping the duplicate ones.
+  let mut events = vec![];
+  let mut error = None;
+  while events.len() < MAX_DEBOUNCED_EVENTS {
+    let cancel = RcRef::map(&resource, |r| &r.cancel);
+    match tokio::time::timeout(debounce, receiver.recv())
+      .or_cancel(cancel)
+      .await?
+    {
+      Ok(Some(Ok(event))) => {
+        if event != *first && !events.contains(&event) {
+          events.push(event);
+        }
+      }
+      Ok(Some(Err(err))) => {
+        error = Some(err);
+        break;
+      }
+      Ok(None) | Err(_) => break,
+    }
+  }
+  let mut pending = resource.pending.borrow_mut();
+  pending.extend(events.into_iter().map(Ok));
+  pending.extend(error.map(Err));
+  maybe_result.transpose()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_event_filter() {
+    let root = PathBuf::from("/project");
+    let filter = EventFilter::new(
+      &[root.clone()],
+      &["**/*.ts".to_string(), "*.json".to_string()],
+      &["node_modules/**".to_string()],
+      &[],
+    )
+    .unwrap();
+    assert!(filter.matches(&root.join("main.ts")));
+    assert!(filter.matches(&root.join("src/util/mod.ts")));
+    assert!(filter.matches(&root.join("deno.json")));
+    assert!(!filter.matches(&root.join("src/deno.json")));
+    assert!(!filter.matches(&root.join("node_modules/chalk/index.ts")));
+    assert!(!filter.matches(&root.join("README.md")));
+
+    let filter =
+      EventFilter::new(&[root.clone()], &[], &[], &[".ts".to_string()])
+        .unwrap();
+    assert!(filter.matches(&root.join("src/main.ts")));
+    assert!(!filter.matches(&root.join("src/main.js")));
+    assert!(!filter.matches(&root.join("src")));
+
+    let event = FsEvent {
+      kind: "modify",
+      paths: vec![root.join("a.js"), root.join("b.ts")],
+      flag: None,
+    };
+    assert!(filter.matches_event(&event));
+
+    assert!(EventFilter::new(&[root], &["[".to_string()], &[], &[]).is_err());
   }
 }
@@ -0,0 +1,261 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The message channel between a parent and a child Deno process spawned with
+//! the `ipc` option of `Deno.Command`. On Unix it is a socket pair, whose child
+//! end is inherited by the child, which finds it through the `DENO_IPC_FD`
+//! environment variable, and the handles are sent alongside the messages as
+//! `SCM_RIGHTS`. On Windows it is a named pipe, whose client end is inherited
+//! through the `DENO_IPC_HANDLE` environment variable, and sending handles
+//! isn't supported. The messages are framed on the JS side.
+
+use deno_core::error::AnyError;
+use deno_core::op;
+use deno_core::OpState;
+use deno_core::ResourceId;
+
+#[cfg(unix)]
+const IPC_FD_ENV_VAR: &str = "DENO_IPC_FD";
+
+#[cfg(windows)]
+const IPC_HANDLE_ENV_VAR: &str = "DENO_IPC_HANDLE";
+
+#[cfg(unix)]
+pub use unix::*;
+
+#[cfg(unix)]
+mod unix {
+  use super::IPC_FD_ENV_VAR;
+  use deno_core::error::AnyError;
+  use std::io;
+  use std::os::unix::io::AsRawFd;
+  use std::os::unix::net::UnixStream;
+  use std::os::unix::process::CommandExt;
+
+  pub type IpcStream = UnixStream;
+
+  /// Creates the channel, and lets the `command` inherit its child end.
+  /// Returns the parent and the child ends, the latter of which must be kept
+  /// open until the command is spawned.
+  pub fn attach_ipc(
+    command: &mut std::process::Command,
+  ) -> Result<(UnixStream, UnixStream), AnyError> {
+    let (parent, child) = UnixStream::pair()?;
+    let fd = child.as_raw_fd();
+    command.env(IPC_FD_ENV_VAR, fd.to_string());
+    // SAFETY: only async-signal-safe functions are called after the fork.
+    unsafe {
+      command.pre_exec(move || {
+        // std opens the sockets with close-on-exec.
+        if libc::fcntl(fd, libc::F_SETFD, 0) == -1 {
+          return Err(io::Error::last_os_error());
+        }
+        Ok(())
+      });
+    }
+    Ok((parent, child))
+  }
+}
+
+#[cfg(windows)]
+pub use windows::*;
+
+#[cfg(windows)]
+mod windows {
+  use super::IPC_HANDLE_ENV_VAR;
+  use deno_core::error::AnyError;
+  use deno_core::AsyncResult;
+  use deno_core::Resource;
+  use deno_net::io::FullDuplexResource;
+  use std::borrow::Cow;
+  use std::ffi::OsStr;
+  use std::io;
+  use std::os::windows::ffi::OsStrExt;
+  use std::os::windows::io::FromRawHandle;
+  use std::os::windows::io::OwnedHandle;
+  use std::rc::Rc;
+  use tokio::io::AsyncRead;
+  use tokio::io::AsyncWrite;
+  use tokio::net::windows::named_pipe::NamedPipeServer;
+  use tokio::net::windows::named_pipe::ServerOptions;
+  use winapi::shared::minwindef::TRUE;
+  use winapi::um::fileapi::CreateFileW;
+  use winapi::um::fileapi::OPEN_EXISTING;
+  use winapi::um::handleapi::INVALID_HANDLE_VALUE;
+  use winapi::um::minwinbase::SECURITY_ATTRIBUTES;
+  use winapi::um::winbase::FILE_FLAG_OVERLAPPED;
+  use winapi::um::winnt::GENERIC_READ;
+  use winapi::um::winnt::GENERIC_WRITE;
+
+  pub type IpcStream = NamedPipeServer;
+
+  /// Creates the channel, and lets the `command` inherit its client end.
+  /// Returns the server and the client ends, the latter of which must be kept
+  /// open until the command is spawned.
+  pub fn attach_ipc(
+    command: &mut std::process::Command,
+  ) -> Result<(NamedPipeServer, OwnedHandle), AnyError> {
+    let name = format!(r"\\.\pipe\deno-ipc-{}", uuid::Uuid::new_v4());
+    let server = ServerOptions::new()
+      .first_pipe_instance(true)
+      .max_instances(1)
+      .reject_remote_clients(true)
+      .create(&name)?;
+
+    let name = OsStr::new(&name)
+      .encode_wide()
+      .chain(Some(0))
+      .collect::<Vec<_>>();
+    let mut security_attributes = SECURITY_ATTRIBUTES {
+      nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+      lpSecurityDescriptor: std::ptr::null_mut(),
+      bInheritHandle: TRUE,
+    };
+    // SAFETY: winapi call with a null terminated name. The handle is opened
+    // for overlapped I/O, which tokio requires of the child.
+    let handle = unsafe {
+      CreateFileW(
+        name.as_ptr(),
+        GENERIC_READ | GENERIC_WRITE,
+        0,
+        &mut security_attributes,
+        OPEN_EXISTING,
+        FILE_FLAG_OVERLAPPED,
+        std::ptr::null_mut(),
+      )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+      return Err(io::Error::last_os_error().into());
+    }
+    // SAFETY: the handle was just opened, and nothing else owns it.
+    let client = unsafe { OwnedHandle::from_raw_handle(handle as _) };
+    // The client connected before the server waits for it, which leaves the
+    // pipe connected, so there is nothing to wait for.
+    command.env(IPC_HANDLE_ENV_VAR, (handle as usize).to_string());
+    Ok((server, client))
+  }
+
+  type BoxedPipe =
+    FullDuplexResource<Box<dyn AsyncRead + Unpin>, Box<dyn AsyncWrite + Unpin>>;
+
+  /// An end of the named pipe of a channel.
+  pub struct IpcPipeResource(Rc<BoxedPipe>);
+
+  impl IpcPipeResource {
+    pub fn new<T>(pipe: T) -> Self
+    where
+      T: AsyncRead + AsyncWrite + Unpin + 'static,
+    {
+      let (rd, wr) = tokio::io::split(pipe);
+      Self(Rc::new(FullDuplexResource::new((
+        Box::new(rd),
+        Box::new(wr),
+      ))))
+    }
+
+    async fn read(self: Rc<Self>, data: &mut [u8]) -> Result<usize, AnyError> {
+      self.0.clone().read(data).await
+    }
+
+    async fn write(self: Rc<Self>, data: &[u8]) -> Result<usize, AnyError> {
+      self.0.clone().write(data).await
+    }
+  }
+
+  impl Resource for IpcPipeResource {
+    deno_core::impl_readable_byob!();
+    deno_core::impl_writable!();
+
+    fn name(&self) -> Cow<str> {
+      "ipcPipe".into()
+    }
+
+    fn close(self: Rc<Self>) {
+      self.0.cancel_read_ops();
+    }
+  }
+}
+
+/// Adds the parent end of the channel as a Unix connection.
+#[cfg(unix)]
+pub fn add_ipc_resource(
+  state: &mut OpState,
+  stream: IpcStream,
+) -> Result<ResourceId, AnyError> {
+  stream.set_nonblocking(true)?;
+  let stream = tokio::net::UnixStream::from_std(stream)?;
+  Ok(
+    state
+      .resource_table
+      .add(deno_net::io::UnixStreamResource::new(stream.into_split())),
+  )
+}
+
+/// Adds the server end of the channel as a pipe.
+#[cfg(windows)]
+pub fn add_ipc_resource(
+  state: &mut OpState,
+  stream: IpcStream,
+) -> Result<ResourceId, AnyError> {
+  Ok(state.resource_table.add(IpcPipeResource::new(stream)))
+}
+
+/// Opens the channel to the parent process, if this process was spawned with
+/// the `ipc` option. It can only be opened once.
+#[op]
+pub fn op_ipc_parent_open(
+  state: &mut OpState,
+) -> Result<Option<ResourceId>, AnyError> {
+  super::check_unstable(state, "Deno.parentIpc");
+  #[cfg(unix)]
+  {
+    use std::os::unix::io::FromRawFd;
+    use std::os::unix::io::RawFd;
+
+    let Some(fd) = std::env::var(IPC_FD_ENV_VAR)
+      .ok()
+      .and_then(|fd| fd.parse::<RawFd>().ok())
+    else {
+      return Ok(None);
+    };
+    // The variable isn't inherited by the grandchildren, which would
+    // otherwise take an unrelated descriptor for the channel.
+    std::env::remove_var(IPC_FD_ENV_VAR);
+    // SAFETY: the descriptor was inherited from the parent for the channel,
+    // and nothing else owns it.
+    let stream = unsafe { std::os::unix::net::UnixStream::from_raw_fd(fd) };
+    // Set close-on-exec again, so that it isn't leaked into the grandchildren.
+    // SAFETY: libc call on a valid fd
+    if unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+      return Err(std::io::Error::last_os_error().into());
+    }
+    add_ipc_resource(state, stream).map(Some)
+  }
+  #[cfg(windows)]
+  {
+    use std::os::windows::io::RawHandle;
+    use tokio::net::windows::named_pipe::NamedPipeClient;
+    use winapi::um::handleapi::SetHandleInformation;
+    use winapi::um::winbase::HANDLE_FLAG_INHERIT;
+
+    let Some(handle) = std::env::var(IPC_HANDLE_ENV_VAR)
+      .ok()
+      .and_then(|handle| handle.parse::<usize>().ok())
+    else {
+      return Ok(None);
+    };
+    // The variable isn't inherited by the grandchildren, which would
+    // otherwise take an unrelated handle for the channel.
+    std::env::remove_var(IPC_HANDLE_ENV_VAR);
+    // Nor is the handle itself.
+    // SAFETY: winapi call on the handle that was inherited for the channel
+    if unsafe { SetHandleInformation(handle as _, HANDLE_FLAG_INHERIT, 0) } == 0
+    {
+      return Err(std::io::Error::last_os_error().into());
+    }
+    // SAFETY: the handle was inherited from the parent for the channel, opened
+    // for overlapped I/O, and nothing else owns it.
+    let pipe =
+      unsafe { NamedPipeClient::from_raw_handle(handle as RawHandle)? };
+    Ok(Some(state.resource_table.add(IpcPipeResource::new(pipe))))
+  }
+}
@@ -2,9 +2,11 @@
 
 pub mod fs_events;
 pub mod http;
+pub mod ipc;
 pub mod os;
 pub mod permissions;
 pub mod process;
+pub mod pty;
 pub mod runtime;
 pub mod signal;
 pub mod tty;
@@ -8,12 +8,24 @@ use deno_core::error::AnyError;
 use deno_core::op;
 use deno_core::url::Url;
 use deno_core::v8;
+use deno_core::AsyncRefCell;
+use deno_core::CancelFuture;
+use deno_core::CancelHandle;
 use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
+use deno_core::ResourceId;
 use deno_node::NODE_ENV_VAR_ALLOWLIST;
 use serde::Serialize;
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::env;
+use std::rc::Rc;
 
+mod network;
+mod priority;
 mod sys_info;
 
 deno_core::ops!(
@@ -25,17 +37,22 @@ deno_core::ops!(
     op_delete_env,
     op_get_env,
     op_gid,
+    op_get_priority,
     op_hostname,
     op_loadavg,
     op_network_interfaces,
+    op_network_changes_open,
+    op_network_changes_poll,
     op_os_release,
     op_os_uptime,
     op_node_unstable_os_uptime,
     op_set_env,
     op_set_exit_code,
+    op_set_priority,
     op_system_memory_info,
     op_uid,
     op_runtime_memory_usage,
+    op_resource_usage,
   ]
 );
 
@@ -192,7 +209,16 @@ fn op_network_interfaces(
   state
     .borrow_mut::<PermissionsContainer>()
     .check_sys("networkInterfaces", "Deno.networkInterfaces()")?;
-  Ok(netif::up()?.map(NetworkInterface::from).collect())
+  let stats = network::interface_stats();
+  Ok(
+    netif::up()?
+      .map(NetworkInterface::from)
+      .map(|mut interface| {
+        interface.stats = stats.get(&interface.name).copied();
+        interface
+      })
+      .collect(),
+  )
 }
 
 #[derive(serde::Serialize)]
@@ -204,6 +230,7 @@ struct NetworkInterface {
   scopeid: Option<u32>,
   cidr: String,
   mac: String,
+  stats: Option<network::InterfaceStats>,
 }
 
 impl From<netif::Interface> for NetworkInterface {
@@ -232,10 +259,97 @@ impl From<netif::Interface> for NetworkInterface {
       scopeid,
       cidr,
       mac,
+      stats: None,
     }
   }
 }
 
+struct NetworkChangesResource {
+  source: AsyncRefCell<network::NetworkChangeSource>,
+  /// The changes of the last notification that haven't been polled yet.
+  pending: RefCell<VecDeque<network::NetworkChange>>,
+  cancel: CancelHandle,
+}
+
+impl Resource for NetworkChangesResource {
+  fn name(&self) -> Cow<str> {
+    "networkChanges".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel.cancel();
+  }
+}
+
+#[op]
+fn op_network_changes_open(
+  state: &mut OpState,
+) -> Result<ResourceId, AnyError> {
+  super::check_unstable(state, "Deno.watchNetworkChanges");
+  state
+    .borrow_mut::<PermissionsContainer>()
+    .check_sys("networkInterfaces", "Deno.watchNetworkChanges()")?;
+  let resource = NetworkChangesResource {
+    source: AsyncRefCell::new(network::NetworkChangeSource::new()?),
+    pending: Default::default(),
+    cancel: Default::default(),
+  };
+  Ok(state.resource_table.add(resource))
+}
+
+#[op]
+async fn op_network_changes_poll(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+) -> Result<Option<network::NetworkChange>, AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<NetworkChangesResource>(rid)?;
+  if let Some(change) = resource.pending.borrow_mut().pop_front() {
+    return Ok(Some(change));
+  }
+  let mut source = RcRef::map(&resource, |r| &r.source).borrow_mut().await;
+  let cancel = RcRef::map(&resource, |r| &r.cancel);
+  let Ok(changes) = source.recv().or_cancel(cancel).await else {
+    return Ok(None);
+  };
+  let mut changes = VecDeque::from(changes?);
+  let first = changes.pop_front();
+  resource.pending.borrow_mut().extend(changes);
+  Ok(first)
+}
+
+#[op]
+fn op_get_priority(state: &mut OpState, pid: u32) -> Result<i32, AnyError> {
+  super::check_unstable(state, "Deno.getPriority");
+  state
+    .borrow_mut::<PermissionsContainer>()
+    .check_sys("getPriority", "Deno.getPriority()")?;
+  priority::get_priority(pid)
+}
+
+#[op]
+fn op_set_priority(
+  state: &mut OpState,
+  pid: u32,
+  priority: i32,
+) -> Result<(), AnyError> {
+  super::check_unstable(state, "Deno.setPriority");
+  state
+    .borrow_mut::<PermissionsContainer>()
+    .check_sys("setPriority", "Deno.setPriority()")?;
+  if !(priority::PRIORITY_HIGHEST..=priority::PRIORITY_LOW).contains(&priority)
+  {
+    return Err(type_error(format!(
+      "Priority must be between {} and {}, got {priority}",
+      priority::PRIORITY_HIGHEST,
+      priority::PRIORITY_LOW
+    )));
+  }
+  priority::set_priority(pid, priority)
+}
+
 #[op]
 fn op_system_memory_info(
   state: &mut OpState,
@@ -312,6 +426,12 @@ fn op_runtime_memory_usage(scope: &mut v8::HandleScope) -> MemoryUsage {
   }
 }
 
+#[op]
+fn op_resource_usage(state: &mut OpState) -> sys_info::ResourceUsage {
+  super::check_unstable(state, "Deno.resourceUsage");
+  sys_info::resource_usage()
+}
+
 #[cfg(target_os = "linux")]
 fn rss() -> usize {
   // Inspired by https://github.com/Arc-blroth/memory-stats/blob/5364d0d09143de2a470d33161b2330914228fde9/src/linux.rs
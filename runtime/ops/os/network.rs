@@ -0,0 +1,508 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::error::AnyError;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The traffic counters of a network interface, since it was brought up.
+#[derive(Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct InterfaceStats {
+  pub rx_bytes: u64,
+  pub tx_bytes: u64,
+  pub rx_packets: u64,
+  pub tx_packets: u64,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn interface_stats() -> HashMap<String, InterfaceStats> {
+  let read_counter = |name: &str, counter: &str| {
+    std::fs::read_to_string(format!(
+      "/sys/class/net/{name}/statistics/{counter}"
+    ))
+    .ok()
+    .and_then(|s| s.trim().parse().ok())
+    .unwrap_or(0)
+  };
+  let Ok(entries) = std::fs::read_dir("/sys/class/net") else {
+    return HashMap::new();
+  };
+  entries
+    .flatten()
+    .filter_map(|entry| entry.file_name().into_string().ok())
+    .map(|name| {
+      let stats = InterfaceStats {
+        rx_bytes: read_counter(&name, "rx_bytes"),
+        tx_bytes: read_counter(&name, "tx_bytes"),
+        rx_packets: read_counter(&name, "rx_packets"),
+        tx_packets: read_counter(&name, "tx_packets"),
+      };
+      (name, stats)
+    })
+    .collect()
+}
+
+#[cfg(any(
+  target_vendor = "apple",
+  target_os = "freebsd",
+  target_os = "openbsd"
+))]
+pub fn interface_stats() -> HashMap<String, InterfaceStats> {
+  use std::ffi::CStr;
+
+  let mut stats = HashMap::new();
+  let mut ifaddrs = std::ptr::null_mut();
+  // SAFETY: `ifaddrs` is a valid pointer to a pointer.
+  if unsafe { libc::getifaddrs(&mut ifaddrs) } != 0 {
+    return stats;
+  }
+  let mut ifa = ifaddrs;
+  while !ifa.is_null() {
+    // SAFETY: `ifa` is a non-null entry of the list returned by getifaddrs.
+    let entry = unsafe { &*ifa };
+    ifa = entry.ifa_next;
+    // The counters are in the data of the link level address of interfaces.
+    if entry.ifa_addr.is_null() || entry.ifa_data.is_null() {
+      continue;
+    }
+    // SAFETY: `ifa_addr` is non-null.
+    if unsafe { (*entry.ifa_addr).sa_family } as i32 != libc::AF_LINK {
+      continue;
+    }
+    // SAFETY: `ifa_data` points to an `if_data` struct for AF_LINK entries,
+    // and `ifa_name` is a valid C string.
+    let (data, name) = unsafe {
+      (
+        &*(entry.ifa_data as *const libc::if_data),
+        CStr::from_ptr(entry.ifa_name),
+      )
+    };
+    stats.insert(
+      name.to_string_lossy().into_owned(),
+      InterfaceStats {
+        rx_bytes: data.ifi_ibytes as u64,
+        tx_bytes: data.ifi_obytes as u64,
+        rx_packets: data.ifi_ipackets as u64,
+        tx_packets: data.ifi_opackets as u64,
+      },
+    );
+  }
+  // SAFETY: `ifaddrs` was returned by getifaddrs.
+  unsafe { libc::freeifaddrs(ifaddrs) };
+  stats
+}
+
+#[cfg(windows)]
+pub fn interface_stats() -> HashMap<String, InterfaceStats> {
+  use winapi::shared::netioapi::FreeMibTable;
+  use winapi::shared::netioapi::GetIfTable2;
+  use winapi::shared::netioapi::PMIB_IF_TABLE2;
+  use winapi::shared::winerror::NO_ERROR;
+
+  let mut stats = HashMap::new();
+  let mut table: PMIB_IF_TABLE2 = std::ptr::null_mut();
+  // SAFETY: winapi call
+  if unsafe { GetIfTable2(&mut table) } != NO_ERROR {
+    return stats;
+  }
+  // SAFETY: `table` was initialized by GetIfTable2, with `NumEntries` rows.
+  let rows = unsafe {
+    std::slice::from_raw_parts(
+      (*table).Table.as_ptr(),
+      (*table).NumEntries as usize,
+    )
+  };
+  for row in rows {
+    let len = row.Alias.iter().position(|c| *c == 0).unwrap_or(0);
+    let name = String::from_utf16_lossy(&row.Alias[..len]);
+    stats.insert(
+      name,
+      InterfaceStats {
+        rx_bytes: row.InOctets,
+        tx_bytes: row.OutOctets,
+        rx_packets: row.InUcastPkts + row.InNUcastPkts,
+        tx_packets: row.OutUcastPkts + row.OutNUcastPkts,
+      },
+    );
+  }
+  // SAFETY: `table` was allocated by GetIfTable2.
+  unsafe { FreeMibTable(table as _) };
+  stats
+}
+
+/// A change of the network configuration.
+#[derive(Serialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkChange {
+  /// An interface was added, removed, or brought up or down.
+  Interface,
+  /// An address was added to or removed from an interface.
+  Address,
+  /// A default route was added, removed or changed.
+  Route,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use netlink::NetworkChangeSource;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod netlink {
+  use super::NetworkChange;
+  use deno_core::error::AnyError;
+  use std::io;
+  use std::os::unix::io::AsRawFd;
+  use std::os::unix::io::FromRawFd;
+  use std::os::unix::io::OwnedFd;
+  use tokio::io::unix::AsyncFd;
+
+  /// A netlink socket that receives the changes of the links, addresses and
+  /// routes of the system.
+  pub struct NetworkChangeSource {
+    fd: AsyncFd<OwnedFd>,
+    buf: Vec<u8>,
+  }
+
+  impl NetworkChangeSource {
+    pub fn new() -> Result<Self, AnyError> {
+      // SAFETY: libc call
+      let fd = unsafe {
+        libc::socket(
+          libc::AF_NETLINK,
+          libc::SOCK_RAW | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+          libc::NETLINK_ROUTE,
+        )
+      };
+      if fd < 0 {
+        return Err(io::Error::last_os_error().into());
+      }
+      // SAFETY: the socket was just created and nothing else refers to it.
+      let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+      // SAFETY: an all-zero `sockaddr_nl` is valid.
+      let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+      addr.nl_family = libc::AF_NETLINK as _;
+      addr.nl_groups = (libc::RTMGRP_LINK
+        | libc::RTMGRP_IPV4_IFADDR
+        | libc::RTMGRP_IPV6_IFADDR
+        | libc::RTMGRP_IPV4_ROUTE
+        | libc::RTMGRP_IPV6_ROUTE) as u32;
+      // SAFETY: `addr` is a valid `sockaddr_nl` of the given size.
+      let res = unsafe {
+        libc::bind(
+          fd.as_raw_fd(),
+          &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+          std::mem::size_of::<libc::sockaddr_nl>() as _,
+        )
+      };
+      if res < 0 {
+        return Err(io::Error::last_os_error().into());
+      }
+      Ok(Self {
+        fd: AsyncFd::new(fd)?,
+        buf: vec![0; 16 * 1024],
+      })
+    }
+
+    pub async fn recv(&mut self) -> Result<Vec<NetworkChange>, AnyError> {
+      loop {
+        let mut guard = self.fd.readable().await?;
+        let buf = &mut self.buf;
+        let result = guard.try_io(|fd| {
+          // SAFETY: `buf` is a valid buffer of the given length.
+          let n = unsafe {
+            libc::recv(
+              fd.as_raw_fd(),
+              buf.as_mut_ptr() as *mut libc::c_void,
+              buf.len(),
+              0,
+            )
+          };
+          if n < 0 {
+            Err(io::Error::last_os_error())
+          } else {
+            Ok(n as usize)
+          }
+        });
+        let n = match result {
+          Ok(result) => result?,
+          Err(_would_block) => continue,
+        };
+        let changes = parse_messages(&self.buf[..n]);
+        if !changes.is_empty() {
+          return Ok(changes);
+        }
+      }
+    }
+  }
+
+  fn parse_messages(mut buf: &[u8]) -> Vec<NetworkChange> {
+    const HEADER_LEN: usize = std::mem::size_of::<libc::nlmsghdr>();
+    let mut changes = vec![];
+    while buf.len() >= HEADER_LEN {
+      // SAFETY: `buf` holds at least a header, which is read unaligned.
+      let header = unsafe {
+        std::ptr::read_unaligned(buf.as_ptr() as *const libc::nlmsghdr)
+      };
+      let len = header.nlmsg_len as usize;
+      if len < HEADER_LEN || len > buf.len() {
+        break;
+      }
+      let payload = &buf[HEADER_LEN..len];
+      let change = match header.nlmsg_type {
+        libc::RTM_NEWLINK | libc::RTM_DELLINK => Some(NetworkChange::Interface),
+        libc::RTM_NEWADDR | libc::RTM_DELADDR => Some(NetworkChange::Address),
+        // The second byte of `rtmsg` is the length of the destination prefix,
+        // which is 0 for default routes.
+        libc::RTM_NEWROUTE | libc::RTM_DELROUTE
+          if payload.get(1) == Some(&0) =>
+        {
+          Some(NetworkChange::Route)
+        }
+        _ => None,
+      };
+      if let Some(change) = change {
+        if !changes.contains(&change) {
+          changes.push(change);
+        }
+      }
+      // Messages are aligned to 4 bytes.
+      let aligned_len = (len + 3) & !3;
+      buf = &buf[aligned_len.min(buf.len())..];
+    }
+    changes
+  }
+}
+
+#[cfg(any(
+  target_vendor = "apple",
+  target_os = "freebsd",
+  target_os = "openbsd"
+))]
+pub use route_socket::NetworkChangeSource;
+
+#[cfg(any(
+  target_vendor = "apple",
+  target_os = "freebsd",
+  target_os = "openbsd"
+))]
+mod route_socket {
+  use super::NetworkChange;
+  use deno_core::error::AnyError;
+  use std::io;
+  use std::os::unix::io::AsRawFd;
+  use std::os::unix::io::FromRawFd;
+  use std::os::unix::io::OwnedFd;
+  use tokio::io::unix::AsyncFd;
+
+  /// A routing socket that receives the changes of the interfaces, addresses
+  /// and routes of the system.
+  pub struct NetworkChangeSource {
+    fd: AsyncFd<OwnedFd>,
+    buf: Vec<u8>,
+  }
+
+  impl NetworkChangeSource {
+    pub fn new() -> Result<Self, AnyError> {
+      // SAFETY: libc call
+      let fd = unsafe {
+        libc::socket(libc::PF_ROUTE, libc::SOCK_RAW, libc::AF_UNSPEC)
+      };
+      if fd < 0 {
+        return Err(io::Error::last_os_error().into());
+      }
+      // SAFETY: the socket was just created and nothing else refers to it.
+      let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+      // SAFETY: libc calls on a valid fd
+      unsafe {
+        let flags = libc::fcntl(fd.as_raw_fd(), libc::F_GETFL);
+        libc::fcntl(fd.as_raw_fd(), libc::F_SETFL, flags | libc::O_NONBLOCK);
+        libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC);
+      }
+      Ok(Self {
+        fd: AsyncFd::new(fd)?,
+        buf: vec![0; 16 * 1024],
+      })
+    }
+
+    pub async fn recv(&mut self) -> Result<Vec<NetworkChange>, AnyError> {
+      loop {
+        let mut guard = self.fd.readable().await?;
+        let buf = &mut self.buf;
+        let result = guard.try_io(|fd| {
+          // SAFETY: `buf` is a valid buffer of the given length.
+          let n = unsafe {
+            libc::read(
+              fd.as_raw_fd(),
+              buf.as_mut_ptr() as *mut libc::c_void,
+              buf.len(),
+            )
+          };
+          if n < 0 {
+            Err(io::Error::last_os_error())
+          } else {
+            Ok(n as usize)
+          }
+        });
+        let n = match result {
+          Ok(result) => result?,
+          Err(_would_block) => continue,
+        };
+        // Each read returns a single message, which starts with the u16
+        // length, the u8 version and the u8 type of the message.
+        if n < 4 {
+          continue;
+        }
+        let change = match self.buf[3] as libc::c_int {
+          libc::RTM_IFINFO => NetworkChange::Interface,
+          libc::RTM_NEWADDR | libc::RTM_DELADDR => NetworkChange::Address,
+          libc::RTM_ADD | libc::RTM_DELETE | libc::RTM_CHANGE => {
+            NetworkChange::Route
+          }
+          _ => continue,
+        };
+        return Ok(vec![change]);
+      }
+    }
+  }
+}
+
+#[cfg(windows)]
+pub use notify_change::NetworkChangeSource;
+
+#[cfg(windows)]
+mod notify_change {
+  use super::NetworkChange;
+  use deno_core::error::AnyError;
+  use std::ffi::c_void;
+  use tokio::sync::mpsc;
+  use winapi::shared::netioapi::CancelMibChangeNotify2;
+  use winapi::shared::netioapi::NotifyIpInterfaceChange;
+  use winapi::shared::netioapi::NotifyRouteChange2;
+  use winapi::shared::netioapi::NotifyUnicastIpAddressChange;
+  use winapi::shared::netioapi::MIB_IPFORWARD_ROW2;
+  use winapi::shared::netioapi::MIB_IPINTERFACE_ROW;
+  use winapi::shared::netioapi::MIB_NOTIFICATION_TYPE;
+  use winapi::shared::netioapi::MIB_UNICASTIPADDRESS_ROW;
+  use winapi::shared::winerror::NO_ERROR;
+  use winapi::shared::ws2def::AF_UNSPEC;
+  use winapi::um::winnt::HANDLE;
+
+  type Sender = mpsc::UnboundedSender<NetworkChange>;
+
+  /// The notifications of the changes of the interfaces, addresses and routes
+  /// of the system, which are sent from the callbacks of the system's thread
+  /// pool.
+  pub struct NetworkChangeSource {
+    receiver: mpsc::UnboundedReceiver<NetworkChange>,
+    handles: Vec<HANDLE>,
+    // Kept alive for as long as the callbacks are registered.
+    senders: Box<[Sender; 3]>,
+  }
+
+  unsafe extern "system" fn on_interface_change(
+    context: *mut c_void,
+    _row: *mut MIB_IPINTERFACE_ROW,
+    _notification_type: MIB_NOTIFICATION_TYPE,
+  ) {
+    // SAFETY: `context` points to a sender that outlives the registration.
+    let _ = (*(context as *const Sender)).send(NetworkChange::Interface);
+  }
+
+  unsafe extern "system" fn on_address_change(
+    context: *mut c_void,
+    _row: *mut MIB_UNICASTIPADDRESS_ROW,
+    _notification_type: MIB_NOTIFICATION_TYPE,
+  ) {
+    // SAFETY: `context` points to a sender that outlives the registration.
+    let _ = (*(context as *const Sender)).send(NetworkChange::Address);
+  }
+
+  unsafe extern "system" fn on_route_change(
+    context: *mut c_void,
+    row: *mut MIB_IPFORWARD_ROW2,
+    _notification_type: MIB_NOTIFICATION_TYPE,
+  ) {
+    // Only the default routes are reported.
+    if !row.is_null() && (*row).DestinationPrefix.PrefixLength != 0 {
+      return;
+    }
+    // SAFETY: `context` points to a sender that outlives the registration.
+    let _ = (*(context as *const Sender)).send(NetworkChange::Route);
+  }
+
+  impl NetworkChangeSource {
+    pub fn new() -> Result<Self, AnyError> {
+      let (sender, receiver) = mpsc::unbounded_channel();
+      let senders = Box::new([sender.clone(), sender.clone(), sender]);
+      let mut source = Self {
+        receiver,
+        handles: vec![],
+        senders,
+      };
+      let contexts: Vec<*mut c_void> = source
+        .senders
+        .iter()
+        .map(|sender| sender as *const Sender as *mut c_void)
+        .collect();
+      let mut handle: HANDLE = std::ptr::null_mut();
+      // SAFETY: winapi calls, with contexts that outlive the registrations.
+      unsafe {
+        check(NotifyIpInterfaceChange(
+          AF_UNSPEC as _,
+          Some(on_interface_change),
+          contexts[0],
+          0,
+          &mut handle,
+        ))?;
+        source.handles.push(handle);
+        check(NotifyUnicastIpAddressChange(
+          AF_UNSPEC as _,
+          Some(on_address_change),
+          contexts[1],
+          0,
+          &mut handle,
+        ))?;
+        source.handles.push(handle);
+        check(NotifyRouteChange2(
+          AF_UNSPEC as _,
+          Some(on_route_change),
+          contexts[2],
+          0,
+          &mut handle,
+        ))?;
+        source.handles.push(handle);
+      }
+      Ok(source)
+    }
+
+    pub async fn recv(&mut self) -> Result<Vec<NetworkChange>, AnyError> {
+      let mut changes = vec![];
+      if let Some(change) = self.receiver.recv().await {
+        changes.push(change);
+      }
+      while let Ok(change) = self.receiver.try_recv() {
+        if !changes.contains(&change) {
+          changes.push(change);
+        }
+      }
+      Ok(changes)
+    }
+  }
+
+  impl Drop for NetworkChangeSource {
+    fn drop(&mut self) {
+      for handle in self.handles.drain(..) {
+        // SAFETY: `handle` was returned by a notification registration, and
+        // CancelMibChangeNotify2 waits for the running callbacks.
+        unsafe { CancelMibChangeNotify2(handle) };
+      }
+    }
+  }
+
+  fn check(result: u32) -> Result<(), AnyError> {
+    if result == NO_ERROR {
+      Ok(())
+    } else {
+      Err(std::io::Error::from_raw_os_error(result as i32).into())
+    }
+  }
+}
@@ -0,0 +1,131 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::error::AnyError;
+
+// The priorities are nice values, from the highest priority -20 to the lowest
+// priority 19, which are mapped onto the priority classes on Windows like
+// libuv does.
+pub const PRIORITY_HIGHEST: i32 = -20;
+#[cfg(windows)]
+const PRIORITY_HIGH: i32 = -14;
+#[cfg(windows)]
+const PRIORITY_ABOVE_NORMAL: i32 = -7;
+#[cfg(windows)]
+const PRIORITY_NORMAL: i32 = 0;
+#[cfg(windows)]
+const PRIORITY_BELOW_NORMAL: i32 = 10;
+pub const PRIORITY_LOW: i32 = 19;
+
+#[cfg(unix)]
+pub fn get_priority(pid: u32) -> Result<i32, AnyError> {
+  use nix::errno::Errno;
+
+  Errno::clear();
+  // SAFETY: libc call
+  let priority = unsafe { libc::getpriority(libc::PRIO_PROCESS, pid as _) };
+  // -1 is a valid priority, so errors can only be told apart by errno.
+  if priority == -1 && Errno::last() != Errno::UnknownErrno {
+    return Err(std::io::Error::last_os_error().into());
+  }
+  Ok(priority)
+}
+
+#[cfg(unix)]
+pub fn set_priority(pid: u32, priority: i32) -> Result<(), AnyError> {
+  // SAFETY: libc call
+  if unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as _, priority) } == -1
+  {
+    return Err(std::io::Error::last_os_error().into());
+  }
+  Ok(())
+}
+
+#[cfg(windows)]
+pub fn get_priority(pid: u32) -> Result<i32, AnyError> {
+  use winapi::um::processthreadsapi::GetPriorityClass;
+  use winapi::um::winbase::ABOVE_NORMAL_PRIORITY_CLASS;
+  use winapi::um::winbase::BELOW_NORMAL_PRIORITY_CLASS;
+  use winapi::um::winbase::HIGH_PRIORITY_CLASS;
+  use winapi::um::winbase::IDLE_PRIORITY_CLASS;
+  use winapi::um::winbase::REALTIME_PRIORITY_CLASS;
+  use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+
+  with_process_handle(pid, PROCESS_QUERY_LIMITED_INFORMATION, |handle| {
+    // SAFETY: winapi call
+    let priority_class = unsafe { GetPriorityClass(handle) };
+    if priority_class == 0 {
+      return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(match priority_class {
+      REALTIME_PRIORITY_CLASS => PRIORITY_HIGHEST,
+      HIGH_PRIORITY_CLASS => PRIORITY_HIGH,
+      ABOVE_NORMAL_PRIORITY_CLASS => PRIORITY_ABOVE_NORMAL,
+      BELOW_NORMAL_PRIORITY_CLASS => PRIORITY_BELOW_NORMAL,
+      IDLE_PRIORITY_CLASS => PRIORITY_LOW,
+      _ => PRIORITY_NORMAL,
+    })
+  })
+}
+
+#[cfg(windows)]
+pub fn set_priority(pid: u32, priority: i32) -> Result<(), AnyError> {
+  use winapi::shared::minwindef::FALSE;
+  use winapi::um::processthreadsapi::SetPriorityClass;
+  use winapi::um::winbase::ABOVE_NORMAL_PRIORITY_CLASS;
+  use winapi::um::winbase::BELOW_NORMAL_PRIORITY_CLASS;
+  use winapi::um::winbase::HIGH_PRIORITY_CLASS;
+  use winapi::um::winbase::IDLE_PRIORITY_CLASS;
+  use winapi::um::winbase::NORMAL_PRIORITY_CLASS;
+  use winapi::um::winbase::REALTIME_PRIORITY_CLASS;
+  use winapi::um::winnt::PROCESS_SET_INFORMATION;
+
+  let priority_class = if priority < PRIORITY_HIGH {
+    REALTIME_PRIORITY_CLASS
+  } else if priority < PRIORITY_ABOVE_NORMAL {
+    HIGH_PRIORITY_CLASS
+  } else if priority < PRIORITY_NORMAL {
+    ABOVE_NORMAL_PRIORITY_CLASS
+  } else if priority < PRIORITY_BELOW_NORMAL {
+    NORMAL_PRIORITY_CLASS
+  } else if priority < PRIORITY_LOW {
+    BELOW_NORMAL_PRIORITY_CLASS
+  } else {
+    IDLE_PRIORITY_CLASS
+  };
+  with_process_handle(pid, PROCESS_SET_INFORMATION, |handle| {
+    // SAFETY: winapi call
+    if unsafe { SetPriorityClass(handle, priority_class) } == FALSE {
+      return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+  })
+}
+
+/// Calls `f` with a handle of the process `pid`, or of the current process if
+/// `pid` is 0.
+#[cfg(windows)]
+fn with_process_handle<T>(
+  pid: u32,
+  access: winapi::shared::minwindef::DWORD,
+  f: impl FnOnce(winapi::um::winnt::HANDLE) -> Result<T, AnyError>,
+) -> Result<T, AnyError> {
+  use winapi::shared::minwindef::FALSE;
+  use winapi::um::handleapi::CloseHandle;
+  use winapi::um::processthreadsapi::GetCurrentProcess;
+  use winapi::um::processthreadsapi::OpenProcess;
+
+  if pid == 0 {
+    // SAFETY: winapi call
+    // this handle is a constant—no need to close it
+    return f(unsafe { GetCurrentProcess() });
+  }
+  // SAFETY: winapi call
+  let handle = unsafe { OpenProcess(access, FALSE, pid) };
+  if handle.is_null() {
+    return Err(std::io::Error::last_os_error().into());
+  }
+  let result = f(handle);
+  // SAFETY: winapi call
+  unsafe { CloseHandle(handle) };
+  result
+}
@@ -391,3 +391,106 @@ pub fn os_uptime() -> u64 {
 
   uptime
 }
+
+#[derive(serde::Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceUsage {
+  /// In microseconds.
+  pub user_cpu_time: u64,
+  /// In microseconds.
+  pub system_cpu_time: u64,
+  /// In bytes.
+  pub max_rss: u64,
+  pub minor_page_faults: u64,
+  pub major_page_faults: u64,
+  pub fs_read: u64,
+  pub fs_write: u64,
+}
+
+pub fn resource_usage() -> ResourceUsage {
+  let mut usage = ResourceUsage::default();
+  #[cfg(unix)]
+  {
+    let mut rusage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    // SAFETY: `rusage` is a valid pointer to a `libc::rusage` struct.
+    let res =
+      unsafe { libc::getrusage(libc::RUSAGE_SELF, rusage.as_mut_ptr()) };
+    if res == 0 {
+      // SAFETY: `getrusage` initializes the struct.
+      let rusage = unsafe { rusage.assume_init() };
+      let micros = |time: libc::timeval| {
+        time.tv_sec as u64 * 1_000_000 + time.tv_usec as u64
+      };
+      usage.user_cpu_time = micros(rusage.ru_utime);
+      usage.system_cpu_time = micros(rusage.ru_stime);
+      // The maximum resident set size is in bytes on macOS, and in kilobytes
+      // on the other platforms.
+      usage.max_rss = if cfg!(target_vendor = "apple") {
+        rusage.ru_maxrss as u64
+      } else {
+        rusage.ru_maxrss as u64 * 1024
+      };
+      usage.minor_page_faults = rusage.ru_minflt as u64;
+      usage.major_page_faults = rusage.ru_majflt as u64;
+      usage.fs_read = rusage.ru_inblock as u64;
+      usage.fs_write = rusage.ru_oublock as u64;
+    }
+  }
+  #[cfg(target_family = "windows")]
+  // SAFETY: windows API usage
+  unsafe {
+    use std::mem;
+    use winapi::shared::minwindef::DWORD;
+    use winapi::shared::minwindef::FALSE;
+    use winapi::shared::minwindef::FILETIME;
+    use winapi::um::processthreadsapi::GetCurrentProcess;
+    use winapi::um::processthreadsapi::GetProcessTimes;
+    use winapi::um::psapi::GetProcessMemoryInfo;
+    use winapi::um::psapi::PROCESS_MEMORY_COUNTERS;
+    use winapi::um::winbase::GetProcessIoCounters;
+    use winapi::um::winnt::IO_COUNTERS;
+
+    // this handle is a constant—no need to close it
+    let current_process = GetCurrentProcess();
+
+    // The times are in units of 100 nanoseconds.
+    let micros = |time: FILETIME| {
+      (((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64) / 10
+    };
+    let mut creation_time: FILETIME = mem::zeroed();
+    let mut exit_time: FILETIME = mem::zeroed();
+    let mut kernel_time: FILETIME = mem::zeroed();
+    let mut user_time: FILETIME = mem::zeroed();
+    if GetProcessTimes(
+      current_process,
+      &mut creation_time,
+      &mut exit_time,
+      &mut kernel_time,
+      &mut user_time,
+    ) != FALSE
+    {
+      usage.user_cpu_time = micros(user_time);
+      usage.system_cpu_time = micros(kernel_time);
+    }
+
+    let mut pmc: PROCESS_MEMORY_COUNTERS = mem::zeroed();
+    if GetProcessMemoryInfo(
+      current_process,
+      &mut pmc,
+      mem::size_of::<PROCESS_MEMORY_COUNTERS>() as DWORD,
+    ) != FALSE
+    {
+      usage.max_rss = pmc.PeakWorkingSetSize as u64;
+      // Windows doesn't distinguish the page faults that require IO, so they
+      // are all reported as major page faults, like libuv does.
+      usage.major_page_faults = pmc.PageFaultCount as u64;
+    }
+
+    let mut io_counters: IO_COUNTERS = mem::zeroed();
+    if GetProcessIoCounters(current_process, &mut io_counters) != FALSE {
+      usage.fs_read = io_counters.ReadOperationCount;
+      usage.fs_write = io_counters.WriteOperationCount;
+    }
+  }
+  usage
+}
@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use crate::permissions::parse_sys_kind;
+use crate::permissions::NetDescriptor;
 use crate::permissions::PermissionsContainer;
 use deno_core::error::custom_error;
 use deno_core::error::uri_error;
@@ -10,6 +11,7 @@ use deno_core::url;
 use deno_core::OpState;
 use serde::Deserialize;
 use std::path::Path;
+use std::str::FromStr;
 
 deno_core::extension!(
   deno_permissions,
@@ -31,6 +33,14 @@ pub struct PermissionArgs {
   variable: Option<String>,
   kind: Option<String>,
   command: Option<String>,
+  cid: Option<u32>,
+  port: Option<u32>,
+}
+
+impl PermissionArgs {
+  fn vsock_addr(&self) -> Option<(u32, Option<u32>)> {
+    self.cid.map(|cid| (cid, self.port))
+  }
 }
 
 #[op]
@@ -50,6 +60,7 @@ pub fn op_query_permission(
       }
       .as_ref(),
     ),
+    "vsock" => permissions.vsock.query(args.vsock_addr()),
     "env" => permissions.env.query(args.variable.as_deref()),
     "sys" => permissions
       .sys
@@ -84,6 +95,7 @@ pub fn op_revoke_permission(
       }
       .as_ref(),
     ),
+    "vsock" => permissions.vsock.revoke(args.vsock_addr()),
     "env" => permissions.env.revoke(args.variable.as_deref()),
     "sys" => permissions
       .sys
@@ -118,6 +130,7 @@ pub fn op_request_permission(
       }
       .as_ref(),
     ),
+    "vsock" => permissions.vsock.request(args.vsock_addr()),
     "env" => permissions.env.request(args.variable.as_deref()),
     "sys" => permissions
       .sys
@@ -136,11 +149,27 @@ pub fn op_request_permission(
 }
 
 fn parse_host(host_str: &str) -> Result<(String, Option<u16>), AnyError> {
+  // Sockets in the Linux abstract namespace, like "@name"
+  if host_str.starts_with('@') {
+    return Ok((host_str.to_string(), None));
+  }
+  // Ranges of IP addresses in CIDR notation, like "10.0.0.0/8"
+  if host_str.contains('/') {
+    if let Ok(range) = NetDescriptor::from_str(host_str) {
+      return Ok((range.0, range.1));
+    }
+  }
   let url = url::Url::parse(&format!("http://{host_str}/"))
     .map_err(|_| uri_error("Invalid host"))?;
   if url.path() != "/" {
     return Err(uri_error("Invalid host"));
   }
   let hostname = url.host_str().unwrap();
+  // Wildcards for all the subdomains, like "*.deno.land"
+  if hostname.contains('*') {
+    let host = NetDescriptor::from_str(host_str)
+      .map_err(|_| uri_error("Invalid host"))?;
+    return Ok((host.0, host.1));
+  }
   Ok((hostname.to_string(), url.port()))
 }
@@ -1,7 +1,19 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use super::check_unstable;
+use super::ipc::add_ipc_resource;
+use super::ipc::attach_ipc;
+#[cfg(unix)]
+use super::pty::attach_pty;
+#[cfg(windows)]
+use super::pty::PtyFd;
+use super::pty::PtyResource;
+use super::pty::PtySize;
 use crate::permissions::PermissionsContainer;
+#[cfg(not(target_os = "linux"))]
+use deno_core::error::not_supported;
+#[cfg(target_os = "linux")]
+use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::op;
 use deno_core::serde_json;
@@ -27,6 +39,8 @@ use tokio::process::Command;
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
+#[cfg(target_os = "linux")]
+use std::os::unix::ffi::OsStringExt;
 #[cfg(unix)]
 use std::os::unix::prelude::ExitStatusExt;
 #[cfg(unix)]
@@ -109,13 +123,46 @@ deno_core::extension!(
     deprecated::op_run,
     deprecated::op_run_status,
     deprecated::op_kill,
+    super::pty::op_pty_resize,
+    super::ipc::op_ipc_parent_open,
   ],
   customizer = |ext: &mut deno_core::ExtensionBuilder| {
     ext.force_op_registration();
   },
 );
 
-struct ChildResource(tokio::process::Child);
+/// A child spawned by a `std::process::Command`, or with a pseudo console on
+/// Windows, see `spawn_pty_child()`.
+enum ChildProcess {
+  Command(tokio::process::Child),
+  #[cfg(windows)]
+  Pty(super::pty::PtyChild),
+}
+
+impl ChildProcess {
+  async fn wait(&mut self) -> std::io::Result<ExitStatus> {
+    match self {
+      ChildProcess::Command(child) => child.wait().await,
+      #[cfg(windows)]
+      ChildProcess::Pty(child) => child.wait().await,
+    }
+  }
+
+  fn start_kill(&mut self) -> std::io::Result<()> {
+    match self {
+      ChildProcess::Command(child) => child.start_kill(),
+      #[cfg(windows)]
+      ChildProcess::Pty(child) => child.kill(),
+    }
+  }
+}
+
+struct ChildResource {
+  child: AsyncRefCell<ChildProcess>,
+  /// Detached children outlive the runtime, the others are killed when the
+  /// resource is dropped, which is when the runtime exits before they do.
+  detached: bool,
+}
 
 impl Resource for ChildResource {
   fn name(&self) -> Cow<str> {
@@ -123,6 +170,18 @@ impl Resource for ChildResource {
   }
 }
 
+impl Drop for ChildResource {
+  fn drop(&mut self) {
+    if !self.detached {
+      // SAFETY: the borrows of the cell hold a reference to the resource, so
+      // none are left once it is dropped.
+      let child = unsafe { &mut *self.child.as_ptr() };
+      // Fails if the child has already exited.
+      let _ = child.start_kill();
+    }
+  }
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SpawnArgs {
@@ -137,11 +196,44 @@ pub struct SpawnArgs {
   uid: Option<u32>,
   #[cfg(windows)]
   windows_raw_arguments: bool,
+  #[serde(default)]
+  process_group: bool,
+  #[serde(default)]
+  detached: bool,
+  #[serde(default)]
+  pty: Option<PtySize>,
+  #[serde(default)]
+  ipc: bool,
+  #[serde(default)]
+  limits: Option<ResourceLimits>,
+  #[serde(default)]
+  cgroup: Option<String>,
 
   #[serde(flatten)]
   stdio: ChildStdio,
 }
 
+impl SpawnArgs {
+  /// Whether the subprocess runs with another environment or working
+  /// directory than this process, see `check_run()`.
+  fn is_env_changed(&self) -> bool {
+    self.clear_env || !self.env.is_empty() || self.cwd.is_some()
+  }
+}
+
+/// The resource limits of a subprocess, which are set as both its soft and
+/// hard limits, so that it can't raise them.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLimits {
+  /// The CPU time, in seconds.
+  cpu_time: Option<u64>,
+  /// The size of the virtual memory, in bytes.
+  memory: Option<u64>,
+  /// The number of open file descriptors.
+  open_files: Option<u64>,
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChildStdio {
@@ -205,10 +297,13 @@ fn create_command(
   state: &mut OpState,
   args: SpawnArgs,
   api_name: &str,
-) -> Result<std::process::Command, AnyError> {
-  state
-    .borrow_mut::<PermissionsContainer>()
-    .check_run(&args.cmd, api_name)?;
+) -> Result<PreparedCommand, AnyError> {
+  state.borrow_mut::<PermissionsContainer>().check_run(
+    &args.cmd,
+    &args.args,
+    args.is_env_changed(),
+    api_name,
+  )?;
 
   let mut command = std::process::Command::new(args.cmd);
 
@@ -241,6 +336,74 @@ fn create_command(
   if let Some(uid) = args.uid {
     command.uid(uid);
   }
+  // The child leads a new process group, whose id is its pid, so that it can
+  // be signalled together with its descendants. A new session, that is
+  // created for detached children and pseudo-terminals, is also a new process
+  // group, and can't be created by a process group leader.
+  #[cfg(unix)]
+  if args.process_group && !args.detached && args.pty.is_none() {
+    command.process_group(0);
+  }
+  // Detached children don't receive the signals of the terminal session of
+  // the parent, like SIGHUP when it is closed. Pseudo-terminals are attached
+  // in a new session of their own.
+  #[cfg(unix)]
+  if args.detached && args.pty.is_none() {
+    // SAFETY: only async-signal-safe functions are called after the fork.
+    unsafe {
+      command.pre_exec(|| {
+        if libc::setsid() == -1 {
+          return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+      });
+    }
+  }
+  // Detached children lead a process group too, like the session that they
+  // lead on Unix.
+  #[cfg(windows)]
+  let leads_process_group = args.process_group || args.detached;
+  #[cfg(windows)]
+  {
+    use winapi::um::winbase::CREATE_BREAKAWAY_FROM_JOB;
+    use winapi::um::winbase::CREATE_NEW_PROCESS_GROUP;
+    use winapi::um::winbase::CREATE_SUSPENDED;
+    use winapi::um::winbase::DETACHED_PROCESS;
+
+    let mut flags = 0;
+    if args.process_group {
+      flags |= CREATE_NEW_PROCESS_GROUP;
+    }
+    // Detached children have no console, and aren't terminated with the job
+    // object of the parent, if any.
+    if args.detached {
+      flags |=
+        DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP | CREATE_BREAKAWAY_FROM_JOB;
+    }
+    // The child is resumed once it's assigned to its job object, see
+    // `JobObject::assign()`.
+    if args.limits.is_some() || leads_process_group {
+      flags |= CREATE_SUSPENDED;
+    }
+    command.creation_flags(flags);
+  }
+  if args.limits.is_some() {
+    check_unstable(state, "Deno.Command.limits");
+  }
+  #[cfg(unix)]
+  if let Some(limits) = args.limits {
+    set_resource_limits(&mut command, limits)?;
+  }
+  #[cfg(windows)]
+  let job = if args.limits.is_some() || leads_process_group {
+    Some(JobObject::new(args.limits)?)
+  } else {
+    None
+  };
+  if let Some(cgroup) = args.cgroup {
+    check_unstable(state, "Deno.Command.cgroup");
+    join_cgroup(state, &mut command, &cgroup, api_name)?;
+  }
   #[cfg(unix)]
   // TODO(bartlomieju):
   #[allow(clippy::undocumented_unsafe_blocks)]
@@ -261,7 +424,289 @@ fn create_command(
     value => value.as_stdio(),
   });
 
-  Ok(command)
+  Ok(PreparedCommand {
+    command,
+    #[cfg(windows)]
+    job,
+    #[cfg(windows)]
+    leads_process_group,
+  })
+}
+
+#[cfg(unix)]
+fn set_resource_limits(
+  command: &mut std::process::Command,
+  limits: ResourceLimits,
+) -> Result<(), AnyError> {
+  let rlimits = [
+    (libc::RLIMIT_CPU, limits.cpu_time),
+    (libc::RLIMIT_AS, limits.memory),
+    (libc::RLIMIT_NOFILE, limits.open_files),
+  ];
+  // SAFETY: only async-signal-safe functions are called after the fork.
+  unsafe {
+    command.pre_exec(move || {
+      for (resource, limit) in rlimits {
+        let Some(limit) = limit else {
+          continue;
+        };
+        let rlimit = libc::rlimit {
+          rlim_cur: limit as libc::rlim_t,
+          rlim_max: limit as libc::rlim_t,
+        };
+        if libc::setrlimit(resource, &rlimit) == -1 {
+          return Err(std::io::Error::last_os_error());
+        }
+      }
+      Ok(())
+    });
+  }
+  Ok(())
+}
+
+/// A job object, which enforces the resource limits of the processes that
+/// are assigned to it, and of their descendants, on Windows. It's also what
+/// makes up a process group there, see `ProcessGroups`.
+#[cfg(windows)]
+mod job_object {
+  use super::ResourceLimits;
+  use deno_core::error::not_supported;
+  use deno_core::error::AnyError;
+  use ntapi::ntpsapi::NtResumeProcess;
+  use ntapi::ntrtl::RtlNtStatusToDosError;
+  use std::io::Error;
+  use std::os::windows::io::RawHandle;
+  use winapi::shared::minwindef::DWORD;
+  use winapi::shared::minwindef::FALSE;
+  use winapi::shared::minwindef::LPVOID;
+  use winapi::um::handleapi::CloseHandle;
+  use winapi::um::jobapi2::AssignProcessToJobObject;
+  use winapi::um::jobapi2::QueryInformationJobObject;
+  use winapi::um::jobapi2::SetInformationJobObject;
+  use winapi::um::jobapi2::TerminateJobObject;
+  use winapi::um::processthreadsapi::TerminateProcess;
+  use winapi::um::winbase::CreateJobObjectW;
+  use winapi::um::winnt::JobObjectBasicAccountingInformation;
+  use winapi::um::winnt::JobObjectExtendedLimitInformation;
+  use winapi::um::winnt::HANDLE;
+  use winapi::um::winnt::JOBOBJECT_BASIC_ACCOUNTING_INFORMATION;
+  use winapi::um::winnt::JOBOBJECT_EXTENDED_LIMIT_INFORMATION;
+  use winapi::um::winnt::JOB_OBJECT_LIMIT_BREAKAWAY_OK;
+  use winapi::um::winnt::JOB_OBJECT_LIMIT_PROCESS_MEMORY;
+  use winapi::um::winnt::JOB_OBJECT_LIMIT_PROCESS_TIME;
+
+  pub struct JobObject(HANDLE);
+
+  impl JobObject {
+    pub fn new(limits: Option<ResourceLimits>) -> Result<Self, AnyError> {
+      // The number of handles of a process can't be limited.
+      if limits.as_ref().is_some_and(|l| l.open_files.is_some()) {
+        return Err(not_supported());
+      }
+      // SAFETY: winapi call
+      let handle =
+        unsafe { CreateJobObjectW(std::ptr::null_mut(), std::ptr::null()) };
+      if handle.is_null() {
+        return Err(Error::last_os_error().into());
+      }
+      let job = Self(handle);
+
+      // SAFETY: the structure is plain data, for which zeroes are valid.
+      let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION =
+        unsafe { std::mem::zeroed() };
+      // Detached descendants leave the job, like they leave the process group
+      // on Unix, unless it enforces resource limits, which they would escape.
+      if limits.is_none() {
+        info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_BREAKAWAY_OK;
+      }
+      let limits = limits.unwrap_or_default();
+      if let Some(cpu_time) = limits.cpu_time {
+        info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_TIME;
+        // The time is in units of 100 nanoseconds.
+        let cpu_time = i64::try_from(cpu_time)
+          .unwrap_or(i64::MAX)
+          .saturating_mul(10_000_000);
+        // SAFETY: the union is a 64-bit integer.
+        unsafe {
+          *info
+            .BasicLimitInformation
+            .PerProcessUserTimeLimit
+            .QuadPart_mut() = cpu_time;
+        }
+      }
+      if let Some(memory) = limits.memory {
+        info.BasicLimitInformation.LimitFlags |=
+          JOB_OBJECT_LIMIT_PROCESS_MEMORY;
+        info.ProcessMemoryLimit = usize::try_from(memory).unwrap_or(usize::MAX);
+      }
+      // SAFETY: winapi call
+      let is_set = unsafe {
+        SetInformationJobObject(
+          job.0,
+          JobObjectExtendedLimitInformation,
+          &mut info as *mut _ as LPVOID,
+          std::mem::size_of_val(&info) as DWORD,
+        )
+      };
+      if is_set == FALSE {
+        return Err(Error::last_os_error().into());
+      }
+      Ok(job)
+    }
+
+    /// Assigns the `process`, which was created suspended, to the job before
+    /// resuming it, so that it can't run or create processes outside of the
+    /// job. The process is terminated if that fails.
+    pub fn assign(&self, process: RawHandle) -> Result<(), AnyError> {
+      let process = process as HANDLE;
+      // SAFETY: winapi calls
+      unsafe {
+        let err = if AssignProcessToJobObject(self.0, process) == FALSE {
+          Error::last_os_error()
+        } else {
+          let status = NtResumeProcess(process);
+          if status >= 0 {
+            return Ok(());
+          }
+          Error::from_raw_os_error(RtlNtStatusToDosError(status) as i32)
+        };
+        TerminateProcess(process, 1);
+        Err(err.into())
+      }
+    }
+
+    /// Whether all of the processes of the job have exited.
+    pub fn is_empty(&self) -> bool {
+      // SAFETY: the structure is plain data, for which zeroes are valid.
+      let mut info: JOBOBJECT_BASIC_ACCOUNTING_INFORMATION =
+        unsafe { std::mem::zeroed() };
+      // SAFETY: winapi call
+      let is_queried = unsafe {
+        QueryInformationJobObject(
+          self.0,
+          JobObjectBasicAccountingInformation,
+          &mut info as *mut _ as LPVOID,
+          std::mem::size_of_val(&info) as DWORD,
+          std::ptr::null_mut(),
+        )
+      };
+      is_queried != FALSE && info.ActiveProcesses == 0
+    }
+
+    pub fn terminate(&self) -> Result<(), AnyError> {
+      // SAFETY: winapi call
+      if unsafe { TerminateJobObject(self.0, 1) } == FALSE {
+        return Err(Error::last_os_error().into());
+      }
+      Ok(())
+    }
+  }
+
+  impl Drop for JobObject {
+    fn drop(&mut self) {
+      // The job object lives on as long as the processes that are assigned
+      // to it.
+      // SAFETY: winapi call
+      unsafe { CloseHandle(self.0) };
+    }
+  }
+}
+
+#[cfg(windows)]
+use job_object::JobObject;
+
+/// The process groups that are led by children on Windows, by the pid of
+/// their leader. Windows can't list the processes of a process group, so a
+/// group is made up of the processes of the job object that its leader is
+/// assigned to.
+#[cfg(windows)]
+#[derive(Default)]
+struct ProcessGroups(std::collections::HashMap<u32, JobObject>);
+
+#[cfg(windows)]
+impl ProcessGroups {
+  fn insert(state: &mut OpState, pid: u32, job: JobObject) {
+    if !state.has::<Self>() {
+      state.put(Self::default());
+    }
+    let groups = &mut state.borrow_mut::<Self>().0;
+    groups.retain(|_, job| !job.is_empty());
+    groups.insert(pid, job);
+  }
+}
+
+/// A command, and on Windows the job object that its child is assigned to
+/// once it's spawned, which enforces the resource limits of the child, and
+/// makes up the process group that it leads, if any.
+struct PreparedCommand {
+  command: std::process::Command,
+  #[cfg(windows)]
+  job: Option<JobObject>,
+  #[cfg(windows)]
+  leads_process_group: bool,
+}
+
+impl PreparedCommand {
+  fn output(self) -> Result<std::process::Output, AnyError> {
+    let mut command = self.command;
+    #[cfg(windows)]
+    if let Some(job) = self.job {
+      use std::os::windows::io::AsRawHandle;
+
+      let child = command.spawn()?;
+      job.assign(child.as_raw_handle())?;
+      return Ok(child.wait_with_output()?);
+    }
+    Ok(command.output()?)
+  }
+}
+
+/// Moves the child into the cgroup v2 at `cgroup`, which is either absolute
+/// or relative to `/sys/fs/cgroup`, before it executes the command, so that
+/// the limits of the cgroup apply to it and its descendants from the start.
+#[cfg(target_os = "linux")]
+fn join_cgroup(
+  state: &mut OpState,
+  command: &mut std::process::Command,
+  cgroup: &str,
+  api_name: &str,
+) -> Result<(), AnyError> {
+  let procs = std::path::Path::new("/sys/fs/cgroup")
+    .join(cgroup)
+    .join("cgroup.procs");
+  state
+    .borrow_mut::<PermissionsContainer>()
+    .check_write(&procs, api_name)?;
+  let procs = std::ffi::CString::new(procs.into_os_string().into_vec())
+    .map_err(|_| type_error("Invalid cgroup path"))?;
+  // SAFETY: only async-signal-safe functions are called after the fork.
+  unsafe {
+    command.pre_exec(move || {
+      let fd = libc::open(procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+      if fd == -1 {
+        return Err(std::io::Error::last_os_error());
+      }
+      // Writing 0 moves the writing process.
+      let written = libc::write(fd, b"0".as_ptr() as *const libc::c_void, 1);
+      let err = std::io::Error::last_os_error();
+      libc::close(fd);
+      if written == -1 {
+        return Err(err);
+      }
+      Ok(())
+    });
+  }
+  Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn join_cgroup(
+  _state: &mut OpState,
+  _command: &mut std::process::Command,
+  _cgroup: &str,
+  _api_name: &str,
+) -> Result<(), AnyError> {
+  Err(not_supported())
 }
 
 #[derive(Serialize)]
@@ -272,20 +717,48 @@ struct Child {
   stdin_rid: Option<ResourceId>,
   stdout_rid: Option<ResourceId>,
   stderr_rid: Option<ResourceId>,
+  pty_rid: Option<ResourceId>,
+  ipc_rid: Option<ResourceId>,
 }
 
 fn spawn_child(
   state: &mut OpState,
-  command: std::process::Command,
+  command: PreparedCommand,
+  pty: Option<PtySize>,
+  ipc: bool,
+  detached: bool,
 ) -> Result<Child, AnyError> {
+  let PreparedCommand {
+    mut command,
+    #[cfg(windows)]
+    job,
+    #[cfg(windows)]
+    leads_process_group,
+  } = command;
+  #[cfg(unix)]
+  let pty = pty.map(|size| attach_pty(&mut command, size)).transpose()?;
+  // Pseudo consoles are attached when the process is created on Windows, see
+  // `spawn_pty_child()`.
+  #[cfg(windows)]
+  let pty: Option<PtyFd> = {
+    debug_assert!(pty.is_none());
+    None
+  };
+  let ipc = ipc.then(|| attach_ipc(&mut command)).transpose()?;
   let mut command = tokio::process::Command::from(command);
-  // TODO(@crowlkats): allow detaching processes.
-  //  currently deno will orphan a process when exiting with an error or Deno.exit()
-  // We want to kill child when it's closed
-  command.kill_on_drop(true);
+  // The child is killed when it's closed, unless it is detached, see
+  // `ChildResource`.
+  command.kill_on_drop(false);
 
   let mut child = command.spawn()?;
   let pid = child.id().expect("Process ID should be set.");
+  #[cfg(windows)]
+  if let Some(job) = job {
+    job.assign(child.raw_handle().expect("Process should be running."))?;
+    if leads_process_group {
+      ProcessGroups::insert(state, pid, job);
+    }
+  }
 
   let stdin_rid = child
     .stdin
@@ -302,7 +775,23 @@ fn spawn_child(
     .take()
     .map(|stderr| state.resource_table.add(ChildStderrResource::from(stderr)));
 
-  let child_rid = state.resource_table.add(ChildResource(child));
+  // The slave side of the pseudo-terminal and the child end of the IPC
+  // channel are only kept open by the child, so that reading them ends once
+  // it has exited.
+  drop(command);
+  let pty_rid = match pty {
+    Some(fd) => Some(state.resource_table.add(PtyResource::new(fd)?)),
+    None => None,
+  };
+  let ipc_rid = match ipc {
+    Some((parent, _child)) => Some(add_ipc_resource(state, parent)?),
+    None => None,
+  };
+
+  let child_rid = state.resource_table.add(ChildResource {
+    child: AsyncRefCell::new(ChildProcess::Command(child)),
+    detached,
+  });
 
   Ok(Child {
     rid: child_rid,
@@ -310,6 +799,8 @@ fn spawn_child(
     stdin_rid,
     stdout_rid,
     stderr_rid,
+    pty_rid,
+    ipc_rid,
   })
 }
 
@@ -319,8 +810,114 @@ fn op_spawn_child(
   args: SpawnArgs,
   api_name: String,
 ) -> Result<Child, AnyError> {
+  let pty = args.pty;
+  if pty.is_some() {
+    check_unstable(state, "Deno.Command.pty");
+  }
+  let ipc = args.ipc;
+  if ipc {
+    check_unstable(state, "Deno.Command.ipc");
+  }
+  #[cfg(windows)]
+  if let Some(size) = pty {
+    return spawn_pty_child(state, args, size, &api_name);
+  }
+  let detached = args.detached;
   let command = create_command(state, args, &api_name)?;
-  spawn_child(state, command)
+  spawn_child(state, command, pty, ipc, detached)
+}
+
+/// Spawns a child whose console is a pseudo console, which can't be done
+/// with a `std::process::Command` on Windows. The stdio of the child is the
+/// pseudo console, like the pseudo-terminal is on Unix.
+#[cfg(windows)]
+fn spawn_pty_child(
+  state: &mut OpState,
+  args: SpawnArgs,
+  size: PtySize,
+  api_name: &str,
+) -> Result<Child, AnyError> {
+  use super::pty::append_arg;
+  use super::pty::spawn_pty;
+  use winapi::um::winbase::CREATE_BREAKAWAY_FROM_JOB;
+  use winapi::um::winbase::CREATE_NEW_PROCESS_GROUP;
+  use winapi::um::winbase::CREATE_SUSPENDED;
+
+  state.borrow_mut::<PermissionsContainer>().check_run(
+    &args.cmd,
+    &args.args,
+    args.is_env_changed(),
+    api_name,
+  )?;
+  if args.ipc || args.limits.is_some() || args.cgroup.is_some() {
+    return Err(not_supported());
+  }
+
+  let mut command_line = String::new();
+  append_arg(&mut command_line, &args.cmd);
+  for arg in &args.args {
+    if args.windows_raw_arguments {
+      command_line.push(' ');
+      command_line.push_str(arg);
+    } else {
+      append_arg(&mut command_line, arg);
+    }
+  }
+
+  let mut env: Vec<(String, String)> = if args.clear_env {
+    vec![]
+  } else {
+    std::env::vars_os()
+      .map(|(key, value)| {
+        (
+          key.to_string_lossy().into_owned(),
+          value.to_string_lossy().into_owned(),
+        )
+      })
+      .collect()
+  };
+  // The names of environment variables are case insensitive on Windows.
+  for (key, value) in args.env {
+    env.retain(|(k, _)| !k.eq_ignore_ascii_case(&key));
+    env.push((key, value));
+  }
+
+  // Unlike other detached children, this one has a console: the pseudo
+  // console.
+  let mut flags = 0;
+  let leads_process_group = args.process_group || args.detached;
+  if leads_process_group {
+    flags |= CREATE_NEW_PROCESS_GROUP | CREATE_SUSPENDED;
+  }
+  if args.detached {
+    flags |= CREATE_BREAKAWAY_FROM_JOB;
+  }
+  let job = leads_process_group
+    .then(|| JobObject::new(None))
+    .transpose()?;
+
+  let (child, pty) =
+    spawn_pty(&command_line, args.cwd.as_deref(), &env, flags, size)?;
+  let pid = child.id();
+  if let Some(job) = job {
+    job.assign(child.raw_handle())?;
+    ProcessGroups::insert(state, pid, job);
+  }
+  let pty_rid = state.resource_table.add(pty);
+  let child_rid = state.resource_table.add(ChildResource {
+    child: AsyncRefCell::new(ChildProcess::Pty(child)),
+    detached: args.detached,
+  });
+
+  Ok(Child {
+    rid: child_rid,
+    pid,
+    stdin_rid: None,
+    stdout_rid: None,
+    stderr_rid: None,
+    pty_rid: Some(pty_rid),
+    ipc_rid: None,
+  })
 }
 
 #[op]
@@ -331,14 +928,14 @@ async fn op_spawn_wait(
   let resource = state
     .borrow_mut()
     .resource_table
-    .take::<ChildResource>(rid)?;
-  Rc::try_unwrap(resource)
-    .ok()
-    .unwrap()
-    .0
+    .get::<ChildResource>(rid)?;
+  let result = RcRef::map(&resource, |r| &r.child)
+    .borrow_mut()
+    .await
     .wait()
-    .await?
-    .try_into()
+    .await;
+  state.borrow_mut().resource_table.close(rid).ok();
+  result?.try_into()
 }
 
 #[op]
@@ -418,9 +1015,14 @@ mod deprecated {
     run_args: RunArgs,
   ) -> Result<RunInfo, AnyError> {
     let args = run_args.cmd;
-    state
-      .borrow_mut::<PermissionsContainer>()
-      .check_run(&args[0], "Deno.run()")?;
+    let is_env_changed =
+      run_args.clear_env || !run_args.env.is_empty() || run_args.cwd.is_some();
+    state.borrow_mut::<PermissionsContainer>().check_run(
+      &args[0],
+      &args[1..],
+      is_env_changed,
+      "Deno.run()",
+    )?;
     let env = run_args.env;
     let cwd = run_args.cwd;
 
@@ -576,11 +1178,40 @@ mod deprecated {
   }
 
   #[cfg(not(unix))]
-  pub fn kill(pid: i32, signal: &str) -> Result<(), AnyError> {
+  pub fn kill(
+    state: &mut OpState,
+    pid: i32,
+    signal: &str,
+  ) -> Result<(), AnyError> {
     use deno_core::error::type_error;
     use std::io::Error;
     use std::io::ErrorKind::NotFound;
     use winapi::shared::minwindef::DWORD;
+
+    if !matches!(signal, "SIGKILL" | "SIGTERM") {
+      Err(type_error(format!("Invalid signal: {signal}")))
+    } else if pid == 0 || pid == -1 {
+      Err(type_error("Invalid pid"))
+    } else if pid < 0 {
+      // Like on Unix, a negative pid is the id of a process group, which is
+      // the pid of the process that leads it. Only the process groups of the
+      // children of this process are known, see `ProcessGroups`.
+      let pgid = -pid as DWORD;
+      let job = state
+        .try_borrow_mut::<ProcessGroups>()
+        .and_then(|groups| groups.0.remove(&pgid))
+        .filter(|job| !job.is_empty())
+        .ok_or_else(|| Error::from(NotFound))?;
+      job.terminate()
+    } else {
+      terminate_process(pid as DWORD)
+    }
+  }
+
+  #[cfg(not(unix))]
+  fn terminate_process(pid: u32) -> Result<(), AnyError> {
+    use std::io::Error;
+    use std::io::ErrorKind::NotFound;
     use winapi::shared::minwindef::FALSE;
     use winapi::shared::minwindef::TRUE;
     use winapi::shared::winerror::ERROR_INVALID_PARAMETER;
@@ -590,32 +1221,26 @@ mod deprecated {
     use winapi::um::processthreadsapi::TerminateProcess;
     use winapi::um::winnt::PROCESS_TERMINATE;
 
-    if !matches!(signal, "SIGKILL" | "SIGTERM") {
-      Err(type_error(format!("Invalid signal: {signal}")))
-    } else if pid <= 0 {
-      Err(type_error("Invalid pid"))
+    let handle =
+      // SAFETY: winapi call
+      unsafe { OpenProcess(PROCESS_TERMINATE, FALSE, pid) };
+
+    if handle.is_null() {
+      // SAFETY: winapi call
+      let err = match unsafe { GetLastError() } {
+        ERROR_INVALID_PARAMETER => Error::from(NotFound), // Invalid `pid`.
+        errno => Error::from_raw_os_error(errno as i32),
+      };
+      Err(err.into())
     } else {
-      let handle =
-        // SAFETY: winapi call
-        unsafe { OpenProcess(PROCESS_TERMINATE, FALSE, pid as DWORD) };
-
-      if handle.is_null() {
-        // SAFETY: winapi call
-        let err = match unsafe { GetLastError() } {
-          ERROR_INVALID_PARAMETER => Error::from(NotFound), // Invalid `pid`.
-          errno => Error::from_raw_os_error(errno as i32),
-        };
-        Err(err.into())
-      } else {
-        // SAFETY: winapi calls
-        unsafe {
-          let is_terminated = TerminateProcess(handle, 1);
-          CloseHandle(handle);
-          match is_terminated {
-            FALSE => Err(Error::last_os_error().into()),
-            TRUE => Ok(()),
-            _ => unreachable!(),
-          }
+      // SAFETY: winapi calls
+      unsafe {
+        let is_terminated = TerminateProcess(handle, 1);
+        CloseHandle(handle);
+        match is_terminated {
+          FALSE => Err(Error::last_os_error().into()),
+          TRUE => Ok(()),
+          _ => unreachable!(),
         }
       }
     }
@@ -631,7 +1256,10 @@ mod deprecated {
     state
       .borrow_mut::<PermissionsContainer>()
       .check_run_all(&api_name)?;
+    #[cfg(unix)]
     kill(pid, &signal)?;
+    #[cfg(not(unix))]
+    kill(state, pid, &signal)?;
     Ok(())
   }
 }
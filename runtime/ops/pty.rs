@@ -0,0 +1,659 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Pseudo-terminals for the subprocesses spawned with the `pty` option of
+//! `Deno.Command`.
+
+use deno_core::error::AnyError;
+use deno_core::op;
+use deno_core::OpState;
+use deno_core::ResourceId;
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct PtySize {
+  columns: u16,
+  rows: u16,
+}
+
+#[cfg(unix)]
+pub use unix::*;
+
+#[cfg(unix)]
+mod unix {
+  use super::PtySize;
+  use deno_core::error::AnyError;
+  use deno_core::AsyncResult;
+  use deno_core::BufView;
+  use deno_core::CancelFuture;
+  use deno_core::CancelHandle;
+  use deno_core::RcRef;
+  use deno_core::Resource;
+  use deno_core::WriteOutcome;
+  use std::borrow::Cow;
+  use std::io;
+  use std::os::unix::io::AsRawFd;
+  use std::os::unix::io::FromRawFd;
+  use std::os::unix::io::OwnedFd;
+  use std::os::unix::process::CommandExt;
+  use std::rc::Rc;
+  use tokio::io::unix::AsyncFd;
+
+  /// The master side of a pseudo-terminal, see `attach_pty()`.
+  pub type PtyFd = OwnedFd;
+
+  fn winsize(size: PtySize) -> libc::winsize {
+    libc::winsize {
+      ws_row: size.rows,
+      ws_col: size.columns,
+      ws_xpixel: 0,
+      ws_ypixel: 0,
+    }
+  }
+
+  /// Opens a pseudo-terminal of the given size, and makes its slave side the
+  /// controlling terminal and the stdio of the `command`. Returns the master
+  /// side, which is closed by the parent once the command is spawned.
+  pub fn attach_pty(
+    command: &mut std::process::Command,
+    size: PtySize,
+  ) -> Result<OwnedFd, AnyError> {
+    let pty = nix::pty::openpty(Some(&winsize(size)), None)?;
+    // SAFETY: the fds were just opened by openpty and nothing else owns them.
+    let (master, slave) = unsafe {
+      (
+        OwnedFd::from_raw_fd(pty.master),
+        OwnedFd::from_raw_fd(pty.slave),
+      )
+    };
+    set_cloexec_nonblock(&master)?;
+
+    command.stdin(slave.try_clone()?);
+    command.stdout(slave.try_clone()?);
+    command.stderr(slave);
+    // SAFETY: only async-signal-safe functions are called after the fork.
+    unsafe {
+      command.pre_exec(|| {
+        // The child leads a new session, of which the pseudo-terminal, that
+        // is its stdin by now, is the controlling terminal.
+        if libc::setsid() == -1 {
+          return Err(io::Error::last_os_error());
+        }
+        if libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+          return Err(io::Error::last_os_error());
+        }
+        Ok(())
+      });
+    }
+    Ok(master)
+  }
+
+  fn set_cloexec_nonblock(fd: &OwnedFd) -> io::Result<()> {
+    // SAFETY: libc calls on a valid fd
+    unsafe {
+      let flags = libc::fcntl(fd.as_raw_fd(), libc::F_GETFL);
+      if flags == -1
+        || libc::fcntl(fd.as_raw_fd(), libc::F_SETFL, flags | libc::O_NONBLOCK)
+          == -1
+        || libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) == -1
+      {
+        return Err(io::Error::last_os_error());
+      }
+    }
+    Ok(())
+  }
+
+  /// The master side of a pseudo-terminal. Reading returns what the
+  /// subprocess writes to the terminal, and writing is its input.
+  pub struct PtyResource {
+    fd: AsyncFd<OwnedFd>,
+    cancel: CancelHandle,
+  }
+
+  impl PtyResource {
+    pub fn new(fd: OwnedFd) -> Result<Self, AnyError> {
+      Ok(Self {
+        fd: AsyncFd::new(fd)?,
+        cancel: Default::default(),
+      })
+    }
+
+    pub fn resize(&self, size: PtySize) -> Result<(), AnyError> {
+      let winsize = winsize(size);
+      // SAFETY: `winsize` is a valid `libc::winsize` struct.
+      let res = unsafe {
+        libc::ioctl(self.fd.as_raw_fd(), libc::TIOCSWINSZ as _, &winsize)
+      };
+      if res == -1 {
+        return Err(io::Error::last_os_error().into());
+      }
+      Ok(())
+    }
+
+    async fn read(&self, limit: usize) -> Result<BufView, AnyError> {
+      let mut buf = vec![0; limit];
+      loop {
+        let mut guard = self.fd.readable().await?;
+        match guard.try_io(|fd| {
+          // SAFETY: `buf` is a valid buffer of the given length.
+          let n = unsafe {
+            libc::read(
+              fd.as_raw_fd(),
+              buf.as_mut_ptr() as *mut libc::c_void,
+              buf.len(),
+            )
+          };
+          if n < 0 {
+            Err(io::Error::last_os_error())
+          } else {
+            Ok(n as usize)
+          }
+        }) {
+          Ok(Ok(n)) => {
+            buf.truncate(n);
+            return Ok(buf.into());
+          }
+          // Reading the master side fails with EIO once all the processes
+          // that have the terminal open have exited, which is the EOF.
+          Ok(Err(err)) if err.raw_os_error() == Some(libc::EIO) => {
+            return Ok(BufView::empty());
+          }
+          Ok(Err(err)) => return Err(err.into()),
+          Err(_would_block) => continue,
+        }
+      }
+    }
+
+    async fn write(&self, buf: &[u8]) -> Result<usize, AnyError> {
+      loop {
+        let mut guard = self.fd.writable().await?;
+        match guard.try_io(|fd| {
+          // SAFETY: `buf` is a valid buffer of the given length.
+          let n = unsafe {
+            libc::write(
+              fd.as_raw_fd(),
+              buf.as_ptr() as *const libc::c_void,
+              buf.len(),
+            )
+          };
+          if n < 0 {
+            Err(io::Error::last_os_error())
+          } else {
+            Ok(n as usize)
+          }
+        }) {
+          Ok(result) => return Ok(result?),
+          Err(_would_block) => continue,
+        }
+      }
+    }
+  }
+
+  impl Resource for PtyResource {
+    fn name(&self) -> Cow<str> {
+      "pty".into()
+    }
+
+    fn read(self: Rc<Self>, limit: usize) -> AsyncResult<BufView> {
+      Box::pin(async move {
+        let cancel = RcRef::map(&self, |r| &r.cancel);
+        PtyResource::read(&self, limit).try_or_cancel(cancel).await
+      })
+    }
+
+    fn write(self: Rc<Self>, view: BufView) -> AsyncResult<WriteOutcome> {
+      Box::pin(async move {
+        let cancel = RcRef::map(&self, |r| &r.cancel);
+        let nwritten = PtyResource::write(&self, &view)
+          .try_or_cancel(cancel)
+          .await?;
+        Ok(WriteOutcome::Partial { nwritten, view })
+      })
+    }
+
+    fn close(self: Rc<Self>) {
+      self.cancel.cancel();
+    }
+  }
+}
+
+#[cfg(windows)]
+pub use windows::*;
+
+#[cfg(windows)]
+mod windows {
+  use super::PtySize;
+  use deno_core::error::AnyError;
+  use deno_core::parking_lot::Mutex;
+  use deno_core::AsyncResult;
+  use deno_core::BufView;
+  use deno_core::CancelFuture;
+  use deno_core::CancelHandle;
+  use deno_core::RcRef;
+  use deno_core::Resource;
+  use deno_core::WriteOutcome;
+  use std::borrow::Cow;
+  use std::ffi::OsStr;
+  use std::fs::File;
+  use std::io;
+  use std::io::Read;
+  use std::io::Write;
+  use std::os::windows::ffi::OsStrExt;
+  use std::os::windows::io::AsRawHandle;
+  use std::os::windows::io::FromRawHandle;
+  use std::os::windows::io::OwnedHandle;
+  use std::os::windows::io::RawHandle;
+  use std::os::windows::process::ExitStatusExt;
+  use std::process::ExitStatus;
+  use std::ptr;
+  use std::rc::Rc;
+  use std::sync::Arc;
+  use winapi::shared::minwindef::DWORD;
+  use winapi::shared::minwindef::FALSE;
+  use winapi::shared::winerror::S_OK;
+  use winapi::um::consoleapi::ClosePseudoConsole;
+  use winapi::um::consoleapi::CreatePseudoConsole;
+  use winapi::um::consoleapi::ResizePseudoConsole;
+  use winapi::um::namedpipeapi::CreatePipe;
+  use winapi::um::processthreadsapi::CreateProcessW;
+  use winapi::um::processthreadsapi::DeleteProcThreadAttributeList;
+  use winapi::um::processthreadsapi::GetExitCodeProcess;
+  use winapi::um::processthreadsapi::InitializeProcThreadAttributeList;
+  use winapi::um::processthreadsapi::TerminateProcess;
+  use winapi::um::processthreadsapi::UpdateProcThreadAttribute;
+  use winapi::um::processthreadsapi::LPPROC_THREAD_ATTRIBUTE_LIST;
+  use winapi::um::processthreadsapi::PROCESS_INFORMATION;
+  use winapi::um::synchapi::WaitForSingleObject;
+  use winapi::um::winbase::CREATE_UNICODE_ENVIRONMENT;
+  use winapi::um::winbase::EXTENDED_STARTUPINFO_PRESENT;
+  use winapi::um::winbase::INFINITE;
+  use winapi::um::winbase::STARTUPINFOEXW;
+  use winapi::um::winbase::WAIT_OBJECT_0;
+  use winapi::um::wincontypes::COORD;
+  use winapi::um::wincontypes::HPCON;
+
+  const PROC_THREAD_ATTRIBUTE_PSEUDOCONSOLE: usize = 0x00020016;
+
+  fn coord(size: PtySize) -> COORD {
+    COORD {
+      X: size.columns as i16,
+      Y: size.rows as i16,
+    }
+  }
+
+  /// A pseudo console, which is closed when dropped.
+  struct PseudoConsole(HPCON);
+
+  // SAFETY: a pseudo console can be used and closed from any thread.
+  unsafe impl Send for PseudoConsole {}
+
+  impl Drop for PseudoConsole {
+    fn drop(&mut self) {
+      // SAFETY: the pseudo console was opened by CreatePseudoConsole, and is
+      // only closed here.
+      unsafe { ClosePseudoConsole(self.0) };
+    }
+  }
+
+  /// Pseudo-terminals are attached when the process is created on Windows,
+  /// see `spawn_pty()`, so there never is one to attach to a
+  /// `std::process::Command`.
+  pub enum PtyFd {}
+
+  /// Spawns the command line with a pseudo console of the given size as its
+  /// console. This is done with `CreateProcessW()`, since
+  /// `std::process::Command` can't create the process with the
+  /// `PROC_THREAD_ATTRIBUTE_PSEUDOCONSOLE` attribute.
+  ///
+  /// `env` is the whole environment of the process.
+  pub fn spawn_pty(
+    command_line: &str,
+    cwd: Option<&str>,
+    env: &[(String, String)],
+    creation_flags: DWORD,
+    size: PtySize,
+  ) -> Result<(PtyChild, PtyResource), AnyError> {
+    // https://learn.microsoft.com/en-us/windows/console/creating-a-pseudoconsole-session
+    let (input_read, input_write) = create_pipe()?;
+    let (output_read, output_write) = create_pipe()?;
+    let mut console = ptr::null_mut();
+    // SAFETY: the pipe handles are valid, and `console` is a valid pointer.
+    let result = unsafe {
+      CreatePseudoConsole(
+        coord(size),
+        input_read.as_raw_handle() as _,
+        output_write.as_raw_handle() as _,
+        0,
+        &mut console,
+      )
+    };
+    if result != S_OK {
+      return Err(io::Error::from_raw_os_error(result).into());
+    }
+    let console = PseudoConsole(console);
+
+    let mut attribute_list_size = 0;
+    // SAFETY: this only queries the size of a list with one attribute, and
+    // fails with ERROR_INSUFFICIENT_BUFFER.
+    unsafe {
+      InitializeProcThreadAttributeList(
+        ptr::null_mut(),
+        1,
+        0,
+        &mut attribute_list_size,
+      )
+    };
+    let mut attribute_list = vec![0u8; attribute_list_size];
+    let attribute_list_ptr =
+      attribute_list.as_mut_ptr() as LPPROC_THREAD_ATTRIBUTE_LIST;
+    // SAFETY: the buffer has the size that was just queried.
+    if unsafe {
+      InitializeProcThreadAttributeList(
+        attribute_list_ptr,
+        1,
+        0,
+        &mut attribute_list_size,
+      )
+    } == FALSE
+    {
+      return Err(io::Error::last_os_error().into());
+    }
+
+    let mut command_line = to_wide(command_line);
+    let mut env_block = env_block(env);
+    let cwd = cwd.map(to_wide);
+    // SAFETY: zeroed structs are valid values of these types.
+    let mut startup_info: STARTUPINFOEXW = unsafe { std::mem::zeroed() };
+    startup_info.StartupInfo.cb = std::mem::size_of::<STARTUPINFOEXW>() as u32;
+    startup_info.lpAttributeList = attribute_list_ptr;
+    // SAFETY: see above.
+    let mut process_info: PROCESS_INFORMATION = unsafe { std::mem::zeroed() };
+    // SAFETY: the attribute list was initialized above, the pseudo console
+    // outlives the call, and the strings are null-terminated.
+    let created = unsafe {
+      let created = UpdateProcThreadAttribute(
+        attribute_list_ptr,
+        0,
+        PROC_THREAD_ATTRIBUTE_PSEUDOCONSOLE,
+        console.0,
+        std::mem::size_of::<HPCON>(),
+        ptr::null_mut(),
+        ptr::null_mut(),
+      ) != FALSE
+        && CreateProcessW(
+          ptr::null(),
+          command_line.as_mut_ptr(),
+          ptr::null_mut(),
+          ptr::null_mut(),
+          FALSE,
+          EXTENDED_STARTUPINFO_PRESENT
+            | CREATE_UNICODE_ENVIRONMENT
+            | creation_flags,
+          env_block.as_mut_ptr() as _,
+          cwd.as_ref().map_or(ptr::null(), |cwd| cwd.as_ptr()),
+          &mut startup_info.StartupInfo,
+          &mut process_info,
+        ) != FALSE;
+      let err = io::Error::last_os_error();
+      DeleteProcThreadAttributeList(attribute_list_ptr);
+      created.then_some(()).ok_or(err)
+    };
+    created?;
+
+    // SAFETY: the handles were just created, and nothing else owns them.
+    let (process, _thread) = unsafe {
+      (
+        OwnedHandle::from_raw_handle(process_info.hProcess as _),
+        OwnedHandle::from_raw_handle(process_info.hThread as _),
+      )
+    };
+    // The pseudo console has its own handles to the ends of the pipes of the
+    // child.
+    drop(input_read);
+    drop(output_write);
+
+    // Reading the output of the pseudo console only ends once it is closed,
+    // which is done when the child has exited.
+    let console = Arc::new(Mutex::new(Some(console)));
+    let process = Arc::new(process);
+    std::thread::spawn({
+      let console = console.clone();
+      let process = process.clone();
+      move || {
+        // SAFETY: the process handle is valid while `process` is alive.
+        unsafe { WaitForSingleObject(process.as_raw_handle() as _, INFINITE) };
+        let console = console.lock().take();
+        drop(console);
+      }
+    });
+
+    Ok((
+      PtyChild {
+        process,
+        pid: process_info.dwProcessId,
+      },
+      PtyResource {
+        console,
+        input: Arc::new(File::from(input_write)),
+        output: Arc::new(File::from(output_read)),
+        cancel: Default::default(),
+      },
+    ))
+  }
+
+  fn create_pipe() -> io::Result<(OwnedHandle, OwnedHandle)> {
+    let mut read = ptr::null_mut();
+    let mut write = ptr::null_mut();
+    // SAFETY: `read` and `write` are valid pointers.
+    if unsafe { CreatePipe(&mut read, &mut write, ptr::null_mut(), 0) } == FALSE
+    {
+      return Err(io::Error::last_os_error());
+    }
+    // SAFETY: the handles were just created, and nothing else owns them.
+    unsafe {
+      Ok((
+        OwnedHandle::from_raw_handle(read as _),
+        OwnedHandle::from_raw_handle(write as _),
+      ))
+    }
+  }
+
+  fn to_wide(s: &str) -> Vec<u16> {
+    OsStr::new(s).encode_wide().chain(Some(0)).collect()
+  }
+
+  /// The environment block of `CreateProcessW()`, where every variable is
+  /// `name=value\0` and the block ends with another `\0`. The variables must
+  /// be sorted by name, case insensitively.
+  fn env_block(env: &[(String, String)]) -> Vec<u16> {
+    let mut parts = env
+      .iter()
+      .map(|(key, value)| format!("{key}={value}\0"))
+      .collect::<Vec<_>>();
+    parts.sort_by_key(|part| part.to_uppercase());
+    if parts.is_empty() {
+      parts.push("\0".to_string());
+    }
+    format!("{}\0", parts.join("")).encode_utf16().collect()
+  }
+
+  /// Appends an argument to a command line, quoted the way that the C
+  /// runtime parses them if it needs to be, like `std::process::Command`
+  /// does.
+  pub fn append_arg(command_line: &mut String, arg: &str) {
+    if !command_line.is_empty() {
+      command_line.push(' ');
+    }
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+      command_line.push_str(arg);
+      return;
+    }
+    command_line.push('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+      if c == '\\' {
+        backslashes += 1;
+      } else {
+        if c == '"' {
+          // The backslashes before a quote are escaped, and so is the quote.
+          command_line.extend(std::iter::repeat('\\').take(backslashes + 1));
+        }
+        backslashes = 0;
+      }
+      command_line.push(c);
+    }
+    // The backslashes before the closing quote are escaped too.
+    command_line.extend(std::iter::repeat('\\').take(backslashes));
+    command_line.push('"');
+  }
+
+  /// A process spawned by `spawn_pty()`.
+  pub struct PtyChild {
+    process: Arc<OwnedHandle>,
+    pid: u32,
+  }
+
+  impl PtyChild {
+    pub fn id(&self) -> u32 {
+      self.pid
+    }
+
+    pub fn raw_handle(&self) -> RawHandle {
+      self.process.as_raw_handle()
+    }
+
+    pub async fn wait(&self) -> io::Result<ExitStatus> {
+      let process = self.process.clone();
+      tokio::task::spawn_blocking(move || {
+        let handle = process.as_raw_handle() as _;
+        let mut code: DWORD = 0;
+        // SAFETY: the process handle is valid while `process` is alive.
+        unsafe {
+          if WaitForSingleObject(handle, INFINITE) != WAIT_OBJECT_0
+            || GetExitCodeProcess(handle, &mut code) == FALSE
+          {
+            return Err(io::Error::last_os_error());
+          }
+        }
+        Ok(ExitStatus::from_raw(code))
+      })
+      .await?
+    }
+
+    /// Terminates the process with exit code 1, like
+    /// `std::process::Child::kill()`.
+    pub fn kill(&self) -> io::Result<()> {
+      // SAFETY: the process handle is valid.
+      if unsafe { TerminateProcess(self.process.as_raw_handle() as _, 1) }
+        == FALSE
+      {
+        return Err(io::Error::last_os_error());
+      }
+      Ok(())
+    }
+  }
+
+  /// The pseudo console of a child. Reading returns what the subprocess
+  /// writes to it, and writing is its input.
+  ///
+  /// Anonymous pipes can't be read and written asynchronously on Windows, so
+  /// this is done on the blocking threads.
+  pub struct PtyResource {
+    console: Arc<Mutex<Option<PseudoConsole>>>,
+    input: Arc<File>,
+    output: Arc<File>,
+    cancel: CancelHandle,
+  }
+
+  impl PtyResource {
+    pub fn new(fd: PtyFd) -> Result<Self, AnyError> {
+      match fd {}
+    }
+
+    pub fn resize(&self, size: PtySize) -> Result<(), AnyError> {
+      let console = self.console.lock();
+      // The pseudo console is closed once the child has exited.
+      let Some(console) = console.as_ref() else {
+        return Ok(());
+      };
+      // SAFETY: the pseudo console is open while it's in the mutex.
+      let result = unsafe { ResizePseudoConsole(console.0, coord(size)) };
+      if result != S_OK {
+        return Err(io::Error::from_raw_os_error(result).into());
+      }
+      Ok(())
+    }
+
+    async fn read(&self, limit: usize) -> Result<BufView, AnyError> {
+      let output = self.output.clone();
+      let buf = tokio::task::spawn_blocking(move || {
+        let mut buf = vec![0; limit];
+        // Reading a pipe whose write end is closed returns 0, which is the
+        // EOF.
+        let n = (&*output).read(&mut buf)?;
+        buf.truncate(n);
+        Ok::<_, io::Error>(buf)
+      })
+      .await??;
+      Ok(buf.into())
+    }
+
+    async fn write(&self, buf: &[u8]) -> Result<usize, AnyError> {
+      let input = self.input.clone();
+      let buf = buf.to_vec();
+      let nwritten =
+        tokio::task::spawn_blocking(move || (&*input).write(&buf)).await??;
+      Ok(nwritten)
+    }
+  }
+
+  impl Resource for PtyResource {
+    fn name(&self) -> Cow<str> {
+      "pty".into()
+    }
+
+    fn read(self: Rc<Self>, limit: usize) -> AsyncResult<BufView> {
+      Box::pin(async move {
+        let cancel = RcRef::map(&self, |r| &r.cancel);
+        PtyResource::read(&self, limit).try_or_cancel(cancel).await
+      })
+    }
+
+    fn write(self: Rc<Self>, view: BufView) -> AsyncResult<WriteOutcome> {
+      Box::pin(async move {
+        let cancel = RcRef::map(&self, |r| &r.cancel);
+        let nwritten = PtyResource::write(&self, &view)
+          .try_or_cancel(cancel)
+          .await?;
+        Ok(WriteOutcome::Partial { nwritten, view })
+      })
+    }
+
+    fn close(self: Rc<Self>) {
+      self.cancel.cancel();
+    }
+  }
+
+  #[cfg(test)]
+  mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_arg() {
+      let mut command_line = String::new();
+      for arg in ["deno", "", "a b", "a\"b", "a\\b", "a b\\", "a\\\"b"] {
+        append_arg(&mut command_line, arg);
+      }
+      assert_eq!(command_line, r#"deno "" "a b" "a\"b" a\b "a b\\" "a\\\"b""#);
+    }
+  }
+}
+
+#[op]
+pub fn op_pty_resize(
+  state: &mut OpState,
+  rid: ResourceId,
+  size: PtySize,
+) -> Result<(), AnyError> {
+  state.resource_table.get::<PtyResource>(rid)?.resize(size)
+}
@@ -25,9 +25,21 @@ use tokio::signal::windows::ctrl_break;
 #[cfg(windows)]
 use tokio::signal::windows::ctrl_c;
 #[cfg(windows)]
+use tokio::signal::windows::ctrl_close;
+#[cfg(windows)]
+use tokio::signal::windows::ctrl_logoff;
+#[cfg(windows)]
+use tokio::signal::windows::ctrl_shutdown;
+#[cfg(windows)]
 use tokio::signal::windows::CtrlBreak;
 #[cfg(windows)]
 use tokio::signal::windows::CtrlC;
+#[cfg(windows)]
+use tokio::signal::windows::CtrlClose;
+#[cfg(windows)]
+use tokio::signal::windows::CtrlLogoff;
+#[cfg(windows)]
+use tokio::signal::windows::CtrlShutdown;
 
 deno_core::extension!(
   deno_signal,
@@ -56,12 +68,15 @@ impl Resource for SignalStreamResource {
   }
 }
 
-// TODO: CtrlClose could be mapped to SIGHUP but that needs a
-// tokio::windows::signal::CtrlClose type, or something from a different crate
+/// The console control events of Windows, mapped onto signals: closing the
+/// console is SIGHUP, like in libuv, and the logoff of the user or the
+/// shutdown of the system, which are only received by services, are SIGTERM.
 #[cfg(windows)]
 enum WindowsSignal {
   Sigint(CtrlC),
   Sigbreak(CtrlBreak),
+  Sighup(CtrlClose),
+  Sigterm(CtrlShutdown, CtrlLogoff),
 }
 
 #[cfg(windows)]
@@ -78,12 +93,26 @@ impl From<CtrlBreak> for WindowsSignal {
   }
 }
 
+#[cfg(windows)]
+impl From<CtrlClose> for WindowsSignal {
+  fn from(ctrl_close: CtrlClose) -> Self {
+    WindowsSignal::Sighup(ctrl_close)
+  }
+}
+
 #[cfg(windows)]
 impl WindowsSignal {
   pub async fn recv(&mut self) -> Option<()> {
     match self {
       WindowsSignal::Sigint(ctrl_c) => ctrl_c.recv().await,
       WindowsSignal::Sigbreak(ctrl_break) => ctrl_break.recv().await,
+      WindowsSignal::Sighup(ctrl_close) => ctrl_close.recv().await,
+      WindowsSignal::Sigterm(ctrl_shutdown, ctrl_logoff) => {
+        tokio::select! {
+          r = ctrl_shutdown.recv() => r,
+          r = ctrl_logoff.recv() => r,
+        }
+      }
     }
   }
 }
@@ -434,25 +463,28 @@ pub fn signal_int_to_str(s: libc::c_int) -> Result<&'static str, AnyError> {
   }
 }
 
+#[cfg(target_os = "windows")]
+const WINDOWS_SIGNALS_ERROR: &str = "Windows only supports ctrl-c (SIGINT), ctrl-break (SIGBREAK), console close (SIGHUP) and shutdown or logoff (SIGTERM).";
+
 #[cfg(target_os = "windows")]
 pub fn signal_str_to_int(s: &str) -> Result<libc::c_int, AnyError> {
   match s {
+    "SIGHUP" => Ok(1),
     "SIGINT" => Ok(2),
+    "SIGTERM" => Ok(15),
     "SIGBREAK" => Ok(21),
-    _ => Err(type_error(
-      "Windows only supports ctrl-c (SIGINT) and ctrl-break (SIGBREAK).",
-    )),
+    _ => Err(type_error(WINDOWS_SIGNALS_ERROR)),
   }
 }
 
 #[cfg(target_os = "windows")]
 pub fn signal_int_to_str(s: libc::c_int) -> Result<&'static str, AnyError> {
   match s {
+    1 => Ok("SIGHUP"),
     2 => Ok("SIGINT"),
+    15 => Ok("SIGTERM"),
     21 => Ok("SIGBREAK"),
-    _ => Err(type_error(
-      "Windows only supports ctrl-c (SIGINT) and ctrl-break (SIGBREAK).",
-    )),
+    _ => Err(type_error(WINDOWS_SIGNALS_ERROR)),
   }
 }
 
@@ -485,10 +517,21 @@ fn op_signal_bind(
   let signo = signal_str_to_int(sig)?;
   let resource = SignalStreamResource {
     signal: AsyncRefCell::new(match signo {
+      // SIGHUP
+      1 => ctrl_close()
+        .expect("There was an issue creating ctrl+close event stream.")
+        .into(),
       // SIGINT
       2 => ctrl_c()
         .expect("There was an issue creating ctrl+c event stream.")
         .into(),
+      // SIGTERM
+      15 => WindowsSignal::Sigterm(
+        ctrl_shutdown()
+          .expect("There was an issue creating ctrl+shutdown event stream."),
+        ctrl_logoff()
+          .expect("There was an issue creating ctrl+logoff event stream."),
+      ),
       // SIGBREAK
       21 => ctrl_break()
         .expect("There was an issue creating ctrl+break event stream.")
@@ -2,7 +2,9 @@
 
 use crate::ops::TestingFeaturesEnabled;
 use crate::permissions::create_child_permissions;
+use crate::permissions::create_child_permissions_from_preset;
 use crate::permissions::ChildPermissionsArg;
+use crate::permissions::PermissionPresets;
 use crate::permissions::PermissionsContainer;
 use crate::web_worker::run_web_worker;
 use crate::web_worker::SendableWebWorkerHandle;
@@ -12,6 +14,7 @@ use crate::web_worker::WebWorkerType;
 use crate::web_worker::WorkerControlEvent;
 use crate::web_worker::WorkerId;
 use crate::worker::FormatJsErrorFn;
+use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::futures::future::LocalFutureObj;
 use deno_core::op;
@@ -101,6 +104,7 @@ deno_core::extension!(
     preload_module_cb: Arc<WorkerEventCb>,
     pre_execute_module_cb: Arc<WorkerEventCb>,
     format_js_error_fn: Option<Arc<FormatJsErrorFn>>,
+    permission_presets: PermissionPresets,
   },
   state = |state, options| {
     state.put::<WorkersTable>(WorkersTable::default());
@@ -118,6 +122,7 @@ deno_core::extension!(
     let format_js_error_fn_holder =
       FormatJsErrorFnHolder(options.format_js_error_fn);
     state.put::<FormatJsErrorFnHolder>(format_js_error_fn_holder);
+    state.put::<PermissionPresets>(options.permission_presets);
   },
   customizer = |ext: &mut deno_core::ExtensionBuilder| {
     ext.force_op_registration();
@@ -163,12 +168,32 @@ fn op_create_worker(
   if args.permissions.is_some() {
     super::check_unstable(state, "Worker.deno.permissions");
   }
+  // The preset is resolved once, when the worker is created.
+  let maybe_preset =
+    match args.permissions.as_ref().and_then(|p| p.preset_name()) {
+      Some(name) => match state.borrow::<PermissionPresets>().get(name) {
+        Some(preset) => Some(preset.clone()),
+        None => {
+          return Err(type_error(format!(
+            "Unknown permission preset \"{name}\""
+          )))
+        }
+      },
+      None => None,
+    };
   let parent_permissions = state.borrow_mut::<PermissionsContainer>();
   let worker_permissions = if let Some(child_permissions_arg) = args.permissions
   {
     let mut parent_permissions = parent_permissions.0.lock();
-    let perms =
-      create_child_permissions(&mut parent_permissions, child_permissions_arg)?;
+    let perms = match maybe_preset {
+      Some(preset) => {
+        create_child_permissions_from_preset(&mut parent_permissions, &preset)?
+      }
+      None => create_child_permissions(
+        &mut parent_permissions,
+        child_permissions_arg,
+      )?,
+    };
     PermissionsContainer::new(perms)
   } else {
     parent_permissions.clone()
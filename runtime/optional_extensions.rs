@@ -0,0 +1,53 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The extensions that can be left out of the runtime, with the "ffi" and
+//! "kv" features, to make its binary smaller. A runtime without one of them
+//! has an extension of the same name in its place, whose APIs throw, so that
+//! the order of the extensions stays the same as in `build.rs`.
+
+use std::path::PathBuf;
+
+use deno_core::Extension;
+
+#[cfg(not(feature = "ffi"))]
+deno_core::extension!(deno_ffi);
+
+#[cfg(not(feature = "kv"))]
+deno_core::extension!(deno_kv);
+
+/// The FFI extension, or its placeholder.
+pub fn deno_ffi_init_ops(unstable: bool) -> Extension {
+  #[cfg(feature = "ffi")]
+  {
+    deno_ffi::deno_ffi::init_ops::<crate::permissions::PermissionsContainer>(
+      unstable,
+    )
+  }
+  #[cfg(not(feature = "ffi"))]
+  {
+    let _ = unstable;
+    deno_ffi::init_ops()
+  }
+}
+
+/// The KV extension, which stores the databases in `origin_storage_dir`, or
+/// its placeholder.
+pub fn deno_kv_init_ops(
+  origin_storage_dir: Option<PathBuf>,
+  unstable: bool,
+) -> Extension {
+  #[cfg(feature = "kv")]
+  {
+    deno_kv::deno_kv::init_ops(
+      deno_kv::sqlite::SqliteDbHandler::<
+        crate::permissions::PermissionsContainer,
+      >::new(origin_storage_dir),
+      unstable,
+    )
+  }
+  #[cfg(not(feature = "kv"))]
+  {
+    let _ = (origin_storage_dir, unstable);
+    deno_kv::init_ops()
+  }
+}
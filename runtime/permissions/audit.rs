@@ -0,0 +1,141 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
+use deno_core::serde_json::json;
+use deno_core::v8;
+use deno_core::JsRuntime;
+use once_cell::sync::Lazy;
+use std::cell::RefCell;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::LineWriter;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+static AUDIT_LOG_ENABLED: AtomicBool = AtomicBool::new(false);
+
+static MAYBE_AUDIT_LOG: Lazy<Mutex<Option<LineWriter<File>>>> =
+  Lazy::new(|| Mutex::new(None));
+
+/// The most frames of the JavaScript stack that are recorded with a check.
+const MAX_STACK_FRAMES: usize = 10;
+
+thread_local! {
+  /// The isolate of the runtime on this thread, see `record_audit_stack()`.
+  static STACK_ISOLATE: RefCell<Option<(*mut v8::Isolate, v8::Global<v8::Context>)>> =
+    RefCell::new(None);
+}
+
+/// Record every permission check of the process, as JSON lines appended to
+/// the file at `path`.
+pub fn set_audit_log(path: &Path) -> Result<(), AnyError> {
+  let file = OpenOptions::new().create(true).append(true).open(path)?;
+  *MAYBE_AUDIT_LOG.lock() = Some(LineWriter::new(file));
+  AUDIT_LOG_ENABLED.store(true, Ordering::Relaxed);
+  Ok(())
+}
+
+/// Record the JavaScript stack of the permission checks on this thread, which
+/// are done by the ops of `js_runtime`, until the returned guard is dropped.
+/// The guard must be dropped before the runtime is. Returns `None` if the
+/// audit log is disabled.
+pub fn record_audit_stack(
+  js_runtime: &mut JsRuntime,
+) -> Option<AuditStackGuard> {
+  if !AUDIT_LOG_ENABLED.load(Ordering::Relaxed) {
+    return None;
+  }
+  let context = js_runtime.global_context();
+  let isolate: *mut v8::Isolate = &mut **js_runtime.v8_isolate();
+  STACK_ISOLATE.with(|cell| *cell.borrow_mut() = Some((isolate, context)));
+  Some(AuditStackGuard(PhantomData))
+}
+
+pub struct AuditStackGuard(PhantomData<*const ()>);
+
+impl Drop for AuditStackGuard {
+  fn drop(&mut self) {
+    STACK_ISOLATE.with(|cell| cell.borrow_mut().take());
+  }
+}
+
+/// The frames of the JavaScript stack of the current permission check, from
+/// the innermost one, without the frames of the runtime itself.
+fn current_stack() -> Option<Vec<String>> {
+  STACK_ISOLATE.with(|cell| {
+    let cell = cell.borrow();
+    let (isolate, context) = cell.as_ref()?;
+    // SAFETY: the isolate is alive while the guard is, and the checks run on
+    // its thread, from its ops, so this scope is nested in those of the op.
+    let scope = &mut unsafe { v8::CallbackScope::new(&mut **isolate) };
+    let scope = &mut v8::HandleScope::new(scope);
+    let context = v8::Local::new(scope, context);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    let stack = v8::StackTrace::current_stack_trace(scope, MAX_STACK_FRAMES)?;
+    let frames = (0..stack.get_frame_count())
+      .filter_map(|index| stack.get_frame(scope, index))
+      .filter_map(|frame| {
+        let script_name = frame
+          .get_script_name(scope)
+          .map(|name| name.to_rust_string_lossy(scope))
+          .unwrap_or_default();
+        if script_name.starts_with("ext:") {
+          return None;
+        }
+        let location = format!(
+          "{}:{}:{}",
+          script_name,
+          frame.get_line_number(),
+          frame.get_column()
+        );
+        let function_name = frame
+          .get_function_name(scope)
+          .map(|name| name.to_rust_string_lossy(scope))
+          .filter(|name| !name.is_empty());
+        Some(match function_name {
+          Some(function_name) => format!("{function_name} ({location})"),
+          None => location,
+        })
+      })
+      .collect();
+    Some(frames)
+  })
+}
+
+#[inline(always)]
+pub fn audit_permission_check(
+  name: &str,
+  api_name: Option<&str>,
+  info: impl FnOnce() -> Option<String>,
+  granted: bool,
+  prompted: bool,
+) {
+  // Eliminates the overhead of the lock, when the audit log is disabled.
+  if !AUDIT_LOG_ENABLED.load(Ordering::Relaxed) {
+    return;
+  }
+  let timestamp = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_millis())
+    .unwrap_or_default();
+  let record = json!({
+    "timestamp": timestamp,
+    "permission": name,
+    "descriptor": info(),
+    "api": api_name,
+    "result": if granted { "granted" } else { "denied" },
+    "prompted": prompted,
+    "stack": current_stack(),
+  });
+  if let Some(log) = MAYBE_AUDIT_LOG.lock().as_mut() {
+    if let Err(err) = writeln!(log, "{record}") {
+      log::warn!("Failed to write the permission audit log: {err}");
+    }
+  }
+}
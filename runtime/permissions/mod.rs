@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use crate::colors;
+use crate::fs_util::canonicalize_path_maybe_not_exists;
 use crate::fs_util::resolve_from_cwd;
 use deno_core::error::custom_error;
 use deno_core::error::type_error;
@@ -17,20 +18,32 @@ use deno_core::ModuleSpecifier;
 use deno_core::OpState;
 use log;
 use once_cell::sync::Lazy;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt;
 use std::hash::Hash;
+use std::net::IpAddr;
+use std::net::SocketAddr;
 use std::path::Path;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::string::ToString;
 use std::sync::Arc;
 
+mod audit;
+mod prompt_store;
 mod prompter;
+use audit::audit_permission_check;
 use prompter::permission_prompt;
 use prompter::PromptResponse;
 use prompter::PERMISSION_EMOJI;
 
+pub use audit::record_audit_stack;
+pub use audit::set_audit_log;
+pub use audit::AuditStackGuard;
+pub use prompt_store::remembered_grants;
+pub use prompt_store::set_prompt_store;
+pub use prompt_store::RememberedGrant;
 pub use prompter::set_prompt_callbacks;
 pub use prompter::PromptCallback;
 
@@ -106,6 +119,20 @@ impl PermissionState {
     api_name: Option<&str>,
     info: impl Fn() -> Option<String>,
     prompt: bool,
+  ) -> (Result<(), AnyError>, bool, bool) {
+    let (result, prompted, is_allow_all) =
+      self.check_state(name, api_name, &info, prompt);
+    audit_permission_check(name, api_name, info, result.is_ok(), prompted);
+    (result, prompted, is_allow_all)
+  }
+
+  #[inline]
+  fn check_state(
+    self,
+    name: &str,
+    api_name: Option<&str>,
+    info: impl Fn() -> Option<String>,
+    prompt: bool,
   ) -> (Result<(), AnyError>, bool, bool) {
     match self {
       PermissionState::Granted => {
@@ -231,9 +258,52 @@ pub struct UnaryPermission<T: Eq + Hash> {
   pub global_state: PermissionState,
   pub granted_list: HashSet<T>,
   pub denied_list: HashSet<T>,
+  /// Whether all of the access is denied by a `--deny-*` flag.
+  pub flag_denied_global: bool,
+  /// The access denied by a `--deny-*` flag, over anything else.
+  pub flag_denied_list: HashSet<T>,
   pub prompt: bool,
 }
 
+impl<T: Eq + Hash> UnaryPermission<T> {
+  /// Whether the access is denied by the `--deny-*` flags, which take
+  /// precedence over the granted permissions and the prompts. The access to
+  /// everything is denied as soon as any part of it is.
+  fn is_flag_denied<D>(
+    &self,
+    desc: Option<D>,
+    matches: impl Fn(&T, &D) -> bool,
+  ) -> bool {
+    self.flag_denied_global
+      || match desc {
+        None => !self.flag_denied_list.is_empty(),
+        Some(desc) => self
+          .flag_denied_list
+          .iter()
+          .any(|denied| matches(denied, &desc)),
+      }
+  }
+
+  /// Denies the access that `denied` grants, which is created from the values
+  /// of a `--deny-*` flag like the permission from the ones of `--allow-*`.
+  fn with_flag_denied(mut self, denied: UnaryPermission<T>) -> Self {
+    self.flag_denied_global = denied.global_state == PermissionState::Granted;
+    self.flag_denied_list = denied.granted_list;
+    self
+  }
+
+  /// Denies the access denied by the flags of `other` too.
+  fn add_flag_denied(&mut self, other: &UnaryPermission<T>)
+  where
+    T: Clone,
+  {
+    self.flag_denied_global |= other.flag_denied_global;
+    self
+      .flag_denied_list
+      .extend(other.flag_denied_list.iter().cloned());
+  }
+}
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub struct ReadDescriptor(pub PathBuf);
 
@@ -247,14 +317,74 @@ impl NetDescriptor {
   fn new<T: AsRef<str>>(host: &&(T, Option<u16>)) -> Self {
     NetDescriptor(host.0.as_ref().to_string(), host.1)
   }
+
+  /// Whether the descriptor is a wildcard like "*.deno.land", and `host` is
+  /// one of the subdomains it matches, or a narrower wildcard.
+  fn matches_subdomain(&self, host: &str, port: Option<u16>) -> bool {
+    let Some(suffix) = self.0.strip_prefix('*') else {
+      return false;
+    };
+    let host = normalize_host(host);
+    (self.1.is_none() || self.1 == port)
+      && host.len() > suffix.len()
+      && host.ends_with(suffix)
+  }
+
+  /// Whether the descriptor, from a `--deny-net` flag, matches the host. Host
+  /// names aren't resolved here: their addresses are checked against denied
+  /// ranges when connecting to them, see `check_resolved()`.
+  fn matches_denied_host(&self, host: &str, port: Option<u16>) -> bool {
+    if let Some(range) = self.ip_range() {
+      return is_in_ip_ranges(&[range], host);
+    }
+    (normalize_host(&self.0) == normalize_host(host)
+      && (self.1.is_none() || port.is_none() || self.1 == port))
+      || self.matches_subdomain(host, port)
+  }
+
+  /// The range of IP addresses of the descriptor, if it's one.
+  fn ip_range(&self) -> Option<IpRange> {
+    match self.1 {
+      None => IpRange::parse(&self.0),
+      Some(_) => None,
+    }
+  }
+}
+
+/// Host names are matched case-insensitively and with or without the trailing
+/// dot of fully qualified names.
+fn normalize_host(host: &str) -> String {
+  host.trim_end_matches('.').to_ascii_lowercase()
 }
 
 impl FromStr for NetDescriptor {
   type Err = AnyError;
 
   fn from_str(s: &str) -> Result<Self, Self::Err> {
+    // Sockets in the Linux abstract namespace, like "@name"
+    if s.starts_with('@') {
+      return Ok(NetDescriptor(s.to_string(), None));
+    }
+    // Ranges of IP addresses in CIDR notation, like "10.0.0.0/8"
+    if s
+      .split_once('/')
+      .map_or(false, |(addr, _)| parse_ip(addr).is_some())
+    {
+      let range = IpRange::parse(s).ok_or_else(|| {
+        type_error(format!("Invalid IP address range \"{s}\""))
+      })?;
+      return Ok(NetDescriptor(range.to_string(), None));
+    }
     let url = url::Url::parse(&format!("http://{s}"))?;
     let hostname = url.host_str().unwrap().to_string();
+    // Wildcards are only allowed for all the subdomains, like "*.deno.land"
+    if hostname.contains('*')
+      && !hostname
+        .strip_prefix("*.")
+        .map_or(false, |domain| !domain.is_empty() && !domain.contains('*'))
+    {
+      return Err(type_error(format!("Invalid host wildcard \"{s}\"")));
+    }
 
     Ok(NetDescriptor(hostname, url.port()))
   }
@@ -269,6 +399,139 @@ impl fmt::Display for NetDescriptor {
   }
 }
 
+/// A range of IP addresses in CIDR notation, like "192.168.1.0/24".
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+struct IpRange {
+  network: IpAddr,
+  prefix_len: u8,
+}
+
+impl IpRange {
+  /// Parses a range, with the bits of the address after the prefix cleared.
+  fn parse(s: &str) -> Option<Self> {
+    let (addr, prefix_len) = s.split_once('/')?;
+    let addr = parse_ip(addr)?;
+    let prefix_len = prefix_len.parse::<u8>().ok()?;
+    let network = match addr {
+      IpAddr::V4(addr) if prefix_len <= 32 => {
+        IpAddr::V4((u32::from(addr) & Self::mask(prefix_len, 32) as u32).into())
+      }
+      IpAddr::V6(addr) if prefix_len <= 128 => {
+        IpAddr::V6((u128::from(addr) & Self::mask(prefix_len, 128)).into())
+      }
+      _ => return None,
+    };
+    Some(IpRange {
+      network,
+      prefix_len,
+    })
+  }
+
+  /// The mask with the first `prefix_len` of `bits` bits set.
+  fn mask(prefix_len: u8, bits: u32) -> u128 {
+    match u128::MAX.checked_shl(bits - prefix_len as u32) {
+      Some(mask) => mask & (u128::MAX >> (128 - bits)),
+      None => 0,
+    }
+  }
+
+  fn contains(&self, addr: IpAddr) -> bool {
+    // IPv4 addresses may be mapped into IPv6 ones, like "::ffff:10.0.0.1".
+    let addr = match addr {
+      IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(addr, IpAddr::V4),
+      addr => addr,
+    };
+    match (self.network, addr) {
+      (IpAddr::V4(network), IpAddr::V4(addr)) => {
+        let mask = Self::mask(self.prefix_len, 32) as u32;
+        u32::from(addr) & mask == u32::from(network)
+      }
+      (IpAddr::V6(network), IpAddr::V6(addr)) => {
+        let mask = Self::mask(self.prefix_len, 128);
+        u128::from(addr) & mask == u128::from(network)
+      }
+      _ => false,
+    }
+  }
+
+  fn contains_range(&self, other: &IpRange) -> bool {
+    other.prefix_len >= self.prefix_len && self.contains(other.network)
+  }
+}
+
+impl fmt::Display for IpRange {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}/{}", self.network, self.prefix_len)
+  }
+}
+
+/// Parses an IP address, which has brackets in URLs if it's an IPv6 one.
+fn parse_ip(s: &str) -> Option<IpAddr> {
+  let s = s
+    .strip_prefix('[')
+    .and_then(|s| s.strip_suffix(']'))
+    .unwrap_or(s);
+  s.parse().ok()
+}
+
+/// Whether the host, an IP address or a range of them, is in the ranges of IP
+/// addresses. Host names never are, as the addresses they resolve to may
+/// change by the time they're connected to.
+fn is_in_ip_ranges(ranges: &[IpRange], host: &str) -> bool {
+  if let Some(range) = IpRange::parse(host) {
+    return ranges.iter().any(|r| r.contains_range(&range));
+  }
+  match parse_ip(host) {
+    Some(addr) => ranges.iter().any(|r| r.contains(addr)),
+    None => false,
+  }
+}
+
+/// Whether the path is in a directory, or is a file, denied by a flag. Both
+/// are canonicalized too, so that the path can't be reached through a
+/// symlink into the denied directory.
+fn is_path_flag_denied(denied: &Path, path: &Path) -> bool {
+  let Ok(path) = resolve_from_cwd(path) else {
+    return false;
+  };
+  if path.starts_with(denied) {
+    return true;
+  }
+  match (
+    canonicalize_path_maybe_not_exists(&path),
+    canonicalize_path_maybe_not_exists(denied),
+  ) {
+    (Ok(path), Ok(denied)) => path.starts_with(denied),
+    _ => false,
+  }
+}
+
+/// A vsock address, the context ID of a VM or host and an optional port.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct VsockDescriptor(pub u32, pub Option<u32>);
+
+impl FromStr for VsockDescriptor {
+  type Err = AnyError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let invalid = || type_error(format!("Invalid vsock address \"{s}\""));
+    let (cid, port) = match s.split_once(':') {
+      Some((cid, port)) => (cid, Some(port.parse().map_err(|_| invalid())?)),
+      None => (s, None),
+    };
+    Ok(VsockDescriptor(cid.parse().map_err(|_| invalid())?, port))
+  }
+}
+
+impl fmt::Display for VsockDescriptor {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self.1 {
+      None => write!(f, "{}", self.0),
+      Some(port) => write!(f, "{}:{}", self.0, port),
+    }
+  }
+}
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub struct EnvDescriptor(EnvVarName);
 
@@ -276,6 +539,18 @@ impl EnvDescriptor {
   pub fn new(env: impl AsRef<str>) -> Self {
     Self(EnvVarName::new(env))
   }
+
+  /// Whether the descriptor grants access to `env`, either by name or as a
+  /// prefix wildcard like "AWS_*" that matches it, or a narrower wildcard.
+  fn matches(&self, env: &EnvVarName) -> bool {
+    if &self.0 == env {
+      return true;
+    }
+    match self.as_ref().strip_suffix('*') {
+      Some(prefix) => env.as_ref().starts_with(prefix),
+      None => false,
+    }
+  }
 }
 
 impl AsRef<str> for EnvDescriptor {
@@ -288,6 +563,91 @@ impl AsRef<str> for EnvDescriptor {
 pub enum RunDescriptor {
   Name(String),
   Path(PathBuf),
+  /// A program that can only run with arguments starting with the prefix.
+  WithArgs(Box<RunDescriptor>, Vec<String>),
+}
+
+impl RunDescriptor {
+  /// Parse the value of a `--allow-run` or `--deny-run` flag, a program
+  /// optionally followed by a prefix of its arguments, like "git:status" or
+  /// "git:remote show".
+  pub fn parse_with_args(s: &str) -> Self {
+    // don't split the drive letter of windows paths
+    let start = if cfg!(windows) && s.as_bytes().get(1) == Some(&b':') {
+      2
+    } else {
+      0
+    };
+    let Some(i) = s[start..].find(':').map(|i| i + start) else {
+      return Self::from_str(s).unwrap();
+    };
+    let cmd = Self::from_str(&s[..i]).unwrap();
+    let args = s[i + 1..]
+      .split_whitespace()
+      .map(ToString::to_string)
+      .collect::<Vec<_>>();
+    if args.is_empty() {
+      cmd
+    } else {
+      Self::WithArgs(Box::new(cmd), args)
+    }
+  }
+
+  /// Whether the descriptor matches the program, and its arguments if the
+  /// descriptor constrains them, which requires them to be known.
+  fn matches(&self, cmd: &str, args: Option<&[String]>) -> bool {
+    match self {
+      Self::WithArgs(desc, prefix) => {
+        args.map_or(false, |args| args.starts_with(prefix))
+          && desc.as_ref() == &Self::from_str(cmd).unwrap()
+      }
+      desc => desc == &Self::from_str(cmd).unwrap(),
+    }
+  }
+
+  /// As `matches()`, for a descriptor from a `--deny-run` flag. Names and
+  /// paths are also compared by the program they run, so that denying "git"
+  /// denies "/usr/bin/git" too, and the other way around.
+  fn matches_denied(&self, cmd: &str, args: Option<&[String]>) -> bool {
+    if self.matches(cmd, args) {
+      return true;
+    }
+    let args_match = match self {
+      Self::WithArgs(_, prefix) => {
+        args.map_or(false, |args| args.starts_with(prefix))
+      }
+      _ => true,
+    };
+    args_match
+      && match (
+        self.resolve_program(),
+        Self::from_str(cmd).unwrap().resolve_program(),
+      ) {
+        (Some(denied), Some(program)) => denied == program,
+        _ => false,
+      }
+  }
+
+  /// The canonical path of the program, with names looked up in `PATH`.
+  fn resolve_program(&self) -> Option<PathBuf> {
+    match self {
+      Self::Path(path) => path.canonicalize().ok(),
+      Self::Name(name) => {
+        let paths = std::env::var_os("PATH")?;
+        std::env::split_paths(&paths).find_map(|dir| {
+          let path = dir.join(name);
+          #[cfg(windows)]
+          let path = if path.extension().is_none() {
+            path.with_extension("exe")
+          } else {
+            path
+          };
+          path.canonicalize().ok().filter(|path| path.is_file())
+        })
+      }
+      Self::WithArgs(desc, _) => desc.resolve_program(),
+    }
+  }
 }
 
 impl FromStr for RunDescriptor {
@@ -310,6 +670,9 @@ impl ToString for RunDescriptor {
     match self {
       RunDescriptor::Name(s) => s.clone(),
       RunDescriptor::Path(p) => p.to_string_lossy().to_string(),
+      RunDescriptor::WithArgs(desc, args) => {
+        format!("{}:{}", desc.to_string(), args.join(" "))
+      }
     }
   }
 }
@@ -320,7 +683,9 @@ pub struct SysDescriptor(pub String);
 pub fn parse_sys_kind(kind: &str) -> Result<&str, AnyError> {
   match kind {
     "hostname" | "osRelease" | "osUptime" | "loadavg" | "networkInterfaces"
-    | "systemMemoryInfo" | "uid" | "gid" => Ok(kind),
+    | "systemMemoryInfo" | "uid" | "gid" | "getPriority" | "setPriority" => {
+      Ok(kind)
+    }
     _ => Err(type_error(format!("unknown system info kind \"{kind}\""))),
   }
 }
@@ -330,6 +695,11 @@ pub struct FfiDescriptor(pub PathBuf);
 
 impl UnaryPermission<ReadDescriptor> {
   pub fn query(&self, path: Option<&Path>) -> PermissionState {
+    if self
+      .is_flag_denied(path, |denied, path| is_path_flag_denied(&denied.0, path))
+    {
+      return PermissionState::Denied;
+    }
     if self.global_state == PermissionState::Granted {
       return PermissionState::Granted;
     }
@@ -517,6 +887,8 @@ impl Default for UnaryPermission<ReadDescriptor> {
       global_state: Default::default(),
       granted_list: Default::default(),
       denied_list: Default::default(),
+      flag_denied_global: false,
+      flag_denied_list: Default::default(),
       prompt: false,
     }
   }
@@ -524,6 +896,11 @@ impl Default for UnaryPermission<ReadDescriptor> {
 
 impl UnaryPermission<WriteDescriptor> {
   pub fn query(&self, path: Option<&Path>) -> PermissionState {
+    if self
+      .is_flag_denied(path, |denied, path| is_path_flag_denied(&denied.0, path))
+    {
+      return PermissionState::Denied;
+    }
     if self.global_state == PermissionState::Granted {
       return PermissionState::Granted;
     }
@@ -711,6 +1088,8 @@ impl Default for UnaryPermission<WriteDescriptor> {
       global_state: Default::default(),
       granted_list: Default::default(),
       denied_list: Default::default(),
+      flag_denied_global: false,
+      flag_denied_list: Default::default(),
       prompt: false,
     }
   }
@@ -721,15 +1100,26 @@ impl UnaryPermission<NetDescriptor> {
     &self,
     host: Option<&(T, Option<u16>)>,
   ) -> PermissionState {
+    if self.is_flag_denied(host, |denied, host| {
+      denied.matches_denied_host(host.0.as_ref(), host.1)
+    }) {
+      return PermissionState::Denied;
+    }
     if self.global_state == PermissionState::Denied
       && match host.as_ref() {
         None => true,
         Some(host) => match host.1 {
-          None => self
-            .denied_list
-            .iter()
-            .any(|host_| host.0.as_ref() == host_.0),
-          Some(_) => self.denied_list.contains(&NetDescriptor::new(host)),
+          None => self.denied_list.iter().any(|host_| {
+            host.0.as_ref() == host_.0
+              || host_.matches_subdomain(host.0.as_ref(), host_.1)
+          }),
+          Some(_) => {
+            self.denied_list.contains(&NetDescriptor::new(host))
+              || self
+                .denied_list
+                .iter()
+                .any(|host_| host_.matches_subdomain(host.0.as_ref(), host.1))
+          }
         },
       }
     {
@@ -743,6 +1133,11 @@ impl UnaryPermission<NetDescriptor> {
             None,
           )))
             || self.granted_list.contains(&NetDescriptor::new(host))
+            || self
+              .granted_list
+              .iter()
+              .any(|host_| host_.matches_subdomain(host.0.as_ref(), host.1))
+            || self.is_in_granted_ranges(host.0.as_ref())
         }
       }
     {
@@ -752,6 +1147,16 @@ impl UnaryPermission<NetDescriptor> {
     }
   }
 
+  /// Whether the host is in the granted ranges of IP addresses.
+  fn is_in_granted_ranges(&self, host: &str) -> bool {
+    let ranges = self
+      .granted_list
+      .iter()
+      .filter_map(NetDescriptor::ip_range)
+      .collect::<Vec<_>>();
+    is_in_ip_ranges(&ranges, host)
+  }
+
   pub fn request<T: AsRef<str>>(
     &mut self,
     host: Option<&(T, Option<u16>)>,
@@ -861,6 +1266,69 @@ impl UnaryPermission<NetDescriptor> {
     result
   }
 
+  /// Checks a host before it's resolved. A host name that isn't granted
+  /// itself may still resolve to addresses in granted ranges of IP addresses,
+  /// so its check is left to `check_resolved()` when there are any.
+  pub fn check_unresolved<T: AsRef<str>>(
+    &mut self,
+    host: &(T, Option<u16>),
+    api_name: Option<&str>,
+  ) -> Result<(), AnyError> {
+    if parse_ip(host.0.as_ref()).is_none()
+      && self.query(Some(host)) == PermissionState::Prompt
+      && self.granted_list.iter().any(|d| d.ip_range().is_some())
+    {
+      return Ok(());
+    }
+    self.check(host, api_name)
+  }
+
+  /// Checks the addresses that a host resolved to, right before connecting to
+  /// them, against the ranges of IP addresses denied by `--deny-net`. A host
+  /// name that isn't granted itself is allowed if all of its addresses are in
+  /// granted ranges, and checked as usual otherwise. The host must have been
+  /// checked with `check_unresolved()` or `check()` before it was resolved.
+  pub fn check_resolved<T: AsRef<str>>(
+    &mut self,
+    host: &(T, Option<u16>),
+    addrs: &[SocketAddr],
+    api_name: Option<&str>,
+  ) -> Result<(), AnyError> {
+    let denied_ranges = self
+      .flag_denied_list
+      .iter()
+      .filter_map(NetDescriptor::ip_range)
+      .collect::<Vec<_>>();
+    if let Some(addr) = addrs
+      .iter()
+      .find(|addr| denied_ranges.iter().any(|r| r.contains(addr.ip())))
+    {
+      let host = NetDescriptor::new(&host);
+      let info = format!("\"{host}\" (resolved to \"{}\")", addr.ip());
+      return PermissionState::Denied
+        .check(self.name, api_name, Some(&info), false)
+        .0;
+    }
+    if parse_ip(host.0.as_ref()).is_some()
+      || self.query(Some(host)) != PermissionState::Prompt
+    {
+      return Ok(());
+    }
+    let granted_ranges = self
+      .granted_list
+      .iter()
+      .filter_map(NetDescriptor::ip_range)
+      .collect::<Vec<_>>();
+    if !addrs.is_empty()
+      && addrs
+        .iter()
+        .all(|addr| granted_ranges.iter().any(|r| r.contains(addr.ip())))
+    {
+      return Ok(());
+    }
+    self.check(host, api_name)
+  }
+
   pub fn check_url(
     &mut self,
     url: &url::Url,
@@ -921,6 +1389,168 @@ impl Default for UnaryPermission<NetDescriptor> {
       global_state: Default::default(),
       granted_list: Default::default(),
       denied_list: Default::default(),
+      flag_denied_global: false,
+      flag_denied_list: Default::default(),
+      prompt: false,
+    }
+  }
+}
+
+impl UnaryPermission<VsockDescriptor> {
+  pub fn query(&self, addr: Option<(u32, Option<u32>)>) -> PermissionState {
+    if self.is_flag_denied(addr, |denied, (cid, port)| {
+      denied.0 == *cid
+        && (denied.1.is_none() || port.is_none() || denied.1 == *port)
+    }) {
+      return PermissionState::Denied;
+    }
+    if self.global_state == PermissionState::Denied
+      && match addr {
+        None => true,
+        Some((cid, None)) => self.denied_list.iter().any(|desc| desc.0 == cid),
+        Some((cid, port)) => {
+          self.denied_list.contains(&VsockDescriptor(cid, port))
+        }
+      }
+    {
+      PermissionState::Denied
+    } else if self.global_state == PermissionState::Granted
+      || match addr {
+        None => false,
+        Some((cid, port)) => {
+          self.granted_list.contains(&VsockDescriptor(cid, None))
+            || self.granted_list.contains(&VsockDescriptor(cid, port))
+        }
+      }
+    {
+      PermissionState::Granted
+    } else {
+      PermissionState::Prompt
+    }
+  }
+
+  pub fn request(
+    &mut self,
+    addr: Option<(u32, Option<u32>)>,
+  ) -> PermissionState {
+    let state = self.query(addr);
+    if state != PermissionState::Prompt {
+      return state;
+    }
+    if let Some((cid, port)) = addr {
+      let desc = VsockDescriptor(cid, port);
+      match permission_prompt(
+        &format!("vsock access to \"{desc}\""),
+        self.name,
+        Some("Deno.permissions.query()"),
+        true,
+      ) {
+        PromptResponse::Allow => {
+          self.granted_list.insert(desc);
+          PermissionState::Granted
+        }
+        PromptResponse::Deny => {
+          self.denied_list.insert(desc);
+          self.global_state = PermissionState::Denied;
+          PermissionState::Denied
+        }
+        PromptResponse::AllowAll => {
+          self.granted_list.clear();
+          self.global_state = PermissionState::Granted;
+          PermissionState::Granted
+        }
+      }
+    } else {
+      if PromptResponse::Allow
+        == permission_prompt(
+          "vsock access",
+          self.name,
+          Some("Deno.permissions.query()"),
+          true,
+        )
+      {
+        self.granted_list.clear();
+        self.global_state = PermissionState::Granted;
+      } else {
+        self.global_state = PermissionState::Denied;
+      }
+      self.global_state
+    }
+  }
+
+  pub fn revoke(
+    &mut self,
+    addr: Option<(u32, Option<u32>)>,
+  ) -> PermissionState {
+    if let Some((cid, port)) = addr {
+      if port.is_some() {
+        self.granted_list.remove(&VsockDescriptor(cid, port));
+      }
+      self.granted_list.remove(&VsockDescriptor(cid, None));
+    } else {
+      self.granted_list.clear();
+    }
+    if self.global_state == PermissionState::Granted {
+      self.global_state = PermissionState::Prompt;
+    }
+    self.query(addr)
+  }
+
+  pub fn check(
+    &mut self,
+    cid: u32,
+    port: Option<u32>,
+    api_name: Option<&str>,
+  ) -> Result<(), AnyError> {
+    let desc = VsockDescriptor(cid, port);
+    let (result, prompted, is_allow_all) = self.query(Some((cid, port))).check(
+      self.name,
+      api_name,
+      Some(&format!("\"{desc}\"")),
+      self.prompt,
+    );
+    if prompted {
+      if result.is_ok() {
+        if is_allow_all {
+          self.granted_list.clear();
+          self.global_state = PermissionState::Granted;
+        } else {
+          self.granted_list.insert(desc);
+        }
+      } else {
+        self.denied_list.insert(desc);
+        self.global_state = PermissionState::Denied;
+      }
+    }
+    result
+  }
+
+  pub fn check_all(&mut self) -> Result<(), AnyError> {
+    let (result, prompted, _) =
+      self
+        .query(None)
+        .check(self.name, None, Some("all"), self.prompt);
+    if prompted {
+      if result.is_ok() {
+        self.global_state = PermissionState::Granted;
+      } else {
+        self.global_state = PermissionState::Denied;
+      }
+    }
+    result
+  }
+}
+
+impl Default for UnaryPermission<VsockDescriptor> {
+  fn default() -> Self {
+    UnaryPermission::<VsockDescriptor> {
+      name: "vsock",
+      description: "vsock",
+      global_state: Default::default(),
+      granted_list: Default::default(),
+      denied_list: Default::default(),
+      flag_denied_global: false,
+      flag_denied_list: Default::default(),
       prompt: false,
     }
   }
@@ -929,6 +1559,9 @@ impl Default for UnaryPermission<NetDescriptor> {
 impl UnaryPermission<EnvDescriptor> {
   pub fn query(&self, env: Option<&str>) -> PermissionState {
     let env = env.map(EnvVarName::new);
+    if self.is_flag_denied(env.as_ref(), |denied, env| denied.matches(env)) {
+      return PermissionState::Denied;
+    }
     if self.global_state == PermissionState::Denied
       && match env.as_ref() {
         None => true,
@@ -939,7 +1572,7 @@ impl UnaryPermission<EnvDescriptor> {
     } else if self.global_state == PermissionState::Granted
       || match env.as_ref() {
         None => false,
-        Some(env) => self.granted_list.contains(&EnvDescriptor::new(env)),
+        Some(env) => self.granted_list.iter().any(|desc| desc.matches(env)),
       }
     {
       PermissionState::Granted
@@ -1062,6 +1695,8 @@ impl Default for UnaryPermission<EnvDescriptor> {
       global_state: Default::default(),
       granted_list: Default::default(),
       denied_list: Default::default(),
+      flag_denied_global: false,
+      flag_denied_list: Default::default(),
       prompt: false,
     }
   }
@@ -1069,6 +1704,9 @@ impl Default for UnaryPermission<EnvDescriptor> {
 
 impl UnaryPermission<SysDescriptor> {
   pub fn query(&self, kind: Option<&str>) -> PermissionState {
+    if self.is_flag_denied(kind, |denied, kind| denied.0 == *kind) {
+      return PermissionState::Denied;
+    }
     if self.global_state == PermissionState::Denied
       && match kind {
         None => true,
@@ -1201,6 +1839,8 @@ impl Default for UnaryPermission<SysDescriptor> {
       global_state: Default::default(),
       granted_list: Default::default(),
       denied_list: Default::default(),
+      flag_denied_global: false,
+      flag_denied_list: Default::default(),
       prompt: false,
     }
   }
@@ -1208,6 +1848,24 @@ impl Default for UnaryPermission<SysDescriptor> {
 
 impl UnaryPermission<RunDescriptor> {
   pub fn query(&self, cmd: Option<&str>) -> PermissionState {
+    self.query_with_args(cmd, None, false)
+  }
+
+  /// As `query()`, but the access granted to the program with a prefix of
+  /// the arguments also applies, unless the program is run with a changed
+  /// environment or working directory, which can change what the arguments
+  /// do, like `GIT_CONFIG_*` variables or a repository with hooks do for
+  /// "git:status".
+  pub fn query_with_args(
+    &self,
+    cmd: Option<&str>,
+    args: Option<&[String]>,
+    is_env_changed: bool,
+  ) -> PermissionState {
+    if self.is_flag_denied(cmd, |denied, cmd| denied.matches_denied(cmd, args))
+    {
+      return PermissionState::Denied;
+    }
     if self.global_state == PermissionState::Denied
       && match cmd {
         None => true,
@@ -1220,9 +1878,10 @@ impl UnaryPermission<RunDescriptor> {
     } else if self.global_state == PermissionState::Granted
       || match cmd {
         None => false,
-        Some(cmd) => self
-          .granted_list
-          .contains(&RunDescriptor::from_str(cmd).unwrap()),
+        Some(cmd) => self.granted_list.iter().any(|desc| match desc {
+          RunDescriptor::WithArgs(..) if is_env_changed => false,
+          desc => desc.matches(cmd, args),
+        }),
       }
     {
       PermissionState::Granted
@@ -1311,12 +1970,26 @@ impl UnaryPermission<RunDescriptor> {
     cmd: &str,
     api_name: Option<&str>,
   ) -> Result<(), AnyError> {
-    let (result, prompted, is_allow_all) = self.query(Some(cmd)).check(
-      self.name,
-      api_name,
-      Some(&format!("\"{cmd}\"")),
-      self.prompt,
-    );
+    self.check_with_args(cmd, None, false, api_name)
+  }
+
+  /// As `check()`, with the arguments the program is run with, and whether
+  /// it's run with a changed environment or working directory, see
+  /// `query_with_args()`.
+  pub fn check_with_args(
+    &mut self,
+    cmd: &str,
+    args: Option<&[String]>,
+    is_env_changed: bool,
+    api_name: Option<&str>,
+  ) -> Result<(), AnyError> {
+    let (result, prompted, is_allow_all) =
+      self.query_with_args(Some(cmd), args, is_env_changed).check(
+        self.name,
+        api_name,
+        Some(&format!("\"{cmd}\"")),
+        self.prompt,
+      );
     if prompted {
       if result.is_ok() {
         if is_allow_all {
@@ -1361,6 +2034,8 @@ impl Default for UnaryPermission<RunDescriptor> {
       global_state: Default::default(),
       granted_list: Default::default(),
       denied_list: Default::default(),
+      flag_denied_global: false,
+      flag_denied_list: Default::default(),
       prompt: false,
     }
   }
@@ -1368,6 +2043,11 @@ impl Default for UnaryPermission<RunDescriptor> {
 
 impl UnaryPermission<FfiDescriptor> {
   pub fn query(&self, path: Option<&Path>) -> PermissionState {
+    if self
+      .is_flag_denied(path, |denied, path| is_path_flag_denied(&denied.0, path))
+    {
+      return PermissionState::Denied;
+    }
     let path = path.map(|p| resolve_from_cwd(p).unwrap());
     if self.global_state == PermissionState::Denied
       && match path.as_ref() {
@@ -1531,6 +2211,8 @@ impl Default for UnaryPermission<FfiDescriptor> {
       global_state: Default::default(),
       granted_list: Default::default(),
       denied_list: Default::default(),
+      flag_denied_global: false,
+      flag_denied_list: Default::default(),
       prompt: false,
     }
   }
@@ -1541,6 +2223,7 @@ pub struct Permissions {
   pub read: UnaryPermission<ReadDescriptor>,
   pub write: UnaryPermission<WriteDescriptor>,
   pub net: UnaryPermission<NetDescriptor>,
+  pub vsock: UnaryPermission<VsockDescriptor>,
   pub env: UnaryPermission<EnvDescriptor>,
   pub sys: UnaryPermission<SysDescriptor>,
   pub run: UnaryPermission<RunDescriptor>,
@@ -1554,6 +2237,7 @@ impl Default for Permissions {
       read: Permissions::new_read(&None, false).unwrap(),
       write: Permissions::new_write(&None, false).unwrap(),
       net: Permissions::new_net(&None, false).unwrap(),
+      vsock: Permissions::new_vsock(&None, false).unwrap(),
       env: Permissions::new_env(&None, false).unwrap(),
       sys: Permissions::new_sys(&None, false).unwrap(),
       run: Permissions::new_run(&None, false).unwrap(),
@@ -1572,7 +2256,17 @@ pub struct PermissionsOptions {
   pub allow_read: Option<Vec<PathBuf>>,
   pub allow_run: Option<Vec<String>>,
   pub allow_sys: Option<Vec<String>>,
+  pub allow_vsock: Option<Vec<String>>,
   pub allow_write: Option<Vec<PathBuf>>,
+  pub deny_env: Option<Vec<String>>,
+  pub deny_hrtime: bool,
+  pub deny_net: Option<Vec<String>>,
+  pub deny_ffi: Option<Vec<PathBuf>>,
+  pub deny_read: Option<Vec<PathBuf>>,
+  pub deny_run: Option<Vec<String>>,
+  pub deny_sys: Option<Vec<String>>,
+  pub deny_vsock: Option<Vec<String>>,
+  pub deny_write: Option<Vec<PathBuf>>,
   pub prompt: bool,
 }
 
@@ -1620,6 +2314,25 @@ impl Permissions {
     })
   }
 
+  pub fn new_vsock(
+    state: &Option<Vec<String>>,
+    prompt: bool,
+  ) -> Result<UnaryPermission<VsockDescriptor>, AnyError> {
+    Ok(UnaryPermission::<VsockDescriptor> {
+      global_state: global_state_from_option(state),
+      granted_list: state
+        .as_ref()
+        .map(|v| {
+          v.iter()
+            .map(|x| VsockDescriptor::from_str(x))
+            .collect::<Result<HashSet<VsockDescriptor>, AnyError>>()
+        })
+        .unwrap_or_else(|| Ok(HashSet::new()))?,
+      prompt,
+      ..Default::default()
+    })
+  }
+
   pub fn new_env(
     state: &Option<Vec<String>>,
     prompt: bool,
@@ -1633,6 +2346,11 @@ impl Permissions {
             .map(|x| {
               if x.is_empty() {
                 Err(AnyError::msg("Empty path is not allowed"))
+              } else if x.strip_suffix('*').map_or(x.contains('*'), |prefix| {
+                prefix.is_empty() || prefix.contains('*')
+              }) {
+                // Wildcards are only allowed as a suffix, like "AWS_*"
+                Err(type_error(format!("Invalid env wildcard \"{x}\"")))
               } else {
                 Ok(EnvDescriptor::new(x))
               }
@@ -1684,7 +2402,7 @@ impl Permissions {
               if x.is_empty() {
                 Err(AnyError::msg("Empty path is not allowed"))
               } else {
-                Ok(RunDescriptor::from_str(x).unwrap())
+                Ok(RunDescriptor::parse_with_args(x))
               }
             })
             .collect()
@@ -1717,15 +2435,28 @@ impl Permissions {
   }
 
   pub fn from_options(opts: &PermissionsOptions) -> Result<Self, AnyError> {
+    let mut hrtime = Permissions::new_hrtime(opts.allow_hrtime);
+    if opts.deny_hrtime {
+      hrtime.state = PermissionState::Denied;
+    }
     Ok(Self {
-      read: Permissions::new_read(&opts.allow_read, opts.prompt)?,
-      write: Permissions::new_write(&opts.allow_write, opts.prompt)?,
-      net: Permissions::new_net(&opts.allow_net, opts.prompt)?,
-      env: Permissions::new_env(&opts.allow_env, opts.prompt)?,
-      sys: Permissions::new_sys(&opts.allow_sys, opts.prompt)?,
-      run: Permissions::new_run(&opts.allow_run, opts.prompt)?,
-      ffi: Permissions::new_ffi(&opts.allow_ffi, opts.prompt)?,
-      hrtime: Permissions::new_hrtime(opts.allow_hrtime),
+      read: Permissions::new_read(&opts.allow_read, opts.prompt)?
+        .with_flag_denied(Permissions::new_read(&opts.deny_read, false)?),
+      write: Permissions::new_write(&opts.allow_write, opts.prompt)?
+        .with_flag_denied(Permissions::new_write(&opts.deny_write, false)?),
+      net: Permissions::new_net(&opts.allow_net, opts.prompt)?
+        .with_flag_denied(Permissions::new_net(&opts.deny_net, false)?),
+      vsock: Permissions::new_vsock(&opts.allow_vsock, opts.prompt)?
+        .with_flag_denied(Permissions::new_vsock(&opts.deny_vsock, false)?),
+      env: Permissions::new_env(&opts.allow_env, opts.prompt)?
+        .with_flag_denied(Permissions::new_env(&opts.deny_env, false)?),
+      sys: Permissions::new_sys(&opts.allow_sys, opts.prompt)?
+        .with_flag_denied(Permissions::new_sys(&opts.deny_sys, false)?),
+      run: Permissions::new_run(&opts.allow_run, opts.prompt)?
+        .with_flag_denied(Permissions::new_run(&opts.deny_run, false)?),
+      ffi: Permissions::new_ffi(&opts.allow_ffi, opts.prompt)?
+        .with_flag_denied(Permissions::new_ffi(&opts.deny_ffi, false)?),
+      hrtime,
     })
   }
 
@@ -1734,6 +2465,7 @@ impl Permissions {
       read: Permissions::new_read(&Some(vec![]), false).unwrap(),
       write: Permissions::new_write(&Some(vec![]), false).unwrap(),
       net: Permissions::new_net(&Some(vec![]), false).unwrap(),
+      vsock: Permissions::new_vsock(&Some(vec![]), false).unwrap(),
       env: Permissions::new_env(&Some(vec![]), false).unwrap(),
       sys: Permissions::new_sys(&Some(vec![]), false).unwrap(),
       run: Permissions::new_run(&Some(vec![]), false).unwrap(),
@@ -1780,6 +2512,19 @@ impl PermissionsContainer {
     Self::new(Permissions::allow_all())
   }
 
+  /// Returns the check of the addresses that the hosts of `fetch()` requests
+  /// resolve to, which rejects the addresses in denied IP ranges.
+  pub fn fetch_resolved_addrs_check(&self) -> deno_fetch::ResolvedAddrsCheck {
+    let permissions = self.0.clone();
+    Arc::new(move |host: &str, addrs: &[SocketAddr]| {
+      permissions.lock().net.check_resolved(
+        &(host, None),
+        addrs,
+        Some("fetch()"),
+      )
+    })
+  }
+
   #[inline(always)]
   pub fn check_specifier(
     &self,
@@ -1840,9 +2585,16 @@ impl PermissionsContainer {
   pub fn check_run(
     &mut self,
     cmd: &str,
+    args: &[String],
+    is_env_changed: bool,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().run.check(cmd, Some(api_name))
+    self.0.lock().run.check_with_args(
+      cmd,
+      Some(args),
+      is_env_changed,
+      Some(api_name),
+    )
   }
 
   #[inline(always)]
@@ -1881,10 +2633,43 @@ impl deno_net::NetPermissions for PermissionsContainer {
   #[inline(always)]
   fn check_net<T: AsRef<str>>(
     &mut self,
-    host: &(T, Option<u16>),
+    host: &(T, Option<u16>),
+    api_name: &str,
+  ) -> Result<(), AnyError> {
+    self.0.lock().net.check(host, Some(api_name))
+  }
+
+  #[inline(always)]
+  fn check_net_unresolved<T: AsRef<str>>(
+    &mut self,
+    host: &(T, Option<u16>),
+    api_name: &str,
+  ) -> Result<(), AnyError> {
+    self.0.lock().net.check_unresolved(host, Some(api_name))
+  }
+
+  #[inline(always)]
+  fn check_net_resolved<T: AsRef<str>>(
+    &mut self,
+    host: &(T, Option<u16>),
+    addrs: &[SocketAddr],
+    api_name: &str,
+  ) -> Result<(), AnyError> {
+    self
+      .0
+      .lock()
+      .net
+      .check_resolved(host, addrs, Some(api_name))
+  }
+
+  #[inline(always)]
+  fn check_vsock(
+    &mut self,
+    cid: u32,
+    port: u32,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().net.check(host, Some(api_name))
+    self.0.lock().vsock.check(cid, Some(port), Some(api_name))
   }
 
   #[inline(always)]
@@ -1924,6 +2709,15 @@ impl deno_fetch::FetchPermissions for PermissionsContainer {
   ) -> Result<(), AnyError> {
     self.0.lock().read.check(path, Some(api_name))
   }
+
+  #[inline(always)]
+  fn check_write(
+    &mut self,
+    path: &Path,
+    api_name: &str,
+  ) -> Result<(), AnyError> {
+    self.0.lock().write.check(path, Some(api_name))
+  }
 }
 
 impl deno_web::TimersPermission for PermissionsContainer {
@@ -1947,6 +2741,20 @@ impl deno_websocket::WebSocketPermissions for PermissionsContainer {
   ) -> Result<(), AnyError> {
     self.0.lock().net.check_url(url, Some(api_name))
   }
+
+  #[inline(always)]
+  fn check_net_resolved<T: AsRef<str>>(
+    &mut self,
+    host: &(T, Option<u16>),
+    addrs: &[SocketAddr],
+    api_name: &str,
+  ) -> Result<(), AnyError> {
+    self
+      .0
+      .lock()
+      .net
+      .check_resolved(host, addrs, Some(api_name))
+  }
 }
 
 impl deno_fs::FsPermissions for PermissionsContainer {
@@ -2002,6 +2810,7 @@ impl deno_napi::NapiPermissions for PermissionsContainer {
   }
 }
 
+#[cfg(feature = "ffi")]
 impl deno_ffi::FfiPermissions for PermissionsContainer {
   #[inline(always)]
   fn check(&mut self, path: Option<&Path>) -> Result<(), AnyError> {
@@ -2009,6 +2818,7 @@ impl deno_ffi::FfiPermissions for PermissionsContainer {
   }
 }
 
+#[cfg(feature = "kv")]
 impl deno_kv::sqlite::SqliteDbHandlerPermissions for PermissionsContainer {
   #[inline(always)]
   fn check_read(&mut self, p: &Path, api_name: &str) -> Result<(), AnyError> {
@@ -2246,7 +3056,9 @@ pub struct ChildPermissionsArg {
   read: ChildUnaryPermissionArg,
   run: ChildUnaryPermissionArg,
   sys: ChildUnaryPermissionArg,
+  vsock: ChildUnaryPermissionArg,
   write: ChildUnaryPermissionArg,
+  preset: Option<String>,
 }
 
 impl ChildPermissionsArg {
@@ -2259,7 +3071,9 @@ impl ChildPermissionsArg {
       read: ChildUnaryPermissionArg::Inherit,
       run: ChildUnaryPermissionArg::Inherit,
       sys: ChildUnaryPermissionArg::Inherit,
+      vsock: ChildUnaryPermissionArg::Inherit,
       write: ChildUnaryPermissionArg::Inherit,
+      preset: None,
     }
   }
 
@@ -2272,7 +3086,56 @@ impl ChildPermissionsArg {
       read: ChildUnaryPermissionArg::NotGranted,
       run: ChildUnaryPermissionArg::NotGranted,
       sys: ChildUnaryPermissionArg::NotGranted,
+      vsock: ChildUnaryPermissionArg::NotGranted,
       write: ChildUnaryPermissionArg::NotGranted,
+      preset: None,
+    }
+  }
+
+  /// The permissions of the named preset, resolved by the host.
+  pub fn preset(name: &str) -> Self {
+    ChildPermissionsArg {
+      preset: Some(name.to_string()),
+      ..Self::none()
+    }
+  }
+
+  /// The name of the preset of the permissions, if they're one.
+  pub fn preset_name(&self) -> Option<&str> {
+    self.preset.as_deref()
+  }
+
+  /// The permissions granted by the options of a preset, which can't escalate
+  /// the ones of the parent.
+  pub fn from_options(opts: &PermissionsOptions) -> Self {
+    fn unary_arg<T>(
+      list: &Option<Vec<T>>,
+      to_string: impl Fn(&T) -> String,
+    ) -> ChildUnaryPermissionArg {
+      match list {
+        None => ChildUnaryPermissionArg::NotGranted,
+        Some(list) if list.is_empty() => ChildUnaryPermissionArg::Granted,
+        Some(list) => ChildUnaryPermissionArg::GrantedList(
+          list.iter().map(to_string).collect(),
+        ),
+      }
+    }
+    let path_to_string = |p: &PathBuf| p.to_string_lossy().to_string();
+    ChildPermissionsArg {
+      env: unary_arg(&opts.allow_env, String::clone),
+      hrtime: if opts.allow_hrtime {
+        ChildUnitPermissionArg::Granted
+      } else {
+        ChildUnitPermissionArg::NotGranted
+      },
+      net: unary_arg(&opts.allow_net, String::clone),
+      ffi: unary_arg(&opts.allow_ffi, path_to_string),
+      read: unary_arg(&opts.allow_read, path_to_string),
+      run: unary_arg(&opts.allow_run, String::clone),
+      sys: unary_arg(&opts.allow_sys, String::clone),
+      vsock: unary_arg(&opts.allow_vsock, String::clone),
+      write: unary_arg(&opts.allow_write, path_to_string),
+      preset: None,
     }
   }
 }
@@ -2287,7 +3150,9 @@ impl<'de> Deserialize<'de> for ChildPermissionsArg {
       type Value = ChildPermissionsArg;
 
       fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter.write_str("\"inherit\" or \"none\" or object")
+        formatter.write_str(
+          "\"inherit\" or \"none\" or the name of a preset or object",
+        )
       }
 
       fn visit_unit<E>(self) -> Result<ChildPermissionsArg, E>
@@ -2305,6 +3170,8 @@ impl<'de> Deserialize<'de> for ChildPermissionsArg {
           Ok(ChildPermissionsArg::inherit())
         } else if v == "none" {
           Ok(ChildPermissionsArg::none())
+        } else if !v.is_empty() {
+          Ok(ChildPermissionsArg::preset(v))
         } else {
           Err(de::Error::invalid_value(de::Unexpected::Str(v), &self))
         }
@@ -2353,6 +3220,11 @@ impl<'de> Deserialize<'de> for ChildPermissionsArg {
             child_permissions_arg.sys = arg.map_err(|e| {
               de::Error::custom(format!("(deno.permissions.sys) {e}"))
             })?;
+          } else if key == "vsock" {
+            let arg = serde_json::from_value::<ChildUnaryPermissionArg>(value);
+            child_permissions_arg.vsock = arg.map_err(|e| {
+              de::Error::custom(format!("(deno.permissions.vsock) {e}"))
+            })?;
           } else if key == "write" {
             let arg = serde_json::from_value::<ChildUnaryPermissionArg>(value);
             child_permissions_arg.write = arg.map_err(|e| {
@@ -2369,10 +3241,55 @@ impl<'de> Deserialize<'de> for ChildPermissionsArg {
   }
 }
 
+/// Named permission sets that workers can be created with, by passing the
+/// name of one as their permissions.
+#[derive(Clone, Debug, Default)]
+pub struct PermissionPresets(pub Arc<HashMap<String, PermissionsOptions>>);
+
+impl PermissionPresets {
+  pub fn new(presets: HashMap<String, PermissionsOptions>) -> Self {
+    Self(Arc::new(presets))
+  }
+
+  pub fn get(&self, name: &str) -> Option<&PermissionsOptions> {
+    self.0.get(name)
+  }
+}
+
+/// Create the permissions of a child from a preset, which are granted like by
+/// `create_child_permissions()` and denied over those of the parent.
+pub fn create_child_permissions_from_preset(
+  main_perms: &mut Permissions,
+  preset: &PermissionsOptions,
+) -> Result<Permissions, AnyError> {
+  let mut worker_perms = create_child_permissions(
+    main_perms,
+    ChildPermissionsArg::from_options(preset),
+  )?;
+  let denied = Permissions::from_options(preset)?;
+  worker_perms.read.add_flag_denied(&denied.read);
+  worker_perms.write.add_flag_denied(&denied.write);
+  worker_perms.net.add_flag_denied(&denied.net);
+  worker_perms.vsock.add_flag_denied(&denied.vsock);
+  worker_perms.env.add_flag_denied(&denied.env);
+  worker_perms.sys.add_flag_denied(&denied.sys);
+  worker_perms.run.add_flag_denied(&denied.run);
+  worker_perms.ffi.add_flag_denied(&denied.ffi);
+  if preset.deny_hrtime {
+    worker_perms.hrtime.state = PermissionState::Denied;
+  }
+  Ok(worker_perms)
+}
+
 pub fn create_child_permissions(
   main_perms: &mut Permissions,
   child_permissions_arg: ChildPermissionsArg,
 ) -> Result<Permissions, AnyError> {
+  if let Some(preset) = child_permissions_arg.preset {
+    return Err(type_error(format!(
+      "Permission presets are only supported by workers, got \"{preset}\""
+    )));
+  }
   let mut worker_perms = Permissions::default();
   match child_permissions_arg.env {
     ChildUnaryPermissionArg::Inherit => {
@@ -2399,6 +3316,8 @@ pub fn create_child_permissions(
     }
   }
   worker_perms.env.denied_list = main_perms.env.denied_list.clone();
+  worker_perms.env.flag_denied_global = main_perms.env.flag_denied_global;
+  worker_perms.env.flag_denied_list = main_perms.env.flag_denied_list.clone();
   if main_perms.env.global_state == PermissionState::Denied {
     worker_perms.env.global_state = PermissionState::Denied;
   }
@@ -2428,10 +3347,44 @@ pub fn create_child_permissions(
     }
   }
   worker_perms.sys.denied_list = main_perms.sys.denied_list.clone();
+  worker_perms.sys.flag_denied_global = main_perms.sys.flag_denied_global;
+  worker_perms.sys.flag_denied_list = main_perms.sys.flag_denied_list.clone();
   if main_perms.sys.global_state == PermissionState::Denied {
     worker_perms.sys.global_state = PermissionState::Denied;
   }
   worker_perms.sys.prompt = main_perms.sys.prompt;
+  match child_permissions_arg.vsock {
+    ChildUnaryPermissionArg::Inherit => {
+      worker_perms.vsock = main_perms.vsock.clone();
+    }
+    ChildUnaryPermissionArg::Granted => {
+      if main_perms.vsock.check_all().is_err() {
+        return Err(escalation_error());
+      }
+      worker_perms.vsock.global_state = PermissionState::Granted;
+    }
+    ChildUnaryPermissionArg::NotGranted => {}
+    ChildUnaryPermissionArg::GrantedList(granted_list) => {
+      worker_perms.vsock.granted_list =
+        Permissions::new_vsock(&Some(granted_list), false)?.granted_list;
+      if !worker_perms
+        .vsock
+        .granted_list
+        .iter()
+        .all(|desc| main_perms.vsock.check(desc.0, desc.1, None).is_ok())
+      {
+        return Err(escalation_error());
+      }
+    }
+  }
+  worker_perms.vsock.denied_list = main_perms.vsock.denied_list.clone();
+  worker_perms.vsock.flag_denied_global = main_perms.vsock.flag_denied_global;
+  worker_perms.vsock.flag_denied_list =
+    main_perms.vsock.flag_denied_list.clone();
+  if main_perms.vsock.global_state == PermissionState::Denied {
+    worker_perms.vsock.global_state = PermissionState::Denied;
+  }
+  worker_perms.vsock.prompt = main_perms.vsock.prompt;
   match child_permissions_arg.hrtime {
     ChildUnitPermissionArg::Inherit => {
       worker_perms.hrtime = main_perms.hrtime.clone();
@@ -2473,6 +3426,8 @@ pub fn create_child_permissions(
     }
   }
   worker_perms.net.denied_list = main_perms.net.denied_list.clone();
+  worker_perms.net.flag_denied_global = main_perms.net.flag_denied_global;
+  worker_perms.net.flag_denied_list = main_perms.net.flag_denied_list.clone();
   if main_perms.net.global_state == PermissionState::Denied {
     worker_perms.net.global_state = PermissionState::Denied;
   }
@@ -2505,6 +3460,8 @@ pub fn create_child_permissions(
     }
   }
   worker_perms.ffi.denied_list = main_perms.ffi.denied_list.clone();
+  worker_perms.ffi.flag_denied_global = main_perms.ffi.flag_denied_global;
+  worker_perms.ffi.flag_denied_list = main_perms.ffi.flag_denied_list.clone();
   if main_perms.ffi.global_state == PermissionState::Denied {
     worker_perms.ffi.global_state = PermissionState::Denied;
   }
@@ -2537,6 +3494,8 @@ pub fn create_child_permissions(
     }
   }
   worker_perms.read.denied_list = main_perms.read.denied_list.clone();
+  worker_perms.read.flag_denied_global = main_perms.read.flag_denied_global;
+  worker_perms.read.flag_denied_list = main_perms.read.flag_denied_list.clone();
   if main_perms.read.global_state == PermissionState::Denied {
     worker_perms.read.global_state = PermissionState::Denied;
   }
@@ -2555,17 +3514,20 @@ pub fn create_child_permissions(
     ChildUnaryPermissionArg::GrantedList(granted_list) => {
       worker_perms.run.granted_list =
         Permissions::new_run(&Some(granted_list), false)?.granted_list;
-      if !worker_perms
-        .run
-        .granted_list
-        .iter()
-        .all(|desc| main_perms.run.check(&desc.to_string(), None).is_ok())
-      {
+      if !worker_perms.run.granted_list.iter().all(|desc| match desc {
+        RunDescriptor::WithArgs(cmd, args) => main_perms
+          .run
+          .check_with_args(&cmd.to_string(), Some(args), false, None)
+          .is_ok(),
+        desc => main_perms.run.check(&desc.to_string(), None).is_ok(),
+      }) {
         return Err(escalation_error());
       }
     }
   }
   worker_perms.run.denied_list = main_perms.run.denied_list.clone();
+  worker_perms.run.flag_denied_global = main_perms.run.flag_denied_global;
+  worker_perms.run.flag_denied_list = main_perms.run.flag_denied_list.clone();
   if main_perms.run.global_state == PermissionState::Denied {
     worker_perms.run.global_state = PermissionState::Denied;
   }
@@ -2598,6 +3560,9 @@ pub fn create_child_permissions(
     }
   }
   worker_perms.write.denied_list = main_perms.write.denied_list.clone();
+  worker_perms.write.flag_denied_global = main_perms.write.flag_denied_global;
+  worker_perms.write.flag_denied_list =
+    main_perms.write.flag_denied_list.clone();
   if main_perms.write.global_state == PermissionState::Denied {
     worker_perms.write.global_state = PermissionState::Denied;
   }
@@ -2762,6 +3727,359 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_check_net_abstract_socket() {
+    set_prompter(Box::new(TestPrompter));
+    let mut perms = Permissions::from_options(&PermissionsOptions {
+      allow_net: Some(svec!["@dbus"]),
+      ..Default::default()
+    })
+    .unwrap();
+
+    assert!(perms.net.check(&("@dbus", None), None).is_ok());
+    assert!(perms.net.check(&("dbus", None), None).is_err());
+    assert!(perms.net.check(&("@containerd", None), None).is_err());
+  }
+
+  #[test]
+  fn test_check_net_ip_ranges() {
+    set_prompter(Box::new(TestPrompter));
+    let mut perms = Permissions::from_options(&PermissionsOptions {
+      allow_net: Some(svec![
+        "10.0.0.0/8",
+        "192.168.1.7/24",
+        "fd00::/8",
+        "127.0.0.0/8"
+      ]),
+      ..Default::default()
+    })
+    .unwrap();
+
+    let ip_tests = vec![
+      ("10.0.0.1", true),
+      ("10.255.255.255", true),
+      ("11.0.0.1", false),
+      ("192.168.1.1", true),
+      ("192.168.2.1", false),
+      ("[fd12:3456::1]", true),
+      ("[fe80::1]", false),
+      ("[::ffff:10.1.2.3]", true),
+      ("127.0.0.1", true),
+      // Host names aren't resolved
+      ("localhost", false),
+    ];
+    for (host, is_ok) in ip_tests {
+      assert_eq!(is_ok, perms.net.check(&(host, Some(80)), None).is_ok());
+    }
+
+    assert_eq!(
+      perms.net.query(Some(&("10.1.0.0/16", None))),
+      PermissionState::Granted
+    );
+    assert_eq!(
+      perms.net.query(Some(&("192.168.1.0/24", None))),
+      PermissionState::Granted
+    );
+    assert_eq!(
+      perms.net.query(Some(&("192.168.0.0/16", None))),
+      PermissionState::Prompt
+    );
+    assert_eq!(
+      perms.net.query(Some(&("0.0.0.0/0", None))),
+      PermissionState::Prompt
+    );
+
+    // Host names are matched by the addresses they resolve to
+    let host = &("db.internal", Some(5432));
+    assert!(perms.net.check_unresolved(host, None).is_ok());
+    assert!(perms
+      .net
+      .check_resolved(host, &["10.1.2.3:5432".parse().unwrap()], None)
+      .is_ok());
+    assert!(perms
+      .net
+      .check_resolved(
+        host,
+        &[
+          "10.1.2.3:5432".parse().unwrap(),
+          "8.8.8.8:5432".parse().unwrap()
+        ],
+        None
+      )
+      .is_err());
+    assert!(perms.net.check_resolved(host, &[], None).is_err());
+    assert!(perms
+      .net
+      .check_unresolved(&("10.0.0.1", Some(80)), None)
+      .is_ok());
+    assert!(perms
+      .net
+      .check_unresolved(&("11.0.0.1", Some(80)), None)
+      .is_err());
+
+    assert!(Permissions::new_net(&Some(svec!["10.0.0.0/33"]), false).is_err());
+    assert!(Permissions::new_net(&Some(svec!["fd00::/129"]), false).is_err());
+    assert!(Permissions::new_net(&Some(svec!["10.0.0.0/x"]), false).is_err());
+  }
+
+  #[test]
+  fn test_check_net_wildcards() {
+    set_prompter(Box::new(TestPrompter));
+    let mut perms = Permissions::from_options(&PermissionsOptions {
+      allow_net: Some(svec!["*.example.com", "*.deno.land:443"]),
+      ..Default::default()
+    })
+    .unwrap();
+
+    let domain_tests = vec![
+      ("api.example.com", 80, true),
+      ("eu.api.example.com", 443, true),
+      ("API.Example.com", 80, true),
+      ("example.com", 80, false),
+      ("badexample.com", 80, false),
+      ("example.com.evil", 80, false),
+      ("x.deno.land", 443, true),
+      ("x.deno.land", 80, false),
+      ("deno.land", 443, false),
+    ];
+    for (host, port, is_ok) in domain_tests {
+      assert_eq!(is_ok, perms.net.check(&(host, Some(port)), None).is_ok());
+    }
+
+    assert_eq!(
+      perms.net.query(Some(&("*.eu.example.com", None))),
+      PermissionState::Granted
+    );
+    assert_eq!(
+      perms.net.query(Some(&("*.com", None))),
+      PermissionState::Prompt
+    );
+
+    assert!(Permissions::new_net(&Some(svec!["*"]), false).is_err());
+    assert!(
+      Permissions::new_net(&Some(svec!["api*.example.com"]), false).is_err()
+    );
+    assert!(
+      Permissions::new_net(&Some(svec!["*.*.example.com"]), false).is_err()
+    );
+  }
+
+  #[test]
+  fn test_check_env_wildcards() {
+    set_prompter(Box::new(TestPrompter));
+    let mut perms = Permissions::from_options(&PermissionsOptions {
+      allow_env: Some(svec!["AWS_*", "MYAPP_*", "HOME"]),
+      deny_env: Some(svec!["AWS_SECRET_*"]),
+      ..Default::default()
+    })
+    .unwrap();
+
+    let env_tests = vec![
+      ("AWS_REGION", true),
+      ("AWS_", true),
+      ("MYAPP_PORT", true),
+      ("HOME", true),
+      ("HOMEPATH", false),
+      ("AWS", false),
+      ("XAWS_REGION", false),
+      ("AWS_SECRET_ACCESS_KEY", false),
+    ];
+    for (env, is_ok) in env_tests {
+      assert_eq!(is_ok, perms.env.check(env).is_ok(), "{env}");
+    }
+
+    assert_eq!(perms.env.query(Some("AWS_S3_*")), PermissionState::Granted);
+    assert_eq!(perms.env.query(Some("*")), PermissionState::Prompt);
+
+    assert!(Permissions::new_env(&Some(svec!["*"]), false).is_err());
+    assert!(Permissions::new_env(&Some(svec!["AWS_**"]), false).is_err());
+    assert!(Permissions::new_env(&Some(svec!["AWS_*_KEY"]), false).is_err());
+  }
+
+  #[test]
+  fn test_check_deny_flags() {
+    set_prompter(Box::new(TestPrompter));
+    let mut perms = Permissions::from_options(&PermissionsOptions {
+      allow_read: Some(vec![]),
+      allow_net: Some(vec![]),
+      allow_env: Some(vec![]),
+      allow_run: Some(svec!["deno", "git"]),
+      allow_hrtime: true,
+      deny_read: Some(vec![PathBuf::from("/etc")]),
+      deny_net: Some(svec!["metadata.internal", "169.254.0.0/16"]),
+      deny_env: Some(svec!["SECRET"]),
+      deny_run: Some(svec!["git"]),
+      deny_write: Some(vec![]),
+      deny_hrtime: true,
+      prompt: true,
+      ..Default::default()
+    })
+    .unwrap();
+
+    assert!(perms.read.check(Path::new("/etc/passwd"), None).is_err());
+    assert!(perms.read.check(Path::new("/etc"), None).is_err());
+    assert!(perms.read.check(Path::new("/etcetera"), None).is_ok());
+    assert!(perms.read.check(Path::new("/home"), None).is_ok());
+    assert!(perms.read.check_all(None).is_err());
+    assert!(perms.write.check(Path::new("/tmp/a"), None).is_err());
+    assert!(perms
+      .net
+      .check(&("metadata.internal", Some(80)), None)
+      .is_err());
+    assert!(perms
+      .net
+      .check(&("METADATA.internal.", Some(80)), None)
+      .is_err());
+    assert!(perms
+      .net
+      .check(&("169.254.169.254", Some(80)), None)
+      .is_err());
+    assert!(perms.net.check(&("deno.land", Some(443)), None).is_ok());
+    assert!(perms
+      .net
+      .check_resolved(
+        &("deno.land", Some(443)),
+        &["169.254.169.254:443".parse().unwrap()],
+        None
+      )
+      .is_err());
+    assert!(perms
+      .net
+      .check_resolved(
+        &("deno.land", Some(443)),
+        &["1.1.1.1:443".parse().unwrap()],
+        None
+      )
+      .is_ok());
+    assert!(perms.env.check("SECRET").is_err());
+    assert!(perms.env.check("HOME").is_ok());
+    assert!(perms.env.check_all().is_err());
+    assert!(perms.run.check("git", None).is_err());
+    assert!(perms.run.check("deno", None).is_ok());
+    assert!(perms.hrtime.check().is_err());
+
+    // The flags take precedence over the requests and the revocations.
+    assert_eq!(
+      perms.read.request(Some(Path::new("/etc/hosts"))),
+      PermissionState::Denied
+    );
+    assert_eq!(perms.write.request(None), PermissionState::Denied);
+    assert_eq!(perms.write.revoke(None), PermissionState::Denied);
+    assert_eq!(perms.read.query(None), PermissionState::Denied);
+    assert_eq!(perms.hrtime.request(), PermissionState::Denied);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_check_deny_flags_symlink() {
+    set_prompter(Box::new(TestPrompter));
+    let temp_dir = test_util::TempDir::new();
+    temp_dir.create_dir_all("secret");
+    temp_dir.write("secret/key.txt", "");
+    let secret = temp_dir.path().join("secret");
+    let link = temp_dir.path().join("link");
+    std::os::unix::fs::symlink(&secret, &link).unwrap();
+    let mut perms = Permissions::from_options(&PermissionsOptions {
+      allow_read: Some(vec![]),
+      allow_write: Some(vec![]),
+      deny_read: Some(vec![secret.clone()]),
+      deny_write: Some(vec![secret]),
+      ..Default::default()
+    })
+    .unwrap();
+
+    assert!(perms.read.check(&link.join("key.txt"), None).is_err());
+    assert!(perms.read.check(&link, None).is_err());
+    assert!(perms.write.check(&link.join("new.txt"), None).is_err());
+    assert!(perms.read.check(temp_dir.path(), None).is_ok());
+    assert!(perms
+      .write
+      .check(&temp_dir.path().join("other.txt"), None)
+      .is_ok());
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_check_deny_run_path() {
+    set_prompter(Box::new(TestPrompter));
+    let mut perms = Permissions::from_options(&PermissionsOptions {
+      allow_run: Some(vec![]),
+      deny_run: Some(svec!["sh"]),
+      ..Default::default()
+    })
+    .unwrap();
+
+    assert!(perms.run.check("sh", None).is_err());
+    assert!(perms.run.check("/bin/sh", None).is_err());
+    assert!(perms.run.check("/bin/ls", None).is_ok());
+  }
+
+  #[test]
+  fn test_check_run_args() {
+    set_prompter(Box::new(TestPrompter));
+    let mut perms = Permissions::from_options(&PermissionsOptions {
+      allow_run: Some(svec!["git:status", "git:remote show", "deno"]),
+      deny_run: Some(svec!["deno:upgrade"]),
+      ..Default::default()
+    })
+    .unwrap();
+
+    let args =
+      |args: &[&str]| args.iter().map(ToString::to_string).collect::<Vec<_>>();
+    let run = &mut perms.run;
+    assert!(run
+      .check_with_args("git", Some(&args(&["status"])), false, None)
+      .is_ok());
+    assert!(run
+      .check_with_args("git", Some(&args(&["status", "--short"])), false, None)
+      .is_ok());
+    assert!(run
+      .check_with_args(
+        "git",
+        Some(&args(&["remote", "show", "origin"])),
+        false,
+        None
+      )
+      .is_ok());
+    assert!(run
+      .check_with_args("git", Some(&args(&["remote", "add"])), false, None)
+      .is_err());
+    assert!(run
+      .check_with_args("git", Some(&args(&["push", "--force"])), false, None)
+      .is_err());
+    assert!(run.check("git", None).is_err());
+    assert_eq!(run.query(Some("git")), PermissionState::Prompt);
+    assert!(run
+      .check_with_args("deno", Some(&args(&["run", "main.ts"])), false, None)
+      .is_ok());
+    assert!(run
+      .check_with_args("deno", Some(&args(&["upgrade"])), false, None)
+      .is_err());
+    assert!(run
+      .check_with_args("git", Some(&args(&["status"])), true, None)
+      .is_err());
+    assert!(run
+      .check_with_args("deno", Some(&args(&["run", "main.ts"])), true, None)
+      .is_ok());
+  }
+
+  #[test]
+  fn test_check_vsock() {
+    set_prompter(Box::new(TestPrompter));
+    let mut perms = Permissions::from_options(&PermissionsOptions {
+      allow_vsock: Some(svec!["2", "3:1024"]),
+      ..Default::default()
+    })
+    .unwrap();
+
+    assert!(perms.vsock.check(2, Some(52), None).is_ok());
+    assert!(perms.vsock.check(3, Some(1024), None).is_ok());
+    assert!(perms.vsock.check(3, Some(1025), None).is_err());
+    assert!(perms.vsock.check(4, Some(1024), None).is_err());
+    assert_eq!(perms.vsock.query(Some((3, None))), PermissionState::Prompt);
+    assert!(Permissions::new_vsock(&Some(svec!["host"]), false).is_err());
+  }
+
   #[test]
   fn test_check_net_only_flag() {
     set_prompter(Box::new(TestPrompter));
@@ -2989,6 +4307,10 @@ mod tests {
         global_state: PermissionState::Prompt,
         ..Permissions::new_net(&Some(svec!["127.0.0.1:8000"]), false).unwrap()
       },
+      vsock: UnaryPermission {
+        global_state: PermissionState::Prompt,
+        ..Permissions::new_vsock(&Some(svec!["3:1024"]), false).unwrap()
+      },
       env: UnaryPermission {
         global_state: PermissionState::Prompt,
         ..Permissions::new_env(&Some(svec!["HOME"]), false).unwrap()
@@ -3027,6 +4349,10 @@ mod tests {
       assert_eq!(perms1.net.query(Some(&("127.0.0.1", None))), PermissionState::Granted);
       assert_eq!(perms2.net.query::<&str>(None), PermissionState::Prompt);
       assert_eq!(perms2.net.query(Some(&("127.0.0.1", Some(8000)))), PermissionState::Granted);
+      assert_eq!(perms1.vsock.query(Some((3, None))), PermissionState::Granted);
+      assert_eq!(perms2.vsock.query(None), PermissionState::Prompt);
+      assert_eq!(perms2.vsock.query(Some((3, Some(1024)))), PermissionState::Granted);
+      assert_eq!(perms2.vsock.query(Some((3, Some(1025)))), PermissionState::Prompt);
       assert_eq!(perms1.env.query(None), PermissionState::Granted);
       assert_eq!(perms1.env.query(Some("HOME")), PermissionState::Granted);
       assert_eq!(perms2.env.query(None), PermissionState::Prompt);
@@ -3128,6 +4454,10 @@ mod tests {
         )
         .unwrap()
       },
+      vsock: UnaryPermission {
+        global_state: PermissionState::Prompt,
+        ..Permissions::new_vsock(&Some(svec!["3", "3:1024"]), false).unwrap()
+      },
       env: UnaryPermission {
         global_state: PermissionState::Prompt,
         ..Permissions::new_env(&Some(svec!["HOME"]), false).unwrap()
@@ -3159,6 +4489,9 @@ mod tests {
       assert_eq!(perms.net.revoke(Some(&("127.0.0.1", Some(9000)))), PermissionState::Prompt);
       assert_eq!(perms.net.query(Some(&("127.0.0.1", None))), PermissionState::Prompt);
       assert_eq!(perms.net.query(Some(&("127.0.0.1", Some(8000)))), PermissionState::Granted);
+      assert_eq!(perms.vsock.revoke(Some((3, Some(2048)))), PermissionState::Prompt);
+      assert_eq!(perms.vsock.query(Some((3, None))), PermissionState::Prompt);
+      assert_eq!(perms.vsock.query(Some((3, Some(1024)))), PermissionState::Granted);
       assert_eq!(perms.env.revoke(Some("HOME")), PermissionState::Prompt);
       assert_eq!(perms.env.revoke(Some("hostname")), PermissionState::Prompt);
       assert_eq!(perms.run.revoke(Some("deno")), PermissionState::Prompt);
@@ -3173,6 +4506,7 @@ mod tests {
       read: Permissions::new_read(&None, true).unwrap(),
       write: Permissions::new_write(&None, true).unwrap(),
       net: Permissions::new_net(&None, true).unwrap(),
+      vsock: Permissions::new_vsock(&None, true).unwrap(),
       env: Permissions::new_env(&None, true).unwrap(),
       sys: Permissions::new_sys(&None, true).unwrap(),
       run: Permissions::new_run(&None, true).unwrap(),
@@ -3237,6 +4571,7 @@ mod tests {
       read: Permissions::new_read(&None, true).unwrap(),
       write: Permissions::new_write(&None, true).unwrap(),
       net: Permissions::new_net(&None, true).unwrap(),
+      vsock: Permissions::new_vsock(&None, true).unwrap(),
       env: Permissions::new_env(&None, true).unwrap(),
       sys: Permissions::new_sys(&None, true).unwrap(),
       run: Permissions::new_run(&None, true).unwrap(),
@@ -3343,7 +4678,9 @@ mod tests {
         read: ChildUnaryPermissionArg::Inherit,
         run: ChildUnaryPermissionArg::Inherit,
         sys: ChildUnaryPermissionArg::Inherit,
+        vsock: ChildUnaryPermissionArg::Inherit,
         write: ChildUnaryPermissionArg::Inherit,
+        preset: None,
       }
     );
     assert_eq!(
@@ -3356,7 +4693,9 @@ mod tests {
         read: ChildUnaryPermissionArg::NotGranted,
         run: ChildUnaryPermissionArg::NotGranted,
         sys: ChildUnaryPermissionArg::NotGranted,
+        vsock: ChildUnaryPermissionArg::NotGranted,
         write: ChildUnaryPermissionArg::NotGranted,
+        preset: None,
       }
     );
     assert_eq!(
@@ -3381,6 +4720,16 @@ mod tests {
         ..ChildPermissionsArg::none()
       }
     );
+    assert_eq!(
+      serde_json::from_value::<ChildPermissionsArg>(json!({
+        "vsock": ["2", "3:1024"],
+      }))
+      .unwrap(),
+      ChildPermissionsArg {
+        vsock: ChildUnaryPermissionArg::GrantedList(svec!["2", "3:1024"]),
+        ..ChildPermissionsArg::none()
+      }
+    );
     assert_eq!(
       serde_json::from_value::<ChildPermissionsArg>(json!({
         "hrtime": true,
@@ -3579,6 +4928,48 @@ mod tests {
     assert_eq!(worker_perms.write.denied_list, main_perms.write.denied_list);
   }
 
+  #[test]
+  fn test_create_child_permissions_from_preset() {
+    set_prompter(Box::new(TestPrompter));
+    assert_eq!(
+      serde_json::from_value::<ChildPermissionsArg>(json!("worker-sandbox"))
+        .unwrap(),
+      ChildPermissionsArg::preset("worker-sandbox")
+    );
+    let mut main_perms = Permissions {
+      env: Permissions::new_env(&Some(vec![]), false).unwrap(),
+      net: Permissions::new_net(&Some(svec!["foo", "bar"]), false).unwrap(),
+      ..Default::default()
+    };
+    let worker_perms = create_child_permissions_from_preset(
+      &mut main_perms.clone(),
+      &PermissionsOptions {
+        allow_env: Some(vec![]),
+        deny_env: Some(svec!["SECRET"]),
+        allow_net: Some(svec!["foo"]),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    assert!(worker_perms.env.check("HOME").is_ok());
+    assert!(worker_perms.env.check("SECRET").is_err());
+    assert!(worker_perms.net.check(&("foo", None), None).is_ok());
+    assert!(worker_perms.net.check(&("bar", None), None).is_err());
+    assert!(create_child_permissions_from_preset(
+      &mut main_perms.clone(),
+      &PermissionsOptions {
+        allow_net: Some(vec![]),
+        ..Default::default()
+      },
+    )
+    .is_err());
+    assert!(create_child_permissions(
+      &mut main_perms,
+      ChildPermissionsArg::preset("worker-sandbox"),
+    )
+    .is_err());
+  }
+
   #[test]
   fn test_handle_empty_value() {
     set_prompter(Box::new(TestPrompter));
@@ -0,0 +1,189 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
+use deno_core::serde::Deserialize;
+use deno_core::serde::Serialize;
+use deno_core::serde_json;
+use once_cell::sync::Lazy;
+use std::path::Path;
+use std::path::PathBuf;
+
+static MAYBE_PROMPT_STORE: Lazy<Mutex<Option<PromptStore>>> =
+  Lazy::new(|| Mutex::new(None));
+
+/// A permission prompt that was answered with "allow and remember", which is
+/// keyed by the name of the permission and the message of the prompt.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RememberedGrant {
+  pub permission: String,
+  pub message: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PromptStoreData {
+  grants: Vec<RememberedGrant>,
+}
+
+struct PromptStore {
+  path: PathBuf,
+  data: PromptStoreData,
+}
+
+impl PromptStore {
+  fn load(path: &Path) -> Self {
+    let data = match std::fs::read_to_string(path) {
+      Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+        log::warn!(
+          "Ignoring the invalid permission prompt store at {}: {err}",
+          path.display()
+        );
+        PromptStoreData::default()
+      }),
+      Err(_) => PromptStoreData::default(),
+    };
+    Self {
+      path: path.to_path_buf(),
+      data,
+    }
+  }
+
+  fn contains(&self, grant: &RememberedGrant) -> bool {
+    self.data.grants.contains(grant)
+  }
+
+  fn insert(&mut self, grant: RememberedGrant) -> Result<(), AnyError> {
+    if self.contains(&grant) {
+      return Ok(());
+    }
+    self.data.grants.push(grant);
+    if let Some(parent) = self.path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&self.path, serde_json::to_string_pretty(&self.data)?)?;
+    Ok(())
+  }
+}
+
+/// Persist the permission prompt decisions that are answered with "allow and
+/// remember" to the file at `path`, and grant the ones already stored there
+/// without prompting.
+pub fn set_prompt_store(path: &Path) {
+  *MAYBE_PROMPT_STORE.lock() = Some(PromptStore::load(path));
+}
+
+/// The grants that are remembered in the prompt store at `path`.
+pub fn remembered_grants(path: &Path) -> Vec<RememberedGrant> {
+  PromptStore::load(path).data.grants
+}
+
+pub(crate) fn is_prompt_store_enabled() -> bool {
+  MAYBE_PROMPT_STORE.lock().is_some()
+}
+
+pub(crate) fn is_remembered(name: &str, message: &str) -> bool {
+  let grant = RememberedGrant {
+    permission: name.to_string(),
+    message: message.to_string(),
+  };
+  MAYBE_PROMPT_STORE
+    .lock()
+    .as_ref()
+    .map(|store| store.contains(&grant))
+    .unwrap_or(false)
+}
+
+pub(crate) fn remember(name: &str, message: &str) {
+  if let Some(store) = MAYBE_PROMPT_STORE.lock().as_mut() {
+    let grant = RememberedGrant {
+      permission: name.to_string(),
+      message: message.to_string(),
+    };
+    if let Err(err) = store.insert(grant) {
+      log::warn!("Failed to remember the permission prompt decision: {err}");
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_util::TempDir;
+
+  fn grant(permission: &str, message: &str) -> RememberedGrant {
+    RememberedGrant {
+      permission: permission.to_string(),
+      message: message.to_string(),
+    }
+  }
+
+  #[test]
+  fn test_load() {
+    let temp_dir = TempDir::new();
+    let path = temp_dir.path().join("missing.json");
+    assert!(PromptStore::load(&path).data.grants.is_empty());
+
+    temp_dir.write(
+      "store.json",
+      r#"{ "grants": [{ "permission": "read", "message": "read access to \"foo\"" }] }"#,
+    );
+    let store = PromptStore::load(&temp_dir.path().join("store.json"));
+    assert_eq!(store.data.grants, [grant("read", "read access to \"foo\"")]);
+  }
+
+  #[test]
+  fn test_load_corrupt() {
+    let temp_dir = TempDir::new();
+    temp_dir.write("store.json", "{ \"grants\": [");
+    let path = temp_dir.path().join("store.json");
+    let mut store = PromptStore::load(&path);
+    assert!(store.data.grants.is_empty());
+
+    // The corrupt file is replaced once a grant is remembered
+    store
+      .insert(grant("env", "env access to \"HOME\""))
+      .unwrap();
+    assert_eq!(
+      remembered_grants(&path),
+      [grant("env", "env access to \"HOME\"")]
+    );
+  }
+
+  #[test]
+  fn test_insert() {
+    let temp_dir = TempDir::new();
+    let path = temp_dir.path().join("prompts").join("store.json");
+    let mut store = PromptStore::load(&path);
+    store
+      .insert(grant("read", "read access to \"foo\""))
+      .unwrap();
+    store
+      .insert(grant("read", "read access to \"foo\""))
+      .unwrap();
+    store
+      .insert(grant("read", "read access to \"bar\""))
+      .unwrap();
+    assert_eq!(
+      remembered_grants(&path),
+      [
+        grant("read", "read access to \"foo\""),
+        grant("read", "read access to \"bar\"")
+      ]
+    );
+  }
+
+  #[test]
+  fn test_keying() {
+    let temp_dir = TempDir::new();
+    let path = temp_dir.path().join("store.json");
+    let mut store = PromptStore::load(&path);
+    store
+      .insert(grant("run", "run access to \"deno\""))
+      .unwrap();
+
+    let store = PromptStore::load(&path);
+    assert!(store.contains(&grant("run", "run access to \"deno\"")));
+    assert!(!store.contains(&grant("run", "run access to \"git\"")));
+    assert!(!store.contains(&grant("read", "run access to \"deno\"")));
+  }
+}
@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use super::prompt_store;
 use crate::colors;
 use deno_core::error::AnyError;
 use deno_core::parking_lot::Mutex;
@@ -38,6 +39,9 @@ pub fn permission_prompt(
   api_name: Option<&str>,
   is_unary: bool,
 ) -> PromptResponse {
+  if prompt_store::is_remembered(flag, message) {
+    return PromptResponse::Allow;
+  }
   if let Some(before_callback) = MAYBE_BEFORE_PROMPT_CALLBACK.lock().as_mut() {
     before_callback();
   }
@@ -83,6 +87,8 @@ impl PermissionPrompter for TtyPrompter {
     if !atty::is(atty::Stream::Stdin) || !atty::is(atty::Stream::Stderr) {
       return PromptResponse::Deny;
     };
+    let can_remember = prompt_store::is_prompt_store_enabled();
+    let remember_key = (name.to_string(), message.to_string());
 
     #[cfg(unix)]
     fn clear_stdin() -> Result<(), AnyError> {
@@ -217,10 +223,11 @@ impl PermissionPrompter for TtyPrompter {
     let api_name = api_name.map(strip_ansi_codes_and_ascii_control);
 
     // print to stderr so that if stdout is piped this is still displayed.
-    let opts: String = if is_unary {
-      format!("[y/n/A] (y = yes, allow; n = no, deny; A = allow all {name} permissions)")
-    } else {
-      "[y/n] (y = yes, allow; n = no, deny)".to_string()
+    let opts: String = match (is_unary, can_remember) {
+      (true, true) => format!("[y/n/p/A] (y = yes, allow; n = no, deny; p = allow and remember for this project; A = allow all {name} permissions)"),
+      (true, false) => format!("[y/n/A] (y = yes, allow; n = no, deny; A = allow all {name} permissions)"),
+      (false, true) => "[y/n/p] (y = yes, allow; n = no, deny; p = allow and remember for this project)".to_string(),
+      (false, false) => "[y/n] (y = yes, allow; n = no, deny)".to_string(),
     };
 
     // output everything in one shot to make the tests more reliable
@@ -265,6 +272,14 @@ impl PermissionPrompter for TtyPrompter {
           eprintln!("❌ {}", colors::bold(&msg));
           break PromptResponse::Deny;
         }
+        'p' | 'P' if can_remember => {
+          clear_n_lines(if api_name.is_some() { 4 } else { 3 });
+          prompt_store::remember(&remember_key.0, &remember_key.1);
+          let msg =
+            format!("Granted {message} and remembered it for this project.");
+          eprintln!("✅ {}", colors::bold(&msg));
+          break PromptResponse::Allow;
+        }
         'A' if is_unary => {
           clear_n_lines(if api_name.is_some() { 4 } else { 3 });
           let msg = format!("Granted all {name} access.");
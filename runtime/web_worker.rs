@@ -2,6 +2,10 @@
 use crate::colors;
 use crate::inspector_server::InspectorServer;
 use crate::ops;
+use crate::optional_extensions;
+use crate::permissions::record_audit_stack;
+use crate::permissions::AuditStackGuard;
+use crate::permissions::PermissionPresets;
 use crate::permissions::PermissionsContainer;
 use crate::tokio_util::run_local;
 use crate::worker::FormatJsErrorFn;
@@ -36,7 +40,6 @@ use deno_core::Snapshot;
 use deno_core::SourceMapGetter;
 use deno_fs::FileSystem;
 use deno_io::Stdio;
-use deno_kv::sqlite::SqliteDbHandler;
 use deno_tls::RootCertStoreProvider;
 use deno_web::create_entangled_message_port;
 use deno_web::BlobStore;
@@ -315,6 +318,8 @@ fn create_handles(
 /// `WebWorker`.
 pub struct WebWorker {
   id: WorkerId,
+  /// Declared before the runtime, since it must be dropped first.
+  _audit_stack: Option<AuditStackGuard>,
   pub js_runtime: JsRuntime,
   pub name: String,
   internal_handle: WebWorkerInternalHandle,
@@ -338,6 +343,7 @@ pub struct WebWorkerOptions {
   pub preload_module_cb: Arc<ops::worker_host::WorkerEventCb>,
   pub pre_execute_module_cb: Arc<ops::worker_host::WorkerEventCb>,
   pub format_js_error_fn: Option<Arc<FormatJsErrorFn>>,
+  pub permission_presets: PermissionPresets,
   pub source_map_getter: Option<Box<dyn SourceMapGetter>>,
   pub worker_type: WebWorkerType,
   pub maybe_inspector_server: Option<Arc<InspectorServer>>,
@@ -347,6 +353,8 @@ pub struct WebWorkerOptions {
   pub shared_array_buffer_store: Option<SharedArrayBufferStore>,
   pub compiled_wasm_module_store: Option<CompiledWasmModuleStore>,
   pub cache_storage_dir: Option<std::path::PathBuf>,
+  /// The directory of the HTTP cache of `fetch()`.
+  pub http_cache_dir: Option<std::path::PathBuf>,
   pub stdio: Stdio,
 }
 
@@ -412,6 +420,8 @@ impl WebWorker {
             .unsafely_ignore_certificate_errors
             .clone(),
           file_fetch_handler: Rc::new(deno_fetch::FsFetchHandler),
+          http_cache_dir: options.http_cache_dir.clone(),
+          resolved_addrs_check: Some(permissions.fetch_resolved_addrs_check()),
           ..Default::default()
         },
       ),
@@ -427,17 +437,14 @@ impl WebWorker {
         options.broadcast_channel.clone(),
         unstable,
       ),
-      deno_ffi::deno_ffi::init_ops::<PermissionsContainer>(unstable),
+      optional_extensions::deno_ffi_init_ops(unstable),
       deno_net::deno_net::init_ops::<PermissionsContainer>(
         options.root_cert_store_provider.clone(),
         unstable,
         options.unsafely_ignore_certificate_errors.clone(),
       ),
       deno_tls::deno_tls::init_ops(),
-      deno_kv::deno_kv::init_ops(
-        SqliteDbHandler::<PermissionsContainer>::new(None),
-        unstable,
-      ),
+      optional_extensions::deno_kv_init_ops(None, unstable),
       deno_napi::deno_napi::init_ops::<PermissionsContainer>(),
       deno_http::deno_http::init_ops(),
       deno_io::deno_io::init_ops(Some(options.stdio)),
@@ -457,6 +464,7 @@ impl WebWorker {
         options.preload_module_cb.clone(),
         options.pre_execute_module_cb.clone(),
         options.format_js_error_fn.clone(),
+        options.permission_presets.clone(),
       ),
       ops::fs_events::deno_fs_events::init_ops(),
       ops::os::deno_os_worker::init_ops(),
@@ -544,6 +552,7 @@ impl WebWorker {
     (
       Self {
         id: worker_id,
+        _audit_stack: record_audit_stack(&mut js_runtime),
         js_runtime,
         name,
         internal_handle,
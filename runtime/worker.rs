@@ -32,13 +32,16 @@ use deno_core::Snapshot;
 use deno_core::SourceMapGetter;
 use deno_fs::FileSystem;
 use deno_io::Stdio;
-use deno_kv::sqlite::SqliteDbHandler;
 use deno_tls::RootCertStoreProvider;
 use deno_web::BlobStore;
 use log::debug;
 
 use crate::inspector_server::InspectorServer;
 use crate::ops;
+use crate::optional_extensions;
+use crate::permissions::record_audit_stack;
+use crate::permissions::AuditStackGuard;
+use crate::permissions::PermissionPresets;
 use crate::permissions::PermissionsContainer;
 use crate::BootstrapOptions;
 
@@ -64,6 +67,8 @@ impl ExitCode {
 /// All `WebWorker`s created during program execution
 /// are descendants of this worker.
 pub struct MainWorker {
+  /// Declared before the runtime, since it must be dropped first.
+  _audit_stack: Option<AuditStackGuard>,
   pub js_runtime: JsRuntime,
   should_break_on_first_statement: bool,
   should_wait_for_inspector_session: bool,
@@ -100,6 +105,8 @@ pub struct WorkerOptions {
   pub web_worker_preload_module_cb: Arc<ops::worker_host::WorkerEventCb>,
   pub web_worker_pre_execute_module_cb: Arc<ops::worker_host::WorkerEventCb>,
   pub format_js_error_fn: Option<Arc<FormatJsErrorFn>>,
+  /// Named permission sets that workers can be created with.
+  pub permission_presets: PermissionPresets,
 
   /// Source map reference for errors.
   pub source_map_getter: Option<Box<dyn SourceMapGetter>>,
@@ -117,6 +124,8 @@ pub struct WorkerOptions {
   pub get_error_class_fn: Option<GetErrorClassFn>,
   pub cache_storage_dir: Option<std::path::PathBuf>,
   pub origin_storage_dir: Option<std::path::PathBuf>,
+  /// The directory of the HTTP cache of `fetch()`.
+  pub http_cache_dir: Option<std::path::PathBuf>,
   pub blob_store: BlobStore,
   pub broadcast_channel: InMemoryBroadcastChannel,
 
@@ -159,9 +168,11 @@ impl Default for WorkerOptions {
       shared_array_buffer_store: Default::default(),
       maybe_inspector_server: Default::default(),
       format_js_error_fn: Default::default(),
+      permission_presets: Default::default(),
       get_error_class_fn: Default::default(),
       origin_storage_dir: Default::default(),
       cache_storage_dir: Default::default(),
+      http_cache_dir: Default::default(),
       broadcast_channel: Default::default(),
       source_map_getter: Default::default(),
       root_cert_store_provider: Default::default(),
@@ -233,6 +244,8 @@ impl MainWorker {
             .unsafely_ignore_certificate_errors
             .clone(),
           file_fetch_handler: Rc::new(deno_fetch::FsFetchHandler),
+          http_cache_dir: options.http_cache_dir.clone(),
+          resolved_addrs_check: Some(permissions.fetch_resolved_addrs_check()),
           ..Default::default()
         },
       ),
@@ -250,17 +263,15 @@ impl MainWorker {
         options.broadcast_channel.clone(),
         unstable,
       ),
-      deno_ffi::deno_ffi::init_ops::<PermissionsContainer>(unstable),
+      optional_extensions::deno_ffi_init_ops(unstable),
       deno_net::deno_net::init_ops::<PermissionsContainer>(
         options.root_cert_store_provider.clone(),
         unstable,
         options.unsafely_ignore_certificate_errors.clone(),
       ),
       deno_tls::deno_tls::init_ops(),
-      deno_kv::deno_kv::init_ops(
-        SqliteDbHandler::<PermissionsContainer>::new(
-          options.origin_storage_dir.clone(),
-        ),
+      optional_extensions::deno_kv_init_ops(
+        options.origin_storage_dir.clone(),
         unstable,
       ),
       deno_napi::deno_napi::init_ops::<PermissionsContainer>(),
@@ -281,6 +292,7 @@ impl MainWorker {
         options.web_worker_preload_module_cb.clone(),
         options.web_worker_pre_execute_module_cb.clone(),
         options.format_js_error_fn.clone(),
+        options.permission_presets.clone(),
       ),
       ops::fs_events::deno_fs_events::init_ops(),
       ops::os::deno_os::init_ops(exit_code.clone()),
@@ -356,6 +368,7 @@ impl MainWorker {
     };
 
     Self {
+      _audit_stack: record_audit_stack(&mut js_runtime),
       js_runtime,
       should_break_on_first_statement: options.should_break_on_first_statement,
       should_wait_for_inspector_session: options